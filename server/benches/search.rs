@@ -0,0 +1,460 @@
+//! Search benchmark.
+//!
+//! Seeds synthetic people, organizations, locations and productions (with
+//! embeddings) into the test SurrealDB, then times representative queries
+//! through the repo layer (`services::search`) and through the full `/search`
+//! handler, template rendering included, and reports p50/p99 latency.
+//!
+//! Usage: make bench
+//!   or:  cargo bench --bench search   (against a database from `make test-services`)
+//!
+//!   BENCH_DB_URL      SurrealDB endpoint (default localhost:8100, the test instance)
+//!   BENCH_RECORDS     People to seed; the other tables get a quarter as many (default 2000)
+//!   BENCH_ITERATIONS  Timed runs per query, after one warm-up run (default 30)
+//!   BENCH_EMBEDDINGS  "model" to embed records and queries with the real model
+//!                     instead of random unit vectors (slow to seed, but realistic
+//!                     similarity scores and lets the handler run its vector path)
+//!
+//! The seeded rows go into the `bench` database of the `slatehub-test` namespace,
+//! which is replaced on every run.
+
+use axum::body::Body;
+use axum::http::Request;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use slatehub::config;
+use slatehub::db::DB;
+use slatehub::services::embedding::{generate_embedding, generate_embeddings_batch, init_embedding_service};
+use slatehub::services::search::{self, SearchParams};
+use slatehub::services::search_indexes::EMBEDDING_DIMENSION;
+use slatehub::services::search_utils;
+use std::time::{Duration, Instant};
+use surrealdb::engine::remote::ws::Ws;
+use surrealdb::opt::auth::Root;
+use tower::ServiceExt;
+
+const NAMESPACE: &str = "slatehub-test";
+const DATABASE: &str = "bench";
+const BATCH: usize = 250;
+
+const QUERIES: &[&str] = &[
+    "male actor ages 20-40 in berlin",
+    "cinematographer in los angeles",
+    "modern office in los angeles with lots of light",
+    "vfx studio",
+    "documentary in post-production",
+    "sound mixer with documentary experience",
+];
+
+const FIRST_NAMES: &[&str] = &[
+    "Alex", "Sam", "Jordan", "Maria", "Jonas", "Lena", "Priya", "Kenji", "Amara", "Luca",
+    "Sofia", "Mateo", "Hannah", "Omar", "Chloe", "Noah",
+];
+const LAST_NAMES: &[&str] = &[
+    "Schmidt", "Garcia", "Okafor", "Tanaka", "Rossi", "Novak", "Silva", "Cohen", "Berg",
+    "Moreau", "Patel", "Kowalski",
+];
+const ROLES: &[&str] = &[
+    "Actor", "Director", "Cinematographer", "Gaffer", "Sound Mixer", "Editor", "Producer",
+    "Production Designer", "Costume Designer", "Stunt Performer", "Colorist", "Casting Director",
+];
+const CITIES: &[&str] = &[
+    "Berlin", "Los Angeles", "London", "Vancouver", "Atlanta", "Toronto", "Paris", "Madrid",
+    "Munich", "New York", "Lisbon", "Prague",
+];
+const GENRES: &[&str] = &[
+    "documentary", "feature film", "commercial", "music video", "drama series", "horror",
+    "comedy", "animation",
+];
+const SPACES: &[&str] = &[
+    "modern office", "industrial warehouse", "loft apartment", "beach house", "diner",
+    "rooftop terrace", "forest cabin", "sound stage", "train station", "art deco theater",
+];
+const ORG_KINDS: &[&str] = &[
+    "VFX Studio", "Production Company", "Casting Agency", "Post Production House",
+    "Sound Studio", "Talent Agency", "Equipment Rental",
+];
+const STATUSES: &[&str] = &["Development", "Pre-Production", "Production", "Post-Production"];
+
+fn env_or<T: std::str::FromStr>(var: &str, default: T) -> T {
+    std::env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+fn pick<'a>(rng: &mut StdRng, items: &[&'a str]) -> &'a str {
+    items.choose(rng).copied().unwrap_or_default()
+}
+
+fn random_unit_vector(rng: &mut StdRng) -> Vec<f32> {
+    let v: Vec<f32> = (0..EMBEDDING_DIMENSION).map(|_| rng.gen_range(-1.0..1.0)).collect();
+    let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt().max(f32::EPSILON);
+    v.into_iter().map(|x| x / norm).collect()
+}
+
+struct Embedder {
+    model: bool,
+    rng: StdRng,
+}
+
+impl Embedder {
+    fn embed(&mut self, texts: &[String]) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error>> {
+        if self.model {
+            Ok(generate_embeddings_batch(texts.to_vec())?)
+        } else {
+            Ok(texts.iter().map(|_| random_unit_vector(&mut self.rng)).collect())
+        }
+    }
+
+    fn embed_query(&mut self, query: &str) -> Result<Vec<f32>, Box<dyn std::error::Error>> {
+        if self.model {
+            Ok(generate_embedding(query)?)
+        } else {
+            Ok(random_unit_vector(&mut self.rng))
+        }
+    }
+}
+
+async fn connect(url: &str) -> Result<(), Box<dyn std::error::Error>> {
+    DB.connect::<Ws>(url).await?;
+    DB.signin(Root {
+        username: "root".to_string(),
+        password: "root".to_string(),
+    })
+    .await?;
+    DB.query(format!("REMOVE DATABASE IF EXISTS `{}`", DATABASE)).await?.check()?;
+    DB.use_ns(NAMESPACE).use_db(DATABASE).await?;
+    DB.query(include_str!("../../db/schema.surql")).await?.check()?;
+    Ok(())
+}
+
+// Each seed function builds a batch of rows in Rust, binds the columns as arrays
+// and creates the rows with a single `FOR` loop per batch.
+
+async fn seed_people(count: usize, rng: &mut StdRng, embedder: &mut Embedder) -> Result<(), Box<dyn std::error::Error>> {
+    for start in (0..count).step_by(BATCH) {
+        let n = BATCH.min(count - start);
+        let mut names = Vec::with_capacity(n);
+        let mut headlines = Vec::with_capacity(n);
+        let mut cities = Vec::with_capacity(n);
+        let mut genders = Vec::with_capacity(n);
+        let mut ages = Vec::with_capacity(n);
+        let mut texts = Vec::with_capacity(n);
+        for i in start..start + n {
+            let name = format!("{} {}", pick(rng, FIRST_NAMES), pick(rng, LAST_NAMES));
+            let role = pick(rng, ROLES);
+            let city = pick(rng, CITIES);
+            let genre = pick(rng, GENRES);
+            let gender = if i % 2 == 0 { "Male" } else { "Female" };
+            let age = rng.gen_range(18..70i64);
+            texts.push(format!(
+                "{} is a {} {} based in {}, with {} experience. Playing age {}-{}.",
+                name, gender, role, city, genre, age, age + 10
+            ));
+            names.push(name);
+            headlines.push(format!("{} • {}", role, genre));
+            cities.push(city.to_string());
+            genders.push(gender.to_string());
+            ages.push(age);
+        }
+        let embeddings = embedder.embed(&texts)?;
+
+        DB.query(
+            "FOR $i IN 0..array::len($names) {
+                CREATE person SET
+                    email = string::concat('bench', $start + $i, '@example.com'),
+                    username = string::concat('bench', $start + $i),
+                    password = 'bench',
+                    name = $names[$i],
+                    profile = {
+                        name: $names[$i],
+                        headline: $headlines[$i],
+                        location: $cities[$i],
+                        gender: $genders[$i],
+                        acting_age_range: { min: $ages[$i], max: $ages[$i] + 10 },
+                        is_public: true,
+                        ethnicity: [], media_other: [], reels: [], skills: [],
+                        social_links: [], unions: [], languages: [], education: [], awards: []
+                    },
+                    embedding_text = $texts[$i],
+                    embedding = $embeddings[$i];
+            }",
+        )
+        .bind(("start", start as i64))
+        .bind(("names", names))
+        .bind(("headlines", headlines))
+        .bind(("cities", cities))
+        .bind(("genders", genders))
+        .bind(("ages", ages))
+        .bind(("texts", texts))
+        .bind(("embeddings", embeddings))
+        .await?
+        .check()?;
+    }
+    Ok(())
+}
+
+async fn seed_organizations(count: usize, rng: &mut StdRng, embedder: &mut Embedder) -> Result<(), Box<dyn std::error::Error>> {
+    for start in (0..count).step_by(BATCH) {
+        let n = BATCH.min(count - start);
+        let mut names = Vec::with_capacity(n);
+        let mut cities = Vec::with_capacity(n);
+        let mut texts = Vec::with_capacity(n);
+        for _ in 0..n {
+            let kind = pick(rng, ORG_KINDS);
+            let city = pick(rng, CITIES);
+            let name = format!("{} {}", pick(rng, LAST_NAMES), kind);
+            texts.push(format!("{} is a {} in {} working on {} projects.", name, kind, city, pick(rng, GENRES)));
+            names.push(name);
+            cities.push(city.to_string());
+        }
+        let embeddings = embedder.embed(&texts)?;
+
+        DB.query(
+            "LET $type = (SELECT VALUE id FROM organization_type LIMIT 1)[0];
+            FOR $i IN 0..array::len($names) {
+                CREATE organization SET
+                    name = $names[$i],
+                    slug = string::concat('bench-org-', $start + $i),
+                    type = $type,
+                    description = $texts[$i],
+                    location = $cities[$i],
+                    social_links = [],
+                    services = [],
+                    public = true,
+                    embedding_text = $texts[$i],
+                    embedding = $embeddings[$i];
+            }",
+        )
+        .bind(("start", start as i64))
+        .bind(("names", names))
+        .bind(("cities", cities))
+        .bind(("texts", texts))
+        .bind(("embeddings", embeddings))
+        .await?
+        .check()?;
+    }
+    Ok(())
+}
+
+async fn seed_locations(count: usize, rng: &mut StdRng, embedder: &mut Embedder) -> Result<(), Box<dyn std::error::Error>> {
+    for start in (0..count).step_by(BATCH) {
+        let n = BATCH.min(count - start);
+        let mut names = Vec::with_capacity(n);
+        let mut cities = Vec::with_capacity(n);
+        let mut texts = Vec::with_capacity(n);
+        for _ in 0..n {
+            let space = pick(rng, SPACES);
+            let city = pick(rng, CITIES);
+            let light = if rng.gen_bool(0.5) { "lots of natural light" } else { "blackout capable" };
+            texts.push(format!("A {} in {} with {}.", space, city, light));
+            names.push(format!("{} {}", city, space));
+            cities.push(city.to_string());
+        }
+        let embeddings = embedder.embed(&texts)?;
+
+        DB.query(
+            "LET $owner = (SELECT VALUE id FROM person LIMIT 1)[0];
+            FOR $i IN 0..array::len($names) {
+                CREATE location SET
+                    name = $names[$i],
+                    address = '1 Bench Street',
+                    city = $cities[$i],
+                    state = '',
+                    country = '',
+                    description = $texts[$i],
+                    contact_name = 'Bench',
+                    contact_email = 'bench@example.com',
+                    is_public = true,
+                    created_by = $owner,
+                    embedding_text = $texts[$i],
+                    embedding = $embeddings[$i];
+            }",
+        )
+        .bind(("names", names))
+        .bind(("cities", cities))
+        .bind(("texts", texts))
+        .bind(("embeddings", embeddings))
+        .await?
+        .check()?;
+    }
+    Ok(())
+}
+
+async fn seed_productions(count: usize, rng: &mut StdRng, embedder: &mut Embedder) -> Result<(), Box<dyn std::error::Error>> {
+    for start in (0..count).step_by(BATCH) {
+        let n = BATCH.min(count - start);
+        let mut titles = Vec::with_capacity(n);
+        let mut statuses = Vec::with_capacity(n);
+        let mut cities = Vec::with_capacity(n);
+        let mut texts = Vec::with_capacity(n);
+        for _ in 0..n {
+            let genre = pick(rng, GENRES);
+            let city = pick(rng, CITIES);
+            let status = pick(rng, STATUSES);
+            let title = format!("The {} {}", pick(rng, LAST_NAMES), pick(rng, SPACES));
+            texts.push(format!("{} is a {} shooting in {}, currently in {}.", title, genre, city, status));
+            titles.push(title);
+            statuses.push(status.to_string());
+            cities.push(city.to_string());
+        }
+        let embeddings = embedder.embed(&texts)?;
+
+        DB.query(
+            "FOR $i IN 0..array::len($titles) {
+                CREATE production SET
+                    title = $titles[$i],
+                    slug = string::concat('bench-production-', $start + $i),
+                    type = 'Film',
+                    status = $statuses[$i],
+                    description = $texts[$i],
+                    location = $cities[$i],
+                    embedding_text = $texts[$i],
+                    embedding = $embeddings[$i];
+            }",
+        )
+        .bind(("start", start as i64))
+        .bind(("titles", titles))
+        .bind(("statuses", statuses))
+        .bind(("cities", cities))
+        .bind(("texts", texts))
+        .bind(("embeddings", embeddings))
+        .await?
+        .check()?;
+    }
+    Ok(())
+}
+
+/// The same per-table searches the `/search` handler runs, without rendering.
+async fn repo_search(query: &str, embedding: &Vec<f32>) -> Result<usize, Box<dyn std::error::Error>> {
+    let config = config::search_config();
+
+    let parsed = search_utils::parse_query(query);
+    let people_params = SearchParams {
+        query: &parsed.cleaned,
+        embedding: Some(embedding),
+        config,
+        limit: 20,
+        offset: 0,
+    };
+    let people = search::search_people(&people_params, &parsed, None).await?;
+
+    let (location, cleaned) = search_utils::extract_location(query);
+    let normalized = search_utils::normalize_query(&cleaned);
+    let params = SearchParams {
+        query: &normalized,
+        embedding: Some(embedding),
+        config,
+        limit: 10,
+        offset: 0,
+    };
+    let organizations = search::search_organizations(&params, location.as_deref()).await?;
+    let locations = search::search_locations(&params, location.as_deref(), None).await?;
+    let productions = search::search_productions(&params, None).await?;
+
+    Ok(people.len() + organizations.len() + locations.len() + productions.len())
+}
+
+async fn handler_search(app: &axum::Router, query: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    let uri = format!("/search?q={}", urlencoding::encode(query));
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("/search returned {}", response.status()).into());
+    }
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    Ok(body.len())
+}
+
+struct Stats {
+    p50: Duration,
+    p99: Duration,
+    mean: Duration,
+}
+
+fn stats(mut samples: Vec<Duration>) -> Stats {
+    samples.sort();
+    let at = |q: f64| samples[((samples.len() as f64 * q).ceil() as usize).clamp(1, samples.len()) - 1];
+    Stats {
+        p50: at(0.50),
+        p99: at(0.99),
+        mean: samples.iter().sum::<Duration>() / samples.len() as u32,
+    }
+}
+
+fn report(label: &str, query: &str, samples: Vec<Duration>, hits: usize) {
+    let s = stats(samples);
+    println!(
+        "  {:<8} {:<50} p50 {:>8.2?}  p99 {:>8.2?}  mean {:>8.2?}  ({})",
+        label, query, s.p50, s.p99, s.mean, hits
+    );
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let url: String = env_or("BENCH_DB_URL", "localhost:8100".to_string());
+    let people: usize = env_or("BENCH_RECORDS", 2000);
+    let iterations: usize = env_or("BENCH_ITERATIONS", 30).max(1);
+    let use_model = std::env::var("BENCH_EMBEDDINGS").map(|v| v == "model").unwrap_or(false);
+
+    slatehub::templates::init()?;
+    if use_model {
+        println!("Loading embedding model...");
+        init_embedding_service().await?;
+    }
+
+    println!("Connecting to {} and loading the schema into {}/{}...", url, NAMESPACE, DATABASE);
+    connect(&url).await?;
+
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut embedder = Embedder {
+        model: use_model,
+        rng: StdRng::seed_from_u64(7),
+    };
+    let others = (people / 4).max(1);
+
+    let started = Instant::now();
+    seed_people(people, &mut rng, &mut embedder).await?;
+    seed_organizations(others, &mut rng, &mut embedder).await?;
+    seed_locations(others, &mut rng, &mut embedder).await?;
+    seed_productions(others, &mut rng, &mut embedder).await?;
+    println!(
+        "Seeded {} people and {} each of organizations, locations and productions in {:.1?}\n",
+        people,
+        others,
+        started.elapsed()
+    );
+
+    println!("Repo layer ({} runs per query; hits = results returned):", iterations);
+    for query in QUERIES {
+        let embedding = embedder.embed_query(query)?;
+        let mut hits = repo_search(query, &embedding).await?;
+        let mut samples = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let t = Instant::now();
+            hits = repo_search(query, &embedding).await?;
+            samples.push(t.elapsed());
+        }
+        report("repo", query, samples, hits);
+    }
+
+    println!(
+        "\n/search handler ({} runs per query; {}; size = response bytes):",
+        iterations,
+        if use_model { "model embeddings" } else { "text-only, no model loaded" }
+    );
+    let app = slatehub::routes::app();
+    for query in QUERIES {
+        let mut size = handler_search(&app, query).await?;
+        let mut samples = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let t = Instant::now();
+            size = handler_search(&app, query).await?;
+            samples.push(t.elapsed());
+        }
+        report("handler", query, samples, size);
+    }
+
+    Ok(())
+}
@@ -28,6 +28,12 @@ pub struct Claims {
     pub iat: u64,
     /// Expiration (Unix timestamp)
     pub exp: u64,
+    /// Impersonation session ID, set when an admin is viewing the site as this user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imp: Option<String>,
+    /// Tenant slug the token was issued for, unset on the primary install
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tnt: Option<String>,
 }
 
 /// Configuration for password hashing (matches SurrealDB's settings)
@@ -84,12 +90,12 @@ pub struct JwtConfig;
 impl JwtConfig {
     /// Get the JWT secret from environment (required)
     pub fn secret() -> String {
-        std::env::var("JWT_SECRET").expect("JWT_SECRET environment variable must be set")
+        crate::settings::var("JWT_SECRET").expect("JWT_SECRET environment variable must be set")
     }
 
     /// Token validity duration in seconds (12 hours by default)
     pub fn token_duration() -> u64 {
-        std::env::var("JWT_DURATION")
+        crate::settings::var("JWT_DURATION")
             .unwrap_or_else(|_| "43200".to_string())
             .parse()
             .unwrap_or(43200)
@@ -98,6 +104,28 @@ impl JwtConfig {
 
 /// Create a JWT token for a user
 pub fn create_jwt(user_id: &str, username: &str, email: &str) -> Result<String> {
+    encode_jwt(user_id, username, email, JwtConfig::token_duration(), None)
+}
+
+/// Create a JWT that signs an admin in as someone else for an impersonation
+/// session. It lasts `duration` seconds and names the session in `imp`.
+pub fn create_impersonation_jwt(
+    user_id: &str,
+    username: &str,
+    email: &str,
+    session_id: &str,
+    duration: u64,
+) -> Result<String> {
+    encode_jwt(user_id, username, email, duration, Some(session_id.to_string()))
+}
+
+fn encode_jwt(
+    user_id: &str,
+    username: &str,
+    email: &str,
+    duration: u64,
+    imp: Option<String>,
+) -> Result<String> {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .map_err(|e| Error::Internal(format!("System time error: {}", e)))?
@@ -108,7 +136,9 @@ pub fn create_jwt(user_id: &str, username: &str, email: &str) -> Result<String>
         username: username.to_string(),
         email: email.to_string(),
         iat: now,
-        exp: now + JwtConfig::token_duration(),
+        exp: now + duration,
+        imp,
+        tnt: crate::db::current_tenant(),
     };
 
     let header = Header::new(JwtAlgorithm::HS256);
@@ -137,6 +167,12 @@ pub fn decode_jwt(token: &str) -> Result<Claims> {
         Error::Unauthorized
     })?;
 
+    // A token only signs in to the install or tenant that issued it
+    if token_data.claims.tnt != crate::db::current_tenant() {
+        tracing::debug!("JWT issued for tenant {:?} used elsewhere", token_data.claims.tnt);
+        return Err(Error::Unauthorized);
+    }
+
     Ok(token_data.claims)
 }
 
@@ -0,0 +1,212 @@
+//! CLI tool to back up and restore a SlateHub install: the SurrealDB data and
+//! the S3 bucket, in the archive format described in `slatehub::services::backup`.
+//!
+//! Usage: cargo run --bin backup -- <create|restore|inspect> <archive.zip> [flags]
+//!   or:  make backup FILE=<archive.zip>  /  make restore FILE=<archive.zip>
+//!
+//!   create <archive>    Export the database and every S3 object
+//!       --manifest-only   Record the bucket's objects without copying them
+//!   restore <archive>   Import the database, then upload objects missing from the bucket
+//!       --force           Replace the database even if it already has data
+//!       --skip-database   Only restore files
+//!       --skip-files      Only restore the database
+//!   inspect <archive>   Print the manifest summary without touching anything
+
+use slatehub::config::Config;
+use slatehub::db::DB;
+use slatehub::services::backup::{self, Manifest};
+use slatehub::services::s3::init_s3;
+use surrealdb::engine::remote::ws::Ws;
+use surrealdb::opt::auth::Root;
+
+const USAGE: &str = "Usage: backup <create|restore|inspect> <archive.zip> [--manifest-only | --force --skip-database --skip-files]";
+
+async fn connect(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let db_url = config.database.connection_url();
+    println!("Connecting to database at: {}", db_url);
+    DB.connect::<Ws>(&db_url).await?;
+    DB.signin(Root {
+        username: config.database.username.clone(),
+        password: config.database.password.clone(),
+    })
+    .await?;
+    DB.use_ns(&config.database.namespace)
+        .use_db(&config.database.name)
+        .await?;
+    println!("Connected to database.\n");
+    Ok(())
+}
+
+fn print_manifest(manifest: &Manifest) {
+    let total: u64 = manifest.objects.iter().map(|o| o.size).sum();
+    let copied = manifest.objects.iter().filter(|o| o.sha256.is_some()).count();
+    if manifest.format == 0 {
+        println!("  Format:     legacy (no manifest)");
+    } else {
+        println!("  Created:    {}", manifest.created_at);
+        println!("  Version:    {}", manifest.app_version);
+        println!("  Database:   {}/{}", manifest.namespace, manifest.database);
+        println!("  Bucket:     {}", manifest.bucket);
+    }
+    println!(
+        "  Objects:    {} ({:.1} MB), {} copied into the archive",
+        manifest.objects.len(),
+        total as f64 / 1_048_576.0,
+        copied
+    );
+}
+
+async fn create(config: &Config, path: &str, manifest_only: bool) -> Result<(), Box<dyn std::error::Error>> {
+    init_s3().await?;
+
+    // Write to a temporary name so an interrupted backup never looks complete
+    let partial = format!("{}.partial", path);
+    let file = std::fs::File::create(&partial)?;
+    println!(
+        "Backing up {}/{}{}...",
+        config.database.namespace,
+        config.database.name,
+        if manifest_only { " (manifest only)" } else { " and all files" }
+    );
+    let manifest = backup::write_archive(file, &config.database, !manifest_only).await?;
+    std::fs::rename(&partial, path)?;
+
+    let skipped = manifest
+        .objects
+        .iter()
+        .filter(|o| !manifest_only && o.sha256.is_none())
+        .count();
+    println!("Wrote {}", path);
+    print_manifest(&manifest);
+    if skipped > 0 {
+        println!("  Warning: {} objects could not be downloaded and are only in the manifest", skipped);
+    }
+    Ok(())
+}
+
+async fn database_has_data() -> Result<bool, Box<dyn std::error::Error>> {
+    let mut response = DB.query("INFO FOR DB").await?;
+    let info: Option<serde_json::Value> = response.take(0)?;
+    let tables = info
+        .as_ref()
+        .and_then(|i| i["tables"].as_object())
+        .map(|t| t.keys().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+    for table in tables {
+        let mut response = DB
+            .query(format!("SELECT VALUE id FROM `{}` LIMIT 1", table))
+            .await?;
+        let ids: Vec<serde_json::Value> = response.take(0)?;
+        if !ids.is_empty() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+async fn restore(
+    config: &Config,
+    path: &str,
+    force: bool,
+    restore_database: bool,
+    restore_files: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if restore_database {
+        connect(config).await?;
+        if database_has_data().await? {
+            if !force {
+                return Err(format!(
+                    "{}/{} already has data. Restore into an empty database, or pass --force to replace it.",
+                    config.database.namespace, config.database.name
+                )
+                .into());
+            }
+            println!("Removing existing database {}...", config.database.name);
+            DB.query(format!(
+                "REMOVE DATABASE `{name}`; DEFINE DATABASE `{name}`;",
+                name = config.database.name
+            ))
+            .await?
+            .check()?;
+        }
+    }
+    if restore_files {
+        init_s3().await?;
+    }
+
+    println!("Restoring from {}...", path);
+    let file = std::fs::File::open(path)?;
+    let report = backup::restore_archive(file, &config.database, restore_database, restore_files).await?;
+
+    if report.database_restored {
+        println!("  Database restored.");
+    }
+    if restore_files {
+        println!("  Files uploaded:        {}", report.files_uploaded);
+        println!("  Already in bucket:     {}", report.files_present);
+        if !report.files_missing.is_empty() {
+            println!("  Missing (not in archive or bucket): {}", report.files_missing.len());
+            for key in &report.files_missing {
+                println!("    {}", key);
+            }
+        }
+        if !report.files_corrupt.is_empty() {
+            println!("  Checksum mismatch, not uploaded: {}", report.files_corrupt.len());
+            for key in &report.files_corrupt {
+                println!("    {}", key);
+            }
+        }
+    }
+
+    if report.files_corrupt.is_empty() {
+        Ok(())
+    } else {
+        Err("some files failed their checksum".into())
+    }
+}
+
+fn inspect(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+    let manifest = backup::read_manifest(&mut archive)?;
+    let has_export = archive.by_name(backup::DB_EXPORT_NAME).is_ok();
+    println!("{}", path);
+    println!("  DB export:  {}", if has_export { "yes" } else { "MISSING" });
+    print_manifest(&manifest);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    slatehub::logging::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let flag = |name: &str| args.iter().any(|a| a == name);
+    let positional: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+
+    let (command, path) = match positional.as_slice() {
+        [command, path] => (command.as_str(), path.as_str()),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    match command {
+        "inspect" => inspect(path),
+        "create" => {
+            let config = Config::from_env()?;
+            create(&config, path, flag("--manifest-only")).await
+        }
+        "restore" => {
+            let config = Config::from_env()?;
+            let restore_database = !flag("--skip-database");
+            let restore_files = !flag("--skip-files");
+            restore(&config, path, flag("--force"), restore_database, restore_files).await
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
@@ -2,20 +2,24 @@
 //!
 //! Connects to SurrealDB, fetches all records from each entity table,
 //! builds embedding text, generates embeddings, and updates each record.
+//! Embeddings are written in the precision set by `EMBEDDING_PRECISION`.
 //!
 //! Usage: cargo run --bin rebuild-embeddings
 //!   or:  make rebuild-embeddings
 
 use slatehub::config::Config;
 use slatehub::db::DB;
+use slatehub::models::audio_reel::AudioReelModel;
+use slatehub::models::company_credit::CompanyCreditModel;
+use slatehub::physical_attributes::describe;
 use slatehub::services::embedding::{
     build_location_embedding_text, build_organization_embedding_text,
     build_person_embedding_text, build_production_embedding_text, generate_embedding,
-    init_embedding_service,
+    init_embedding_service, store_embedding,
 };
 use surrealdb::engine::remote::ws::Ws;
 use surrealdb::opt::auth::Root;
-use surrealdb::types::SurrealValue;
+use surrealdb::types::{RecordId, SurrealValue};
 
 // ── Lightweight DB structs for each entity (only fields needed for embedding) ──
 
@@ -25,6 +29,7 @@ struct PersonRow {
     name: Option<String>,
     username: Option<String>,
     profile: Option<PersonProfileRow>,
+    is_minor: Option<bool>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize, SurrealValue)]
@@ -38,8 +43,11 @@ struct PersonProfileRow {
     age_range: Option<AgeRangeRow>,
     height_mm: Option<i32>,
     body_type: Option<String>,
+    body_type_other: Option<String>,
     hair_color: Option<String>,
+    hair_color_other: Option<String>,
     eye_color: Option<String>,
+    eye_color_other: Option<String>,
     languages: Option<Vec<String>>,
     unions: Option<Vec<String>>,
     acting_age_range: Option<AgeRangeRow>,
@@ -93,23 +101,15 @@ struct ProductionRow {
     end_date: Option<String>,
 }
 
-/// Update a record's embedding and embedding_text fields.
+/// Update a record's embedding and embedding_text fields, in the configured precision.
 /// `raw_id` is the full record ID string from SurrealDB (e.g. "person:abc123").
 async fn update_embedding(
     raw_id: String,
     embedding: Vec<f32>,
     embedding_text: String,
-) -> Result<(), surrealdb::Error> {
-    let query = format!(
-        "UPDATE {} SET embedding = $embedding, embedding_text = $embedding_text",
-        raw_id
-    );
-
-    DB.query(&query)
-        .bind(("embedding", embedding))
-        .bind(("embedding_text", embedding_text))
-        .await?;
-
+) -> Result<(), Box<dyn std::error::Error>> {
+    let id = RecordId::parse_simple(&raw_id)?;
+    store_embedding(&DB, id, embedding, embedding_text).await?;
     Ok(())
 }
 
@@ -146,18 +146,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     {
         println!("=== Rebuilding person embeddings ===");
         let mut resp = DB
-            .query("SELECT <string> id AS id, name, username, profile FROM person")
+            .query("SELECT <string> id AS id, name, username, profile, is_minor FROM person")
             .await?;
         let people: Vec<PersonRow> = resp.take(0)?;
         let count = people.len();
         println!("Found {} person records", count);
 
-        for person in people {
+        for mut person in people {
+            // Minor profiles leave sensitive physical fields out of search
+            if person.is_minor == Some(true)
+                && let Some(profile) = person.profile.as_mut()
+            {
+                profile.ethnicity = None;
+                profile.height_mm = None;
+                profile.body_type = None;
+                profile.body_type_other = None;
+            }
+
             let display_name = person
                 .name
                 .as_deref()
                 .unwrap_or(person.username.as_deref().unwrap_or("unknown"))
                 .to_string();
+            let audio_work = match RecordId::parse_simple(&person.id) {
+                Ok(id) => AudioReelModel::embedding_lines(&id).await.unwrap_or_else(|e| {
+                    eprintln!("  Failed to load audio reels for {}: {}", display_name, e);
+                    Vec::new()
+                }),
+                Err(_) => Vec::new(),
+            };
 
             let embedding_text = if let Some(profile) = &person.profile {
                 build_person_embedding_text(
@@ -170,21 +187,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     profile.gender.as_deref(),
                     &profile.ethnicity.clone().unwrap_or_default(),
                     profile.height_mm,
-                    profile.body_type.as_deref(),
-                    profile.hair_color.as_deref(),
-                    profile.eye_color.as_deref(),
+                    describe(profile.body_type.as_deref(), profile.body_type_other.as_deref()),
+                    describe(profile.hair_color.as_deref(), profile.hair_color_other.as_deref()),
+                    describe(profile.eye_color.as_deref(), profile.eye_color_other.as_deref()),
                     &profile.languages.clone().unwrap_or_default(),
                     &profile.unions.clone().unwrap_or_default(),
                     &[],
                     profile.acting_age_range.as_ref().map(|ar| (ar.min, ar.max)),
                     &profile.acting_ethnicities.clone().unwrap_or_default(),
                     profile.nationality.as_deref(),
+                    &audio_work,
                 )
             } else {
                 build_person_embedding_text(
                     &display_name,
                     None, None, &[], None, None, None, &[], None, None, None, None, &[], &[], &[],
-                    None, &[], None,
+                    None, &[], None, &audio_work,
                 )
             };
 
@@ -218,6 +236,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         for org in orgs {
             let name = org.name.as_deref().unwrap_or("unknown").to_string();
+            let slate = match RecordId::parse_simple(&org.id) {
+                Ok(id) => CompanyCreditModel::slate_lines(&id).await.unwrap_or_default(),
+                Err(_) => Vec::new(),
+            };
             let embedding_text = build_organization_embedding_text(
                 &name,
                 org.org_type.as_deref().unwrap_or(""),
@@ -226,6 +248,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 org.location.as_deref(),
                 org.founded_year,
                 org.employees_count,
+                &slate,
             );
 
             match generate_embedding(&embedding_text) {
@@ -300,6 +323,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         for prod in productions {
             let title = prod.title.as_deref().unwrap_or("unknown").to_string();
+            let companies = match RecordId::parse_simple(&prod.id) {
+                Ok(id) => CompanyCreditModel::company_lines(&id).await.unwrap_or_default(),
+                Err(_) => Vec::new(),
+            };
             let embedding_text = build_production_embedding_text(
                 &title,
                 prod.production_type.as_deref().unwrap_or(""),
@@ -308,6 +335,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 prod.location.as_deref(),
                 prod.start_date.as_deref(),
                 prod.end_date.as_deref(),
+                &companies,
             );
 
             match generate_embedding(&embedding_text) {
@@ -0,0 +1,135 @@
+//! CLI tool to manage the full-text and vector indexes used by search.
+//!
+//! Indexes are declared in `slatehub::services::search_indexes`. Before any
+//! index is written, the embedding model is loaded and the length of a probe
+//! vector is compared against every vector index dimension.
+//!
+//! Usage: cargo run --bin search-indexes -- <list|check|apply|rebuild>
+//!   or:  make search-indexes CMD=<list|check|apply|rebuild>
+//!
+//!   list     Show the spec and which indexes exist in the database
+//!   check    Verify vector index dimensions against the embedding model
+//!   apply    Define (or redefine) all analyzers and indexes
+//!   rebuild  Define all indexes, then rebuild each one over existing rows
+
+use slatehub::config::Config;
+use slatehub::db::DB;
+use slatehub::services::embedding::{generate_embedding, init_embedding_service};
+use slatehub::services::search_indexes::{self, ANALYZERS, INDEXES};
+use surrealdb::engine::remote::ws::Ws;
+use surrealdb::opt::auth::Root;
+
+const USAGE: &str = "Usage: search-indexes <list|check|apply|rebuild>";
+
+async fn connect(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let db_url = config.database.connection_url();
+    println!("Connecting to database at: {}", db_url);
+    DB.connect::<Ws>(&db_url).await?;
+    DB.signin(Root {
+        username: config.database.username.clone(),
+        password: config.database.password.clone(),
+    })
+    .await?;
+    DB.use_ns(&config.database.namespace)
+        .use_db(&config.database.name)
+        .await?;
+    println!("Connected to database.\n");
+    Ok(())
+}
+
+/// Load the embedding model and make sure every vector index matches its output size.
+async fn check_dimensions() -> Result<(), Box<dyn std::error::Error>> {
+    println!("Loading embedding model to verify vector dimensions...");
+    init_embedding_service().await?;
+    let model_dimension = generate_embedding("dimension probe")?.len();
+    println!("Embedding model produces {}-dimensional vectors.", model_dimension);
+
+    if model_dimension != search_indexes::EMBEDDING_DIMENSION {
+        eprintln!(
+            "  Warning: EMBEDDING_DIMENSION is {} but the model produced {}",
+            search_indexes::EMBEDDING_DIMENSION,
+            model_dimension
+        );
+    }
+
+    match search_indexes::verify_dimensions(model_dimension) {
+        Ok(()) => {
+            println!("All vector indexes match.\n");
+            Ok(())
+        }
+        Err(mismatches) => {
+            for m in &mismatches {
+                eprintln!("  Mismatch: {}", m);
+            }
+            Err(format!("{} vector index(es) do not match the embedding model", mismatches.len()).into())
+        }
+    }
+}
+
+async fn list() -> Result<(), Box<dyn std::error::Error>> {
+    for analyzer in ANALYZERS {
+        println!("analyzer  {:<28} {}", analyzer.name, analyzer.define_statement());
+    }
+    println!();
+
+    for spec in INDEXES {
+        let existing = search_indexes::existing_indexes(spec.table).await?;
+        let state = if existing.iter().any(|n| n == spec.name) { "present" } else { "MISSING" };
+        println!(
+            "{:<9} {:<28} {:<14} {}",
+            spec.kind_label(),
+            spec.name,
+            spec.table,
+            state
+        );
+    }
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv::dotenv().ok();
+    slatehub::logging::init();
+
+    let command = match std::env::args().nth(1) {
+        Some(c) => c,
+        None => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let config = Config::from_env()?;
+
+    match command.as_str() {
+        "list" => {
+            connect(&config).await?;
+            list().await?;
+        }
+        "check" => {
+            check_dimensions().await?;
+        }
+        "apply" | "rebuild" => {
+            let rebuild = command == "rebuild";
+            check_dimensions().await?;
+            connect(&config).await?;
+
+            println!(
+                "{} {} analyzer(s) and {} index(es)...",
+                if rebuild { "Defining and rebuilding" } else { "Defining" },
+                ANALYZERS.len(),
+                INDEXES.len()
+            );
+            search_indexes::apply(rebuild).await?;
+
+            println!("========================================");
+            println!("Search indexes {}.", if rebuild { "rebuilt" } else { "applied" });
+        }
+        other => {
+            eprintln!("Unknown command: {}\n{}", other, USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use serde::Deserialize;
-use std::env;
 use std::net::SocketAddr;
 use thiserror::Error;
 
+use crate::settings::var;
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     pub database: DatabaseConfig,
@@ -31,13 +32,17 @@ pub enum ConfigError {
     MissingEnvVar(String),
     #[error("Invalid value for {0}: {1}")]
     InvalidValue(String, String),
+    #[error("Invalid configuration: {}", .0.join("; "))]
+    Invalid(Vec<String>),
 }
 
 impl Config {
-    /// Load configuration from environment variables
+    /// Load configuration from environment variables and the config file,
+    /// see `crate::settings`
     pub fn from_env() -> Result<Self, ConfigError> {
         // Load .env file if it exists (safe to call multiple times)
         dotenv::dotenv().ok();
+        crate::settings::validate()?;
 
         Ok(Config {
             database: DatabaseConfig::from_env()?,
@@ -49,8 +54,8 @@ impl Config {
 impl DatabaseConfig {
     fn from_env() -> Result<Self, ConfigError> {
         Ok(DatabaseConfig {
-            host: env::var("DB_HOST").unwrap_or_else(|_| "localhost".to_string()),
-            port: env::var("DB_PORT")
+            host: var("DB_HOST").unwrap_or_else(|_| "localhost".to_string()),
+            port: var("DB_PORT")
                 .unwrap_or_else(|_| "8000".to_string())
                 .parse()
                 .map_err(|_| {
@@ -59,21 +64,21 @@ impl DatabaseConfig {
                         "must be a valid port number".to_string(),
                     )
                 })?,
-            username: env::var("DB_USERNAME")
-                .or_else(|_| env::var("DB_USER"))
+            username: var("DB_USERNAME")
+                .or_else(|_| var("DB_USER"))
                 .map_err(|_| ConfigError::MissingEnvVar("DB_USERNAME or DB_USER".to_string()))?,
-            password: env::var("DB_PASSWORD")
-                .or_else(|_| env::var("DB_PASS"))
+            password: var("DB_PASSWORD")
+                .or_else(|_| var("DB_PASS"))
                 .map_err(|_| ConfigError::MissingEnvVar("DB_PASSWORD or DB_PASS".to_string()))?,
-            namespace: env::var("DB_NAMESPACE").unwrap_or_else(|_| "slatehub".to_string()),
-            name: env::var("DB_NAME").unwrap_or_else(|_| "main".to_string()),
+            namespace: var("DB_NAMESPACE").unwrap_or_else(|_| "slatehub".to_string()),
+            name: var("DB_NAME").unwrap_or_else(|_| "main".to_string()),
         })
     }
 
     /// Get the database connection URL
     pub fn connection_url(&self) -> String {
         // Check if DATABASE_URL is explicitly set
-        if let Ok(url) = env::var("DATABASE_URL") {
+        if let Ok(url) = var("DATABASE_URL") {
             if !url.is_empty() {
                 return url;
             }
@@ -82,18 +87,47 @@ impl DatabaseConfig {
         // Otherwise construct it from individual components
         format!("{}:{}", self.host, self.port)
     }
+
+    /// Separate endpoint for read-heavy queries (search, browse), from DATABASE_READ_URL.
+    /// Uses the same credentials, namespace and database as the primary.
+    pub fn read_endpoint(&self) -> Option<String> {
+        var("DATABASE_READ_URL").ok().filter(|u| !u.trim().is_empty())
+    }
+
+    /// Endpoints to try, in order, when connecting or failing over.
+    /// Reads a comma-separated DATABASE_URLS list, falling back to `connection_url()`.
+    pub fn endpoints(&self) -> Vec<String> {
+        let urls: Vec<String> = var("DATABASE_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty())
+            .collect();
+
+        if urls.is_empty() {
+            vec![self.connection_url()]
+        } else {
+            urls
+        }
+    }
 }
 
 /// Get the application base URL (e.g. "https://slatehub.com").
 /// Reads from APP_URL env var, defaults to "http://localhost:3000".
 /// Returned without a trailing slash.
 pub fn app_url() -> String {
-    env::var("APP_URL")
+    var("APP_URL")
         .unwrap_or_else(|_| "http://localhost:3000".to_string())
         .trim_end_matches('/')
         .to_string()
 }
 
+/// Whether cookies are marked `Secure`. On unless COOKIE_SECURE is "false",
+/// for local development over plain HTTP.
+pub fn cookie_secure() -> bool {
+    var("COOKIE_SECURE").unwrap_or_else(|_| "true".to_string()) != "false"
+}
+
 /// Search scoring weights — configurable via env vars.
 #[derive(Debug, Clone)]
 pub struct SearchWeights {
@@ -102,22 +136,122 @@ pub struct SearchWeights {
     pub location_match: i32,
     pub vector_multiplier: i32,
     pub vector_threshold: f64,
+    /// Score for each tie a result has to a signed-in searcher (same city,
+    /// a shared organization, a past collaboration); people and productions
+    /// only
+    pub connection_match: i32,
+    /// Most rows ranked per table for one search, keyword hits included
+    /// (0 = no limit)
+    pub max_candidates: usize,
+    /// How long one table's query may take before the page is shown without
+    /// it, in milliseconds (0 = no limit)
+    pub timeout_ms: u64,
 }
 
 impl SearchWeights {
+    pub const DEFAULT: SearchWeights = SearchWeights {
+        name_match: 50,
+        headline_match: 20,
+        location_match: 10,
+        vector_multiplier: 50,
+        vector_threshold: 0.75,
+        connection_match: 10,
+        max_candidates: 0,
+        timeout_ms: 3000,
+    };
+
     pub fn from_env() -> Self {
-        fn parse_or(var: &str, default: i32) -> i32 {
-            env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        Self::from_prefix("SEARCH", &Self::DEFAULT)
+    }
+
+    /// Weights from `{prefix}_WEIGHT_NAME`, `{prefix}_VECTOR_THRESHOLD` and
+    /// so on, with `defaults` for any that aren't set
+    pub fn from_prefix(prefix: &str, defaults: &SearchWeights) -> Self {
+        fn parse_or<T: std::str::FromStr>(var: &str, default: T) -> T {
+            var(var).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        }
+        Self {
+            name_match: parse_or(&format!("{prefix}_WEIGHT_NAME"), defaults.name_match),
+            headline_match: parse_or(&format!("{prefix}_WEIGHT_HEADLINE"), defaults.headline_match),
+            location_match: parse_or(&format!("{prefix}_WEIGHT_LOCATION"), defaults.location_match),
+            vector_multiplier: parse_or(&format!("{prefix}_WEIGHT_VECTOR"), defaults.vector_multiplier),
+            vector_threshold: parse_or(&format!("{prefix}_VECTOR_THRESHOLD"), defaults.vector_threshold),
+            connection_match: parse_or(&format!("{prefix}_WEIGHT_CONNECTION"), defaults.connection_match),
+            max_candidates: parse_or(&format!("{prefix}_MAX_CANDIDATES"), defaults.max_candidates),
+            timeout_ms: parse_or(&format!("{prefix}_TIMEOUT_MS"), defaults.timeout_ms),
+        }
+    }
+
+    /// Cap a query's row window at `max_candidates`
+    pub fn candidate_window(&self, window: usize) -> usize {
+        match self.max_candidates {
+            0 => window,
+            max => window.min(max),
         }
-        fn parse_f64_or(var: &str, default: f64) -> f64 {
-            env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+    }
+
+    /// The per-table time limit, if there is one
+    pub fn timeout(&self) -> Option<std::time::Duration> {
+        (self.timeout_ms > 0).then(|| std::time::Duration::from_millis(self.timeout_ms))
+    }
+}
+
+/// Search tuning per entity type. Each type reads its own settings, e.g.
+/// `SEARCH_PEOPLE_VECTOR_THRESHOLD` or `[search.jobs] max_candidates` in the
+/// config file, and falls back to the shared `SEARCH_*` value for the rest.
+#[derive(Debug, Clone)]
+pub struct SearchConfig {
+    pub people: SearchWeights,
+    pub organizations: SearchWeights,
+    pub locations: SearchWeights,
+    pub productions: SearchWeights,
+    pub jobs: SearchWeights,
+}
+
+impl SearchConfig {
+    /// Settings for every type under `prefix`, falling back to `shared`
+    pub fn from_prefix(prefix: &str, shared: &SearchWeights) -> Self {
+        let entity = |name: &str| SearchWeights::from_prefix(&format!("{prefix}_{name}"), shared);
+        Self {
+            people: entity("PEOPLE"),
+            organizations: entity("ORGANIZATIONS"),
+            locations: entity("LOCATIONS"),
+            productions: entity("PRODUCTIONS"),
+            jobs: entity("JOBS"),
         }
+    }
+
+    /// The same weights for every type
+    pub fn uniform(weights: &SearchWeights) -> Self {
+        Self {
+            people: weights.clone(),
+            organizations: weights.clone(),
+            locations: weights.clone(),
+            productions: weights.clone(),
+            jobs: weights.clone(),
+        }
+    }
+
+    /// Adjust every type's weights the same way
+    pub fn map(&self, f: impl Fn(&SearchWeights) -> SearchWeights) -> Self {
         Self {
-            name_match: parse_or("SEARCH_WEIGHT_NAME", 50),
-            headline_match: parse_or("SEARCH_WEIGHT_HEADLINE", 20),
-            location_match: parse_or("SEARCH_WEIGHT_LOCATION", 10),
-            vector_multiplier: parse_or("SEARCH_WEIGHT_VECTOR", 50),
-            vector_threshold: parse_f64_or("SEARCH_VECTOR_THRESHOLD", 0.75),
+            people: f(&self.people),
+            organizations: f(&self.organizations),
+            locations: f(&self.locations),
+            productions: f(&self.productions),
+            jobs: f(&self.jobs),
+        }
+    }
+
+    /// Weights for records in `table`
+    pub fn for_table(&self, table: &str) -> Option<&SearchWeights> {
+        match table {
+            "person" => Some(&self.people),
+            "organization" => Some(&self.organizations),
+            "location" => Some(&self.locations),
+            "production" => Some(&self.productions),
+            "job_posting" => Some(&self.jobs),
+            _ => None,
         }
     }
 }
@@ -129,38 +263,580 @@ static SEARCH_WEIGHTS: std::sync::LazyLock<SearchWeights> =
         SearchWeights::from_env()
     });
 
+/// Shared search weights, before any per-type settings
 pub fn search_weights() -> &'static SearchWeights {
     &SEARCH_WEIGHTS
 }
 
+static SEARCH_CONFIG: std::sync::LazyLock<SearchConfig> =
+    std::sync::LazyLock::new(|| SearchConfig::from_prefix("SEARCH", search_weights()));
+
+/// Per-type search weights for the web and the API
+pub fn search_config() -> &'static SearchConfig {
+    &SEARCH_CONFIG
+}
+
 /// MCP-specific search weights — typically lower thresholds since the LLM filters results itself.
 static MCP_SEARCH_WEIGHTS: std::sync::LazyLock<SearchWeights> =
     std::sync::LazyLock::new(|| {
         dotenv::dotenv().ok();
-        fn parse_or(var: &str, default: i32) -> i32 {
-            env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        SearchWeights::from_prefix(
+            "MCP_SEARCH",
+            &SearchWeights {
+                vector_threshold: 0.55,
+                ..SearchWeights::DEFAULT
+            },
+        )
+    });
+
+pub fn mcp_search_weights() -> &'static SearchWeights {
+    &MCP_SEARCH_WEIGHTS
+}
+
+static MCP_SEARCH_CONFIG: std::sync::LazyLock<SearchConfig> =
+    std::sync::LazyLock::new(|| SearchConfig::from_prefix("MCP_SEARCH", mcp_search_weights()));
+
+pub fn mcp_search_config() -> &'static SearchConfig {
+    &MCP_SEARCH_CONFIG
+}
+
+/// Database query instrumentation — configurable via env vars.
+#[derive(Debug, Clone)]
+pub struct QueryInstrumentation {
+    /// Queries slower than this are logged with their route (0 disables)
+    pub slow_query_ms: u64,
+    /// Maximum queries per request before a warning is logged (0 disables)
+    pub query_budget: u32,
+    /// Fail queries once the budget is exceeded instead of only warning
+    pub enforce_budget: bool,
+}
+
+impl QueryInstrumentation {
+    pub fn from_env() -> Self {
+        Self {
+            slow_query_ms: var("DB_SLOW_QUERY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(250),
+            query_budget: var("DB_QUERY_BUDGET")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            enforce_budget: var("DB_QUERY_BUDGET_ENFORCE")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
         }
-        fn parse_f64_or(var: &str, default: f64) -> f64 {
-            env::var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+    }
+}
+
+static QUERY_INSTRUMENTATION: std::sync::LazyLock<QueryInstrumentation> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        QueryInstrumentation::from_env()
+    });
+
+pub fn query_instrumentation() -> &'static QueryInstrumentation {
+    &QUERY_INSTRUMENTATION
+}
+
+/// Password rules for new passwords — configurable via env vars.
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_mixed_case: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Local breached-hash range files, see `services::password_policy`
+    pub breach_dir: Option<std::path::PathBuf>,
+}
+
+impl PasswordPolicy {
+    pub fn from_env() -> Self {
+        let flag = |var: &str| var(var).map(|v| v == "true" || v == "1").unwrap_or(false);
+        Self {
+            min_length: var("PASSWORD_MIN_LENGTH")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(8)
+                .max(8),
+            require_mixed_case: flag("PASSWORD_REQUIRE_MIXED_CASE"),
+            require_digit: flag("PASSWORD_REQUIRE_DIGIT"),
+            require_symbol: flag("PASSWORD_REQUIRE_SYMBOL"),
+            breach_dir: var("PASSWORD_BREACH_DIR")
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .map(std::path::PathBuf::from),
         }
-        SearchWeights {
-            name_match: parse_or("MCP_SEARCH_WEIGHT_NAME", 50),
-            headline_match: parse_or("MCP_SEARCH_WEIGHT_HEADLINE", 20),
-            location_match: parse_or("MCP_SEARCH_WEIGHT_LOCATION", 10),
-            vector_multiplier: parse_or("MCP_SEARCH_WEIGHT_VECTOR", 50),
-            vector_threshold: parse_f64_or("MCP_SEARCH_VECTOR_THRESHOLD", 0.55),
+    }
+}
+
+static PASSWORD_POLICY: std::sync::LazyLock<PasswordPolicy> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        PasswordPolicy::from_env()
+    });
+
+pub fn password_policy() -> &'static PasswordPolicy {
+    &PASSWORD_POLICY
+}
+
+/// Failed-login lockout and new-device verification — configurable via env vars.
+#[derive(Debug, Clone)]
+pub struct LoginSecurity {
+    pub max_failures: u32,
+    pub max_failures_per_ip: u32,
+    pub failure_window_mins: i64,
+    pub lockout_mins: i64,
+    pub verify_new_devices: bool,
+    /// Reverse proxies in front of the server whose `X-Forwarded-For` entries
+    /// are believed (0: use the socket address)
+    pub trusted_proxy_hops: usize,
+}
+
+impl LoginSecurity {
+    pub fn from_env() -> Self {
+        fn parse_or<T: std::str::FromStr>(var: &str, default: T) -> T {
+            var(var).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        }
+        Self {
+            max_failures: parse_or("LOGIN_MAX_FAILURES", 5),
+            max_failures_per_ip: parse_or("LOGIN_MAX_FAILURES_PER_IP", 20),
+            failure_window_mins: parse_or("LOGIN_FAILURE_WINDOW_MINS", 15),
+            lockout_mins: parse_or("LOGIN_LOCKOUT_MINS", 15),
+            verify_new_devices: var("LOGIN_VERIFY_NEW_DEVICES")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            trusted_proxy_hops: parse_or("TRUSTED_PROXY_HOPS", 0),
         }
+    }
+}
+
+static LOGIN_SECURITY: std::sync::LazyLock<LoginSecurity> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        LoginSecurity::from_env()
     });
 
-pub fn mcp_search_weights() -> &'static SearchWeights {
-    &MCP_SEARCH_WEIGHTS
+pub fn login_security() -> &'static LoginSecurity {
+    &LOGIN_SECURITY
+}
+
+/// Current Terms of Service and Privacy Policy versions. Bumping either sends
+/// signed-in users through the re-acceptance page.
+#[derive(Debug, Clone)]
+pub struct LegalVersions {
+    pub terms: String,
+    pub privacy: String,
+}
+
+impl LegalVersions {
+    pub fn from_env() -> Self {
+        Self {
+            terms: var("TERMS_VERSION").unwrap_or_else(|_| "2026-03".to_string()),
+            privacy: var("PRIVACY_VERSION").unwrap_or_else(|_| "2026-03".to_string()),
+        }
+    }
+
+    /// Current version of a document ("terms" or "privacy")
+    pub fn version_of(&self, document: &str) -> Option<&str> {
+        match document {
+            "terms" => Some(&self.terms),
+            "privacy" => Some(&self.privacy),
+            _ => None,
+        }
+    }
+}
+
+static LEGAL_VERSIONS: std::sync::LazyLock<LegalVersions> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        LegalVersions::from_env()
+    });
+
+pub fn legal_versions() -> &'static LegalVersions {
+    &LEGAL_VERSIONS
+}
+
+/// Third-party identity verification provider. Without a start URL and
+/// webhook secret, people verify by uploading a document for admin review.
+#[derive(Debug, Clone)]
+pub struct IdVerification {
+    pub provider_name: String,
+    pub provider_start_url: Option<String>,
+    pub provider_secret: Option<String>,
+}
+
+impl IdVerification {
+    pub fn from_env() -> Self {
+        let non_empty = |var: &str| var(var).ok().filter(|v| !v.trim().is_empty());
+        Self {
+            provider_name: var("ID_VERIFICATION_PROVIDER_NAME")
+                .unwrap_or_else(|_| "our verification partner".to_string()),
+            provider_start_url: non_empty("ID_VERIFICATION_PROVIDER_URL"),
+            provider_secret: non_empty("ID_VERIFICATION_PROVIDER_SECRET"),
+        }
+    }
+}
+
+static ID_VERIFICATION: std::sync::LazyLock<IdVerification> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        IdVerification::from_env()
+    });
+
+pub fn id_verification() -> &'static IdVerification {
+    &ID_VERIFICATION
+}
+
+/// Server-side video transcoding. Without an ffmpeg binary, uploaded videos
+/// are played back as they were uploaded.
+#[derive(Debug, Clone)]
+pub struct Transcoding {
+    pub ffmpeg_path: Option<String>,
+    /// Longest a single transcode may run before it is killed
+    pub timeout_secs: u64,
+}
+
+impl Transcoding {
+    pub fn from_env() -> Self {
+        Self {
+            ffmpeg_path: var("FFMPEG_PATH").ok().filter(|v| !v.trim().is_empty()),
+            timeout_secs: var("TRANSCODE_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1800),
+        }
+    }
+}
+
+static TRANSCODING: std::sync::LazyLock<Transcoding> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        Transcoding::from_env()
+    });
+
+pub fn transcoding() -> &'static Transcoding {
+    &TRANSCODING
+}
+
+/// WhatsApp delivery. The bot in `whatsapp-bot/` collects queued messages with
+/// this token; without one, nothing is queued.
+#[derive(Debug, Clone)]
+pub struct WhatsApp {
+    pub bot_token: Option<String>,
+    /// Messages left unsent this long after the bot claimed them are retried
+    pub claim_timeout_secs: i64,
+    /// Attempts before a message is given up on
+    pub max_attempts: i64,
+}
+
+impl WhatsApp {
+    pub fn from_env() -> Self {
+        Self {
+            bot_token: var("WHATSAPP_BOT_TOKEN").ok().filter(|v| !v.trim().is_empty()),
+            claim_timeout_secs: var("WHATSAPP_CLAIM_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(300),
+            max_attempts: var("WHATSAPP_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(3),
+        }
+    }
+}
+
+static WHATSAPP: std::sync::LazyLock<WhatsApp> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        WhatsApp::from_env()
+    });
+
+pub fn whatsapp() -> &'static WhatsApp {
+    &WHATSAPP
+}
+
+/// Production inboxes. Mail to `{slug}.{token}@{domain}` is handed to the
+/// inbound mail webhook by the mail provider, which authenticates with the
+/// secret; without both, productions can't turn their inbox on.
+#[derive(Debug, Clone)]
+pub struct InboundMail {
+    pub domain: Option<String>,
+    pub secret: Option<String>,
+}
+
+impl InboundMail {
+    pub fn from_env() -> Self {
+        let non_empty = |var: &str| var(var).ok().filter(|v| !v.trim().is_empty());
+        Self {
+            domain: non_empty("INBOUND_MAIL_DOMAIN").map(|d| d.trim().to_ascii_lowercase()),
+            secret: non_empty("INBOUND_MAIL_SECRET"),
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.domain.is_some() && self.secret.is_some()
+    }
+}
+
+static INBOUND_MAIL: std::sync::LazyLock<InboundMail> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        InboundMail::from_env()
+    });
+
+pub fn inbound_mail() -> &'static InboundMail {
+    &INBOUND_MAIL
+}
+
+/// Admin impersonation sessions end on their own after this many minutes.
+#[derive(Debug, Clone)]
+pub struct Impersonation {
+    pub session_mins: i64,
+}
+
+impl Impersonation {
+    pub fn from_env() -> Self {
+        Self {
+            session_mins: var("IMPERSONATION_SESSION_MINS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(60),
+        }
+    }
+}
+
+static IMPERSONATION: std::sync::LazyLock<Impersonation> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        Impersonation::from_env()
+    });
+
+pub fn impersonation() -> &'static Impersonation {
+    &IMPERSONATION
+}
+
+/// Search warm-up and query cache. The listed queries, plus the most frequent
+/// recent ones from the search log, are embedded at startup and their results
+/// cached for `ttl_secs`; other queries are cached as they're searched.
+#[derive(Debug, Clone)]
+pub struct SearchCache {
+    pub warmup_queries: Vec<String>,
+    /// How many of the most searched queries of the last week to add
+    pub popular_from_log: usize,
+    /// 0 turns result caching off; popular queries are still pre-embedded
+    pub ttl_secs: u64,
+    /// Most recent queries whose embeddings, and whose result sets, are kept
+    pub max_entries: usize,
+}
+
+impl SearchCache {
+    pub fn from_env() -> Self {
+        Self {
+            warmup_queries: var("SEARCH_WARMUP_QUERIES")
+                .map(|v| {
+                    v.split(',')
+                        .map(|q| q.trim().to_string())
+                        .filter(|q| !q.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            popular_from_log: var("SEARCH_WARMUP_TOP")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(25),
+            ttl_secs: var("SEARCH_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(120),
+            max_entries: var("SEARCH_CACHE_MAX_ENTRIES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1000),
+        }
+    }
+}
+
+static SEARCH_CACHE: std::sync::LazyLock<SearchCache> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        SearchCache::from_env()
+    });
+
+pub fn search_cache() -> &'static SearchCache {
+    &SEARCH_CACHE
+}
+
+/// Public read API rate limits, in requests per minute. Anonymous callers are
+/// counted by IP; callers sending an OAuth access token by app.
+#[derive(Debug, Clone)]
+pub struct PublicApi {
+    pub requests_per_minute: u32,
+    pub app_requests_per_minute: u32,
+}
+
+impl PublicApi {
+    pub fn from_env() -> Self {
+        fn parse_or(name: &str, default: u32) -> u32 {
+            var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+        }
+        Self {
+            requests_per_minute: parse_or("PUBLIC_API_RATE_LIMIT", 60),
+            app_requests_per_minute: parse_or("PUBLIC_API_APP_RATE_LIMIT", 600),
+        }
+    }
+}
+
+static PUBLIC_API: std::sync::LazyLock<PublicApi> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        PublicApi::from_env()
+    });
+
+pub fn public_api() -> &'static PublicApi {
+    &PUBLIC_API
+}
+
+/// Searches allowed per IP per minute on `/search` and `/api/search`, which
+/// cost an embedding and several queries each when uncached. 0 turns the
+/// limit off.
+#[derive(Debug, Clone)]
+pub struct SearchRateLimit {
+    pub requests_per_minute: u32,
+}
+
+impl SearchRateLimit {
+    pub fn from_env() -> Self {
+        Self {
+            requests_per_minute: var("SEARCH_RATE_LIMIT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(60),
+        }
+    }
+}
+
+static SEARCH_RATE_LIMIT: std::sync::LazyLock<SearchRateLimit> = std::sync::LazyLock::new(|| {
+    dotenv::dotenv().ok();
+    SearchRateLimit::from_env()
+});
+
+pub fn search_rate_limit() -> &'static SearchRateLimit {
+    &SEARCH_RATE_LIMIT
+}
+
+/// Second-pass reranking of search results. With `SEARCH_RERANK` on, a
+/// cross-encoder model is loaded at startup and re-scores the top candidates
+/// of queries with at least `SEARCH_RERANK_MIN_WORDS` words against the query
+/// text, which sharpens long natural-language searches.
+#[derive(Debug, Clone)]
+pub struct SearchRerank {
+    pub enabled: bool,
+    pub min_words: usize,
+}
+
+impl SearchRerank {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: var("SEARCH_RERANK")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            min_words: var("SEARCH_RERANK_MIN_WORDS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(4),
+        }
+    }
+
+    /// Whether `query` is long enough to be worth reranking
+    pub fn applies_to(&self, query: &str) -> bool {
+        self.enabled && query.split_whitespace().count() >= self.min_words
+    }
+}
+
+static SEARCH_RERANK: std::sync::LazyLock<SearchRerank> = std::sync::LazyLock::new(|| {
+    dotenv::dotenv().ok();
+    SearchRerank::from_env()
+});
+
+pub fn search_rerank() -> &'static SearchRerank {
+    &SEARCH_RERANK
+}
+
+/// How embeddings are stored. `EMBEDDING_PRECISION` is `f32` (default) or
+/// `int8`; switching converts existing records in the background.
+/// `EMBEDDING_FIELDS` (default true) also embeds each passage of a record so
+/// search can explain why it matched.
+#[derive(Debug, Clone)]
+pub struct EmbeddingStorage {
+    pub precision: crate::services::embedding::Precision,
+    pub fields: bool,
+}
+
+impl EmbeddingStorage {
+    pub fn from_env() -> Self {
+        use crate::services::embedding::Precision;
+
+        let precision = match var("EMBEDDING_PRECISION") {
+            Ok(value) if !value.trim().is_empty() => Precision::parse(&value).unwrap_or_else(|| {
+                tracing::warn!("Unknown EMBEDDING_PRECISION '{}', using f32", value);
+                Precision::F32
+            }),
+            _ => Precision::F32,
+        };
+        let fields = var("EMBEDDING_FIELDS")
+            .map(|v| v == "true" || v == "1")
+            .unwrap_or(true);
+        Self { precision, fields }
+    }
+}
+
+static EMBEDDING_STORAGE: std::sync::LazyLock<EmbeddingStorage> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        EmbeddingStorage::from_env()
+    });
+
+pub fn embedding_storage() -> &'static EmbeddingStorage {
+    &EMBEDDING_STORAGE
+}
+
+/// Multi-tenancy: each tenant is served from its own namespace/database,
+/// chosen by the request's hostname. Off unless `MULTI_TENANT` is set.
+#[derive(Debug, Clone)]
+pub struct MultiTenancy {
+    pub enabled: bool,
+    /// Schema run against a new tenant's database when it is provisioned
+    pub schema_path: String,
+}
+
+impl MultiTenancy {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: var("MULTI_TENANT")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            schema_path: var("TENANT_SCHEMA_PATH").unwrap_or_else(|_| "../db/schema.surql".to_string()),
+        }
+    }
+}
+
+static MULTI_TENANCY: std::sync::LazyLock<MultiTenancy> =
+    std::sync::LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        MultiTenancy::from_env()
+    });
+
+pub fn multi_tenancy() -> &'static MultiTenancy {
+    &MULTI_TENANCY
 }
 
 impl ServerConfig {
     fn from_env() -> Result<Self, ConfigError> {
         Ok(ServerConfig {
-            host: env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-            port: env::var("SERVER_PORT")
+            host: var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
+            port: var("SERVER_PORT")
                 .unwrap_or_else(|_| "3000".to_string())
                 .parse()
                 .map_err(|_| {
@@ -0,0 +1,29 @@
+//! Minimal CSV writing for exports (RFC 4180 quoting).
+//!
+//! Cells that a spreadsheet would run as a formula are prefixed with a quote
+//! so exported user text can't execute when opened in Excel or Sheets.
+
+/// Escape a single cell
+pub fn field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// One CSV line, including the trailing CRLF
+pub fn row<S: AsRef<str>>(cells: &[S]) -> String {
+    let mut line = cells
+        .iter()
+        .map(|cell| field(cell.as_ref()))
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
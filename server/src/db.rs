@@ -1,17 +1,513 @@
+use crate::config::{DatabaseConfig, QueryInstrumentation};
+use crate::error::Error;
 use crate::log_db_error;
-use std::sync::LazyLock;
+use std::collections::HashMap;
+use std::future::{Future, IntoFuture};
+use std::ops::Deref;
+use std::panic::Location;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, OnceLock};
+use std::time::{Duration, Instant};
+use surrealdb::engine::remote::ws::Ws;
+use surrealdb::opt::auth::Root;
+use surrealdb::types::SurrealValue;
 use surrealdb::{Surreal, engine::remote::ws::Client};
-use tracing::{debug, info, instrument};
+use tokio::sync::Notify;
+use tracing::{debug, error, info, instrument, warn};
 
-pub static DB: LazyLock<Surreal<Client>> = LazyLock::new(|| {
+/// The database for the current request: the tenant's own namespace/database
+/// when one is in scope (see `with_tenant`), otherwise the primary.
+pub static DB: Database = Database;
+
+/// Handle behind `DB`. Dereferences to the client for the tenant in scope.
+pub struct Database;
+
+impl Database {
+    fn client(&self) -> &'static Surreal<Client> {
+        TENANT.try_with(|t| t.client).unwrap_or(&*PRIMARY)
+    }
+
+    /// Start a query on the client in scope, counted against the current
+    /// request when it's awaited (see `Query`)
+    #[track_caller]
+    pub fn query(&self, query: impl Into<String>) -> Query {
+        Query::new(self.client(), query.into())
+    }
+}
+
+impl Deref for Database {
+    type Target = Surreal<Client>;
+
+    fn deref(&self) -> &Surreal<Client> {
+        self.client()
+    }
+}
+
+/// Client for the install's own namespace/database
+static PRIMARY: LazyLock<Surreal<Client>> = LazyLock::new(|| {
     debug!("Initializing database client");
     Surreal::init()
 });
 
+/// The primary client regardless of tenant, for install-wide data such as the
+/// tenant registry and scheduler state.
+pub fn primary() -> &'static Surreal<Client> {
+    &PRIMARY
+}
+
+/// Client for the read replica (`DATABASE_READ_URL`). Only used through
+/// `reader()`, which falls back to `DB` when no replica is configured or it is down.
+pub static DB_READ: LazyLock<Surreal<Client>> = LazyLock::new(|| {
+    debug!("Initializing read replica client");
+    Surreal::init()
+});
+
+/// Whether `DB_READ` is connected and passed its last health check
+static REPLICA_AVAILABLE: AtomicBool = AtomicBool::new(false);
+
+tokio::task_local! {
+    static PREFER_PRIMARY: bool;
+    static TENANT: TenantScope;
+}
+
+/// Client for heavy read-only work (search, browse listings): the read replica
+/// when one is configured and healthy, otherwise the primary.
+///
+/// Replicas lag behind the primary. Code that must see its own writes should
+/// use `DB` directly or run the read inside `prefer_primary`.
+pub fn reader() -> Reader {
+    // Tenants have no replica
+    if let Ok(client) = TENANT.try_with(|t| t.client) {
+        return Reader(client);
+    }
+    let forced_primary = PREFER_PRIMARY.try_with(|p| *p).unwrap_or(false);
+    if !forced_primary && REPLICA_AVAILABLE.load(Ordering::Relaxed) {
+        Reader(&DB_READ)
+    } else {
+        Reader(&PRIMARY)
+    }
+}
+
+/// The client `reader()` picked
+#[derive(Clone, Copy)]
+pub struct Reader(&'static Surreal<Client>);
+
+impl Reader {
+    /// Start a read, counted against the current request when it's awaited and
+    /// retried if the connection drops (see `ReadQuery`)
+    #[track_caller]
+    pub fn query(self, query: impl Into<String>) -> ReadQuery {
+        ReadQuery {
+            query: query.into(),
+            bindings: Vec::new(),
+            caller: Location::caller(),
+        }
+    }
+}
+
+impl Deref for Reader {
+    type Target = Surreal<Client>;
+
+    fn deref(&self) -> &Surreal<Client> {
+        self.0
+    }
+}
+
+/// Run `f` with every `reader()` call inside it routed to the primary, for
+/// read-your-writes flows such as listing a record right after creating it.
+pub async fn prefer_primary<F: Future>(f: F) -> F::Output {
+    PREFER_PRIMARY.scope(true, f).await
+}
+
+// ============================
+// Tenants
+// ============================
+
+/// A tenant's slug and connected client, in scope for a request or task
+#[derive(Clone)]
+pub struct TenantScope {
+    pub slug: String,
+    namespace: String,
+    database: String,
+    client: &'static Surreal<Client>,
+}
+
+/// Connected tenant clients by "namespace/database". Clients live for the rest
+/// of the process; there is one per tenant, so they are never many.
+static TENANT_CLIENTS: LazyLock<tokio::sync::Mutex<HashMap<String, &'static Surreal<Client>>>> =
+    LazyLock::new(|| tokio::sync::Mutex::new(HashMap::new()));
+
+/// Scope for a tenant's namespace/database, connecting on first use with the
+/// primary's credentials and active endpoint.
+pub async fn tenant_scope(slug: &str, namespace: &str, database: &str) -> Result<TenantScope, Error> {
+    let key = format!("{}/{}", namespace, database);
+    let mut clients = TENANT_CLIENTS.lock().await;
+    let client = match clients.get(&key) {
+        Some(client) => *client,
+        None => {
+            let config = tenant_config(namespace, database)
+                .ok_or_else(|| Error::Database("Database is not connected".to_string()))?;
+            let client: &'static Surreal<Client> = Box::leak(Box::new(Surreal::init()));
+            let endpoint = active_endpoint().unwrap_or_else(|| config.connection_url());
+            connect_client(client, &config, &endpoint).await?;
+            info!("Connected tenant {} to {}", slug, key);
+            clients.insert(key, client);
+            client
+        }
+    };
+    Ok(TenantScope {
+        slug: slug.to_string(),
+        namespace: namespace.to_string(),
+        database: database.to_string(),
+        client,
+    })
+}
+
+fn tenant_config(namespace: &str, database: &str) -> Option<DatabaseConfig> {
+    let mut config = DB_CONFIG.get()?.clone();
+    config.namespace = namespace.to_string();
+    config.name = database.to_string();
+    Some(config)
+}
+
+/// Run `f` against a tenant's database. `DB`, `reader()` and S3 keys inside it
+/// all resolve to the tenant.
+pub async fn with_tenant<F: Future>(scope: TenantScope, f: F) -> F::Output {
+    TENANT.scope(scope, f).await
+}
+
+/// Slug of the tenant in scope, `None` for the primary install
+pub fn current_tenant() -> Option<String> {
+    TENANT.try_with(|t| t.slug.clone()).ok()
+}
+
+/// Connection settings for the database in scope: the tenant's namespace and
+/// database when one is in scope, otherwise the primary's
+pub fn current_database_config() -> Option<DatabaseConfig> {
+    match TENANT.try_with(|t| (t.namespace.clone(), t.database.clone())) {
+        Ok((namespace, database)) => tenant_config(&namespace, &database),
+        Err(_) => DB_CONFIG.get().cloned(),
+    }
+}
+
+/// Qualify an in-memory cache key with the tenant in scope, so caches shared
+/// by the whole process never answer for another tenant's records
+pub fn tenant_key(key: &str) -> String {
+    match current_tenant() {
+        Some(slug) => format!("{}/{}", slug, key),
+        None => key.to_string(),
+    }
+}
+
+/// `tokio::spawn` that keeps the current tenant, for background work started
+/// from a request (a plain spawn would write to the primary database).
+pub fn spawn<F>(f: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match TENANT.try_with(|t| t.clone()) {
+        Ok(scope) => tokio::spawn(TENANT.scope(scope, f)),
+        Err(_) => tokio::spawn(f),
+    }
+}
+
+/// Reconnect every tenant client to the endpoint the primary is now using
+async fn reconnect_tenants() {
+    let Some(endpoint) = active_endpoint() else {
+        return;
+    };
+    let clients = TENANT_CLIENTS.lock().await;
+    for (key, client) in clients.iter() {
+        let Some((namespace, database)) = key.split_once('/') else {
+            continue;
+        };
+        let Some(config) = tenant_config(namespace, database) else {
+            continue;
+        };
+        if let Err(e) = connect_client(client, &config, &endpoint).await {
+            warn!("Tenant database {} reconnect failed: {}", key, e);
+        }
+    }
+}
+
+// ============================
+// Connection management and failover
+// ============================
+
+/// Connection settings kept for reconnecting after the initial `connect`
+static DB_CONFIG: OnceLock<DatabaseConfig> = OnceLock::new();
+
+/// Index into `DatabaseConfig::endpoints()` of the endpoint currently in use
+static ACTIVE_ENDPOINT: AtomicUsize = AtomicUsize::new(0);
+
+/// Set while a reconnect is in progress so concurrent failures don't pile up
+static RECONNECTING: AtomicBool = AtomicBool::new(false);
+
+/// Wakes the health monitor early when a query hits a connection error
+static RECONNECT_REQUESTED: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+const CONNECT_ROUNDS: u32 = 10;
+/// Times a read is retried after a connection error
+pub const READ_RETRIES: u32 = 2;
+
+/// Connect, sign in and select the namespace/database, trying each configured
+/// endpoint in turn. Retries the whole list up to 10 times, 2 seconds apart.
+pub async fn connect(config: &DatabaseConfig) -> Result<(), surrealdb::Error> {
+    let _ = DB_CONFIG.set(config.clone());
+    let endpoints = config.endpoints();
+
+    let mut last_error = None;
+    for round in 1..=CONNECT_ROUNDS {
+        for (index, endpoint) in endpoints.iter().enumerate() {
+            match connect_endpoint(config, endpoint).await {
+                Ok(()) => {
+                    ACTIVE_ENDPOINT.store(index, Ordering::Relaxed);
+                    info!("Database connection established to {}", endpoint);
+                    return Ok(());
+                }
+                Err(e) => {
+                    error!(
+                        "Failed to connect to database at {} (attempt {}/{}): {}",
+                        endpoint, round, CONNECT_ROUNDS, e
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+        if round < CONNECT_ROUNDS {
+            tokio::time::sleep(Duration::from_secs(2)).await;
+        }
+    }
+
+    Err(last_error.expect("at least one endpoint is always configured"))
+}
+
+async fn connect_endpoint(config: &DatabaseConfig, endpoint: &str) -> Result<(), surrealdb::Error> {
+    connect_client(&PRIMARY, config, endpoint).await
+}
+
+async fn connect_client(
+    client: &Surreal<Client>,
+    config: &DatabaseConfig,
+    endpoint: &str,
+) -> Result<(), surrealdb::Error> {
+    client.connect::<Ws>(endpoint).await?;
+    client
+        .signin(Root {
+            username: config.username.clone(),
+            password: config.password.clone(),
+        })
+        .await?;
+    client.use_ns(&config.namespace).use_db(&config.name).await?;
+    Ok(())
+}
+
+/// Connect the read replica if `DATABASE_READ_URL` is set. Failure is not fatal:
+/// reads stay on the primary and the health monitor keeps retrying.
+pub async fn connect_replica(config: &DatabaseConfig) {
+    let Some(endpoint) = config.read_endpoint() else {
+        return;
+    };
+
+    match connect_client(&DB_READ, config, &endpoint).await {
+        Ok(()) => {
+            REPLICA_AVAILABLE.store(true, Ordering::Relaxed);
+            info!("Read replica connected at {}", endpoint);
+        }
+        Err(e) => {
+            REPLICA_AVAILABLE.store(false, Ordering::Relaxed);
+            warn!("Read replica at {} unavailable, reading from primary: {}", endpoint, e);
+        }
+    }
+}
+
+/// Whether reads are currently being routed to the replica
+pub fn replica_available() -> bool {
+    REPLICA_AVAILABLE.load(Ordering::Relaxed)
+}
+
+/// Settings the primary connected with
+pub fn database_config() -> Option<&'static DatabaseConfig> {
+    DB_CONFIG.get()
+}
+
+/// Endpoint the client is currently connected to
+pub fn active_endpoint() -> Option<String> {
+    let config = DB_CONFIG.get()?;
+    config
+        .endpoints()
+        .get(ACTIVE_ENDPOINT.load(Ordering::Relaxed))
+        .cloned()
+}
+
+/// Cheap round trip that exercises the connection and the session
+pub async fn is_healthy() -> bool {
+    client_is_healthy(&PRIMARY).await
+}
+
+async fn client_is_healthy(client: &Surreal<Client>) -> bool {
+    matches!(
+        tokio::time::timeout(Duration::from_secs(5), client.query("RETURN true")).await,
+        Ok(Ok(_))
+    )
+}
+
+/// Take the replica out of rotation when it fails a health check and bring it
+/// back once it reconnects
+async fn check_replica() {
+    let Some(config) = DB_CONFIG.get() else {
+        return;
+    };
+    if config.read_endpoint().is_none() {
+        return;
+    }
+
+    if REPLICA_AVAILABLE.load(Ordering::Relaxed) && client_is_healthy(&DB_READ).await {
+        return;
+    }
+
+    if REPLICA_AVAILABLE.swap(false, Ordering::Relaxed) {
+        warn!("Read replica failed health check, routing reads to primary");
+    }
+    connect_replica(config).await;
+}
+
+/// Re-establish the connection, starting with the endpoint that was in use and
+/// failing over to the others. A single pass; the health monitor retries.
+pub async fn reconnect() -> bool {
+    let Some(config) = DB_CONFIG.get() else {
+        return false;
+    };
+    if RECONNECTING.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+
+    let endpoints = config.endpoints();
+    let start = ACTIVE_ENDPOINT.load(Ordering::Relaxed);
+    let mut connected = false;
+
+    for offset in 0..endpoints.len() {
+        let index = (start + offset) % endpoints.len();
+        let endpoint = &endpoints[index];
+        match connect_endpoint(config, endpoint).await {
+            Ok(()) => {
+                if index != start {
+                    warn!("Database failed over to {}", endpoint);
+                } else {
+                    info!("Database reconnected to {}", endpoint);
+                }
+                ACTIVE_ENDPOINT.store(index, Ordering::Relaxed);
+                connected = true;
+                break;
+            }
+            Err(e) => warn!("Database reconnect to {} failed: {}", endpoint, e),
+        }
+    }
+
+    if connected {
+        reconnect_tenants().await;
+    }
+
+    RECONNECTING.store(false, Ordering::SeqCst);
+    connected
+}
+
+/// Ask the health monitor to check the connection now
+pub fn request_reconnect() {
+    RECONNECT_REQUESTED.notify_one();
+}
+
+/// Spawn a background loop that checks the connection every
+/// `DB_HEALTH_CHECK_SECS` seconds (default 15) and reconnects when it fails.
+pub fn spawn_health_monitor() {
+    let interval = crate::settings::var("DB_HEALTH_CHECK_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .filter(|secs: &u64| *secs > 0)
+        .unwrap_or(15);
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                _ = RECONNECT_REQUESTED.notified() => {}
+            }
+
+            check_replica().await;
+
+            if is_healthy().await {
+                continue;
+            }
+
+            warn!("Database health check failed, reconnecting");
+            if !reconnect().await {
+                error!("Database is unreachable, will retry in {}s", interval);
+            }
+        }
+    });
+}
+
+/// Whether an error message looks like a dropped connection rather than a
+/// problem with the query itself
+pub fn is_connection_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "connection",
+        "websocket",
+        "broken pipe",
+        "not connected",
+        "timed out",
+        "channel closed",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// Run an idempotent read, retrying it (up to twice) when it fails because the
+/// connection dropped. The query is rebuilt for every attempt,
+/// so pass a closure: `retry_read("label", || DB.query(sql).bind(..))`.
+/// Reads through `reader()` already retry; this is for those that must see the
+/// primary.
+///
+/// Only use this for reads — a write may have been applied before the
+/// connection failed.
+pub async fn retry_read<F, Q, T, E>(label: &str, make_query: F) -> Result<T, Error>
+where
+    F: Fn() -> Q,
+    Q: IntoFuture<Output = Result<T, E>>,
+    E: Into<Error>,
+{
+    let mut attempt = 0;
+    loop {
+        match make_query().await.map_err(Into::into) {
+            Err(Error::Database(msg)) if should_retry_read(attempt, &msg) => {
+                attempt += 1;
+                before_retry(label, attempt, &msg).await;
+            }
+            other => return other,
+        }
+    }
+}
+
+/// Whether a read that failed with `message` is tried again, `attempt` being
+/// the number of retries so far
+pub fn should_retry_read(attempt: u32, message: &str) -> bool {
+    attempt < READ_RETRIES && is_connection_error(message)
+}
+
+/// Wake the health monitor and back off before retry number `attempt`
+async fn before_retry(label: impl std::fmt::Display, attempt: u32, message: &str) {
+    warn!(query = %label, attempt = attempt, "Retrying read after connection error: {}", message);
+    request_reconnect();
+    tokio::time::sleep(Duration::from_millis(250 * attempt as u64)).await;
+}
+
 /// Ensures the database client is initialized and ready
 pub async fn ensure_db_initialized() -> Result<(), surrealdb::Error> {
     // Force initialization of the LazyLock if not already done
-    let _ = &*DB;
+    let _ = &*PRIMARY;
 
     // Verify we can perform a basic operation
     debug!("Verifying database connection is ready");
@@ -48,3 +544,204 @@ where
         }
     }
 }
+
+// ============================
+// Per-request query instrumentation
+// ============================
+
+/// Query counters for a single request, scoped by `middleware::query_stats`.
+#[derive(Debug)]
+pub struct QueryStats {
+    pub route: String,
+    count: AtomicU32,
+    total_micros: AtomicU64,
+}
+
+impl QueryStats {
+    pub fn new(route: impl Into<String>) -> Self {
+        Self {
+            route: route.into(),
+            count: AtomicU32::new(0),
+            total_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub fn total_ms(&self) -> u64 {
+        self.total_micros.load(Ordering::Relaxed) / 1000
+    }
+
+    /// Count a query that took `elapsed`
+    pub fn record(&self, elapsed: Duration) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Why the next query is refused: the request already ran its budget of
+    /// queries and `DB_QUERY_BUDGET_ENFORCE` is on. `caller` names the query.
+    pub fn budget_error(
+        &self,
+        settings: &QueryInstrumentation,
+        caller: impl std::fmt::Display,
+    ) -> Option<String> {
+        let over = settings.enforce_budget
+            && settings.query_budget > 0
+            && self.count() >= settings.query_budget;
+        over.then(|| {
+            format!(
+                "query budget of {} exceeded on {} at {}",
+                settings.query_budget, self.route, caller
+            )
+        })
+    }
+}
+
+/// Whether a query that took `elapsed` is logged as slow (`DB_SLOW_QUERY_MS`)
+pub fn is_slow(settings: &QueryInstrumentation, elapsed: Duration) -> bool {
+    settings.slow_query_ms > 0 && elapsed.as_millis() as u64 >= settings.slow_query_ms
+}
+
+tokio::task_local! {
+    static QUERY_STATS: Arc<QueryStats>;
+}
+
+/// Run `f` with a fresh set of query counters. Queries awaited inside `f` (but
+/// not inside tasks it spawns) are counted against it.
+pub async fn with_query_stats<F: Future>(route: String, f: F) -> (F::Output, Arc<QueryStats>) {
+    let stats = Arc::new(QueryStats::new(route));
+    let output = QUERY_STATS.scope(stats.clone(), f).await;
+    (output, stats)
+}
+
+/// Number of queries run so far in the current request, if any.
+pub fn current_query_count() -> Option<u32> {
+    QUERY_STATS.try_with(|s| s.count()).ok()
+}
+
+type SdkQuery = surrealdb::method::Query<'static, Client>;
+
+/// A query started from `DB` or `reader()`. Awaiting it counts it against the
+/// current request and logs it if slower than `DB_SLOW_QUERY_MS`, naming the
+/// call site. Once a request is over `DB_QUERY_BUDGET` with
+/// `DB_QUERY_BUDGET_ENFORCE=true`, the query isn't run: taking its results
+/// gives the budget error instead.
+#[must_use = "queries do nothing unless awaited"]
+pub struct Query {
+    inner: SdkQuery,
+    caller: &'static Location<'static>,
+}
+
+impl Query {
+    #[track_caller]
+    fn new(client: &'static Surreal<Client>, query: String) -> Self {
+        Self::at(client, query, Location::caller())
+    }
+
+    fn at(
+        client: &'static Surreal<Client>,
+        query: String,
+        caller: &'static Location<'static>,
+    ) -> Self {
+        let refused = QUERY_STATS
+            .try_with(|s| s.budget_error(crate::config::query_instrumentation(), caller))
+            .ok()
+            .flatten();
+        let inner = match refused {
+            Some(message) => client.query("THROW $message").bind(("message", message)),
+            None => client.query(query),
+        };
+        Self { inner, caller }
+    }
+
+    /// Bind a parameter (a `("name", value)` pair) or an object of them
+    pub fn bind(self, bindings: impl SurrealValue + 'static) -> Self {
+        Self {
+            inner: self.inner.bind(bindings),
+            caller: self.caller,
+        }
+    }
+}
+
+impl IntoFuture for Query {
+    type Output = <SdkQuery as IntoFuture>::Output;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let stats = QUERY_STATS.try_with(|s| s.clone()).ok();
+        Box::pin(async move {
+            let start = Instant::now();
+            let result = self.inner.await;
+            let elapsed = start.elapsed();
+
+            if let Some(stats) = &stats {
+                stats.record(elapsed);
+            }
+            if is_slow(crate::config::query_instrumentation(), elapsed) {
+                warn!(
+                    query = %self.caller,
+                    route = %stats.as_ref().map(|s| s.route.as_str()).unwrap_or("-"),
+                    elapsed_ms = elapsed.as_millis() as u64,
+                    "Slow database query"
+                );
+            }
+            result
+        })
+    }
+}
+
+type Binding = Arc<dyn Fn(SdkQuery) -> SdkQuery + Send + Sync>;
+
+/// A query started from `reader()`. Reads are idempotent, so one that fails
+/// because the connection dropped is retried (up to twice) on whichever client
+/// `reader()` picks by then. Each attempt is counted like any other `Query`,
+/// and bound values are cloned for it.
+#[must_use = "queries do nothing unless awaited"]
+pub struct ReadQuery {
+    query: String,
+    bindings: Vec<Binding>,
+    caller: &'static Location<'static>,
+}
+
+impl ReadQuery {
+    /// Bind a parameter (a `("name", value)` pair) or an object of them
+    pub fn bind<T>(mut self, bindings: T) -> Self
+    where
+        T: SurrealValue + Clone + Send + Sync + 'static,
+    {
+        self.bindings
+            .push(Arc::new(move |query| query.bind(bindings.clone())));
+        self
+    }
+
+    fn attempt(&self) -> Query {
+        let mut query = Query::at(reader().0, self.query.clone(), self.caller);
+        for bind in &self.bindings {
+            query.inner = bind(query.inner);
+        }
+        query
+    }
+}
+
+impl IntoFuture for ReadQuery {
+    type Output = <Query as IntoFuture>::Output;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move {
+            let mut attempt = 0;
+            loop {
+                match self.attempt().await {
+                    Err(e) if should_retry_read(attempt, &e.to_string()) => {
+                        attempt += 1;
+                        before_retry(self.caller, attempt, &e.to_string()).await;
+                    }
+                    other => return other,
+                }
+            }
+        })
+    }
+}
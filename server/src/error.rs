@@ -1,7 +1,7 @@
 use crate::log_colored_error;
 use crate::log_db_error;
 use axum::Json;
-use axum::http::{HeaderValue, StatusCode};
+use axum::http::{HeaderValue, StatusCode, header};
 use axum::response::{IntoResponse, Response};
 use serde_json::json;
 use thiserror::Error;
@@ -39,67 +39,139 @@ pub enum Error {
     ExternalService(String),
 }
 
-impl IntoResponse for Error {
-    fn into_response(self) -> Response {
-        let (status, error_message, custom_message) = match &self {
-            Error::Database(msg) => {
-                log_db_error!(msg);
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Database error occurred",
-                    None,
-                )
+impl Error {
+    /// HTTP status this error maps to
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Error::Database(_) | Error::Template(_) | Error::Internal(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
             }
+            Error::NotFound => StatusCode::NOT_FOUND,
+            Error::BadRequest(_) => StatusCode::BAD_REQUEST,
+            Error::Unauthorized => StatusCode::UNAUTHORIZED,
+            Error::Forbidden => StatusCode::FORBIDDEN,
+            Error::Conflict(_) => StatusCode::CONFLICT,
+            Error::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::ExternalService(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+
+    /// Stable machine-readable error code. API clients can rely on these
+    /// not changing even if the human-readable messages do.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Database(_) => "database_error",
+            Error::Template(_) => "template_error",
+            Error::NotFound => "not_found",
+            Error::Internal(_) => "internal_error",
+            Error::BadRequest(_) => "bad_request",
+            Error::Unauthorized => "unauthorized",
+            Error::Forbidden => "forbidden",
+            Error::Conflict(_) => "conflict",
+            Error::Validation(_) => "validation_failed",
+            Error::ExternalService(_) => "external_service_error",
+        }
+    }
+
+    /// Short summary shown to clients. Never includes internal details.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Error::Database(_) => "Database error occurred",
+            Error::Template(_) => "Template rendering failed",
+            Error::NotFound => "Resource not found",
+            Error::Internal(_) => "Internal server error",
+            Error::BadRequest(_) => "Bad request",
+            Error::Unauthorized => "Unauthorized",
+            Error::Forbidden => "Forbidden",
+            Error::Conflict(_) => "Conflict",
+            Error::Validation(_) => "Validation failed",
+            Error::ExternalService(_) => "External service error",
+        }
+    }
+
+    /// Message that is safe to show to the user, if the error carries one.
+    /// Server-side errors keep their details in the logs only.
+    pub fn public_message(&self) -> Option<String> {
+        match self {
+            Error::BadRequest(msg) | Error::Conflict(msg) | Error::Validation(msg) => {
+                Some(msg.clone())
+            }
+            _ => None,
+        }
+    }
+
+    /// Log server-side errors with their full detail
+    pub(crate) fn log(&self) {
+        match self {
+            Error::Database(msg) => log_db_error!(msg),
             Error::Template(msg) => {
-                log_colored_error!("internal", format!("Template rendering error: {}", msg));
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Template rendering failed",
-                    None,
-                )
+                log_colored_error!("internal", format!("Template rendering error: {}", msg))
             }
-            Error::NotFound => (StatusCode::NOT_FOUND, "Resource not found", None),
             Error::Internal(msg) => {
-                log_colored_error!("internal", format!("Internal server error: {}", msg));
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Internal server error",
-                    None,
-                )
+                log_colored_error!("internal", format!("Internal server error: {}", msg))
             }
-            Error::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.as_str(), Some(msg.clone())),
-            Error::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized", None),
-            Error::Forbidden => (StatusCode::FORBIDDEN, "Forbidden", None),
-            Error::Conflict(msg) => (StatusCode::CONFLICT, msg.as_str(), Some(msg.clone())),
-            Error::Validation(msg) => (
-                StatusCode::UNPROCESSABLE_ENTITY,
-                msg.as_str(),
-                Some(msg.clone()),
-            ),
             Error::ExternalService(msg) => {
-                log_colored_error!("network", format!("External service error: {}", msg));
-                (StatusCode::BAD_GATEWAY, "External service error", None)
+                log_colored_error!("network", format!("External service error: {}", msg))
             }
-        };
+            _ => {}
+        }
+    }
+}
+
+/// RFC 7807 problem details body. `instance` and `request_id` are filled in by
+/// `error_response_middleware`, which knows the request.
+pub fn problem_json(
+    status: StatusCode,
+    code: &str,
+    title: &str,
+    detail: Option<&str>,
+    instance: Option<&str>,
+    request_id: Option<&str>,
+) -> serde_json::Value {
+    json!({
+        "type": format!("{}/problems/{}", crate::config::app_url(), code),
+        "title": title,
+        "status": status.as_u16(),
+        "detail": detail.unwrap_or(title),
+        "instance": instance,
+        "code": code,
+        "request_id": request_id,
+        // Kept for older clients that read `error` directly
+        "error": detail.unwrap_or(title),
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
 
-        // Create a JSON response with error details
-        let body = json!({
-            "error": error_message,
-            "status": status.as_u16(),
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-        });
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        self.log();
+
+        let status = self.status_code();
+        let custom_message = self.public_message();
+        let body = problem_json(
+            status,
+            self.code(),
+            self.title(),
+            custom_message.as_deref(),
+            None,
+            None,
+        );
 
-        // Add a special header to indicate this is an error that could be converted to HTML
-        // The middleware will check for this header and the Accept header to determine
-        // whether to convert to HTML
+        // The X-Error-* headers let error_response_middleware rebuild the
+        // response as an HTML page or a problem+json body with request context
         let mut response = (status, Json(body)).into_response();
-        response.headers_mut().insert(
+        let headers = response.headers_mut();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        headers.insert("X-Error-Code", HeaderValue::from_static(self.code()));
+        headers.insert(
             "X-Error-Message",
-            HeaderValue::from_str(error_message)
+            HeaderValue::from_str(custom_message.as_deref().unwrap_or(self.title()))
                 .unwrap_or_else(|_| HeaderValue::from_static("error")),
         );
         if let Some(custom_msg) = custom_message {
-            response.headers_mut().insert(
+            headers.insert(
                 "X-Error-Custom-Message",
                 HeaderValue::from_str(&custom_msg).unwrap_or_else(|_| HeaderValue::from_static("")),
             );
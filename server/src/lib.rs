@@ -1,5 +1,6 @@
 pub mod auth;
 pub mod config;
+pub mod csv;
 pub mod db;
 pub mod error;
 pub mod logging;
@@ -7,14 +8,19 @@ pub mod markdown;
 pub mod mcp;
 pub mod middleware;
 pub mod models;
+pub mod pdf;
+pub mod physical_attributes;
 pub mod record_id_ext;
 pub mod response;
 pub mod routes;
 pub mod serde_utils;
 pub mod services;
+pub mod settings;
 pub mod stats;
 pub mod social_platforms;
 pub mod templates;
+pub mod units;
+pub mod validation;
 pub mod verification_limits;
 pub mod version;
 pub mod video_platforms;
@@ -1,4 +1,3 @@
-use std::env;
 use std::fmt::Display;
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -70,7 +69,7 @@ pub fn format_colored_error<T: Display>(error_type: &str, message: T) -> String
 pub fn init() {
     // Get log format from environment, default to "dev" for better debugging
     // Options: "json", "compact", "dev", "pretty"
-    let log_format = env::var("LOG_FORMAT").unwrap_or_else(|_| "dev".to_string());
+    let log_format = crate::settings::var("LOG_FORMAT").unwrap_or_else(|_| "dev".to_string());
 
     // Create env filter from RUST_LOG or use default
     let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
@@ -1,9 +1,8 @@
 use slatehub::config::Config;
-use slatehub::db::{DB, ensure_db_initialized};
+use slatehub::db::ensure_db_initialized;
 use slatehub::services::embedding::init_embedding_service;
 use slatehub::services::s3::init_s3;
-use surrealdb::{engine::remote::ws::Ws, opt::auth::Root};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -37,7 +36,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
 
     // Connect to database using configuration
-    let db_url = config.database.connection_url();
+    let endpoints = config.database.endpoints();
 
     info!("Database Config:");
     info!("  User: {}", config.database.username);
@@ -51,71 +50,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
     info!("  Namespace: {}", config.database.namespace);
     info!("  Database: {}", config.database.name);
+    info!("  Endpoints: {}", endpoints.join(", "));
 
-    info!("Connecting to database at: {}", db_url);
-
-    let max_retries = 10;
-    let mut retry_count = 0;
-
-    loop {
-        match DB.connect::<Ws>(&db_url).await {
-            Ok(_) => {
-                info!("Database connection established");
-                break;
-            }
-            Err(e) => {
-                retry_count += 1;
-                if retry_count >= max_retries {
-                    error!(
-                        "Failed to connect to database after {} attempts: {}",
-                        max_retries, e
-                    );
-                    return Err(e.into());
-                }
-                error!(
-                    "Failed to connect to database (attempt {}/{}): {}. Retrying in 2 seconds...",
-                    retry_count, max_retries, e
-                );
-                tokio::time::sleep(std::time::Duration::from_secs(2)).await;
-            }
-        }
-    }
-
-    // Sign in to database using configured credentials
-    debug!("Authenticating with database");
-    match DB
-        .signin(Root {
-            username: config.database.username.clone(),
-            password: config.database.password.clone(),
-        })
-        .await
-    {
-        Ok(_) => info!("Database authentication successful"),
-        Err(e) => {
-            error!("Database authentication failed: {}", e);
-            return Err(e.into());
-        }
+    // Connect, authenticate and select namespace/database, failing over between endpoints
+    if let Err(e) = slatehub::db::connect(&config.database).await {
+        error!("Failed to connect to database: {}", e);
+        return Err(e.into());
     }
-
-    // Use configured namespace and database
-    debug!(
-        "Setting namespace: {} and database: {}",
+    info!(
+        "Using namespace: {} and database: {}",
         config.database.namespace, config.database.name
     );
-    match DB
-        .use_ns(&config.database.namespace)
-        .use_db(&config.database.name)
-        .await
-    {
-        Ok(_) => info!(
-            "Using namespace: {} and database: {}",
-            config.database.namespace, config.database.name
-        ),
-        Err(e) => {
-            error!("Failed to set namespace/database: {}", e);
-            return Err(e.into());
-        }
-    }
+    slatehub::db::connect_replica(&config.database).await;
 
     // Verify database is properly initialized and ready
     debug!("Verifying database initialization");
@@ -127,6 +73,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // Apply settings overridden from /admin/config
+    if let Err(e) = slatehub::settings::load_overrides().await {
+        error!("Failed to load config overrides: {}", e);
+    }
+
+    // Load status notices for the site banner
+    if let Err(e) = slatehub::services::status::refresh().await {
+        error!("Failed to load status notices: {}", e);
+    }
+
+    // Reconnect automatically if SurrealDB restarts or an endpoint goes away
+    slatehub::db::spawn_health_monitor();
+
     // Initialize S3 service
     debug!("Initializing S3 service");
     match init_s3().await {
@@ -145,6 +104,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             info!("Embedding service initialized successfully");
             // Process any embeddings that were pending when the server last stopped
             slatehub::services::embedding::backfill_pending_embeddings().await;
+            if slatehub::config::search_rerank().enabled
+                && let Err(e) = slatehub::services::embedding::init_reranker().await
+            {
+                warn!("Failed to initialize search reranker: {}", e);
+            }
+            // Pre-embed popular queries so the first searches after a deploy are fast
+            tokio::spawn(async {
+                if let Err(e) = slatehub::services::search_cache::warm_up().await {
+                    warn!("Search warm-up failed: {}", e);
+                }
+            });
         }
         Err(e) => {
             error!("Failed to initialize embedding service: {}", e);
@@ -156,19 +126,152 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Start system stats tracking
     slatehub::stats::init();
 
-    // Start daily activity cleanup (90-day retention)
-    tokio::spawn(async {
-        loop {
-            tokio::time::sleep(std::time::Duration::from_secs(86400)).await;
-            info!("Running activity event cleanup");
-            slatehub::models::activity::ActivityModel::cleanup(90).await;
-        }
-    });
+    // Register recurring background tasks and start the scheduler
+    {
+        use slatehub::services::scheduler::{self, ScheduledTask};
+        use std::time::Duration;
+
+        scheduler::register(
+            ScheduledTask::new("activity_cleanup", Duration::from_secs(86400), || {
+                slatehub::models::activity::ActivityModel::cleanup(90)
+            })
+            .with_description("Delete activity events older than 90 days")
+            .with_jitter(Duration::from_secs(600)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("pending_embeddings", Duration::from_secs(900), || async {
+                if slatehub::services::embedding::is_initialized() {
+                    slatehub::services::embedding::backfill_pending_embeddings().await;
+                }
+                Ok(())
+            })
+            .with_description("Retry embeddings that failed or were interrupted")
+            .with_jitter(Duration::from_secs(60)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("embedding_precision", Duration::from_secs(120), || async {
+                slatehub::services::embedding::convert_precision().await.map(|_| ())
+            })
+            .with_description("Convert stored embeddings to the configured EMBEDDING_PRECISION")
+            .with_jitter(Duration::from_secs(20)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("search_warmup", Duration::from_secs(6 * 3600), || {
+                slatehub::services::search_cache::warm_up()
+            })
+            .with_description("Refresh the popular search queries and their cached results")
+            .with_jitter(Duration::from_secs(600)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("weekly_digest", Duration::from_secs(900), || {
+                slatehub::services::digest::send_due()
+            })
+            .with_description("Email weekly activity digests that have come due")
+            .with_jitter(Duration::from_secs(60)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("daily_reports", Duration::from_secs(3600), || {
+                slatehub::models::daily_report::DailyReportModel::assemble_due()
+            })
+            .with_description("Draft daily production reports for shoot days that have wrapped")
+            .with_jitter(Duration::from_secs(300)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("expired_uploads", Duration::from_secs(3600), || {
+                slatehub::services::uploads::expire_stale()
+            })
+            .with_description("Discard resumable uploads that were abandoned or never claimed")
+            .with_jitter(Duration::from_secs(300)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("transcode_videos", Duration::from_secs(30), || {
+                slatehub::services::transcode::process_queue()
+            })
+            .with_description("Transcode uploaded videos into web MP4/HLS renditions and poster frames")
+            .with_jitter(Duration::from_secs(5)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("audio_waveforms", Duration::from_secs(30), || {
+                slatehub::models::audio_reel::AudioReelModel::process_queue()
+            })
+            .with_description("Draw waveforms and read tags for uploaded audio reels")
+            .with_jitter(Duration::from_secs(5)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("equipment_service_reminders", Duration::from_secs(3600), || {
+                slatehub::models::equipment_service::EquipmentServiceModel::send_reminders()
+            })
+            .with_description("Remind owners when equipment comes due for service")
+            .with_jitter(Duration::from_secs(300)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("festival_deadline_reminders", Duration::from_secs(3600), || {
+                slatehub::models::festival_submission::FestivalSubmissionModel::send_reminders()
+            })
+            .with_description("Remind productions about festival deadlines coming up")
+            .with_jitter(Duration::from_secs(300)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("expired_oauth_grants", Duration::from_secs(3600), || {
+                slatehub::services::oauth::expire_stale()
+            })
+            .with_description("Drop unused authorization codes and OAuth tokens past their refresh window")
+            .with_jitter(Duration::from_secs(300)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("profile_completeness", Duration::from_secs(86400), || {
+                slatehub::models::completeness::CompletenessModel::refresh_all()
+            })
+            .with_description("Rescore how complete each profile is, for admin analytics")
+            .with_jitter(Duration::from_secs(600)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("match_suggestions", Duration::from_secs(86400), || {
+                slatehub::models::match_suggestion::MatchSuggestionModel::refresh_all()
+            })
+            .with_description("Suggest matching profiles to job posters, weekly per posting")
+            .with_jitter(Duration::from_secs(1800)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("retention_policies", Duration::from_secs(86400), || {
+                slatehub::services::retention::enforce_all()
+            })
+            .with_description("Apply each organization's data retention rules")
+            .with_jitter(Duration::from_secs(600)),
+        );
+
+        scheduler::register(
+            ScheduledTask::new("search_alerts", Duration::from_secs(6 * 3600), || {
+                slatehub::models::search_alert::SearchAlertModel::run_all()
+            })
+            .with_description("Rerun saved searches and notify people about new matches")
+            .with_jitter(Duration::from_secs(900)),
+        );
+
+        scheduler::start().await;
+    }
 
     // Start live notification stream
     info!("Starting notification live stream");
     slatehub::services::notification_stream::init().await;
 
+    // Drop cached search results when searchable records change
+    slatehub::services::search_cache::listen_for_changes();
+
     // Create the application
     debug!("Building application routes");
     let app = slatehub::routes::app();
@@ -191,8 +294,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("SlateHub server is ready to accept connections");
 
-    // Run the server
-    match axum::serve(listener, app).await {
+    // Run the server, with each connection's address for `ClientInfo`
+    match axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    {
         Ok(_) => {
             info!("Server shutdown gracefully");
             Ok(())
@@ -9,8 +9,9 @@ use rmcp::transport::streamable_http_server::{StreamableHttpServerConfig, Stream
 use rmcp::{ServerHandler, schemars, tool, tool_handler, tool_router};
 use serde::Deserialize;
 use tokio_util::sync::CancellationToken;
-use crate::config::mcp_search_weights;
+use crate::config::mcp_search_config;
 use crate::db::DB;
+use crate::models::person_availability::AvailabilityWindow;
 use crate::services::embedding::generate_embedding_async;
 use crate::services::search::{SearchParams, search_people as svc_search_people, search_productions as svc_search_productions, search_organizations as svc_search_organizations, search_locations as svc_search_locations, search_jobs as svc_search_jobs};
 use crate::services::search_log::log_search;
@@ -29,6 +30,15 @@ pub struct SearchPeopleParams {
     /// Filter by skill or role, e.g. "cinematographer", "editor", "actor"
     #[schemars(default)]
     pub skill: Option<String>,
+    /// Only return identity-verified people
+    #[schemars(default)]
+    pub verified_only: Option<bool>,
+    /// Only return people free on every day from this date (YYYY-MM-DD)
+    #[schemars(default)]
+    pub available_from: Option<String>,
+    /// Last day people must be free (YYYY-MM-DD, inclusive); defaults to available_from
+    #[schemars(default)]
+    pub available_to: Option<String>,
     /// Maximum number of results (default 50, max 100)
     #[schemars(default)]
     pub limit: Option<usize>,
@@ -127,6 +137,7 @@ fn clamp_limit(limit: Option<usize>) -> usize {
 }
 
 use crate::services::search_utils::{parse_query, extract_location, normalize_query};
+use crate::units::{self, UnitSystem};
 
 #[tool_router]
 impl SlateHubMcp {
@@ -135,7 +146,7 @@ impl SlateHubMcp {
     /// Returns name, headline, location, skills, and profile URL.
     #[tool(
         name = "search_people",
-        description = "Search for people (actors, crew, filmmakers, creators) on SlateHub. Supports natural language queries like 'cinematographers in Berlin' with optional hard filters for location and skill."
+        description = "Search for people (actors, crew, filmmakers, creators) on SlateHub. Supports natural language queries like 'cinematographers in Berlin' with optional hard filters for location, skill and the dates people must be free."
     )]
     async fn search_people(&self, Parameters(params): Parameters<SearchPeopleParams>) -> String {
         match self.do_search_people(params).await {
@@ -301,23 +312,36 @@ impl SlateHubMcp {
         if let Some(ref loc) = params.location {
             parsed.location = Some(loc.clone());
         }
+        if let Some(verified_only) = params.verified_only {
+            parsed.verified_only = verified_only;
+        }
+        let available = AvailabilityWindow::parse(
+            params.available_from.as_deref(),
+            params.available_to.as_deref(),
+        )
+        .map_err(|e| e.to_string())?;
 
         let cleaned_query = parsed.cleaned.clone();
         let query_embedding = generate_embedding_async(&cleaned_query).await.ok();
-        let weights = mcp_search_weights();
+        let config = mcp_search_config();
 
         let search_params = SearchParams {
             query: &cleaned_query,
             embedding: query_embedding.as_ref(),
-            weights,
+            config,
             limit,
             offset: 0,
+            connections: None,
+            viewer: None,
+            syntax: None,
+            scope: None,
         };
 
         let results = svc_search_people(
             &search_params,
             &parsed,
             params.skill.as_deref(),
+            available.as_ref(),
         )
         .await
         .map_err(|e| e.to_string())?;
@@ -369,14 +393,18 @@ impl SlateHubMcp {
         let cleaned_query = normalize_query(&params.query);
 
         let query_embedding = generate_embedding_async(&cleaned_query).await.ok();
-        let weights = mcp_search_weights();
+        let config = mcp_search_config();
 
         let search_params = SearchParams {
             query: &cleaned_query,
             embedding: query_embedding.as_ref(),
-            weights,
+            config,
             limit,
             offset: 0,
+            connections: None,
+            viewer: None,
+            syntax: None,
+            scope: None,
         };
 
         let results = svc_search_productions(
@@ -436,14 +464,18 @@ impl SlateHubMcp {
         let effective_location = params.location.as_ref().or(parsed_location.as_ref());
 
         let query_embedding = generate_embedding_async(&cleaned_query).await.ok();
-        let weights = mcp_search_weights();
+        let config = mcp_search_config();
 
         let search_params = SearchParams {
             query: &cleaned_query,
             embedding: query_embedding.as_ref(),
-            weights,
+            config,
             limit,
             offset: 0,
+            connections: None,
+            viewer: None,
+            syntax: None,
+            scope: None,
         };
 
         let results = svc_search_organizations(
@@ -496,14 +528,18 @@ impl SlateHubMcp {
         let effective_city = params.city.as_ref().or(parsed_city.as_ref());
 
         let query_embedding = generate_embedding_async(&cleaned_query).await.ok();
-        let weights = mcp_search_weights();
+        let config = mcp_search_config();
 
         let search_params = SearchParams {
             query: &cleaned_query,
             embedding: query_embedding.as_ref(),
-            weights,
+            config,
             limit,
             offset: 0,
+            connections: None,
+            viewer: None,
+            syntax: None,
+            scope: None,
         };
 
         let results = svc_search_locations(
@@ -568,15 +604,19 @@ impl SlateHubMcp {
         let effective_location = params.location.as_ref().or(parsed_location.as_ref());
 
         let query_embedding = generate_embedding_async(&cleaned_query).await.ok();
-        let weights = mcp_search_weights();
+        let config = mcp_search_config();
         let open_only = params.open_only.unwrap_or(true);
 
         let search_params = SearchParams {
             query: &cleaned_query,
             embedding: query_embedding.as_ref(),
-            weights,
+            config,
             limit,
             offset: 0,
+            connections: None,
+            viewer: None,
+            syntax: None,
+            scope: None,
         };
 
         let results = svc_search_jobs(
@@ -647,9 +687,9 @@ impl SlateHubMcp {
                     profile.photos AS photos,
                     profile.gender AS gender,
                     profile.height_mm AS height_mm,
-                    profile.body_type AS body_type,
-                    profile.hair_color AS hair_color,
-                    profile.eye_color AS eye_color,
+                    IF profile.body_type = 'Other' AND profile.body_type_other THEN profile.body_type_other ELSE profile.body_type END AS body_type,
+                    IF profile.hair_color = 'Other' AND profile.hair_color_other THEN profile.hair_color_other ELSE profile.hair_color END AS hair_color,
+                    IF profile.eye_color = 'Other' AND profile.eye_color_other THEN profile.eye_color_other ELSE profile.eye_color END AS eye_color,
                     profile.ethnicity AS ethnicity,
                     profile.acting_age_range AS acting_age_range,
                     profile.acting_ethnicities AS acting_ethnicities,
@@ -702,11 +742,8 @@ impl SlateHubMcp {
 
         // Physical attributes
         let mut phys = Vec::new();
-        if let Some(h) = row["height_mm"].as_i64() {
-            let cm = h;
-            let feet = cm / 30;
-            let inches = (cm % 30) * 12 / 30;
-            phys.push(format!("Height: {}cm ({}'{}\")", cm, feet, inches));
+        if let Some(mm) = row["height_mm"].as_i64().filter(|mm| *mm > 0) {
+            phys.push(format!("Height: {}", units::height_both(mm as i32, UnitSystem::Metric)));
         }
         if let Some(bt) = row["body_type"].as_str().filter(|s| !s.is_empty()) {
             phys.push(format!("Build: {}", bt));
@@ -12,6 +12,7 @@ use crate::{
     auth,
     error::Error,
     models::person::{Person, SessionUser},
+    services::impersonation,
 };
 
 // Re-export SessionUser as CurrentUser for compatibility
@@ -54,7 +55,20 @@ pub async fn auth_middleware(
 
                 // Get user info from database using the ID from JWT
                 match get_user_from_id(user_id).await {
-                    Ok(user) => {
+                    Ok(mut user) => {
+                        // An impersonation token is only good while its session runs
+                        if let Some(session_id) = &claims.imp {
+                            match impersonation::active(session_id, user_id).await {
+                                Some(impersonator) => user.impersonator = Some(impersonator),
+                                None => {
+                                    debug!(
+                                        "Auth middleware: Impersonation session '{}' is over, continuing without authentication",
+                                        session_id
+                                    );
+                                    return Ok(next.run(request).await);
+                                }
+                            }
+                        }
                         debug!(
                             "Auth middleware: Successfully authenticated user: '{}' with id: '{}' and email: '{}'",
                             user.username, user.id, user.email
@@ -88,8 +102,20 @@ pub async fn auth_middleware(
     }
 
     debug!("Auth middleware: Passing request to next handler");
+    let user = request.extensions().get::<Arc<CurrentUser>>().cloned();
+
     // Continue to the next middleware/handler
-    Ok(next.run(request).await)
+    let mut response = next.run(request).await;
+
+    // Error pages are rendered by error_response_middleware, which sits outside
+    // this layer and never sees the request extensions — hand the user over
+    if let Some(user) = user {
+        if response.status().is_client_error() || response.status().is_server_error() {
+            response.extensions_mut().insert(user);
+        }
+    }
+
+    Ok(response)
 }
 
 /// Extract user information from ID using the Person model
@@ -0,0 +1,53 @@
+//! Response compression
+//!
+//! Brotli or gzip, whichever the client prefers, for text-like responses:
+//! pages, JSON, CSS, JS and SVG. Skipped for responses that are already
+//! compressed (images, video, audio, fonts, archives, PDFs), event streams,
+//! and anything smaller than `COMPRESSION_MIN_BYTES`. Streamed responses are
+//! compressed as they go. A handler opts a response out by adding the
+//! `NoCompression` extension.
+
+use axum::http::{Extensions, HeaderMap, StatusCode, Version};
+use tower_http::compression::{
+    CompressionLayer, CompressionLevel,
+    predicate::{DefaultPredicate, NotForContentType, Predicate, SizeAbove},
+};
+
+/// Responses smaller than this aren't worth the CPU
+const DEFAULT_MIN_BYTES: u16 = 1024;
+
+/// Response extension that leaves a response uncompressed
+#[derive(Debug, Clone, Copy)]
+pub struct NoCompression;
+
+fn not_opted_out(_: StatusCode, _: Version, _: &HeaderMap, extensions: &Extensions) -> bool {
+    extensions.get::<NoCompression>().is_none()
+}
+
+/// Smallest response compressed, from `COMPRESSION_MIN_BYTES`
+pub fn min_bytes() -> u16 {
+    crate::settings::var("COMPRESSION_MIN_BYTES")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_MIN_BYTES)
+}
+
+/// The compression layer for the whole app
+pub fn layer() -> CompressionLayer<impl Predicate> {
+    // Content types below are compressed already, matched as prefixes
+    let predicate = DefaultPredicate::new()
+        .and(SizeAbove::new(min_bytes()))
+        .and(NotForContentType::const_new("video/"))
+        .and(NotForContentType::const_new("audio/"))
+        .and(NotForContentType::const_new("font/woff"))
+        .and(NotForContentType::const_new("application/zip"))
+        .and(NotForContentType::const_new("application/gzip"))
+        .and(NotForContentType::const_new("application/pdf"))
+        .and(NotForContentType::const_new("application/octet-stream"))
+        .and(not_opted_out);
+    CompressionLayer::new()
+        .br(true)
+        .gzip(true)
+        .quality(CompressionLevel::Default)
+        .compress_when(predicate)
+}
@@ -0,0 +1,47 @@
+use axum::{
+    extract::Request,
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+use surrealdb::types::RecordId;
+
+use super::auth::CurrentUser;
+use super::error_handler::accepts_html;
+use crate::services::consent;
+
+/// Middleware that sends signed-in people to `/legal/accept` when the Terms of
+/// Service or Privacy Policy changed since they last accepted them.
+/// Only page navigations are redirected; assets, APIs and form posts pass through.
+/// Must run after auth middleware so user identity is available.
+pub async fn consent_middleware(request: Request, next: Next) -> Response {
+    if request.method() != Method::GET
+        || consent::is_exempt_path(request.uri().path())
+        || !accepts_html(request.headers())
+    {
+        return next.run(request).await;
+    }
+
+    let person = request
+        .extensions()
+        .get::<Arc<CurrentUser>>()
+        .and_then(|u| RecordId::parse_simple(&u.id).ok());
+    let Some(person) = person else {
+        return next.run(request).await;
+    };
+
+    if consent::outstanding(&person).await.is_empty() {
+        return next.run(request).await;
+    }
+
+    let next_path = request
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str())
+        .unwrap_or("/");
+    crate::response::redirect_temporary(&format!(
+        "/legal/accept?next={}",
+        urlencoding::encode(next_path)
+    ))
+}
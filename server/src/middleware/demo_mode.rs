@@ -0,0 +1,63 @@
+use axum::{
+    body::{Body, to_bytes},
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use axum_extra::extract::cookie::CookieJar;
+use std::sync::Arc;
+use tracing::warn;
+
+use super::auth::CurrentUser;
+use crate::services::demo_mode::{self, Anonymizer};
+
+/// Pages larger than this are passed through untouched
+const MAX_PAGE_BYTES: usize = 8 * 1024 * 1024;
+
+/// Middleware that swaps people's details on rendered pages for made-up ones
+/// while an admin has demo mode on. Only HTML is rewritten.
+/// Must run after auth middleware so user identity is available.
+pub async fn demo_mode_middleware(request: Request, next: Next) -> Response {
+    let demo = CookieJar::from_headers(request.headers())
+        .get(demo_mode::COOKIE)
+        .is_some_and(|c| c.value() == "1");
+    let viewer = request.extensions().get::<Arc<CurrentUser>>().cloned();
+    let Some(viewer) = viewer.filter(|_| demo) else {
+        return next.run(request).await;
+    };
+    if !demo_mode::allowed(&viewer.id).await {
+        return next.run(request).await;
+    }
+
+    let response = next.run(request).await;
+    let is_html = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/html"));
+    if !is_html {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_PAGE_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("Demo mode couldn't read a page: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+    let html = String::from_utf8_lossy(&bytes);
+
+    let mut usernames = demo_mode::profile_links(&html);
+    usernames.push(viewer.username.clone());
+    let people = demo_mode::people(usernames).await.unwrap_or_else(|e| {
+        warn!("Demo mode couldn't look up people: {}", e);
+        Vec::new()
+    });
+    let html = Anonymizer::new(people).apply(&html);
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(html))
+}
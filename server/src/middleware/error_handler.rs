@@ -1,18 +1,24 @@
+use askama::Template;
 use axum::{
     Json,
     extract::Request,
-    http::{HeaderMap, StatusCode, header},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
     middleware::Next,
     response::{Html, IntoResponse, Response},
 };
-use serde_json::json;
+use std::sync::Arc;
 use tracing::{error, warn};
 
-use crate::{error::Error, middleware::RequestIdExt};
-use crate::{log_colored_error, log_db_error};
+use crate::error::{Error, PROBLEM_JSON, problem_json};
+use crate::middleware::RequestIdExt;
+use crate::models::person::SessionUser;
+use crate::templates::{
+    BaseContext, ForbiddenErrorTemplate, GenericErrorTemplate, NotFoundErrorTemplate,
+    ServerErrorTemplate, UnauthorizedErrorTemplate, User,
+};
 
 /// Check if the client accepts HTML responses
-fn accepts_html(headers: &HeaderMap) -> bool {
+pub(crate) fn accepts_html(headers: &HeaderMap) -> bool {
     headers
         .get(header::ACCEPT)
         .and_then(|v| v.to_str().ok())
@@ -20,175 +26,176 @@ fn accepts_html(headers: &HeaderMap) -> bool {
         .unwrap_or(false)
 }
 
-/// Create an error response based on the client's Accept header
+/// API routes always get problem+json, whatever the Accept header says
+fn is_api_path(path: &str) -> bool {
+    path.starts_with("/api/") || path == "/api" || path.starts_with("/mcp")
+}
+
+/// Create an error response based on the request path and Accept header
 pub fn create_error_response(
     error: &Error,
     headers: &HeaderMap,
     request_path: Option<String>,
     request_id: Option<String>,
 ) -> Response {
-    let (status, error_message, custom_message) = match error {
-        Error::Database(msg) => {
-            log_db_error!(msg);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Database error occurred",
-                None,
-            )
-        }
-        Error::Template(msg) => {
-            log_colored_error!("internal", format!("Template rendering error: {}", msg));
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Template rendering failed",
-                None,
-            )
-        }
-        Error::NotFound => (StatusCode::NOT_FOUND, "Resource not found", None),
-        Error::Internal(msg) => {
-            log_colored_error!("internal", format!("Internal server error: {}", msg));
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Internal server error",
-                None,
-            )
-        }
-        Error::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.as_str(), Some(msg.clone())),
-        Error::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized", None),
-        Error::Forbidden => (StatusCode::FORBIDDEN, "Forbidden", None),
-        Error::Conflict(msg) => (StatusCode::CONFLICT, msg.as_str(), Some(msg.clone())),
-        Error::Validation(msg) => (
-            StatusCode::UNPROCESSABLE_ENTITY,
-            msg.as_str(),
-            Some(msg.clone()),
-        ),
-        Error::ExternalService(msg) => {
-            log_colored_error!("network", format!("External service error: {}", msg));
-            (StatusCode::BAD_GATEWAY, "External service error", None)
-        }
-    };
+    error.log();
+    build_error_response(error, headers, request_path, request_id, None)
+}
 
-    if accepts_html(headers) {
-        render_html_error(
-            status,
-            error_message,
-            custom_message,
-            request_path,
-            request_id,
-        )
+fn build_error_response(
+    error: &Error,
+    headers: &HeaderMap,
+    request_path: Option<String>,
+    request_id: Option<String>,
+    user: Option<User>,
+) -> Response {
+    let is_api = request_path.as_deref().map(is_api_path).unwrap_or(false);
+
+    if !is_api && accepts_html(headers) {
+        render_html_error(error, request_path, request_id, user)
     } else {
-        render_json_error(status, error_message, request_id)
+        render_problem_json(error, request_path, request_id)
     }
 }
 
-/// Render an HTML error page
+/// Render an HTML error page from the errors/ templates
 fn render_html_error(
-    status: StatusCode,
-    error_message: &str,
-    custom_message: Option<String>,
+    error: &Error,
     request_path: Option<String>,
     request_id: Option<String>,
+    user: Option<User>,
 ) -> Response {
-    let status_code = status.as_u16();
-    let status_text = status.canonical_reason().unwrap_or("Error");
-
-    let (title, heading, description) = match status {
-        StatusCode::NOT_FOUND => (
-            "Page Not Found",
-            "404".to_string(),
-            custom_message.unwrap_or_else(|| "The page you're looking for doesn't exist or may have been moved.".to_string()),
-        ),
-        StatusCode::UNAUTHORIZED => (
-            "Unauthorized",
-            "401".to_string(),
-            custom_message.unwrap_or_else(|| "You need to be signed in to access this page.".to_string()),
-        ),
-        StatusCode::FORBIDDEN => (
-            "Access Forbidden",
-            "403".to_string(),
-            custom_message.unwrap_or_else(|| "You don't have permission to access this resource.".to_string()),
-        ),
-        StatusCode::INTERNAL_SERVER_ERROR | StatusCode::BAD_GATEWAY => (
-            "Server Error",
-            "500".to_string(),
-            custom_message.unwrap_or_else(|| "Something went wrong on our end. We've been notified and are working to fix the issue.".to_string()),
-        ),
-        StatusCode::UNPROCESSABLE_ENTITY => (
-            "Invalid Input",
-            "422".to_string(),
-            custom_message.unwrap_or_else(|| "The information you provided couldn't be processed. Please check your input and try again.".to_string()),
-        ),
-        StatusCode::BAD_REQUEST => (
-            "Bad Request",
-            "400".to_string(),
-            custom_message.unwrap_or_else(|| "Your request couldn't be understood. Please check your input and try again.".to_string()),
-        ),
-        _ => (
-            status_text,
-            status_code.to_string(),
-            custom_message.unwrap_or_else(|| error_message.to_string()),
-        ),
+    let page = ErrorPage {
+        status: error.status_code(),
+        error_code: error.code(),
+        title: error.title(),
+        message: error.public_message(),
+        retry_after: None,
     };
+    page.render(request_path, request_id, user)
+}
 
-    let _ = (request_path, request_id);
+/// An error page for a response that isn't an `Error`, such as a 429
+pub(crate) struct ErrorPage<'a> {
+    pub status: StatusCode,
+    pub error_code: &'a str,
+    /// Plain-text fallback if the page fails to render
+    pub title: &'a str,
+    pub message: Option<String>,
+    /// Seconds the client should wait before retrying
+    pub retry_after: Option<u64>,
+}
 
-    let html = format!(
-        r#"<!DOCTYPE html>
-<html lang="en" data-theme="dark">
-<head>
-    <meta charset="utf-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1">
-    <meta name="color-scheme" content="dark">
-    <title>{title} - SlateHub</title>
-    <link rel="stylesheet" href="/static/css/main.css">
-    <link rel="stylesheet" href="/static/css/pages/errors.css">
-</head>
-<body data-page="error-{status_code}" style="background:#171717;color:#d6d8ca">
-    <main id="main-content">
-        <article data-component="error-page" data-error-code="{status_code}">
-            <a href="/" data-role="error-brand" aria-label="SlateHub Home">
-                <img src="/static/images/logo.svg" alt="SlateHub" height="14" />
-            </a>
-            <header data-role="error-header">
-                <span data-role="error-code">{heading}</span>
-                <h1>{status_text}</h1>
-                <p data-role="error-description">{description}</p>
-            </header>
-            <nav data-role="error-actions">
-                <a href="/" role="button" data-type="primary">Homepage</a>
-                <a href="/login" role="button" data-type="secondary">Sign In</a>
-            </nav>
-            <footer data-role="error-footer">
-                <p>If this persists, please <a href="/contact">contact support</a>.</p>
-            </footer>
-        </article>
-    </main>
-</body>
-</html>"#,
-        title = title,
-        status_code = status_code,
-        heading = heading,
-        status_text = status_text,
-        description = description,
-    );
+impl ErrorPage<'_> {
+    pub(crate) fn render(
+        self,
+        request_path: Option<String>,
+        request_id: Option<String>,
+        user: Option<User>,
+    ) -> Response {
+        let status = self.status;
+        let base = BaseContext::new().with_page("error");
 
-    (status, Html(html)).into_response()
+        macro_rules! error_template {
+            ($template:ident) => {
+                $template {
+                    app_name: base.app_name,
+                    year: base.year,
+                    version: base.version,
+                    active_page: base.active_page,
+                    user,
+                    status_code: status.as_u16(),
+                    status_text: status.canonical_reason().unwrap_or("Error").to_string(),
+                    error_code: self.error_code.to_string(),
+                    message: self.message,
+                    request_id,
+                    request_path,
+                    timestamp: Some(
+                        chrono::Utc::now()
+                            .format("%Y-%m-%d %H:%M:%S UTC")
+                            .to_string(),
+                    ),
+                    retry_after: self.retry_after,
+                }
+                .render()
+            };
+        }
+
+        let rendered = match status {
+            StatusCode::UNAUTHORIZED => error_template!(UnauthorizedErrorTemplate),
+            StatusCode::FORBIDDEN => error_template!(ForbiddenErrorTemplate),
+            StatusCode::NOT_FOUND => error_template!(NotFoundErrorTemplate),
+            s if s.is_server_error() => error_template!(ServerErrorTemplate),
+            _ => error_template!(GenericErrorTemplate),
+        };
+
+        match rendered {
+            Ok(html) => (status, Html(html)).into_response(),
+            Err(e) => {
+                // Never fail to produce an error page: fall back to plain text
+                error!("Failed to render error page: {}", e);
+                (status, format!("{} {}", status.as_u16(), self.title)).into_response()
+            }
+        }
+    }
 }
 
-/// Render a JSON error response
-fn render_json_error(
-    status: StatusCode,
-    error_message: &str,
+/// Render an RFC 7807 problem+json response
+fn render_problem_json(
+    error: &Error,
+    request_path: Option<String>,
     request_id: Option<String>,
 ) -> Response {
-    let body = json!({
-        "error": error_message,
-        "status": status.as_u16(),
-        "request_id": request_id,
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-    });
+    let status = error.status_code();
+    let detail = error.public_message();
+    let body = problem_json(
+        status,
+        error.code(),
+        error.title(),
+        detail.as_deref(),
+        request_path.as_deref(),
+        request_id.as_deref(),
+    );
+
+    let mut response = (status, Json(body)).into_response();
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+    response
+}
+
+/// Rebuild the `Error` from the headers `Error::into_response` attached
+fn error_from_response(status: StatusCode, response: &Response) -> Error {
+    let custom_message = response
+        .headers()
+        .get("X-Error-Custom-Message")
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+        .map(String::from);
+    let code = response
+        .headers()
+        .get("X-Error-Code")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
 
-    (status, Json(body)).into_response()
+    match (status, code) {
+        (StatusCode::NOT_FOUND, _) => Error::NotFound,
+        (StatusCode::UNAUTHORIZED, _) => Error::Unauthorized,
+        (StatusCode::FORBIDDEN, _) => Error::Forbidden,
+        (StatusCode::BAD_REQUEST, _) => {
+            Error::BadRequest(custom_message.unwrap_or_else(|| "Bad request".to_string()))
+        }
+        (StatusCode::CONFLICT, _) => {
+            Error::Conflict(custom_message.unwrap_or_else(|| "Conflict".to_string()))
+        }
+        (StatusCode::UNPROCESSABLE_ENTITY, _) => {
+            Error::Validation(custom_message.unwrap_or_else(|| "Validation error".to_string()))
+        }
+        (StatusCode::BAD_GATEWAY, _) => Error::ExternalService("External service error".to_string()),
+        (_, "database_error") => Error::Database("see earlier log entry".to_string()),
+        (_, "template_error") => Error::Template("see earlier log entry".to_string()),
+        _ => Error::Internal(format!("HTTP {}", status.as_u16())),
+    }
 }
 
 /// Middleware to handle errors and render appropriate responses
@@ -279,57 +286,19 @@ pub async fn error_response_middleware(req: Request, next: Next) -> Response {
             }
         }
 
-        // Check if this is our error response (has X-Error-Message header) and client accepts HTML
-        if accepts_html(&headers) {
-            // Check for our special error headers
-            let has_error_header = response.headers().contains_key("X-Error-Message");
+        // Rebuild our own error responses (marked with X-Error-Message) with request context:
+        // an HTML page for browsers, problem+json with instance/request_id for everything else
+        if response.headers().contains_key("X-Error-Message") {
+            let error = error_from_response(status, &response);
 
-            if has_error_header {
-                // Extract custom message if available
-                let custom_message = response
-                    .headers()
-                    .get("X-Error-Custom-Message")
-                    .and_then(|v| v.to_str().ok())
-                    .map(String::from);
+            let user = match response.extensions().get::<Arc<SessionUser>>() {
+                Some(session_user) if accepts_html(&headers) && !is_api_path(&path) => {
+                    Some(User::from_session_user(session_user).await)
+                }
+                _ => None,
+            };
 
-                // Create the appropriate error based on status code
-                let error = match status {
-                    StatusCode::NOT_FOUND => Error::NotFound,
-                    StatusCode::UNAUTHORIZED => Error::Unauthorized,
-                    StatusCode::FORBIDDEN => Error::Forbidden,
-                    StatusCode::BAD_REQUEST => {
-                        if let Some(msg) = custom_message.clone() {
-                            Error::BadRequest(msg)
-                        } else {
-                            Error::BadRequest("Bad request".to_string())
-                        }
-                    }
-                    StatusCode::CONFLICT => {
-                        if let Some(msg) = custom_message.clone() {
-                            Error::Conflict(msg)
-                        } else {
-                            Error::Conflict("Conflict".to_string())
-                        }
-                    }
-                    StatusCode::UNPROCESSABLE_ENTITY => {
-                        if let Some(msg) = custom_message.clone() {
-                            Error::Validation(msg)
-                        } else {
-                            Error::Validation("Validation error".to_string())
-                        }
-                    }
-                    StatusCode::BAD_GATEWAY => {
-                        Error::ExternalService("External service error".to_string())
-                    }
-                    StatusCode::INTERNAL_SERVER_ERROR => {
-                        Error::Internal("Internal server error".to_string())
-                    }
-                    _ => Error::Internal(format!("HTTP {}", status.as_u16())),
-                };
-
-                // Replace the response with an HTML error page
-                return create_error_response(&error, &headers, Some(path), request_id);
-            }
+            return build_error_response(&error, &headers, Some(path), request_id, user);
         }
     }
 
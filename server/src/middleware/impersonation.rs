@@ -0,0 +1,54 @@
+use axum::{
+    extract::Request,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+use tracing::warn;
+
+use super::auth::CurrentUser;
+use crate::error::Error;
+use crate::services::impersonation;
+
+/// Middleware that writes every request made while an admin is impersonating
+/// someone to the session's audit trail, and refuses the requests that stay
+/// off limits during impersonation.
+/// Must run after auth middleware so user identity is available.
+pub async fn impersonation_middleware(request: Request, next: Next) -> Response {
+    let Some(impersonator) = request
+        .extensions()
+        .get::<Arc<CurrentUser>>()
+        .and_then(|u| u.impersonator.clone())
+    else {
+        return next.run(request).await;
+    };
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| path.clone());
+
+    let response = if impersonation::is_blocked(&method, &path) {
+        warn!(
+            "Admin '{}' tried {} {} while impersonating; refused",
+            impersonator.admin_username, method, path
+        );
+        Error::Forbidden.into_response()
+    } else {
+        next.run(request).await
+    };
+
+    if impersonation::is_audited(&path) {
+        impersonation::record(
+            &impersonator.session_id,
+            &method,
+            &path_and_query,
+            response.status().as_u16(),
+        );
+    }
+
+    response
+}
@@ -1,10 +1,19 @@
 pub mod activity;
 pub mod auth;
+pub mod compression;
+pub mod consent;
+pub mod demo_mode;
 pub mod error_handler;
+pub mod impersonation;
 pub mod logging;
+pub mod query_stats;
+pub mod rate_limit;
+pub mod recent_requests;
 pub mod request_id;
+pub mod tenant;
 
 pub use auth::{AuthenticatedUser, CurrentUser, UserExtractor, auth_middleware};
 pub use error_handler::{ErrorWithContext, ResultExt, error_response_middleware};
 pub use logging::{filtered_logging_middleware, logging_middleware};
+pub use query_stats::query_stats_middleware;
 pub use request_id::{RequestId, RequestIdExt, request_id_middleware};
@@ -0,0 +1,55 @@
+use axum::{
+    extract::{MatchedPath, Request},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+use tracing::{debug, warn};
+
+use crate::db::with_query_stats;
+
+/// Middleware that counts the database queries each request runs through `DB`
+/// or `reader()` and warns when a request goes over `DB_QUERY_BUDGET`.
+///
+/// Must be layered outside the auth middleware so the session lookup is counted.
+/// In debug builds the count is also returned in an `X-DB-Query-Count` header.
+pub async fn query_stats_middleware(request: Request, next: Next) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let method = request.method().clone();
+
+    let (mut response, stats) = with_query_stats(route, next.run(request)).await;
+
+    let count = stats.count();
+    let budget = crate::config::query_instrumentation().query_budget;
+
+    if budget > 0 && count > budget {
+        warn!(
+            method = %method,
+            route = %stats.route,
+            queries = count,
+            budget = budget,
+            total_ms = stats.total_ms(),
+            "Request exceeded database query budget"
+        );
+    } else {
+        debug!(
+            method = %method,
+            route = %stats.route,
+            queries = count,
+            total_ms = stats.total_ms(),
+            "Database queries for request"
+        );
+    }
+
+    if cfg!(debug_assertions) {
+        if let Ok(value) = HeaderValue::from_str(&count.to_string()) {
+            response.headers_mut().insert("X-DB-Query-Count", value);
+        }
+    }
+
+    response
+}
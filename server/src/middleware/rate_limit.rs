@@ -0,0 +1,98 @@
+use axum::{
+    extract::Request,
+    http::{HeaderValue, StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+use super::error_handler::{ErrorPage, accepts_html};
+use super::{RequestIdExt, UserExtractor};
+use crate::error::{PROBLEM_JSON, problem_json};
+use crate::services::login_security::ClientInfo;
+use crate::services::public_api::{RateLimit, RateLimiter};
+use crate::templates::User;
+
+/// Search counters, separate from the public API's so one allowance doesn't
+/// eat into the other
+static SEARCH_LIMITER: LazyLock<RateLimiter> = LazyLock::new(RateLimiter::default);
+
+/// Middleware that limits how often one IP can search (see
+/// `config::SearchRateLimit`). Requests over the limit get a 429 with
+/// `Retry-After`; every response carries the `x-ratelimit-*` headers.
+pub async fn search_rate_limit_middleware(request: Request, next: Next) -> Response {
+    let limit = crate::config::search_rate_limit().requests_per_minute;
+    if limit == 0 {
+        return next.run(request).await;
+    }
+
+    let Some(ClientInfo { ip, .. }) =
+        ClientInfo::from_parts(request.headers(), request.extensions())
+    else {
+        return next.run(request).await;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let rate = SEARCH_LIMITER.check(&ip, limit, now);
+
+    let response = if rate.allowed {
+        next.run(request).await
+    } else {
+        warn!(ip = %ip, path = %request.uri().path(), "Search rate limit exceeded");
+        too_many_searches(&request, &rate).await
+    };
+    with_rate_headers(response, &rate)
+}
+
+async fn too_many_searches(request: &Request, rate: &RateLimit) -> Response {
+    let detail = format!(
+        "Too many searches; try again in {} seconds",
+        rate.reset_secs
+    );
+    let path = request.uri().path();
+    let request_id = request.request_id().map(|id| id.to_string());
+    if accepts_html(request.headers()) && !path.starts_with("/api/") {
+        let user = match request.get_user() {
+            Some(user) => Some(User::from_session_user(&user).await),
+            None => None,
+        };
+        let page = ErrorPage {
+            status: StatusCode::TOO_MANY_REQUESTS,
+            error_code: "rate_limited",
+            title: "Too many requests",
+            message: Some(detail),
+            retry_after: Some(rate.reset_secs),
+        };
+        return page.render(Some(path.to_string()), request_id, user);
+    }
+
+    let body = problem_json(
+        StatusCode::TOO_MANY_REQUESTS,
+        "rate_limited",
+        "Too many requests",
+        Some(&detail),
+        Some(path),
+        request_id.as_deref(),
+    );
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::CONTENT_TYPE, PROBLEM_JSON)],
+        body.to_string(),
+    )
+        .into_response()
+}
+
+/// Add the `x-ratelimit-*` headers, and `Retry-After` once over the limit
+pub fn with_rate_headers(mut response: Response, rate: &RateLimit) -> Response {
+    let headers = response.headers_mut();
+    headers.insert("x-ratelimit-limit", HeaderValue::from(rate.limit));
+    headers.insert("x-ratelimit-remaining", HeaderValue::from(rate.remaining));
+    headers.insert("x-ratelimit-reset", HeaderValue::from(rate.reset_secs));
+    if !rate.allowed {
+        headers.insert(header::RETRY_AFTER, HeaderValue::from(rate.reset_secs));
+    }
+    response
+}
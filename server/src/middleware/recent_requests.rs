@@ -0,0 +1,109 @@
+//! Recent requests per visitor
+//!
+//! Keeps the IDs of each visitor's last few requests in memory so feedback
+//! and bug reports can say which requests led up to them. Visitors are told
+//! apart by their account when signed in, otherwise by the `visitor_id`
+//! cookie; requests from anyone with neither aren't kept. Nothing is written
+//! anywhere until the visitor sends feedback.
+
+use axum::{extract::Request, http::HeaderMap, middleware::Next, response::Response};
+use axum_extra::extract::cookie::CookieJar;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::auth::CurrentUser;
+use super::request_id::RequestId;
+use crate::services::experiments::VISITOR_COOKIE;
+
+/// Request IDs kept per visitor
+pub const KEPT_PER_VISITOR: usize = 10;
+
+/// Visitors tracked at most; past this, those idle longest make room
+const MAX_VISITORS: usize = 10_000;
+
+/// How long a visitor goes without a request before making room for others
+const IDLE_SECS: u64 = 30 * 60;
+
+#[derive(Default)]
+pub struct RecentRequests {
+    visitors: Mutex<HashMap<String, (u64, VecDeque<String>)>>,
+}
+
+impl RecentRequests {
+    /// Keep `request_id` as the visitor's latest request at `now` (Unix
+    /// seconds). While every tracked visitor is active a new one isn't kept.
+    pub fn record(&self, visitor: &str, request_id: &str, now: u64) {
+        let mut visitors = self.visitors.lock().unwrap_or_else(|e| e.into_inner());
+        if visitors.len() >= MAX_VISITORS && !visitors.contains_key(visitor) {
+            visitors.retain(|_, (seen, _)| now.saturating_sub(*seen) < IDLE_SECS);
+            if visitors.len() >= MAX_VISITORS {
+                return;
+            }
+        }
+        let (seen, ids) = visitors.entry(visitor.to_string()).or_default();
+        *seen = now;
+        if ids.len() >= KEPT_PER_VISITOR {
+            ids.pop_front();
+        }
+        ids.push_back(request_id.to_string());
+    }
+
+    /// The visitor's kept request IDs, newest first
+    pub fn recent(&self, visitor: &str) -> Vec<String> {
+        let visitors = self.visitors.lock().unwrap_or_else(|e| e.into_inner());
+        visitors
+            .get(visitor)
+            .map(|(_, ids)| ids.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+static RECENT: LazyLock<RecentRequests> = LazyLock::new(RecentRequests::default);
+
+/// Whose requests these are: the signed-in person's id, or the visitor cookie
+pub fn visitor_key(user: Option<&CurrentUser>, headers: &HeaderMap) -> Option<String> {
+    if let Some(user) = user {
+        return Some(user.id.clone());
+    }
+    CookieJar::from_headers(headers)
+        .get(VISITOR_COOKIE)
+        .map(|c| c.value())
+        .filter(|id| !id.is_empty())
+        .map(|id| format!("visitor:{}", id))
+}
+
+/// The request IDs kept for whoever sent these headers, newest first
+pub fn recent_for(user: Option<&CurrentUser>, headers: &HeaderMap) -> Vec<String> {
+    visitor_key(user, headers)
+        .map(|key| RECENT.recent(&key))
+        .unwrap_or_default()
+}
+
+/// Middleware that keeps each visitor's recent request IDs.
+/// Must run after auth middleware so user identity is available.
+pub async fn recent_requests_middleware(request: Request, next: Next) -> Response {
+    let key = should_track(request.uri().path())
+        .then(|| {
+            let user = request.extensions().get::<Arc<CurrentUser>>();
+            visitor_key(user.map(|u| u.as_ref()), request.headers())
+        })
+        .flatten();
+    let request_id = request.extensions().get::<RequestId>().cloned();
+
+    if let (Some(key), Some(request_id)) = (key, request_id) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        RECENT.record(&key, request_id.as_str(), now);
+    }
+
+    next.run(request).await
+}
+
+fn should_track(path: &str) -> bool {
+    !path.starts_with("/static/")
+        && !path.starts_with("/favicon")
+        && !path.starts_with("/healthcheck")
+        && path != "/api/feedback"
+}
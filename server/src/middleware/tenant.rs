@@ -0,0 +1,41 @@
+use axum::{
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tracing::error;
+
+use crate::db;
+use crate::error::Error;
+use crate::services::tenants;
+
+/// Middleware that serves requests for a tenant's hostname from the tenant's
+/// own database and S3 prefix. Hostnames that belong to no tenant are served
+/// by the primary install. Does nothing unless multi-tenant mode is on.
+/// Must run before auth middleware so sessions are looked up in the tenant.
+pub async fn tenant_middleware(request: Request, next: Next) -> Response {
+    if !tenants::enabled() {
+        return next.run(request).await;
+    }
+
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .or_else(|| request.uri().host())
+        .unwrap_or_default()
+        .to_string();
+
+    let Some(tenant) = tenants::for_host(&host).await else {
+        return next.run(request).await;
+    };
+
+    match tenants::scope(&tenant).await {
+        Ok(scope) => db::with_tenant(scope, next.run(request)).await,
+        Err(e) => {
+            error!("Failed to connect tenant {}: {}", tenant.slug, e);
+            Error::ExternalService(format!("{} is unavailable", tenant.name)).into_response()
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::db::DB;
+use crate::error::Error;
 use serde::{Deserialize, Serialize};
 use surrealdb::types::SurrealValue;
 use tracing::debug;
@@ -159,14 +160,16 @@ impl ActivityModel {
         results.into_iter().map(|r| (r.event_type, r.count)).collect()
     }
 
-    pub async fn cleanup(days: u32) {
+    pub async fn cleanup(days: u32) -> Result<(), Error> {
         debug!("Cleaning up activity events older than {} days", days);
         let query = format!(
             "DELETE FROM activity_event WHERE created_at < time::now() - {}d",
             days
         );
-        if let Err(e) = DB.query(&query).await {
+        DB.query(&query).await.map_err(|e| {
             tracing::error!("Failed to cleanup activity events: {}", e);
-        }
+            Error::Database(e.to_string())
+        })?;
+        Ok(())
     }
 }
@@ -1,6 +1,6 @@
 use crate::{db::DB, error::Error};
 use serde::{Deserialize, Serialize};
-use surrealdb::types::RecordId;
+use surrealdb::types::{RecordId, SurrealValue};
 
 pub struct AnalyticsModel;
 
@@ -30,6 +30,15 @@ impl PeriodStat {
     }
 }
 
+/// Someone who viewed a profile while signed in and not browsing anonymously
+#[derive(Debug, Clone)]
+pub struct RecentViewer {
+    pub username: String,
+    pub name: String,
+    pub views: u64,
+    pub last_viewed: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct ProfileAnalytics {
     pub total_views: u64,
@@ -39,6 +48,10 @@ pub struct ProfileAnalytics {
     pub views_90d: PeriodStat,
     pub views_1y: PeriodStat,
     pub referrer_breakdown: Vec<ReferrerCount>,
+    /// Signed-in people who viewed in the last 30 days, named or not
+    pub recent_viewer_count: u64,
+    /// Views from people browsing anonymously in the last 30 days
+    pub anonymous_views_30d: u64,
 }
 
 fn normalize_referrer(referrer: Option<&str>) -> String {
@@ -74,7 +87,8 @@ fn normalize_referrer(referrer: Option<&str>) -> String {
 }
 
 impl AnalyticsModel {
-    /// Record a profile view (fire-and-forget)
+    /// Record a profile view (fire-and-forget). Views from people browsing
+    /// anonymously are counted without the viewer.
     pub async fn record_view(
         profile_id: &RecordId,
         viewer_id: Option<&RecordId>,
@@ -83,14 +97,20 @@ impl AnalyticsModel {
     ) -> Result<(), Error> {
         let source = normalize_referrer(referrer);
 
+        let anonymous = match viewer_id {
+            Some(vid) => Self::browses_anonymously(vid).await?,
+            None => false,
+        };
+
         let mut query_builder = DB
-            .query("CREATE profile_view SET profile_id = $profile_id, viewer_id = $viewer_id, referrer = $referrer, referrer_source = $source, user_agent = $ua")
+            .query("CREATE profile_view SET profile_id = $profile_id, viewer_id = $viewer_id, anonymous = $anonymous, referrer = $referrer, referrer_source = $source, user_agent = $ua")
             .bind(("profile_id", profile_id.clone()))
+            .bind(("anonymous", anonymous))
             .bind(("referrer", referrer.map(|s| s.to_string())))
             .bind(("source", source))
             .bind(("ua", user_agent.map(|s| s.to_string())));
 
-        if let Some(vid) = viewer_id {
+        if let Some(vid) = viewer_id.filter(|_| !anonymous) {
             query_builder = query_builder.bind(("viewer_id", Some(vid.clone())));
         } else {
             query_builder = query_builder.bind(("viewer_id", None::<RecordId>));
@@ -192,6 +212,127 @@ impl AnalyticsModel {
             .collect())
     }
 
+    /// Whether a person has chosen to browse profiles anonymously
+    pub async fn browses_anonymously(person: &RecordId) -> Result<bool, Error> {
+        let mut result = DB
+            .query("SELECT VALUE browse_anonymously FROM ONLY $pid")
+            .bind(("pid", person.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to get browsing preference: {}", e)))?;
+
+        let anonymous: Option<bool> = result.take(0)?;
+        Ok(anonymous.unwrap_or(false))
+    }
+
+    /// Set whether a person's profile views are recorded anonymously
+    pub async fn set_browse_anonymously(person: &RecordId, anonymous: bool) -> Result<(), Error> {
+        DB.query("UPDATE $pid SET browse_anonymously = $anonymous")
+            .bind(("pid", person.clone()))
+            .bind(("anonymous", anonymous))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to set browsing preference: {}", e)))?;
+        Ok(())
+    }
+
+    /// Delete the views of a person's profile and the views they made of
+    /// others (account deletion)
+    pub async fn forget(person: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE profile_view WHERE profile_id = $pid OR viewer_id = $pid")
+            .bind(("pid", person.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete profile views: {}", e)))?;
+        Ok(())
+    }
+
+    /// Signed-in viewers in the last `days`: distinct named viewers plus
+    /// anonymous views, which can't be told apart
+    pub async fn get_viewer_count(profile_id: &RecordId, days: u32) -> Result<u64, Error> {
+        let query = format!(
+            "SELECT count() AS count FROM (SELECT viewer_id FROM profile_view WHERE profile_id = $pid AND viewer_id IS NOT NULL AND viewed_at > time::now() - {days}d GROUP BY viewer_id) GROUP ALL;\
+             SELECT count() AS count FROM profile_view WHERE profile_id = $pid AND anonymous = true AND viewed_at > time::now() - {days}d GROUP ALL;",
+            days = days,
+        );
+
+        let mut result = DB
+            .query(&query)
+            .bind(("pid", profile_id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to get viewer count: {}", e)))?;
+
+        let named: Option<serde_json::Value> = result.take(0)?;
+        let anonymous: Option<serde_json::Value> = result.take(1)?;
+        Ok([named, anonymous]
+            .iter()
+            .map(|row| row.as_ref().and_then(|v| v.get("count").and_then(|c| c.as_u64())).unwrap_or(0))
+            .sum())
+    }
+
+    /// Get view count from people browsing anonymously in the last `days`
+    pub async fn get_anonymous_views(profile_id: &RecordId, days: u32) -> Result<u64, Error> {
+        let query = format!(
+            "SELECT count() AS count FROM profile_view WHERE profile_id = $pid AND anonymous = true AND viewed_at > time::now() - {days}d GROUP ALL",
+        );
+
+        let mut result = DB
+            .query(&query)
+            .bind(("pid", profile_id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to get anonymous views: {}", e)))?;
+
+        let row: Option<serde_json::Value> = result.take(0)?;
+        Ok(row
+            .and_then(|v| v.get("count").and_then(|c| c.as_u64()))
+            .unwrap_or(0))
+    }
+
+    /// Get named viewers from the last `days`, most recent first (pro plan only)
+    pub async fn get_recent_viewers(profile_id: &RecordId, days: u32, limit: usize) -> Result<Vec<RecentViewer>, Error> {
+        let query = format!(
+            "SELECT viewer_id, count() AS views, math::max(viewed_at) AS last_viewed FROM profile_view \
+             WHERE profile_id = $pid AND viewer_id IS NOT NULL AND viewed_at > time::now() - {days}d \
+             GROUP BY viewer_id ORDER BY last_viewed DESC LIMIT {limit};\
+             SELECT id, username, name FROM person WHERE id IN (SELECT VALUE viewer_id FROM profile_view \
+             WHERE profile_id = $pid AND viewer_id IS NOT NULL AND viewed_at > time::now() - {days}d);",
+        );
+
+        #[derive(Debug, Deserialize, SurrealValue)]
+        struct ViewRow {
+            viewer_id: RecordId,
+            views: u64,
+            last_viewed: chrono::DateTime<chrono::Utc>,
+        }
+
+        #[derive(Debug, Deserialize, SurrealValue)]
+        struct ViewerRow {
+            id: RecordId,
+            username: String,
+            name: Option<String>,
+        }
+
+        let mut result = DB
+            .query(&query)
+            .bind(("pid", profile_id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to get recent viewers: {}", e)))?;
+
+        let views: Vec<ViewRow> = result.take(0)?;
+        let people: Vec<ViewerRow> = result.take(1)?;
+
+        // Viewers who have since deleted their account drop out
+        Ok(views
+            .into_iter()
+            .filter_map(|view| {
+                let person = people.iter().find(|p| p.id == view.viewer_id)?;
+                Some(RecentViewer {
+                    username: person.username.clone(),
+                    name: person.name.clone().unwrap_or_else(|| person.username.clone()),
+                    views: view.views,
+                    last_viewed: view.last_viewed.format("%b %d, %Y").to_string(),
+                })
+            })
+            .collect())
+    }
+
     /// Get all analytics data for a profile
     pub async fn get_profile_analytics(profile_id: &RecordId) -> Result<ProfileAnalytics, Error> {
         let (total_views, unique_views, likes_received, views_30d, views_90d, views_1y, referrer_breakdown) = tokio::join!(
@@ -203,6 +344,10 @@ impl AnalyticsModel {
             Self::get_views_for_period(profile_id, 365),
             Self::get_referrer_breakdown(profile_id),
         );
+        let (recent_viewer_count, anonymous_views_30d) = tokio::join!(
+            Self::get_viewer_count(profile_id, 30),
+            Self::get_anonymous_views(profile_id, 30),
+        );
 
         Ok(ProfileAnalytics {
             total_views: total_views.unwrap_or(0),
@@ -212,6 +357,8 @@ impl AnalyticsModel {
             views_90d: views_90d.unwrap_or(PeriodStat { current: 0, previous: 0 }),
             views_1y: views_1y.unwrap_or(PeriodStat { current: 0, previous: 0 }),
             referrer_breakdown: referrer_breakdown.unwrap_or_default(),
+            recent_viewer_count: recent_viewer_count.unwrap_or(0),
+            anonymous_views_30d: anonymous_views_30d.unwrap_or(0),
         })
     }
 }
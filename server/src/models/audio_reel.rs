@@ -0,0 +1,574 @@
+//! Audio reels
+//!
+//! Composers and sound designers show their work as audio rather than video.
+//! An audio reel is a file sent as a resumable upload, plus what the person
+//! says about it: genre, mood, instruments and any lyrics or transcript.
+//! After the upload a scheduled worker decodes the file with the configured
+//! transcoder to draw its waveform (a fixed number of peak values the profile
+//! page renders as a scrubber) and reads the tags embedded in it. Both the
+//! description and the identified tags feed the person's search embedding.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{error, info, warn};
+use ulid::Ulid;
+
+use crate::{
+    config,
+    db::DB,
+    error::Error,
+    models::person::Person,
+    record_id_ext::RecordIdExt,
+    services::{
+        s3::s3,
+        transcode::{self, Transcoder},
+        uploads::UploadSession,
+    },
+};
+
+/// Resumable upload purpose for audio reels
+pub const UPLOAD_PURPOSE: &str = "audio_reel";
+
+/// Accepted audio formats, as (extension, content type)
+pub const AUDIO_TYPES: &[(&str, &str)] = &[
+    ("mp3", "audio/mpeg"),
+    ("m4a", "audio/mp4"),
+    ("aac", "audio/aac"),
+    ("wav", "audio/wav"),
+    ("ogg", "audio/ogg"),
+    ("flac", "audio/flac"),
+];
+
+/// Largest audio file that can be uploaded (200 MB)
+pub const MAX_AUDIO_BYTES: i64 = 200 * 1024 * 1024;
+
+/// Values in a waveform
+pub const PEAK_COUNT: usize = 400;
+
+/// Rate audio is decoded at for the waveform. Peaks don't need more.
+const SAMPLE_RATE: u32 = 8000;
+
+/// Jobs one scheduled run works through
+const REELS_PER_RUN: usize = 10;
+
+pub const MAX_TITLE_CHARS: usize = 120;
+pub const MAX_DESCRIPTION_CHARS: usize = 2000;
+pub const MAX_TRANSCRIPT_CHARS: usize = 10_000;
+const MAX_INSTRUMENTS: usize = 20;
+const MAX_TAG_CHARS: usize = 500;
+
+/// How much of a transcript goes into the embedding, so a long one doesn't
+/// crowd out the rest of the profile
+const EMBEDDED_TRANSCRIPT_CHARS: usize = 300;
+
+/// Tags kept from a file's metadata, as (tag, label)
+pub const IDENTIFIED_TAGS: &[(&str, &str)] = &[
+    ("title", "Title"),
+    ("artist", "Artist"),
+    ("album_artist", "Album artist"),
+    ("album", "Album"),
+    ("composer", "Composer"),
+    ("genre", "Genre"),
+    ("comment", "Comment"),
+    ("description", "Description"),
+    ("lyrics", "Lyrics"),
+];
+
+/// A tag read from the uploaded file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SurrealValue)]
+pub struct FileTag {
+    pub name: String,
+    pub value: String,
+}
+
+impl FileTag {
+    /// How the tag is labelled on a profile
+    pub fn label(&self) -> &str {
+        IDENTIFIED_TAGS
+            .iter()
+            .find(|(name, _)| *name == self.name)
+            .map(|(_, label)| *label)
+            .unwrap_or(self.name.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct AudioReel {
+    pub id: RecordId,
+    pub person: RecordId,
+    pub title: String,
+    pub description: Option<String>,
+    pub genre: Option<String>,
+    pub mood: Option<String>,
+    pub instruments: Vec<String>,
+    /// Lyrics or spoken words, for voice and sound design work
+    pub transcript: Option<String>,
+    pub file_key: String,
+    pub content_type: String,
+    pub size: i64,
+    pub duration_seconds: Option<f64>,
+    /// Waveform: `PEAK_COUNT` values from 0 to 1
+    pub peaks: Vec<f64>,
+    pub file_tags: Vec<FileTag>,
+    /// "queued", "processing", "ready" or "failed"
+    pub status: String,
+    pub attempts: i64,
+    pub error: Option<String>,
+    pub position: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AudioReel {
+    /// The waveform is still being made
+    pub fn is_pending(&self) -> bool {
+        self.status == "queued" || self.status == "processing"
+    }
+
+    /// Tags from the file, less a title tag that only repeats the reel's title
+    pub fn extra_tags(&self) -> impl Iterator<Item = &FileTag> {
+        self.file_tags
+            .iter()
+            .filter(|tag| !(tag.name == "title" && tag.value.eq_ignore_ascii_case(&self.title)))
+    }
+
+    /// How the reel reads in the person's search embedding
+    pub fn embedding_line(&self) -> String {
+        let mut parts = vec![self.title.clone()];
+        if let Some(genre) = &self.genre {
+            parts.push(format!("genre {}", genre));
+        }
+        if let Some(mood) = &self.mood {
+            parts.push(format!("mood {}", mood));
+        }
+        if !self.instruments.is_empty() {
+            parts.push(format!("instruments {}", self.instruments.join(", ")));
+        }
+        if let Some(seconds) = self.duration_seconds {
+            parts.push(format!("length {}", format_duration(seconds)));
+        }
+        if let Some(description) = &self.description {
+            parts.push(description.clone());
+        }
+        for tag in self.extra_tags() {
+            parts.push(format!("{} {}", tag.label().to_lowercase(), tag.value));
+        }
+        if let Some(transcript) = &self.transcript {
+            let words: String = transcript.chars().take(EMBEDDED_TRANSCRIPT_CHARS).collect();
+            parts.push(format!("words: {}", words));
+        }
+        parts.join("; ")
+    }
+}
+
+/// What a person says about a reel, checked and tidied
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioReelDetails {
+    pub title: String,
+    pub description: Option<String>,
+    pub genre: Option<String>,
+    pub mood: Option<String>,
+    pub instruments: Vec<String>,
+    pub transcript: Option<String>,
+}
+
+fn optional(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Instruments from a comma-separated list, without blanks or repeats
+pub fn parse_instruments(text: &str) -> Vec<String> {
+    let mut instruments: Vec<String> = Vec::new();
+    for item in text.split([',', '\n']) {
+        let item = item.trim();
+        if !item.is_empty() && !instruments.iter().any(|i| i.eq_ignore_ascii_case(item)) {
+            instruments.push(item.to_string());
+        }
+    }
+    instruments
+}
+
+impl AudioReelDetails {
+    pub fn parse(
+        title: &str,
+        description: &str,
+        genre: &str,
+        mood: &str,
+        instruments: &str,
+        transcript: &str,
+    ) -> Result<Self, Error> {
+        let title = title.trim();
+        if title.is_empty() {
+            return Err(Error::Validation("Give the reel a title".to_string()));
+        }
+        if title.chars().count() > MAX_TITLE_CHARS {
+            return Err(Error::Validation(format!("Titles can be up to {} characters", MAX_TITLE_CHARS)));
+        }
+        if description.trim().chars().count() > MAX_DESCRIPTION_CHARS {
+            return Err(Error::Validation(format!(
+                "Descriptions can be up to {} characters",
+                MAX_DESCRIPTION_CHARS
+            )));
+        }
+        if transcript.trim().chars().count() > MAX_TRANSCRIPT_CHARS {
+            return Err(Error::Validation(format!(
+                "Lyrics and transcripts can be up to {} characters",
+                MAX_TRANSCRIPT_CHARS
+            )));
+        }
+        let instruments = parse_instruments(instruments);
+        if instruments.len() > MAX_INSTRUMENTS {
+            return Err(Error::Validation(format!("List up to {} instruments", MAX_INSTRUMENTS)));
+        }
+        Ok(Self {
+            title: title.to_string(),
+            description: optional(description),
+            genre: optional(genre),
+            mood: optional(mood),
+            instruments,
+            transcript: optional(transcript),
+        })
+    }
+}
+
+/// Extension and content type for an uploaded audio file's name
+pub fn audio_type(filename: &str) -> Option<(&'static str, &'static str)> {
+    let extension = filename.rsplit_once('.')?.1.to_lowercase();
+    AUDIO_TYPES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(ext, content_type)| (*ext, *content_type))
+}
+
+fn audio_key_prefix(person: &RecordId) -> String {
+    format!("profiles/{}/audio/", person.key_string())
+}
+
+/// Where a new audio upload for a person is stored
+pub fn audio_key(person: &RecordId, extension: &str) -> String {
+    format!("{}{}.{}", audio_key_prefix(person), Ulid::new(), extension)
+}
+
+/// Whether a storage key was issued for this person's audio, so a client
+/// can't attach someone else's upload
+pub fn owns_audio_key(person: &RecordId, key: &str) -> bool {
+    key.strip_prefix(&audio_key_prefix(person))
+        .is_some_and(|rest| !rest.is_empty() && !rest.contains('/') && !rest.contains(".."))
+}
+
+/// Waveform peaks: the loudest sample in each of `count` equal slices,
+/// scaled so the loudest slice is 1 and rounded to two decimals. Silence
+/// stays flat.
+pub fn compute_peaks(samples: &[i16], count: usize) -> Vec<f64> {
+    if samples.is_empty() || count == 0 {
+        return Vec::new();
+    }
+    let count = count.min(samples.len());
+    let mut peaks: Vec<f64> = (0..count)
+        .map(|i| {
+            let start = i * samples.len() / count;
+            let end = (i + 1) * samples.len() / count;
+            samples[start..end]
+                .iter()
+                .map(|s| (*s as i32).unsigned_abs())
+                .max()
+                .unwrap_or(0) as f64
+        })
+        .collect();
+    let loudest = peaks.iter().cloned().fold(0.0, f64::max);
+    if loudest > 0.0 {
+        for peak in &mut peaks {
+            *peak = (*peak / loudest * 100.0).round() / 100.0;
+        }
+    }
+    peaks
+}
+
+/// "3:05", or "1:02:03" for an hour or more
+pub fn format_duration(seconds: f64) -> String {
+    let total = seconds.max(0.0).round() as i64;
+    let (hours, minutes, secs) = (total / 3600, total % 3600 / 60, total % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, secs)
+    } else {
+        format!("{}:{:02}", minutes, secs)
+    }
+}
+
+/// The tags worth keeping from a file's metadata, in `IDENTIFIED_TAGS` order
+pub fn identified_tags(tags: &[(String, String)]) -> Vec<FileTag> {
+    IDENTIFIED_TAGS
+        .iter()
+        .filter_map(|(name, _)| {
+            tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| FileTag {
+                name: name.to_string(),
+                value: value.chars().take(MAX_TAG_CHARS).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Delete a stored file, logging rather than failing when storage is unavailable
+async fn delete_file(key: &str) {
+    match s3() {
+        Ok(s3) => {
+            if let Err(e) = s3.delete_file(key).await {
+                error!("Failed to delete audio reel {}: {}", key, e);
+            }
+        }
+        Err(e) => error!("S3 unavailable, audio reel {} not deleted: {}", key, e),
+    }
+}
+
+/// Re-embed a person after their audio reels change
+async fn refresh_embedding(person: &RecordId) {
+    match Person::find_by_id(&person.to_raw_string()).await {
+        Ok(Some(person)) => person.refresh_embedding().await,
+        Ok(None) => {}
+        Err(e) => error!("Failed to load {} to refresh its embedding: {}", person.display(), e),
+    }
+}
+
+pub struct AudioReelModel;
+
+impl AudioReelModel {
+    /// A person's reels in the order they arranged them
+    pub async fn for_person(person: &RecordId) -> Result<Vec<AudioReel>, Error> {
+        Ok(DB
+            .query("SELECT * FROM audio_reel WHERE person = $person ORDER BY position ASC, created_at ASC")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Lines for the person's search embedding
+    pub async fn embedding_lines(person: &RecordId) -> Result<Vec<String>, Error> {
+        Ok(Self::for_person(person)
+            .await?
+            .iter()
+            .map(AudioReel::embedding_line)
+            .collect())
+    }
+
+    /// A reel, if it belongs to the person
+    pub async fn get(reel_id: &str, person: &RecordId) -> Result<AudioReel, Error> {
+        let reel: Option<AudioReel> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("audio_reel", reel_id)))
+            .await?
+            .take(0)?;
+        match reel {
+            Some(reel) if reel.person == *person => Ok(reel),
+            Some(_) => Err(Error::Forbidden),
+            None => Err(Error::NotFound),
+        }
+    }
+
+    /// Add a reel from a finished upload. Its waveform is made in the
+    /// background when a transcoder is configured.
+    pub async fn create(person: &RecordId, upload: &UploadSession, details: AudioReelDetails) -> Result<AudioReel, Error> {
+        let status = if transcode::transcoder().is_some() { "queued" } else { "ready" };
+        let reel: Option<AudioReel> = DB
+            .query(
+                "LET $position = (SELECT VALUE position FROM audio_reel WHERE person = $person
+                    ORDER BY position DESC LIMIT 1)[0] ?? -1;
+                 CREATE audio_reel SET person = $person, title = $title, description = $description,
+                    genre = $genre, mood = $mood, instruments = $instruments, transcript = $transcript,
+                    file_key = $file_key, content_type = $content_type, size = $size, peaks = [],
+                    file_tags = [], status = $status, attempts = 0, position = $position + 1,
+                    created_at = time::now(), updated_at = time::now();",
+            )
+            .bind(("person", person.clone()))
+            .bind(("title", details.title))
+            .bind(("description", details.description))
+            .bind(("genre", details.genre))
+            .bind(("mood", details.mood))
+            .bind(("instruments", details.instruments))
+            .bind(("transcript", details.transcript))
+            .bind(("file_key", upload.key.clone()))
+            .bind(("content_type", upload.content_type.clone()))
+            .bind(("size", upload.size))
+            .bind(("status", status.to_string()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to add audio reel: {}", e)))?
+            .take(1)?;
+        let reel = reel.ok_or_else(|| Error::Internal("Failed to add audio reel".to_string()))?;
+        refresh_embedding(person).await;
+        Ok(reel)
+    }
+
+    pub async fn update_details(reel: &AudioReel, details: AudioReelDetails) -> Result<(), Error> {
+        DB.query(
+            "UPDATE $id SET title = $title, description = $description, genre = $genre, mood = $mood,
+                instruments = $instruments, transcript = $transcript, updated_at = time::now()",
+        )
+        .bind(("id", reel.id.clone()))
+        .bind(("title", details.title))
+        .bind(("description", details.description))
+        .bind(("genre", details.genre))
+        .bind(("mood", details.mood))
+        .bind(("instruments", details.instruments))
+        .bind(("transcript", details.transcript))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update audio reel: {}", e)))?;
+        refresh_embedding(&reel.person).await;
+        Ok(())
+    }
+
+    /// Move a reel one place earlier or later in the person's list
+    pub async fn shift(reel: &AudioReel, earlier: bool) -> Result<(), Error> {
+        let mut order: Vec<RecordId> = Self::for_person(&reel.person)
+            .await?
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        let Some(index) = order.iter().position(|id| *id == reel.id) else {
+            return Err(Error::NotFound);
+        };
+        let other = if earlier { index.checked_sub(1) } else { Some(index + 1) };
+        let Some(other) = other.filter(|i| *i < order.len()) else {
+            return Ok(());
+        };
+        order.swap(index, other);
+        // Number the whole list afresh so ties between older rows don't stick
+        for (position, id) in order.into_iter().enumerate() {
+            DB.query("UPDATE $id SET position = $position")
+                .bind(("id", id))
+                .bind(("position", position as i64))
+                .await
+                .map_err(|e| Error::Database(format!("Failed to reorder audio reels: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    pub async fn delete(reel: &AudioReel) -> Result<(), Error> {
+        DB.query("DELETE $id")
+            .bind(("id", reel.id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete audio reel: {}", e)))?;
+        delete_file(&reel.file_key).await;
+        refresh_embedding(&reel.person).await;
+        Ok(())
+    }
+
+    /// Delete a person's reels and files, for account deletion
+    pub async fn forget(person: &RecordId) -> Result<(), Error> {
+        let keys: Vec<String> = DB
+            .query("DELETE audio_reel WHERE person = $person RETURN BEFORE")
+            .bind(("person", person.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete audio reels: {}", e)))?
+            .take::<Vec<AudioReel>>(0)?
+            .into_iter()
+            .map(|reel| reel.file_key)
+            .collect();
+        for key in &keys {
+            delete_file(key).await;
+        }
+        Ok(())
+    }
+
+    /// Take the next queued reel, if another worker hasn't got to it first
+    async fn claim_next() -> Result<Option<AudioReel>, Error> {
+        let next: Option<RecordId> = DB
+            .query("SELECT VALUE id FROM audio_reel WHERE status = 'queued' ORDER BY created_at ASC LIMIT 1")
+            .await?
+            .take(0)?;
+        let Some(id) = next else {
+            return Ok(None);
+        };
+        Ok(DB
+            .query(
+                "UPDATE $id SET status = 'processing', attempts += 1, updated_at = time::now()
+                 WHERE status = 'queued' RETURN AFTER",
+            )
+            .bind(("id", id))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to claim audio reel: {}", e)))?
+            .take(0)?)
+    }
+
+    async fn analyse(transcoder: &dyn Transcoder, reel: &AudioReel) -> Result<(Vec<i16>, Vec<FileTag>), Error> {
+        let extension = reel.file_key.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("audio");
+        let path = std::env::temp_dir().join(format!("slatehub-audio-{}.{}", reel.id.key_string(), extension));
+        let result = async {
+            s3()?.download_to_path(&reel.file_key, &path).await?;
+            let samples = transcoder.audio_samples(&path, SAMPLE_RATE).await?;
+            // Tags are a bonus; a file without any still gets its waveform
+            let tags = match transcoder.tags(&path).await {
+                Ok(tags) => identified_tags(&tags),
+                Err(e) => {
+                    warn!("Couldn't read tags of {}: {}", reel.file_key, e);
+                    Vec::new()
+                }
+            };
+            Ok::<_, Error>((samples, tags))
+        }
+        .await;
+        if let Err(e) = tokio::fs::remove_file(&path).await
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("Failed to remove {}: {}", path.display(), e);
+        }
+        result
+    }
+
+    async fn process(transcoder: &dyn Transcoder, reel: AudioReel) -> Result<(), Error> {
+        match Self::analyse(transcoder, &reel).await {
+            Ok((samples, tags)) => {
+                let duration = samples.len() as f64 / SAMPLE_RATE as f64;
+                DB.query(
+                    "UPDATE $id SET status = 'ready', error = NONE, peaks = $peaks,
+                        duration_seconds = $duration, file_tags = $tags, updated_at = time::now()",
+                )
+                .bind(("id", reel.id.clone()))
+                .bind(("peaks", compute_peaks(&samples, PEAK_COUNT)))
+                .bind(("duration", duration))
+                .bind(("tags", tags))
+                .await
+                .map_err(|e| Error::Database(format!("Failed to save waveform: {}", e)))?;
+                info!("Made waveform for audio reel {} ({:.0}s)", reel.id.display(), duration);
+                refresh_embedding(&reel.person).await;
+            }
+            Err(e) => {
+                warn!("Waveform for {} failed (attempt {}): {}", reel.file_key, reel.attempts, e);
+                DB.query("UPDATE $id SET status = $status, error = $error, updated_at = time::now()")
+                    .bind(("id", reel.id.clone()))
+                    .bind(("status", transcode::status_after_failure(reel.attempts).to_string()))
+                    .bind(("error", e.to_string()))
+                    .await
+                    .map_err(|e| Error::Database(format!("Failed to record waveform failure: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Make waveforms for queued reels. Runs on a schedule. Reels left
+    /// "processing" by a worker that died are retried.
+    pub async fn process_queue() -> Result<(), Error> {
+        let Some(transcoder) = transcode::transcoder() else {
+            return Ok(());
+        };
+
+        let stale_before = Utc::now() - Duration::seconds(config::transcoding().timeout_secs as i64 * 2);
+        DB.query(
+            "UPDATE audio_reel SET status = IF attempts >= $max_attempts THEN 'failed' ELSE 'queued' END,
+                updated_at = time::now()
+             WHERE status = 'processing' AND updated_at < $before",
+        )
+        .bind(("before", stale_before))
+        .bind(("max_attempts", transcode::MAX_ATTEMPTS))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to requeue stale audio reels: {}", e)))?;
+
+        for _ in 0..REELS_PER_RUN {
+            let Some(reel) = Self::claim_next().await? else {
+                break;
+            };
+            Self::process(transcoder, reel).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+//! Location blackouts: times a location can't be booked, like neighbourhood
+//! quiet hours or bookings the owner took outside SlateHub.
+//!
+//! A blackout covers a range of dates (open at either end), either all day or
+//! between two times each day; an end time before the start time runs past
+//! midnight. Booking a shoot day at a location during one of its blackouts is
+//! refused, and days booked before a blackout was added are flagged on the
+//! shot list and can't have their call sheet sent.
+
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+use crate::{db::DB, error::Error, models::daily_report, record_id_ext::RecordIdExt};
+
+/// Longest blackout label
+pub const MAX_LABEL_CHARS: usize = 120;
+
+/// Most blackouts one location can list
+pub const MAX_BLACKOUTS: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Blackout {
+    pub id: RecordId,
+    pub location: RecordId,
+    pub label: String,
+    /// "YYYY-MM-DD"; `None` for no start
+    pub start_date: Option<String>,
+    /// Inclusive; `None` for no end
+    pub end_date: Option<String>,
+    /// "HH:MM" each day; `None` for the whole day
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A validated blackout from the location page form
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlackoutData {
+    pub label: String,
+    pub start_date: Option<String>,
+    pub end_date: Option<String>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+}
+
+fn parse_date(value: &str) -> Result<Option<NaiveDate>, Error> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| Error::Validation(format!("\"{}\" isn't a date (use YYYY-MM-DD)", value)))
+}
+
+fn time(value: &Option<String>) -> Option<NaiveTime> {
+    value
+        .as_deref()
+        .and_then(|t| NaiveTime::parse_from_str(t, "%H:%M").ok())
+}
+
+/// Check and normalize a blackout from the form. Empty dates leave that end
+/// open; empty times mean the whole day.
+pub fn clean_blackout(
+    label: &str,
+    start_date: &str,
+    end_date: &str,
+    start_time: &str,
+    end_time: &str,
+) -> Result<BlackoutData, Error> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(Error::Validation(
+            "Say what the blackout is for, like \"Neighbourhood quiet hours\"".to_string(),
+        ));
+    }
+    if label.chars().count() > MAX_LABEL_CHARS {
+        return Err(Error::Validation(format!(
+            "Keep the label under {} characters",
+            MAX_LABEL_CHARS
+        )));
+    }
+
+    let start_date = parse_date(start_date)?;
+    let end_date = parse_date(end_date)?;
+    if matches!((start_date, end_date), (Some(start), Some(end)) if end < start) {
+        return Err(Error::Validation(
+            "The blackout ends before it starts".to_string(),
+        ));
+    }
+
+    let start_time = daily_report::parse_time(start_time)?;
+    let end_time = daily_report::parse_time(end_time)?;
+    if start_time.is_some() != end_time.is_some() {
+        return Err(Error::Validation(
+            "Give both a start and end time, or neither to black out whole days".to_string(),
+        ));
+    }
+    if start_time.is_some() && start_time == end_time {
+        return Err(Error::Validation(
+            "The start and end times are the same".to_string(),
+        ));
+    }
+    if start_date.is_none() && end_date.is_none() && start_time.is_none() {
+        return Err(Error::Validation(
+            "Give the blackout dates, daily times, or both".to_string(),
+        ));
+    }
+
+    Ok(BlackoutData {
+        label: label.to_string(),
+        start_date: start_date.map(|d| d.format("%Y-%m-%d").to_string()),
+        end_date: end_date.map(|d| d.format("%Y-%m-%d").to_string()),
+        start_time,
+        end_time,
+    })
+}
+
+/// Call and wrap times as a pair, when both are set
+pub fn shoot_hours(
+    call_time: Option<&str>,
+    wrap_time: Option<&str>,
+) -> Option<(NaiveTime, NaiveTime)> {
+    let call = NaiveTime::parse_from_str(call_time?, "%H:%M").ok()?;
+    let wrap = NaiveTime::parse_from_str(wrap_time?, "%H:%M").ok()?;
+    Some((call, wrap))
+}
+
+/// A span from `start` to `end` on `date`, running into the next day when
+/// `end` isn't after `start`
+fn span(date: NaiveDate, start: NaiveTime, end: NaiveTime) -> (NaiveDateTime, NaiveDateTime) {
+    let from = date.and_time(start);
+    let mut to = date.and_time(end);
+    if to <= from {
+        to += Duration::days(1);
+    }
+    (from, to)
+}
+
+impl Blackout {
+    /// Whether the blackout applies on `date`
+    fn covers_date(&self, date: NaiveDate) -> bool {
+        let date = date.format("%Y-%m-%d").to_string();
+        self.start_date.as_ref().is_none_or(|start| &date >= start)
+            && self.end_date.as_ref().is_none_or(|end| &date <= end)
+    }
+
+    /// Whether a shoot day on `date` runs into the blackout. Without call
+    /// and wrap times the day is taken to cover the whole date.
+    pub fn conflicts_with(&self, date: NaiveDate, hours: Option<(NaiveTime, NaiveTime)>) -> bool {
+        let day = match hours {
+            Some((call, wrap)) => span(date, call, wrap),
+            None => span(date, NaiveTime::MIN, NaiveTime::MIN),
+        };
+        // An overnight blackout from the day before can still be running
+        [date - Duration::days(1), date, date + Duration::days(1)]
+            .into_iter()
+            .filter(|d| self.covers_date(*d))
+            .any(|d| {
+                let blackout = match (time(&self.start_time), time(&self.end_time)) {
+                    (Some(start), Some(end)) => span(d, start, end),
+                    _ => span(d, NaiveTime::MIN, NaiveTime::MIN),
+                };
+                blackout.0 < day.1 && day.0 < blackout.1
+            })
+    }
+
+    /// Whether it only blacks out part of each day
+    pub fn is_daily_hours(&self) -> bool {
+        self.start_time.is_some() && self.end_time.is_some()
+    }
+
+    /// Whether it ended before `today`
+    pub fn is_over(&self, today: NaiveDate) -> bool {
+        self.end_date
+            .as_deref()
+            .and_then(|end| NaiveDate::parse_from_str(end, "%Y-%m-%d").ok())
+            .is_some_and(|end| end < today)
+    }
+
+    /// When it applies: "22:00-07:00 every day", "all day 2026-06-01 to
+    /// 2026-06-03"
+    pub fn when(&self) -> String {
+        let hours = match (&self.start_time, &self.end_time) {
+            (Some(start), Some(end)) => format!("{}-{}", start, end),
+            _ => "all day".to_string(),
+        };
+        let dates = match (&self.start_date, &self.end_date) {
+            (Some(start), Some(end)) if start == end => format!("on {}", start),
+            (Some(start), Some(end)) => format!("{} to {}", start, end),
+            (Some(start), None) => format!("from {}", start),
+            (None, Some(end)) => format!("until {}", end),
+            (None, None) => "every day".to_string(),
+        };
+        format!("{} {}", hours, dates)
+    }
+
+    /// "Quiet hours (22:00-07:00 every day)"
+    pub fn describe(&self) -> String {
+        format!("{} ({})", self.label, self.when())
+    }
+}
+
+/// The blackouts a shoot day on `date` runs into
+pub fn conflicts(
+    blackouts: &[Blackout],
+    date: NaiveDate,
+    hours: Option<(NaiveTime, NaiveTime)>,
+) -> Vec<&Blackout> {
+    blackouts
+        .iter()
+        .filter(|b| b.conflicts_with(date, hours))
+        .collect()
+}
+
+/// What to tell someone booking a location during its blackouts
+pub fn conflict_message(
+    location_name: &str,
+    date: &str,
+    conflicts: &[&Blackout],
+    has_hours: bool,
+) -> String {
+    let reasons: Vec<String> = conflicts.iter().map(|b| b.describe()).collect();
+    let mut message = format!(
+        "{} isn't available on {}: {}.",
+        location_name,
+        date,
+        reasons.join("; ")
+    );
+    if !has_hours && conflicts.iter().all(|b| b.is_daily_hours()) {
+        message.push_str(" Set call and wrap times outside those hours to book it.");
+    }
+    message
+}
+
+pub struct BlackoutModel;
+
+impl BlackoutModel {
+    pub async fn create(location: &RecordId, data: BlackoutData) -> Result<Blackout, Error> {
+        if Self::for_location(location).await?.len() >= MAX_BLACKOUTS {
+            return Err(Error::Validation(format!(
+                "A location can list up to {} blackouts",
+                MAX_BLACKOUTS
+            )));
+        }
+
+        debug!("Adding blackout '{}' to {}", data.label, location.display());
+        let blackout: Option<Blackout> = DB
+            .query(
+                "CREATE location_blackout SET location = $location, label = $label,
+                    start_date = $start_date, end_date = $end_date,
+                    start_time = $start_time, end_time = $end_time, created_at = time::now()",
+            )
+            .bind(("location", location.clone()))
+            .bind(("label", data.label))
+            .bind(("start_date", data.start_date))
+            .bind(("end_date", data.end_date))
+            .bind(("start_time", data.start_time))
+            .bind(("end_time", data.end_time))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to add blackout: {}", e)))?
+            .take(0)?;
+        blackout.ok_or_else(|| Error::Internal("Failed to add blackout".to_string()))
+    }
+
+    /// A location's blackouts, earliest first; open-ended ones lead
+    pub async fn for_location(location: &RecordId) -> Result<Vec<Blackout>, Error> {
+        Ok(DB
+            .query(
+                "SELECT * FROM location_blackout WHERE location = $location
+                 ORDER BY start_date ASC, start_time ASC, created_at ASC",
+            )
+            .bind(("location", location.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn delete(location: &RecordId, blackout_id: &str) -> Result<(), Error> {
+        DB.query("DELETE $id WHERE location = $location")
+            .bind(("id", RecordId::new("location_blackout", blackout_id)))
+            .bind(("location", location.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete blackout: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn delete_for_location(location: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE location_blackout WHERE location = $location")
+            .bind(("location", location.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete blackouts: {}", e)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+//! Block and mute edges between people
+//!
+//! `person -> blocks -> person` stops messaging in both directions, stops the
+//! blocked person applying to the blocker's job postings, hides the blocker's
+//! contact details from them, and keeps the two out of each other's search
+//! results. `person -> mutes -> person` is one-sided and quieter: the muted
+//! person's messages still arrive but don't notify, and they drop out of the
+//! muter's search results.
+
+use crate::{db::DB, error::Error, record_id_ext::RecordIdExt};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::info;
+
+pub struct BlockModel;
+
+/// Which edge a listed person is behind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    Block,
+    Mute,
+}
+
+impl BlockKind {
+    fn table(self) -> &'static str {
+        match self {
+            BlockKind::Block => "blocks",
+            BlockKind::Mute => "mutes",
+        }
+    }
+
+    pub fn from_action(action: &str) -> Option<(Self, bool)> {
+        match action {
+            "block" => Some((BlockKind::Block, true)),
+            "unblock" => Some((BlockKind::Block, false)),
+            "mute" => Some((BlockKind::Mute, true)),
+            "unmute" => Some((BlockKind::Mute, false)),
+            _ => None,
+        }
+    }
+}
+
+/// Someone the current person has blocked or muted, for the settings page
+#[derive(Debug, Clone)]
+pub struct BlockedPerson {
+    pub username: String,
+    pub name: String,
+    pub since: String,
+}
+
+impl BlockModel {
+    /// Create or remove a block/mute edge. Idempotent either way.
+    pub async fn set(person: &RecordId, target: &RecordId, kind: BlockKind, on: bool) -> Result<(), Error> {
+        if person == target {
+            return Err(Error::BadRequest("You can't block or mute yourself".to_string()));
+        }
+        if !target.to_raw_string().starts_with("person:") {
+            return Err(Error::BadRequest("Expected a person record ID".to_string()));
+        }
+
+        let exists = match kind {
+            BlockKind::Block => Self::has_blocked(person, target).await?,
+            BlockKind::Mute => Self::has_muted(person, target).await?,
+        };
+        if exists == on {
+            return Ok(());
+        }
+
+        let table = kind.table();
+        let query = if on {
+            format!("RELATE $person -> {table} -> $target SET created_at = time::now()")
+        } else {
+            format!("DELETE {table} WHERE in = $person AND out = $target")
+        };
+
+        DB.query(&query)
+            .bind(("person", person.clone()))
+            .bind(("target", target.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update {}: {}", table, e)))?;
+
+        info!(
+            "{} {} {}",
+            person.display(),
+            match (kind, on) {
+                (BlockKind::Block, true) => "blocked",
+                (BlockKind::Block, false) => "unblocked",
+                (BlockKind::Mute, true) => "muted",
+                (BlockKind::Mute, false) => "unmuted",
+            },
+            target.display()
+        );
+        Ok(())
+    }
+
+    async fn exists(query: &str, a: &RecordId, b: &RecordId) -> Result<bool, Error> {
+        let mut result = DB
+            .query(query)
+            .bind(("a", a.clone()))
+            .bind(("b", b.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to check blocks: {}", e)))?;
+
+        let count: Option<serde_json::Value> = result.take(0)?;
+        Ok(count
+            .and_then(|v| v.get("count").and_then(|c| c.as_u64()))
+            .unwrap_or(0)
+            > 0)
+    }
+
+    /// Whether `person` has blocked `target`
+    pub async fn has_blocked(person: &RecordId, target: &RecordId) -> Result<bool, Error> {
+        Self::exists(
+            "SELECT count() AS count FROM blocks WHERE in = $a AND out = $b GROUP ALL",
+            person,
+            target,
+        )
+        .await
+    }
+
+    /// Whether `person` has muted `target`
+    pub async fn has_muted(person: &RecordId, target: &RecordId) -> Result<bool, Error> {
+        Self::exists(
+            "SELECT count() AS count FROM mutes WHERE in = $a AND out = $b GROUP ALL",
+            person,
+            target,
+        )
+        .await
+    }
+
+    /// Whether either person has blocked the other
+    pub async fn is_blocked_between(a: &RecordId, b: &RecordId) -> Result<bool, Error> {
+        Self::exists(
+            "SELECT count() AS count FROM blocks WHERE (in = $a AND out = $b) OR (in = $b AND out = $a) GROUP ALL",
+            a,
+            b,
+        )
+        .await
+    }
+
+    /// Raw ids ("person:abc") of everyone who should be left out of this
+    /// person's search results: people they blocked or muted, and people who
+    /// blocked them.
+    pub async fn hidden_ids(person: &RecordId) -> Result<Vec<String>, Error> {
+        let mut result = DB
+            .query(
+                "SELECT VALUE out FROM blocks WHERE in = $person;
+                 SELECT VALUE out FROM mutes WHERE in = $person;
+                 SELECT VALUE in FROM blocks WHERE out = $person;",
+            )
+            .bind(("person", person.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to load blocks: {}", e)))?;
+
+        let mut ids = Vec::new();
+        for index in 0..3 {
+            let rows: Vec<RecordId> = result.take(index).unwrap_or_default();
+            ids.extend(rows.iter().map(|id| id.to_raw_string()));
+        }
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    /// Everyone this person has blocked or muted, newest first
+    pub async fn list(person: &RecordId, kind: BlockKind) -> Result<Vec<BlockedPerson>, Error> {
+        #[derive(Debug, Deserialize, SurrealValue)]
+        struct Row {
+            username: Option<String>,
+            name: Option<String>,
+            created_at: DateTime<Utc>,
+        }
+
+        let query = format!(
+            "SELECT out.username AS username, out.name AS name, created_at FROM {}
+             WHERE in = $person ORDER BY created_at DESC",
+            kind.table()
+        );
+        let rows: Vec<Row> = DB
+            .query(&query)
+            .bind(("person", person.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to list {}: {}", kind.table(), e)))?
+            .take(0)?;
+
+        // People who have since deleted their account drop out
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                let username = row.username?;
+                Some(BlockedPerson {
+                    name: row.name.unwrap_or_else(|| username.clone()),
+                    username,
+                    since: row.created_at.format("%b %d, %Y").to_string(),
+                })
+            })
+            .collect())
+    }
+}
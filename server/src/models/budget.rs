@@ -0,0 +1,191 @@
+//! Production budgets
+//!
+//! A production's owners and admins keep its budget as a list of lines, each
+//! an amount under a category. Accepting a vendor's rental quote (see
+//! `gear_quote`) adds a line for it; others are added by hand.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+use crate::{
+    db::DB, error::Error, models::production_gear::format_value, record_id_ext::RecordIdExt,
+};
+
+/// Budget categories, with their labels
+pub const CATEGORIES: &[(&str, &str)] = &[
+    ("equipment", "Equipment rental"),
+    ("crew", "Crew"),
+    ("locations", "Locations"),
+    ("travel", "Travel"),
+    ("post", "Post-production"),
+    ("other", "Other"),
+];
+
+pub const MAX_DESCRIPTION_CHARS: usize = 200;
+
+/// "Equipment rental" for "equipment"
+pub fn category_label(category: &str) -> &'static str {
+    CATEGORIES
+        .iter()
+        .find(|(key, _)| *key == category)
+        .map_or("Other", |(_, label)| *label)
+}
+
+/// An amount from a form: "$1,250.00", "1250"
+pub fn parse_amount(value: &str) -> Result<f64, Error> {
+    let cleaned: String = value
+        .trim()
+        .chars()
+        .filter(|c| *c != ',' && *c != '$')
+        .collect();
+    match cleaned.parse::<f64>() {
+        Ok(amount) if amount > 0.0 && amount <= 100_000_000.0 => Ok(amount),
+        _ => Err(Error::Validation(
+            "Enter an amount greater than zero".to_string(),
+        )),
+    }
+}
+
+/// A line added by hand, checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetLineData {
+    pub category: String,
+    pub description: String,
+    pub amount: f64,
+}
+
+impl BudgetLineData {
+    pub fn parse(category: &str, description: &str, amount: &str) -> Result<Self, Error> {
+        if !CATEGORIES.iter().any(|(key, _)| *key == category) {
+            return Err(Error::Validation("Choose a budget category".to_string()));
+        }
+        let description = description.trim();
+        if description.is_empty() {
+            return Err(Error::Validation(
+                "Describe what the money is for".to_string(),
+            ));
+        }
+        if description.chars().count() > MAX_DESCRIPTION_CHARS {
+            return Err(Error::Validation(format!(
+                "The description can be up to {} characters",
+                MAX_DESCRIPTION_CHARS
+            )));
+        }
+        Ok(Self {
+            category: category.to_string(),
+            description: description.to_string(),
+            amount: parse_amount(amount)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct BudgetLine {
+    pub id: RecordId,
+    pub category: String,
+    pub description: String,
+    pub amount: f64,
+    pub vendor_name: Option<String>,
+    pub vendor_slug: Option<String>,
+    /// The accepted quote it came from
+    pub quote: Option<RecordId>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BudgetLine {
+    pub fn category_label(&self) -> &'static str {
+        category_label(&self.category)
+    }
+
+    /// "$1,250.00"
+    pub fn amount_label(&self) -> String {
+        format!("${}", format_value(self.amount))
+    }
+}
+
+/// The budget's total, and each category's in [`CATEGORIES`] order, leaving
+/// out empty ones
+pub fn totals(lines: &[BudgetLine]) -> (f64, Vec<(&'static str, f64)>) {
+    let by_category = CATEGORIES
+        .iter()
+        .filter_map(|(key, label)| {
+            let matching: Vec<f64> = lines
+                .iter()
+                .filter(|l| l.category == *key)
+                .map(|l| l.amount)
+                .collect();
+            (!matching.is_empty()).then(|| (*label, matching.iter().sum()))
+        })
+        .collect();
+    (lines.iter().map(|l| l.amount).sum(), by_category)
+}
+
+const LINE_FIELDS: &str = "id, category, description, amount, vendor.name AS vendor_name,
+    vendor.slug AS vendor_slug, quote, created_at";
+
+pub struct BudgetModel;
+
+impl BudgetModel {
+    /// A production's budget lines, by category and then oldest first
+    pub async fn for_production(production: &RecordId) -> Result<Vec<BudgetLine>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM budget_line WHERE production = $production
+                 ORDER BY category ASC, created_at ASC",
+                LINE_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn add(
+        production: &RecordId,
+        data: &BudgetLineData,
+        vendor: Option<&RecordId>,
+        quote: Option<&RecordId>,
+        created_by: &RecordId,
+    ) -> Result<RecordId, Error> {
+        let id: Option<RecordId> = DB
+            .query(
+                "CREATE budget_line SET production = $production, category = $category,
+                    description = $description, amount = $amount, vendor = $vendor,
+                    quote = $quote, created_by = $created_by
+                 RETURN VALUE id",
+            )
+            .bind(("production", production.clone()))
+            .bind(("category", data.category.clone()))
+            .bind(("description", data.description.clone()))
+            .bind(("amount", data.amount))
+            .bind(("vendor", vendor.cloned()))
+            .bind(("quote", quote.cloned()))
+            .bind(("created_by", created_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to add budget line: {}", e)))?
+            .take(0)?;
+        let id = id.ok_or_else(|| Error::Internal("Budget line was not created".to_string()))?;
+        debug!(
+            "Added budget line {} to {}",
+            id.display(),
+            production.display()
+        );
+        Ok(id)
+    }
+
+    /// Remove a line, unlinking the quote it came from
+    pub async fn remove(production: &RecordId, line_id: &str) -> Result<(), Error> {
+        DB.query(
+            "UPDATE gear_quote SET budget_line = NONE
+                WHERE budget_line = $id AND production = $production;
+             DELETE $id WHERE production = $production;",
+        )
+        .bind(("id", RecordId::new("budget_line", line_id)))
+        .bind(("production", production.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to remove budget line: {}", e)))?
+        .check()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+//! Call sheets
+//!
+//! The plan for a shoot day sent to the production's distribution list: the
+//! date, where the day shoots with the location's house rules, the scenes
+//! scheduled and the crew. Sending waits until the key roles have
+//! acknowledged the location's house rules (see `house_rules`). Productions
+//! owned by an organization that has set up branding get its letterhead.
+
+use crate::{
+    models::{
+        org_branding::Letterhead,
+        shot_list::{SceneShots, ShootDay},
+    },
+    pdf::{Flow, PageSize},
+};
+
+/// Where the day shoots, as printed on the call sheet
+#[derive(Debug, Clone, Default)]
+pub struct CallSheetLocation {
+    pub name: String,
+    pub address: String,
+    pub parking: Option<String>,
+    pub house_rules: Vec<String>,
+}
+
+/// One crew member on the call sheet
+#[derive(Debug, Clone, Default)]
+pub struct CallSheetCrew {
+    pub name: String,
+    pub roles: Vec<String>,
+}
+
+/// Render a call sheet as a PDF
+pub fn call_sheet_pdf(
+    production_title: &str,
+    day: &ShootDay,
+    location: Option<&CallSheetLocation>,
+    scenes: &[SceneShots],
+    crew: &[CallSheetCrew],
+    letterhead: Option<&Letterhead>,
+) -> Vec<u8> {
+    let mut flow = Flow::new(PageSize::LETTER).with_footer(format!(
+        "{} - Call Sheet - Day {}",
+        production_title, day.day_number
+    ));
+    if let Some(letterhead) = letterhead {
+        flow = flow.with_heading_color(letterhead.brand.accent_rgb());
+        letterhead.draw(&mut flow, "CALL SHEET");
+    }
+
+    flow.heading(production_title, 18.0);
+    flow.heading(
+        &format!("Call Sheet - Day {} ({})", day.day_number, day.date),
+        12.0,
+    );
+    if let Some(notes) = day.notes.as_ref().filter(|n| !n.is_empty()) {
+        flow.paragraph(notes, 10.0);
+    }
+    let mut hours = Vec::new();
+    if let Some(call) = &day.call_time {
+        hours.push(("Crew call", call.clone()));
+    }
+    if let Some(wrap) = &day.wrap_time {
+        hours.push(("Estimated wrap", wrap.clone()));
+    }
+    if !hours.is_empty() {
+        flow.key_values(&hours, 10.0);
+    }
+    flow.space(6.0);
+
+    flow.heading("Location", 12.0);
+    match location {
+        Some(location) => {
+            let mut details = vec![
+                ("Location", location.name.clone()),
+                ("Address", location.address.clone()),
+            ];
+            if let Some(parking) = location.parking.as_ref().filter(|p| !p.is_empty()) {
+                details.push(("Parking", parking.clone()));
+            }
+            flow.key_values(&details, 10.0);
+
+            if !location.house_rules.is_empty() {
+                flow.heading("House Rules", 12.0);
+                for (i, rule) in location.house_rules.iter().enumerate() {
+                    flow.paragraph(&format!("{}. {}", i + 1, rule), 10.0);
+                }
+            }
+        }
+        None => flow.paragraph("No location booked for this day.", 10.0),
+    }
+
+    flow.heading("Scenes", 12.0);
+    if scenes.is_empty() {
+        flow.paragraph("No scenes scheduled.", 10.0);
+    } else {
+        let rows: Vec<Vec<String>> = scenes
+            .iter()
+            .map(|s| {
+                vec![
+                    s.scene.number.clone(),
+                    s.scene.heading.clone(),
+                    s.scene.description.clone().unwrap_or_default(),
+                    s.shot_count().to_string(),
+                ]
+            })
+            .collect();
+        flow.table(
+            &["Scene", "Heading", "Description", "Shots"],
+            &[1.0, 3.0, 4.0, 1.0],
+            &rows,
+            9.0,
+        );
+    }
+
+    flow.heading("Crew", 12.0);
+    if crew.is_empty() {
+        flow.paragraph("No crew listed.", 10.0);
+    } else {
+        let rows: Vec<Vec<String>> = crew
+            .iter()
+            .map(|c| vec![c.name.clone(), c.roles.join(", ")])
+            .collect();
+        flow.table(&["Name", "Role"], &[4.0, 5.0], &rows, 9.0);
+    }
+
+    flow.finish()
+}
+
+/// Plain-text summary used as the body of the distribution email
+pub fn call_sheet_summary(
+    production_title: &str,
+    day: &ShootDay,
+    location: Option<&CallSheetLocation>,
+    scenes: &[SceneShots],
+) -> String {
+    let place = location
+        .map(|l| format!("{}, {}", l.name, l.address))
+        .unwrap_or_else(|| "TBC".to_string());
+    let scene_numbers: Vec<&str> = scenes.iter().map(|s| s.scene.number.as_str()).collect();
+    let mut text = format!(
+        "{} - Call Sheet, Day {} ({})\n\nLocation: {}\nScenes: {}\n",
+        production_title,
+        day.day_number,
+        day.date,
+        place,
+        if scene_numbers.is_empty() {
+            "None".to_string()
+        } else {
+            scene_numbers.join(", ")
+        },
+    );
+    if let Some(call) = &day.call_time {
+        text.push_str(&format!("Crew call: {}\n", call));
+    }
+    if let Some(location) = location.filter(|l| !l.house_rules.is_empty()) {
+        text.push_str("\nHouse rules:\n");
+        for rule in &location.house_rules {
+            text.push_str(&format!("- {}\n", rule));
+        }
+    }
+    text.push_str("\nThe full call sheet is attached as a PDF.");
+    text
+}
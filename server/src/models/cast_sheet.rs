@@ -0,0 +1,197 @@
+//! Cast contact sheets
+//!
+//! A page of headshots for a production's cast, each with the actor's name,
+//! the character they play and their phone number. Headshots go through the
+//! same download-and-scale step as portfolio photos. Phone numbers are only
+//! printed for the production's owners and admins, and never for minors.
+
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::warn;
+
+use crate::{
+    db::DB,
+    error::Error,
+    models::portfolio::load_image,
+    pdf::{Flow, Font, PageSize, text_width, wrap},
+};
+
+/// Headshots per row on the PDF
+pub const COLUMNS: usize = 4;
+
+/// Longest side headshots are scaled down to before embedding
+const HEADSHOT_MAX_PX: u32 = 480;
+
+/// Headshot height to width, as on an 8x10
+const HEADSHOT_ASPECT: f32 = 1.25;
+
+/// A credited cast member, as listed on the sheet
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct CastMember {
+    pub name: String,
+    pub username: String,
+    /// The character played
+    pub role: Option<String>,
+    pub avatar: Option<String>,
+    pub phone: Option<String>,
+}
+
+/// One cell of the PDF grid
+pub struct CastSheetEntry {
+    pub name: String,
+    pub role: Option<String>,
+    pub phone: Option<String>,
+    pub headshot: Option<DynamicImage>,
+}
+
+/// "AM" for "Alex Morgan", for cells without a headshot
+pub fn initials(name: &str) -> String {
+    name.split_whitespace()
+        .filter_map(|word| word.chars().next())
+        .filter(|c| c.is_alphanumeric())
+        .take(2)
+        .flat_map(char::to_uppercase)
+        .collect()
+}
+
+/// Size of a headshot drawn to fill a `width` x `height` box without
+/// stretching, and its offset inside the box
+fn fit(image: &DynamicImage, width: f32, height: f32) -> (f32, f32, f32, f32) {
+    let aspect = image.height() as f32 / image.width().max(1) as f32;
+    let (w, h) = if aspect > height / width {
+        (height / aspect, height)
+    } else {
+        (width, width * aspect)
+    };
+    ((width - w) / 2.0, (height - h) / 2.0, w, h)
+}
+
+/// Render a cast contact sheet as a PDF
+pub fn cast_sheet_pdf(
+    production_title: &str,
+    entries: &[CastSheetEntry],
+    size: PageSize,
+) -> Vec<u8> {
+    let mut flow =
+        Flow::new(size).with_footer(format!("{} - Cast Contact Sheet", production_title));
+    flow.heading(&format!("{} - Cast", production_title), 18.0);
+    flow.space(6.0);
+
+    if entries.is_empty() {
+        flow.paragraph("No cast has been credited yet.", 10.0);
+        return flow.finish();
+    }
+
+    let gap = 14.0;
+    let cell_width = (flow.content_width() - gap * (COLUMNS as f32 - 1.0)) / COLUMNS as f32;
+    let photo_height = cell_width * HEADSHOT_ASPECT;
+    let text_height = 42.0;
+    let row_height = photo_height + text_height;
+
+    for row in entries.chunks(COLUMNS) {
+        flow.ensure(row_height);
+        let top = flow.y();
+        let margin = flow.margin();
+        for (i, entry) in row.iter().enumerate() {
+            let x = margin + i as f32 * (cell_width + gap);
+            let photo_bottom = top - photo_height;
+            let mut drawn = false;
+            if let Some(headshot) = &entry.headshot {
+                let (dx, dy, w, h) = fit(headshot, cell_width, photo_height);
+                match flow.doc_mut().add_image(headshot) {
+                    Ok(id) => {
+                        flow.doc_mut()
+                            .draw_image(id, x + dx, photo_bottom + dy, w, h);
+                        drawn = true;
+                    }
+                    Err(e) => warn!("Failed to embed headshot: {}", e),
+                }
+            }
+            if !drawn {
+                let doc = flow.doc_mut();
+                doc.fill_rect(x, photo_bottom, cell_width, photo_height, 0.9);
+                let label = initials(&entry.name);
+                let label_x = x + (cell_width - text_width(&label, 24.0, Font::Bold)) / 2.0;
+                doc.text(
+                    label_x,
+                    photo_bottom + photo_height / 2.0 - 8.0,
+                    24.0,
+                    Font::Bold,
+                    &label,
+                );
+            }
+            flow.doc_mut()
+                .rect(x, photo_bottom, cell_width, photo_height, 0.5);
+
+            let lines = [
+                (entry.name.as_str(), 9.0, Font::Bold),
+                (
+                    entry.role.as_deref().unwrap_or_default(),
+                    8.0,
+                    Font::Regular,
+                ),
+                (
+                    entry.phone.as_deref().unwrap_or_default(),
+                    8.0,
+                    Font::Regular,
+                ),
+            ];
+            let mut y = photo_bottom - 2.0;
+            for (text, size, font) in lines {
+                y -= size * 1.35;
+                if let Some(line) = wrap(text, cell_width, size, font).first() {
+                    flow.doc_mut().text(x, y, size, font, line);
+                }
+            }
+        }
+        flow.space(row_height + gap);
+    }
+
+    flow.finish()
+}
+
+pub struct CastSheetModel;
+
+impl CastSheetModel {
+    /// The production's credited cast, by name. Phone numbers come back only
+    /// when `with_phones`, and never for minors.
+    pub async fn cast(production: &RecordId, with_phones: bool) -> Result<Vec<CastMember>, Error> {
+        Ok(DB
+            .query(
+                "SELECT
+                    in.name ?? in.username AS name,
+                    in.username AS username,
+                    role,
+                    in.profile.avatar AS avatar,
+                    IF $with_phones AND !(in.is_minor ?? false) THEN in.profile.phone ELSE NONE END AS phone
+                FROM involvement
+                WHERE out = $production
+                    AND relation_type = 'cast'
+                    AND verification_status != 'rejected'
+                ORDER BY name ASC",
+            )
+            .bind(("production", production.clone()))
+            .bind(("with_phones", with_phones))
+            .await?
+            .take(0)?)
+    }
+
+    /// The cast with their headshots downloaded and scaled, ready for the PDF
+    pub async fn entries(cast: Vec<CastMember>) -> Vec<CastSheetEntry> {
+        let mut entries = Vec::with_capacity(cast.len());
+        for member in cast {
+            let headshot = match member.avatar.as_deref() {
+                Some(url) => load_image(url, HEADSHOT_MAX_PX).await,
+                None => None,
+            };
+            entries.push(CastSheetEntry {
+                name: member.name,
+                role: member.role.filter(|r| !r.is_empty()),
+                phone: member.phone.filter(|p| !p.is_empty()),
+                headshot,
+            });
+        }
+        entries
+    }
+}
@@ -0,0 +1,335 @@
+//! Company credits: `organization->company_credit->production` edges recording
+//! how a company took part in a production — as a producer, a financier or a
+//! vendor. They make up an organization's production slate, the company list
+//! on a production page, and are written into both records' embedding texts so
+//! "productions financed by X" and "camera vendors for features" match.
+
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, warn};
+
+use crate::db::DB;
+use crate::error::Error;
+use crate::record_id_ext::RecordIdExt;
+use crate::services::embedding::{
+    build_organization_embedding_text, build_production_embedding_text, spawn_embedding_update,
+};
+
+/// Stored role and its label
+pub const COMPANY_ROLES: &[(&str, &str)] = &[
+    ("producer", "Producer"),
+    ("financier", "Financier"),
+    ("vendor", "Vendor"),
+];
+
+/// Longest note on what a company supplied
+const MAX_SERVICES_LEN: usize = 120;
+
+pub fn role_label(role: &str) -> &'static str {
+    COMPANY_ROLES
+        .iter()
+        .find(|(value, _)| *value == role)
+        .map(|(_, label)| *label)
+        .unwrap_or("Company")
+}
+
+/// How the credit reads in the organization's embedding text:
+/// "producer of Harbor Lights", "vendor on Harbor Lights (camera package)"
+pub fn slate_line(role: &str, title: &str, services: Option<&str>) -> String {
+    let preposition = if role == "vendor" { "on" } else { "of" };
+    match services.filter(|s| !s.is_empty()) {
+        Some(services) => format!("{} {} {} ({})", role, preposition, title, services),
+        None => format!("{} {} {}", role, preposition, title),
+    }
+}
+
+/// How the credit reads in the production's embedding text:
+/// "Northlight Pictures (producer)", "Lens House (vendor: camera package)"
+pub fn company_line(name: &str, role: &str, services: Option<&str>) -> String {
+    match services.filter(|s| !s.is_empty()) {
+        Some(services) => format!("{} ({}: {})", name, role, services),
+        None => format!("{} ({})", name, role),
+    }
+}
+
+/// One production on an organization's slate
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct SlateEntry {
+    pub id: RecordId,
+    pub role: String,
+    pub services: Option<String>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub production_type: String,
+    pub status: String,
+    pub poster_url: Option<String>,
+    pub poster_photo: Option<String>,
+    pub release_date: Option<String>,
+    /// Start date, or when the production was added (not used in display)
+    #[serde(default)]
+    #[surreal(default)]
+    pub sort_date: Option<String>,
+}
+
+impl SlateEntry {
+    pub fn role_label(&self) -> &'static str {
+        role_label(&self.role)
+    }
+
+    /// Uploaded poster first, then the TMDB one
+    pub fn poster(&self) -> Option<&str> {
+        self.poster_photo.as_deref().or(self.poster_url.as_deref())
+    }
+
+    /// Year of release, for the slate listing
+    pub fn year(&self) -> Option<&str> {
+        self.release_date.as_deref().and_then(|d| d.get(..4))
+    }
+}
+
+/// One company credited on a production
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ProductionCompany {
+    pub id: RecordId,
+    pub role: String,
+    pub services: Option<String>,
+    pub org_name: String,
+    pub org_slug: String,
+    pub org_logo: Option<String>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub org_verified: bool,
+}
+
+impl ProductionCompany {
+    pub fn role_label(&self) -> &'static str {
+        role_label(&self.role)
+    }
+
+    /// Record key, for the remove form
+    pub fn key(&self) -> String {
+        self.id.key_string()
+    }
+}
+
+pub struct CompanyCreditModel;
+
+impl CompanyCreditModel {
+    /// Credit an organization on a production. Crediting the same company
+    /// twice in one role updates the services note instead.
+    pub async fn add(
+        org_id: &RecordId,
+        production_id: &RecordId,
+        role: &str,
+        services: Option<&str>,
+        added_by: &RecordId,
+    ) -> Result<(), Error> {
+        if !COMPANY_ROLES.iter().any(|(value, _)| *value == role) {
+            return Err(Error::Validation(format!("Unknown company role '{}'", role)));
+        }
+        let services = services.map(str::trim).filter(|s| !s.is_empty());
+        if services.is_some_and(|s| s.chars().count() > MAX_SERVICES_LEN) {
+            return Err(Error::Validation(format!(
+                "Keep the services note under {} characters",
+                MAX_SERVICES_LEN
+            )));
+        }
+        debug!(
+            "Crediting {} as {} on {}",
+            org_id.display(),
+            role,
+            production_id.display()
+        );
+
+        DB.query(
+            "IF (SELECT VALUE id FROM company_credit WHERE in = $org AND out = $production AND role = $role) {
+                 UPDATE company_credit SET services = $services
+                 WHERE in = $org AND out = $production AND role = $role;
+             } ELSE {
+                 RELATE $org->company_credit->$production
+                     SET role = $role, services = $services, added_by = $added_by;
+             };",
+        )
+        .bind(("org", org_id.clone()))
+        .bind(("production", production_id.clone()))
+        .bind(("role", role.to_string()))
+        .bind(("services", services.map(str::to_string)))
+        .bind(("added_by", added_by.clone()))
+        .await?
+        .check()?;
+
+        refresh_embeddings(org_id, production_id).await;
+        Ok(())
+    }
+
+    /// Remove a credit from a production. Returns false if it isn't one of its credits.
+    pub async fn remove(credit_id: &RecordId, production_id: &RecordId) -> Result<bool, Error> {
+        let org_id: Option<RecordId> = DB
+            .query("SELECT VALUE in FROM company_credit WHERE id = $id AND out = $production")
+            .bind(("id", credit_id.clone()))
+            .bind(("production", production_id.clone()))
+            .await?
+            .take(0)?;
+        let Some(org_id) = org_id else {
+            return Ok(false);
+        };
+
+        DB.query("DELETE $id")
+            .bind(("id", credit_id.clone()))
+            .await?
+            .check()?;
+        refresh_embeddings(&org_id, production_id).await;
+        Ok(true)
+    }
+
+    /// An organization's production slate, most recent first
+    pub async fn slate_for_organization(org_id: &RecordId) -> Result<Vec<SlateEntry>, Error> {
+        let entries: Vec<SlateEntry> = DB
+            .query(
+                "SELECT id, role, services,
+                     out.title AS production_title,
+                     out.slug AS production_slug,
+                     out.`type` AS production_type,
+                     out.status AS status,
+                     out.poster_url AS poster_url,
+                     out.poster_photo AS poster_photo,
+                     out.release_date AS release_date,
+                     <string> (out.start_date ?? out.created_at) AS sort_date
+                 FROM company_credit WHERE in = $org
+                 ORDER BY sort_date DESC",
+            )
+            .bind(("org", org_id.clone()))
+            .await?
+            .take(0)?;
+        Ok(entries)
+    }
+
+    /// Companies credited on a production, by role
+    pub async fn companies_for_production(
+        production_id: &RecordId,
+    ) -> Result<Vec<ProductionCompany>, Error> {
+        let companies: Vec<ProductionCompany> = DB
+            .query(
+                "SELECT id, role, services,
+                     in.name AS org_name,
+                     in.slug AS org_slug,
+                     in.logo AS org_logo,
+                     in.verified ?? false AS org_verified
+                 FROM company_credit WHERE out = $production
+                 ORDER BY role, org_name",
+            )
+            .bind(("production", production_id.clone()))
+            .await?
+            .take(0)?;
+        Ok(companies)
+    }
+
+    /// The organization's credits as embedding text lines
+    pub async fn slate_lines(org_id: &RecordId) -> Result<Vec<String>, Error> {
+        Ok(Self::slate_for_organization(org_id)
+            .await?
+            .iter()
+            .map(|e| slate_line(&e.role, &e.production_title, e.services.as_deref()))
+            .collect())
+    }
+
+    /// The production's companies as embedding text lines
+    pub async fn company_lines(production_id: &RecordId) -> Result<Vec<String>, Error> {
+        Ok(Self::companies_for_production(production_id)
+            .await?
+            .iter()
+            .map(|c| company_line(&c.org_name, &c.role, c.services.as_deref()))
+            .collect())
+    }
+}
+
+/// Re-embed both ends of a credit that was added or removed
+async fn refresh_embeddings(org_id: &RecordId, production_id: &RecordId) {
+    if let Err(e) = refresh_organization_embedding(org_id).await {
+        warn!("Failed to refresh embedding for {}: {}", org_id.display(), e);
+    }
+    if let Err(e) = refresh_production_embedding(production_id).await {
+        warn!("Failed to refresh embedding for {}: {}", production_id.display(), e);
+    }
+}
+
+async fn refresh_organization_embedding(org_id: &RecordId) -> Result<(), Error> {
+    #[derive(Deserialize, SurrealValue)]
+    struct Row {
+        name: String,
+        org_type: Option<String>,
+        description: Option<String>,
+        services: Option<Vec<String>>,
+        location: Option<String>,
+        founded_year: Option<i32>,
+        employees_count: Option<i32>,
+    }
+
+    let row: Option<Row> = DB
+        .query(
+            "SELECT name, type.name AS org_type, description, services, location, founded_year,
+                 employees_count
+             FROM ONLY $id",
+        )
+        .bind(("id", org_id.clone()))
+        .await?
+        .take(0)?;
+    let Some(row) = row else {
+        return Ok(());
+    };
+
+    let slate = CompanyCreditModel::slate_lines(org_id).await?;
+    let embedding_text = build_organization_embedding_text(
+        &row.name,
+        row.org_type.as_deref().unwrap_or(""),
+        row.description.as_deref(),
+        &row.services.unwrap_or_default(),
+        row.location.as_deref(),
+        row.founded_year,
+        row.employees_count,
+        &slate,
+    );
+    spawn_embedding_update(org_id.clone(), embedding_text);
+    Ok(())
+}
+
+async fn refresh_production_embedding(production_id: &RecordId) -> Result<(), Error> {
+    #[derive(Deserialize, SurrealValue)]
+    struct Row {
+        title: String,
+        production_type: String,
+        status: String,
+        description: Option<String>,
+        location: Option<String>,
+        start_date: Option<String>,
+        end_date: Option<String>,
+    }
+
+    let row: Option<Row> = DB
+        .query(
+            "SELECT title, type AS production_type, status, description, location,
+                 IF start_date THEN <string> start_date END AS start_date,
+                 IF end_date THEN <string> end_date END AS end_date
+             FROM ONLY $id",
+        )
+        .bind(("id", production_id.clone()))
+        .await?
+        .take(0)?;
+    let Some(row) = row else {
+        return Ok(());
+    };
+
+    let companies = CompanyCreditModel::company_lines(production_id).await?;
+    let embedding_text = build_production_embedding_text(
+        &row.title,
+        &row.production_type,
+        &row.status,
+        row.description.as_deref(),
+        row.location.as_deref(),
+        row.start_date.as_deref(),
+        row.end_date.as_deref(),
+        &companies,
+    );
+    spawn_embedding_update(production_id.clone(), embedding_text);
+    Ok(())
+}
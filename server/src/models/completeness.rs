@@ -0,0 +1,283 @@
+//! Profile completeness
+//!
+//! A 0-100 score worked out from which profile fields and media a person has
+//! filled in, and the checklist of what's left. Sparse profiles make poor
+//! embeddings and rank badly in search, so the owner sees the checklist on
+//! their own profile. The score is also stored on the person, refreshed when
+//! the profile changes and daily by a scheduled task, for admin analytics.
+
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, info};
+
+use crate::{db::DB, error::Error, models::person::Profile};
+
+/// Credits a profile needs before that item is checked off
+pub const CREDITS_WANTED: usize = 3;
+
+/// Skills a profile needs before that item is checked off
+pub const SKILLS_WANTED: usize = 3;
+
+/// A bio shorter than this doesn't count as written
+pub const BIO_MIN_CHARS: usize = 80;
+
+/// One checklist entry
+#[derive(Debug, Clone)]
+pub struct ChecklistItem {
+    pub label: String,
+    /// Where on the edit page to fix it
+    pub link: &'static str,
+    /// Points this item is worth toward the score
+    pub points: u32,
+    /// Points earned so far; only credits give partial points
+    pub earned: u32,
+}
+
+impl ChecklistItem {
+    pub fn is_done(&self) -> bool {
+        self.earned >= self.points
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Completeness {
+    /// 0 to 100
+    pub score: u32,
+    pub items: Vec<ChecklistItem>,
+}
+
+impl Completeness {
+    /// Items not yet done, worth the most first
+    pub fn remaining(&self) -> Vec<&ChecklistItem> {
+        let mut remaining: Vec<_> = self.items.iter().filter(|i| !i.is_done()).collect();
+        remaining.sort_by(|a, b| (b.points - b.earned).cmp(&(a.points - a.earned)));
+        remaining
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.score >= 100
+    }
+}
+
+fn filled(value: Option<&String>) -> bool {
+    value.is_some_and(|v| !v.trim().is_empty())
+}
+
+fn item(label: impl Into<String>, link: &'static str, points: u32, done: bool) -> ChecklistItem {
+    ChecklistItem {
+        label: label.into(),
+        link,
+        points,
+        earned: if done { points } else { 0 },
+    }
+}
+
+/// Score a profile. `credits` is the number of productions the person is
+/// credited on.
+pub fn completeness(profile: Option<&Profile>, credits: usize) -> Completeness {
+    let empty = Profile::default();
+    let p = profile.unwrap_or(&empty);
+
+    let credits_label = match CREDITS_WANTED.saturating_sub(credits) {
+        1 if credits > 0 => "Add 1 more credit".to_string(),
+        n if credits > 0 && n > 0 => format!("Add {} more credits", n),
+        _ => format!("Add {} credits", CREDITS_WANTED),
+    };
+    let credits_points = 20;
+    let skills_done = p.skills.iter().filter(|s| !s.trim().is_empty()).count() >= SKILLS_WANTED;
+    let bio_done = p
+        .bio
+        .as_deref()
+        .is_some_and(|b| b.trim().chars().count() >= BIO_MIN_CHARS);
+
+    let items = vec![
+        item(
+            "Upload a headshot",
+            "/profile/edit#profile-edit-avatar",
+            20,
+            filled(p.avatar.as_ref()),
+        ),
+        item(
+            "Write a headline",
+            "/profile/edit#field-headline",
+            10,
+            filled(p.headline.as_ref()),
+        ),
+        item(
+            format!("Write a bio of at least {} characters", BIO_MIN_CHARS),
+            "/profile/edit#field-bio",
+            15,
+            bio_done,
+        ),
+        item(
+            "Add your location",
+            "/profile/edit#field-location",
+            5,
+            filled(p.location.as_ref()),
+        ),
+        ChecklistItem {
+            label: credits_label,
+            link: "/profile/edit#section-credits",
+            points: credits_points,
+            earned: credits_points * credits.min(CREDITS_WANTED) as u32 / CREDITS_WANTED as u32,
+        },
+        item(
+            format!("List at least {} skills", SKILLS_WANTED),
+            "/profile/edit#section-skills-languages",
+            10,
+            skills_done,
+        ),
+        item(
+            "Add a reel",
+            "/profile/edit#section-reels",
+            10,
+            !p.reels.is_empty(),
+        ),
+        item(
+            "Add photos",
+            "/profile/edit#section-photos",
+            5,
+            !p.photos.is_empty(),
+        ),
+        item(
+            "Link your website or socials",
+            "/profile/edit#section-social-links",
+            5,
+            filled(p.website.as_ref()) || !p.social_links.is_empty(),
+        ),
+    ];
+
+    Completeness {
+        score: items.iter().map(|i| i.earned).sum(),
+        items,
+    }
+}
+
+/// Score bands shown on the admin dashboard
+pub const BANDS: &[(&str, u32, u32)] = &[
+    ("0-24", 0, 24),
+    ("25-49", 25, 49),
+    ("50-74", 50, 74),
+    ("75-99", 75, 99),
+    ("100", 100, 100),
+];
+
+/// Completeness across all profiles, for the admin dashboard
+#[derive(Debug, Clone, Default)]
+pub struct CompletenessStats {
+    /// Profiles that have been scored
+    pub scored: u64,
+    pub average: u32,
+    /// Profile count per entry of `BANDS`
+    pub bands: Vec<(&'static str, u64)>,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct ScoringRow {
+    id: RecordId,
+    profile: Option<Profile>,
+    credits: i64,
+    completeness: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct ScoreRow {
+    completeness: i64,
+}
+
+pub struct CompletenessModel;
+
+impl CompletenessModel {
+    /// Number of productions a person is credited on
+    pub async fn credit_count(person: &RecordId) -> Result<usize, Error> {
+        let count: Option<i64> = DB
+            .query("RETURN array::len(array::distinct($person->involvement->production))")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        Ok(count.unwrap_or(0).max(0) as usize)
+    }
+
+    /// Store a freshly worked out score on the person
+    pub async fn store(person: &RecordId, score: u32) -> Result<(), Error> {
+        DB.query("UPDATE $person SET completeness = $score")
+            .bind(("person", person.clone()))
+            .bind(("score", score as i64))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Recompute and store a person's score, after a profile or credit change
+    pub async fn refresh(person: &RecordId) -> Result<Completeness, Error> {
+        let profile: Option<Profile> = DB
+            .query("SELECT VALUE profile FROM ONLY $person")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        let credits = Self::credit_count(person).await?;
+        let result = completeness(profile.as_ref(), credits);
+        Self::store(person, result.score).await?;
+        Ok(result)
+    }
+
+    /// Rescore every profile, writing only the scores that changed
+    pub async fn refresh_all() -> Result<(), Error> {
+        let rows: Vec<ScoringRow> = DB
+            .query(
+                "SELECT id, profile, completeness,
+                    array::len(array::distinct(->involvement->production)) AS credits
+                FROM person",
+            )
+            .await?
+            .take(0)?;
+
+        let mut updated = 0;
+        for row in &rows {
+            let score = completeness(row.profile.as_ref(), row.credits.max(0) as usize).score;
+            if row.completeness != Some(score as i64) {
+                Self::store(&row.id, score).await?;
+                updated += 1;
+            }
+        }
+        if updated > 0 {
+            info!(
+                "Rescored completeness for {} of {} profiles",
+                updated,
+                rows.len()
+            );
+        } else {
+            debug!("Profile completeness scores are up to date");
+        }
+        Ok(())
+    }
+
+    pub async fn stats() -> Result<CompletenessStats, Error> {
+        let rows: Vec<ScoreRow> = DB
+            .query("SELECT completeness FROM person WHERE completeness IS NOT NONE")
+            .await?
+            .take(0)?;
+
+        let scored = rows.len() as u64;
+        let total: i64 = rows.iter().map(|r| r.completeness).sum();
+        let bands = BANDS
+            .iter()
+            .map(|(label, low, high)| {
+                let count = rows
+                    .iter()
+                    .filter(|r| (*low as i64..=*high as i64).contains(&r.completeness))
+                    .count() as u64;
+                (*label, count)
+            })
+            .collect();
+        Ok(CompletenessStats {
+            scored,
+            average: if scored == 0 {
+                0
+            } else {
+                (total / scored as i64) as u32
+            },
+            bands,
+        })
+    }
+}
@@ -0,0 +1,559 @@
+//! Daily production reports (DPR)
+//!
+//! At wrap the AD assembles a report for the shoot day: the day's timings,
+//! the scenes scheduled and completed and the shots captured (taken from the
+//! shot list), crew in/out times and any incidents. Reports start as drafts
+//! the AD edits, and become final when they are sent to the production's
+//! distribution list as a PDF.
+
+use crate::{
+    db::DB,
+    error::Error,
+    models::{
+        production::ProductionModel,
+        shot_list::{SceneShots, ShootDay, ShotListModel},
+        timecard::{TimecardListing, TimecardModel},
+    },
+    pdf::{Flow, PageSize},
+    record_id_ext::RecordIdExt,
+};
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+/// Whether production roles include an assistant director ("1st Assistant
+/// Director", "2nd AD", ...). ADs may edit and send reports without edit
+/// rights on the production itself.
+pub fn is_assistant_director(roles: &[String]) -> bool {
+    roles.iter().any(|role| {
+        let role = role.to_lowercase();
+        role.contains("assistant director") || role.split_whitespace().any(|w| w == "ad")
+    })
+}
+
+/// Most addresses a distribution list can hold
+pub const MAX_RECIPIENTS: usize = 50;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SurrealValue)]
+pub struct CrewTime {
+    pub name: String,
+    pub role: String,
+    /// "HH:MM", empty until recorded
+    pub time_in: String,
+    pub time_out: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SurrealValue)]
+pub struct Incident {
+    /// "HH:MM", empty when not recorded
+    pub time: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct DailyReport {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub shoot_day: RecordId,
+    /// "draft" or "final"
+    pub status: String,
+    pub call_time: Option<String>,
+    pub first_shot: Option<String>,
+    pub lunch_start: Option<String>,
+    pub lunch_end: Option<String>,
+    pub wrap_time: Option<String>,
+    pub weather: Option<String>,
+    pub notes: Option<String>,
+    /// Scene numbers scheduled for the day
+    pub scenes_scheduled: Vec<String>,
+    /// Scene numbers with every shot captured
+    pub scenes_completed: Vec<String>,
+    pub shots_total: i64,
+    pub shots_captured: i64,
+    pub crew: Vec<CrewTime>,
+    pub incidents: Vec<Incident>,
+    pub sent_to: Vec<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DailyReport {
+    pub fn is_final(&self) -> bool {
+        self.status == "final"
+    }
+
+    /// Scheduled scenes not completed on the day
+    pub fn scenes_remaining(&self) -> Vec<String> {
+        self.scenes_scheduled
+            .iter()
+            .filter(|s| !self.scenes_completed.contains(s))
+            .cloned()
+            .collect()
+    }
+}
+
+/// AD edits to a report. Times are "HH:MM"; crew and incidents use the
+/// line formats of [`parse_crew`] and [`parse_incidents`].
+#[derive(Debug, Clone, Default)]
+pub struct UpdateReportData {
+    pub call_time: String,
+    pub first_shot: String,
+    pub lunch_start: String,
+    pub lunch_end: String,
+    pub wrap_time: String,
+    pub weather: String,
+    pub notes: String,
+    pub crew: String,
+    pub incidents: String,
+}
+
+/// Normalize a time of day to "HH:MM". Accepts "7:05", "07:05" and "0705".
+/// Empty input is `Ok(None)`.
+pub fn parse_time(input: &str) -> Result<Option<String>, Error> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let parsed = NaiveTime::parse_from_str(input, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H%M"))
+        .map_err(|_| Error::Validation(format!("\"{}\" isn't a time of day (use HH:MM)", input)))?;
+    Ok(Some(parsed.format("%H:%M").to_string()))
+}
+
+/// Hours between two "HH:MM" times, wrapping past midnight
+pub fn hours_between(start: &str, end: &str) -> Option<f64> {
+    let start = NaiveTime::parse_from_str(start, "%H:%M").ok()?;
+    let end = NaiveTime::parse_from_str(end, "%H:%M").ok()?;
+    let mut minutes = (end - start).num_minutes();
+    if minutes < 0 {
+        minutes += 24 * 60;
+    }
+    Some(minutes as f64 / 60.0)
+}
+
+/// Crew lines as "Name | Role | In | Out". Role and times may be left out.
+pub fn parse_crew(input: &str) -> Result<Vec<CrewTime>, Error> {
+    let mut crew = Vec::new();
+    for line in input.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let mut parts = line.split('|').map(str::trim);
+        let name = parts.next().unwrap_or_default().to_string();
+        if name.is_empty() {
+            return Err(Error::Validation(format!("Crew line \"{}\" needs a name", line)));
+        }
+        let role = parts.next().unwrap_or_default().to_string();
+        let time_in = parse_time(parts.next().unwrap_or_default())?.unwrap_or_default();
+        let time_out = parse_time(parts.next().unwrap_or_default())?.unwrap_or_default();
+        crew.push(CrewTime {
+            name,
+            role,
+            time_in,
+            time_out,
+        });
+    }
+    Ok(crew)
+}
+
+pub fn crew_text(crew: &[CrewTime]) -> String {
+    crew.iter()
+        .map(|c| format!("{} | {} | {} | {}", c.name, c.role, c.time_in, c.time_out))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Incident lines, each optionally starting with a "HH:MM" time
+pub fn parse_incidents(input: &str) -> Vec<Incident> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|line| {
+            let (first, rest) = line.split_once(' ').unwrap_or((line, ""));
+            match parse_time(first) {
+                Ok(Some(time)) if !rest.trim().is_empty() => Incident {
+                    time,
+                    description: rest.trim().to_string(),
+                },
+                _ => Incident {
+                    time: String::new(),
+                    description: line.to_string(),
+                },
+            }
+        })
+        .collect()
+}
+
+pub fn incidents_text(incidents: &[Incident]) -> String {
+    incidents
+        .iter()
+        .map(|i| {
+            if i.time.is_empty() {
+                i.description.clone()
+            } else {
+                format!("{} {}", i.time, i.description)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Distribution list addresses, separated by commas or new lines
+pub fn parse_recipients(input: &str) -> Result<Vec<String>, Error> {
+    let mut recipients: Vec<String> = Vec::new();
+    for address in input
+        .split([',', ';', '\n'])
+        .map(|a| a.trim().to_lowercase())
+        .filter(|a| !a.is_empty())
+    {
+        let valid = address
+            .split_once('@')
+            .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.') && !domain.starts_with('.'));
+        if !valid || address.contains(char::is_whitespace) {
+            return Err(Error::Validation(format!("\"{}\" isn't an email address", address)));
+        }
+        if !recipients.contains(&address) {
+            recipients.push(address);
+        }
+    }
+    if recipients.len() > MAX_RECIPIENTS {
+        return Err(Error::Validation(format!(
+            "A distribution list can hold up to {} addresses",
+            MAX_RECIPIENTS
+        )));
+    }
+    Ok(recipients)
+}
+
+/// Fill crew in/out times from the day's timecards, matching by name. Crew
+/// with a timecard but no line on the report are added. Rejected timecards
+/// are ignored, and times the AD has already entered are kept.
+pub fn fill_crew_times(crew: &mut Vec<CrewTime>, timecards: &[TimecardListing]) {
+    for timecard in timecards.iter().filter(|t| t.status != "rejected") {
+        let time_out = timecard.time_out.clone().unwrap_or_default();
+        match crew.iter_mut().find(|c| c.name == timecard.person_name) {
+            Some(line) => {
+                if line.time_in.is_empty() {
+                    line.time_in = timecard.time_in.clone();
+                }
+                if line.time_out.is_empty() {
+                    line.time_out = time_out;
+                }
+            }
+            None => crew.push(CrewTime {
+                name: timecard.person_name.clone(),
+                role: String::new(),
+                time_in: timecard.time_in.clone(),
+                time_out,
+            }),
+        }
+    }
+}
+
+/// Scene numbers scheduled on the day and those completed
+pub fn scene_summary(scenes: &[SceneShots]) -> (Vec<String>, Vec<String>) {
+    let scheduled = scenes.iter().map(|s| s.scene.number.clone()).collect();
+    let completed = scenes
+        .iter()
+        .filter(|s| s.is_complete())
+        .map(|s| s.scene.number.clone())
+        .collect();
+    (scheduled, completed)
+}
+
+fn or_dash(value: &Option<String>) -> String {
+    value.clone().filter(|v| !v.is_empty()).unwrap_or_else(|| "-".to_string())
+}
+
+fn join_or_none(values: &[String]) -> String {
+    if values.is_empty() {
+        "None".to_string()
+    } else {
+        values.join(", ")
+    }
+}
+
+/// Render a report as a PDF
+pub fn report_pdf(production_title: &str, day: &ShootDay, report: &DailyReport) -> Vec<u8> {
+    let mut flow = Flow::new(PageSize::LETTER).with_footer(format!(
+        "{} - Daily Production Report - Day {}",
+        production_title, day.day_number
+    ));
+
+    flow.heading(production_title, 18.0);
+    flow.heading(
+        &format!(
+            "Daily Production Report - Day {} ({}){}",
+            day.day_number,
+            day.date,
+            if report.is_final() { "" } else { " - DRAFT" }
+        ),
+        12.0,
+    );
+    flow.space(6.0);
+
+    flow.key_values(
+        &[
+            ("Crew call", or_dash(&report.call_time)),
+            ("First shot", or_dash(&report.first_shot)),
+            ("Lunch", format!("{} - {}", or_dash(&report.lunch_start), or_dash(&report.lunch_end))),
+            ("Wrap", or_dash(&report.wrap_time)),
+            ("Weather", or_dash(&report.weather)),
+        ],
+        10.0,
+    );
+
+    flow.heading("Progress", 12.0);
+    flow.key_values(
+        &[
+            ("Scenes scheduled", join_or_none(&report.scenes_scheduled)),
+            ("Scenes completed", join_or_none(&report.scenes_completed)),
+            ("Scenes remaining", join_or_none(&report.scenes_remaining())),
+            (
+                "Shots captured",
+                format!("{} of {}", report.shots_captured, report.shots_total),
+            ),
+        ],
+        10.0,
+    );
+
+    flow.heading("Crew", 12.0);
+    if report.crew.is_empty() {
+        flow.paragraph("No crew recorded.", 10.0);
+    } else {
+        let rows: Vec<Vec<String>> = report
+            .crew
+            .iter()
+            .map(|c| {
+                vec![
+                    c.name.clone(),
+                    c.role.clone(),
+                    c.time_in.clone(),
+                    c.time_out.clone(),
+                    hours_between(&c.time_in, &c.time_out)
+                        .map(|h| format!("{:.1}", h))
+                        .unwrap_or_default(),
+                ]
+            })
+            .collect();
+        flow.table(&["Name", "Role", "In", "Out", "Hours"], &[3.0, 3.0, 1.0, 1.0, 1.0], &rows, 9.0);
+    }
+
+    flow.heading("Incidents", 12.0);
+    if report.incidents.is_empty() {
+        flow.paragraph("No incidents reported.", 10.0);
+    } else {
+        let rows: Vec<Vec<String>> = report
+            .incidents
+            .iter()
+            .map(|i| vec![i.time.clone(), i.description.clone()])
+            .collect();
+        flow.table(&["Time", "Description"], &[1.0, 8.0], &rows, 9.0);
+    }
+
+    if let Some(notes) = report.notes.as_ref().filter(|n| !n.is_empty()) {
+        flow.heading("Notes", 12.0);
+        flow.paragraph(notes, 10.0);
+    }
+
+    flow.finish()
+}
+
+/// Plain-text summary used as the body of the distribution email
+pub fn report_summary(production_title: &str, day: &ShootDay, report: &DailyReport) -> String {
+    format!(
+        "{title} - Daily Production Report, Day {number} ({date})\n\n\
+         Crew call: {call}\nFirst shot: {first}\nWrap: {wrap}\n\n\
+         Scenes completed: {completed}\nScenes remaining: {remaining}\n\
+         Shots captured: {captured} of {total}\nIncidents: {incidents}\n\n\
+         The full report is attached as a PDF.",
+        title = production_title,
+        number = day.day_number,
+        date = day.date,
+        call = or_dash(&report.call_time),
+        first = or_dash(&report.first_shot),
+        wrap = or_dash(&report.wrap_time),
+        completed = join_or_none(&report.scenes_completed),
+        remaining = join_or_none(&report.scenes_remaining()),
+        captured = report.shots_captured,
+        total = report.shots_total,
+        incidents = report.incidents.len(),
+    )
+}
+
+pub struct DailyReportModel;
+
+impl DailyReportModel {
+    pub async fn for_day(day: &RecordId) -> Result<Option<DailyReport>, Error> {
+        Ok(DB
+            .query("SELECT * FROM daily_report WHERE shoot_day = $day LIMIT 1")
+            .bind(("day", day.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Reports for a production, keyed by shoot day
+    pub async fn for_production(production: &RecordId) -> Result<Vec<DailyReport>, Error> {
+        Ok(DB
+            .query("SELECT * FROM daily_report WHERE production = $production")
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Assemble the report for a shoot day. A new report is prefilled with
+    /// the accepted members of the production as crew; an existing draft has
+    /// its scene and shot figures refreshed from the shot list. Crew times
+    /// are filled from timecards either way. Final reports are left as sent.
+    pub async fn assemble(production: &RecordId, day: &ShootDay) -> Result<DailyReport, Error> {
+        let existing = Self::for_day(&day.id).await?;
+        if let Some(report) = existing.as_ref().filter(|r| r.is_final()) {
+            return Ok(report.clone());
+        }
+
+        let scenes = ShotListModel::scenes(production, Some(&day.id)).await?;
+        let (scheduled, completed) = scene_summary(&scenes);
+        let shots_total: usize = scenes.iter().map(|s| s.shot_count()).sum();
+        let shots_captured: usize = scenes.iter().map(|s| s.captured_count()).sum();
+
+        let query = if existing.is_some() {
+            "UPDATE daily_report SET scenes_scheduled = $scheduled, scenes_completed = $completed,
+                shots_total = $shots_total, shots_captured = $shots_captured, crew = $crew,
+                updated_at = time::now()
+             WHERE shoot_day = $day RETURN AFTER"
+        } else {
+            "CREATE daily_report SET production = $production, shoot_day = $day, status = 'draft',
+                scenes_scheduled = $scheduled, scenes_completed = $completed,
+                shots_total = $shots_total, shots_captured = $shots_captured,
+                crew = $crew, incidents = [], sent_to = [],
+                created_at = time::now(), updated_at = time::now()"
+        };
+        let mut crew = match existing {
+            Some(report) => report.crew,
+            None => Self::default_crew(production).await?,
+        };
+        fill_crew_times(&mut crew, &TimecardModel::for_day(&day.id).await?);
+
+        debug!("Assembling daily report for {}", day.id.display());
+        let report: Option<DailyReport> = DB
+            .query(query)
+            .bind(("production", production.clone()))
+            .bind(("day", day.id.clone()))
+            .bind(("scheduled", scheduled))
+            .bind(("completed", completed))
+            .bind(("shots_total", shots_total as i64))
+            .bind(("shots_captured", shots_captured as i64))
+            .bind(("crew", crew))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to assemble daily report: {}", e)))?
+            .take(0)?;
+        report.ok_or_else(|| Error::Internal("Failed to assemble daily report".to_string()))
+    }
+
+    /// Accepted person members of the production, with their production roles
+    async fn default_crew(production: &RecordId) -> Result<Vec<CrewTime>, Error> {
+        Ok(ProductionModel::get_members(production)
+            .await?
+            .into_iter()
+            .filter(|m| m.member_type == "person" && m.invitation_status == "accepted")
+            .map(|m| CrewTime {
+                name: m.name,
+                role: m.production_roles.unwrap_or_default().join(", "),
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    /// Apply AD edits to a draft report
+    pub async fn update(report: &DailyReport, data: UpdateReportData) -> Result<DailyReport, Error> {
+        if report.is_final() {
+            return Err(Error::Conflict("This report has been sent and can no longer be edited".to_string()));
+        }
+        let crew = parse_crew(&data.crew)?;
+        let incidents = parse_incidents(&data.incidents);
+        let optional = |s: String| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+
+        let updated: Option<DailyReport> = DB
+            .query(
+                "UPDATE $id SET call_time = $call_time, first_shot = $first_shot,
+                    lunch_start = $lunch_start, lunch_end = $lunch_end, wrap_time = $wrap_time,
+                    weather = $weather, notes = $notes, crew = $crew, incidents = $incidents,
+                    updated_at = time::now()
+                 RETURN AFTER",
+            )
+            .bind(("id", report.id.clone()))
+            .bind(("call_time", parse_time(&data.call_time)?))
+            .bind(("first_shot", parse_time(&data.first_shot)?))
+            .bind(("lunch_start", parse_time(&data.lunch_start)?))
+            .bind(("lunch_end", parse_time(&data.lunch_end)?))
+            .bind(("wrap_time", parse_time(&data.wrap_time)?))
+            .bind(("weather", optional(data.weather)))
+            .bind(("notes", optional(data.notes)))
+            .bind(("crew", crew))
+            .bind(("incidents", incidents))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update daily report: {}", e)))?
+            .take(0)?;
+        updated.ok_or(Error::NotFound)
+    }
+
+    /// Mark a report final, recording who it went to
+    pub async fn finalize(report: &RecordId, sent_to: Vec<String>) -> Result<(), Error> {
+        DB.query("UPDATE $id SET status = 'final', sent_to = $sent_to, sent_at = time::now(), updated_at = time::now()")
+            .bind(("id", report.clone()))
+            .bind(("sent_to", sent_to))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to finalize daily report: {}", e)))?;
+        Ok(())
+    }
+
+    /// Reopen a sent report for corrections
+    pub async fn reopen(report: &RecordId) -> Result<(), Error> {
+        DB.query("UPDATE $id SET status = 'draft', updated_at = time::now()")
+            .bind(("id", report.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to reopen daily report: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn recipients(production: &RecordId) -> Result<Vec<String>, Error> {
+        let recipients: Option<Vec<String>> = DB
+            .query("SELECT VALUE report_recipients FROM ONLY $production")
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        Ok(recipients.unwrap_or_default())
+    }
+
+    pub async fn set_recipients(production: &RecordId, input: &str) -> Result<Vec<String>, Error> {
+        let recipients = parse_recipients(input)?;
+        DB.query("UPDATE $production SET report_recipients = $recipients")
+            .bind(("production", production.clone()))
+            .bind(("recipients", recipients.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to save distribution list: {}", e)))?;
+        Ok(recipients)
+    }
+
+    /// Draft reports for shoot days that have passed without one. Run by the
+    /// `daily_reports` scheduled task so every day has a report to start from.
+    pub async fn assemble_due() -> Result<(), Error> {
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+        let days: Vec<ShootDay> = DB
+            .query(
+                "SELECT * FROM shoot_day WHERE date < $today
+                    AND id NOT IN (SELECT VALUE shoot_day FROM daily_report)",
+            )
+            .bind(("today", today))
+            .await?
+            .take(0)?;
+
+        for day in &days {
+            Self::assemble(&day.production, day).await?;
+        }
+        if !days.is_empty() {
+            debug!("Assembled {} daily report(s)", days.len());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,579 @@
+//! Post-production deliverables
+//!
+//! Each production keeps a checklist of what it owes distributors and
+//! festivals: masters, caption files, DCPs, key art and the like. A
+//! deliverable carries its specs, a due date and the vendor making it, and
+//! moves from not started through delivered to approved. Its members attach
+//! files along the way (caption files, QC reports, artwork proofs), which are
+//! stored privately in S3 and only served to the production's members.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, error};
+use ulid::Ulid;
+
+use crate::{db::DB, error::Error, record_id_ext::RecordIdExt, services::s3::s3};
+
+const FILE_PREFIX: &str = "private/deliverables";
+
+/// Kinds of deliverable, with their labels
+pub const KINDS: &[(&str, &str)] = &[
+    ("master", "Master"),
+    ("captions", "Captions & subtitles"),
+    ("dcp", "DCP"),
+    ("artwork", "Key art"),
+    ("trailer", "Trailer"),
+    ("stills", "Stills"),
+    ("music", "Music & M&E"),
+    ("paperwork", "Paperwork"),
+    ("other", "Other"),
+];
+
+/// Statuses in the order a deliverable moves through them, with their labels
+pub const STATUSES: &[(&str, &str)] = &[
+    ("not_started", "Not started"),
+    ("in_progress", "In progress"),
+    ("delivered", "Delivered"),
+    ("approved", "Approved"),
+];
+
+pub const MAX_TITLE_CHARS: usize = 120;
+pub const MAX_SPECS_CHARS: usize = 1000;
+pub const MAX_VENDOR_CHARS: usize = 120;
+
+/// Largest attachment; masters themselves go through the vendor's own
+/// transfer, not here
+pub const MAX_FILE_SIZE: usize = 50 * 1024 * 1024;
+
+/// Extensions an attachment can have
+pub const ALLOWED_EXTENSIONS: &[&str] = &[
+    "pdf", "png", "jpg", "jpeg", "tif", "tiff", "psd", "srt", "vtt", "scc", "stl", "txt", "csv",
+    "xml", "docx", "xlsx", "zip",
+];
+
+/// "Captions & subtitles" for "captions"
+pub fn kind_label(kind: &str) -> &'static str {
+    KINDS
+        .iter()
+        .find(|(key, _)| *key == kind)
+        .map_or("Other", |(_, label)| *label)
+}
+
+/// "In progress" for "in_progress"
+pub fn status_label(status: &str) -> &'static str {
+    STATUSES
+        .iter()
+        .find(|(key, _)| *key == status)
+        .map_or("Not started", |(_, label)| *label)
+}
+
+/// Whether a status counts as handed over
+pub fn is_done(status: &str) -> bool {
+    matches!(status, "delivered" | "approved")
+}
+
+/// Whether an attachment's name has an allowed extension
+pub fn attachment_allowed(file_name: &str) -> bool {
+    file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .is_some_and(|ext| ALLOWED_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// A file name safe to use in a storage key and a download header:
+/// letters, digits, dots, dashes and underscores, at most 100 characters
+pub fn safe_file_name(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let mut cleaned = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() || matches!(c, '.' | '_') {
+            cleaned.push(c);
+        } else if !cleaned.ends_with('-') {
+            cleaned.push('-');
+        }
+    }
+    let cleaned: String = cleaned
+        .trim_matches(|c| c == '-' || c == '.')
+        .chars()
+        .take(100)
+        .collect();
+    if cleaned.is_empty() {
+        "file".to_string()
+    } else {
+        cleaned
+    }
+}
+
+/// Where an attachment is stored
+pub fn file_key(production_key: &str, deliverable_key: &str, id: &str, file_name: &str) -> String {
+    format!(
+        "{}/{}/{}/{}-{}",
+        FILE_PREFIX,
+        production_key,
+        deliverable_key,
+        id,
+        safe_file_name(file_name)
+    )
+}
+
+/// "1.2 MB"
+pub fn size_label(bytes: i64) -> String {
+    let bytes = bytes.max(0) as f64;
+    if bytes >= 1_048_576.0 {
+        format!("{:.1} MB", bytes / 1_048_576.0)
+    } else if bytes >= 1_024.0 {
+        format!("{:.0} KB", bytes / 1_024.0)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+fn clean_text(value: &str, max: usize, what: &str) -> Result<Option<String>, Error> {
+    let value = value.trim();
+    if value.chars().count() > max {
+        return Err(Error::Validation(format!(
+            "{} can be up to {} characters",
+            what, max
+        )));
+    }
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/// A deliverable from the form, checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliverableData {
+    pub kind: String,
+    pub title: String,
+    pub specs: Option<String>,
+    /// YYYY-MM-DD
+    pub due_date: Option<String>,
+    /// A vendor not on SlateHub, when none of the production's organizations
+    /// is picked
+    pub vendor_name: Option<String>,
+}
+
+impl DeliverableData {
+    pub fn parse(
+        kind: &str,
+        title: &str,
+        specs: &str,
+        due_date: &str,
+        vendor_name: &str,
+    ) -> Result<Self, Error> {
+        if !KINDS.iter().any(|(key, _)| *key == kind) {
+            return Err(Error::Validation(
+                "Choose what kind of deliverable it is".to_string(),
+            ));
+        }
+        let title = clean_text(title, MAX_TITLE_CHARS, "The name")?
+            .ok_or_else(|| Error::Validation("Name the deliverable".to_string()))?;
+        let due_date = match due_date.trim() {
+            "" => None,
+            value => {
+                NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                    Error::Validation(format!("\"{}\" isn't a date (use YYYY-MM-DD)", value))
+                })?;
+                Some(value.to_string())
+            }
+        };
+        Ok(Self {
+            kind: kind.to_string(),
+            title,
+            specs: clean_text(specs, MAX_SPECS_CHARS, "Specs")?,
+            due_date,
+            vendor_name: clean_text(vendor_name, MAX_VENDOR_CHARS, "The vendor's name")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Deliverable {
+    pub id: RecordId,
+    pub kind: String,
+    pub title: String,
+    pub specs: Option<String>,
+    pub due_date: Option<String>,
+    pub vendor: Option<RecordId>,
+    pub vendor_org_name: Option<String>,
+    pub vendor_slug: Option<String>,
+    pub vendor_name: Option<String>,
+    pub status: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Deliverable {
+    pub fn kind_label(&self) -> &'static str {
+        kind_label(&self.kind)
+    }
+
+    pub fn status_label(&self) -> &'static str {
+        status_label(&self.status)
+    }
+
+    pub fn is_done(&self) -> bool {
+        is_done(&self.status)
+    }
+
+    /// Past its due date without being delivered. `today` is YYYY-MM-DD.
+    pub fn is_overdue(&self, today: &str) -> bool {
+        !self.is_done() && self.due_date.as_deref().is_some_and(|due| due < today)
+    }
+
+    /// The organization's name, or the name typed in
+    pub fn vendor_label(&self) -> Option<String> {
+        self.vendor_org_name
+            .clone()
+            .or_else(|| self.vendor_name.clone())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct DeliverableFile {
+    pub id: RecordId,
+    pub deliverable: RecordId,
+    pub file_key: String,
+    pub file_name: String,
+    pub file_size: i64,
+    pub mime_type: String,
+    pub uploaded_by: RecordId,
+    pub uploader_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DeliverableFile {
+    pub fn size_label(&self) -> String {
+        size_label(self.file_size)
+    }
+}
+
+/// Where a production's deliverables stand, for its dashboard
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Rollup {
+    pub total: usize,
+    /// Delivered or approved
+    pub done: usize,
+    pub approved: usize,
+    pub overdue: usize,
+    /// The earliest due date still open, YYYY-MM-DD
+    pub next_due: Option<String>,
+}
+
+impl Rollup {
+    /// Share handed over, 0 to 100
+    pub fn percent(&self) -> usize {
+        (self.done * 100).checked_div(self.total).unwrap_or(0)
+    }
+
+    /// "3 of 8 delivered, 1 overdue"
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{} of {} delivered", self.done, self.total);
+        if self.overdue > 0 {
+            summary.push_str(&format!(", {} overdue", self.overdue));
+        }
+        summary
+    }
+}
+
+/// Roll a checklist up. `today` is YYYY-MM-DD.
+pub fn rollup(deliverables: &[Deliverable], today: &str) -> Rollup {
+    Rollup {
+        total: deliverables.len(),
+        done: deliverables.iter().filter(|d| d.is_done()).count(),
+        approved: deliverables
+            .iter()
+            .filter(|d| d.status == "approved")
+            .count(),
+        overdue: deliverables.iter().filter(|d| d.is_overdue(today)).count(),
+        next_due: deliverables
+            .iter()
+            .filter(|d| !d.is_done())
+            .filter_map(|d| d.due_date.clone())
+            .min(),
+    }
+}
+
+/// Delete stored attachments in the background, logging rather than failing
+/// when storage is unavailable
+pub fn delete_stored_files(keys: Vec<String>) {
+    if keys.is_empty() {
+        return;
+    }
+    crate::db::spawn(async move {
+        let s3 = match s3() {
+            Ok(s3) => s3,
+            Err(e) => {
+                error!(
+                    "S3 unavailable, {} deliverable files not deleted: {}",
+                    keys.len(),
+                    e
+                );
+                return;
+            }
+        };
+        for key in keys {
+            if let Err(e) = s3.delete_file(&key).await {
+                error!("Failed to delete deliverable file {}: {}", key, e);
+            }
+        }
+    });
+}
+
+const DELIVERABLE_FIELDS: &str = "id, kind, title, specs, due_date, vendor,
+    vendor.name AS vendor_org_name, vendor.slug AS vendor_slug, vendor_name, status, updated_at";
+
+const FILE_FIELDS: &str = "id, deliverable, file_key, file_name, file_size, mime_type,
+    uploaded_by, guest_name ?? uploaded_by.name ?? uploaded_by.username AS uploader_name,
+    created_at";
+
+pub struct DeliverableModel;
+
+impl DeliverableModel {
+    /// A production's checklist, soonest due first with undated ones last
+    pub async fn for_production(production: &RecordId) -> Result<Vec<Deliverable>, Error> {
+        let mut deliverables: Vec<Deliverable> = DB
+            .query(format!(
+                "SELECT {} FROM deliverable WHERE production = $production",
+                DELIVERABLE_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        deliverables.sort_by(|a, b| {
+            (a.due_date.is_none(), &a.due_date, &a.title).cmp(&(
+                b.due_date.is_none(),
+                &b.due_date,
+                &b.title,
+            ))
+        });
+        Ok(deliverables)
+    }
+
+    pub async fn get(production: &RecordId, deliverable_id: &str) -> Result<Deliverable, Error> {
+        let deliverable: Option<Deliverable> = DB
+            .query(format!(
+                "SELECT {} FROM $id WHERE production = $production",
+                DELIVERABLE_FIELDS
+            ))
+            .bind(("id", RecordId::new("deliverable", deliverable_id)))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        deliverable.ok_or(Error::NotFound)
+    }
+
+    pub async fn create(
+        production: &RecordId,
+        data: &DeliverableData,
+        vendor: Option<&RecordId>,
+        created_by: &RecordId,
+    ) -> Result<RecordId, Error> {
+        let id: Option<RecordId> = DB
+            .query(
+                "CREATE deliverable SET production = $production, kind = $kind, title = $title,
+                    specs = $specs, due_date = $due_date, vendor = $vendor,
+                    vendor_name = $vendor_name, created_by = $created_by
+                 RETURN VALUE id",
+            )
+            .bind(("production", production.clone()))
+            .bind(("kind", data.kind.clone()))
+            .bind(("title", data.title.clone()))
+            .bind(("specs", data.specs.clone()))
+            .bind(("due_date", data.due_date.clone()))
+            .bind(("vendor", vendor.cloned()))
+            .bind((
+                "vendor_name",
+                if vendor.is_some() {
+                    None
+                } else {
+                    data.vendor_name.clone()
+                },
+            ))
+            .bind(("created_by", created_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to add deliverable: {}", e)))?
+            .take(0)?;
+        let id = id.ok_or_else(|| Error::Internal("Deliverable was not created".to_string()))?;
+        debug!(
+            "Added deliverable {} to {}",
+            id.display(),
+            production.display()
+        );
+        Ok(id)
+    }
+
+    pub async fn update(
+        production: &RecordId,
+        deliverable_id: &str,
+        data: &DeliverableData,
+        vendor: Option<&RecordId>,
+    ) -> Result<(), Error> {
+        DB.query(
+            "UPDATE $id SET kind = $kind, title = $title, specs = $specs, due_date = $due_date,
+                vendor = $vendor, vendor_name = $vendor_name
+             WHERE production = $production",
+        )
+        .bind(("id", RecordId::new("deliverable", deliverable_id)))
+        .bind(("production", production.clone()))
+        .bind(("kind", data.kind.clone()))
+        .bind(("title", data.title.clone()))
+        .bind(("specs", data.specs.clone()))
+        .bind(("due_date", data.due_date.clone()))
+        .bind(("vendor", vendor.cloned()))
+        .bind((
+            "vendor_name",
+            if vendor.is_some() {
+                None
+            } else {
+                data.vendor_name.clone()
+            },
+        ))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update deliverable: {}", e)))?
+        .check()?;
+        Ok(())
+    }
+
+    pub async fn set_status(
+        production: &RecordId,
+        deliverable_id: &str,
+        status: &str,
+    ) -> Result<(), Error> {
+        if !STATUSES.iter().any(|(key, _)| *key == status) {
+            return Err(Error::Validation("Choose a status".to_string()));
+        }
+        DB.query("UPDATE $id SET status = $status WHERE production = $production")
+            .bind(("id", RecordId::new("deliverable", deliverable_id)))
+            .bind(("production", production.clone()))
+            .bind(("status", status.to_string()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update deliverable: {}", e)))?
+            .check()?;
+        Ok(())
+    }
+
+    /// Delete a deliverable and its attachments
+    pub async fn delete(production: &RecordId, deliverable_id: &str) -> Result<(), Error> {
+        let keys: Vec<String> = DB
+            .query(
+                "LET $keys = SELECT VALUE file_key FROM deliverable_file
+                    WHERE deliverable = $id AND production = $production;
+                 DELETE deliverable_file WHERE deliverable = $id AND production = $production;
+                 DELETE $id WHERE production = $production;
+                 RETURN $keys;",
+            )
+            .bind(("id", RecordId::new("deliverable", deliverable_id)))
+            .bind(("production", production.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete deliverable: {}", e)))?
+            .take(3)?;
+        delete_stored_files(keys);
+        Ok(())
+    }
+
+    /// Every attachment on a production's checklist, oldest first
+    pub async fn files_for_production(
+        production: &RecordId,
+    ) -> Result<Vec<DeliverableFile>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM deliverable_file WHERE production = $production
+                 ORDER BY created_at ASC",
+                FILE_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Store an attachment and record it on the deliverable. `guest_name` is
+    /// set for files uploaded through a guest link, which are recorded
+    /// against the person who issued it.
+    pub async fn add_file(
+        production: &RecordId,
+        deliverable: &Deliverable,
+        file_name: &str,
+        data: bytes::Bytes,
+        mime_type: &str,
+        uploaded_by: &RecordId,
+        guest_name: Option<&str>,
+    ) -> Result<(), Error> {
+        let key = file_key(
+            &production.key_string(),
+            &deliverable.id.key_string(),
+            &Ulid::new().to_string(),
+            file_name,
+        );
+        let file_size = data.len() as i64;
+        s3()?.upload_file(&key, data, mime_type).await?;
+
+        let result = DB
+            .query(
+                "CREATE deliverable_file SET deliverable = $deliverable, production = $production,
+                    file_key = $key, file_name = $file_name, file_size = $file_size,
+                    mime_type = $mime_type, uploaded_by = $uploaded_by, guest_name = $guest_name;
+                 UPDATE $deliverable SET updated_at = time::now();",
+            )
+            .bind(("deliverable", deliverable.id.clone()))
+            .bind(("production", production.clone()))
+            .bind(("key", key.clone()))
+            .bind(("file_name", safe_file_name(file_name)))
+            .bind(("file_size", file_size))
+            .bind(("mime_type", mime_type.to_string()))
+            .bind(("uploaded_by", uploaded_by.clone()))
+            .bind(("guest_name", guest_name.map(str::to_string)))
+            .await
+            .and_then(|response| response.check());
+        if let Err(e) = result {
+            delete_stored_files(vec![key]);
+            return Err(Error::Database(format!("Failed to attach file: {}", e)));
+        }
+        debug!("Attached {} to {}", key, deliverable.id.display());
+        Ok(())
+    }
+
+    pub async fn get_file(production: &RecordId, file_id: &str) -> Result<DeliverableFile, Error> {
+        let file: Option<DeliverableFile> = DB
+            .query(format!(
+                "SELECT {} FROM $id WHERE production = $production",
+                FILE_FIELDS
+            ))
+            .bind(("id", RecordId::new("deliverable_file", file_id)))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        file.ok_or(Error::NotFound)
+    }
+
+    pub async fn remove_file(file: &DeliverableFile) -> Result<(), Error> {
+        DB.query("DELETE $id")
+            .bind(("id", file.id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to remove file: {}", e)))?
+            .check()?;
+        delete_stored_files(vec![file.file_key.clone()]);
+        Ok(())
+    }
+
+    /// Where a production's deliverables stand. `today` is YYYY-MM-DD.
+    pub async fn rollup(production: &RecordId, today: &str) -> Result<Rollup, Error> {
+        Ok(rollup(&Self::for_production(production).await?, today))
+    }
+
+    /// Delete a production's checklist and its attachments
+    pub async fn delete_for_production(production: &RecordId) -> Result<(), Error> {
+        let keys: Vec<String> = DB
+            .query(
+                "LET $keys = SELECT VALUE file_key FROM deliverable_file WHERE production = $production;
+                 DELETE deliverable_file WHERE production = $production;
+                 DELETE deliverable WHERE production = $production;
+                 RETURN $keys;",
+            )
+            .bind(("production", production.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete deliverables: {}", e)))?
+            .take(3)?;
+        delete_stored_files(keys);
+        Ok(())
+    }
+}
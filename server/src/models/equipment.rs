@@ -4,7 +4,12 @@ use surrealdb::types::{RecordId, SurrealValue};
 use tracing::{debug, error};
 use uuid::Uuid;
 
-use crate::{db::DB, error::Error};
+use crate::{
+    db::DB,
+    error::Error,
+    models::production_gear::{self, ProductionGearModel},
+    record_id_ext::RecordIdExt,
+};
 
 // ============================
 // Data Structures
@@ -35,6 +40,10 @@ pub struct Equipment {
     pub description: Option<String>,
     pub purchase_date: Option<DateTime<Utc>>,
     pub purchase_price: Option<f64>,
+    /// What it would cost to replace, for insurance
+    #[serde(default)]
+    #[surreal(default)]
+    pub replacement_value: Option<f64>,
     pub condition: EquipmentCondition,
     pub notes: Option<String>,
     pub qr_code: Option<String>,
@@ -45,10 +54,42 @@ pub struct Equipment {
     pub parent_kit: Option<RecordId>,
     pub is_available: bool,
     pub current_location: Option<String>,
+    /// Hour meter / usage counter
+    #[serde(default)]
+    #[surreal(default)]
+    pub usage_hours: f64,
+    #[serde(default)]
+    #[surreal(default)]
+    pub service_interval_hours: Option<f64>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub service_interval_days: Option<i64>,
+    /// "YYYY-MM-DD"
+    #[serde(default)]
+    #[surreal(default)]
+    pub last_serviced_on: Option<String>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub hours_at_last_service: Option<f64>,
+    /// Can't be checked out while set
+    #[serde(default)]
+    #[surreal(default)]
+    pub out_of_service: bool,
+    #[serde(default)]
+    #[surreal(default)]
+    pub service_reminder_sent_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Equipment {
+    /// Whether it can be checked out now: it's not out already and not
+    /// pulled from service
+    pub fn can_check_out(&self) -> bool {
+        self.is_available && !self.out_of_service
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, SurrealValue, PartialEq)]
 pub struct EquipmentKit {
     pub id: RecordId,
@@ -103,6 +144,7 @@ pub struct CreateEquipmentData {
     pub description: Option<String>,
     pub purchase_date: Option<DateTime<Utc>>,
     pub purchase_price: Option<f64>,
+    pub replacement_value: Option<f64>,
     pub condition: String,
     pub notes: Option<String>,
     pub owner_type: String,
@@ -123,6 +165,7 @@ pub struct UpdateEquipmentData {
     pub description: Option<String>,
     pub purchase_date: Option<DateTime<Utc>>,
     pub purchase_price: Option<f64>,
+    pub replacement_value: Option<f64>,
     pub condition: String,
     pub notes: Option<String>,
     pub current_location: Option<String>,
@@ -194,6 +237,7 @@ impl EquipmentModel {
                 description: $description,
                 purchase_date: IF $purchase_date THEN <datetime>$purchase_date ELSE NONE END,
                 purchase_price: $purchase_price,
+                replacement_value: $replacement_value,
                 condition: type::record('equipment_condition', $condition),
                 notes: $notes,
                 qr_code: $qr_code,
@@ -222,6 +266,7 @@ impl EquipmentModel {
                 data.purchase_date.map(|dt| dt.to_rfc3339()),
             ))
             .bind(("purchase_price", data.purchase_price))
+            .bind(("replacement_value", data.replacement_value))
             .bind(("condition", data.condition.clone()))
             .bind(("notes", data.notes.clone()))
             .bind(("qr_code", qr_code.clone()))
@@ -282,6 +327,7 @@ impl EquipmentModel {
                 description = $description,
                 purchase_date = IF $purchase_date THEN <datetime>$purchase_date ELSE NONE END,
                 purchase_price = $purchase_price,
+                replacement_value = $replacement_value,
                 condition = type::record('equipment_condition', $condition),
                 notes = $notes,
                 current_location = $current_location,
@@ -303,6 +349,7 @@ impl EquipmentModel {
                 data.purchase_date.map(|dt| dt.to_rfc3339()),
             ))
             .bind(("purchase_price", data.purchase_price))
+            .bind(("replacement_value", data.replacement_value))
             .bind(("condition", data.condition.clone()))
             .bind(("notes", data.notes.clone()))
             .bind(("current_location", data.current_location.clone()))
@@ -332,6 +379,9 @@ impl EquipmentModel {
         }
 
         let query = r#"
+            DELETE equipment_service WHERE equipment = type::record('equipment', $id);
+            DELETE production_gear WHERE equipment = type::record('equipment', $id);
+            UPDATE gear_quote SET equipment -= type::record('equipment', $id) WHERE equipment CONTAINS type::record('equipment', $id);
             DELETE type::record('equipment', $id);
         "#;
 
@@ -631,6 +681,22 @@ impl EquipmentModel {
         Ok(kits)
     }
 
+    /// The equipment records a checkout covers: the item, or the kit's items
+    pub async fn booking_items(
+        equipment_id: Option<&str>,
+        kit_id: Option<&str>,
+    ) -> Result<Vec<RecordId>, Error> {
+        Ok(match (equipment_id, kit_id) {
+            (Some(eq_id), _) => vec![RecordId::new("equipment", eq_id)],
+            (None, Some(kit_id)) => Self::get_kit_items(kit_id)
+                .await?
+                .into_iter()
+                .map(|item| item.id)
+                .collect(),
+            (None, None) => Vec::new(),
+        })
+    }
+
     // Rental Operations
 
     pub async fn checkout_equipment(data: CheckoutData) -> Result<EquipmentRental, Error> {
@@ -644,6 +710,12 @@ impl EquipmentModel {
                     "Equipment is not available for checkout".to_string(),
                 ));
             }
+            if equipment.out_of_service {
+                return Err(Error::Validation(format!(
+                    "{} is out of service",
+                    equipment.name
+                )));
+            }
         }
 
         if let Some(ref kit_id) = data.kit_id {
@@ -653,6 +725,44 @@ impl EquipmentModel {
                     "Kit is not available for checkout".to_string(),
                 ));
             }
+            let out: Vec<String> = Self::get_kit_items(kit_id)
+                .await?
+                .into_iter()
+                .filter(|item| item.out_of_service)
+                .map(|item| item.name)
+                .collect();
+            if !out.is_empty() {
+                return Err(Error::Validation(format!(
+                    "The kit can't go out while {} is out of service",
+                    out.join(", ")
+                )));
+            }
+        }
+
+        // Gear booked by a production stays with it for those dates
+        let items =
+            Self::booking_items(data.equipment_id.as_deref(), data.kit_id.as_deref()).await?;
+        let renter = match (&data.renter_person, &data.renter_organization) {
+            (Some(person), _) => RecordId::new("person", person.as_str()).to_raw_string(),
+            (None, Some(org)) => RecordId::new("organization", org.as_str()).to_raw_string(),
+            (None, None) => String::new(),
+        };
+        let today = Utc::now().date_naive();
+        let start = today.format("%Y-%m-%d").to_string();
+        let end = data
+            .expected_return_date
+            .map(|d| d.date_naive().max(today))
+            .unwrap_or(today)
+            .format("%Y-%m-%d")
+            .to_string();
+        let conflicts =
+            ProductionGearModel::checkout_conflicts(&items, &start, &end, &renter).await?;
+        if !conflicts.is_empty() {
+            let conflicts: Vec<_> = conflicts.iter().collect();
+            return Err(Error::Validation(format!(
+                "{} Only crew on that production can check it out for those dates.",
+                production_gear::conflict_message(&conflicts)
+            )));
         }
 
         let query = r#"
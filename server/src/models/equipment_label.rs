@@ -0,0 +1,224 @@
+//! Equipment QR labels and scanning.
+//!
+//! Every equipment record and kit has a code ("EQ-<uuid>", "KIT-<uuid>").
+//! Labels print it as a QR code linking to its scan page, with the code
+//! underneath for when there's no camera handy. Scanning opens the page,
+//! which checks the item out to whoever scanned it or back in, in one tap.
+
+use qrcode::QrCode;
+
+use crate::{
+    error::Error,
+    models::production_gear::{self, GearBooking},
+    pdf::{Font, PageSize, PdfDocument, text_width},
+};
+
+/// Labels across a sheet
+pub const LABEL_COLUMNS: usize = 3;
+
+/// Labels down a sheet
+pub const LABEL_ROWS: usize = 5;
+
+/// Sheet margin in points
+const SHEET_MARGIN: f32 = 36.0;
+
+/// Printed QR code size in points (1.33in)
+const QR_SIZE: f32 = 96.0;
+
+/// Blank modules around the code, as scanners expect
+const QUIET_ZONE: usize = 2;
+
+/// What a scanned code points at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScannedCode {
+    Equipment(String),
+    Kit(String),
+}
+
+impl ScannedCode {
+    /// The code as stored on the record
+    pub fn code(&self) -> &str {
+        match self {
+            ScannedCode::Equipment(code) | ScannedCode::Kit(code) => code,
+        }
+    }
+}
+
+/// Read a code from what was scanned or typed: the code itself, in any case,
+/// or a scan page link
+pub fn parse_scan(input: &str) -> Option<ScannedCode> {
+    let input = input.trim();
+    let code = match input.find("/equipment/scan/") {
+        Some(at) => input[at + "/equipment/scan/".len()..]
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or(""),
+        None => input,
+    };
+    let (prefix, id) = code.split_once('-')?;
+    let id = uuid::Uuid::parse_str(id).ok()?.to_string();
+    match prefix.to_ascii_uppercase().as_str() {
+        "EQ" => Some(ScannedCode::Equipment(format!("EQ-{}", id))),
+        "KIT" => Some(ScannedCode::Kit(format!("KIT-{}", id))),
+        _ => None,
+    }
+}
+
+/// Path of the scan page a label links to
+pub fn scan_path(code: &str) -> String {
+    format!("/equipment/scan/{}", code)
+}
+
+/// A QR code as rows of dark (true) and light modules, quiet zone included
+pub fn qr_modules(data: &str) -> Result<Vec<Vec<bool>>, Error> {
+    let code = QrCode::new(data.as_bytes())
+        .map_err(|e| Error::Internal(format!("Failed to encode QR code: {}", e)))?;
+    let width = code.width();
+    let colors = code.to_colors();
+    let size = width + QUIET_ZONE * 2;
+    let mut rows = vec![vec![false; size]; size];
+    for (i, color) in colors.iter().enumerate() {
+        rows[i / width + QUIET_ZONE][i % width + QUIET_ZONE] = *color == qrcode::Color::Dark;
+    }
+    Ok(rows)
+}
+
+/// A QR code as a PNG, `scale` pixels per module
+pub fn qr_png(data: &str, scale: u32) -> Result<Vec<u8>, Error> {
+    let rows = qr_modules(data)?;
+    let size = rows.len() as u32 * scale;
+    let mut image = image::GrayImage::from_pixel(size, size, image::Luma([255u8]));
+    for (y, row) in rows.iter().enumerate() {
+        for (x, dark) in row.iter().enumerate() {
+            if !dark {
+                continue;
+            }
+            for dy in 0..scale {
+                for dx in 0..scale {
+                    image.put_pixel(
+                        x as u32 * scale + dx,
+                        y as u32 * scale + dy,
+                        image::Luma([0u8]),
+                    );
+                }
+            }
+        }
+    }
+    let mut buf = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::ImageLuma8(image)
+        .write_to(&mut buf, image::ImageFormat::Png)
+        .map_err(|e| Error::Internal(format!("Failed to encode QR image: {}", e)))?;
+    Ok(buf.into_inner())
+}
+
+/// One label on the sheet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub name: String,
+    pub code: String,
+    /// Serial number or kit category, printed small under the name
+    pub detail: Option<String>,
+}
+
+/// Shorten `text` with an ellipsis to fit `width` points
+fn fit(text: &str, width: f32, size: f32, font: Font) -> String {
+    if text_width(text, size, font) <= width {
+        return text.to_string();
+    }
+    let mut fitted: String = text.to_string();
+    while !fitted.is_empty() && text_width(&format!("{}...", fitted), size, font) > width {
+        fitted.pop();
+    }
+    format!("{}...", fitted.trim_end())
+}
+
+/// A sheet of labels, left to right and top to bottom, with light cut lines
+/// between them. Each QR code links to `app_url` plus the item's scan page.
+pub fn labels_pdf(labels: &[Label], app_url: &str) -> Result<Vec<u8>, Error> {
+    let size = PageSize::LETTER;
+    let mut doc = PdfDocument::new(size);
+    let cell_width = (size.width - SHEET_MARGIN * 2.0) / LABEL_COLUMNS as f32;
+    let cell_height = (size.height - SHEET_MARGIN * 2.0) / LABEL_ROWS as f32;
+    let per_page = LABEL_COLUMNS * LABEL_ROWS;
+
+    for (i, label) in labels.iter().enumerate() {
+        if i > 0 && i % per_page == 0 {
+            doc.new_page();
+        }
+        let slot = i % per_page;
+        let left = SHEET_MARGIN + (slot % LABEL_COLUMNS) as f32 * cell_width;
+        let top = size.height - SHEET_MARGIN - (slot / LABEL_COLUMNS) as f32 * cell_height;
+        doc.rect(left, top - cell_height, cell_width, cell_height, 0.25);
+
+        let rows = qr_modules(&format!("{}{}", app_url, scan_path(&label.code)))?;
+        let module = QR_SIZE / rows.len() as f32;
+        let qr_left = left + (cell_width - QR_SIZE) / 2.0;
+        let qr_top = top - 8.0;
+        for (y, row) in rows.iter().enumerate() {
+            for (x, dark) in row.iter().enumerate() {
+                if *dark {
+                    doc.fill_rect(
+                        qr_left + x as f32 * module,
+                        qr_top - (y + 1) as f32 * module,
+                        module,
+                        module,
+                        0.0,
+                    );
+                }
+            }
+        }
+
+        let max_width = cell_width - 12.0;
+        let mut baseline = qr_top - QR_SIZE - 11.0;
+        let lines = [
+            Some((
+                fit(&label.name, max_width, 9.0, Font::Bold),
+                9.0,
+                Font::Bold,
+            )),
+            label
+                .detail
+                .as_deref()
+                .map(|d| (fit(d, max_width, 7.0, Font::Regular), 7.0, Font::Regular)),
+            Some((label.code.clone(), 6.0, Font::Regular)),
+        ];
+        for (text, font_size, font) in lines.into_iter().flatten() {
+            let x = left + (cell_width - text_width(&text, font_size, font)) / 2.0;
+            doc.text(x, baseline, font_size, font, &text);
+            baseline -= font_size + 2.0;
+        }
+    }
+
+    Ok(doc.finish())
+}
+
+/// The WhatsApp bot's answer to `/sh scan`: what the item is, whether it's
+/// out, and the link to check it out or in
+pub fn scan_reply(name: &str, out_to: Option<&str>, available: bool, url: &str) -> String {
+    let status = match (available, out_to) {
+        (true, _) => "Available".to_string(),
+        (false, Some(renter)) => format!("Checked out to {}", renter),
+        (false, None) => "Checked out".to_string(),
+    };
+    let action = if available {
+        "check it out"
+    } else {
+        "check it in"
+    };
+    format!("*{}*\n{}\n\nTap to {}: {}", name, status, action, url)
+}
+
+/// The WhatsApp bot's answer to a successful `/sh checkout`, with a heads-up
+/// about production bookings coming up after it's due back
+pub fn checkout_reply(name: &str, until: Option<&str>, later: &[&GearBooking]) -> String {
+    let mut reply = match until {
+        Some(date) => format!("*{}* is checked out to you until {}.", name, date),
+        None => format!("*{}* is checked out to you.", name),
+    };
+    if !later.is_empty() {
+        reply.push_str("\n\nHeads up: ");
+        reply.push_str(&production_gear::conflict_message(later));
+        reply.push_str(" Bring it back before then.");
+    }
+    reply
+}
@@ -0,0 +1,542 @@
+//! Equipment maintenance: service history, the usage hour counter and
+//! "due for service" flags.
+//!
+//! Owners log services, repairs, inspections and hour meter readings against
+//! an item. An item can have a service interval in hours of use, in days, or
+//! both; whichever comes first makes it due. The `equipment_service_reminders`
+//! scheduled task tells the owner (or the owning organization's admins) once
+//! an item comes due, and again only after it's been serviced and come due
+//! again. Gear switched out of service can't be checked out.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, info, warn};
+
+use crate::{
+    db::DB,
+    error::Error,
+    models::{
+        equipment::Equipment, notification::NotificationModel, organization::OrganizationModel,
+    },
+    record_id_ext::RecordIdExt,
+};
+
+/// Kinds of entry in an item's history
+pub const SERVICE_KINDS: [(&str, &str); 4] = [
+    ("service", "Service"),
+    ("repair", "Repair"),
+    ("inspection", "Inspection"),
+    ("usage", "Hour meter reading"),
+];
+
+/// Longest description of a service
+pub const MAX_DESCRIPTION_CHARS: usize = 1000;
+
+/// Longest vendor name
+pub const MAX_VENDOR_CHARS: usize = 120;
+
+/// How close to the interval an item counts as due soon: the last tenth of
+/// its hours, or the last week of its days (a quarter of short intervals)
+const DUE_SOON_HOURS_FRACTION: f64 = 0.1;
+const DUE_SOON_DAYS: i64 = 7;
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ServiceEvent {
+    pub id: RecordId,
+    pub equipment: RecordId,
+    pub kind: String,
+    /// "YYYY-MM-DD"
+    pub performed_on: String,
+    pub description: String,
+    /// Hour meter reading at the time
+    pub usage_hours: Option<f64>,
+    pub cost: Option<f64>,
+    pub vendor: Option<String>,
+    pub logged_by: RecordId,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ServiceEvent {
+    pub fn kind_label(&self) -> &'static str {
+        kind_label(&self.kind)
+    }
+
+    /// Whether it resets the service interval; hour readings don't
+    pub fn is_service(&self) -> bool {
+        is_service_kind(&self.kind)
+    }
+
+    /// The hour meter reading, if one was taken
+    pub fn hours_label(&self) -> Option<String> {
+        self.usage_hours.map(format_hours)
+    }
+}
+
+pub fn kind_label(kind: &str) -> &'static str {
+    SERVICE_KINDS
+        .iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, label)| *label)
+        .unwrap_or("Other")
+}
+
+fn is_service_kind(kind: &str) -> bool {
+    matches!(kind, "service" | "repair" | "inspection")
+}
+
+/// A validated history entry from the equipment page form
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceEventData {
+    pub kind: String,
+    pub performed_on: String,
+    pub description: String,
+    pub usage_hours: Option<f64>,
+    pub cost: Option<f64>,
+    pub vendor: Option<String>,
+}
+
+fn parse_amount(value: &str, what: &str) -> Result<Option<f64>, Error> {
+    let value = value.trim().trim_start_matches('$').replace(',', "");
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<f64>() {
+        Ok(n) if n.is_finite() && n >= 0.0 => Ok(Some(n)),
+        _ => Err(Error::Validation(format!(
+            "{} should be a number, like 120.5",
+            what
+        ))),
+    }
+}
+
+/// Check and normalize a history entry. Empty dates mean `today`; hour meter
+/// readings need the reading and nothing else.
+pub fn clean_service_event(
+    kind: &str,
+    performed_on: &str,
+    description: &str,
+    usage_hours: &str,
+    cost: &str,
+    vendor: &str,
+    today: NaiveDate,
+) -> Result<ServiceEventData, Error> {
+    let kind = kind.trim();
+    if !SERVICE_KINDS.iter().any(|(k, _)| *k == kind) {
+        return Err(Error::Validation(
+            "Pick what kind of entry this is".to_string(),
+        ));
+    }
+
+    let performed_on = match performed_on.trim() {
+        "" => today,
+        date => NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+            Error::Validation(format!("\"{}\" isn't a date (use YYYY-MM-DD)", date))
+        })?,
+    };
+    if performed_on > today {
+        return Err(Error::Validation(
+            "Log services once they've been done".to_string(),
+        ));
+    }
+
+    let usage_hours = parse_amount(usage_hours, "The hour meter reading")?;
+    let cost = parse_amount(cost, "The cost")?;
+    let description = description.trim();
+    if kind == "usage" {
+        if usage_hours.is_none() {
+            return Err(Error::Validation(
+                "Enter the hour meter reading".to_string(),
+            ));
+        }
+    } else if description.is_empty() {
+        return Err(Error::Validation(
+            "Say what was done, like \"Cleaned sensor, replaced fan filter\"".to_string(),
+        ));
+    }
+    if description.chars().count() > MAX_DESCRIPTION_CHARS {
+        return Err(Error::Validation(format!(
+            "Keep the description under {} characters",
+            MAX_DESCRIPTION_CHARS
+        )));
+    }
+    let vendor = vendor.trim();
+    if vendor.chars().count() > MAX_VENDOR_CHARS {
+        return Err(Error::Validation(format!(
+            "Keep the vendor under {} characters",
+            MAX_VENDOR_CHARS
+        )));
+    }
+
+    Ok(ServiceEventData {
+        kind: kind.to_string(),
+        performed_on: performed_on.format("%Y-%m-%d").to_string(),
+        description: if description.is_empty() {
+            "Hour meter reading".to_string()
+        } else {
+            description.to_string()
+        },
+        usage_hours,
+        cost,
+        vendor: (!vendor.is_empty()).then(|| vendor.to_string()),
+    })
+}
+
+/// Check the service interval fields; empty means no interval
+pub fn clean_intervals(hours: &str, days: &str) -> Result<(Option<f64>, Option<i64>), Error> {
+    let hours = match parse_amount(hours, "The hour interval")? {
+        Some(h) if h <= 0.0 => {
+            return Err(Error::Validation(
+                "The hour interval should be more than zero".to_string(),
+            ));
+        }
+        hours => hours,
+    };
+    let days = match days.trim() {
+        "" => None,
+        value => match value.parse::<i64>() {
+            Ok(d) if (1..=3650).contains(&d) => Some(d),
+            _ => {
+                return Err(Error::Validation(
+                    "The day interval should be a whole number of days, up to ten years"
+                        .to_string(),
+                ));
+            }
+        },
+    };
+    Ok((hours, days))
+}
+
+/// Where an item stands against its service interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceStatus {
+    /// No interval set
+    Unscheduled,
+    Ok,
+    DueSoon,
+    Due,
+}
+
+impl ServiceStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ServiceStatus::Unscheduled => "No schedule",
+            ServiceStatus::Ok => "Up to date",
+            ServiceStatus::DueSoon => "Service due soon",
+            ServiceStatus::Due => "Due for service",
+        }
+    }
+
+    /// For `data-status` in templates
+    pub fn key(&self) -> &'static str {
+        match self {
+            ServiceStatus::Unscheduled => "unscheduled",
+            ServiceStatus::Ok => "ok",
+            ServiceStatus::DueSoon => "due-soon",
+            ServiceStatus::Due => "due",
+        }
+    }
+}
+
+/// Service status from an item's counters. Hours count from the reading at
+/// the last service (or zero); days from the last service, or from when the
+/// item was added if it's never been serviced.
+pub fn service_status(
+    usage_hours: f64,
+    interval_hours: Option<f64>,
+    hours_at_last_service: Option<f64>,
+    interval_days: Option<i64>,
+    last_serviced_on: Option<NaiveDate>,
+    added_on: NaiveDate,
+    today: NaiveDate,
+) -> ServiceStatus {
+    if interval_hours.is_none() && interval_days.is_none() {
+        return ServiceStatus::Unscheduled;
+    }
+    let mut status = ServiceStatus::Ok;
+
+    if let Some(interval) = interval_hours {
+        let used = usage_hours - hours_at_last_service.unwrap_or(0.0);
+        if used >= interval {
+            return ServiceStatus::Due;
+        }
+        if used >= interval * (1.0 - DUE_SOON_HOURS_FRACTION) {
+            status = ServiceStatus::DueSoon;
+        }
+    }
+    if let Some(interval) = interval_days {
+        let since = last_serviced_on.unwrap_or(added_on);
+        let elapsed = (today - since).num_days();
+        if elapsed >= interval {
+            return ServiceStatus::Due;
+        }
+        if elapsed >= interval - DUE_SOON_DAYS.min(interval / 4).max(1) {
+            status = ServiceStatus::DueSoon;
+        }
+    }
+    status
+}
+
+/// Service status of an equipment record
+pub fn equipment_status(equipment: &Equipment, today: NaiveDate) -> ServiceStatus {
+    service_status(
+        equipment.usage_hours,
+        equipment.service_interval_hours,
+        equipment.hours_at_last_service,
+        equipment.service_interval_days,
+        equipment
+            .last_serviced_on
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok()),
+        equipment.created_at.date_naive(),
+        today,
+    )
+}
+
+/// What's left before the next service: "120 h or 14 days to go"
+pub fn remaining(equipment: &Equipment, today: NaiveDate) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(interval) = equipment.service_interval_hours {
+        let left =
+            interval - (equipment.usage_hours - equipment.hours_at_last_service.unwrap_or(0.0));
+        parts.push(format!("{} h", format_hours(left.max(0.0))));
+    }
+    if let Some(interval) = equipment.service_interval_days {
+        let since = equipment
+            .last_serviced_on
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .unwrap_or(equipment.created_at.date_naive());
+        let left = (interval - (today - since).num_days()).max(0);
+        parts.push(format!(
+            "{} {}",
+            left,
+            if left == 1 { "day" } else { "days" }
+        ));
+    }
+    (!parts.is_empty()).then(|| format!("{} to go", parts.join(" or ")))
+}
+
+/// The service interval: "Every 200 h or 180 days"
+pub fn interval_label(equipment: &Equipment) -> Option<String> {
+    let parts: Vec<String> = [
+        equipment
+            .service_interval_hours
+            .map(|h| format!("{} h", format_hours(h))),
+        equipment
+            .service_interval_days
+            .map(|d| format!("{} {}", d, if d == 1 { "day" } else { "days" })),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    (!parts.is_empty()).then(|| format!("Every {}", parts.join(" or ")))
+}
+
+/// The last service: "2026-05-01 at 340 h"
+pub fn last_service_label(equipment: &Equipment) -> Option<String> {
+    let date = equipment.last_serviced_on.as_deref()?;
+    Some(match equipment.hours_at_last_service {
+        Some(hours) => format!("{} at {} h", date, format_hours(hours)),
+        None => date.to_string(),
+    })
+}
+
+/// Hours without a trailing ".0"
+pub fn format_hours(hours: f64) -> String {
+    let rounded = (hours * 10.0).round() / 10.0;
+    if rounded.fract().abs() < f64::EPSILON {
+        format!("{}", rounded as i64)
+    } else {
+        format!("{:.1}", rounded)
+    }
+}
+
+pub struct EquipmentServiceModel;
+
+impl EquipmentServiceModel {
+    /// Add an entry to an item's history. Hour readings move the counter
+    /// forward; services, repairs and inspections also restart the interval
+    /// and clear the reminder.
+    pub async fn log(
+        equipment: &Equipment,
+        data: ServiceEventData,
+        logged_by: &RecordId,
+    ) -> Result<ServiceEvent, Error> {
+        if data
+            .usage_hours
+            .is_some_and(|reading| reading < equipment.usage_hours)
+            && !is_service_kind(&data.kind)
+        {
+            return Err(Error::Validation(format!(
+                "The hour meter already reads {} h",
+                format_hours(equipment.usage_hours)
+            )));
+        }
+
+        debug!("Logging {} on {}", data.kind, equipment.id.display());
+        let resets = is_service_kind(&data.kind);
+        let mut response = DB
+            .query(
+                "CREATE equipment_service SET equipment = $equipment, kind = $kind,
+                    performed_on = $performed_on, description = $description,
+                    usage_hours = $usage_hours, cost = $cost, vendor = $vendor,
+                    logged_by = $logged_by, created_at = time::now();
+                 LET $last = (SELECT VALUE last_serviced_on FROM ONLY $equipment);
+                 LET $latest = $resets AND ($last IS NONE OR $last <= $performed_on);
+                 UPDATE $equipment SET
+                    hours_at_last_service = IF $latest THEN ($usage_hours OR usage_hours) ELSE hours_at_last_service END,
+                    last_serviced_on = IF $latest THEN $performed_on ELSE last_serviced_on END,
+                    usage_hours = math::max([usage_hours, $usage_hours OR 0]),
+                    service_reminder_sent_at = IF $resets THEN NONE ELSE service_reminder_sent_at END;",
+            )
+            .bind(("equipment", equipment.id.clone()))
+            .bind(("kind", data.kind))
+            .bind(("performed_on", data.performed_on))
+            .bind(("description", data.description))
+            .bind(("usage_hours", data.usage_hours))
+            .bind(("cost", data.cost))
+            .bind(("vendor", data.vendor))
+            .bind(("logged_by", logged_by.clone()))
+            .bind(("resets", resets))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to log service: {}", e)))?;
+        let event: Option<ServiceEvent> = response.take(0)?;
+        event.ok_or_else(|| Error::Internal("Failed to log service".to_string()))
+    }
+
+    /// An item's history, newest first
+    pub async fn history(equipment: &RecordId) -> Result<Vec<ServiceEvent>, Error> {
+        Ok(DB
+            .query(
+                "SELECT * FROM equipment_service WHERE equipment = $equipment
+                 ORDER BY performed_on DESC, created_at DESC",
+            )
+            .bind(("equipment", equipment.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn delete(equipment: &RecordId, event_id: &str) -> Result<(), Error> {
+        DB.query("DELETE $id WHERE equipment = $equipment")
+            .bind(("id", RecordId::new("equipment_service", event_id)))
+            .bind(("equipment", equipment.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete service entry: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn set_intervals(
+        equipment: &RecordId,
+        hours: Option<f64>,
+        days: Option<i64>,
+    ) -> Result<(), Error> {
+        DB.query(
+            "UPDATE $equipment SET service_interval_hours = $hours,
+                service_interval_days = $days, service_reminder_sent_at = NONE",
+        )
+        .bind(("equipment", equipment.clone()))
+        .bind(("hours", hours))
+        .bind(("days", days))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to save service interval: {}", e)))?
+        .check()?;
+        Ok(())
+    }
+
+    pub async fn set_out_of_service(
+        equipment: &RecordId,
+        out_of_service: bool,
+    ) -> Result<(), Error> {
+        DB.query("UPDATE $equipment SET out_of_service = $out_of_service")
+            .bind(("equipment", equipment.clone()))
+            .bind(("out_of_service", out_of_service))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update service state: {}", e)))?
+            .check()?;
+        Ok(())
+    }
+
+    /// Who hears about an item coming due: its owner, or the owning
+    /// organization's owners and admins (every member if it has none)
+    async fn recipients(equipment: &Equipment) -> Vec<RecordId> {
+        if let Some(person) = equipment.owner_person.as_ref() {
+            return vec![person.clone()];
+        }
+        let Some(org) = equipment.owner_organization.as_ref() else {
+            return Vec::new();
+        };
+        let members: Vec<_> = OrganizationModel::new()
+            .get_members(&org.to_raw_string())
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|m| m.invitation_status == "accepted")
+            .collect();
+        let admins: Vec<RecordId> = members
+            .iter()
+            .filter(|m| m.role == "owner" || m.role == "admin")
+            .map(|m| m.person_id.clone())
+            .collect();
+        if admins.is_empty() {
+            members.into_iter().map(|m| m.person_id).collect()
+        } else {
+            admins
+        }
+    }
+
+    /// Notify owners of gear that has come due since it was last serviced.
+    /// Run by the `equipment_service_reminders` scheduled task.
+    pub async fn send_reminders() -> Result<(), Error> {
+        let candidates: Vec<Equipment> = DB
+            .query(
+                "SELECT * FROM equipment
+                 WHERE (service_interval_hours IS NOT NONE OR service_interval_days IS NOT NONE)
+                    AND service_reminder_sent_at IS NONE
+                 FETCH category, condition",
+            )
+            .await?
+            .take(0)?;
+        let today = Utc::now().date_naive();
+        let notifications = NotificationModel::new();
+        let mut sent = 0;
+        for equipment in candidates {
+            if equipment_status(&equipment, today) != ServiceStatus::Due {
+                continue;
+            }
+            let link = format!("/equipment/{}#maintenance", equipment.id.to_raw_string());
+            let message = format!(
+                "{} is due for service. Log the service on its page once it's done.",
+                equipment.name
+            );
+            for person in Self::recipients(&equipment).await {
+                if let Err(e) = notifications
+                    .create(
+                        &person.to_raw_string(),
+                        "equipment",
+                        "Equipment due for service",
+                        &message,
+                        Some(&link),
+                        Some(&equipment.id.to_raw_string()),
+                    )
+                    .await
+                {
+                    warn!(
+                        "Failed to send service reminder for {}: {}",
+                        equipment.id.display(),
+                        e
+                    );
+                }
+            }
+            DB.query("UPDATE $equipment SET service_reminder_sent_at = time::now()")
+                .bind(("equipment", equipment.id.clone()))
+                .await?
+                .check()?;
+            sent += 1;
+        }
+        if sent > 0 {
+            info!("Sent service reminders for {} equipment items", sent);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+//! Feedback and bug reports
+//!
+//! Sent from the feedback widget on every page. Each records the page it was
+//! sent from, who sent it when signed in, and the IDs of the sender's last
+//! few requests (see `middleware::recent_requests`) so a bug report can be
+//! matched against the logs. Admins triage them under /admin/feedback, moving
+//! each from new to triaged, resolved or dismissed with an optional note, and
+//! can forward one to the configured webhook or issue tracker.
+
+use crate::{db::DB, error::Error, record_id_ext::RecordIdExt, response};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::info;
+
+pub const MAX_MESSAGE_CHARS: usize = 2000;
+pub const MAX_NOTE_CHARS: usize = 1000;
+const MAX_PAGE_URL_CHARS: usize = 500;
+const MAX_USER_AGENT_CHARS: usize = 300;
+
+/// Statuses in triage order, with their labels
+pub const STATUSES: &[(&str, &str)] = &[
+    ("new", "New"),
+    ("triaged", "Triaged"),
+    ("resolved", "Resolved"),
+    ("dismissed", "Dismissed"),
+];
+
+pub fn status_label(status: &str) -> &'static str {
+    STATUSES
+        .iter()
+        .find(|(value, _)| *value == status)
+        .map_or("New", |(_, label)| label)
+}
+
+pub fn parse_status(status: &str) -> Result<&'static str, Error> {
+    STATUSES
+        .iter()
+        .map(|(value, _)| *value)
+        .find(|value| *value == status.trim())
+        .ok_or_else(|| Error::Validation("Unknown feedback status".to_string()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedbackKind {
+    Feedback,
+    Bug,
+}
+
+impl FeedbackKind {
+    /// "feedback" or "bug"; the widget's older form sends neither
+    pub fn parse(kind: Option<&str>) -> Result<Self, Error> {
+        match kind.map(str::trim).unwrap_or_default() {
+            "" | "feedback" => Ok(Self::Feedback),
+            "bug" => Ok(Self::Bug),
+            _ => Err(Error::Validation("Unknown feedback type".to_string())),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Feedback => "feedback",
+            Self::Bug => "bug",
+        }
+    }
+}
+
+pub fn kind_label(kind: &str) -> &'static str {
+    if kind == "bug" {
+        "Bug report"
+    } else {
+        "Feedback"
+    }
+}
+
+/// What the widget sent, checked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub kind: FeedbackKind,
+    pub message: String,
+    pub page_url: String,
+}
+
+pub fn parse_submission(
+    kind: Option<&str>,
+    message: &str,
+    page_url: &str,
+) -> Result<Submission, Error> {
+    let kind = FeedbackKind::parse(kind)?;
+    let message = message.trim();
+    if message.is_empty() {
+        return Err(Error::Validation("Message is required".to_string()));
+    }
+    if message.chars().count() > MAX_MESSAGE_CHARS {
+        return Err(Error::Validation(format!(
+            "Message must be {} characters or less",
+            MAX_MESSAGE_CHARS
+        )));
+    }
+    Ok(Submission {
+        kind,
+        message: message.to_string(),
+        page_url: clean_page_url(page_url),
+    })
+}
+
+/// The page as a path on this site; anything else is kept as "/"
+fn clean_page_url(page_url: &str) -> String {
+    let page_url = page_url.trim();
+    if !response::is_local_path(page_url) {
+        return "/".to_string();
+    }
+    page_url.chars().take(MAX_PAGE_URL_CHARS).collect()
+}
+
+/// An admin's triage note; blank clears it
+pub fn parse_note(note: &str) -> Result<Option<String>, Error> {
+    let note = note.trim();
+    if note.chars().count() > MAX_NOTE_CHARS {
+        return Err(Error::Validation(format!(
+            "Keep the note under {} characters",
+            MAX_NOTE_CHARS
+        )));
+    }
+    Ok((!note.is_empty()).then(|| note.to_string()))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Feedback {
+    pub id: RecordId,
+    pub username: String,
+    /// "feedback" or "bug"
+    pub kind: String,
+    pub page_url: String,
+    pub message: String,
+    /// The sender's requests before sending, newest first
+    pub request_ids: Vec<String>,
+    pub user_agent: Option<String>,
+    pub status: String,
+    pub note: Option<String>,
+    pub triaged_by_name: Option<String>,
+    pub triaged_at: Option<DateTime<Utc>>,
+    pub forwarded_at: Option<DateTime<Utc>>,
+    pub issue_url: Option<String>,
+    pub forward_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct StatusCount {
+    status: String,
+    count: u64,
+}
+
+const FEEDBACK_FIELDS: &str = "id, username, kind, page_url, message, request_ids, user_agent,
+    status, note, triaged_by.name ?? triaged_by.username AS triaged_by_name, triaged_at,
+    forwarded_at, issue_url, forward_error, created_at";
+
+pub struct FeedbackModel;
+
+impl FeedbackModel {
+    /// Save a submission. `username` is "anonymous" for signed-out visitors.
+    pub async fn create(
+        submission: &Submission,
+        username: &str,
+        person: Option<RecordId>,
+        request_ids: Vec<String>,
+        user_agent: Option<&str>,
+    ) -> Result<RecordId, Error> {
+        let user_agent = user_agent
+            .map(|ua| ua.chars().take(MAX_USER_AGENT_CHARS).collect::<String>())
+            .filter(|ua| !ua.is_empty());
+        let id: Option<RecordId> = DB
+            .query(
+                "CREATE ONLY feedback SET username = $username, person = $person, kind = $kind,
+                    page_url = $page_url, message = $message, request_ids = $request_ids,
+                    user_agent = $user_agent
+                 RETURN VALUE id",
+            )
+            .bind(("username", username.to_string()))
+            .bind(("person", person))
+            .bind(("kind", submission.kind.as_str().to_string()))
+            .bind(("page_url", submission.page_url.clone()))
+            .bind(("message", submission.message.clone()))
+            .bind(("request_ids", request_ids))
+            .bind(("user_agent", user_agent))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to save feedback: {}", e)))?
+            .take(0)?;
+        id.ok_or_else(|| Error::Database("Feedback was not saved".to_string()))
+    }
+
+    /// Newest first, only those with `status` when given
+    pub async fn list(status: Option<&str>, limit: usize) -> Result<Vec<Feedback>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM feedback WHERE $status = NONE OR status = $status
+                 ORDER BY created_at DESC LIMIT $limit",
+                FEEDBACK_FIELDS
+            ))
+            .bind(("status", status.map(str::to_string)))
+            .bind(("limit", limit as i64))
+            .await?
+            .take(0)?)
+    }
+
+    /// How many there are of each status, in `STATUSES` order
+    pub async fn status_counts() -> Result<Vec<(&'static str, u64)>, Error> {
+        let counts: Vec<StatusCount> = DB
+            .query("SELECT status, count() AS count FROM feedback GROUP BY status")
+            .await?
+            .take(0)?;
+        Ok(STATUSES
+            .iter()
+            .map(|(status, _)| {
+                let count = counts
+                    .iter()
+                    .find(|c| c.status == *status)
+                    .map_or(0, |c| c.count);
+                (*status, count)
+            })
+            .collect())
+    }
+
+    pub async fn get(id: &str) -> Result<Feedback, Error> {
+        let feedback: Option<Feedback> = DB
+            .query(format!("SELECT {} FROM ONLY $id", FEEDBACK_FIELDS))
+            .bind(("id", RecordId::new("feedback", id)))
+            .await?
+            .take(0)?;
+        feedback.ok_or(Error::NotFound)
+    }
+
+    /// Move feedback to `status` with the admin's note
+    pub async fn set_status(
+        id: &str,
+        status: &str,
+        note: Option<String>,
+        admin: &RecordId,
+    ) -> Result<(), Error> {
+        let updated: Option<RecordId> = DB
+            .query(
+                "UPDATE ONLY $id SET status = $status, note = $note, triaged_by = $admin,
+                    triaged_at = time::now()
+                 RETURN VALUE id",
+            )
+            .bind(("id", RecordId::new("feedback", id)))
+            .bind(("status", status.to_string()))
+            .bind(("note", note))
+            .bind(("admin", admin.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to triage feedback: {}", e)))?
+            .take(0)?;
+        updated.ok_or(Error::NotFound)?;
+        info!("Feedback {} marked {} by {}", id, status, admin.display());
+        Ok(())
+    }
+
+    /// Record how forwarding went: the issue link, if any, or the error
+    pub async fn record_forward(
+        id: &RecordId,
+        outcome: &Result<Option<String>, Error>,
+    ) -> Result<(), Error> {
+        let query = match outcome {
+            Ok(issue_url) => DB
+                .query(
+                    "UPDATE $id SET forwarded_at = time::now(), forward_error = NONE,
+                        issue_url = $issue_url ?? issue_url",
+                )
+                .bind(("issue_url", issue_url.clone())),
+            Err(e) => DB
+                .query("UPDATE $id SET forward_error = $error")
+                .bind(("error", e.to_string())),
+        };
+        query
+            .bind(("id", id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to record forwarding: {}", e)))?
+            .check()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,614 @@
+//! Festival submissions
+//!
+//! A production's owners and admins track the festivals it's entering: the
+//! deadline, the fee, the category, and how it went. A submission starts out
+//! planned, is marked submitted once it's in (or withdrawn), and then
+//! records the result. The `festival_deadline_reminders` scheduled task
+//! reminds them about planned submissions a week before the deadline.
+//! Selections they choose to publish are listed under "Selections & Awards"
+//! on the public production page.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, info, warn};
+
+use crate::{
+    db::DB,
+    error::Error,
+    models::{budget, notification::NotificationModel, production_gear::format_value},
+    record_id_ext::RecordIdExt,
+};
+
+/// Where a submission stands, with labels
+pub const STATUSES: &[(&str, &str)] = &[
+    ("planned", "Planned"),
+    ("submitted", "Submitted"),
+    ("withdrawn", "Withdrawn"),
+];
+
+/// How a submission went, with labels
+pub const RESULTS: &[(&str, &str)] = &[
+    ("pending", "Awaiting results"),
+    ("selected", "Selected"),
+    ("not_selected", "Not selected"),
+];
+
+/// Days before the deadline a planned submission is reminded about
+pub const REMINDER_DAYS: i64 = 7;
+
+pub const MAX_NAME_CHARS: usize = 120;
+pub const MAX_NOTES_CHARS: usize = 1000;
+
+fn label(options: &[(&str, &'static str)], value: &str) -> &'static str {
+    options
+        .iter()
+        .find(|(key, _)| *key == value)
+        .map_or(options[0].1, |(_, label)| *label)
+}
+
+pub fn status_label(status: &str) -> &'static str {
+    label(STATUSES, status)
+}
+
+pub fn result_label(result: &str) -> &'static str {
+    label(RESULTS, result)
+}
+
+fn parse_date(value: &str) -> Result<Option<String>, Error> {
+    match value.trim() {
+        "" => Ok(None),
+        value => {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                Error::Validation(format!("\"{}\" isn't a date (use YYYY-MM-DD)", value))
+            })?;
+            Ok(Some(value.to_string()))
+        }
+    }
+}
+
+fn clean_text(value: &str, max: usize, what: &str) -> Result<Option<String>, Error> {
+    let value = value.trim();
+    if value.chars().count() > max {
+        return Err(Error::Validation(format!(
+            "{} can be up to {} characters",
+            what, max
+        )));
+    }
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/// A submission from the form, checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmissionData {
+    pub festival: String,
+    pub festival_url: Option<String>,
+    pub category: Option<String>,
+    /// YYYY-MM-DD
+    pub deadline: String,
+    /// YYYY-MM-DD
+    pub notification_date: Option<String>,
+    pub fee: Option<f64>,
+    pub notes: Option<String>,
+}
+
+impl SubmissionData {
+    pub fn parse(
+        festival: &str,
+        festival_url: &str,
+        category: &str,
+        deadline: &str,
+        notification_date: &str,
+        fee: &str,
+        notes: &str,
+    ) -> Result<Self, Error> {
+        let festival = clean_text(festival, MAX_NAME_CHARS, "The festival's name")?
+            .ok_or_else(|| Error::Validation("Name the festival".to_string()))?;
+        let festival_url = clean_text(festival_url, 500, "The link")?;
+        if let Some(url) = festival_url.as_deref()
+            && !(url.starts_with("https://") || url.starts_with("http://"))
+        {
+            return Err(Error::Validation(
+                "The festival's link should start with https://".to_string(),
+            ));
+        }
+        let deadline = parse_date(deadline)?
+            .ok_or_else(|| Error::Validation("Give the submission deadline".to_string()))?;
+        let notification_date = parse_date(notification_date)?;
+        if notification_date
+            .as_deref()
+            .is_some_and(|date| date < deadline.as_str())
+        {
+            return Err(Error::Validation(
+                "Results can't be announced before the deadline".to_string(),
+            ));
+        }
+        let fee = match fee.trim() {
+            "" | "0" => None,
+            value => Some(
+                budget::parse_amount(value)
+                    .map_err(|_| Error::Validation("Enter the fee as an amount".to_string()))?,
+            ),
+        };
+        Ok(Self {
+            festival,
+            festival_url,
+            category: clean_text(category, MAX_NAME_CHARS, "The category")?,
+            deadline,
+            notification_date,
+            fee,
+            notes: clean_text(notes, MAX_NOTES_CHARS, "Notes")?,
+        })
+    }
+}
+
+/// Where a submission stands and how it went, checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub status: String,
+    pub result: String,
+    pub award: Option<String>,
+    /// Shown on the production page; only selections can be
+    pub public: bool,
+}
+
+impl Outcome {
+    pub fn parse(status: &str, result: &str, award: &str, public: bool) -> Result<Self, Error> {
+        if !STATUSES.iter().any(|(key, _)| *key == status) {
+            return Err(Error::Validation("Choose a status".to_string()));
+        }
+        if !RESULTS.iter().any(|(key, _)| *key == result) {
+            return Err(Error::Validation("Choose a result".to_string()));
+        }
+        if result != "pending" && status != "submitted" {
+            return Err(Error::Validation(
+                "Only submitted films get a result".to_string(),
+            ));
+        }
+        let award = clean_text(award, MAX_NAME_CHARS, "The award")?;
+        if award.is_some() && result != "selected" {
+            return Err(Error::Validation("Awards go with a selection".to_string()));
+        }
+        Ok(Self {
+            status: status.to_string(),
+            result: result.to_string(),
+            award,
+            public: public && result == "selected",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct FestivalSubmission {
+    pub id: RecordId,
+    pub festival: String,
+    pub festival_url: Option<String>,
+    pub category: Option<String>,
+    pub deadline: String,
+    pub notification_date: Option<String>,
+    pub fee: Option<f64>,
+    pub status: String,
+    pub result: String,
+    pub award: Option<String>,
+    pub public: bool,
+    pub notes: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FestivalSubmission {
+    pub fn status_label(&self) -> &'static str {
+        status_label(&self.status)
+    }
+
+    pub fn result_label(&self) -> &'static str {
+        result_label(&self.result)
+    }
+
+    /// "$75.00"
+    pub fn fee_label(&self) -> Option<String> {
+        self.fee.map(|fee| format!("${}", format_value(fee)))
+    }
+
+    /// Days from `today` to the deadline; negative once it's passed
+    pub fn days_to_deadline(&self, today: NaiveDate) -> Option<i64> {
+        NaiveDate::parse_from_str(&self.deadline, "%Y-%m-%d")
+            .ok()
+            .map(|deadline| (deadline - today).num_days())
+    }
+
+    /// "Due today", "Due in 3 days", "Deadline passed", for planned ones
+    pub fn deadline_note(&self, today: NaiveDate) -> Option<String> {
+        if self.status != "planned" {
+            return None;
+        }
+        Some(match self.days_to_deadline(today)? {
+            ..0 => "Deadline passed".to_string(),
+            days => format!("Due {}", due_phrase(days)),
+        })
+    }
+
+    /// A planned submission whose deadline is within [`REMINDER_DAYS`]
+    pub fn needs_reminder(&self, today: NaiveDate) -> bool {
+        self.status == "planned"
+            && self
+                .days_to_deadline(today)
+                .is_some_and(|days| (0..=REMINDER_DAYS).contains(&days))
+    }
+
+    /// "Jury Prize, Best Short", or "Official Selection"
+    pub fn laurel(&self) -> String {
+        self.award
+            .clone()
+            .unwrap_or_else(|| "Official Selection".to_string())
+    }
+
+    /// "2026" from the deadline
+    pub fn year(&self) -> &str {
+        self.deadline.get(..4).unwrap_or_default()
+    }
+}
+
+/// "today", "tomorrow", "in 3 days"
+pub fn due_phrase(days: i64) -> String {
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        days => format!("in {} days", days),
+    }
+}
+
+/// Fees paid on submitted entries, and those still to pay on planned ones
+pub fn fee_totals(submissions: &[FestivalSubmission]) -> (f64, f64) {
+    let total = |status: &str| {
+        submissions
+            .iter()
+            .filter(|s| s.status == status)
+            .filter_map(|s| s.fee)
+            .sum()
+    };
+    (total("submitted"), total("planned"))
+}
+
+/// Something on the calendar
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarEvent {
+    pub submission_id: String,
+    /// "deadline" or "notification"
+    pub kind: &'static str,
+    /// "Sundance deadline"
+    pub label: String,
+}
+
+/// One day on the month calendar
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarDay {
+    /// "YYYY-MM-DD"
+    pub date: String,
+    pub day: u32,
+    /// Falls in the month shown rather than the days padding its weeks
+    pub in_month: bool,
+    pub is_today: bool,
+    pub events: Vec<CalendarEvent>,
+}
+
+/// The first of the month from "YYYY-MM", or `today`'s month
+pub fn month_start(month: Option<&str>, today: NaiveDate) -> NaiveDate {
+    month
+        .and_then(|value| {
+            NaiveDate::parse_from_str(&format!("{}-01", value.trim()), "%Y-%m-%d").ok()
+        })
+        .unwrap_or_else(|| today.with_day(1).unwrap_or(today))
+}
+
+/// The first of the months before and after
+pub fn adjacent_months(start: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let previous = (start - Duration::days(1)).with_day(1).unwrap_or(start);
+    let next = (start + Duration::days(31)).with_day(1).unwrap_or(start);
+    (previous, next)
+}
+
+/// The Monday-first weeks covering the month starting `start`, with the
+/// deadlines and result announcements on each day
+pub fn month_calendar(
+    submissions: &[FestivalSubmission],
+    start: NaiveDate,
+    today: NaiveDate,
+) -> Vec<Vec<CalendarDay>> {
+    let first = start - Duration::days(start.weekday().num_days_from_monday() as i64);
+    let (_, next) = adjacent_months(start);
+    let mut weeks = Vec::new();
+    let mut monday = first;
+    while monday < next {
+        weeks.push(
+            (0..7)
+                .map(|weekday| {
+                    let date = monday + Duration::days(weekday);
+                    let key = date.format("%Y-%m-%d").to_string();
+                    let mut events = Vec::new();
+                    for submission in submissions.iter().filter(|s| s.status != "withdrawn") {
+                        if submission.deadline == key {
+                            events.push(CalendarEvent {
+                                submission_id: submission.id.key_string(),
+                                kind: "deadline",
+                                label: format!("{} deadline", submission.festival),
+                            });
+                        }
+                        if submission.notification_date.as_deref() == Some(key.as_str()) {
+                            events.push(CalendarEvent {
+                                submission_id: submission.id.key_string(),
+                                kind: "notification",
+                                label: format!("{} results", submission.festival),
+                            });
+                        }
+                    }
+                    CalendarDay {
+                        day: date.day(),
+                        in_month: date.month() == start.month(),
+                        is_today: date == today,
+                        events,
+                        date: key,
+                    }
+                })
+                .collect(),
+        );
+        monday += Duration::days(7);
+    }
+    weeks
+}
+
+/// Who to remind about a submission, and where to send them
+#[derive(Debug, Serialize, Deserialize, SurrealValue)]
+struct ReminderContext {
+    production: RecordId,
+    production_slug: String,
+    production_title: String,
+    created_by: RecordId,
+}
+
+const SUBMISSION_FIELDS: &str = "id, festival, festival_url, category, deadline, notification_date,
+    fee, status, result, award, public, notes, updated_at";
+
+pub struct FestivalSubmissionModel;
+
+impl FestivalSubmissionModel {
+    /// A production's submissions, by deadline
+    pub async fn for_production(production: &RecordId) -> Result<Vec<FestivalSubmission>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM festival_submission WHERE production = $production
+                 ORDER BY deadline ASC, festival ASC",
+                SUBMISSION_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Selections the production has chosen to show, most recent first
+    pub async fn public_selections(
+        production: &RecordId,
+    ) -> Result<Vec<FestivalSubmission>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM festival_submission
+                 WHERE production = $production AND public = true AND result = 'selected'
+                 ORDER BY deadline DESC",
+                SUBMISSION_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn get(
+        production: &RecordId,
+        submission_id: &str,
+    ) -> Result<FestivalSubmission, Error> {
+        let submission: Option<FestivalSubmission> = DB
+            .query(format!(
+                "SELECT {} FROM $id WHERE production = $production",
+                SUBMISSION_FIELDS
+            ))
+            .bind(("id", RecordId::new("festival_submission", submission_id)))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        submission.ok_or(Error::NotFound)
+    }
+
+    pub async fn create(
+        production: &RecordId,
+        data: &SubmissionData,
+        created_by: &RecordId,
+    ) -> Result<RecordId, Error> {
+        let id: Option<RecordId> = DB
+            .query(
+                "CREATE festival_submission SET production = $production, festival = $festival,
+                    festival_url = $festival_url, category = $category, deadline = $deadline,
+                    notification_date = $notification_date, fee = $fee, notes = $notes,
+                    created_by = $created_by
+                 RETURN VALUE id",
+            )
+            .bind(("production", production.clone()))
+            .bind(("festival", data.festival.clone()))
+            .bind(("festival_url", data.festival_url.clone()))
+            .bind(("category", data.category.clone()))
+            .bind(("deadline", data.deadline.clone()))
+            .bind(("notification_date", data.notification_date.clone()))
+            .bind(("fee", data.fee))
+            .bind(("notes", data.notes.clone()))
+            .bind(("created_by", created_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to add festival submission: {}", e)))?
+            .take(0)?;
+        let id =
+            id.ok_or_else(|| Error::Internal("Festival submission was not created".to_string()))?;
+        debug!(
+            "Added festival submission {} to {}",
+            id.display(),
+            production.display()
+        );
+        Ok(id)
+    }
+
+    /// Update the details; moving the deadline re-arms its reminder
+    pub async fn update(
+        production: &RecordId,
+        submission_id: &str,
+        data: &SubmissionData,
+    ) -> Result<(), Error> {
+        DB.query(
+            "UPDATE $id SET
+                reminder_sent_at = IF deadline = $deadline THEN reminder_sent_at ELSE NONE END,
+                festival = $festival, festival_url = $festival_url, category = $category,
+                deadline = $deadline, notification_date = $notification_date, fee = $fee,
+                notes = $notes
+             WHERE production = $production",
+        )
+        .bind(("id", RecordId::new("festival_submission", submission_id)))
+        .bind(("production", production.clone()))
+        .bind(("festival", data.festival.clone()))
+        .bind(("festival_url", data.festival_url.clone()))
+        .bind(("category", data.category.clone()))
+        .bind(("deadline", data.deadline.clone()))
+        .bind(("notification_date", data.notification_date.clone()))
+        .bind(("fee", data.fee))
+        .bind(("notes", data.notes.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update festival submission: {}", e)))?
+        .check()?;
+        Ok(())
+    }
+
+    pub async fn set_outcome(
+        production: &RecordId,
+        submission_id: &str,
+        outcome: &Outcome,
+    ) -> Result<(), Error> {
+        DB.query(
+            "UPDATE $id SET status = $status, result = $result, award = $award, public = $public
+             WHERE production = $production",
+        )
+        .bind(("id", RecordId::new("festival_submission", submission_id)))
+        .bind(("production", production.clone()))
+        .bind(("status", outcome.status.clone()))
+        .bind(("result", outcome.result.clone()))
+        .bind(("award", outcome.award.clone()))
+        .bind(("public", outcome.public))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update festival submission: {}", e)))?
+        .check()?;
+        Ok(())
+    }
+
+    pub async fn delete(production: &RecordId, submission_id: &str) -> Result<(), Error> {
+        DB.query("DELETE $id WHERE production = $production")
+            .bind(("id", RecordId::new("festival_submission", submission_id)))
+            .bind(("production", production.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete festival submission: {}", e)))?
+            .check()?;
+        Ok(())
+    }
+
+    /// The production's owners and admins, who hear about deadlines
+    async fn editors(production: &RecordId) -> Result<Vec<RecordId>, Error> {
+        Ok(DB
+            .query(
+                "SELECT VALUE in FROM member_of
+                 WHERE out = $production AND role IN ['owner', 'admin']
+                    AND invitation_status = 'accepted'
+                    AND <string> type::table(in) = 'person'",
+            )
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Remind the production's owners and admins about planned submissions
+    /// whose deadline is coming up, once per deadline
+    pub async fn send_reminders() -> Result<(), Error> {
+        let today = Utc::now().date_naive();
+        let last_day = (today + Duration::days(REMINDER_DAYS))
+            .format("%Y-%m-%d")
+            .to_string();
+        let due: Vec<FestivalSubmission> = DB
+            .query(format!(
+                "SELECT {} FROM festival_submission
+                 WHERE status = 'planned' AND reminder_sent_at IS NONE
+                    AND deadline >= $today AND deadline <= $last_day",
+                SUBMISSION_FIELDS
+            ))
+            .bind(("today", today.format("%Y-%m-%d").to_string()))
+            .bind(("last_day", last_day))
+            .await?
+            .take(0)?;
+
+        let notifications = NotificationModel::new();
+        let mut sent = 0;
+        for submission in due {
+            let Some(days) = submission
+                .days_to_deadline(today)
+                .filter(|_| submission.needs_reminder(today))
+            else {
+                continue;
+            };
+            let context: Option<ReminderContext> = DB
+                .query(
+                    "SELECT production, production.slug AS production_slug,
+                        production.title AS production_title, created_by
+                     FROM ONLY $id",
+                )
+                .bind(("id", submission.id.clone()))
+                .await?
+                .take(0)?;
+            let Some(context) = context else {
+                continue;
+            };
+            let mut recipients = Self::editors(&context.production).await.unwrap_or_default();
+            if !recipients.contains(&context.created_by) {
+                recipients.push(context.created_by.clone());
+            }
+            let link = format!(
+                "/productions/{}/festivals#submission-{}",
+                context.production_slug,
+                submission.id.key_string()
+            );
+            let message = format!(
+                "The {} submission for {} is due {} ({}).",
+                submission.festival,
+                context.production_title,
+                due_phrase(days),
+                submission.deadline
+            );
+            for person in recipients {
+                if let Err(e) = notifications
+                    .create(
+                        &person.to_raw_string(),
+                        "festival",
+                        "Festival deadline coming up",
+                        &message,
+                        Some(&link),
+                        Some(&submission.id.to_raw_string()),
+                    )
+                    .await
+                {
+                    warn!(
+                        "Failed to send deadline reminder for {}: {}",
+                        submission.id.display(),
+                        e
+                    );
+                }
+            }
+            DB.query("UPDATE $id SET reminder_sent_at = time::now()")
+                .bind(("id", submission.id.clone()))
+                .await?
+                .check()?;
+            sent += 1;
+        }
+        if sent > 0 {
+            info!("Sent deadline reminders for {} festival submissions", sent);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,525 @@
+//! Rental quotes from vendors
+//!
+//! A production's owners and admins ask an organization that rents out gear
+//! to quote for some of its equipment over a range of dates. The vendor's
+//! members answer with a price for the lot, or decline; they can quote again
+//! until the production decides. The production accepts the price or
+//! withdraws the request. Accepting books the gear on the production for the
+//! dates (see `production_gear`) and adds the price to its budget (see
+//! `budget`) as an equipment rental line.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, info};
+
+use crate::{
+    db::DB,
+    error::Error,
+    models::{
+        budget::{self, BudgetLineData, BudgetModel},
+        equipment::Equipment,
+        production_gear::{self, ProductionGearModel},
+    },
+    record_id_ext::RecordIdExt,
+};
+
+pub const MAX_NOTE_CHARS: usize = 1000;
+
+/// Something either side can do to a quote
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteAction {
+    /// The vendor prices the request, or prices it again
+    Quote,
+    /// The vendor turns the request down
+    Decline,
+    /// The production takes the price
+    Accept,
+    /// The production drops the request
+    Withdraw,
+}
+
+/// The status a quote moves to, or `None` when the action isn't open to it
+pub fn next_status(current: &str, action: QuoteAction) -> Option<&'static str> {
+    match (current, action) {
+        ("requested" | "quoted", QuoteAction::Quote) => Some("quoted"),
+        ("requested" | "quoted", QuoteAction::Decline) => Some("declined"),
+        ("quoted", QuoteAction::Accept) => Some("accepted"),
+        ("requested" | "quoted", QuoteAction::Withdraw) => Some("withdrawn"),
+        _ => None,
+    }
+}
+
+fn clean_note(value: &str) -> Result<Option<String>, Error> {
+    let value = value.trim();
+    if value.chars().count() > MAX_NOTE_CHARS {
+        return Err(Error::Validation(format!(
+            "Notes can be up to {} characters",
+            MAX_NOTE_CHARS
+        )));
+    }
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/// What a production is asking a vendor to quote for, checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteRequest {
+    pub equipment: Vec<RecordId>,
+    pub start_date: String,
+    pub end_date: String,
+    pub note: Option<String>,
+}
+
+impl QuoteRequest {
+    /// Check a request. `selected` are equipment keys from the form and
+    /// `vendor_gear` what the vendor has to rent.
+    pub fn parse(
+        selected: &[String],
+        vendor_gear: &[RecordId],
+        start_date: &str,
+        end_date: &str,
+        note: &str,
+        today: NaiveDate,
+    ) -> Result<Self, Error> {
+        if selected.is_empty() {
+            return Err(Error::Validation(
+                "Pick the gear you'd like quoted".to_string(),
+            ));
+        }
+        let mut equipment: Vec<RecordId> = Vec::new();
+        for key in selected {
+            let id = vendor_gear
+                .iter()
+                .find(|id| id.key_string() == *key)
+                .ok_or_else(|| Error::Validation("Pick gear this vendor rents out".to_string()))?;
+            if !equipment.contains(id) {
+                equipment.push(id.clone());
+            }
+        }
+        let (start_date, end_date) = production_gear::clean_booking_dates(start_date, end_date)?
+            .ok_or_else(|| {
+                Error::Validation("Give the first and last day you need the gear".to_string())
+            })?;
+        if start_date < today.format("%Y-%m-%d").to_string() {
+            return Err(Error::Validation(
+                "The start date has already passed".to_string(),
+            ));
+        }
+        Ok(Self {
+            equipment,
+            start_date,
+            end_date,
+            note: clean_note(note)?,
+        })
+    }
+}
+
+/// A vendor's price, checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuotePrice {
+    pub price: f64,
+    /// YYYY-MM-DD
+    pub valid_until: Option<String>,
+    pub note: Option<String>,
+}
+
+impl QuotePrice {
+    /// Check a price. A blank "valid until" keeps it open until the
+    /// production decides.
+    pub fn parse(
+        price: &str,
+        valid_until: &str,
+        note: &str,
+        today: NaiveDate,
+    ) -> Result<Self, Error> {
+        let price = budget::parse_amount(price)
+            .map_err(|_| Error::Validation("Enter a price greater than zero".to_string()))?;
+        let valid_until = match valid_until.trim() {
+            "" => None,
+            value => {
+                let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+                    Error::Validation("Enter a valid \"valid until\" date".to_string())
+                })?;
+                if date < today {
+                    return Err(Error::Validation(
+                        "The quote can't expire before today".to_string(),
+                    ));
+                }
+                Some(date.format("%Y-%m-%d").to_string())
+            }
+        };
+        Ok(Self {
+            price,
+            valid_until,
+            note: clean_note(note)?,
+        })
+    }
+}
+
+/// Whether a quote's "valid until" date has passed
+pub fn is_expired(valid_until: Option<&str>, today: &str) -> bool {
+    valid_until.is_some_and(|date| date < today)
+}
+
+/// "Northlight Rentals: Canon C70, SkyPanel S60 (2026-06-01 to 2026-06-05)"
+pub fn budget_description(vendor_name: &str, items: &[String], start: &str, end: &str) -> String {
+    let description = format!(
+        "{}: {} ({})",
+        vendor_name,
+        items.join(", "),
+        production_gear::dates_label(start, end)
+    );
+    if description.chars().count() > budget::MAX_DESCRIPTION_CHARS {
+        let cut: String = description
+            .chars()
+            .take(budget::MAX_DESCRIPTION_CHARS - 1)
+            .collect();
+        format!("{}…", cut)
+    } else {
+        description
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct GearQuote {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub vendor: RecordId,
+    pub equipment: Vec<RecordId>,
+    /// YYYY-MM-DD
+    pub start_date: String,
+    /// YYYY-MM-DD, inclusive
+    pub end_date: String,
+    pub request_note: Option<String>,
+    /// "requested", "quoted", "accepted", "declined" or "withdrawn"
+    pub status: String,
+    pub price: Option<f64>,
+    pub valid_until: Option<String>,
+    pub vendor_note: Option<String>,
+    pub requested_by: RecordId,
+    pub quoted_by: Option<RecordId>,
+    pub budget_line: Option<RecordId>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A quote with the names either side needs to see
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct QuoteListing {
+    pub id: RecordId,
+    pub production_title: String,
+    pub production_slug: String,
+    pub vendor_name: String,
+    pub vendor_slug: String,
+    /// Equipment names
+    pub items: Vec<String>,
+    pub start_date: String,
+    pub end_date: String,
+    pub request_note: Option<String>,
+    pub status: String,
+    pub price: Option<f64>,
+    pub valid_until: Option<String>,
+    pub vendor_note: Option<String>,
+    pub requester_name: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl QuoteListing {
+    /// "2026-06-01 to 2026-06-05"
+    pub fn dates_label(&self) -> String {
+        production_gear::dates_label(&self.start_date, &self.end_date)
+    }
+
+    /// "$1,250.00", once it's priced
+    pub fn price_label(&self) -> Option<String> {
+        self.price
+            .map(|price| format!("${}", production_gear::format_value(price)))
+    }
+
+    /// "Canon C70, SkyPanel S60"
+    pub fn items_label(&self) -> String {
+        self.items.join(", ")
+    }
+
+    /// Whether it's still waiting on either side
+    pub fn is_open(&self) -> bool {
+        self.status == "requested" || self.status == "quoted"
+    }
+
+    pub fn is_expired(&self) -> bool {
+        let today = Utc::now().date_naive().format("%Y-%m-%d").to_string();
+        self.status == "quoted" && is_expired(self.valid_until.as_deref(), &today)
+    }
+}
+
+const LISTING_FIELDS: &str =
+    "id, production.title AS production_title, production.slug AS production_slug,
+    vendor.name AS vendor_name, vendor.slug AS vendor_slug, equipment.name AS items,
+    start_date, end_date, request_note, status, price, valid_until, vendor_note,
+    requested_by.name ?? requested_by.username AS requester_name, updated_at";
+
+/// An organization with gear to rent
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Vendor {
+    pub id: RecordId,
+    pub name: String,
+    pub slug: String,
+}
+
+pub struct GearQuoteModel;
+
+impl GearQuoteModel {
+    /// Organizations that own equipment in service, by name
+    pub async fn vendors() -> Result<Vec<Vendor>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, name, slug FROM organization
+                 WHERE id IN (SELECT VALUE owner_organization FROM equipment
+                    WHERE owner_type = 'organization' AND out_of_service != true)
+                 ORDER BY name ASC",
+            )
+            .await?
+            .take(0)?)
+    }
+
+    /// A vendor's equipment in service, by name
+    pub async fn vendor_gear(vendor: &RecordId) -> Result<Vec<Equipment>, Error> {
+        Ok(DB
+            .query(
+                "SELECT * FROM equipment
+                 WHERE owner_type = 'organization' AND owner_organization = $vendor
+                    AND out_of_service != true
+                 ORDER BY name ASC
+                 FETCH category, condition",
+            )
+            .bind(("vendor", vendor.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn create(
+        production: &RecordId,
+        vendor: &RecordId,
+        request: &QuoteRequest,
+        requested_by: &RecordId,
+    ) -> Result<GearQuote, Error> {
+        let quote: Option<GearQuote> = DB
+            .query(
+                "CREATE gear_quote SET production = $production, vendor = $vendor,
+                    equipment = $equipment, start_date = $start_date, end_date = $end_date,
+                    request_note = $note, status = 'requested', requested_by = $requested_by",
+            )
+            .bind(("production", production.clone()))
+            .bind(("vendor", vendor.clone()))
+            .bind(("equipment", request.equipment.clone()))
+            .bind(("start_date", request.start_date.clone()))
+            .bind(("end_date", request.end_date.clone()))
+            .bind(("note", request.note.clone()))
+            .bind(("requested_by", requested_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to request quote: {}", e)))?
+            .take(0)?;
+        let quote = quote.ok_or_else(|| Error::Internal("Quote was not created".to_string()))?;
+        debug!(
+            "Requested quote {} from {}",
+            quote.id.display(),
+            vendor.display()
+        );
+        Ok(quote)
+    }
+
+    pub async fn get(quote_id: &str) -> Result<GearQuote, Error> {
+        let quote: Option<GearQuote> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("gear_quote", quote_id)))
+            .await?
+            .take(0)?;
+        quote.ok_or(Error::NotFound)
+    }
+
+    pub async fn listing(quote: &RecordId) -> Result<QuoteListing, Error> {
+        let listing: Option<QuoteListing> = DB
+            .query(format!("SELECT {LISTING_FIELDS} FROM $quote"))
+            .bind(("quote", quote.clone()))
+            .await?
+            .take(0)?;
+        listing.ok_or(Error::NotFound)
+    }
+
+    pub async fn for_production(production: &RecordId) -> Result<Vec<QuoteListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM gear_quote WHERE production = $production
+                 ORDER BY updated_at DESC"
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Requests sent to a vendor; withdrawn ones drop off
+    pub async fn for_vendor(vendor: &RecordId) -> Result<Vec<QuoteListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM gear_quote
+                 WHERE vendor = $vendor AND status != 'withdrawn'
+                 ORDER BY updated_at DESC"
+            ))
+            .bind(("vendor", vendor.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// The status `action` moves the quote to, if it's open to it
+    fn target(quote: &GearQuote, action: QuoteAction) -> Result<&'static str, Error> {
+        next_status(&quote.status, action)
+            .ok_or_else(|| Error::Conflict("This quote can't be changed any more".to_string()))
+    }
+
+    fn moved(quote: &GearQuote, status: &str, updated: Option<GearQuote>) -> Option<GearQuote> {
+        if updated.is_some() {
+            info!(
+                "Quote {} {} -> {}",
+                quote.id.display(),
+                quote.status,
+                status
+            );
+        }
+        updated
+    }
+
+    // Each update below only applies while the quote still has the status the
+    // caller saw, and returns `None` when someone else got there first.
+
+    pub async fn quote(
+        quote: &GearQuote,
+        price: &QuotePrice,
+        quoted_by: &RecordId,
+    ) -> Result<Option<GearQuote>, Error> {
+        let status = Self::target(quote, QuoteAction::Quote)?;
+        let updated: Option<GearQuote> = DB
+            .query(
+                "UPDATE $quote SET status = $status, price = $price, valid_until = $valid_until,
+                    vendor_note = $note, quoted_by = $quoted_by
+                 WHERE status = $current RETURN AFTER",
+            )
+            .bind(("quote", quote.id.clone()))
+            .bind(("status", status))
+            .bind(("current", quote.status.clone()))
+            .bind(("price", price.price))
+            .bind(("valid_until", price.valid_until.clone()))
+            .bind(("note", price.note.clone()))
+            .bind(("quoted_by", quoted_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to quote: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(quote, status, updated))
+    }
+
+    pub async fn decline(quote: &GearQuote, note: &str) -> Result<Option<GearQuote>, Error> {
+        let status = Self::target(quote, QuoteAction::Decline)?;
+        let updated: Option<GearQuote> = DB
+            .query(
+                "UPDATE $quote SET status = $status, vendor_note = $note
+                 WHERE status = $current RETURN AFTER",
+            )
+            .bind(("quote", quote.id.clone()))
+            .bind(("status", status))
+            .bind(("current", quote.status.clone()))
+            .bind(("note", clean_note(note)?))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to decline quote: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(quote, status, updated))
+    }
+
+    pub async fn withdraw(quote: &GearQuote) -> Result<Option<GearQuote>, Error> {
+        let status = Self::target(quote, QuoteAction::Withdraw)?;
+        let updated: Option<GearQuote> = DB
+            .query("UPDATE $quote SET status = $status WHERE status = $current RETURN AFTER")
+            .bind(("quote", quote.id.clone()))
+            .bind(("status", status))
+            .bind(("current", quote.status.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to withdraw quote: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(quote, status, updated))
+    }
+
+    /// Take the vendor's price. Refused once it has expired, or while any of
+    /// the gear is booked by another production for the dates.
+    pub async fn accept(quote: &GearQuote, today: &str) -> Result<Option<GearQuote>, Error> {
+        let status = Self::target(quote, QuoteAction::Accept)?;
+        if is_expired(quote.valid_until.as_deref(), today) {
+            return Err(Error::Validation(
+                "This quote has expired. Ask the vendor to quote again.".to_string(),
+            ));
+        }
+        let bookings = ProductionGearModel::bookings(&quote.equipment, &quote.start_date).await?;
+        let conflicts = production_gear::conflicting(
+            &bookings,
+            &quote.start_date,
+            &quote.end_date,
+            Some(&quote.production),
+        );
+        if !conflicts.is_empty() {
+            return Err(Error::Validation(production_gear::conflict_message(
+                &conflicts,
+            )));
+        }
+        let updated: Option<GearQuote> = DB
+            .query("UPDATE $quote SET status = $status WHERE status = $current RETURN AFTER")
+            .bind(("quote", quote.id.clone()))
+            .bind(("status", status))
+            .bind(("current", quote.status.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to accept quote: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(quote, status, updated))
+    }
+
+    /// Confirm an accepted quote: book the gear on the production for its
+    /// dates and add the price to the budget
+    pub async fn confirm(quote: &GearQuote, accepted_by: &RecordId) -> Result<(), Error> {
+        for equipment in &quote.equipment {
+            ProductionGearModel::assign(
+                &quote.production,
+                equipment,
+                Some((quote.start_date.clone(), quote.end_date.clone())),
+                accepted_by,
+            )
+            .await?;
+        }
+        let listing = Self::listing(&quote.id).await?;
+        let line = BudgetLineData {
+            category: "equipment".to_string(),
+            description: budget_description(
+                &listing.vendor_name,
+                &listing.items,
+                &quote.start_date,
+                &quote.end_date,
+            ),
+            amount: quote.price.unwrap_or_default(),
+        };
+        let line = BudgetModel::add(
+            &quote.production,
+            &line,
+            Some(&quote.vendor),
+            Some(&quote.id),
+            accepted_by,
+        )
+        .await?;
+        DB.query("UPDATE $quote SET budget_line = $line")
+            .bind(("quote", quote.id.clone()))
+            .bind(("line", line))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to link budget line: {}", e)))?;
+        info!(
+            "Booked {} item(s) on {} from quote {}",
+            quote.equipment.len(),
+            quote.production.display(),
+            quote.id.display()
+        );
+        Ok(())
+    }
+}
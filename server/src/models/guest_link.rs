@@ -0,0 +1,228 @@
+//! Guest access links
+//!
+//! Owners and admins hand people who aren't on SlateHub a link that does one
+//! thing: show the production's shooting schedule, or let them upload files
+//! to a single deliverable. The token in the link is the guest's session;
+//! only its hash is stored, so the link is shown once when it's made. Every
+//! request through a link checks that it's still live, so it stops working
+//! the moment it expires or is revoked, without touching the production's
+//! other links.
+
+use crate::{
+    db::DB,
+    error::Error,
+    record_id_ext::RecordIdExt,
+    services::tokens::{self, token_hash},
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+pub const MAX_LABEL_CHARS: usize = 80;
+
+const TOKEN_LENGTH: usize = 32;
+
+/// What a link lets its guest do, as (value, label)
+pub const SCOPES: &[(&str, &str)] = &[
+    ("schedule", "View the shooting schedule"),
+    ("upload", "Upload files to one deliverable"),
+];
+
+/// How long a new link lasts, as (days, label)
+pub const EXPIRY_OPTIONS: &[(i64, &str)] = &[
+    (1, "1 day"),
+    (7, "1 week"),
+    (30, "30 days"),
+    (90, "90 days"),
+];
+
+pub fn scope_label(scope: &str) -> &'static str {
+    SCOPES
+        .iter()
+        .find(|(value, _)| *value == scope)
+        .map(|(_, label)| *label)
+        .unwrap_or("Guest access")
+}
+
+/// A new link as submitted by the production's owner or admin
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuestLinkData {
+    pub label: String,
+    pub scope: &'static str,
+    /// Key of the deliverable an upload link puts files in
+    pub deliverable: Option<String>,
+    pub days: i64,
+}
+
+impl GuestLinkData {
+    pub fn parse(label: &str, scope: &str, deliverable: &str, days: &str) -> Result<Self, Error> {
+        let label = label.trim();
+        if label.is_empty() {
+            return Err(Error::Validation(
+                "Say who the link is for, so you can tell links apart".to_string(),
+            ));
+        }
+        if label.chars().count() > MAX_LABEL_CHARS {
+            return Err(Error::Validation(format!(
+                "Keep who it's for under {} characters",
+                MAX_LABEL_CHARS
+            )));
+        }
+        let scope = SCOPES
+            .iter()
+            .map(|(value, _)| *value)
+            .find(|value| *value == scope)
+            .ok_or_else(|| Error::Validation("Choose what the link allows".to_string()))?;
+        let deliverable = match scope {
+            "upload" => Some(
+                Some(deliverable.trim())
+                    .filter(|d| !d.is_empty())
+                    .ok_or_else(|| {
+                        Error::Validation("Choose the deliverable to upload to".to_string())
+                    })?
+                    .to_string(),
+            ),
+            _ => None,
+        };
+        let days = days
+            .trim()
+            .parse::<i64>()
+            .ok()
+            .filter(|days| EXPIRY_OPTIONS.iter().any(|(option, _)| option == days))
+            .ok_or_else(|| Error::Validation("Choose how long the link lasts".to_string()))?;
+        Ok(Self {
+            label: label.to_string(),
+            scope,
+            deliverable,
+            days,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct GuestLink {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub label: String,
+    pub scope: String,
+    pub deliverable: Option<RecordId>,
+    pub deliverable_title: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_by: RecordId,
+    pub creator_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl GuestLink {
+    /// "active", "expired" or "revoked"
+    pub fn status(&self, now: DateTime<Utc>) -> &'static str {
+        if self.revoked_at.is_some() {
+            "revoked"
+        } else if self.expires_at <= now {
+            "expired"
+        } else {
+            "active"
+        }
+    }
+
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.status(now) == "active"
+    }
+
+    pub fn scope_label(&self) -> &'static str {
+        scope_label(&self.scope)
+    }
+}
+
+const LINK_FIELDS: &str = "id, production, label, scope, deliverable,
+    deliverable.title AS deliverable_title, expires_at, revoked_at, last_used_at,
+    created_by, created_by.name ?? created_by.username AS creator_name, created_at";
+
+pub struct GuestLinkModel;
+
+impl GuestLinkModel {
+    /// Issue a link and return its token, which isn't kept anywhere else
+    pub async fn create(
+        production: &RecordId,
+        data: &GuestLinkData,
+        deliverable: Option<&RecordId>,
+        created_by: &RecordId,
+    ) -> Result<String, Error> {
+        let token = tokens::random_token(TOKEN_LENGTH);
+        DB.query(
+            "CREATE guest_link SET production = $production, token_hash = $token_hash,
+                label = $label, scope = $scope, deliverable = $deliverable,
+                expires_at = $expires_at, created_by = $created_by",
+        )
+        .bind(("production", production.clone()))
+        .bind(("token_hash", token_hash(&token)))
+        .bind(("label", data.label.clone()))
+        .bind(("scope", data.scope.to_string()))
+        .bind(("deliverable", deliverable.cloned()))
+        .bind(("expires_at", Utc::now() + Duration::days(data.days)))
+        .bind(("created_by", created_by.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to create guest link: {}", e)))?
+        .check()?;
+        debug!(
+            "Issued {} guest link for {}",
+            data.scope,
+            production.display()
+        );
+        Ok(token)
+    }
+
+    /// A production's links, newest first, including expired and revoked ones
+    pub async fn for_production(production: &RecordId) -> Result<Vec<GuestLink>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM guest_link WHERE production = $production
+                 ORDER BY created_at DESC",
+                LINK_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// The live link a token belongs to, noting that it was used. Expired,
+    /// revoked and unknown tokens are all simply not found.
+    pub async fn by_token(token: &str) -> Result<GuestLink, Error> {
+        if !tokens::is_token(token, TOKEN_LENGTH) {
+            return Err(Error::NotFound);
+        }
+        let link: Option<GuestLink> = DB
+            .query(format!(
+                "SELECT {} FROM guest_link WHERE token_hash = $token_hash
+                    AND revoked_at = NONE AND expires_at > time::now() LIMIT 1",
+                LINK_FIELDS
+            ))
+            .bind(("token_hash", token_hash(token)))
+            .await?
+            .take(0)?;
+        let link = link.ok_or(Error::NotFound)?;
+        DB.query("UPDATE $id SET last_used_at = time::now()")
+            .bind(("id", link.id.clone()))
+            .await?;
+        Ok(link)
+    }
+
+    /// Stop one link working. Revoked links stay listed so it's clear who
+    /// had access.
+    pub async fn revoke(production: &RecordId, link_id: &str) -> Result<(), Error> {
+        let revoked: Option<RecordId> = DB
+            .query(
+                "UPDATE $id SET revoked_at = revoked_at ?? time::now()
+                 WHERE production = $production RETURN VALUE id",
+            )
+            .bind(("id", RecordId::new("guest_link", link_id)))
+            .bind(("production", production.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to revoke guest link: {}", e)))?
+            .take(0)?;
+        revoked.map(|_| ()).ok_or(Error::NotFound)
+    }
+}
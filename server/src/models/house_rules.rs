@@ -0,0 +1,249 @@
+//! House rules: a location's rules for productions shooting there (no
+//! smoking indoors, load in through the side gate, quiet after 22:00).
+//!
+//! A production books a location by setting it on a shoot day. Its crew then
+//! acknowledge the rules; an acknowledgment counts until the owner changes the
+//! rules. A day's call sheet isn't sent until everyone in a key role
+//! (director, producers, ADs, the location manager) has acknowledged.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+use crate::{
+    db::DB, error::Error, models::daily_report, models::production::ProductionModel,
+    record_id_ext::RecordIdExt,
+};
+
+/// Most rules a location can list
+pub const MAX_RULES: usize = 30;
+
+/// Longest single rule
+pub const MAX_RULE_CHARS: usize = 300;
+
+/// Whether production roles include one that has to acknowledge house rules
+/// before call sheets go out: the director, producers, production and
+/// location managers, and assistant directors. Department heads titled
+/// "director" (art, casting, photography) aren't included.
+pub fn is_key_role(roles: &[String]) -> bool {
+    daily_report::is_assistant_director(roles)
+        || roles.iter().any(|role| {
+            let role = role.trim().to_lowercase();
+            role == "director"
+                || role.contains("producer")
+                || role.contains("production manager")
+                || role.contains("location manager")
+                || role.split_whitespace().any(|w| w == "upm")
+        })
+}
+
+/// Rules from the edit form, one per line. Blank lines are dropped.
+pub fn parse_rules(input: &str) -> Result<Vec<String>, Error> {
+    let rules: Vec<String> = input
+        .lines()
+        .map(|line| line.trim().trim_start_matches(['-', '*', '•']).trim())
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect();
+    if rules.len() > MAX_RULES {
+        return Err(Error::Validation(format!(
+            "List up to {} house rules",
+            MAX_RULES
+        )));
+    }
+    if rules.iter().any(|r| r.chars().count() > MAX_RULE_CHARS) {
+        return Err(Error::Validation(format!(
+            "Keep each house rule under {} characters",
+            MAX_RULE_CHARS
+        )));
+    }
+    Ok(rules)
+}
+
+/// A location one of a production's shoot days is booked at
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct BookedLocation {
+    pub id: RecordId,
+    pub name: String,
+    pub address: String,
+    pub city: String,
+    #[serde(default)]
+    #[surreal(default)]
+    pub house_rules: Vec<String>,
+    pub house_rules_updated_at: Option<DateTime<Utc>>,
+}
+
+/// Where a production member stands on a location's house rules
+#[derive(Debug, Clone)]
+pub struct Acknowledgment {
+    pub person_id: String,
+    pub name: String,
+    pub roles: Vec<String>,
+    pub key_role: bool,
+    /// When they acknowledged the current rules; `None` if they haven't
+    pub acknowledged_at: Option<DateTime<Utc>>,
+}
+
+/// Key-role members who haven't acknowledged, as "Name (Role)"
+pub fn pending_key_people(acknowledgments: &[Acknowledgment]) -> Vec<String> {
+    acknowledgments
+        .iter()
+        .filter(|a| a.key_role && a.acknowledged_at.is_none())
+        .map(|a| {
+            if a.roles.is_empty() {
+                a.name.clone()
+            } else {
+                format!("{} ({})", a.name, a.roles.join(", "))
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct AckRow {
+    person: String,
+    acknowledged_at: DateTime<Utc>,
+}
+
+pub struct HouseRulesModel;
+
+impl HouseRulesModel {
+    /// Replace a location's rules. Changing them makes earlier
+    /// acknowledgments out of date; saving the same rules doesn't.
+    pub async fn set_rules(location: &RecordId, rules: Vec<String>) -> Result<(), Error> {
+        debug!(
+            "Saving {} house rules for {}",
+            rules.len(),
+            location.display()
+        );
+        DB.query(
+            "UPDATE $location SET
+                 house_rules_updated_at = IF (house_rules ?? []) != $rules THEN time::now() ELSE house_rules_updated_at END,
+                 house_rules = IF array::len($rules) > 0 THEN $rules ELSE NONE END",
+        )
+        .bind(("location", location.clone()))
+        .bind(("rules", rules))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to save house rules: {}", e)))?
+        .check()?;
+        Ok(())
+    }
+
+    /// Locations booked on the production's shoot days, by name
+    pub async fn booked_locations(production: &RecordId) -> Result<Vec<BookedLocation>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, name, address, city, house_rules ?? [] AS house_rules, house_rules_updated_at
+                 FROM array::distinct((SELECT VALUE location FROM shoot_day
+                     WHERE production = $production AND location != NONE))
+                 ORDER BY name ASC",
+            )
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Whether the location is booked on one of the production's shoot days
+    pub async fn is_booked(production: &RecordId, location: &RecordId) -> Result<bool, Error> {
+        let count: Option<i64> = DB
+            .query(
+                "SELECT VALUE count() FROM shoot_day
+                 WHERE production = $production AND location = $location GROUP ALL",
+            )
+            .bind(("production", production.clone()))
+            .bind(("location", location.clone()))
+            .await?
+            .take(0)?;
+        Ok(count.unwrap_or(0) > 0)
+    }
+
+    pub async fn acknowledge(
+        location: &RecordId,
+        production: &RecordId,
+        person: &RecordId,
+    ) -> Result<(), Error> {
+        DB.query(
+            "DELETE house_rules_ack WHERE location = $location AND production = $production AND person = $person;
+             CREATE house_rules_ack SET location = $location, production = $production,
+                 person = $person, acknowledged_at = time::now();",
+        )
+        .bind(("location", location.clone()))
+        .bind(("production", production.clone()))
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to record acknowledgment: {}", e)))?
+        .check()?;
+        Ok(())
+    }
+
+    /// Every accepted member of the production and whether they've
+    /// acknowledged the location's current rules, key roles first
+    pub async fn acknowledgments(
+        production: &RecordId,
+        location: &BookedLocation,
+    ) -> Result<Vec<Acknowledgment>, Error> {
+        let rows: Vec<AckRow> = DB
+            .query(
+                "SELECT <string> person AS person, acknowledged_at FROM house_rules_ack
+                 WHERE location = $location AND production = $production",
+            )
+            .bind(("location", location.id.clone()))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+
+        let mut acknowledgments: Vec<Acknowledgment> = ProductionModel::get_members(production)
+            .await?
+            .into_iter()
+            .filter(|m| m.member_type == "person" && m.invitation_status == "accepted")
+            .map(|m| {
+                let roles = m.production_roles.unwrap_or_default();
+                let acknowledged_at = rows
+                    .iter()
+                    .find(|r| r.person == m.id)
+                    .map(|r| r.acknowledged_at)
+                    .filter(|at| {
+                        location
+                            .house_rules_updated_at
+                            .is_none_or(|updated| *at >= updated)
+                    });
+                Acknowledgment {
+                    key_role: is_key_role(&roles),
+                    person_id: m.id,
+                    name: m.name,
+                    roles,
+                    acknowledged_at,
+                }
+            })
+            .collect();
+        acknowledgments.sort_by_key(|a| !a.key_role);
+        Ok(acknowledgments)
+    }
+
+    /// Key-role members who still have to acknowledge the rules at the
+    /// location a shoot day is booked at. Empty when the location has no rules.
+    pub async fn pending_for_day(
+        production: &RecordId,
+        location: &RecordId,
+    ) -> Result<Vec<String>, Error> {
+        let booked = Self::booked_locations(production).await?;
+        let Some(location) = booked.iter().find(|l| &l.id == location) else {
+            return Ok(Vec::new());
+        };
+        if location.house_rules.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(pending_key_people(
+            &Self::acknowledgments(production, location).await?,
+        ))
+    }
+
+    pub async fn delete_for_location(location: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE house_rules_ack WHERE location = $location; UPDATE shoot_day SET location = NONE WHERE location = $location")
+            .bind(("location", location.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete house rule acknowledgments: {}", e)))?;
+        Ok(())
+    }
+}
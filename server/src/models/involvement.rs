@@ -46,6 +46,61 @@ pub struct InvolvementWithPerson {
     pub person_verification_status: Option<String>,
 }
 
+/// A person's credits, newest production first. `person` is a SurrealQL
+/// expression for the person's id, e.g. `$person` or `$parent.id` in a preload.
+pub fn person_credits_query(person: &str) -> String {
+    format!(
+        "SELECT
+            id,
+            role,
+            relation_type,
+            department,
+            credit_type,
+            verification_status,
+            source,
+            out.id AS production_id,
+            out.title AS production_title,
+            out.slug AS production_slug,
+            out.`type` AS production_type,
+            IF out.poster_photo IS NOT NONE THEN out.poster_photo ELSE out.poster_url END AS poster_url,
+            out.tmdb_url AS tmdb_url,
+            out.tmdb_id AS tmdb_id,
+            out.media_type AS media_type,
+            out.release_date AS release_date,
+            count(out<-member_of[WHERE role = 'owner']) > 0 AS is_claimed,
+            out.release_date IS NONE AS has_no_date
+        FROM involvement
+        WHERE in = {}
+            AND verification_status != 'rejected'
+        ORDER BY has_no_date DESC, release_date DESC, production_title ASC",
+        person
+    )
+}
+
+/// A production's cast and crew credits. `production` is a SurrealQL
+/// expression for the production's id.
+pub fn production_credits_query(production: &str) -> String {
+    format!(
+        "SELECT
+            id,
+            role,
+            relation_type,
+            department,
+            credit_type,
+            verification_status,
+            in.id AS person_id,
+            in.name AS person_name,
+            in.username AS person_username,
+            in.profile.avatar AS person_avatar,
+            in.verification_status AS person_verification_status
+        FROM involvement
+        WHERE out = {}
+            AND verification_status != 'rejected'
+        ORDER BY relation_type ASC, role ASC",
+        production
+    )
+}
+
 pub struct InvolvementModel;
 
 /// Parse a "table:key" string into a RecordId
@@ -127,39 +182,9 @@ impl InvolvementModel {
 
         let person_rid = to_record_id(person_id);
 
-        let query = r#"
-            SELECT
-                id,
-                role,
-                relation_type,
-                department,
-                credit_type,
-                verification_status,
-                source,
-                out.id AS production_id,
-                out.title AS production_title,
-                out.slug AS production_slug,
-                out.`type` AS production_type,
-                IF out.poster_photo IS NOT NONE THEN out.poster_photo ELSE out.poster_url END AS poster_url,
-                out.tmdb_url AS tmdb_url,
-                out.tmdb_id AS tmdb_id,
-                out.media_type AS media_type,
-                out.release_date AS release_date,
-                count(out<-member_of[WHERE role = 'owner']) > 0 AS is_claimed,
-                out.release_date IS NONE AS has_no_date
-            FROM involvement
-            WHERE in = $person
-                AND verification_status != 'rejected'
-            ORDER BY has_no_date DESC, release_date DESC, production_title ASC
-        "#;
+        let query = person_credits_query("$person");
 
-        let mut result = DB
-            .query(query)
-            .bind(("person", person_rid))
-            .await
-            .map_err(|e| {
-                Error::Database(format!("Failed to fetch person involvements: {}", e))
-            })?;
+        let mut result = DB.query(&query).bind(("person", person_rid)).await?;
 
         let involvements: Vec<InvolvementWithProduction> = result.take(0)?;
         debug!("Found {} involvements for person", involvements.len());
@@ -172,27 +197,10 @@ impl InvolvementModel {
     ) -> Result<Vec<InvolvementWithPerson>, Error> {
         debug!("Fetching involvements for production: {:?}", production_id);
 
-        let query = r#"
-            SELECT
-                id,
-                role,
-                relation_type,
-                department,
-                credit_type,
-                verification_status,
-                in.id AS person_id,
-                in.name AS person_name,
-                in.username AS person_username,
-                in.profile.avatar AS person_avatar,
-                in.verification_status AS person_verification_status
-            FROM involvement
-            WHERE out = $production_id
-                AND verification_status != 'rejected'
-            ORDER BY relation_type ASC, role ASC
-        "#;
+        let query = production_credits_query("$production_id");
 
         let mut result = DB
-            .query(query)
+            .query(&query)
             .bind(("production_id", production_id.clone()))
             .await
             .map_err(|e| {
@@ -3,6 +3,7 @@ use crate::error::Error;
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use crate::record_id_ext::RecordIdExt;
+use crate::validation::{FieldErrors, Validate};
 use surrealdb::types::RecordId;
 use tracing::debug;
 
@@ -119,6 +120,8 @@ pub struct ApplicationView {
     pub cover_letter: Option<String>,
     pub status: String,
     pub applied_at: String,
+    /// Agency that submitted the applicant, if any
+    pub agency_name: Option<String>,
 }
 
 /// User's own application view
@@ -134,6 +137,77 @@ pub struct UserApplicationView {
     pub applied_at: String,
 }
 
+/// The "Post a job" form as submitted. Roles arrive as parallel arrays, one
+/// entry per role block on the form.
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateJobForm {
+    pub title: String,
+    pub description: String,
+    pub location: Option<String>,
+    pub post_as: Option<String>,
+    pub related_production: Option<String>,
+    pub contact_name: Option<String>,
+    pub contact_email: Option<String>,
+    pub contact_phone: Option<String>,
+    pub contact_website: Option<String>,
+    pub applications_enabled: Option<String>,
+    pub expires_in: String,
+    #[serde(default, rename = "role_title[]")]
+    pub role_title: Vec<String>,
+    #[serde(default, rename = "role_description[]")]
+    pub role_description: Vec<String>,
+    #[serde(default, rename = "role_rate_type[]")]
+    pub role_rate_type: Vec<String>,
+    #[serde(default, rename = "role_rate_amount[]")]
+    pub role_rate_amount: Vec<String>,
+    #[serde(default, rename = "role_location[]")]
+    pub role_location: Vec<String>,
+}
+
+impl CreateJobForm {
+    /// The roles that were given a title
+    pub fn roles(&self) -> Vec<CreateJobRoleData> {
+        self.role_title
+            .iter()
+            .enumerate()
+            .filter(|(_, title)| !title.trim().is_empty())
+            .map(|(i, title)| CreateJobRoleData {
+                title: title.clone(),
+                description: non_empty(self.role_description.get(i)),
+                rate_type: self
+                    .role_rate_type
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| "TBD".to_string()),
+                rate_amount: non_empty(self.role_rate_amount.get(i)),
+                location_override: non_empty(self.role_location.get(i)),
+            })
+            .collect()
+    }
+}
+
+fn non_empty(value: Option<&String>) -> Option<String> {
+    value.filter(|s| !s.is_empty()).cloned()
+}
+
+impl Validate for CreateJobForm {
+    fn validate(&self) -> FieldErrors {
+        let mut errors = FieldErrors::new();
+        errors.required("title", &self.title, "Title");
+        errors.max_chars("title", &self.title, 200, "Title");
+        errors.required("description", &self.description, "Description");
+        errors.email("contact_email", self.contact_email.as_deref().unwrap_or(""));
+        errors.url(
+            "contact_website",
+            self.contact_website.as_deref().unwrap_or(""),
+        );
+        if self.roles().is_empty() {
+            errors.add("roles", "At least one role is required");
+        }
+        errors
+    }
+}
+
 /// Data to create a job posting
 #[derive(Debug, Clone)]
 pub struct CreateJobData {
@@ -314,8 +388,8 @@ impl JobModel {
                     (IF string::lowercase(title ?? '') CONTAINS string::lowercase($search ?? '') THEN 50 ELSE 0 END)
                     + (IF string::lowercase(description ?? '') CONTAINS string::lowercase($search ?? '') THEN 20 ELSE 0 END)
                     + (IF string::lowercase(location ?? '') CONTAINS string::lowercase($search ?? '') THEN 20 ELSE 0 END)
-                    + (IF embedding IS NOT NONE AND $has_embedding = true
-                        THEN vector::similarity::cosine(embedding, $query_embedding) * 30
+                    + (IF (embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                        THEN vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) * 30
                         ELSE 0
                     END)
                 ) AS _score"
@@ -337,7 +411,7 @@ impl JobModel {
                 text_or_vector.push("string::lowercase(string::join(' ', roles.*.title)) CONTAINS string::lowercase($search)".to_string());
             }
             if has_embedding {
-                text_or_vector.push(format!("(embedding IS NOT NONE AND $has_embedding = true AND vector::similarity::cosine(embedding, $query_embedding) > {})", crate::config::search_weights().vector_threshold));
+                text_or_vector.push(format!("((embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true AND vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) > {})", crate::config::search_config().jobs.vector_threshold));
             }
             query.push_str(&format!(" AND ({})", text_or_vector.join(" OR ")));
         }
@@ -352,7 +426,7 @@ impl JobModel {
             query.push_str(&format!(" START {}", offset));
         }
 
-        let mut db_query = DB.query(&query);
+        let mut db_query = crate::db::reader().query(&query);
         if let Some(s) = search {
             db_query = db_query.bind(("search", s.to_string()));
         }
@@ -587,7 +661,17 @@ impl JobModel {
         validate_record_key(key)?;
         let job_id = RecordId::new("job_posting", key);
 
-        // Delete applications
+        // Delete self-tape requests, submissions and their videos
+        crate::models::selftape::SelfTapeModel::delete_for_job(&job_id).await?;
+
+        // Delete shortlists for its roles
+        crate::models::shortlist::ShortlistModel::delete_for_job(&job_id).await?;
+
+        // Delete agency submissions and applications
+        DB.query("DELETE FROM talent_submission WHERE job = $job")
+            .bind(("job", job_id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete submissions: {}", e)))?;
         let delete_apps = format!("DELETE FROM application WHERE out = {}", job_id.display());
         DB.query(&delete_apps).await.map_err(|e| Error::Database(format!("Failed to delete applications: {}", e)))?;
 
@@ -620,6 +704,18 @@ impl JobModel {
         let person_record = parse_record_id(person_id)?;
         let job_record = parse_record_id(job_id)?;
 
+        // People blocked by the poster can't apply to their postings
+        let mut poster_result = DB.query("SELECT VALUE posted_by FROM ONLY $job")
+            .bind(("job", job_record.clone()))
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?;
+        let poster: Option<RecordId> = poster_result.take(0)?;
+        if let Some(poster) = poster
+            && crate::models::block::BlockModel::has_blocked(&poster, &person_record).await?
+        {
+            return Err(Error::Forbidden);
+        }
+
         // Check not already applied to this role
         let check = format!(
             "SELECT count() AS count FROM application WHERE in = {} AND out = {} AND role_title = $role_title AND status != 'withdrawn' GROUP ALL",
@@ -690,7 +786,8 @@ impl JobModel {
                 role_title,
                 cover_letter,
                 status,
-                <string> applied_at AS applied_at
+                <string> applied_at AS applied_at,
+                submitted_by.name AS agency_name
             FROM application
             WHERE out = {}
             AND status != 'withdrawn'
@@ -713,6 +810,7 @@ impl JobModel {
             cover_letter: r.get("cover_letter").and_then(|v| v.as_str()).map(String::from),
             status: r.get("status").and_then(|v| v.as_str()).unwrap_or("submitted").to_string(),
             applied_at: r.get("applied_at").and_then(|v| v.as_str()).unwrap_or("").to_string(),
+            agency_name: r.get("agency_name").and_then(|v| v.as_str()).map(String::from),
         }}).collect())
     }
 
@@ -0,0 +1,275 @@
+//! Legal holds
+//!
+//! A production's owners and admins put the whole production, or one of its
+//! scripts or deliverables, on hold when it may be needed as evidence. While
+//! a hold is in place nothing it covers can be deleted: the production, the
+//! document, its attachments or their stored files. Retention rules skip
+//! applications to job postings for a held production. A hold stays on
+//! record once lifted, and placing it, lifting it and every deletion it
+//! refused are logged as audit entries.
+
+use crate::{db::DB, error::Error, record_id_ext::RecordIdExt};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{info, warn};
+
+pub const MAX_REASON_CHARS: usize = 500;
+
+/// What a hold covers, as picked on the legal holds page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HoldTarget {
+    /// The production and everything in it
+    Production,
+    /// One script version, by key
+    Script(String),
+    /// One deliverable and its attachments, by key
+    Deliverable(String),
+}
+
+impl HoldTarget {
+    /// "production", "script:<key>" or "deliverable:<key>"
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        let invalid = || Error::Validation("Choose what to put on hold".to_string());
+        let value = value.trim();
+        if value == "production" {
+            return Ok(Self::Production);
+        }
+        let (kind, key) = value.split_once(':').ok_or_else(invalid)?;
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(invalid());
+        }
+        match kind {
+            "script" => Ok(Self::Script(key.to_string())),
+            "deliverable" => Ok(Self::Deliverable(key.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// The record held: the production itself or the document
+    pub fn record_id(&self, production: &RecordId) -> RecordId {
+        match self {
+            Self::Production => production.clone(),
+            Self::Script(key) => RecordId::new("production_script", key.as_str()),
+            Self::Deliverable(key) => RecordId::new("deliverable", key.as_str()),
+        }
+    }
+}
+
+/// A hold's reason as submitted; it's how people later tell holds apart
+pub fn parse_reason(reason: &str) -> Result<String, Error> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(Error::Validation(
+            "Give a reason, such as the matter or counsel's reference".to_string(),
+        ));
+    }
+    if reason.chars().count() > MAX_REASON_CHARS {
+        return Err(Error::Validation(format!(
+            "Keep the reason under {} characters",
+            MAX_REASON_CHARS
+        )));
+    }
+    Ok(reason.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct LegalHold {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub target: RecordId,
+    /// "production", "production_script" or "deliverable"
+    pub target_kind: String,
+    /// The document's title, or `None` for the production itself
+    pub target_title: Option<String>,
+    pub reason: String,
+    pub placed_by_name: Option<String>,
+    pub placed_at: DateTime<Utc>,
+    pub lifted_by_name: Option<String>,
+    pub lifted_at: Option<DateTime<Utc>>,
+}
+
+impl LegalHold {
+    pub fn is_active(&self) -> bool {
+        self.lifted_at.is_none()
+    }
+
+    /// "Whole production", "Script: Draft 3" or "Deliverable: Final master"
+    pub fn target_label(&self) -> String {
+        let title = self.target_title.as_deref().unwrap_or("(deleted)");
+        match self.target_kind.as_str() {
+            "production" => "Whole production".to_string(),
+            "production_script" => format!("Script: {}", title),
+            "deliverable" => format!("Deliverable: {}", title),
+            _ => title.to_string(),
+        }
+    }
+}
+
+/// One audit entry: a hold placed or lifted, or a deletion it refused
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct LegalHoldEvent {
+    pub action: String,
+    pub reason: Option<String>,
+    pub detail: Option<String>,
+    /// `None` for scheduled tasks
+    pub actor_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+const HOLD_FIELDS: &str = "id, production, target, <string> type::table(target) AS target_kind,
+    IF target = production THEN NONE ELSE target.title END AS target_title, reason,
+    placed_by.name ?? placed_by.username AS placed_by_name, placed_at,
+    lifted_by.name ?? lifted_by.username AS lifted_by_name, lifted_at";
+
+pub struct LegalHoldModel;
+
+impl LegalHoldModel {
+    /// Put `target` on hold. A record can only have one hold in place.
+    pub async fn place(
+        production: &RecordId,
+        target: &RecordId,
+        reason: &str,
+        placed_by: &RecordId,
+    ) -> Result<(), Error> {
+        let existing: Option<RecordId> = DB
+            .query(
+                "SELECT VALUE id FROM legal_hold
+                 WHERE target = $target AND lifted_at = NONE LIMIT 1",
+            )
+            .bind(("target", target.clone()))
+            .await?
+            .take(0)?;
+        if existing.is_some() {
+            return Err(Error::Conflict("That's already on hold".to_string()));
+        }
+
+        DB.query(
+            "LET $hold = CREATE ONLY legal_hold SET production = $production, target = $target,
+                reason = $reason, placed_by = $placed_by;
+             CREATE legal_hold_event SET hold = $hold.id, production = $production,
+                action = 'placed', actor = $placed_by;",
+        )
+        .bind(("production", production.clone()))
+        .bind(("target", target.clone()))
+        .bind(("reason", reason.to_string()))
+        .bind(("placed_by", placed_by.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to place legal hold: {}", e)))?
+        .check()?;
+        info!(
+            "Legal hold placed on {} by {}",
+            target.display(),
+            placed_by.display()
+        );
+        Ok(())
+    }
+
+    /// Lift one of the production's holds; it stays listed as lifted
+    pub async fn lift(
+        production: &RecordId,
+        hold_id: &str,
+        lifted_by: &RecordId,
+    ) -> Result<(), Error> {
+        let lifted: Option<RecordId> = DB
+            .query(
+                "LET $lifted = UPDATE $id SET lifted_at = time::now(), lifted_by = $lifted_by
+                    WHERE production = $production AND lifted_at = NONE RETURN VALUE id;
+                 IF $lifted {
+                    CREATE legal_hold_event SET hold = $id, production = $production,
+                        action = 'lifted', actor = $lifted_by;
+                 };
+                 RETURN $lifted[0];",
+            )
+            .bind(("id", RecordId::new("legal_hold", hold_id)))
+            .bind(("production", production.clone()))
+            .bind(("lifted_by", lifted_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to lift legal hold: {}", e)))?
+            .take(2)?;
+        let lifted = lifted.ok_or(Error::NotFound)?;
+        info!(
+            "Legal hold {} lifted by {}",
+            lifted.display(),
+            lifted_by.display()
+        );
+        Ok(())
+    }
+
+    /// The production's holds, in place ones first, then newest first
+    pub async fn for_production(production: &RecordId) -> Result<Vec<LegalHold>, Error> {
+        let mut holds: Vec<LegalHold> = DB
+            .query(format!(
+                "SELECT {} FROM legal_hold WHERE production = $production
+                 ORDER BY placed_at DESC",
+                HOLD_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        holds.sort_by_key(|hold| !hold.is_active());
+        Ok(holds)
+    }
+
+    /// The production's audit entries, newest first
+    pub async fn history(production: &RecordId) -> Result<Vec<LegalHoldEvent>, Error> {
+        Ok(DB
+            .query(
+                "SELECT action, hold.reason AS reason, detail,
+                    actor.name ?? actor.username AS actor_name, created_at
+                 FROM legal_hold_event WHERE production = $production
+                 ORDER BY created_at DESC LIMIT 100",
+            )
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Refuse to delete `target` — the production itself and everything in
+    /// it when `None` — while a hold covers it. A refusal is logged against
+    /// the hold, with `what` ("production", "script", "deliverable" or
+    /// "file") saying what was to be deleted.
+    pub async fn ensure_deletable(
+        production: &RecordId,
+        target: Option<&RecordId>,
+        actor: Option<&RecordId>,
+        what: &str,
+    ) -> Result<(), Error> {
+        let hold: Option<RecordId> = DB
+            .query(
+                "SELECT VALUE id FROM legal_hold
+                 WHERE production = $production AND lifted_at = NONE
+                    AND ($target = NONE OR target = $production OR target = $target)
+                 LIMIT 1",
+            )
+            .bind(("production", production.clone()))
+            .bind(("target", target.cloned()))
+            .await?
+            .take(0)?;
+        let Some(hold) = hold else {
+            return Ok(());
+        };
+
+        DB.query(
+            "CREATE legal_hold_event SET hold = $hold, production = $production,
+                action = 'refused', detail = $detail, actor = $actor",
+        )
+        .bind(("hold", hold.clone()))
+        .bind(("production", production.clone()))
+        .bind(("detail", what.to_string()))
+        .bind(("actor", actor.cloned()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to log legal hold: {}", e)))?
+        .check()?;
+        warn!(
+            "Legal hold {} refused deleting {} on {}",
+            hold.display(),
+            what,
+            production.display()
+        );
+        Err(Error::Conflict(format!(
+            "This {} is under legal hold and can't be deleted until the hold is lifted",
+            what
+        )))
+    }
+}
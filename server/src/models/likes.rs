@@ -72,8 +72,7 @@ impl LikesModel {
             .query(query)
             .bind(("person_id", person_id.clone()))
             .bind(("target_id", target_id.clone()))
-            .await
-            .map_err(|e| Error::Database(format!("Failed to check like: {}", e)))?;
+            .await?;
 
         let count: Option<serde_json::Value> = result.take(0)?;
         Ok(count
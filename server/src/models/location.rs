@@ -1,7 +1,9 @@
 use crate::db::DB;
 use crate::error::Error;
 use crate::record_id_ext::RecordIdExt;
+use crate::serde_utils::deserialize_optional_i32;
 use crate::services::embedding::build_location_embedding_text;
+use crate::validation::{FieldErrors, Validate};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use surrealdb::types::{RecordId, SurrealValue};
@@ -38,6 +40,13 @@ pub struct Location {
     pub profile_photo: Option<String>,
     #[serde(default)]
     pub photos: Vec<LocationPhoto>,
+    /// Map coordinates, for distances to a scouting basecamp
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Rules productions shooting here acknowledge, one per entry
+    #[serde(default)]
+    #[surreal(default)]
+    pub house_rules: Option<Vec<String>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub created_by: RecordId,
@@ -63,6 +72,49 @@ pub struct CreateLocationData {
     pub max_capacity: Option<i32>,
 }
 
+/// The "Add location" form as submitted. Also carried back to the form when
+/// it's re-rendered with errors, so nothing typed is lost.
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateLocationForm {
+    pub name: String,
+    pub address: String,
+    pub city: String,
+    pub state: String,
+    pub country: String,
+    pub postal_code: Option<String>,
+    pub description: Option<String>,
+    pub contact_name: String,
+    pub contact_email: String,
+    pub contact_phone: Option<String>,
+    pub is_public: Option<bool>,
+    pub amenities: Option<String>,
+    pub restrictions: Option<String>,
+    pub parking_info: Option<String>,
+    #[serde(deserialize_with = "deserialize_optional_i32")]
+    pub max_capacity: Option<i32>,
+}
+
+impl Validate for CreateLocationForm {
+    fn validate(&self) -> FieldErrors {
+        let mut errors = FieldErrors::new();
+        errors.required("name", &self.name, "Name");
+        errors.max_chars("name", &self.name, 200, "Name");
+        errors.required("address", &self.address, "Address");
+        errors.required("city", &self.city, "City");
+        errors.required("state", &self.state, "State/Province");
+        errors.required("country", &self.country, "Country");
+        let description = self.description.as_deref().unwrap_or("");
+        errors.max_chars("description", description, 5000, "Description");
+        errors.required("contact_name", &self.contact_name, "Contact name");
+        errors.required("contact_email", &self.contact_email, "Contact email");
+        errors.email("contact_email", &self.contact_email);
+        if self.max_capacity.is_some_and(|n| n < 1) {
+            errors.add("max_capacity", "Maximum capacity must be at least 1");
+        }
+        errors
+    }
+}
+
 /// Data for updating an existing location
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateLocationData {
@@ -226,8 +278,8 @@ impl LocationModel {
                     (IF string::lowercase(name ?? '') CONTAINS string::lowercase($filter ?? '') THEN 50 ELSE 0 END)
                     + (IF string::lowercase(city ?? '') CONTAINS string::lowercase($filter ?? '') THEN 30 ELSE 0 END)
                     + (IF string::lowercase(description ?? '') CONTAINS string::lowercase($filter ?? '') THEN 10 ELSE 0 END)
-                    + (IF embedding IS NOT NONE AND $has_embedding = true
-                        THEN vector::similarity::cosine(embedding, $query_embedding) * 30
+                    + (IF (embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                        THEN vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) * 30
                         ELSE 0
                     END)
                 ) AS _score"
@@ -257,7 +309,7 @@ impl LocationModel {
                 text_or_vector.push("string::lowercase(address) CONTAINS string::lowercase($filter)".to_string());
             }
             if has_embedding {
-                text_or_vector.push(format!("(embedding IS NOT NONE AND $has_embedding = true AND vector::similarity::cosine(embedding, $query_embedding) > {})", crate::config::search_weights().vector_threshold));
+                text_or_vector.push(format!("((embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true AND vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) > {})", crate::config::search_config().locations.vector_threshold));
             }
             query.push_str(&format!(" AND ({})", text_or_vector.join(" OR ")));
         }
@@ -281,7 +333,7 @@ impl LocationModel {
             query.push_str(&format!(" START {}", offset));
         }
 
-        let mut db_query = DB.query(&query);
+        let mut db_query = crate::db::reader().query(&query);
 
         if let Some(city) = city {
             db_query = db_query.bind(("city", city.to_string()));
@@ -498,6 +550,18 @@ impl LocationModel {
             .await
             .map_err(|e| Error::Database(format!("Failed to delete rates: {}", e)))?;
 
+        // Take it out of scouting collections
+        DB.query("DELETE scouting_entry WHERE location = $location_id")
+            .bind(("location_id", location_id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete scouting entries: {}", e)))?;
+
+        // Drop its blackouts
+        crate::models::blackout::BlackoutModel::delete_for_location(location_id).await?;
+
+        // Unbook it from shoot days and drop house rule acknowledgments
+        crate::models::house_rules::HouseRulesModel::delete_for_location(location_id).await?;
+
         // Delete the location
         DB.query("DELETE $location_id")
             .bind(("location_id", location_id.clone()))
@@ -512,6 +576,20 @@ impl LocationModel {
         Ok(())
     }
 
+    /// Set or clear a location's map coordinates
+    pub async fn set_coordinates(
+        location_id: &RecordId,
+        point: Option<(f64, f64)>,
+    ) -> Result<(), Error> {
+        DB.query("UPDATE $location_id SET latitude = $latitude, longitude = $longitude")
+            .bind(("location_id", location_id.clone()))
+            .bind(("latitude", point.map(|p| p.0)))
+            .bind(("longitude", point.map(|p| p.1)))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to set coordinates: {}", e)))?;
+        Ok(())
+    }
+
     /// Check if a user can edit a location
     pub async fn can_edit(location_id: &RecordId, user_id: &str) -> Result<bool, Error> {
         debug!(
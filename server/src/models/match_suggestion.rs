@@ -0,0 +1,347 @@
+//! Suggested matches for job postings ("people you should meet")
+//!
+//! Once a week per posting, the daily `match_suggestions` task embeds each
+//! open posting and stores the closest few profiles that haven't applied or been suggested for
+//! it before. Posters see them on `/my-jobs` and in their weekly digest, and
+//! can mark each one as a good match or not relevant. That feedback moves the
+//! next week's query toward the profiles they liked and away from the rest.
+
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, info, warn};
+
+use crate::{
+    config::search_config,
+    db::DB,
+    error::{Error, Result},
+    models::block::BlockModel,
+    record_id_ext::RecordIdExt,
+    services::embedding::{self, Quantized},
+};
+
+/// New suggestions stored per posting each week
+pub const SUGGESTIONS_PER_JOB: usize = 5;
+
+/// Accepted feedback values
+pub const FEEDBACK: &[&str] = &["good", "not_relevant"];
+
+/// How far liked and disliked profiles pull the query (Rocchio weights)
+const LIKED_WEIGHT: f32 = 0.5;
+const DISLIKED_WEIGHT: f32 = 0.25;
+
+/// Days between rounds of suggestions for the same posting
+pub const INTERVAL_DAYS: i64 = 7;
+
+/// Candidates fetched per posting before dropping excluded people
+const CANDIDATE_WINDOW: usize = 50;
+
+/// The text a posting is embedded from
+pub fn job_text(title: &str, description: &str, role_titles: &[String]) -> String {
+    let mut text = title.trim().to_string();
+    if !role_titles.is_empty() {
+        text.push_str("\nRoles: ");
+        text.push_str(&role_titles.join(", "));
+    }
+    if !description.trim().is_empty() {
+        text.push('\n');
+        text.push_str(description.trim());
+    }
+    text
+}
+
+fn normalized(vector: &[f32]) -> Option<Vec<f32>> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return None;
+    }
+    Some(vector.iter().map(|v| v / norm).collect())
+}
+
+/// Steer a posting's embedding with the poster's feedback: add the average of
+/// the profiles they liked and subtract the average of those they didn't.
+/// Every vector is scaled to unit length first, so quantized and full
+/// precision profiles count the same. Vectors of the wrong length are ignored.
+pub fn tune_query(base: &[f32], liked: &[Vec<f32>], disliked: &[Vec<f32>]) -> Vec<f32> {
+    let Some(mut query) = normalized(base) else {
+        return base.to_vec();
+    };
+
+    for (vectors, weight) in [(liked, LIKED_WEIGHT), (disliked, -DISLIKED_WEIGHT)] {
+        let units: Vec<Vec<f32>> = vectors
+            .iter()
+            .filter(|v| v.len() == query.len())
+            .filter_map(|v| normalized(v))
+            .collect();
+        if units.is_empty() {
+            continue;
+        }
+        let scale = weight / units.len() as f32;
+        for unit in &units {
+            for (q, v) in query.iter_mut().zip(unit) {
+                *q += v * scale;
+            }
+        }
+    }
+
+    normalized(&query).unwrap_or_else(|| base.to_vec())
+}
+
+/// A suggestion as shown to the poster
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct SuggestedMatch {
+    pub id: RecordId,
+    pub job: RecordId,
+    pub job_title: String,
+    pub username: String,
+    pub name: Option<String>,
+    pub headline: Option<String>,
+    pub avatar: Option<String>,
+    pub location: Option<String>,
+    pub score: f64,
+}
+
+impl SuggestedMatch {
+    pub fn key(&self) -> String {
+        self.id.key_string()
+    }
+
+    pub fn job_key(&self) -> String {
+        self.job.key_string()
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.name
+            .as_deref()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or(&self.username)
+    }
+
+    /// Similarity as a whole percentage
+    pub fn percent(&self) -> i64 {
+        (self.score.clamp(0.0, 1.0) * 100.0).round() as i64
+    }
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct JobRow {
+    id: RecordId,
+    title: String,
+    description: String,
+    posted_by: RecordId,
+    role_titles: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct VectorRow {
+    feedback: String,
+    embedding: Option<Vec<f32>>,
+    embedding_q: Option<Vec<i64>>,
+    embedding_scale: Option<f32>,
+}
+
+impl VectorRow {
+    fn vector(self) -> Option<Vec<f32>> {
+        if let Some(embedding) = self.embedding {
+            return Some(embedding);
+        }
+        let values = self.embedding_q?;
+        Some(
+            Quantized {
+                values: values.iter().map(|&v| v.clamp(-127, 127) as i8).collect(),
+                scale: self.embedding_scale.unwrap_or(1.0),
+            }
+            .dequantize(),
+        )
+    }
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct CandidateRow {
+    id: RecordId,
+    score: f64,
+}
+
+const SUGGESTION_FIELDS: &str = "id, job, job.title AS job_title, person.username AS username,
+    person.name AS name, person.profile.headline AS headline, person.profile.avatar AS avatar,
+    person.profile.location AS location, score";
+
+pub struct MatchSuggestionModel;
+
+impl MatchSuggestionModel {
+    /// Find new suggestions for every open posting that hasn't had any in the
+    /// last week. Run daily by the `match_suggestions` scheduled task, so a
+    /// restart never pushes a round back by more than a day.
+    pub async fn refresh_all() -> Result<()> {
+        if !embedding::is_initialized() {
+            debug!("Skipping match suggestions, the embedding model isn't loaded");
+            return Ok(());
+        }
+
+        let jobs: Vec<JobRow> = DB
+            .query(format!(
+                "SELECT id, title, description, posted_by, roles.title ?? [] AS role_titles
+                FROM job_posting
+                WHERE status = 'open' AND expires_at > time::now()
+                    AND count(SELECT id FROM match_suggestion
+                        WHERE job = $parent.id AND created_at > time::now() - {INTERVAL_DAYS}d) = 0"
+            ))
+            .await?
+            .take(0)?;
+
+        let mut total = 0;
+        for job in &jobs {
+            match Self::refresh_job(job).await {
+                Ok(count) => total += count,
+                Err(e) => warn!("Failed to suggest matches for {}: {}", job.id.display(), e),
+            }
+        }
+        info!(
+            "Stored {} match suggestions across {} open postings",
+            total,
+            jobs.len()
+        );
+        Ok(())
+    }
+
+    async fn refresh_job(job: &JobRow) -> Result<usize> {
+        let text = job_text(&job.title, &job.description, &job.role_titles);
+        let base = embedding::generate_embedding_async(&text)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to embed posting: {}", e)))?;
+
+        let mut result = DB
+            .query(
+                "SELECT feedback, person.embedding AS embedding, person.embedding_q AS embedding_q,
+                    person.embedding_scale AS embedding_scale
+                FROM match_suggestion WHERE job = $job AND feedback IS NOT NONE",
+            )
+            .query("SELECT VALUE person FROM match_suggestion WHERE job = $job")
+            .query("SELECT VALUE in FROM application WHERE out = $job")
+            .bind(("job", job.id.clone()))
+            .await?;
+        let feedback: Vec<VectorRow> = result.take(0)?;
+        let suggested: Vec<RecordId> = result.take(1)?;
+        let applicants: Vec<RecordId> = result.take(2)?;
+
+        let (mut liked, mut disliked) = (Vec::new(), Vec::new());
+        for row in feedback {
+            let good = row.feedback == "good";
+            if let Some(vector) = row.vector() {
+                if good {
+                    liked.push(vector)
+                } else {
+                    disliked.push(vector)
+                }
+            }
+        }
+        let query = tune_query(&base, &liked, &disliked);
+
+        // People the poster has blocked or muted, or who blocked them. Blocks
+        // are between people, so there are none for organization postings.
+        let hidden: HashSet<String> = BlockModel::hidden_ids(&job.posted_by)
+            .await?
+            .into_iter()
+            .collect();
+
+        let candidates: Vec<CandidateRow> = DB
+            .query(format!(
+                "SELECT id, vector::similarity::cosine(embedding ?? embedding_q, $query) AS score
+                FROM person
+                WHERE (embedding ?? embedding_q) IS NOT NONE
+                    AND is_minor != true
+                    AND (visibility ?? 'public') != 'hidden'
+                    AND id != $poster
+                    AND vector::similarity::cosine(embedding ?? embedding_q, $query) > {threshold}
+                ORDER BY score DESC
+                LIMIT {CANDIDATE_WINDOW}",
+                threshold = search_config().people.vector_threshold,
+            ))
+            .bind(("query", query))
+            .bind(("poster", job.posted_by.clone()))
+            .await?
+            .take(0)?;
+
+        let mut stored = 0;
+        for candidate in candidates {
+            if stored == SUGGESTIONS_PER_JOB {
+                break;
+            }
+            if suggested.contains(&candidate.id)
+                || applicants.contains(&candidate.id)
+                || hidden.contains(&candidate.id.to_raw_string())
+            {
+                continue;
+            }
+            DB.query("CREATE match_suggestion SET job = $job, person = $person, score = $score")
+                .bind(("job", job.id.clone()))
+                .bind(("person", candidate.id))
+                .bind(("score", candidate.score))
+                .await?
+                .check()?;
+            stored += 1;
+        }
+
+        if stored > 0 {
+            debug!("Suggested {} people for {}", stored, job.id.display());
+        }
+        Ok(stored)
+    }
+
+    /// Suggestions for these postings still waiting on feedback, best first
+    pub async fn pending_for_jobs(jobs: &[RecordId]) -> Result<Vec<SuggestedMatch>> {
+        if jobs.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(DB
+            .query(format!(
+                "SELECT {SUGGESTION_FIELDS} FROM match_suggestion
+                WHERE job INSIDE $jobs AND feedback IS NONE AND job.status = 'open'
+                ORDER BY score DESC"
+            ))
+            .bind(("jobs", jobs.to_vec()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Suggestions made in the last `days` for postings by this person or an
+    /// organization they run, for the weekly digest
+    pub async fn recent_for_poster(person: &RecordId, days: i64) -> Result<Vec<SuggestedMatch>> {
+        Ok(DB
+            .query(format!(
+                "SELECT {SUGGESTION_FIELDS} FROM match_suggestion
+                WHERE created_at > time::now() - {days}d AND feedback IS NONE
+                    AND job.status = 'open'
+                    AND (job.posted_by = $person OR job.posted_by INSIDE (
+                        SELECT VALUE out FROM member_of WHERE in = $person
+                            AND role IN ['owner', 'admin'] AND invitation_status = 'accepted'
+                    ))
+                ORDER BY score DESC"
+            ))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Record the poster's verdict on a suggestion
+    pub async fn set_feedback(job_key: &str, suggestion_key: &str, feedback: &str) -> Result<()> {
+        if !FEEDBACK.contains(&feedback) {
+            return Err(Error::BadRequest(format!("Invalid feedback: {}", feedback)));
+        }
+        let updated: Vec<RecordId> = DB
+            .query(
+                "UPDATE $id SET feedback = $feedback, feedback_at = time::now()
+                WHERE job = $job RETURN VALUE id",
+            )
+            .bind(("id", RecordId::new("match_suggestion", suggestion_key)))
+            .bind(("job", RecordId::new("job_posting", job_key)))
+            .bind(("feedback", feedback.to_string()))
+            .await?
+            .take(0)?;
+        if updated.is_empty() {
+            return Err(Error::NotFound);
+        }
+        Ok(())
+    }
+}
@@ -38,6 +38,15 @@ pub struct Media {
     pub uploaded_by: RecordId,
 }
 
+/// Media someone uploaded, newest first. `person` is a SurrealQL expression
+/// for their id, e.g. `$parent.id` in a preload.
+pub fn uploaded_by_query(person: &str) -> String {
+    format!(
+        "SELECT * FROM media WHERE uploaded_by = {} ORDER BY uploaded_at DESC",
+        person
+    )
+}
+
 /// Media dimensions for images/videos
 #[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
 pub struct MediaDimensions {
@@ -75,7 +84,7 @@ impl Media {
             format!("person:{}", input.uploaded_by)
         };
 
-        // Create the media record using the SDK's create method
+        // Create the media record with a specific ID
         #[derive(serde::Serialize, serde::Deserialize, SurrealValue)]
         struct MediaData {
             media_type: String,
@@ -103,11 +112,11 @@ impl Media {
             uploaded_by: uploaded_by_record,
         };
 
-        // Use the SDK's create method with a specific ID
-        let _result: Option<serde_json::Value> = DB
-            .create(RecordId::new("media", id_part.clone()))
-            .content(data)
+        DB.query("CREATE ONLY $id CONTENT $data")
+            .bind(("id", RecordId::new("media", id_part.clone())))
+            .bind(("data", data))
             .await
+            .and_then(|response| response.check())
             .map_err(|e| Error::database(format!("Failed to create media record: {}", e)))?;
 
         info!("Created media record with ID: {}", media_id);
@@ -1,17 +1,58 @@
 pub mod activity;
 pub mod analytics;
+pub mod audio_reel;
+pub mod blackout;
+pub mod block;
+pub mod budget;
+pub mod call_sheet;
+pub mod cast_sheet;
+pub mod company_credit;
+pub mod completeness;
+pub mod daily_report;
+pub mod deliverable;
 pub mod equipment;
+pub mod equipment_label;
+pub mod equipment_service;
+pub mod feedback;
+pub mod festival_submission;
+pub mod gear_quote;
+pub mod guest_link;
+pub mod house_rules;
 pub mod involvement;
 pub mod job;
+pub mod legal_hold;
 pub mod likes;
 pub mod location;
+pub mod match_suggestion;
 pub mod media;
 pub mod membership;
+pub mod nearby_services;
 pub mod messaging;
 pub mod notification;
+pub mod offer;
+pub mod org_branding;
 pub mod organization;
+pub mod pagination;
 pub mod pending_invitation;
 pub mod person;
+pub mod person_availability;
+pub mod portfolio;
+pub mod preload;
+pub mod press_kit;
 pub mod production;
+pub mod production_gear;
+pub mod production_inbox;
+pub mod reaction;
+pub mod representation;
+pub mod saved_search;
+pub mod scouting;
 pub mod script;
+pub mod search_alert;
+pub mod search_history;
+pub mod selftape;
+pub mod share_link;
+pub mod shortlist;
+pub mod shot_list;
 pub mod system;
+pub mod talent_submission;
+pub mod timecard;
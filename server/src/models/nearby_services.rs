@@ -0,0 +1,171 @@
+//! Nearby services: public organizations of the vendor types a shoot needs
+//! on the day (rental houses, caterers, location services), within a radius
+//! of a location's coordinates. Shown as a panel on location pages and served
+//! as JSON at `/api/locations/{id}/nearby`.
+
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+
+use crate::db::DB;
+use crate::error::Error;
+use crate::units;
+
+/// Organization types counted as services, with the panel heading and the
+/// short name used in the API's `category` parameter
+pub const SERVICE_CATEGORIES: &[ServiceCategory] = &[
+    ServiceCategory {
+        key: "rental",
+        org_type: "Equipment Rental",
+        label: "Rental Houses",
+    },
+    ServiceCategory {
+        key: "catering",
+        org_type: "Catering Company",
+        label: "Catering",
+    },
+    ServiceCategory {
+        key: "locations",
+        org_type: "Location Services",
+        label: "Location Services",
+    },
+    ServiceCategory {
+        key: "sound",
+        org_type: "Sound Studio",
+        label: "Sound Studios",
+    },
+];
+
+pub const DEFAULT_RADIUS_KM: f64 = 50.0;
+pub const MAX_RADIUS_KM: f64 = 200.0;
+
+/// Most services returned per lookup
+const MAX_RESULTS: usize = 40;
+
+pub struct ServiceCategory {
+    pub key: &'static str,
+    pub org_type: &'static str,
+    pub label: &'static str,
+}
+
+/// The category for an API `category` value: its key or organization type
+pub fn category(value: &str) -> Option<&'static ServiceCategory> {
+    let value = value.trim();
+    SERVICE_CATEGORIES
+        .iter()
+        .find(|c| c.key.eq_ignore_ascii_case(value) || c.org_type.eq_ignore_ascii_case(value))
+}
+
+/// A requested radius, within 1 km and `MAX_RADIUS_KM`; the default when unset
+pub fn clamp_radius(radius_km: Option<f64>) -> f64 {
+    match radius_km.filter(|r| r.is_finite()) {
+        Some(r) => r.clamp(1.0, MAX_RADIUS_KM),
+        None => DEFAULT_RADIUS_KM,
+    }
+}
+
+/// Latitude and longitude ranges that contain every point within `radius_km`
+/// of `center`, to narrow the query before exact distances are worked out.
+/// Longitude is unbounded near the poles or when the box crosses 180°.
+pub fn bounding_box(center: (f64, f64), radius_km: f64) -> ((f64, f64), Option<(f64, f64)>) {
+    let (latitude, longitude) = center;
+    let angle = radius_km / units::EARTH_RADIUS_KM;
+    let lat_delta = angle.to_degrees();
+    let latitudes = (
+        (latitude - lat_delta).max(-90.0),
+        (latitude + lat_delta).min(90.0),
+    );
+    if latitudes.0 <= -90.0 || latitudes.1 >= 90.0 {
+        return (latitudes, None);
+    }
+
+    let ratio = angle.sin() / latitude.to_radians().cos();
+    if ratio >= 1.0 {
+        return (latitudes, None);
+    }
+    let lng_delta = ratio.asin().to_degrees();
+    let longitudes = (longitude - lng_delta, longitude + lng_delta);
+    if longitudes.0 < -180.0 || longitudes.1 > 180.0 {
+        return (latitudes, None);
+    }
+    (latitudes, Some(longitudes))
+}
+
+/// One organization near a location
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct NearbyService {
+    pub id: RecordId,
+    pub name: String,
+    pub slug: String,
+    pub org_type: String,
+    pub logo: Option<String>,
+    pub location: Option<String>,
+    pub phone: Option<String>,
+    pub website: Option<String>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub services: Vec<String>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub verified: bool,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Filled in after the query
+    #[serde(default)]
+    #[surreal(default)]
+    pub distance_km: f64,
+}
+
+pub struct NearbyServicesModel;
+
+impl NearbyServicesModel {
+    /// Public service organizations within `radius_km` of `center`, nearest
+    /// first. `categories` narrows to those organization types; empty means
+    /// all of `SERVICE_CATEGORIES`.
+    pub async fn near(
+        center: (f64, f64),
+        radius_km: f64,
+        categories: &[&ServiceCategory],
+    ) -> Result<Vec<NearbyService>, Error> {
+        let org_types: Vec<String> = if categories.is_empty() {
+            SERVICE_CATEGORIES
+                .iter()
+                .map(|c| c.org_type.to_string())
+                .collect()
+        } else {
+            categories.iter().map(|c| c.org_type.to_string()).collect()
+        };
+        let (latitudes, longitudes) = bounding_box(center, radius_km);
+        let longitude_filter = if longitudes.is_some() {
+            "AND longitude >= $min_lng AND longitude <= $max_lng"
+        } else {
+            ""
+        };
+        let (min_lng, max_lng) = longitudes.unwrap_or((-180.0, 180.0));
+
+        let mut services: Vec<NearbyService> = DB
+            .query(format!(
+                "SELECT id, name, slug, type.name AS org_type, logo, location, phone, website,
+                     services, verified ?? false AS verified, latitude, longitude
+                 FROM organization
+                 WHERE public = true AND type.name IN $org_types
+                     AND latitude != NONE AND longitude != NONE
+                     AND latitude >= $min_lat AND latitude <= $max_lat {}",
+                longitude_filter
+            ))
+            .bind(("org_types", org_types))
+            .bind(("min_lat", latitudes.0))
+            .bind(("max_lat", latitudes.1))
+            .bind(("min_lng", min_lng))
+            .bind(("max_lng", max_lng))
+            .await?
+            .take(0)?;
+
+        for service in &mut services {
+            service.distance_km = units::distance_km(center, (service.latitude, service.longitude));
+        }
+        services.retain(|s| s.distance_km <= radius_km);
+        services.sort_by(|a, b| a.distance_km.total_cmp(&b.distance_km));
+        services.truncate(MAX_RESULTS);
+        Ok(services)
+    }
+}
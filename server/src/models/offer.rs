@@ -0,0 +1,610 @@
+//! Offers and bookings
+//!
+//! A production's owners and admins send a person a formal offer for a role:
+//! the dates, the rate and any conditions. The person accepts, declines or
+//! counters with their own rate or dates; the production can take a counter,
+//! revise the terms and send them again, or withdraw. Accepting puts the person
+//! on the production's roster, sets their crew deal when the rate is hourly or
+//! daily, and books the dates so they show as unavailable on their profile.
+
+use crate::{db::DB, error::Error, models::production::ProductionModel, record_id_ext::RecordIdExt};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, info};
+
+/// Rate types an offer can use, with their labels
+pub const RATE_TYPES: &[(&str, &str)] = &[
+    ("daily", "Per day"),
+    ("hourly", "Per hour"),
+    ("weekly", "Per week"),
+    ("flat", "Flat fee"),
+];
+
+pub const MAX_ROLE_CHARS: usize = 120;
+
+pub const MAX_CONDITIONS_CHARS: usize = 2000;
+
+pub const MAX_NOTE_CHARS: usize = 1000;
+
+/// Longest booking one offer can cover
+pub const MAX_DAYS: i64 = 366;
+
+/// Something either side can do to an offer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfferAction {
+    /// The person takes the offer as sent
+    Accept,
+    /// The person turns it down
+    Decline,
+    /// The person proposes their own rate or dates
+    Counter,
+    /// The production takes the person's counter
+    AcceptCounter,
+    /// The production sends new terms
+    Revise,
+    /// The production takes the offer back
+    Withdraw,
+}
+
+/// The status an offer moves to, or `None` when the action isn't open to it
+pub fn next_status(current: &str, action: OfferAction) -> Option<&'static str> {
+    match (current, action) {
+        ("sent", OfferAction::Accept) => Some("accepted"),
+        ("sent", OfferAction::Decline) => Some("declined"),
+        ("sent", OfferAction::Counter) => Some("countered"),
+        ("countered", OfferAction::AcceptCounter) => Some("accepted"),
+        ("sent" | "countered", OfferAction::Revise) => Some("sent"),
+        ("sent" | "countered", OfferAction::Withdraw) => Some("withdrawn"),
+        _ => None,
+    }
+}
+
+fn parse_date(value: &str, what: &str) -> Result<NaiveDate, Error> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| Error::Validation(format!("Enter a valid {} date", what)))
+}
+
+fn parse_dates(start: &str, end: &str, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), Error> {
+    let start = parse_date(start, "start")?;
+    let end = if end.trim().is_empty() { start } else { parse_date(end, "end")? };
+    if start < today {
+        return Err(Error::Validation("The start date has already passed".to_string()));
+    }
+    if end < start {
+        return Err(Error::Validation("The end date can't be before the start date".to_string()));
+    }
+    if (end - start).num_days() >= MAX_DAYS {
+        return Err(Error::Validation("An offer can cover up to a year".to_string()));
+    }
+    Ok((start, end))
+}
+
+fn parse_rate(value: &str) -> Result<f64, Error> {
+    let cleaned: String = value.trim().chars().filter(|c| *c != ',' && *c != '$').collect();
+    match cleaned.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate <= 10_000_000.0 => Ok(rate),
+        _ => Err(Error::Validation("Enter a rate greater than zero".to_string())),
+    }
+}
+
+fn clean_text(value: &str, max: usize, what: &str) -> Result<Option<String>, Error> {
+    let value = value.trim();
+    if value.chars().count() > max {
+        return Err(Error::Validation(format!("{} can be up to {} characters", what, max)));
+    }
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/// What's on offer, checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct OfferTerms {
+    pub role_title: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub rate_amount: f64,
+    pub rate_type: String,
+    pub conditions: Option<String>,
+}
+
+impl OfferTerms {
+    /// Check submitted terms. An empty end date books a single day.
+    pub fn parse(
+        role_title: &str,
+        start_date: &str,
+        end_date: &str,
+        rate_amount: &str,
+        rate_type: &str,
+        conditions: &str,
+        today: NaiveDate,
+    ) -> Result<Self, Error> {
+        let role_title = clean_text(role_title, MAX_ROLE_CHARS, "The role")?
+            .ok_or_else(|| Error::Validation("Name the role you're offering".to_string()))?;
+        let (start_date, end_date) = parse_dates(start_date, end_date, today)?;
+        if !RATE_TYPES.iter().any(|(v, _)| *v == rate_type) {
+            return Err(Error::Validation("Choose how the rate is paid".to_string()));
+        }
+        Ok(Self {
+            role_title,
+            start_date,
+            end_date,
+            rate_amount: parse_rate(rate_amount)?,
+            rate_type: rate_type.to_string(),
+            conditions: clean_text(conditions, MAX_CONDITIONS_CHARS, "Conditions")?,
+        })
+    }
+}
+
+/// A person's counter: a different rate, different dates, or both
+#[derive(Debug, Clone, PartialEq)]
+pub struct CounterTerms {
+    pub rate_amount: Option<f64>,
+    pub dates: Option<(NaiveDate, NaiveDate)>,
+    pub note: Option<String>,
+}
+
+impl CounterTerms {
+    /// Check a counter against the offer it answers. Blank fields keep the
+    /// offer's terms; at least one has to change.
+    pub fn parse(
+        offer: &Offer,
+        rate_amount: &str,
+        start_date: &str,
+        end_date: &str,
+        note: &str,
+        today: NaiveDate,
+    ) -> Result<Self, Error> {
+        let rate_amount = if rate_amount.trim().is_empty() {
+            None
+        } else {
+            Some(parse_rate(rate_amount)?).filter(|r| *r != offer.rate_amount)
+        };
+        let dates = if start_date.trim().is_empty() && end_date.trim().is_empty() {
+            None
+        } else {
+            let start = if start_date.trim().is_empty() { offer.start_date.as_str() } else { start_date };
+            let end = if end_date.trim().is_empty() { offer.end_date.as_str() } else { end_date };
+            let (start, end) = parse_dates(start, end, today)?;
+            let unchanged = start.to_string() == offer.start_date && end.to_string() == offer.end_date;
+            (!unchanged).then_some((start, end))
+        };
+        if rate_amount.is_none() && dates.is_none() {
+            return Err(Error::Validation("Propose a different rate or different dates".to_string()));
+        }
+        Ok(Self {
+            rate_amount,
+            dates,
+            note: clean_text(note, MAX_NOTE_CHARS, "Notes")?,
+        })
+    }
+}
+
+fn format_amount(amount: f64) -> String {
+    if amount.fract() == 0.0 {
+        format!("{:.0}", amount)
+    } else {
+        format!("{:.2}", amount)
+    }
+}
+
+/// "450 per day", "12,000 flat"
+pub fn rate_label(amount: f64, rate_type: &str) -> String {
+    let whole = format_amount(amount);
+    let (digits, cents) = whole.split_once('.').map_or((whole.as_str(), None), |(d, c)| (d, Some(c)));
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    if let Some(cents) = cents {
+        grouped = format!("{}.{}", grouped, cents);
+    }
+    match rate_type {
+        "hourly" => format!("{} per hour", grouped),
+        "daily" => format!("{} per day", grouped),
+        "weekly" => format!("{} per week", grouped),
+        _ => format!("{} flat", grouped),
+    }
+}
+
+/// "Mar 3 – Mar 7, 2026", or one date for a single day. Dates that don't
+/// parse are shown as stored.
+pub fn dates_label(start: &str, end: &str) -> String {
+    let (Ok(start_date), Ok(end_date)) = (
+        NaiveDate::parse_from_str(start, "%Y-%m-%d"),
+        NaiveDate::parse_from_str(end, "%Y-%m-%d"),
+    ) else {
+        return format!("{} – {}", start, end);
+    };
+    if start_date == end_date {
+        start_date.format("%b %-d, %Y").to_string()
+    } else if start_date.format("%Y").to_string() == end_date.format("%Y").to_string() {
+        format!("{} – {}", start_date.format("%b %-d"), end_date.format("%b %-d, %Y"))
+    } else {
+        format!("{} – {}", start_date.format("%b %-d, %Y"), end_date.format("%b %-d, %Y"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Offer {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub person: RecordId,
+    pub role_title: String,
+    /// YYYY-MM-DD
+    pub start_date: String,
+    /// YYYY-MM-DD
+    pub end_date: String,
+    pub rate_amount: f64,
+    pub rate_type: String,
+    pub conditions: Option<String>,
+    /// "sent", "accepted", "declined", "countered" or "withdrawn"
+    pub status: String,
+    pub counter_rate_amount: Option<f64>,
+    pub counter_start_date: Option<String>,
+    pub counter_end_date: Option<String>,
+    pub response_note: Option<String>,
+    pub shortlist_entry: Option<RecordId>,
+    pub sent_by: RecordId,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// An offer with the names either side needs to see
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct OfferListing {
+    pub id: RecordId,
+    pub production_title: String,
+    pub production_slug: String,
+    pub person_name: String,
+    pub username: String,
+    pub role_title: String,
+    pub start_date: String,
+    pub end_date: String,
+    pub rate_amount: f64,
+    pub rate_type: String,
+    pub conditions: Option<String>,
+    pub status: String,
+    pub counter_rate_amount: Option<f64>,
+    pub counter_start_date: Option<String>,
+    pub counter_end_date: Option<String>,
+    pub response_note: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+const LISTING_FIELDS: &str = "id, production.title AS production_title, production.slug AS production_slug,
+    person.name ?? person.username AS person_name, person.username AS username,
+    role_title, start_date, end_date, rate_amount, rate_type, conditions, status,
+    counter_rate_amount, counter_start_date, counter_end_date, response_note, updated_at";
+
+/// Dates a person is booked on a production
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Booking {
+    pub production_title: String,
+    pub production_slug: String,
+    pub role_title: String,
+    pub start_date: String,
+    pub end_date: String,
+}
+
+impl Booking {
+    pub fn dates_label(&self) -> String {
+        dates_label(&self.start_date, &self.end_date)
+    }
+}
+
+pub struct OfferModel;
+
+impl OfferModel {
+    pub async fn create(
+        production: &RecordId,
+        person: &RecordId,
+        terms: &OfferTerms,
+        sent_by: &RecordId,
+        shortlist_entry: Option<&RecordId>,
+    ) -> Result<Offer, Error> {
+        let offer: Option<Offer> = DB
+            .query(
+                "CREATE offer SET production = $production, person = $person, role_title = $role_title,
+                    start_date = $start_date, end_date = $end_date, rate_amount = $rate_amount,
+                    rate_type = $rate_type, conditions = $conditions, status = 'sent',
+                    shortlist_entry = $shortlist_entry, sent_by = $sent_by",
+            )
+            .bind(("production", production.clone()))
+            .bind(("person", person.clone()))
+            .bind(("role_title", terms.role_title.clone()))
+            .bind(("start_date", terms.start_date.to_string()))
+            .bind(("end_date", terms.end_date.to_string()))
+            .bind(("rate_amount", terms.rate_amount))
+            .bind(("rate_type", terms.rate_type.clone()))
+            .bind(("conditions", terms.conditions.clone()))
+            .bind(("shortlist_entry", shortlist_entry.cloned()))
+            .bind(("sent_by", sent_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to create offer: {}", e)))?
+            .take(0)?;
+        let offer = offer.ok_or_else(|| Error::Internal("Offer was not created".to_string()))?;
+        debug!("Created offer {} for {}", offer.id.display(), person.display());
+        Ok(offer)
+    }
+
+    pub async fn get(offer_id: &str) -> Result<Offer, Error> {
+        let offer: Option<Offer> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("offer", offer_id)))
+            .await?
+            .take(0)?;
+        offer.ok_or(Error::NotFound)
+    }
+
+    pub async fn for_production(production: &RecordId) -> Result<Vec<OfferListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM offer WHERE production = $production ORDER BY updated_at DESC"
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Offers sent to a person; withdrawn ones drop off
+    pub async fn for_person(person: &RecordId) -> Result<Vec<OfferListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM offer WHERE person = $person AND status != 'withdrawn'
+                 ORDER BY updated_at DESC"
+            ))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Offers sent to any of these people, e.g. an agent's clients
+    pub async fn for_people(people: &[RecordId]) -> Result<Vec<OfferListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM offer WHERE person INSIDE $people AND status != 'withdrawn'
+                 ORDER BY updated_at DESC"
+            ))
+            .bind(("people", people.to_vec()))
+            .await?
+            .take(0)?)
+    }
+
+    /// The status `action` moves the offer to, if it's open to it
+    fn target(offer: &Offer, action: OfferAction) -> Result<&'static str, Error> {
+        next_status(&offer.status, action)
+            .ok_or_else(|| Error::Conflict("This offer can't be changed any more".to_string()))
+    }
+
+    fn moved(offer: &Offer, status: &str, updated: Option<Offer>) -> Option<Offer> {
+        if updated.is_some() {
+            info!("Offer {} {} -> {}", offer.id.display(), offer.status, status);
+        }
+        updated
+    }
+
+    // Each update below only applies while the offer still has the status the
+    // caller saw, and returns `None` when someone else got there first.
+
+    pub async fn accept(offer: &Offer) -> Result<Option<Offer>, Error> {
+        let status = Self::target(offer, OfferAction::Accept)?;
+        let updated: Option<Offer> = DB
+            .query(
+                "UPDATE $offer SET status = $status, responded_at = time::now()
+                 WHERE status = $current RETURN AFTER",
+            )
+            .bind(("offer", offer.id.clone()))
+            .bind(("status", status))
+            .bind(("current", offer.status.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to accept offer: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(offer, status, updated))
+    }
+
+    pub async fn decline(offer: &Offer, note: &str) -> Result<Option<Offer>, Error> {
+        let status = Self::target(offer, OfferAction::Decline)?;
+        let note = clean_text(note, MAX_NOTE_CHARS, "Notes")?;
+        let updated: Option<Offer> = DB
+            .query(
+                "UPDATE $offer SET status = $status, response_note = $note, responded_at = time::now()
+                 WHERE status = $current RETURN AFTER",
+            )
+            .bind(("offer", offer.id.clone()))
+            .bind(("status", status))
+            .bind(("current", offer.status.clone()))
+            .bind(("note", note))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to decline offer: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(offer, status, updated))
+    }
+
+    pub async fn counter(offer: &Offer, counter: &CounterTerms) -> Result<Option<Offer>, Error> {
+        let status = Self::target(offer, OfferAction::Counter)?;
+        let updated: Option<Offer> = DB
+            .query(
+                "UPDATE $offer SET status = $status, counter_rate_amount = $rate,
+                    counter_start_date = $start_date, counter_end_date = $end_date,
+                    response_note = $note, responded_at = time::now()
+                 WHERE status = $current RETURN AFTER",
+            )
+            .bind(("offer", offer.id.clone()))
+            .bind(("status", status))
+            .bind(("current", offer.status.clone()))
+            .bind(("rate", counter.rate_amount))
+            .bind(("start_date", counter.dates.map(|(start, _)| start.to_string())))
+            .bind(("end_date", counter.dates.map(|(_, end)| end.to_string())))
+            .bind(("note", counter.note.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to counter offer: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(offer, status, updated))
+    }
+
+    /// Take the person's counter: its rate and dates become the terms
+    pub async fn accept_counter(offer: &Offer) -> Result<Option<Offer>, Error> {
+        let status = Self::target(offer, OfferAction::AcceptCounter)?;
+        let updated: Option<Offer> = DB
+            .query(
+                "UPDATE $offer SET status = $status, rate_amount = counter_rate_amount ?? rate_amount,
+                    start_date = counter_start_date ?? start_date, end_date = counter_end_date ?? end_date
+                 WHERE status = $current RETURN AFTER",
+            )
+            .bind(("offer", offer.id.clone()))
+            .bind(("status", status))
+            .bind(("current", offer.status.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to accept counter: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(offer, status, updated))
+    }
+
+    /// Send new terms, clearing any counter
+    pub async fn revise(offer: &Offer, terms: &OfferTerms) -> Result<Option<Offer>, Error> {
+        let status = Self::target(offer, OfferAction::Revise)?;
+        let updated: Option<Offer> = DB
+            .query(
+                "UPDATE $offer SET status = $status, role_title = $role_title, start_date = $start_date,
+                    end_date = $end_date, rate_amount = $rate_amount, rate_type = $rate_type,
+                    conditions = $conditions, counter_rate_amount = NONE, counter_start_date = NONE,
+                    counter_end_date = NONE, response_note = NONE, responded_at = NONE
+                 WHERE status = $current RETURN AFTER",
+            )
+            .bind(("offer", offer.id.clone()))
+            .bind(("status", status))
+            .bind(("current", offer.status.clone()))
+            .bind(("role_title", terms.role_title.clone()))
+            .bind(("start_date", terms.start_date.to_string()))
+            .bind(("end_date", terms.end_date.to_string()))
+            .bind(("rate_amount", terms.rate_amount))
+            .bind(("rate_type", terms.rate_type.clone()))
+            .bind(("conditions", terms.conditions.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to revise offer: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(offer, status, updated))
+    }
+
+    pub async fn withdraw(offer: &Offer) -> Result<Option<Offer>, Error> {
+        let status = Self::target(offer, OfferAction::Withdraw)?;
+        let updated: Option<Offer> = DB
+            .query("UPDATE $offer SET status = $status WHERE status = $current RETURN AFTER")
+            .bind(("offer", offer.id.clone()))
+            .bind(("status", status))
+            .bind(("current", offer.status.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to withdraw offer: {}", e)))?
+            .take(0)?;
+        Ok(Self::moved(offer, status, updated))
+    }
+
+    /// Confirm an accepted offer: add the person to the roster with the role,
+    /// set their crew deal for hourly and daily rates, and book the dates.
+    pub async fn confirm(offer: &Offer) -> Result<(), Error> {
+        use crate::models::timecard::{DealData, OvertimeRules, TimecardModel};
+
+        let person = offer.person.to_raw_string();
+        if ProductionModel::is_member(&offer.production, &person).await? {
+            let mut roles: Vec<String> = DB
+                .query("SELECT VALUE production_roles FROM member_of WHERE in = $person AND out = $production LIMIT 1")
+                .bind(("person", offer.person.clone()))
+                .bind(("production", offer.production.clone()))
+                .await?
+                .take::<Option<Vec<String>>>(0)?
+                .unwrap_or_default();
+            if !roles.contains(&offer.role_title) {
+                roles.push(offer.role_title.clone());
+                ProductionModel::update_member_roles(&offer.production, &person, roles).await?;
+            }
+            DB.query("UPDATE member_of SET invitation_status = 'accepted' WHERE in = $person AND out = $production")
+                .bind(("person", offer.person.clone()))
+                .bind(("production", offer.production.clone()))
+                .await
+                .map_err(|e| Error::Database(format!("Failed to confirm membership: {}", e)))?;
+        } else {
+            ProductionModel::add_member_accepted(
+                &offer.production,
+                &person,
+                "member",
+                Some(vec![offer.role_title.clone()]),
+            )
+            .await?;
+        }
+
+        if offer.rate_type == "hourly" || offer.rate_type == "daily" {
+            let rules = TimecardModel::deal(&offer.production, &offer.person)
+                .await?
+                .map(|deal| deal.rules())
+                .unwrap_or_default();
+            let OvertimeRules {
+                straight_hours,
+                double_time_after,
+                overtime_multiplier,
+                double_time_multiplier,
+            } = rules;
+            TimecardModel::set_deal(
+                &offer.production,
+                &offer.person,
+                DealData {
+                    rate_type: offer.rate_type.clone(),
+                    rate: Some(offer.rate_amount),
+                    straight_hours,
+                    double_time_after,
+                    overtime_multiplier,
+                    double_time_multiplier,
+                },
+            )
+            .await?;
+        }
+
+        DB.query(
+            "UPSERT booking SET person = $person, production = $production, offer = $offer,
+                role_title = $role_title, start_date = $start_date, end_date = $end_date
+             WHERE offer = $offer",
+        )
+        .bind(("person", offer.person.clone()))
+        .bind(("production", offer.production.clone()))
+        .bind(("offer", offer.id.clone()))
+        .bind(("role_title", offer.role_title.clone()))
+        .bind(("start_date", offer.start_date.clone()))
+        .bind(("end_date", offer.end_date.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to book dates: {}", e)))?;
+        info!(
+            "Booked {} on {} for {} to {}",
+            offer.person.display(),
+            offer.production.display(),
+            offer.start_date,
+            offer.end_date
+        );
+        Ok(())
+    }
+
+    /// Bookings that haven't ended by `from` (YYYY-MM-DD), soonest first
+    pub async fn upcoming_bookings(person: &RecordId, from: &str) -> Result<Vec<Booking>, Error> {
+        Ok(DB
+            .query(
+                "SELECT production.title AS production_title, production.slug AS production_slug,
+                    role_title, start_date, end_date
+                 FROM booking WHERE person = $person AND end_date >= $from ORDER BY start_date ASC",
+            )
+            .bind(("person", person.clone()))
+            .bind(("from", from.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Delete a person's offers and bookings, for account deletion
+    pub async fn forget(person: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE booking WHERE person = $person; DELETE offer WHERE person = $person;")
+            .bind(("person", person.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete offers: {}", e)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,343 @@
+//! Organization branding
+//!
+//! An organization's owners and admins can upload a wordmark and pick a brand
+//! colour and an accent colour. Productions the organization owns carry them:
+//! call sheet PDFs open with a letterhead band in the brand colour with the
+//! wordmark (or the logo when there's no wordmark), the press kit page takes
+//! the colours and shows the wordmark, and call sheet and daily report
+//! emails end with a footer naming the organization.
+
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::warn;
+
+use crate::{
+    config,
+    db::DB,
+    error::Error,
+    models::portfolio,
+    pdf::{Flow, Font, text_width},
+};
+
+/// The site's own colour, used where an organization hasn't picked one
+pub const DEFAULT_COLOR: &str = "#eb5437";
+
+/// Largest wordmark kept, in pixels; bigger uploads are scaled down
+pub const WORDMARK_MAX_WIDTH: u32 = 800;
+pub const WORDMARK_MAX_HEIGHT: u32 = 240;
+
+/// Largest wordmark upload accepted
+pub const WORDMARK_MAX_BYTES: usize = 5 * 1024 * 1024;
+
+/// Height of the letterhead band on branded PDFs
+const BAND_HEIGHT: f32 = 56.0;
+
+/// "#rrggbb" from "#rgb", "#rrggbb" or either without the "#". Blank means
+/// the default.
+pub fn parse_color(value: &str, what: &str) -> Result<Option<String>, Error> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let hex = value.strip_prefix('#').unwrap_or(value).to_lowercase();
+    let hex = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex,
+        _ => String::new(),
+    };
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(Error::Validation(format!(
+            "{} should be a hex colour like #1f6feb",
+            what
+        )));
+    }
+    Ok(Some(format!("#{}", hex)))
+}
+
+/// The brand and accent colours from the branding form
+pub fn parse_colors(color: &str, accent: &str) -> Result<(Option<String>, Option<String>), Error> {
+    Ok((
+        parse_color(color, "The brand colour")?,
+        parse_color(accent, "The accent colour")?,
+    ))
+}
+
+/// The channels of a "#rrggbb" colour
+pub fn hex_rgb(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+/// Black or white, whichever reads better on `background`
+pub fn text_on(background: [u8; 3]) -> [u8; 3] {
+    let [r, g, b] = background.map(f32::from);
+    let luminance = 0.299 * r + 0.587 * g + 0.114 * b;
+    if luminance > 150.0 {
+        [0, 0, 0]
+    } else {
+        [255, 255, 255]
+    }
+}
+
+/// An uploaded wordmark scaled down to fit `WORDMARK_MAX_*`, as PNG so a
+/// transparent background stays transparent
+pub fn process_wordmark(data: &[u8]) -> Result<Vec<u8>, Error> {
+    let image = image::load_from_memory(data)
+        .map_err(|e| Error::Validation(format!("That file isn't an image we can read: {}", e)))?;
+    let image = if image.width() > WORDMARK_MAX_WIDTH || image.height() > WORDMARK_MAX_HEIGHT {
+        image.thumbnail(WORDMARK_MAX_WIDTH, WORDMARK_MAX_HEIGHT)
+    } else {
+        image
+    };
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| Error::Internal(format!("Failed to encode wordmark: {}", e)))?;
+    Ok(png.into_inner())
+}
+
+/// `image` laid over a solid `background`. PDFs embed images as JPEG, which
+/// has no transparency.
+pub fn flatten(image: &DynamicImage, background: [u8; 3]) -> DynamicImage {
+    let rgba = image.to_rgba8();
+    let flat = RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let alpha = f32::from(a) / 255.0;
+        let blend = |c: u8, bg: u8| (f32::from(c) * alpha + f32::from(bg) * (1.0 - alpha)) as u8;
+        Rgb([
+            blend(r, background[0]),
+            blend(g, background[1]),
+            blend(b, background[2]),
+        ])
+    });
+    DynamicImage::ImageRgb8(flat)
+}
+
+/// What an organization has set on its branding page
+#[derive(Debug, Clone, Default, Deserialize, SurrealValue)]
+pub struct OrgBranding {
+    pub wordmark: Option<String>,
+    /// "#rrggbb"
+    pub color: Option<String>,
+    pub accent: Option<String>,
+}
+
+impl OrgBranding {
+    pub fn is_empty(&self) -> bool {
+        self.wordmark.is_none() && self.color.is_none() && self.accent.is_none()
+    }
+}
+
+/// The branding a production's documents and pages are dressed in
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct Brand {
+    /// The organization's name and slug
+    pub name: String,
+    pub slug: String,
+    /// The wordmark, or the logo when there's no wordmark
+    pub image: Option<String>,
+    pub color: Option<String>,
+    pub accent: Option<String>,
+}
+
+impl Brand {
+    /// The brand colour as "#rrggbb"
+    pub fn color(&self) -> &str {
+        self.color.as_deref().unwrap_or(DEFAULT_COLOR)
+    }
+
+    /// The accent colour, or the brand colour when there's no accent
+    pub fn accent(&self) -> &str {
+        self.accent.as_deref().unwrap_or(self.color())
+    }
+
+    pub fn color_rgb(&self) -> [u8; 3] {
+        hex_rgb(self.color()).unwrap_or([235, 84, 55])
+    }
+
+    pub fn accent_rgb(&self) -> [u8; 3] {
+        hex_rgb(self.accent()).unwrap_or_else(|| self.color_rgb())
+    }
+
+    /// "#ffffff" or "#000000", for text on the brand colour
+    pub fn text_color(&self) -> String {
+        let [r, g, b] = text_on(self.color_rgb());
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+
+    /// The organization's page
+    pub fn url(&self) -> String {
+        format!("{}/orgs/{}", config::app_url(), self.slug)
+    }
+
+    /// Plain-text lines to end an email with
+    pub fn email_footer_text(&self) -> String {
+        format!("\n\n--\nSent for {}\n{}", self.name, self.url())
+    }
+
+    /// HTML to end an email with: the wordmark (or name) on a rule in the
+    /// brand colour
+    pub fn email_footer_html(&self) -> String {
+        let name = ammonia::clean_text(&self.name);
+        let mark = match &self.image {
+            Some(image) => {
+                let src = if image.starts_with('/') {
+                    format!("{}{}", config::app_url(), image)
+                } else {
+                    image.clone()
+                };
+                format!(
+                    "<img src=\"{}\" alt=\"{}\" style=\"max-height: 40px; max-width: 200px\" />",
+                    ammonia::clean_text(&src),
+                    name
+                )
+            }
+            None => format!("<strong>{}</strong>", name),
+        };
+        format!(
+            "<div style=\"margin-top: 24px; padding-top: 12px; border-top: 3px solid {}; font-family: sans-serif; font-size: 12px\">{}<br />Sent for <a href=\"{}\" style=\"color: {}\">{}</a></div>",
+            self.color(),
+            mark,
+            ammonia::clean_text(&self.url()),
+            self.accent(),
+            name
+        )
+    }
+}
+
+/// A production's brand with its wordmark loaded, for drawing on PDFs
+pub struct Letterhead {
+    pub brand: Brand,
+    pub image: Option<DynamicImage>,
+}
+
+impl Letterhead {
+    /// The letterhead of the organization that owns a production, if it has
+    /// set any branding
+    pub async fn for_production(production: &RecordId) -> Result<Option<Self>, Error> {
+        let Some(brand) = OrgBrandingModel::for_production(production).await? else {
+            return Ok(None);
+        };
+        let image = match &brand.image {
+            Some(url) => portfolio::load_image(url, WORDMARK_MAX_WIDTH).await,
+            None => None,
+        };
+        Ok(Some(Self { brand, image }))
+    }
+
+    /// A band across the top of the first page in the brand colour, with the
+    /// wordmark (or name) on the left and `label` on the right. Leaves the
+    /// cursor below it.
+    pub fn draw(&self, flow: &mut Flow, label: &str) {
+        let size = flow.doc_mut().size();
+        let margin = flow.margin();
+        let color = self.brand.color_rgb();
+        let ink = text_on(color);
+        let top = size.height - BAND_HEIGHT;
+        flow.doc_mut()
+            .fill_rect_rgb(0.0, top, size.width, BAND_HEIGHT, color);
+
+        let mut drew_mark = false;
+        if let Some(image) = &self.image {
+            let max_height = BAND_HEIGHT - 16.0;
+            let max_width = size.width / 2.0 - margin;
+            let aspect = image.width() as f32 / image.height().max(1) as f32;
+            let (width, height) = if max_height * aspect > max_width {
+                (max_width, max_width / aspect)
+            } else {
+                (max_height * aspect, max_height)
+            };
+            match flow.doc_mut().add_image(&flatten(image, color)) {
+                Ok(id) => {
+                    let y = top + (BAND_HEIGHT - height) / 2.0;
+                    flow.doc_mut().draw_image(id, margin, y, width, height);
+                    drew_mark = true;
+                }
+                Err(e) => warn!("Failed to embed {}'s wordmark: {}", self.brand.slug, e),
+            }
+        }
+        let baseline = top + BAND_HEIGHT / 2.0 - 4.0;
+        if !drew_mark {
+            flow.doc_mut()
+                .text_rgb(margin, baseline, 13.0, Font::Bold, &self.brand.name, ink);
+        }
+        let x = size.width - margin - text_width(label, 10.0, Font::Regular);
+        flow.doc_mut()
+            .text_rgb(x, baseline, 10.0, Font::Regular, label, ink);
+
+        flow.space(BAND_HEIGHT - margin + 18.0);
+    }
+}
+
+pub struct OrgBrandingModel;
+
+impl OrgBrandingModel {
+    pub async fn get(organization: &RecordId) -> Result<OrgBranding, Error> {
+        let branding: Option<OrgBranding> = DB
+            .query(
+                "SELECT wordmark, brand_color AS color, brand_accent AS accent
+                 FROM ONLY $organization",
+            )
+            .bind(("organization", organization.clone()))
+            .await?
+            .take(0)?;
+        Ok(branding.unwrap_or_default())
+    }
+
+    /// Set the brand and accent colours; None goes back to the default
+    pub async fn set_colors(
+        organization: &RecordId,
+        color: Option<String>,
+        accent: Option<String>,
+    ) -> Result<(), Error> {
+        DB.query("UPDATE $organization SET brand_color = $color, brand_accent = $accent")
+            .bind(("organization", organization.clone()))
+            .bind(("color", color))
+            .bind(("accent", accent))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Set or clear the wordmark, returning the one it replaced so its file
+    /// can be deleted
+    pub async fn set_wordmark(
+        organization: &RecordId,
+        url: Option<String>,
+    ) -> Result<Option<String>, Error> {
+        let previous = Self::get(organization).await?.wordmark;
+        DB.query("UPDATE $organization SET wordmark = $url")
+            .bind(("organization", organization.clone()))
+            .bind(("url", url))
+            .await?
+            .check()?;
+        Ok(previous)
+    }
+
+    /// The branding of the organization that owns a production, if it has
+    /// set any. Productions owned by a person keep the site's look.
+    pub async fn for_production(production: &RecordId) -> Result<Option<Brand>, Error> {
+        let brands: Vec<Brand> = DB
+            .query(
+                "SELECT in.name AS name, in.slug AS slug, in.wordmark ?? in.logo AS image,
+                     in.brand_color AS color, in.brand_accent AS accent
+                 FROM member_of
+                 WHERE out = $production AND role = 'owner'
+                     AND <string> type::table(in) = 'organization'
+                     AND invitation_status = 'accepted'
+                     AND (in.wordmark != NONE OR in.brand_color != NONE OR in.brand_accent != NONE)
+                 LIMIT 1",
+            )
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        Ok(brands.into_iter().next())
+    }
+}
@@ -6,6 +6,7 @@ use tracing::{debug, error, warn};
 use crate::{
     db::DB,
     error::Error,
+    models::company_credit::CompanyCreditModel,
     models::membership::{MembershipModel, MembershipRole},
     record_id_ext::RecordIdExt,
     services::embedding::build_organization_embedding_text,
@@ -40,6 +41,13 @@ pub struct Organization {
     pub org_type: OrganizationType, // Contains embedded OrganizationType with its RecordId
     pub description: Option<String>,
     pub location: Option<String>,
+    /// Map position, for nearby-services lookups from locations
+    #[serde(default)]
+    #[surreal(default)]
+    pub latitude: Option<f64>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub longitude: Option<f64>,
     pub website: Option<String>,
     pub social_links: Vec<SocialLink>,
     pub logo: Option<String>,
@@ -59,6 +67,16 @@ pub struct Organization {
     pub updated_at: DateTime<Utc>,
 }
 
+impl Organization {
+    /// "latitude, longitude" for the edit form, empty when not set
+    pub fn coordinates(&self) -> String {
+        self.latitude
+            .zip(self.longitude)
+            .map(crate::units::format_coordinates)
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
 pub struct OrganizationMember {
     pub id: RecordId,
@@ -119,8 +137,10 @@ impl OrganizationModel {
     async fn validate_organization_type(&self, org_type_id: &RecordId) -> Result<bool, Error> {
         debug!("Validating organization type: {}", org_type_id.display());
         Ok(DB
-            .select::<Option<OrganizationType>>(org_type_id)
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", org_type_id.clone()))
             .await?
+            .take::<Option<OrganizationType>>(0)?
             .is_some())
     }
 
@@ -311,8 +331,8 @@ impl OrganizationModel {
                 ", <float> (
                     (IF string::lowercase(name ?? '') CONTAINS string::lowercase($query ?? '') THEN 50 ELSE 0 END)
                     + (IF string::lowercase(description ?? '') CONTAINS string::lowercase($query ?? '') THEN 20 ELSE 0 END)
-                    + (IF embedding IS NOT NONE AND $has_embedding = true
-                        THEN vector::similarity::cosine(embedding, $query_embedding) * 30
+                    + (IF (embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                        THEN vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) * 30
                         ELSE 0
                     END)
                 ) AS _score"
@@ -329,7 +349,7 @@ impl OrganizationModel {
                 text_or_vector.push("string::lowercase(description ?? '') CONTAINS string::lowercase($query)".to_string());
             }
             if has_embedding {
-                text_or_vector.push(format!("(embedding IS NOT NONE AND $has_embedding = true AND vector::similarity::cosine(embedding, $query_embedding) > {})", crate::config::search_weights().vector_threshold));
+                text_or_vector.push(format!("((embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true AND vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) > {})", crate::config::search_config().organizations.vector_threshold));
             }
             conditions.push(format!("({})", text_or_vector.join(" OR ")));
         }
@@ -356,7 +376,7 @@ impl OrganizationModel {
             sql.push_str(&format!(" START {}", offset));
         }
 
-        let mut result = DB.query(&sql);
+        let mut result = crate::db::reader().query(&sql);
         if let Some(q) = query {
             result = result.bind(("query", q.to_string()));
         }
@@ -382,6 +402,7 @@ impl OrganizationModel {
             .map_err(|e| Error::BadRequest(e.to_string()))?;
 
         // Build embedding text for background update
+        let slate = CompanyCreditModel::slate_lines(&id).await.unwrap_or_default();
         let embedding_text = build_organization_embedding_text(
             &data.name,
             &data.org_type,
@@ -390,6 +411,7 @@ impl OrganizationModel {
             data.location.as_deref(),
             data.founded_year,
             data.employees_count,
+            &slate,
         );
 
         DB.query(
@@ -428,6 +450,21 @@ impl OrganizationModel {
         Ok(())
     }
 
+    /// Set or clear an organization's map position
+    pub async fn set_coordinates(
+        &self,
+        id: &RecordId,
+        point: Option<(f64, f64)>,
+    ) -> Result<(), Error> {
+        DB.query("UPDATE $id SET latitude = $latitude, longitude = $longitude")
+            .bind(("id", id.clone()))
+            .bind(("latitude", point.map(|p| p.0)))
+            .bind(("longitude", point.map(|p| p.1)))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
     /// Delete an organization and all its relationships
     pub async fn delete(&self, id: &str) -> Result<(), Error> {
         debug!("Deleting organization: {}", id);
@@ -442,6 +479,44 @@ impl OrganizationModel {
             .take(0)
             .unwrap_or_default();
 
+        // Delete company credits
+        DB.query("DELETE company_credit WHERE in = $id")
+            .bind(("id", id.clone()))
+            .await?;
+
+        // Delete SSO settings (and with them the SCIM token) and verified domains
+        DB.query(
+            "DELETE org_sso WHERE organization = $id;
+             DELETE sso_domain WHERE organization = $id",
+        )
+        .bind(("id", id.clone()))
+        .await?;
+
+        // Delete rental quotes; budget lines from accepted ones keep their amounts
+        DB.query(
+            "UPDATE budget_line SET vendor = NONE, quote = NONE WHERE vendor = $id;
+             DELETE gear_quote WHERE vendor = $id;",
+        )
+        .bind(("id", id.clone()))
+        .await?;
+
+        // Deliverables it was making keep its name as a vendor not on SlateHub
+        DB.query("UPDATE deliverable SET vendor_name = vendor.name, vendor = NONE WHERE vendor = $id")
+            .bind(("id", id.clone()))
+            .await?;
+
+        // Drop its roster and submissions; applications it made stay with the talent
+        DB.query(
+            "DELETE represented_by WHERE out = $id;
+             DELETE talent_submission WHERE agency = $id;
+             UPDATE application SET submitted_by = NONE WHERE submitted_by = $id;",
+        )
+        .bind(("id", id.clone()))
+        .await?;
+
+        // Delete claims and any ownership documents still held
+        crate::services::org_claims::forget_organization(&id).await?;
+
         // Delete the organization
         let _: Vec<()> = DB
             .query("DELETE $id")
@@ -0,0 +1,216 @@
+//! Cursor pagination
+//!
+//! Offset pagination rescans every row it skips and shifts when rows are
+//! added ahead of the page, so pages repeat or miss rows on busy tables. A
+//! cursor instead carries the sort keys and record id of the last row served,
+//! and the next page starts strictly after it.
+//!
+//! Ordering guarantees: `id` always ends the order (in the direction of the
+//! last sort key), so the order is total and ties can't straddle a page
+//! boundary. Every row is served exactly once as long as its sort keys don't
+//! change while it's being paged through; one whose keys do change shows up
+//! wherever its new keys put it. Sort keys must never be NONE.
+//!
+//! A query pages with `ORDER BY {order_clause}`, adds `after_clause` to its
+//! WHERE when there's a cursor, binds `Cursor::binds`, and fetches one row
+//! more than it shows so `Page::from_rows` can tell whether another follows.
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, SecondsFormat, Utc};
+use surrealdb::types::RecordId;
+
+use crate::record_id_ext::RecordIdExt;
+
+/// Type of a sort key, for casting the cursor's copy back in a query
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyKind {
+    Bool,
+    Int,
+    Float,
+    Text,
+    Datetime,
+}
+
+impl KeyKind {
+    fn cast(&self) -> &'static str {
+        match self {
+            KeyKind::Bool => "<bool> ",
+            KeyKind::Int => "<int> ",
+            KeyKind::Float => "<float> ",
+            KeyKind::Text => "",
+            KeyKind::Datetime => "<datetime> ",
+        }
+    }
+
+    fn is_valid(&self, value: &str) -> bool {
+        match self {
+            KeyKind::Bool => value == "true" || value == "false",
+            KeyKind::Int => value.parse::<i64>().is_ok(),
+            KeyKind::Float => value.parse::<f64>().is_ok_and(f64::is_finite),
+            KeyKind::Text => true,
+            KeyKind::Datetime => DateTime::parse_from_rfc3339(value).is_ok(),
+        }
+    }
+}
+
+/// One column of a paged order. `field` is what ORDER BY names (a field or
+/// an alias in the SELECT); `expr` computes it in WHERE.
+#[derive(Debug, Clone, Copy)]
+pub struct SortKey {
+    pub field: &'static str,
+    pub expr: &'static str,
+    pub kind: KeyKind,
+    pub descending: bool,
+}
+
+impl SortKey {
+    pub const fn asc(field: &'static str, kind: KeyKind) -> Self {
+        Self {
+            field,
+            expr: field,
+            kind,
+            descending: false,
+        }
+    }
+
+    pub const fn desc(field: &'static str, kind: KeyKind) -> Self {
+        Self {
+            field,
+            expr: field,
+            kind,
+            descending: true,
+        }
+    }
+
+    /// For an alias: how WHERE computes it
+    pub const fn computed(self, expr: &'static str) -> Self {
+        Self { expr, ..self }
+    }
+
+    fn direction(&self) -> &'static str {
+        if self.descending { "DESC" } else { "ASC" }
+    }
+
+    fn after(&self) -> &'static str {
+        if self.descending { "<" } else { ">" }
+    }
+}
+
+fn id_descending(keys: &[SortKey]) -> bool {
+    keys.last().is_some_and(|k| k.descending)
+}
+
+/// `ORDER BY` for `keys`, ending with `id`
+pub fn order_clause(keys: &[SortKey]) -> String {
+    let mut order: Vec<String> = keys
+        .iter()
+        .map(|k| format!("{} {}", k.field, k.direction()))
+        .collect();
+    order.push(format!(
+        "id {}",
+        if id_descending(keys) { "DESC" } else { "ASC" }
+    ));
+    format!("ORDER BY {}", order.join(", "))
+}
+
+/// Condition for rows after the cursor: past it on the first key, or tied on
+/// the first and past it on the second, and so on down to `id`
+pub fn after_clause(keys: &[SortKey]) -> String {
+    let value = |i: usize| format!("{}$after_{}", keys[i].kind.cast(), i);
+    let mut branches = Vec::new();
+    for i in 0..=keys.len() {
+        let mut parts: Vec<String> = (0..i)
+            .map(|j| format!("{} = {}", keys[j].expr, value(j)))
+            .collect();
+        parts.push(match keys.get(i) {
+            Some(key) => format!("{} {} {}", key.expr, key.after(), value(i)),
+            None => format!(
+                "id {} $after_id",
+                if id_descending(keys) { "<" } else { ">" }
+            ),
+        });
+        branches.push(format!("({})", parts.join(" AND ")));
+    }
+    format!("({})", branches.join(" OR "))
+}
+
+/// Where a page ends: the last row's sort keys and record id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub values: Vec<String>,
+    pub id: String,
+}
+
+impl Cursor {
+    pub fn new(values: Vec<String>, id: &RecordId) -> Self {
+        Self {
+            values,
+            id: id.to_raw_string(),
+        }
+    }
+
+    /// A datetime sort key as it's kept in a cursor
+    pub fn datetime(at: &DateTime<Utc>) -> String {
+        at.to_rfc3339_opts(SecondsFormat::Nanos, true)
+    }
+
+    pub fn encode(&self) -> String {
+        let mut parts = self.values.clone();
+        parts.push(self.id.clone());
+        URL_SAFE_NO_PAD.encode(serde_json::Value::from(parts).to_string())
+    }
+
+    /// A cursor for this order on `table`, or `None` if it's malformed or
+    /// from another order
+    pub fn decode(value: &str, table: &str, keys: &[SortKey]) -> Option<Self> {
+        let raw = URL_SAFE_NO_PAD.decode(value.trim()).ok()?;
+        let mut parts: Vec<String> = serde_json::from_slice(&raw).ok()?;
+        if parts.len() != keys.len() + 1 {
+            return None;
+        }
+        let id = parts.pop()?;
+        let record = RecordId::parse_simple(&id).ok()?;
+        if record.table.to_string() != table {
+            return None;
+        }
+        if !keys.iter().zip(&parts).all(|(k, v)| k.kind.is_valid(v)) {
+            return None;
+        }
+        Some(Self { values: parts, id })
+    }
+
+    /// `$after_N` bindings for `after_clause`; `$after_id` is `record_id`
+    pub fn binds(&self) -> Vec<(String, String)> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (format!("after_{}", i), v.clone()))
+            .collect()
+    }
+
+    pub fn record_id(&self) -> Option<RecordId> {
+        RecordId::parse_simple(&self.id).ok()
+    }
+}
+
+/// One page of rows, and the cursor for the next if there is one
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// A page from rows fetched with `LIMIT size + 1`: the extra row only
+    /// says that another page follows
+    pub fn from_rows(mut rows: Vec<T>, size: usize, cursor: impl Fn(&T) -> Cursor) -> Self {
+        let more = rows.len() > size;
+        rows.truncate(size);
+        let next = if more {
+            rows.last().map(|row| cursor(row).encode())
+        } else {
+            None
+        };
+        Self { items: rows, next }
+    }
+}
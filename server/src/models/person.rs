@@ -7,9 +7,17 @@
 use crate::auth;
 use crate::db::DB;
 use crate::error::{Error, Result};
+use crate::models::involvement::{InvolvementWithProduction, person_credits_query};
+use crate::models::media::{Media, uploaded_by_query};
+use crate::models::pagination::{self, Cursor, KeyKind, Page, SortKey};
+use crate::models::preload::Preload;
+use crate::physical_attributes::{Attribute, describe, normalize};
 use crate::record_id_ext::RecordIdExt;
 use crate::services::embedding::build_person_embedding_text;
+use crate::services::search_visibility;
+use crate::validation::{FieldErrors, Validate};
 use crate::{db_span, log_error};
+use chrono::{DateTime, Utc};
 use regex::Regex;
 use std::sync::LazyLock;
 use serde::{Deserialize, Serialize};
@@ -22,13 +30,16 @@ use tracing::{debug, error, info};
 
 static USERNAME_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[a-z0-9._]+$").unwrap());
 
+/// Account plans. `pro` unlocks the list of recent profile viewers.
+pub const PLANS: &[&str] = &["free", "pro"];
+
 const RESERVED_USERNAMES: &[&str] = &[
     "about", "account", "admin", "api", "auth", "contact", "dashboard", "delete",
     "equipment", "feedback", "get-verified", "health", "healthcheck", "help", "home", "i", "invitations",
     "likes", "locations", "login", "logout", "messages", "my-orgs", "notifications",
-    "org", "orgs", "people", "privacy", "productions", "profile", "project", "projects",
-    "qr", "resend-verification", "search", "settings", "signup", "static", "stats",
-    "support", "terms", "upload", "verify-email",
+    "offers", "org", "orgs", "people", "privacy", "productions", "profile", "project", "projects",
+    "qr", "resend-verification", "scim", "search", "settings", "signup", "sso", "static",
+    "stats", "support", "terms", "upload", "verify-email",
 ];
 
 /// Validates and normalizes a username to Instagram-style handle rules.
@@ -89,6 +100,20 @@ pub struct Person {
     #[serde(default = "default_messaging_preference")]
     #[surreal(default = "default_messaging_preference")]
     pub messaging_preference: String,
+    /// Units heights and weights are shown in: "metric" or "imperial".
+    /// Unset means metric, with the browser's locale deciding on profiles.
+    #[serde(default)]
+    #[surreal(default)]
+    pub units: Option<String>,
+    /// Minor profile mode: contact details and sensitive physical fields are
+    /// withheld, and only the guardian and verified organizations can message.
+    #[serde(default)]
+    #[surreal(default)]
+    pub is_minor: bool,
+    /// Who can find them in search and browse: "public", "members" or "hidden"
+    #[serde(default = "default_visibility")]
+    #[surreal(default = "default_visibility")]
+    pub visibility: String,
 }
 
 fn default_verification_status() -> String {
@@ -99,6 +124,10 @@ fn default_messaging_preference() -> String {
     "anyone".to_string()
 }
 
+fn default_visibility() -> String {
+    "public".to_string()
+}
+
 /// Represents the detailed profile of a person.
 /// Corresponds to the flexible `profile` object in the `person` table.
 #[derive(Debug, Clone, Serialize, Deserialize, Default, SurrealValue)]
@@ -117,9 +146,14 @@ pub struct Profile {
     // Physical Attributes
     pub height_mm: Option<i32>,
     pub weight_kg: Option<i32>,
+    /// Body type, hair and eye color hold a `physical_attributes` value;
+    /// for "Other", the `_other` field keeps the person's own words
     pub body_type: Option<String>,
+    pub body_type_other: Option<String>,
     pub hair_color: Option<String>,
+    pub hair_color_other: Option<String>,
     pub eye_color: Option<String>,
+    pub eye_color_other: Option<String>,
     pub gender: Option<String>,
     pub ethnicity: Vec<String>,
     pub age_range: Option<AgeRange>,
@@ -197,6 +231,53 @@ pub struct DateRange {
     pub end: Option<String>,
 }
 
+/// A person as shown on the people browse page
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct PersonListing {
+    pub id: RecordId,
+    pub username: String,
+    #[serde(default = "default_verification_status")]
+    #[surreal(default = "default_verification_status")]
+    pub verification_status: String,
+    #[serde(default)]
+    #[surreal(default)]
+    pub profile: Option<Profile>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A person with what their profile page shows, loaded in one query
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct PersonPage {
+    pub person: Person,
+    pub credits: Vec<InvolvementWithProduction>,
+    pub media: Vec<Media>,
+}
+
+/// How `PersonPage` is loaded
+pub fn page_preload() -> Preload {
+    Preload::new("person")
+        .with("credits", person_credits_query("$parent.id"))
+        .with("media", uploaded_by_query("$parent.id"))
+}
+
+/// People browse order: identity-verified people first, then newest
+const BROWSE_ORDER: &[SortKey] = &[
+    SortKey::desc("_vord", KeyKind::Bool).computed("(verification_status = 'identity')"),
+    SortKey::desc("created_at", KeyKind::Datetime),
+];
+
+impl PersonListing {
+    fn cursor(&self) -> Cursor {
+        Cursor::new(
+            vec![
+                (self.verification_status == "identity").to_string(),
+                Cursor::datetime(&self.created_at),
+            ],
+            &self.id,
+        )
+    }
+}
+
 // -----------------------------------------------------------------------------
 // Database Implementations
 // -----------------------------------------------------------------------------
@@ -212,7 +293,13 @@ impl Person {
     /// `None` if not found, or an `Error` if the database operation fails.
     pub async fn get(id: &RecordId) -> Result<Option<Self>> {
         let _span = db_span!("Person::get", id.to_raw_string()).entered();
-        match DB.select(id).await {
+        let person = async {
+            DB.query("SELECT * FROM ONLY $id")
+                .bind(("id", id.clone()))
+                .await?
+                .take(0)
+        };
+        match person.await {
             Ok(person) => Ok(person),
             Err(e) => {
                 log_error!(e, "Failed to get person");
@@ -221,6 +308,26 @@ impl Person {
         }
     }
 
+    /// Whether a person is on the pro plan. Missing people and lookup
+    /// failures count as the free plan.
+    pub async fn is_pro(id: &RecordId) -> bool {
+        let plan: Result<Option<String>> = async {
+            Ok(DB
+                .query("SELECT VALUE plan FROM ONLY $id")
+                .bind(("id", id.clone()))
+                .await?
+                .take(0)?)
+        }
+        .await;
+        match plan {
+            Ok(plan) => plan.as_deref() == Some("pro"),
+            Err(e) => {
+                error!("Failed to look up plan for {}: {}", id.display(), e);
+                false
+            }
+        }
+    }
+
     /// Updates the current person's record in the database.
     ///
     /// # Returns
@@ -228,7 +335,14 @@ impl Person {
     /// Returns an `Error` if the update operation fails.
     pub async fn update(&self) -> Result<Option<Self>> {
         let _span = db_span!("Person::update", self.id.to_raw_string()).entered();
-        match DB.update(&self.id).content(self.clone()).await {
+        let person = async {
+            DB.query("UPDATE ONLY $id CONTENT $person")
+                .bind(("id", self.id.clone()))
+                .bind(("person", self.clone()))
+                .await?
+                .take(0)
+        };
+        match person.await {
             Ok(person) => Ok(person),
             Err(e) => {
                 log_error!(e, "Failed to update person");
@@ -244,7 +358,13 @@ impl Person {
     /// Returns an `Error` if the deletion fails.
     pub async fn delete(&self) -> Result<Option<Self>> {
         let _span = db_span!("Person::delete", self.id.to_raw_string()).entered();
-        match DB.delete(&self.id).await {
+        let person = async {
+            DB.query("DELETE ONLY $id RETURN BEFORE")
+                .bind(("id", self.id.clone()))
+                .await?
+                .take(0)
+        };
+        match person.await {
             Ok(person) => Ok(person),
             Err(e) => {
                 log_error!(e, "Failed to delete person");
@@ -267,10 +387,10 @@ impl Person {
         let sql = "SELECT * FROM person WHERE username = string::lowercase($username)";
         debug!("Executing query: {} with username: '{}'", sql, username);
 
-        let mut response = DB
-            .query(sql)
-            .bind(("username", username.to_string()))
-            .await?;
+        let mut response = crate::db::retry_read("person.find_by_username", || {
+            DB.query(sql).bind(("username", username.to_string()))
+        })
+        .await?;
 
         debug!(
             "Query executed successfully, attempting to extract results: {:?}",
@@ -331,7 +451,10 @@ impl Person {
             "Executing parameterized record query"
         );
 
-        let mut response = DB.query(sql).bind(("id", record_key.to_string())).await?;
+        let mut response = crate::db::retry_read("person.find_by_id", || {
+            DB.query(sql).bind(("id", record_key.to_string()))
+        })
+        .await?;
 
         debug!("Query executed, extracting results");
 
@@ -441,6 +564,71 @@ impl Person {
         Ok(persons)
     }
 
+    /// A page of the people browse listing: people with something on their
+    /// profile, leaving out minors without guardian approval and anyone the
+    /// viewer can't find. Signed-out viewers only see public profiles.
+    pub async fn browse(
+        cursor: Option<&str>,
+        size: usize,
+        signed_in: bool,
+    ) -> Result<Page<PersonListing>> {
+        let cursor = match cursor {
+            Some(value) => Some(
+                Cursor::decode(value, "person", BROWSE_ORDER)
+                    .ok_or_else(|| Error::BadRequest("Invalid cursor".to_string()))?,
+            ),
+            None => None,
+        };
+        let sql = format!(
+            "SELECT id, username, verification_status, profile, created_at,
+                verification_status = 'identity' AS _vord
+            FROM person
+            WHERE (profile.name IS NOT NULL
+               OR profile.headline IS NOT NULL
+               OR profile.bio IS NOT NULL)
+              AND (is_minor != true OR guardian_approved = true)
+              AND {}
+              {}
+            {}
+            LIMIT $limit",
+            if signed_in {
+                search_visibility::NOT_HIDDEN
+            } else {
+                search_visibility::PUBLIC_ONLY
+            },
+            if cursor.is_some() {
+                format!("AND {}", pagination::after_clause(BROWSE_ORDER))
+            } else {
+                String::new()
+            },
+            pagination::order_clause(BROWSE_ORDER)
+        );
+
+        let mut query = crate::db::reader()
+            .query(sql)
+            .bind(("limit", size as i64 + 1));
+        if let Some(cursor) = &cursor {
+            for bind in cursor.binds() {
+                query = query.bind(bind);
+            }
+            query = query.bind(("after_id", cursor.record_id()));
+        }
+        let rows: Vec<PersonListing> = query.await?.take(0)?;
+        Ok(Page::from_rows(rows, size, PersonListing::cursor))
+    }
+
+    /// A person by username with their credits and media, in one round trip
+    pub async fn page(username: &str) -> Result<Option<PersonPage>> {
+        let sql =
+            page_preload().sql("person WHERE username = string::lowercase($username) LIMIT 1");
+        let mut response = crate::db::retry_read("person.page", || {
+            DB.query(&sql).bind(("username", username.to_string()))
+        })
+        .await?;
+        let pages: Vec<PersonPage> = response.take(0)?;
+        Ok(pages.into_iter().next())
+    }
+
     /// Searches for persons by skill.
     ///
     /// # Arguments
@@ -486,6 +674,7 @@ impl Person {
                 .as_ref()
                 .and_then(|p| p.name.clone())
                 .unwrap_or_else(|| self.username.clone()),
+            impersonator: None,
         }
     }
 
@@ -645,8 +834,11 @@ impl Person {
                 height_mm: None,
                 weight_kg: None,
                 body_type: None,
+                body_type_other: None,
                 hair_color: None,
+                hair_color_other: None,
                 eye_color: None,
+                eye_color_other: None,
                 gender: None,
                 ethnicity: Vec::new(),
                 age_range: None,
@@ -737,14 +929,15 @@ impl Person {
             if let Some(w) = weight_kg {
                 profile.weight_kg = if w == 0 { None } else { Some(w) };
             }
+            // Free text outside the vocabulary is kept as "Other" plus the text
             if let Some(bt) = body_type {
-                profile.body_type = if bt.is_empty() { None } else { Some(bt) };
+                (profile.body_type, profile.body_type_other) = normalize(Attribute::BodyType, &bt);
             }
             if let Some(hc) = hair_color {
-                profile.hair_color = if hc.is_empty() { None } else { Some(hc) };
+                (profile.hair_color, profile.hair_color_other) = normalize(Attribute::HairColor, &hc);
             }
             if let Some(ec) = eye_color {
-                profile.eye_color = if ec.is_empty() { None } else { Some(ec) };
+                (profile.eye_color, profile.eye_color_other) = normalize(Attribute::EyeColor, &ec);
             }
             if let Some(eth) = ethnicity {
                 profile.ethnicity = if eth.is_empty() {
@@ -780,14 +973,25 @@ impl Person {
             }
         }
 
+        // A birthday under 18 switches the account into minor mode. Only the
+        // guardian can switch it back off, so an edit never clears it here.
+        if !person.is_minor
+            && let Some(birthday) = person.profile.as_ref().and_then(|p| p.birthday.as_deref())
+            && crate::services::minors::is_under_18(birthday, chrono::Utc::now().date_naive())
+        {
+            info!("Birthday puts {} under 18, switching to minor mode", person.username);
+            person.is_minor = true;
+        }
+
         // Save profile to DB immediately (no embedding — that happens in background)
-        let query = "UPDATE $id MERGE { name: $name, profile: $profile } RETURN AFTER";
+        let query = "UPDATE $id MERGE { name: $name, profile: $profile, is_minor: $is_minor } RETURN AFTER";
 
         let mut response = DB
             .query(query)
             .bind(("id", person.id.clone()))
             .bind(("name", person.name.clone()))
             .bind(("profile", person.profile.clone()))
+            .bind(("is_minor", person.is_minor))
             .await
             .map_err(|e| {
                 log_error!(e, "Failed to update person profile");
@@ -798,42 +1002,63 @@ impl Person {
 
         // Generate embedding in the background (fire-and-forget)
         // Always generate — even with minimal profile data, the person should be searchable.
-        {
-            let display_name = person.name.as_deref().unwrap_or(&person.username);
-            let embedding_text = if let Some(profile) = &person.profile {
-                build_person_embedding_text(
-                    display_name,
-                    profile.headline.as_deref(),
-                    profile.bio.as_deref(),
-                    &profile.skills,
-                    profile.location.as_deref(),
-                    profile.age_range.as_ref().map(|r| (r.min, r.max)),
-                    profile.gender.as_deref(),
-                    &profile.ethnicity,
-                    profile.height_mm,
-                    profile.body_type.as_deref(),
-                    profile.hair_color.as_deref(),
-                    profile.eye_color.as_deref(),
-                    &profile.languages,
-                    &profile.unions,
-                    &[],
-                    profile.acting_age_range.as_ref().map(|r| (r.min, r.max)),
-                    &profile.acting_ethnicities,
-                    profile.nationality.as_deref(),
-                )
-            } else {
-                build_person_embedding_text(
-                    display_name, None, None, &[], None, None, None, &[], None, None, None, None, &[], &[], &[], None, &[], None,
-                )
-            };
-            crate::services::embedding::spawn_embedding_update(person.id.clone(), embedding_text);
-        }
+        person.refresh_embedding().await;
 
         Ok(updated)
     }
 }
 
 impl Person {
+    /// Re-embed the person in the background, with their audio reels
+    pub async fn refresh_embedding(&self) {
+        let audio_work = crate::models::audio_reel::AudioReelModel::embedding_lines(&self.id)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Failed to load audio reels for {}: {}", self.username, e);
+                Vec::new()
+            });
+        crate::services::embedding::spawn_embedding_update(self.id.clone(), self.embedding_text(&audio_work));
+    }
+
+    /// Text fed to the embedding model for semantic search. Minor profiles
+    /// leave out the fields they never show publicly.
+    pub fn embedding_text(&self, audio_work: &[String]) -> String {
+        let display_name = self.name.as_deref().unwrap_or(&self.username);
+        let mut profile = self.profile.clone();
+        if self.is_minor
+            && let Some(profile) = profile.as_mut()
+        {
+            crate::services::minors::redact_profile(profile);
+        }
+        match &profile {
+            Some(profile) => build_person_embedding_text(
+                display_name,
+                profile.headline.as_deref(),
+                profile.bio.as_deref(),
+                &profile.skills,
+                profile.location.as_deref(),
+                profile.age_range.as_ref().map(|r| (r.min, r.max)),
+                profile.gender.as_deref(),
+                &profile.ethnicity,
+                profile.height_mm,
+                describe(profile.body_type.as_deref(), profile.body_type_other.as_deref()),
+                describe(profile.hair_color.as_deref(), profile.hair_color_other.as_deref()),
+                describe(profile.eye_color.as_deref(), profile.eye_color_other.as_deref()),
+                &profile.languages,
+                &profile.unions,
+                &[],
+                profile.acting_age_range.as_ref().map(|r| (r.min, r.max)),
+                &profile.acting_ethnicities,
+                profile.nationality.as_deref(),
+                audio_work,
+            ),
+            None => build_person_embedding_text(
+                display_name, None, None, &[], None, None, None, &[], None, None, None, None, &[], &[], &[], None, &[], None,
+                audio_work,
+            ),
+        }
+    }
+
     /// Signs up a new user by creating a person record with hashed password.
     ///
     /// # Arguments
@@ -890,11 +1115,23 @@ impl Person {
         // this ensures the person is discoverable via semantic search from day one.
         {
             let embedding_text = build_person_embedding_text(
-                &username, None, None, &[], None, None, None, &[], None, None, None, None, &[], &[], &[], None, &[], None,
+                &username, None, None, &[], None, None, None, &[], None, None, None, None, &[], &[], &[], None, &[], None, &[],
             );
             crate::services::embedding::spawn_embedding_update(person.id.clone(), embedding_text);
         }
 
+        // The signup form requires agreeing to the current terms and privacy policy
+        if let Err(e) = crate::services::consent::accept(
+            &person.id,
+            crate::services::consent::DOCUMENTS,
+            "signup",
+            None,
+        )
+        .await
+        {
+            error!("Failed to record terms acceptance for {}: {}", username, e);
+        }
+
         // Generate verification code and send email
         use crate::services::email::EmailService;
         use crate::services::verification::{CodeType, VerificationService};
@@ -910,7 +1147,7 @@ impl Person {
         // Send verification email (non-blocking, log error if it fails)
         if let Ok(email_service) = EmailService::from_env() {
             let email_clone = email.clone();
-            tokio::spawn(async move {
+            crate::db::spawn(async move {
                 if let Err(e) = email_service
                     .send_verification_email(&email_clone, None, &verification_code)
                     .await
@@ -1059,6 +1296,19 @@ pub struct SessionUser {
     pub username: String,
     pub email: String,
     pub name: String,
+    /// Set when an admin is viewing the site as this user
+    #[serde(default)]
+    pub impersonator: Option<Impersonator>,
+}
+
+/// The admin behind an impersonation session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Impersonator {
+    /// impersonation_session record ID
+    pub session_id: String,
+    /// Admin person ID (format: "person:xxxxx")
+    pub admin_id: String,
+    pub admin_username: String,
 }
 
 /// Represents the data required to create a new user account.
@@ -1072,6 +1322,17 @@ pub struct CreateUser {
     pub redirect: Option<String>,
 }
 
+impl Validate for CreateUser {
+    fn validate(&self) -> FieldErrors {
+        let mut errors = FieldErrors::new();
+        errors.check("username", validate_username(&self.username));
+        errors.required("email", &self.email, "Email address");
+        errors.email("email", &self.email);
+        errors.required("password", &self.password, "Password");
+        errors
+    }
+}
+
 /// Represents the data required for a user to log in.
 /// Used for deserializing the login form data.
 #[derive(Debug, Deserialize)]
@@ -0,0 +1,255 @@
+//! Availability calendars on person profiles
+//!
+//! A person marks the date ranges they can't work (a holiday, another show).
+//! Together with their confirmed bookings these make up their calendar: the
+//! account page draws it, the profile lists the upcoming ranges, and people
+//! search can leave out anyone who isn't free for a whole shoot window.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+use crate::{
+    db::{DB, reader},
+    error::Error,
+    models::{
+        offer::{self, Booking},
+        production_gear::{self, CalendarDay},
+    },
+    record_id_ext::RecordIdExt,
+};
+
+/// Most unavailable ranges one person can list
+pub const MAX_PERIODS: usize = 50;
+
+/// Longest note on a range
+pub const MAX_NOTE_CHARS: usize = 120;
+
+/// Longest range, and longest shoot window people search accepts
+pub const MAX_DAYS: i64 = 366;
+
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct UnavailablePeriod {
+    pub id: RecordId,
+    /// "YYYY-MM-DD"
+    pub start_date: String,
+    /// Inclusive
+    pub end_date: String,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl UnavailablePeriod {
+    pub fn key(&self) -> String {
+        self.id.key_string()
+    }
+
+    pub fn overlaps(&self, start: &str, end: &str) -> bool {
+        production_gear::overlaps(&self.start_date, &self.end_date, start, end)
+    }
+
+    /// "Jun 1 – Jun 5, 2026"
+    pub fn dates_label(&self) -> String {
+        offer::dates_label(&self.start_date, &self.end_date)
+    }
+}
+
+fn parse_date(value: &str) -> Result<Option<NaiveDate>, Error> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(Some)
+        .map_err(|_| Error::Validation(format!("\"{}\" isn't a date (use YYYY-MM-DD)", value)))
+}
+
+/// Dates someone needs a person free for, as given to people search
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AvailabilityWindow {
+    pub from: NaiveDate,
+    /// Inclusive
+    pub to: NaiveDate,
+}
+
+impl AvailabilityWindow {
+    /// The window from `available_from`/`available_to` search params. Either
+    /// alone means that one day; neither means search doesn't filter on
+    /// availability.
+    pub fn parse(from: Option<&str>, to: Option<&str>) -> Result<Option<Self>, Error> {
+        let from = parse_date(from.unwrap_or(""))?;
+        let to = parse_date(to.unwrap_or(""))?;
+        let (from, to) = match (from, to) {
+            (None, None) => return Ok(None),
+            (Some(from), None) => (from, from),
+            (None, Some(to)) => (to, to),
+            (Some(from), Some(to)) => (from, to),
+        };
+        check_range(from, to)?;
+        Ok(Some(Self { from, to }))
+    }
+
+    /// "YYYY-MM-DD"
+    pub fn first_day(&self) -> String {
+        self.from.format("%Y-%m-%d").to_string()
+    }
+
+    pub fn last_day(&self) -> String {
+        self.to.format("%Y-%m-%d").to_string()
+    }
+}
+
+fn check_range(start: NaiveDate, end: NaiveDate) -> Result<(), Error> {
+    if end < start {
+        return Err(Error::Validation(
+            "The end date can't be before the start date".to_string(),
+        ));
+    }
+    if (end - start).num_days() >= MAX_DAYS {
+        return Err(Error::Validation(format!(
+            "A date range can't be longer than {} days",
+            MAX_DAYS
+        )));
+    }
+    Ok(())
+}
+
+/// A validated range from the account page form: "YYYY-MM-DD" dates, in
+/// order, and the note trimmed (empty for none)
+pub fn clean_period(
+    start: &str,
+    end: &str,
+    note: &str,
+) -> Result<(String, String, Option<String>), Error> {
+    let start = parse_date(start)?
+        .ok_or_else(|| Error::Validation("Pick the first day you're unavailable".to_string()))?;
+    // A single day needs no end date
+    let end = parse_date(end)?.unwrap_or(start);
+    check_range(start, end)?;
+
+    let note = note.trim();
+    if note.chars().count() > MAX_NOTE_CHARS {
+        return Err(Error::Validation(format!(
+            "Keep the note under {} characters",
+            MAX_NOTE_CHARS
+        )));
+    }
+    Ok((
+        start.format("%Y-%m-%d").to_string(),
+        end.format("%Y-%m-%d").to_string(),
+        Some(note.to_string()).filter(|n| !n.is_empty()),
+    ))
+}
+
+/// Weeks of days from the Monday of `today`'s week, each with what keeps the
+/// person from working that day: the note of an unavailable range
+/// ("Unavailable" without one) or the production of a booking
+pub fn calendar_weeks(
+    periods: &[UnavailablePeriod],
+    bookings: &[Booking],
+    today: NaiveDate,
+    weeks: usize,
+) -> Vec<Vec<CalendarDay>> {
+    let mut calendar = production_gear::calendar_weeks(&[], today, weeks);
+    for day in calendar.iter_mut().flatten() {
+        day.booked_for = periods
+            .iter()
+            .filter(|p| p.overlaps(&day.date, &day.date))
+            .map(|p| p.note.clone().unwrap_or_else(|| "Unavailable".to_string()))
+            .chain(
+                bookings
+                    .iter()
+                    .filter(|b| {
+                        production_gear::overlaps(&b.start_date, &b.end_date, &day.date, &day.date)
+                    })
+                    .map(|b| b.production_title.clone()),
+            )
+            .collect();
+    }
+    calendar
+}
+
+pub struct PersonAvailabilityModel;
+
+impl PersonAvailabilityModel {
+    /// A person's unavailable ranges that haven't ended before `from`,
+    /// soonest first
+    pub async fn upcoming(person: &RecordId, from: &str) -> Result<Vec<UnavailablePeriod>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, start_date, end_date, note, created_at FROM unavailable_period
+                 WHERE person = $person AND end_date >= $from ORDER BY start_date ASC",
+            )
+            .bind(("person", person.clone()))
+            .bind(("from", from.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Mark a range of days as unavailable
+    pub async fn add(person: &RecordId, start: &str, end: &str, note: &str) -> Result<(), Error> {
+        let (start, end, note) = clean_period(start, end, note)?;
+
+        let count: Option<i64> = DB
+            .query("SELECT VALUE count() FROM unavailable_period WHERE person = $person GROUP ALL")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        if count.unwrap_or(0) as usize >= MAX_PERIODS {
+            return Err(Error::Validation(format!(
+                "You can list up to {} unavailable ranges; remove some that have passed",
+                MAX_PERIODS
+            )));
+        }
+
+        DB.query(
+            "CREATE unavailable_period SET person = $person, start_date = $start,
+                end_date = $end, note = $note",
+        )
+        .bind(("person", person.clone()))
+        .bind(("start", start.clone()))
+        .bind(("end", end.clone()))
+        .bind(("note", note))
+        .await?
+        .check()?;
+        debug!("{} is unavailable {} to {}", person.display(), start, end);
+        Ok(())
+    }
+
+    /// Remove one of a person's unavailable ranges
+    pub async fn remove(person: &RecordId, key: &str) -> Result<(), Error> {
+        DB.query("DELETE unavailable_period WHERE id = $id AND person = $person")
+            .bind(("id", RecordId::new("unavailable_period", key)))
+            .bind(("person", person.clone()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// People who are unavailable or booked on any day of `window`
+    pub async fn busy_during(window: &AvailabilityWindow) -> Result<Vec<RecordId>, Error> {
+        Ok(reader()
+            .query(
+                "RETURN array::union(
+                    (SELECT VALUE person FROM unavailable_period
+                        WHERE start_date <= $to AND end_date >= $from),
+                    (SELECT VALUE person FROM booking
+                        WHERE start_date <= $to AND end_date >= $from)
+                )",
+            )
+            .bind(("from", window.first_day()))
+            .bind(("to", window.last_day()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Delete a person's unavailable ranges, for account deletion
+    pub async fn forget(person: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE unavailable_period WHERE person = $person")
+            .bind(("person", person.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete availability: {}", e)))?;
+        Ok(())
+    }
+}
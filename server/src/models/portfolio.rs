@@ -0,0 +1,545 @@
+//! Portfolio PDFs
+//!
+//! Plenty of submissions still go out as a printed or attached résumé. A
+//! portfolio export renders a person's profile, credits and a few photos they
+//! pick into a branded PDF. Each export is stored privately in S3 so it can
+//! be downloaded again, and only the most recent few are kept.
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{error, info, warn};
+use ulid::Ulid;
+
+use crate::{
+    config,
+    db::DB,
+    error::Error,
+    models::{
+        involvement::InvolvementModel,
+        person::{Education, Person, Profile},
+    },
+    pdf::{Flow, Font, PageSize, text_width, wrap},
+    physical_attributes::describe,
+    record_id_ext::RecordIdExt,
+    services::{minors, s3::s3},
+    units::{self, UnitSystem},
+};
+
+const PORTFOLIO_PREFIX: &str = "private/portfolios";
+
+/// Page sizes a portfolio can be exported in, as (value, label)
+pub const PAGE_SIZES: &[(&str, &str)] = &[("letter", "US Letter"), ("a4", "A4")];
+
+/// Most photos that can go into one portfolio
+pub const MAX_STILLS: usize = 6;
+
+/// Exports kept per person; older ones are deleted
+const KEEP_EXPORTS: usize = 5;
+
+/// Longest side photos are scaled down to before embedding
+const IMAGE_MAX_PX: u32 = 1200;
+
+const BRAND_RGB: [u8; 3] = [235, 84, 55];
+const WHITE_RGB: [u8; 3] = [255, 255, 255];
+const BAND_HEIGHT: f32 = 36.0;
+const HEADSHOT_WIDTH: f32 = 150.0;
+
+pub fn page_size(name: &str) -> Option<PageSize> {
+    match name {
+        "letter" => Some(PageSize::LETTER),
+        "a4" => Some(PageSize::A4),
+        _ => None,
+    }
+}
+
+/// Storage key of an uploaded image from its `/api/media/` URL. Images
+/// hosted elsewhere are skipped.
+pub fn media_key(url: &str) -> Option<&str> {
+    url.strip_prefix("/api/media/")
+        .filter(|key| !key.is_empty() && !key.contains(".."))
+}
+
+/// "180 cm (5'11")" from a height in millimetres, in the owner's units first
+pub fn height_text(mm: i32, system: UnitSystem) -> String {
+    units::height_both(mm, system)
+}
+
+/// The year of a release date such as "2021-06-04"
+pub fn release_year(date: Option<&str>) -> Option<String> {
+    date.and_then(|d| d.get(..4))
+        .filter(|year| year.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+}
+
+pub fn education_line(education: &Education) -> String {
+    let mut line = education.institution.clone();
+    let course: Vec<&str> = [education.degree.as_deref(), education.field.as_deref()]
+        .into_iter()
+        .flatten()
+        .filter(|s| !s.is_empty())
+        .collect();
+    if !course.is_empty() {
+        line.push_str(&format!(" - {}", course.join(", ")));
+    }
+    if let Some(dates) = &education.dates {
+        let years: Vec<&str> = [dates.start.as_deref(), dates.end.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty())
+            .collect();
+        if !years.is_empty() {
+            line.push_str(&format!(" ({})", years.join(" - ")));
+        }
+    }
+    line
+}
+
+/// Casting details worth printing, as (label, value), measured in `system`
+pub fn portfolio_details(profile: &Profile, system: UnitSystem) -> Vec<(&'static str, String)> {
+    let mut details = Vec::new();
+    if let Some(range) = &profile.acting_age_range {
+        details.push(("Playing age", format!("{} - {}", range.min, range.max)));
+    }
+    if let Some(mm) = profile.height_mm.filter(|mm| *mm > 0) {
+        details.push(("Height", height_text(mm, system)));
+    }
+    if let Some(kg) = profile.weight_kg.filter(|kg| *kg > 0) {
+        details.push(("Weight", units::weight(kg, system)));
+    }
+    let text_fields = [
+        ("Build", describe(profile.body_type.as_deref(), profile.body_type_other.as_deref())),
+        ("Hair", describe(profile.hair_color.as_deref(), profile.hair_color_other.as_deref())),
+        ("Eyes", describe(profile.eye_color.as_deref(), profile.eye_color_other.as_deref())),
+        ("Nationality", profile.nationality.as_deref()),
+        ("Availability", profile.availability.as_deref()),
+    ];
+    for (label, value) in text_fields {
+        if let Some(value) = value.filter(|v| !v.is_empty()) {
+            details.push((label, value.to_string()));
+        }
+    }
+    for (label, values) in [
+        ("Languages", &profile.languages),
+        ("Skills", &profile.skills),
+        ("Unions", &profile.unions),
+    ] {
+        if !values.is_empty() {
+            details.push((label, values.join(", ")));
+        }
+    }
+    details
+}
+
+pub struct PortfolioCredit {
+    pub production: String,
+    pub role: String,
+    pub kind: String,
+    pub year: String,
+}
+
+pub struct Still {
+    pub image: DynamicImage,
+    pub caption: String,
+}
+
+/// Everything printed in a portfolio
+pub struct Portfolio {
+    pub name: String,
+    pub headline: Option<String>,
+    pub location: Option<String>,
+    pub profile_url: String,
+    pub website: Option<String>,
+    pub phone: Option<String>,
+    pub bio: Option<String>,
+    pub details: Vec<(&'static str, String)>,
+    pub credits: Vec<PortfolioCredit>,
+    pub education: Vec<String>,
+    pub awards: Vec<String>,
+    pub headshot: Option<DynamicImage>,
+    pub stills: Vec<Still>,
+}
+
+/// Height of an image drawn `width` wide, capped at `max_height`
+fn scaled_height(image: &DynamicImage, width: f32, max_height: f32) -> (f32, f32) {
+    let aspect = image.height() as f32 / image.width().max(1) as f32;
+    let height = width * aspect;
+    if height > max_height {
+        (max_height / aspect, max_height)
+    } else {
+        (width, height)
+    }
+}
+
+fn draw_band(flow: &mut Flow) {
+    let size = flow.doc_mut().size();
+    let margin = flow.margin();
+    let doc = flow.doc_mut();
+    doc.fill_rect_rgb(0.0, size.height - BAND_HEIGHT, size.width, BAND_HEIGHT, BRAND_RGB);
+    let baseline = size.height - BAND_HEIGHT / 2.0 - 4.0;
+    doc.text_rgb(margin, baseline, 12.0, Font::Bold, "SLATEHUB", WHITE_RGB);
+    let label = "PORTFOLIO";
+    let x = size.width - margin - text_width(label, 10.0, Font::Regular);
+    doc.text_rgb(x, baseline, 10.0, Font::Regular, label, WHITE_RGB);
+}
+
+/// Wrapped text drawn downwards from `y`, returning the y below it
+fn text_lines(flow: &mut Flow, x: f32, mut y: f32, width: f32, text: &str, size: f32, font: Font) -> f32 {
+    for line in wrap(text, width, size, font) {
+        y -= size * 1.3;
+        flow.doc_mut().text(x, y, size, font, &line);
+    }
+    y
+}
+
+/// Name, headline and contact lines beside the headshot
+fn draw_header(flow: &mut Flow, portfolio: &Portfolio) {
+    let margin = flow.margin();
+    let top = flow.y();
+    let content_width = flow.content_width();
+
+    let mut photo_height = 0.0;
+    let mut text_x = margin;
+    if let Some(headshot) = &portfolio.headshot {
+        let (width, height) = scaled_height(headshot, HEADSHOT_WIDTH, HEADSHOT_WIDTH * 1.35);
+        match flow.doc_mut().add_image(headshot) {
+            Ok(id) => {
+                flow.doc_mut().draw_image(id, margin, top - height, width, height);
+                photo_height = height;
+                text_x = margin + HEADSHOT_WIDTH + 18.0;
+            }
+            Err(e) => warn!("Failed to embed headshot: {}", e),
+        }
+    }
+
+    let width = content_width - (text_x - margin);
+    let mut y = text_lines(flow, text_x, top, width, &portfolio.name, 22.0, Font::Bold);
+    if let Some(headline) = &portfolio.headline {
+        y = text_lines(flow, text_x, y, width, headline, 12.0, Font::Regular);
+    }
+    if let Some(location) = &portfolio.location {
+        y = text_lines(flow, text_x, y, width, location, 10.0, Font::Regular);
+    }
+    y -= 6.0;
+    let contact = [Some(&portfolio.profile_url), portfolio.website.as_ref(), portfolio.phone.as_ref()];
+    for value in contact.into_iter().flatten() {
+        y = text_lines(flow, text_x, y, width, value, 9.0, Font::Regular);
+    }
+
+    let used = (top - y).max(photo_height);
+    flow.space(used + 18.0);
+}
+
+/// Photos two to a row, each with its caption
+fn draw_stills(flow: &mut Flow, stills: &[Still]) {
+    let gap = 16.0;
+    let cell_width = (flow.content_width() - gap) / 2.0;
+    let max_height = cell_width * 1.3;
+    for row in stills.chunks(2) {
+        let sizes: Vec<(f32, f32)> = row
+            .iter()
+            .map(|still| scaled_height(&still.image, cell_width, max_height))
+            .collect();
+        let image_height = sizes.iter().map(|(_, h)| *h).fold(0.0, f32::max);
+        let has_caption = row.iter().any(|s| !s.caption.is_empty());
+        let row_height = image_height + if has_caption { 16.0 } else { 0.0 };
+
+        flow.ensure(row_height);
+        let top = flow.y();
+        let margin = flow.margin();
+        for (i, (still, (width, height))) in row.iter().zip(&sizes).enumerate() {
+            let x = margin + i as f32 * (cell_width + gap);
+            match flow.doc_mut().add_image(&still.image) {
+                Ok(id) => flow.doc_mut().draw_image(id, x, top - height, *width, *height),
+                Err(e) => warn!("Failed to embed portfolio photo: {}", e),
+            }
+            if let Some(caption) = wrap(&still.caption, cell_width, 8.0, Font::Regular).first() {
+                flow.doc_mut().text(x, top - image_height - 11.0, 8.0, Font::Regular, caption);
+            }
+        }
+        flow.space(row_height + gap);
+    }
+}
+
+/// Render a portfolio as a PDF
+pub fn portfolio_pdf(portfolio: &Portfolio, size: PageSize) -> Vec<u8> {
+    let mut flow = Flow::new(size).with_footer(format!("{} - {}", portfolio.name, portfolio.profile_url));
+
+    draw_band(&mut flow);
+    flow.space(BAND_HEIGHT - flow.margin() + 24.0);
+    draw_header(&mut flow, portfolio);
+
+    if let Some(bio) = &portfolio.bio {
+        flow.heading("About", 12.0);
+        flow.paragraph(bio, 10.0);
+    }
+
+    if !portfolio.details.is_empty() {
+        flow.heading("Details", 12.0);
+        let pairs: Vec<(&str, String)> = portfolio
+            .details
+            .iter()
+            .map(|(label, value)| (*label, value.clone()))
+            .collect();
+        flow.key_values(&pairs, 10.0);
+    }
+
+    if !portfolio.credits.is_empty() {
+        flow.heading("Credits", 12.0);
+        let rows: Vec<Vec<String>> = portfolio
+            .credits
+            .iter()
+            .map(|c| vec![c.production.clone(), c.role.clone(), c.kind.clone(), c.year.clone()])
+            .collect();
+        flow.table(&["Production", "Role", "Type", "Year"], &[4.0, 3.0, 2.0, 1.0], &rows, 9.0);
+    }
+
+    if !portfolio.education.is_empty() {
+        flow.heading("Training & Education", 12.0);
+        for line in &portfolio.education {
+            flow.paragraph(line, 10.0);
+        }
+    }
+
+    if !portfolio.awards.is_empty() {
+        flow.heading("Awards", 12.0);
+        for line in &portfolio.awards {
+            flow.paragraph(line, 10.0);
+        }
+    }
+
+    if !portfolio.stills.is_empty() {
+        flow.ensure(200.0);
+        flow.heading("Photos", 12.0);
+        draw_stills(&mut flow, &portfolio.stills);
+    }
+
+    flow.finish()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct PortfolioExport {
+    pub id: RecordId,
+    pub person: RecordId,
+    pub file_key: String,
+    /// "letter" or "a4"
+    pub page_size: String,
+    /// URLs of the photos included
+    pub stills: Vec<String>,
+    pub size: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Options chosen on the export page
+#[derive(Debug, Clone, Default)]
+pub struct PortfolioOptions {
+    pub page_size: String,
+    pub stills: Vec<String>,
+    pub include_phone: bool,
+}
+
+/// Fetch an uploaded image and scale it down to `max_px` on its longest side
+/// for embedding in a PDF
+pub async fn load_image(url: &str, max_px: u32) -> Option<DynamicImage> {
+    let key = media_key(url)?;
+    let data = match s3() {
+        Ok(s3) => match s3.download_file(key).await {
+            Ok((data, _)) => data,
+            Err(e) => {
+                warn!("Failed to download {} for a PDF: {}", key, e);
+                return None;
+            }
+        },
+        Err(e) => {
+            warn!("S3 unavailable, {} left out of a PDF: {}", key, e);
+            return None;
+        }
+    };
+    let decoded = tokio::task::spawn_blocking(move || {
+        image::load_from_memory(&data).map(|image| image.thumbnail(max_px, max_px))
+    })
+    .await;
+    match decoded {
+        Ok(Ok(image)) => Some(image),
+        Ok(Err(e)) => {
+            warn!("Couldn't decode {} for a PDF: {}", key, e);
+            None
+        }
+        Err(e) => {
+            error!("Image decoding task failed: {}", e);
+            None
+        }
+    }
+}
+
+async fn delete_file(key: &str) {
+    match s3() {
+        Ok(s3) => {
+            if let Err(e) = s3.delete_file(key).await {
+                error!("Failed to delete portfolio {}: {}", key, e);
+            }
+        }
+        Err(e) => error!("S3 unavailable, portfolio {} not deleted: {}", key, e),
+    }
+}
+
+pub struct PortfolioModel;
+
+impl PortfolioModel {
+    /// A person's exports, newest first
+    pub async fn for_person(person: &RecordId) -> Result<Vec<PortfolioExport>, Error> {
+        Ok(DB
+            .query("SELECT * FROM portfolio_export WHERE person = $person ORDER BY created_at DESC")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// An export, if it belongs to the person
+    pub async fn get(export_id: &str, person: &RecordId) -> Result<PortfolioExport, Error> {
+        let export: Option<PortfolioExport> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("portfolio_export", export_id)))
+            .await?
+            .take(0)?;
+        match export {
+            Some(export) if export.person == *person => Ok(export),
+            Some(_) => Err(Error::Forbidden),
+            None => Err(Error::NotFound),
+        }
+    }
+
+    /// Gather what goes into a person's portfolio. Minors' portfolios leave
+    /// out what their public profile hides.
+    async fn assemble(person: &Person, options: &PortfolioOptions) -> Result<Portfolio, Error> {
+        let mut profile = person.profile.clone().unwrap_or_default();
+        if person.is_minor {
+            minors::redact_profile(&mut profile);
+        }
+
+        let credits = InvolvementModel::get_for_person(&person.id.to_raw_string())
+            .await?
+            .into_iter()
+            .map(|inv| PortfolioCredit {
+                production: inv.production_title,
+                role: inv.role.or(inv.department).unwrap_or_default(),
+                kind: inv.production_type,
+                year: release_year(inv.release_date.as_deref()).unwrap_or_default(),
+            })
+            .collect();
+
+        let headshot = match profile.avatar.as_deref() {
+            Some(url) => load_image(url, IMAGE_MAX_PX).await,
+            None => None,
+        };
+        let mut stills = Vec::new();
+        for photo in profile.photos.iter().filter(|p| options.stills.contains(&p.url)) {
+            if let Some(image) = load_image(&photo.url, IMAGE_MAX_PX).await {
+                stills.push(Still {
+                    image,
+                    caption: photo.caption.clone(),
+                });
+            }
+        }
+
+        Ok(Portfolio {
+            name: person.get_display_name(),
+            headline: profile.headline.clone().filter(|s| !s.is_empty()),
+            location: profile.location.clone().filter(|s| !s.is_empty()),
+            profile_url: format!("{}/{}", config::app_url(), person.username),
+            website: profile.website.clone().filter(|s| !s.is_empty()),
+            phone: profile.phone.clone().filter(|s| options.include_phone && !s.is_empty()),
+            bio: profile.bio.clone().filter(|s| !s.trim().is_empty()),
+            // The portfolio is the owner's document, in their units
+            details: portfolio_details(
+                &profile,
+                UnitSystem::from_preference(person.units.as_deref()),
+            ),
+            credits,
+            education: profile.education.iter().map(education_line).collect(),
+            awards: profile
+                .awards
+                .iter()
+                .map(|a| match &a.description {
+                    Some(description) if !description.is_empty() => {
+                        format!("{} ({}) - {}", a.name, a.year, description)
+                    }
+                    _ => format!("{} ({})", a.name, a.year),
+                })
+                .collect(),
+            headshot,
+            stills,
+        })
+    }
+
+    /// Render a new portfolio, store it and drop exports beyond the few kept
+    pub async fn create(person: &Person, options: PortfolioOptions) -> Result<PortfolioExport, Error> {
+        let size = page_size(&options.page_size)
+            .ok_or_else(|| Error::Validation("Choose US Letter or A4".to_string()))?;
+        if options.stills.len() > MAX_STILLS {
+            return Err(Error::Validation(format!("Choose up to {} photos", MAX_STILLS)));
+        }
+        let photos = person.profile.as_ref().map(|p| p.photos.as_slice()).unwrap_or_default();
+        if options.stills.iter().any(|url| !photos.iter().any(|p| p.url == *url)) {
+            return Err(Error::Validation("Choose photos from your profile".to_string()));
+        }
+
+        let portfolio = Self::assemble(person, &options).await?;
+        let pdf = tokio::task::spawn_blocking(move || portfolio_pdf(&portfolio, size))
+            .await
+            .map_err(|e| Error::Internal(format!("Portfolio rendering failed: {}", e)))?;
+
+        let key = format!("{}/{}/{}.pdf", PORTFOLIO_PREFIX, person.id.key_string(), Ulid::new());
+        let byte_count = pdf.len() as i64;
+        s3()?.upload_file(&key, Bytes::from(pdf), "application/pdf").await?;
+
+        let export: Option<PortfolioExport> = DB
+            .query(
+                "CREATE portfolio_export SET person = $person, file_key = $key, page_size = $page_size,
+                    stills = $stills, size = $size, created_at = time::now()",
+            )
+            .bind(("person", person.id.clone()))
+            .bind(("key", key.clone()))
+            .bind(("page_size", options.page_size))
+            .bind(("stills", options.stills))
+            .bind(("size", byte_count))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to save portfolio: {}", e)))?
+            .take(0)?;
+        let Some(export) = export else {
+            delete_file(&key).await;
+            return Err(Error::Internal("Failed to save portfolio".to_string()));
+        };
+        info!("Exported portfolio for {} ({} bytes)", person.username, byte_count);
+
+        for old in Self::for_person(&person.id).await?.into_iter().skip(KEEP_EXPORTS) {
+            Self::delete(&old).await?;
+        }
+        Ok(export)
+    }
+
+    pub async fn delete(export: &PortfolioExport) -> Result<(), Error> {
+        DB.query("DELETE $id")
+            .bind(("id", export.id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete portfolio: {}", e)))?;
+        delete_file(&export.file_key).await;
+        Ok(())
+    }
+
+    /// Delete a person's exports and files, for account deletion
+    pub async fn forget(person: &RecordId) -> Result<(), Error> {
+        let exports: Vec<PortfolioExport> = DB
+            .query("DELETE portfolio_export WHERE person = $person RETURN BEFORE")
+            .bind(("person", person.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete portfolios: {}", e)))?
+            .take(0)?;
+        for export in &exports {
+            delete_file(&export.file_key).await;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+//! Loading a record together with its related records
+//!
+//! A page that shows a production with its members and credits would
+//! otherwise run a query per relation, and a loop over rows that looked up
+//! each one's relations would run a query per row. A `Preload` puts each
+//! relation in a subquery of one SELECT instead, so the record and
+//! everything hanging off it come back in a single round trip, shaped like
+//! the DTO it deserializes into:
+//!
+//! ```text
+//! SELECT $this AS production,
+//!     (SELECT ... FROM member_of WHERE out = $parent.id) AS members,
+//!     count(<-member_of[WHERE role = 'owner']) > 0 AS is_claimed
+//! FROM production WHERE slug = $slug LIMIT 1
+//! ```
+//!
+//! Inside a subquery `$parent` is the record being loaded. A relation can
+//! also be a plain expression on the record, such as a graph traversal.
+
+/// A record and the relations to load with it
+#[derive(Debug, Clone)]
+pub struct Preload {
+    root: &'static str,
+    relations: Vec<(&'static str, String)>,
+}
+
+impl Preload {
+    /// Load records whole into the DTO's `root` field
+    pub fn new(root: &'static str) -> Self {
+        Self {
+            root,
+            relations: Vec::new(),
+        }
+    }
+
+    /// Load `expr` into the DTO's `name` field. A SELECT is wrapped as a
+    /// subquery; anything else is used as is.
+    pub fn with(mut self, name: &'static str, expr: impl Into<String>) -> Self {
+        let expr = expr.into();
+        let expr = expr.trim();
+        let expr = if expr.to_uppercase().starts_with("SELECT") {
+            format!("({})", expr)
+        } else {
+            expr.to_string()
+        };
+        self.relations.push((name, expr));
+        self
+    }
+
+    pub fn relation_names(&self) -> Vec<&'static str> {
+        self.relations.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// The one statement loading the records `from` selects, e.g.
+    /// `"production WHERE slug = $slug LIMIT 1"`
+    pub fn sql(&self, from: &str) -> String {
+        let mut fields = vec![format!("$this AS {}", self.root)];
+        fields.extend(
+            self.relations
+                .iter()
+                .map(|(name, expr)| format!("{} AS {}", expr, name)),
+        );
+        format!("SELECT {} FROM {}", fields.join(",\n    "), from)
+    }
+}
+
+/// Top-level statements in `sql`, i.e. round trips it would take if each
+/// were sent alone. Semicolons inside strings, brackets and subqueries don't
+/// count.
+pub fn statement_count(sql: &str) -> usize {
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut count = 0;
+    let mut pending = false;
+    for c in sql.chars() {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' | '`' => {
+                quote = Some(c);
+                pending = true;
+            }
+            '(' | '[' | '{' => {
+                depth += 1;
+                pending = true;
+            }
+            ')' | ']' | '}' => {
+                depth -= 1;
+                pending = true;
+            }
+            ';' if depth == 0 => {
+                if pending {
+                    count += 1;
+                }
+                pending = false;
+            }
+            c if !c.is_whitespace() => pending = true,
+            _ => {}
+        }
+    }
+    if pending { count + 1 } else { count }
+}
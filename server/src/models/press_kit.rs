@@ -0,0 +1,510 @@
+//! Press kits
+//!
+//! A production's owners and admins put together its electronic press kit:
+//! a logline, synopsis and director's statement, a trailer, stills picked
+//! from the production's photos and who the press should contact. Credits,
+//! companies and published festival selections come from the production
+//! itself, so the kit never goes stale. Once published it's a public page
+//! at `/productions/{slug}/press`, and everything can be downloaded as one
+//! zip of plain-text press notes, the poster and the stills.
+
+use std::io::{Cursor, Write};
+
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::warn;
+
+use crate::{
+    config,
+    db::DB,
+    error::Error,
+    models::{
+        company_credit::{CompanyCreditModel, role_label},
+        festival_submission::{FestivalSubmission, FestivalSubmissionModel},
+        involvement::InvolvementModel,
+        portfolio::{media_key, release_year},
+        production::{Production, ProductionPhoto},
+    },
+    services::s3::s3,
+    video_platforms,
+};
+
+/// Most stills a kit can carry
+pub const MAX_STILLS: usize = 12;
+
+/// Press contacts a kit lists
+pub const MAX_CONTACTS: usize = 3;
+
+pub const MAX_LOGLINE_CHARS: usize = 300;
+pub const MAX_TEXT_CHARS: usize = 5000;
+pub const MAX_CONTACT_CHARS: usize = 120;
+
+fn clean_text(value: &str, max: usize, what: &str) -> Result<Option<String>, Error> {
+    let value = value.trim();
+    if value.chars().count() > max {
+        return Err(Error::Validation(format!(
+            "{} can be up to {} characters",
+            what, max
+        )));
+    }
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/// Who the press should get in touch with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SurrealValue)]
+pub struct PressContact {
+    /// "Publicity", "Sales"
+    pub role: String,
+    pub name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+}
+
+/// Contacts from the form's parallel columns. Rows left blank are dropped.
+pub fn parse_contacts(
+    roles: &[String],
+    names: &[String],
+    emails: &[String],
+    phones: &[String],
+) -> Result<Vec<PressContact>, Error> {
+    let cell = |column: &[String], row: usize| column.get(row).map_or("", |v| v.as_str());
+    let rows = roles
+        .len()
+        .max(names.len())
+        .max(emails.len())
+        .max(phones.len());
+    let mut contacts = Vec::new();
+    for row in 0..rows {
+        let role = clean_text(cell(roles, row), MAX_CONTACT_CHARS, "A contact's role")?;
+        let name = clean_text(cell(names, row), MAX_CONTACT_CHARS, "A contact's name")?;
+        let email = clean_text(cell(emails, row), MAX_CONTACT_CHARS, "A contact's email")?;
+        let phone = clean_text(cell(phones, row), MAX_CONTACT_CHARS, "A contact's phone")?;
+        if name.is_none() && email.is_none() && phone.is_none() {
+            continue;
+        }
+        let name = name.ok_or_else(|| Error::Validation("Name each press contact".to_string()))?;
+        if email.is_none() && phone.is_none() {
+            return Err(Error::Validation(format!(
+                "Give an email or phone number for {}",
+                name
+            )));
+        }
+        if let Some(email) = email.as_deref()
+            && !(email.contains('@') && email.contains('.'))
+        {
+            return Err(Error::Validation(format!(
+                "\"{}\" isn't an email address",
+                email
+            )));
+        }
+        contacts.push(PressContact {
+            role: role.unwrap_or_else(|| "Press".to_string()),
+            name,
+            email,
+            phone,
+        });
+    }
+    if contacts.len() > MAX_CONTACTS {
+        return Err(Error::Validation(format!(
+            "A press kit lists up to {} contacts",
+            MAX_CONTACTS
+        )));
+    }
+    Ok(contacts)
+}
+
+/// A press kit from the form, checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct PressKitData {
+    pub published: bool,
+    pub logline: Option<String>,
+    pub synopsis: Option<String>,
+    pub statement: Option<String>,
+    pub trailer_url: Option<String>,
+    pub stills: Vec<String>,
+    pub contacts: Vec<PressContact>,
+}
+
+impl PressKitData {
+    /// `gallery` is the URLs of the production's photos; picked stills have
+    /// to be among them.
+    #[allow(clippy::too_many_arguments)]
+    pub fn parse(
+        published: bool,
+        logline: &str,
+        synopsis: &str,
+        statement: &str,
+        trailer_url: &str,
+        stills: &[String],
+        gallery: &[String],
+        contacts: Vec<PressContact>,
+    ) -> Result<Self, Error> {
+        let trailer_url = clean_text(trailer_url, 500, "The trailer link")?;
+        if let Some(url) = trailer_url.as_deref()
+            && video_platforms::parse_video_url(url).is_none()
+        {
+            return Err(Error::Validation(
+                "Link the trailer on YouTube, Vimeo, TikTok or Dailymotion".to_string(),
+            ));
+        }
+        let mut picked: Vec<String> = Vec::new();
+        for url in stills {
+            if !gallery.contains(url) {
+                return Err(Error::Validation(
+                    "Stills have to come from the production's photos".to_string(),
+                ));
+            }
+            if !picked.contains(url) {
+                picked.push(url.clone());
+            }
+        }
+        if picked.len() > MAX_STILLS {
+            return Err(Error::Validation(format!(
+                "Pick up to {} stills",
+                MAX_STILLS
+            )));
+        }
+        let synopsis = clean_text(synopsis, MAX_TEXT_CHARS, "The synopsis")?;
+        if published && synopsis.is_none() {
+            return Err(Error::Validation(
+                "Write a synopsis before publishing the press kit".to_string(),
+            ));
+        }
+        Ok(Self {
+            published,
+            logline: clean_text(logline, MAX_LOGLINE_CHARS, "The logline")?,
+            synopsis,
+            statement: clean_text(statement, MAX_TEXT_CHARS, "The director's statement")?,
+            trailer_url,
+            stills: picked,
+            contacts,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct PressKit {
+    pub id: RecordId,
+    pub published: bool,
+    pub logline: Option<String>,
+    pub synopsis: Option<String>,
+    pub statement: Option<String>,
+    pub trailer_url: Option<String>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub stills: Vec<String>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub contacts: Vec<PressContact>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Player URL for a trailer link, without autoplay
+pub fn trailer_embed(url: &str) -> Option<String> {
+    let info = video_platforms::parse_video_url(url)?;
+    let embed = video_platforms::embed_url(info.platform, &info.video_id);
+    Some(embed.split('?').next().unwrap_or_default().to_string())
+}
+
+/// The picked stills in the kit's order, skipping any since removed from
+/// the production's photos
+pub fn press_stills(photos: &[ProductionPhoto], picked: &[String]) -> Vec<ProductionPhoto> {
+    picked
+        .iter()
+        .filter_map(|url| photos.iter().find(|photo| &photo.url == url))
+        .cloned()
+        .collect()
+}
+
+/// "stills/03.png" for the third still, keeping the stored extension
+pub fn still_file_name(index: usize, url: &str) -> String {
+    format!("stills/{:02}.{}", index + 1, image_extension(url))
+}
+
+fn image_extension(url: &str) -> String {
+    url.rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .filter(|ext| matches!(ext.as_str(), "jpg" | "jpeg" | "png" | "webp" | "gif"))
+        .unwrap_or_else(|| "jpg".to_string())
+}
+
+pub fn zip_name(slug: &str) -> String {
+    format!("{}-press-kit.zip", slug)
+}
+
+/// One line of the credits: "Director" / "Ana Ruiz"
+#[derive(Debug, Clone, PartialEq)]
+pub struct PressCredit {
+    pub role: String,
+    pub name: String,
+}
+
+/// Everything on a press kit, with the production's own details filled in
+#[derive(Debug, Clone)]
+pub struct PressKitContent {
+    pub title: String,
+    /// "Short Film · 2026"
+    pub tagline: String,
+    pub logline: Option<String>,
+    pub synopsis: Option<String>,
+    pub statement: Option<String>,
+    pub trailer_url: Option<String>,
+    pub stills: Vec<ProductionPhoto>,
+    pub cast: Vec<PressCredit>,
+    pub crew: Vec<PressCredit>,
+    pub companies: Vec<PressCredit>,
+    pub selections: Vec<FestivalSubmission>,
+    pub contacts: Vec<PressContact>,
+    /// Where the kit lives online
+    pub page_url: String,
+}
+
+/// "Feature Film · 2026", from the production's type and release (or start)
+/// year
+pub fn tagline(production: &Production) -> String {
+    let year = release_year(production.release_date.as_deref())
+        .or_else(|| production.start_date.map(|d| d.year().to_string()));
+    match year {
+        Some(year) => format!("{} · {}", production.production_type, year),
+        None => production.production_type.clone(),
+    }
+}
+
+fn section(out: &mut String, heading: &str, body: &str) {
+    out.push_str(&format!(
+        "\n{}\n{}\n{}\n",
+        heading,
+        "-".repeat(heading.chars().count()),
+        body
+    ));
+}
+
+/// The kit as plain-text press notes, for the zip
+pub fn press_text(content: &PressKitContent) -> String {
+    let mut out = format!("{}\n{}\n", content.title, content.tagline);
+    if let Some(logline) = &content.logline {
+        out.push_str(&format!("\n{}\n", logline));
+    }
+    if let Some(synopsis) = &content.synopsis {
+        section(&mut out, "Synopsis", synopsis);
+    }
+    if let Some(statement) = &content.statement {
+        section(&mut out, "Director's Statement", statement);
+    }
+    if let Some(url) = &content.trailer_url {
+        section(&mut out, "Trailer", url);
+    }
+    let credit_lines = |credits: &[PressCredit]| {
+        credits
+            .iter()
+            .map(|c| format!("{}: {}", c.role, c.name))
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    if !content.crew.is_empty() {
+        section(&mut out, "Key Crew", &credit_lines(&content.crew));
+    }
+    if !content.cast.is_empty() {
+        let cast = content
+            .cast
+            .iter()
+            .map(|c| format!("{} as {}", c.name, c.role))
+            .collect::<Vec<_>>()
+            .join("\n");
+        section(&mut out, "Cast", &cast);
+    }
+    if !content.companies.is_empty() {
+        section(&mut out, "Companies", &credit_lines(&content.companies));
+    }
+    if !content.selections.is_empty() {
+        let laurels = content
+            .selections
+            .iter()
+            .map(|s| format!("{} - {} {}", s.laurel(), s.festival, s.year()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        section(&mut out, "Selections & Awards", &laurels);
+    }
+    if !content.contacts.is_empty() {
+        let contacts = content
+            .contacts
+            .iter()
+            .map(|c| {
+                let reach: Vec<&str> = [c.email.as_deref(), c.phone.as_deref()]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                format!("{}: {} ({})", c.role, c.name, reach.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        section(&mut out, "Contacts", &contacts);
+    }
+    if !content.stills.is_empty() {
+        let captions = content
+            .stills
+            .iter()
+            .enumerate()
+            .map(|(i, still)| {
+                let name = still_file_name(i, &still.url);
+                match still.caption.trim() {
+                    "" => name,
+                    caption => format!("{}: {}", name, caption),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        section(&mut out, "Stills", &captions);
+    }
+    out.push_str(&format!("\nOnline: {}\n", content.page_url));
+    out
+}
+
+const KIT_FIELDS: &str =
+    "id, published, logline, synopsis, statement, trailer_url, stills, contacts, updated_at";
+
+pub struct PressKitModel;
+
+impl PressKitModel {
+    pub async fn for_production(production: &RecordId) -> Result<Option<PressKit>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM press_kit WHERE production = $production LIMIT 1",
+                KIT_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn is_published(production: &RecordId) -> Result<bool, Error> {
+        Ok(Self::for_production(production)
+            .await?
+            .is_some_and(|kit| kit.published))
+    }
+
+    pub async fn save(
+        production: &RecordId,
+        data: &PressKitData,
+        updated_by: &RecordId,
+    ) -> Result<(), Error> {
+        DB.query(
+            "UPSERT press_kit SET production = $production, published = $published,
+                logline = $logline, synopsis = $synopsis, statement = $statement,
+                trailer_url = $trailer_url, stills = $stills, contacts = $contacts,
+                updated_by = $updated_by
+             WHERE production = $production",
+        )
+        .bind(("production", production.clone()))
+        .bind(("published", data.published))
+        .bind(("logline", data.logline.clone()))
+        .bind(("synopsis", data.synopsis.clone()))
+        .bind(("statement", data.statement.clone()))
+        .bind(("trailer_url", data.trailer_url.clone()))
+        .bind(("stills", data.stills.clone()))
+        .bind(("contacts", data.contacts.clone()))
+        .bind(("updated_by", updated_by.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to save press kit: {}", e)))?
+        .check()?;
+        Ok(())
+    }
+
+    /// The kit with the production's credits, companies and published
+    /// selections
+    pub async fn content(
+        production: &Production,
+        kit: &PressKit,
+    ) -> Result<PressKitContent, Error> {
+        let mut cast = Vec::new();
+        let mut crew = Vec::new();
+        for involvement in InvolvementModel::get_for_production(&production.id).await? {
+            let name = involvement
+                .person_name
+                .filter(|name| !name.is_empty())
+                .unwrap_or(involvement.person_username);
+            if involvement.relation_type == "cast" {
+                let role = involvement.role.unwrap_or_else(|| "Cast".to_string());
+                cast.push(PressCredit { role, name });
+            } else {
+                let role = involvement
+                    .role
+                    .or(involvement.department)
+                    .unwrap_or_else(|| "Crew".to_string());
+                crew.push(PressCredit { role, name });
+            }
+        }
+        let companies = CompanyCreditModel::companies_for_production(&production.id)
+            .await?
+            .into_iter()
+            .map(|company| PressCredit {
+                role: role_label(&company.role).to_string(),
+                name: company.org_name,
+            })
+            .collect();
+
+        Ok(PressKitContent {
+            title: production.title.clone(),
+            tagline: tagline(production),
+            logline: kit.logline.clone(),
+            synopsis: kit.synopsis.clone(),
+            statement: kit.statement.clone(),
+            trailer_url: kit.trailer_url.clone(),
+            stills: press_stills(&production.photos, &kit.stills),
+            cast,
+            crew,
+            companies,
+            selections: FestivalSubmissionModel::public_selections(&production.id).await?,
+            contacts: kit.contacts.clone(),
+            page_url: format!(
+                "{}/productions/{}/press",
+                config::app_url(),
+                production.slug
+            ),
+        })
+    }
+
+    /// The downloadable kit: press notes, the poster and the stills. Images
+    /// hosted elsewhere, or that can't be fetched, are left out.
+    pub async fn zip(content: &PressKitContent, poster: Option<&str>) -> Result<Vec<u8>, Error> {
+        let mut images: Vec<(String, Vec<u8>)> = Vec::new();
+        let wanted = poster
+            .map(|url| (format!("poster.{}", image_extension(url)), url))
+            .into_iter()
+            .chain(
+                content
+                    .stills
+                    .iter()
+                    .enumerate()
+                    .map(|(i, still)| (still_file_name(i, &still.url), still.url.as_str())),
+            );
+        for (name, url) in wanted {
+            let Some(key) = media_key(url) else {
+                continue;
+            };
+            match s3()?.download_file(key).await {
+                Ok((data, _)) => images.push((name, data.to_vec())),
+                Err(e) => warn!("Leaving {} out of the press kit: {}", key, e),
+            }
+        }
+
+        let zip_err = |e: zip::result::ZipError| Error::Internal(format!("Zip error: {}", e));
+        let io_err = |e: std::io::Error| Error::Internal(format!("Zip write error: {}", e));
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let text = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        // Images are compressed already
+        let stored = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+
+        zip.start_file("press-notes.txt", text).map_err(zip_err)?;
+        zip.write_all(press_text(content).as_bytes())
+            .map_err(io_err)?;
+        for (name, data) in images {
+            zip.start_file(name, stored).map_err(zip_err)?;
+            zip.write_all(&data).map_err(io_err)?;
+        }
+        Ok(zip.finish().map_err(zip_err)?.into_inner())
+    }
+}
@@ -1,8 +1,12 @@
 use crate::db::DB;
 use crate::error::Error;
+use crate::models::company_credit::CompanyCreditModel;
+use crate::models::involvement::{InvolvementWithPerson, production_credits_query};
+use crate::models::preload::Preload;
 use crate::record_id_ext::RecordIdExt;
 use crate::services::embedding::build_production_embedding_text;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use surrealdb::types::{RecordId, SurrealValue};
 use tracing::debug;
@@ -148,6 +152,108 @@ pub struct ProductionMember {
     pub is_verified: bool,               // Whether org is verified (gold checkmark)
 }
 
+/// Members of a production, owners first. `production` is a SurrealQL
+/// expression for the production's id, e.g. `$production` or `$parent.id` in
+/// a preload.
+pub fn members_query(production: &str) -> String {
+    format!(
+        "SELECT
+            <string> in.id as id,
+            in.name as name,
+            in.username as username,
+            in.slug as slug,
+            IF <string> type::table(in) = 'person' THEN in.profile.avatar ELSE in.logo END as avatar,
+            role,
+            production_roles,
+            <string> type::table(in) as member_type,
+            invitation_status,
+            in.verified ?? false as is_verified
+        FROM member_of
+        WHERE out = {}
+        ORDER BY role ASC, in.name ASC",
+        production
+    )
+}
+
+/// A production with what its page shows, loaded in one query
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ProductionPage {
+    pub production: Production,
+    pub members: Vec<ProductionMember>,
+    pub credits: Vec<InvolvementWithPerson>,
+    pub is_claimed: bool,
+}
+
+/// How `ProductionPage` is loaded
+pub fn page_preload() -> Preload {
+    Preload::new("production")
+        .with("members", members_query("$parent.id"))
+        .with("credits", production_credits_query("$parent.id"))
+        .with("is_claimed", "count(<-member_of[WHERE role = 'owner']) > 0")
+}
+
+/// One person on a production's contact sheet. Minors' email and phone are
+/// left out.
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct CrewContact {
+    pub name: String,
+    pub username: String,
+    #[serde(default)]
+    #[surreal(default)]
+    pub production_roles: Option<Vec<String>>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+}
+
+/// One way a person is linked to a production: a member of its team, a cast
+/// or crew credit, or an application to one of its jobs
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ProductionLink {
+    pub person: RecordId,
+    /// "member", "cast", "crew", "credit" or "applicant"
+    pub kind: String,
+    /// Production roles, character, job title or role applied for
+    #[serde(default)]
+    #[surreal(default)]
+    pub role: Option<String>,
+}
+
+impl ProductionLink {
+    /// "Crew: Gaffer", "Cast as Ophelia", "Applied for Gaffer"
+    pub fn label(&self) -> String {
+        let role = self
+            .role
+            .as_deref()
+            .map(str::trim)
+            .filter(|r| !r.is_empty());
+        match (self.kind.as_str(), role) {
+            ("member", Some(role)) => format!("Team: {}", role),
+            ("member", None) => "Team".to_string(),
+            ("cast", Some(role)) => format!("Cast as {}", role),
+            ("cast", None) => "Cast".to_string(),
+            ("crew", Some(role)) => format!("Crew: {}", role),
+            ("crew", None) => "Crew".to_string(),
+            ("applicant", Some(role)) => format!("Applied for {}", role),
+            ("applicant", None) => "Applicant".to_string(),
+            (_, Some(role)) => format!("Credit: {}", role),
+            (_, None) => "Credit".to_string(),
+        }
+    }
+}
+
+/// Each linked person's labels, keyed by their id as "person:key"
+pub fn link_labels(links: &[ProductionLink]) -> HashMap<String, Vec<String>> {
+    let mut labels: HashMap<String, Vec<String>> = HashMap::new();
+    for link in links {
+        let entry = labels.entry(link.person.to_raw_string()).or_default();
+        let label = link.label();
+        if !entry.contains(&label) {
+            entry.push(label);
+        }
+    }
+    labels
+}
+
 /// Production membership info (for "my productions" listing)
 #[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
 pub struct ProductionMembership {
@@ -258,6 +364,7 @@ impl ProductionModel {
             data.location.as_deref(),
             data.start_date.as_deref(),
             data.end_date.as_deref(),
+            &[],
         );
 
         let mut result = DB
@@ -357,6 +464,18 @@ impl ProductionModel {
             .ok_or_else(|| Error::NotFound)
     }
 
+    /// A production by slug with its members, credits and claim status, in
+    /// one round trip
+    pub async fn page(slug: &str) -> Result<ProductionPage, Error> {
+        let sql = page_preload().sql("production WHERE slug = $slug LIMIT 1");
+        let mut result = crate::db::retry_read("production.page", || {
+            DB.query(&sql).bind(("slug", slug.to_string()))
+        })
+        .await?;
+        let pages: Vec<ProductionPage> = result.take(0)?;
+        pages.into_iter().next().ok_or(Error::NotFound)
+    }
+
     /// List all productions with optional filters
     pub async fn list(
         limit: Option<usize>,
@@ -383,8 +502,8 @@ impl ProductionModel {
                     (IF string::lowercase(title ?? '') CONTAINS string::lowercase($filter ?? '') THEN 50 ELSE 0 END)
                     + (IF string::lowercase(description ?? '') CONTAINS string::lowercase($filter ?? '') THEN 20 ELSE 0 END)
                     + (IF string::lowercase(location ?? '') CONTAINS string::lowercase($filter ?? '') THEN 20 ELSE 0 END)
-                    + (IF embedding IS NOT NONE AND $has_embedding = true
-                        THEN vector::similarity::cosine(embedding, $query_embedding) * 30
+                    + (IF (embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                        THEN vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) * 30
                         ELSE 0
                     END)
                 ) AS _score"
@@ -409,7 +528,7 @@ impl ProductionModel {
                 text_or_vector.push("string::lowercase(location ?? '') CONTAINS string::lowercase($filter)".to_string());
             }
             if has_embedding {
-                text_or_vector.push(format!("(embedding IS NOT NONE AND $has_embedding = true AND vector::similarity::cosine(embedding, $query_embedding) > {})", crate::config::search_weights().vector_threshold));
+                text_or_vector.push(format!("((embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true AND vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) > {})", crate::config::search_config().productions.vector_threshold));
             }
             query.push_str(&format!(" AND ({})", text_or_vector.join(" OR ")));
         }
@@ -433,7 +552,7 @@ impl ProductionModel {
             query.push_str(&format!(" START {}", offset));
         }
 
-        let mut db_query = DB.query(&query);
+        let mut db_query = crate::db::reader().query(&query);
 
         if let Some(status) = status_filter {
             db_query = db_query.bind(("status", status.to_string()));
@@ -514,6 +633,9 @@ impl ProductionModel {
         let start_date = data.start_date.as_ref().or(current_start_str.as_ref());
         let end_date = data.end_date.as_ref().or(current_end_str.as_ref());
 
+        let companies = CompanyCreditModel::company_lines(production_id)
+            .await
+            .unwrap_or_default();
         let embedding_text = build_production_embedding_text(
             title,
             production_type,
@@ -522,6 +644,7 @@ impl ProductionModel {
             location.map(|s| s.as_str()),
             start_date.map(|s| s.as_str()),
             end_date.map(|s| s.as_str()),
+            &companies,
         );
 
         let query = format!(
@@ -595,6 +718,27 @@ impl ProductionModel {
                 Error::Database(format!("Failed to delete involvement relations: {}", e))
             })?;
 
+        // Delete company credits
+        DB.query("DELETE company_credit WHERE out = $id")
+            .bind(("id", production_id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete company credits: {}", e)))?;
+
+        // Delete scouting collections
+        crate::models::scouting::ScoutingModel::delete_for_production(production_id).await?;
+
+        // Delete the deliverables checklist and its attachments
+        crate::models::deliverable::DeliverableModel::delete_for_production(production_id).await?;
+
+        // Delete the inbox and its attachments
+        crate::models::production_inbox::ProductionInboxModel::delete_for_production(production_id).await?;
+
+        // Delete the shot list, daily reports, timecards, offers and bookings
+        DB.query("DELETE booking WHERE production = $id; DELETE offer WHERE production = $id; DELETE timecard WHERE production = $id; DELETE crew_deal WHERE production = $id; DELETE daily_report WHERE production = $id; DELETE shot WHERE production = $id; DELETE scene WHERE production = $id; DELETE shoot_day WHERE production = $id; DELETE house_rules_ack WHERE production = $id; DELETE production_gear WHERE production = $id; DELETE gear_quote WHERE production = $id; DELETE budget_line WHERE production = $id; DELETE festival_submission WHERE production = $id; DELETE press_kit WHERE production = $id; DELETE guest_link WHERE production = $id")
+            .bind(("id", production_id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete shot list: {}", e)))?;
+
         // Delete the production
         DB.query(&format!("DELETE {}", production_id.display()))
             .await
@@ -647,26 +791,9 @@ impl ProductionModel {
     pub async fn get_members(production_id: &RecordId) -> Result<Vec<ProductionMember>, Error> {
         debug!("Fetching members for production: {}", production_id.display());
 
-        let query = format!(
-            "SELECT
-                <string> in.id as id,
-                in.name as name,
-                in.username as username,
-                in.slug as slug,
-                IF <string> type::table(in) = 'person' THEN in.profile.avatar ELSE in.logo END as avatar,
-                role,
-                production_roles,
-                <string> type::table(in) as member_type,
-                invitation_status,
-                in.verified ?? false as is_verified
-            FROM member_of
-            WHERE out = {}
-            ORDER BY role ASC, in.name ASC",
-            production_id.display()
-        );
-
         let mut result = DB
-            .query(&query)
+            .query(members_query("$production"))
+            .bind(("production", production_id.clone()))
             .await
             .map_err(|e| Error::Database(format!("Failed to fetch production members: {}", e)))?;
 
@@ -674,6 +801,61 @@ impl ProductionModel {
         Ok(members)
     }
 
+    /// Contact details of the people who accepted a place on the production,
+    /// for its contact sheet
+    pub async fn contact_sheet(production_id: &RecordId) -> Result<Vec<CrewContact>, Error> {
+        let mut result = DB
+            .query(
+                "SELECT
+                    in.name ?? in.username AS name,
+                    in.username AS username,
+                    production_roles,
+                    IF in.is_minor ?? false THEN NONE ELSE in.email END AS email,
+                    IF in.is_minor ?? false THEN NONE ELSE in.profile.phone END AS phone
+                FROM member_of
+                WHERE out = $production
+                    AND invitation_status = 'accepted'
+                    AND type::table(in) = 'person'
+                ORDER BY name ASC",
+            )
+            .bind(("production", production_id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch contact sheet: {}", e)))?;
+
+        let contacts: Vec<CrewContact> = result.take(0)?;
+        Ok(contacts)
+    }
+
+    /// Everyone linked to a production: accepted members, cast and crew
+    /// credits, and applicants to its jobs who haven't withdrawn
+    pub async fn linked_people(production_id: &RecordId) -> Result<Vec<ProductionLink>, Error> {
+        let mut result = DB
+            .query(
+                "SELECT in AS person, 'member' AS kind,
+                    array::join(production_roles ?? [], ', ') AS role
+                FROM member_of
+                WHERE out = $production
+                    AND invitation_status = 'accepted'
+                    AND type::table(in) = 'person';
+                SELECT in AS person, relation_type AS kind, role
+                FROM involvement
+                WHERE out = $production AND type::table(in) = 'person';
+                SELECT in AS person, 'applicant' AS kind, role_title AS role
+                FROM application
+                WHERE out.related_production = $production AND status != 'withdrawn';",
+            )
+            .bind(("production", production_id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to fetch linked people: {}", e)))?;
+
+        let mut links: Vec<ProductionLink> = result.take(0)?;
+        let credits: Vec<ProductionLink> = result.take(1)?;
+        let applicants: Vec<ProductionLink> = result.take(2)?;
+        links.extend(credits);
+        links.extend(applicants);
+        Ok(links)
+    }
+
     /// Check if a user or organization is a member of a production
     pub async fn is_member(production_id: &RecordId, member_id: &str) -> Result<bool, Error> {
         let member_rid = validate_record_id_str(member_id)?;
@@ -1110,6 +1292,7 @@ impl ProductionModel {
             None,
             release_date.as_deref(),
             None,
+            &[],
         );
 
         let query = r#"
@@ -0,0 +1,480 @@
+//! Production gear: the equipment assigned to a production, and the manifest
+//! of it insurers ask for before a shoot.
+//!
+//! Production owners and admins assign equipment they own, personally or
+//! through one of their organizations. The manifest lists every item with its
+//! serial number, owner and replacement value, and exports as CSV or PDF.
+//!
+//! An assignment with dates books the gear for them: it can't be assigned to
+//! another production for overlapping dates, and checkouts that overlap are
+//! refused unless the renter is on the production that booked it.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+use crate::{
+    csv,
+    db::DB,
+    error::Error,
+    models::{equipment::Equipment, production::ProductionModel},
+    pdf::{Flow, PageSize},
+    record_id_ext::RecordIdExt,
+};
+
+/// One item on a production's manifest
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ManifestLine {
+    /// The `production_gear` record
+    pub id: RecordId,
+    pub equipment: RecordId,
+    pub name: String,
+    pub category: String,
+    pub manufacturer: Option<String>,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    /// Person or organization that owns it
+    pub owner_name: Option<String>,
+    pub kit_name: Option<String>,
+    pub replacement_value: Option<f64>,
+    /// "YYYY-MM-DD"; `None` when it isn't booked for dates
+    #[serde(default)]
+    #[surreal(default)]
+    pub start_date: Option<String>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub end_date: Option<String>,
+    pub added_at: DateTime<Utc>,
+}
+
+impl ManifestLine {
+    /// "Canon C70", or "ARRI SkyPanel S60-C" with the maker and model
+    pub fn make_and_model(&self) -> String {
+        [self.manufacturer.as_deref(), self.model.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// "2026-06-01 to 2026-06-05", if it's booked for dates
+    pub fn dates_label(&self) -> Option<String> {
+        match (&self.start_date, &self.end_date) {
+            (Some(start), Some(end)) => Some(dates_label(start, end)),
+            _ => None,
+        }
+    }
+
+    /// "$12,500.00", if it has a replacement value
+    pub fn value_label(&self) -> Option<String> {
+        self.replacement_value
+            .map(|v| format!("${}", format_value(v)))
+    }
+}
+
+/// Total replacement value, and how many items have none set
+pub fn manifest_total(lines: &[ManifestLine]) -> (f64, usize) {
+    let total = lines.iter().filter_map(|l| l.replacement_value).sum();
+    let missing = lines
+        .iter()
+        .filter(|l| l.replacement_value.is_none())
+        .count();
+    (total, missing)
+}
+
+/// "12,500.00"
+pub fn format_value(value: f64) -> String {
+    let fixed = format!("{:.2}", value.abs());
+    let (digits, cents) = fixed.split_once('.').unwrap_or((&fixed, "00"));
+    let mut grouped = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    format!(
+        "{}{}.{}",
+        if value < 0.0 { "-" } else { "" },
+        grouped,
+        cents
+    )
+}
+
+/// The manifest as CSV, one row per item with a total row at the end
+pub fn manifest_csv(lines: &[ManifestLine]) -> String {
+    let mut out = csv::row(&[
+        "Item",
+        "Category",
+        "Manufacturer",
+        "Model",
+        "Serial Number",
+        "Owner",
+        "Kit",
+        "Replacement Value",
+    ]);
+    for line in lines {
+        out.push_str(&csv::row(&[
+            line.name.clone(),
+            line.category.clone(),
+            line.manufacturer.clone().unwrap_or_default(),
+            line.model.clone().unwrap_or_default(),
+            line.serial_number.clone().unwrap_or_default(),
+            line.owner_name.clone().unwrap_or_default(),
+            line.kit_name.clone().unwrap_or_default(),
+            line.replacement_value
+                .map(|v| format!("{:.2}", v))
+                .unwrap_or_default(),
+        ]));
+    }
+    let (total, _) = manifest_total(lines);
+    out.push_str(&csv::row(&[
+        "Total".to_string(),
+        String::new(),
+        String::new(),
+        String::new(),
+        String::new(),
+        String::new(),
+        String::new(),
+        format!("{:.2}", total),
+    ]));
+    out
+}
+
+/// The manifest as a PDF for the insurer
+pub fn manifest_pdf(production_title: &str, lines: &[ManifestLine], generated_on: &str) -> Vec<u8> {
+    let mut flow = Flow::new(PageSize::LETTER.landscape())
+        .with_footer(format!("{} - Equipment Manifest", production_title));
+
+    flow.heading(production_title, 18.0);
+    flow.heading("Equipment Manifest", 12.0);
+    let (total, missing) = manifest_total(lines);
+    let mut summary = vec![
+        ("Prepared", generated_on.to_string()),
+        ("Items", lines.len().to_string()),
+        (
+            "Total replacement value",
+            format!("${}", format_value(total)),
+        ),
+    ];
+    if missing > 0 {
+        summary.push(("Items without a value", missing.to_string()));
+    }
+    flow.key_values(&summary, 10.0);
+
+    if lines.is_empty() {
+        flow.paragraph("No equipment assigned.", 10.0);
+    } else {
+        let rows: Vec<Vec<String>> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                vec![
+                    (i + 1).to_string(),
+                    line.name.clone(),
+                    line.category.clone(),
+                    line.make_and_model(),
+                    line.serial_number.clone().unwrap_or_default(),
+                    line.owner_name.clone().unwrap_or_default(),
+                    line.value_label().unwrap_or_else(|| "-".to_string()),
+                ]
+            })
+            .collect();
+        flow.table(
+            &[
+                "#",
+                "Item",
+                "Category",
+                "Make / Model",
+                "Serial Number",
+                "Owner",
+                "Replacement Value",
+            ],
+            &[0.5, 3.0, 1.5, 3.0, 2.0, 2.0, 1.6],
+            &rows,
+            9.0,
+        );
+    }
+    flow.finish()
+}
+
+/// Longest a piece of gear can be booked for in one go
+pub const MAX_BOOKING_DAYS: i64 = 366;
+
+/// Gear held for a production between two dates
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct GearBooking {
+    /// The `production_gear` record
+    pub id: RecordId,
+    pub production: RecordId,
+    pub production_title: String,
+    pub production_slug: String,
+    pub equipment: RecordId,
+    pub equipment_name: String,
+    /// "YYYY-MM-DD"
+    pub start_date: String,
+    /// Inclusive
+    pub end_date: String,
+}
+
+impl GearBooking {
+    pub fn overlaps(&self, start: &str, end: &str) -> bool {
+        overlaps(&self.start_date, &self.end_date, start, end)
+    }
+
+    /// "2026-06-01 to 2026-06-05"
+    pub fn dates_label(&self) -> String {
+        dates_label(&self.start_date, &self.end_date)
+    }
+
+    /// "Night Shift, 2026-06-01 to 2026-06-05"
+    pub fn describe(&self) -> String {
+        format!("{}, {}", self.production_title, self.dates_label())
+    }
+}
+
+/// "2026-06-01 to 2026-06-05", or "2026-06-01" for a single day
+pub fn dates_label(start: &str, end: &str) -> String {
+    if start == end {
+        start.to_string()
+    } else {
+        format!("{} to {}", start, end)
+    }
+}
+
+/// Whether two inclusive "YYYY-MM-DD" ranges share a day
+pub fn overlaps(start: &str, end: &str, other_start: &str, other_end: &str) -> bool {
+    start <= other_end && other_start <= end
+}
+
+/// Check booking dates from the form: both or neither, in order, and not
+/// more than [`MAX_BOOKING_DAYS`] apart
+pub fn clean_booking_dates(start: &str, end: &str) -> Result<Option<(String, String)>, Error> {
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|_| Error::Validation(format!("\"{}\" isn't a date (use YYYY-MM-DD)", value)))
+    };
+    let (start, end) = (start.trim(), end.trim());
+    match (start.is_empty(), end.is_empty()) {
+        (true, true) => return Ok(None),
+        (false, false) => {}
+        _ => {
+            return Err(Error::Validation(
+                "Give both the first and last day to book it, or neither".to_string(),
+            ));
+        }
+    }
+    let (start, end) = (parse(start)?, parse(end)?);
+    if end < start {
+        return Err(Error::Validation(
+            "The booking ends before it starts".to_string(),
+        ));
+    }
+    if (end - start).num_days() >= MAX_BOOKING_DAYS {
+        return Err(Error::Validation(
+            "Book gear for up to a year at a time".to_string(),
+        ));
+    }
+    Ok(Some((
+        start.format("%Y-%m-%d").to_string(),
+        end.format("%Y-%m-%d").to_string(),
+    )))
+}
+
+/// Bookings that overlap `start`..=`end`, leaving out `except`'s own
+pub fn conflicting<'a>(
+    bookings: &'a [GearBooking],
+    start: &str,
+    end: &str,
+    except: Option<&RecordId>,
+) -> Vec<&'a GearBooking> {
+    bookings
+        .iter()
+        .filter(|b| except.is_none_or(|p| &b.production != p))
+        .filter(|b| b.overlaps(start, end))
+        .collect()
+}
+
+/// What to tell someone whose assignment or checkout runs into bookings
+pub fn conflict_message(conflicts: &[&GearBooking]) -> String {
+    let reasons: Vec<String> = conflicts
+        .iter()
+        .map(|b| format!("{} is booked for {}", b.equipment_name, b.describe()))
+        .collect();
+    format!("{}.", reasons.join("; "))
+}
+
+/// One day on the availability calendar
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarDay {
+    /// "YYYY-MM-DD"
+    pub date: String,
+    pub day: u32,
+    pub is_today: bool,
+    pub is_past: bool,
+    /// Titles of the productions holding it that day
+    pub booked_for: Vec<String>,
+}
+
+/// Weeks of days from the Monday of `today`'s week, with the bookings on each
+pub fn calendar_weeks(
+    bookings: &[GearBooking],
+    today: NaiveDate,
+    weeks: usize,
+) -> Vec<Vec<CalendarDay>> {
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    (0..weeks)
+        .map(|week| {
+            (0..7)
+                .map(|weekday| {
+                    let date = monday + Duration::days((week * 7 + weekday) as i64);
+                    let key = date.format("%Y-%m-%d").to_string();
+                    CalendarDay {
+                        booked_for: bookings
+                            .iter()
+                            .filter(|b| b.overlaps(&key, &key))
+                            .map(|b| b.production_title.clone())
+                            .collect(),
+                        day: date.day(),
+                        is_today: date == today,
+                        is_past: date < today,
+                        date: key,
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+const MANIFEST_FIELDS: &str = "id, equipment, equipment.name AS name,
+    equipment.category.name AS category, equipment.manufacturer AS manufacturer,
+    equipment.model AS model, equipment.serial_number AS serial_number,
+    equipment.owner_person.name ?? equipment.owner_person.username ?? equipment.owner_organization.name AS owner_name,
+    equipment.parent_kit.name AS kit_name, equipment.replacement_value AS replacement_value,
+    start_date, end_date, created_at AS added_at";
+
+const BOOKING_FIELDS: &str = "id, production, production.title AS production_title,
+    production.slug AS production_slug, equipment, equipment.name AS equipment_name,
+    start_date, end_date";
+
+pub struct ProductionGearModel;
+
+impl ProductionGearModel {
+    /// The production's manifest, by category then name
+    pub async fn manifest(production: &RecordId) -> Result<Vec<ManifestLine>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM production_gear WHERE production = $production
+                 ORDER BY category ASC, name ASC",
+                MANIFEST_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Equipment `person` can assign: theirs, and their organizations'
+    pub async fn assignable(person: &RecordId) -> Result<Vec<Equipment>, Error> {
+        Ok(DB
+            .query(
+                "SELECT * FROM equipment
+                 WHERE owner_person = $person
+                    OR owner_organization IN (SELECT VALUE out FROM member_of
+                        WHERE in = $person AND invitation_status = 'accepted')
+                 ORDER BY name ASC
+                 FETCH category, condition",
+            )
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Bookings of any of `equipment` that run to `from` or later, soonest first
+    pub async fn bookings(equipment: &[RecordId], from: &str) -> Result<Vec<GearBooking>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM production_gear
+                 WHERE equipment IN $equipment AND start_date IS NOT NONE AND end_date >= $from
+                 ORDER BY start_date ASC",
+                BOOKING_FIELDS
+            ))
+            .bind(("equipment", equipment.to_vec()))
+            .bind(("from", from.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Bookings a checkout of `equipment` from `start` to `end` would run
+    /// into: those of productions `renter` ("person:..." or
+    /// "organization:...") isn't on
+    pub async fn checkout_conflicts(
+        equipment: &[RecordId],
+        start: &str,
+        end: &str,
+        renter: &str,
+    ) -> Result<Vec<GearBooking>, Error> {
+        let mut conflicts = Vec::new();
+        for booking in Self::bookings(equipment, start).await? {
+            if booking.overlaps(start, end)
+                && !ProductionModel::is_member(&booking.production, renter).await?
+            {
+                conflicts.push(booking);
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Put equipment on the production, or change the dates it's booked for.
+    /// Dates that overlap another production's booking are refused.
+    pub async fn assign(
+        production: &RecordId,
+        equipment: &RecordId,
+        dates: Option<(String, String)>,
+        added_by: &RecordId,
+    ) -> Result<(), Error> {
+        debug!(
+            "Assigning {} to {}",
+            equipment.display(),
+            production.display()
+        );
+        if let Some((start, end)) = dates.as_ref() {
+            let bookings = Self::bookings(std::slice::from_ref(equipment), start).await?;
+            let conflicts = conflicting(&bookings, start, end, Some(production));
+            if !conflicts.is_empty() {
+                return Err(Error::Validation(conflict_message(&conflicts)));
+            }
+        }
+        let (start_date, end_date) = dates.unzip();
+        DB.query(
+            "LET $existing = (SELECT VALUE id FROM production_gear
+                WHERE production = $production AND equipment = $equipment LIMIT 1)[0];
+             IF $existing THEN
+                (UPDATE $existing SET start_date = $start_date, end_date = $end_date)
+             ELSE
+                (CREATE production_gear SET production = $production, equipment = $equipment,
+                    start_date = $start_date, end_date = $end_date,
+                    added_by = $added_by, created_at = time::now())
+             END;",
+        )
+        .bind(("production", production.clone()))
+        .bind(("equipment", equipment.clone()))
+        .bind(("start_date", start_date))
+        .bind(("end_date", end_date))
+        .bind(("added_by", added_by.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to assign equipment: {}", e)))?
+        .check()?;
+        Ok(())
+    }
+
+    pub async fn remove(production: &RecordId, assignment_id: &str) -> Result<(), Error> {
+        DB.query("DELETE $id WHERE production = $production")
+            .bind(("id", RecordId::new("production_gear", assignment_id)))
+            .bind(("production", production.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to remove equipment: {}", e)))?;
+        Ok(())
+    }
+}
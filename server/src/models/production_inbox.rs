@@ -0,0 +1,508 @@
+//! Production inboxes
+//!
+//! An owner or admin turns on the production's inbox and gets an address
+//! like `my-film.k3x9q2m7p4ra@inbox.example.com`. Producers forward vendor
+//! quotes and notes to it; the mail provider hands each message to the
+//! inbound mail webhook, which keeps it as a note on the production with its
+//! attachments stored privately in S3, and lets the owners and admins know.
+//! The random part of the address is what routes mail, so getting a new
+//! address or turning the inbox off stops the old one working.
+
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, error, info, warn};
+use ulid::Ulid;
+
+use crate::{
+    config,
+    db::DB,
+    error::Error,
+    models::{
+        deliverable::{attachment_allowed, delete_stored_files, safe_file_name, size_label},
+        notification::NotificationModel,
+    },
+    record_id_ext::RecordIdExt,
+    services::{s3::s3, tokens},
+};
+
+const FILE_PREFIX: &str = "private/inbox";
+
+/// Length of the random part of an address
+pub const TOKEN_LENGTH: usize = 12;
+
+pub const MAX_SUBJECT_CHARS: usize = 200;
+pub const MAX_BODY_CHARS: usize = 20_000;
+
+/// Attachments kept from one message; the rest are dropped
+pub const MAX_ATTACHMENTS: usize = 10;
+
+/// Largest attachment kept
+pub const MAX_FILE_SIZE: usize = 25 * 1024 * 1024;
+
+/// A production's inbox address
+pub fn address(slug: &str, token: &str, domain: &str) -> String {
+    format!("{}.{}@{}", slug, token, domain)
+}
+
+/// Split a mailbox like `"Jane Doe" <jane@example.com>` into its display
+/// name and lowercased address
+pub fn parse_mailbox(value: &str) -> (Option<String>, String) {
+    let value = value.trim();
+    match (value.rfind('<'), value.rfind('>')) {
+        (Some(open), Some(close)) if open < close => {
+            let name = value[..open].trim().trim_matches('"').trim();
+            (
+                (!name.is_empty()).then(|| name.to_string()),
+                value[open + 1..close].trim().to_ascii_lowercase(),
+            )
+        }
+        _ => (None, value.to_ascii_lowercase()),
+    }
+}
+
+/// The inbox token of the first recipient on `domain`, from a To header that
+/// may list several mailboxes
+pub fn token_from_recipients(recipients: &str, domain: &str) -> Option<String> {
+    recipients.split(',').find_map(|mailbox| {
+        let (_, address) = parse_mailbox(mailbox);
+        let (local, host) = address.rsplit_once('@')?;
+        if !host.eq_ignore_ascii_case(domain) {
+            return None;
+        }
+        let token = local.rsplit('.').next()?;
+        tokens::is_token(token, TOKEN_LENGTH)
+            .then(|| token.to_string())
+    })
+}
+
+/// Check the inbound mail webhook's bearer token
+pub fn verify_secret(token: &str) -> bool {
+    let Some(expected) = config::inbound_mail().secret.as_deref() else {
+        return false;
+    };
+    let (a, b) = (expected.as_bytes(), token.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Where an attachment is stored
+pub fn file_key(production_key: &str, message_key: &str, id: &str, file_name: &str) -> String {
+    format!(
+        "{}/{}/{}/{}-{}",
+        FILE_PREFIX,
+        production_key,
+        message_key,
+        id,
+        safe_file_name(file_name)
+    )
+}
+
+/// A message as the mail provider posts it
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InboundEmail {
+    #[serde(default)]
+    pub to: String,
+    #[serde(default)]
+    pub from: String,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<InboundAttachment>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct InboundAttachment {
+    #[serde(default)]
+    pub filename: String,
+    pub content_type: Option<String>,
+    /// Base64
+    #[serde(default)]
+    pub content: String,
+}
+
+/// An attachment worth keeping, decoded
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attachment {
+    pub file_name: String,
+    pub mime_type: String,
+    pub data: bytes::Bytes,
+}
+
+impl InboundEmail {
+    /// The subject, trimmed to length, or "(no subject)"
+    pub fn clean_subject(&self) -> String {
+        let subject = self.subject.trim();
+        if subject.is_empty() {
+            "(no subject)".to_string()
+        } else {
+            subject.chars().take(MAX_SUBJECT_CHARS).collect()
+        }
+    }
+
+    /// The plain text body, trimmed to length
+    pub fn clean_body(&self) -> Option<String> {
+        self.text
+            .as_deref()
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(|text| text.chars().take(MAX_BODY_CHARS).collect())
+    }
+
+    /// Attachments with an allowed type and size, up to the limit. Ones that
+    /// can't be decoded or don't qualify are left out.
+    pub fn attachments(&self) -> Vec<Attachment> {
+        self.attachments
+            .iter()
+            .filter(|a| attachment_allowed(&a.filename))
+            .filter_map(|a| {
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(a.content.trim())
+                    .ok()?;
+                (!data.is_empty() && data.len() <= MAX_FILE_SIZE).then(|| Attachment {
+                    file_name: safe_file_name(&a.filename),
+                    mime_type: a
+                        .content_type
+                        .clone()
+                        .filter(|t| !t.trim().is_empty())
+                        .unwrap_or_else(|| "application/octet-stream".to_string()),
+                    data: data.into(),
+                })
+            })
+            .take(MAX_ATTACHMENTS)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct InboxMessage {
+    pub id: RecordId,
+    pub from_address: String,
+    pub from_name: Option<String>,
+    pub subject: String,
+    pub body: Option<String>,
+    /// The member it came from, when the sender's address is theirs
+    pub sender_name: Option<String>,
+    pub received_at: DateTime<Utc>,
+}
+
+impl InboxMessage {
+    /// "Jane Doe <jane@example.com>", or the member's name
+    pub fn from_label(&self) -> String {
+        match self.sender_name.as_ref().or(self.from_name.as_ref()) {
+            Some(name) => format!("{} <{}>", name, self.from_address),
+            None => self.from_address.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct InboxFile {
+    pub id: RecordId,
+    pub message: RecordId,
+    pub file_key: String,
+    pub file_name: String,
+    pub file_size: i64,
+    pub mime_type: String,
+}
+
+impl InboxFile {
+    pub fn size_label(&self) -> String {
+        size_label(self.file_size)
+    }
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct InboxProduction {
+    id: RecordId,
+    slug: String,
+    title: String,
+}
+
+const MESSAGE_FIELDS: &str = "id, from_address, from_name, subject, body,
+    sender.name ?? sender.username AS sender_name, received_at";
+
+const FILE_FIELDS: &str = "id, message, file_key, file_name, file_size, mime_type";
+
+pub struct ProductionInboxModel;
+
+impl ProductionInboxModel {
+    /// The random part of a production's address, if its inbox is on
+    pub async fn get_token(production: &RecordId) -> Result<Option<String>, Error> {
+        let tokens: Vec<Option<String>> = DB
+            .query("SELECT VALUE inbox_token FROM $production")
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        Ok(tokens.into_iter().next().flatten())
+    }
+
+    /// Turn the inbox on with a fresh address, replacing any existing one
+    pub async fn reset_token(production: &RecordId) -> Result<String, Error> {
+        let token = tokens::random_token(TOKEN_LENGTH).to_ascii_lowercase();
+        DB.query("UPDATE $production SET inbox_token = $token")
+            .bind(("production", production.clone()))
+            .bind(("token", token.clone()))
+            .await?
+            .check()?;
+        info!("Inbox address reset for {}", production.display());
+        Ok(token)
+    }
+
+    /// Turn the inbox off; mail to the old address is dropped
+    pub async fn disable(production: &RecordId) -> Result<(), Error> {
+        DB.query("UPDATE $production SET inbox_token = NONE")
+            .bind(("production", production.clone()))
+            .await?
+            .check()?;
+        info!("Inbox turned off for {}", production.display());
+        Ok(())
+    }
+
+    /// File a message posted by the mail provider under the production it
+    /// was addressed to. Returns `None` when no inbox matches, so the
+    /// provider isn't asked to retry mail that has nowhere to go.
+    pub async fn receive(email: &InboundEmail) -> Result<Option<RecordId>, Error> {
+        let Some(domain) = config::inbound_mail().domain.as_deref() else {
+            return Ok(None);
+        };
+        let Some(token) = token_from_recipients(&email.to, domain) else {
+            return Ok(None);
+        };
+        let production: Option<InboxProduction> = DB
+            .query("SELECT id, slug, title FROM production WHERE inbox_token = $token LIMIT 1")
+            .bind(("token", token))
+            .await?
+            .take(0)?;
+        let Some(production) = production else {
+            return Ok(None);
+        };
+
+        let (from_name, from_address) = parse_mailbox(&email.from);
+        if from_address.is_empty() {
+            return Err(Error::BadRequest("Message has no sender".to_string()));
+        }
+        let subject = email.clean_subject();
+        let created: Vec<RecordId> = DB
+            .query(
+                "LET $sender = (SELECT VALUE in FROM member_of
+                    WHERE out = $production AND invitation_status = 'accepted'
+                        AND <string> type::table(in) = 'person'
+                        AND string::lowercase(in.email) = $from_address
+                    LIMIT 1)[0];
+                 CREATE inbox_message SET production = $production, from_address = $from_address,
+                    from_name = $from_name, subject = $subject, body = $body, sender = $sender
+                 RETURN VALUE id;",
+            )
+            .bind(("production", production.id.clone()))
+            .bind(("from_address", from_address.clone()))
+            .bind(("from_name", from_name))
+            .bind(("subject", subject.clone()))
+            .bind(("body", email.clean_body()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to file inbox message: {}", e)))?
+            .take(1)?;
+        let message = created
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Internal("Inbox message was not created".to_string()))?;
+
+        let attachments = email.attachments();
+        if attachments.len() < email.attachments.len() {
+            warn!(
+                "Dropped {} of {} attachments mailed to {}",
+                email.attachments.len() - attachments.len(),
+                email.attachments.len(),
+                production.id.display()
+            );
+        }
+        for attachment in attachments {
+            if let Err(e) = Self::add_file(&production.id, &message, attachment).await {
+                error!(
+                    "Failed to store an attachment mailed to {}: {}",
+                    production.id.display(),
+                    e
+                );
+            }
+        }
+
+        Self::notify_editors(&production, &message, &from_address, &subject).await;
+        info!(
+            "Filed mail from {} to {}'s inbox",
+            from_address, production.slug
+        );
+        Ok(Some(message))
+    }
+
+    async fn add_file(
+        production: &RecordId,
+        message: &RecordId,
+        attachment: Attachment,
+    ) -> Result<(), Error> {
+        let key = file_key(
+            &production.key_string(),
+            &message.key_string(),
+            &Ulid::new().to_string(),
+            &attachment.file_name,
+        );
+        let file_size = attachment.data.len() as i64;
+        s3()?
+            .upload_file(&key, attachment.data, &attachment.mime_type)
+            .await?;
+
+        let result = DB
+            .query(
+                "CREATE inbox_file SET message = $message, production = $production,
+                    file_key = $key, file_name = $file_name, file_size = $file_size,
+                    mime_type = $mime_type",
+            )
+            .bind(("message", message.clone()))
+            .bind(("production", production.clone()))
+            .bind(("key", key.clone()))
+            .bind(("file_name", attachment.file_name))
+            .bind(("file_size", file_size))
+            .bind(("mime_type", attachment.mime_type))
+            .await
+            .and_then(|response| response.check());
+        if let Err(e) = result {
+            delete_stored_files(vec![key]);
+            return Err(Error::Database(format!("Failed to attach file: {}", e)));
+        }
+        debug!("Stored {} for {}", key, message.display());
+        Ok(())
+    }
+
+    /// Let the production's owners and admins know mail has come in
+    async fn notify_editors(
+        production: &InboxProduction,
+        message: &RecordId,
+        from_address: &str,
+        subject: &str,
+    ) {
+        let editors: Vec<RecordId> = match DB
+            .query(
+                "SELECT VALUE in FROM member_of
+                 WHERE out = $production AND role IN ['owner', 'admin']
+                    AND invitation_status = 'accepted'
+                    AND <string> type::table(in) = 'person'",
+            )
+            .bind(("production", production.id.clone()))
+            .await
+            .and_then(|mut response| response.take(0))
+        {
+            Ok(editors) => editors,
+            Err(e) => {
+                error!(
+                    "Failed to look up who to tell about mail to {}: {}",
+                    production.id.display(),
+                    e
+                );
+                return;
+            }
+        };
+
+        let link = format!(
+            "/productions/{}/inbox#message-{}",
+            production.slug,
+            message.key_string()
+        );
+        let text = format!(
+            "{} sent \"{}\" to {}'s inbox",
+            from_address, subject, production.title
+        );
+        let notifications = NotificationModel::new();
+        for person in editors {
+            if let Err(e) = notifications
+                .create(
+                    &person.to_raw_string(),
+                    "inbox",
+                    "New mail in the production inbox",
+                    &text,
+                    Some(&link),
+                    Some(&message.to_raw_string()),
+                )
+                .await
+            {
+                warn!("Failed to notify {} of inbox mail: {}", person.display(), e);
+            }
+        }
+    }
+
+    /// A production's mail, newest first
+    pub async fn for_production(production: &RecordId) -> Result<Vec<InboxMessage>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM inbox_message WHERE production = $production
+                 ORDER BY received_at DESC",
+                MESSAGE_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn files_for_production(production: &RecordId) -> Result<Vec<InboxFile>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {} FROM inbox_file WHERE production = $production ORDER BY file_name",
+                FILE_FIELDS
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn get_file(production: &RecordId, file_id: &str) -> Result<InboxFile, Error> {
+        let file: Option<InboxFile> = DB
+            .query(format!(
+                "SELECT {} FROM $id WHERE production = $production",
+                FILE_FIELDS
+            ))
+            .bind(("id", RecordId::new("inbox_file", file_id)))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        file.ok_or(Error::NotFound)
+    }
+
+    /// Delete a message, its attachments and its reactions
+    pub async fn delete_message(production: &RecordId, message_id: &str) -> Result<(), Error> {
+        let message = RecordId::new("inbox_message", message_id);
+        let keys: Vec<String> = DB
+            .query(
+                "LET $keys = SELECT VALUE file_key FROM inbox_file
+                    WHERE message = $message AND production = $production;
+                 DELETE inbox_file WHERE message = $message AND production = $production;
+                 DELETE reacts WHERE out = $message AND out.production = $production;
+                 DELETE $message WHERE production = $production;
+                 RETURN $keys;",
+            )
+            .bind(("message", message))
+            .bind(("production", production.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete inbox message: {}", e)))?
+            .take(4)?;
+        delete_stored_files(keys);
+        Ok(())
+    }
+
+    /// Delete a production's mail and its attachments
+    pub async fn delete_for_production(production: &RecordId) -> Result<(), Error> {
+        let keys: Vec<String> = DB
+            .query(
+                "LET $keys = SELECT VALUE file_key FROM inbox_file WHERE production = $production;
+                 DELETE inbox_file WHERE production = $production;
+                 DELETE reacts WHERE out.production = $production;
+                 DELETE inbox_message WHERE production = $production;
+                 RETURN $keys;",
+            )
+            .bind(("production", production.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete inbox: {}", e)))?
+            .take(4)?;
+        delete_stored_files(keys);
+        Ok(())
+    }
+}
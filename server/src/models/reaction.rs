@@ -0,0 +1,187 @@
+//! Emoji reactions on direct messages and production inbox notes
+//!
+//! `person -> reacts -> record` carries one emoji; a person can leave several
+//! different emoji on a record but each only once, and reacting with the same
+//! one again takes it back. Reactions are shown as a count per emoji, in the
+//! order of the palette, marking the ones the viewer left.
+//!
+//! Who can react to a record is whoever can see it: the two people in a
+//! message's conversation, or the members of the production a note was
+//! emailed to. The same people are told when its reactions change.
+
+use std::collections::HashMap;
+
+use crate::{db::DB, error::Error, record_id_ext::RecordIdExt};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+/// The emoji people can react with, in the order they're shown
+pub const EMOJI: [&str; 6] = ["👍", "❤️", "😂", "🎉", "👀", "🎬"];
+
+/// Tables whose records can be reacted to
+pub const TABLES: [&str; 2] = ["direct_message", "inbox_message"];
+
+/// How many people reacted to a record with one emoji
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReactionCount {
+    pub emoji: String,
+    pub count: usize,
+    /// Whether the viewer is one of them
+    pub mine: bool,
+}
+
+/// Counts for `(emoji, person id)` pairs, in palette order, leaving out
+/// emoji no one used
+pub fn tally<'a>(
+    reactions: impl IntoIterator<Item = (&'a str, &'a str)>,
+    viewer: &str,
+) -> Vec<ReactionCount> {
+    let mut counts: Vec<ReactionCount> = EMOJI
+        .iter()
+        .map(|emoji| ReactionCount {
+            emoji: emoji.to_string(),
+            count: 0,
+            mine: false,
+        })
+        .collect();
+    for (emoji, person) in reactions {
+        if let Some(count) = counts.iter_mut().find(|c| c.emoji == emoji) {
+            count.count += 1;
+            count.mine |= person == viewer;
+        }
+    }
+    counts.retain(|c| c.count > 0);
+    counts
+}
+
+/// A record that can be reacted to, from its table and key as they appear in
+/// reaction URLs
+pub fn target(table: &str, key: &str) -> Result<RecordId, Error> {
+    if !TABLES.contains(&table) || key.is_empty() {
+        return Err(Error::NotFound);
+    }
+    Ok(RecordId::new(table, key))
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct ReactionRow {
+    target: String,
+    person: String,
+    emoji: String,
+}
+
+pub struct ReactionModel;
+
+impl ReactionModel {
+    /// Add an emoji to a record, or take it back if the person already left
+    /// it. Returns true if it was added.
+    pub async fn toggle(person: &RecordId, target: &RecordId, emoji: &str) -> Result<bool, Error> {
+        if !EMOJI.contains(&emoji) {
+            return Err(Error::BadRequest(format!("Unknown reaction: {}", emoji)));
+        }
+        debug!(
+            "Toggling {} reaction: {} -> {}",
+            emoji,
+            person.display(),
+            target.display()
+        );
+
+        let existing: Option<i64> = DB
+            .query(
+                "SELECT VALUE count() FROM reacts
+                 WHERE in = $person AND out = $target AND emoji = $emoji
+                 GROUP ALL",
+            )
+            .bind(("person", person.clone()))
+            .bind(("target", target.clone()))
+            .bind(("emoji", emoji.to_string()))
+            .await?
+            .take(0)?;
+        if existing.unwrap_or(0) > 0 {
+            DB.query("DELETE reacts WHERE in = $person AND out = $target AND emoji = $emoji")
+                .bind(("person", person.clone()))
+                .bind(("target", target.clone()))
+                .bind(("emoji", emoji.to_string()))
+                .await
+                .map_err(|e| Error::Database(format!("Failed to remove reaction: {}", e)))?;
+            return Ok(false);
+        }
+
+        DB.query(
+            "RELATE $person -> reacts -> $target SET emoji = $emoji, created_at = time::now()",
+        )
+        .bind(("person", person.clone()))
+        .bind(("target", target.clone()))
+        .bind(("emoji", emoji.to_string()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to add reaction: {}", e)))?;
+        Ok(true)
+    }
+
+    /// Reaction counts for each of `targets` that has any, by raw record id,
+    /// marking the viewer's own
+    pub async fn counts(
+        targets: &[RecordId],
+        viewer: &str,
+    ) -> Result<HashMap<String, Vec<ReactionCount>>, Error> {
+        if targets.is_empty() {
+            return Ok(HashMap::new());
+        }
+        let rows: Vec<ReactionRow> = DB
+            .query(
+                "SELECT <string> out AS target, <string> in AS person, emoji FROM reacts
+                 WHERE out IN $targets",
+            )
+            .bind(("targets", targets.to_vec()))
+            .await?
+            .take(0)?;
+
+        let mut by_target: HashMap<&str, Vec<(&str, &str)>> = HashMap::new();
+        for row in &rows {
+            by_target
+                .entry(row.target.as_str())
+                .or_default()
+                .push((row.emoji.as_str(), row.person.as_str()));
+        }
+        Ok(by_target
+            .into_iter()
+            .map(|(target, reactions)| (target.to_string(), tally(reactions, viewer)))
+            .collect())
+    }
+
+    /// Reaction counts for one record
+    pub async fn counts_for(target: &RecordId, viewer: &str) -> Result<Vec<ReactionCount>, Error> {
+        let mut counts = Self::counts(std::slice::from_ref(target), viewer).await?;
+        Ok(counts.remove(&target.to_raw_string()).unwrap_or_default())
+    }
+
+    /// The people who can see a record, and so react to it: the two in a
+    /// message's conversation, or the members of a note's production
+    pub async fn audience(target: &RecordId) -> Result<Vec<String>, Error> {
+        let raw = target.to_raw_string();
+        if raw.starts_with("direct_message:") {
+            let participants: Option<Vec<String>> = DB
+                .query(
+                    "SELECT VALUE [<string> conversation.participant_a, <string> conversation.participant_b]
+                     FROM ONLY $target",
+                )
+                .bind(("target", target.clone()))
+                .await?
+                .take(0)?;
+            participants.ok_or(Error::NotFound)
+        } else if raw.starts_with("inbox_message:") {
+            Ok(DB
+                .query(
+                    "SELECT VALUE <string> in FROM member_of
+                     WHERE out = $target.production AND type::table(in) = 'person'
+                         AND invitation_status = 'accepted'",
+                )
+                .bind(("target", target.clone()))
+                .await?
+                .take(0)?)
+        } else {
+            Err(Error::NotFound)
+        }
+    }
+}
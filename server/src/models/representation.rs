@@ -0,0 +1,447 @@
+//! Talent representation
+//!
+//! A person names the agency or management company that represents them with
+//! a `represented_by` edge (person → organization). An owner or admin of the
+//! organization confirms it and becomes the contact for inquiries. With
+//! `contact_mode` set to "representative", new conversations with the person
+//! are refused with a pointer to that contact, and offers are delivered to the
+//! representative, who can answer them on the person's behalf. Members of the
+//! representing organization can still reach their client directly.
+
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{error, info};
+
+use crate::{
+    db::DB,
+    error::{Error, Result},
+    models::notification::NotificationModel,
+    record_id_ext::RecordIdExt,
+};
+
+/// How a person wants to be contacted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContactMode {
+    #[default]
+    Direct,
+    /// Inquiries go to the person's confirmed representative
+    Representative,
+}
+
+impl ContactMode {
+    pub const ALL: [ContactMode; 2] = [ContactMode::Direct, ContactMode::Representative];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "direct" => Some(ContactMode::Direct),
+            "representative" => Some(ContactMode::Representative),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContactMode::Direct => "direct",
+            ContactMode::Representative => "representative",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ContactMode::Direct => "Contact me directly",
+            ContactMode::Representative => "Send inquiries to my representative",
+        }
+    }
+}
+
+/// A person's link to the organization representing them
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct Representation {
+    pub id: RecordId,
+    pub organization: RecordId,
+    pub org_name: String,
+    pub org_slug: String,
+    /// "pending" until the organization confirms, then "accepted"
+    pub status: String,
+    /// The agent handling inquiries, set when the organization confirms
+    pub contact: Option<RecordId>,
+    pub contact_username: Option<String>,
+    pub contact_name: Option<String>,
+}
+
+impl Representation {
+    pub fn is_accepted(&self) -> bool {
+        self.status == "accepted"
+    }
+
+    pub fn contact_display(&self) -> Option<&str> {
+        self.contact_name
+            .as_deref()
+            .filter(|n| !n.trim().is_empty())
+            .or(self.contact_username.as_deref())
+    }
+}
+
+/// Why a new conversation with a represented person was refused
+pub fn redirect_notice(person_name: &str, representation: &Representation) -> String {
+    match &representation.contact_username {
+        Some(username) => format!(
+            "{} takes inquiries through {}. Message their representative, @{}, instead.",
+            person_name, representation.org_name, username
+        ),
+        None => format!(
+            "{} takes inquiries through {}.",
+            person_name, representation.org_name
+        ),
+    }
+}
+
+/// Someone asking an organization to confirm it represents them
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct RepresentationRequest {
+    pub id: RecordId,
+    pub username: String,
+    pub name: Option<String>,
+    pub avatar: Option<String>,
+}
+
+impl RepresentationRequest {
+    pub fn key(&self) -> String {
+        self.id.key_string()
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.name
+            .as_deref()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or(&self.username)
+    }
+}
+
+/// A client on an agency's roster
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct RosterEntry {
+    /// The client
+    pub id: RecordId,
+    pub username: String,
+    pub name: Option<String>,
+    pub avatar: Option<String>,
+    pub headline: Option<String>,
+    pub org_name: String,
+    pub contact_username: Option<String>,
+}
+
+impl RosterEntry {
+    pub fn key(&self) -> String {
+        self.id.key_string()
+    }
+
+    pub fn display_name(&self) -> &str {
+        self.name
+            .as_deref()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or(&self.username)
+    }
+}
+
+const ROSTER_FIELDS: &str = "in AS id, in.username AS username, in.name AS name,
+    in.profile.avatar AS avatar, in.profile.headline AS headline, out.name AS org_name,
+    contact.username AS contact_username";
+
+const REPRESENTATION_FIELDS: &str = "id, out AS organization, out.name AS org_name,
+    out.slug AS org_slug, status, contact, contact.username AS contact_username,
+    contact.name AS contact_name";
+
+/// Organizations a person is an accepted member of
+const MEMBER_ORGS: &str =
+    "(SELECT VALUE out FROM member_of WHERE in = $agent AND invitation_status = 'accepted')";
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct OrgRow {
+    id: RecordId,
+    name: String,
+    slug: String,
+}
+
+pub struct RepresentationModel;
+
+impl RepresentationModel {
+    /// The person's representation, confirmed or not
+    pub async fn get(person: &RecordId) -> Result<Option<Representation>> {
+        let rows: Vec<Representation> = DB
+            .query(format!(
+                "SELECT {REPRESENTATION_FIELDS} FROM represented_by WHERE in = $person LIMIT 1"
+            ))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        Ok(rows.into_iter().next())
+    }
+
+    pub async fn contact_mode(person: &RecordId) -> Result<ContactMode> {
+        let mode: Option<String> = DB
+            .query("SELECT VALUE contact_mode FROM ONLY $person")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        Ok(mode
+            .as_deref()
+            .and_then(ContactMode::parse)
+            .unwrap_or_default())
+    }
+
+    pub async fn set_contact_mode(person: &RecordId, mode: ContactMode) -> Result<()> {
+        DB.query("UPDATE $person SET contact_mode = $mode")
+            .bind(("person", person.clone()))
+            .bind(("mode", mode.as_str().to_string()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// The representative inquiries to this person go to, if they've asked
+    /// for that and their representation is confirmed
+    pub async fn route(person: &RecordId) -> Result<Option<Representation>> {
+        if Self::contact_mode(person).await? != ContactMode::Representative {
+            return Ok(None);
+        }
+        Ok(Self::get(person)
+            .await?
+            .filter(|r| r.is_accepted() && r.contact.is_some()))
+    }
+
+    /// Whether `agent` works for the organization confirmed to represent `person`
+    pub async fn represents(agent: &RecordId, person: &RecordId) -> Result<bool> {
+        let ids: Vec<RecordId> = DB
+            .query(format!(
+                "SELECT VALUE id FROM represented_by
+                WHERE in = $person AND status = 'accepted' AND out INSIDE {MEMBER_ORGS}"
+            ))
+            .bind(("agent", agent.clone()))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        Ok(!ids.is_empty())
+    }
+
+    /// Everyone represented by an organization `agent` works for
+    pub async fn clients(agent: &RecordId) -> Result<Vec<RecordId>> {
+        Ok(DB
+            .query(format!(
+                "SELECT VALUE in FROM represented_by
+                WHERE status = 'accepted' AND out INSIDE {MEMBER_ORGS}"
+            ))
+            .bind(("agent", agent.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// An organization's confirmed clients, by name
+    pub async fn roster(org: &RecordId) -> Result<Vec<RosterEntry>> {
+        Ok(DB
+            .query(format!(
+                "SELECT {ROSTER_FIELDS} FROM represented_by
+                WHERE out = $org AND status = 'accepted' ORDER BY name ASC"
+            ))
+            .bind(("org", org.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Confirmed clients of every organization `agent` works for, by name
+    pub async fn agent_roster(agent: &RecordId) -> Result<Vec<RosterEntry>> {
+        Ok(DB
+            .query(format!(
+                "SELECT {ROSTER_FIELDS} FROM represented_by
+                WHERE status = 'accepted' AND out INSIDE {MEMBER_ORGS} ORDER BY name ASC"
+            ))
+            .bind(("agent", agent.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Take a client off an organization's roster. Their submissions still
+    /// waiting on approval are withdrawn and inquiries go to them directly.
+    pub async fn drop_client(org: &RecordId, person_key: &str) -> Result<()> {
+        let person = RecordId::new("person", person_key);
+        let edges: Vec<RecordId> = DB
+            .query(
+                "SELECT VALUE id FROM represented_by
+                WHERE in = $person AND out = $org AND status = 'accepted'",
+            )
+            .bind(("person", person.clone()))
+            .bind(("org", org.clone()))
+            .await?
+            .take(0)?;
+        if edges.is_empty() {
+            return Err(Error::NotFound);
+        }
+        DB.query("DELETE $edges")
+            .query("UPDATE $person SET contact_mode = 'direct'")
+            .query(
+                "DELETE talent_submission WHERE talent = $person AND agency = $org
+                AND status = 'pending'",
+            )
+            .bind(("edges", edges))
+            .bind(("person", person.clone()))
+            .bind(("org", org.clone()))
+            .await?
+            .check()?;
+        Self::notify_person(&person, org, "no longer represents you").await;
+        info!(
+            "{} dropped {} from its roster",
+            org.display(),
+            person.display()
+        );
+        Ok(())
+    }
+
+    /// Name the organization that represents a person, replacing any earlier
+    /// one, and ask its owners and admins to confirm
+    pub async fn request(person: &RecordId, person_name: &str, org_slug: &str) -> Result<()> {
+        let org: Option<OrgRow> = DB
+            .query("SELECT id, name, slug FROM ONLY organization WHERE slug = $slug LIMIT 1")
+            .bind((
+                "slug",
+                org_slug.trim().trim_start_matches("/orgs/").to_string(),
+            ))
+            .await?
+            .take(0)?;
+        let org = org.ok_or_else(|| {
+            Error::Validation("No organization found with that address".to_string())
+        })?;
+
+        let mut result = DB
+            .query("DELETE represented_by WHERE in = $person")
+            .query("RELATE $person->represented_by->$org SET status = 'pending' RETURN VALUE id")
+            .query(
+                "SELECT VALUE in FROM member_of WHERE out = $org
+                    AND role IN ['owner', 'admin'] AND invitation_status = 'accepted'",
+            )
+            .bind(("person", person.clone()))
+            .bind(("org", org.id.clone()))
+            .await?;
+        let created: Vec<RecordId> = result.take(1)?;
+        let admins: Vec<RecordId> = result.take(2)?;
+        let edge = created
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Internal("Representation was not created".to_string()))?;
+
+        let notifications = NotificationModel::new();
+        for admin in admins.iter().filter(|a| *a != person) {
+            if let Err(e) = notifications
+                .create(
+                    &admin.to_raw_string(),
+                    "representation_request",
+                    "Representation request",
+                    &format!("{} says {} represents them", person_name, org.name),
+                    Some(&format!("/orgs/{}#org-representation-requests", org.slug)),
+                    Some(&edge.to_raw_string()),
+                )
+                .await
+            {
+                error!(
+                    "Failed to notify {} of a representation request: {}",
+                    admin.display(),
+                    e
+                );
+            }
+        }
+        info!(
+            "{} asked {} to confirm representation",
+            person.display(),
+            org.slug
+        );
+        Ok(())
+    }
+
+    /// Drop a person's representation. Inquiries go to them directly again.
+    pub async fn remove(person: &RecordId) -> Result<()> {
+        DB.query("DELETE represented_by WHERE in = $person")
+            .query("UPDATE $person SET contact_mode = 'direct'")
+            .query("DELETE talent_submission WHERE talent = $person AND status = 'pending'")
+            .bind(("person", person.clone()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Requests waiting on an organization, oldest first
+    pub async fn pending_for_org(org: &RecordId) -> Result<Vec<RepresentationRequest>> {
+        Ok(DB
+            .query(
+                "SELECT id, in.username AS username, in.name AS name, in.profile.avatar AS avatar
+                FROM represented_by WHERE out = $org AND status = 'pending'
+                ORDER BY created_at ASC",
+            )
+            .bind(("org", org.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Confirm a request; `contact` handles the person's inquiries from now on
+    pub async fn accept(org: &RecordId, key: &str, contact: &RecordId) -> Result<()> {
+        let people: Vec<RecordId> = DB
+            .query(
+                "UPDATE $id SET status = 'accepted', contact = $contact, accepted_at = time::now()
+                WHERE out = $org AND status = 'pending' RETURN VALUE in",
+            )
+            .bind(("id", RecordId::new("represented_by", key)))
+            .bind(("org", org.clone()))
+            .bind(("contact", contact.clone()))
+            .await?
+            .take(0)?;
+        let person = people.into_iter().next().ok_or(Error::NotFound)?;
+        Self::notify_person(&person, org, "confirmed that it represents you").await;
+        Ok(())
+    }
+
+    /// Turn a request down
+    pub async fn decline(org: &RecordId, key: &str) -> Result<()> {
+        let people: Vec<RecordId> = DB
+            .query(
+                "SELECT VALUE in FROM $id WHERE out = $org AND status = 'pending';
+                DELETE $id WHERE out = $org AND status = 'pending';",
+            )
+            .bind(("id", RecordId::new("represented_by", key)))
+            .bind(("org", org.clone()))
+            .await?
+            .take(0)?;
+        let person = people.into_iter().next().ok_or(Error::NotFound)?;
+        Self::notify_person(&person, org, "didn't confirm that it represents you").await;
+        Ok(())
+    }
+
+    async fn notify_person(person: &RecordId, org: &RecordId, what: &str) {
+        let name: Option<String> = DB
+            .query("SELECT VALUE name FROM ONLY $org")
+            .bind(("org", org.clone()))
+            .await
+            .ok()
+            .and_then(|mut r| r.take(0).ok())
+            .flatten();
+        let message = format!(
+            "{} {}",
+            name.unwrap_or_else(|| "The organization".to_string()),
+            what
+        );
+        if let Err(e) = NotificationModel::new()
+            .create(
+                &person.to_raw_string(),
+                "representation",
+                "Representation",
+                &message,
+                Some("/account/representation"),
+                None,
+            )
+            .await
+        {
+            error!(
+                "Failed to notify {} about representation: {}",
+                person.display(),
+                e
+            );
+        }
+    }
+}
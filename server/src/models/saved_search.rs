@@ -0,0 +1,243 @@
+//! Saved job searches
+//!
+//! A saved search is a set of keywords and an optional location. When a job
+//! is posted, every saved search it matches notifies its owner. The
+//! onboarding wizard sets these up from the roles and location a person picks;
+//! they're listed, and can be removed, under account settings.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, info, warn};
+
+use crate::{
+    db::DB, error::Error, models::notification::NotificationModel, record_id_ext::RecordIdExt,
+};
+
+/// Saved searches one person can have
+pub const MAX_PER_PERSON: usize = 20;
+
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct SavedSearch {
+    pub id: RecordId,
+    pub query: String,
+    pub location: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_alerted_at: Option<DateTime<Utc>>,
+}
+
+impl SavedSearch {
+    pub fn key(&self) -> String {
+        self.id.key_string()
+    }
+
+    /// "camera in Berlin", for the settings page and notifications
+    pub fn describe(&self) -> String {
+        match &self.location {
+            Some(location) => format!("{} in {}", self.query, location),
+            None => self.query.clone(),
+        }
+    }
+}
+
+/// Lowercased, with runs of whitespace collapsed
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Whether a job matches a saved search: every keyword starts a word of the
+/// job's text (so "grip" finds "grips" but "art" doesn't find "start"), and
+/// the job is in the saved location (the part before the
+/// first comma, so "Berlin" also matches "Berlin, Germany") or remote.
+pub fn matches(
+    query: &str,
+    location: Option<&str>,
+    job_text: &str,
+    job_location: Option<&str>,
+) -> bool {
+    let text = job_text.to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let keywords_match = query.split_whitespace().all(|keyword| {
+        let keyword = keyword.to_lowercase();
+        words.iter().any(|w| w.starts_with(&keyword))
+    });
+    if !keywords_match {
+        return false;
+    }
+
+    let Some(wanted) = location
+        .and_then(|l| l.split(',').next())
+        .map(normalize)
+        .filter(|l| !l.is_empty())
+    else {
+        return true;
+    };
+    let job_location = normalize(job_location.unwrap_or_default());
+    job_location.contains(&wanted) || job_location.contains("remote")
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct JobRow {
+    title: String,
+    description: String,
+    location: Option<String>,
+    posted_by: RecordId,
+    role_titles: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct AlertRow {
+    id: RecordId,
+    person: RecordId,
+    query: String,
+    location: Option<String>,
+}
+
+pub struct SavedSearchModel;
+
+impl SavedSearchModel {
+    /// Save a search. Saving the same search twice is a no-op.
+    pub async fn create(
+        person: &RecordId,
+        query: &str,
+        location: Option<&str>,
+    ) -> Result<(), Error> {
+        let query = normalize(query);
+        if query.is_empty() {
+            return Err(Error::Validation(
+                "A saved search needs some keywords".to_string(),
+            ));
+        }
+        let location = location
+            .map(|l| l.trim().to_string())
+            .filter(|l| !l.is_empty());
+
+        let existing = Self::list(person).await?;
+        if existing.iter().any(|s| {
+            s.query == query
+                && s.location.as_deref().map(normalize) == location.as_deref().map(normalize)
+        }) {
+            return Ok(());
+        }
+        if existing.len() >= MAX_PER_PERSON {
+            return Err(Error::Validation(format!(
+                "You can have up to {} saved searches",
+                MAX_PER_PERSON
+            )));
+        }
+
+        DB.query("CREATE saved_search SET person = $person, query = $query, location = $location")
+            .bind(("person", person.clone()))
+            .bind(("query", query.clone()))
+            .bind(("location", location))
+            .await?
+            .check()?;
+        debug!("{} saved a job search for '{}'", person.display(), query);
+        Ok(())
+    }
+
+    /// A person's saved searches, oldest first
+    pub async fn list(person: &RecordId) -> Result<Vec<SavedSearch>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, query, location, created_at, last_alerted_at
+                FROM saved_search WHERE person = $person ORDER BY created_at ASC",
+            )
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Remove one of a person's saved searches
+    pub async fn delete(person: &RecordId, key: &str) -> Result<(), Error> {
+        DB.query("DELETE saved_search WHERE id = $id AND person = $person")
+            .bind(("id", RecordId::new("saved_search", key)))
+            .bind(("person", person.clone()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Notify everyone whose saved searches match a newly posted job. Each
+    /// person is notified once, however many of their searches match.
+    pub async fn alert_new_job(key: &str) -> Result<(), Error> {
+        let job_id = RecordId::new("job_posting", key);
+        let job: Option<JobRow> = DB
+            .query(
+                "SELECT title, description, location, posted_by, roles.title ?? [] AS role_titles
+                FROM ONLY $job",
+            )
+            .bind(("job", job_id))
+            .await?
+            .take(0)?;
+        let Some(job) = job else {
+            warn!("Job {} went away before saved search alerts ran", key);
+            return Ok(());
+        };
+
+        let job_text = format!(
+            "{} {} {}",
+            job.title,
+            job.description,
+            job.role_titles.join(" ")
+        );
+        let searches: Vec<AlertRow> = DB
+            .query("SELECT id, person, query, location FROM saved_search")
+            .await?
+            .take(0)?;
+
+        let mut alerted: Vec<RecordId> = Vec::new();
+        let notifications = NotificationModel::new();
+        for search in searches {
+            if search.person == job.posted_by
+                || alerted.contains(&search.person)
+                || !matches(
+                    &search.query,
+                    search.location.as_deref(),
+                    &job_text,
+                    job.location.as_deref(),
+                )
+            {
+                continue;
+            }
+
+            let message = match &search.location {
+                Some(location) => format!(
+                    "Matches your saved search \"{}\" in {}",
+                    search.query, location
+                ),
+                None => format!("Matches your saved search \"{}\"", search.query),
+            };
+            notifications
+                .create(
+                    &search.person.to_raw_string(),
+                    "saved_search",
+                    &format!("New job: {}", job.title),
+                    &message,
+                    Some(&format!("/jobs/{}", key)),
+                    Some(key),
+                )
+                .await?;
+            DB.query("UPDATE $id SET last_alerted_at = time::now()")
+                .bind(("id", search.id))
+                .await?
+                .check()?;
+            alerted.push(search.person);
+        }
+
+        if !alerted.is_empty() {
+            info!(
+                "Job {} matched saved searches for {} people",
+                key,
+                alerted.len()
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,433 @@
+//! Location scouting collections
+//!
+//! A production's members keep named collections of candidate locations,
+//! each with the scout's notes, and compare them side by side: photos,
+//! capacity, rates and the distance to the collection's basecamp. A
+//! collection can be shared with the director through a secret link that
+//! either only shows the comparison or also lets them pick the locations
+//! they want. Links are hashed and expire like every `share_link`.
+
+use crate::{
+    db::DB,
+    error::Error,
+    models::{
+        location::LocationPhoto,
+        share_link::{self, SHARED_FIELD, ShareAccess},
+    },
+    record_id_ext::RecordIdExt,
+    units,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+pub const MAX_NAME_CHARS: usize = 120;
+
+pub const MAX_NOTE_CHARS: usize = 2000;
+
+/// Most locations one collection can hold; more stops being comparable
+pub const MAX_ENTRIES: usize = 50;
+
+/// A collection name, trimmed and checked
+pub fn clean_name(name: &str) -> Result<String, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("Give the collection a name".to_string()));
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(Error::Validation(format!(
+            "Collection names can be up to {} characters",
+            MAX_NAME_CHARS
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// A scout's note, trimmed; an empty note clears it
+pub fn clean_note(note: &str) -> Result<Option<String>, Error> {
+    let note = note.trim();
+    if note.chars().count() > MAX_NOTE_CHARS {
+        return Err(Error::Validation(format!(
+            "Notes can be up to {} characters",
+            MAX_NOTE_CHARS
+        )));
+    }
+    Ok((!note.is_empty()).then(|| note.to_string()))
+}
+
+/// Basecamp from the form: a description and optional coordinates. Both
+/// empty clears it.
+pub fn parse_basecamp(
+    name: &str,
+    coordinates: &str,
+) -> Result<(Option<String>, Option<(f64, f64)>), Error> {
+    let name = name.trim();
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(Error::Validation(format!(
+            "Keep the basecamp under {} characters",
+            MAX_NAME_CHARS
+        )));
+    }
+    let coordinates = coordinates.trim();
+    let point = if coordinates.is_empty() {
+        None
+    } else {
+        Some(units::parse_coordinates(coordinates).ok_or_else(|| {
+            Error::Validation(
+                "Enter the basecamp as latitude, longitude, like 34.0522, -118.2437".to_string(),
+            )
+        })?)
+    };
+    Ok(((!name.is_empty()).then(|| name.to_string()), point))
+}
+
+fn point(latitude: Option<f64>, longitude: Option<f64>) -> Option<(f64, f64)> {
+    Some((latitude?, longitude?))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ScoutingCollection {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub name: String,
+    pub basecamp: Option<String>,
+    pub basecamp_latitude: Option<f64>,
+    pub basecamp_longitude: Option<f64>,
+    /// None once the creator deleted their account
+    pub created_by: Option<RecordId>,
+    /// "view" or "pick" while shared
+    pub share_access: Option<String>,
+    pub share_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ScoutingCollection {
+    /// What the share link allows, while it hasn't expired
+    pub fn access(&self) -> Option<ShareAccess> {
+        share_link::live_access(self.share_access.as_deref(), self.share_expires_at)
+    }
+
+    pub fn basecamp_point(&self) -> Option<(f64, f64)> {
+        point(self.basecamp_latitude, self.basecamp_longitude)
+    }
+}
+
+/// A collection with its counts, for the production's list
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct CollectionSummary {
+    pub id: RecordId,
+    pub name: String,
+    pub basecamp: Option<String>,
+    pub locations: i64,
+    pub picked: i64,
+    pub shared: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A collection someone can save a location to, for the location page
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct CollectionChoice {
+    pub id: RecordId,
+    pub name: String,
+    pub production_title: String,
+}
+
+/// A location in one of a production's collections, for booking it on a
+/// shoot day
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ScoutedLocation {
+    pub id: RecordId,
+    pub name: String,
+    pub city: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ScoutingEntry {
+    pub id: RecordId,
+    pub collection: RecordId,
+    pub location: RecordId,
+    pub position: i64,
+    pub note: Option<String>,
+    pub selected: bool,
+    /// None once whoever added it deleted their account
+    pub added_by: Option<RecordId>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ScoutingRate {
+    pub rate_type: String,
+    pub amount: f64,
+    pub currency: String,
+    pub minimum_duration: Option<i32>,
+}
+
+/// An entry with what's needed to compare locations side by side
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ScoutingEntryListing {
+    pub id: RecordId,
+    pub location: RecordId,
+    pub name: String,
+    pub address: String,
+    pub city: String,
+    pub state: String,
+    pub country: String,
+    pub profile_photo: Option<String>,
+    pub photos: Vec<LocationPhoto>,
+    pub max_capacity: Option<i32>,
+    pub parking_info: Option<String>,
+    pub amenities: Vec<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub rates: Vec<ScoutingRate>,
+    pub note: Option<String>,
+    pub selected: bool,
+}
+
+impl ScoutingEntryListing {
+    pub fn point(&self) -> Option<(f64, f64)> {
+        point(self.latitude, self.longitude)
+    }
+}
+
+const LISTING_FIELDS: &str = "id, location, note, selected,
+    location.name AS name, location.address AS address, location.city AS city,
+    location.state AS state, location.country AS country,
+    location.profile_photo AS profile_photo, location.photos ?? [] AS photos,
+    location.max_capacity AS max_capacity, location.parking_info AS parking_info,
+    location.amenities ?? [] AS amenities,
+    location.latitude AS latitude, location.longitude AS longitude,
+    (SELECT rate_type, <float> amount AS amount, currency, minimum_duration
+        FROM location_rate WHERE location = $parent.location ORDER BY amount ASC) AS rates";
+
+pub struct ScoutingModel;
+
+impl ScoutingModel {
+    pub async fn create(
+        production: &RecordId,
+        name: &str,
+        created_by: &RecordId,
+    ) -> Result<ScoutingCollection, Error> {
+        let name = clean_name(name)?;
+
+        debug!(
+            "Creating scouting collection '{}' on {}",
+            name,
+            production.display()
+        );
+        let collection: Option<ScoutingCollection> = DB
+            .query(
+                "CREATE scouting_collection SET production = $production, name = $name,
+                    created_by = $created_by, created_at = time::now()",
+            )
+            .bind(("production", production.clone()))
+            .bind(("name", name))
+            .bind(("created_by", created_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to create scouting collection: {}", e)))?
+            .take(0)?;
+        collection
+            .ok_or_else(|| Error::Internal("Failed to create scouting collection".to_string()))
+    }
+
+    pub async fn get(collection_id: &str) -> Result<ScoutingCollection, Error> {
+        let collection: Option<ScoutingCollection> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("scouting_collection", collection_id)))
+            .await?
+            .take(0)?;
+        collection.ok_or(Error::NotFound)
+    }
+
+    /// The collection a share link points at, while it's still shared
+    pub async fn by_share_token(token: &str) -> Result<ScoutingCollection, Error> {
+        let token_hash = share_link::lookup_hash(token).ok_or(Error::NotFound)?;
+        let collection: Option<ScoutingCollection> = DB
+            .query("SELECT * FROM scouting_collection WHERE share_token_hash = $token_hash LIMIT 1")
+            .bind(("token_hash", token_hash))
+            .await?
+            .take(0)?;
+        collection
+            .filter(|c| c.access().is_some())
+            .ok_or(Error::NotFound)
+    }
+
+    /// A production's collections, newest first
+    pub async fn for_production(production: &RecordId) -> Result<Vec<CollectionSummary>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT id, name, basecamp, created_at, {SHARED_FIELD},
+                    count((SELECT id FROM scouting_entry WHERE collection = $parent.id)) AS locations,
+                    count((SELECT id FROM scouting_entry WHERE collection = $parent.id AND selected = true)) AS picked
+                 FROM scouting_collection WHERE production = $production ORDER BY created_at DESC"
+            ))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Collections on productions the person is a member of
+    pub async fn choices_for(person: &RecordId) -> Result<Vec<CollectionChoice>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, name, production.title AS production_title FROM scouting_collection
+                 WHERE production IN (SELECT VALUE out FROM member_of WHERE in = $person)
+                 ORDER BY production_title ASC, created_at DESC",
+            )
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Every location in any of the production's collections, by name
+    pub async fn locations_for(production: &RecordId) -> Result<Vec<ScoutedLocation>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, name, city FROM array::distinct((SELECT VALUE location FROM scouting_entry
+                     WHERE collection.production = $production))
+                 ORDER BY name ASC",
+            )
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn rename(collection: &RecordId, name: &str) -> Result<(), Error> {
+        let name = clean_name(name)?;
+        DB.query("UPDATE $id SET name = $name")
+            .bind(("id", collection.clone()))
+            .bind(("name", name))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to rename scouting collection: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn set_basecamp(
+        collection: &RecordId,
+        basecamp: Option<String>,
+        point: Option<(f64, f64)>,
+    ) -> Result<(), Error> {
+        DB.query(
+            "UPDATE $id SET basecamp = $basecamp, basecamp_latitude = $latitude,
+                basecamp_longitude = $longitude",
+        )
+        .bind(("id", collection.clone()))
+        .bind(("basecamp", basecamp))
+        .bind(("latitude", point.map(|p| p.0)))
+        .bind(("longitude", point.map(|p| p.1)))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to set basecamp: {}", e)))?;
+        Ok(())
+    }
+
+    /// Every location in a collection, in the order they were added
+    pub async fn entries(collection: &RecordId) -> Result<Vec<ScoutingEntryListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM scouting_entry
+                 WHERE collection = $collection ORDER BY position ASC, created_at ASC"
+            ))
+            .bind(("collection", collection.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn get_entry(entry_id: &str) -> Result<ScoutingEntry, Error> {
+        let entry: Option<ScoutingEntry> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("scouting_entry", entry_id)))
+            .await?
+            .take(0)?;
+        entry.ok_or(Error::NotFound)
+    }
+
+    /// Add a location to the end of a collection. Returns false if it was
+    /// already there.
+    pub async fn add(
+        collection: &RecordId,
+        location: &RecordId,
+        added_by: &RecordId,
+    ) -> Result<bool, Error> {
+        let existing: Vec<RecordId> = DB
+            .query("SELECT VALUE location FROM scouting_entry WHERE collection = $collection")
+            .bind(("collection", collection.clone()))
+            .await?
+            .take(0)?;
+        if existing.contains(location) {
+            return Ok(false);
+        }
+        if existing.len() >= MAX_ENTRIES {
+            return Err(Error::Validation(format!(
+                "A collection can hold up to {} locations",
+                MAX_ENTRIES
+            )));
+        }
+
+        DB.query(
+            "CREATE scouting_entry SET collection = $collection, location = $location,
+                position = $position, selected = false, added_by = $added_by,
+                created_at = time::now();
+             UPDATE $collection SET updated_at = time::now();",
+        )
+        .bind(("collection", collection.clone()))
+        .bind(("location", location.clone()))
+        .bind(("position", existing.len() as i64))
+        .bind(("added_by", added_by.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to add to scouting collection: {}", e)))?
+        .check()?;
+        Ok(true)
+    }
+
+    pub async fn set_note(entry: &RecordId, note: &str) -> Result<(), Error> {
+        let note = clean_note(note)?;
+        DB.query("UPDATE $id SET note = $note")
+            .bind(("id", entry.clone()))
+            .bind(("note", note))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to save note: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn set_selected(entry: &RecordId, selected: bool) -> Result<(), Error> {
+        DB.query("UPDATE $id SET selected = $selected")
+            .bind(("id", entry.clone()))
+            .bind(("selected", selected))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update scouting collection: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn remove(entry: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE $id")
+            .bind(("id", entry.clone()))
+            .await
+            .map_err(|e| {
+                Error::Database(format!("Failed to remove from scouting collection: {}", e))
+            })?;
+        Ok(())
+    }
+
+    pub async fn delete(collection: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE scouting_entry WHERE collection = $collection; DELETE $collection;")
+            .bind(("collection", collection.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete scouting collection: {}", e)))?;
+        Ok(())
+    }
+
+    /// Delete a production's collections
+    pub async fn delete_for_production(production: &RecordId) -> Result<(), Error> {
+        DB.query(
+            "LET $collections = SELECT VALUE id FROM scouting_collection WHERE production = $production;
+             DELETE scouting_entry WHERE collection IN $collections;
+             DELETE scouting_collection WHERE production = $production;",
+        )
+        .bind(("production", production.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to delete scouting collections: {}", e)))?;
+        Ok(())
+    }
+}
@@ -89,13 +89,7 @@ impl ScriptModel {
         debug!("Getting latest scripts for production {:?}", production_id);
 
         // Get all scripts, then deduplicate by title keeping highest version
-        let scripts: Vec<ProductionScript> = DB
-            .query(
-                "SELECT * FROM production_script WHERE production = $prod ORDER BY title ASC, version DESC",
-            )
-            .bind(("prod", production_id.clone()))
-            .await?
-            .take(0)?;
+        let scripts = Self::for_production(production_id).await?;
 
         // Keep only the first (highest version) for each title
         let mut seen_titles = std::collections::HashSet::new();
@@ -107,6 +101,20 @@ impl ScriptModel {
         Ok(latest)
     }
 
+    /// Every version of every script for a production, by title, newest
+    /// version first
+    pub async fn for_production(production_id: &RecordId) -> Result<Vec<ProductionScript>, Error> {
+        let scripts: Vec<ProductionScript> = DB
+            .query(
+                "SELECT * FROM production_script WHERE production = $prod ORDER BY title ASC, version DESC",
+            )
+            .bind(("prod", production_id.clone()))
+            .await?
+            .take(0)?;
+
+        Ok(scripts)
+    }
+
     /// Get all versions of a specific script by title
     pub async fn get_versions(
         production_id: &RecordId,
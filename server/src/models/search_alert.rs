@@ -0,0 +1,285 @@
+//! Saved site searches
+//!
+//! A signed-in person can save what they searched for on the search page,
+//! facets included. A scheduled task reruns every saved search and notifies
+//! its owner when people or productions turn up that weren't in the results
+//! before. The results at the time of saving count as already seen, so the
+//! first alert is about something new. Saved job searches, which alert as
+//! soon as a job is posted, are `saved_search`.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, error, info};
+
+use crate::{
+    config,
+    db::DB,
+    error::Error,
+    models::{block::BlockModel, notification::NotificationModel},
+    record_id_ext::RecordIdExt,
+    services::{
+        search::{self, CombinedResults},
+        search_cache,
+        search_facets::SearchFilters,
+    },
+};
+
+/// Saved site searches one person can have
+pub const MAX_PER_PERSON: usize = 20;
+
+/// Result ids remembered per search; the oldest are forgotten first
+pub const MAX_SEEN: usize = 500;
+
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct SearchAlert {
+    pub id: RecordId,
+    pub query: String,
+    pub filters: SearchFilters,
+    pub created_at: DateTime<Utc>,
+    pub last_alerted_at: Option<DateTime<Utc>>,
+}
+
+impl SearchAlert {
+    pub fn key(&self) -> String {
+        self.id.key_string()
+    }
+
+    /// "gaffer · Location: Berlin", for the saved searches page
+    pub fn describe(&self) -> String {
+        describe(&self.query, &self.filters)
+    }
+
+    /// The search page with this search and its filters
+    pub fn url(&self) -> String {
+        self.filters.url(&self.query, "", None)
+    }
+}
+
+pub fn describe(query: &str, filters: &SearchFilters) -> String {
+    let mut text = query.to_string();
+    for (_, label, value) in filters.active() {
+        text.push_str(&format!(" · {}: {}", label, value));
+    }
+    text
+}
+
+/// Raw ids of the people and productions in a set of results, the two
+/// kinds of result that alerts are about
+pub fn result_ids(results: &CombinedResults) -> Vec<String> {
+    results
+        .people
+        .iter()
+        .map(|p| p.id.clone())
+        .chain(results.productions.iter().map(|p| p.id.clone()))
+        .collect()
+}
+
+/// `seen` with `ids` added, keeping the newest `MAX_SEEN`
+pub fn remember(seen: &[String], ids: &[String]) -> Vec<String> {
+    let mut all: Vec<String> = seen
+        .iter()
+        .filter(|id| !ids.contains(id))
+        .cloned()
+        .collect();
+    all.extend(ids.iter().cloned());
+    let excess = all.len().saturating_sub(MAX_SEEN);
+    all.split_off(excess)
+}
+
+/// "2 new people and 1 new production"
+pub fn summary(people: usize, productions: usize) -> String {
+    let part = |count: usize, one: &str, many: &str| {
+        format!("{} new {}", count, if count == 1 { one } else { many })
+    };
+    match (people, productions) {
+        (0, p) => part(p, "production", "productions"),
+        (n, 0) => part(n, "person", "people"),
+        (n, p) => format!(
+            "{} and {}",
+            part(n, "person", "people"),
+            part(p, "production", "productions")
+        ),
+    }
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct RunRow {
+    id: RecordId,
+    person: RecordId,
+    query: String,
+    filters: SearchFilters,
+    seen: Vec<String>,
+}
+
+pub struct SearchAlertModel;
+
+impl SearchAlertModel {
+    /// What a search finds for this person right now, with their filters
+    /// applied and the people they've blocked or muted left out
+    async fn current_results(
+        person: &RecordId,
+        query: &str,
+        filters: &SearchFilters,
+    ) -> Result<CombinedResults, Error> {
+        let mut results = match search_cache::results(query, None) {
+            Some(results) => CombinedResults::clone(&results),
+            None => {
+                let embedding = search_cache::embedding(query).await;
+                search::search_all(
+                    query,
+                    embedding.as_ref(),
+                    config::search_config(),
+                    None,
+                    None,
+                    None,
+                )
+                .await?
+            }
+        };
+        let hidden = BlockModel::hidden_ids(person).await.unwrap_or_default();
+        let me = person.to_raw_string();
+        results
+            .people
+            .retain(|p| p.id != me && !hidden.contains(&p.id));
+        filters.apply(&mut results);
+        Ok(results)
+    }
+
+    /// Save a search. Saving the same search twice is a no-op.
+    pub async fn create(
+        person: &RecordId,
+        query: &str,
+        filters: &SearchFilters,
+    ) -> Result<(), Error> {
+        let query = query.split_whitespace().collect::<Vec<_>>().join(" ");
+        if query.is_empty() {
+            return Err(Error::Validation(
+                "Search for something before saving it".to_string(),
+            ));
+        }
+
+        let existing = Self::list(person).await?;
+        if existing
+            .iter()
+            .any(|s| s.query.eq_ignore_ascii_case(&query) && s.filters == *filters)
+        {
+            return Ok(());
+        }
+        if existing.len() >= MAX_PER_PERSON {
+            return Err(Error::Validation(format!(
+                "You can have up to {} saved searches",
+                MAX_PER_PERSON
+            )));
+        }
+
+        // Only what turns up from now on is worth an alert
+        let seen = result_ids(&Self::current_results(person, &query, filters).await?);
+        DB.query(
+            "CREATE search_alert SET person = $person, query = $query, filters = $filters,
+                seen = $seen, last_run_at = time::now()",
+        )
+        .bind(("person", person.clone()))
+        .bind(("query", query.clone()))
+        .bind(("filters", filters.clone()))
+        .bind(("seen", remember(&[], &seen)))
+        .await?
+        .check()?;
+        debug!("{} saved a search for '{}'", person.display(), query);
+        Ok(())
+    }
+
+    /// A person's saved searches, newest first
+    pub async fn list(person: &RecordId) -> Result<Vec<SearchAlert>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, query, filters, created_at, last_alerted_at
+                FROM search_alert WHERE person = $person ORDER BY created_at DESC",
+            )
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Remove one of a person's saved searches
+    pub async fn delete(person: &RecordId, key: &str) -> Result<(), Error> {
+        DB.query("DELETE search_alert WHERE id = $id AND person = $person")
+            .bind(("id", RecordId::new("search_alert", key)))
+            .bind(("person", person.clone()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    /// Rerun one saved search and notify its owner about anything new.
+    /// Returns whether they were notified.
+    async fn run(alert: RunRow, notifications: &NotificationModel) -> Result<bool, Error> {
+        let results = Self::current_results(&alert.person, &alert.query, &alert.filters).await?;
+        let new_people = results
+            .people
+            .iter()
+            .filter(|p| !alert.seen.contains(&p.id))
+            .count();
+        let new_productions = results
+            .productions
+            .iter()
+            .filter(|p| !alert.seen.contains(&p.id))
+            .count();
+
+        let notify = new_people + new_productions > 0;
+        if notify {
+            notifications
+                .create(
+                    &alert.person.to_raw_string(),
+                    "search_alert",
+                    &format!("New results for \"{}\"", alert.query),
+                    &format!(
+                        "{} match your saved search",
+                        summary(new_people, new_productions)
+                    ),
+                    Some(&alert.filters.url(&alert.query, "", None)),
+                    Some(&alert.id.key_string()),
+                )
+                .await?;
+        }
+
+        DB.query(
+            "UPDATE $id SET seen = $seen, last_run_at = time::now(),
+                last_alerted_at = IF $notify { time::now() } ELSE { last_alerted_at }",
+        )
+        .bind(("id", alert.id))
+        .bind(("seen", remember(&alert.seen, &result_ids(&results))))
+        .bind(("notify", notify))
+        .await?
+        .check()?;
+        Ok(notify)
+    }
+
+    /// Rerun every saved search, for the scheduled task. One search failing
+    /// doesn't hold up the rest.
+    pub async fn run_all() -> Result<(), Error> {
+        let alerts: Vec<RunRow> = DB
+            .query("SELECT id, person, query, filters, seen FROM search_alert")
+            .await?
+            .take(0)?;
+
+        let total = alerts.len();
+        let notifications = NotificationModel::new();
+        let mut notified = 0;
+        for alert in alerts {
+            let id = alert.id.to_raw_string();
+            match Self::run(alert, &notifications).await {
+                Ok(true) => notified += 1,
+                Ok(false) => {}
+                Err(e) => error!("Saved search {} failed: {}", id, e),
+            }
+        }
+        if notified > 0 {
+            info!(
+                "Reran {} saved searches, {} had new results",
+                total, notified
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,117 @@
+//! Search history
+//!
+//! Queries a signed-in member runs from the search page are kept so the empty
+//! search page can offer the most recent ones again. A query is kept once, with
+//! when it was last run, and only the newest `MAX_KEPT` are kept. History is on
+//! by default; turning it off under account settings clears it, and it can be
+//! cleared from the search page at any time.
+
+use surrealdb::types::RecordId;
+use tracing::{debug, info, warn};
+
+use crate::{db::DB, error::Error, record_id_ext::RecordIdExt};
+
+/// Recent searches offered on the empty search page
+pub const RECENT_SHOWN: usize = 10;
+
+/// Queries kept per person; older ones are dropped as new ones come in
+pub const MAX_KEPT: usize = 50;
+
+/// Longer queries aren't worth offering again and aren't kept
+pub const MAX_QUERY_CHARS: usize = 200;
+
+/// The query as kept, with runs of whitespace collapsed. `None` when there's
+/// nothing to keep.
+pub fn normalize_query(query: &str) -> Option<String> {
+    let query = query.split_whitespace().collect::<Vec<_>>().join(" ");
+    if query.is_empty() || query.chars().count() > MAX_QUERY_CHARS {
+        return None;
+    }
+    Some(query)
+}
+
+pub struct SearchHistoryModel;
+
+impl SearchHistoryModel {
+    /// Keep a query in the background, unless the person turned history off.
+    /// Running the same query again moves it back to the top.
+    pub fn record(person: &RecordId, query: &str) {
+        let Some(query) = normalize_query(query) else {
+            return;
+        };
+        let person = person.clone();
+        crate::db::spawn(async move {
+            let res = DB
+                .query(
+                    "IF (SELECT VALUE search_history_enabled FROM ONLY $person) != false {
+                        UPSERT search_history SET person = $person, query = $query,
+                            searched_at = time::now()
+                        WHERE person = $person AND query = $query;
+                        DELETE search_history WHERE person = $person AND id NOT IN
+                            (SELECT VALUE id FROM search_history WHERE person = $person
+                             ORDER BY searched_at DESC LIMIT $keep);
+                    };",
+                )
+                .bind(("person", person.clone()))
+                .bind(("query", query))
+                .bind(("keep", MAX_KEPT as i64))
+                .await
+                .and_then(|res| res.check());
+            if let Err(e) = res {
+                warn!(error = %e, "Failed to record search history for {}", person.display());
+            }
+        });
+    }
+
+    /// A person's most recent queries, newest first
+    pub async fn recent(person: &RecordId, limit: usize) -> Result<Vec<String>, Error> {
+        Ok(DB
+            .query(
+                "SELECT VALUE query FROM (
+                    SELECT query, searched_at FROM search_history WHERE person = $person
+                    ORDER BY searched_at DESC LIMIT $limit
+                )",
+            )
+            .bind(("person", person.clone()))
+            .bind(("limit", limit as i64))
+            .await?
+            .take(0)?)
+    }
+
+    /// Forget everything a person searched for
+    pub async fn clear(person: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE search_history WHERE person = $person")
+            .bind(("person", person.clone()))
+            .await?
+            .check()?;
+        debug!("Cleared search history for {}", person.display());
+        Ok(())
+    }
+
+    pub async fn is_enabled(person: &RecordId) -> Result<bool, Error> {
+        let enabled: Option<bool> = DB
+            .query("SELECT VALUE search_history_enabled FROM ONLY $person")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        Ok(enabled.unwrap_or(true))
+    }
+
+    /// Turn history on or off; turning it off also clears it
+    pub async fn set_enabled(person: &RecordId, enabled: bool) -> Result<(), Error> {
+        DB.query("UPDATE $person SET search_history_enabled = $enabled")
+            .bind(("person", person.clone()))
+            .bind(("enabled", enabled))
+            .await?
+            .check()?;
+        if !enabled {
+            Self::clear(person).await?;
+        }
+        info!(
+            "Search history for {} {}",
+            person.display(),
+            if enabled { "enabled" } else { "disabled" }
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,552 @@
+//! Casting self-tapes
+//!
+//! Whoever can edit a job posting can ask actors to self-tape for one of its
+//! roles, with instructions and a deadline. Each invited actor gets a
+//! `selftape` row to upload their video against; the video is sent as a
+//! resumable upload into private S3 storage and queued for transcoding into
+//! web renditions. The casting team then reviews the submissions in a
+//! gallery, rating and commenting on each tape.
+
+use crate::{
+    db::DB,
+    error::Error,
+    record_id_ext::RecordIdExt,
+    services::{
+        s3::s3,
+        transcode::{self, Rendition},
+    },
+};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, error};
+use ulid::Ulid;
+
+const VIDEO_PREFIX: &str = "private/selftapes";
+
+/// Resumable upload purpose for self-tape videos
+pub const UPLOAD_PURPOSE: &str = "selftape";
+
+/// Accepted video formats, as (extension, content type)
+pub const VIDEO_TYPES: &[(&str, &str)] = &[
+    ("mp4", "video/mp4"),
+    ("m4v", "video/x-m4v"),
+    ("mov", "video/quicktime"),
+    ("webm", "video/webm"),
+];
+
+/// Largest self-tape that can be uploaded (2 GB)
+pub const MAX_VIDEO_BYTES: i64 = 2 * 1024 * 1024 * 1024;
+
+/// Most people that can be invited in one go
+pub const MAX_INVITEES: usize = 100;
+
+pub const MAX_COMMENT_CHARS: usize = 2000;
+
+/// Extension and content type for an uploaded video's filename
+pub fn video_type(filename: &str) -> Option<(&'static str, &'static str)> {
+    let extension = filename.rsplit_once('.')?.1.to_lowercase();
+    VIDEO_TYPES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(ext, content_type)| (*ext, *content_type))
+}
+
+/// Where a new upload for a tape is stored. The key is unique per upload so a
+/// replacement never overwrites the video reviewers may be watching.
+pub fn video_key(tape: &SelfTape, extension: &str) -> String {
+    format!("{}{}.{}", video_key_prefix(tape), Ulid::new(), extension)
+}
+
+fn video_key_prefix(tape: &SelfTape) -> String {
+    format!(
+        "{}/{}/{}-",
+        VIDEO_PREFIX,
+        tape.request.key_string(),
+        tape.person.key_string()
+    )
+}
+
+/// Whether a storage key was issued for this tape, so a client can't attach
+/// someone else's video to their submission
+pub fn owns_video_key(tape: &SelfTape, key: &str) -> bool {
+    key.strip_prefix(&video_key_prefix(tape))
+        .is_some_and(|rest| !rest.contains('/') && !rest.contains(".."))
+}
+
+/// Usernames from a free-text list separated by commas, spaces or new lines.
+/// A leading "@" is ignored and duplicates are dropped.
+pub fn parse_usernames(text: &str) -> Vec<String> {
+    let mut usernames: Vec<String> = Vec::new();
+    for word in text.split(|c: char| c == ',' || c.is_whitespace()) {
+        let username = word.trim().trim_start_matches('@').to_lowercase();
+        if !username.is_empty() && !usernames.contains(&username) {
+            usernames.push(username);
+        }
+    }
+    usernames
+}
+
+/// A deadline from a "YYYY-MM-DD" date and optional "HH:MM" time in UTC. A
+/// date alone means the end of that day. The deadline has to be in the future.
+pub fn parse_deadline(date: &str, time: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, Error> {
+    let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+        .map_err(|_| Error::Validation("Choose a deadline date".to_string()))?;
+    let time = match time.trim() {
+        "" => NaiveTime::from_hms_opt(23, 59, 0).expect("valid time"),
+        time => NaiveTime::parse_from_str(time, "%H:%M")
+            .map_err(|_| Error::Validation("Enter the deadline time as HH:MM".to_string()))?,
+    };
+    let deadline = date.and_time(time).and_utc();
+    if deadline <= now {
+        return Err(Error::Validation("The deadline has to be in the future".to_string()));
+    }
+    Ok(deadline)
+}
+
+/// Mean of the ratings to one decimal place
+pub fn average_rating(ratings: &[i64]) -> Option<f64> {
+    if ratings.is_empty() {
+        return None;
+    }
+    let mean = ratings.iter().sum::<i64>() as f64 / ratings.len() as f64;
+    Some((mean * 10.0).round() / 10.0)
+}
+
+/// A request for self-tapes for one role on a job posting
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct SelfTapeRequest {
+    pub id: RecordId,
+    pub job: RecordId,
+    pub role_title: String,
+    pub instructions: String,
+    pub deadline: DateTime<Utc>,
+    pub created_by: RecordId,
+    /// "open" or "closed"
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SelfTapeRequest {
+    /// Whether tapes can still be uploaded
+    pub fn accepts_uploads(&self, now: DateTime<Utc>) -> bool {
+        self.status == "open" && now < self.deadline
+    }
+}
+
+/// A request with its submission counts, for the job's request list
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct SelfTapeRequestSummary {
+    pub id: RecordId,
+    pub role_title: String,
+    pub deadline: DateTime<Utc>,
+    pub status: String,
+    pub invited: i64,
+    pub submitted: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One actor's tape for a request
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct SelfTape {
+    pub id: RecordId,
+    pub request: RecordId,
+    pub person: RecordId,
+    /// "requested" or "submitted"
+    pub status: String,
+    pub video_key: Option<String>,
+    pub content_type: Option<String>,
+    pub size: Option<i64>,
+    pub note: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A tape with the actor's name and every rating it has, for the gallery
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct SelfTapeListing {
+    pub id: RecordId,
+    pub person: RecordId,
+    pub person_name: String,
+    pub username: String,
+    pub status: String,
+    pub video_key: Option<String>,
+    pub content_type: Option<String>,
+    pub note: Option<String>,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub ratings: Vec<i64>,
+    /// Transcoded versions of the video, if any were queued
+    pub rendition: Option<Rendition>,
+}
+
+/// An actor's tape with the request and job it's for
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct MySelfTape {
+    pub id: RecordId,
+    pub status: String,
+    pub submitted_at: Option<DateTime<Utc>>,
+    pub job: RecordId,
+    pub job_title: String,
+    pub role_title: String,
+    pub deadline: DateTime<Utc>,
+    pub request_status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct SelfTapeRating {
+    pub selftape: RecordId,
+    pub rating: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct SelfTapeComment {
+    pub id: RecordId,
+    pub selftape: RecordId,
+    pub author_name: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+const LISTING_FIELDS: &str = "id, person, person.name ?? person.username AS person_name,
+    person.username AS username, status, video_key, content_type, note, submitted_at,
+    (SELECT VALUE rating FROM selftape_rating WHERE selftape = $parent.id) AS ratings,
+    (SELECT status, mp4_key, poster_key, hls_key FROM transcode_job
+        WHERE source_key = $parent.video_key LIMIT 1)[0] AS rendition";
+
+/// Delete a stored video and its renditions, logging rather than failing
+/// when storage is unavailable
+async fn delete_video(key: &str) {
+    if let Err(e) = transcode::discard(key).await {
+        error!("Failed to delete renditions of self-tape {}: {}", key, e);
+    }
+    match s3() {
+        Ok(s3) => {
+            if let Err(e) = s3.delete_file(key).await {
+                error!("Failed to delete self-tape {}: {}", key, e);
+            }
+        }
+        Err(e) => error!("S3 unavailable, self-tape {} not deleted: {}", key, e),
+    }
+}
+
+pub struct SelfTapeModel;
+
+impl SelfTapeModel {
+    pub async fn create_request(
+        job: &RecordId,
+        role_title: &str,
+        instructions: &str,
+        deadline: DateTime<Utc>,
+        created_by: &RecordId,
+    ) -> Result<SelfTapeRequest, Error> {
+        if role_title.trim().is_empty() {
+            return Err(Error::Validation("Choose the role to tape for".to_string()));
+        }
+        if instructions.trim().is_empty() {
+            return Err(Error::Validation(
+                "Add instructions: the sides to read, framing, slate and so on".to_string(),
+            ));
+        }
+
+        debug!("Creating self-tape request for {} on {}", role_title, job.display());
+        let request: Option<SelfTapeRequest> = DB
+            .query(
+                "CREATE selftape_request SET job = $job, role_title = $role_title,
+                    instructions = $instructions, deadline = $deadline, created_by = $created_by,
+                    status = 'open', created_at = time::now()",
+            )
+            .bind(("job", job.clone()))
+            .bind(("role_title", role_title.trim().to_string()))
+            .bind(("instructions", instructions.trim().to_string()))
+            .bind(("deadline", deadline))
+            .bind(("created_by", created_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to create self-tape request: {}", e)))?
+            .take(0)?;
+        request.ok_or_else(|| Error::Internal("Failed to create self-tape request".to_string()))
+    }
+
+    pub async fn get_request(request_id: &str) -> Result<SelfTapeRequest, Error> {
+        let request: Option<SelfTapeRequest> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("selftape_request", request_id)))
+            .await?
+            .take(0)?;
+        request.ok_or(Error::NotFound)
+    }
+
+    /// A job's requests, newest first
+    pub async fn requests_for_job(job: &RecordId) -> Result<Vec<SelfTapeRequestSummary>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, role_title, deadline, status, created_at,
+                    count((SELECT id FROM selftape WHERE request = $parent.id)) AS invited,
+                    count((SELECT id FROM selftape WHERE request = $parent.id AND status = 'submitted')) AS submitted
+                 FROM selftape_request WHERE job = $job ORDER BY created_at DESC",
+            )
+            .bind(("job", job.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn close_request(request: &RecordId) -> Result<(), Error> {
+        DB.query("UPDATE $id SET status = 'closed'")
+            .bind(("id", request.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to close self-tape request: {}", e)))?;
+        Ok(())
+    }
+
+    /// Applicants to a role who haven't withdrawn
+    pub async fn role_applicants(job: &RecordId, role_title: &str) -> Result<Vec<RecordId>, Error> {
+        Ok(DB
+            .query(
+                "SELECT VALUE in FROM application
+                 WHERE out = $job AND role_title = $role_title AND status NOT IN ['withdrawn', 'rejected']",
+            )
+            .bind(("job", job.clone()))
+            .bind(("role_title", role_title.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    /// People with these usernames. Unknown usernames are returned separately.
+    pub async fn people_by_username(usernames: &[String]) -> Result<(Vec<RecordId>, Vec<String>), Error> {
+        #[derive(Deserialize, SurrealValue)]
+        struct Found {
+            id: RecordId,
+            username: String,
+        }
+
+        let found: Vec<Found> = DB
+            .query("SELECT id, username FROM person WHERE string::lowercase(username) IN $usernames")
+            .bind(("usernames", usernames.to_vec()))
+            .await?
+            .take(0)?;
+        let unknown = usernames
+            .iter()
+            .filter(|u| !found.iter().any(|f| f.username.eq_ignore_ascii_case(u)))
+            .cloned()
+            .collect();
+        Ok((found.into_iter().map(|f| f.id).collect(), unknown))
+    }
+
+    /// Ask people to tape for a request. Returns who was newly invited; anyone
+    /// already invited is skipped.
+    pub async fn invite(request: &SelfTapeRequest, people: &[RecordId]) -> Result<Vec<RecordId>, Error> {
+        if people.len() > MAX_INVITEES {
+            return Err(Error::Validation(format!(
+                "You can invite up to {} people at a time",
+                MAX_INVITEES
+            )));
+        }
+        let existing: Vec<RecordId> = DB
+            .query("SELECT VALUE person FROM selftape WHERE request = $request")
+            .bind(("request", request.id.clone()))
+            .await?
+            .take(0)?;
+        let mut new_people: Vec<RecordId> = Vec::new();
+        for person in people {
+            if !existing.contains(person) && !new_people.contains(person) {
+                new_people.push(person.clone());
+            }
+        }
+        if new_people.is_empty() {
+            return Ok(new_people);
+        }
+
+        DB.query(
+            "FOR $person IN $people {
+                CREATE selftape SET request = $request, person = $person, status = 'requested',
+                    created_at = time::now();
+            }",
+        )
+        .bind(("request", request.id.clone()))
+        .bind(("people", new_people.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to invite to self-tape: {}", e)))?
+        .check()?;
+        Ok(new_people)
+    }
+
+    pub async fn get(tape_id: &str) -> Result<SelfTape, Error> {
+        let tape: Option<SelfTape> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("selftape", tape_id)))
+            .await?
+            .take(0)?;
+        tape.ok_or(Error::NotFound)
+    }
+
+    /// Every tape for a request: submitted first, most recent first
+    pub async fn tapes(request: &RecordId) -> Result<Vec<SelfTapeListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM selftape WHERE request = $request
+                 ORDER BY status DESC, submitted_at DESC, person_name ASC"
+            ))
+            .bind(("request", request.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// A person's self-tape requests, nearest deadline first
+    pub async fn for_person(person: &RecordId) -> Result<Vec<MySelfTape>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, status, submitted_at, request.job AS job, request.job.title AS job_title,
+                    request.role_title AS role_title, request.deadline AS deadline,
+                    request.status AS request_status
+                 FROM selftape WHERE person = $person ORDER BY deadline ASC",
+            )
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Attach an uploaded video to a tape, submitting it, and queue it for
+    /// transcoding. A video uploaded earlier is replaced.
+    pub async fn submit(
+        tape: &SelfTape,
+        video_key: &str,
+        content_type: &str,
+        size: i64,
+        note: Option<String>,
+    ) -> Result<(), Error> {
+        if note.as_ref().is_some_and(|n| n.chars().count() > MAX_COMMENT_CHARS) {
+            return Err(Error::Validation(format!(
+                "Notes can be up to {} characters",
+                MAX_COMMENT_CHARS
+            )));
+        }
+        DB.query(
+            "UPDATE $id SET status = 'submitted', video_key = $video_key, content_type = $content_type,
+                size = $size, note = $note, submitted_at = time::now()",
+        )
+        .bind(("id", tape.id.clone()))
+        .bind(("video_key", video_key.to_string()))
+        .bind(("content_type", content_type.to_string()))
+        .bind(("size", size))
+        .bind(("note", note))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to submit self-tape: {}", e)))?;
+
+        if let Err(e) = transcode::enqueue(&tape.person, video_key).await {
+            error!("Failed to queue transcode of self-tape {}: {}", video_key, e);
+        }
+        if let Some(previous) = tape.video_key.as_deref()
+            && previous != video_key
+        {
+            delete_video(previous).await;
+        }
+        Ok(())
+    }
+
+    /// Rate a tape from 1 to 5, replacing the reviewer's earlier rating
+    pub async fn rate(tape: &RecordId, reviewer: &RecordId, rating: i64) -> Result<(), Error> {
+        if !(1..=5).contains(&rating) {
+            return Err(Error::Validation("Ratings go from 1 to 5".to_string()));
+        }
+        DB.query(
+            "UPSERT selftape_rating SET selftape = $tape, reviewer = $reviewer, rating = $rating
+             WHERE selftape = $tape AND reviewer = $reviewer",
+        )
+        .bind(("tape", tape.clone()))
+        .bind(("reviewer", reviewer.clone()))
+        .bind(("rating", rating))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to rate self-tape: {}", e)))?;
+        Ok(())
+    }
+
+    /// A reviewer's own ratings across a request's tapes
+    pub async fn ratings_by(request: &RecordId, reviewer: &RecordId) -> Result<Vec<SelfTapeRating>, Error> {
+        Ok(DB
+            .query("SELECT selftape, rating FROM selftape_rating WHERE reviewer = $reviewer AND selftape.request = $request")
+            .bind(("request", request.clone()))
+            .bind(("reviewer", reviewer.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn comment(tape: &RecordId, author: &RecordId, body: &str) -> Result<(), Error> {
+        let body = body.trim();
+        if body.is_empty() {
+            return Err(Error::Validation("Write a comment first".to_string()));
+        }
+        if body.chars().count() > MAX_COMMENT_CHARS {
+            return Err(Error::Validation(format!(
+                "Comments can be up to {} characters",
+                MAX_COMMENT_CHARS
+            )));
+        }
+        DB.query("CREATE selftape_comment SET selftape = $tape, author = $author, body = $body, created_at = time::now()")
+            .bind(("tape", tape.clone()))
+            .bind(("author", author.clone()))
+            .bind(("body", body.to_string()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to add comment: {}", e)))?;
+        Ok(())
+    }
+
+    /// Comments on a request's tapes, oldest first
+    pub async fn comments(request: &RecordId) -> Result<Vec<SelfTapeComment>, Error> {
+        Ok(DB
+            .query(
+                "SELECT id, selftape, author.name ?? author.username AS author_name, body, created_at
+                 FROM selftape_comment WHERE selftape.request = $request ORDER BY created_at ASC",
+            )
+            .bind(("request", request.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Delete a job's requests, tapes and reviews, and the stored videos
+    pub async fn delete_for_job(job: &RecordId) -> Result<(), Error> {
+        let keys: Vec<String> = DB
+            .query("SELECT VALUE video_key FROM selftape WHERE request.job = $job AND video_key != NONE")
+            .bind(("job", job.clone()))
+            .await?
+            .take(0)?;
+        for key in &keys {
+            delete_video(key).await;
+        }
+
+        DB.query(
+            "LET $requests = SELECT VALUE id FROM selftape_request WHERE job = $job;
+             LET $tapes = SELECT VALUE id FROM selftape WHERE request IN $requests;
+             DELETE selftape_rating WHERE selftape IN $tapes;
+             DELETE selftape_comment WHERE selftape IN $tapes;
+             DELETE selftape WHERE request IN $requests;
+             DELETE selftape_request WHERE job = $job;",
+        )
+        .bind(("job", job.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to delete self-tapes: {}", e)))?;
+        Ok(())
+    }
+
+    /// Delete a person's tapes and videos, and the ratings and comments they
+    /// left, for account deletion
+    pub async fn forget(person: &RecordId) -> Result<(), Error> {
+        let keys: Vec<String> = DB
+            .query("SELECT VALUE video_key FROM selftape WHERE person = $person AND video_key != NONE")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        for key in &keys {
+            delete_video(key).await;
+        }
+
+        DB.query(
+            "LET $tapes = SELECT VALUE id FROM selftape WHERE person = $person;
+             DELETE selftape_rating WHERE selftape IN $tapes OR reviewer = $person;
+             DELETE selftape_comment WHERE selftape IN $tapes OR author = $person;
+             DELETE selftape WHERE person = $person;",
+        )
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to delete self-tapes: {}", e)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+//! Secret share links
+//!
+//! Casting shortlists and scouting collections can be shared with someone
+//! outside the team (a producer, the director) through a link that either
+//! only shows the list or also lets them pick from it. Both keep the link on
+//! their own record in the same fields: `share_token_hash`, `share_access`,
+//! `shared_at` and `share_expires_at`.
+//!
+//! Only a hash of the token is stored, so a link is shown once, when it's
+//! made, and it stops working after `SHARE_DAYS`. Changing what a live link
+//! allows keeps the link; turning sharing off and on again makes a new one.
+
+use crate::{
+    db::DB,
+    error::Error,
+    services::tokens::{self, token_hash},
+};
+use chrono::{DateTime, Duration, Utc};
+use surrealdb::types::RecordId;
+
+const TOKEN_LENGTH: usize = 32;
+
+/// How long a share link works
+pub const SHARE_DAYS: i64 = 30;
+
+/// Selects whether a record's link still works, as `shared`
+pub const SHARED_FIELD: &str = "share_expires_at > time::now() AS shared";
+
+/// What a share link lets its holder do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareAccess {
+    /// See the list in order
+    View,
+    /// Also pick from it
+    Pick,
+}
+
+impl ShareAccess {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "view" => Some(Self::View),
+            "pick" => Some(Self::Pick),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::View => "view",
+            Self::Pick => "pick",
+        }
+    }
+}
+
+/// What a record's link allows, while it hasn't expired
+pub fn live_access(access: Option<&str>, expires_at: Option<DateTime<Utc>>) -> Option<ShareAccess> {
+    expires_at.filter(|at| *at > Utc::now())?;
+    access.and_then(ShareAccess::parse)
+}
+
+/// The stored hash to look a link up by, or `None` when the token can't be
+/// one of ours
+pub fn lookup_hash(token: &str) -> Option<String> {
+    tokens::is_token(token, TOKEN_LENGTH).then(|| token_hash(token))
+}
+
+/// Share a record. A link that still works (`current` is its access) keeps
+/// working with the new access. Otherwise a new link is made and its token
+/// returned; this is the only time it's known.
+pub async fn share(
+    record: &RecordId,
+    current: Option<ShareAccess>,
+    access: ShareAccess,
+) -> Result<Option<String>, Error> {
+    if current.is_some() {
+        DB.query("UPDATE $id SET share_access = $access")
+            .bind(("id", record.clone()))
+            .bind(("access", access.as_str().to_string()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update share link: {}", e)))?;
+        return Ok(None);
+    }
+
+    let token = tokens::random_token(TOKEN_LENGTH);
+    DB.query(
+        "UPDATE $id SET share_token_hash = $token_hash, share_access = $access,
+            shared_at = time::now(), share_expires_at = $expires_at",
+    )
+    .bind(("id", record.clone()))
+    .bind(("token_hash", token_hash(&token)))
+    .bind(("access", access.as_str().to_string()))
+    .bind(("expires_at", Utc::now() + Duration::days(SHARE_DAYS)))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to make share link: {}", e)))?;
+    Ok(Some(token))
+}
+
+/// Turn a record's link off
+pub async fn unshare(record: &RecordId) -> Result<(), Error> {
+    DB.query(
+        "UPDATE $id SET share_token_hash = NONE, share_access = NONE, shared_at = NONE,
+            share_expires_at = NONE",
+    )
+    .bind(("id", record.clone()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to turn off share link: {}", e)))?;
+    Ok(())
+}
@@ -0,0 +1,391 @@
+//! Casting shortlists
+//!
+//! Whoever can edit a job posting can keep named shortlists for each of its
+//! roles: people ranked in order, each with a private note for the casting
+//! team. A shortlist can be shared with a producer through a secret link that
+//! either only shows the list or also lets them pick favourites, and the
+//! selected people can then be sent offers. Notes never leave the casting
+//! team. Links are hashed and expire like every `share_link`.
+
+use crate::{
+    db::DB,
+    error::Error,
+    models::{
+        person::AgeRange,
+        share_link::{self, SHARED_FIELD, ShareAccess},
+    },
+    record_id_ext::RecordIdExt,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+pub const MAX_NAME_CHARS: usize = 120;
+
+pub const MAX_NOTE_CHARS: usize = 2000;
+
+/// Most people one shortlist can hold
+pub const MAX_ENTRIES: usize = 200;
+
+/// A shortlist name, trimmed and checked
+pub fn clean_name(name: &str) -> Result<String, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("Give the shortlist a name".to_string()));
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(Error::Validation(format!(
+            "Shortlist names can be up to {} characters",
+            MAX_NAME_CHARS
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// A private note, trimmed; an empty note clears it
+pub fn clean_note(note: &str) -> Result<Option<String>, Error> {
+    let note = note.trim();
+    if note.chars().count() > MAX_NOTE_CHARS {
+        return Err(Error::Validation(format!(
+            "Notes can be up to {} characters",
+            MAX_NOTE_CHARS
+        )));
+    }
+    Ok((!note.is_empty()).then(|| note.to_string()))
+}
+
+/// The order after moving `id` one place earlier or later, or `None` when it
+/// can't move that way
+pub fn shifted(order: &[RecordId], id: &RecordId, earlier: bool) -> Option<Vec<RecordId>> {
+    let index = order.iter().position(|other| other == id)?;
+    let other = if earlier { index.checked_sub(1)? } else { index + 1 };
+    if other >= order.len() {
+        return None;
+    }
+    let mut order = order.to_vec();
+    order.swap(index, other);
+    Some(order)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Shortlist {
+    pub id: RecordId,
+    pub job: RecordId,
+    pub role_title: String,
+    pub name: String,
+    pub created_by: RecordId,
+    /// "view" or "pick" while shared
+    pub share_access: Option<String>,
+    pub share_expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Shortlist {
+    /// What the share link allows, while it hasn't expired
+    pub fn access(&self) -> Option<ShareAccess> {
+        share_link::live_access(self.share_access.as_deref(), self.share_expires_at)
+    }
+}
+
+/// A shortlist with its counts, for the job's list
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ShortlistSummary {
+    pub id: RecordId,
+    pub role_title: String,
+    pub name: String,
+    pub people: i64,
+    pub selected: i64,
+    pub shared: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ShortlistEntry {
+    pub id: RecordId,
+    pub shortlist: RecordId,
+    pub person: RecordId,
+    pub position: i64,
+    pub note: Option<String>,
+    pub selected: bool,
+    pub offered_at: Option<DateTime<Utc>>,
+    pub added_by: RecordId,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An entry with what's needed to compare people side by side
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ShortlistEntryListing {
+    pub id: RecordId,
+    pub person: RecordId,
+    pub person_name: String,
+    pub username: String,
+    pub avatar: Option<String>,
+    pub headline: Option<String>,
+    pub location: Option<String>,
+    pub age_range: Option<AgeRange>,
+    /// Hidden for minors
+    pub height: Option<i32>,
+    pub unions: Vec<String>,
+    pub note: Option<String>,
+    pub selected: bool,
+    pub offered_at: Option<DateTime<Utc>>,
+    /// Ratings of their self-tape for the same role, if they sent one
+    pub tape_ratings: Vec<i64>,
+    pub tape_request: Option<RecordId>,
+    pub tape: Option<RecordId>,
+}
+
+const LISTING_FIELDS: &str = "id, person, note, selected, offered_at,
+    person.name ?? person.username AS person_name, person.username AS username,
+    person.profile.avatar AS avatar, person.profile.headline AS headline,
+    person.profile.location AS location, person.profile.acting_age_range AS age_range,
+    IF person.is_minor THEN NONE ELSE person.profile.height_mm END AS height,
+    person.profile.unions ?? [] AS unions,
+    (SELECT VALUE id FROM selftape WHERE person = $parent.person AND request.job = $job
+        AND request.role_title = $role_title AND status = 'submitted' LIMIT 1)[0] AS tape,
+    (SELECT VALUE request FROM selftape WHERE person = $parent.person AND request.job = $job
+        AND request.role_title = $role_title AND status = 'submitted' LIMIT 1)[0] AS tape_request,
+    (SELECT VALUE rating FROM selftape_rating WHERE selftape.person = $parent.person
+        AND selftape.request.job = $job AND selftape.request.role_title = $role_title) AS tape_ratings";
+
+pub struct ShortlistModel;
+
+impl ShortlistModel {
+    pub async fn create(
+        job: &RecordId,
+        role_title: &str,
+        name: &str,
+        created_by: &RecordId,
+    ) -> Result<Shortlist, Error> {
+        if role_title.trim().is_empty() {
+            return Err(Error::Validation("Choose the role to shortlist for".to_string()));
+        }
+        let name = clean_name(name)?;
+
+        debug!("Creating shortlist '{}' for {} on {}", name, role_title, job.display());
+        let shortlist: Option<Shortlist> = DB
+            .query(
+                "CREATE shortlist SET job = $job, role_title = $role_title, name = $name,
+                    created_by = $created_by, created_at = time::now()",
+            )
+            .bind(("job", job.clone()))
+            .bind(("role_title", role_title.trim().to_string()))
+            .bind(("name", name))
+            .bind(("created_by", created_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to create shortlist: {}", e)))?
+            .take(0)?;
+        shortlist.ok_or_else(|| Error::Internal("Failed to create shortlist".to_string()))
+    }
+
+    pub async fn get(shortlist_id: &str) -> Result<Shortlist, Error> {
+        let shortlist: Option<Shortlist> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("shortlist", shortlist_id)))
+            .await?
+            .take(0)?;
+        shortlist.ok_or(Error::NotFound)
+    }
+
+    /// The shortlist a share link points at, while it's still shared
+    pub async fn by_share_token(token: &str) -> Result<Shortlist, Error> {
+        let token_hash = share_link::lookup_hash(token).ok_or(Error::NotFound)?;
+        let shortlist: Option<Shortlist> = DB
+            .query("SELECT * FROM shortlist WHERE share_token_hash = $token_hash LIMIT 1")
+            .bind(("token_hash", token_hash))
+            .await?
+            .take(0)?;
+        shortlist.filter(|s| s.access().is_some()).ok_or(Error::NotFound)
+    }
+
+    /// A job's shortlists grouped by role, newest first within each
+    pub async fn for_job(job: &RecordId) -> Result<Vec<ShortlistSummary>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT id, role_title, name, created_at, {SHARED_FIELD},
+                    count((SELECT id FROM shortlist_entry WHERE shortlist = $parent.id)) AS people,
+                    count((SELECT id FROM shortlist_entry WHERE shortlist = $parent.id AND selected = true)) AS selected
+                 FROM shortlist WHERE job = $job ORDER BY role_title ASC, created_at DESC"
+            ))
+            .bind(("job", job.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn rename(shortlist: &RecordId, name: &str) -> Result<(), Error> {
+        let name = clean_name(name)?;
+        DB.query("UPDATE $id SET name = $name")
+            .bind(("id", shortlist.clone()))
+            .bind(("name", name))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to rename shortlist: {}", e)))?;
+        Ok(())
+    }
+
+    /// Everyone on a shortlist, in order
+    pub async fn entries(shortlist: &Shortlist) -> Result<Vec<ShortlistEntryListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM shortlist_entry
+                 WHERE shortlist = $shortlist ORDER BY position ASC, created_at ASC"
+            ))
+            .bind(("shortlist", shortlist.id.clone()))
+            .bind(("job", shortlist.job.clone()))
+            .bind(("role_title", shortlist.role_title.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn get_entry(entry_id: &str) -> Result<ShortlistEntry, Error> {
+        let entry: Option<ShortlistEntry> = DB
+            .query("SELECT * FROM ONLY $id")
+            .bind(("id", RecordId::new("shortlist_entry", entry_id)))
+            .await?
+            .take(0)?;
+        entry.ok_or(Error::NotFound)
+    }
+
+    /// Add people to the end of a shortlist. Returns who was newly added;
+    /// anyone already on it is skipped.
+    pub async fn add(shortlist: &RecordId, people: &[RecordId], added_by: &RecordId) -> Result<Vec<RecordId>, Error> {
+        let existing: Vec<RecordId> = DB
+            .query("SELECT VALUE person FROM shortlist_entry WHERE shortlist = $shortlist")
+            .bind(("shortlist", shortlist.clone()))
+            .await?
+            .take(0)?;
+        let mut new_people: Vec<RecordId> = Vec::new();
+        for person in people {
+            if !existing.contains(person) && !new_people.contains(person) {
+                new_people.push(person.clone());
+            }
+        }
+        if existing.len() + new_people.len() > MAX_ENTRIES {
+            return Err(Error::Validation(format!(
+                "A shortlist can hold up to {} people",
+                MAX_ENTRIES
+            )));
+        }
+        if new_people.is_empty() {
+            return Ok(new_people);
+        }
+
+        let last: Option<i64> = DB
+            .query("SELECT VALUE position FROM shortlist_entry WHERE shortlist = $shortlist ORDER BY position DESC LIMIT 1")
+            .bind(("shortlist", shortlist.clone()))
+            .await?
+            .take(0)?;
+        let next = last.map_or(0, |p| p + 1);
+        for (offset, person) in new_people.iter().enumerate() {
+            DB.query(
+                "CREATE shortlist_entry SET shortlist = $shortlist, person = $person, position = $position,
+                    selected = false, added_by = $added_by, created_at = time::now()",
+            )
+            .bind(("shortlist", shortlist.clone()))
+            .bind(("person", person.clone()))
+            .bind(("position", next + offset as i64))
+            .bind(("added_by", added_by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to add to shortlist: {}", e)))?
+            .check()?;
+        }
+        DB.query("UPDATE $id SET updated_at = time::now()")
+            .bind(("id", shortlist.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update shortlist: {}", e)))?;
+        Ok(new_people)
+    }
+
+    pub async fn set_note(entry: &RecordId, note: &str) -> Result<(), Error> {
+        let note = clean_note(note)?;
+        DB.query("UPDATE $id SET note = $note")
+            .bind(("id", entry.clone()))
+            .bind(("note", note))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to save note: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn set_selected(entry: &RecordId, selected: bool) -> Result<(), Error> {
+        DB.query("UPDATE $id SET selected = $selected")
+            .bind(("id", entry.clone()))
+            .bind(("selected", selected))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update shortlist: {}", e)))?;
+        Ok(())
+    }
+
+    /// Move an entry one place earlier or later on its shortlist
+    pub async fn shift(entry: &ShortlistEntry, earlier: bool) -> Result<(), Error> {
+        let order: Vec<RecordId> = DB
+            .query("SELECT VALUE id FROM shortlist_entry WHERE shortlist = $shortlist ORDER BY position ASC, created_at ASC")
+            .bind(("shortlist", entry.shortlist.clone()))
+            .await?
+            .take(0)?;
+        let Some(order) = shifted(&order, &entry.id, earlier) else {
+            return Ok(());
+        };
+        // Number the whole list afresh so ties between older rows don't stick
+        for (position, id) in order.into_iter().enumerate() {
+            DB.query("UPDATE $id SET position = $position")
+                .bind(("id", id))
+                .bind(("position", position as i64))
+                .await
+                .map_err(|e| Error::Database(format!("Failed to reorder shortlist: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    pub async fn remove(entry: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE $id")
+            .bind(("id", entry.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to remove from shortlist: {}", e)))?;
+        Ok(())
+    }
+
+    /// Mark the selected people who haven't had an offer yet as offered, and
+    /// return them
+    pub async fn mark_offered(shortlist: &RecordId) -> Result<Vec<ShortlistEntry>, Error> {
+        Ok(DB
+            .query(
+                "UPDATE shortlist_entry SET offered_at = time::now()
+                 WHERE shortlist = $shortlist AND selected = true AND offered_at = NONE RETURN AFTER",
+            )
+            .bind(("shortlist", shortlist.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to make offers: {}", e)))?
+            .take(0)?)
+    }
+
+    pub async fn delete(shortlist: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE shortlist_entry WHERE shortlist = $shortlist; DELETE $shortlist;")
+            .bind(("shortlist", shortlist.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete shortlist: {}", e)))?;
+        Ok(())
+    }
+
+    /// Delete a job's shortlists
+    pub async fn delete_for_job(job: &RecordId) -> Result<(), Error> {
+        DB.query(
+            "LET $shortlists = SELECT VALUE id FROM shortlist WHERE job = $job;
+             DELETE shortlist_entry WHERE shortlist IN $shortlists;
+             DELETE shortlist WHERE job = $job;",
+        )
+        .bind(("job", job.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to delete shortlists: {}", e)))?;
+        Ok(())
+    }
+
+    /// Take a person off every shortlist, for account deletion
+    pub async fn forget(person: &RecordId) -> Result<(), Error> {
+        DB.query("DELETE shortlist_entry WHERE person = $person")
+            .bind(("person", person.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete shortlist entries: {}", e)))?;
+        Ok(())
+    }
+}
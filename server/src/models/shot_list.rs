@@ -0,0 +1,544 @@
+//! Shot lists: shoot days, scenes and the shots planned for them
+//!
+//! Scenes are scheduled onto shoot days. Each scene's shots are grouped into
+//! camera setups (all the shots taken without relighting or moving the
+//! camera), and marked as captured on set. Captured counts per day feed the
+//! daily progress report.
+
+use crate::{db::DB, error::Error, record_id_ext::RecordIdExt};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+/// Shot sizes, as (value, label)
+pub const SHOT_SIZES: &[(&str, &str)] = &[
+    ("ews", "Extreme wide"),
+    ("ws", "Wide"),
+    ("mws", "Medium wide"),
+    ("ms", "Medium"),
+    ("mcu", "Medium close-up"),
+    ("cu", "Close-up"),
+    ("ecu", "Extreme close-up"),
+    ("ots", "Over the shoulder"),
+    ("insert", "Insert"),
+];
+
+/// Camera angles, as (value, label)
+pub const SHOT_ANGLES: &[(&str, &str)] = &[
+    ("eye_level", "Eye level"),
+    ("high", "High angle"),
+    ("low", "Low angle"),
+    ("overhead", "Overhead"),
+    ("dutch", "Dutch"),
+    ("pov", "POV"),
+];
+
+/// Camera movements, as (value, label)
+pub const SHOT_MOVEMENTS: &[(&str, &str)] = &[
+    ("static", "Static"),
+    ("pan", "Pan"),
+    ("tilt", "Tilt"),
+    ("dolly", "Dolly"),
+    ("tracking", "Tracking"),
+    ("handheld", "Handheld"),
+    ("steadicam", "Steadicam"),
+    ("crane", "Crane / jib"),
+    ("drone", "Drone"),
+    ("zoom", "Zoom"),
+];
+
+/// Label for a vocabulary value, falling back to the value itself
+pub fn label(options: &[(&str, &str)], value: &str) -> String {
+    options
+        .iter()
+        .find(|(v, _)| *v == value)
+        .map(|(_, l)| l.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ShootDay {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub day_number: i64,
+    /// "YYYY-MM-DD"
+    pub date: String,
+    pub notes: Option<String>,
+    /// The location booked for the day
+    #[serde(default)]
+    #[surreal(default)]
+    pub location: Option<RecordId>,
+    #[serde(default)]
+    #[surreal(default)]
+    pub call_sheet_sent_at: Option<DateTime<Utc>>,
+    /// "HH:MM"
+    #[serde(default)]
+    #[surreal(default)]
+    pub call_time: Option<String>,
+    /// "HH:MM", before `call_time` when the day runs past midnight
+    #[serde(default)]
+    #[surreal(default)]
+    pub wrap_time: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Scene {
+    pub id: RecordId,
+    pub production: RecordId,
+    /// Script scene number, e.g. "12" or "12A"
+    pub number: String,
+    /// Slugline, e.g. "INT. KITCHEN - NIGHT"
+    pub heading: String,
+    pub description: Option<String>,
+    pub shoot_day: Option<RecordId>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Shot {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub scene: RecordId,
+    /// Camera setup the shot belongs to, numbered within the scene
+    pub setup: i64,
+    /// Shot number within the scene
+    pub number: i64,
+    pub description: String,
+    pub size: String,
+    pub angle: String,
+    pub movement: String,
+    pub gear: Vec<String>,
+    pub captured: bool,
+    pub captured_at: Option<DateTime<Utc>>,
+    pub captured_by: Option<RecordId>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Shots sharing one camera setup
+#[derive(Debug, Clone)]
+pub struct Setup {
+    pub number: i64,
+    pub shots: Vec<Shot>,
+}
+
+/// A scene with its shots grouped by setup
+#[derive(Debug, Clone)]
+pub struct SceneShots {
+    pub scene: Scene,
+    pub setups: Vec<Setup>,
+}
+
+impl SceneShots {
+    pub fn shot_count(&self) -> usize {
+        self.setups.iter().map(|s| s.shots.len()).sum()
+    }
+
+    pub fn captured_count(&self) -> usize {
+        self.setups
+            .iter()
+            .flat_map(|s| &s.shots)
+            .filter(|shot| shot.captured)
+            .count()
+    }
+
+    /// A scene is complete once it has shots and all of them are captured
+    pub fn is_complete(&self) -> bool {
+        let total = self.shot_count();
+        total > 0 && self.captured_count() == total
+    }
+}
+
+/// Shot list progress for a shoot day
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DayProgress {
+    pub shots_total: usize,
+    pub shots_captured: usize,
+    pub scenes_total: usize,
+    pub scenes_completed: usize,
+}
+
+impl DayProgress {
+    pub fn from_scenes(scenes: &[SceneShots]) -> Self {
+        Self {
+            shots_total: scenes.iter().map(SceneShots::shot_count).sum(),
+            shots_captured: scenes.iter().map(SceneShots::captured_count).sum(),
+            scenes_total: scenes.len(),
+            scenes_completed: scenes.iter().filter(|s| s.is_complete()).count(),
+        }
+    }
+
+    /// Captured shots as a whole percentage
+    pub fn percent(&self) -> u32 {
+        if self.shots_total == 0 {
+            return 0;
+        }
+        (self.shots_captured * 100 / self.shots_total) as u32
+    }
+}
+
+/// Group a scene's shots by setup, both in ascending order
+pub fn group_setups(mut shots: Vec<Shot>) -> Vec<Setup> {
+    shots.sort_by_key(|shot| (shot.setup, shot.number));
+    let mut setups: Vec<Setup> = Vec::new();
+    for shot in shots {
+        match setups.last_mut() {
+            Some(setup) if setup.number == shot.setup => setup.shots.push(shot),
+            _ => setups.push(Setup {
+                number: shot.setup,
+                shots: vec![shot],
+            }),
+        }
+    }
+    setups
+}
+
+/// Parse a comma-separated gear list, dropping blanks and duplicates
+pub fn parse_gear(input: &str) -> Vec<String> {
+    let mut gear: Vec<String> = Vec::new();
+    for item in input.split([',', '\n']).map(str::trim).filter(|g| !g.is_empty()) {
+        if !gear.iter().any(|g| g.eq_ignore_ascii_case(item)) {
+            gear.push(item.to_string());
+        }
+    }
+    gear
+}
+
+/// CSV export of a day's shot list
+pub fn shot_list_csv(day: &ShootDay, scenes: &[SceneShots]) -> String {
+    let mut out = crate::csv::row(&[
+        "Day", "Date", "Scene", "Heading", "Setup", "Shot", "Size", "Angle", "Movement",
+        "Description", "Gear", "Captured",
+    ]);
+    for scene in scenes {
+        for setup in &scene.setups {
+            for shot in &setup.shots {
+                out.push_str(&crate::csv::row(&[
+                    day.day_number.to_string(),
+                    day.date.clone(),
+                    scene.scene.number.clone(),
+                    scene.scene.heading.clone(),
+                    setup.number.to_string(),
+                    shot.number.to_string(),
+                    label(SHOT_SIZES, &shot.size),
+                    label(SHOT_ANGLES, &shot.angle),
+                    label(SHOT_MOVEMENTS, &shot.movement),
+                    shot.description.clone(),
+                    shot.gear.join("; "),
+                    if shot.captured { "yes" } else { "no" }.to_string(),
+                ]));
+            }
+        }
+    }
+    out
+}
+
+/// Data for a new shot
+#[derive(Debug)]
+pub struct CreateShotData {
+    /// Setup to add the shot to; `None` starts a new setup
+    pub setup: Option<i64>,
+    pub description: String,
+    pub size: String,
+    pub angle: String,
+    pub movement: String,
+    pub gear: Vec<String>,
+}
+
+pub struct ShotListModel;
+
+impl ShotListModel {
+    // -- Shoot days --
+
+    /// Shoot days for a production, in date order
+    pub async fn days(production: &RecordId) -> Result<Vec<ShootDay>, Error> {
+        Ok(DB
+            .query("SELECT * FROM shoot_day WHERE production = $production ORDER BY date ASC, day_number ASC")
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn get_day(production: &RecordId, day_id: &str) -> Result<ShootDay, Error> {
+        let day: Option<ShootDay> = DB
+            .query("SELECT * FROM $id WHERE production = $production")
+            .bind(("id", RecordId::new("shoot_day", day_id)))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        day.ok_or(Error::NotFound)
+    }
+
+    /// Add a shoot day. Days are numbered in the order they're added.
+    pub async fn create_day(production: &RecordId, date: &str, notes: Option<String>) -> Result<ShootDay, Error> {
+        let date = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| Error::Validation("Enter the shoot date".to_string()))?
+            .format("%Y-%m-%d")
+            .to_string();
+
+        let day: Option<ShootDay> = DB
+            .query(
+                "CREATE shoot_day SET production = $production, date = $date, notes = $notes,
+                    day_number = (math::max((SELECT VALUE day_number FROM shoot_day WHERE production = $production)) ?? 0) + 1,
+                    created_at = time::now()",
+            )
+            .bind(("production", production.clone()))
+            .bind(("date", date))
+            .bind(("notes", notes.filter(|n| !n.trim().is_empty())))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to create shoot day: {}", e)))?
+            .take(0)?;
+        day.ok_or_else(|| Error::Internal("Failed to create shoot day".to_string()))
+    }
+
+    /// Remove a shoot day with its daily report and timecards. Its scenes go
+    /// back to unscheduled.
+    pub async fn delete_day(production: &RecordId, day_id: &str) -> Result<(), Error> {
+        let day = Self::get_day(production, day_id).await?;
+        DB.query("UPDATE scene SET shoot_day = NONE WHERE shoot_day = $day; DELETE daily_report WHERE shoot_day = $day; DELETE timecard WHERE shoot_day = $day; DELETE $day")
+            .bind(("day", day.id))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete shoot day: {}", e)))?;
+        Ok(())
+    }
+
+    /// Book a location for the day, or clear it
+    pub async fn set_day_location(day: &RecordId, location: Option<RecordId>) -> Result<(), Error> {
+        DB.query("UPDATE $day SET location = $location")
+            .bind(("day", day.clone()))
+            .bind(("location", location))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to set shoot day location: {}", e)))?;
+        Ok(())
+    }
+
+    /// Set the day's call and wrap times ("HH:MM"), or clear them
+    pub async fn set_day_hours(
+        day: &RecordId,
+        call_time: Option<String>,
+        wrap_time: Option<String>,
+    ) -> Result<(), Error> {
+        DB.query("UPDATE $day SET call_time = $call_time, wrap_time = $wrap_time")
+            .bind(("day", day.clone()))
+            .bind(("call_time", call_time))
+            .bind(("wrap_time", wrap_time))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to set shoot day hours: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn mark_call_sheet_sent(day: &RecordId) -> Result<(), Error> {
+        DB.query("UPDATE $day SET call_sheet_sent_at = time::now()")
+            .bind(("day", day.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to mark call sheet sent: {}", e)))?;
+        Ok(())
+    }
+
+    // -- Scenes --
+
+    async fn get_scene(production: &RecordId, scene_id: &str) -> Result<Scene, Error> {
+        let scene: Option<Scene> = DB
+            .query("SELECT * FROM $id WHERE production = $production")
+            .bind(("id", RecordId::new("scene", scene_id)))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        scene.ok_or(Error::NotFound)
+    }
+
+    pub async fn create_scene(
+        production: &RecordId,
+        number: &str,
+        heading: &str,
+        description: Option<String>,
+        day_id: Option<&str>,
+    ) -> Result<Scene, Error> {
+        let number = number.trim().to_uppercase();
+        if number.is_empty() {
+            return Err(Error::Validation("Enter the scene number".to_string()));
+        }
+        let shoot_day = match day_id.filter(|d| !d.is_empty()) {
+            Some(day_id) => Some(Self::get_day(production, day_id).await?.id),
+            None => None,
+        };
+
+        let scene: Option<Scene> = DB
+            .query(
+                "CREATE scene SET production = $production, number = $number, heading = $heading,
+                    description = $description, shoot_day = $shoot_day, created_at = time::now()",
+            )
+            .bind(("production", production.clone()))
+            .bind(("number", number))
+            .bind(("heading", heading.trim().to_string()))
+            .bind(("description", description.filter(|d| !d.trim().is_empty())))
+            .bind(("shoot_day", shoot_day))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to create scene: {}", e)))?
+            .take(0)?;
+        scene.ok_or_else(|| Error::Internal("Failed to create scene".to_string()))
+    }
+
+    /// Move a scene to a shoot day, or back to unscheduled with `None`
+    pub async fn schedule_scene(production: &RecordId, scene_id: &str, day_id: Option<&str>) -> Result<(), Error> {
+        let scene = Self::get_scene(production, scene_id).await?;
+        let shoot_day = match day_id.filter(|d| !d.is_empty()) {
+            Some(day_id) => Some(Self::get_day(production, day_id).await?.id),
+            None => None,
+        };
+        DB.query("UPDATE $scene SET shoot_day = $shoot_day")
+            .bind(("scene", scene.id))
+            .bind(("shoot_day", shoot_day))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to schedule scene: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn delete_scene(production: &RecordId, scene_id: &str) -> Result<(), Error> {
+        let scene = Self::get_scene(production, scene_id).await?;
+        DB.query("DELETE shot WHERE scene = $scene; DELETE $scene")
+            .bind(("scene", scene.id))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete scene: {}", e)))?;
+        Ok(())
+    }
+
+    /// Scenes with their shots, ordered by scene number. `day` limits them to
+    /// one shoot day.
+    pub async fn scenes(production: &RecordId, day: Option<&RecordId>) -> Result<Vec<SceneShots>, Error> {
+        let filter = if day.is_some() { "AND shoot_day = $day" } else { "" };
+        let mut response = DB
+            .query(format!(
+                "SELECT * FROM scene WHERE production = $production {filter};
+                 SELECT * FROM shot WHERE production = $production
+                    AND scene IN (SELECT VALUE id FROM scene WHERE production = $production {filter})"
+            ))
+            .bind(("production", production.clone()))
+            .bind(("day", day.cloned()))
+            .await?;
+        let mut scenes: Vec<Scene> = response.take(0)?;
+        let shots: Vec<Shot> = response.take(1)?;
+
+        scenes.sort_by(|a, b| scene_order(&a.number).cmp(&scene_order(&b.number)));
+        debug!("Loaded {} scenes and {} shots for {}", scenes.len(), shots.len(), production.display());
+
+        Ok(scenes
+            .into_iter()
+            .map(|scene| {
+                let scene_shots = shots.iter().filter(|s| s.scene == scene.id).cloned().collect();
+                SceneShots {
+                    setups: group_setups(scene_shots),
+                    scene,
+                }
+            })
+            .collect())
+    }
+
+    /// Shot list progress for one shoot day
+    pub async fn day_progress(production: &RecordId, day: &RecordId) -> Result<DayProgress, Error> {
+        Ok(DayProgress::from_scenes(&Self::scenes(production, Some(day)).await?))
+    }
+
+    // -- Shots --
+
+    async fn get_shot(production: &RecordId, shot_id: &str) -> Result<Shot, Error> {
+        let shot: Option<Shot> = DB
+            .query("SELECT * FROM $id WHERE production = $production")
+            .bind(("id", RecordId::new("shot", shot_id)))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        shot.ok_or(Error::NotFound)
+    }
+
+    pub async fn create_shot(production: &RecordId, scene_id: &str, data: CreateShotData) -> Result<Shot, Error> {
+        let scene = Self::get_scene(production, scene_id).await?;
+        for (value, options, name) in [
+            (&data.size, SHOT_SIZES, "shot size"),
+            (&data.angle, SHOT_ANGLES, "angle"),
+            (&data.movement, SHOT_MOVEMENTS, "movement"),
+        ] {
+            if !options.iter().any(|(v, _)| v == value) {
+                return Err(Error::Validation(format!("Choose a {}", name)));
+            }
+        }
+
+        #[derive(Debug, Deserialize, SurrealValue)]
+        struct Numbers {
+            max_setup: Option<i64>,
+            max_number: Option<i64>,
+        }
+        let numbers: Option<Numbers> = DB
+            .query(
+                "RETURN {
+                    max_setup: math::max((SELECT VALUE setup FROM shot WHERE scene = $scene)),
+                    max_number: math::max((SELECT VALUE number FROM shot WHERE scene = $scene))
+                }",
+            )
+            .bind(("scene", scene.id.clone()))
+            .await?
+            .take(0)?;
+        let (max_setup, max_number) = numbers
+            .map(|n| (n.max_setup.unwrap_or(0), n.max_number.unwrap_or(0)))
+            .unwrap_or((0, 0));
+        let setup = match data.setup {
+            Some(setup) if (1..=max_setup).contains(&setup) => setup,
+            _ => max_setup + 1,
+        };
+
+        let shot: Option<Shot> = DB
+            .query(
+                "CREATE shot SET production = $production, scene = $scene, setup = $setup, number = $number,
+                    description = $description, size = $size, angle = $angle, movement = $movement,
+                    gear = $gear, captured = false, created_at = time::now()",
+            )
+            .bind(("production", production.clone()))
+            .bind(("scene", scene.id))
+            .bind(("setup", setup))
+            .bind(("number", max_number + 1))
+            .bind(("description", data.description.trim().to_string()))
+            .bind(("size", data.size))
+            .bind(("angle", data.angle))
+            .bind(("movement", data.movement))
+            .bind(("gear", data.gear))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to create shot: {}", e)))?
+            .take(0)?;
+        shot.ok_or_else(|| Error::Internal("Failed to create shot".to_string()))
+    }
+
+    pub async fn delete_shot(production: &RecordId, shot_id: &str) -> Result<(), Error> {
+        let shot = Self::get_shot(production, shot_id).await?;
+        DB.query("DELETE $shot")
+            .bind(("shot", shot.id))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete shot: {}", e)))?;
+        Ok(())
+    }
+
+    /// Flip a shot between captured and not captured. Returns the new state.
+    pub async fn toggle_captured(production: &RecordId, shot_id: &str, by: &RecordId) -> Result<bool, Error> {
+        let shot = Self::get_shot(production, shot_id).await?;
+        let captured = !shot.captured;
+        let query = if captured {
+            "UPDATE $shot SET captured = true, captured_at = time::now(), captured_by = $by"
+        } else {
+            "UPDATE $shot SET captured = false, captured_at = NONE, captured_by = NONE"
+        };
+        DB.query(query)
+            .bind(("shot", shot.id))
+            .bind(("by", by.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update shot: {}", e)))?;
+        Ok(captured)
+    }
+}
+
+/// Sort key for scene numbers: numeric part first, then any letter suffix
+/// ("2" < "10" < "10A")
+pub fn scene_order(number: &str) -> (u32, String) {
+    let digits: String = number.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let rest = number[digits.len()..].to_string();
+    (digits.parse().unwrap_or(u32::MAX), rest)
+}
@@ -104,13 +104,11 @@ impl System {
     /// # Returns
     /// A `Result` containing the `SystemInfo`
     pub async fn get_system_info() -> Result<SystemInfo> {
-        use std::env;
-
         debug!("Getting system information");
 
         let database_status = Self::check_database_health().await?;
-        let namespace = env::var("DB_NAMESPACE").unwrap_or_else(|_| "unknown".to_string());
-        let database = env::var("DB_NAME").unwrap_or_else(|_| "unknown".to_string());
+        let namespace = crate::settings::var("DB_NAMESPACE").unwrap_or_else(|_| "unknown".to_string());
+        let database = crate::settings::var("DB_NAME").unwrap_or_else(|_| "unknown".to_string());
         let version = env!("CARGO_PKG_VERSION").to_string();
 
         Ok(SystemInfo {
@@ -0,0 +1,319 @@
+//! Agency submissions
+//!
+//! A member of an agency submits a client on its roster to a role on a job
+//! posting. The client sees every submission made for them and has the final
+//! say: approving one turns it into an ordinary application from them,
+//! credited to the agency, and declining drops it before the poster ever sees
+//! it. The agency's roster page follows each submission through to the
+//! poster's decision.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{error, info};
+
+use crate::{
+    db::DB,
+    error::{Error, Result},
+    models::{job::JobModel, notification::NotificationModel, representation::RepresentationModel},
+    record_id_ext::RecordIdExt,
+};
+
+pub const MAX_NOTE_CHARS: usize = 2000;
+
+/// Where a submission stands, as shown to the agency and the client
+pub fn status_label(status: &str, application_status: Option<&str>) -> &'static str {
+    match status {
+        "pending" => "Awaiting client approval",
+        "declined" => "Declined by client",
+        _ => match application_status {
+            Some("reviewed") => "Reviewed",
+            Some("shortlisted") => "Shortlisted",
+            Some("rejected") => "Not selected",
+            Some("withdrawn") => "Withdrawn",
+            _ => "Submitted",
+        },
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct Submission {
+    pub id: RecordId,
+    pub job: RecordId,
+    pub job_title: String,
+    pub role_title: String,
+    pub note: Option<String>,
+    /// "pending" until the client decides, then "approved" or "declined"
+    pub status: String,
+    /// The poster's decision on the application, once approved
+    pub application_status: Option<String>,
+    pub talent_username: String,
+    pub talent_name: Option<String>,
+    pub agency_name: String,
+    pub agency_slug: String,
+    pub agent_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Submission {
+    pub fn key(&self) -> String {
+        self.id.key_string()
+    }
+
+    pub fn job_key(&self) -> String {
+        self.job.key_string()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == "pending"
+    }
+
+    pub fn talent_display(&self) -> &str {
+        self.talent_name
+            .as_deref()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or(&self.talent_username)
+    }
+
+    pub fn status_label(&self) -> &'static str {
+        status_label(&self.status, self.application_status.as_deref())
+    }
+}
+
+const SUBMISSION_FIELDS: &str = "id, job, job.title AS job_title, role_title, note, status,
+    application.status AS application_status, talent.username AS talent_username,
+    talent.name AS talent_name, agency.name AS agency_name, agency.slug AS agency_slug,
+    submitted_by.name ?? submitted_by.username AS agent_name, created_at";
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct DecisionRow {
+    job: RecordId,
+    job_title: String,
+    role_title: String,
+    note: Option<String>,
+    agency: RecordId,
+    agency_slug: String,
+    submitted_by: Option<RecordId>,
+    talent_name: String,
+}
+
+const DECISION_FIELDS: &str = "job, job.title AS job_title, role_title, note, agency,
+    agency.slug AS agency_slug, submitted_by, talent.name ?? talent.username AS talent_name";
+
+pub struct TalentSubmissionModel;
+
+impl TalentSubmissionModel {
+    /// Submit a client for a role and ask them to approve it. `agent` must
+    /// work for the organization confirmed to represent the client.
+    pub async fn submit(
+        agent: &RecordId,
+        talent_key: &str,
+        job_key: &str,
+        role_title: &str,
+        note: Option<&str>,
+    ) -> Result<()> {
+        let talent = RecordId::new("person", talent_key);
+        if !RepresentationModel::represents(agent, &talent).await? {
+            return Err(Error::Forbidden);
+        }
+        let agency = RepresentationModel::get(&talent)
+            .await?
+            .ok_or(Error::Forbidden)?;
+
+        let note = note.map(str::trim).filter(|n| !n.is_empty());
+        if note.is_some_and(|n| n.chars().count() > MAX_NOTE_CHARS) {
+            return Err(Error::Validation(format!(
+                "Keep the note under {} characters",
+                MAX_NOTE_CHARS
+            )));
+        }
+
+        let job = RecordId::new("job_posting", job_key);
+        let mut result = DB
+            .query(
+                "SELECT VALUE title FROM $job WHERE status = 'open' AND applications_enabled = true
+                    AND expires_at > time::now() AND $role_title INSIDE roles.title",
+            )
+            .query(
+                "SELECT VALUE id FROM talent_submission WHERE talent = $talent AND job = $job
+                    AND role_title = $role_title AND status IN ['pending', 'approved']",
+            )
+            .query(
+                "SELECT VALUE id FROM application WHERE in = $talent AND out = $job
+                    AND role_title = $role_title AND status != 'withdrawn'",
+            )
+            .bind(("job", job.clone()))
+            .bind(("talent", talent.clone()))
+            .bind(("role_title", role_title.to_string()))
+            .await?;
+        let titles: Vec<String> = result.take(0)?;
+        let submitted: Vec<RecordId> = result.take(1)?;
+        let applied: Vec<RecordId> = result.take(2)?;
+        let job_title = titles
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Validation("That role isn't taking applications".to_string()))?;
+        if !submitted.is_empty() || !applied.is_empty() {
+            return Err(Error::Conflict(
+                "They've already been put forward for this role".to_string(),
+            ));
+        }
+
+        let created: Vec<RecordId> = DB
+            .query(
+                "CREATE talent_submission SET agency = $agency, talent = $talent, job = $job,
+                    role_title = $role_title, note = $note, submitted_by = $agent
+                RETURN VALUE id",
+            )
+            .bind(("agency", agency.organization.clone()))
+            .bind(("talent", talent.clone()))
+            .bind(("job", job))
+            .bind(("role_title", role_title.to_string()))
+            .bind(("note", note.map(str::to_string)))
+            .bind(("agent", agent.clone()))
+            .await?
+            .take(0)?;
+        let id = created
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Internal("Submission was not created".to_string()))?;
+
+        if let Err(e) = NotificationModel::new()
+            .create(
+                &talent.to_raw_string(),
+                "talent_submission",
+                "Approve a submission",
+                &format!(
+                    "{} wants to submit you for {} on \"{}\"",
+                    agency.org_name, role_title, job_title
+                ),
+                Some("/account/representation#submissions"),
+                Some(&id.to_raw_string()),
+            )
+            .await
+        {
+            error!(
+                "Failed to notify {} of a submission: {}",
+                talent.display(),
+                e
+            );
+        }
+        info!(
+            "{} submitted {} for '{}' on {}",
+            agent.display(),
+            talent.display(),
+            role_title,
+            job_key
+        );
+        Ok(())
+    }
+
+    /// Every submission made for a person, newest first
+    pub async fn for_talent(person: &RecordId) -> Result<Vec<Submission>> {
+        Ok(DB
+            .query(format!(
+                "SELECT {SUBMISSION_FIELDS} FROM talent_submission
+                WHERE talent = $person ORDER BY created_at DESC"
+            ))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// An agency's submissions across its roster, newest first
+    pub async fn for_agency(org: &RecordId) -> Result<Vec<Submission>> {
+        Ok(DB
+            .query(format!(
+                "SELECT {SUBMISSION_FIELDS} FROM talent_submission
+                WHERE agency = $org ORDER BY created_at DESC"
+            ))
+            .bind(("org", org.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// The client approves: the submission becomes their application,
+    /// credited to the agency. Returns the job and role applied to.
+    pub async fn approve(person: &RecordId, key: &str) -> Result<(RecordId, String)> {
+        let id = RecordId::new("talent_submission", key);
+        let row = Self::pending(person, &id).await?;
+
+        JobModel::apply(
+            &person.to_raw_string(),
+            &row.job.to_raw_string(),
+            &row.role_title,
+            row.note.clone(),
+        )
+        .await?;
+
+        DB.query(
+            "LET $application = (SELECT VALUE id FROM application
+                WHERE in = $person AND out = $job AND role_title = $role_title
+                    AND status != 'withdrawn'
+                ORDER BY applied_at DESC LIMIT 1)[0];
+            UPDATE $application SET submitted_by = $agency;
+            UPDATE $id SET status = 'approved', application = $application,
+                decided_at = time::now();",
+        )
+        .bind(("person", person.clone()))
+        .bind(("job", row.job.clone()))
+        .bind(("role_title", row.role_title.clone()))
+        .bind(("agency", row.agency.clone()))
+        .bind(("id", id))
+        .await?
+        .check()?;
+
+        Self::notify_agent(&row, "approved").await;
+        Ok((row.job, row.role_title))
+    }
+
+    /// The client turns a submission down; the poster never sees it
+    pub async fn decline(person: &RecordId, key: &str) -> Result<()> {
+        let id = RecordId::new("talent_submission", key);
+        let row = Self::pending(person, &id).await?;
+        DB.query("UPDATE $id SET status = 'declined', decided_at = time::now()")
+            .bind(("id", id))
+            .await?
+            .check()?;
+        Self::notify_agent(&row, "declined").await;
+        Ok(())
+    }
+
+    async fn pending(person: &RecordId, id: &RecordId) -> Result<DecisionRow> {
+        let rows: Vec<DecisionRow> = DB
+            .query(format!(
+                "SELECT {DECISION_FIELDS} FROM $id WHERE talent = $person AND status = 'pending'"
+            ))
+            .bind(("id", id.clone()))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        rows.into_iter().next().ok_or(Error::NotFound)
+    }
+
+    async fn notify_agent(row: &DecisionRow, decision: &str) {
+        let Some(agent) = &row.submitted_by else {
+            return;
+        };
+        if let Err(e) = NotificationModel::new()
+            .create(
+                &agent.to_raw_string(),
+                "talent_submission",
+                &format!("Submission {}", decision),
+                &format!(
+                    "{} {} your submission for {} on \"{}\"",
+                    row.talent_name, decision, row.role_title, row.job_title
+                ),
+                Some(&format!("/orgs/{}/roster", row.agency_slug)),
+                None,
+            )
+            .await
+        {
+            error!(
+                "Failed to notify {} of a submission decision: {}",
+                agent.display(),
+                e
+            );
+        }
+    }
+}
@@ -0,0 +1,635 @@
+//! On-set timecards
+//!
+//! Crew clock in and out for each shoot day. Clocking out (or entering the
+//! times by hand) submits the timecard with its hours split into straight
+//! time, overtime and double time by the overtime rules of the person's deal
+//! on the production. Producers approve or reject submitted timecards, and
+//! approved hours export to CSV for payroll. Crew who have linked a WhatsApp
+//! number can also clock in and out by messaging the bot.
+
+use crate::{
+    csv,
+    db::DB,
+    error::Error,
+    models::{
+        daily_report::{hours_between, parse_time},
+        pagination::{self, Cursor, KeyKind, Page, SortKey},
+        shot_list::ShootDay,
+    },
+    record_id_ext::RecordIdExt,
+};
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::debug;
+
+/// Deal rate types, as (value, label)
+pub const RATE_TYPES: &[(&str, &str)] = &[("hourly", "Hourly"), ("daily", "Daily")];
+
+/// Longest unpaid meal break that can be recorded, in minutes
+pub const MAX_MEAL_MINUTES: i64 = 180;
+
+/// Whether production roles include a producer. Producers approve timecards
+/// without edit rights on the production itself.
+pub fn is_producer(roles: &[String]) -> bool {
+    roles.iter().any(|role| {
+        let role = role.to_lowercase();
+        role.contains("producer") || role.contains("production manager")
+    })
+}
+
+/// When overtime starts and how it's paid
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OvertimeRules {
+    /// Hours paid at the straight rate
+    pub straight_hours: f64,
+    /// Hours after which double time applies
+    pub double_time_after: f64,
+    pub overtime_multiplier: f64,
+    pub double_time_multiplier: f64,
+}
+
+impl Default for OvertimeRules {
+    /// A 10-hour day, time and a half to 12 hours, double time after
+    fn default() -> Self {
+        Self {
+            straight_hours: 10.0,
+            double_time_after: 12.0,
+            overtime_multiplier: 1.5,
+            double_time_multiplier: 2.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct HoursBreakdown {
+    pub worked: f64,
+    pub regular: f64,
+    pub overtime: f64,
+    pub double_time: f64,
+}
+
+/// Split a shift into straight, overtime and double-time hours. Times are
+/// "HH:MM"; a clock-out earlier than the clock-in is on the next day. The
+/// unpaid meal break is deducted first.
+pub fn compute_hours(time_in: &str, time_out: &str, meal_minutes: i64, rules: &OvertimeRules) -> Option<HoursBreakdown> {
+    let worked = (hours_between(time_in, time_out)? - meal_minutes.max(0) as f64 / 60.0).max(0.0);
+    let straight = rules.straight_hours.max(0.0);
+    let double_after = rules.double_time_after.max(straight);
+
+    let regular = worked.min(straight);
+    let overtime = (worked.min(double_after) - straight).max(0.0);
+    let double_time = (worked - double_after).max(0.0);
+    Some(HoursBreakdown {
+        worked: round_hours(worked),
+        regular: round_hours(regular),
+        overtime: round_hours(overtime),
+        double_time: round_hours(double_time),
+    })
+}
+
+/// Round to the nearest hundredth of an hour
+fn round_hours(hours: f64) -> f64 {
+    (hours * 100.0).round() / 100.0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct CrewDeal {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub person: RecordId,
+    /// "hourly" or "daily"
+    pub rate_type: String,
+    pub rate: Option<f64>,
+    pub straight_hours: f64,
+    pub double_time_after: f64,
+    pub overtime_multiplier: f64,
+    pub double_time_multiplier: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CrewDeal {
+    pub fn rules(&self) -> OvertimeRules {
+        OvertimeRules {
+            straight_hours: self.straight_hours,
+            double_time_after: self.double_time_after,
+            overtime_multiplier: self.overtime_multiplier,
+            double_time_multiplier: self.double_time_multiplier,
+        }
+    }
+
+    /// Straight-time hourly rate. A daily rate covers the straight hours.
+    pub fn hourly_rate(&self) -> Option<f64> {
+        let rate = self.rate?;
+        match self.rate_type.as_str() {
+            "daily" if self.straight_hours > 0.0 => Some(rate / self.straight_hours),
+            "daily" => None,
+            _ => Some(rate),
+        }
+    }
+
+    /// Gross pay for a shift, when the deal has a rate
+    pub fn gross_pay(&self, hours: &HoursBreakdown) -> Option<f64> {
+        let hourly = self.hourly_rate()?;
+        let overtime = hours.overtime * hourly * self.overtime_multiplier
+            + hours.double_time * hourly * self.double_time_multiplier;
+        let straight = match self.rate_type.as_str() {
+            // The day rate is guaranteed however short the day
+            "daily" => self.rate.unwrap_or_default(),
+            _ => hours.regular * hourly,
+        };
+        Some(((straight + overtime) * 100.0).round() / 100.0)
+    }
+}
+
+/// Deal terms as entered by a production editor
+#[derive(Debug, Clone)]
+pub struct DealData {
+    pub rate_type: String,
+    pub rate: Option<f64>,
+    pub straight_hours: f64,
+    pub double_time_after: f64,
+    pub overtime_multiplier: f64,
+    pub double_time_multiplier: f64,
+}
+
+impl DealData {
+    pub fn validate(&self) -> Result<(), Error> {
+        if !RATE_TYPES.iter().any(|(v, _)| *v == self.rate_type) {
+            return Err(Error::Validation("Choose an hourly or daily rate".to_string()));
+        }
+        if self.rate.is_some_and(|r| !(0.0..=1_000_000.0).contains(&r)) {
+            return Err(Error::Validation("Enter a rate of zero or more".to_string()));
+        }
+        if !(1.0..=24.0).contains(&self.straight_hours) {
+            return Err(Error::Validation("Straight time must be between 1 and 24 hours".to_string()));
+        }
+        if self.double_time_after < self.straight_hours || self.double_time_after > 24.0 {
+            return Err(Error::Validation(
+                "Double time must start after straight time ends, within 24 hours".to_string(),
+            ));
+        }
+        if !(1.0..=5.0).contains(&self.overtime_multiplier) || !(1.0..=5.0).contains(&self.double_time_multiplier) {
+            return Err(Error::Validation("Overtime multipliers must be between 1 and 5".to_string()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Timecard {
+    pub id: RecordId,
+    pub production: RecordId,
+    pub shoot_day: RecordId,
+    pub person: RecordId,
+    /// "HH:MM"
+    pub time_in: String,
+    pub time_out: Option<String>,
+    pub meal_minutes: i64,
+    /// "open" (clocked in), "submitted", "approved" or "rejected"
+    pub status: String,
+    pub regular_hours: f64,
+    pub overtime_hours: f64,
+    pub double_time_hours: f64,
+    pub note: Option<String>,
+    pub reviewed_by: Option<RecordId>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A timecard with the names and dates needed to list or export it
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct TimecardListing {
+    pub id: RecordId,
+    pub person: RecordId,
+    pub person_name: String,
+    pub shoot_day: RecordId,
+    pub date: String,
+    pub day_number: i64,
+    pub time_in: String,
+    pub time_out: Option<String>,
+    pub meal_minutes: i64,
+    pub status: String,
+    pub regular_hours: f64,
+    pub overtime_hours: f64,
+    pub double_time_hours: f64,
+    pub note: Option<String>,
+}
+
+impl TimecardListing {
+    pub fn hours(&self) -> HoursBreakdown {
+        HoursBreakdown {
+            worked: round_hours(self.regular_hours + self.overtime_hours + self.double_time_hours),
+            regular: self.regular_hours,
+            overtime: self.overtime_hours,
+            double_time: self.double_time_hours,
+        }
+    }
+}
+
+/// Header row of the payroll export
+pub fn payroll_header() -> String {
+    csv::row(&[
+        "Date",
+        "Day",
+        "Name",
+        "In",
+        "Out",
+        "Meal (min)",
+        "Regular",
+        "Overtime",
+        "Double Time",
+        "Total Hours",
+        "Rate Type",
+        "Rate",
+        "Gross Pay",
+    ])
+}
+
+/// One shift in the payroll export. Pay columns are filled when the person's
+/// deal has a rate.
+pub fn payroll_row(timecard: &TimecardListing, deals: &[CrewDeal]) -> String {
+    let hours = timecard.hours();
+    let deal = deals.iter().find(|d| d.person == timecard.person);
+    let gross = deal.and_then(|d| d.gross_pay(&hours));
+    csv::row(&[
+        timecard.date.clone(),
+        timecard.day_number.to_string(),
+        timecard.person_name.clone(),
+        timecard.time_in.clone(),
+        timecard.time_out.clone().unwrap_or_default(),
+        timecard.meal_minutes.to_string(),
+        format!("{:.2}", hours.regular),
+        format!("{:.2}", hours.overtime),
+        format!("{:.2}", hours.double_time),
+        format!("{:.2}", hours.worked),
+        deal.map(|d| d.rate_type.clone()).unwrap_or_default(),
+        deal.and_then(|d| d.rate).map(|r| format!("{:.2}", r)).unwrap_or_default(),
+        gross.map(|g| format!("{:.2}", g)).unwrap_or_default(),
+    ])
+}
+
+/// Payroll export of approved timecards, one row per shift
+pub fn payroll_csv(timecards: &[TimecardListing], deals: &[CrewDeal]) -> String {
+    let mut out = payroll_header();
+    for timecard in timecards {
+        out.push_str(&payroll_row(timecard, deals));
+    }
+    out
+}
+
+const LISTING_FIELDS: &str = "id, person, person.name ?? person.username AS person_name, shoot_day,
+    shoot_day.date AS date, shoot_day.day_number AS day_number, time_in, time_out, meal_minutes,
+    status, regular_hours, overtime_hours, double_time_hours, note";
+
+/// Timecards fetched per query while exporting payroll
+const EXPORT_PAGE_SIZE: usize = 500;
+
+/// Approved timecards in payroll order: oldest shoot day first, then by name
+const APPROVED_ORDER: &[SortKey] = &[
+    SortKey::asc("date", KeyKind::Text).computed("shoot_day.date"),
+    SortKey::asc("person_name", KeyKind::Text).computed("(person.name ?? person.username)"),
+];
+
+impl TimecardListing {
+    fn approved_cursor(&self) -> Cursor {
+        Cursor::new(vec![self.date.clone(), self.person_name.clone()], &self.id)
+    }
+}
+
+pub struct TimecardModel;
+
+impl TimecardModel {
+    pub async fn get(production: &RecordId, timecard_id: &str) -> Result<Timecard, Error> {
+        let timecard: Option<Timecard> = DB
+            .query("SELECT * FROM $id WHERE production = $production")
+            .bind(("id", RecordId::new("timecard", timecard_id)))
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?;
+        timecard.ok_or(Error::NotFound)
+    }
+
+    pub async fn for_person_day(day: &RecordId, person: &RecordId) -> Result<Option<Timecard>, Error> {
+        Ok(DB
+            .query("SELECT * FROM timecard WHERE shoot_day = $day AND person = $person LIMIT 1")
+            .bind(("day", day.clone()))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// A person's timecards on a production, by shoot day
+    pub async fn for_person(production: &RecordId, person: &RecordId) -> Result<Vec<Timecard>, Error> {
+        Ok(DB
+            .query("SELECT * FROM timecard WHERE production = $production AND person = $person")
+            .bind(("production", production.clone()))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Every timecard for a shoot day
+    pub async fn for_day(day: &RecordId) -> Result<Vec<TimecardListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM timecard WHERE shoot_day = $day ORDER BY person_name ASC"
+            ))
+            .bind(("day", day.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Timecards in one status, oldest shoot day first
+    pub async fn with_status(production: &RecordId, status: &str) -> Result<Vec<TimecardListing>, Error> {
+        Ok(DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM timecard WHERE production = $production AND status = $status
+                 ORDER BY date ASC, person_name ASC"
+            ))
+            .bind(("production", production.clone()))
+            .bind(("status", status.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    /// One page of approved timecards in payroll order, after `cursor`
+    pub async fn approved_page(
+        production: &RecordId,
+        cursor: Option<&str>,
+        size: usize,
+    ) -> Result<Page<TimecardListing>, Error> {
+        let cursor = match cursor {
+            Some(value) => Some(
+                Cursor::decode(value, "timecard", APPROVED_ORDER)
+                    .ok_or_else(|| Error::BadRequest("Invalid cursor".to_string()))?,
+            ),
+            None => None,
+        };
+        let after = match cursor {
+            Some(_) => format!("AND {}", pagination::after_clause(APPROVED_ORDER)),
+            None => String::new(),
+        };
+        let mut query = DB
+            .query(format!(
+                "SELECT {LISTING_FIELDS} FROM timecard WHERE production = $production AND status = 'approved' {after}
+                 {} LIMIT $limit",
+                pagination::order_clause(APPROVED_ORDER)
+            ))
+            .bind(("production", production.clone()))
+            .bind(("limit", size as i64 + 1));
+        if let Some(cursor) = &cursor {
+            for bind in cursor.binds() {
+                query = query.bind(bind);
+            }
+            query = query.bind(("after_id", cursor.record_id()));
+        }
+        let rows: Vec<TimecardListing> = query.await?.take(0)?;
+        Ok(Page::from_rows(rows, size, TimecardListing::approved_cursor))
+    }
+
+    /// Overtime rules for a person: their deal's, or the defaults
+    async fn rules(production: &RecordId, person: &RecordId) -> Result<OvertimeRules, Error> {
+        Ok(Self::deal(production, person)
+            .await?
+            .map(|d| d.rules())
+            .unwrap_or_default())
+    }
+
+    /// Clock in for a shoot day at a local "HH:MM" time
+    pub async fn clock_in(day: &ShootDay, person: &RecordId, time: &str) -> Result<Timecard, Error> {
+        let time_in = parse_time(time)?.ok_or_else(|| Error::Validation("Enter the time you started".to_string()))?;
+        if let Some(existing) = Self::for_person_day(&day.id, person).await?
+            && existing.status != "rejected"
+        {
+            return Err(Error::Conflict("You've already clocked in for this day".to_string()));
+        }
+
+        debug!("{} clocking in on {}", person.display(), day.id.display());
+        let timecard: Option<Timecard> = DB
+            .query(
+                "DELETE timecard WHERE shoot_day = $day AND person = $person AND status = 'rejected';
+                 CREATE timecard SET production = $production, shoot_day = $day, person = $person,
+                    time_in = $time_in, time_out = NONE, meal_minutes = 0, status = 'open',
+                    regular_hours = 0.0, overtime_hours = 0.0, double_time_hours = 0.0,
+                    created_at = time::now(), updated_at = time::now()",
+            )
+            .bind(("production", day.production.clone()))
+            .bind(("day", day.id.clone()))
+            .bind(("person", person.clone()))
+            .bind(("time_in", time_in))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to clock in: {}", e)))?
+            .take(1)?;
+        timecard.ok_or_else(|| Error::Internal("Failed to clock in".to_string()))
+    }
+
+    /// Clock out of an open timecard, submitting it for approval
+    pub async fn clock_out(day: &ShootDay, person: &RecordId, time: &str, meal_minutes: i64) -> Result<Timecard, Error> {
+        let existing = Self::for_person_day(&day.id, person)
+            .await?
+            .filter(|t| t.status == "open")
+            .ok_or_else(|| Error::Conflict("You haven't clocked in for this day".to_string()))?;
+        Self::submit(day, person, &existing.time_in, time, meal_minutes).await
+    }
+
+    /// Shoot days on `date` ("YYYY-MM-DD") for productions `person` is an
+    /// accepted member of, for clocking in from WhatsApp without a day picked
+    pub async fn days_on(person: &RecordId, date: &str) -> Result<Vec<ShootDay>, Error> {
+        Ok(DB
+            .query(
+                "SELECT * FROM shoot_day WHERE date = $date AND production IN
+                    (SELECT VALUE out FROM member_of WHERE in = $person AND invitation_status = 'accepted')
+                 ORDER BY day_number ASC",
+            )
+            .bind(("person", person.clone()))
+            .bind(("date", date.to_string()))
+            .await?
+            .take(0)?)
+    }
+
+    /// The timecard `person` is clocked in on, if any
+    pub async fn open_for(person: &RecordId) -> Result<Option<Timecard>, Error> {
+        Ok(DB
+            .query("SELECT * FROM timecard WHERE person = $person AND status = 'open' ORDER BY created_at DESC LIMIT 1")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Enter or correct a shift's times by hand, submitting it for approval.
+    /// Approved timecards can't be changed.
+    pub async fn submit(
+        day: &ShootDay,
+        person: &RecordId,
+        time_in: &str,
+        time_out: &str,
+        meal_minutes: i64,
+    ) -> Result<Timecard, Error> {
+        let time_in = parse_time(time_in)?.ok_or_else(|| Error::Validation("Enter the time you started".to_string()))?;
+        let time_out = parse_time(time_out)?.ok_or_else(|| Error::Validation("Enter the time you finished".to_string()))?;
+        if !(0..=MAX_MEAL_MINUTES).contains(&meal_minutes) {
+            return Err(Error::Validation(format!(
+                "Meal breaks can be up to {} minutes",
+                MAX_MEAL_MINUTES
+            )));
+        }
+        if let Some(existing) = Self::for_person_day(&day.id, person).await?
+            && existing.status == "approved"
+        {
+            return Err(Error::Conflict("This timecard has been approved and can't be changed".to_string()));
+        }
+
+        let rules = Self::rules(&day.production, person).await?;
+        let hours = compute_hours(&time_in, &time_out, meal_minutes, &rules)
+            .ok_or_else(|| Error::Validation("Enter times as HH:MM".to_string()))?;
+
+        let timecard: Option<Timecard> = DB
+            .query(
+                "UPSERT timecard SET production = $production, shoot_day = $day, person = $person,
+                    time_in = $time_in, time_out = $time_out, meal_minutes = $meal_minutes,
+                    status = 'submitted', regular_hours = $regular, overtime_hours = $overtime,
+                    double_time_hours = $double_time, note = NONE, reviewed_by = NONE, reviewed_at = NONE,
+                    updated_at = time::now()
+                 WHERE shoot_day = $day AND person = $person",
+            )
+            .bind(("production", day.production.clone()))
+            .bind(("day", day.id.clone()))
+            .bind(("person", person.clone()))
+            .bind(("time_in", time_in))
+            .bind(("time_out", time_out))
+            .bind(("meal_minutes", meal_minutes))
+            .bind(("regular", hours.regular))
+            .bind(("overtime", hours.overtime))
+            .bind(("double_time", hours.double_time))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to submit timecard: {}", e)))?
+            .take(0)?;
+        timecard.ok_or_else(|| Error::Internal("Failed to submit timecard".to_string()))
+    }
+
+    /// Approve or reject a submitted timecard
+    pub async fn review(
+        production: &RecordId,
+        timecard_id: &str,
+        approve: bool,
+        reviewer: &RecordId,
+        note: Option<String>,
+    ) -> Result<Timecard, Error> {
+        let timecard = Self::get(production, timecard_id).await?;
+        if timecard.status != "submitted" {
+            return Err(Error::Conflict("Only submitted timecards can be reviewed".to_string()));
+        }
+        let reviewed: Option<Timecard> = DB
+            .query(
+                "UPDATE $id SET status = $status, note = $note, reviewed_by = $reviewer,
+                    reviewed_at = time::now(), updated_at = time::now()
+                 RETURN AFTER",
+            )
+            .bind(("id", timecard.id))
+            .bind(("status", if approve { "approved" } else { "rejected" }.to_string()))
+            .bind(("note", note.filter(|n| !n.trim().is_empty())))
+            .bind(("reviewer", reviewer.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to review timecard: {}", e)))?
+            .take(0)?;
+        reviewed.ok_or(Error::NotFound)
+    }
+
+    // -- Deals --
+
+    pub async fn deal(production: &RecordId, person: &RecordId) -> Result<Option<CrewDeal>, Error> {
+        Ok(DB
+            .query("SELECT * FROM crew_deal WHERE production = $production AND person = $person LIMIT 1")
+            .bind(("production", production.clone()))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    pub async fn deals(production: &RecordId) -> Result<Vec<CrewDeal>, Error> {
+        Ok(DB
+            .query("SELECT * FROM crew_deal WHERE production = $production")
+            .bind(("production", production.clone()))
+            .await?
+            .take(0)?)
+    }
+
+    /// Set a person's deal. Submitted timecards are recalculated under the new
+    /// rules; approved ones keep the hours they were approved with.
+    pub async fn set_deal(production: &RecordId, person: &RecordId, data: DealData) -> Result<(), Error> {
+        data.validate()?;
+        DB.query(
+            "UPSERT crew_deal SET production = $production, person = $person, rate_type = $rate_type,
+                rate = $rate, straight_hours = $straight_hours, double_time_after = $double_time_after,
+                overtime_multiplier = $overtime_multiplier, double_time_multiplier = $double_time_multiplier,
+                updated_at = time::now()
+             WHERE production = $production AND person = $person",
+        )
+        .bind(("production", production.clone()))
+        .bind(("person", person.clone()))
+        .bind(("rate_type", data.rate_type.clone()))
+        .bind(("rate", data.rate))
+        .bind(("straight_hours", data.straight_hours))
+        .bind(("double_time_after", data.double_time_after))
+        .bind(("overtime_multiplier", data.overtime_multiplier))
+        .bind(("double_time_multiplier", data.double_time_multiplier))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to save deal: {}", e)))?;
+
+        let rules = OvertimeRules {
+            straight_hours: data.straight_hours,
+            double_time_after: data.double_time_after,
+            overtime_multiplier: data.overtime_multiplier,
+            double_time_multiplier: data.double_time_multiplier,
+        };
+        let submitted: Vec<Timecard> = DB
+            .query("SELECT * FROM timecard WHERE production = $production AND person = $person AND status = 'submitted'")
+            .bind(("production", production.clone()))
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?;
+        for timecard in submitted {
+            let Some(time_out) = timecard.time_out.as_deref() else {
+                continue;
+            };
+            if let Some(hours) = compute_hours(&timecard.time_in, time_out, timecard.meal_minutes, &rules) {
+                DB.query(
+                    "UPDATE $id SET regular_hours = $regular, overtime_hours = $overtime,
+                        double_time_hours = $double_time, updated_at = time::now()",
+                )
+                .bind(("id", timecard.id))
+                .bind(("regular", hours.regular))
+                .bind(("overtime", hours.overtime))
+                .bind(("double_time", hours.double_time))
+                .await
+                .map_err(|e| Error::Database(format!("Failed to recalculate timecard: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Approved hours for payroll as CSV, streamed a page of timecards at a
+    /// time so a long shoot isn't held in memory
+    pub fn payroll_export(production: RecordId) -> impl Stream<Item = Result<String, Error>> + Send {
+        async_stream::try_stream! {
+            let deals = Self::deals(&production).await?;
+            yield payroll_header();
+            let mut cursor: Option<String> = None;
+            loop {
+                let page = Self::approved_page(&production, cursor.as_deref(), EXPORT_PAGE_SIZE).await?;
+                let mut chunk = String::new();
+                for timecard in &page.items {
+                    chunk.push_str(&payroll_row(timecard, &deals));
+                }
+                yield chunk;
+                match page.next {
+                    Some(next) => cursor = Some(next),
+                    None => break,
+                }
+            }
+        }
+    }
+}
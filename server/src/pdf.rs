@@ -0,0 +1,611 @@
+//! Minimal PDF writer for reports and printable exports.
+//!
+//! Produces PDF 1.4 using the built-in Helvetica fonts (WinAnsi encoding), so
+//! no fonts need embedding. Images are embedded as JPEG. [`PdfDocument`] draws
+//! at absolute positions in points from the bottom-left corner; [`Flow`] lays
+//! out headings, paragraphs and tables top-down and breaks pages as it goes.
+
+use std::fmt::Write as _;
+
+/// Page size in points (1/72 inch)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+impl PageSize {
+    pub const A4: PageSize = PageSize {
+        width: 595.0,
+        height: 842.0,
+    };
+    pub const LETTER: PageSize = PageSize {
+        width: 612.0,
+        height: 792.0,
+    };
+
+    pub fn landscape(self) -> PageSize {
+        PageSize {
+            width: self.height,
+            height: self.width,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+        }
+    }
+}
+
+/// Handle for an image added with [`PdfDocument::add_jpeg`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageId(usize);
+
+struct Image {
+    data: Vec<u8>,
+    width: u32,
+    height: u32,
+    gray: bool,
+}
+
+/// Helvetica advance widths (per 1000 em) for ASCII 32..=126
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // ' '..'/'
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, // '0'..'9'
+    278, 278, 584, 584, 584, 556, 1015, // ':'..'@'
+    667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, // 'A'..'M'
+    722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, // 'N'..'Z'
+    278, 278, 278, 469, 556, 333, // '['..'`'
+    556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, // 'a'..'m'
+    556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, // 'n'..'z'
+    334, 260, 334, 584, // '{'..'~'
+];
+
+/// Approximate width of `text` in points
+pub fn text_width(text: &str, size: f32, font: Font) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| {
+            let code = c as u32;
+            if (32..=126).contains(&code) {
+                HELVETICA_WIDTHS[(code - 32) as usize] as u32
+            } else {
+                556
+            }
+        })
+        .sum();
+    let bold = if font == Font::Bold { 1.06 } else { 1.0 };
+    units as f32 * size / 1000.0 * bold
+}
+
+/// Break `text` into lines no wider than `max_width`. Words longer than a
+/// line are split.
+pub fn wrap(text: &str, max_width: f32, size: f32, font: Font) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if text_width(&candidate, size, font) <= max_width {
+                line = candidate;
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // Split words that don't fit on a line of their own
+            for c in word.chars() {
+                line.push(c);
+                if text_width(&line, size, font) > max_width && line.chars().count() > 1 {
+                    let last = line.pop().unwrap_or(c);
+                    lines.push(std::mem::take(&mut line));
+                    line.push(last);
+                }
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Encode text as a PDF string literal in WinAnsi, escaping as needed.
+/// Characters outside WinAnsi become '?'.
+pub fn encode_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('(');
+    for c in text.chars() {
+        let byte: u8 = match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+                continue;
+            }
+            ' '..='~' => {
+                out.push(c);
+                continue;
+            }
+            '€' => 0x80,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            '\u{a0}'..='\u{ff}' => c as u32 as u8,
+            _ => b'?',
+        };
+        if byte == b'?' {
+            out.push('?');
+        } else {
+            let _ = write!(out, "\\{:03o}", byte);
+        }
+    }
+    out.push(')');
+    out
+}
+
+fn fmt_num(n: f32) -> String {
+    let s = format!("{:.2}", n);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn fmt_rgb(rgb: [u8; 3]) -> String {
+    rgb.iter()
+        .map(|c| fmt_num(*c as f32 / 255.0))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A PDF being drawn page by page
+pub struct PdfDocument {
+    size: PageSize,
+    pages: Vec<String>,
+    images: Vec<Image>,
+}
+
+impl PdfDocument {
+    /// A new document with one blank page
+    pub fn new(size: PageSize) -> Self {
+        Self {
+            size,
+            pages: vec![String::new()],
+            images: Vec::new(),
+        }
+    }
+
+    pub fn size(&self) -> PageSize {
+        self.size
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn new_page(&mut self) {
+        self.pages.push(String::new());
+    }
+
+    fn content(&mut self) -> &mut String {
+        self.pages.last_mut().expect("document always has a page")
+    }
+
+    /// Draw text with its baseline at (x, y)
+    pub fn text(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str) {
+        let encoded = encode_text(text);
+        let _ = writeln!(
+            self.content(),
+            "BT /{} {} Tf {} {} Td {} Tj ET",
+            font.resource(),
+            fmt_num(size),
+            fmt_num(x),
+            fmt_num(y),
+            encoded
+        );
+    }
+
+    /// Draw text in a colour, `rgb` being 0–255 per channel
+    pub fn text_rgb(&mut self, x: f32, y: f32, size: f32, font: Font, text: &str, rgb: [u8; 3]) {
+        let encoded = encode_text(text);
+        let _ = writeln!(
+            self.content(),
+            "q {} rg BT /{} {} Tf {} {} Td {} Tj ET Q",
+            fmt_rgb(rgb),
+            font.resource(),
+            fmt_num(size),
+            fmt_num(x),
+            fmt_num(y),
+            encoded
+        );
+    }
+
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, width: f32) {
+        let _ = writeln!(
+            self.content(),
+            "{} w {} {} m {} {} l S",
+            fmt_num(width),
+            fmt_num(x1),
+            fmt_num(y1),
+            fmt_num(x2),
+            fmt_num(y2)
+        );
+    }
+
+    /// Outline a rectangle whose bottom-left corner is (x, y)
+    pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32, width: f32) {
+        let _ = writeln!(
+            self.content(),
+            "{} w {} {} {} {} re S",
+            fmt_num(width),
+            fmt_num(x),
+            fmt_num(y),
+            fmt_num(w),
+            fmt_num(h)
+        );
+    }
+
+    /// Fill a rectangle with a gray level (0 black, 1 white)
+    pub fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32, gray: f32) {
+        let _ = writeln!(
+            self.content(),
+            "q {} g {} {} {} {} re f Q",
+            fmt_num(gray),
+            fmt_num(x),
+            fmt_num(y),
+            fmt_num(w),
+            fmt_num(h)
+        );
+    }
+
+    /// Fill a rectangle with a colour, `rgb` being 0–255 per channel
+    pub fn fill_rect_rgb(&mut self, x: f32, y: f32, w: f32, h: f32, rgb: [u8; 3]) {
+        let _ = writeln!(
+            self.content(),
+            "q {} rg {} {} {} {} re f Q",
+            fmt_rgb(rgb),
+            fmt_num(x),
+            fmt_num(y),
+            fmt_num(w),
+            fmt_num(h)
+        );
+    }
+
+    /// Add a baseline JPEG. `gray` is true for single-channel images.
+    pub fn add_jpeg(&mut self, data: Vec<u8>, width: u32, height: u32, gray: bool) -> ImageId {
+        self.images.push(Image {
+            data,
+            width,
+            height,
+            gray,
+        });
+        ImageId(self.images.len() - 1)
+    }
+
+    /// Add any decoded image, re-encoded as JPEG
+    pub fn add_image(&mut self, image: &image::DynamicImage) -> Result<ImageId, image::ImageError> {
+        let rgb = image.to_rgb8();
+        let mut data = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, 85).encode(
+            rgb.as_raw(),
+            rgb.width(),
+            rgb.height(),
+            image::ColorType::Rgb8,
+        )?;
+        Ok(self.add_jpeg(data, rgb.width(), rgb.height(), false))
+    }
+
+    /// Draw an image scaled to the box whose bottom-left corner is (x, y)
+    pub fn draw_image(&mut self, id: ImageId, x: f32, y: f32, w: f32, h: f32) {
+        let _ = writeln!(
+            self.content(),
+            "q {} 0 0 {} {} {} cm /Im{} Do Q",
+            fmt_num(w),
+            fmt_num(h),
+            fmt_num(x),
+            fmt_num(y),
+            id.0
+        );
+    }
+
+    /// Serialize the document
+    pub fn finish(self) -> Vec<u8> {
+        // Object numbers: 1 catalog, 2 pages, 3-4 fonts, then images, then
+        // a page and content stream pair per page
+        let image_base = 5;
+        let page_base = image_base + self.images.len();
+        let object_count = page_base + self.pages.len() * 2 - 1;
+
+        let mut out: Vec<u8> = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = vec![0usize; object_count + 1];
+
+        fn object(out: &mut Vec<u8>, offsets: &mut [usize], number: usize, body: &[u8]) {
+            offsets[number] = out.len();
+            out.extend_from_slice(format!("{} 0 obj\n", number).as_bytes());
+            out.extend_from_slice(body);
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        object(&mut out, &mut offsets, 1, b"<< /Type /Catalog /Pages 2 0 R >>");
+
+        let kids: Vec<String> = (0..self.pages.len())
+            .map(|i| format!("{} 0 R", page_base + i * 2))
+            .collect();
+        object(
+            &mut out,
+            &mut offsets,
+            2,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            )
+            .as_bytes(),
+        );
+        object(
+            &mut out,
+            &mut offsets,
+            3,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>",
+        );
+        object(
+            &mut out,
+            &mut offsets,
+            4,
+            b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>",
+        );
+
+        for (i, image) in self.images.iter().enumerate() {
+            let mut body = format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /{} /BitsPerComponent 8 /Filter /DCTDecode /Length {} >>\nstream\n",
+                image.width,
+                image.height,
+                if image.gray { "DeviceGray" } else { "DeviceRGB" },
+                image.data.len()
+            )
+            .into_bytes();
+            body.extend_from_slice(&image.data);
+            body.extend_from_slice(b"\nendstream");
+            object(&mut out, &mut offsets, image_base + i, &body);
+        }
+
+        let xobjects: String = (0..self.images.len())
+            .map(|i| format!("/Im{} {} 0 R ", i, image_base + i))
+            .collect();
+        for (i, content) in self.pages.iter().enumerate() {
+            let page_number = page_base + i * 2;
+            object(
+                &mut out,
+                &mut offsets,
+                page_number,
+                format!(
+                    "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /XObject << {}>> >> /Contents {} 0 R >>",
+                    fmt_num(self.size.width),
+                    fmt_num(self.size.height),
+                    xobjects,
+                    page_number + 1
+                )
+                .as_bytes(),
+            );
+            let mut body = format!("<< /Length {} >>\nstream\n", content.len()).into_bytes();
+            body.extend_from_slice(content.as_bytes());
+            body.extend_from_slice(b"endstream");
+            object(&mut out, &mut offsets, page_number + 1, &body);
+        }
+
+        let xref_offset = out.len();
+        let mut xref = format!("xref\n0 {}\n0000000000 65535 f \n", object_count + 1);
+        for offset in &offsets[1..] {
+            let _ = writeln!(xref, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            xref,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            object_count + 1,
+            xref_offset
+        );
+        out.extend_from_slice(xref.as_bytes());
+        out
+    }
+}
+
+/// Top-down layout over a [`PdfDocument`], starting new pages as needed
+pub struct Flow {
+    doc: PdfDocument,
+    margin: f32,
+    /// Distance of the cursor from the top of the page
+    cursor: f32,
+    footer: Option<String>,
+    heading_rgb: Option<[u8; 3]>,
+}
+
+impl Flow {
+    pub fn new(size: PageSize) -> Self {
+        Self {
+            doc: PdfDocument::new(size),
+            margin: 40.0,
+            cursor: 40.0,
+            footer: None,
+            heading_rgb: None,
+        }
+    }
+
+    /// Text printed at the bottom of every page, with the page number
+    pub fn with_footer(mut self, footer: impl Into<String>) -> Self {
+        self.footer = Some(footer.into());
+        self
+    }
+
+    /// Draw headings in a colour instead of black, `rgb` being 0–255 per channel
+    pub fn with_heading_color(mut self, rgb: [u8; 3]) -> Self {
+        self.heading_rgb = Some(rgb);
+        self
+    }
+
+    pub fn doc_mut(&mut self) -> &mut PdfDocument {
+        &mut self.doc
+    }
+
+    pub fn content_width(&self) -> f32 {
+        self.doc.size().width - self.margin * 2.0
+    }
+
+    pub fn margin(&self) -> f32 {
+        self.margin
+    }
+
+    /// Cursor as a PDF y coordinate (from the bottom)
+    pub fn y(&self) -> f32 {
+        self.doc.size().height - self.cursor
+    }
+
+    fn bottom_limit(&self) -> f32 {
+        self.doc.size().height - self.margin - if self.footer.is_some() { 16.0 } else { 0.0 }
+    }
+
+    fn draw_footer(&mut self) {
+        if let Some(footer) = self.footer.clone() {
+            let text = format!("{}  |  Page {}", footer, self.doc.page_count());
+            let margin = self.margin;
+            self.doc.text(margin, margin / 2.0, 8.0, Font::Regular, &text);
+        }
+    }
+
+    pub fn page_break(&mut self) {
+        self.draw_footer();
+        self.doc.new_page();
+        self.cursor = self.margin;
+    }
+
+    /// Start a new page unless `height` more points fit on this one
+    pub fn ensure(&mut self, height: f32) {
+        if self.cursor + height > self.bottom_limit() && self.cursor > self.margin {
+            self.page_break();
+        }
+    }
+
+    pub fn space(&mut self, height: f32) {
+        self.cursor += height;
+    }
+
+    /// Move the cursor down by `height`, returning the PDF y of the new position
+    pub fn advance(&mut self, height: f32) -> f32 {
+        self.ensure(height);
+        self.cursor += height;
+        self.y()
+    }
+
+    pub fn heading(&mut self, text: &str, size: f32) {
+        let width = self.content_width();
+        for line in wrap(text, width, size, Font::Bold) {
+            let y = self.advance(size * 1.3);
+            let x = self.margin;
+            match self.heading_rgb {
+                Some(rgb) => self.doc.text_rgb(x, y, size, Font::Bold, &line, rgb),
+                None => self.doc.text(x, y, size, Font::Bold, &line),
+            }
+        }
+        self.space(size * 0.3);
+    }
+
+    pub fn paragraph(&mut self, text: &str, size: f32) {
+        let width = self.content_width();
+        for line in wrap(text, width, size, Font::Regular) {
+            let y = self.advance(size * 1.35);
+            let x = self.margin;
+            self.doc.text(x, y, size, Font::Regular, &line);
+        }
+        self.space(size * 0.5);
+    }
+
+    /// Label/value pairs, one per line
+    pub fn key_values(&mut self, pairs: &[(&str, String)], size: f32) {
+        let label_width = pairs
+            .iter()
+            .map(|(label, _)| text_width(label, size, Font::Bold))
+            .fold(0.0, f32::max)
+            + 12.0;
+        let value_width = self.content_width() - label_width;
+        for (label, value) in pairs {
+            let lines = wrap(value, value_width, size, Font::Regular);
+            for (i, line) in lines.iter().enumerate() {
+                let y = self.advance(size * 1.35);
+                let x = self.margin;
+                if i == 0 {
+                    self.doc.text(x, y, size, Font::Bold, label);
+                }
+                self.doc.text(x + label_width, y, size, Font::Regular, line);
+            }
+        }
+        self.space(size * 0.5);
+    }
+
+    /// A table with a bold header row. `widths` are relative column widths.
+    /// The header repeats on every page the table spans.
+    pub fn table(&mut self, headers: &[&str], widths: &[f32], rows: &[Vec<String>], size: f32) {
+        let total: f32 = widths.iter().sum();
+        let content_width = self.content_width();
+        let columns: Vec<f32> = widths.iter().map(|w| w / total * content_width).collect();
+        let leading = size * 1.3;
+        let padding = 3.0;
+
+        let layout = |cells: &[String], font: Font| -> (Vec<Vec<String>>, f32) {
+            let wrapped: Vec<Vec<String>> = cells
+                .iter()
+                .zip(&columns)
+                .map(|(cell, width)| wrap(cell, width - padding * 2.0, size, font))
+                .collect();
+            let lines = wrapped.iter().map(Vec::len).max().unwrap_or(1).max(1);
+            (wrapped, lines as f32 * leading + padding * 2.0)
+        };
+        let draw = |flow: &mut Flow, wrapped: &[Vec<String>], height: f32, font: Font| {
+            let top = flow.y();
+            let mut x = flow.margin;
+            for (cell_lines, width) in wrapped.iter().zip(&columns) {
+                for (i, line) in cell_lines.iter().enumerate() {
+                    let baseline = top - padding - leading * (i as f32 + 1.0) + size * 0.25;
+                    flow.doc.text(x + padding, baseline, size, font, line);
+                }
+                x += width;
+            }
+            flow.cursor += height;
+            let y = flow.y();
+            let left = flow.margin;
+            let rule = if font == Font::Bold { 0.8 } else { 0.3 };
+            flow.doc.line(left, y, left + content_width, y, rule);
+        };
+
+        let header_cells: Vec<String> = headers.iter().map(|h| h.to_string()).collect();
+        let (header, header_height) = layout(&header_cells, Font::Bold);
+        self.ensure(header_height * 2.0);
+        draw(self, &header, header_height, Font::Bold);
+        for row in rows {
+            let (wrapped, height) = layout(row, Font::Regular);
+            if self.cursor + height > self.bottom_limit() {
+                self.page_break();
+                draw(self, &header, header_height, Font::Bold);
+            }
+            draw(self, &wrapped, height, Font::Regular);
+        }
+        self.space(size);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.draw_footer();
+        self.doc.finish()
+    }
+}
@@ -0,0 +1,220 @@
+//! Controlled vocabularies for physical attributes on profiles.
+//!
+//! Hair color, eye color and body type are stored as one of a fixed set of
+//! values (the English names, matching the `hair_color`, `eye_color` and
+//! `body_type` tables), so search filters match exactly. Anything outside the
+//! list is stored as "Other" with the person's own words kept next to it in
+//! `<field>_other`. Labels are translated for display; values never are.
+
+/// Languages labels are available in. The first is the fallback.
+pub const LANGUAGES: &[&str] = &["en", "es", "fr"];
+
+/// The value stored when nothing in the vocabulary fits
+pub const OTHER: &str = "Other";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attribute {
+    HairColor,
+    EyeColor,
+    BodyType,
+}
+
+pub struct AttributeOption {
+    /// Stored value
+    pub value: &'static str,
+    /// Display labels, one per entry of `LANGUAGES`
+    pub labels: [&'static str; 3],
+}
+
+const fn option(value: &'static str, es: &'static str, fr: &'static str) -> AttributeOption {
+    AttributeOption {
+        value,
+        labels: [value, es, fr],
+    }
+}
+
+pub const HAIR_COLORS: &[AttributeOption] = &[
+    option("Black", "Negro", "Noir"),
+    option("Brown", "Castaño", "Châtain"),
+    option("Blonde", "Rubio", "Blond"),
+    option("Red", "Pelirrojo", "Roux"),
+    option("Gray", "Canoso", "Gris"),
+    option("White", "Blanco", "Blanc"),
+    option("Bald", "Calvo", "Chauve"),
+    option(OTHER, "Otro", "Autre"),
+];
+
+pub const EYE_COLORS: &[AttributeOption] = &[
+    option("Brown", "Marrones", "Marron"),
+    option("Blue", "Azules", "Bleus"),
+    option("Green", "Verdes", "Verts"),
+    option("Hazel", "Avellana", "Noisette"),
+    option("Gray", "Grises", "Gris"),
+    option("Black", "Negros", "Noirs"),
+    option(OTHER, "Otro", "Autre"),
+];
+
+pub const BODY_TYPES: &[AttributeOption] = &[
+    option("Athletic", "Atlético", "Athlétique"),
+    option("Average", "Promedio", "Moyenne"),
+    option("Slender", "Delgado", "Mince"),
+    option("Curvy", "Con curvas", "Pulpeuse"),
+    option("Muscular", "Musculoso", "Musclé"),
+    option("Petite", "Menudo", "Menue"),
+    option("Plus Size", "Talla grande", "Grande taille"),
+    option("Tall", "Alto", "Grand"),
+    option(OTHER, "Otro", "Autre"),
+];
+
+/// Common free-text answers and the value they mean. Keep in step with
+/// `db/migrations/035_physical_attribute_vocabularies.surql`.
+const HAIR_SYNONYMS: &[(&str, &str)] = &[
+    ("blond", "Blonde"),
+    ("dirty blonde", "Blonde"),
+    ("dirty blond", "Blonde"),
+    ("platinum", "Blonde"),
+    ("platinum blonde", "Blonde"),
+    ("platinum blond", "Blonde"),
+    ("brunette", "Brown"),
+    ("light brown", "Brown"),
+    ("dark brown", "Brown"),
+    ("chestnut", "Brown"),
+    ("auburn", "Red"),
+    ("ginger", "Red"),
+    ("redhead", "Red"),
+    ("copper", "Red"),
+    ("strawberry blonde", "Red"),
+    ("strawberry blond", "Red"),
+    ("grey", "Gray"),
+    ("silver", "Gray"),
+    ("salt and pepper", "Gray"),
+    ("salt & pepper", "Gray"),
+    ("shaved", "Bald"),
+    ("shaved head", "Bald"),
+];
+
+const EYE_SYNONYMS: &[(&str, &str)] = &[
+    ("grey", "Gray"),
+    ("dark brown", "Brown"),
+    ("light brown", "Brown"),
+    ("light blue", "Blue"),
+    ("dark blue", "Blue"),
+];
+
+const BODY_SYNONYMS: &[(&str, &str)] = &[
+    ("slim", "Slender"),
+    ("thin", "Slender"),
+    ("lean", "Slender"),
+    ("fit", "Athletic"),
+    ("toned", "Athletic"),
+    ("sporty", "Athletic"),
+    ("medium", "Average"),
+    ("regular", "Average"),
+    ("normal", "Average"),
+    ("muscled", "Muscular"),
+    ("built", "Muscular"),
+    ("hourglass", "Curvy"),
+    ("voluptuous", "Curvy"),
+    ("plus", "Plus Size"),
+    ("plussize", "Plus Size"),
+    ("full figured", "Plus Size"),
+    ("heavyset", "Plus Size"),
+];
+
+impl Attribute {
+    pub fn options(&self) -> &'static [AttributeOption] {
+        match self {
+            Attribute::HairColor => HAIR_COLORS,
+            Attribute::EyeColor => EYE_COLORS,
+            Attribute::BodyType => BODY_TYPES,
+        }
+    }
+
+    fn synonyms(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Attribute::HairColor => HAIR_SYNONYMS,
+            Attribute::EyeColor => EYE_SYNONYMS,
+            Attribute::BodyType => BODY_SYNONYMS,
+        }
+    }
+}
+
+fn lang_index(lang: &str) -> usize {
+    LANGUAGES.iter().position(|l| *l == lang).unwrap_or(0)
+}
+
+/// Lowercase, with hyphens and repeated spaces collapsed to one space
+fn fold(text: &str) -> String {
+    text.replace('-', " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// The stored value for an answer: a vocabulary value, a translated label or
+/// a known synonym. `None` when nothing matches.
+pub fn lookup(attribute: Attribute, text: &str) -> Option<&'static str> {
+    let folded = fold(text);
+    if folded.is_empty() {
+        return None;
+    }
+    attribute
+        .options()
+        .iter()
+        .find(|o| o.labels.iter().any(|l| fold(l) == folded))
+        .map(|o| o.value)
+        .or_else(|| {
+            attribute
+                .synonyms()
+                .iter()
+                .find(|(synonym, _)| *synonym == folded)
+                .map(|(_, value)| *value)
+        })
+}
+
+/// Split an answer into what's stored: `(value, other)`. Unknown answers are
+/// stored as "Other" with the original text; empty answers clear both.
+pub fn normalize(attribute: Attribute, text: &str) -> (Option<String>, Option<String>) {
+    let text = text.trim();
+    if text.is_empty() {
+        return (None, None);
+    }
+    match lookup(attribute, text) {
+        Some(value) => (Some(value.to_string()), None),
+        None => (Some(OTHER.to_string()), Some(text.to_string())),
+    }
+}
+
+/// A stored value in `lang`. Values outside the vocabulary are shown as is.
+pub fn label(attribute: Attribute, value: &str, lang: &str) -> String {
+    attribute
+        .options()
+        .iter()
+        .find(|o| o.value == value)
+        .map(|o| o.labels[lang_index(lang)].to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// What to show or index for a stored pair: the person's own words for
+/// "Other", the value otherwise
+pub fn describe<'a>(value: Option<&'a str>, other: Option<&'a str>) -> Option<&'a str> {
+    match (value, other.filter(|o| !o.trim().is_empty())) {
+        (Some(OTHER), Some(other)) => Some(other),
+        (value, _) => value.filter(|v| !v.is_empty()),
+    }
+}
+
+/// The first supported language in an `Accept-Language` header
+pub fn lang_from_accept_language(header: Option<&str>) -> &'static str {
+    header
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|part| {
+            let tag = part.split(';').next()?.trim();
+            let primary = tag.split('-').next()?.to_ascii_lowercase();
+            LANGUAGES.iter().find(|l| **l == primary).copied()
+        })
+        .next()
+        .unwrap_or(LANGUAGES[0])
+}
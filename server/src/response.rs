@@ -4,12 +4,16 @@
 //! throughout the application, including redirects, HTML responses, and JSON responses.
 
 use axum::{
+    body::Body,
     http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
+use futures::Stream;
 use serde::Serialize;
 use tracing::debug;
 
+use crate::error::Error;
+
 /// Create a redirect response to the specified path
 ///
 /// This creates a 303 See Other redirect, which is the recommended status code
@@ -94,6 +98,22 @@ pub fn redirect_with_status(path: &str, status: StatusCode) -> Response {
     response
 }
 
+/// Whether `path` is a path on this site that's safe to redirect to. It must
+/// start with a single `/`: browsers read `//evil.com` and `/\evil.com` as
+/// another host, and drop tabs and newlines before doing so, so control
+/// characters are refused too.
+pub fn is_local_path(path: &str) -> bool {
+    let mut chars = path.chars();
+    chars.next() == Some('/')
+        && !matches!(chars.next(), Some('/' | '\\'))
+        && !path.chars().any(char::is_control)
+}
+
+/// `next` when it's a path on this site (see `is_local_path`)
+pub fn local_path(next: Option<String>) -> Option<String> {
+    next.filter(|n| is_local_path(n))
+}
+
 /// Create a redirect with cookies
 ///
 /// This is useful when you need to set or remove cookies while redirecting.
@@ -135,6 +155,25 @@ pub fn html(content: impl Into<String>) -> Response {
         .into_response()
 }
 
+/// Create an HTML response for a form re-rendered with its field errors
+/// (422 Unprocessable Entity)
+///
+/// # Example
+/// ```
+/// return Ok(invalid_form(template.render()?));
+/// ```
+pub fn invalid_form(content: impl Into<String>) -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        )],
+        content.into(),
+    )
+        .into_response()
+}
+
 /// Create a JSON response
 ///
 /// # Example
@@ -162,6 +201,151 @@ pub fn json<T: Serialize>(data: &T) -> Response {
     }
 }
 
+/// Items serialized into each chunk of a list body
+const LIST_CHUNK_ITEMS: usize = 100;
+
+/// Create a JSON response for a list that's already loaded, serializing it a
+/// chunk at a time as the body is sent rather than into one string up front
+///
+/// The items themselves are all in memory, so this is for pages of results;
+/// exports read their rows with `json_stream`. The fields of `head` come
+/// first, then the list under `key`; with no key the body is the bare array.
+///
+/// # Example
+/// ```
+/// return Ok(json_list(json!({ "query": query }), Some("results"), results));
+/// ```
+pub fn json_list<T: Serialize + Send + 'static>(
+    head: serde_json::Value,
+    key: Option<&str>,
+    items: Vec<T>,
+) -> Response {
+    let chunks = json_list_chunks(head, key, items);
+    (
+        StatusCode::OK,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )],
+        Body::from_stream(futures::stream::iter(chunks)),
+    )
+        .into_response()
+}
+
+/// The pieces of a `json_list` body, serialized one chunk at a time as
+/// they're pulled. Joined, they're the same JSON as serializing `head` with
+/// the list added under `key`.
+pub fn json_list_chunks<T: Serialize>(
+    head: serde_json::Value,
+    key: Option<&str>,
+    items: Vec<T>,
+) -> impl Iterator<Item = serde_json::Result<String>> + use<T> {
+    let (open, close) = list_brackets(head, key);
+
+    let mut items = items.into_iter();
+    let mut first = true;
+    let body = std::iter::from_fn(move || {
+        let mut chunk = String::new();
+        for item in items.by_ref().take(LIST_CHUNK_ITEMS) {
+            if !first {
+                chunk.push(',');
+            }
+            first = false;
+            match serde_json::to_string(&item) {
+                Ok(json) => chunk.push_str(&json),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        (!chunk.is_empty()).then_some(Ok(chunk))
+    });
+
+    std::iter::once(Ok(open))
+        .chain(body)
+        .chain(std::iter::once(Ok(close.to_string())))
+}
+
+/// Create a JSON response whose list is read as the body is sent, a page at
+/// a time from `pages`, so an export never holds the whole table in memory
+///
+/// `pages` is usually a model stream that walks a cursor-paged query. The
+/// body is laid out like `json_list`. An error from `pages` ends the body
+/// early, so the client gets truncated JSON rather than a partial list that
+/// looks complete.
+///
+/// # Example
+/// ```
+/// let consents = consent::history(person.id);
+/// return Ok(json_stream(json!({ "account": account }), Some("consents"), consents));
+/// ```
+pub fn json_stream<T, S>(head: serde_json::Value, key: Option<&str>, pages: S) -> Response
+where
+    T: Serialize + Send + 'static,
+    S: Stream<Item = Result<Vec<T>, Error>> + Send + 'static,
+{
+    (
+        StatusCode::OK,
+        [(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        )],
+        Body::from_stream(json_stream_chunks(head, key, pages)),
+    )
+        .into_response()
+}
+
+/// The pieces of a `json_stream` body: the opening, one chunk per page, and
+/// the closing
+pub fn json_stream_chunks<T, S>(
+    head: serde_json::Value,
+    key: Option<&str>,
+    pages: S,
+) -> impl Stream<Item = Result<String, Error>> + use<T, S>
+where
+    T: Serialize,
+    S: Stream<Item = Result<Vec<T>, Error>>,
+{
+    let (open, close) = list_brackets(head, key);
+    async_stream::try_stream! {
+        yield open;
+        let mut first = true;
+        for await page in pages {
+            let mut chunk = String::new();
+            for item in page? {
+                if !first {
+                    chunk.push(',');
+                }
+                first = false;
+                chunk.push_str(&serde_json::to_string(&item)?);
+            }
+            if !chunk.is_empty() {
+                yield chunk;
+            }
+        }
+        yield close.to_string();
+    }
+}
+
+/// The opening of a list body, up to its first item, and its closing
+fn list_brackets(head: serde_json::Value, key: Option<&str>) -> (String, &'static str) {
+    match key {
+        Some(key) => {
+            let mut open = match head {
+                serde_json::Value::Object(fields) if !fields.is_empty() => {
+                    let mut head = serde_json::Value::Object(fields).to_string();
+                    head.pop();
+                    head.push(',');
+                    head
+                }
+                _ => "{".to_string(),
+            };
+            open.push_str(&serde_json::Value::from(key).to_string());
+            open.push_str(":[");
+            (open, "]}")
+        }
+        None => ("[".to_string(), "]"),
+    }
+}
+
 /// Create a no content (204) response
 ///
 /// Use this for successful operations that don't return data.
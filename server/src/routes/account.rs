@@ -2,12 +2,13 @@ use askama::Template;
 use axum::{
     Form, Router,
     extract::Query,
+    http::header,
     response::{Html, IntoResponse, Response},
     routing::{get, post},
 };
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use serde::Deserialize;
-use std::env;
+use surrealdb::types::RecordId;
 use tracing::{error, info};
 
 use crate::{
@@ -15,10 +16,35 @@ use crate::{
     db::DB,
     error::Error,
     middleware::AuthenticatedUser,
-    models::person::Person,
+    models::{
+        analytics::AnalyticsModel,
+        audio_reel::AudioReelModel,
+        block::{BlockKind, BlockModel},
+        offer::OfferModel,
+        person::{Person, SessionUser},
+        person_availability::{self, PersonAvailabilityModel},
+        portfolio::PortfolioModel,
+        representation::{ContactMode, RepresentationModel},
+        saved_search::SavedSearchModel,
+        search_history::SearchHistoryModel,
+        selftape::SelfTapeModel,
+        shortlist::ShortlistModel,
+        talent_submission::TalentSubmissionModel,
+    },
     record_id_ext::RecordIdExt,
     response,
-    templates::{AccountSettingsTemplate, BaseContext, User},
+    services::{
+        availability_badge, change_requests, consent,
+        digest::{self, DigestPreference},
+        id_verification, login_security, minors, oauth, org_claims,
+        password_policy, search_visibility, transcode, triggers, uploads, whatsapp,
+    },
+    templates::{
+        AccountAlertsTemplate, AccountAvailabilityTemplate, AccountBlocksTemplate,
+        AccountGuardianTemplate, AccountRepresentationTemplate, AccountSettingsTemplate,
+        BaseContext, User,
+    },
+    units::UnitSystem,
 };
 
 pub fn router() -> Router {
@@ -28,7 +54,26 @@ pub fn router() -> Router {
         .route("/account/change-email", post(change_email))
         .route("/account/change-username", post(change_username))
         .route("/account/messaging-preference", post(change_messaging_preference))
+        .route("/account/units", post(change_units))
         .route("/account/contact-visibility", post(change_contact_visibility))
+        .route("/account/profile-visibility", post(change_profile_visibility))
+        .route("/account/search-history", post(change_search_history))
+        .route("/account/digest", post(change_digest))
+        .route("/account/whatsapp", post(change_whatsapp))
+        .route("/account/availability-badge", post(change_availability_badge))
+        .route("/account/blocks", get(blocks_page).post(update_block))
+        .route("/account/alerts", get(alerts_page).post(update_alerts))
+        .route(
+            "/account/availability",
+            get(availability_page).post(update_availability),
+        )
+        .route(
+            "/account/representation",
+            get(representation_page).post(update_representation),
+        )
+        .route("/account/guardian-link", post(change_guardian))
+        .route("/account/guardian", get(guardian_page).post(update_ward))
+        .route("/account/export", get(export_account))
         .route("/account/delete", post(delete_account))
 }
 
@@ -52,7 +97,16 @@ async fn account_settings_page(
     template.username = person.username;
     template.email = person.email;
     template.messaging_preference = person.messaging_preference;
+    template.units = UnitSystem::from_preference(person.units.as_deref()).as_str();
     template.show_contact_info = person.profile.as_ref().map(|p| p.is_public).unwrap_or(false);
+    template.set_visibility(&person.visibility);
+    template.search_history_enabled = SearchHistoryModel::is_enabled(&person.id)
+        .await
+        .unwrap_or(true);
+    template.set_digest(digest::get_preference(&person.id).await.unwrap_or_default());
+    template.set_whatsapp(whatsapp::get_preference(&person.id).await.unwrap_or_default());
+    template.set_badge(availability_badge::get_token(&person.id).await.unwrap_or_default());
+    template.minor = minors::get_link(&person.id).await.unwrap_or_default();
     template.success = query.success;
 
     let html = template.render().map_err(|e| {
@@ -81,13 +135,12 @@ async fn change_password(
         return render_settings_with_error(&current_user.id, "New passwords do not match.").await;
     }
 
-    // Validate new password length
-    if form.new_password.len() < 8 {
-        return render_settings_with_error(
-            &current_user.id,
-            "New password must be at least 8 characters.",
-        )
-        .await;
+    // Validate new password strength
+    let email_local = current_user.email.split('@').next().unwrap_or_default();
+    if let Err(message) =
+        password_policy::validate(&form.new_password, &[&current_user.username, email_local]).await
+    {
+        return render_settings_with_error(&current_user.id, &message).await;
     }
 
     // Verify current password
@@ -174,7 +227,7 @@ async fn change_email(
         .path("/")
         .same_site(SameSite::Lax)
         .http_only(true)
-        .secure(env::var("COOKIE_SECURE").unwrap_or_else(|_| "true".to_string()) != "false")
+        .secure(crate::config::cookie_secure())
         .build();
 
     Ok((
@@ -245,7 +298,7 @@ async fn change_username(
         .path("/")
         .same_site(SameSite::Lax)
         .http_only(true)
-        .secure(env::var("COOKIE_SECURE").unwrap_or_else(|_| "true".to_string()) != "false")
+        .secure(crate::config::cookie_secure())
         .build();
 
     // Redirect back so the new cookie takes effect
@@ -287,6 +340,37 @@ async fn change_messaging_preference(
     render_settings_with_success(&current_user.id, "Messaging preference updated.").await
 }
 
+// -- Units --
+
+#[derive(Debug, Deserialize)]
+struct UnitsForm {
+    units: String,
+}
+
+/// Whether heights and weights are shown in metric or imperial
+async fn change_units(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<UnitsForm>,
+) -> Result<Response, Error> {
+    let Some(units) = UnitSystem::parse(&form.units) else {
+        return render_settings_with_error(&current_user.id, "Invalid units.").await;
+    };
+
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    DB.query("UPDATE $id SET units = $units")
+        .bind(("id", person.id.clone()))
+        .bind(("units", units.as_str().to_string()))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    info!("Units changed to '{}' for user: {}", units.as_str(), current_user.username);
+
+    render_settings_with_success(&current_user.id, "Units updated.").await
+}
+
 // -- Contact Visibility --
 
 #[derive(Debug, Deserialize)]
@@ -318,6 +402,608 @@ async fn change_contact_visibility(
     render_settings_with_success(&current_user.id, "Contact visibility updated.").await
 }
 
+// -- Profile Visibility --
+
+#[derive(Debug, Deserialize)]
+struct ProfileVisibilityForm {
+    visibility: String,
+}
+
+async fn change_profile_visibility(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<ProfileVisibilityForm>,
+) -> Result<Response, Error> {
+    let visibility = match search_visibility::parse_visibility(&form.visibility) {
+        Ok(visibility) => visibility,
+        Err(Error::Validation(msg)) => {
+            return render_settings_with_error(&current_user.id, &msg).await;
+        }
+        Err(e) => return Err(e),
+    };
+
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    DB.query("UPDATE $id SET visibility = $visibility")
+        .bind(("id", person.id.clone()))
+        .bind(("visibility", visibility.to_string()))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    info!("Profile visibility changed to '{}' for user: {}", visibility, current_user.username);
+
+    render_settings_with_success(&current_user.id, "Profile visibility updated.").await
+}
+
+// -- Search History --
+
+#[derive(Debug, Deserialize)]
+struct SearchHistoryForm {
+    search_history_enabled: Option<String>,
+}
+
+async fn change_search_history(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<SearchHistoryForm>,
+) -> Result<Response, Error> {
+    let enabled = form.search_history_enabled.as_deref() == Some("on");
+
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    SearchHistoryModel::set_enabled(&person.id, enabled).await?;
+
+    let message = if enabled {
+        "Search history turned on."
+    } else {
+        "Search history turned off and cleared."
+    };
+    render_settings_with_success(&current_user.id, message).await
+}
+
+// -- Weekly Digest --
+
+#[derive(Debug, Deserialize)]
+struct DigestForm {
+    digest_enabled: Option<String>,
+    digest_day: u32,
+    digest_hour: u32,
+}
+
+async fn change_digest(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<DigestForm>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let pref = DigestPreference {
+        enabled: form.digest_enabled.as_deref() == Some("on"),
+        day: form.digest_day,
+        hour: form.digest_hour,
+    };
+    match digest::set_preference(&person.id, &pref).await {
+        Ok(()) => {}
+        Err(Error::Validation(msg)) => return render_settings_with_error(&current_user.id, &msg).await,
+        Err(e) => return Err(e),
+    }
+
+    let message = if pref.enabled {
+        "Weekly digest turned on."
+    } else {
+        "Weekly digest turned off."
+    };
+    render_settings_with_success(&current_user.id, message).await
+}
+
+// -- WhatsApp --
+
+#[derive(Debug, Deserialize)]
+struct WhatsAppForm {
+    whatsapp_enabled: Option<String>,
+    #[serde(default)]
+    whatsapp_number: String,
+}
+
+async fn change_whatsapp(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<WhatsAppForm>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let enabled = form.whatsapp_enabled.as_deref() == Some("on");
+    match whatsapp::set_preference(&person.id, enabled, &form.whatsapp_number).await {
+        Ok(_) => {}
+        Err(Error::Validation(msg)) => return render_settings_with_error(&current_user.id, &msg).await,
+        Err(e) => return Err(e),
+    }
+
+    let message = if enabled {
+        "WhatsApp messages turned on."
+    } else {
+        "WhatsApp messages turned off."
+    };
+    render_settings_with_success(&current_user.id, message).await
+}
+
+// -- Availability Badge --
+
+#[derive(Debug, Deserialize)]
+struct AvailabilityBadgeForm {
+    /// "enable", "reset" or "disable"
+    action: String,
+}
+
+async fn change_availability_badge(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<AvailabilityBadgeForm>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let message = match form.action.as_str() {
+        "enable" => {
+            availability_badge::reset_token(&person.id).await?;
+            "Availability badge turned on."
+        }
+        "reset" => {
+            availability_badge::reset_token(&person.id).await?;
+            "New badge link created. Embeds using the old link no longer load."
+        }
+        "disable" => {
+            availability_badge::revoke(&person.id).await?;
+            "Availability badge turned off."
+        }
+        _ => return Err(Error::BadRequest("Unknown badge action".to_string())),
+    };
+    render_settings_with_success(&current_user.id, message).await
+}
+
+// -- Blocked & Muted --
+
+async fn blocks_page(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Query(query): Query<AccountQuery>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let base = BaseContext::new()
+        .with_page("account")
+        .with_user(User::from_session_user(&current_user).await);
+
+    let mut template = AccountBlocksTemplate::new(base);
+    template.blocked = BlockModel::list(&person.id, BlockKind::Block).await?;
+    template.muted = BlockModel::list(&person.id, BlockKind::Mute).await?;
+    template.success = query.success;
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render blocks template: {}", e);
+        Error::template(e.to_string())
+    })?;
+
+    Ok(Html(html).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockForm {
+    username: String,
+    action: String,
+    next: Option<String>,
+}
+
+/// Block, unblock, mute or unmute someone, then go back where the form was
+async fn update_block(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<BlockForm>,
+) -> Result<Response, Error> {
+    let (kind, on) = BlockKind::from_action(&form.action)
+        .ok_or_else(|| Error::BadRequest(format!("Invalid action: {}", form.action)))?;
+
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let target = Person::find_by_username(&form.username)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    BlockModel::set(&person.id, &target.id, kind, on).await?;
+
+    let next = response::local_path(form.next).unwrap_or_else(|| format!("/{}", target.username));
+    Ok(response::redirect(&next))
+}
+
+// -- Job Alerts --
+
+async fn render_alerts(
+    current_user: &SessionUser,
+    success: Option<String>,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let base = BaseContext::new()
+        .with_page("account")
+        .with_user(User::from_session_user(current_user).await);
+
+    let mut template = AccountAlertsTemplate::new(base);
+    template.searches = SavedSearchModel::list(&person.id).await?;
+    template.success = success;
+    template.error = error;
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render alerts template: {}", e);
+        Error::template(e.to_string())
+    })?;
+
+    Ok(Html(html).into_response())
+}
+
+async fn alerts_page(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Query(query): Query<AccountQuery>,
+) -> Result<Response, Error> {
+    render_alerts(&current_user, query.success, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct AlertForm {
+    action: String,
+    id: Option<String>,
+    query: Option<String>,
+    location: Option<String>,
+}
+
+/// Add or remove a saved job search
+async fn update_alerts(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<AlertForm>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let message = match form.action.as_str() {
+        "add" => {
+            let query = form.query.unwrap_or_default();
+            match SavedSearchModel::create(&person.id, &query, form.location.as_deref()).await {
+                Ok(()) => "Saved search added.",
+                Err(Error::Validation(message)) => {
+                    return render_alerts(&current_user, None, Some(message)).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        "delete" => {
+            let id = form
+                .id
+                .ok_or_else(|| Error::BadRequest("Missing saved search".to_string()))?;
+            SavedSearchModel::delete(&person.id, &id).await?;
+            "Saved search removed."
+        }
+        other => return Err(Error::BadRequest(format!("Invalid action: {}", other))),
+    };
+    Ok(response::redirect(&format!(
+        "/account/alerts?success={}",
+        urlencoding::encode(message)
+    )))
+}
+
+// -- Availability --
+
+/// Weeks shown on the availability calendar
+const CALENDAR_WEEKS: usize = 8;
+
+async fn render_availability(
+    current_user: &SessionUser,
+    success: Option<String>,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let base = BaseContext::new()
+        .with_page("account")
+        .with_user(User::from_session_user(current_user).await);
+
+    let today = chrono::Utc::now().date_naive();
+    let from = today.format("%Y-%m-%d").to_string();
+    let mut template = AccountAvailabilityTemplate::new(base);
+    template.periods = PersonAvailabilityModel::upcoming(&person.id, &from).await?;
+    template.bookings = OfferModel::upcoming_bookings(&person.id, &from).await?;
+    template.calendar = person_availability::calendar_weeks(
+        &template.periods,
+        &template.bookings,
+        today,
+        CALENDAR_WEEKS,
+    );
+    template.success = success;
+    template.error = error;
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render availability template: {}", e);
+        Error::template(e.to_string())
+    })?;
+
+    Ok(Html(html).into_response())
+}
+
+/// Upcoming unavailable ranges for a profile page, dates only. Failures are
+/// logged and leave the row off rather than breaking the page.
+pub(crate) async fn profile_unavailable_dates(person: &RecordId) -> Vec<String> {
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    match PersonAvailabilityModel::upcoming(person, &today).await {
+        Ok(periods) => periods.iter().map(|p| p.dates_label()).collect(),
+        Err(e) => {
+            error!(
+                "Failed to load availability for {}: {}",
+                person.display(),
+                e
+            );
+            Vec::new()
+        }
+    }
+}
+
+async fn availability_page(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Query(query): Query<AccountQuery>,
+) -> Result<Response, Error> {
+    render_availability(&current_user, query.success, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct AvailabilityForm {
+    action: String,
+    id: Option<String>,
+    #[serde(default)]
+    start_date: String,
+    #[serde(default)]
+    end_date: String,
+    #[serde(default)]
+    note: String,
+}
+
+/// Mark days unavailable, or clear a range
+async fn update_availability(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<AvailabilityForm>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let message = match form.action.as_str() {
+        "add" => {
+            match PersonAvailabilityModel::add(
+                &person.id,
+                &form.start_date,
+                &form.end_date,
+                &form.note,
+            )
+            .await
+            {
+                Ok(()) => "Marked as unavailable.",
+                Err(Error::Validation(message)) => {
+                    return render_availability(&current_user, None, Some(message)).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        "delete" => {
+            let id = form
+                .id
+                .ok_or_else(|| Error::BadRequest("Missing date range".to_string()))?;
+            PersonAvailabilityModel::remove(&person.id, &id).await?;
+            "Date range removed."
+        }
+        other => return Err(Error::BadRequest(format!("Invalid action: {}", other))),
+    };
+    Ok(response::redirect(&format!(
+        "/account/availability?success={}",
+        urlencoding::encode(message)
+    )))
+}
+
+// -- Representation --
+
+async fn render_representation(
+    current_user: &SessionUser,
+    success: Option<String>,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let base = BaseContext::new()
+        .with_page("account")
+        .with_user(User::from_session_user(current_user).await);
+
+    let mut template = AccountRepresentationTemplate::new(base);
+    template.representation = RepresentationModel::get(&person.id).await?;
+    template.contact_mode = RepresentationModel::contact_mode(&person.id).await?;
+    template.submissions = TalentSubmissionModel::for_talent(&person.id).await?;
+    template.success = success;
+    template.error = error;
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render representation template: {}", e);
+        Error::template(e.to_string())
+    })?;
+
+    Ok(Html(html).into_response())
+}
+
+async fn representation_page(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Query(query): Query<AccountQuery>,
+) -> Result<Response, Error> {
+    render_representation(&current_user, query.success, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct RepresentationForm {
+    action: String,
+    organization: Option<String>,
+    contact_mode: Option<String>,
+    submission: Option<String>,
+}
+
+/// Name a representative, choose how inquiries reach you, drop representation,
+/// or decide on a submission your agency made for you
+async fn update_representation(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<RepresentationForm>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let message = match form.action.as_str() {
+        "request" => {
+            let slug = form.organization.unwrap_or_default();
+            match RepresentationModel::request(&person.id, &person.get_display_name(), &slug).await
+            {
+                Ok(()) => "Request sent. Your representative will be asked to confirm.",
+                Err(Error::Validation(message)) => {
+                    return render_representation(&current_user, None, Some(message)).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        "contact_mode" => {
+            let mode = form
+                .contact_mode
+                .as_deref()
+                .and_then(ContactMode::parse)
+                .ok_or_else(|| Error::BadRequest("Invalid contact mode".to_string()))?;
+            RepresentationModel::set_contact_mode(&person.id, mode).await?;
+            info!(
+                "Contact mode for {} set to {}",
+                current_user.username,
+                mode.as_str()
+            );
+            "Contact preference updated."
+        }
+        "remove" => {
+            RepresentationModel::remove(&person.id).await?;
+            "Representation removed. People can contact you directly again."
+        }
+        "approve_submission" => {
+            let key = form.submission.unwrap_or_default();
+            let (job, role_title) = TalentSubmissionModel::approve(&person.id, &key).await?;
+            triggers::application_created(
+                &person.id.to_raw_string(),
+                &job.to_raw_string(),
+                &role_title,
+            );
+            info!("{} approved submission {}", current_user.username, key);
+            "Submission approved and sent to the poster as your application."
+        }
+        "decline_submission" => {
+            let key = form.submission.unwrap_or_default();
+            TalentSubmissionModel::decline(&person.id, &key).await?;
+            "Submission declined. The poster won't see it."
+        }
+        other => return Err(Error::BadRequest(format!("Invalid action: {}", other))),
+    };
+    Ok(response::redirect(&format!(
+        "/account/representation?success={}",
+        urlencoding::encode(message)
+    )))
+}
+
+// -- Minor Profile & Guardian --
+
+#[derive(Debug, Deserialize)]
+struct GuardianLinkForm {
+    guardian_username: String,
+}
+
+/// Name a guardian for a minor profile; the guardian then approves it
+async fn change_guardian(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<GuardianLinkForm>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    match minors::request_guardian(&person, &form.guardian_username).await {
+        Ok(()) => {}
+        Err(Error::Validation(msg)) => return render_settings_with_error(&current_user.id, &msg).await,
+        Err(e) => return Err(e),
+    }
+
+    render_settings_with_success(
+        &current_user.id,
+        "Guardian request sent. Your profile stays private until they approve it.",
+    )
+    .await
+}
+
+async fn guardian_page(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Query(query): Query<AccountQuery>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let base = BaseContext::new()
+        .with_page("account")
+        .with_user(User::from_session_user(&current_user).await);
+
+    let mut template = AccountGuardianTemplate::new(base);
+    template.wards = minors::wards(&person.id).await?;
+    template.success = query.success;
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render guardian template: {}", e);
+        Error::template(e.to_string())
+    })?;
+
+    Ok(Html(html).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct WardForm {
+    username: String,
+    action: String,
+}
+
+/// Approve, decline or release a minor who named the current person as guardian
+async fn update_ward(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<WardForm>,
+) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    minors::guardian_action(&person.id, &form.username, &form.action).await?;
+
+    let message = match form.action.as_str() {
+        "approve" => "Guardian link approved.",
+        "decline" => "Guardian link declined.",
+        _ => "Minor mode ended.",
+    };
+    Ok(response::redirect(&format!(
+        "/account/guardian?success={}",
+        urlencoding::encode(message)
+    )))
+}
+
 // -- Delete Account --
 
 #[derive(Debug, Deserialize)]
@@ -347,12 +1033,84 @@ async fn delete_account(
 
     let person_id_str = person.id.to_raw_string();
 
+    // ID documents live outside the database, so they go first
+    if let Err(e) = id_verification::forget(&person.id).await {
+        error!("Failed to delete verification requests for {}: {}", person.username, e);
+    }
+    if let Err(e) = org_claims::forget(&person.id).await {
+        error!("Failed to delete organization claims for {}: {}", person.username, e);
+    }
+    if let Err(e) = change_requests::forget(&person.id).await {
+        error!("Failed to delete suggestions for {}: {}", person.username, e);
+    }
+    if let Err(e) = SelfTapeModel::forget(&person.id).await {
+        error!("Failed to delete self-tapes for {}: {}", person.username, e);
+    }
+    if let Err(e) = uploads::forget(&person.id).await {
+        error!("Failed to cancel uploads for {}: {}", person.username, e);
+    }
+    if let Err(e) = transcode::forget(&person.id).await {
+        error!("Failed to delete video renditions for {}: {}", person.username, e);
+    }
+    if let Err(e) = AudioReelModel::forget(&person.id).await {
+        error!("Failed to delete audio reels for {}: {}", person.username, e);
+    }
+    if let Err(e) = PortfolioModel::forget(&person.id).await {
+        error!("Failed to delete portfolio exports for {}: {}", person.username, e);
+    }
+    if let Err(e) = ShortlistModel::forget(&person.id).await {
+        error!("Failed to delete shortlist entries for {}: {}", person.username, e);
+    }
+    if let Err(e) = OfferModel::forget(&person.id).await {
+        error!("Failed to delete offers for {}: {}", person.username, e);
+    }
+    if let Err(e) = PersonAvailabilityModel::forget(&person.id).await {
+        error!(
+            "Failed to delete availability for {}: {}",
+            person.username, e
+        );
+    }
+    if let Err(e) = whatsapp::forget(&person.id).await {
+        error!("Failed to delete WhatsApp messages for {}: {}", person.username, e);
+    }
+    if let Err(e) = login_security::forget(&person.id).await {
+        error!("Failed to delete sign-in history for {}: {}", person.username, e);
+    }
+    if let Err(e) = oauth::forget(&person.id).await {
+        error!("Failed to revoke OAuth apps and grants for {}: {}", person.username, e);
+    }
+    if let Err(e) = AnalyticsModel::forget(&person.id).await {
+        error!("Failed to delete profile views for {}: {}", person.username, e);
+    }
+
     // Delete related data: involvements, notifications, verification codes, org memberships
     let cleanup_sql = "
         DELETE FROM involvement WHERE in = $person_id;
         DELETE FROM notification WHERE person_id = $person_id;
         DELETE FROM verification_code WHERE person_id = $person_id;
         DELETE FROM member_of WHERE in = $person_id;
+        DELETE FROM consent WHERE person = $person_id;
+        DELETE FROM blocks WHERE in = $person_id OR out = $person_id;
+        DELETE FROM mutes WHERE in = $person_id OR out = $person_id;
+        DELETE FROM reacts WHERE in = $person_id;
+        DELETE FROM timecard WHERE person = $person_id;
+        DELETE FROM house_rules_ack WHERE person = $person_id;
+        DELETE FROM crew_deal WHERE person = $person_id;
+        DELETE FROM represented_by WHERE in = $person_id;
+        UPDATE represented_by SET contact = NONE WHERE contact = $person_id;
+        DELETE FROM talent_submission WHERE talent = $person_id;
+        UPDATE talent_submission SET submitted_by = NONE WHERE submitted_by = $person_id;
+        DELETE FROM saved_search WHERE person = $person_id;
+        DELETE FROM search_history WHERE person = $person_id;
+        DELETE FROM search_alert WHERE person = $person_id;
+        UPDATE feedback SET person = NONE WHERE person = $person_id;
+        UPDATE feedback SET triaged_by = NONE WHERE triaged_by = $person_id;
+        DELETE FROM match_suggestion WHERE person = $person_id;
+        DELETE FROM guest_link WHERE created_by = $person_id;
+        UPDATE scouting_collection SET created_by = NONE WHERE created_by = $person_id;
+        UPDATE scouting_entry SET added_by = NONE WHERE added_by = $person_id;
+        DELETE FROM webhook_subscription WHERE person = $person_id;
+        UPDATE person SET guardian = NONE, guardian_approved = false WHERE guardian = $person_id;
     ";
     if let Err(e) = DB
         .query(cleanup_sql)
@@ -379,13 +1137,50 @@ async fn delete_account(
         .path("/")
         .same_site(SameSite::Lax)
         .http_only(true)
-        .secure(env::var("COOKIE_SECURE").unwrap_or_else(|_| "true".to_string()) != "false")
+        .secure(crate::config::cookie_secure())
         .max_age(Default::default())
         .build();
 
     Ok((CookieJar::new().remove(cookie), response::redirect("/")).into_response())
 }
 
+// -- Data Export --
+
+/// Download everything stored about the account as JSON, including the history
+/// of accepted terms and privacy policy versions. The history is read from the
+/// database as the body is sent.
+async fn export_account(AuthenticatedUser(current_user): AuthenticatedUser) -> Result<Response, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let head = serde_json::json!({
+        "exported_at": chrono::Utc::now(),
+        "account": {
+            "id": person.id.to_raw_string(),
+            "username": person.username,
+            "email": person.email,
+            "name": person.name,
+            "verification_status": person.verification_status,
+            "messaging_preference": person.messaging_preference,
+            "units": person.units,
+        },
+        "profile": person.profile,
+    });
+
+    info!("Account data exported: {}", current_user.username);
+
+    let terms_acceptance = consent::history(person.id);
+    Ok((
+        [(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"slatehub-{}.json\"", current_user.username),
+        )],
+        response::json_stream(head, Some("terms_acceptance"), terms_acceptance),
+    )
+        .into_response())
+}
+
 // -- Helpers --
 
 async fn render_settings_with_error(person_id: &str, error_msg: &str) -> Result<Response, Error> {
@@ -398,7 +1193,16 @@ async fn render_settings_with_error(person_id: &str, error_msg: &str) -> Result<
     template.username = person.username;
     template.email = person.email;
     template.messaging_preference = person.messaging_preference;
+    template.units = UnitSystem::from_preference(person.units.as_deref()).as_str();
     template.show_contact_info = person.profile.as_ref().map(|p| p.is_public).unwrap_or(false);
+    template.set_visibility(&person.visibility);
+    template.search_history_enabled = SearchHistoryModel::is_enabled(&person.id)
+        .await
+        .unwrap_or(true);
+    template.set_digest(digest::get_preference(&person.id).await.unwrap_or_default());
+    template.set_whatsapp(whatsapp::get_preference(&person.id).await.unwrap_or_default());
+    template.set_badge(availability_badge::get_token(&person.id).await.unwrap_or_default());
+    template.minor = minors::get_link(&person.id).await.unwrap_or_default();
     template.error = Some(error_msg.to_string());
 
     let html = template.render().map_err(|e| {
@@ -427,7 +1231,16 @@ async fn render_settings_with_success(
     template.username = person.username;
     template.email = person.email;
     template.messaging_preference = person.messaging_preference;
+    template.units = UnitSystem::from_preference(person.units.as_deref()).as_str();
     template.show_contact_info = person.profile.as_ref().map(|p| p.is_public).unwrap_or(false);
+    template.set_visibility(&person.visibility);
+    template.search_history_enabled = SearchHistoryModel::is_enabled(&person.id)
+        .await
+        .unwrap_or(true);
+    template.set_digest(digest::get_preference(&person.id).await.unwrap_or_default());
+    template.set_whatsapp(whatsapp::get_preference(&person.id).await.unwrap_or_default());
+    template.set_badge(availability_badge::get_token(&person.id).await.unwrap_or_default());
+    template.minor = minors::get_link(&person.id).await.unwrap_or_default();
     template.success = Some(success_msg.to_string());
 
     let html = template.render().map_err(|e| {
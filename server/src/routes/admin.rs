@@ -2,9 +2,10 @@ use askama::Template;
 use axum::{
     Router,
     extract::{Path, Query},
-    response::{Html, IntoResponse, Redirect},
+    response::{Html, IntoResponse, Redirect, Response},
     routing::{get, post},
 };
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use serde::Deserialize;
 use std::sync::atomic::{AtomicBool, Ordering};
 use surrealdb::types::SurrealValue;
@@ -14,10 +15,22 @@ use crate::{
     db::DB,
     error::Error,
     middleware::AuthenticatedUser,
-    models::person::SessionUser,
+    models::{
+        audio_reel::AudioReelModel,
+        feedback::{self, FeedbackModel},
+        legal_hold::LegalHoldModel,
+        offer::OfferModel,
+        person::{Person, SessionUser},
+        portfolio::PortfolioModel,
+        selftape::SelfTapeModel,
+        shortlist::ShortlistModel,
+    },
     record_id_ext::RecordIdExt,
-    services::s3::s3,
-    templates::{BaseContext, User},
+    services::{
+        change_requests, demo_mode, feedback as feedback_service, id_verification, impersonation,
+        login_security::ClientInfo, org_claims, s3::s3, transcode, uploads, whatsapp,
+    },
+    templates::{BaseContext, SelectOption, User},
 };
 
 mod filters {
@@ -41,6 +54,15 @@ async fn require_admin(user: &SessionUser) -> Result<User, Error> {
     Ok(template_user)
 }
 
+/// Admin of the install itself, for pages that affect every tenant (tenants,
+/// config, scheduled tasks). Tenant admins get a 403.
+async fn require_install_admin(user: &SessionUser) -> Result<User, Error> {
+    if crate::db::current_tenant().is_some() {
+        return Err(Error::Forbidden);
+    }
+    require_admin(user).await
+}
+
 // ============================
 // Templates
 // ============================
@@ -56,6 +78,8 @@ struct AdminDashboardTemplate {
     stats: AdminStats,
     embedding_rebuild_in_progress: bool,
     build_info: String,
+    /// Whether this browser has demo mode on
+    demo_mode: bool,
 }
 
 struct AdminStats {
@@ -68,6 +92,7 @@ struct AdminStats {
     top_pages: Vec<crate::models::activity::PageStat>,
     daily_activity: Vec<crate::models::activity::DayStat>,
     event_counts: Vec<(String, u64)>,
+    completeness: crate::models::completeness::CompletenessStats,
 }
 
 #[derive(Template)]
@@ -79,16 +104,74 @@ struct AdminFeedbackTemplate {
     active_page: String,
     user: Option<User>,
     feedback_items: Vec<FeedbackItem>,
+    /// The status shown, or empty for all
+    status: String,
+    status_tabs: Vec<FeedbackStatusTab>,
+    /// Whether a webhook is configured to forward feedback to
+    can_forward: bool,
+    max_note: usize,
+}
+
+struct FeedbackStatusTab {
+    value: String,
+    label: String,
+    count: u64,
 }
 
 struct FeedbackItem {
     id: String,
     username: String,
+    is_bug: bool,
+    kind_label: String,
     page_url: String,
     message: String,
+    request_ids: Vec<String>,
+    user_agent: String,
+    status_label: String,
+    status_options: Vec<SelectOption>,
+    note: String,
+    /// When and by whom it was last triaged
+    triaged: Option<String>,
+    forwarded: Option<String>,
+    issue_url: Option<String>,
+    forward_error: Option<String>,
     created_at: String,
 }
 
+#[derive(Template)]
+#[template(path = "admin/verifications.html")]
+struct AdminVerificationsTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    requests: Vec<crate::services::id_verification::PendingReview>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/claims.html")]
+struct AdminClaimsTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    claims: Vec<org_claims::PendingClaim>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/suggestions.html")]
+struct AdminSuggestionsTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    suggestions: Vec<change_requests::PendingChange>,
+    error: Option<String>,
+}
+
 #[derive(Template)]
 #[template(path = "admin/people.html")]
 struct AdminPeopleTemplate {
@@ -108,9 +191,53 @@ struct PersonRow {
     name: Option<String>,
     is_admin: bool,
     verification_status: String,
+    plan: String,
     created_at: String,
 }
 
+#[derive(Template)]
+#[template(path = "admin/impersonation.html")]
+struct AdminImpersonationTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    sessions: Vec<ImpersonationRow>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/impersonation_session.html")]
+struct AdminImpersonationSessionTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    session: ImpersonationRow,
+    actions: Vec<ImpersonationActionRow>,
+}
+
+struct ImpersonationRow {
+    id: String,
+    admin_username: String,
+    person_username: String,
+    reason: String,
+    ip: String,
+    user_agent: String,
+    started_at: String,
+    /// When it ended, or empty while it is still running
+    ended_at: String,
+    action_count: i64,
+}
+
+struct ImpersonationActionRow {
+    time: String,
+    method: String,
+    path: String,
+    status: i64,
+}
+
 #[derive(Template)]
 #[template(path = "admin/productions.html")]
 struct AdminProductionsTemplate {
@@ -175,6 +302,117 @@ struct LocationRow {
     created_at: String,
 }
 
+#[derive(Template)]
+#[template(path = "admin/tasks.html")]
+struct AdminTasksTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    tasks: Vec<TaskRow>,
+}
+
+struct TaskRow {
+    name: String,
+    description: String,
+    interval: String,
+    last_run: String,
+    last_duration: String,
+    last_error: String,
+    next_run: String,
+    run_count: u64,
+    failure_count: u64,
+    running: bool,
+}
+
+#[derive(Template)]
+#[template(path = "admin/flags.html")]
+struct AdminFlagsTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    flags: Vec<FlagRow>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/experiments.html")]
+struct AdminExperimentsTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    experiments: Vec<ExperimentRow>,
+    days: i64,
+}
+
+struct ExperimentRow {
+    name: String,
+    description: String,
+    /// "on (25%)", "off" or "no flag"
+    audience: String,
+    variants: Vec<VariantRow>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/config.html")]
+struct AdminConfigTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    settings: Vec<crate::settings::SettingView>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/status.html")]
+struct AdminStatusTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    notices: Vec<crate::services::status::Notice>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/tenants.html")]
+struct AdminTenantsTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    enabled: bool,
+    tenants: Vec<crate::services::tenants::Tenant>,
+}
+
+struct VariantRow {
+    name: String,
+    searches: i64,
+    clicked_searches: i64,
+    clicks: i64,
+    ctr: String,
+    clicks_per_search: String,
+    /// Lift and z-score against the control; empty for the control itself
+    lift: String,
+    z_score: String,
+    significant: bool,
+}
+
+struct FlagRow {
+    name: String,
+    description: String,
+    enabled: bool,
+    rollout_percent: u8,
+    enabled_orgs: Vec<String>,
+    disabled_orgs: Vec<String>,
+}
+
 // ============================
 // Router
 // ============================
@@ -183,12 +421,28 @@ pub fn router() -> Router {
     Router::new()
         .route("/admin", get(dashboard))
         .route("/admin/feedback", get(list_feedback))
+        .route("/admin/feedback/{id}/status", post(triage_feedback))
+        .route("/admin/feedback/{id}/forward", post(forward_feedback))
         .route("/admin/feedback/{id}/delete", post(delete_feedback))
+        .route("/admin/verifications", get(list_verifications))
+        .route("/admin/verifications/{id}/document", get(verification_document))
+        .route("/admin/verifications/{id}/review", post(review_verification))
+        .route("/admin/claims", get(list_claims))
+        .route("/admin/claims/{id}/document", get(claim_document))
+        .route("/admin/claims/{id}/review", post(review_claim))
+        .route("/admin/suggestions", get(list_suggestions))
+        .route("/admin/suggestions/{id}/review", post(review_suggestion))
         .route("/admin/people", get(list_people))
         .route("/admin/people/{id}/delete", post(delete_person))
         .route("/admin/people/{id}/toggle-admin", post(toggle_admin))
         .route("/admin/people/{id}/reset-password", post(admin_reset_password))
         .route("/admin/people/{id}/verification", post(update_verification))
+        .route("/admin/people/{id}/plan", post(update_plan))
+        .route("/admin/people/{id}/impersonate", post(impersonate_person))
+        .route("/admin/impersonation", get(list_impersonation))
+        .route("/admin/impersonation/{id}", get(impersonation_session))
+        .route("/impersonation/exit", post(exit_impersonation))
+        .route("/admin/demo-mode", post(set_demo_mode))
         .route("/admin/productions", get(list_productions))
         .route("/admin/productions/{id}/delete", post(delete_production))
         .route("/admin/organizations", get(list_organizations))
@@ -200,6 +454,19 @@ pub fn router() -> Router {
         .route("/admin/backup", post(backup_all))
         .route("/admin/cleanup-files", get(preview_orphaned_files))
         .route("/admin/cleanup-files", post(cleanup_orphaned_files))
+        .route("/admin/tasks", get(list_tasks))
+        .route("/admin/tasks/{name}/run", post(run_task))
+        .route("/admin/flags", get(list_flags).post(create_flag))
+        .route("/admin/flags/{name}", post(update_flag))
+        .route("/admin/flags/{name}/override", post(override_flag))
+        .route("/admin/flags/{name}/delete", post(delete_flag))
+        .route("/admin/experiments", get(list_experiments))
+        .route("/admin/config", get(list_config))
+        .route("/admin/config/{key}", post(update_config))
+        .route("/admin/status", get(list_status_notices).post(post_status_notice))
+        .route("/admin/status/{id}/resolve", post(resolve_status_notice))
+        .route("/admin/tenants", get(list_tenants).post(create_tenant))
+        .route("/admin/tenants/{slug}", post(update_tenant))
 }
 
 // ============================
@@ -208,6 +475,7 @@ pub fn router() -> Router {
 
 async fn dashboard(
     AuthenticatedUser(user): AuthenticatedUser,
+    jar: CookieJar,
 ) -> Result<Html<String>, Error> {
     let template_user = require_admin(&user).await?;
 
@@ -215,7 +483,7 @@ async fn dashboard(
 
     // Run all queries in parallel
     let (person_count, production_count, location_count, organization_count, feedback_count,
-         engagement, top_pages, daily_activity, event_counts) = tokio::join!(
+         engagement, top_pages, daily_activity, event_counts, completeness) = tokio::join!(
         count_table("person"),
         count_table("production"),
         count_table("location"),
@@ -225,6 +493,7 @@ async fn dashboard(
         ActivityModel::top_pages(10),
         ActivityModel::daily_activity(30),
         ActivityModel::event_counts(),
+        crate::models::completeness::CompletenessModel::stats(),
     );
 
     let stats = AdminStats {
@@ -237,6 +506,10 @@ async fn dashboard(
         top_pages,
         daily_activity,
         event_counts,
+        completeness: completeness.unwrap_or_else(|e| {
+            error!("Failed to load profile completeness stats: {}", e);
+            Default::default()
+        }),
     };
 
     let base = BaseContext::new()
@@ -252,6 +525,7 @@ async fn dashboard(
         stats,
         embedding_rebuild_in_progress: REBUILD_IN_PROGRESS.load(Ordering::Relaxed),
         build_info: format!("v{}", crate::version::VERSION),
+        demo_mode: jar.get(demo_mode::COOKIE).is_some_and(|c| c.value() == "1"),
     };
 
     Ok(Html(template.render().map_err(|e| {
@@ -262,38 +536,70 @@ async fn dashboard(
 
 // -- Feedback --
 
+#[derive(Deserialize)]
+struct FeedbackParams {
+    status: Option<String>,
+}
+
 async fn list_feedback(
     AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<FeedbackParams>,
 ) -> Result<Html<String>, Error> {
     let template_user = require_admin(&user).await?;
 
-    #[derive(Debug, Deserialize, surrealdb::types::SurrealValue)]
-    struct FeedbackRow {
-        id: surrealdb::types::RecordId,
-        username: String,
-        page_url: String,
-        message: String,
-        created_at: chrono::DateTime<chrono::Utc>,
-    }
-
-    let items: Vec<FeedbackRow> = DB
-        .query("SELECT * FROM feedback ORDER BY created_at DESC LIMIT 100")
-        .await
-        .map_err(|e| Error::Database(e.to_string()))?
-        .take(0)
-        .unwrap_or_default();
+    let status = params
+        .status
+        .as_deref()
+        .and_then(|status| feedback::parse_status(status).ok());
+    let items = FeedbackModel::list(status, 100).await?;
+    let counts = FeedbackModel::status_counts().await?;
 
     let feedback_items: Vec<FeedbackItem> = items
         .into_iter()
         .map(|f| FeedbackItem {
             id: f.id.key_string(),
             username: f.username,
+            is_bug: f.kind == "bug",
+            kind_label: feedback::kind_label(&f.kind).to_string(),
             page_url: f.page_url,
             message: f.message,
+            request_ids: f.request_ids,
+            user_agent: f.user_agent.unwrap_or_default(),
+            status_label: feedback::status_label(&f.status).to_string(),
+            status_options: feedback::STATUSES
+                .iter()
+                .map(|(value, label)| {
+                    SelectOption::new(value, label.to_string(), *value == f.status)
+                })
+                .collect(),
+            note: f.note.unwrap_or_default(),
+            triaged: f.triaged_at.map(|at| {
+                format!(
+                    "{} by {}",
+                    at.format("%b %d, %Y %H:%M"),
+                    f.triaged_by_name.as_deref().unwrap_or("a former admin")
+                )
+            }),
+            forwarded: f
+                .forwarded_at
+                .map(|at| at.format("%b %d, %Y %H:%M").to_string()),
+            issue_url: f.issue_url,
+            forward_error: f.forward_error,
             created_at: f.created_at.format("%b %d, %Y %H:%M").to_string(),
         })
         .collect();
 
+    let mut status_tabs = vec![FeedbackStatusTab {
+        value: String::new(),
+        label: "All".to_string(),
+        count: counts.iter().map(|(_, count)| count).sum(),
+    }];
+    status_tabs.extend(counts.into_iter().map(|(value, count)| FeedbackStatusTab {
+        value: value.to_string(),
+        label: feedback::status_label(value).to_string(),
+        count,
+    }));
+
     let base = BaseContext::new()
         .with_page("admin")
         .with_user(template_user);
@@ -305,6 +611,10 @@ async fn list_feedback(
         active_page: base.active_page,
         user: base.user,
         feedback_items,
+        status: status.unwrap_or_default().to_string(),
+        status_tabs,
+        can_forward: feedback_service::webhook_url().is_some(),
+        max_note: feedback::MAX_NOTE_CHARS,
     };
 
     Ok(Html(template.render().map_err(|e| {
@@ -313,6 +623,66 @@ async fn list_feedback(
     })?))
 }
 
+/// Back to the feedback list, on the status tab the admin was on
+fn feedback_list_url(filter: Option<&str>) -> String {
+    match filter.and_then(|status| feedback::parse_status(status).ok()) {
+        Some(status) => format!("/admin/feedback?status={}", status),
+        None => "/admin/feedback".to_string(),
+    }
+}
+
+#[derive(Deserialize)]
+struct FeedbackTriageForm {
+    status: String,
+    #[serde(default)]
+    note: String,
+    filter: Option<String>,
+}
+
+async fn triage_feedback(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    axum::Form(form): axum::Form<FeedbackTriageForm>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    let status = feedback::parse_status(&form.status)?;
+    let note = feedback::parse_note(&form.note)?;
+    let admin = surrealdb::types::RecordId::parse_simple(&user.id)
+        .map_err(|e| Error::BadRequest(e.to_string()))?;
+    FeedbackModel::set_status(&id, status, note, &admin).await?;
+
+    info!("Admin {} marked feedback {} {}", user.username, id, status);
+    Ok(Redirect::to(&feedback_list_url(form.filter.as_deref())))
+}
+
+#[derive(Deserialize)]
+struct FeedbackForwardForm {
+    filter: Option<String>,
+}
+
+async fn forward_feedback(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    axum::Form(form): axum::Form<FeedbackForwardForm>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    let item = FeedbackModel::get(&id).await?;
+    // A failure is kept on the feedback and shown in the list
+    let outcome = feedback_service::forward(&item).await;
+    FeedbackModel::record_forward(&item.id, &outcome).await?;
+
+    match &outcome {
+        Ok(_) => info!("Admin {} forwarded feedback {}", user.username, id),
+        Err(e) => warn!(
+            "Admin {} failed to forward feedback {}: {}",
+            user.username, id, e
+        ),
+    }
+    Ok(Redirect::to(&feedback_list_url(form.filter.as_deref())))
+}
+
 async fn delete_feedback(
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<String>,
@@ -340,298 +710,239 @@ async fn delete_feedback(
     Ok(Redirect::to("/admin/feedback"))
 }
 
-// -- People --
-
-#[derive(Deserialize)]
-struct SearchParams {
-    q: Option<String>,
-}
-
-async fn list_people(
+async fn list_verifications(
     AuthenticatedUser(user): AuthenticatedUser,
-    Query(params): Query<SearchParams>,
 ) -> Result<Html<String>, Error> {
     let template_user = require_admin(&user).await?;
 
-    let search = params.q.clone().unwrap_or_default();
-
-    #[derive(Debug, Deserialize, surrealdb::types::SurrealValue)]
-    struct PRow {
-        id: surrealdb::types::RecordId,
-        username: String,
-        email: String,
-        name: Option<String>,
-        is_admin: Option<bool>,
-        verification_status: String,
-        created_at: Option<chrono::DateTime<chrono::Utc>>,
-    }
-
-    let people: Vec<PRow> = if search.is_empty() {
-        DB.query("SELECT id, username, email, name, is_admin, verification_status, created_at FROM person ORDER BY created_at DESC LIMIT 50")
-            .await
-            .map_err(|e| Error::Database(e.to_string()))?
-            .take(0)
-            .unwrap_or_default()
-    } else {
-        let q = search.to_lowercase();
-        DB.query("SELECT id, username, email, name, is_admin, verification_status, created_at FROM person WHERE string::lowercase(username) CONTAINS $q OR string::lowercase(email) CONTAINS $q OR string::lowercase(name ?? '') CONTAINS $q ORDER BY created_at DESC LIMIT 50")
-            .bind(("q", q))
-            .await
-            .map_err(|e| Error::Database(e.to_string()))?
-            .take(0)
-            .unwrap_or_default()
-    };
-
-    let people: Vec<PersonRow> = people
-        .into_iter()
-        .map(|p| PersonRow {
-            id: p.id.key_string(),
-            username: p.username,
-            email: p.email,
-            name: p.name,
-            is_admin: p.is_admin.unwrap_or(false),
-            verification_status: p.verification_status,
-            created_at: p.created_at
-                .map(|d| d.format("%b %d, %Y").to_string())
-                .unwrap_or_default(),
-        })
-        .collect();
+    let requests = id_verification::pending_reviews().await?;
 
     let base = BaseContext::new()
         .with_page("admin")
         .with_user(template_user);
 
-    let template = AdminPeopleTemplate {
+    let template = AdminVerificationsTemplate {
         app_name: base.app_name,
         year: base.year,
         version: base.version,
         active_page: base.active_page,
         user: base.user,
-        people,
-        search_query: search,
+        requests,
     };
 
     Ok(Html(template.render().map_err(|e| {
-        error!("Failed to render admin people: {}", e);
+        error!("Failed to render admin verifications: {}", e);
         Error::template(e.to_string())
     })?))
 }
 
-async fn delete_person(
+/// Stream a pending ID document to the reviewing admin. Never cached.
+async fn verification_document(
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<String>,
-) -> Result<Redirect, Error> {
+) -> Result<axum::response::Response, Error> {
     require_admin(&user).await?;
 
-    let record_id = surrealdb::types::RecordId::new("person", id.as_str());
-
-    // Don't allow deleting yourself
-    let self_key = if user.id.starts_with("person:") {
-        user.id.strip_prefix("person:").unwrap_or(&user.id).to_string()
-    } else {
-        user.id.clone()
-    };
-    if id == self_key {
-        return Err(Error::BadRequest("Cannot delete your own account from admin".to_string()));
-    }
+    let (data, content_type) = id_verification::document(&id).await?;
+    info!("Admin {} viewed verification document {}", user.username, id);
 
-    // Clean up related data then delete
-    DB.query("DELETE FROM involvement WHERE in = $pid; DELETE FROM notification WHERE person_id = $pid; DELETE FROM member_of WHERE in = $pid; DELETE $pid")
-        .bind(("pid", record_id))
-        .await
-        .map_err(|e| Error::Database(e.to_string()))?;
+    axum::response::Response::builder()
+        .header(axum::http::header::CONTENT_TYPE, content_type)
+        .header(axum::http::header::CACHE_CONTROL, "no-store")
+        .header(axum::http::header::CONTENT_DISPOSITION, "inline")
+        .body(axum::body::Body::from(data))
+        .map_err(|e| Error::Internal(format!("Failed to build response: {}", e)))
+}
 
-    info!("Admin {} deleted person {}", user.username, id);
-    Ok(Redirect::to("/admin/people"))
+#[derive(Debug, Deserialize)]
+struct ReviewForm {
+    decision: String,
+    note: Option<String>,
 }
 
-async fn toggle_admin(
+async fn review_verification(
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<String>,
+    axum::Form(form): axum::Form<ReviewForm>,
 ) -> Result<Redirect, Error> {
     require_admin(&user).await?;
 
-    let record_id = surrealdb::types::RecordId::new("person", id.as_str());
-
-    // Don't allow toggling your own admin status
-    let self_key = if user.id.starts_with("person:") {
-        user.id.strip_prefix("person:").unwrap_or(&user.id).to_string()
+    let approve = match form.decision.as_str() {
+        "approve" => true,
+        "reject" => false,
+        other => return Err(Error::BadRequest(format!("Invalid decision: {}", other))),
+    };
+    let reviewer = if user.id.starts_with("person:") {
+        surrealdb::types::RecordId::parse_simple(&user.id)
+            .map_err(|e| Error::BadRequest(e.to_string()))?
     } else {
-        user.id.clone()
+        surrealdb::types::RecordId::new("person", user.id.as_str())
     };
-    if id == self_key {
-        return Err(Error::BadRequest("Cannot change your own admin status".to_string()));
-    }
-
-    DB.query("UPDATE $pid SET is_admin = !is_admin")
-        .bind(("pid", record_id))
-        .await
-        .map_err(|e| Error::Database(e.to_string()))?;
 
-    info!("Admin {} toggled admin for {}", user.username, id);
-    Ok(Redirect::to("/admin/people"))
-}
+    id_verification::review(&id, &reviewer, approve, form.note).await?;
 
-#[derive(Deserialize)]
-struct AdminResetPasswordForm {
-    new_password: String,
+    info!(
+        "Admin {} {} verification request {}",
+        user.username,
+        if approve { "approved" } else { "rejected" },
+        id
+    );
+    Ok(Redirect::to("/admin/verifications"))
 }
 
-async fn admin_reset_password(
+async fn list_claims(
     AuthenticatedUser(user): AuthenticatedUser,
-    Path(id): Path<String>,
-    axum::Form(form): axum::Form<AdminResetPasswordForm>,
-) -> Result<Redirect, Error> {
-    require_admin(&user).await?;
+) -> Result<Html<String>, Error> {
+    let template_user = require_admin(&user).await?;
 
-    if form.new_password.len() < 8 {
-        return Err(Error::BadRequest("Password must be at least 8 characters".to_string()));
-    }
-
-    let record_id = surrealdb::types::RecordId::new("person", id.as_str());
-    let password_hash = crate::auth::hash_password(&form.new_password)?;
+    let claims = org_claims::pending_reviews().await?;
 
-    DB.query("UPDATE $pid SET password = $password")
-        .bind(("pid", record_id))
-        .bind(("password", password_hash))
-        .await
-        .map_err(|e| Error::Database(e.to_string()))?;
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
 
-    info!("Admin {} reset password for person:{}", user.username, id);
-    Ok(Redirect::to("/admin/people"))
-}
+    let template = AdminClaimsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        claims,
+    };
 
-#[derive(Deserialize)]
-struct VerificationForm {
-    status: String,
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin claims: {}", e);
+        Error::template(e.to_string())
+    })?))
 }
 
-async fn update_verification(
+/// Stream a pending claim's ownership document to the reviewing admin. Never cached.
+async fn claim_document(
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<String>,
-    axum::extract::Form(form): axum::extract::Form<VerificationForm>,
-) -> Result<Redirect, Error> {
+) -> Result<axum::response::Response, Error> {
     require_admin(&user).await?;
 
-    let valid_statuses = ["unverified", "email", "sms", "identity"];
-    if !valid_statuses.contains(&form.status.as_str()) {
-        return Err(Error::BadRequest(format!("Invalid verification status: {}", form.status)));
-    }
+    let (data, content_type) = org_claims::document(&id).await?;
+    info!("Admin {} viewed organization claim document {}", user.username, id);
 
-    let record_id = surrealdb::types::RecordId::new("person", id.as_str());
+    axum::response::Response::builder()
+        .header(axum::http::header::CONTENT_TYPE, content_type)
+        .header(axum::http::header::CACHE_CONTROL, "no-store")
+        .header(axum::http::header::CONTENT_DISPOSITION, "inline")
+        .body(axum::body::Body::from(data))
+        .map_err(|e| Error::Internal(format!("Failed to build response: {}", e)))
+}
 
-    info!("Updating verification_status for person:{} to '{}' (RecordId: {:?})", id, form.status, record_id);
+async fn review_claim(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    axum::Form(form): axum::Form<ReviewForm>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
 
-    let response = DB.query("UPDATE $pid SET verification_status = $status")
-        .bind(("pid", record_id))
-        .bind(("status", form.status.clone()))
-        .await
-        .map_err(|e| {
-            error!("Verification update query failed: {}", e);
-            Error::Database(e.to_string())
-        })?;
+    let approve = match form.decision.as_str() {
+        "approve" => true,
+        "reject" => false,
+        other => return Err(Error::BadRequest(format!("Invalid decision: {}", other))),
+    };
+    let reviewer = if user.id.starts_with("person:") {
+        surrealdb::types::RecordId::parse_simple(&user.id)
+            .map_err(|e| Error::BadRequest(e.to_string()))?
+    } else {
+        surrealdb::types::RecordId::new("person", user.id.as_str())
+    };
 
-    // Check for statement-level errors in the response
-    match response.check() {
-        Ok(_) => info!("Admin {} set verification_status to '{}' for person:{}", user.username, form.status, id),
-        Err(e) => {
-            error!("Verification update statement error: {}", e);
-            return Err(Error::Database(e.to_string()));
-        }
-    }
+    org_claims::review(&id, &reviewer, approve, form.note).await?;
 
-    Ok(Redirect::to("/admin/people"))
+    info!(
+        "Admin {} {} organization claim {}",
+        user.username,
+        if approve { "approved" } else { "rejected" },
+        id
+    );
+    Ok(Redirect::to("/admin/claims"))
 }
 
-// -- Productions --
+#[derive(Deserialize)]
+struct SuggestionsQuery {
+    error: Option<String>,
+}
 
-async fn list_productions(
+async fn list_suggestions(
     AuthenticatedUser(user): AuthenticatedUser,
-    Query(params): Query<SearchParams>,
+    Query(query): Query<SuggestionsQuery>,
 ) -> Result<Html<String>, Error> {
     let template_user = require_admin(&user).await?;
 
-    let search = params.q.clone().unwrap_or_default();
-
-    #[derive(Debug, Deserialize, surrealdb::types::SurrealValue)]
-    struct ProdRow {
-        id: surrealdb::types::RecordId,
-        title: String,
-        slug: String,
-        production_type: String,
-        status: String,
-        created_at: chrono::DateTime<chrono::Utc>,
-    }
-
-    let productions: Vec<ProdRow> = if search.is_empty() {
-        DB.query("SELECT id, title, slug, type AS production_type, status, created_at FROM production ORDER BY created_at DESC LIMIT 50")
-            .await
-            .map_err(|e| Error::Database(e.to_string()))?
-            .take(0)
-            .unwrap_or_default()
-    } else {
-        let q = search.to_lowercase();
-        DB.query("SELECT id, title, slug, type AS production_type, status, created_at FROM production WHERE string::lowercase(title) CONTAINS $q OR string::lowercase(slug) CONTAINS $q ORDER BY created_at DESC LIMIT 50")
-            .bind(("q", q))
-            .await
-            .map_err(|e| Error::Database(e.to_string()))?
-            .take(0)
-            .unwrap_or_default()
-    };
-
-    let productions: Vec<ProductionRow> = productions
-        .into_iter()
-        .map(|p| ProductionRow {
-            id: p.id.key_string(),
-            title: p.title,
-            slug: p.slug,
-            production_type: p.production_type,
-            status: p.status,
-            created_at: p.created_at.format("%b %d, %Y").to_string(),
-        })
-        .collect();
+    let suggestions = change_requests::pending_reviews().await?;
 
     let base = BaseContext::new()
         .with_page("admin")
         .with_user(template_user);
 
-    let template = AdminProductionsTemplate {
+    let template = AdminSuggestionsTemplate {
         app_name: base.app_name,
         year: base.year,
         version: base.version,
         active_page: base.active_page,
         user: base.user,
-        productions,
-        search_query: search,
+        suggestions,
+        error: query.error,
     };
 
     Ok(Html(template.render().map_err(|e| {
-        error!("Failed to render admin productions: {}", e);
+        error!("Failed to render admin suggestions: {}", e);
         Error::template(e.to_string())
     })?))
 }
 
-async fn delete_production(
+/// Apply or turn down a suggested edit. One that can no longer be applied
+/// (its name was taken or its entry removed since) stays in the queue with
+/// the reason shown.
+async fn review_suggestion(
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<String>,
+    axum::Form(form): axum::Form<ReviewForm>,
 ) -> Result<Redirect, Error> {
     require_admin(&user).await?;
 
-    let record_id = surrealdb::types::RecordId::new("production", id.as_str());
-
-    // Clean up involvements then delete
-    DB.query("DELETE FROM involvement WHERE out = $pid; DELETE FROM member_of WHERE out = $pid; DELETE $pid")
-        .bind(("pid", record_id))
-        .await
-        .map_err(|e| Error::Database(e.to_string()))?;
+    let approve = match form.decision.as_str() {
+        "approve" => true,
+        "reject" => false,
+        other => return Err(Error::BadRequest(format!("Invalid decision: {}", other))),
+    };
+    let reviewer = surrealdb::types::RecordId::parse_simple(&user.id)
+        .map_err(|e| Error::BadRequest(e.to_string()))?;
+
+    match change_requests::review(&id, &reviewer, approve, form.note).await {
+        Ok(()) => {}
+        Err(Error::Conflict(msg)) => {
+            return Ok(Redirect::to(&format!(
+                "/admin/suggestions?error={}",
+                urlencoding::encode(&msg)
+            )));
+        }
+        Err(e) => return Err(e),
+    }
 
-    info!("Admin {} deleted production {}", user.username, id);
-    Ok(Redirect::to("/admin/productions"))
+    info!(
+        "Admin {} {} suggestion {}",
+        user.username,
+        if approve { "applied" } else { "rejected" },
+        id
+    );
+    Ok(Redirect::to("/admin/suggestions"))
 }
 
-// -- Organizations --
+// -- People --
 
-async fn list_organizations(
+#[derive(Deserialize)]
+struct SearchParams {
+    q: Option<String>,
+}
+
+async fn list_people(
     AuthenticatedUser(user): AuthenticatedUser,
     Query(params): Query<SearchParams>,
 ) -> Result<Html<String>, Error> {
@@ -640,27 +951,26 @@ async fn list_organizations(
     let search = params.q.clone().unwrap_or_default();
 
     #[derive(Debug, Deserialize, surrealdb::types::SurrealValue)]
-    struct DbOrgRow {
+    struct PRow {
         id: surrealdb::types::RecordId,
-        name: String,
-        slug: String,
-        org_type: Option<String>,
-        public: Option<bool>,
-        #[serde(default)]
-        #[surreal(default)]
-        verified: bool,
-        created_at: chrono::DateTime<chrono::Utc>,
+        username: String,
+        email: String,
+        name: Option<String>,
+        is_admin: Option<bool>,
+        verification_status: String,
+        plan: Option<String>,
+        created_at: Option<chrono::DateTime<chrono::Utc>>,
     }
 
-    let orgs: Vec<DbOrgRow> = if search.is_empty() {
-        DB.query("SELECT id, name, slug, type.name AS org_type, public, verified, created_at FROM organization ORDER BY created_at DESC LIMIT 50")
+    let people: Vec<PRow> = if search.is_empty() {
+        DB.query("SELECT id, username, email, name, is_admin, verification_status, plan, created_at FROM person ORDER BY created_at DESC LIMIT 50")
             .await
             .map_err(|e| Error::Database(e.to_string()))?
             .take(0)
             .unwrap_or_default()
     } else {
         let q = search.to_lowercase();
-        DB.query("SELECT id, name, slug, type.name AS org_type, public, verified, created_at FROM organization WHERE string::lowercase(name) CONTAINS $q OR string::lowercase(slug) CONTAINS $q ORDER BY created_at DESC LIMIT 50")
+        DB.query("SELECT id, username, email, name, is_admin, verification_status, plan, created_at FROM person WHERE string::lowercase(username) CONTAINS $q OR string::lowercase(email) CONTAINS $q OR string::lowercase(name ?? '') CONTAINS $q ORDER BY created_at DESC LIMIT 50")
             .bind(("q", q))
             .await
             .map_err(|e| Error::Database(e.to_string()))?
@@ -668,16 +978,19 @@ async fn list_organizations(
             .unwrap_or_default()
     };
 
-    let organizations: Vec<OrgRow> = orgs
+    let people: Vec<PersonRow> = people
         .into_iter()
-        .map(|o| OrgRow {
-            id: o.id.key_string(),
-            name: o.name,
-            slug: o.slug,
-            org_type: o.org_type.unwrap_or_default(),
-            is_public: o.public.unwrap_or(false),
-            is_verified: o.verified,
-            created_at: o.created_at.format("%b %d, %Y").to_string(),
+        .map(|p| PersonRow {
+            id: p.id.key_string(),
+            username: p.username,
+            email: p.email,
+            name: p.name,
+            is_admin: p.is_admin.unwrap_or(false),
+            verification_status: p.verification_status,
+            plan: p.plan.unwrap_or_else(|| "free".to_string()),
+            created_at: p.created_at
+                .map(|d| d.format("%b %d, %Y").to_string())
+                .unwrap_or_default(),
         })
         .collect();
 
@@ -685,86 +998,402 @@ async fn list_organizations(
         .with_page("admin")
         .with_user(template_user);
 
-    let template = AdminOrganizationsTemplate {
+    let template = AdminPeopleTemplate {
         app_name: base.app_name,
         year: base.year,
         version: base.version,
         active_page: base.active_page,
         user: base.user,
-        organizations,
+        people,
         search_query: search,
     };
 
     Ok(Html(template.render().map_err(|e| {
-        error!("Failed to render admin organizations: {}", e);
+        error!("Failed to render admin people: {}", e);
         Error::template(e.to_string())
     })?))
 }
 
-async fn delete_organization(
+async fn delete_person(
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<String>,
 ) -> Result<Redirect, Error> {
     require_admin(&user).await?;
 
-    let record_id = surrealdb::types::RecordId::new("organization", id.as_str());
+    let record_id = surrealdb::types::RecordId::new("person", id.as_str());
 
-    // Clean up memberships then delete
-    DB.query("DELETE FROM member_of WHERE out = $oid; DELETE $oid")
-        .bind(("oid", record_id))
+    // Don't allow deleting yourself
+    let self_key = if user.id.starts_with("person:") {
+        user.id.strip_prefix("person:").unwrap_or(&user.id).to_string()
+    } else {
+        user.id.clone()
+    };
+    if id == self_key {
+        return Err(Error::BadRequest("Cannot delete your own account from admin".to_string()));
+    }
+
+    // Clean up related data then delete
+    if let Err(e) = id_verification::forget(&record_id).await {
+        error!("Failed to delete verification requests for person {}: {}", id, e);
+    }
+    if let Err(e) = org_claims::forget(&record_id).await {
+        error!("Failed to delete organization claims for person {}: {}", id, e);
+    }
+    if let Err(e) = change_requests::forget(&record_id).await {
+        error!("Failed to delete suggestions for person {}: {}", id, e);
+    }
+    if let Err(e) = SelfTapeModel::forget(&record_id).await {
+        error!("Failed to delete self-tapes for person {}: {}", id, e);
+    }
+    if let Err(e) = uploads::forget(&record_id).await {
+        error!("Failed to cancel uploads for person {}: {}", id, e);
+    }
+    if let Err(e) = transcode::forget(&record_id).await {
+        error!("Failed to delete video renditions for person {}: {}", id, e);
+    }
+    if let Err(e) = AudioReelModel::forget(&record_id).await {
+        error!("Failed to delete audio reels for person {}: {}", id, e);
+    }
+    if let Err(e) = PortfolioModel::forget(&record_id).await {
+        error!("Failed to delete portfolio exports for person {}: {}", id, e);
+    }
+    if let Err(e) = ShortlistModel::forget(&record_id).await {
+        error!("Failed to delete shortlist entries for person {}: {}", id, e);
+    }
+    if let Err(e) = OfferModel::forget(&record_id).await {
+        error!("Failed to delete offers for person {}: {}", id, e);
+    }
+    if let Err(e) = whatsapp::forget(&record_id).await {
+        error!("Failed to delete WhatsApp messages for person {}: {}", id, e);
+    }
+    DB.query("DELETE FROM involvement WHERE in = $pid; DELETE FROM notification WHERE person_id = $pid; DELETE FROM member_of WHERE in = $pid; DELETE FROM blocks WHERE in = $pid OR out = $pid; DELETE FROM mutes WHERE in = $pid OR out = $pid; DELETE FROM timecard WHERE person = $pid; DELETE FROM house_rules_ack WHERE person = $pid; DELETE FROM crew_deal WHERE person = $pid; DELETE FROM guest_link WHERE created_by = $pid; UPDATE person SET guardian = NONE, guardian_approved = false WHERE guardian = $pid; DELETE $pid")
+        .bind(("pid", record_id))
         .await
         .map_err(|e| Error::Database(e.to_string()))?;
 
-    info!("Admin {} deleted organization {}", user.username, id);
-    Ok(Redirect::to("/admin/organizations"))
+    info!("Admin {} deleted person {}", user.username, id);
+    Ok(Redirect::to("/admin/people"))
 }
 
-async fn toggle_org_verified(
+async fn toggle_admin(
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<String>,
 ) -> Result<Redirect, Error> {
     require_admin(&user).await?;
 
-    let record_id = surrealdb::types::RecordId::new("organization", id.as_str());
+    let record_id = surrealdb::types::RecordId::new("person", id.as_str());
 
-    DB.query("UPDATE $oid SET verified = !verified")
-        .bind(("oid", record_id))
+    // Don't allow toggling your own admin status
+    let self_key = if user.id.starts_with("person:") {
+        user.id.strip_prefix("person:").unwrap_or(&user.id).to_string()
+    } else {
+        user.id.clone()
+    };
+    if id == self_key {
+        return Err(Error::BadRequest("Cannot change your own admin status".to_string()));
+    }
+
+    DB.query("UPDATE $pid SET is_admin = !is_admin")
+        .bind(("pid", record_id))
         .await
         .map_err(|e| Error::Database(e.to_string()))?;
 
-    info!("Admin {} toggled verification for organization {}", user.username, id);
-    Ok(Redirect::to("/admin/organizations"))
+    info!("Admin {} toggled admin for {}", user.username, id);
+    Ok(Redirect::to("/admin/people"))
 }
 
-// -- Locations --
+#[derive(Deserialize)]
+struct AdminResetPasswordForm {
+    new_password: String,
+}
 
-async fn list_locations(
+async fn admin_reset_password(
     AuthenticatedUser(user): AuthenticatedUser,
-    Query(params): Query<SearchParams>,
-) -> Result<Html<String>, Error> {
-    let template_user = require_admin(&user).await?;
+    Path(id): Path<String>,
+    axum::Form(form): axum::Form<AdminResetPasswordForm>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
 
-    let search = params.q.clone().unwrap_or_default();
+    crate::services::password_policy::validate(&form.new_password, &[])
+        .await
+        .map_err(Error::Validation)?;
 
-    #[derive(Debug, Deserialize, surrealdb::types::SurrealValue)]
-    struct LocRow {
-        id: surrealdb::types::RecordId,
-        name: String,
-        city: String,
-        state: String,
-        is_public: bool,
+    let record_id = surrealdb::types::RecordId::new("person", id.as_str());
+    let password_hash = crate::auth::hash_password(&form.new_password)?;
+
+    DB.query("UPDATE $pid SET password = $password")
+        .bind(("pid", record_id))
+        .bind(("password", password_hash))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    info!("Admin {} reset password for person:{}", user.username, id);
+    Ok(Redirect::to("/admin/people"))
+}
+
+#[derive(Deserialize)]
+struct VerificationForm {
+    status: String,
+}
+
+async fn update_verification(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    axum::extract::Form(form): axum::extract::Form<VerificationForm>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    let valid_statuses = ["unverified", "email", "sms", "identity"];
+    if !valid_statuses.contains(&form.status.as_str()) {
+        return Err(Error::BadRequest(format!("Invalid verification status: {}", form.status)));
+    }
+
+    let record_id = surrealdb::types::RecordId::new("person", id.as_str());
+
+    info!("Updating verification_status for person:{} to '{}' (RecordId: {:?})", id, form.status, record_id);
+
+    let response = DB.query("UPDATE $pid SET verification_status = $status")
+        .bind(("pid", record_id))
+        .bind(("status", form.status.clone()))
+        .await
+        .map_err(|e| {
+            error!("Verification update query failed: {}", e);
+            Error::Database(e.to_string())
+        })?;
+
+    // Check for statement-level errors in the response
+    match response.check() {
+        Ok(_) => info!("Admin {} set verification_status to '{}' for person:{}", user.username, form.status, id),
+        Err(e) => {
+            error!("Verification update statement error: {}", e);
+            return Err(Error::Database(e.to_string()));
+        }
+    }
+
+    Ok(Redirect::to("/admin/people"))
+}
+
+#[derive(Deserialize)]
+struct PlanForm {
+    plan: String,
+}
+
+async fn update_plan(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    axum::extract::Form(form): axum::extract::Form<PlanForm>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    if !crate::models::person::PLANS.contains(&form.plan.as_str()) {
+        return Err(Error::BadRequest(format!("Invalid plan: {}", form.plan)));
+    }
+
+    let record_id = surrealdb::types::RecordId::new("person", id.as_str());
+    DB.query("UPDATE $pid SET plan = $plan")
+        .bind(("pid", record_id))
+        .bind(("plan", form.plan.clone()))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?
+        .check()
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    info!("Admin {} set plan to '{}' for person:{}", user.username, form.plan, id);
+    Ok(Redirect::to("/admin/people"))
+}
+
+// -- Productions --
+
+// -- Impersonation --
+
+#[derive(Deserialize)]
+struct ImpersonateForm {
+    reason: String,
+}
+
+async fn impersonate_person(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    client: ClientInfo,
+    axum::Form(form): axum::Form<ImpersonateForm>,
+) -> Result<Response, Error> {
+    require_admin(&user).await?;
+
+    let self_key = user.id.strip_prefix("person:").unwrap_or(&user.id);
+    if id == self_key {
+        return Err(Error::BadRequest("You can't impersonate yourself".to_string()));
+    }
+
+    let target = Person::find_by_id(&id).await?.ok_or(Error::NotFound)?;
+    if person_is_admin(&target.id).await {
+        return Err(Error::BadRequest("Admins can't be impersonated".to_string()));
+    }
+
+    let admin_id = surrealdb::types::RecordId::parse_simple(&user.id)
+        .map_err(|_| Error::Internal("Invalid admin ID".to_string()))?;
+    let session = impersonation::start(
+        &admin_id,
+        &user.username,
+        &target.id,
+        &target.username,
+        &form.reason,
+        &client,
+    )
+    .await?;
+
+    let token = crate::auth::create_impersonation_jwt(
+        &target.id.to_raw_string(),
+        &target.username,
+        &target.email,
+        &session.to_raw_string(),
+        (crate::config::impersonation().session_mins * 60) as u64,
+    )?;
+
+    warn!(
+        "Admin {} started impersonating {} from {} (session {}): {}",
+        user.username,
+        target.username,
+        client.ip,
+        session.key_string(),
+        form.reason.trim()
+    );
+    Ok(super::auth::session_response(token, Some(format!("/{}", target.username))))
+}
+
+/// End the impersonation session and sign the admin back in as themselves
+async fn exit_impersonation(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Response, Error> {
+    let Some(impersonator) = &user.impersonator else {
+        return Ok(Redirect::to("/").into_response());
+    };
+
+    impersonation::end(&impersonator.session_id).await?;
+    info!(
+        "Admin {} stopped impersonating {} (session {})",
+        impersonator.admin_username, user.username, impersonator.session_id
+    );
+
+    let session_key = impersonator
+        .session_id
+        .strip_prefix("impersonation_session:")
+        .unwrap_or(&impersonator.session_id);
+    let admin = Person::find_by_id(&impersonator.admin_id).await?;
+    match admin {
+        Some(admin) if person_is_admin(&admin.id).await => {
+            let token = crate::auth::create_jwt(
+                &admin.id.to_raw_string(),
+                &admin.username,
+                &admin.email,
+            )?;
+            Ok(super::auth::session_response(
+                token,
+                Some(format!("/admin/impersonation/{}", session_key)),
+            ))
+        }
+        // The admin account is gone or no longer an admin; the ended session
+        // leaves this browser signed out
+        _ => Ok(Redirect::to("/login").into_response()),
+    }
+}
+
+async fn list_impersonation(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Html<String>, Error> {
+    let template_user = require_admin(&user).await?;
+
+    let now = chrono::Utc::now();
+    let sessions = impersonation::recent(100)
+        .await?
+        .iter()
+        .map(|s| impersonation_row(s, now))
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminImpersonationTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        sessions,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin impersonation: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+async fn impersonation_session(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Html<String>, Error> {
+    let template_user = require_admin(&user).await?;
+
+    let session_id = surrealdb::types::RecordId::new("impersonation_session", id.as_str());
+    let session = impersonation::get(&session_id).await?.ok_or(Error::NotFound)?;
+    let actions = impersonation::actions(&session_id)
+        .await?
+        .into_iter()
+        .map(|a| ImpersonationActionRow {
+            time: a.created_at.format("%b %d, %Y %H:%M:%S").to_string(),
+            method: a.method,
+            path: a.path,
+            status: a.status,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminImpersonationSessionTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        session: impersonation_row(&session, chrono::Utc::now()),
+        actions,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin impersonation session: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+async fn list_productions(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<SearchParams>,
+) -> Result<Html<String>, Error> {
+    let template_user = require_admin(&user).await?;
+
+    let search = params.q.clone().unwrap_or_default();
+
+    #[derive(Debug, Deserialize, surrealdb::types::SurrealValue)]
+    struct ProdRow {
+        id: surrealdb::types::RecordId,
+        title: String,
+        slug: String,
+        production_type: String,
+        status: String,
         created_at: chrono::DateTime<chrono::Utc>,
     }
 
-    let locations: Vec<LocRow> = if search.is_empty() {
-        DB.query("SELECT id, name, city, state, is_public, created_at FROM location ORDER BY created_at DESC LIMIT 50")
+    let productions: Vec<ProdRow> = if search.is_empty() {
+        DB.query("SELECT id, title, slug, type AS production_type, status, created_at FROM production ORDER BY created_at DESC LIMIT 50")
             .await
             .map_err(|e| Error::Database(e.to_string()))?
             .take(0)
             .unwrap_or_default()
     } else {
         let q = search.to_lowercase();
-        DB.query("SELECT id, name, city, state, is_public, created_at FROM location WHERE string::lowercase(name) CONTAINS $q OR string::lowercase(city) CONTAINS $q ORDER BY created_at DESC LIMIT 50")
+        DB.query("SELECT id, title, slug, type AS production_type, status, created_at FROM production WHERE string::lowercase(title) CONTAINS $q OR string::lowercase(slug) CONTAINS $q ORDER BY created_at DESC LIMIT 50")
             .bind(("q", q))
             .await
             .map_err(|e| Error::Database(e.to_string()))?
@@ -772,15 +1401,15 @@ async fn list_locations(
             .unwrap_or_default()
     };
 
-    let locations: Vec<LocationRow> = locations
+    let productions: Vec<ProductionRow> = productions
         .into_iter()
-        .map(|l| LocationRow {
-            id: l.id.key_string(),
-            name: l.name,
-            city: l.city,
-            state: l.state,
-            is_public: l.is_public,
-            created_at: l.created_at.format("%b %d, %Y").to_string(),
+        .map(|p| ProductionRow {
+            id: p.id.key_string(),
+            title: p.title,
+            slug: p.slug,
+            production_type: p.production_type,
+            status: p.status,
+            created_at: p.created_at.format("%b %d, %Y").to_string(),
         })
         .collect();
 
@@ -788,68 +1417,269 @@ async fn list_locations(
         .with_page("admin")
         .with_user(template_user);
 
-    let template = AdminLocationsTemplate {
+    let template = AdminProductionsTemplate {
         app_name: base.app_name,
         year: base.year,
         version: base.version,
         active_page: base.active_page,
         user: base.user,
-        locations,
+        productions,
         search_query: search,
     };
 
     Ok(Html(template.render().map_err(|e| {
-        error!("Failed to render admin locations: {}", e);
+        error!("Failed to render admin productions: {}", e);
         Error::template(e.to_string())
     })?))
 }
 
-async fn delete_location(
+async fn delete_production(
     AuthenticatedUser(user): AuthenticatedUser,
     Path(id): Path<String>,
 ) -> Result<Redirect, Error> {
     require_admin(&user).await?;
 
-    let record_id = surrealdb::types::RecordId::new("location", id.as_str());
+    let record_id = surrealdb::types::RecordId::new("production", id.as_str());
 
-    DB.query("DELETE $id")
-        .bind(("id", record_id))
+    // Legal holds bind admins too
+    let admin = surrealdb::types::RecordId::parse_simple(&user.id).ok();
+    LegalHoldModel::ensure_deletable(&record_id, None, admin.as_ref(), "production").await?;
+
+    crate::models::scouting::ScoutingModel::delete_for_production(&record_id).await?;
+
+    // Clean up involvements then delete
+    DB.query("DELETE FROM involvement WHERE out = $pid; DELETE FROM member_of WHERE out = $pid; DELETE FROM company_credit WHERE out = $pid; DELETE FROM house_rules_ack WHERE production = $pid; DELETE $pid")
+        .bind(("pid", record_id))
         .await
         .map_err(|e| Error::Database(e.to_string()))?;
 
-    info!("Admin {} deleted location {}", user.username, id);
-    Ok(Redirect::to("/admin/locations"))
+    info!("Admin {} deleted production {}", user.username, id);
+    Ok(Redirect::to("/admin/productions"))
 }
 
-// -- Embedding rebuild --
+// -- Organizations --
 
-async fn rebuild_embeddings(
+async fn list_organizations(
     AuthenticatedUser(user): AuthenticatedUser,
-) -> Result<Redirect, Error> {
-    require_admin(&user).await?;
+    Query(params): Query<SearchParams>,
+) -> Result<Html<String>, Error> {
+    let template_user = require_admin(&user).await?;
 
-    if REBUILD_IN_PROGRESS.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
-        return Err(Error::BadRequest("Embedding rebuild is already in progress".to_string()));
-    }
+    let search = params.q.clone().unwrap_or_default();
 
-    info!("Admin {} triggered embedding rebuild", user.username);
+    #[derive(Debug, Deserialize, surrealdb::types::SurrealValue)]
+    struct DbOrgRow {
+        id: surrealdb::types::RecordId,
+        name: String,
+        slug: String,
+        org_type: Option<String>,
+        public: Option<bool>,
+        #[serde(default)]
+        #[surreal(default)]
+        verified: bool,
+        created_at: chrono::DateTime<chrono::Utc>,
+    }
 
-    tokio::spawn(async move {
-        if let Err(e) = run_embedding_rebuild().await {
-            error!("Embedding rebuild failed: {}", e);
-        }
-        REBUILD_IN_PROGRESS.store(false, Ordering::SeqCst);
-    });
+    let orgs: Vec<DbOrgRow> = if search.is_empty() {
+        DB.query("SELECT id, name, slug, type.name AS org_type, public, verified, created_at FROM organization ORDER BY created_at DESC LIMIT 50")
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?
+            .take(0)
+            .unwrap_or_default()
+    } else {
+        let q = search.to_lowercase();
+        DB.query("SELECT id, name, slug, type.name AS org_type, public, verified, created_at FROM organization WHERE string::lowercase(name) CONTAINS $q OR string::lowercase(slug) CONTAINS $q ORDER BY created_at DESC LIMIT 50")
+            .bind(("q", q))
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?
+            .take(0)
+            .unwrap_or_default()
+    };
 
-    Ok(Redirect::to("/admin"))
-}
+    let organizations: Vec<OrgRow> = orgs
+        .into_iter()
+        .map(|o| OrgRow {
+            id: o.id.key_string(),
+            name: o.name,
+            slug: o.slug,
+            org_type: o.org_type.unwrap_or_default(),
+            is_public: o.public.unwrap_or(false),
+            is_verified: o.verified,
+            created_at: o.created_at.format("%b %d, %Y").to_string(),
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminOrganizationsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        organizations,
+        search_query: search,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin organizations: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+async fn delete_organization(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    let record_id = surrealdb::types::RecordId::new("organization", id.as_str());
+
+    // Clean up memberships then delete
+    DB.query("DELETE FROM member_of WHERE out = $oid; DELETE FROM company_credit WHERE in = $oid; DELETE $oid")
+        .bind(("oid", record_id))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    info!("Admin {} deleted organization {}", user.username, id);
+    Ok(Redirect::to("/admin/organizations"))
+}
+
+async fn toggle_org_verified(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    let record_id = surrealdb::types::RecordId::new("organization", id.as_str());
+
+    DB.query("UPDATE $oid SET verified = !verified")
+        .bind(("oid", record_id))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    info!("Admin {} toggled verification for organization {}", user.username, id);
+    Ok(Redirect::to("/admin/organizations"))
+}
+
+// -- Locations --
+
+async fn list_locations(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<SearchParams>,
+) -> Result<Html<String>, Error> {
+    let template_user = require_admin(&user).await?;
+
+    let search = params.q.clone().unwrap_or_default();
+
+    #[derive(Debug, Deserialize, surrealdb::types::SurrealValue)]
+    struct LocRow {
+        id: surrealdb::types::RecordId,
+        name: String,
+        city: String,
+        state: String,
+        is_public: bool,
+        created_at: chrono::DateTime<chrono::Utc>,
+    }
+
+    let locations: Vec<LocRow> = if search.is_empty() {
+        DB.query("SELECT id, name, city, state, is_public, created_at FROM location ORDER BY created_at DESC LIMIT 50")
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?
+            .take(0)
+            .unwrap_or_default()
+    } else {
+        let q = search.to_lowercase();
+        DB.query("SELECT id, name, city, state, is_public, created_at FROM location WHERE string::lowercase(name) CONTAINS $q OR string::lowercase(city) CONTAINS $q ORDER BY created_at DESC LIMIT 50")
+            .bind(("q", q))
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?
+            .take(0)
+            .unwrap_or_default()
+    };
+
+    let locations: Vec<LocationRow> = locations
+        .into_iter()
+        .map(|l| LocationRow {
+            id: l.id.key_string(),
+            name: l.name,
+            city: l.city,
+            state: l.state,
+            is_public: l.is_public,
+            created_at: l.created_at.format("%b %d, %Y").to_string(),
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminLocationsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        locations,
+        search_query: search,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin locations: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+async fn delete_location(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    let record_id = surrealdb::types::RecordId::new("location", id.as_str());
+
+    DB.query("DELETE $id")
+        .bind(("id", record_id))
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+    info!("Admin {} deleted location {}", user.username, id);
+    Ok(Redirect::to("/admin/locations"))
+}
+
+// -- Embedding rebuild --
+
+async fn rebuild_embeddings(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    if REBUILD_IN_PROGRESS.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+        return Err(Error::BadRequest("Embedding rebuild is already in progress".to_string()));
+    }
+
+    info!("Admin {} triggered embedding rebuild", user.username);
+
+    crate::db::spawn(async move {
+        if let Err(e) = run_embedding_rebuild().await {
+            error!("Embedding rebuild failed: {}", e);
+        }
+        REBUILD_IN_PROGRESS.store(false, Ordering::SeqCst);
+    });
+
+    Ok(Redirect::to("/admin"))
+}
 
 async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     use crate::services::embedding::{
         build_location_embedding_text, build_organization_embedding_text,
         build_person_embedding_text, build_production_embedding_text,
-        generate_embedding_async,
+        generate_embedding_async, store_embedding,
     };
+    use crate::models::company_credit::CompanyCreditModel;
+    use crate::physical_attributes::describe;
 
     info!("Starting full embedding rebuild");
     let mut total_updated: u32 = 0;
@@ -863,6 +1693,7 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
             name: Option<String>,
             username: Option<String>,
             profile: Option<PersonProfileRow>,
+            is_minor: Option<bool>,
         }
         #[derive(Debug, Clone, serde::Deserialize, SurrealValue)]
         struct PersonProfileRow {
@@ -875,8 +1706,11 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
             age_range: Option<AgeRangeRow>,
             height_mm: Option<i32>,
             body_type: Option<String>,
+            body_type_other: Option<String>,
             hair_color: Option<String>,
+            hair_color_other: Option<String>,
             eye_color: Option<String>,
+            eye_color_other: Option<String>,
             languages: Option<Vec<String>>,
             unions: Option<Vec<String>>,
             acting_age_range: Option<AgeRangeRow>,
@@ -886,7 +1720,7 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
         #[derive(Debug, Clone, serde::Deserialize, SurrealValue)]
         struct AgeRangeRow { min: i32, max: i32 }
 
-        let mut resp = DB.query("SELECT id, name, username, profile FROM person").await?;
+        let mut resp = DB.query("SELECT id, name, username, profile, is_minor FROM person").await?;
         let people: Vec<PersonRow> = match resp.take(0) {
             Ok(p) => p,
             Err(e) => {
@@ -901,15 +1735,29 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
                 let mut resp2 = DB.query("SELECT id, name, username FROM person").await?;
                 let basics: Vec<PersonBasic> = resp2.take(0).unwrap_or_default();
                 basics.into_iter().map(|b| PersonRow {
-                    id: b.id, name: b.name, username: b.username, profile: None,
+                    id: b.id, name: b.name, username: b.username, profile: None, is_minor: None,
                 }).collect()
             }
         };
         info!("Rebuilding embeddings for {} people", people.len());
 
-        for person in people {
+        for mut person in people {
+            // Minor profiles leave sensitive physical fields out of search
+            if person.is_minor == Some(true)
+                && let Some(profile) = person.profile.as_mut()
+            {
+                profile.ethnicity = None;
+                profile.height_mm = None;
+                profile.body_type = None;
+                profile.body_type_other = None;
+            }
+
             let display_name = person.name.as_deref()
                 .unwrap_or(person.username.as_deref().unwrap_or("unknown"));
+            let audio_work = AudioReelModel::embedding_lines(&person.id).await.unwrap_or_else(|e| {
+                warn!("Failed to load audio reels for {:?}: {}", person.id, e);
+                Vec::new()
+            });
             let embedding_text = if let Some(ref profile) = person.profile {
                 build_person_embedding_text(
                     display_name,
@@ -921,29 +1769,27 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
                     profile.gender.as_deref(),
                     &profile.ethnicity.clone().unwrap_or_default(),
                     profile.height_mm,
-                    profile.body_type.as_deref(),
-                    profile.hair_color.as_deref(),
-                    profile.eye_color.as_deref(),
+                    describe(profile.body_type.as_deref(), profile.body_type_other.as_deref()),
+                    describe(profile.hair_color.as_deref(), profile.hair_color_other.as_deref()),
+                    describe(profile.eye_color.as_deref(), profile.eye_color_other.as_deref()),
                     &profile.languages.clone().unwrap_or_default(),
                     &profile.unions.clone().unwrap_or_default(),
                     &[],
                     profile.acting_age_range.as_ref().map(|ar| (ar.min, ar.max)),
                     &profile.acting_ethnicities.clone().unwrap_or_default(),
                     profile.nationality.as_deref(),
+                    &audio_work,
                 )
             } else {
                 build_person_embedding_text(
                     display_name, None, None, &[], None, None, None, &[], None, None, None, None, &[], &[], &[], None, &[], None,
+                    &audio_work,
                 )
             };
 
             match generate_embedding_async(&embedding_text).await {
                 Ok(emb) => {
-                    if let Err(e) = DB.query("UPDATE $id SET embedding = $embedding, embedding_text = $embedding_text")
-                        .bind(("id", person.id.clone()))
-                        .bind(("embedding", emb))
-                        .bind(("embedding_text", embedding_text))
-                        .await
+                    if let Err(e) = store_embedding(&DB, person.id.clone(), emb, embedding_text).await
                     {
                         warn!("Failed to update embedding for person {:?}: {}", person.id, e);
                         total_failed += 1;
@@ -979,6 +1825,7 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
 
         for org in orgs {
             let name = org.name.as_deref().unwrap_or("unknown");
+            let slate = CompanyCreditModel::slate_lines(&org.id).await.unwrap_or_default();
             let embedding_text = build_organization_embedding_text(
                 name,
                 org.org_type.as_deref().unwrap_or(""),
@@ -987,15 +1834,12 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
                 org.location.as_deref(),
                 org.founded_year,
                 org.employees_count,
+                &slate,
             );
 
             match generate_embedding_async(&embedding_text).await {
                 Ok(emb) => {
-                    if let Err(e) = DB.query("UPDATE $id SET embedding = $embedding, embedding_text = $embedding_text")
-                        .bind(("id", org.id.clone()))
-                        .bind(("embedding", emb))
-                        .bind(("embedding_text", embedding_text))
-                        .await
+                    if let Err(e) = store_embedding(&DB, org.id.clone(), emb, embedding_text).await
                     {
                         warn!("Failed to update embedding for org {:?}: {}", org.id, e);
                         total_failed += 1;
@@ -1047,11 +1891,7 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
 
             match generate_embedding_async(&embedding_text).await {
                 Ok(emb) => {
-                    if let Err(e) = DB.query("UPDATE $id SET embedding = $embedding, embedding_text = $embedding_text")
-                        .bind(("id", loc.id.clone()))
-                        .bind(("embedding", emb))
-                        .bind(("embedding_text", embedding_text))
-                        .await
+                    if let Err(e) = store_embedding(&DB, loc.id.clone(), emb, embedding_text).await
                     {
                         warn!("Failed to update embedding for location {:?}: {}", loc.id, e);
                         total_failed += 1;
@@ -1087,6 +1927,7 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
 
         for prod in productions {
             let title = prod.title.as_deref().unwrap_or("unknown");
+            let companies = CompanyCreditModel::company_lines(&prod.id).await.unwrap_or_default();
             let embedding_text = build_production_embedding_text(
                 title,
                 prod.production_type.as_deref().unwrap_or(""),
@@ -1095,15 +1936,12 @@ async fn run_embedding_rebuild() -> Result<(), Box<dyn std::error::Error + Send
                 prod.location.as_deref(),
                 prod.start_date.as_deref(),
                 prod.end_date.as_deref(),
+                &companies,
             );
 
             match generate_embedding_async(&embedding_text).await {
                 Ok(emb) => {
-                    if let Err(e) = DB.query("UPDATE $id SET embedding = $embedding, embedding_text = $embedding_text")
-                        .bind(("id", prod.id.clone()))
-                        .bind(("embedding", emb))
-                        .bind(("embedding_text", embedding_text))
-                        .await
+                    if let Err(e) = store_embedding(&DB, prod.id.clone(), emb, embedding_text).await
                     {
                         warn!("Failed to update embedding for production {:?}: {}", prod.id, e);
                         total_failed += 1;
@@ -1132,67 +1970,19 @@ async fn backup_all(
 
     info!("Admin {} initiated full backup", user.username);
 
-    // 1. Export database via SurrealDB HTTP endpoint
-    //    (WS client doesn't support export, so we hit the HTTP API directly)
-    let db_config = crate::config::Config::from_env()
-        .map_err(|e| Error::Internal(format!("Config error: {}", e)))?;
-    let db = &db_config.database;
-    let export_url = format!("http://{}:{}/export", db.host, db.port);
-
-    let http_client = reqwest::Client::new();
-    let db_export = http_client
-        .get(&export_url)
-        .header("Accept", "application/octet-stream")
-        .header("surreal-ns", &db.namespace)
-        .header("surreal-db", &db.name)
-        .basic_auth(&db.username, Some(&db.password))
-        .send()
-        .await
-        .map_err(|e| Error::Internal(format!("DB export request failed: {}", e)))?
-        .bytes()
-        .await
-        .map_err(|e| Error::Internal(format!("DB export read failed: {}", e)))?;
-
-    info!("DB export complete: {} bytes", db_export.len());
-
-    // 2. List all S3 objects
-    let s3_service = s3()?;
-    let all_keys = s3_service.list_all_objects().await?;
-    info!("Found {} files in S3 to back up", all_keys.len());
+    // Inside a tenant this backs up the tenant's database and files only
+    let db_config = crate::db::current_database_config()
+        .ok_or_else(|| Error::Internal("Database is not connected".to_string()))?;
 
-    // 3. Build zip archive in memory
     let mut zip_buffer = Vec::new();
-    {
-        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut zip_buffer));
-        let options = zip::write::SimpleFileOptions::default()
-            .compression_method(zip::CompressionMethod::Deflated);
-
-        // Add DB export
-        zip.start_file("db_export.surql", options)
-            .map_err(|e| Error::Internal(format!("Zip error: {}", e)))?;
-        std::io::Write::write_all(&mut zip, &db_export)
-            .map_err(|e| Error::Internal(format!("Zip write error: {}", e)))?;
-
-        // Add each S3 file
-        for key in &all_keys {
-            match s3_service.download_file(key).await {
-                Ok((data, _content_type)) => {
-                    let path = format!("files/{}", key);
-                    zip.start_file(&path, options)
-                        .map_err(|e| Error::Internal(format!("Zip error: {}", e)))?;
-                    std::io::Write::write_all(&mut zip, &data)
-                        .map_err(|e| Error::Internal(format!("Zip write error: {}", e)))?;
-                }
-                Err(e) => {
-                    warn!("Skipping file {} during backup: {}", key, e);
-                }
-            }
-        }
-
-        zip.finish()
-            .map_err(|e| Error::Internal(format!("Zip finish error: {}", e)))?;
-    }
-
+    let manifest = crate::services::backup::write_archive(
+        std::io::Cursor::new(&mut zip_buffer),
+        &db_config,
+        true,
+    )
+    .await?;
+
+    info!("Backed up {} files", manifest.objects.len());
     info!("Backup zip created: {} bytes", zip_buffer.len());
 
     let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
@@ -1418,6 +2208,74 @@ async fn collect_all_referenced_files() -> Result<(std::collections::HashSet<Str
         }
     }
 
+    // Self-tape videos, audio reels, and finished uploads waiting to be claimed
+    {
+        #[derive(Debug, Deserialize, SurrealValue)]
+        struct PrivateFile {
+            id: String,
+            key: String,
+        }
+
+        let rows: Vec<PrivateFile> = DB
+            .query("SELECT <string> id AS id, video_key AS key FROM selftape WHERE video_key != NONE")
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?
+            .take(0)
+            .unwrap_or_default();
+        let audio: Vec<PrivateFile> = DB
+            .query("SELECT <string> id AS id, file_key AS key FROM audio_reel")
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?
+            .take(0)
+            .unwrap_or_default();
+        let portfolios: Vec<PrivateFile> = DB
+            .query("SELECT <string> id AS id, file_key AS key FROM portfolio_export")
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?
+            .take(0)
+            .unwrap_or_default();
+        let uploads: Vec<PrivateFile> = DB
+            .query("SELECT <string> id AS id, key FROM upload_session WHERE status = 'complete'")
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?
+            .take(0)
+            .unwrap_or_default();
+
+        for (row, field) in rows
+            .into_iter()
+            .map(|r| (r, "video_key"))
+            .chain(audio.into_iter().map(|r| (r, "file_key")))
+            .chain(portfolios.into_iter().map(|r| (r, "file_key")))
+            .chain(uploads.into_iter().map(|r| (r, "key")))
+        {
+            keys.insert(row.key.clone());
+            refs.push(FileRef { key: row.key, entity: row.id, field: field.to_string() });
+        }
+    }
+
+    // Transcoded renditions of uploaded videos
+    {
+        #[derive(Debug, Deserialize, SurrealValue)]
+        struct Renditions {
+            id: String,
+            files: Vec<String>,
+        }
+
+        let rows: Vec<Renditions> = DB
+            .query("SELECT <string> id AS id, files FROM transcode_job WHERE files != []")
+            .await
+            .map_err(|e| Error::Database(e.to_string()))?
+            .take(0)
+            .unwrap_or_default();
+
+        for row in rows {
+            for key in row.files {
+                keys.insert(key.clone());
+                refs.push(FileRef { key, entity: row.id.clone(), field: "files".to_string() });
+            }
+        }
+    }
+
     Ok((keys, refs))
 }
 
@@ -1620,11 +2478,572 @@ async fn cleanup_orphaned_files(
     Ok(Redirect::to("/admin/cleanup-files"))
 }
 
+// -- Demo mode --
+
+#[derive(Deserialize)]
+struct DemoModeForm {
+    /// "on" or "off"
+    action: String,
+}
+
+/// Turn demo mode on or off for this browser
+async fn set_demo_mode(
+    AuthenticatedUser(user): AuthenticatedUser,
+    jar: CookieJar,
+    axum::Form(form): axum::Form<DemoModeForm>,
+) -> Result<Response, Error> {
+    require_admin(&user).await?;
+
+    let jar = match form.action.as_str() {
+        "on" => jar.add(
+            Cookie::build((demo_mode::COOKIE, "1"))
+                .path("/")
+                .same_site(SameSite::Lax)
+                .http_only(true)
+                .secure(crate::config::cookie_secure())
+                .build(),
+        ),
+        "off" => jar.remove(Cookie::build(demo_mode::COOKIE).path("/")),
+        other => return Err(Error::BadRequest(format!("Invalid action: {}", other))),
+    };
+    info!("Admin {} turned demo mode {}", user.username, form.action);
+    Ok((jar, Redirect::to("/admin")).into_response())
+}
+
+// -- Scheduled tasks --
+
+async fn list_tasks(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Html<String>, Error> {
+    let template_user = require_install_admin(&user).await?;
+
+    let fmt_time = |t: Option<chrono::DateTime<chrono::Utc>>| {
+        t.map(|d| d.format("%b %d, %Y %H:%M").to_string())
+            .unwrap_or_else(|| "—".to_string())
+    };
+
+    let tasks: Vec<TaskRow> = crate::services::scheduler::tasks()
+        .into_iter()
+        .map(|t| TaskRow {
+            interval: format_interval(t.interval_secs),
+            last_run: fmt_time(t.status.last_started),
+            last_duration: t.status.last_duration_ms
+                .map(|ms| format!("{}ms", ms))
+                .unwrap_or_else(|| "—".to_string()),
+            last_error: t.status.last_error.unwrap_or_default(),
+            next_run: fmt_time(t.status.next_run),
+            run_count: t.status.run_count,
+            failure_count: t.status.failure_count,
+            running: t.running,
+            name: t.name,
+            description: t.description,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminTasksTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        tasks,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin tasks: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+async fn run_task(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(name): Path<String>,
+) -> Result<Redirect, Error> {
+    require_install_admin(&user).await?;
+
+    if crate::services::scheduler::run_now(&name)? {
+        info!("Admin {} triggered scheduled task {}", user.username, name);
+    } else {
+        warn!("Scheduled task {} is already running", name);
+    }
+
+    Ok(Redirect::to("/admin/tasks"))
+}
+
+// -- Feature flags --
+
+async fn list_flags(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Html<String>, Error> {
+    let template_user = require_admin(&user).await?;
+
+    let flags = crate::services::flags::list().await?;
+
+    // Show override organizations by slug rather than record id
+    #[derive(Deserialize, SurrealValue)]
+    struct OrgSlug {
+        id: String,
+        slug: String,
+    }
+    let org_ids: Vec<String> = flags
+        .iter()
+        .flat_map(|f| f.enabled_orgs.iter().chain(f.disabled_orgs.iter()).cloned())
+        .collect();
+    let slugs: std::collections::HashMap<String, String> = if org_ids.is_empty() {
+        Default::default()
+    } else {
+        let rows: Vec<OrgSlug> = DB
+            .query("SELECT <string> id AS id, slug FROM organization WHERE <string> id IN $ids")
+            .bind(("ids", org_ids))
+            .await?
+            .take(0)
+            .unwrap_or_default();
+        rows.into_iter().map(|r| (r.id, r.slug)).collect()
+    };
+    let to_slugs = |ids: Vec<String>| -> Vec<String> {
+        ids.into_iter()
+            .map(|id| slugs.get(&id).cloned().unwrap_or(id))
+            .collect()
+    };
+
+    let flags: Vec<FlagRow> = flags
+        .into_iter()
+        .map(|f| FlagRow {
+            enabled_orgs: to_slugs(f.enabled_orgs),
+            disabled_orgs: to_slugs(f.disabled_orgs),
+            name: f.name,
+            description: f.description,
+            enabled: f.enabled,
+            rollout_percent: f.rollout_percent,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminFlagsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        flags,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin flags: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+#[derive(Deserialize)]
+struct CreateFlagForm {
+    name: String,
+    #[serde(default)]
+    description: String,
+}
+
+async fn create_flag(
+    AuthenticatedUser(user): AuthenticatedUser,
+    axum::Form(form): axum::Form<CreateFlagForm>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    let name = form.name.trim().to_lowercase();
+    crate::services::flags::upsert(&name, &form.description).await?;
+    info!("Admin {} created feature flag {}", user.username, name);
+
+    Ok(Redirect::to("/admin/flags"))
+}
+
+#[derive(Deserialize)]
+struct UpdateFlagForm {
+    #[serde(default)]
+    enabled: Option<String>,
+    rollout_percent: u8,
+}
+
+async fn update_flag(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(name): Path<String>,
+    axum::Form(form): axum::Form<UpdateFlagForm>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    let enabled = form.enabled.is_some();
+    crate::services::flags::set_state(&name, enabled, form.rollout_percent).await?;
+    info!(
+        "Admin {} set feature flag {} to enabled={} rollout={}%",
+        user.username, name, enabled, form.rollout_percent
+    );
+
+    Ok(Redirect::to("/admin/flags"))
+}
+
+#[derive(Deserialize)]
+struct OverrideFlagForm {
+    org_slug: String,
+    /// "enable", "disable" or "clear"
+    state: String,
+}
+
+async fn override_flag(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(name): Path<String>,
+    axum::Form(form): axum::Form<OverrideFlagForm>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    let state = match form.state.as_str() {
+        "enable" => Some(true),
+        "disable" => Some(false),
+        "clear" => None,
+        other => return Err(Error::BadRequest(format!("Unknown override state: {}", other))),
+    };
+
+    let org = crate::models::organization::OrganizationModel::new()
+        .get_by_slug(form.org_slug.trim())
+        .await?;
+    crate::services::flags::set_org_override(&name, org.id, state).await?;
+    info!(
+        "Admin {} set feature flag {} override for {} to {}",
+        user.username, name, form.org_slug, form.state
+    );
+
+    Ok(Redirect::to("/admin/flags"))
+}
+
+async fn delete_flag(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(name): Path<String>,
+) -> Result<Redirect, Error> {
+    require_admin(&user).await?;
+
+    crate::services::flags::delete(&name).await?;
+    info!("Admin {} deleted feature flag {}", user.username, name);
+
+    Ok(Redirect::to("/admin/flags"))
+}
+
+// -- Experiments --
+
+#[derive(Deserialize)]
+struct ExperimentParams {
+    days: Option<i64>,
+}
+
+async fn list_experiments(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<ExperimentParams>,
+) -> Result<Html<String>, Error> {
+    use crate::services::experiments::{self, EXPERIMENTS};
+
+    let template_user = require_admin(&user).await?;
+
+    let days = params.days.unwrap_or(14).clamp(1, 365);
+    let since = chrono::Utc::now() - chrono::Duration::days(days);
+    let flags = crate::services::flags::list().await?;
+
+    let mut rows = Vec::new();
+    for experiment in EXPERIMENTS {
+        let stats = experiments::report(experiment, since).await?;
+        let control = stats.first().cloned().unwrap_or_default();
+
+        let audience = match flags.iter().find(|f| f.name == experiment.name) {
+            Some(f) if f.enabled => format!("on ({}%)", f.rollout_percent),
+            Some(_) => "off".to_string(),
+            None => "no flag".to_string(),
+        };
+
+        let variants = stats
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let z = if i == 0 { None } else { experiments::z_score(&control, s) };
+                VariantRow {
+                    name: s.variant.clone(),
+                    searches: s.searches,
+                    clicked_searches: s.clicked_searches,
+                    clicks: s.clicks,
+                    ctr: format!("{:.1}%", s.ctr()),
+                    clicks_per_search: format!("{:.2}", s.clicks_per_search()),
+                    lift: if i == 0 {
+                        String::new()
+                    } else {
+                        experiments::lift(&control, s)
+                            .map(|l| format!("{:+.1}%", l))
+                            .unwrap_or_else(|| "—".to_string())
+                    },
+                    z_score: z.map(|z| format!("{:.2}", z)).unwrap_or_default(),
+                    significant: z.is_some_and(|z| z.abs() >= 1.96),
+                }
+            })
+            .collect();
+
+        rows.push(ExperimentRow {
+            name: experiment.name.to_string(),
+            description: experiment.description.to_string(),
+            audience,
+            variants,
+        });
+    }
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminExperimentsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        experiments: rows,
+        days,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin experiments: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+// -- Configuration --
+
+async fn list_config(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Html<String>, Error> {
+    let template_user = require_install_admin(&user).await?;
+
+    let settings = crate::settings::list().await?;
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminConfigTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        settings,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin config: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+#[derive(Deserialize)]
+struct UpdateConfigForm {
+    /// Empty clears the override
+    value: String,
+}
+
+async fn update_config(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(key): Path<String>,
+    axum::Form(form): axum::Form<UpdateConfigForm>,
+) -> Result<Redirect, Error> {
+    require_install_admin(&user).await?;
+
+    if form.value.trim().is_empty() {
+        crate::settings::clear_override(&key).await?;
+        info!("Admin {} cleared config override {}", user.username, key);
+    } else {
+        crate::settings::set_override(&key, &form.value).await?;
+        info!("Admin {} set config override {} = {}", user.username, key, form.value.trim());
+    }
+
+    Ok(Redirect::to("/admin/config"))
+}
+
+// -- Status notices --
+
+async fn list_status_notices(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Html<String>, Error> {
+    let template_user = require_install_admin(&user).await?;
+
+    let notices = crate::services::status::list_all(100).await?;
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminStatusTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        notices,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin status notices: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+#[derive(Deserialize)]
+struct StatusNoticeForm {
+    kind: String,
+    message: String,
+}
+
+async fn post_status_notice(
+    AuthenticatedUser(user): AuthenticatedUser,
+    axum::Form(form): axum::Form<StatusNoticeForm>,
+) -> Result<Redirect, Error> {
+    require_install_admin(&user).await?;
+
+    crate::services::status::post(&form.kind, &form.message, &user.username).await?;
+
+    Ok(Redirect::to("/admin/status"))
+}
+
+async fn resolve_status_notice(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Redirect, Error> {
+    require_install_admin(&user).await?;
+
+    crate::services::status::resolve(surrealdb::types::RecordId::new("status_notice", id.as_str())).await?;
+    info!("Admin {} resolved status notice {}", user.username, id);
+
+    Ok(Redirect::to("/admin/status"))
+}
+
+// -- Tenants --
+
+async fn list_tenants(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Html<String>, Error> {
+    let template_user = require_install_admin(&user).await?;
+
+    let tenants = crate::services::tenants::list().await?;
+
+    let base = BaseContext::new()
+        .with_page("admin")
+        .with_user(template_user);
+
+    let template = AdminTenantsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        enabled: crate::services::tenants::enabled(),
+        tenants,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render admin tenants: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+#[derive(Deserialize)]
+struct CreateTenantForm {
+    slug: String,
+    name: String,
+    hostnames: String,
+}
+
+async fn create_tenant(
+    AuthenticatedUser(user): AuthenticatedUser,
+    axum::Form(form): axum::Form<CreateTenantForm>,
+) -> Result<Redirect, Error> {
+    require_install_admin(&user).await?;
+    if !crate::services::tenants::enabled() {
+        return Err(Error::BadRequest("Multi-tenant mode is off".to_string()));
+    }
+
+    let slug = form.slug.trim().to_lowercase();
+    let hostnames = crate::services::tenants::parse_hostnames(&form.hostnames);
+    crate::services::tenants::create(&slug, &form.name, hostnames).await?;
+    info!("Admin {} created tenant {}", user.username, slug);
+
+    Ok(Redirect::to("/admin/tenants"))
+}
+
+#[derive(Deserialize)]
+struct UpdateTenantForm {
+    name: String,
+    hostnames: String,
+    #[serde(default)]
+    active: Option<String>,
+}
+
+async fn update_tenant(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    axum::Form(form): axum::Form<UpdateTenantForm>,
+) -> Result<Redirect, Error> {
+    require_install_admin(&user).await?;
+
+    let hostnames = crate::services::tenants::parse_hostnames(&form.hostnames);
+    let active = form.active.is_some();
+    crate::services::tenants::update(&slug, &form.name, hostnames, active).await?;
+    info!("Admin {} updated tenant {} (active={})", user.username, slug, active);
+
+    Ok(Redirect::to("/admin/tenants"))
+}
 
 // ============================
 // Helpers
 // ============================
 
+fn impersonation_row(
+    session: &impersonation::SessionSummary,
+    now: chrono::DateTime<chrono::Utc>,
+) -> ImpersonationRow {
+    ImpersonationRow {
+        id: session.id.key_string(),
+        admin_username: session.admin_username.clone(),
+        person_username: session.person_username.clone(),
+        reason: session.reason.clone(),
+        ip: session.ip.clone(),
+        user_agent: session.user_agent.clone().unwrap_or_default(),
+        started_at: session.started_at.format("%b %d, %Y %H:%M").to_string(),
+        ended_at: session
+            .finished_at(now)
+            .map(|d| d.format("%b %d, %Y %H:%M").to_string())
+            .unwrap_or_default(),
+        action_count: session.action_count,
+    }
+}
+
+async fn person_is_admin(person: &surrealdb::types::RecordId) -> bool {
+    DB.query("SELECT VALUE is_admin FROM ONLY $pid")
+        .bind(("pid", person.clone()))
+        .await
+        .and_then(|mut r| r.take::<Option<bool>>(0))
+        .ok()
+        .flatten()
+        .unwrap_or(false)
+}
+
+fn format_interval(secs: u64) -> String {
+    if secs % 86400 == 0 {
+        format!("{}d", secs / 86400)
+    } else if secs % 3600 == 0 {
+        format!("{}h", secs / 3600)
+    } else if secs % 60 == 0 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 async fn count_table(table: &str) -> usize {
     use crate::models::system::System;
     System::count_records(table).await.unwrap_or(0)
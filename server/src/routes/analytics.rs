@@ -1,19 +1,30 @@
 use askama::Template;
-use axum::{Router, extract::Request, response::Html, routing::get};
-use tracing::error;
+use axum::{
+    Form, Router,
+    extract::Request,
+    response::{Html, Redirect},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use tracing::{error, info};
 
 use crate::{
     error::Error,
-    middleware::UserExtractor,
+    middleware::{AuthenticatedUser, UserExtractor},
     models::analytics::AnalyticsModel,
     models::person::Person,
     templates::{BaseContext, ProfileAnalyticsTemplate, User},
 };
 
 pub fn router() -> Router {
-    Router::new().route("/profile/analytics", get(analytics_page))
+    Router::new()
+        .route("/profile/analytics", get(analytics_page))
+        .route("/profile/analytics/privacy", post(update_privacy))
 }
 
+/// How many named viewers a pro member sees
+const RECENT_VIEWERS_LIMIT: usize = 20;
+
 async fn analytics_page(request: Request) -> Result<Html<String>, Error> {
     let current_user = match request.get_user() {
         Some(u) => u,
@@ -29,6 +40,13 @@ async fn analytics_page(request: Request) -> Result<Html<String>, Error> {
 
     let profile_id = &person.id;
     let analytics = AnalyticsModel::get_profile_analytics(profile_id).await?;
+    let is_pro = Person::is_pro(profile_id).await;
+    let recent_viewers = if is_pro {
+        AnalyticsModel::get_recent_viewers(profile_id, 30, RECENT_VIEWERS_LIMIT).await?
+    } else {
+        Vec::new()
+    };
+    let browse_anonymously = AnalyticsModel::browses_anonymously(profile_id).await?;
 
     let template = ProfileAnalyticsTemplate {
         app_name: base.app_name,
@@ -45,6 +63,11 @@ async fn analytics_page(request: Request) -> Result<Html<String>, Error> {
         views_90d: analytics.views_90d,
         views_1y: analytics.views_1y,
         referrer_breakdown: analytics.referrer_breakdown,
+        recent_viewer_count: analytics.recent_viewer_count,
+        anonymous_views_30d: analytics.anonymous_views_30d,
+        recent_viewers,
+        is_pro,
+        browse_anonymously,
     };
 
     let html = template.render().map_err(|e| {
@@ -54,3 +77,23 @@ async fn analytics_page(request: Request) -> Result<Html<String>, Error> {
 
     Ok(Html(html))
 }
+
+#[derive(Debug, Deserialize)]
+struct PrivacyForm {
+    browse_anonymously: Option<String>,
+}
+
+async fn update_privacy(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Form(form): Form<PrivacyForm>,
+) -> Result<Redirect, Error> {
+    let person = Person::find_by_id(&current_user.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+
+    let anonymous = form.browse_anonymously.as_deref() == Some("on");
+    AnalyticsModel::set_browse_anonymously(&person.id, anonymous).await?;
+    info!("Anonymous browsing set to '{}' for user: {}", anonymous, current_user.username);
+
+    Ok(Redirect::to("/profile/analytics"))
+}
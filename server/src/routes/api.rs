@@ -1,7 +1,8 @@
 use axum::{
     Extension, Json, Router,
     extract::{Path, Query},
-    http::{StatusCode, header},
+    http::{HeaderMap, StatusCode, header},
+    middleware::from_fn,
     response::{IntoResponse, Redirect, Response},
     routing::{delete, get, post},
 };
@@ -11,11 +12,25 @@ use std::sync::Arc;
 use tracing::{debug, error, info};
 
 use crate::db::DB;
+use crate::error::Error;
+use crate::middleware::rate_limit::search_rate_limit_middleware;
+use crate::middleware::recent_requests;
 use crate::middleware::{AuthenticatedUser, CurrentUser};
+use crate::models::feedback::{self, FeedbackModel};
 use crate::models::involvement::InvolvementModel;
+use crate::models::person_availability::AvailabilityWindow;
 use crate::models::production::ProductionModel;
+use crate::models::search_history::SearchHistoryModel;
 use crate::models::system::System;
 use crate::record_id_ext::RecordIdExt;
+use crate::response::json_list;
+use crate::services::search::{self as search_service, Pagination, SearchKind, SearchParams};
+use crate::services::search_connections::Connections;
+use crate::services::search_scope::{self, OrgScope};
+use crate::services::search_visibility::{self, Viewer};
+use crate::services::{
+    mentions, palette as palette_service, search_cache, search_log, search_suggest, search_utils,
+};
 
 /// Escape HTML special characters to prevent XSS in SSE HTML fragments.
 /// Uses ammonia::clean_text which escapes <, >, &, ", '.
@@ -45,6 +60,14 @@ pub fn router() -> Router {
         .route("/tmdb/search", get(tmdb_search))
         .route("/tmdb/credits/{person_id}", get(tmdb_credits))
         .route("/tmdb/import", post(tmdb_import))
+        .route(
+            "/search",
+            get(search).layer(from_fn(search_rate_limit_middleware)),
+        )
+        .route("/search/suggest", get(search_suggestions))
+        .route("/search/history", delete(clear_search_history))
+        .route("/palette", get(palette))
+        .route("/mentions", get(mention_suggestions))
         .route("/productions/search", get(productions_search))
         .route("/productions/{slug}/claim", post(production_claim))
         .route("/involvements", post(create_involvement))
@@ -328,6 +351,235 @@ async fn tmdb_import(
     }))
 }
 
+// --- Search ---
+
+#[derive(Debug, Deserialize)]
+struct SearchApiQuery {
+    q: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+    available_from: Option<String>,
+    available_to: Option<String>,
+    scope: Option<String>,
+}
+
+/// The site search as JSON, one entity type at a time:
+/// `GET /api/search?q=...&type=people|organizations|locations|productions|jobs`.
+/// People searches can pass `available_from`/`available_to` (YYYY-MM-DD) to
+/// get only people free for that whole window. Uses the same ranking as the
+/// search page. Signed-in callers don't see
+/// people they've blocked or muted, or who blocked them, and get people and
+/// productions they're connected to ranked higher. `scope=org:<slug>` keeps
+/// results to that organization's roster, locations, productions and jobs.
+async fn search(
+    user: Option<Extension<Arc<CurrentUser>>>,
+    Query(params): Query<SearchApiQuery>,
+) -> Result<Response, Error> {
+    let query = params.q.as_deref().unwrap_or("").trim().to_string();
+    if query.is_empty() {
+        return Err(Error::BadRequest("Missing search query `q`".to_string()));
+    }
+    let kind_name = params.kind.as_deref().unwrap_or("people");
+    let kind = SearchKind::parse(kind_name).ok_or_else(|| {
+        let names: Vec<&str> = SearchKind::ALL.iter().map(|k| k.as_str()).collect();
+        Error::BadRequest(format!("`type` must be one of: {}", names.join(", ")))
+    })?;
+    let available = AvailabilityWindow::parse(
+        params.available_from.as_deref(),
+        params.available_to.as_deref(),
+    )?;
+    let scope_slug = search_scope::parse(params.scope.as_deref())?;
+    let (page, per_page) = Pagination::clamp(params.page, params.per_page);
+    let (limit, offset) = Pagination::window(page, per_page);
+
+    let embedding = search_cache::embedding(&query).await;
+    let config = crate::config::search_config();
+
+    // People get structured filters; the rest a location filter, as on the search page
+    let parsed = search_utils::parse_query(&query);
+    let (location, cleaned) = search_utils::extract_location(&query);
+    let normalized = search_utils::normalize_query(&cleaned);
+    // People and productions tied to a signed-in caller rank higher
+    let connections = match &user {
+        Some(user) if matches!(kind, SearchKind::People | SearchKind::Productions) => {
+            match surrealdb::types::RecordId::parse_simple(&user.id) {
+                Ok(person) => Connections::load(&person).await.ok(),
+                Err(_) => None,
+            }
+        }
+        _ => None,
+    };
+    // Members-only profiles are for signed-in callers, and blocks go both ways
+    let viewer = match &user {
+        Some(user) if kind == SearchKind::People => {
+            match surrealdb::types::RecordId::parse_simple(&user.id) {
+                Ok(person) => Viewer::load(&person).await.ok(),
+                Err(_) => None,
+            }
+        }
+        _ => None,
+    };
+    // Members also see the talent their organization represents
+    let scope = match &scope_slug {
+        Some(slug) => {
+            let searcher = user
+                .as_ref()
+                .and_then(|user| surrealdb::types::RecordId::parse_simple(&user.id).ok());
+            Some(OrgScope::load(slug, searcher.as_ref()).await?)
+        }
+        None => None,
+    };
+    let search_params = SearchParams {
+        query: if kind == SearchKind::People {
+            &parsed.cleaned
+        } else {
+            &normalized
+        },
+        embedding: embedding.as_ref(),
+        config,
+        limit,
+        offset,
+        connections: connections.as_ref(),
+        viewer: viewer.as_ref(),
+        syntax: None,
+        scope: scope.as_ref(),
+    };
+
+    Ok(match kind {
+        SearchKind::People => {
+            let mut people =
+                search_service::search_people(&search_params, &parsed, None, available.as_ref())
+                    .await?;
+            let pagination = Pagination::trim(&mut people, page, per_page);
+            search_response(&query, kind, people, pagination)
+        }
+        SearchKind::Organizations => {
+            let mut orgs =
+                search_service::search_organizations(&search_params, location.as_deref()).await?;
+            let pagination = Pagination::trim(&mut orgs, page, per_page);
+            search_response(&query, kind, orgs, pagination)
+        }
+        SearchKind::Locations => {
+            let mut locations =
+                search_service::search_locations(&search_params, location.as_deref(), None).await?;
+            let pagination = Pagination::trim(&mut locations, page, per_page);
+            search_response(&query, kind, locations, pagination)
+        }
+        SearchKind::Productions => {
+            let mut productions = search_service::search_productions(&search_params, None).await?;
+            let pagination = Pagination::trim(&mut productions, page, per_page);
+            search_response(&query, kind, productions, pagination)
+        }
+        SearchKind::Jobs => {
+            let mut jobs =
+                search_service::search_jobs(&search_params, location.as_deref(), true).await?;
+            let pagination = Pagination::trim(&mut jobs, page, per_page);
+            search_response(&query, kind, jobs, pagination)
+        }
+    })
+}
+
+/// Log a search and send its results
+fn search_response<T: Serialize + Send + 'static>(
+    query: &str,
+    kind: SearchKind,
+    results: Vec<T>,
+    pagination: Pagination,
+) -> Response {
+    search_log::log_search(query, "api", kind.as_str(), Some(results.len()));
+    json_list(
+        serde_json::json!({
+            "query": query,
+            "type": kind.as_str(),
+            "pagination": pagination,
+        }),
+        Some("results"),
+        results,
+    )
+}
+
+#[derive(Debug, Deserialize)]
+struct SuggestQuery {
+    q: Option<String>,
+}
+
+/// Type-ahead for the navbar search box: `GET /api/search/suggest?q=...`.
+/// Up to 8 people, organizations, locations and productions whose names start
+/// with the words typed so far. Keystrokes aren't logged as searches.
+async fn search_suggestions(Query(params): Query<SuggestQuery>) -> impl IntoResponse {
+    let query = params.q.unwrap_or_default();
+    let suggestions = search_suggest::suggest(&query).await;
+    Json(serde_json::json!({
+        "query": query.trim(),
+        "suggestions": suggestions,
+    }))
+}
+
+/// Forget the caller's recent searches: `DELETE /api/search/history`
+async fn clear_search_history(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<StatusCode, Error> {
+    let person = surrealdb::types::RecordId::parse_simple(&user.id)
+        .map_err(|e| Error::BadRequest(e.to_string()))?;
+    SearchHistoryModel::clear(&person).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The Cmd-K palette: `GET /api/palette?q=...`. Pages and settings, the
+/// caller's own productions and recent chats, and navbar search suggestions,
+/// all matching `q`. Visitors only get the public pages and search hits;
+/// people the caller has blocked or muted, or who blocked them, are left out.
+async fn palette(
+    user: Option<Extension<Arc<CurrentUser>>>,
+    Query(params): Query<SuggestQuery>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let query = params.q.unwrap_or_default();
+    let person = match &user {
+        Some(user) => Some(
+            surrealdb::types::RecordId::parse_simple(&user.id)
+                .map_err(|e| Error::BadRequest(e.to_string()))?,
+        ),
+        None => None,
+    };
+    let items = palette_service::palette(&query, person.as_ref()).await?;
+    Ok(Json(serde_json::json!({
+        "query": query.trim(),
+        "items": items,
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+struct MentionQuery {
+    production: Option<String>,
+    org: Option<String>,
+    q: Option<String>,
+}
+
+/// Members to offer after `@`:
+/// `GET /api/mentions?production=<slug>&q=...` (or `org=<slug>`). Only the
+/// production's or organization's own members can look them up.
+async fn mention_suggestions(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<MentionQuery>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let scope = match (&params.production, &params.org) {
+        (Some(slug), _) => mentions::scope("production", slug).await?,
+        (None, Some(slug)) => mentions::scope("org", slug).await?,
+        (None, None) => {
+            return Err(Error::BadRequest(
+                "Pass a production or org to look up".to_string(),
+            ));
+        }
+    };
+    if !mentions::is_member(&scope, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+    let people = mentions::candidates(&scope, params.q.as_deref().unwrap_or("")).await?;
+    Ok(Json(serde_json::json!({ "people": people })))
+}
+
 // --- Production Search ---
 
 /// Search productions by title for autocomplete / dedup
@@ -720,35 +972,43 @@ async fn reject_involvement(
 struct FeedbackRequest {
     page_url: String,
     message: String,
+    /// "feedback" or "bug"
+    #[serde(default)]
+    kind: Option<String>,
 }
 
 #[axum::debug_handler]
 async fn submit_feedback(
     user: Option<Extension<Arc<CurrentUser>>>,
+    headers: HeaderMap,
     Json(body): Json<FeedbackRequest>,
 ) -> impl IntoResponse {
-    let message = body.message.trim().to_string();
-    if message.is_empty() {
-        return Json(serde_json::json!({ "error": "Message is required" }));
-    }
-    if message.len() > 2000 {
-        return Json(serde_json::json!({ "error": "Message must be 2000 characters or less" }));
-    }
+    let submission =
+        match feedback::parse_submission(body.kind.as_deref(), &body.message, &body.page_url) {
+            Ok(submission) => submission,
+            Err(Error::Validation(msg)) => return Json(serde_json::json!({ "error": msg })),
+            Err(e) => return Json(serde_json::json!({ "error": e.to_string() })),
+        };
 
+    let user = user.map(|Extension(u)| u);
     let username = user
+        .as_ref()
         .map(|u| u.username.clone())
         .unwrap_or_else(|| "anonymous".to_string());
-
-    let page_url = body.page_url;
+    let person = user
+        .as_ref()
+        .and_then(|u| surrealdb::types::RecordId::parse_simple(&u.id).ok());
+    let request_ids = recent_requests::recent_for(user.as_deref(), &headers);
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok());
+
+    let page_url = submission.page_url.clone();
+    let message = submission.message.clone();
     debug!("Feedback from {} on {}", username, page_url);
 
-    let sql = "INSERT INTO feedback (username, page_url, message) VALUES ($username, $page_url, $message)";
-    if let Err(e) = DB
-        .query(sql)
-        .bind(("username", username.clone()))
-        .bind(("page_url", page_url.clone()))
-        .bind(("message", message.clone()))
-        .await
+    if let Err(e) =
+        FeedbackModel::create(&submission, &username, person, request_ids, user_agent).await
     {
         error!("Failed to save feedback: {}", e);
         return Json(serde_json::json!({ "error": "Failed to save feedback" }));
@@ -758,7 +1018,7 @@ async fn submit_feedback(
     let username_owned = username.clone();
     let page_url_owned = page_url.clone();
     let message_owned = message.clone();
-    tokio::spawn(async move {
+    crate::db::spawn(async move {
         match crate::services::email::EmailService::from_env() {
             Ok(email_service) => {
                 if let Err(e) = email_service
@@ -845,7 +1105,8 @@ async fn people_search(
         avatar_url: Option<String>,
     }
 
-    let sql = "SELECT
+    let sql = format!(
+        "SELECT
             <string> id AS id,
             name,
             username,
@@ -854,10 +1115,13 @@ async fn people_search(
             created_at
         FROM person
         WHERE
-            string::lowercase(name ?? '') CONTAINS $q
-            OR string::lowercase(username ?? '') CONTAINS $q
+            (string::lowercase(name ?? '') CONTAINS $q
+                OR string::lowercase(username ?? '') CONTAINS $q)
+            AND {}
         ORDER BY _vord DESC, created_at DESC
-        LIMIT 8";
+        LIMIT 8",
+        search_visibility::NOT_HIDDEN
+    );
 
     let results: Vec<PersonHit> = match DB.query(sql).bind(("q", query_lower)).await {
         Ok(mut resp) => resp.take(0).unwrap_or_default(),
@@ -973,7 +1237,8 @@ async fn people_search_sse(
         avatar_url: Option<String>,
     }
 
-    let sql = "SELECT
+    let sql = format!(
+        "SELECT
             <string> id AS id,
             name,
             username,
@@ -982,10 +1247,13 @@ async fn people_search_sse(
             created_at
         FROM person
         WHERE
-            string::lowercase(name ?? '') CONTAINS $q
-            OR string::lowercase(username ?? '') CONTAINS $q
+            (string::lowercase(name ?? '') CONTAINS $q
+                OR string::lowercase(username ?? '') CONTAINS $q)
+            AND {}
         ORDER BY _vord DESC, created_at DESC
-        LIMIT 8";
+        LIMIT 8",
+        search_visibility::NOT_HIDDEN
+    );
 
     let results: Vec<PersonHit> = match DB.query(sql).bind(("q", query_lower)).await {
         Ok(mut resp) => resp.take(0).unwrap_or_default(),
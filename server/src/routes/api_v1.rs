@@ -0,0 +1,586 @@
+//! Versioned API for third-party apps, mounted at `/api/v1`
+//!
+//! Every request carries an OAuth access token (see `services::oauth`) as a
+//! bearer token, and each endpoint needs one scope. Errors follow RFC 6750.
+//! The exception is `/public`, which anyone can read within a rate limit (see
+//! `services::public_api`). `/triggers` and `/hooks` serve workflow tools (see
+//! `services::triggers`).
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::future::Future;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::rate_limit::with_rate_headers,
+    models::{
+        equipment::{CreateEquipmentData, Equipment, EquipmentModel},
+        person::Person,
+        production::ProductionModel,
+        timecard::{self, TimecardListing, TimecardModel},
+    },
+    record_id_ext::RecordIdExt,
+    response::json_list,
+    services::{
+        login_security::ClientInfo,
+        oauth::{self, AccessGrant},
+        public_api::{self, RateLimit},
+        triggers::{self, Cursor, Subscription},
+    },
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/me", get(me))
+        .route("/equipment", get(list_equipment).post(create_equipment))
+        .route("/productions/{slug}/timecards", get(approved_timecards))
+        .route("/public/profiles/{username}", get(public_profile))
+        .route("/public/organizations/{slug}/members", get(public_members))
+        .route("/triggers/jobs", get(job_trigger))
+        .route("/triggers/jobs/sample", get(job_trigger_sample))
+        .route("/triggers/applications", get(application_trigger))
+        .route(
+            "/triggers/applications/sample",
+            get(application_trigger_sample),
+        )
+        .route("/hooks", get(list_hooks).post(create_hook))
+        .route("/hooks/{id}", delete(delete_hook))
+}
+
+/// Bearer token error (RFC 6750 §3)
+struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    description: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, code: &'static str, description: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            description: description.into(),
+        }
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(e: Error) -> Self {
+        let status = e.status_code();
+        if status.is_server_error() {
+            error!("API request failed: {}", e);
+            return Self::new(status, "server_error", "Internal error");
+        }
+        let code = match status {
+            StatusCode::NOT_FOUND => "not_found",
+            StatusCode::FORBIDDEN => "forbidden",
+            _ => "invalid_request",
+        };
+        let description = match e {
+            Error::Conflict(m) | Error::Validation(m) | Error::BadRequest(m) => m,
+            other => other.to_string(),
+        };
+        Self::new(status, code, description)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut response = (
+            self.status,
+            Json(json!({
+                "error": self.code,
+                "error_description": self.description,
+            })),
+        )
+            .into_response();
+        if matches!(
+            self.status,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+        ) && let Ok(value) = HeaderValue::from_str(&format!("Bearer error=\"{}\"", self.code))
+        {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, value);
+        }
+        response
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// Check the bearer token
+async fn authenticate(headers: &HeaderMap) -> Result<AccessGrant, ApiError> {
+    let token = bearer_token(headers).ok_or_else(|| {
+        ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "invalid_request",
+            "Bearer token required",
+        )
+    })?;
+    let grant = oauth::authenticate(token).await?.ok_or_else(|| {
+        ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "invalid_token",
+            "The access token is invalid or expired",
+        )
+    })?;
+    Ok(grant)
+}
+
+/// Check the bearer token and that it was granted `scope`
+async fn authorize(headers: &HeaderMap, scope: &str) -> Result<AccessGrant, ApiError> {
+    let grant = authenticate(headers).await?;
+    if !grant.allows(scope) {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "insufficient_scope",
+            format!("This needs the {} scope", scope),
+        ));
+    }
+    Ok(grant)
+}
+
+// ============================
+// Profile
+// ============================
+
+async fn me(headers: HeaderMap) -> Result<Response, ApiError> {
+    let grant = authorize(&headers, "read:profile").await?;
+    let person = Person::find_by_id(&grant.person.to_raw_string())
+        .await?
+        .ok_or(Error::NotFound)?;
+    let profile = person.profile.as_ref();
+    Ok(Json(json!({
+        "id": person.id.key_string(),
+        "username": person.username,
+        "name": person.name,
+        "headline": profile.and_then(|p| p.headline.clone()),
+        "location": profile.and_then(|p| p.location.clone()),
+        "avatar": profile.and_then(|p| p.avatar.clone()),
+        "profile_url": format!("{}/{}", crate::config::app_url(), person.username),
+    }))
+    .into_response())
+}
+
+// ============================
+// Equipment
+// ============================
+
+#[derive(Debug, Serialize)]
+struct EquipmentItem {
+    id: String,
+    name: String,
+    category: String,
+    condition: String,
+    manufacturer: Option<String>,
+    model: Option<String>,
+    serial_number: Option<String>,
+    notes: Option<String>,
+    is_available: bool,
+}
+
+impl From<Equipment> for EquipmentItem {
+    fn from(equipment: Equipment) -> Self {
+        Self {
+            id: equipment.id.key_string(),
+            name: equipment.name,
+            category: equipment.category.name,
+            condition: equipment.condition.name,
+            manufacturer: equipment.manufacturer,
+            model: equipment.model,
+            serial_number: equipment.serial_number,
+            notes: equipment.notes,
+            is_available: equipment.can_check_out(),
+        }
+    }
+}
+
+async fn list_equipment(headers: HeaderMap) -> Result<Response, ApiError> {
+    let grant = authorize(&headers, "read:equipment").await?;
+    let items: Vec<EquipmentItem> =
+        EquipmentModel::list_equipment_for_owner("person", &grant.person.key_string())
+            .await?
+            .into_iter()
+            .map(EquipmentItem::from)
+            .collect();
+    Ok(json_list(json!({}), Some("equipment"), items))
+}
+
+#[derive(Debug, Deserialize)]
+struct NewEquipment {
+    name: String,
+    /// Category name, e.g. "Camera"
+    category: String,
+    /// Condition name; defaults to the first one listed
+    condition: Option<String>,
+    manufacturer: Option<String>,
+    model: Option<String>,
+    serial_number: Option<String>,
+    notes: Option<String>,
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+async fn create_equipment(
+    headers: HeaderMap,
+    Json(body): Json<NewEquipment>,
+) -> Result<Response, ApiError> {
+    let grant = authorize(&headers, "write:equipment").await?;
+    let name = body.name.trim().to_string();
+    if name.is_empty() {
+        return Err(Error::Validation("name is required".to_string()).into());
+    }
+    let category = EquipmentModel::get_all_categories()
+        .await?
+        .into_iter()
+        .find(|c| c.name.eq_ignore_ascii_case(body.category.trim()))
+        .ok_or_else(|| Error::Validation(format!("Unknown category \"{}\"", body.category)))?;
+    let conditions = EquipmentModel::get_all_conditions().await?;
+    let condition = match body.condition.as_deref().map(str::trim) {
+        Some(wanted) => conditions
+            .into_iter()
+            .find(|c| c.name.eq_ignore_ascii_case(wanted)),
+        None => conditions.into_iter().next(),
+    }
+    .ok_or_else(|| {
+        Error::Validation(format!(
+            "Unknown condition \"{}\"",
+            body.condition.unwrap_or_default()
+        ))
+    })?;
+
+    let equipment = EquipmentModel::create_equipment(CreateEquipmentData {
+        name,
+        category: category.id.key_string(),
+        serial_number: non_empty(body.serial_number),
+        model: non_empty(body.model),
+        manufacturer: non_empty(body.manufacturer),
+        description: None,
+        purchase_date: None,
+        purchase_price: None,
+        replacement_value: None,
+        condition: condition.id.key_string(),
+        notes: non_empty(body.notes),
+        owner_type: "person".to_string(),
+        owner_person: Some(grant.person.key_string()),
+        owner_organization: None,
+        is_kit_item: false,
+        parent_kit: None,
+        current_location: None,
+    })
+    .await?;
+    info!(
+        "Equipment {} created through the API by {}",
+        equipment.id.display(),
+        grant.client.display()
+    );
+    Ok((StatusCode::CREATED, Json(EquipmentItem::from(equipment))).into_response())
+}
+
+// ============================
+// Timecards
+// ============================
+
+/// Approved timecards per page
+const TIMECARD_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct PageQuery {
+    /// `next_cursor` from the previous page
+    cursor: Option<String>,
+}
+
+/// Approved timecards, for payroll, a page at a time in payroll order. Only
+/// for people who approve timecards on the production.
+async fn approved_timecards(
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Response, ApiError> {
+    let grant = authorize(&headers, "read:timecards").await?;
+    let user_id = grant.person.to_raw_string();
+    let production = ProductionModel::get_by_slug(&slug).await?;
+    let can_approve = ProductionModel::can_edit(&production.id, &user_id).await?
+        || ProductionModel::get_members(&production.id)
+            .await?
+            .into_iter()
+            .find(|m| m.id == user_id && m.invitation_status == "accepted")
+            .is_some_and(|m| timecard::is_producer(&m.production_roles.unwrap_or_default()));
+    if !can_approve {
+        return Err(Error::Forbidden.into());
+    }
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+    let page = TimecardModel::approved_page(&production.id, cursor, TIMECARD_PAGE_SIZE).await?;
+    let timecards: Vec<serde_json::Value> = page
+        .items
+        .into_iter()
+        .map(|t: TimecardListing| {
+            let hours = t.hours();
+            json!({
+                "id": t.id.key_string(),
+                "person_id": t.person.key_string(),
+                "person_name": t.person_name,
+                "date": t.date,
+                "day_number": t.day_number,
+                "time_in": t.time_in,
+                "time_out": t.time_out,
+                "meal_minutes": t.meal_minutes,
+                "hours": hours.worked,
+                "regular_hours": hours.regular,
+                "overtime_hours": hours.overtime,
+                "double_time_hours": hours.double_time,
+                "note": t.note,
+            })
+        })
+        .collect();
+    Ok(json_list(
+        json!({ "production": production.slug, "next_cursor": page.next }),
+        Some("timecards"),
+        timecards,
+    ))
+}
+
+// ============================
+// Public
+// ============================
+
+/// Count the request against the caller's allowance: its app's if it sent an
+/// access token, otherwise its IP's
+async fn public_rate(headers: &HeaderMap, client: &ClientInfo) -> Result<RateLimit, ApiError> {
+    let limits = crate::config::public_api();
+    let (key, limit) = match bearer_token(headers) {
+        Some(token) => {
+            let grant = oauth::authenticate(token).await?.ok_or_else(|| {
+                ApiError::new(
+                    StatusCode::UNAUTHORIZED,
+                    "invalid_token",
+                    "The access token is invalid or expired",
+                )
+            })?;
+            (
+                format!("app:{}", grant.client.key_string()),
+                limits.app_requests_per_minute,
+            )
+        }
+        None => (format!("ip:{}", client.ip), limits.requests_per_minute),
+    };
+    Ok(public_api::check_rate(&key, limit))
+}
+
+/// Serve a public payload with its ETag, or an empty 304 if the caller
+/// already has it
+async fn serve_public<T: Serialize>(
+    headers: &HeaderMap,
+    client: &ClientInfo,
+    load: impl Future<Output = Result<T, Error>>,
+) -> Response {
+    let rate = match public_rate(headers, client).await {
+        Ok(rate) => rate,
+        Err(e) => return e.into_response(),
+    };
+    if !rate.allowed {
+        let error = ApiError::new(
+            StatusCode::TOO_MANY_REQUESTS,
+            "rate_limited",
+            format!(
+                "Too many requests; try again in {} seconds",
+                rate.reset_secs
+            ),
+        );
+        return with_rate_headers(error.into_response(), &rate);
+    }
+    let body = match load.await.and_then(|payload| {
+        serde_json::to_vec(&payload).map_err(|e| Error::Internal(e.to_string()))
+    }) {
+        Ok(body) => body,
+        Err(e) => return with_rate_headers(ApiError::from(e).into_response(), &rate),
+    };
+
+    let etag = public_api::etag_for(&body);
+    let cache = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, "public, max-age=60".to_string()),
+    ];
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| public_api::etag_matches(v, &etag));
+    let response = if unchanged {
+        (StatusCode::NOT_MODIFIED, cache).into_response()
+    } else {
+        (
+            cache,
+            [(header::CONTENT_TYPE, "application/json".to_string())],
+            body,
+        )
+            .into_response()
+    };
+    with_rate_headers(response, &rate)
+}
+
+async fn public_profile(
+    client: ClientInfo,
+    headers: HeaderMap,
+    Path(username): Path<String>,
+) -> Response {
+    serve_public(&headers, &client, public_api::profile(&username)).await
+}
+
+async fn public_members(
+    client: ClientInfo,
+    headers: HeaderMap,
+    Path(slug): Path<String>,
+) -> Response {
+    serve_public(&headers, &client, async {
+        Ok(json!({
+            "organization": slug,
+            "members": public_api::organization_members(&slug).await?,
+        }))
+    })
+    .await
+}
+
+// ============================
+// Triggers
+// ============================
+
+#[derive(Debug, Deserialize)]
+struct TriggerQuery {
+    cursor: Option<String>,
+}
+
+impl TriggerQuery {
+    fn cursor(&self) -> Result<Option<Cursor>, ApiError> {
+        match self
+            .cursor
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+        {
+            None => Ok(None),
+            Some(value) => Cursor::decode(value)
+                .map(Some)
+                .ok_or_else(|| Error::Validation("Invalid cursor".to_string()).into()),
+        }
+    }
+}
+
+/// A poll's items as a bare array, which is what workflow tools expect, with
+/// where to pick up next in `X-Next-Cursor`
+fn trigger_response<T: Serialize + Send + 'static>(items: Vec<T>, next: Option<&str>) -> Response {
+    let mut response = json_list(json!({}), None, items);
+    if let Some(value) = next.and_then(|n| HeaderValue::from_str(n).ok()) {
+        response.headers_mut().insert("x-next-cursor", value);
+    }
+    response
+}
+
+async fn job_trigger(
+    headers: HeaderMap,
+    Query(query): Query<TriggerQuery>,
+) -> Result<Response, ApiError> {
+    authorize(&headers, "read:jobs").await?;
+    let cursor = query.cursor()?;
+    let items = triggers::jobs_since(cursor.as_ref()).await?;
+    let next = items
+        .first()
+        .map(|i| i.cursor.clone())
+        .or(query.cursor.clone());
+    Ok(trigger_response(items, next.as_deref()))
+}
+
+async fn job_trigger_sample(headers: HeaderMap) -> Result<Response, ApiError> {
+    authorize(&headers, "read:jobs").await?;
+    Ok(Json([triggers::sample_job()]).into_response())
+}
+
+async fn application_trigger(
+    headers: HeaderMap,
+    Query(query): Query<TriggerQuery>,
+) -> Result<Response, ApiError> {
+    let grant = authorize(&headers, "read:applications").await?;
+    let cursor = query.cursor()?;
+    let items = triggers::applications_since(&grant.person, cursor.as_ref()).await?;
+    let next = items
+        .first()
+        .map(|i| i.cursor.clone())
+        .or(query.cursor.clone());
+    Ok(trigger_response(items, next.as_deref()))
+}
+
+async fn application_trigger_sample(headers: HeaderMap) -> Result<Response, ApiError> {
+    authorize(&headers, "read:applications").await?;
+    Ok(Json([triggers::sample_application()]).into_response())
+}
+
+// ============================
+// REST hooks
+// ============================
+
+fn hook_json(subscription: &Subscription) -> serde_json::Value {
+    json!({
+        "id": subscription.id.key_string(),
+        "event": subscription.event,
+        "target_url": subscription.target_url,
+        "created_at": subscription.created_at,
+    })
+}
+
+async fn list_hooks(headers: HeaderMap) -> Result<Response, ApiError> {
+    let grant = authenticate(&headers).await?;
+    let hooks: Vec<serde_json::Value> = triggers::subscriptions(&grant.person, &grant.client)
+        .await?
+        .iter()
+        .map(hook_json)
+        .collect();
+    Ok(Json(json!({ "hooks": hooks })).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct NewHook {
+    /// `job.created` or `application.created`
+    event: String,
+    target_url: String,
+}
+
+/// Subscribe to an event. The secret deliveries are signed with is only
+/// returned here.
+async fn create_hook(headers: HeaderMap, Json(body): Json<NewHook>) -> Result<Response, ApiError> {
+    let scope = triggers::event_scope(&body.event)
+        .ok_or_else(|| Error::Validation(format!("Unknown event \"{}\"", body.event)))?;
+    let grant = authorize(&headers, scope).await?;
+    let (subscription, secret) =
+        triggers::subscribe(&grant.person, &grant.client, &body.event, &body.target_url).await?;
+    let mut payload = hook_json(&subscription);
+    payload["secret"] = json!(secret);
+    Ok((StatusCode::CREATED, Json(payload)).into_response())
+}
+
+async fn delete_hook(headers: HeaderMap, Path(id): Path<String>) -> Result<Response, ApiError> {
+    let grant = authenticate(&headers).await?;
+    triggers::unsubscribe(&grant.person, &grant.client, &id).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -0,0 +1,342 @@
+use askama::Template;
+use axum::{
+    Form, Json, Router,
+    extract::Path,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        audio_reel::{self, AudioReel, AudioReelDetails, AudioReelModel},
+        person::{Person, SessionUser},
+    },
+    record_id_ext::RecordIdExt,
+    services::{s3::s3, uploads},
+    templates::{AudioReelDisplay, BaseContext, User, filters},
+    verification_limits,
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/profile/audio", get(manage_page).post(add_reel))
+        .route("/profile/audio/uploads", post(start_upload))
+        .route("/profile/audio/{reel_id}", post(update_reel))
+        .route("/profile/audio/{reel_id}/move", post(move_reel))
+        .route("/profile/audio/{reel_id}/delete", post(delete_reel))
+        .route("/profile/audio/{reel_id}/status", get(reel_status))
+}
+
+// ============================
+// Views
+// ============================
+
+/// A reel on the manage page, with the raw values its edit form needs
+pub struct AudioReelRow {
+    pub reel: AudioReelDisplay,
+    pub description: String,
+    pub genre: String,
+    pub mood: String,
+    pub instruments: String,
+    pub transcript: String,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "persons/audio_reels.html")]
+pub struct AudioReelsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub reels: Vec<AudioReelRow>,
+    pub limit: Option<usize>,
+    pub can_add: bool,
+    pub accept: String,
+    pub max_bytes: i64,
+    pub error: Option<String>,
+}
+
+// ============================
+// Helpers
+// ============================
+
+fn person_id(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+async fn limit_for(user: &SessionUser) -> Result<Option<usize>, Error> {
+    let person = Person::find_by_id(&user.id).await?.ok_or(Error::NotFound)?;
+    Ok(verification_limits::limits_for_status(&person.verification_status).max_audio_reels)
+}
+
+/// Refuse another reel when the person is at their limit
+async fn ensure_room(user: &SessionUser, person: &RecordId) -> Result<(), Error> {
+    if let Some(max) = limit_for(user).await?
+        && AudioReelModel::for_person(person).await?.len() >= max
+    {
+        return Err(Error::bad_request(format!(
+            "Maximum of {} audio reels allowed. Get verified to remove this limit.",
+            max
+        )));
+    }
+    Ok(())
+}
+
+async fn to_display(reel: &AudioReel) -> Result<AudioReelDisplay, Error> {
+    let tags = reel
+        .extra_tags()
+        .map(|tag| (tag.label().to_string(), tag.value.clone()))
+        .collect();
+    Ok(AudioReelDisplay {
+        id: reel.id.key_string(),
+        title: reel.title.clone(),
+        description: reel.description.clone(),
+        genre: reel.genre.clone(),
+        mood: reel.mood.clone(),
+        instruments: reel.instruments.clone(),
+        duration: reel.duration_seconds.map(audio_reel::format_duration),
+        url: s3()?.generate_download_url(&reel.file_key).await?,
+        content_type: reel.content_type.clone(),
+        peaks: serde_json::to_string(&reel.peaks).unwrap_or_else(|_| "[]".to_string()),
+        processing: reel.is_pending(),
+        tags,
+    })
+}
+
+/// A person's audio reels for their profile page. Failures are logged and
+/// leave the section empty rather than breaking the page.
+pub(crate) async fn profile_audio_reels(person: &RecordId) -> Vec<AudioReelDisplay> {
+    let reels = match AudioReelModel::for_person(person).await {
+        Ok(reels) => reels,
+        Err(e) => {
+            error!("Failed to load audio reels for {}: {}", person.display(), e);
+            return Vec::new();
+        }
+    };
+    let mut displays = Vec::with_capacity(reels.len());
+    for reel in reels.iter().filter(|r| r.status != "failed") {
+        match to_display(reel).await {
+            Ok(display) => displays.push(display),
+            Err(e) => error!("Failed to show audio reel {}: {}", reel.id.display(), e),
+        }
+    }
+    displays
+}
+
+async fn render_manage(user: &SessionUser, error: Option<String>) -> Result<Response, Error> {
+    let person = person_id(user)?;
+    let limit = limit_for(user).await?;
+    let mut rows = Vec::new();
+    for reel in AudioReelModel::for_person(&person).await? {
+        rows.push(AudioReelRow {
+            reel: to_display(&reel).await?,
+            description: reel.description.clone().unwrap_or_default(),
+            genre: reel.genre.clone().unwrap_or_default(),
+            mood: reel.mood.clone().unwrap_or_default(),
+            instruments: reel.instruments.join(", "),
+            transcript: reel.transcript.clone().unwrap_or_default(),
+            error: (reel.status == "failed").then(|| {
+                "We couldn't read this file to draw its waveform. It still plays, but try re-uploading it as MP3 or WAV."
+                    .to_string()
+            }),
+        });
+    }
+
+    let base = BaseContext::new()
+        .with_page("profile")
+        .with_user(User::from_session_user(user).await);
+    let template = AudioReelsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        can_add: limit.is_none_or(|max| rows.len() < max),
+        reels: rows,
+        limit,
+        accept: audio_reel::AUDIO_TYPES
+            .iter()
+            .flat_map(|(ext, content_type)| [format!(".{}", ext), content_type.to_string()])
+            .collect::<Vec<_>>()
+            .join(","),
+        max_bytes: audio_reel::MAX_AUDIO_BYTES,
+        error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render audio reels template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn manage_page(AuthenticatedUser(user): AuthenticatedUser) -> Result<Response, Error> {
+    render_manage(&user, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct StartUploadRequest {
+    filename: String,
+    size: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct StartUploadResponse {
+    id: String,
+    url: String,
+    chunk_size: i64,
+}
+
+/// Start a resumable upload for a new audio reel
+async fn start_upload(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(body): Json<StartUploadRequest>,
+) -> Result<Json<StartUploadResponse>, Error> {
+    let person = person_id(&user)?;
+    ensure_room(&user, &person).await?;
+    let (extension, content_type) = audio_reel::audio_type(&body.filename)
+        .ok_or_else(|| Error::Validation("Upload an MP3, M4A, AAC, WAV, OGG or FLAC file".to_string()))?;
+    if body.size <= 0 || body.size > audio_reel::MAX_AUDIO_BYTES {
+        return Err(Error::Validation("Audio reels can be up to 200 MB".to_string()));
+    }
+
+    let key = audio_reel::audio_key(&person, extension);
+    let session = uploads::start(
+        &person,
+        audio_reel::UPLOAD_PURPOSE,
+        &key,
+        &body.filename,
+        content_type,
+        body.size,
+    )
+    .await?;
+    let id = session.id.key_string();
+    Ok(Json(StartUploadResponse {
+        url: format!("/uploads/{}", id),
+        id,
+        chunk_size: session.chunk_size,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct AddReelRequest {
+    upload: String,
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    genre: String,
+    #[serde(default)]
+    mood: String,
+    #[serde(default)]
+    instruments: String,
+    #[serde(default)]
+    transcript: String,
+}
+
+/// Add the reel once its upload has finished
+async fn add_reel(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Json(body): Json<AddReelRequest>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let person = person_id(&user)?;
+    let details = AudioReelDetails::parse(
+        &body.title,
+        &body.description,
+        &body.genre,
+        &body.mood,
+        &body.instruments,
+        &body.transcript,
+    )?;
+    ensure_room(&user, &person).await?;
+    let session = uploads::get(&body.upload, &person).await?;
+    if !audio_reel::owns_audio_key(&person, &session.key) {
+        return Err(Error::BadRequest("Unknown upload".to_string()));
+    }
+    let session = uploads::claim(&body.upload, &person, audio_reel::UPLOAD_PURPOSE).await?;
+
+    let reel = AudioReelModel::create(&person, &session, details).await?;
+    info!("{} added audio reel {}", user.username, reel.id.key_string());
+    Ok(Json(serde_json::json!({ "id": reel.id.key_string() })))
+}
+
+#[derive(Debug, Deserialize)]
+struct ReelForm {
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    genre: String,
+    #[serde(default)]
+    mood: String,
+    #[serde(default)]
+    instruments: String,
+    #[serde(default)]
+    transcript: String,
+}
+
+async fn update_reel(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(reel_id): Path<String>,
+    Form(form): Form<ReelForm>,
+) -> Result<Response, Error> {
+    let reel = AudioReelModel::get(&reel_id, &person_id(&user)?).await?;
+    let details = match AudioReelDetails::parse(
+        &form.title,
+        &form.description,
+        &form.genre,
+        &form.mood,
+        &form.instruments,
+        &form.transcript,
+    ) {
+        Ok(details) => details,
+        Err(Error::Validation(msg)) => return render_manage(&user, Some(msg)).await,
+        Err(e) => return Err(e),
+    };
+    AudioReelModel::update_details(&reel, details).await?;
+    Ok(Redirect::to(&format!("/profile/audio#reel-{}", reel_id)).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct MoveForm {
+    direction: String,
+}
+
+async fn move_reel(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(reel_id): Path<String>,
+    Form(form): Form<MoveForm>,
+) -> Result<Response, Error> {
+    let reel = AudioReelModel::get(&reel_id, &person_id(&user)?).await?;
+    AudioReelModel::shift(&reel, form.direction == "up").await?;
+    Ok(Redirect::to(&format!("/profile/audio#reel-{}", reel_id)).into_response())
+}
+
+async fn delete_reel(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(reel_id): Path<String>,
+) -> Result<Response, Error> {
+    let reel = AudioReelModel::get(&reel_id, &person_id(&user)?).await?;
+    AudioReelModel::delete(&reel).await?;
+    info!("{} deleted audio reel {}", user.username, reel_id);
+    Ok(Redirect::to("/profile/audio").into_response())
+}
+
+/// Whether a reel's waveform is ready, for the page waiting on it
+async fn reel_status(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(reel_id): Path<String>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let reel = AudioReelModel::get(&reel_id, &person_id(&user)?).await?;
+    Ok(Json(serde_json::json!({ "status": reel.status })))
+}
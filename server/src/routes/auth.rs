@@ -7,8 +7,7 @@ use axum::{
 };
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use serde::Deserialize;
-
-use std::env;
+use surrealdb::types::RecordId;
 
 use tracing::{debug, error, info, warn};
 
@@ -20,12 +19,16 @@ use crate::{
     response,
     services::{
         email::EmailService,
+        impersonation,
+        login_security::{self, ClientInfo},
+        password_policy,
         verification::{CodeType, VerificationService},
     },
     templates::{
         BaseContext, EmailVerificationTemplate, ForgotPasswordTemplate, LoginTemplate,
-        ResetPasswordTemplate, SignupTemplate, User,
+        LoginVerifyTemplate, ResetPasswordTemplate, SignupTemplate, User,
     },
+    validation::{FieldErrors, Validate},
 };
 
 pub fn router() -> Router {
@@ -33,6 +36,7 @@ pub fn router() -> Router {
         .route("/i/{token}", get(invite_link))
         .route("/signup", get(signup_form).post(signup))
         .route("/login", get(login_form).post(login))
+        .route("/login/verify", post(login_verify))
         .route("/logout", post(logout))
         .route("/verify-email", get(verify_email_form).post(verify_email))
         .route("/verify-email/confirm", get(verify_email_link))
@@ -82,10 +86,25 @@ async fn signup_form(
 async fn signup(Form(form): Form<CreateUser>) -> Result<Response, Error> {
     debug!("Processing signup for email: {}", form.email);
 
+    let mut errors = form.validate();
+    let email_local = form.email.split('@').next().unwrap_or_default();
+    if let Err(message) = password_policy::validate(&form.password, &[&form.username, email_local]).await {
+        errors.add("password", message);
+    }
+    if !errors.is_empty() {
+        return render_signup_errors(form, errors, None);
+    }
+
     // Try to create the user
     let email = form.email.clone();
     let redirect = form.redirect.clone();
-    match Person::signup(form.username, form.email, form.password).await {
+    match Person::signup(
+        form.username.clone(),
+        form.email.clone(),
+        form.password.clone(),
+    )
+    .await
+    {
         Ok(token) => {
             info!("User created successfully");
             crate::services::activity::log_activity(None, "signup", "/signup");
@@ -95,7 +114,7 @@ async fn signup(Form(form): Form<CreateUser>) -> Result<Response, Error> {
                 .path("/")
                 .same_site(SameSite::Lax)
                 .http_only(true)
-                .secure(env::var("COOKIE_SECURE").unwrap_or_else(|_| "true".to_string()) != "false")
+                .secure(crate::config::cookie_secure())
                 .build();
 
             // Redirect to email verification page, forwarding redirect param
@@ -110,23 +129,43 @@ async fn signup(Form(form): Form<CreateUser>) -> Result<Response, Error> {
             )
                 .into_response())
         }
+        // A taken username or email belongs next to that field
+        Err(Error::Conflict(message)) => {
+            let field = if message.starts_with("Username") {
+                "username"
+            } else {
+                "email"
+            };
+            errors.add(field, message);
+            render_signup_errors(form, errors, None)
+        }
         Err(e) => {
             error!("Signup failed: {}", e);
+            render_signup_errors(form, errors, Some(e.to_string()))
+        }
+    }
+}
 
-            // Re-render the signup form with error
-            let base = BaseContext::new().with_page("signup");
-
-            let mut template = SignupTemplate::new(base);
-            template.error = Some(e.to_string());
+/// Re-render the signup form with what was entered (minus the password) and
+/// why it wasn't accepted
+fn render_signup_errors(
+    form: CreateUser,
+    errors: FieldErrors,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let mut template = SignupTemplate::new(BaseContext::new().with_page("signup"));
+    template.error = error;
+    template.errors = errors;
+    template.prefill_username = Some(form.username);
+    template.prefill_email = Some(form.email);
+    template.redirect = form.redirect;
 
-            let html = template.render().map_err(|e| {
-                error!("Failed to render signup template with error: {}", e);
-                Error::template(e.to_string())
-            })?;
+    let html = template.render().map_err(|e| {
+        error!("Failed to render signup template with error: {}", e);
+        Error::template(e.to_string())
+    })?;
 
-            Ok(Html(html).into_response())
-        }
-    }
+    Ok(response::invalid_form(html))
 }
 
 async fn login_form(
@@ -153,71 +192,201 @@ async fn login_form(
 }
 
 #[axum::debug_handler]
-async fn login(Form(form): Form<LoginUser>) -> Result<Response, Error> {
+async fn login(client: ClientInfo, Form(form): Form<LoginUser>) -> Result<Response, Error> {
     debug!("Processing login for: {}", form.email);
 
+    // Refuse outright while the account or IP is locked out
+    match login_security::check_lockout(&form.email, &client).await {
+        Ok(Some(lockout)) => {
+            warn!("Login refused for {} from {}: locked out", form.email, client.ip);
+            return render_login_error(lockout.message(), form.redirect_to);
+        }
+        Ok(None) => {}
+        Err(e) => warn!("Lockout check failed, allowing login attempt: {}", e),
+    }
+
     // Try to authenticate the user (signin accepts username or email as identifier)
     match Person::signin(form.email.clone(), form.password).await {
         Ok(token) => {
-            info!("User logged in successfully");
-            crate::services::activity::log_activity(None, "login", "/login");
+            let claims = crate::auth::decode_jwt(&token)?;
+            let person_id = RecordId::parse_simple(&claims.sub)
+                .map_err(|e| Error::Internal(format!("Invalid person id in token: {}", e)))?;
 
-            // Create authentication cookie with the JWT token
-            let cookie = Cookie::build(("auth_token", token))
-                .path("/")
-                .same_site(SameSite::Lax)
-                .http_only(true)
-                .secure(env::var("COOKIE_SECURE").unwrap_or_else(|_| "true".to_string()) != "false")
-                .build();
+            // Unrecognised device: hold the session until the emailed code is entered
+            if login_security::requires_device_verification(&person_id, &client)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Device check failed, skipping new-device verification: {}", e);
+                    false
+                })
+            {
+                info!("Login for {} from new device at {}, sending code", claims.username, client.ip);
+                send_login_code(&person_id, &claims.email, &client).await?;
+
+                let mut template =
+                    LoginVerifyTemplate::new(BaseContext::new().with_page("login"));
+                template.identifier = form.email;
+                template.redirect_to = form.redirect_to;
+
+                let html = template.render().map_err(|e| {
+                    error!("Failed to render login verification template: {}", e);
+                    Error::template(e.to_string())
+                })?;
+                return Ok(Html(html).into_response());
+            }
 
-            // Redirect to profile or the originally requested page
-            let redirect_to = form.redirect_to.unwrap_or_else(|| "/profile".to_string());
+            login_security::record_attempt(&form.email, Some(&person_id), &client, true).await;
+            info!("User logged in successfully");
+            crate::services::activity::log_activity(None, "login", "/login");
 
-            Ok((
-                CookieJar::new().add(cookie),
-                response::redirect(&redirect_to),
-            )
-                .into_response())
+            Ok(session_response(token, form.redirect_to))
         }
         Err(e) => {
             error!("Login failed for {}: {}", form.email, e);
 
-            // Re-render the login form with error
-            let base = BaseContext::new().with_page("login");
-
-            let mut template = LoginTemplate::new(base);
-
             // Check if the error is about email verification
             let error_message = match &e {
                 Error::Validation(msg) if msg.contains("email address has not been verified") => {
                     msg.clone()
                 }
-                _ => "Invalid email or password".to_string(),
+                _ => {
+                    login_security::record_attempt(&form.email, None, &client, false).await;
+                    "Invalid email or password".to_string()
+                }
             };
 
-            template.error = Some(error_message);
-            template.redirect_to = form.redirect_to;
+            render_login_error(error_message, form.redirect_to)
+        }
+    }
+}
 
-            let html = template.render().map_err(|e| {
-                error!("Failed to render login template with error: {}", e);
-                Error::template(e.to_string())
-            })?;
+#[derive(Debug, Deserialize)]
+struct LoginVerifyForm {
+    identifier: String,
+    code: String,
+    redirect_to: Option<String>,
+}
 
-            Ok(Html(html).into_response())
-        }
+#[axum::debug_handler]
+async fn login_verify(client: ClientInfo, Form(form): Form<LoginVerifyForm>) -> Result<Response, Error> {
+    match login_security::check_lockout(&form.identifier, &client).await {
+        Ok(Some(lockout)) => return render_login_error(lockout.message(), form.redirect_to),
+        Ok(None) => {}
+        Err(e) => warn!("Lockout check failed, allowing verification attempt: {}", e),
+    }
+
+    let identifier = form.identifier.trim().to_lowercase();
+    let person = match Person::find_by_email(&identifier).await? {
+        Some(p) => Some(p),
+        None => Person::find_by_username(&identifier).await?,
+    };
+    let Some(person) = person else {
+        return render_login_error("Invalid email or password".to_string(), form.redirect_to);
+    };
+
+    if let Err(e) =
+        VerificationService::verify_code(&person.id, form.code.trim(), CodeType::LoginVerification).await
+    {
+        debug!("Login verification failed for {}: {}", person.username, e);
+        login_security::record_attempt(&form.identifier, Some(&person.id), &client, false).await;
+
+        let mut template = LoginVerifyTemplate::new(BaseContext::new().with_page("login"));
+        template.error = Some("That code is invalid or has expired.".to_string());
+        template.identifier = form.identifier;
+        template.redirect_to = form.redirect_to;
+
+        let html = template.render().map_err(|e| {
+            error!("Failed to render login verification template: {}", e);
+            Error::template(e.to_string())
+        })?;
+        return Ok(Html(html).into_response());
     }
+
+    login_security::remember_device(&person.id, &client).await?;
+    login_security::record_attempt(&form.identifier, Some(&person.id), &client, true).await;
+    info!("User {} confirmed sign-in from new device", person.username);
+    crate::services::activity::log_activity(None, "login", "/login");
+
+    let token = crate::auth::create_jwt(&person.id.to_raw_string(), &person.username, &person.email)?;
+    Ok(session_response(token, form.redirect_to))
+}
+
+/// Issue the one-time code for a new-device sign-in and email it
+async fn send_login_code(person_id: &RecordId, email: &str, client: &ClientInfo) -> Result<(), Error> {
+    let code = VerificationService::create_verification_code(person_id, CodeType::LoginVerification).await?;
+
+    let email = email.to_string();
+    let client = client.clone();
+    crate::db::spawn(async move {
+        match EmailService::from_env() {
+            Ok(service) => {
+                if let Err(e) = service
+                    .send_login_code_email(
+                        &email,
+                        None,
+                        &code,
+                        &client.ip,
+                        client.user_agent.as_deref().unwrap_or("Unknown device"),
+                    )
+                    .await
+                {
+                    error!("Failed to send login code email: {}", e);
+                }
+            }
+            Err(e) => error!("Email service unavailable, login code not sent: {}", e),
+        }
+    });
+
+    Ok(())
+}
+
+/// Set the auth cookie and redirect to profile or the originally requested page
+pub(super) fn session_response(token: String, redirect_to: Option<String>) -> Response {
+    let cookie = Cookie::build(("auth_token", token))
+        .path("/")
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .secure(crate::config::cookie_secure())
+        .build();
+
+    let redirect_to = redirect_to.unwrap_or_else(|| "/profile".to_string());
+
+    (CookieJar::new().add(cookie), response::redirect(&redirect_to)).into_response()
+}
+
+/// Re-render the login form with an error
+fn render_login_error(message: String, redirect_to: Option<String>) -> Result<Response, Error> {
+    let mut template = LoginTemplate::new(BaseContext::new().with_page("login"));
+    template.error = Some(message);
+    template.redirect_to = redirect_to;
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render login template with error: {}", e);
+        Error::template(e.to_string())
+    })?;
+
+    Ok(Html(html).into_response())
 }
 
 #[axum::debug_handler]
 async fn logout(jar: CookieJar) -> Response {
     debug!("Processing logout");
 
+    // Logging out of an impersonation session ends it
+    if let Some(token) = jar.get("auth_token")
+        && let Ok(claims) = crate::auth::decode_jwt(token.value())
+        && let Some(session_id) = claims.imp
+        && let Err(e) = impersonation::end(&session_id).await
+    {
+        error!("Failed to end impersonation session {}: {}", session_id, e);
+    }
+
     // Create a cookie that expires immediately to clear the auth
     let cookie = Cookie::build(("auth_token", ""))
         .path("/")
         .same_site(SameSite::Lax)
         .http_only(true)
-        .secure(env::var("COOKIE_SECURE").unwrap_or_else(|_| "true".to_string()) != "false")
+        .secure(crate::config::cookie_secure())
         .max_age(Default::default())
         .build();
 
@@ -306,7 +475,7 @@ async fn verify_email(
                 .path("/")
                 .same_site(SameSite::Lax)
                 .http_only(true)
-                .secure(env::var("COOKIE_SECURE").unwrap_or_else(|_| "true".to_string()) != "false")
+                .secure(crate::config::cookie_secure())
                 .build();
 
             // Redirect to invitation target or profile
@@ -400,7 +569,7 @@ async fn forgot_password(Form(form): Form<ForgotPasswordForm>) -> Result<Respons
         if let Ok(email_service) = EmailService::from_env() {
             let email_clone = form.email.clone();
             let person_name = person.name.clone();
-            tokio::spawn(async move {
+            crate::db::spawn(async move {
                 if let Err(e) = email_service
                     .send_password_reset_email(&email_clone, person_name.as_deref(), &reset_code)
                     .await
@@ -503,6 +672,21 @@ async fn reset_password(Form(form): Form<ResetPasswordForm>) -> Result<Response,
         .await?
         .ok_or_else(|| Error::NotFound)?;
 
+    let email_local = form.email.split('@').next().unwrap_or_default();
+    if let Err(message) = password_policy::validate(&form.password, &[&person.username, email_local]).await {
+        let mut template = ResetPasswordTemplate::new(BaseContext::new().with_page("reset-password"));
+        template.error = Some(message);
+        template.email = Some(form.email);
+        template.code = Some(form.code);
+
+        let html = template.render().map_err(|e| {
+            error!("Failed to render reset password template with error: {}", e);
+            Error::template(e.to_string())
+        })?;
+
+        return Ok(Html(html).into_response());
+    }
+
     // Verify the reset code
     match VerificationService::verify_code(&person.id, &form.code, CodeType::PasswordReset).await {
         Ok(_) => {
@@ -575,7 +759,7 @@ async fn resend_verification(Form(form): Form<ResendVerificationForm>) -> Result
             if let Ok(email_service) = EmailService::from_env() {
                 let email_clone = form.email.clone();
                 let person_name = person.name.clone();
-                tokio::spawn(async move {
+                crate::db::spawn(async move {
                     if let Err(e) = email_service
                         .send_verification_email(
                             &email_clone,
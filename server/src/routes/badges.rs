@@ -0,0 +1,60 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::Path,
+    http::header,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+};
+use tracing::error;
+
+use crate::{error::Error, services::availability_badge, templates::BaseContext};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/badge/{token}", get(badge_frame))
+        .route("/badge/{token}/availability.svg", get(badge_svg))
+}
+
+/// Badges are fetched from other people's sites, so they're cached briefly
+/// rather than looked up on every page view
+const CACHE_CONTROL: &str = "public, max-age=300";
+
+#[derive(Template)]
+#[template(path = "persons/badge.html")]
+pub struct BadgeFrameTemplate {
+    pub app_name: String,
+    pub name: String,
+    pub profile_url: String,
+    pub status: String,
+    pub label: String,
+}
+
+async fn badge_svg(Path(token): Path<String>) -> Result<Response, Error> {
+    let badge = availability_badge::lookup(&token).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/svg+xml"),
+            (header::CACHE_CONTROL, CACHE_CONTROL),
+        ],
+        availability_badge::render_svg(&badge.name, &badge.status),
+    )
+        .into_response())
+}
+
+/// The badge as a page for an iframe, linking back to the profile
+async fn badge_frame(Path(token): Path<String>) -> Result<Response, Error> {
+    let badge = availability_badge::lookup(&token).await?;
+    let template = BadgeFrameTemplate {
+        app_name: BaseContext::new().app_name,
+        profile_url: format!("{}/{}", crate::config::app_url(), badge.username),
+        status: badge.status.as_str().to_string(),
+        label: badge.status.label(),
+        name: badge.name,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render badge template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(([(header::CACHE_CONTROL, CACHE_CONTROL)], Html(html)).into_response())
+}
@@ -0,0 +1,183 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        budget::{self, BudgetLine, BudgetLineData, BudgetModel},
+        person::SessionUser,
+        production::{Production, ProductionModel},
+        production_gear::format_value,
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/productions/{slug}/budget",
+            get(budget_page).post(add_line),
+        )
+        .route(
+            "/productions/{slug}/budget/{line_id}/remove",
+            post(remove_line),
+        )
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct CategoryTotal {
+    pub label: &'static str,
+    /// "12,500.00"
+    pub amount: String,
+}
+
+#[derive(Template)]
+#[template(path = "productions/budget.html")]
+pub struct BudgetTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub lines: Vec<BudgetLine>,
+    /// "12,500.00"
+    pub total: String,
+    pub categories: Vec<CategoryTotal>,
+    pub category_options: Vec<SelectOption>,
+    pub max_description: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BudgetQuery {
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LineForm {
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub amount: String,
+}
+
+// ============================
+// Helpers
+// ============================
+
+/// The production, if the user can edit it. The budget is for its owners
+/// and admins.
+async fn require_editor(slug: &str, user: &SessionUser) -> Result<Production, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    if !ProductionModel::can_edit(&production.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok(production)
+}
+
+fn back_to_budget(slug: &str, error: Option<&str>) -> Response {
+    match error {
+        Some(message) => Redirect::to(&format!(
+            "/productions/{}/budget?error={}",
+            slug,
+            urlencoding::encode(message)
+        ))
+        .into_response(),
+        None => Redirect::to(&format!("/productions/{}/budget", slug)).into_response(),
+    }
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn budget_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<BudgetQuery>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    let lines = BudgetModel::for_production(&production.id).await?;
+    let (total, by_category) = budget::totals(&lines);
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(&user).await);
+    let template = BudgetTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        lines,
+        total: format_value(total),
+        categories: by_category
+            .into_iter()
+            .map(|(label, amount)| CategoryTotal {
+                label,
+                amount: format_value(amount),
+            })
+            .collect(),
+        category_options: budget::CATEGORIES
+            .iter()
+            .map(|(value, label)| SelectOption::new(value, label.to_string(), *value == "other"))
+            .collect(),
+        max_description: budget::MAX_DESCRIPTION_CHARS,
+        error: query.error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render budget template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn add_line(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<LineForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    let data = match BudgetLineData::parse(&form.category, &form.description, &form.amount) {
+        Ok(data) => data,
+        Err(Error::Validation(message)) => return Ok(back_to_budget(&slug, Some(&message))),
+        Err(e) => return Err(e),
+    };
+    let person = RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    BudgetModel::add(&production.id, &data, None, None, &person).await?;
+    info!("{} added a budget line to {}", user.username, slug);
+    Ok(back_to_budget(&slug, None))
+}
+
+async fn remove_line(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, line_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    BudgetModel::remove(&production.id, &line_id).await?;
+    info!(
+        "{} removed budget line {} from {}",
+        user.username, line_id, slug
+    );
+    Ok(back_to_budget(&slug, None))
+}
@@ -0,0 +1,119 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::{Path, Query},
+    http::header,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+};
+use serde::Deserialize;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        cast_sheet::{self, CastMember, CastSheetModel},
+        portfolio::{PAGE_SIZES, page_size},
+        production::{Production, ProductionModel},
+    },
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/productions/{slug}/cast-sheet", get(cast_sheet_page))
+        .route("/productions/{slug}/cast-sheet.pdf", get(cast_sheet_pdf))
+}
+
+#[derive(Template)]
+#[template(path = "productions/cast_sheet.html")]
+pub struct CastSheetTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub cast: Vec<CastMember>,
+    /// Whether phone numbers are shown, for owners and admins
+    pub shows_phones: bool,
+    pub page_sizes: &'static [(&'static str, &'static str)],
+}
+
+/// The sheet is for the production's members; phone numbers only for its
+/// owners and admins
+async fn require_member(slug: &str, user_id: &str) -> Result<(Production, bool), Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    let can_edit = ProductionModel::can_edit(&production.id, user_id).await?;
+    if !can_edit && !ProductionModel::is_member(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok((production, can_edit))
+}
+
+async fn cast_sheet_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Html<String>, Error> {
+    let (production, can_edit) = require_member(&slug, &user.id).await?;
+    let cast = CastSheetModel::cast(&production.id, can_edit).await?;
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(&user).await);
+
+    let template = CastSheetTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        cast,
+        shows_phones: can_edit,
+        page_sizes: PAGE_SIZES,
+    };
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render cast sheet: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+#[derive(Debug, Deserialize)]
+struct PdfQuery {
+    /// "letter" (default) or "a4"
+    size: Option<String>,
+}
+
+async fn cast_sheet_pdf(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<PdfQuery>,
+) -> Result<Response, Error> {
+    let size = page_size(query.size.as_deref().unwrap_or("letter"))
+        .ok_or_else(|| Error::BadRequest("Choose US Letter or A4".to_string()))?;
+    let (production, can_edit) = require_member(&slug, &user.id).await?;
+    let cast = CastSheetModel::cast(&production.id, can_edit).await?;
+    let entries = CastSheetModel::entries(cast).await;
+
+    let title = production.title.clone();
+    let pdf =
+        tokio::task::spawn_blocking(move || cast_sheet::cast_sheet_pdf(&title, &entries, size))
+            .await
+            .map_err(|e| Error::Internal(format!("Cast sheet rendering failed: {}", e)))?;
+    info!("{} exported the cast sheet for {}", user.username, slug);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}-cast-sheet.pdf\"", production.slug),
+            ),
+        ],
+        pdf,
+    )
+        .into_response())
+}
@@ -0,0 +1,479 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::Path,
+    http::header,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        daily_report::{self, DailyReport, DailyReportModel, UpdateReportData},
+        org_branding::OrgBrandingModel,
+        person::SessionUser,
+        production::{Production, ProductionModel},
+        shot_list::{ShootDay, ShotListModel},
+    },
+    record_id_ext::RecordIdExt,
+    services::{
+        email::{EmailAttachment, EmailService},
+        mentions,
+    },
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/productions/{slug}/reports", get(reports_page))
+        .route("/productions/{slug}/reports/recipients", post(save_recipients))
+        .route("/productions/{slug}/reports/{day_id}", get(report_page).post(save_report))
+        .route("/productions/{slug}/reports/{day_id}/assemble", post(assemble_report))
+        .route("/productions/{slug}/reports/{day_id}/report.pdf", get(export_pdf))
+        .route("/productions/{slug}/reports/{day_id}/send", post(send_report))
+        .route("/productions/{slug}/reports/{day_id}/reopen", post(reopen_report))
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct ReportRow {
+    pub day_id: String,
+    pub day_number: i64,
+    pub date: String,
+    /// "none", "draft" or "final"
+    pub status: String,
+    pub sent_at: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/reports.html")]
+pub struct ReportsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub can_edit: bool,
+    pub rows: Vec<ReportRow>,
+    pub recipients: String,
+    pub error: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/report.html")]
+pub struct ReportTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub can_edit: bool,
+    pub day_id: String,
+    pub day_number: i64,
+    pub date: String,
+    pub report: DailyReport,
+    pub scenes_remaining: String,
+    pub crew_text: String,
+    pub incidents_text: String,
+    pub recipient_count: usize,
+    pub sent_at: Option<String>,
+    pub error: Option<String>,
+    pub message: Option<String>,
+}
+
+impl ReportTemplate {
+    /// The notes with their @mentions linked
+    fn notes_html(&self) -> String {
+        mentions::link(self.report.notes.as_deref().unwrap_or(""))
+    }
+}
+
+// ============================
+// Access
+// ============================
+
+struct Access {
+    production: Production,
+    can_edit: bool,
+}
+
+/// Reports are visible to the production's members. Owners, admins and
+/// assistant directors may assemble, edit and send them.
+async fn require_member(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    if ProductionModel::can_edit(&production.id, user_id).await? {
+        return Ok(Access {
+            production,
+            can_edit: true,
+        });
+    }
+    let member = ProductionModel::get_members(&production.id)
+        .await?
+        .into_iter()
+        .find(|m| m.id == user_id && m.invitation_status == "accepted")
+        .ok_or(Error::Forbidden)?;
+    let can_edit = daily_report::is_assistant_director(&member.production_roles.unwrap_or_default());
+    Ok(Access { production, can_edit })
+}
+
+async fn require_editor(slug: &str, user_id: &str) -> Result<Production, Error> {
+    let access = require_member(slug, user_id).await?;
+    if !access.can_edit {
+        return Err(Error::Forbidden);
+    }
+    Ok(access.production)
+}
+
+fn to_report(slug: &str, day_id: &str) -> Response {
+    Redirect::to(&format!("/productions/{}/reports/{}", slug, day_id)).into_response()
+}
+
+async fn render_list(
+    user: &SessionUser,
+    access: Access,
+    error: Option<String>,
+    message: Option<String>,
+) -> Result<Response, Error> {
+    let days = ShotListModel::days(&access.production.id).await?;
+    let reports = DailyReportModel::for_production(&access.production.id).await?;
+    let recipients = DailyReportModel::recipients(&access.production.id).await?;
+
+    let rows = days
+        .into_iter()
+        .map(|day| {
+            let report = reports.iter().find(|r| r.shoot_day == day.id);
+            ReportRow {
+                day_id: day.id.key_string(),
+                day_number: day.day_number,
+                date: day.date,
+                status: report.map(|r| r.status.clone()).unwrap_or_else(|| "none".to_string()),
+                sent_at: report
+                    .and_then(|r| r.sent_at)
+                    .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()),
+            }
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+    let template = ReportsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: access.production.title,
+        production_slug: access.production.slug,
+        can_edit: access.can_edit,
+        rows,
+        recipients: recipients.join("\n"),
+        error,
+        message,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render daily reports template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn render_report(
+    user: &SessionUser,
+    access: Access,
+    day: ShootDay,
+    report: DailyReport,
+    error: Option<String>,
+    message: Option<String>,
+) -> Result<Response, Error> {
+    let recipient_count = DailyReportModel::recipients(&access.production.id).await?.len();
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+    let template = ReportTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: access.production.title,
+        production_slug: access.production.slug,
+        can_edit: access.can_edit,
+        day_id: day.id.key_string(),
+        day_number: day.day_number,
+        date: day.date,
+        scenes_remaining: report.scenes_remaining().join(", "),
+        crew_text: daily_report::crew_text(&report.crew),
+        incidents_text: daily_report::incidents_text(&report.incidents),
+        recipient_count,
+        sent_at: report.sent_at.map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()),
+        report,
+        error,
+        message,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render daily report template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+/// The report for a day, assembling a draft the first time an editor opens it
+async fn load_report(access: &Access, day: &ShootDay) -> Result<Option<DailyReport>, Error> {
+    match DailyReportModel::for_day(&day.id).await? {
+        Some(report) => Ok(Some(report)),
+        None if access.can_edit => Ok(Some(DailyReportModel::assemble(&access.production.id, day).await?)),
+        None => Ok(None),
+    }
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn reports_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    render_list(&user, access, None, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct RecipientsForm {
+    #[serde(default)]
+    recipients: String,
+}
+
+async fn save_recipients(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<RecipientsForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    let result = DailyReportModel::set_recipients(&production.id, &form.recipients).await;
+    let access = require_member(&slug, &user.id).await?;
+    match result {
+        Ok(recipients) => {
+            info!("{} set {} report recipient(s) for {}", user.username, recipients.len(), slug);
+            render_list(&user, access, None, Some("Distribution list saved".to_string())).await
+        }
+        Err(Error::Validation(msg)) => render_list(&user, access, Some(msg), None).await,
+        Err(e) => Err(e),
+    }
+}
+
+async fn report_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let day = ShotListModel::get_day(&access.production.id, &day_id).await?;
+    let report = load_report(&access, &day).await?.ok_or(Error::NotFound)?;
+    render_report(&user, access, day, report, None, None).await
+}
+
+/// Rebuild the scene and shot figures from the shot list at wrap
+async fn assemble_report(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    let day = ShotListModel::get_day(&production.id, &day_id).await?;
+    DailyReportModel::assemble(&production.id, &day).await?;
+    Ok(to_report(&slug, &day_id))
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportForm {
+    #[serde(default)]
+    call_time: String,
+    #[serde(default)]
+    first_shot: String,
+    #[serde(default)]
+    lunch_start: String,
+    #[serde(default)]
+    lunch_end: String,
+    #[serde(default)]
+    wrap_time: String,
+    #[serde(default)]
+    weather: String,
+    #[serde(default)]
+    notes: String,
+    #[serde(default)]
+    crew: String,
+    #[serde(default)]
+    incidents: String,
+}
+
+async fn save_report(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+    Form(form): Form<ReportForm>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    if !access.can_edit {
+        return Err(Error::Forbidden);
+    }
+    let day = ShotListModel::get_day(&access.production.id, &day_id).await?;
+    let report = load_report(&access, &day).await?.ok_or(Error::NotFound)?;
+
+    let data = UpdateReportData {
+        call_time: form.call_time,
+        first_shot: form.first_shot,
+        lunch_start: form.lunch_start,
+        lunch_end: form.lunch_end,
+        wrap_time: form.wrap_time,
+        weather: form.weather,
+        notes: form.notes,
+        crew: form.crew,
+        incidents: form.incidents,
+    };
+    let before = report.notes.clone().unwrap_or_default();
+    match DailyReportModel::update(&report, data).await {
+        Ok(updated) => {
+            let notes = updated.notes.as_deref().unwrap_or("");
+            mentions::notify(
+                &access.production.id,
+                &mentions::added(&before, notes),
+                &user,
+                &format!("in the Day {} report for {}", day.day_number, access.production.title),
+                notes,
+                &format!("/productions/{}/reports/{}", slug, day_id),
+            )
+            .await;
+            render_report(&user, access, day, updated, None, Some("Report saved".to_string())).await
+        }
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => {
+            render_report(&user, access, day, report, Some(msg), None).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn export_pdf(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let day = ShotListModel::get_day(&access.production.id, &day_id).await?;
+    let report = load_report(&access, &day).await?.ok_or(Error::NotFound)?;
+    let pdf = daily_report::report_pdf(&access.production.title, &day, &report);
+    let filename = format!("{}-day-{}-report.pdf", slug, day.day_number);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", filename),
+            ),
+        ],
+        pdf,
+    )
+        .into_response())
+}
+
+/// Email the report to the distribution list as a PDF and mark it final
+async fn send_report(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    if !access.can_edit {
+        return Err(Error::Forbidden);
+    }
+    let day = ShotListModel::get_day(&access.production.id, &day_id).await?;
+    let report = load_report(&access, &day).await?.ok_or(Error::NotFound)?;
+
+    let recipients = DailyReportModel::recipients(&access.production.id).await?;
+    if recipients.is_empty() {
+        let msg = "Add a distribution list on the reports page before sending".to_string();
+        return render_report(&user, access, day, report, Some(msg), None).await;
+    }
+
+    // The emailed copy is the final one
+    let mut sent = report.clone();
+    sent.status = "final".to_string();
+    let title = access.production.title.clone();
+    let attachment = EmailAttachment {
+        filename: format!("{}-day-{}-report.pdf", slug, day.day_number),
+        content_type: "application/pdf".to_string(),
+        data: daily_report::report_pdf(&title, &day, &sent),
+    };
+    let subject = format!("{} - Daily Production Report, Day {} ({})", title, day.day_number, day.date);
+    let mut text = daily_report::report_summary(&title, &day, &sent);
+    let mut html = format!("<pre style=\"font-family: sans-serif\">{}</pre>", ammonia::clean_text(&text));
+    if let Some(brand) = OrgBrandingModel::for_production(&access.production.id).await? {
+        text.push_str(&brand.email_footer_text());
+        html.push_str(&brand.email_footer_html());
+    }
+
+    let email = match EmailService::from_env() {
+        Ok(email) => email,
+        Err(e) => {
+            error!("Email service unavailable for daily report: {}", e);
+            let msg = "Email isn't configured, so the report couldn't be sent".to_string();
+            return render_report(&user, access, day, report, Some(msg), None).await;
+        }
+    };
+
+    let mut delivered = Vec::new();
+    let mut failed = Vec::new();
+    for recipient in recipients {
+        match email
+            .send_email_with_attachments(&recipient, None, &subject, &text, &html, std::slice::from_ref(&attachment))
+            .await
+        {
+            Ok(()) => delivered.push(recipient),
+            Err(e) => {
+                warn!("Failed to send daily report to {}: {}", recipient, e);
+                failed.push(recipient);
+            }
+        }
+    }
+
+    if delivered.is_empty() {
+        let msg = "The report couldn't be sent. Please try again.".to_string();
+        return render_report(&user, access, day, report, Some(msg), None).await;
+    }
+
+    DailyReportModel::finalize(&report.id, delivered.clone()).await?;
+    info!(
+        "{} sent the day {} report for {} to {} recipient(s)",
+        user.username,
+        day.day_number,
+        slug,
+        delivered.len()
+    );
+
+    let report = DailyReportModel::for_day(&day.id).await?.ok_or(Error::NotFound)?;
+    let error = (!failed.is_empty()).then(|| format!("Couldn't deliver to: {}", failed.join(", ")));
+    let message = Some(format!("Report sent to {} recipient(s)", delivered.len()));
+    render_report(&user, access, day, report, error, message).await
+}
+
+/// Reopen a sent report so it can be corrected and sent again
+async fn reopen_report(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    let day = ShotListModel::get_day(&production.id, &day_id).await?;
+    let report = DailyReportModel::for_day(&day.id).await?.ok_or(Error::NotFound)?;
+    DailyReportModel::reopen(&report.id).await?;
+    Ok(to_report(&slug, &day_id))
+}
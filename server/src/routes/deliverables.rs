@@ -0,0 +1,541 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query, multipart::Multipart},
+    http::header,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use chrono::Utc;
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        deliverable::{
+            self, Deliverable, DeliverableData, DeliverableFile, DeliverableModel, Rollup,
+        },
+        legal_hold::LegalHoldModel,
+        production::{Production, ProductionModel},
+    },
+    record_id_ext::RecordIdExt,
+    services::s3::s3,
+    templates::{BaseContext, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/productions/{slug}/deliverables",
+            get(deliverables_page).post(add_deliverable),
+        )
+        .route(
+            "/productions/{slug}/deliverables/{deliverable_id}/edit",
+            post(edit_deliverable),
+        )
+        .route(
+            "/productions/{slug}/deliverables/{deliverable_id}/status",
+            post(set_status),
+        )
+        .route(
+            "/productions/{slug}/deliverables/{deliverable_id}/delete",
+            post(delete_deliverable),
+        )
+        .route(
+            "/productions/{slug}/deliverables/{deliverable_id}/files",
+            post(upload_file),
+        )
+        .route(
+            "/productions/{slug}/deliverables/files/{file_id}",
+            get(download_file),
+        )
+        .route(
+            "/productions/{slug}/deliverables/files/{file_id}/remove",
+            post(remove_file),
+        )
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct DeliverableView {
+    pub item: Deliverable,
+    pub overdue: bool,
+    pub files: Vec<DeliverableFile>,
+    pub kind_options: Vec<SelectOption>,
+    pub status_options: Vec<SelectOption>,
+    pub vendor_options: Vec<SelectOption>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/deliverables.html")]
+pub struct DeliverablesTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub can_edit: bool,
+    /// Members can remove the files they attached
+    pub viewer: RecordId,
+    pub deliverables: Vec<DeliverableView>,
+    pub rollup: Rollup,
+    pub kind_options: Vec<SelectOption>,
+    pub vendor_options: Vec<SelectOption>,
+    pub max_title: usize,
+    pub max_specs: usize,
+    /// ".pdf,.srt,..." for the file pickers
+    pub accept: String,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliverablesQuery {
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliverableForm {
+    #[serde(default)]
+    pub kind: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub specs: String,
+    #[serde(default)]
+    pub due_date: String,
+    /// Key of one of the production's organizations
+    #[serde(default)]
+    pub vendor: String,
+    #[serde(default)]
+    pub vendor_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatusForm {
+    #[serde(default)]
+    pub status: String,
+}
+
+// ============================
+// Access
+// ============================
+
+struct Access {
+    production: Production,
+    person: RecordId,
+    can_edit: bool,
+}
+
+/// The checklist is for the production's members, who can move deliverables
+/// along and attach files; owners and admins set it up
+async fn require_member(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    let person = RecordId::parse_simple(user_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    let can_edit = ProductionModel::can_edit(&production.id, user_id).await?;
+    if !can_edit && !ProductionModel::is_member(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok(Access {
+        production,
+        person,
+        can_edit,
+    })
+}
+
+async fn require_editor(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let access = require_member(slug, user_id).await?;
+    if !access.can_edit {
+        return Err(Error::Forbidden);
+    }
+    Ok(access)
+}
+
+fn back_to_deliverables(slug: &str, error: Option<&str>) -> Response {
+    match error {
+        Some(message) => Redirect::to(&format!(
+            "/productions/{}/deliverables?error={}",
+            slug,
+            urlencoding::encode(message)
+        ))
+        .into_response(),
+        None => Redirect::to(&format!("/productions/{}/deliverables", slug)).into_response(),
+    }
+}
+
+fn today() -> String {
+    Utc::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+/// The production's organizations that have joined it, as (key, name); a
+/// deliverable's vendor is picked from these
+async fn vendor_orgs(production: &RecordId) -> Result<Vec<(String, String)>, Error> {
+    Ok(ProductionModel::get_members(production)
+        .await?
+        .into_iter()
+        .filter(|m| m.member_type == "organization" && m.invitation_status == "accepted")
+        .filter_map(|m| {
+            m.id.strip_prefix("organization:")
+                .map(|key| (key.to_string(), m.name))
+        })
+        .collect())
+}
+
+fn vendor_options(orgs: &[(String, String)], selected: Option<&RecordId>) -> Vec<SelectOption> {
+    let selected = selected.map(|id| id.key_string());
+    std::iter::once(SelectOption::new(
+        "",
+        "Not on SlateHub".to_string(),
+        selected.is_none(),
+    ))
+    .chain(orgs.iter().map(|(key, name)| {
+        SelectOption::new(key, name.clone(), selected.as_deref() == Some(key.as_str()))
+    }))
+    .collect()
+}
+
+fn kind_options(selected: &str) -> Vec<SelectOption> {
+    deliverable::KINDS
+        .iter()
+        .map(|(value, label)| SelectOption::new(value, label.to_string(), *value == selected))
+        .collect()
+}
+
+/// Check the form, resolving the vendor to one of the production's
+/// organizations
+async fn parse_form(
+    production: &RecordId,
+    form: &DeliverableForm,
+) -> Result<(DeliverableData, Option<RecordId>), Error> {
+    let data = DeliverableData::parse(
+        &form.kind,
+        &form.title,
+        &form.specs,
+        &form.due_date,
+        &form.vendor_name,
+    )?;
+    let vendor = match form.vendor.trim() {
+        "" => None,
+        key => match vendor_orgs(production)
+            .await?
+            .into_iter()
+            .find(|(org, _)| org == key)
+        {
+            Some((org, _)) => Some(RecordId::new("organization", org.as_str())),
+            None => {
+                return Err(Error::Validation(
+                    "Pick one of the production's organizations as the vendor".to_string(),
+                ));
+            }
+        },
+    };
+    Ok((data, vendor))
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn deliverables_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<DeliverablesQuery>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let items = DeliverableModel::for_production(&access.production.id).await?;
+    let files = DeliverableModel::files_for_production(&access.production.id).await?;
+    let orgs = if access.can_edit {
+        vendor_orgs(&access.production.id).await?
+    } else {
+        Vec::new()
+    };
+    let today = today();
+    let rollup = deliverable::rollup(&items, &today);
+
+    let deliverables = items
+        .into_iter()
+        .map(|item| DeliverableView {
+            overdue: item.is_overdue(&today),
+            files: files
+                .iter()
+                .filter(|f| f.deliverable == item.id)
+                .cloned()
+                .collect(),
+            kind_options: kind_options(&item.kind),
+            status_options: deliverable::STATUSES
+                .iter()
+                .map(|(value, label)| {
+                    SelectOption::new(value, label.to_string(), *value == item.status)
+                })
+                .collect(),
+            vendor_options: vendor_options(&orgs, item.vendor.as_ref()),
+            item,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(&user).await);
+    let template = DeliverablesTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: access.production.title,
+        production_slug: access.production.slug,
+        can_edit: access.can_edit,
+        viewer: access.person,
+        deliverables,
+        rollup,
+        kind_options: kind_options("master"),
+        vendor_options: vendor_options(&orgs, None),
+        max_title: deliverable::MAX_TITLE_CHARS,
+        max_specs: deliverable::MAX_SPECS_CHARS,
+        accept: deliverable::ALLOWED_EXTENSIONS
+            .iter()
+            .map(|ext| format!(".{}", ext))
+            .collect::<Vec<_>>()
+            .join(","),
+        error: query.error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render deliverables template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn add_deliverable(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<DeliverableForm>,
+) -> Result<Response, Error> {
+    let access = require_editor(&slug, &user.id).await?;
+    let result = async {
+        let (data, vendor) = parse_form(&access.production.id, &form).await?;
+        DeliverableModel::create(
+            &access.production.id,
+            &data,
+            vendor.as_ref(),
+            &access.person,
+        )
+        .await
+    }
+    .await;
+    match result {
+        Ok(id) => {
+            info!(
+                "{} added deliverable {} to {}",
+                user.username,
+                id.display(),
+                slug
+            );
+            Ok(back_to_deliverables(&slug, None))
+        }
+        Err(Error::Validation(message)) => Ok(back_to_deliverables(&slug, Some(&message))),
+        Err(e) => Err(e),
+    }
+}
+
+async fn edit_deliverable(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, deliverable_id)): Path<(String, String)>,
+    Form(form): Form<DeliverableForm>,
+) -> Result<Response, Error> {
+    let access = require_editor(&slug, &user.id).await?;
+    DeliverableModel::get(&access.production.id, &deliverable_id).await?;
+    let result = async {
+        let (data, vendor) = parse_form(&access.production.id, &form).await?;
+        DeliverableModel::update(
+            &access.production.id,
+            &deliverable_id,
+            &data,
+            vendor.as_ref(),
+        )
+        .await
+    }
+    .await;
+    match result {
+        Ok(()) => {
+            info!(
+                "{} updated deliverable {} on {}",
+                user.username, deliverable_id, slug
+            );
+            Ok(back_to_deliverables(&slug, None))
+        }
+        Err(Error::Validation(message)) => Ok(back_to_deliverables(&slug, Some(&message))),
+        Err(e) => Err(e),
+    }
+}
+
+async fn set_status(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, deliverable_id)): Path<(String, String)>,
+    Form(form): Form<StatusForm>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    DeliverableModel::get(&access.production.id, &deliverable_id).await?;
+    match DeliverableModel::set_status(&access.production.id, &deliverable_id, &form.status).await {
+        Ok(()) => {
+            info!(
+                "{} marked deliverable {} on {} as {}",
+                user.username, deliverable_id, slug, form.status
+            );
+            Ok(back_to_deliverables(&slug, None))
+        }
+        Err(Error::Validation(message)) => Ok(back_to_deliverables(&slug, Some(&message))),
+        Err(e) => Err(e),
+    }
+}
+
+async fn delete_deliverable(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, deliverable_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_editor(&slug, &user.id).await?;
+    match LegalHoldModel::ensure_deletable(
+        &access.production.id,
+        Some(&RecordId::new("deliverable", deliverable_id.as_str())),
+        Some(&access.person),
+        "deliverable",
+    )
+    .await
+    {
+        Ok(()) => {}
+        Err(Error::Conflict(message)) => return Ok(back_to_deliverables(&slug, Some(&message))),
+        Err(e) => return Err(e),
+    }
+    DeliverableModel::delete(&access.production.id, &deliverable_id).await?;
+    info!(
+        "{} deleted deliverable {} from {}",
+        user.username, deliverable_id, slug
+    );
+    Ok(back_to_deliverables(&slug, None))
+}
+
+async fn upload_file(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, deliverable_id)): Path<(String, String)>,
+    mut multipart: Multipart,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let item = DeliverableModel::get(&access.production.id, &deliverable_id).await?;
+
+    let mut upload: Option<(String, String, bytes::Bytes)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| Error::bad_request(format!("Failed to read multipart: {}", e)))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let file_name = field.file_name().unwrap_or("").to_string();
+        if !deliverable::attachment_allowed(&file_name) {
+            return Ok(back_to_deliverables(
+                &slug,
+                Some("That type of file can't be attached"),
+            ));
+        }
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let data = field
+            .bytes()
+            .await
+            .map_err(|e| Error::bad_request(format!("Failed to read file data: {}", e)))?;
+        if data.len() > deliverable::MAX_FILE_SIZE {
+            return Ok(back_to_deliverables(
+                &slug,
+                Some("Attachments can be up to 50MB"),
+            ));
+        }
+        upload = Some((file_name, content_type, data));
+    }
+
+    let Some((file_name, content_type, data)) = upload.filter(|(_, _, data)| !data.is_empty())
+    else {
+        return Ok(back_to_deliverables(&slug, Some("Choose a file to attach")));
+    };
+    DeliverableModel::add_file(
+        &access.production.id,
+        &item,
+        &file_name,
+        data,
+        &content_type,
+        &access.person,
+        None,
+    )
+    .await?;
+    info!(
+        "{} attached {} to deliverable {} on {}",
+        user.username, file_name, deliverable_id, slug
+    );
+    Ok(back_to_deliverables(&slug, None))
+}
+
+/// Attachments are private to the production's members, so they're served
+/// from here rather than the public media proxy
+async fn download_file(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, file_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let file = DeliverableModel::get_file(&access.production.id, &file_id).await?;
+    let (data, _) = s3()?.download_file(&file.file_key).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, file.mime_type),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", file.file_name),
+            ),
+            (header::CACHE_CONTROL, "private, no-store".to_string()),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+/// Owners and admins can remove any attachment; members their own
+async fn remove_file(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, file_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let file = DeliverableModel::get_file(&access.production.id, &file_id).await?;
+    if !access.can_edit && file.uploaded_by != access.person {
+        return Err(Error::Forbidden);
+    }
+    match LegalHoldModel::ensure_deletable(
+        &access.production.id,
+        Some(&file.deliverable),
+        Some(&access.person),
+        "file",
+    )
+    .await
+    {
+        Ok(()) => {}
+        Err(Error::Conflict(message)) => return Ok(back_to_deliverables(&slug, Some(&message))),
+        Err(e) => return Err(e),
+    }
+    DeliverableModel::remove_file(&file).await?;
+    info!(
+        "{} removed {} from deliverable {} on {}",
+        user.username,
+        file.file_name,
+        file.deliverable.key_string(),
+        slug
+    );
+    Ok(back_to_deliverables(&slug, None))
+}
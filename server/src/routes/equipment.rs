@@ -1,11 +1,13 @@
 use axum::{
-    Form, Router,
+    Form, Json, Router,
     extract::{Path, Query, Request},
+    http::{HeaderMap, StatusCode, header},
     response::{Html, IntoResponse, Redirect, Response},
     routing::{get, post},
 };
 use serde::{Deserialize, Deserializer};
-use tracing::info;
+use surrealdb::types::RecordId;
+use tracing::{info, warn};
 
 use crate::{
     error::Error,
@@ -14,19 +16,28 @@ use crate::{
     models::{
         equipment::{
             CheckinData, CheckoutData, CreateEquipmentData, CreateKitData, Equipment,
-            EquipmentModel, UpdateEquipmentData,
+            EquipmentModel, EquipmentRental, UpdateEquipmentData,
         },
+        equipment_label::{self, Label, ScannedCode},
+        equipment_service::{self, EquipmentServiceModel},
         organization::OrganizationModel,
+        person::{Person, SessionUser},
+        production_gear::{self, ProductionGearModel},
     },
+    services::whatsapp,
     templates::{
         BaseContext, User,
         equipment::{
             EquipmentCheckInTemplate, EquipmentCheckoutTemplate, EquipmentDetailTemplate,
-            EquipmentFormTemplate, EquipmentListTemplate, KitDetailTemplate, KitFormTemplate,
+            EquipmentFormTemplate, EquipmentItemsTemplate, EquipmentListTemplate,
+            EquipmentScanTemplate, KitDetailTemplate, KitFormTemplate, ScanItem,
         },
     },
 };
 
+/// Weeks shown on an item's availability calendar
+const CALENDAR_WEEKS: usize = 6;
+
 // ============================
 // Query Parameters
 // ============================
@@ -37,8 +48,10 @@ pub struct EquipmentQuery {
     pub owner_id: Option<String>,
     pub category: Option<String>,
     pub available_only: Option<bool>,
+    pub hide_out_of_service: Option<bool>,
     pub equipment_id: Option<String>,
     pub kit_id: Option<String>,
+    pub error: Option<String>,
 }
 
 // ============================
@@ -78,6 +91,8 @@ pub struct EquipmentFormData {
     pub purchase_date: Option<String>,
     #[serde(default, deserialize_with = "deserialize_optional_float")]
     pub purchase_price: Option<f64>,
+    #[serde(default, deserialize_with = "deserialize_optional_float")]
+    pub replacement_value: Option<f64>,
     pub condition: String,
     pub notes: Option<String>,
     pub current_location: Option<String>,
@@ -109,6 +124,34 @@ pub struct CheckinFormData {
     pub return_notes: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ServiceFormData {
+    pub kind: String,
+    #[serde(default)]
+    pub performed_on: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub usage_hours: String,
+    #[serde(default)]
+    pub cost: String,
+    #[serde(default)]
+    pub vendor: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ServiceScheduleFormData {
+    #[serde(default)]
+    pub interval_hours: String,
+    #[serde(default)]
+    pub interval_days: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OutOfServiceFormData {
+    pub out_of_service: bool,
+}
+
 // ============================
 // Equipment List & Management
 // ============================
@@ -117,73 +160,137 @@ pub async fn list_equipment(
     AuthenticatedUser(current_user): AuthenticatedUser,
     Query(query): Query<EquipmentQuery>,
 ) -> Result<Response, Error> {
-    // Determine owner context
-    let (owner_type, owner_id) = if let (Some(ot), Some(oi)) = (query.owner_type, query.owner_id) {
-        // Verify authorization for the specified owner
-        if ot == "organization" {
-            // Check if user is a member of the organization
-            let org_model = OrganizationModel::new();
-            let _org = org_model.get_by_id(&oi).await?;
-            let members = org_model.get_members(&oi).await?;
-            if !members
-                .iter()
-                .any(|m| m.person_id.to_raw_string() == current_user.id)
-            {
-                return Err(Error::Unauthorized);
-            }
-            ("organization".to_string(), oi)
-        } else if ot == "person" && oi == current_user.id {
-            ("person".to_string(), oi)
-        } else {
+    let (owner_type, owner_id) = equipment_owner(&current_user, &query).await?;
+    let items = equipment_items(owner_type.clone(), owner_id.clone(), &query).await?;
+
+    // Get kits list
+    let kits = EquipmentModel::list_kits_for_owner(&owner_type, &owner_id).await?;
+
+    let base = BaseContext::new().with_page("equipment");
+    let user = User::from_session_user(&current_user).await;
+
+    let template = EquipmentListTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: Some(user),
+        current_user: Some((*current_user).clone()),
+        items,
+        kits,
+        owner_type,
+        owner_id,
+        page_title: "Equipment".to_string(),
+        error_message: None,
+    };
+
+    Ok(Html(template.to_string()).into_response())
+}
+
+/// Just the list's items section, for applying filters without reloading
+/// the page
+pub async fn list_equipment_items(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Query(query): Query<EquipmentQuery>,
+) -> Result<Response, Error> {
+    let (owner_type, owner_id) = equipment_owner(&current_user, &query).await?;
+    let items = equipment_items(owner_type, owner_id, &query).await?;
+    Ok(Html(items.to_string()).into_response())
+}
+
+/// Whose equipment the list shows: an organization the user belongs to, or
+/// by default the user's own
+async fn equipment_owner(
+    current_user: &SessionUser,
+    query: &EquipmentQuery,
+) -> Result<(String, String), Error> {
+    let (Some(ot), Some(oi)) = (query.owner_type.clone(), query.owner_id.clone()) else {
+        return Ok(("person".to_string(), current_user.id.clone()));
+    };
+    // Verify authorization for the specified owner
+    if ot == "organization" {
+        // Check if user is a member of the organization
+        let org_model = OrganizationModel::new();
+        let _org = org_model.get_by_id(&oi).await?;
+        let members = org_model.get_members(&oi).await?;
+        if !members
+            .iter()
+            .any(|m| m.person_id.to_raw_string() == current_user.id)
+        {
             return Err(Error::Unauthorized);
         }
+        Ok(("organization".to_string(), oi))
+    } else if ot == "person" && oi == current_user.id {
+        Ok(("person".to_string(), oi))
     } else {
-        // Default to current user's personal equipment
-        ("person".to_string(), current_user.id.clone())
-    };
+        Err(Error::Unauthorized)
+    }
+}
 
-    // Get equipment list
+/// The owner's equipment, narrowed by the list's filters
+async fn equipment_items(
+    owner_type: String,
+    owner_id: String,
+    query: &EquipmentQuery,
+) -> Result<EquipmentItemsTemplate, Error> {
     let equipment = EquipmentModel::list_equipment_for_owner(&owner_type, &owner_id).await?;
 
-    // Get kits list
-    let kits = EquipmentModel::list_kits_for_owner(&owner_type, &owner_id).await?;
-
     // Filter by category if specified
-    let equipment: Vec<Equipment> = if let Some(category) = query.category {
+    let equipment: Vec<Equipment> = if let Some(category) = &query.category {
         equipment
             .into_iter()
-            .filter(|e| e.category.name == category)
+            .filter(|e| &e.category.name == category)
             .collect()
     } else {
         equipment
     };
 
-    // Filter by availability if specified
+    // Filter by availability if specified; gear out of service or booked by
+    // a production today isn't available
     let equipment: Vec<Equipment> = if let Some(true) = query.available_only {
-        equipment.into_iter().filter(|e| e.is_available).collect()
+        let ids: Vec<RecordId> = equipment.iter().map(|e| e.id.clone()).collect();
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let booked = ProductionGearModel::bookings(&ids, &today).await?;
+        equipment
+            .into_iter()
+            .filter(|e| e.can_check_out())
+            .filter(|e| {
+                !booked
+                    .iter()
+                    .any(|b| b.equipment == e.id && b.overlaps(&today, &today))
+            })
+            .collect()
     } else {
         equipment
     };
 
-    let base = BaseContext::new().with_page("equipment");
-    let user = User::from_session_user(&current_user).await;
+    let equipment: Vec<Equipment> = if let Some(true) = query.hide_out_of_service {
+        equipment.into_iter().filter(|e| !e.out_of_service).collect()
+    } else {
+        equipment
+    };
 
-    let template = EquipmentListTemplate {
-        app_name: base.app_name,
-        year: base.year,
-        version: base.version,
-        active_page: base.active_page,
-        user: Some(user),
-        current_user: Some((*current_user).clone()),
+    let mut fragment_src = format!(
+        "/equipment/items?owner_type={}&owner_id={}",
+        urlencoding::encode(&owner_type),
+        urlencoding::encode(&owner_id)
+    );
+    if let Some(category) = query.category.as_deref().filter(|c| !c.is_empty()) {
+        fragment_src.push_str(&format!("&category={}", urlencoding::encode(category)));
+    }
+    if query.available_only == Some(true) {
+        fragment_src.push_str("&available_only=true");
+    }
+    if query.hide_out_of_service == Some(true) {
+        fragment_src.push_str("&hide_out_of_service=true");
+    }
+
+    Ok(EquipmentItemsTemplate {
         equipment,
-        kits,
         owner_type,
         owner_id,
-        page_title: "Equipment".to_string(),
-        error_message: None,
-    };
-
-    Ok(Html(template.to_string()).into_response())
+        fragment_src,
+    })
 }
 
 // ============================
@@ -298,6 +405,7 @@ pub async fn create_equipment(
         description: form.description,
         purchase_date,
         purchase_price: form.purchase_price,
+        replacement_value: form.replacement_value,
         condition: form.condition,
         notes: form.notes,
         owner_type: owner_type.clone(),
@@ -325,6 +433,7 @@ pub async fn create_equipment(
 
 pub async fn show_equipment_detail(
     Path(id): Path<String>,
+    Query(error_query): Query<ErrorQuery>,
     request: Request,
 ) -> Result<Response, Error> {
     let current_user_opt = request.get_user();
@@ -334,6 +443,20 @@ pub async fn show_equipment_detail(
     // Get rental history
     let rentals = EquipmentModel::get_rental_history_for_equipment(&id).await?;
 
+    // Maintenance
+    let service_events = EquipmentServiceModel::history(&equipment.id).await?;
+    let today = chrono::Utc::now().date_naive();
+    let service_status = equipment_service::equipment_status(&equipment, today);
+    let service_remaining = equipment_service::remaining(&equipment, today);
+
+    // Production bookings
+    let bookings = ProductionGearModel::bookings(
+        std::slice::from_ref(&equipment.id),
+        &today.format("%Y-%m-%d").to_string(),
+    )
+    .await?;
+    let calendar = production_gear::calendar_weeks(&bookings, today, CALENDAR_WEEKS);
+
     // Check if user can edit (is owner)
     let can_edit = if let Some(ref user) = current_user_opt {
         if equipment.owner_type == "person" {
@@ -371,9 +494,16 @@ pub async fn show_equipment_detail(
         current_user: current_user_opt.as_ref().map(|u| (**u).clone()),
         equipment,
         rentals,
+        service_events,
+        service_status,
+        service_remaining,
+        service_kinds: equipment_service::SERVICE_KINDS.to_vec(),
+        today: today.format("%Y-%m-%d").to_string(),
+        bookings,
+        calendar,
         can_edit,
         page_title: "Equipment Details".to_string(),
-        error_message: None,
+        error_message: error_query.error,
     };
 
     Ok(Html(template.to_string()).into_response())
@@ -501,6 +631,7 @@ pub async fn update_equipment(
         description: form.description,
         purchase_date,
         purchase_price: form.purchase_price,
+        replacement_value: form.replacement_value,
         condition: form.condition,
         notes: form.notes,
         current_location: form.current_location,
@@ -574,7 +705,7 @@ pub async fn show_create_kit_form(
     let available_equipment = EquipmentModel::list_equipment_for_owner(&owner_type, &owner_id)
         .await?
         .into_iter()
-        .filter(|e| e.is_available && !e.is_kit_item)
+        .filter(|e| e.can_check_out() && !e.is_kit_item)
         .collect();
 
     // Get categories for dropdown
@@ -727,6 +858,12 @@ pub async fn show_checkout_form(
         ));
     };
 
+    let items =
+        EquipmentModel::booking_items(query.equipment_id.as_deref(), query.kit_id.as_deref())
+            .await?;
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let bookings = ProductionGearModel::bookings(&items, &today).await?;
+
     let base = BaseContext::new().with_page("equipment");
     let user = User::from_session_user(&current_user).await;
 
@@ -740,8 +877,9 @@ pub async fn show_checkout_form(
         equipment,
         kit,
         conditions,
+        bookings,
         page_title: "Checkout Equipment".to_string(),
-        error_message: None,
+        error_message: query.error,
     };
 
     Ok(Html(template.to_string()).into_response())
@@ -779,7 +917,23 @@ pub async fn checkout_equipment_post(
         checkout_by: current_user.id.clone(),
     };
 
-    let rental = EquipmentModel::checkout_equipment(data).await?;
+    let rental = match EquipmentModel::checkout_equipment(data).await {
+        Ok(rental) => rental,
+        Err(Error::Validation(message)) => {
+            let item = match (&form.equipment_id, &form.kit_id) {
+                (Some(eq_id), _) => format!("equipment_id={}", urlencoding::encode(eq_id)),
+                (None, Some(kit_id)) => format!("kit_id={}", urlencoding::encode(kit_id)),
+                (None, None) => return Err(Error::Validation(message)),
+            };
+            return Ok(Redirect::to(&format!(
+                "/equipment/checkout?{}&error={}",
+                item,
+                urlencoding::encode(&message)
+            ))
+            .into_response());
+        }
+        Err(e) => return Err(e),
+    };
 
     info!("Equipment checked out - rental: {}", rental.id.display());
 
@@ -846,6 +1000,599 @@ pub async fn checkin_equipment_post(
     }
 }
 
+// ============================
+// Maintenance
+// ============================
+
+/// The equipment, if the user is one of its owners
+async fn require_equipment_owner(id: &str, current_user: &SessionUser) -> Result<Equipment, Error> {
+    let equipment = EquipmentModel::get_equipment(id).await?;
+    if !is_owner(
+        &equipment.owner_type,
+        equipment.owner_person.as_ref(),
+        equipment.owner_organization.as_ref(),
+        &current_user.id,
+    )
+    .await
+    {
+        return Err(Error::Unauthorized);
+    }
+    Ok(equipment)
+}
+
+/// Back to the maintenance section, with the validation message if there was one
+fn back_to_maintenance(id: &str, result: Result<(), Error>) -> Result<Response, Error> {
+    match result {
+        Ok(()) => Ok(Redirect::to(&format!("/equipment/{}#maintenance", id)).into_response()),
+        Err(Error::Validation(message)) => Ok(Redirect::to(&format!(
+            "/equipment/{}?error={}#maintenance",
+            id,
+            urlencoding::encode(&message)
+        ))
+        .into_response()),
+        Err(e) => Err(e),
+    }
+}
+
+pub async fn log_service(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Path(id): Path<String>,
+    Form(form): Form<ServiceFormData>,
+) -> Result<Response, Error> {
+    let equipment = require_equipment_owner(&id, &current_user).await?;
+    let person =
+        RecordId::parse_simple(&current_user.id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    let result = async {
+        let data = equipment_service::clean_service_event(
+            &form.kind,
+            &form.performed_on,
+            &form.description,
+            &form.usage_hours,
+            &form.cost,
+            &form.vendor,
+            chrono::Utc::now().date_naive(),
+        )?;
+        let event = EquipmentServiceModel::log(&equipment, data, &person).await?;
+        info!("Logged {} on {}", event.kind, equipment.id.display());
+        Ok::<(), Error>(())
+    }
+    .await;
+    back_to_maintenance(&id, result)
+}
+
+pub async fn delete_service(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Path((id, event_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let equipment = require_equipment_owner(&id, &current_user).await?;
+    EquipmentServiceModel::delete(&equipment.id, &event_id).await?;
+    back_to_maintenance(&id, Ok(()))
+}
+
+pub async fn update_service_schedule(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Path(id): Path<String>,
+    Form(form): Form<ServiceScheduleFormData>,
+) -> Result<Response, Error> {
+    let equipment = require_equipment_owner(&id, &current_user).await?;
+    let result = async {
+        let (hours, days) =
+            equipment_service::clean_intervals(&form.interval_hours, &form.interval_days)?;
+        EquipmentServiceModel::set_intervals(&equipment.id, hours, days).await?;
+        Ok::<(), Error>(())
+    }
+    .await;
+    back_to_maintenance(&id, result)
+}
+
+pub async fn set_out_of_service(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Path(id): Path<String>,
+    Form(form): Form<OutOfServiceFormData>,
+) -> Result<Response, Error> {
+    let equipment = require_equipment_owner(&id, &current_user).await?;
+    EquipmentServiceModel::set_out_of_service(&equipment.id, form.out_of_service).await?;
+    info!(
+        "{} {} service by {}",
+        equipment.id.display(),
+        if form.out_of_service {
+            "taken out of"
+        } else {
+            "returned to"
+        },
+        current_user.username
+    );
+    back_to_maintenance(&id, Ok(()))
+}
+
+// ============================
+// Labels & Scanning
+// ============================
+
+#[derive(Debug, Deserialize)]
+pub struct ScanQuery {
+    pub code: Option<String>,
+    pub done: Option<String>,
+}
+
+/// Whether the user manages gear with this owner: it's theirs, or they're in
+/// the organization that owns it
+async fn is_owner(
+    owner_type: &str,
+    owner_person: Option<&RecordId>,
+    owner_organization: Option<&RecordId>,
+    user_id: &str,
+) -> bool {
+    match (owner_type, owner_person, owner_organization) {
+        ("person", Some(person), _) => person.to_raw_string() == user_id,
+        ("organization", _, Some(org)) => OrganizationModel::new()
+            .get_members(&org.to_raw_string())
+            .await
+            .unwrap_or_default()
+            .iter()
+            .any(|m| m.person_id.to_raw_string() == user_id),
+        _ => false,
+    }
+}
+
+/// Who has a rental out, by name
+async fn renter_name(rental: &EquipmentRental) -> String {
+    if let Some(person) = rental.renter_person.as_ref() {
+        if let Ok(Some(person)) = Person::get(person).await {
+            return person.get_display_name();
+        }
+    } else if let Some(org) = rental.renter_organization.as_ref() {
+        if let Ok(org) = OrganizationModel::new()
+            .get_by_id(&org.to_raw_string())
+            .await
+        {
+            return org.name;
+        }
+    }
+    "someone".to_string()
+}
+
+/// The condition a quick checkout records: the item's own, or "good" for kits
+async fn checkout_condition(condition_id: Option<String>) -> Result<String, Error> {
+    if let Some(condition) = condition_id {
+        return Ok(condition);
+    }
+    let conditions = EquipmentModel::get_all_conditions().await?;
+    conditions
+        .iter()
+        .find(|c| c.name.eq_ignore_ascii_case("good"))
+        .or(conditions.first())
+        .map(|c| c.id.key_string())
+        .ok_or_else(|| Error::Internal("No equipment conditions defined".to_string()))
+}
+
+/// The item behind a code and its open rental, if it's out
+async fn scanned_item(
+    code: &str,
+) -> Result<(ScannedCode, ScanItem, Option<EquipmentRental>), Error> {
+    let scanned = equipment_label::parse_scan(code).ok_or(Error::NotFound)?;
+    let (item, rentals) = match &scanned {
+        ScannedCode::Equipment(code) => {
+            let equipment = EquipmentModel::get_equipment_by_qr(code).await?;
+            let key = equipment.id.key_string();
+            let rentals = EquipmentModel::get_active_rentals_for_equipment(&key).await?;
+            let item = ScanItem {
+                kind: "equipment".to_string(),
+                id: key,
+                name: equipment.name,
+                code: code.clone(),
+                detail_url: format!("/equipment/{}", equipment.id.to_raw_string()),
+                category: equipment.category.name,
+                condition: Some(equipment.condition.name),
+                condition_id: Some(equipment.condition.id.key_string()),
+                owner_type: equipment.owner_type,
+                owner_person: equipment.owner_person,
+                owner_organization: equipment.owner_organization,
+                available: equipment.can_check_out(),
+                out_of_service: equipment.out_of_service,
+                out_to: None,
+                out_since: None,
+                can_checkin: false,
+            };
+            (item, rentals)
+        }
+        ScannedCode::Kit(code) => {
+            let kit = EquipmentModel::get_kit_by_qr(code).await?;
+            let key = kit.id.key_string();
+            let rentals = EquipmentModel::get_active_rentals_for_kit(&key).await?;
+            let item = ScanItem {
+                kind: "kit".to_string(),
+                id: key,
+                name: kit.name,
+                code: code.clone(),
+                detail_url: format!("/equipment/kit/{}", kit.id.to_raw_string()),
+                category: kit.category.name,
+                condition: None,
+                condition_id: None,
+                owner_type: kit.owner_type,
+                owner_person: kit.owner_person,
+                owner_organization: kit.owner_organization,
+                available: kit.is_available,
+                out_of_service: false,
+                out_to: None,
+                out_since: None,
+                can_checkin: false,
+            };
+            (item, rentals)
+        }
+    };
+    Ok((scanned, item, rentals.into_iter().next()))
+}
+
+/// The scan page, with a box to type or scan a code when `item` is empty
+async fn render_scan(
+    current_user: &SessionUser,
+    item: Option<ScanItem>,
+    notice: Option<String>,
+    error_message: Option<String>,
+) -> Result<Response, Error> {
+    let base = BaseContext::new().with_page("equipment");
+    let template = EquipmentScanTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: Some(User::from_session_user(current_user).await),
+        current_user: Some(current_user.clone()),
+        item,
+        notice,
+        page_title: "Scan Equipment".to_string(),
+        error_message,
+    };
+
+    Ok(Html(template.to_string()).into_response())
+}
+
+pub async fn show_scan_entry(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Query(query): Query<ScanQuery>,
+) -> Result<Response, Error> {
+    let error_message = match query.code.as_deref().map(str::trim) {
+        Some("") | None => None,
+        Some(input) => match equipment_label::parse_scan(input) {
+            Some(scanned) => {
+                return Ok(
+                    Redirect::to(&equipment_label::scan_path(scanned.code())).into_response()
+                );
+            }
+            None => Some(format!("\"{}\" isn't an equipment or kit code", input)),
+        },
+    };
+    render_scan(&current_user, None, None, error_message).await
+}
+
+pub async fn show_scanned_item(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Path(code): Path<String>,
+    Query(query): Query<ScanQuery>,
+) -> Result<Response, Error> {
+    let (_, mut item, rental) = scanned_item(&code).await?;
+    if let Some(rental) = rental.as_ref() {
+        item.out_to = Some(renter_name(rental).await);
+        item.out_since = Some(rental.checkout_date.format("%b %-d, %Y %H:%M").to_string());
+        item.can_checkin = rental
+            .renter_person
+            .as_ref()
+            .is_some_and(|p| p.to_raw_string() == current_user.id)
+            || is_owner(
+                &item.owner_type,
+                item.owner_person.as_ref(),
+                item.owner_organization.as_ref(),
+                &current_user.id,
+            )
+            .await;
+    }
+    let notice = match query.done.as_deref() {
+        Some("checkout") => Some(format!("{} is checked out to you.", item.name)),
+        Some("checkin") => Some(format!("{} is checked back in.", item.name)),
+        _ => None,
+    };
+    render_scan(&current_user, Some(item), notice, None).await
+}
+
+/// Check a scanned item out to whoever scanned it, in the condition it's
+/// listed in
+pub async fn scan_checkout(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Path(code): Path<String>,
+) -> Result<Response, Error> {
+    let (scanned, item, _) = scanned_item(&code).await?;
+    let condition = checkout_condition(item.condition_id).await?;
+    let person =
+        RecordId::parse_simple(&current_user.id).map_err(|e| Error::BadRequest(e.to_string()))?;
+
+    let data = CheckoutData {
+        equipment_id: matches!(scanned, ScannedCode::Equipment(_)).then(|| item.id.clone()),
+        kit_id: matches!(scanned, ScannedCode::Kit(_)).then(|| item.id.clone()),
+        renter_type: "person".to_string(),
+        renter_person: Some(person.key_string()),
+        renter_organization: None,
+        expected_return_date: None,
+        condition,
+        notes: Some("Checked out by scanning its label".to_string()),
+        checkout_by: person.key_string(),
+    };
+    let rental = EquipmentModel::checkout_equipment(data).await?;
+    info!(
+        "{} checked out {} by scan - rental: {}",
+        current_user.username,
+        scanned.code(),
+        rental.id.display()
+    );
+
+    Ok(Redirect::to(&format!(
+        "{}?done=checkout",
+        equipment_label::scan_path(scanned.code())
+    ))
+    .into_response())
+}
+
+/// Check a scanned item back in, for whoever has it or its owners
+pub async fn scan_checkin(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Path(code): Path<String>,
+) -> Result<Response, Error> {
+    let (scanned, item, rental) = scanned_item(&code).await?;
+    let rental =
+        rental.ok_or_else(|| Error::Validation(format!("{} isn't checked out", item.name)))?;
+    let renter = rental
+        .renter_person
+        .as_ref()
+        .is_some_and(|p| p.to_raw_string() == current_user.id);
+    if !renter
+        && !is_owner(
+            &item.owner_type,
+            item.owner_person.as_ref(),
+            item.owner_organization.as_ref(),
+            &current_user.id,
+        )
+        .await
+    {
+        return Err(Error::Forbidden);
+    }
+    let person =
+        RecordId::parse_simple(&current_user.id).map_err(|e| Error::BadRequest(e.to_string()))?;
+
+    let data = CheckinData {
+        return_condition: rental.checkout_condition.id.key_string(),
+        return_notes: Some("Checked in by scanning its label".to_string()),
+        return_by: person.key_string(),
+    };
+    EquipmentModel::checkin_equipment(&rental.id.key_string(), data).await?;
+    info!(
+        "{} checked in {} by scan",
+        current_user.username,
+        scanned.code()
+    );
+
+    Ok(Redirect::to(&format!(
+        "{}?done=checkin",
+        equipment_label::scan_path(scanned.code())
+    ))
+    .into_response())
+}
+
+/// PNG of the QR code on an item's label
+pub async fn scan_qr_image(Path(code): Path<String>) -> Result<Response, Error> {
+    let scanned = equipment_label::parse_scan(&code).ok_or(Error::NotFound)?;
+    let url = format!(
+        "{}{}",
+        crate::config::app_url(),
+        equipment_label::scan_path(scanned.code())
+    );
+    let png = equipment_label::qr_png(&url, 8)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        png,
+    )
+        .into_response())
+}
+
+/// A printable sheet of QR labels for everything an owner has
+pub async fn equipment_labels_pdf(
+    AuthenticatedUser(current_user): AuthenticatedUser,
+    Query(query): Query<EquipmentQuery>,
+) -> Result<Response, Error> {
+    let (owner_type, owner_id) = match (query.owner_type, query.owner_id) {
+        (Some(owner_type), Some(owner_id)) => (owner_type, owner_id),
+        _ => ("person".to_string(), current_user.id.clone()),
+    };
+    let owner = RecordId::parse_simple(&owner_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    let (person, org) = match owner_type.as_str() {
+        "person" => (Some(&owner), None),
+        "organization" => (None, Some(&owner)),
+        _ => return Err(Error::BadRequest("Unknown owner type".to_string())),
+    };
+    if !is_owner(&owner_type, person, org, &current_user.id).await {
+        return Err(Error::Unauthorized);
+    }
+
+    let equipment = EquipmentModel::list_equipment_for_owner(&owner_type, &owner_id).await?;
+    let kits = EquipmentModel::list_kits_for_owner(&owner_type, &owner_id).await?;
+    let labels: Vec<Label> = kits
+        .into_iter()
+        .filter_map(|kit| {
+            Some(Label {
+                code: kit.qr_code?,
+                detail: Some(format!("Kit - {}", kit.category.name)),
+                name: kit.name,
+            })
+        })
+        .chain(equipment.into_iter().filter_map(|item| {
+            Some(Label {
+                code: item.qr_code?,
+                detail: item.serial_number.map(|s| format!("S/N {}", s)),
+                name: item.name,
+            })
+        }))
+        .collect();
+    if labels.is_empty() {
+        return Err(Error::Validation(
+            "There's no equipment to print labels for yet".to_string(),
+        ));
+    }
+    let pdf = equipment_label::labels_pdf(&labels, &crate::config::app_url())?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                "inline; filename=\"equipment-labels.pdf\"".to_string(),
+            ),
+        ],
+        pdf,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BotScanRequest {
+    /// The sender's number, digits only
+    pub from: String,
+    /// Code or scan link from the message
+    pub code: String,
+}
+
+/// `/sh scan` from the WhatsApp bot: look the item up for someone who's
+/// linked their number, and reply with the link to check it out or in
+pub async fn whatsapp_scan(
+    headers: HeaderMap,
+    Json(request): Json<BotScanRequest>,
+) -> Result<Response, Error> {
+    if !whatsapp::bot_authorized(&headers) {
+        warn!("Rejected WhatsApp scan request with an invalid token");
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+    let reply = if whatsapp::person_for_number(&request.from).await?.is_none() {
+        "Link this number in your SlateHub account settings to look up equipment here.".to_string()
+    } else {
+        match scanned_item(&request.code).await {
+            Ok((scanned, item, rental)) => {
+                let out_to = match rental.as_ref() {
+                    Some(rental) => Some(renter_name(rental).await),
+                    None => None,
+                };
+                let url = format!("{}{}", crate::config::app_url(), equipment_label::scan_path(scanned.code()));
+                equipment_label::scan_reply(&item.name, out_to.as_deref(), item.available, &url)
+            }
+            Err(Error::NotFound) => {
+                "That isn't a SlateHub equipment code. Send the code printed under the QR, like /sh scan EQ-...".to_string()
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    Ok(Json(serde_json::json!({ "reply": reply })).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BotCheckoutRequest {
+    /// The sender's number, digits only
+    pub from: String,
+    /// Code or scan link from the message
+    pub code: String,
+    /// "YYYY-MM-DD" they'll bring it back by
+    #[serde(default)]
+    pub until: Option<String>,
+}
+
+fn bot_reply(reply: String) -> Response {
+    Json(serde_json::json!({ "reply": reply })).into_response()
+}
+
+/// `/sh checkout` from the WhatsApp bot: check the item out to whoever sent
+/// it. Production bookings it would run into are refused as on the web, and
+/// later ones come back as a heads-up.
+pub async fn whatsapp_checkout(
+    headers: HeaderMap,
+    Json(request): Json<BotCheckoutRequest>,
+) -> Result<Response, Error> {
+    if !whatsapp::bot_authorized(&headers) {
+        warn!("Rejected WhatsApp checkout request with an invalid token");
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+    let Some(person) = whatsapp::person_for_number(&request.from).await? else {
+        return Ok(bot_reply(
+            "Link this number in your SlateHub account settings to check out equipment here."
+                .to_string(),
+        ));
+    };
+    let today = chrono::Utc::now().date_naive();
+    let until = match request
+        .until
+        .as_deref()
+        .map(str::trim)
+        .filter(|u| !u.is_empty())
+    {
+        None => None,
+        Some(value) => match chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+            Ok(date) if date >= today => Some(date),
+            _ => {
+                return Ok(bot_reply(
+                    "Give the return date as YYYY-MM-DD from today on, like /sh checkout EQ-... until 2026-06-05".to_string(),
+                ));
+            }
+        },
+    };
+    let (scanned, item, _) = match scanned_item(&request.code).await {
+        Ok(found) => found,
+        Err(Error::NotFound) => {
+            return Ok(bot_reply(
+                "That isn't a SlateHub equipment code. Send the code printed under the QR, like /sh checkout EQ-...".to_string(),
+            ));
+        }
+        Err(e) => return Err(e),
+    };
+
+    let (equipment_id, kit_id) = match &scanned {
+        ScannedCode::Equipment(_) => (Some(item.id.clone()), None),
+        ScannedCode::Kit(_) => (None, Some(item.id.clone())),
+    };
+    let items = EquipmentModel::booking_items(equipment_id.as_deref(), kit_id.as_deref()).await?;
+    let data = CheckoutData {
+        equipment_id,
+        kit_id,
+        renter_type: "person".to_string(),
+        renter_person: Some(person.key_string()),
+        renter_organization: None,
+        expected_return_date: until
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|dt| chrono::DateTime::from_naive_utc_and_offset(dt, chrono::Utc)),
+        condition: checkout_condition(item.condition_id.clone()).await?,
+        notes: Some("Checked out from WhatsApp".to_string()),
+        checkout_by: person.key_string(),
+    };
+    let reply = match EquipmentModel::checkout_equipment(data).await {
+        Ok(rental) => {
+            info!(
+                "{} checked out {} from WhatsApp - rental: {}",
+                person.display(),
+                scanned.code(),
+                rental.id.display()
+            );
+            let end = until.unwrap_or(today).format("%Y-%m-%d").to_string();
+            let later = ProductionGearModel::bookings(&items, &end).await?;
+            let later: Vec<_> = later.iter().filter(|b| b.start_date > end).collect();
+            let until = until.map(|d| d.format("%Y-%m-%d").to_string());
+            equipment_label::checkout_reply(&item.name, until.as_deref(), &later)
+        }
+        Err(Error::Validation(message)) => {
+            format!("Can't check out *{}*: {}", item.name, message)
+        }
+        Err(e) => return Err(e),
+    };
+    Ok(bot_reply(reply))
+}
+
 // ============================
 // Router Configuration
 // ============================
@@ -854,6 +1601,7 @@ pub fn router() -> Router {
     Router::new()
         // Equipment list
         .route("/equipment", get(list_equipment))
+        .route("/equipment/items", get(list_equipment_items))
         // Equipment CRUD
         .route(
             "/equipment/new",
@@ -865,6 +1613,14 @@ pub fn router() -> Router {
             get(show_edit_equipment_form).post(update_equipment),
         )
         .route("/equipment/{id}/delete", post(delete_equipment))
+        // Maintenance
+        .route("/equipment/{id}/service", post(log_service))
+        .route(
+            "/equipment/{id}/service/{event_id}/delete",
+            post(delete_service),
+        )
+        .route("/equipment/{id}/service-schedule", post(update_service_schedule))
+        .route("/equipment/{id}/out-of-service", post(set_out_of_service))
         // Kit management
         .route(
             "/equipment/kit/new",
@@ -880,4 +1636,13 @@ pub fn router() -> Router {
             "/equipment/rental/{id}/checkin",
             get(show_checkin_form).post(checkin_equipment_post),
         )
+        // Labels and scanning
+        .route("/equipment/labels.pdf", get(equipment_labels_pdf))
+        .route("/equipment/scan", get(show_scan_entry))
+        .route("/equipment/scan/{code}", get(show_scanned_item))
+        .route("/equipment/scan/{code}/checkout", post(scan_checkout))
+        .route("/equipment/scan/{code}/checkin", post(scan_checkin))
+        .route("/equipment/scan/{code}/qr", get(scan_qr_image))
+        .route("/api/whatsapp/scan", post(whatsapp_scan))
+        .route("/api/whatsapp/checkout", post(whatsapp_checkout))
 }
@@ -0,0 +1,327 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        festival_submission::{
+            self, CalendarDay, FestivalSubmission, FestivalSubmissionModel, Outcome, SubmissionData,
+        },
+        production::{Production, ProductionModel},
+        production_gear::format_value,
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/productions/{slug}/festivals",
+            get(festivals_page).post(add_submission),
+        )
+        .route(
+            "/productions/{slug}/festivals/{submission_id}/edit",
+            post(edit_submission),
+        )
+        .route(
+            "/productions/{slug}/festivals/{submission_id}/outcome",
+            post(set_outcome),
+        )
+        .route(
+            "/productions/{slug}/festivals/{submission_id}/delete",
+            post(delete_submission),
+        )
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct SubmissionView {
+    pub item: FestivalSubmission,
+    /// "Due in 3 days", for planned ones
+    pub deadline_note: Option<String>,
+    pub status_options: Vec<SelectOption>,
+    pub result_options: Vec<SelectOption>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/festivals.html")]
+pub struct FestivalsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub can_edit: bool,
+    pub submissions: Vec<SubmissionView>,
+    /// "1,250.00"
+    pub fees_paid: String,
+    pub fees_planned: String,
+    /// "October 2026"
+    pub month_label: String,
+    /// "2026-09", "2026-11"
+    pub previous_month: String,
+    pub next_month: String,
+    pub weeks: Vec<Vec<CalendarDay>>,
+    pub max_name: usize,
+    pub max_notes: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FestivalsQuery {
+    /// "YYYY-MM" for the calendar
+    pub month: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmissionForm {
+    #[serde(default)]
+    pub festival: String,
+    #[serde(default)]
+    pub festival_url: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub deadline: String,
+    #[serde(default)]
+    pub notification_date: String,
+    #[serde(default)]
+    pub fee: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+impl SubmissionForm {
+    fn parse(&self) -> Result<SubmissionData, Error> {
+        SubmissionData::parse(
+            &self.festival,
+            &self.festival_url,
+            &self.category,
+            &self.deadline,
+            &self.notification_date,
+            &self.fee,
+            &self.notes,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OutcomeForm {
+    #[serde(default)]
+    pub status: String,
+    #[serde(default)]
+    pub result: String,
+    #[serde(default)]
+    pub award: String,
+    pub public: Option<String>,
+}
+
+// ============================
+// Access
+// ============================
+
+struct Access {
+    production: Production,
+    person: RecordId,
+    can_edit: bool,
+}
+
+/// Submissions are visible to the production's members; owners and admins
+/// manage them
+async fn require_member(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    let person = RecordId::parse_simple(user_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    let can_edit = ProductionModel::can_edit(&production.id, user_id).await?;
+    if !can_edit && !ProductionModel::is_member(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok(Access {
+        production,
+        person,
+        can_edit,
+    })
+}
+
+async fn require_editor(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let access = require_member(slug, user_id).await?;
+    if !access.can_edit {
+        return Err(Error::Forbidden);
+    }
+    Ok(access)
+}
+
+fn back_to_festivals(slug: &str, error: Option<&str>) -> Response {
+    match error {
+        Some(message) => Redirect::to(&format!(
+            "/productions/{}/festivals?error={}",
+            slug,
+            urlencoding::encode(message)
+        ))
+        .into_response(),
+        None => Redirect::to(&format!("/productions/{}/festivals", slug)).into_response(),
+    }
+}
+
+fn options(choices: &[(&str, &str)], selected: &str) -> Vec<SelectOption> {
+    choices
+        .iter()
+        .map(|(value, label)| SelectOption::new(value, label.to_string(), *value == selected))
+        .collect()
+}
+
+fn month_key(date: NaiveDate) -> String {
+    date.format("%Y-%m").to_string()
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn festivals_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<FestivalsQuery>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let items = FestivalSubmissionModel::for_production(&access.production.id).await?;
+    let today = Utc::now().date_naive();
+    let start = festival_submission::month_start(query.month.as_deref(), today);
+    let (previous, next) = festival_submission::adjacent_months(start);
+    let weeks = festival_submission::month_calendar(&items, start, today);
+    let (paid, planned) = festival_submission::fee_totals(&items);
+
+    let submissions = items
+        .into_iter()
+        .map(|item| SubmissionView {
+            deadline_note: item.deadline_note(today),
+            status_options: options(festival_submission::STATUSES, &item.status),
+            result_options: options(festival_submission::RESULTS, &item.result),
+            item,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(&user).await);
+    let template = FestivalsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: access.production.title,
+        production_slug: access.production.slug,
+        can_edit: access.can_edit,
+        submissions,
+        fees_paid: format_value(paid),
+        fees_planned: format_value(planned),
+        month_label: start.format("%B %Y").to_string(),
+        previous_month: month_key(previous),
+        next_month: month_key(next),
+        weeks,
+        max_name: festival_submission::MAX_NAME_CHARS,
+        max_notes: festival_submission::MAX_NOTES_CHARS,
+        error: query.error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render festivals template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn add_submission(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<SubmissionForm>,
+) -> Result<Response, Error> {
+    let access = require_editor(&slug, &user.id).await?;
+    let data = match form.parse() {
+        Ok(data) => data,
+        Err(Error::Validation(message)) => return Ok(back_to_festivals(&slug, Some(&message))),
+        Err(e) => return Err(e),
+    };
+    let id = FestivalSubmissionModel::create(&access.production.id, &data, &access.person).await?;
+    info!(
+        "{} added festival submission {} to {}",
+        user.username,
+        id.display(),
+        slug
+    );
+    Ok(back_to_festivals(&slug, None))
+}
+
+async fn edit_submission(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, submission_id)): Path<(String, String)>,
+    Form(form): Form<SubmissionForm>,
+) -> Result<Response, Error> {
+    let access = require_editor(&slug, &user.id).await?;
+    FestivalSubmissionModel::get(&access.production.id, &submission_id).await?;
+    let data = match form.parse() {
+        Ok(data) => data,
+        Err(Error::Validation(message)) => return Ok(back_to_festivals(&slug, Some(&message))),
+        Err(e) => return Err(e),
+    };
+    FestivalSubmissionModel::update(&access.production.id, &submission_id, &data).await?;
+    info!(
+        "{} updated festival submission {} on {}",
+        user.username, submission_id, slug
+    );
+    Ok(back_to_festivals(&slug, None))
+}
+
+async fn set_outcome(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, submission_id)): Path<(String, String)>,
+    Form(form): Form<OutcomeForm>,
+) -> Result<Response, Error> {
+    let access = require_editor(&slug, &user.id).await?;
+    FestivalSubmissionModel::get(&access.production.id, &submission_id).await?;
+    let outcome = match Outcome::parse(
+        &form.status,
+        &form.result,
+        &form.award,
+        form.public.is_some(),
+    ) {
+        Ok(outcome) => outcome,
+        Err(Error::Validation(message)) => return Ok(back_to_festivals(&slug, Some(&message))),
+        Err(e) => return Err(e),
+    };
+    FestivalSubmissionModel::set_outcome(&access.production.id, &submission_id, &outcome).await?;
+    info!(
+        "{} marked festival submission {} on {} as {} ({})",
+        user.username, submission_id, slug, outcome.status, outcome.result
+    );
+    Ok(back_to_festivals(&slug, None))
+}
+
+async fn delete_submission(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, submission_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_editor(&slug, &user.id).await?;
+    FestivalSubmissionModel::delete(&access.production.id, &submission_id).await?;
+    info!(
+        "{} deleted festival submission {} from {}",
+        user.username, submission_id, slug
+    );
+    Ok(back_to_festivals(&slug, None))
+}
@@ -0,0 +1,551 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use axum_extra::extract::Form as HtmlForm;
+use chrono::Utc;
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        gear_quote::{
+            self, GearQuote, GearQuoteModel, QuoteListing, QuotePrice, QuoteRequest, Vendor,
+        },
+        notification::NotificationModel,
+        organization::{Organization, OrganizationModel},
+        person::SessionUser,
+        production::{Production, ProductionModel},
+        production_gear,
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/productions/{slug}/quotes",
+            get(production_quotes_page).post(request_quote),
+        )
+        .route(
+            "/productions/{slug}/quotes/{quote_id}/accept",
+            post(accept_quote),
+        )
+        .route(
+            "/productions/{slug}/quotes/{quote_id}/withdraw",
+            post(withdraw_quote),
+        )
+        .route("/orgs/{slug}/quotes", get(vendor_quotes_page))
+        .route("/orgs/{slug}/quotes/{quote_id}/quote", post(send_price))
+        .route(
+            "/orgs/{slug}/quotes/{quote_id}/decline",
+            post(decline_quote),
+        )
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct QuoteGearOption {
+    pub id: String,
+    /// "Canon C70 (SN 1234)"
+    pub label: String,
+}
+
+#[derive(Template)]
+#[template(path = "productions/quotes.html")]
+pub struct ProductionQuotesTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub quotes: Vec<QuoteListing>,
+    pub vendors: Vec<Vendor>,
+    /// The vendor picked for a new request, with its gear
+    pub vendor: Option<Vendor>,
+    pub gear: Vec<QuoteGearOption>,
+    pub today: String,
+    pub max_note: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "organizations/quotes.html")]
+pub struct VendorQuotesTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub organization_name: String,
+    pub organization_slug: String,
+    pub quotes: Vec<QuoteListing>,
+    pub today: String,
+    pub max_note: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuotesQuery {
+    pub vendor: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequestQuoteForm {
+    #[serde(default)]
+    pub vendor_id: String,
+    #[serde(default, rename = "equipment_id")]
+    pub equipment_ids: Vec<String>,
+    #[serde(default)]
+    pub start_date: String,
+    #[serde(default)]
+    pub end_date: String,
+    #[serde(default)]
+    pub note: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PriceForm {
+    #[serde(default)]
+    pub price: String,
+    #[serde(default)]
+    pub valid_until: String,
+    #[serde(default)]
+    pub note: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeclineForm {
+    #[serde(default)]
+    pub note: String,
+}
+
+// ============================
+// Helpers
+// ============================
+
+fn person_id(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+fn today() -> String {
+    Utc::now().date_naive().format("%Y-%m-%d").to_string()
+}
+
+/// The production, if the user can edit it. Quotes carry prices, so they're
+/// for the production's owners and admins like its offers.
+async fn require_editor(slug: &str, user: &SessionUser) -> Result<Production, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    if !ProductionModel::can_edit(&production.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok(production)
+}
+
+/// The organization, if the user is one of its members
+async fn require_vendor(slug: &str, user: &SessionUser) -> Result<Organization, Error> {
+    let model = OrganizationModel::new();
+    let organization = model.get_by_slug(slug).await?;
+    if model
+        .get_member_role(&organization.id.to_raw_string(), &user.id)
+        .await?
+        .is_none()
+    {
+        return Err(Error::Forbidden);
+    }
+    Ok(organization)
+}
+
+async fn require_production_quote(
+    quote_id: &str,
+    production: &Production,
+) -> Result<GearQuote, Error> {
+    let quote = GearQuoteModel::get(quote_id).await?;
+    if quote.production != production.id {
+        return Err(Error::NotFound);
+    }
+    Ok(quote)
+}
+
+async fn require_vendor_quote(
+    quote_id: &str,
+    organization: &Organization,
+) -> Result<GearQuote, Error> {
+    let quote = GearQuoteModel::get(quote_id).await?;
+    if quote.vendor != organization.id || quote.status == "withdrawn" {
+        return Err(Error::NotFound);
+    }
+    Ok(quote)
+}
+
+fn back_to_production(slug: &str, error: Option<&str>) -> Response {
+    match error {
+        Some(message) => Redirect::to(&format!(
+            "/productions/{}/quotes?error={}",
+            slug,
+            urlencoding::encode(message)
+        ))
+        .into_response(),
+        None => Redirect::to(&format!("/productions/{}/quotes", slug)).into_response(),
+    }
+}
+
+fn back_to_vendor(slug: &str, error: Option<&str>) -> Response {
+    match error {
+        Some(message) => Redirect::to(&format!(
+            "/orgs/{}/quotes?error={}",
+            slug,
+            urlencoding::encode(message)
+        ))
+        .into_response(),
+        None => Redirect::to(&format!("/orgs/{}/quotes", slug)).into_response(),
+    }
+}
+
+async fn notify(person: &RecordId, title: &str, message: &str, link: &str, quote: &RecordId) {
+    if let Err(e) = NotificationModel::new()
+        .create(
+            &person.to_raw_string(),
+            "quote",
+            title,
+            message,
+            Some(link),
+            Some(&quote.to_raw_string()),
+        )
+        .await
+    {
+        error!("Failed to notify {} about quote: {}", person.display(), e);
+    }
+}
+
+/// Tell the vendor's owners and admins about a request or the production's answer
+async fn notify_vendor(quote: &GearQuote, title: &str, message: &str) {
+    let listing = match GearQuoteModel::listing(&quote.id).await {
+        Ok(listing) => listing,
+        Err(e) => {
+            error!("Failed to load quote {}: {}", quote.id.display(), e);
+            return;
+        }
+    };
+    let members = match OrganizationModel::new()
+        .get_members(&quote.vendor.to_raw_string())
+        .await
+    {
+        Ok(members) => members,
+        Err(e) => {
+            error!(
+                "Failed to load members of {}: {}",
+                quote.vendor.display(),
+                e
+            );
+            return;
+        }
+    };
+    let link = format!(
+        "/orgs/{}/quotes#quote-{}",
+        listing.vendor_slug,
+        quote.id.key_string()
+    );
+    for member in members
+        .iter()
+        .filter(|m| m.invitation_status == "accepted" && (m.role == "owner" || m.role == "admin"))
+    {
+        notify(&member.person_id, title, message, &link, &quote.id).await;
+    }
+}
+
+/// Tell whoever asked for the quote how the vendor answered
+async fn notify_requester(quote: &GearQuote, title: &str, message: &str) {
+    let listing = match GearQuoteModel::listing(&quote.id).await {
+        Ok(listing) => listing,
+        Err(e) => {
+            error!("Failed to load quote {}: {}", quote.id.display(), e);
+            return;
+        }
+    };
+    let link = format!(
+        "/productions/{}/quotes#quote-{}",
+        listing.production_slug,
+        quote.id.key_string()
+    );
+    notify(&quote.requested_by, title, message, &link, &quote.id).await;
+}
+
+// ============================
+// Production side
+// ============================
+
+async fn production_quotes_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<QuotesQuery>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    let quotes = GearQuoteModel::for_production(&production.id).await?;
+    let vendors = GearQuoteModel::vendors().await?;
+    let vendor = query
+        .vendor
+        .as_deref()
+        .and_then(|key| vendors.iter().find(|v| v.id.key_string() == key))
+        .cloned();
+    let gear = match vendor.as_ref() {
+        Some(vendor) => GearQuoteModel::vendor_gear(&vendor.id)
+            .await?
+            .into_iter()
+            .map(|e| QuoteGearOption {
+                id: e.id.key_string(),
+                label: match e.serial_number.as_deref().filter(|s| !s.is_empty()) {
+                    Some(serial) => format!("{} (SN {})", e.name, serial),
+                    None => e.name,
+                },
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(&user).await);
+    let template = ProductionQuotesTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        quotes,
+        vendors,
+        vendor,
+        gear,
+        today: today(),
+        max_note: gear_quote::MAX_NOTE_CHARS,
+        error: query.error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render production quotes template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn request_quote(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    HtmlForm(form): HtmlForm<RequestQuoteForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    let vendor = RecordId::new("organization", form.vendor_id.as_str());
+    let vendor_gear: Vec<RecordId> = GearQuoteModel::vendor_gear(&vendor)
+        .await?
+        .into_iter()
+        .map(|e| e.id)
+        .collect();
+    let request = match QuoteRequest::parse(
+        &form.equipment_ids,
+        &vendor_gear,
+        &form.start_date,
+        &form.end_date,
+        &form.note,
+        Utc::now().date_naive(),
+    ) {
+        Ok(request) => request,
+        Err(Error::Validation(message)) => {
+            return Ok(Redirect::to(&format!(
+                "/productions/{}/quotes?vendor={}&error={}",
+                slug,
+                urlencoding::encode(&form.vendor_id),
+                urlencoding::encode(&message)
+            ))
+            .into_response());
+        }
+        Err(e) => return Err(e),
+    };
+
+    let quote =
+        GearQuoteModel::create(&production.id, &vendor, &request, &person_id(&user)?).await?;
+    info!(
+        "{} requested quote {} from {} for {}",
+        user.username,
+        quote.id.display(),
+        vendor.display(),
+        slug
+    );
+    let message = format!(
+        "{} would like a quote for {} item{} for {}.",
+        production.title,
+        request.equipment.len(),
+        if request.equipment.len() == 1 {
+            ""
+        } else {
+            "s"
+        },
+        production_gear::dates_label(&request.start_date, &request.end_date)
+    );
+    notify_vendor(&quote, "New quote request", &message).await;
+    Ok(back_to_production(&slug, None))
+}
+
+async fn accept_quote(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, quote_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    let quote = require_production_quote(&quote_id, &production).await?;
+    let accepted = match GearQuoteModel::accept(&quote, &today()).await {
+        Ok(Some(accepted)) => accepted,
+        Ok(None) => {
+            return Ok(back_to_production(
+                &slug,
+                Some("The vendor changed this quote. Check it and try again."),
+            ));
+        }
+        Err(Error::Validation(message)) | Err(Error::Conflict(message)) => {
+            return Ok(back_to_production(&slug, Some(&message)));
+        }
+        Err(e) => return Err(e),
+    };
+    GearQuoteModel::confirm(&accepted, &person_id(&user)?).await?;
+    info!("{} accepted quote {} for {}", user.username, quote_id, slug);
+    let message = format!(
+        "{} accepted your quote. The gear is booked for them for {}.",
+        production.title,
+        production_gear::dates_label(&accepted.start_date, &accepted.end_date)
+    );
+    notify_vendor(&accepted, "Quote accepted", &message).await;
+    Ok(Redirect::to(&format!("/productions/{}/budget", slug)).into_response())
+}
+
+async fn withdraw_quote(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, quote_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    let quote = require_production_quote(&quote_id, &production).await?;
+    match GearQuoteModel::withdraw(&quote).await {
+        Ok(Some(withdrawn)) => {
+            info!("{} withdrew quote {} for {}", user.username, quote_id, slug);
+            let message = format!("{} no longer needs this quote.", production.title);
+            notify_vendor(&withdrawn, "Quote request withdrawn", &message).await;
+            Ok(back_to_production(&slug, None))
+        }
+        Ok(None) => Ok(back_to_production(
+            &slug,
+            Some("The vendor changed this quote. Check it and try again."),
+        )),
+        Err(Error::Conflict(message)) => Ok(back_to_production(&slug, Some(&message))),
+        Err(e) => Err(e),
+    }
+}
+
+// ============================
+// Vendor side
+// ============================
+
+async fn vendor_quotes_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<QuotesQuery>,
+) -> Result<Response, Error> {
+    let organization = require_vendor(&slug, &user).await?;
+    let quotes = GearQuoteModel::for_vendor(&organization.id).await?;
+
+    let base = BaseContext::new()
+        .with_page("organizations")
+        .with_user(User::from_session_user(&user).await);
+    let template = VendorQuotesTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        organization_name: organization.name,
+        organization_slug: organization.slug,
+        quotes,
+        today: today(),
+        max_note: gear_quote::MAX_NOTE_CHARS,
+        error: query.error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render vendor quotes template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn send_price(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, quote_id)): Path<(String, String)>,
+    Form(form): Form<PriceForm>,
+) -> Result<Response, Error> {
+    let organization = require_vendor(&slug, &user).await?;
+    let quote = require_vendor_quote(&quote_id, &organization).await?;
+    let price = match QuotePrice::parse(
+        &form.price,
+        &form.valid_until,
+        &form.note,
+        Utc::now().date_naive(),
+    ) {
+        Ok(price) => price,
+        Err(Error::Validation(message)) => return Ok(back_to_vendor(&slug, Some(&message))),
+        Err(e) => return Err(e),
+    };
+    match GearQuoteModel::quote(&quote, &price, &person_id(&user)?).await {
+        Ok(Some(quoted)) => {
+            info!("{} quoted {} for {}", user.username, quote_id, slug);
+            let listing_price = format!("${}", production_gear::format_value(price.price));
+            let message = match price.valid_until.as_deref() {
+                Some(date) => format!(
+                    "{} quoted {}, valid until {}.",
+                    organization.name, listing_price, date
+                ),
+                None => format!("{} quoted {}.", organization.name, listing_price),
+            };
+            notify_requester(&quoted, "Quote received", &message).await;
+            Ok(back_to_vendor(&slug, None))
+        }
+        Ok(None) => Ok(back_to_vendor(
+            &slug,
+            Some("The production changed this request. Check it and try again."),
+        )),
+        Err(Error::Conflict(message)) => Ok(back_to_vendor(&slug, Some(&message))),
+        Err(e) => Err(e),
+    }
+}
+
+async fn decline_quote(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, quote_id)): Path<(String, String)>,
+    Form(form): Form<DeclineForm>,
+) -> Result<Response, Error> {
+    let organization = require_vendor(&slug, &user).await?;
+    let quote = require_vendor_quote(&quote_id, &organization).await?;
+    match GearQuoteModel::decline(&quote, &form.note).await {
+        Ok(Some(declined)) => {
+            info!("{} declined quote {} for {}", user.username, quote_id, slug);
+            let message = format!("{} can't quote for this request.", organization.name);
+            notify_requester(&declined, "Quote declined", &message).await;
+            Ok(back_to_vendor(&slug, None))
+        }
+        Ok(None) => Ok(back_to_vendor(
+            &slug,
+            Some("The production changed this request. Check it and try again."),
+        )),
+        Err(Error::Validation(message)) | Err(Error::Conflict(message)) => {
+            Ok(back_to_vendor(&slug, Some(&message)))
+        }
+        Err(e) => Err(e),
+    }
+}
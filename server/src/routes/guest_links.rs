@@ -0,0 +1,389 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query, multipart::Multipart},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use chrono::Utc;
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use super::shot_lists::{SchedulePrintTemplate, load_days};
+use crate::{
+    config,
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        deliverable::{self, DeliverableModel},
+        guest_link::{self, EXPIRY_OPTIONS, GuestLink, GuestLinkData, GuestLinkModel, SCOPES},
+        person::SessionUser,
+        production::{Production, ProductionModel},
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/productions/{slug}/guest-links",
+            get(guest_links_page).post(create_link),
+        )
+        .route(
+            "/productions/{slug}/guest-links/{link_id}/revoke",
+            post(revoke_link),
+        )
+        .route("/guest/{token}", get(guest_page))
+        .route("/guest/{token}/upload", post(guest_upload))
+}
+
+fn guest_url(token: &str) -> String {
+    format!("{}/guest/{}", config::app_url(), token)
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct GuestLinkView {
+    pub id: String,
+    pub label: String,
+    pub access: String,
+    pub status: &'static str,
+    pub expires_at: String,
+    pub last_used_at: Option<String>,
+    pub created_by: String,
+}
+
+impl GuestLinkView {
+    fn new(link: GuestLink, now: chrono::DateTime<Utc>) -> Self {
+        let access = match &link.deliverable_title {
+            Some(title) => format!("Upload to {}", title),
+            None => link.scope_label().to_string(),
+        };
+        Self {
+            id: link.id.key_string(),
+            status: link.status(now),
+            access,
+            expires_at: link.expires_at.format("%b %-d, %Y %H:%M").to_string(),
+            last_used_at: link
+                .last_used_at
+                .map(|at| at.format("%b %-d, %Y %H:%M").to_string()),
+            created_by: link.creator_name.unwrap_or_default(),
+            label: link.label,
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "productions/guest_links.html")]
+pub struct GuestLinksTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub links: Vec<GuestLinkView>,
+    pub scopes: Vec<SelectOption>,
+    pub deliverables: Vec<SelectOption>,
+    pub expiry_options: Vec<SelectOption>,
+    pub max_label: usize,
+    /// The link just made; its token isn't stored, so this is the only time
+    /// it's shown
+    pub created_url: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/guest_upload.html")]
+pub struct GuestUploadTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub token: String,
+    pub label: String,
+    pub deliverable_title: String,
+    pub specs: Option<String>,
+    pub due_date: Option<String>,
+    pub expires_at: String,
+    pub accept: String,
+    pub message: Option<String>,
+    pub error: Option<String>,
+}
+
+// ============================
+// Managing links
+// ============================
+
+/// Links are issued and revoked by the production's owners and admins
+async fn require_editor(slug: &str, user_id: &str) -> Result<Production, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    if !ProductionModel::can_edit(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok(production)
+}
+
+async fn render_links(
+    user: &SessionUser,
+    production: Production,
+    created_url: Option<String>,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let now = Utc::now();
+    let links = GuestLinkModel::for_production(&production.id)
+        .await?
+        .into_iter()
+        .map(|link| GuestLinkView::new(link, now))
+        .collect();
+    let deliverables = DeliverableModel::for_production(&production.id)
+        .await?
+        .into_iter()
+        .map(|d| SelectOption::new(d.id.key_string(), d.title, false))
+        .collect();
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+
+    let template = GuestLinksTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        links,
+        scopes: SCOPES
+            .iter()
+            .map(|(value, label)| SelectOption::new(value, label.to_string(), false))
+            .collect(),
+        deliverables,
+        expiry_options: EXPIRY_OPTIONS
+            .iter()
+            .map(|(days, label)| SelectOption::new(days.to_string(), label.to_string(), *days == 7))
+            .collect(),
+        max_label: guest_link::MAX_LABEL_CHARS,
+        created_url,
+        error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render guest links template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn guest_links_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    render_links(&user, production, None, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct GuestLinkForm {
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    scope: String,
+    #[serde(default)]
+    deliverable: String,
+    #[serde(default)]
+    days: String,
+}
+
+async fn create_link(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<GuestLinkForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    let data = match GuestLinkData::parse(&form.label, &form.scope, &form.deliverable, &form.days) {
+        Ok(data) => data,
+        Err(Error::Validation(msg)) => {
+            return render_links(&user, production, None, Some(msg)).await;
+        }
+        Err(e) => return Err(e),
+    };
+    let deliverable = match &data.deliverable {
+        Some(key) => Some(DeliverableModel::get(&production.id, key).await?.id),
+        None => None,
+    };
+    let person = RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))?;
+
+    let token =
+        GuestLinkModel::create(&production.id, &data, deliverable.as_ref(), &person).await?;
+    info!(
+        "{} issued a {} guest link on {} for {}",
+        user.username, data.scope, slug, data.label
+    );
+    render_links(&user, production, Some(guest_url(&token)), None).await
+}
+
+async fn revoke_link(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, link_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    GuestLinkModel::revoke(&production.id, &link_id).await?;
+    info!(
+        "{} revoked guest link {} on {}",
+        user.username, link_id, slug
+    );
+    Ok(Redirect::to(&format!("/productions/{}/guest-links", slug)).into_response())
+}
+
+// ============================
+// Guest handlers
+// ============================
+
+#[derive(Debug, Deserialize)]
+struct GuestQuery {
+    message: Option<String>,
+    error: Option<String>,
+}
+
+fn back_to_upload(token: &str, key: &str, value: &str) -> Response {
+    Redirect::to(&format!(
+        "/guest/{}?{}={}",
+        token,
+        key,
+        urlencoding::encode(value)
+    ))
+    .into_response()
+}
+
+async fn guest_page(
+    Path(token): Path<String>,
+    Query(query): Query<GuestQuery>,
+) -> Result<Response, Error> {
+    let link = GuestLinkModel::by_token(&token).await?;
+    let production = ProductionModel::get(&link.production).await?;
+    let base = BaseContext::new();
+
+    let html = match (link.scope.as_str(), &link.deliverable) {
+        ("schedule", _) => {
+            let (days, unscheduled) = load_days(&production.id).await?;
+            SchedulePrintTemplate {
+                app_name: base.app_name,
+                version: base.version,
+                production_title: production.title,
+                production_slug: production.slug,
+                printed_on: Utc::now().format("%b %-d, %Y").to_string(),
+                days,
+                unscheduled,
+                guest: true,
+            }
+            .render()
+        }
+        ("upload", Some(deliverable_id)) => {
+            let item = DeliverableModel::get(&production.id, &deliverable_id.key_string()).await?;
+            GuestUploadTemplate {
+                app_name: base.app_name,
+                year: base.year,
+                version: base.version,
+                active_page: base.active_page,
+                user: None,
+                production_title: production.title,
+                token,
+                label: link.label,
+                deliverable_title: item.title,
+                specs: item.specs,
+                due_date: item.due_date,
+                expires_at: link.expires_at.format("%b %-d, %Y").to_string(),
+                accept: deliverable::ALLOWED_EXTENSIONS
+                    .iter()
+                    .map(|ext| format!(".{}", ext))
+                    .collect::<Vec<_>>()
+                    .join(","),
+                message: query.message,
+                error: query.error,
+            }
+            .render()
+        }
+        _ => return Err(Error::NotFound),
+    };
+    Ok(Html(html.map_err(|e| {
+        error!("Failed to render guest page: {}", e);
+        Error::template(e.to_string())
+    })?)
+    .into_response())
+}
+
+/// Upload links can only add files to their one deliverable
+async fn guest_upload(
+    Path(token): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Response, Error> {
+    let link = GuestLinkModel::by_token(&token).await?;
+    let Some(deliverable_id) = link.deliverable.as_ref().filter(|_| link.scope == "upload") else {
+        return Err(Error::Forbidden);
+    };
+    let item = DeliverableModel::get(&link.production, &deliverable_id.key_string()).await?;
+
+    let mut upload: Option<(String, String, bytes::Bytes)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| Error::bad_request(format!("Failed to read multipart: {}", e)))?
+    {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let file_name = field.file_name().unwrap_or("").to_string();
+        if !deliverable::attachment_allowed(&file_name) {
+            return Ok(back_to_upload(
+                &token,
+                "error",
+                "That type of file can't be uploaded",
+            ));
+        }
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        let data = field
+            .bytes()
+            .await
+            .map_err(|e| Error::bad_request(format!("Failed to read file data: {}", e)))?;
+        if data.len() > deliverable::MAX_FILE_SIZE {
+            return Ok(back_to_upload(&token, "error", "Files can be up to 50MB"));
+        }
+        upload = Some((file_name, content_type, data));
+    }
+
+    let Some((file_name, content_type, data)) = upload.filter(|(_, _, data)| !data.is_empty())
+    else {
+        return Ok(back_to_upload(&token, "error", "Choose a file to upload"));
+    };
+    DeliverableModel::add_file(
+        &link.production,
+        &item,
+        &file_name,
+        data,
+        &content_type,
+        &link.created_by,
+        Some(&link.label),
+    )
+    .await?;
+    info!(
+        "Guest link {} uploaded {} to deliverable {}",
+        link.id.display(),
+        file_name,
+        item.id.display()
+    );
+    Ok(back_to_upload(
+        &token,
+        "message",
+        &format!("Uploaded {}", deliverable::safe_file_name(&file_name)),
+    ))
+}
@@ -0,0 +1,129 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::{Path, Query, Request},
+    response::Html,
+    routing::get,
+};
+use serde::Deserialize;
+use tracing::error;
+
+use crate::{
+    error::Error,
+    middleware::UserExtractor,
+    services::{
+        help::{self, Article},
+        search_log,
+    },
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/help", get(help_index))
+        .route("/help/{slug}", get(help_article))
+}
+
+#[derive(Template)]
+#[template(path = "help/index.html")]
+pub struct HelpIndexTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    /// What was searched, if anything
+    pub query: Option<String>,
+    pub results: Vec<&'static Article>,
+    /// Every article by section, shown when nothing's been searched
+    pub sections: Vec<(&'static str, Vec<&'static Article>)>,
+}
+
+#[derive(Template)]
+#[template(path = "help/article.html")]
+pub struct HelpArticleTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub article: &'static Article,
+    /// Sanitized HTML of the article's text
+    pub content: String,
+    /// Other articles in the same section
+    pub related: Vec<&'static Article>,
+}
+
+async fn base_context(request: &Request) -> BaseContext {
+    let base = BaseContext::new().with_page("help");
+    match request.get_user() {
+        Some(user) => base.with_user(User::from_session_user(&user).await),
+        None => base,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct HelpQuery {
+    q: Option<String>,
+}
+
+/// The help center: every article by section, or those matching `?q=`
+async fn help_index(
+    Query(params): Query<HelpQuery>,
+    request: Request,
+) -> Result<Html<String>, Error> {
+    let base = base_context(&request).await;
+    let query = params
+        .q
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty());
+
+    let results = match &query {
+        Some(query) => {
+            let results = help::search(query).await;
+            search_log::log_search_with_id(query, "help", "help", Some(results.len()), None);
+            results
+        }
+        None => vec![],
+    };
+
+    let template = HelpIndexTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        query,
+        results,
+        sections: help::sections(),
+    };
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render help center: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+async fn help_article(Path(slug): Path<String>, request: Request) -> Result<Html<String>, Error> {
+    let article = help::article(&slug).ok_or(Error::NotFound)?;
+    let base = base_context(&request).await;
+
+    let related = help::articles()
+        .iter()
+        .filter(|other| other.section == article.section && other.slug != article.slug)
+        .collect();
+
+    let template = HelpArticleTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        article,
+        content: article.html(),
+        related,
+    };
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render help article {}: {}", slug, e);
+        Error::template(e.to_string())
+    })?))
+}
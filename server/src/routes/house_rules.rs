@@ -0,0 +1,181 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::Path,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        house_rules::{self, HouseRulesModel},
+        person::SessionUser,
+        production::{Production, ProductionModel},
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/productions/{slug}/house-rules", get(house_rules_page))
+        .route(
+            "/productions/{slug}/house-rules/{location_id}/acknowledge",
+            post(acknowledge),
+        )
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct AckRow {
+    pub name: String,
+    pub roles: String,
+    pub key_role: bool,
+    pub acknowledged_at: Option<String>,
+}
+
+pub struct LocationRules {
+    pub id: String,
+    pub name: String,
+    pub city: String,
+    pub rules: Vec<String>,
+    pub updated_at: Option<String>,
+    pub acknowledged: bool,
+    /// Key crew still to acknowledge, as "Name (Role)"
+    pub pending: Vec<String>,
+    pub rows: Vec<AckRow>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/house_rules.html")]
+pub struct HouseRulesTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub locations: Vec<LocationRules>,
+    /// Booked locations that don't list any rules
+    pub without_rules: Vec<String>,
+}
+
+// ============================
+// Helpers
+// ============================
+
+fn format_time(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.format("%b %-d, %Y %H:%M").to_string()
+}
+
+/// House rules are for the production's members, who each acknowledge them
+async fn require_member(slug: &str, user_id: &str) -> Result<(Production, RecordId), Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    if !ProductionModel::can_edit(&production.id, user_id).await?
+        && !ProductionModel::is_member(&production.id, user_id).await?
+    {
+        return Err(Error::Forbidden);
+    }
+    let person = RecordId::parse_simple(user_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    Ok((production, person))
+}
+
+async fn render_page(
+    user: &SessionUser,
+    production: Production,
+    person: &RecordId,
+) -> Result<Response, Error> {
+    let viewer = person.to_raw_string();
+    let mut locations = Vec::new();
+    let mut without_rules = Vec::new();
+    for location in HouseRulesModel::booked_locations(&production.id).await? {
+        if location.house_rules.is_empty() {
+            without_rules.push(location.name);
+            continue;
+        }
+        let acknowledgments = HouseRulesModel::acknowledgments(&production.id, &location).await?;
+        locations.push(LocationRules {
+            id: location.id.key_string(),
+            acknowledged: acknowledgments
+                .iter()
+                .any(|a| a.person_id == viewer && a.acknowledged_at.is_some()),
+            pending: house_rules::pending_key_people(&acknowledgments),
+            rows: acknowledgments
+                .into_iter()
+                .map(|a| AckRow {
+                    name: a.name,
+                    roles: a.roles.join(", "),
+                    key_role: a.key_role,
+                    acknowledged_at: a.acknowledged_at.map(format_time),
+                })
+                .collect(),
+            name: location.name,
+            city: location.city,
+            rules: location.house_rules,
+            updated_at: location.house_rules_updated_at.map(format_time),
+        });
+    }
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+    let template = HouseRulesTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        locations,
+        without_rules,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render house rules template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn house_rules_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let (production, person) = require_member(&slug, &user.id).await?;
+    render_page(&user, production, &person).await
+}
+
+async fn acknowledge(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, location_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let (production, person) = require_member(&slug, &user.id).await?;
+    let location = RecordId::new("location", location_id.as_str());
+    if !HouseRulesModel::is_booked(&production.id, &location).await? {
+        return Err(Error::NotFound);
+    }
+    HouseRulesModel::acknowledge(&location, &production.id, &person).await?;
+    info!(
+        "{} acknowledged the house rules at {} for {}",
+        user.username,
+        location.display(),
+        slug
+    );
+    Ok(Redirect::to(&format!(
+        "/productions/{}/house-rules#location-{}",
+        slug, location_id
+    ))
+    .into_response())
+}
@@ -1,8 +1,13 @@
 use crate::error::Error;
 use crate::middleware::{AuthenticatedUser, UserExtractor};
 use crate::models::job::{
-    CreateJobData, CreateJobRoleData, JobModel, UpdateJobData,
+    CreateJobData, CreateJobForm, CreateJobRoleData, JobModel, UpdateJobData,
 };
+use crate::models::person::SessionUser;
+use crate::models::match_suggestion::MatchSuggestionModel;
+use crate::models::representation::RepresentationModel;
+use crate::models::saved_search::SavedSearchModel;
+use crate::models::talent_submission::TalentSubmissionModel;
 use crate::templates::{
     BaseContext, JobCreateTemplate, JobDetailView, JobEditTemplate, JobListView,
     JobOrgOption, JobRoleEditData, JobTemplate, JobsTemplate,
@@ -18,9 +23,14 @@ use axum::{
 };
 use axum_extra::extract::Form;
 use serde::Deserialize;
-use tracing::{debug, error, info};
+use surrealdb::types::RecordId;
+use tracing::{debug, error, info, warn};
 use crate::services::embedding::generate_embedding_async;
 use crate::services::search_log::log_search;
+use crate::services::triggers;
+use crate::record_id_ext::RecordIdExt;
+use crate::response;
+use crate::validation::{FieldErrors, Validate};
 
 const JOBS_PAGE_SIZE: usize = 20;
 
@@ -33,8 +43,13 @@ pub fn router() -> Router {
         .route("/jobs/{id}/edit", get(edit_job_form).post(update_job))
         .route("/jobs/{id}/delete", post(delete_job))
         .route("/jobs/{id}/close", post(close_job))
+        .route(
+            "/jobs/{id}/suggestions/{suggestion_id}/feedback",
+            post(suggestion_feedback),
+        )
         .route("/jobs/{id}/roles/{role_index}/apply", post(apply_to_role))
         .route("/jobs/{id}/roles/{role_index}/withdraw", post(withdraw_from_role))
+        .route("/jobs/{id}/roles/{role_index}/submit", post(submit_client))
         .route(
             "/jobs/{id}/applications/{app_id}/status",
             post(update_app_status),
@@ -151,6 +166,19 @@ async fn view_job(
         applications: detail.applications,
     };
 
+    // Agents can submit clients on their agency's roster to open roles
+    let clients = match current_user_id.as_deref().map(RecordId::parse_simple) {
+        Some(Ok(agent)) if job.status == "open" && !job.is_expired => {
+            RepresentationModel::agent_roster(&agent)
+                .await
+                .unwrap_or_else(|e| {
+                    warn!("Failed to load clients for {}: {}", agent.display(), e);
+                    Vec::new()
+                })
+        }
+        _ => Vec::new(),
+    };
+
     let template = JobTemplate {
         app_name: base.app_name,
         year: base.year,
@@ -158,6 +186,7 @@ async fn view_job(
         active_page: base.active_page,
         user: base.user,
         job,
+        clients,
     };
 
     Ok(Html(template.render().map_err(|e| {
@@ -169,8 +198,18 @@ async fn view_job(
 /// Show create job form
 async fn new_job_form(request: Request) -> Result<Html<String>, Error> {
     let user = request.get_user().ok_or(Error::Unauthorized)?;
+    let html = render_job_create(&user, CreateJobForm::default(), FieldErrors::new()).await?;
+    Ok(Html(html))
+}
+
+/// The "Post a job" form, filled in with `form` and showing `errors`
+async fn render_job_create(
+    user: &SessionUser,
+    form: CreateJobForm,
+    errors: FieldErrors,
+) -> Result<String, Error> {
     let mut base = BaseContext::new().with_page("jobs");
-    base = base.with_user(User::from_session_user(&user).await);
+    base = base.with_user(User::from_session_user(user).await);
 
     let pay_rate_types = JobModel::get_pay_rate_types().await.unwrap_or_default();
     let orgs = JobModel::get_user_orgs_for_posting(&user.id).await.unwrap_or_default();
@@ -180,6 +219,28 @@ async fn new_job_form(request: Request) -> Result<Html<String>, Error> {
         .map(|(id, name)| JobOrgOption { id, name })
         .collect();
 
+    // Start with one blank role; keep what was entered when re-rendering
+    let mut roles: Vec<JobRoleEditData> = form
+        .roles()
+        .into_iter()
+        .map(|r| JobRoleEditData {
+            title: r.title,
+            description: r.description,
+            rate_type: r.rate_type,
+            rate_amount: r.rate_amount,
+            location_override: r.location_override,
+        })
+        .collect();
+    if roles.is_empty() {
+        roles.push(JobRoleEditData {
+            title: String::new(),
+            description: None,
+            rate_type: "TBD".to_string(),
+            rate_amount: None,
+            location_override: None,
+        });
+    }
+
     let template = JobCreateTemplate {
         app_name: base.app_name,
         year: base.year,
@@ -188,38 +249,15 @@ async fn new_job_form(request: Request) -> Result<Html<String>, Error> {
         user: base.user,
         pay_rate_types,
         user_organizations,
-        errors: None,
+        form,
+        roles,
+        errors,
     };
 
-    Ok(Html(template.render().map_err(|e| {
+    template.render().map_err(|e| {
         error!("Failed to render job create template: {}", e);
         Error::template(e.to_string())
-    })?))
-}
-
-#[derive(Debug, Deserialize)]
-struct CreateJobForm {
-    title: String,
-    description: String,
-    location: Option<String>,
-    post_as: Option<String>,
-    related_production: Option<String>,
-    contact_name: Option<String>,
-    contact_email: Option<String>,
-    contact_phone: Option<String>,
-    contact_website: Option<String>,
-    applications_enabled: Option<String>,
-    expires_in: String,
-    #[serde(default, rename = "role_title[]")]
-    role_title: Vec<String>,
-    #[serde(default, rename = "role_description[]")]
-    role_description: Vec<String>,
-    #[serde(default, rename = "role_rate_type[]")]
-    role_rate_type: Vec<String>,
-    #[serde(default, rename = "role_rate_amount[]")]
-    role_rate_amount: Vec<String>,
-    #[serde(default, rename = "role_location[]")]
-    role_location: Vec<String>,
+    })
 }
 
 /// Create a new job posting
@@ -229,11 +267,10 @@ async fn create_job(
 ) -> Result<Response, Error> {
     debug!("Creating job: {}", data.title);
 
-    if data.title.is_empty() {
-        return Err(Error::Validation("Title is required".to_string()));
-    }
-    if data.description.is_empty() {
-        return Err(Error::Validation("Description is required".to_string()));
+    let errors = data.validate();
+    if !errors.is_empty() {
+        let html = render_job_create(&user, data, errors).await?;
+        return Ok(response::invalid_form(html));
     }
 
     // Determine poster
@@ -253,25 +290,7 @@ async fn create_job(
         user.id.clone()
     };
 
-    // Build roles from parallel arrays
-    let mut roles = Vec::new();
-    for i in 0..data.role_title.len() {
-        let title = data.role_title.get(i).cloned().unwrap_or_default();
-        if title.is_empty() {
-            continue;
-        }
-        roles.push(CreateJobRoleData {
-            title,
-            description: data.role_description.get(i).cloned().filter(|s| !s.is_empty()),
-            rate_type: data.role_rate_type.get(i).cloned().unwrap_or_else(|| "TBD".to_string()),
-            rate_amount: data.role_rate_amount.get(i).cloned().filter(|s| !s.is_empty()),
-            location_override: data.role_location.get(i).cloned().filter(|s| !s.is_empty()),
-        });
-    }
-
-    if roles.is_empty() {
-        return Err(Error::Validation("At least one role is required".to_string()));
-    }
+    let roles = data.roles();
 
     let job_data = CreateJobData {
         title: data.title,
@@ -287,6 +306,13 @@ async fn create_job(
     };
 
     let key = JobModel::create(job_data, roles, &poster_id).await?;
+    triggers::job_created(&key);
+    let alert_key = key.clone();
+    crate::db::spawn(async move {
+        if let Err(e) = SavedSearchModel::alert_new_job(&alert_key).await {
+            warn!("Failed to send saved search alerts for job {}: {}", alert_key, e);
+        }
+    });
 
     info!("Created job posting: {}", key);
     Ok(Redirect::to(&format!("/jobs/{}", key)).into_response())
@@ -444,6 +470,29 @@ async fn close_job(
     Ok(Redirect::to(&format!("/jobs/{}", id)).into_response())
 }
 
+#[derive(Debug, Deserialize)]
+struct SuggestionFeedbackForm {
+    feedback: String,
+}
+
+/// Rate a suggested match as a good fit or not relevant
+async fn suggestion_feedback(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((id, suggestion_id)): Path<(String, String)>,
+    Form(data): Form<SuggestionFeedbackForm>,
+) -> Result<Response, Error> {
+    if !JobModel::can_edit(&id, &user.id).await.unwrap_or(false) {
+        return Err(Error::Forbidden);
+    }
+
+    MatchSuggestionModel::set_feedback(&id, &suggestion_id, &data.feedback).await?;
+    debug!(
+        "Suggestion {} on job {} rated {}",
+        suggestion_id, id, data.feedback
+    );
+    Ok(Redirect::to("/my-jobs#suggestions").into_response())
+}
+
 #[derive(Debug, Deserialize)]
 struct ApplyForm {
     cover_letter: Option<String>,
@@ -467,11 +516,36 @@ async fn apply_to_role(
         data.cover_letter.filter(|s| !s.is_empty()),
     )
     .await?;
+    triggers::application_created(&user.id, &full_job_id, &role.title);
 
     info!("User {} applied to job {} role '{}'", user.id, id, role.title);
     Ok(Redirect::to(&format!("/jobs/{}", id)).into_response())
 }
 
+#[derive(Debug, Deserialize)]
+struct SubmitClientForm {
+    talent: String,
+    note: Option<String>,
+}
+
+/// An agent submits a client for a role; it goes to the client for approval
+async fn submit_client(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((id, role_index)): Path<(String, usize)>,
+    Form(data): Form<SubmitClientForm>,
+) -> Result<Response, Error> {
+    let detail = JobModel::get(&id, Some(&user.id)).await?;
+    let role = detail.roles.get(role_index)
+        .ok_or_else(|| Error::BadRequest("Invalid role index".to_string()))?;
+    let agent = RecordId::parse_simple(&user.id).map_err(|_| Error::Unauthorized)?;
+
+    TalentSubmissionModel::submit(&agent, &data.talent, &id, &role.title, data.note.as_deref())
+        .await?;
+
+    info!("User {} submitted {} to job {} role '{}'", user.id, data.talent, id, role.title);
+    Ok(Redirect::to(&format!("/jobs/{}", id)).into_response())
+}
+
 /// Withdraw application from a specific role
 async fn withdraw_from_role(
     AuthenticatedUser(user): AuthenticatedUser,
@@ -555,6 +629,21 @@ async fn my_jobs(request: Request) -> Result<Html<String>, Error> {
         })
         .collect();
 
+    let open_postings: Vec<RecordId> = postings
+        .iter()
+        .filter(|j| j.status == "open")
+        .map(|j| RecordId::new("job_posting", j.id.as_str()))
+        .collect();
+    let suggestions = MatchSuggestionModel::pending_for_jobs(&open_postings)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(
+                "Failed to load match suggestions for {}: {}",
+                user.username, e
+            );
+            Vec::new()
+        });
+
     let template = MyJobsTemplate {
         app_name: base.app_name,
         year: base.year,
@@ -563,6 +652,7 @@ async fn my_jobs(request: Request) -> Result<Html<String>, Error> {
         user: base.user,
         postings,
         applications,
+        suggestions,
     };
 
     Ok(Html(template.render().map_err(|e| {
@@ -0,0 +1,114 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::Query,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::error;
+
+use crate::{
+    config::legal_versions,
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::person::SessionUser,
+    response,
+    services::{consent, login_security::ClientInfo},
+    templates::{BaseContext, LegalAcceptTemplate, PendingDocument, User},
+};
+
+pub fn router() -> Router {
+    Router::new().route("/legal/accept", get(accept_page).post(accept))
+}
+
+#[derive(Debug, Deserialize)]
+struct AcceptQuery {
+    next: Option<String>,
+}
+
+/// Only same-site paths, never back to the acceptance page itself
+fn safe_next(next: Option<String>) -> String {
+    response::local_path(next)
+        .filter(|n| !n.starts_with("/legal/"))
+        .unwrap_or_else(|| "/".to_string())
+}
+
+fn person_id(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+async fn render(
+    user: &SessionUser,
+    documents: &[&str],
+    next: String,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let base = BaseContext::new()
+        .with_page("legal-accept")
+        .with_user(User::from_session_user(user).await);
+
+    let versions = legal_versions();
+    let mut template = LegalAcceptTemplate::new(base);
+    template.documents = documents
+        .iter()
+        .map(|doc| PendingDocument {
+            title: consent::document_title(doc).to_string(),
+            url: format!("/{}", doc),
+            version: versions.version_of(doc).unwrap_or_default().to_string(),
+        })
+        .collect();
+    template.next = next;
+    template.error = error;
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render terms acceptance template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn accept_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(query): Query<AcceptQuery>,
+) -> Result<Response, Error> {
+    let next = safe_next(query.next);
+    let outstanding = consent::outstanding(&person_id(&user)?).await;
+    if outstanding.is_empty() {
+        return Ok(response::redirect(&next));
+    }
+    render(&user, &outstanding, next, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct AcceptForm {
+    next: Option<String>,
+    agree: Option<String>,
+}
+
+async fn accept(
+    AuthenticatedUser(user): AuthenticatedUser,
+    client: ClientInfo,
+    Form(form): Form<AcceptForm>,
+) -> Result<Response, Error> {
+    let next = safe_next(form.next);
+    let person = person_id(&user)?;
+    let outstanding = consent::outstanding(&person).await;
+    if outstanding.is_empty() {
+        return Ok(response::redirect(&next));
+    }
+
+    if form.agree.is_none() {
+        return render(
+            &user,
+            &outstanding,
+            next,
+            Some("Please tick the box to accept the updated policies.".to_string()),
+        )
+        .await;
+    }
+
+    consent::accept(&person, &outstanding, "interstitial", Some(&client)).await?;
+    Ok(response::redirect(&next))
+}
@@ -0,0 +1,276 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::Path,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        deliverable::DeliverableModel,
+        legal_hold::{self, HoldTarget, LegalHold, LegalHoldEvent, LegalHoldModel},
+        person::SessionUser,
+        production::{Production, ProductionModel},
+        script::ScriptModel,
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/productions/{slug}/legal-holds",
+            get(legal_holds_page).post(place_hold),
+        )
+        .route(
+            "/productions/{slug}/legal-holds/{hold_id}/lift",
+            post(lift_hold),
+        )
+}
+
+const DATE_FORMAT: &str = "%b %-d, %Y %H:%M";
+
+// ============================
+// Views
+// ============================
+
+pub struct HoldView {
+    pub id: String,
+    pub target: String,
+    pub reason: String,
+    pub placed: String,
+    /// When and by whom, once lifted
+    pub lifted: Option<String>,
+}
+
+impl From<LegalHold> for HoldView {
+    fn from(hold: LegalHold) -> Self {
+        let lifted = hold.lifted_at.map(|at| {
+            format!(
+                "{} by {}",
+                at.format(DATE_FORMAT),
+                hold.lifted_by_name.as_deref().unwrap_or("a former member")
+            )
+        });
+        Self {
+            id: hold.id.key_string(),
+            target: hold.target_label(),
+            placed: format!(
+                "{} by {}",
+                hold.placed_at.format(DATE_FORMAT),
+                hold.placed_by_name.as_deref().unwrap_or("a former member")
+            ),
+            lifted,
+            reason: hold.reason,
+        }
+    }
+}
+
+pub struct EventView {
+    pub action: &'static str,
+    pub reason: String,
+    pub detail: String,
+    pub actor: String,
+    pub at: String,
+}
+
+impl From<LegalHoldEvent> for EventView {
+    fn from(event: LegalHoldEvent) -> Self {
+        Self {
+            action: match event.action.as_str() {
+                "placed" => "Hold placed",
+                "lifted" => "Hold lifted",
+                _ => "Deletion refused",
+            },
+            reason: event.reason.unwrap_or_default(),
+            detail: event.detail.unwrap_or_default(),
+            actor: event
+                .actor_name
+                .unwrap_or_else(|| "Scheduled task".to_string()),
+            at: event.created_at.format(DATE_FORMAT).to_string(),
+        }
+    }
+}
+
+#[derive(Template)]
+#[template(path = "productions/legal_holds.html")]
+pub struct LegalHoldsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub holds: Vec<HoldView>,
+    pub events: Vec<EventView>,
+    /// The production, then each of its scripts and deliverables
+    pub targets: Vec<SelectOption>,
+    pub max_reason: usize,
+    pub error: Option<String>,
+}
+
+// ============================
+// Handlers
+// ============================
+
+/// Holds are placed and lifted by the production's owners and admins,
+/// including those of an organization that owns it
+async fn require_editor(slug: &str, user_id: &str) -> Result<(Production, RecordId), Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    if !ProductionModel::can_edit(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    let person = RecordId::parse_simple(user_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    Ok((production, person))
+}
+
+async fn render_holds(
+    user: &SessionUser,
+    production: Production,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let holds = LegalHoldModel::for_production(&production.id)
+        .await?
+        .into_iter()
+        .map(HoldView::from)
+        .collect();
+    let events = LegalHoldModel::history(&production.id)
+        .await?
+        .into_iter()
+        .map(EventView::from)
+        .collect();
+
+    let mut targets = vec![SelectOption::new(
+        "production",
+        "Whole production".to_string(),
+        false,
+    )];
+    targets.extend(
+        ScriptModel::for_production(&production.id)
+            .await?
+            .into_iter()
+            .map(|script| {
+                SelectOption::new(
+                    format!("script:{}", script.id.key_string()),
+                    format!("Script: {} (v{})", script.title, script.version),
+                    false,
+                )
+            }),
+    );
+    targets.extend(
+        DeliverableModel::for_production(&production.id)
+            .await?
+            .into_iter()
+            .map(|item| {
+                SelectOption::new(
+                    format!("deliverable:{}", item.id.key_string()),
+                    format!("Deliverable: {}", item.title),
+                    false,
+                )
+            }),
+    );
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+    let template = LegalHoldsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        holds,
+        events,
+        targets,
+        max_reason: legal_hold::MAX_REASON_CHARS,
+        error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render legal holds template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn legal_holds_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let (production, _) = require_editor(&slug, &user.id).await?;
+    render_holds(&user, production, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct HoldForm {
+    #[serde(default)]
+    target: String,
+    #[serde(default)]
+    reason: String,
+}
+
+async fn place_hold(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<HoldForm>,
+) -> Result<Response, Error> {
+    let (production, person) = require_editor(&slug, &user.id).await?;
+    let parsed = HoldTarget::parse(&form.target)
+        .and_then(|target| legal_hold::parse_reason(&form.reason).map(|reason| (target, reason)));
+    let (target, reason) = match parsed {
+        Ok(parsed) => parsed,
+        Err(Error::Validation(msg)) => return render_holds(&user, production, Some(msg)).await,
+        Err(e) => return Err(e),
+    };
+
+    // Documents have to belong to this production
+    match &target {
+        HoldTarget::Production => {}
+        HoldTarget::Script(key) => {
+            let script =
+                ScriptModel::get(&RecordId::new("production_script", key.as_str())).await?;
+            if !script.is_some_and(|script| script.production == production.id) {
+                return Err(Error::NotFound);
+            }
+        }
+        HoldTarget::Deliverable(key) => {
+            DeliverableModel::get(&production.id, key).await?;
+        }
+    }
+
+    let target_id = target.record_id(&production.id);
+    match LegalHoldModel::place(&production.id, &target_id, &reason, &person).await {
+        Ok(()) => {}
+        Err(Error::Conflict(msg)) => return render_holds(&user, production, Some(msg)).await,
+        Err(e) => return Err(e),
+    }
+    info!(
+        "{} placed a legal hold on {} in {}",
+        user.username,
+        target_id.display(),
+        slug
+    );
+    Ok(Redirect::to(&format!("/productions/{}/legal-holds", slug)).into_response())
+}
+
+async fn lift_hold(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, hold_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let (production, person) = require_editor(&slug, &user.id).await?;
+    LegalHoldModel::lift(&production.id, &hold_id, &person).await?;
+    info!(
+        "{} lifted legal hold {} on {}",
+        user.username, hold_id, slug
+    );
+    Ok(Redirect::to(&format!("/productions/{}/legal-holds", slug)).into_response())
+}
@@ -1,10 +1,17 @@
 use crate::error::Error;
 use crate::middleware::{AuthenticatedUser, UserExtractor};
+use crate::models::blackout::{self, BlackoutModel};
+use crate::models::house_rules::{self, HouseRulesModel};
 use crate::models::likes::LikesModel;
+use crate::models::nearby_services::{self, NearbyService, NearbyServicesModel};
+use crate::models::scouting::ScoutingModel;
 use crate::models::location::{
-    CreateLocationData, CreateRateData, LocationModel, LocationRate, UpdateLocationData,
+    CreateLocationData, CreateLocationForm, CreateRateData, LocationModel, LocationRate,
+    UpdateLocationData,
 };
+use crate::models::person::SessionUser;
 use crate::record_id_ext::RecordIdExt;
+use crate::response;
 use crate::serde_utils::deserialize_optional_i32;
 use crate::templates::{
     BaseContext, LocationCreateTemplate, LocationEditTemplate, LocationTemplate, LocationsTemplate,
@@ -23,6 +30,7 @@ use surrealdb::types::RecordId;
 use tracing::{debug, error, info};
 use crate::services::embedding::generate_embedding_async;
 use crate::services::search_log::log_search;
+use crate::validation::{FieldErrors, Validate};
 
 const PAGE_SIZE: usize = 20;
 
@@ -43,7 +51,10 @@ pub fn router() -> Router {
         .route("/locations/{id}/rates", get(get_rates))
         .route("/locations/{id}/rates/add", post(add_rate))
         .route("/locations/{id}/rates/{rate_id}/delete", post(delete_rate))
+        .route("/locations/{id}/blackouts", post(add_blackout))
+        .route("/locations/{id}/blackouts/{blackout_id}/delete", post(delete_blackout))
         .route("/api/locations/more-sse", get(locations_more_sse))
+        .route("/api/locations/{id}/nearby", get(nearby_services_api))
 }
 
 /// Query parameters for filtering locations
@@ -187,8 +198,10 @@ async fn view_location(Path(id): Path<String>, request: Request) -> Result<Html<
     // Add user to context if authenticated
     let mut can_edit = false;
     let mut is_liked = false;
-    if let Some(user) = request.get_user() {
-        base = base.with_user(User::from_session_user(&user).await);
+    let mut scouting_collections = Vec::new();
+    let viewer = request.get_user();
+    if let Some(user) = viewer.as_ref() {
+        base = base.with_user(User::from_session_user(user).await);
 
         // Check if user can edit this location
         can_edit = LocationModel::can_edit(&location.id, &user.id)
@@ -203,6 +216,16 @@ async fn view_location(Path(id): Path<String>, request: Request) -> Result<Html<
         };
         if let Some(rid) = person_rid {
             is_liked = LikesModel::is_liked(&rid, &location.id).await.unwrap_or(false);
+            scouting_collections = ScoutingModel::choices_for(&rid)
+                .await
+                .unwrap_or_default()
+                .into_iter()
+                .map(|c| crate::templates::SelectOption {
+                    value: c.id.key_string(),
+                    label: format!("{} ({})", c.name, c.production_title),
+                    selected: false,
+                })
+                .collect();
         }
     }
 
@@ -211,6 +234,40 @@ async fn view_location(Path(id): Path<String>, request: Request) -> Result<Html<
         .await
         .unwrap_or_default();
 
+    // Blackouts that haven't ended, for productions planning shoot days
+    let today = chrono::Utc::now().date_naive();
+    let blackouts = BlackoutModel::for_location(&location.id)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Failed to load blackouts for {}: {}", location.id.display(), e);
+            Vec::new()
+        })
+        .into_iter()
+        .filter(|b| !b.is_over(today))
+        .map(|b| crate::templates::BlackoutView {
+            id: b.id.key_string(),
+            when: b.when(),
+            label: b.label,
+        })
+        .collect();
+
+    let nearby = match location.latitude.zip(location.longitude) {
+        Some(point) => {
+            let units = crate::units::viewer_preference(viewer.as_ref().map(|u| u.id.as_str()))
+                .await
+                .unwrap_or_default();
+            let services =
+                NearbyServicesModel::near(point, nearby_services::DEFAULT_RADIUS_KM, &[])
+                    .await
+                    .unwrap_or_else(|e| {
+                        error!("Failed to look up services near {}: {}", location.id.display(), e);
+                        Vec::new()
+                    });
+            nearby_service_groups(services, units)
+        }
+        None => Vec::new(),
+    };
+
     let template = LocationTemplate {
         app_name: base.app_name,
         year: base.year,
@@ -234,6 +291,7 @@ async fn view_location(Path(id): Path<String>, request: Request) -> Result<Html<
             restrictions: location.restrictions,
             parking_info: location.parking_info,
             max_capacity: location.max_capacity,
+            house_rules: location.house_rules.unwrap_or_default(),
             profile_photo: location.profile_photo,
             photos: location.photos.into_iter().map(|p| crate::templates::LocationPhoto {
                 url: p.url,
@@ -257,9 +315,12 @@ async fn view_location(Path(id): Path<String>, request: Request) -> Result<Html<
                     description: r.description,
                 })
                 .collect(),
+            blackouts,
             can_edit,
         },
         is_liked,
+        scouting_collections,
+        nearby_services: nearby,
     };
 
     let html = template.render().map_err(|e| {
@@ -270,6 +331,34 @@ async fn view_location(Path(id): Path<String>, request: Request) -> Result<Html<
     Ok(Html(html))
 }
 
+/// Group nearby services under their category headings, in the order of
+/// `SERVICE_CATEGORIES`, nearest first within each
+fn nearby_service_groups(
+    services: Vec<NearbyService>,
+    units: crate::units::UnitSystem,
+) -> Vec<crate::templates::NearbyServiceGroup> {
+    nearby_services::SERVICE_CATEGORIES
+        .iter()
+        .filter_map(|category| {
+            let services: Vec<_> = services
+                .iter()
+                .filter(|s| s.org_type == category.org_type)
+                .map(|s| crate::templates::NearbyServiceView {
+                    name: s.name.clone(),
+                    slug: s.slug.clone(),
+                    logo: s.logo.clone(),
+                    verified: s.verified,
+                    distance: crate::units::distance(s.distance_km, units),
+                })
+                .collect();
+            (!services.is_empty()).then(|| crate::templates::NearbyServiceGroup {
+                label: category.label.to_string(),
+                services,
+            })
+        })
+        .collect()
+}
+
 /// Show form to create a new location
 #[axum::debug_handler]
 async fn new_location_form(
@@ -277,8 +366,19 @@ async fn new_location_form(
 ) -> Result<Html<String>, Error> {
     debug!("Showing new location form");
 
+    let html =
+        render_location_create(&user, CreateLocationForm::default(), FieldErrors::new()).await?;
+    Ok(Html(html))
+}
+
+/// The "Add location" form, filled in with `form` and showing `errors`
+async fn render_location_create(
+    user: &SessionUser,
+    form: CreateLocationForm,
+    errors: FieldErrors,
+) -> Result<String, Error> {
     let mut base = BaseContext::new().with_page("locations");
-    base = base.with_user(User::from_session_user(&user).await);
+    base = base.with_user(User::from_session_user(user).await);
 
     let template = LocationCreateTemplate {
         app_name: base.app_name,
@@ -286,15 +386,14 @@ async fn new_location_form(
         version: base.version,
         active_page: base.active_page,
         user: base.user,
-        errors: None,
+        form,
+        errors,
     };
 
-    let html = template.render().map_err(|e| {
+    template.render().map_err(|e| {
         error!("Failed to render location create template: {}", e);
         Error::template(e.to_string())
-    })?;
-
-    Ok(Html(html))
+    })
 }
 
 /// Create a new location
@@ -305,18 +404,10 @@ async fn create_location(
 ) -> Result<Response, Error> {
     debug!("Creating new location: {}", data.name);
 
-    // Validate required fields
-    if data.name.is_empty() {
-        return Err(Error::Validation("Name is required".to_string()));
-    }
-    if data.address.is_empty() {
-        return Err(Error::Validation("Address is required".to_string()));
-    }
-    if data.contact_name.is_empty() {
-        return Err(Error::Validation("Contact name is required".to_string()));
-    }
-    if data.contact_email.is_empty() {
-        return Err(Error::Validation("Contact email is required".to_string()));
+    let errors = data.validate();
+    if !errors.is_empty() {
+        let html = render_location_create(&user, data, errors).await?;
+        return Ok(response::invalid_form(html));
     }
 
     // Create location data
@@ -393,6 +484,12 @@ async fn edit_location_form(
             restrictions: location.restrictions.map(|r| r.join(", ")),
             parking_info: location.parking_info,
             max_capacity: location.max_capacity,
+            coordinates: location
+                .latitude
+                .zip(location.longitude)
+                .map(crate::units::format_coordinates)
+                .unwrap_or_default(),
+            house_rules: location.house_rules.unwrap_or_default().join("\n"),
             profile_photo: location.profile_photo,
             photos: location.photos.into_iter().map(|p| crate::templates::LocationPhoto {
                 url: p.url,
@@ -428,6 +525,24 @@ async fn update_location(
         return Err(Error::Forbidden);
     }
 
+    // Coordinates are optional; an empty field clears them
+    let coordinates = match data.coordinates.as_deref().map(str::trim) {
+        None => None,
+        Some("") => Some(None),
+        Some(text) => Some(Some(crate::units::parse_coordinates(text).ok_or_else(|| {
+            Error::Validation(
+                "Enter coordinates as latitude, longitude (for example 34.0522, -118.2437)"
+                    .to_string(),
+            )
+        })?)),
+    };
+
+    let house_rules = data
+        .house_rules
+        .as_deref()
+        .map(house_rules::parse_rules)
+        .transpose()?;
+
     // Create update data
     let update_data = UpdateLocationData {
         name: data.name.filter(|s| !s.is_empty()),
@@ -453,6 +568,12 @@ async fn update_location(
 
     // Update the location
     let updated = LocationModel::update(&location.id, update_data).await?;
+    if let Some(coordinates) = coordinates {
+        LocationModel::set_coordinates(&location.id, coordinates).await?;
+    }
+    if let Some(rules) = house_rules {
+        HouseRulesModel::set_rules(&location.id, rules).await?;
+    }
 
     info!("Updated location: {} ({})", updated.name, updated.id.display());
 
@@ -496,6 +617,62 @@ async fn get_rates(Path(id): Path<String>) -> Result<Json<Vec<LocationRate>>, Er
     Ok(Json(rates))
 }
 
+#[derive(Debug, Deserialize)]
+struct NearbyQuery {
+    radius_km: Option<f64>,
+    /// Comma-separated category keys ("rental,catering"); all when unset
+    category: Option<String>,
+}
+
+/// Service organizations near a location (JSON API)
+async fn nearby_services_api(
+    Path(id): Path<String>,
+    Query(query): Query<NearbyQuery>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let location_id = RecordId::new("location", id.as_str());
+    let location = LocationModel::get(&location_id).await?;
+    let Some(point) = location.latitude.zip(location.longitude) else {
+        return Err(Error::BadRequest(
+            "This location has no coordinates".to_string(),
+        ));
+    };
+
+    let mut categories = Vec::new();
+    for value in query.category.as_deref().unwrap_or("").split(',') {
+        if value.trim().is_empty() {
+            continue;
+        }
+        let category = nearby_services::category(value)
+            .ok_or_else(|| Error::BadRequest(format!("Unknown service category '{}'", value.trim())))?;
+        categories.push(category);
+    }
+    let radius_km = nearby_services::clamp_radius(query.radius_km);
+    let services = NearbyServicesModel::near(point, radius_km, &categories).await?;
+
+    Ok(Json(serde_json::json!({
+        "location": location.id.to_raw_string(),
+        "radius_km": radius_km,
+        "services": services
+            .iter()
+            .map(|s| serde_json::json!({
+                "id": s.id.to_raw_string(),
+                "name": s.name,
+                "slug": s.slug,
+                "type": s.org_type,
+                "category": nearby_services::category(&s.org_type).map(|c| c.key),
+                "location": s.location,
+                "phone": s.phone,
+                "website": s.website,
+                "services": s.services,
+                "verified": s.verified,
+                "latitude": s.latitude,
+                "longitude": s.longitude,
+                "distance_km": (s.distance_km * 10.0).round() / 10.0,
+            }))
+            .collect::<Vec<_>>(),
+    })))
+}
+
 /// Add a rate to a location
 #[axum::debug_handler]
 async fn add_rate(
@@ -557,6 +734,72 @@ async fn delete_rate(
     Ok(Redirect::to(&format!("/locations/{}", location.id.key_string())).into_response())
 }
 
+#[derive(Debug, Deserialize)]
+struct BlackoutForm {
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    start_date: String,
+    #[serde(default)]
+    end_date: String,
+    #[serde(default)]
+    start_time: String,
+    #[serde(default)]
+    end_time: String,
+}
+
+/// Add a blackout (quiet hours, an outside booking) to a location
+async fn add_blackout(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    Form(data): Form<BlackoutForm>,
+) -> Result<Response, Error> {
+    let location_id = RecordId::new("location", id.as_str());
+    let location = LocationModel::get(&location_id).await?;
+
+    // Check if user can edit
+    if !LocationModel::can_edit(&location.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+
+    let blackout = blackout::clean_blackout(
+        &data.label,
+        &data.start_date,
+        &data.end_date,
+        &data.start_time,
+        &data.end_time,
+    )?;
+    let blackout = BlackoutModel::create(&location.id, blackout).await?;
+
+    info!(
+        "Added blackout '{}' ({}) to location: {}",
+        blackout.label,
+        blackout.when(),
+        location.id.display()
+    );
+
+    Ok(Redirect::to(&format!("/locations/{}#loc-blackouts", location.id.key_string())).into_response())
+}
+
+/// Delete a blackout from a location
+async fn delete_blackout(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((id, blackout_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let location_id = RecordId::new("location", id.as_str());
+    let location = LocationModel::get(&location_id).await?;
+
+    // Check if user can edit
+    if !LocationModel::can_edit(&location.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+
+    BlackoutModel::delete(&location.id, &blackout_id).await?;
+    info!("Deleted blackout {} from location: {}", blackout_id, location.id.display());
+
+    Ok(Redirect::to(&format!("/locations/{}#loc-blackouts", location.id.key_string())).into_response())
+}
+
 // SSE infinite scroll
 
 #[derive(Debug, Deserialize)]
@@ -677,26 +920,6 @@ async fn locations_more_sse(Query(params): Query<MoreQuery>) -> Response {
 
 // Form structures
 
-#[derive(Debug, Deserialize)]
-struct CreateLocationForm {
-    name: String,
-    address: String,
-    city: String,
-    state: String,
-    country: String,
-    postal_code: Option<String>,
-    description: Option<String>,
-    contact_name: String,
-    contact_email: String,
-    contact_phone: Option<String>,
-    is_public: Option<bool>,
-    amenities: Option<String>,
-    restrictions: Option<String>,
-    parking_info: Option<String>,
-    #[serde(deserialize_with = "deserialize_optional_i32")]
-    max_capacity: Option<i32>,
-}
-
 #[derive(Debug, Deserialize)]
 struct UpdateLocationForm {
     name: Option<String>,
@@ -715,6 +938,8 @@ struct UpdateLocationForm {
     parking_info: Option<String>,
     #[serde(deserialize_with = "deserialize_optional_i32")]
     max_capacity: Option<i32>,
+    coordinates: Option<String>,
+    house_rules: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
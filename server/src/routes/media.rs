@@ -1422,6 +1422,11 @@ async fn delete_production_photo(
 async fn proxy_media(Path(path): Path<String>) -> Result<impl IntoResponse, Error> {
     debug!("Proxying media file: {}", path);
 
+    // ID documents and other private uploads are never served publicly
+    if crate::services::id_verification::is_private_key(&path) {
+        return Err(Error::NotFound);
+    }
+
     let s3 = s3()?;
     let (data, content_type) = s3.download_file(&path).await?;
 
@@ -1430,6 +1435,8 @@ async fn proxy_media(Path(path): Path<String>) -> Result<impl IntoResponse, Erro
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, content_type)
         .header(header::CACHE_CONTROL, "public, max-age=31536000") // Cache for 1 year
+        // Served byte-for-byte as stored, so caches keep the original file
+        .extension(crate::middleware::compression::NoCompression)
         .body(Body::from(data))
         .map_err(|e| Error::Internal(format!("Failed to build response: {}", e)))?;
 
@@ -14,12 +14,16 @@ use crate::{
     error::Error,
     middleware::AuthenticatedUser,
     models::{
-        messaging::MessagingModel,
+        block::BlockModel,
+        messaging::{Conversation, MessagingModel},
         notification::NotificationModel,
         person::Person,
+        reaction::ReactionModel,
+        representation::{self, RepresentationModel},
     },
     record_id_ext::RecordIdExt,
-    services::email::EmailService,
+    routes::reactions,
+    services::{email::EmailService, mentions, minors},
     templates::{BaseContext, User},
 };
 
@@ -42,9 +46,12 @@ struct ConversationView {
 }
 
 struct MessageView {
+    /// Sanitized HTML, with @mentions linked
     body: String,
     is_own: bool,
     created_at: String,
+    /// Rendered reaction bar
+    reactions: String,
 }
 
 // -- Templates --
@@ -168,6 +175,9 @@ async fn inbox(
                 verification_status: "unverified".to_string(),
                 profile: None,
                 messaging_preference: "nobody".to_string(),
+                units: None,
+                is_minor: false,
+                visibility: "hidden".to_string(),
             }
         });
 
@@ -243,14 +253,24 @@ async fn view_conversation(
         .last()
         .map(|m| m.created_at.to_rfc3339())
         .unwrap_or_else(|| Utc::now().to_rfc3339());
+    let ids: Vec<_> = raw_messages.iter().map(|m| m.id.clone()).collect();
+    let mut counts = ReactionModel::counts(&ids, &user.id).await?;
+    let next = format!("/messages/{}", conversation_id);
     let messages: Vec<MessageView> = raw_messages
         .into_iter()
         .map(|m| {
             let is_own = m.sender.to_raw_string() == user.id;
+            let counts = counts.remove(&m.id.to_raw_string()).unwrap_or_default();
             MessageView {
-                body: m.body,
+                body: mentions::link_html(&ammonia::clean(&m.body)),
                 is_own,
                 created_at: m.created_at.format("%b %d, %H:%M").to_string(),
+                reactions: reactions::render_bar(
+                    "direct_message",
+                    m.id.key_string(),
+                    counts,
+                    &next,
+                ),
             }
         })
         .collect();
@@ -409,6 +429,8 @@ async fn reply_message(
         .find(|c| c.id.to_raw_string() == conversation_id)
         .ok_or(Error::NotFound)?;
 
+    ensure_not_blocked(conv, &user.id).await?;
+
     let sanitized_body = ammonia::clean(body);
     model
         .send_message(&conversation_id, &user.id, &sanitized_body)
@@ -461,8 +483,10 @@ async fn reply_message_sse(
         .find(|c| c.id.to_raw_string() == conversation_id)
         .ok_or(Error::NotFound)?;
 
+    ensure_not_blocked(conv, &user.id).await?;
+
     let sanitized_body = ammonia::clean(body);
-    model
+    let message = model
         .send_message(&conversation_id, &user.id, &sanitized_body)
         .await?;
 
@@ -482,9 +506,17 @@ async fn reply_message_sse(
     // Build SSE response
     let now = Utc::now();
     let time_str = now.format("%b %d, %H:%M").to_string();
+    let bar = reactions::render_bar(
+        "direct_message",
+        message.id.key_string(),
+        Vec::new(),
+        &format!("/messages/{}", conversation_id),
+    );
     let fragment = format!(
-        r#"<div class="msg" data-own="true"><div class="msg-body">{}</div><div class="msg-time">{}</div></div>"#,
-        sanitized_body, time_str
+        r#"<div class="msg" data-own="true"><div class="msg-body">{}</div><div class="msg-time">{}</div>{}</div>"#,
+        mentions::link_html(&sanitized_body),
+        time_str,
+        bar
     );
 
     let mut sse = String::new();
@@ -550,12 +582,16 @@ async fn poll_new_messages(
 
     // Build HTML fragments and track latest timestamp
     let mut latest_ts = after.unwrap_or_else(Utc::now);
+    let next = format!("/messages/{}", conversation_id);
     let mut html = String::new();
     for m in &new_messages {
         let time_str = m.created_at.format("%b %d, %H:%M").to_string();
+        let bar = reactions::render_bar("direct_message", m.id.key_string(), Vec::new(), &next);
         html += &format!(
-            r#"<div class="msg" data-own="false"><div class="msg-body">{}</div><div class="msg-time">{}</div></div>"#,
-            m.body, time_str
+            r#"<div class="msg" data-own="false"><div class="msg-body">{}</div><div class="msg-time">{}</div>{}</div>"#,
+            mentions::link_html(&m.body),
+            time_str,
+            bar
         );
         if m.created_at > latest_ts {
             latest_ts = m.created_at;
@@ -594,13 +630,43 @@ async fn delete_conversation(
 
 // -- Helpers --
 
-/// Check if the current user can message the recipient based on their messaging_preference.
+/// Check if the current user can message the recipient based on blocks, representation and their messaging_preference.
 /// Returns None if allowed, Some(error_message) if not.
 async fn check_messaging_preference(recipient: &Person, sender_id: &str) -> Option<String> {
     if recipient.id.to_raw_string() == sender_id {
         return Some("You cannot message yourself.".to_string());
     }
 
+    if let Ok(sender) = surrealdb::types::RecordId::parse_simple(sender_id)
+        && BlockModel::is_blocked_between(&sender, &recipient.id).await.unwrap_or(true)
+    {
+        return Some(format!("You can't message {}.", recipient.get_display_name()));
+    }
+
+    // People who take inquiries through a representative can still hear from
+    // the agency that represents them
+    if let Ok(sender) = surrealdb::types::RecordId::parse_simple(sender_id)
+        && let Some(rep) = RepresentationModel::route(&recipient.id).await.ok().flatten()
+        && !RepresentationModel::represents(&sender, &recipient.id).await.unwrap_or(false)
+    {
+        return Some(representation::redirect_notice(&recipient.get_display_name(), &rep));
+    }
+
+    if recipient.is_minor {
+        let allowed = match surrealdb::types::RecordId::parse_simple(sender_id) {
+            Ok(sender) => minors::can_message(&recipient.id, &sender).await,
+            Err(_) => false,
+        };
+        return if allowed {
+            None
+        } else {
+            Some(format!(
+                "{} can only be messaged by members of verified organizations.",
+                recipient.get_display_name()
+            ))
+        };
+    }
+
     match recipient.messaging_preference.as_str() {
         "nobody" => Some(format!(
             "{} is not accepting messages.",
@@ -624,7 +690,23 @@ async fn check_messaging_preference(recipient: &Person, sender_id: &str) -> Opti
     }
 }
 
+/// Refuse replies in a conversation where either side has since blocked the other
+async fn ensure_not_blocked(conv: &Conversation, user_id: &str) -> Result<(), Error> {
+    let other_id = MessagingModel::get_other_participant(conv, user_id);
+    let (Ok(me), Ok(other)) = (
+        surrealdb::types::RecordId::parse_simple(user_id),
+        surrealdb::types::RecordId::parse_simple(&other_id),
+    ) else {
+        return Ok(());
+    };
+    if BlockModel::is_blocked_between(&me, &other).await? {
+        return Err(Error::BadRequest("You can't message this person.".to_string()));
+    }
+    Ok(())
+}
+
 /// Create a notification and send an email for a new message.
+/// Nothing is sent when the recipient has muted the sender.
 async fn send_new_message_notification(
     sender_id: &str,
     sender_username: &str,
@@ -632,6 +714,13 @@ async fn send_new_message_notification(
     conversation_id: &str,
     message_body: &str,
 ) {
+    if let Ok(sender) = surrealdb::types::RecordId::parse_simple(sender_id)
+        && BlockModel::has_muted(&recipient.id, &sender).await.unwrap_or(false)
+    {
+        debug!("{} has muted {}, skipping message notification", recipient.username, sender_username);
+        return;
+    }
+
     let sender_person = Person::find_by_id(sender_id).await.ok().flatten();
     let sender_name = sender_person
         .map(|p| p.get_display_name())
@@ -659,7 +748,7 @@ async fn send_new_message_notification(
     let sender_name_clone = sender_name.clone();
     let body_preview_long = truncate_body(message_body, 200);
     let conv_id = conversation_id.to_string();
-    tokio::spawn(async move {
+    crate::db::spawn(async move {
         if let Ok(email_service) = EmailService::from_env() {
             let base_url = crate::config::app_url();
             let message_url = format!("{}/messages/{}", base_url, conv_id);
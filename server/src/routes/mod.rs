@@ -2,31 +2,70 @@ use axum::extract::DefaultBodyLimit;
 use axum::http::{Request, Response, header, HeaderValue};
 use axum::{Router, middleware, routing::get_service};
 use std::time::Duration;
-use tower_http::{compression::CompressionLayer, services::ServeDir, set_header::SetResponseHeaderLayer, trace::TraceLayer};
+use tower_http::{services::ServeDir, set_header::SetResponseHeaderLayer, trace::TraceLayer};
 use tracing::{Span, error, info};
 
 use crate::middleware::{
-    RequestIdExt, auth_middleware, error_response_middleware, request_id_middleware,
+    RequestIdExt, auth_middleware, error_response_middleware, query_stats_middleware,
+    request_id_middleware,
 };
 
 mod account;
 mod admin;
 mod analytics;
 mod api;
+mod api_v1;
+mod audio_reels;
 mod auth;
+mod badges;
+mod budget;
+mod cast_sheet;
+mod daily_reports;
+mod deliverables;
 mod equipment;
+mod festivals;
+mod gear_quotes;
+mod guest_links;
+mod help;
+mod house_rules;
 mod jobs;
+mod legal;
+mod legal_holds;
 mod likes;
 mod locations;
 mod media;
 mod messages;
 mod notifications;
+mod oauth;
+mod offers;
+mod onboarding;
+mod org_branding;
+mod org_claims;
 mod organizations;
 mod pages;
+mod portfolio;
+mod press_kit;
+mod production_gear;
+mod production_inbox;
+mod production_search;
 mod productions;
 mod profile;
 mod public_profiles;
+mod reactions;
+mod retention;
+mod roster;
+mod saved_searches;
+mod scim;
+mod scouting;
 mod search;
+mod search_preview;
+mod self_tapes;
+mod shortlists;
+mod shot_lists;
+mod sso;
+mod suggestions;
+mod timecards;
+mod uploads;
 mod verification;
 
 pub fn app() -> Router {
@@ -39,16 +78,53 @@ pub fn app() -> Router {
     Router::new()
         // Mount the page routes at the root
         .merge(pages::router())
+        // Mount the help center
+        .merge(help::router())
         // Mount auth routes
         .merge(auth::router())
+        // Mount organization single sign-on and SCIM provisioning routes
+        .merge(sso::router())
+        .merge(scim::router())
+        // Mount OAuth authorization for third-party apps
+        .merge(oauth::router())
+        // Mount terms acceptance routes
+        .merge(legal::router())
+        // Mount the first-login questionnaire
+        .merge(onboarding::router())
         // Mount search routes
         .merge(search::router())
+        .merge(saved_searches::router())
+        .merge(search_preview::router())
         // Mount organizations routes
         .merge(organizations::router())
+        .merge(org_claims::router())
+        .merge(roster::router())
+        .merge(retention::router())
+        .merge(org_branding::router())
         // Mount productions routes
         .merge(productions::router())
+        .merge(shot_lists::router())
+        .merge(cast_sheet::router())
+        .merge(daily_reports::router())
+        .merge(timecards::router())
+        .merge(offers::router())
+        .merge(scouting::router())
+        .merge(house_rules::router())
+        .merge(production_gear::router())
+        .merge(gear_quotes::router())
+        .merge(budget::router())
+        .merge(deliverables::router())
+        .merge(production_inbox::router())
+        .merge(festivals::router())
+        .merge(press_kit::router())
+        .merge(production_search::router())
+        .merge(guest_links::router())
+        .merge(legal_holds::router())
         // Mount jobs routes
         .merge(jobs::router())
+        .merge(self_tapes::router())
+        .merge(shortlists::router())
+        .merge(uploads::router())
         // Mount likes routes
         .merge(likes::router())
         // Mount locations routes
@@ -57,20 +133,28 @@ pub fn app() -> Router {
         .merge(notifications::router())
         // Mount messages routes
         .merge(messages::router())
+        .merge(reactions::router())
         // Mount equipment routes
         .merge(equipment::router())
         // Mount analytics routes (before profile to avoid /{username} conflict)
         .merge(analytics::router())
         // Mount profile routes
         .merge(profile::router())
+        .merge(audio_reels::router())
+        .merge(portfolio::router())
+        .merge(badges::router())
         // Mount verification routes
         .merge(verification::router())
         // Mount account settings routes
         .merge(account::router())
+        // Mount suggested edits to curated lists
+        .merge(suggestions::router())
         // Mount admin routes
         .merge(admin::router())
         // Mount API routes under /api
         .nest("/api", api::router())
+        // Mount the versioned API for OAuth apps under /api/v1
+        .nest("/api/v1", api_v1::router())
         // Mount media routes under /api/media
         .nest("/api/media", media::router())
         // Mount MCP server for AI tool access
@@ -92,10 +176,23 @@ pub fn app() -> Router {
         .merge(public_profiles::router())
         // Track page view activity (runs after auth so user identity is available)
         .layer(middleware::from_fn(crate::middleware::activity::activity_middleware))
+        // Keep each visitor's recent request IDs for feedback (runs after auth so user identity is available)
+        .layer(middleware::from_fn(crate::middleware::recent_requests::recent_requests_middleware))
+        // Ask for re-acceptance of changed terms (runs after auth so user identity is available)
+        .layer(middleware::from_fn(crate::middleware::consent::consent_middleware))
+        // Swap people's details for stand-ins while an admin has demo mode on (runs after auth)
+        .layer(middleware::from_fn(crate::middleware::demo_mode::demo_mode_middleware))
+        // Audit and restrict admin impersonation sessions (runs after auth so user identity is available)
+        .layer(middleware::from_fn(crate::middleware::impersonation::impersonation_middleware))
         // Apply auth middleware to extract user from JWT cookies
         .layer(middleware::from_fn(auth_middleware))
+        // Count database queries per request (outside auth so the session lookup is included)
+        .layer(middleware::from_fn(query_stats_middleware))
         // Error response middleware - converts errors to HTML/JSON based on Accept header
         .layer(middleware::from_fn(error_response_middleware))
+        // Serve tenant hostnames from the tenant's database (outside everything
+        // that reads the database, so it all runs in the tenant's scope)
+        .layer(middleware::from_fn(crate::middleware::tenant::tenant_middleware))
         // Security headers
         .layer(SetResponseHeaderLayer::overriding(
             header::X_FRAME_OPTIONS,
@@ -114,7 +211,7 @@ pub fn app() -> Router {
             HeaderValue::from_static("1; mode=block"),
         ))
         // Middleware
-        .layer(CompressionLayer::new())
+        .layer(crate::middleware::compression::layer())
         .layer(
             TraceLayer::new_for_http()
                 .make_span_with(|request: &Request<_>| {
@@ -12,7 +12,7 @@ use crate::{
     middleware::AuthenticatedUser,
     models::{
         membership::MembershipModel,
-        notification::NotificationModel,
+        notification::{Notification, NotificationModel},
     },
     record_id_ext::RecordIdExt,
     templates::{BaseContext, User},
@@ -36,6 +36,21 @@ struct NotificationView {
     created_at: String,
 }
 
+impl From<Notification> for NotificationView {
+    fn from(n: Notification) -> Self {
+        Self {
+            id: n.id.to_raw_string(),
+            notification_type: n.notification_type,
+            title: n.title,
+            message: n.message,
+            link: n.link,
+            read: n.read,
+            related_id: n.related_id,
+            created_at: n.created_at.format("%b %d, %Y at %H:%M").to_string(),
+        }
+    }
+}
+
 #[derive(Template)]
 #[template(path = "notifications/index.html")]
 struct NotificationsTemplate {
@@ -60,9 +75,20 @@ impl NotificationsTemplate {
     }
 }
 
+/// Notifications shown in the user menu
+const DROPDOWN_LIMIT: u32 = 5;
+
+/// The user menu's recent notifications, loaded when the menu opens
+#[derive(Template)]
+#[template(path = "notifications/_dropdown.html")]
+struct NotificationDropdownTemplate {
+    notifications: Vec<NotificationView>,
+}
+
 pub fn router() -> Router {
     Router::new()
         .route("/notifications", get(list_notifications))
+        .route("/notifications/dropdown", get(notification_dropdown))
         .route("/api/notifications/stream", get(notification_stream_sse))
         .route("/notifications/mark-read", post(mark_read))
         .route("/notifications/read-all", post(mark_all_read))
@@ -82,16 +108,7 @@ async fn list_notifications(
 
     let notifications: Vec<NotificationView> = raw_notifications
         .into_iter()
-        .map(|n| NotificationView {
-            id: n.id.to_raw_string(),
-            notification_type: n.notification_type,
-            title: n.title,
-            message: n.message,
-            link: n.link,
-            read: n.read,
-            related_id: n.related_id,
-            created_at: n.created_at.format("%b %d, %Y at %H:%M").to_string(),
-        })
+        .map(NotificationView::from)
         .collect();
 
     let base = BaseContext::new()
@@ -108,6 +125,26 @@ async fn list_notifications(
     Ok(Html(html))
 }
 
+async fn notification_dropdown(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Html<String>, Error> {
+    let notifications = NotificationModel::new()
+        .get_recent(&user.id, DROPDOWN_LIMIT)
+        .await?
+        .into_iter()
+        .map(NotificationView::from)
+        .collect();
+
+    let html = NotificationDropdownTemplate { notifications }
+        .render()
+        .map_err(|e| {
+            error!("Failed to render notification dropdown: {}", e);
+            Error::template(e.to_string())
+        })?;
+
+    Ok(Html(html))
+}
+
 #[derive(Debug, Deserialize)]
 struct MarkReadForm {
     notification_id: String,
@@ -217,7 +254,8 @@ async fn get_org_slug(org_id: &str) -> Option<String> {
     model.get_by_id(org_id).await.ok().map(|org| org.slug)
 }
 
-/// SSE endpoint that pushes notification count updates to the authenticated user.
+/// SSE endpoint that pushes notification count updates to the authenticated user,
+/// and tells their page when a reaction bar they can see has changed.
 /// The person_id is derived from the JWT — never from URL params.
 async fn notification_stream_sse(
     request: axum::extract::Request,
@@ -241,6 +279,7 @@ async fn notification_stream_sse(
         }
     };
     let mut rx = crate::services::notification_stream::subscribe();
+    let mut reactions = crate::services::reaction_stream::subscribe();
 
     let stream = async_stream::stream! {
         // Send initial count immediately
@@ -275,6 +314,11 @@ async fn notification_stream_sse(
                         Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
                     }
                 }
+                Ok(event) = reactions.recv() => {
+                    if event.audience.contains(&person_id) {
+                        yield Ok(sse_reaction_event(&event.region));
+                    }
+                }
                 _ = keepalive.tick() => {
                     yield Ok(":keepalive\n\n".to_string());
                 }
@@ -292,6 +336,15 @@ async fn notification_stream_sse(
         .unwrap()
 }
 
+/// Tells the page to reload one reaction bar; ids are built from record
+/// keys, so they need no escaping
+fn sse_reaction_event(region: &str) -> String {
+    format!(
+        "event: reaction-update\ndata: {{\"region\":\"{}\"}}\n\n",
+        region
+    )
+}
+
 fn sse_notification_event(count: u32) -> String {
     let badge = if count > 0 {
         format!(
@@ -0,0 +1,601 @@
+//! OAuth2 authorization server for third-party apps (see `services::oauth`)
+//!
+//! `/oauth/authorize` shows the consent screen, `/oauth/token` and
+//! `/oauth/revoke` are called by the apps themselves, and `/account/apps` is
+//! where people register apps and disconnect the ones they've authorized.
+
+use askama::Template;
+use axum::{
+    Json, Router,
+    extract::{Path, Query, Request},
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use axum_extra::extract::Form;
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use serde::Deserialize;
+use serde_json::json;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::{AuthenticatedUser, UserExtractor},
+    models::person::SessionUser,
+    record_id_ext::RecordIdExt,
+    response,
+    services::{
+        oauth::{self, ClientData, ConnectedApp, GrantError, OAuthClient, TokenResponse},
+        tokens,
+    },
+    templates::{BaseContext, User, filters},
+};
+
+/// Cookie matched against the consent form, so another site can't submit an
+/// approval on someone's behalf
+const CONSENT_COOKIE: &str = "oauth_consent";
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/oauth/authorize",
+            get(authorize_page).post(authorize_decision),
+        )
+        .route("/oauth/token", post(token))
+        .route("/oauth/revoke", post(revoke))
+        .route("/account/apps", get(apps_page).post(register_app))
+        .route("/account/apps/{id}", post(update_app))
+        .route("/account/apps/{id}/secret", post(rotate_secret))
+        .route("/account/apps/{id}/delete", post(delete_app))
+        .route(
+            "/account/connected-apps/{id}/disconnect",
+            post(disconnect_app),
+        )
+}
+
+pub struct ScopeOption {
+    pub value: &'static str,
+    pub description: &'static str,
+}
+
+fn scope_options(scopes: &[String]) -> Vec<ScopeOption> {
+    oauth::SCOPES
+        .iter()
+        .filter(|&&(value, _)| scopes.iter().any(|s| s == value))
+        .map(|&(value, description)| ScopeOption { value, description })
+        .collect()
+}
+
+#[derive(Template)]
+#[template(path = "oauth/authorize.html")]
+pub struct OAuthAuthorizeTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub client: OAuthClient,
+    pub scopes: Vec<ScopeOption>,
+    /// Where the person is sent afterwards, shown so they can check it
+    pub redirect_host: String,
+    pub redirect_uri: String,
+    pub scope: String,
+    pub state: String,
+    pub code_challenge: String,
+    pub consent_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "account/apps.html")]
+pub struct AccountAppsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub clients: Vec<OAuthClient>,
+    pub connected: Vec<ConnectedApp>,
+    pub scopes: Vec<ScopeOption>,
+    /// (client ID, secret) for an app just registered or given a new secret,
+    /// shown once
+    pub new_secret: Option<(String, String)>,
+    pub token_url: String,
+    pub authorize_url: String,
+    pub max_redirect_uris: usize,
+    pub error: Option<String>,
+    pub success: Option<String>,
+}
+
+// ============================
+// Authorization
+// ============================
+
+#[derive(Debug, Deserialize)]
+struct AuthorizeQuery {
+    response_type: Option<String>,
+    client_id: Option<String>,
+    redirect_uri: Option<String>,
+    #[serde(default)]
+    scope: String,
+    #[serde(default)]
+    state: String,
+    code_challenge: Option<String>,
+    code_challenge_method: Option<String>,
+}
+
+/// The app and the redirect URI it asked for. Until both check out, errors
+/// are shown here rather than sent to a redirect URI that may not be the
+/// app's.
+async fn requesting_client(
+    client_id: Option<&str>,
+    redirect_uri: Option<&str>,
+) -> Result<(OAuthClient, String), Error> {
+    let client = match client_id.filter(|id| !id.is_empty()) {
+        Some(id) => oauth::client_by_client_id(id).await?,
+        None => None,
+    }
+    .ok_or_else(|| Error::BadRequest("This link doesn't belong to a registered app".to_string()))?;
+    let redirect_uri = match redirect_uri.filter(|uri| !uri.is_empty()) {
+        Some(uri) if client.redirect_uris.iter().any(|u| u == uri) => uri.to_string(),
+        None if client.redirect_uris.len() == 1 => client.redirect_uris[0].clone(),
+        _ => {
+            return Err(Error::BadRequest(format!(
+                "The redirect URI isn't registered for {}",
+                client.name
+            )));
+        }
+    };
+    Ok((client, redirect_uri))
+}
+
+fn redirect_error(redirect_uri: &str, code: &str, state: &str) -> Response {
+    let mut params = vec![("error", code)];
+    if !state.is_empty() {
+        params.push(("state", state));
+    }
+    Redirect::to(&oauth::redirect_with(redirect_uri, &params)).into_response()
+}
+
+/// The host the person is sent back to, for the consent screen
+fn uri_host(uri: &str) -> String {
+    uri.split_once("://")
+        .map_or(uri, |(_, rest)| rest)
+        .split(['/', '?'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+async fn authorize_page(
+    Query(query): Query<AuthorizeQuery>,
+    jar: CookieJar,
+    request: Request,
+) -> Result<Response, Error> {
+    let (client, redirect_uri) =
+        requesting_client(query.client_id.as_deref(), query.redirect_uri.as_deref()).await?;
+
+    if query.response_type.as_deref() != Some("code") {
+        return Ok(redirect_error(
+            &redirect_uri,
+            "unsupported_response_type",
+            &query.state,
+        ));
+    }
+    if query.code_challenge.is_some() && query.code_challenge_method.as_deref() != Some("S256") {
+        return Ok(redirect_error(
+            &redirect_uri,
+            "invalid_request",
+            &query.state,
+        ));
+    }
+    let scopes = match oauth::parse_scopes(&query.scope, &client.scopes) {
+        Ok(scopes) => scopes,
+        Err(_) => return Ok(redirect_error(&redirect_uri, "invalid_scope", &query.state)),
+    };
+
+    let Some(user) = request.get_user() else {
+        let here = request
+            .uri()
+            .path_and_query()
+            .map_or("/oauth/authorize", |pq| pq.as_str());
+        return Ok(
+            Redirect::to(&format!("/login?redirect={}", urlencoding::encode(here))).into_response(),
+        );
+    };
+
+    let consent_token = tokens::random_token(32);
+    let cookie = Cookie::build((CONSENT_COOKIE, consent_token.clone()))
+        .path("/oauth/authorize")
+        .http_only(true)
+        .secure(crate::config::cookie_secure())
+        .same_site(SameSite::Lax)
+        .max_age(cookie::time::Duration::minutes(30))
+        .build();
+
+    let base = BaseContext::new()
+        .with_page("account")
+        .with_user(User::from_session_user(&user).await);
+    let template = OAuthAuthorizeTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        scopes: scope_options(&scopes),
+        scope: scopes.join(" "),
+        redirect_host: uri_host(&redirect_uri),
+        redirect_uri,
+        client,
+        state: query.state,
+        code_challenge: query.code_challenge.unwrap_or_default(),
+        consent_token,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render OAuth consent template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok((jar.add(cookie), Html(html)).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthorizeForm {
+    client_id: String,
+    redirect_uri: String,
+    #[serde(default)]
+    scope: String,
+    #[serde(default)]
+    state: String,
+    #[serde(default)]
+    code_challenge: String,
+    consent_token: String,
+    decision: String,
+}
+
+async fn authorize_decision(
+    AuthenticatedUser(user): AuthenticatedUser,
+    jar: CookieJar,
+    Form(form): Form<AuthorizeForm>,
+) -> Result<Response, Error> {
+    let expected = jar.get(CONSENT_COOKIE).map(|c| c.value().to_string());
+    if expected.as_deref() != Some(form.consent_token.as_str()) {
+        return Err(Error::BadRequest(
+            "This approval has expired. Go back to the app and try again.".to_string(),
+        ));
+    }
+    let jar = jar.remove(Cookie::build(CONSENT_COOKIE).path("/oauth/authorize"));
+
+    let (client, redirect_uri) =
+        requesting_client(Some(&form.client_id), Some(&form.redirect_uri)).await?;
+    if form.decision != "approve" {
+        info!("{} denied {} access", user.username, client.name);
+        return Ok((
+            jar,
+            redirect_error(&redirect_uri, "access_denied", &form.state),
+        )
+            .into_response());
+    }
+    let scopes = match oauth::parse_scopes(&form.scope, &client.scopes) {
+        Ok(scopes) => scopes,
+        Err(_) => {
+            return Ok((
+                jar,
+                redirect_error(&redirect_uri, "invalid_scope", &form.state),
+            )
+                .into_response());
+        }
+    };
+
+    let person = RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    let challenge = Some(form.code_challenge.as_str()).filter(|c| !c.is_empty());
+    let code = oauth::issue_code(&client, &person, &redirect_uri, &scopes, challenge).await?;
+    info!(
+        "{} authorized {} for {}",
+        user.username,
+        client.name,
+        scopes.join(" ")
+    );
+
+    let mut params = vec![("code", code.as_str())];
+    if !form.state.is_empty() {
+        params.push(("state", form.state.as_str()));
+    }
+    Ok((
+        jar,
+        Redirect::to(&oauth::redirect_with(&redirect_uri, &params)),
+    )
+        .into_response())
+}
+
+// ============================
+// Token endpoint
+// ============================
+
+/// An RFC 6749 error body. Tokens and errors are never cached.
+fn oauth_error(status: StatusCode, code: &str) -> Response {
+    (
+        status,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(json!({ "error": code })),
+    )
+        .into_response()
+}
+
+fn grant_error(e: GrantError) -> Response {
+    match e {
+        GrantError::InvalidClient => oauth_error(StatusCode::UNAUTHORIZED, e.code()),
+        GrantError::InvalidGrant => oauth_error(StatusCode::BAD_REQUEST, e.code()),
+    }
+}
+
+/// The calling app, from HTTP Basic credentials or `client_id` and
+/// `client_secret` form fields
+async fn calling_client(
+    headers: &HeaderMap,
+    client_id: Option<&str>,
+    client_secret: Option<&str>,
+) -> Result<Option<OAuthClient>, Error> {
+    let credentials = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(oauth::basic_credentials)
+        .or_else(|| Some((client_id?.to_string(), client_secret?.to_string())));
+    match credentials {
+        Some((id, secret)) => oauth::authenticate_client(&id, &secret).await,
+        None => Ok(None),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenForm {
+    grant_type: String,
+    code: Option<String>,
+    redirect_uri: Option<String>,
+    code_verifier: Option<String>,
+    refresh_token: Option<String>,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+}
+
+async fn token(headers: HeaderMap, Form(form): Form<TokenForm>) -> Result<Response, Error> {
+    let Some(client) = calling_client(
+        &headers,
+        form.client_id.as_deref(),
+        form.client_secret.as_deref(),
+    )
+    .await?
+    else {
+        return Ok(grant_error(GrantError::InvalidClient));
+    };
+
+    let issued = match form.grant_type.as_str() {
+        "authorization_code" => {
+            let (Some(code), Some(redirect_uri)) =
+                (form.code.as_deref(), form.redirect_uri.as_deref())
+            else {
+                return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+            };
+            oauth::exchange_code(&client, code, redirect_uri, form.code_verifier.as_deref()).await?
+        }
+        "refresh_token" => {
+            let Some(refresh_token) = form.refresh_token.as_deref() else {
+                return Ok(oauth_error(StatusCode::BAD_REQUEST, "invalid_request"));
+            };
+            oauth::refresh(&client, refresh_token).await?
+        }
+        _ => {
+            return Ok(oauth_error(
+                StatusCode::BAD_REQUEST,
+                "unsupported_grant_type",
+            ));
+        }
+    };
+
+    Ok(match issued {
+        Ok(tokens) => token_json(tokens),
+        Err(e) => grant_error(e),
+    })
+}
+
+fn token_json(tokens: TokenResponse) -> Response {
+    ([(header::CACHE_CONTROL, "no-store")], Json(tokens)).into_response()
+}
+
+#[derive(Debug, Deserialize)]
+struct RevokeForm {
+    token: String,
+    client_id: Option<String>,
+    client_secret: Option<String>,
+}
+
+/// Token revocation (RFC 7009): succeeds whether or not the token was valid
+async fn revoke(headers: HeaderMap, Form(form): Form<RevokeForm>) -> Result<Response, Error> {
+    let Some(client) = calling_client(
+        &headers,
+        form.client_id.as_deref(),
+        form.client_secret.as_deref(),
+    )
+    .await?
+    else {
+        return Ok(grant_error(GrantError::InvalidClient));
+    };
+    oauth::revoke(&client, &form.token).await?;
+    Ok(StatusCode::OK.into_response())
+}
+
+// ============================
+// Account: registered and connected apps
+// ============================
+
+#[derive(Debug, Deserialize)]
+struct AppsQuery {
+    success: Option<String>,
+    error: Option<String>,
+}
+
+fn person_of(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+async fn render_apps(
+    user: &SessionUser,
+    new_secret: Option<(String, String)>,
+    error: Option<String>,
+    success: Option<String>,
+) -> Result<Response, Error> {
+    let person = person_of(user)?;
+    let base = BaseContext::new()
+        .with_page("account")
+        .with_user(User::from_session_user(user).await);
+    let app_url = crate::config::app_url();
+    let template = AccountAppsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        clients: oauth::clients_for_owner(&person).await?,
+        connected: oauth::connected_apps(&person).await?,
+        scopes: oauth::SCOPES
+            .iter()
+            .map(|&(value, description)| ScopeOption { value, description })
+            .collect(),
+        new_secret,
+        token_url: format!("{}/oauth/token", app_url),
+        authorize_url: format!("{}/oauth/authorize", app_url),
+        max_redirect_uris: oauth::MAX_REDIRECT_URIS,
+        error,
+        success,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render apps template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn apps_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(query): Query<AppsQuery>,
+) -> Result<Response, Error> {
+    render_apps(&user, None, query.error, query.success).await
+}
+
+fn back_to_apps(message: &str, is_error: bool) -> Response {
+    response::redirect(&format!(
+        "/account/apps?{}={}",
+        if is_error { "error" } else { "success" },
+        urlencoding::encode(message)
+    ))
+}
+
+#[derive(Debug, Deserialize)]
+struct AppForm {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    homepage_url: String,
+    #[serde(default)]
+    redirect_uris: String,
+    #[serde(default)]
+    scopes: Vec<String>,
+}
+
+impl AppForm {
+    fn parse(&self) -> Result<ClientData, Error> {
+        ClientData::parse(
+            &self.name,
+            &self.description,
+            &self.homepage_url,
+            &self.redirect_uris,
+            &self.scopes,
+        )
+    }
+}
+
+async fn register_app(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Form(form): Form<AppForm>,
+) -> Result<Response, Error> {
+    let person = person_of(&user)?;
+    let registered = match form.parse() {
+        Ok(data) => oauth::register_client(&person, &data).await,
+        Err(e) => Err(e),
+    };
+    match registered {
+        Ok((client, secret)) => {
+            info!("{} registered the app {}", user.username, client.name);
+            render_apps(
+                &user,
+                Some((client.client_id, secret)),
+                None,
+                Some(format!("Registered {}.", client.name)),
+            )
+            .await
+        }
+        Err(Error::Validation(message)) => render_apps(&user, None, Some(message), None).await,
+        Err(e) => Err(e),
+    }
+}
+
+async fn update_app(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    Form(form): Form<AppForm>,
+) -> Result<Response, Error> {
+    let person = person_of(&user)?;
+    let updated = match form.parse() {
+        Ok(data) => oauth::update_client(&person, &id, &data).await,
+        Err(e) => Err(e),
+    };
+    match updated {
+        Ok(()) => Ok(back_to_apps("App saved.", false)),
+        Err(Error::Validation(message)) => Ok(back_to_apps(&message, true)),
+        Err(e) => Err(e),
+    }
+}
+
+async fn rotate_secret(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Response, Error> {
+    let person = person_of(&user)?;
+    let (client, secret) = oauth::rotate_secret(&person, &id).await?;
+    info!("{} issued a new secret for {}", user.username, client.name);
+    render_apps(
+        &user,
+        Some((client.client_id, secret)),
+        None,
+        Some(format!(
+            "New secret issued for {}. The old one no longer works.",
+            client.name
+        )),
+    )
+    .await
+}
+
+async fn delete_app(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Response, Error> {
+    let person = person_of(&user)?;
+    oauth::delete_client(&person, &id).await?;
+    info!("{} deleted the app {}", user.username, id);
+    Ok(back_to_apps(
+        "App deleted. Its tokens no longer work.",
+        false,
+    ))
+}
+
+async fn disconnect_app(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Response, Error> {
+    let person = person_of(&user)?;
+    oauth::disconnect(&person, &id).await?;
+    info!("{} disconnected the app oauth_client:{}", user.username, id);
+    Ok(back_to_apps("App disconnected.", false))
+}
@@ -0,0 +1,687 @@
+use askama::Template;
+use axum::{
+    Form, Json, Router,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info, warn};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        notification::NotificationModel,
+        offer::{self, CounterTerms, Offer, OfferListing, OfferModel, OfferTerms},
+        person::{Person, SessionUser},
+        production::{Production, ProductionModel},
+        representation::RepresentationModel,
+        selftape::{self, SelfTapeModel},
+    },
+    record_id_ext::RecordIdExt,
+    services::whatsapp::{self, OutboxMessage},
+    templates::{BaseContext, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/productions/{slug}/offers", get(production_offers_page).post(send_offer))
+        .route("/productions/{slug}/offers/{offer_id}/revise", post(revise_offer))
+        .route("/productions/{slug}/offers/{offer_id}/accept-counter", post(accept_counter))
+        .route("/productions/{slug}/offers/{offer_id}/withdraw", post(withdraw_offer))
+        .route("/offers", get(my_offers_page))
+        .route("/offers/{offer_id}/accept", post(accept_offer))
+        .route("/offers/{offer_id}/decline", post(decline_offer))
+        .route("/offers/{offer_id}/counter", post(counter_offer))
+        .route("/api/whatsapp/outbox", get(whatsapp_outbox))
+        .route("/api/whatsapp/outbox/{message_id}", post(whatsapp_report))
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct OfferRow {
+    pub id: String,
+    pub production_title: String,
+    pub production_slug: String,
+    pub person_name: String,
+    pub username: String,
+    pub role_title: String,
+    pub dates: String,
+    pub rate: String,
+    pub conditions: Option<String>,
+    pub status: String,
+    /// The person's proposed rate and dates, while countered
+    pub counter: Option<String>,
+    pub response_note: Option<String>,
+    pub updated_at: String,
+    /// Current terms, for the revise form
+    pub start_date: String,
+    pub end_date: String,
+    pub rate_amount: String,
+    pub rate_types: Vec<SelectOption>,
+    /// Sent to someone the viewer represents rather than to the viewer
+    pub for_client: bool,
+}
+
+pub struct BookingRow {
+    pub production_title: String,
+    pub production_slug: String,
+    pub role_title: String,
+    pub dates: String,
+}
+
+#[derive(Template)]
+#[template(path = "productions/offers.html")]
+pub struct ProductionOffersTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub offers: Vec<OfferRow>,
+    pub form: SendOfferForm,
+    pub rate_types: Vec<SelectOption>,
+    pub today: String,
+    pub max_conditions: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "jobs/my_offers.html")]
+pub struct MyOffersTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub offers: Vec<OfferRow>,
+    pub bookings: Vec<BookingRow>,
+    pub today: String,
+    pub max_note: usize,
+    pub error: Option<String>,
+}
+
+// ============================
+// Helpers
+// ============================
+
+fn person_id(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%b %-d, %Y at %H:%M UTC").to_string()
+}
+
+fn rate_options(selected: &str) -> Vec<SelectOption> {
+    offer::RATE_TYPES
+        .iter()
+        .map(|(value, label)| SelectOption::new(value, label.to_string(), *value == selected))
+        .collect()
+}
+
+fn offer_row(offer: OfferListing) -> OfferRow {
+    let counter = (offer.status == "countered").then(|| {
+        let rate = offer
+            .counter_rate_amount
+            .map(|rate| offer::rate_label(rate, &offer.rate_type));
+        let dates = match (&offer.counter_start_date, &offer.counter_end_date) {
+            (Some(start), Some(end)) => Some(offer::dates_label(start, end)),
+            _ => None,
+        };
+        [rate, dates].into_iter().flatten().collect::<Vec<_>>().join(" · ")
+    });
+    OfferRow {
+        id: offer.id.key_string(),
+        production_title: offer.production_title,
+        production_slug: offer.production_slug,
+        person_name: offer.person_name,
+        username: offer.username,
+        role_title: offer.role_title,
+        dates: offer::dates_label(&offer.start_date, &offer.end_date),
+        rate: offer::rate_label(offer.rate_amount, &offer.rate_type),
+        conditions: offer.conditions,
+        status: offer.status,
+        counter,
+        response_note: offer.response_note,
+        updated_at: format_time(offer.updated_at),
+        start_date: offer.start_date,
+        end_date: offer.end_date,
+        rate_amount: format!("{}", offer.rate_amount),
+        rate_types: rate_options(&offer.rate_type),
+        for_client: false,
+    }
+}
+
+/// The production, if the user can edit it
+async fn require_editor(slug: &str, user: &SessionUser) -> Result<Production, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    if !ProductionModel::can_edit(&production.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok(production)
+}
+
+/// An offer on this production
+async fn require_production_offer(offer_id: &str, production: &Production) -> Result<Offer, Error> {
+    let offer = OfferModel::get(offer_id).await?;
+    if offer.production != production.id {
+        return Err(Error::NotFound);
+    }
+    Ok(offer)
+}
+
+/// An offer sent to the user, or to someone their agency represents
+async fn require_own_offer(offer_id: &str, user: &SessionUser) -> Result<Offer, Error> {
+    let offer = OfferModel::get(offer_id).await?;
+    if offer.status == "withdrawn" {
+        return Err(Error::NotFound);
+    }
+    let me = person_id(user)?;
+    if offer.person != me && !RepresentationModel::represents(&me, &offer.person).await? {
+        return Err(Error::NotFound);
+    }
+    Ok(offer)
+}
+
+/// Who hears about an offer: the person, or their representative if they
+/// take inquiries that way
+async fn offer_recipient(person: &RecordId) -> RecordId {
+    match RepresentationModel::route(person).await {
+        Ok(Some(rep)) => rep.contact.unwrap_or_else(|| person.clone()),
+        Ok(None) => person.clone(),
+        Err(e) => {
+            error!("Failed to look up representation for {}: {}", person.display(), e);
+            person.clone()
+        }
+    }
+}
+
+async fn display_name(person: &RecordId) -> String {
+    match Person::find_by_id(&person.to_raw_string()).await {
+        Ok(Some(person)) => person.get_display_name(),
+        _ => "your client".to_string(),
+    }
+}
+
+/// Tell someone about an offer in the app and, if they've opted in, on WhatsApp
+async fn deliver(person: &RecordId, title: &str, message: &str, link: &str, offer: &RecordId) {
+    if let Err(e) = NotificationModel::new()
+        .create(
+            &person.to_raw_string(),
+            "offer",
+            title,
+            message,
+            Some(link),
+            Some(&offer.to_raw_string()),
+        )
+        .await
+    {
+        error!("Failed to notify {} about offer: {}", person.display(), e);
+    }
+    let text = format!("{}\n\n{}\n{}{}", title, message, crate::config::app_url(), link);
+    if let Err(e) = whatsapp::queue(person, &text).await {
+        error!("Failed to queue WhatsApp message for {}: {}", person.display(), e);
+    }
+}
+
+/// Send the person, or their representative, a new or revised offer
+pub(super) async fn deliver_offer(offer: &Offer, production_title: &str, revised: bool) {
+    let recipient = offer_recipient(&offer.person).await;
+    let (whom, title) = if recipient == offer.person {
+        let title = if revised { "Your offer was updated" } else { "You've been offered a role" };
+        ("you".to_string(), title)
+    } else {
+        let title = if revised { "Your client's offer was updated" } else { "Your client has been offered a role" };
+        (display_name(&offer.person).await, title)
+    };
+    let message = format!(
+        "{} offers {} {} for {}, {}.",
+        production_title,
+        whom,
+        offer.role_title,
+        offer::dates_label(&offer.start_date, &offer.end_date),
+        offer::rate_label(offer.rate_amount, &offer.rate_type)
+    );
+    deliver(
+        &recipient,
+        title,
+        &message,
+        &format!("/offers#offer-{}", offer.id.key_string()),
+        &offer.id,
+    )
+    .await;
+}
+
+/// Tell whoever sent the offer how the person answered
+async fn deliver_response(offer: &Offer, user: &SessionUser) {
+    let production = match ProductionModel::get(&offer.production).await {
+        Ok(production) => production,
+        Err(e) => {
+            error!("Failed to load production for offer {}: {}", offer.id.display(), e);
+            return;
+        }
+    };
+    let (title, verb) = match offer.status.as_str() {
+        "accepted" => ("Offer accepted", "accepted"),
+        "declined" => ("Offer declined", "declined"),
+        _ => ("Offer countered", "countered"),
+    };
+    let responder = if person_id(user).is_ok_and(|me| me == offer.person) {
+        user.name.clone()
+    } else {
+        format!("{}, representing {},", user.name, display_name(&offer.person).await)
+    };
+    let message = format!("{} {} your offer for {} on {}.", responder, verb, offer.role_title, production.title);
+    deliver(
+        &offer.sent_by,
+        title,
+        &message,
+        &format!("/productions/{}/offers#offer-{}", production.slug, offer.id.key_string()),
+        &offer.id,
+    )
+    .await;
+}
+
+/// Book an accepted offer and confirm it to the person, and to their
+/// representative if inquiries go through them
+async fn confirm_booking(offer: &Offer, production_title: &str) {
+    if let Err(e) = OfferModel::confirm(offer).await {
+        error!("Failed to book accepted offer {}: {}", offer.id.display(), e);
+        return;
+    }
+    let message = format!(
+        "You're booked as {} on {} for {}.",
+        offer.role_title,
+        production_title,
+        offer::dates_label(&offer.start_date, &offer.end_date)
+    );
+    let link = format!("/offers#offer-{}", offer.id.key_string());
+    deliver(&offer.person, "Booking confirmed", &message, &link, &offer.id).await;
+
+    let recipient = offer_recipient(&offer.person).await;
+    if recipient != offer.person {
+        let message = format!(
+            "{} is booked as {} on {} for {}.",
+            display_name(&offer.person).await,
+            offer.role_title,
+            production_title,
+            offer::dates_label(&offer.start_date, &offer.end_date)
+        );
+        deliver(&recipient, "Client booking confirmed", &message, &link, &offer.id).await;
+    }
+}
+
+/// Upcoming booked dates for a profile page. Failures are logged and leave
+/// the row off rather than breaking the page.
+pub(crate) async fn profile_booked_dates(person: &RecordId) -> Vec<String> {
+    match OfferModel::upcoming_bookings(person, &today().to_string()).await {
+        Ok(bookings) => bookings
+            .iter()
+            .map(|b| offer::dates_label(&b.start_date, &b.end_date))
+            .collect(),
+        Err(e) => {
+            error!("Failed to load bookings for {}: {}", person.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+async fn render_production_offers(
+    user: &SessionUser,
+    production: Production,
+    form: SendOfferForm,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let offers = OfferModel::for_production(&production.id)
+        .await?
+        .into_iter()
+        .map(offer_row)
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+    let template = ProductionOffersTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        offers,
+        rate_types: rate_options(if form.rate_type.is_empty() { "daily" } else { &form.rate_type }),
+        form,
+        today: today().to_string(),
+        max_conditions: offer::MAX_CONDITIONS_CHARS,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render production offers template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn render_my_offers(user: &SessionUser, error: Option<String>) -> Result<Response, Error> {
+    let person = person_id(user)?;
+    let mut offers: Vec<OfferRow> = OfferModel::for_person(&person)
+        .await?
+        .into_iter()
+        .map(offer_row)
+        .collect();
+    let clients = RepresentationModel::clients(&person).await?;
+    if !clients.is_empty() {
+        offers.extend(OfferModel::for_people(&clients).await?.into_iter().map(|listing| OfferRow {
+            for_client: true,
+            ..offer_row(listing)
+        }));
+    }
+    let bookings = OfferModel::upcoming_bookings(&person, &today().to_string())
+        .await?
+        .into_iter()
+        .map(|b| BookingRow {
+            dates: offer::dates_label(&b.start_date, &b.end_date),
+            production_title: b.production_title,
+            production_slug: b.production_slug,
+            role_title: b.role_title,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("my-jobs")
+        .with_user(User::from_session_user(user).await);
+    let template = MyOffersTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        offers,
+        bookings,
+        today: today().to_string(),
+        max_note: offer::MAX_NOTE_CHARS,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render offers template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+fn back_to_production_offers(slug: &str, offer: &RecordId) -> Response {
+    Redirect::to(&format!("/productions/{}/offers#offer-{}", slug, offer.key_string())).into_response()
+}
+
+fn back_to_my_offers(offer: &RecordId) -> Response {
+    Redirect::to(&format!("/offers#offer-{}", offer.key_string())).into_response()
+}
+
+fn already_answered() -> Error {
+    Error::Conflict("This offer changed while you were looking at it".to_string())
+}
+
+// ============================
+// Production handlers
+// ============================
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SendOfferForm {
+    #[serde(default)]
+    pub usernames: String,
+    #[serde(default)]
+    pub role_title: String,
+    #[serde(default)]
+    pub start_date: String,
+    #[serde(default)]
+    pub end_date: String,
+    #[serde(default)]
+    pub rate_amount: String,
+    #[serde(default)]
+    pub rate_type: String,
+    #[serde(default)]
+    pub conditions: String,
+}
+
+impl SendOfferForm {
+    fn terms(&self) -> Result<OfferTerms, Error> {
+        OfferTerms::parse(
+            &self.role_title,
+            &self.start_date,
+            &self.end_date,
+            &self.rate_amount,
+            &self.rate_type,
+            &self.conditions,
+            today(),
+        )
+    }
+}
+
+async fn production_offers_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    render_production_offers(&user, production, SendOfferForm::default(), None).await
+}
+
+async fn send_offer(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<SendOfferForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+
+    let result = async {
+        let terms = form.terms()?;
+        let usernames = selftape::parse_usernames(&form.usernames);
+        if usernames.is_empty() {
+            return Err(Error::Validation("Enter the username of the person to make the offer to".to_string()));
+        }
+        let (people, unknown) = SelfTapeModel::people_by_username(&usernames).await?;
+        if !unknown.is_empty() {
+            let unknown: Vec<String> = unknown.iter().map(|u| format!("@{}", u)).collect();
+            return Err(Error::Validation(format!("No one goes by {}", unknown.join(", "))));
+        }
+        let sent_by = person_id(&user)?;
+        let mut offers = Vec::new();
+        for person in &people {
+            offers.push(OfferModel::create(&production.id, person, &terms, &sent_by, None).await?);
+        }
+        Ok(offers)
+    }
+    .await;
+
+    match result {
+        Ok(offers) => {
+            info!(
+                "{} sent {} offers for '{}' on {}",
+                user.username,
+                offers.len(),
+                form.role_title.trim(),
+                slug
+            );
+            for offer in &offers {
+                deliver_offer(offer, &production.title, false).await;
+            }
+            Ok(Redirect::to(&format!("/productions/{}/offers", slug)).into_response())
+        }
+        Err(Error::Validation(msg)) => render_production_offers(&user, production, form, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+async fn revise_offer(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, offer_id)): Path<(String, String)>,
+    Form(form): Form<SendOfferForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    let offer = require_production_offer(&offer_id, &production).await?;
+    let terms = match form.terms() {
+        Ok(terms) => terms,
+        Err(Error::Validation(msg)) => {
+            return render_production_offers(&user, production, SendOfferForm::default(), Some(msg)).await;
+        }
+        Err(e) => return Err(e),
+    };
+    let revised = OfferModel::revise(&offer, &terms).await?.ok_or_else(already_answered)?;
+    info!("{} revised offer {}", user.username, offer_id);
+    deliver_offer(&revised, &production.title, true).await;
+    Ok(back_to_production_offers(&slug, &offer.id))
+}
+
+async fn accept_counter(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, offer_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    let offer = require_production_offer(&offer_id, &production).await?;
+    let accepted = OfferModel::accept_counter(&offer).await?.ok_or_else(already_answered)?;
+    info!("{} accepted the counter on offer {}", user.username, offer_id);
+    confirm_booking(&accepted, &production.title).await;
+    Ok(back_to_production_offers(&slug, &offer.id))
+}
+
+async fn withdraw_offer(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, offer_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user).await?;
+    let offer = require_production_offer(&offer_id, &production).await?;
+    OfferModel::withdraw(&offer).await?.ok_or_else(already_answered)?;
+    info!("{} withdrew offer {}", user.username, offer_id);
+    Ok(back_to_production_offers(&slug, &offer.id))
+}
+
+// ============================
+// Person handlers
+// ============================
+
+async fn my_offers_page(AuthenticatedUser(user): AuthenticatedUser) -> Result<Response, Error> {
+    render_my_offers(&user, None).await
+}
+
+async fn accept_offer(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(offer_id): Path<String>,
+) -> Result<Response, Error> {
+    let offer = require_own_offer(&offer_id, &user).await?;
+    let accepted = OfferModel::accept(&offer).await?.ok_or_else(already_answered)?;
+    let production = ProductionModel::get(&accepted.production).await?;
+    confirm_booking(&accepted, &production.title).await;
+    deliver_response(&accepted, &user).await;
+    Ok(back_to_my_offers(&offer.id))
+}
+
+#[derive(Debug, Deserialize)]
+struct DeclineForm {
+    #[serde(default)]
+    note: String,
+}
+
+async fn decline_offer(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(offer_id): Path<String>,
+    Form(form): Form<DeclineForm>,
+) -> Result<Response, Error> {
+    let offer = require_own_offer(&offer_id, &user).await?;
+    match OfferModel::decline(&offer, &form.note).await {
+        Ok(Some(declined)) => {
+            deliver_response(&declined, &user).await;
+            Ok(back_to_my_offers(&offer.id))
+        }
+        Ok(None) => Err(already_answered()),
+        Err(Error::Validation(msg)) => render_my_offers(&user, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CounterForm {
+    #[serde(default)]
+    rate_amount: String,
+    #[serde(default)]
+    start_date: String,
+    #[serde(default)]
+    end_date: String,
+    #[serde(default)]
+    note: String,
+}
+
+async fn counter_offer(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(offer_id): Path<String>,
+    Form(form): Form<CounterForm>,
+) -> Result<Response, Error> {
+    let offer = require_own_offer(&offer_id, &user).await?;
+    let counter = match CounterTerms::parse(
+        &offer,
+        &form.rate_amount,
+        &form.start_date,
+        &form.end_date,
+        &form.note,
+        today(),
+    ) {
+        Ok(counter) => counter,
+        Err(Error::Validation(msg)) => return render_my_offers(&user, Some(msg)).await,
+        Err(e) => return Err(e),
+    };
+    let countered = OfferModel::counter(&offer, &counter).await?.ok_or_else(already_answered)?;
+    deliver_response(&countered, &user).await;
+    Ok(back_to_my_offers(&offer.id))
+}
+
+// ============================
+// WhatsApp bot handlers
+// ============================
+
+async fn whatsapp_outbox(headers: HeaderMap) -> Result<Response, Error> {
+    if !whatsapp::bot_authorized(&headers) {
+        warn!("Rejected WhatsApp outbox request with an invalid token");
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+    let messages: Vec<serde_json::Value> = whatsapp::claim()
+        .await?
+        .into_iter()
+        .map(|OutboxMessage { id, phone, body }| {
+            serde_json::json!({ "id": id.key_string(), "to": phone, "body": body })
+        })
+        .collect();
+    Ok(Json(serde_json::json!({ "messages": messages })).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct DeliveryReport {
+    sent: bool,
+    error: Option<String>,
+}
+
+async fn whatsapp_report(
+    headers: HeaderMap,
+    Path(message_id): Path<String>,
+    Json(report): Json<DeliveryReport>,
+) -> Result<Response, Error> {
+    if !whatsapp::bot_authorized(&headers) {
+        warn!("Rejected WhatsApp delivery report with an invalid token");
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+    whatsapp::mark(&message_id, report.sent, report.error.as_deref()).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -0,0 +1,224 @@
+use askama::Template;
+use axum::{
+    Router,
+    response::{Html, IntoResponse, Response},
+    routing::{get, post},
+};
+use axum_extra::extract::Form;
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, warn};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        completeness::CompletenessModel,
+        person::{Person, SessionUser},
+        production::ProductionModel,
+    },
+    response,
+    services::onboarding::{
+        self, MAX_ROLE_TYPES, OnboardingAnswers, ROLE_TYPES, UNIONS, WorkPreferences, split_list,
+    },
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/welcome", get(welcome_page).post(submit))
+        .route("/welcome/skip", post(skip))
+}
+
+#[derive(Template)]
+#[template(path = "onboarding/welcome.html")]
+pub struct WelcomeTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub error: Option<String>,
+    pub role_types: &'static [(&'static str, &'static str)],
+    pub max_role_types: usize,
+    pub unions: &'static [&'static str],
+    pub project_types: Vec<String>,
+    pub form: WelcomeForm,
+}
+
+impl WelcomeTemplate {
+    fn role_checked(&self, label: &str) -> bool {
+        self.form.role_types.iter().any(|r| r == label)
+    }
+
+    fn union_checked(&self, union: &str) -> bool {
+        self.form.unions.iter().any(|u| u == union)
+    }
+
+    fn project_checked(&self, project: &str) -> bool {
+        self.form.project_types.iter().any(|p| p == project)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct WelcomeForm {
+    #[serde(default)]
+    pub role_types: Vec<String>,
+    #[serde(default)]
+    pub headline: String,
+    #[serde(default)]
+    pub skills: String,
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub unions: Vec<String>,
+    /// Comma-separated unions not in the checkbox list
+    #[serde(default)]
+    pub other_unions: String,
+    #[serde(default)]
+    pub availability: String,
+    #[serde(default)]
+    pub project_types: Vec<String>,
+    pub paid_only: Option<String>,
+    pub remote: Option<String>,
+    pub travel: Option<String>,
+}
+
+fn person_id(id: &str) -> Result<RecordId, Error> {
+    RecordId::parse_simple(id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+async fn render(
+    user: &SessionUser,
+    form: WelcomeForm,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let base = BaseContext::new()
+        .with_page("welcome")
+        .with_user(User::from_session_user(user).await);
+
+    let template = WelcomeTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        error,
+        role_types: ROLE_TYPES,
+        max_role_types: MAX_ROLE_TYPES,
+        unions: UNIONS,
+        project_types: ProductionModel::get_production_types().await?,
+        form,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render onboarding template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn welcome_page(AuthenticatedUser(user): AuthenticatedUser) -> Result<Response, Error> {
+    if !onboarding::needs_onboarding(&person_id(&user.id)?).await {
+        return Ok(response::redirect(&format!("/{}", user.username)));
+    }
+
+    // Start from whatever is already on the profile
+    let person = Person::find_by_id(&user.id).await?.ok_or(Error::NotFound)?;
+    let profile = person.profile.unwrap_or_default();
+    let form = WelcomeForm {
+        headline: profile.headline.unwrap_or_default(),
+        skills: profile.skills.join(", "),
+        location: profile.location.unwrap_or_default(),
+        unions: profile
+            .unions
+            .iter()
+            .filter(|u| UNIONS.contains(&u.as_str()))
+            .cloned()
+            .collect(),
+        other_unions: profile
+            .unions
+            .iter()
+            .filter(|u| !UNIONS.contains(&u.as_str()))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", "),
+        availability: profile.availability.unwrap_or_default(),
+        ..Default::default()
+    };
+    render(&user, form, None).await
+}
+
+async fn submit(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Form(form): Form<WelcomeForm>,
+) -> Result<Response, Error> {
+    let roles: Vec<String> = form
+        .role_types
+        .iter()
+        .filter(|r| ROLE_TYPES.iter().any(|(label, _)| label == r))
+        .cloned()
+        .collect();
+    if roles.is_empty() {
+        return render(
+            &user,
+            form,
+            Some("Pick at least one kind of work you do.".to_string()),
+        )
+        .await;
+    }
+    if roles.len() > MAX_ROLE_TYPES {
+        let message = format!("Pick up to {} kinds of work.", MAX_ROLE_TYPES);
+        return render(&user, form, Some(message)).await;
+    }
+    if !["", "available", "busy", "not_available"].contains(&form.availability.as_str()) {
+        return Err(Error::BadRequest(format!(
+            "Invalid availability: {}",
+            form.availability
+        )));
+    }
+
+    let known_projects = ProductionModel::get_production_types().await?;
+    let mut unions: Vec<String> = form
+        .unions
+        .iter()
+        .filter(|u| UNIONS.contains(&u.as_str()))
+        .cloned()
+        .collect();
+    unions.extend(split_list(&form.other_unions));
+
+    let answers = OnboardingAnswers {
+        headline: Some(form.headline.trim().to_string()),
+        skills: split_list(&form.skills),
+        location: Some(form.location.trim().to_string()),
+        unions,
+        availability: Some(form.availability.clone()),
+        preferences: WorkPreferences {
+            role_types: roles,
+            project_types: form
+                .project_types
+                .iter()
+                .filter(|p| known_projects.contains(p))
+                .cloned()
+                .collect(),
+            paid_only: form.paid_only.is_some(),
+            remote: form.remote.is_some(),
+            travel: form.travel.is_some(),
+        },
+    };
+
+    let person = Person::find_by_id(&user.id).await?.ok_or(Error::NotFound)?;
+    onboarding::complete(&person, answers).await?;
+    if let Err(e) = CompletenessModel::refresh(&person.id).await {
+        warn!(
+            "Failed to rescore completeness for {}: {}",
+            user.username, e
+        );
+    }
+
+    Ok(response::redirect(&format!("/{}", user.username)))
+}
+
+async fn skip(AuthenticatedUser(user): AuthenticatedUser) -> Result<Response, Error> {
+    onboarding::skip(&person_id(&user.id)?).await?;
+    Ok(response::redirect(&format!("/{}", user.username)))
+}
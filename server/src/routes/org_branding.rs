@@ -0,0 +1,253 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query, multipart::Multipart},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use bytes::Bytes;
+use serde::Deserialize;
+use tracing::{error, info, warn};
+use ulid::Ulid;
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        org_branding::{self, DEFAULT_COLOR, OrgBranding, OrgBrandingModel, WORDMARK_MAX_BYTES},
+        organization::{Organization, OrganizationModel},
+        person::SessionUser,
+        portfolio::media_key,
+    },
+    record_id_ext::RecordIdExt,
+    services::s3::s3,
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/orgs/{slug}/branding",
+            get(branding_page).post(save_colors),
+        )
+        .route("/orgs/{slug}/branding/wordmark", post(upload_wordmark))
+        .route(
+            "/orgs/{slug}/branding/wordmark/delete",
+            post(delete_wordmark),
+        )
+}
+
+/// Image types a wordmark can be uploaded as
+const WORDMARK_TYPES: &[&str] = &["image/png", "image/jpeg", "image/webp"];
+
+#[derive(Template)]
+#[template(path = "organizations/branding.html")]
+pub struct BrandingTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub organization_name: String,
+    pub organization_slug: String,
+    pub logo: Option<String>,
+    pub branding: OrgBranding,
+    pub message: Option<String>,
+    pub error: Option<String>,
+}
+
+impl BrandingTemplate {
+    fn default_color(&self) -> &'static str {
+        DEFAULT_COLOR
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BrandingQuery {
+    pub message: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ColorsForm {
+    #[serde(default)]
+    pub color: String,
+    #[serde(default)]
+    pub accent: String,
+}
+
+/// Branding goes on documents sent in the organization's name, so only
+/// owners and admins change it
+async fn require_manager(slug: &str, user: &SessionUser) -> Result<Organization, Error> {
+    let model = OrganizationModel::new();
+    let organization = model.get_by_slug(slug).await?;
+    let role = model
+        .get_member_role(&organization.id.to_raw_string(), &user.id)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    if !matches!(role.as_str(), "owner" | "admin") {
+        return Err(Error::Forbidden);
+    }
+    Ok(organization)
+}
+
+fn back_to_branding(slug: &str, key: &str, text: &str) -> Response {
+    Redirect::to(&format!(
+        "/orgs/{}/branding?{}={}",
+        slug,
+        key,
+        urlencoding::encode(text)
+    ))
+    .into_response()
+}
+
+/// Remove a replaced wordmark's file. The new one is already saved, so a
+/// failure only leaves an orphan behind.
+async fn delete_file(url: &str) {
+    let Some(key) = media_key(url) else {
+        return;
+    };
+    match s3() {
+        Ok(s3) => {
+            if let Err(e) = s3.delete_file(key).await {
+                warn!("Failed to delete old wordmark {}: {}", key, e);
+            }
+        }
+        Err(e) => warn!("S3 unavailable, old wordmark {} kept: {}", key, e),
+    }
+}
+
+async fn branding_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<BrandingQuery>,
+) -> Result<Response, Error> {
+    let organization = require_manager(&slug, &user).await?;
+    let branding = OrgBrandingModel::get(&organization.id).await?;
+
+    let base = BaseContext::new()
+        .with_page("organizations")
+        .with_user(User::from_session_user(&user).await);
+    let template = BrandingTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        organization_name: organization.name,
+        organization_slug: organization.slug,
+        logo: organization.logo,
+        branding,
+        message: query.message,
+        error: query.error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render branding template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn save_colors(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<ColorsForm>,
+) -> Result<Response, Error> {
+    let organization = require_manager(&slug, &user).await?;
+    let (color, accent) = match org_branding::parse_colors(&form.color, &form.accent) {
+        Ok(colors) => colors,
+        Err(Error::Validation(msg)) => return Ok(back_to_branding(&slug, "error", &msg)),
+        Err(e) => return Err(e),
+    };
+
+    OrgBrandingModel::set_colors(&organization.id, color, accent).await?;
+    info!("{} changed the brand colours of {}", user.username, slug);
+    Ok(back_to_branding(&slug, "message", "Colours saved"))
+}
+
+async fn upload_wordmark(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Response, Error> {
+    let organization = require_manager(&slug, &user).await?;
+
+    let mut upload: Option<Bytes> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| Error::bad_request(format!("Failed to read multipart: {}", e)))?
+    {
+        if field.name() != Some("wordmark") {
+            continue;
+        }
+        let content_type = field.content_type().unwrap_or("").to_string();
+        let data = field
+            .bytes()
+            .await
+            .map_err(|e| Error::bad_request(format!("Failed to read file data: {}", e)))?;
+        if data.is_empty() {
+            continue;
+        }
+        if !WORDMARK_TYPES.contains(&content_type.as_str()) {
+            return Ok(back_to_branding(
+                &slug,
+                "error",
+                "Upload the wordmark as a PNG, JPEG or WebP image",
+            ));
+        }
+        if data.len() > WORDMARK_MAX_BYTES {
+            return Ok(back_to_branding(
+                &slug,
+                "error",
+                "The wordmark can be up to 5MB",
+            ));
+        }
+        upload = Some(data);
+    }
+    let Some(data) = upload else {
+        return Ok(back_to_branding(
+            &slug,
+            "error",
+            "Choose an image to upload",
+        ));
+    };
+
+    let png = match tokio::task::spawn_blocking(move || org_branding::process_wordmark(&data))
+        .await
+        .map_err(|e| Error::Internal(format!("Wordmark processing failed: {}", e)))?
+    {
+        Ok(png) => png,
+        Err(Error::Validation(msg)) => return Ok(back_to_branding(&slug, "error", &msg)),
+        Err(e) => return Err(e),
+    };
+
+    let key = format!(
+        "organizations/{}/wordmark_{}.png",
+        organization.slug,
+        Ulid::new()
+    );
+    s3()?
+        .upload_file(&key, Bytes::from(png), "image/png")
+        .await?;
+    let url = format!("/api/media/{}", key);
+
+    let previous = OrgBrandingModel::set_wordmark(&organization.id, Some(url)).await?;
+    if let Some(previous) = previous {
+        delete_file(&previous).await;
+    }
+    info!("{} uploaded a wordmark for {}", user.username, slug);
+    Ok(back_to_branding(&slug, "message", "Wordmark uploaded"))
+}
+
+async fn delete_wordmark(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let organization = require_manager(&slug, &user).await?;
+    if let Some(previous) = OrgBrandingModel::set_wordmark(&organization.id, None).await? {
+        delete_file(&previous).await;
+    }
+    info!("{} removed the wordmark of {}", user.username, slug);
+    Ok(back_to_branding(&slug, "message", "Wordmark removed"))
+}
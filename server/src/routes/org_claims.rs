@@ -0,0 +1,214 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    body::Bytes,
+    extract::{Path, multipart::Multipart},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        organization::{Organization, OrganizationModel},
+        person::SessionUser,
+    },
+    services::{
+        id_verification::MAX_DOCUMENT_SIZE,
+        org_claims::{self, ClaimStatus, DOCUMENT_KINDS},
+    },
+    templates::{BaseContext, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/orgs/{slug}/claim", get(claim_page))
+        .route("/orgs/{slug}/claim/email", post(start_email_claim))
+        .route("/orgs/{slug}/claim/code", post(confirm_claim_code))
+        .route("/orgs/{slug}/claim/document", post(submit_claim_document))
+}
+
+#[derive(Template)]
+#[template(path = "organizations/claim.html")]
+pub struct OrganizationClaimTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub organization: Organization,
+    pub claimable: bool,
+    /// Email domain a work address must be on, when the organization has one
+    pub domain: Option<String>,
+    pub claim: Option<ClaimStatus>,
+    pub awaiting_review: bool,
+    pub code_sent: bool,
+    pub document_kinds: Vec<SelectOption>,
+    pub error: Option<String>,
+}
+
+fn parse_person_rid(person_id: &str) -> Option<RecordId> {
+    if person_id.starts_with("person:") {
+        RecordId::parse_simple(person_id).ok()
+    } else {
+        Some(RecordId::new("person", person_id))
+    }
+}
+
+async fn render_page(user: &SessionUser, organization: Organization, error: Option<String>) -> Result<Response, Error> {
+    let person = parse_person_rid(&user.id)
+        .ok_or_else(|| Error::BadRequest("Invalid person ID".to_string()))?;
+    let claim = org_claims::status(&organization.id, &person).await?;
+    let claimable = org_claims::is_claimable(&organization.id).await?;
+    let status = claim.as_ref().map(|c| c.status.as_str());
+
+    let base = BaseContext::new()
+        .with_page("organization-claim")
+        .with_user(User::from_session_user(user).await);
+    let template = OrganizationClaimTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        domain: org_claims::org_domain(
+            organization.website.as_deref(),
+            organization.contact_email.as_deref(),
+        ),
+        organization,
+        claimable,
+        awaiting_review: status == Some("pending"),
+        code_sent: status == Some("pending_email"),
+        claim,
+        document_kinds: DOCUMENT_KINDS
+            .iter()
+            .map(|(value, label)| SelectOption::new(value, label.to_string(), false))
+            .collect(),
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render organization claim template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn claim_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let organization = OrganizationModel::new().get_by_slug(&slug).await?;
+    render_page(&user, organization, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaimEmailForm {
+    email: String,
+}
+
+async fn start_email_claim(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<ClaimEmailForm>,
+) -> Result<Response, Error> {
+    let organization = OrganizationModel::new().get_by_slug(&slug).await?;
+    let person = parse_person_rid(&user.id)
+        .ok_or_else(|| Error::BadRequest("Invalid person ID".to_string()))?;
+
+    match org_claims::start_email(&organization, &person, &form.email).await {
+        Ok(()) => Ok(Redirect::to(&format!("/orgs/{}/claim", slug)).into_response()),
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => {
+            render_page(&user, organization, Some(msg)).await
+        }
+        Err(Error::ExternalService(e)) => {
+            error!("Failed to send claim code for {}: {}", slug, e);
+            render_page(
+                &user,
+                organization,
+                Some("We couldn't send the code. Please try again.".to_string()),
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaimCodeForm {
+    code: String,
+}
+
+async fn confirm_claim_code(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<ClaimCodeForm>,
+) -> Result<Response, Error> {
+    let organization = OrganizationModel::new().get_by_slug(&slug).await?;
+    let person = parse_person_rid(&user.id)
+        .ok_or_else(|| Error::BadRequest("Invalid person ID".to_string()))?;
+
+    match org_claims::confirm_code(&organization.id, &person, &form.code).await {
+        Ok(()) => {
+            info!("{} sent a claim on {} for review", user.username, slug);
+            Ok(Redirect::to(&format!("/orgs/{}/claim", slug)).into_response())
+        }
+        Err(Error::Validation(msg)) => render_page(&user, organization, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+async fn submit_claim_document(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Response, Error> {
+    let organization = OrganizationModel::new().get_by_slug(&slug).await?;
+    let person = parse_person_rid(&user.id)
+        .ok_or_else(|| Error::BadRequest("Invalid person ID".to_string()))?;
+
+    let mut kind = String::new();
+    let mut document: Option<(String, Bytes)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| Error::bad_request(format!("Failed to read multipart: {}", e)))?
+    {
+        match field.name().unwrap_or("") {
+            "document_kind" => {
+                kind = field.text().await.unwrap_or_default();
+            }
+            "document" => {
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|e| Error::bad_request(format!("Failed to read file data: {}", e)))?;
+                if data.len() > MAX_DOCUMENT_SIZE {
+                    return render_page(&user, organization, Some("File too large. Maximum size is 10MB".to_string())).await;
+                }
+                document = Some((content_type, data));
+            }
+            _ => {}
+        }
+    }
+
+    let Some((content_type, data)) = document else {
+        return render_page(&user, organization, Some("Choose a file to upload".to_string())).await;
+    };
+
+    match org_claims::submit_document(&organization.id, &person, &kind, &content_type, data).await {
+        Ok(()) => Ok(Redirect::to(&format!("/orgs/{}/claim", slug)).into_response()),
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => {
+            render_page(&user, organization, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
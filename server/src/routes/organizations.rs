@@ -13,10 +13,12 @@ use tracing::{debug, error, info};
 use crate::{
     error::Error,
     middleware::{AuthenticatedUser, UserExtractor},
+    models::company_credit::{CompanyCreditModel, SlateEntry},
     models::organization::{
         CreateOrganizationData, Organization, OrganizationMember, OrganizationModel,
         UpdateOrganizationData,
     },
+    models::representation::{RepresentationModel, RepresentationRequest},
     record_id_ext::RecordIdExt,
     services::embedding::generate_embedding_async,
     services::search_log::log_search,
@@ -62,6 +64,14 @@ pub fn router() -> Router {
             "/orgs/{slug}/join-requests/{member_id}/reject",
             post(reject_join_request),
         )
+        .route(
+            "/orgs/{slug}/representation/{request_id}/accept",
+            post(accept_representation),
+        )
+        .route(
+            "/orgs/{slug}/representation/{request_id}/decline",
+            post(decline_representation),
+        )
         // API endpoints
         .route("/api/orgs/more-sse", get(orgs_more_sse))
         .route(
@@ -108,6 +118,7 @@ pub struct UpdateOrganizationForm {
     pub services: Option<String>,        // Comma-separated
     pub founded_year: Option<String>,    // Parse to i32 manually
     pub employees_count: Option<String>, // Parse to i32 manually
+    pub coordinates: Option<String>,     // "latitude, longitude"
     pub public: Option<String>,               // Checkbox value "on" or None
     pub allow_join_requests: Option<String>,  // Checkbox value "on" or None
 }
@@ -184,10 +195,16 @@ pub struct OrganizationProfileTemplate {
     pub description_html: Option<String>,
     pub members: Vec<OrganizationMember>,
     pub join_requests: Vec<OrganizationMember>,
+    /// People asking the organization to confirm it represents them
+    pub representation_requests: Vec<RepresentationRequest>,
     pub is_member: bool,
     pub is_admin: bool,
     pub is_owner: bool,
     pub has_pending_request: bool,
+    /// No real owner yet, so signed-in visitors can claim it
+    pub is_claimable: bool,
+    /// Productions the organization is credited on, most recent first
+    pub slate: Vec<SlateEntry>,
 }
 
 #[derive(Template)]
@@ -495,6 +512,12 @@ async fn organization_profile(
         vec![]
     };
 
+    let representation_requests = if is_admin || is_owner {
+        RepresentationModel::pending_for_org(&organization.id).await?
+    } else {
+        vec![]
+    };
+
     // Double-check pending request from members list (in case membership lookup missed it)
     if !has_pending_request && !is_member {
         if let Some(user) = &user_opt {
@@ -510,6 +533,22 @@ async fn organization_profile(
         }
     }
 
+    let is_claimable = user_opt.is_some()
+        && !is_owner
+        && crate::services::org_claims::is_claimable(&organization.id)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Failed to check whether {} is claimable: {}", slug, e);
+                false
+            });
+
+    let slate = CompanyCreditModel::slate_for_organization(&organization.id)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Failed to load production slate for {}: {}", slug, e);
+            Vec::new()
+        });
+
     let description_html = organization
         .description
         .as_deref()
@@ -525,10 +564,13 @@ async fn organization_profile(
         description_html,
         members,
         join_requests,
+        representation_requests,
         is_member,
         is_admin,
         is_owner,
         has_pending_request,
+        is_claimable,
+        slate,
     };
 
     Ok(Html(template.render().map_err(|e| {
@@ -619,6 +661,18 @@ async fn update_organization(
         .filter(|s| !s.is_empty())
         .and_then(|s| s.parse::<i32>().ok());
 
+    // Coordinates are optional; an empty field clears them
+    let coordinates = match data.coordinates.as_deref().map(str::trim) {
+        None => None,
+        Some("") => Some(None),
+        Some(text) => Some(Some(crate::units::parse_coordinates(text).ok_or_else(|| {
+            Error::Validation(
+                "Enter coordinates as latitude, longitude (for example 34.0522, -118.2437)"
+                    .to_string(),
+            )
+        })?)),
+    };
+
     // Prepare update data
     let update_data = UpdateOrganizationData {
         name: data.name,
@@ -639,6 +693,9 @@ async fn update_organization(
     model
         .update(&organization.id.to_raw_string(), update_data)
         .await?;
+    if let Some(coordinates) = coordinates {
+        model.set_coordinates(&organization.id, coordinates).await?;
+    }
 
     info!("Organization '{}' updated by user {}", slug, user.id);
 
@@ -1000,6 +1057,62 @@ async fn reject_join_request(
     Ok(Redirect::to(&format!("/orgs/{}", slug)).into_response())
 }
 
+/// Confirm the organization represents someone; the admin confirming becomes
+/// their contact for inquiries
+async fn accept_representation(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, request_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let model = OrganizationModel::new();
+    let organization = model.get_by_slug(&slug).await?;
+
+    let user_role = model
+        .get_member_role(&organization.id.to_raw_string(), &user.id)
+        .await?;
+    match user_role.as_deref() {
+        Some("owner") | Some("admin") => {}
+        _ => return Err(Error::Forbidden),
+    }
+
+    let contact = surrealdb::types::RecordId::parse_simple(&user.id)
+        .map_err(|e| Error::BadRequest(e.to_string()))?;
+    RepresentationModel::accept(&organization.id, &request_id, &contact).await?;
+    clear_representation_notifications(&request_id).await;
+    info!("{} confirmed representation {} for {}", user.username, request_id, slug);
+
+    Ok(Redirect::to(&format!("/orgs/{}", slug)).into_response())
+}
+
+async fn decline_representation(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, request_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let model = OrganizationModel::new();
+    let organization = model.get_by_slug(&slug).await?;
+
+    let user_role = model
+        .get_member_role(&organization.id.to_raw_string(), &user.id)
+        .await?;
+    match user_role.as_deref() {
+        Some("owner") | Some("admin") => {}
+        _ => return Err(Error::Forbidden),
+    }
+
+    RepresentationModel::decline(&organization.id, &request_id).await?;
+    clear_representation_notifications(&request_id).await;
+
+    Ok(Redirect::to(&format!("/orgs/{}", slug)).into_response())
+}
+
+/// Remove the request notification from every admin's inbox once it's answered
+async fn clear_representation_notifications(request_id: &str) {
+    let related_id = surrealdb::types::RecordId::new("represented_by", request_id).to_raw_string();
+    let notification_model = crate::models::notification::NotificationModel::new();
+    let _ = notification_model
+        .delete_by_related(&related_id, "representation_request")
+        .await;
+}
+
 #[derive(Debug, Deserialize)]
 struct MoreQuery {
     offset: usize,
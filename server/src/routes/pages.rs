@@ -9,7 +9,7 @@ use crate::{
     middleware::UserExtractor,
     templates::{
         AboutTemplate, Activity, BaseContext, ImpressumTemplate, IndexTemplate, PrivacyTemplate,
-        TermsTemplate, User,
+        StatusTemplate, TermsTemplate, User,
     },
 };
 
@@ -21,6 +21,7 @@ pub fn router() -> Router {
         .route("/privacy", get(privacy))
         .route("/impressum", get(impressum))
         .route("/healthcheck", get(healthcheck))
+        .route("/status", get(status_page))
         .route("/robots.txt", get(robots_txt))
         .route("/llms.txt", get(llms_txt))
         .route("/sitemap.xml", get(sitemap_xml))
@@ -236,7 +237,7 @@ async fn profiles_ticker_sse(
 
     let query = format!(
         "SELECT username, profile.name AS name, profile.headline AS headline, profile.avatar AS avatar \
-         FROM person WHERE profile.avatar IS NOT NONE AND profile.headline IS NOT NONE AND verification_status = 'identity'{} \
+         FROM person WHERE profile.avatar IS NOT NONE AND profile.headline IS NOT NONE AND verification_status = 'identity' AND (visibility ?? 'public') = 'public'{} \
          ORDER BY rand() LIMIT {};",
         exclude_clause, count
     );
@@ -485,6 +486,7 @@ async fn sitemap_xml() -> Response {
         ("/orgs", "0.8", "daily"),
         ("/locations", "0.8", "daily"),
         ("/jobs", "0.9", "daily"),
+        ("/help", "0.5", "monthly"),
         ("/terms", "0.3", "yearly"),
         ("/privacy", "0.3", "yearly"),
         ("/impressum", "0.3", "yearly"),
@@ -496,6 +498,14 @@ async fn sitemap_xml() -> Response {
         ));
     }
 
+    // Help articles
+    for article in crate::services::help::articles() {
+        urls.push(format!(
+            "  <url>\n    <loc>{base}/help/{}</loc>\n    <changefreq>monthly</changefreq>\n    <priority>0.4</priority>\n  </url>",
+            article.slug
+        ));
+    }
+
     // Dynamic entries — single query for all entity types
     if let Ok(mut result) = DB
         .query(
@@ -575,23 +585,48 @@ async fn sitemap_xml() -> Response {
         .into_response()
 }
 
-async fn healthcheck() -> impl IntoResponse {
-    use crate::{version, stats};
+async fn status_page(request: Request) -> Result<Html<String>, Error> {
+    use crate::services::status;
 
-    // Check DB
-    let db_ok = crate::db::DB
-        .query("RETURN true")
-        .await
-        .is_ok();
+    let mut base = BaseContext::new().with_page("status");
+    if let Some(user) = request.get_user() {
+        base = base.with_user(User::from_session_user(&user).await);
+    }
 
-    // Check S3
-    let s3_ok = match crate::services::s3::s3() {
-        Ok(s3) => s3.file_exists("_healthcheck").await.is_ok(),
-        Err(_) => false,
+    let components = status::cached_readiness().await.components();
+    let overall = status::overall(&components);
+    let notices = status::list_active().await.unwrap_or_else(|e| {
+        error!("Failed to load status notices: {}", e);
+        Vec::new()
+    });
+    let resolved = status::list_recent_resolved().await.unwrap_or_default();
+
+    let template = StatusTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        overall,
+        components,
+        notices,
+        resolved,
     };
 
-    let disk_low = stats::disk_space_low();
-    let all_ok = db_ok && s3_ok && !disk_low;
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render status page: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+async fn healthcheck() -> impl IntoResponse {
+    use crate::{version, stats};
+
+    let readiness = crate::services::status::readiness().await;
+    let db_ok = readiness.database;
+    let s3_ok = readiness.storage;
+    let disk_low = readiness.disk_low;
+    let all_ok = readiness.all_ok();
     let system_stats = stats::get_stats().await;
 
     // Get binary file size
@@ -618,7 +653,9 @@ async fn healthcheck() -> impl IntoResponse {
             "database": if db_ok { "ok" } else { "error" },
             "s3": if s3_ok { "ok" } else { "error" },
             "disk": if disk_low { "warning: < 5 GB free" } else { "ok" },
+            "search": if readiness.search { "ok" } else { "warning: keyword only" },
         },
+        "embedding_backlog": readiness.embedding_backlog,
         "stats": system_stats,
     });
 
@@ -0,0 +1,241 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::Path,
+    http::header,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use axum_extra::extract::Form;
+use serde::Deserialize;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        person::{Person, SessionUser},
+        portfolio::{self, PortfolioModel, PortfolioOptions},
+    },
+    record_id_ext::RecordIdExt,
+    services::s3::s3,
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/profile/portfolio", get(portfolio_page).post(export_portfolio))
+        .route("/profile/portfolio/{export_id}/download", get(download_export))
+        .route("/profile/portfolio/{export_id}/delete", post(delete_export))
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct StillOption {
+    pub url: String,
+    pub thumbnail_url: String,
+    pub caption: String,
+    pub selected: bool,
+}
+
+pub struct ExportRow {
+    pub id: String,
+    pub created_at: String,
+    pub page_size: String,
+    pub photos: usize,
+    pub size: String,
+}
+
+#[derive(Template)]
+#[template(path = "persons/portfolio.html")]
+pub struct PortfolioTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub username: String,
+    pub has_headshot: bool,
+    pub has_phone: bool,
+    pub page_sizes: Vec<(String, String)>,
+    pub page_size: String,
+    pub stills: Vec<StillOption>,
+    pub max_stills: usize,
+    pub exports: Vec<ExportRow>,
+    pub error: Option<String>,
+}
+
+// ============================
+// Helpers
+// ============================
+
+fn page_size_label(value: &str) -> String {
+    portfolio::PAGE_SIZES
+        .iter()
+        .find(|(v, _)| *v == value)
+        .map(|(_, label)| label.to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn format_size(bytes: i64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    } else {
+        format!("{} KB", (bytes / 1024).max(1))
+    }
+}
+
+async fn render_page(
+    user: &SessionUser,
+    person: &Person,
+    options: &PortfolioOptions,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let profile = person.profile.clone().unwrap_or_default();
+    let stills = profile
+        .photos
+        .iter()
+        .map(|photo| StillOption {
+            url: photo.url.clone(),
+            thumbnail_url: photo.thumbnail_url.clone(),
+            caption: photo.caption.clone(),
+            selected: options.stills.contains(&photo.url),
+        })
+        .collect();
+    let exports = PortfolioModel::for_person(&person.id)
+        .await?
+        .into_iter()
+        .map(|export| ExportRow {
+            id: export.id.key_string(),
+            created_at: export.created_at.format("%b %-d, %Y at %H:%M UTC").to_string(),
+            page_size: page_size_label(&export.page_size),
+            photos: export.stills.len(),
+            size: format_size(export.size),
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("profile")
+        .with_user(User::from_session_user(user).await);
+    let template = PortfolioTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        username: person.username.clone(),
+        has_headshot: profile.avatar.is_some(),
+        has_phone: profile.phone.as_ref().is_some_and(|p| !p.is_empty()) && !person.is_minor,
+        page_sizes: portfolio::PAGE_SIZES
+            .iter()
+            .map(|(value, label)| (value.to_string(), label.to_string()))
+            .collect(),
+        page_size: options.page_size.clone(),
+        stills,
+        max_stills: portfolio::MAX_STILLS,
+        exports,
+        error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render portfolio template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn current_person(user: &SessionUser) -> Result<Person, Error> {
+    Person::find_by_id(&user.id).await?.ok_or(Error::NotFound)
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn portfolio_page(AuthenticatedUser(user): AuthenticatedUser) -> Result<Response, Error> {
+    let person = current_person(&user).await?;
+    // Offer the choices made last time, or the first few photos
+    let options = match PortfolioModel::for_person(&person.id).await?.into_iter().next() {
+        Some(last) => PortfolioOptions {
+            page_size: last.page_size,
+            stills: last.stills,
+            include_phone: false,
+        },
+        None => PortfolioOptions {
+            page_size: "letter".to_string(),
+            stills: person
+                .profile
+                .as_ref()
+                .map(|p| p.photos.iter().take(4).map(|photo| photo.url.clone()).collect())
+                .unwrap_or_default(),
+            include_phone: false,
+        },
+    };
+    render_page(&user, &person, &options, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportForm {
+    page_size: String,
+    #[serde(default)]
+    stills: Vec<String>,
+    include_phone: Option<String>,
+}
+
+async fn export_portfolio(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Form(form): Form<ExportForm>,
+) -> Result<Response, Error> {
+    let person = current_person(&user).await?;
+    let options = PortfolioOptions {
+        page_size: form.page_size,
+        stills: form.stills,
+        include_phone: form.include_phone.is_some(),
+    };
+    match PortfolioModel::create(&person, options.clone()).await {
+        Ok(export) => {
+            info!("{} exported portfolio {}", user.username, export.id.key_string());
+            Ok(Redirect::to(&format!("/profile/portfolio#export-{}", export.id.key_string())).into_response())
+        }
+        Err(Error::Validation(msg)) => render_page(&user, &person, &options, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+async fn download_export(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(export_id): Path<String>,
+) -> Result<Response, Error> {
+    let person = current_person(&user).await?;
+    let export = PortfolioModel::get(&export_id, &person.id).await?;
+    let (data, _) = s3()?.download_file(&export.file_key).await?;
+    let filename = format!(
+        "{}-portfolio-{}.pdf",
+        person.username,
+        export.created_at.format("%Y-%m-%d")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+            (header::CACHE_CONTROL, "private, no-store".to_string()),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+async fn delete_export(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(export_id): Path<String>,
+) -> Result<Response, Error> {
+    let person = current_person(&user).await?;
+    let export = PortfolioModel::get(&export_id, &person.id).await?;
+    PortfolioModel::delete(&export).await?;
+    Ok(Redirect::to("/profile/portfolio").into_response())
+}
@@ -0,0 +1,396 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::{Path, Query, Request},
+    http::header,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::get,
+};
+use axum_extra::extract::Form;
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::{AuthenticatedUser, UserExtractor},
+    models::{
+        festival_submission::FestivalSubmission,
+        org_branding::{Brand, OrgBrandingModel},
+        press_kit::{self, PressContact, PressCredit, PressKitData, PressKitModel},
+        production::{Production, ProductionModel, ProductionPhoto},
+    },
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/productions/{slug}/press", get(press_page))
+        .route("/productions/{slug}/press/kit.zip", get(download_kit))
+        .route(
+            "/productions/{slug}/press/edit",
+            get(edit_page).post(save_kit),
+        )
+}
+
+// ============================
+// Views
+// ============================
+
+/// The public press kit. It has a look of its own rather than the site's
+/// layout, so it reads well when linked from a festival or press release.
+#[derive(Template)]
+#[template(path = "productions/press_kit.html")]
+pub struct PressKitTemplate {
+    pub app_name: String,
+    pub version: String,
+    pub production_slug: String,
+    pub title: String,
+    pub tagline: String,
+    pub poster: Option<String>,
+    pub logline: Option<String>,
+    pub synopsis: Option<String>,
+    pub statement: Option<String>,
+    /// Player URL for the trailer
+    pub trailer_embed: Option<String>,
+    pub stills: Vec<ProductionPhoto>,
+    pub cast: Vec<PressCredit>,
+    pub crew: Vec<PressCredit>,
+    pub companies: Vec<PressCredit>,
+    pub selections: Vec<FestivalSubmission>,
+    pub contacts: Vec<PressContact>,
+    /// Members previewing a kit that isn't published yet
+    pub is_draft: bool,
+    pub can_edit: bool,
+    /// The owning organization's colours and wordmark
+    pub brand: Option<Brand>,
+}
+
+pub struct StillOption {
+    pub url: String,
+    pub thumbnail_url: String,
+    pub caption: String,
+    pub selected: bool,
+}
+
+#[derive(Template)]
+#[template(path = "productions/press_kit_edit.html")]
+pub struct PressKitEditTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub published: bool,
+    pub logline: String,
+    pub synopsis: String,
+    pub statement: String,
+    pub trailer_url: String,
+    pub stills: Vec<StillOption>,
+    /// The saved contacts, then blank rows up to the limit
+    pub contacts: Vec<PressContact>,
+    pub max_stills: usize,
+    pub max_logline: usize,
+    pub max_text: usize,
+    pub max_contact: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EditQuery {
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PressKitForm {
+    pub published: Option<String>,
+    #[serde(default)]
+    pub logline: String,
+    #[serde(default)]
+    pub synopsis: String,
+    #[serde(default)]
+    pub statement: String,
+    #[serde(default)]
+    pub trailer_url: String,
+    #[serde(default)]
+    pub stills: Vec<String>,
+    #[serde(default)]
+    pub contact_role: Vec<String>,
+    #[serde(default)]
+    pub contact_name: Vec<String>,
+    #[serde(default)]
+    pub contact_email: Vec<String>,
+    #[serde(default)]
+    pub contact_phone: Vec<String>,
+}
+
+impl PressKitForm {
+    fn parse(&self, production: &Production) -> Result<PressKitData, Error> {
+        let contacts = press_kit::parse_contacts(
+            &self.contact_role,
+            &self.contact_name,
+            &self.contact_email,
+            &self.contact_phone,
+        )?;
+        let gallery: Vec<String> = production.photos.iter().map(|p| p.url.clone()).collect();
+        PressKitData::parse(
+            self.published.is_some(),
+            &self.logline,
+            &self.synopsis,
+            &self.statement,
+            &self.trailer_url,
+            &self.stills,
+            &gallery,
+            contacts,
+        )
+    }
+}
+
+// ============================
+// Access
+// ============================
+
+async fn require_editor(slug: &str, user_id: &str) -> Result<(Production, RecordId), Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    if !ProductionModel::can_edit(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    let person = RecordId::parse_simple(user_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    Ok((production, person))
+}
+
+/// A published kit is public; members can also see it while it's a draft.
+/// Anyone else gets a 404 either way. Returns whether the viewer can edit it.
+async fn viewer_access(
+    production: &Production,
+    published: bool,
+    viewer: Option<&str>,
+) -> Result<bool, Error> {
+    let Some(user_id) = viewer else {
+        return if published {
+            Ok(false)
+        } else {
+            Err(Error::NotFound)
+        };
+    };
+    let can_edit = ProductionModel::can_edit(&production.id, user_id).await?;
+    if !published && !can_edit && !ProductionModel::is_member(&production.id, user_id).await? {
+        return Err(Error::NotFound);
+    }
+    Ok(can_edit)
+}
+
+fn back_to_edit(slug: &str, error: Option<&str>) -> Response {
+    match error {
+        Some(message) => Redirect::to(&format!(
+            "/productions/{}/press/edit?error={}",
+            slug,
+            urlencoding::encode(message)
+        ))
+        .into_response(),
+        None => Redirect::to(&format!("/productions/{}/press", slug)).into_response(),
+    }
+}
+
+/// The poster shown on the kit and packed into the zip
+fn poster(production: &Production) -> Option<String> {
+    production
+        .poster_photo
+        .clone()
+        .or_else(|| production.poster_url.clone())
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn press_page(Path(slug): Path<String>, request: Request) -> Result<Response, Error> {
+    let production = ProductionModel::get_by_slug(&slug).await?;
+    let kit = PressKitModel::for_production(&production.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let viewer = request.get_user();
+    let can_edit = viewer_access(
+        &production,
+        kit.published,
+        viewer.as_ref().map(|u| u.id.as_str()),
+    )
+    .await?;
+    let content = PressKitModel::content(&production, &kit).await?;
+    let brand = OrgBrandingModel::for_production(&production.id).await?;
+
+    let base = BaseContext::new();
+    let template = PressKitTemplate {
+        app_name: base.app_name,
+        version: base.version,
+        production_slug: production.slug.clone(),
+        poster: poster(&production),
+        trailer_embed: content
+            .trailer_url
+            .as_deref()
+            .and_then(press_kit::trailer_embed),
+        title: content.title,
+        tagline: content.tagline,
+        logline: content.logline,
+        synopsis: content.synopsis,
+        statement: content.statement,
+        stills: content.stills,
+        cast: content.cast,
+        crew: content.crew,
+        companies: content.companies,
+        selections: content.selections,
+        contacts: content.contacts,
+        is_draft: !kit.published,
+        can_edit,
+        brand,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render press kit template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn download_kit(Path(slug): Path<String>, request: Request) -> Result<Response, Error> {
+    let production = ProductionModel::get_by_slug(&slug).await?;
+    let kit = PressKitModel::for_production(&production.id)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let viewer = request.get_user();
+    viewer_access(
+        &production,
+        kit.published,
+        viewer.as_ref().map(|u| u.id.as_str()),
+    )
+    .await?;
+    let content = PressKitModel::content(&production, &kit).await?;
+    let data = PressKitModel::zip(&content, poster(&production).as_deref()).await?;
+    // Drafts shouldn't be kept by shared caches
+    let cache = if kit.published {
+        "public, max-age=300"
+    } else {
+        "private, no-store"
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", press_kit::zip_name(&slug)),
+            ),
+            (header::CACHE_CONTROL, cache.to_string()),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+async fn edit_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<EditQuery>,
+) -> Result<Response, Error> {
+    let (production, _) = require_editor(&slug, &user.id).await?;
+    let kit = PressKitModel::for_production(&production.id).await?;
+
+    // A new kit starts from the production's description and first photos
+    let (published, logline, synopsis, statement, trailer_url, picked, mut contacts) = match kit {
+        Some(kit) => (
+            kit.published,
+            kit.logline.unwrap_or_default(),
+            kit.synopsis.unwrap_or_default(),
+            kit.statement.unwrap_or_default(),
+            kit.trailer_url.unwrap_or_default(),
+            kit.stills,
+            kit.contacts,
+        ),
+        None => (
+            false,
+            String::new(),
+            production.description.clone().unwrap_or_default(),
+            String::new(),
+            String::new(),
+            production
+                .photos
+                .iter()
+                .take(press_kit::MAX_STILLS)
+                .map(|photo| photo.url.clone())
+                .collect(),
+            Vec::new(),
+        ),
+    };
+    while contacts.len() < press_kit::MAX_CONTACTS {
+        contacts.push(PressContact {
+            role: String::new(),
+            name: String::new(),
+            email: None,
+            phone: None,
+        });
+    }
+    let stills = production
+        .photos
+        .iter()
+        .map(|photo| StillOption {
+            url: photo.url.clone(),
+            thumbnail_url: photo.thumbnail_url.clone(),
+            caption: photo.caption.clone(),
+            selected: picked.contains(&photo.url),
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(&user).await);
+    let template = PressKitEditTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        published,
+        logline,
+        synopsis,
+        statement,
+        trailer_url,
+        stills,
+        contacts,
+        max_stills: press_kit::MAX_STILLS,
+        max_logline: press_kit::MAX_LOGLINE_CHARS,
+        max_text: press_kit::MAX_TEXT_CHARS,
+        max_contact: press_kit::MAX_CONTACT_CHARS,
+        error: query.error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render press kit edit template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn save_kit(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<PressKitForm>,
+) -> Result<Response, Error> {
+    let (production, person) = require_editor(&slug, &user.id).await?;
+    let data = match form.parse(&production) {
+        Ok(data) => data,
+        Err(Error::Validation(message)) => return Ok(back_to_edit(&slug, Some(&message))),
+        Err(e) => return Err(e),
+    };
+    PressKitModel::save(&production.id, &data, &person).await?;
+    info!(
+        "{} saved the press kit for {} ({})",
+        user.username,
+        slug,
+        if data.published { "published" } else { "draft" }
+    );
+    Ok(back_to_edit(&slug, None))
+}
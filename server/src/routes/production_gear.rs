@@ -0,0 +1,287 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query},
+    http::header,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        production::{Production, ProductionModel},
+        production_gear::{self, ManifestLine, ProductionGearModel},
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/productions/{slug}/gear", get(gear_page).post(assign_gear))
+        .route("/productions/{slug}/gear/manifest.csv", get(export_csv))
+        .route("/productions/{slug}/gear/manifest.pdf", get(export_pdf))
+        .route(
+            "/productions/{slug}/gear/{assignment_id}/remove",
+            post(remove_gear),
+        )
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct GearOption {
+    pub id: String,
+    /// "Canon C70 (SN 1234)"
+    pub label: String,
+}
+
+#[derive(Template)]
+#[template(path = "productions/gear.html")]
+pub struct GearTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub can_edit: bool,
+    pub lines: Vec<ManifestLine>,
+    /// "12,500.00"
+    pub total_value: String,
+    pub missing_values: usize,
+    /// The viewer's own gear not on the production yet
+    pub options: Vec<GearOption>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GearQuery {
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignForm {
+    #[serde(default)]
+    pub equipment_id: String,
+    #[serde(default)]
+    pub start_date: String,
+    #[serde(default)]
+    pub end_date: String,
+}
+
+// ============================
+// Access
+// ============================
+
+struct Access {
+    production: Production,
+    person: RecordId,
+    can_edit: bool,
+}
+
+/// The gear list is for the production's members; owners and admins manage it
+async fn require_member(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    let person = RecordId::parse_simple(user_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    let can_edit = ProductionModel::can_edit(&production.id, user_id).await?;
+    if !can_edit && !ProductionModel::is_member(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok(Access {
+        production,
+        person,
+        can_edit,
+    })
+}
+
+async fn require_editor(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let access = require_member(slug, user_id).await?;
+    if !access.can_edit {
+        return Err(Error::Forbidden);
+    }
+    Ok(access)
+}
+
+fn back_to_gear(slug: &str, error: Option<&str>) -> Response {
+    match error {
+        Some(message) => Redirect::to(&format!(
+            "/productions/{}/gear?error={}",
+            slug,
+            urlencoding::encode(message)
+        ))
+        .into_response(),
+        None => Redirect::to(&format!("/productions/{}/gear", slug)).into_response(),
+    }
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn gear_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<GearQuery>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let lines = ProductionGearModel::manifest(&access.production.id).await?;
+    let options = if access.can_edit {
+        ProductionGearModel::assignable(&access.person)
+            .await?
+            .into_iter()
+            .filter(|e| !lines.iter().any(|l| l.equipment == e.id))
+            .map(|e| GearOption {
+                id: e.id.key_string(),
+                label: match e.serial_number.as_deref().filter(|s| !s.is_empty()) {
+                    Some(serial) => format!("{} (SN {})", e.name, serial),
+                    None => e.name,
+                },
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let (total, missing_values) = production_gear::manifest_total(&lines);
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(&user).await);
+    let template = GearTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: access.production.title,
+        production_slug: access.production.slug,
+        can_edit: access.can_edit,
+        lines,
+        total_value: production_gear::format_value(total),
+        missing_values,
+        options,
+        error: query.error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render gear template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn assign_gear(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<AssignForm>,
+) -> Result<Response, Error> {
+    let access = require_editor(&slug, &user.id).await?;
+    // Gear already on the production can be rebooked by any of its editors;
+    // new gear has to be the editor's own
+    let on_production = ProductionGearModel::manifest(&access.production.id)
+        .await?
+        .into_iter()
+        .map(|l| l.equipment)
+        .find(|id| id.key_string() == form.equipment_id);
+    let equipment = match on_production {
+        Some(id) => id,
+        None => match ProductionGearModel::assignable(&access.person)
+            .await?
+            .into_iter()
+            .find(|e| e.id.key_string() == form.equipment_id)
+        {
+            Some(equipment) => equipment.id,
+            None => {
+                return Ok(back_to_gear(
+                    &slug,
+                    Some("Pick a piece of your own or your organization's equipment"),
+                ));
+            }
+        },
+    };
+    let result = async {
+        let dates = production_gear::clean_booking_dates(&form.start_date, &form.end_date)?;
+        ProductionGearModel::assign(&access.production.id, &equipment, dates, &access.person).await
+    }
+    .await;
+    match result {
+        Ok(()) => {
+            info!(
+                "{} assigned {} to {}",
+                user.username,
+                equipment.display(),
+                slug
+            );
+            Ok(back_to_gear(&slug, None))
+        }
+        Err(Error::Validation(message)) => Ok(back_to_gear(&slug, Some(&message))),
+        Err(e) => Err(e),
+    }
+}
+
+async fn remove_gear(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, assignment_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_editor(&slug, &user.id).await?;
+    ProductionGearModel::remove(&access.production.id, &assignment_id).await?;
+    info!(
+        "{} removed gear {} from {}",
+        user.username, assignment_id, slug
+    );
+    Ok(back_to_gear(&slug, None))
+}
+
+/// The manifest as CSV for insurers
+async fn export_csv(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let lines = ProductionGearModel::manifest(&access.production.id).await?;
+    let csv = production_gear::manifest_csv(&lines);
+    let filename = format!("{}-equipment-manifest.csv", slug);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        csv,
+    )
+        .into_response())
+}
+
+/// The manifest as a PDF for insurers
+async fn export_pdf(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let lines = ProductionGearModel::manifest(&access.production.id).await?;
+    let prepared = chrono::Utc::now().format("%B %-d, %Y").to_string();
+    let pdf = production_gear::manifest_pdf(&access.production.title, &lines, &prepared);
+    let filename = format!("{}-equipment-manifest.pdf", slug);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", filename),
+            ),
+        ],
+        pdf,
+    )
+        .into_response())
+}
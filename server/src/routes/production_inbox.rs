@@ -0,0 +1,270 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    body::Bytes,
+    extract::{Path, Query},
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::{
+    config,
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        deliverable,
+        production::{Production, ProductionModel},
+        production_inbox::{self, InboundEmail, InboxFile, InboxMessage, ProductionInboxModel},
+        reaction::ReactionModel,
+    },
+    record_id_ext::RecordIdExt,
+    routes::reactions,
+    services::s3::s3,
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/productions/{slug}/inbox", get(inbox_page))
+        .route("/productions/{slug}/inbox/address", post(set_address))
+        .route(
+            "/productions/{slug}/inbox/{message_id}/delete",
+            post(delete_message),
+        )
+        .route(
+            "/productions/{slug}/inbox/files/{file_id}",
+            get(download_file),
+        )
+        .route("/inbound-mail", post(inbound_mail))
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct MessageView {
+    pub message: InboxMessage,
+    pub files: Vec<InboxFile>,
+    /// Rendered reaction bar
+    pub reactions: String,
+}
+
+#[derive(Template)]
+#[template(path = "productions/inbox.html")]
+pub struct InboxTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub can_edit: bool,
+    /// Whether the server has an inbound mail domain and webhook secret
+    pub configured: bool,
+    /// The production's address, while its inbox is on
+    pub address: Option<String>,
+    pub messages: Vec<MessageView>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InboxQuery {
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddressForm {
+    /// "enable", "reset" or "disable"
+    #[serde(default)]
+    pub action: String,
+}
+
+// ============================
+// Access
+// ============================
+
+/// The inbox is for the production's members; owners and admins turn it on
+/// and clear it out
+async fn require_member(slug: &str, user_id: &str) -> Result<(Production, bool), Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    let can_edit = ProductionModel::can_edit(&production.id, user_id).await?;
+    if !can_edit && !ProductionModel::is_member(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok((production, can_edit))
+}
+
+async fn require_editor(slug: &str, user_id: &str) -> Result<Production, Error> {
+    let (production, can_edit) = require_member(slug, user_id).await?;
+    if !can_edit {
+        return Err(Error::Forbidden);
+    }
+    Ok(production)
+}
+
+fn back_to_inbox(slug: &str) -> Response {
+    Redirect::to(&format!("/productions/{}/inbox", slug)).into_response()
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn inbox_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<InboxQuery>,
+) -> Result<Response, Error> {
+    let (production, can_edit) = require_member(&slug, &user.id).await?;
+    let settings = config::inbound_mail();
+    let address = match settings.domain.as_deref() {
+        Some(domain) if settings.is_configured() => ProductionInboxModel::get_token(&production.id)
+            .await?
+            .map(|token| production_inbox::address(&production.slug, &token, domain)),
+        _ => None,
+    };
+    let files = ProductionInboxModel::files_for_production(&production.id).await?;
+    let messages = ProductionInboxModel::for_production(&production.id).await?;
+    let ids: Vec<_> = messages.iter().map(|m| m.id.clone()).collect();
+    let mut counts = ReactionModel::counts(&ids, &user.id).await?;
+    let next = format!("/productions/{}/inbox", production.slug);
+    let messages = messages
+        .into_iter()
+        .map(|message| MessageView {
+            files: files
+                .iter()
+                .filter(|f| f.message == message.id)
+                .cloned()
+                .collect(),
+            reactions: reactions::render_bar(
+                "inbox_message",
+                message.id.key_string(),
+                counts
+                    .remove(&message.id.to_raw_string())
+                    .unwrap_or_default(),
+                &next,
+            ),
+            message,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(&user).await);
+    let template = InboxTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        can_edit,
+        configured: settings.is_configured(),
+        address,
+        messages,
+        error: query.error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render inbox template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn set_address(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<AddressForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    match form.action.as_str() {
+        "enable" | "reset" => {
+            if !config::inbound_mail().is_configured() {
+                return Ok(Redirect::to(&format!(
+                    "/productions/{}/inbox?error={}",
+                    slug,
+                    urlencoding::encode("Email-in isn't set up on this server")
+                ))
+                .into_response());
+            }
+            ProductionInboxModel::reset_token(&production.id).await?;
+        }
+        "disable" => ProductionInboxModel::disable(&production.id).await?,
+        other => return Err(Error::BadRequest(format!("Unknown action: {}", other))),
+    }
+    info!(
+        "{} set the inbox on {} to {}",
+        user.username, slug, form.action
+    );
+    Ok(back_to_inbox(&slug))
+}
+
+async fn delete_message(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, message_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    ProductionInboxModel::delete_message(&production.id, &message_id).await?;
+    info!(
+        "{} deleted inbox message {} from {}",
+        user.username, message_id, slug
+    );
+    Ok(back_to_inbox(&slug))
+}
+
+/// Attachments are private to the production's members, so they're served
+/// from here rather than the public media proxy
+async fn download_file(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, file_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let (production, _) = require_member(&slug, &user.id).await?;
+    let file = ProductionInboxModel::get_file(&production.id, &file_id).await?;
+    let (data, _) = s3()?.download_file(&file.file_key).await?;
+
+    // The type and name come from whoever sent the mail, so the file is never
+    // rendered from our origin
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/octet-stream".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!(
+                    "attachment; filename=\"{}\"",
+                    deliverable::safe_file_name(&file.file_name)
+                ),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+            (header::CACHE_CONTROL, "private, no-store".to_string()),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+/// Webhook from the mail provider with a message for a production inbox
+async fn inbound_mail(headers: HeaderMap, body: Bytes) -> Result<Response, Error> {
+    let authorized = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| production_inbox::verify_secret(token.trim()));
+    if !authorized {
+        warn!("Rejected inbound mail with an invalid token");
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+
+    let email: InboundEmail = serde_json::from_slice(&body)
+        .map_err(|e| Error::BadRequest(format!("Invalid inbound mail body: {}", e)))?;
+    match ProductionInboxModel::receive(&email).await? {
+        Some(message) => info!("Inbound mail filed as {}", message.display()),
+        None => info!("Dropped inbound mail to {}: no matching inbox", email.to),
+    }
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -0,0 +1,128 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::{Path, Query},
+    response::Html,
+    routing::get,
+};
+use serde::Deserialize;
+use tracing::error;
+
+use crate::{
+    config,
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::production::{self, ProductionModel},
+    services::{
+        embedding::generate_embedding_async,
+        search::{self, MAX_PER_PAGE, PersonSearchResult, SearchParams},
+        search_utils,
+    },
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new().route("/productions/{slug}/search", get(search_page))
+}
+
+/// A matching person and how they're linked to the production
+pub struct LinkedResult {
+    pub person: PersonSearchResult,
+    pub links: Vec<String>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/search.html")]
+pub struct ProductionSearchTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub query: String,
+    /// How many people are linked, searched or not
+    pub linked_count: usize,
+    pub results: Vec<LinkedResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: Option<String>,
+}
+
+/// Search the production's team, cast and crew, and applicants to its jobs.
+/// Applicants are private to the production, so this is for its owners and
+/// admins.
+async fn search_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(params): Query<SearchQuery>,
+) -> Result<Html<String>, Error> {
+    let production = ProductionModel::get_by_slug(&slug).await?;
+    if !ProductionModel::can_edit(&production.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+
+    let query = params.q.unwrap_or_default().trim().to_string();
+    let links = ProductionModel::linked_people(&production.id).await?;
+    let mut labels = production::link_labels(&links);
+    let linked_count = labels.len();
+
+    let results = if labels.is_empty() {
+        Vec::new()
+    } else {
+        let mut people = Vec::with_capacity(linked_count);
+        for link in links {
+            if !people.contains(&link.person) {
+                people.push(link.person);
+            }
+        }
+        let parsed = search_utils::parse_query(&query);
+        let embedding = if parsed.cleaned.is_empty() {
+            None
+        } else {
+            generate_embedding_async(&parsed.cleaned).await.ok()
+        };
+        let search_params = SearchParams {
+            query: &parsed.cleaned,
+            embedding: embedding.as_ref(),
+            config: config::search_config(),
+            limit: MAX_PER_PAGE,
+            offset: 0,
+            connections: None,
+            viewer: None,
+            syntax: None,
+            scope: None,
+        };
+        search::search_people_within(&search_params, &parsed, &people)
+            .await?
+            .into_iter()
+            .map(|person| LinkedResult {
+                links: labels.remove(&person.id).unwrap_or_default(),
+                person,
+            })
+            .collect()
+    };
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(&user).await);
+    let template = ProductionSearchTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        query,
+        linked_count,
+        results,
+    };
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render production search: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
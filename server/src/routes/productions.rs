@@ -1,16 +1,22 @@
 use crate::error::Error;
 use crate::middleware::{AuthenticatedUser, UserExtractor};
+use crate::models::company_credit::{COMPANY_ROLES, CompanyCreditModel};
+use crate::models::deliverable::DeliverableModel;
+use crate::models::festival_submission::FestivalSubmissionModel;
 use crate::models::involvement::InvolvementModel;
+use crate::models::legal_hold::LegalHoldModel;
+use crate::models::press_kit::PressKitModel;
 use crate::models::production::{
-    CreateProductionData, ProductionMember, ProductionMembership, ProductionModel,
+    CreateProductionData, ProductionMember, ProductionMembership, ProductionModel, ProductionPage,
     UpdateProductionData,
 };
 use crate::models::script::ScriptModel;
 use crate::record_id_ext::RecordIdExt;
 use crate::services::invitation::InvitationService;
 use crate::templates::{
-    BaseContext, CastCrewMember, ProductionCreateTemplate, ProductionEditTemplate,
-    ProductionScriptView, ProductionTemplate, ProductionsTemplate, User,
+    BaseContext, CastCrewMember, ContactSheetPrintTemplate, ContactSheetTemplate,
+    CrewListPrintTemplate, FormatQuery, ProductionCreateTemplate, ProductionEditTemplate,
+    ProductionMemberView, ProductionScriptView, ProductionTemplate, ProductionsTemplate, User,
 };
 use askama::Template;
 use axum::{
@@ -23,6 +29,7 @@ use axum::{
 use axum::Form;
 use axum_extra::extract::Form as HtmlForm;
 use serde::Deserialize;
+use surrealdb::types::RecordId;
 use tracing::{debug, error, info};
 use crate::services::embedding::generate_embedding_async;
 use crate::services::search_log::log_search;
@@ -68,10 +75,13 @@ pub fn router() -> Router {
         )
         .route("/productions/{slug}/delete", post(delete_production))
         .route("/productions/{slug}/members", get(get_members))
+        .route("/productions/{slug}/contacts", get(contact_sheet))
         .route("/productions/{slug}/members/add", post(add_member))
         .route("/productions/{slug}/members/add-org", post(add_org_member))
         .route("/productions/{slug}/members/remove", post(remove_member))
         .route("/productions/{slug}/members/update-roles", post(update_member_roles))
+        .route("/productions/{slug}/companies/add", post(add_company_credit))
+        .route("/productions/{slug}/companies/remove", post(remove_company_credit))
         .route("/productions/{slug}/invite", post(invite_to_production))
         .route("/productions/{slug}/create-invite-link", post(create_invite_link))
         .route("/productions/{slug}/revoke-invite", post(revoke_email_invite))
@@ -223,19 +233,47 @@ async fn list_productions(
     Ok(Html(html))
 }
 
+fn member_view(m: ProductionMember) -> ProductionMemberView {
+    ProductionMemberView {
+        id: m
+            .id
+            .strip_prefix("person:")
+            .or_else(|| m.id.strip_prefix("organization:"))
+            .unwrap_or(&m.id)
+            .to_string(),
+        name: m.name,
+        username: m.username,
+        slug: m.slug,
+        avatar: m.avatar,
+        role: m.role,
+        production_roles: m.production_roles,
+        member_type: m.member_type,
+        invitation_status: m.invitation_status,
+        is_verified: m.is_verified,
+    }
+}
+
 /// View a single production
 async fn view_production(
     Path(slug): Path<String>,
+    Query(view): Query<FormatQuery>,
     request: Request,
 ) -> Result<Html<String>, Error> {
     debug!("Viewing production: {}", slug);
 
-    let production = ProductionModel::get_by_slug(&slug).await?;
+    // The production, its members and credits and whether it's claimed, in one query
+    let ProductionPage {
+        production,
+        members,
+        credits: involvements,
+        is_claimed,
+    } = ProductionModel::page(&slug).await?;
 
     let mut base = BaseContext::new().with_page("productions");
 
     // Add user to context if authenticated
     let mut can_edit = false;
+    let mut is_member = false;
     if let Some(user) = request.get_user() {
         base = base.with_user(User::from_session_user(&user).await);
 
@@ -243,21 +281,31 @@ async fn view_production(
         can_edit = ProductionModel::can_edit(&production.id, &user.id)
             .await
             .unwrap_or(false);
+        is_member = can_edit
+            || ProductionModel::is_member(&production.id, &user.id)
+                .await
+                .unwrap_or(false);
     }
 
-    // Get production members
-    let members = ProductionModel::get_members(&production.id)
-        .await
-        .unwrap_or_default();
-
-    // Fetch involvements (cast/crew) via graph traversal
-    let involvements = InvolvementModel::get_for_production(&production.id)
-        .await
-        .unwrap_or_default();
-
-    let is_claimed = ProductionModel::is_claimed(&production.id)
-        .await
-        .unwrap_or(false);
+    if view.is_print() {
+        let template = CrewListPrintTemplate {
+            app_name: base.app_name,
+            version: base.version,
+            production_title: production.title,
+            production_slug: production.slug,
+            production_type: production.production_type,
+            printed_on: chrono::Utc::now().format("%b %-d, %Y").to_string(),
+            members: members
+                .into_iter()
+                .filter(|m| m.invitation_status == "accepted")
+                .map(member_view)
+                .collect(),
+        };
+        return Ok(Html(template.render().map_err(|e| {
+            error!("Failed to render printable crew list: {}", e);
+            Error::template(e.to_string())
+        })?));
+    }
 
     // Split into cast and crew
     let mut cast = Vec::new();
@@ -330,29 +378,44 @@ async fn view_production(
         })
         .collect();
 
+    let companies = CompanyCreditModel::companies_for_production(&production.id)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Failed to load companies for {}: {}", production.id.display(), e);
+            Vec::new()
+        });
+
+    // Where the deliverables checklist stands, for members once it has any
+    let deliverables = if is_member {
+        let today = chrono::Utc::now().date_naive().format("%Y-%m-%d").to_string();
+        DeliverableModel::rollup(&production.id, &today)
+            .await
+            .unwrap_or_else(|e| {
+                error!("Failed to load deliverables for {}: {}", production.id.display(), e);
+                Default::default()
+            })
+    } else {
+        Default::default()
+    };
+    let deliverables = (deliverables.total > 0).then_some(deliverables);
+
+    let selections = FestivalSubmissionModel::public_selections(&production.id)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Failed to load festival selections for {}: {}", production.id.display(), e);
+            Vec::new()
+        });
+    let press_kit_published = PressKitModel::is_published(&production.id)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Failed to load press kit for {}: {}", production.id.display(), e);
+            false
+        });
+
     let production_roles = ProductionModel::get_roles_by_type("individual").await.unwrap_or_default();
     let org_production_roles = ProductionModel::get_roles_by_type("organization").await.unwrap_or_default();
 
-    let all_members: Vec<crate::templates::ProductionMemberView> = members
-        .into_iter()
-        .map(|m| crate::templates::ProductionMemberView {
-            id: m
-                .id
-                .strip_prefix("person:")
-                .or_else(|| m.id.strip_prefix("organization:"))
-                .unwrap_or(&m.id)
-                .to_string(),
-            name: m.name,
-            username: m.username.clone(),
-            slug: m.slug,
-            avatar: m.avatar,
-            role: m.role,
-            production_roles: m.production_roles,
-            member_type: m.member_type.clone(),
-            invitation_status: m.invitation_status,
-            is_verified: m.is_verified,
-        })
-        .collect();
+    let all_members: Vec<ProductionMemberView> = members.into_iter().map(member_view).collect();
     let person_members: Vec<_> = all_members.iter().filter(|m| m.member_type == "person").cloned().collect();
     let org_members: Vec<_> = all_members.iter().filter(|m| m.member_type == "organization").cloned().collect();
 
@@ -364,6 +427,7 @@ async fn view_production(
         user: base.user,
         production_roles,
         org_production_roles,
+        company_roles: COMPANY_ROLES,
         production: crate::templates::ProductionDetail {
             id: production.id.key_string(),
             slug: production.slug.clone(),
@@ -380,6 +444,7 @@ async fn view_production(
             person_members,
             org_members,
             can_edit,
+            is_member,
             poster_url: production.poster_url,
             poster_photo: production.poster_photo,
             header_photo: production.header_photo,
@@ -396,8 +461,12 @@ async fn view_production(
             cast,
             crew,
             pending_credits,
+            companies,
             budget_level: production.budget_level,
             production_tier: production.production_tier,
+            deliverables,
+            selections,
+            press_kit_published,
             pending_email_invites: if can_edit {
                 let pi_model = crate::models::pending_invitation::PendingInvitationModel::new();
                 pi_model
@@ -791,6 +860,10 @@ async fn delete_production(
         return Err(Error::Forbidden);
     }
 
+    // Nothing under a legal hold can go
+    let person = RecordId::parse_simple(&user.id).ok();
+    LegalHoldModel::ensure_deletable(&production.id, None, person.as_ref(), "production").await?;
+
     // Delete the production
     ProductionModel::delete(&production.id).await?;
 
@@ -813,6 +886,53 @@ async fn get_members(Path(slug): Path<String>) -> Result<Json<Vec<ProductionMemb
     Ok(Json(members))
 }
 
+/// Contact sheet for the production's members, or its printable variant
+async fn contact_sheet(
+    Path(slug): Path<String>,
+    Query(view): Query<FormatQuery>,
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Html<String>, Error> {
+    let production = ProductionModel::get_by_slug(&slug).await?;
+    if !ProductionModel::can_edit(&production.id, &user.id).await?
+        && !ProductionModel::is_member(&production.id, &user.id).await?
+    {
+        return Err(Error::Forbidden);
+    }
+    let contacts = ProductionModel::contact_sheet(&production.id).await?;
+
+    let html = if view.is_print() {
+        let base = BaseContext::new();
+        ContactSheetPrintTemplate {
+            app_name: base.app_name,
+            version: base.version,
+            production_title: production.title,
+            production_slug: production.slug,
+            printed_on: chrono::Utc::now().format("%b %-d, %Y").to_string(),
+            contacts,
+        }
+        .render()
+    } else {
+        let base = BaseContext::new()
+            .with_page("productions")
+            .with_user(User::from_session_user(&user).await);
+        ContactSheetTemplate {
+            app_name: base.app_name,
+            year: base.year,
+            version: base.version,
+            active_page: base.active_page,
+            user: base.user,
+            production_title: production.title,
+            production_slug: production.slug,
+            contacts,
+        }
+        .render()
+    };
+    Ok(Html(html.map_err(|e| {
+        error!("Failed to render contact sheet: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
 /// Add a member to a production (via invitation)
 #[axum::debug_handler]
 async fn add_member(
@@ -952,6 +1072,111 @@ async fn remove_member(
     Ok(Redirect::to(&format!("/productions/{}", slug)).into_response())
 }
 
+#[derive(Debug, Deserialize)]
+struct AddCompanyCreditForm {
+    org_id: String,
+    role: String,
+    services: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoveCompanyCreditForm {
+    credit_id: String,
+}
+
+/// Credit an organization as producer, financier or vendor on a production
+#[axum::debug_handler]
+async fn add_company_credit(
+    Path(slug): Path<String>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Form(data): Form<AddCompanyCreditForm>,
+) -> Result<Response, Error> {
+    debug!("Adding company credit to production: {}", slug);
+
+    let production = ProductionModel::get_by_slug(&slug).await?;
+
+    if !ProductionModel::can_edit(&production.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+
+    // org_id comes as the full record id string like "organization:abc123"
+    if !data.org_id.starts_with("organization:") {
+        return Err(Error::BadRequest("Choose an organization".to_string()));
+    }
+    let org_id = RecordId::parse_simple(&data.org_id)
+        .map_err(|e| Error::BadRequest(e.to_string()))?;
+    let added_by = RecordId::parse_simple(&user.id)
+        .map_err(|e| Error::BadRequest(e.to_string()))?;
+
+    CompanyCreditModel::add(
+        &org_id,
+        &production.id,
+        &data.role,
+        data.services.as_deref(),
+        &added_by,
+    )
+    .await?;
+
+    info!(
+        "Credited {} as {} on production {}",
+        data.org_id, data.role, production.title
+    );
+
+    // Let the org owners know their company was credited
+    let notification_model = crate::models::notification::NotificationModel::new();
+    let org_model = crate::models::organization::OrganizationModel::new();
+    if let Ok(owners) = org_model.get_org_owners(&data.org_id).await {
+        let user_name = if user.name.is_empty() { &user.username } else { &user.name };
+        let role = crate::models::company_credit::role_label(&data.role).to_lowercase();
+        for owner_id in owners {
+            let _ = notification_model
+                .create(
+                    &owner_id,
+                    "production_membership",
+                    &format!("Organization credited on {}", production.title),
+                    &format!(
+                        "{} credited your organization as {} on the production {}",
+                        user_name, role, production.title
+                    ),
+                    Some(&format!("/productions/{}", production.slug)),
+                    None,
+                )
+                .await;
+        }
+    }
+
+    Ok(Redirect::to(&format!("/productions/{}", slug)).into_response())
+}
+
+/// Remove a company credit from a production
+#[axum::debug_handler]
+async fn remove_company_credit(
+    Path(slug): Path<String>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Form(data): Form<RemoveCompanyCreditForm>,
+) -> Result<Response, Error> {
+    debug!("Removing company credit from production: {}", slug);
+
+    let production = ProductionModel::get_by_slug(&slug).await?;
+
+    if !ProductionModel::can_edit(&production.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+
+    let credit_id = RecordId::new("company_credit", data.credit_id.as_str());
+    if !CompanyCreditModel::remove(&credit_id, &production.id).await? {
+        return Err(Error::NotFound);
+    }
+
+    info!(
+        "Removed company credit {} from production {}",
+        data.credit_id,
+        production.id.display()
+    );
+
+    Ok(Redirect::to(&format!("/productions/{}", slug)).into_response())
+}
+
 #[derive(Debug, Deserialize)]
 struct RevokeInviteForm {
     invite_id: String,
@@ -1333,10 +1558,19 @@ async fn delete_script(
     }
 
     let script_rid = surrealdb::types::RecordId::new("production_script", &*script_id);
+    if !ScriptModel::get(&script_rid)
+        .await?
+        .is_some_and(|script| script.production == production.id)
+    {
+        return Err(Error::NotFound);
+    }
+    let person = RecordId::parse_simple(&user.id).ok();
+    LegalHoldModel::ensure_deletable(&production.id, Some(&script_rid), person.as_ref(), "script")
+        .await?;
 
     if let Some(file_key) = ScriptModel::delete(&script_rid).await? {
         // Fire-and-forget S3 cleanup
-        tokio::spawn(async move {
+        crate::db::spawn(async move {
             if let Ok(s3_service) = crate::services::s3::s3() {
                 let _ = s3_service.delete_file(&file_key).await;
             }
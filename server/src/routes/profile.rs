@@ -2,23 +2,31 @@ use askama::Template;
 use axum::{
     Form, Router,
     extract::{Path, Request},
+    http::{HeaderMap, header},
     response::{Html, IntoResponse, Redirect, Response},
     routing::get,
 };
 use std::collections::HashMap;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::{
     error::Error,
     middleware::{AuthenticatedUser, UserExtractor},
+    models::completeness::CompletenessModel,
     models::involvement::InvolvementModel,
-    models::person::{Person, Photo, Reel, SocialLink},
+    models::person::{Person, Photo, Reel, SessionUser, SocialLink},
+    physical_attributes::{self, Attribute},
     record_id_ext::RecordIdExt,
+    response,
+    services::onboarding,
     social_platforms::{self, SOCIAL_PLATFORMS},
     templates::{
         BaseContext, DateRange, Education, InvolvementDisplay, PhotoDisplay, ProfileData,
-        ProfileEditTemplate, ReelDisplay, SocialLinkDisplay, SocialPlatformOption, User,
+        ProfileEditTemplate, ReelDisplay, SelectOption, SocialLinkDisplay, SocialPlatformOption,
+        User,
     },
+    units,
+    validation::{FieldErrors, Validate},
     verification_limits,
     video_platforms,
 };
@@ -100,6 +108,13 @@ async fn own_profile(request: Request) -> Result<Response, Error> {
         }
     };
 
+    // New accounts answer the onboarding questionnaire first
+    let person_id = surrealdb::types::RecordId::parse_simple(&current_user.id)
+        .map_err(|e| Error::BadRequest(e.to_string()))?;
+    if onboarding::needs_onboarding(&person_id).await {
+        return Ok(Redirect::to("/welcome").into_response());
+    }
+
     // Redirect to the user's public profile page
     Ok(Redirect::to(&format!("/{}", current_user.username)).into_response())
 }
@@ -124,6 +139,23 @@ async fn edit_profile_form(request: Request) -> Result<Response, Error> {
         }
     };
 
+    let lang = physical_attributes::lang_from_accept_language(
+        request
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok()),
+    );
+    render_profile_edit(&current_user, lang, None, FieldErrors::new()).await
+}
+
+/// The profile edit form. After a failed save, `submitted` is laid over the
+/// stored profile so what was typed is kept, and `errors` are shown inline.
+async fn render_profile_edit(
+    current_user: &SessionUser,
+    lang: &str,
+    submitted: Option<&HashMap<String, String>>,
+    errors: FieldErrors,
+) -> Result<Response, Error> {
     // Fetch the user's current profile data
     let profile_user = match Person::find_by_username(&current_user.username).await {
         Ok(Some(user)) => user,
@@ -143,11 +175,11 @@ async fn edit_profile_form(request: Request) -> Result<Response, Error> {
     // Build base context
     let base = BaseContext::new()
         .with_page("profile")
-        .with_user(User::from_session_user(&current_user).await);
+        .with_user(User::from_session_user(current_user).await);
 
     // Convert Person model to ProfileData
     let profile = profile_user.profile.as_ref();
-    let profile_data = ProfileData {
+    let mut profile_data = ProfileData {
         id: profile_user.id.to_raw_string(),
         name: profile_user.get_display_name(),
         username: profile_user.username.clone(),
@@ -211,6 +243,10 @@ async fn edit_profile_form(request: Request) -> Result<Response, Error> {
         photos: to_photo_displays(
             &profile.map(|p| p.photos.clone()).unwrap_or_default(),
         ),
+        audio_reels: crate::routes::audio_reels::profile_audio_reels(&profile_user.id).await,
+        booked_dates: crate::routes::offers::profile_booked_dates(&profile_user.id).await,
+        unavailable_dates: crate::routes::account::profile_unavailable_dates(&profile_user.id)
+            .await,
         is_own_profile: true,
         is_public: profile.map(|p| p.is_public).unwrap_or(false),
         verification_status: profile_user.verification_status.clone(),
@@ -219,8 +255,11 @@ async fn edit_profile_form(request: Request) -> Result<Response, Error> {
         height_mm: profile.and_then(|p| p.height_mm),
         weight_kg: profile.and_then(|p| p.weight_kg),
         body_type: profile.and_then(|p| p.body_type.clone()),
+        body_type_other: profile.and_then(|p| p.body_type_other.clone()),
         hair_color: profile.and_then(|p| p.hair_color.clone()),
+        hair_color_other: profile.and_then(|p| p.hair_color_other.clone()),
         eye_color: profile.and_then(|p| p.eye_color.clone()),
+        eye_color_other: profile.and_then(|p| p.eye_color_other.clone()),
         ethnicity: profile.map(|p| p.ethnicity.clone()).unwrap_or_default(),
         acting_age_range_min: profile.and_then(|p| p.acting_age_range.as_ref().map(|r| r.min)),
         acting_age_range_max: profile.and_then(|p| p.acting_age_range.as_ref().map(|r| r.max)),
@@ -228,7 +267,22 @@ async fn edit_profile_form(request: Request) -> Result<Response, Error> {
         nationality: profile.and_then(|p| p.nationality.clone()),
         messaging_preference: profile_user.messaging_preference.clone(),
         phone: profile.and_then(|p| p.phone.clone()),
+        units: profile_user.units.clone(),
+        lang: lang.to_string(),
     };
+    if let Some(form) = submitted {
+        let text = |field: &str| form.get(field).filter(|v| !v.is_empty()).cloned();
+        profile_data.name = form.get("name").cloned().unwrap_or_default();
+        profile_data.headline = text("headline");
+        profile_data.bio = text("bio");
+        profile_data.location = text("location");
+        profile_data.website = text("website");
+        profile_data.availability = text("availability");
+        profile_data.birthday = text("birthday");
+        let age = |field: &str| text(field).and_then(|v| v.parse().ok());
+        profile_data.acting_age_range_min = age("acting_age_range_min");
+        profile_data.acting_age_range_max = age("acting_age_range_max");
+    }
 
     // Compute upload limits based on verification status
     let limits = verification_limits::limits_for_status(&profile_user.verification_status);
@@ -245,10 +299,15 @@ async fn edit_profile_form(request: Request) -> Result<Response, Error> {
         reel_count: profile_data.reels.len(),
         photo_limit: limits.max_photos,
         reel_limit: limits.max_reels,
+        audio_reel_limit: limits.max_audio_reels,
         is_identity_verified: is_verified,
+        body_types: attribute_options(Attribute::BodyType, profile_data.body_type.as_deref(), lang),
+        hair_colors: attribute_options(Attribute::HairColor, profile_data.hair_color.as_deref(), lang),
+        eye_colors: attribute_options(Attribute::EyeColor, profile_data.eye_color.as_deref(), lang),
         profile: profile_data,
         platforms: platform_options(),
         error: None,
+        errors,
         success: None,
     };
 
@@ -257,7 +316,53 @@ async fn edit_profile_form(request: Request) -> Result<Response, Error> {
         Error::template(e.to_string())
     })?;
 
-    Ok(Html(html).into_response())
+    if template.errors.is_empty() {
+        Ok(Html(html).into_response())
+    } else {
+        Ok(response::invalid_form(html))
+    }
+}
+
+/// The text fields of the profile edit form
+struct ProfileForm<'a>(&'a HashMap<String, String>);
+
+impl ProfileForm<'_> {
+    fn get(&self, field: &str) -> &str {
+        self.0.get(field).map(|v| v.as_str()).unwrap_or("")
+    }
+}
+
+impl Validate for ProfileForm<'_> {
+    fn validate(&self) -> FieldErrors {
+        let mut errors = FieldErrors::new();
+        errors.required("name", self.get("name"), "Full name");
+        errors.max_chars("name", self.get("name"), 100, "Full name");
+        errors.max_chars("headline", self.get("headline"), 120, "Headline");
+        errors.max_chars("bio", self.get("bio"), 2000, "Bio");
+        errors.url("website", self.get("website"));
+
+        let birthday = self.get("birthday");
+        if !birthday.is_empty() {
+            match chrono::NaiveDate::parse_from_str(birthday, "%Y-%m-%d") {
+                Ok(date) if date <= chrono::Utc::now().date_naive() => {}
+                Ok(_) => errors.add("birthday", "Birthday can't be in the future"),
+                Err(_) => errors.add("birthday", "Enter your birthday as a date"),
+            }
+        }
+
+        let min = self.get("acting_age_range_min");
+        let max = self.get("acting_age_range_max");
+        errors.int_range("acting_age_range", min, 0, 100, "Acting age range");
+        errors.int_range("acting_age_range", max, 0, 100, "Acting age range");
+        let ages = (min.parse::<i32>(), max.parse::<i32>());
+        if matches!(ages, (Ok(min), Ok(max)) if min > max) {
+            errors.add(
+                "acting_age_range",
+                "The youngest age you can play must not be above the oldest",
+            );
+        }
+        errors
+    }
 }
 
 /// Parse social link form fields from the flat form data.
@@ -314,9 +419,8 @@ fn parse_height_mm(form: &HashMap<String, String>) -> Option<i32> {
         "imperial" => {
             let feet: i32 = form.get("height_feet").and_then(|v| v.parse().ok()).unwrap_or(0);
             let inches: i32 = form.get("height_inches").and_then(|v| v.parse().ok()).unwrap_or(0);
-            let total_inches = feet * 12 + inches;
-            if total_inches > 0 {
-                Some((total_inches as f64 * 25.4).round() as i32)
+            if feet * 12 + inches > 0 {
+                Some(units::feet_inches_to_mm(feet, inches))
             } else {
                 Some(0)
             }
@@ -328,6 +432,37 @@ fn parse_height_mm(form: &HashMap<String, String>) -> Option<i32> {
     }
 }
 
+/// Vocabulary options for a physical attribute select, labelled in `lang`
+fn attribute_options(attribute: Attribute, selected: Option<&str>, lang: &str) -> Vec<SelectOption> {
+    attribute
+        .options()
+        .iter()
+        .map(|o| {
+            SelectOption::new(
+                o.value,
+                physical_attributes::label(attribute, o.value, lang),
+                selected == Some(o.value),
+            )
+        })
+        .collect()
+}
+
+/// A physical attribute answer: the selected value, or the text typed next to
+/// "Other". `Person::update_profile` maps it back to the vocabulary if it can.
+fn attribute_answer(form: &HashMap<String, String>, field: &str) -> Option<String> {
+    let value = form.get(field)?;
+    if value == physical_attributes::OTHER {
+        let other = form
+            .get(&format!("{}_other", field))
+            .map(|s| s.trim())
+            .unwrap_or_default();
+        if !other.is_empty() {
+            return Some(other.to_string());
+        }
+    }
+    Some(value.clone())
+}
+
 /// Parse weight from form fields, converting to kg.
 /// Supports metric (kg) and imperial (lbs).
 fn parse_weight_kg(form: &HashMap<String, String>) -> Option<i32> {
@@ -336,7 +471,7 @@ fn parse_weight_kg(form: &HashMap<String, String>) -> Option<i32> {
         "imperial" => {
             let lbs: f64 = form.get("weight_lbs").and_then(|v| v.parse().ok()).unwrap_or(0.0);
             if lbs > 0.0 {
-                Some((lbs * 0.453592).round() as i32)
+                Some(units::lbs_to_kg(lbs))
             } else {
                 Some(0)
             }
@@ -456,10 +591,13 @@ fn parse_photos(form: &HashMap<String, String>) -> Vec<Photo> {
 /// Handler for updating the user's profile
 async fn update_profile(
     AuthenticatedUser(current_user): AuthenticatedUser,
+    headers: HeaderMap,
     Form(form): Form<HashMap<String, String>>,
 ) -> Result<Response, Error> {
     debug!("Handling profile update request");
 
+    let mut errors = ProfileForm(&form).validate();
+
     let social_links = parse_social_links(&form);
     let reels = parse_reels(&form).await;
     let photos = parse_photos(&form);
@@ -469,14 +607,33 @@ async fn update_profile(
     let limits = verification_limits::limits_for_status(&person.verification_status);
     if let Some(max) = limits.max_reels {
         if reels.len() > max {
-            return Err(Error::bad_request(format!("Maximum of {} reels allowed. Get verified to remove this limit.", max)));
+            errors.add(
+                "reels",
+                format!(
+                    "Maximum of {} reels allowed. Get verified to remove this limit.",
+                    max
+                ),
+            );
         }
     }
     if let Some(max) = limits.max_photos {
         if photos.len() > max {
-            return Err(Error::bad_request(format!("Maximum of {} photos allowed. Get verified for more uploads.", max)));
+            errors.add(
+                "photos",
+                format!(
+                    "Maximum of {} photos allowed. Get verified for more uploads.",
+                    max
+                ),
+            );
         }
     }
+    if !errors.is_empty() {
+        let accept_language = headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok());
+        let lang = physical_attributes::lang_from_accept_language(accept_language);
+        return render_profile_edit(&current_user, lang, Some(&form), errors).await;
+    }
 
     // Parse physical attribute fields
     let height_mm = parse_height_mm(&form);
@@ -502,9 +659,9 @@ async fn update_profile(
         form.get("birthday").cloned(),
         height_mm,
         weight_kg,
-        form.get("body_type").cloned(),
-        form.get("hair_color").cloned(),
-        form.get("eye_color").cloned(),
+        attribute_answer(&form, "body_type"),
+        attribute_answer(&form, "hair_color"),
+        attribute_answer(&form, "eye_color"),
         form.get("ethnicity").cloned(),
         acting_age_min,
         acting_age_max,
@@ -518,6 +675,9 @@ async fn update_profile(
                 "Successfully updated profile for user: {}",
                 current_user.username
             );
+            if let Err(e) = CompletenessModel::refresh(&person.id).await {
+                warn!("Failed to rescore profile completeness for {}: {}", current_user.username, e);
+            }
             Ok(Redirect::to(&format!("/{}", current_user.username)).into_response())
         }
         Ok(None) => {
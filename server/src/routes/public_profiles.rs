@@ -11,23 +11,30 @@ use tracing::{debug, error, info};
 
 use crate::{
     config,
-    db::DB,
     error::Error,
     middleware::UserExtractor,
     models::analytics::AnalyticsModel,
-    models::involvement::InvolvementModel,
-    models::likes::LikesModel,
+    models::completeness,
+    models::{block::BlockModel, likes::LikesModel},
+    models::pagination::Page,
     models::person::Person,
+    models::person_availability::AvailabilityWindow,
+    models::representation::{ContactMode, RepresentationModel},
+    physical_attributes::{self, Attribute},
     record_id_ext::RecordIdExt,
     services::embedding::generate_embedding_async,
     services::search::{self, PersonSearchResult, SearchParams},
     services::search_log::log_search,
     services::search_utils,
+    services::search_visibility::Viewer,
+    services::minors,
     social_platforms,
     templates::{
         BaseContext, DateRange, Education, InvolvementDisplay, PeopleTemplate, PersonCard,
-        PhotoDisplay, ProfileData, ProfileTemplate, ReelDisplay, SocialLinkDisplay, User,
+        PhotoDisplay, ProfileData, ProfileTemplate, ReelDisplay, SelectOption, SocialLinkDisplay,
+        User,
     },
+    units,
     video_platforms,
 };
 use surrealdb::types::RecordId;
@@ -59,6 +66,7 @@ const RESERVED_ROUTES: &[&str] = &[
     "login",
     "logout",
     "messages",
+    "offers",
     "org",
     "orgs",
     "people",
@@ -157,15 +165,27 @@ async fn user_profile(
         .map(|u| u.username == username)
         .unwrap_or(false);
 
-    // Fetch the user's profile data using the Person model
-    let profile_user = match Person::find_by_username(&username).await? {
-        Some(p) => p,
+    // Fetch the user's profile data and credits in one query
+    let (profile_user, credits) = match Person::page(&username).await? {
+        Some(page) => (page.person, page.credits),
         None => {
             info!("User profile not found for username: {}", username);
             return Err(Error::NotFound);
         }
     };
 
+    // A minor profile stays hidden until their guardian approves the link;
+    // the guardian can see it in the meantime
+    if profile_user.is_minor && !is_own_profile {
+        let link = minors::get_link(&profile_user.id).await.unwrap_or_default();
+        let is_guardian = current_user.as_ref().is_some_and(|u| {
+            link.guardian_username.as_deref() == Some(u.username.as_str())
+        });
+        if !link.approved && !is_guardian {
+            return Err(Error::NotFound);
+        }
+    }
+
     // Record profile view (fire-and-forget, skip own profile)
     if !is_own_profile {
         let pid = profile_user.id.clone();
@@ -184,7 +204,7 @@ async fn user_profile(
             .get(header::USER_AGENT)
             .and_then(|v| v.to_str().ok())
             .map(String::from);
-        tokio::spawn(async move {
+        crate::db::spawn(async move {
             let _ = AnalyticsModel::record_view(
                 &pid,
                 viewer_rid.as_ref(),
@@ -198,6 +218,9 @@ async fn user_profile(
     // Build base context
     let mut base = BaseContext::new().with_page("profile");
     let mut is_liked = false;
+    let mut is_blocked = false;
+    let mut is_muted = false;
+    let mut blocked_by_owner = false;
     if let Some(ref user) = current_user {
         base = base.with_user(User::from_session_user(&user).await);
 
@@ -212,6 +235,16 @@ async fn user_profile(
                 is_liked = LikesModel::is_liked(&rid, &profile_user.id)
                     .await
                     .unwrap_or(false);
+                is_blocked = BlockModel::has_blocked(&rid, &profile_user.id)
+                    .await
+                    .unwrap_or(false);
+                is_muted = BlockModel::has_muted(&rid, &profile_user.id)
+                    .await
+                    .unwrap_or(false);
+                // Fail closed: on error, treat the viewer as blocked
+                blocked_by_owner = BlockModel::has_blocked(&profile_user.id, &rid)
+                    .await
+                    .unwrap_or(true);
             }
         }
     }
@@ -233,50 +266,41 @@ async fn user_profile(
         languages: profile.map(|p| p.languages.clone()).unwrap_or_default(),
         availability: profile.and_then(|p| p.availability.clone()),
         involvements: {
-            let pid = profile_user.id.to_raw_string();
-            match InvolvementModel::get_for_person(&pid).await {
-                Ok(invs) => {
-                    // Group by production_slug to merge multiple roles into one entry
-                    let mut result: Vec<InvolvementDisplay> = Vec::new();
-                    for inv in invs {
-                        if let Some(existing) = result.iter_mut().find(|d| d.production_slug == inv.production_slug) {
-                            if let Some(new_role) = &inv.role {
-                                if let Some(ref mut existing_role) = existing.role {
-                                    let mut roles: Vec<&str> = existing_role.split(", ").collect();
-                                    if !roles.contains(&new_role.as_str()) {
-                                        roles.push(new_role);
-                                        roles.sort_unstable_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
-                                        *existing_role = roles.join(", ");
-                                    }
-                                } else {
-                                    existing.role = Some(new_role.clone());
-                                }
+            // Group by production_slug to merge multiple roles into one entry
+            let mut result: Vec<InvolvementDisplay> = Vec::new();
+            for inv in credits {
+                if let Some(existing) = result.iter_mut().find(|d| d.production_slug == inv.production_slug) {
+                    if let Some(new_role) = &inv.role {
+                        if let Some(ref mut existing_role) = existing.role {
+                            let mut roles: Vec<&str> = existing_role.split(", ").collect();
+                            if !roles.contains(&new_role.as_str()) {
+                                roles.push(new_role);
+                                roles.sort_unstable_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
+                                *existing_role = roles.join(", ");
                             }
                         } else {
-                            result.push(InvolvementDisplay {
-                                involvement_id: inv.id.to_raw_string(),
-                                role: inv.role,
-                                relation_type: inv.relation_type,
-                                department: inv.department,
-                                verification_status: inv.verification_status,
-                                production_title: inv.production_title,
-                                production_slug: inv.production_slug,
-                                production_type: inv.production_type,
-                                poster_url: inv.poster_url,
-                                tmdb_url: inv.tmdb_url,
-                                release_date: inv.release_date,
-                                media_type: inv.media_type,
-                                is_claimed: inv.is_claimed,
-                            });
+                            existing.role = Some(new_role.clone());
                         }
                     }
-                    result
-                }
-                Err(e) => {
-                    error!("Failed to fetch involvements for {}: {}", username, e);
-                    vec![]
+                } else {
+                    result.push(InvolvementDisplay {
+                        involvement_id: inv.id.to_raw_string(),
+                        role: inv.role,
+                        relation_type: inv.relation_type,
+                        department: inv.department,
+                        verification_status: inv.verification_status,
+                        production_title: inv.production_title,
+                        production_slug: inv.production_slug,
+                        production_type: inv.production_type,
+                        poster_url: inv.poster_url,
+                        tmdb_url: inv.tmdb_url,
+                        release_date: inv.release_date,
+                        media_type: inv.media_type,
+                        is_claimed: inv.is_claimed,
+                    });
                 }
             }
+            result
         },
         education: profile
             .map(|p| p.education.clone())
@@ -301,6 +325,10 @@ async fn user_profile(
         photos: to_photo_displays(
             &profile.map(|p| p.photos.clone()).unwrap_or_default(),
         ),
+        audio_reels: crate::routes::audio_reels::profile_audio_reels(&profile_user.id).await,
+        booked_dates: crate::routes::offers::profile_booked_dates(&profile_user.id).await,
+        unavailable_dates: crate::routes::account::profile_unavailable_dates(&profile_user.id)
+            .await,
         is_own_profile,
         is_public: profile.map(|p| p.is_public).unwrap_or(false),
         verification_status: profile_user.verification_status.clone(),
@@ -309,8 +337,11 @@ async fn user_profile(
         height_mm: profile.and_then(|p| p.height_mm),
         weight_kg: profile.and_then(|p| p.weight_kg),
         body_type: profile.and_then(|p| p.body_type.clone()),
+        body_type_other: profile.and_then(|p| p.body_type_other.clone()),
         hair_color: profile.and_then(|p| p.hair_color.clone()),
+        hair_color_other: profile.and_then(|p| p.hair_color_other.clone()),
         eye_color: profile.and_then(|p| p.eye_color.clone()),
+        eye_color_other: profile.and_then(|p| p.eye_color_other.clone()),
         ethnicity: profile.map(|p| p.ethnicity.clone()).unwrap_or_default(),
         acting_age_range_min: profile.and_then(|p| p.acting_age_range.as_ref().map(|r| r.min)),
         acting_age_range_max: profile.and_then(|p| p.acting_age_range.as_ref().map(|r| r.max)),
@@ -318,8 +349,60 @@ async fn user_profile(
         nationality: profile.and_then(|p| p.nationality.clone()),
         messaging_preference: profile_user.messaging_preference.clone(),
         phone: profile.and_then(|p| p.phone.clone()),
+        units: units::viewer_preference(current_user.as_ref().map(|u| u.id.as_str()))
+            .await
+            .map(|system| system.as_str().to_string()),
+        lang: physical_attributes::lang_from_accept_language(
+            headers
+                .get(header::ACCEPT_LANGUAGE)
+                .and_then(|v| v.to_str().ok()),
+        )
+        .to_string(),
     };
 
+    // Someone the owner has blocked doesn't see their contact details or a message button
+    let mut profile_data = profile_data;
+    if blocked_by_owner {
+        profile_data.is_public = false;
+        profile_data.phone = None;
+        profile_data.messaging_preference = "nobody".to_string();
+    }
+
+    // Minor profiles never show contact details or sensitive physical fields
+    if profile_user.is_minor && !is_own_profile {
+        profile_data.is_public = false;
+        profile_data.phone = None;
+        profile_data.website = None;
+        profile_data.birthday = None;
+        profile_data.height_mm = None;
+        profile_data.weight_kg = None;
+        profile_data.body_type = None;
+        profile_data.body_type_other = None;
+        profile_data.ethnicity.clear();
+    }
+
+    let completeness = is_own_profile
+        .then(|| completeness::completeness(profile, profile_data.involvements.len()));
+
+    // Confirmed representation is public; unconfirmed claims aren't shown
+    let representation = RepresentationModel::get(&profile_user.id)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Failed to load representation for {}: {}", username, e);
+            None
+        })
+        .filter(|r| r.is_accepted());
+    let contact_via_representative = representation.as_ref().is_some_and(|r| r.contact.is_some())
+        && RepresentationModel::contact_mode(&profile_user.id)
+            .await
+            .is_ok_and(|mode| mode == ContactMode::Representative);
+
+    // Contact details stay off the page when inquiries go through a representative
+    if contact_via_representative && !is_own_profile {
+        profile_data.is_public = false;
+        profile_data.phone = None;
+    }
+
     // Create and render template using the same ProfileTemplate
     let template = ProfileTemplate {
         app_name: base.app_name,
@@ -329,6 +412,11 @@ async fn user_profile(
         user: base.user,
         profile: profile_data,
         is_liked,
+        is_blocked,
+        is_muted,
+        completeness,
+        representation,
+        contact_via_representative,
     };
 
     let html = template.render().map_err(|e| {
@@ -342,6 +430,87 @@ async fn user_profile(
 #[derive(Deserialize)]
 struct PeopleQuery {
     filter: Option<String>,
+    hair: Option<String>,
+    eyes: Option<String>,
+    build: Option<String>,
+    available_from: Option<String>,
+    available_to: Option<String>,
+}
+
+/// Exact-match physical attribute filters from the people page selects.
+/// Values outside the vocabulary are ignored.
+#[derive(Debug, Default)]
+struct AttributeFilters {
+    hair: Option<&'static str>,
+    eyes: Option<&'static str>,
+    build: Option<&'static str>,
+}
+
+impl AttributeFilters {
+    fn new(hair: Option<&str>, eyes: Option<&str>, build: Option<&str>) -> Self {
+        Self {
+            hair: hair.and_then(|v| physical_attributes::lookup(Attribute::HairColor, v)),
+            eyes: eyes.and_then(|v| physical_attributes::lookup(Attribute::EyeColor, v)),
+            build: build.and_then(|v| physical_attributes::lookup(Attribute::BodyType, v)),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.hair.is_none() && self.eyes.is_none() && self.build.is_none()
+    }
+
+    /// Take precedence over anything parsed from the search text
+    fn apply(&self, parsed: &mut search_utils::ParsedQuery) {
+        if let Some(hair) = self.hair {
+            parsed.hair_color = Some(hair.to_string());
+        }
+        if let Some(eyes) = self.eyes {
+            parsed.eye_color = Some(eyes.to_string());
+        }
+        if let Some(build) = self.build {
+            parsed.body_type = Some(build.to_string());
+        }
+    }
+
+    /// "&hair=Red&build=Athletic", to carry the filters to the next page
+    fn query_string(&self) -> String {
+        [("hair", self.hair), ("eyes", self.eyes), ("build", self.build)]
+            .into_iter()
+            .filter_map(|(name, value)| Some(format!("&{}={}", name, urlencoding::encode(value?))))
+            .collect()
+    }
+}
+
+/// The shoot window from the people page date inputs. Dates that don't parse
+/// are ignored, like attribute values outside the vocabulary.
+fn availability_filter(from: Option<&str>, to: Option<&str>) -> Option<AvailabilityWindow> {
+    AvailabilityWindow::parse(from, to).ok().flatten()
+}
+
+/// "&available_from=2026-06-01&available_to=2026-06-05", for the next page
+fn availability_query(window: Option<&AvailabilityWindow>) -> String {
+    window
+        .map(|w| {
+            format!(
+                "&available_from={}&available_to={}",
+                w.first_day(),
+                w.last_day()
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Select options for a people page filter, "Any" first
+fn attribute_filter_options(attribute: Attribute, selected: Option<&str>, lang: &str) -> Vec<SelectOption> {
+    std::iter::once(SelectOption::new("", "Any".to_string(), selected.is_none()))
+        .chain(attribute.options().iter().map(|o| {
+            SelectOption::new(
+                o.value,
+                physical_attributes::label(attribute, o.value, lang),
+                selected == Some(o.value),
+            )
+        }))
+        .collect()
 }
 
 async fn people(
@@ -349,7 +518,22 @@ async fn people(
     request: Request,
 ) -> Result<Html<String>, Error> {
     let filter = params.filter.as_deref().map(|s| s.trim()).filter(|s| !s.is_empty());
-    debug!("Rendering people page, filter: {:?}", filter);
+    let attributes = AttributeFilters::new(
+        params.hair.as_deref(),
+        params.eyes.as_deref(),
+        params.build.as_deref(),
+    );
+    let available = availability_filter(
+        params.available_from.as_deref(),
+        params.available_to.as_deref(),
+    );
+    debug!("Rendering people page, filter: {:?}, attributes: {:?}", filter, attributes);
+    let lang = physical_attributes::lang_from_accept_language(
+        request
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok()),
+    );
 
     let mut base = BaseContext::new().with_page("people");
 
@@ -365,6 +549,12 @@ async fn people(
     let mut template = PeopleTemplate::new(base);
     template.current_user_id = current_user_id.clone().unwrap_or_default();
     template.filter = filter.map(|s| s.to_string());
+    template.hair_colors = attribute_filter_options(Attribute::HairColor, attributes.hair, lang);
+    template.eye_colors = attribute_filter_options(Attribute::EyeColor, attributes.eyes, lang);
+    template.body_types = attribute_filter_options(Attribute::BodyType, attributes.build, lang);
+    template.available_from = available.map(|w| w.first_day()).unwrap_or_default();
+    template.available_to = available.map(|w| w.last_day()).unwrap_or_default();
+    template.attribute_query = attributes.query_string() + &availability_query(available.as_ref());
 
     // Add specialties list (in production, fetch from database)
     template.specialties = vec![
@@ -378,64 +568,67 @@ async fn people(
         "Composer".to_string(),
     ];
 
+    // Members-only profiles are for signed-in viewers, and blocks go both ways
+    let viewer = match current_user_id.as_deref().map(RecordId::parse_simple) {
+        Some(Ok(rid)) => Viewer::load(&rid).await.ok(),
+        _ => None,
+    };
+
     // Fetch profiles from the database, optionally filtered
-    let (persons, search_cards) = if let Some(filter_text) = filter {
-        let parsed = search_utils::parse_query(filter_text);
-        let query_embedding = generate_embedding_async(&parsed.cleaned).await.ok();
-        let weights = config::search_weights();
+    let searching = filter.is_some() || !attributes.is_empty() || available.is_some();
+    let (persons, search_cards) = if searching {
+        let filter_text = filter.unwrap_or_default();
+        let mut parsed = search_utils::parse_query(filter_text);
+        attributes.apply(&mut parsed);
+        let query_embedding = if parsed.cleaned.is_empty() {
+            None
+        } else {
+            generate_embedding_async(&parsed.cleaned).await.ok()
+        };
+        let search_config = config::search_config();
 
         let search_params = SearchParams {
             query: &parsed.cleaned,
             embedding: query_embedding.as_ref(),
-            weights,
+            config: search_config,
             limit: PAGE_SIZE + 1,
             offset: 0,
+            connections: None,
+            viewer: viewer.as_ref(),
+            syntax: None,
+            scope: None,
         };
 
-        let results = search::search_people(&search_params, &parsed, None)
+        let results = search::search_people(&search_params, &parsed, None, available.as_ref())
             .await
             .unwrap_or_else(|e| {
                 error!("Failed to search people: {}", e);
                 vec![]
             });
 
-        log_search(filter_text, "web", "people", Some(results.len()));
+        if !filter_text.is_empty() {
+            log_search(filter_text, "web", "people", Some(results.len()));
+        }
         (vec![], Some(results))
     } else {
-        let query = r#"
-            SELECT *, verification_status = 'identity' AS _vord FROM person
-            WHERE profile.name IS NOT NULL
-               OR profile.headline IS NOT NULL
-               OR profile.bio IS NOT NULL
-            ORDER BY _vord DESC, created_at DESC
-            LIMIT $limit
-            START $offset
-        "#;
-        let persons = match DB.query(query)
-            .bind(("limit", PAGE_SIZE as i64 + 1))
-            .bind(("offset", 0i64))
+        let page = Person::browse(None, PAGE_SIZE, viewer.is_some())
             .await
-        {
-            Ok(mut result) => match result.take::<Vec<Person>>(0) {
-                Ok(persons) => persons,
-                Err(e) => {
-                    error!("Failed to fetch persons from database: {}", e);
-                    vec![]
+            .unwrap_or_else(|e| {
+                error!("Failed to fetch persons from database: {}", e);
+                Page {
+                    items: vec![],
+                    next: None,
                 }
-            },
-            Err(e) => {
-                error!("Failed to query persons from database: {}", e);
-                vec![]
-            }
-        };
-        (persons, None::<Vec<PersonSearchResult>>)
+            });
+        template.next_cursor = page.next;
+        (page.items, None::<Vec<PersonSearchResult>>)
     };
 
-    // Convert to PersonCards — either from search results or from Person models
+    // Convert to PersonCards — either from search results or from browse listings
     template.has_more = if let Some(ref results) = search_cards {
         results.len() > PAGE_SIZE
     } else {
-        persons.len() > PAGE_SIZE
+        template.next_cursor.is_some()
     };
 
     template.people = if let Some(results) = search_cards {
@@ -486,6 +679,10 @@ async fn people(
             Some(RecordId::new("person", uid.as_str()))
         };
         if let Some(rid) = person_rid {
+            // Leave out people this viewer blocked or muted, or who blocked them
+            let hidden = BlockModel::hidden_ids(&rid).await.unwrap_or_default();
+            template.people.retain(|p| !hidden.contains(&p.id));
+
             let target_ids: Vec<RecordId> = template
                 .people
                 .iter()
@@ -507,8 +704,16 @@ async fn people(
 
 #[derive(Debug, Deserialize)]
 struct PeopleMoreQuery {
+    #[serde(default)]
     offset: usize,
+    /// Where the browse listing left off; searches use `offset`
+    cursor: Option<String>,
     filter: Option<String>,
+    hair: Option<String>,
+    eyes: Option<String>,
+    build: Option<String>,
+    available_from: Option<String>,
+    available_to: Option<String>,
 }
 
 fn sse_patch_elements(selector: &str, mode: &str, elements: &str) -> String {
@@ -595,24 +800,50 @@ fn render_person_card(person: &PersonCard) -> String {
     html
 }
 
-async fn people_more_sse(Query(params): Query<PeopleMoreQuery>) -> Response {
+async fn people_more_sse(Query(params): Query<PeopleMoreQuery>, request: Request) -> Response {
     let filter = params.filter.as_deref().filter(|s| !s.is_empty());
+    let attributes = AttributeFilters::new(
+        params.hair.as_deref(),
+        params.eyes.as_deref(),
+        params.build.as_deref(),
+    );
+    let available = availability_filter(
+        params.available_from.as_deref(),
+        params.available_to.as_deref(),
+    );
     let offset = params.offset;
+    let mut next_cursor = None;
+    let current_user_id = request.get_user().map(|user| user.id.clone());
+    let viewer = match current_user_id.as_deref().map(RecordId::parse_simple) {
+        Some(Ok(rid)) => Viewer::load(&rid).await.ok(),
+        _ => None,
+    };
 
-    let (persons, search_cards) = if let Some(filter_text) = filter {
-        let parsed = search_utils::parse_query(filter_text);
-        let query_embedding = generate_embedding_async(&parsed.cleaned).await.ok();
-        let weights = config::search_weights();
+    let searching = filter.is_some() || !attributes.is_empty() || available.is_some();
+    let (persons, search_cards) = if searching {
+        let filter_text = filter.unwrap_or_default();
+        let mut parsed = search_utils::parse_query(filter_text);
+        attributes.apply(&mut parsed);
+        let query_embedding = if parsed.cleaned.is_empty() {
+            None
+        } else {
+            generate_embedding_async(&parsed.cleaned).await.ok()
+        };
+        let search_config = config::search_config();
 
         let search_params = SearchParams {
             query: &parsed.cleaned,
             embedding: query_embedding.as_ref(),
-            weights,
+            config: search_config,
             limit: PAGE_SIZE + 1,
             offset,
+            connections: None,
+            viewer: viewer.as_ref(),
+            syntax: None,
+            scope: None,
         };
 
-        let results = search::search_people(&search_params, &parsed, None)
+        let results = search::search_people(&search_params, &parsed, None, available.as_ref())
             .await
             .unwrap_or_else(|e| {
                 error!("Failed to search people (SSE): {}", e);
@@ -621,31 +852,21 @@ async fn people_more_sse(Query(params): Query<PeopleMoreQuery>) -> Response {
 
         (vec![], Some(results))
     } else {
-        let query = r#"
-            SELECT *, verification_status = 'identity' AS _vord FROM person
-            WHERE profile.name IS NOT NULL
-               OR profile.headline IS NOT NULL
-               OR profile.bio IS NOT NULL
-            ORDER BY _vord DESC, created_at DESC
-            LIMIT $limit
-            START $offset
-        "#;
-        let persons: Vec<Person> = match DB
-            .query(query)
-            .bind(("limit", PAGE_SIZE as i64 + 1))
-            .bind(("offset", offset as i64))
+        // An invalid cursor ends the listing
+        let page = Person::browse(params.cursor.as_deref(), PAGE_SIZE, viewer.is_some())
             .await
-        {
-            Ok(mut result) => result.take::<Vec<Person>>(0).unwrap_or_default(),
-            Err(_) => vec![],
-        };
-        (persons, None::<Vec<PersonSearchResult>>)
+            .unwrap_or(Page {
+                items: vec![],
+                next: None,
+            });
+        next_cursor = page.next;
+        (page.items, None::<Vec<PersonSearchResult>>)
     };
 
     let has_more = if let Some(ref results) = search_cards {
         results.len() > PAGE_SIZE
     } else {
-        persons.len() > PAGE_SIZE
+        next_cursor.is_some()
     };
 
     let cards: Vec<PersonCard> = if let Some(results) = search_cards {
@@ -698,14 +919,19 @@ async fn people_more_sse(Query(params): Query<PeopleMoreQuery>) -> Response {
     }
 
     if has_more {
-        let new_offset = offset + PAGE_SIZE;
-        let q_param = match filter {
+        // Searches page by offset through their ranked window; browsing by cursor
+        let position = match next_cursor {
+            Some(cursor) => format!("cursor={}", cursor),
+            None => format!("offset={}", offset + PAGE_SIZE),
+        };
+        let mut q_param = match filter {
             Some(f) => format!("&filter={}", urlencoding::encode(f)),
             None => String::new(),
         };
+        q_param.push_str(&attributes.query_string());
         replacement.push_str(&format!(
-            r#"<div id="people-sentinel" data-on-intersect="@get('/api/people/more-sse?offset={}{}')"><div class="people-loading">Loading more...</div></div>"#,
-            new_offset, q_param
+            r#"<div id="people-sentinel" data-on-intersect="@get('/api/people/more-sse?{}{}')"><div class="people-loading">Loading more...</div></div>"#,
+            position, q_param
         ));
     }
 
@@ -0,0 +1,151 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::get,
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::error;
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        block::BlockModel,
+        reaction::{self, EMOJI, ReactionCount, ReactionModel},
+    },
+    response::local_path,
+    services::reaction_stream::{self, ReactionEvent},
+};
+
+pub fn router() -> Router {
+    Router::new().route(
+        "/reactions/{table}/{key}",
+        get(reaction_bar).post(toggle_reaction),
+    )
+}
+
+/// A record's reactions and the buttons to add to them
+#[derive(Template)]
+#[template(path = "reactions/_bar.html")]
+pub struct ReactionBarTemplate {
+    pub table: &'static str,
+    pub key: String,
+    pub counts: Vec<ReactionCount>,
+    /// The page a reaction made without scripts returns to
+    pub next: String,
+}
+
+impl ReactionBarTemplate {
+    fn region(&self) -> String {
+        region(self.table, &self.key)
+    }
+
+    fn palette(&self) -> &'static [&'static str] {
+        &EMOJI
+    }
+}
+
+/// Element id of a record's reaction bar
+pub fn region(table: &str, key: &str) -> String {
+    format!("reactions-{}-{}", table, key)
+}
+
+/// A record's reaction bar as HTML, for pages that list reactable records
+pub fn render_bar(
+    table: &'static str,
+    key: String,
+    counts: Vec<ReactionCount>,
+    next: &str,
+) -> String {
+    let template = ReactionBarTemplate {
+        table,
+        key,
+        counts,
+        next: next.to_string(),
+    };
+    template.render().unwrap_or_else(|e| {
+        error!("Failed to render reaction bar: {}", e);
+        String::new()
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct BarQuery {
+    next: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReactForm {
+    emoji: String,
+    next: Option<String>,
+}
+
+/// The reactable record named in a URL, and the people who can see it.
+/// Anyone else is told it doesn't exist.
+async fn require_audience(
+    table: &str,
+    key: &str,
+    user_id: &str,
+) -> Result<(&'static str, RecordId, Vec<String>), Error> {
+    let table = reaction::TABLES
+        .into_iter()
+        .find(|t| *t == table)
+        .ok_or(Error::NotFound)?;
+    let target = reaction::target(table, key)?;
+    let audience = ReactionModel::audience(&target).await?;
+    if !audience.iter().any(|p| p == user_id) {
+        return Err(Error::NotFound);
+    }
+    Ok((table, target, audience))
+}
+
+/// Just the reaction bar, reloaded when someone else reacts
+async fn reaction_bar(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((table, key)): Path<(String, String)>,
+    Query(query): Query<BarQuery>,
+) -> Result<Response, Error> {
+    let (table, target, _) = require_audience(&table, &key, &user.id).await?;
+    let counts = ReactionModel::counts_for(&target, &user.id).await?;
+    let next = local_path(query.next).unwrap_or_default();
+    Ok(Html(render_bar(table, key, counts, &next)).into_response())
+}
+
+/// Add or take back a reaction. Posted from the page (no scripts) it returns
+/// to `next`; posted by reactions.js it returns the updated bar.
+async fn toggle_reaction(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((table, key)): Path<(String, String)>,
+    Form(form): Form<ReactForm>,
+) -> Result<Response, Error> {
+    let (table, target, mut audience) = require_audience(&table, &key, &user.id).await?;
+    let person = RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))?;
+
+    // Blocking stops reactions in a conversation along with messages
+    if table == "direct_message" {
+        for other in audience.iter().filter(|p| **p != user.id) {
+            let other =
+                RecordId::parse_simple(other).map_err(|e| Error::BadRequest(e.to_string()))?;
+            if BlockModel::is_blocked_between(&person, &other).await? {
+                return Err(Error::Forbidden);
+            }
+        }
+    }
+
+    ReactionModel::toggle(&person, &target, &form.emoji).await?;
+
+    audience.retain(|p| *p != user.id);
+    reaction_stream::publish(ReactionEvent {
+        region: region(table, &key),
+        audience,
+    });
+
+    if let Some(next) = local_path(form.next) {
+        return Ok(Redirect::to(&next).into_response());
+    }
+    let counts = ReactionModel::counts_for(&target, &user.id).await?;
+    Ok(Html(render_bar(table, key, counts, "")).into_response())
+}
@@ -0,0 +1,176 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        organization::{Organization, OrganizationModel},
+        person::SessionUser,
+    },
+    record_id_ext::RecordIdExt,
+    services::retention::{self, PurgeCounts, PurgeRecord, RetentionPolicy},
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/orgs/{slug}/retention",
+            get(retention_page).post(save_policy),
+        )
+        .route("/orgs/{slug}/retention/purge", post(purge_now))
+}
+
+#[derive(Template)]
+#[template(path = "organizations/retention.html")]
+pub struct RetentionTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub organization_name: String,
+    pub organization_slug: String,
+    pub policy: RetentionPolicy,
+    /// What the saved rules would remove right now
+    pub preview: PurgeCounts,
+    pub history: Vec<PurgeRecord>,
+    pub message: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetentionQuery {
+    pub message: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyForm {
+    #[serde(default)]
+    pub applications_months: String,
+    #[serde(default)]
+    pub invites_days: String,
+    #[serde(default)]
+    pub share_links_days: String,
+}
+
+/// Retention rules delete the organization's data, so only owners and admins
+/// see or change them
+async fn require_manager(slug: &str, user: &SessionUser) -> Result<Organization, Error> {
+    let model = OrganizationModel::new();
+    let organization = model.get_by_slug(slug).await?;
+    let role = model
+        .get_member_role(&organization.id.to_raw_string(), &user.id)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    if !matches!(role.as_str(), "owner" | "admin") {
+        return Err(Error::Forbidden);
+    }
+    Ok(organization)
+}
+
+fn back_to_retention(slug: &str, key: &str, text: &str) -> Response {
+    Redirect::to(&format!(
+        "/orgs/{}/retention?{}={}",
+        slug,
+        key,
+        urlencoding::encode(text)
+    ))
+    .into_response()
+}
+
+async fn retention_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<RetentionQuery>,
+) -> Result<Response, Error> {
+    let organization = require_manager(&slug, &user).await?;
+    let policy = retention::get_policy(&organization.id).await?;
+    let preview = retention::preview(&organization.id, &policy).await?;
+    let history = retention::history(&organization.id).await?;
+
+    let base = BaseContext::new()
+        .with_page("organizations")
+        .with_user(User::from_session_user(&user).await);
+    let template = RetentionTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        organization_name: organization.name,
+        organization_slug: organization.slug,
+        policy,
+        preview,
+        history,
+        message: query.message,
+        error: query.error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render retention template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn save_policy(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<PolicyForm>,
+) -> Result<Response, Error> {
+    let organization = require_manager(&slug, &user).await?;
+    let policy = match RetentionPolicy::parse(
+        &form.applications_months,
+        &form.invites_days,
+        &form.share_links_days,
+    ) {
+        Ok(policy) => policy,
+        Err(Error::Validation(msg)) => return Ok(back_to_retention(&slug, "error", &msg)),
+        Err(e) => return Err(e),
+    };
+
+    retention::set_policy(&organization.id, &policy).await?;
+    info!(
+        "{} set retention rules for {}: {:?}",
+        user.username, slug, policy
+    );
+    Ok(back_to_retention(
+        &slug,
+        "message",
+        "Saved. The rules run once a day.",
+    ))
+}
+
+/// Apply the saved rules now instead of waiting for the daily run
+async fn purge_now(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let organization = require_manager(&slug, &user).await?;
+    let policy = retention::get_policy(&organization.id).await?;
+    if policy.is_empty() {
+        return Ok(back_to_retention(
+            &slug,
+            "error",
+            "Set at least one rule first",
+        ));
+    }
+
+    let person = RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    let counts = retention::enforce(&organization.id, &policy, Some(&person)).await?;
+    Ok(back_to_retention(
+        &slug,
+        "message",
+        &format!("Removed {} items", counts.total()),
+    ))
+}
@@ -0,0 +1,116 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::{Path, Query},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        organization::{Organization, OrganizationModel},
+        person::SessionUser,
+        representation::{RepresentationModel, RosterEntry},
+        talent_submission::{Submission, TalentSubmissionModel},
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/orgs/{slug}/roster", get(roster_page))
+        .route("/orgs/{slug}/roster/{person_id}/remove", post(drop_client))
+}
+
+#[derive(Template)]
+#[template(path = "organizations/roster.html")]
+pub struct RosterTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub organization_name: String,
+    pub organization_slug: String,
+    pub roster: Vec<RosterEntry>,
+    /// Every submission the agency has made, newest first
+    pub submissions: Vec<Submission>,
+    /// Owners and admins can take clients off the roster
+    pub can_manage: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RosterQuery {
+    pub error: Option<String>,
+}
+
+/// The organization and the user's role in it, if they're a member
+async fn require_member(slug: &str, user: &SessionUser) -> Result<(Organization, String), Error> {
+    let model = OrganizationModel::new();
+    let organization = model.get_by_slug(slug).await?;
+    let role = model
+        .get_member_role(&organization.id.to_raw_string(), &user.id)
+        .await?
+        .ok_or(Error::Forbidden)?;
+    Ok((organization, role))
+}
+
+fn can_manage(role: &str) -> bool {
+    matches!(role, "owner" | "admin")
+}
+
+async fn roster_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Query(query): Query<RosterQuery>,
+) -> Result<Response, Error> {
+    let (organization, role) = require_member(&slug, &user).await?;
+    let roster = RepresentationModel::roster(&organization.id).await?;
+    let submissions = TalentSubmissionModel::for_agency(&organization.id).await?;
+
+    let base = BaseContext::new()
+        .with_page("organizations")
+        .with_user(User::from_session_user(&user).await);
+    let template = RosterTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        organization_name: organization.name,
+        organization_slug: organization.slug,
+        roster,
+        submissions,
+        can_manage: can_manage(&role),
+        error: query.error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render roster template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+/// Take a client off the roster; they go back to being contacted directly
+async fn drop_client(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, person_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let (organization, role) = require_member(&slug, &user).await?;
+    if !can_manage(&role) {
+        return Err(Error::Forbidden);
+    }
+
+    RepresentationModel::drop_client(&organization.id, &person_id).await?;
+    info!(
+        "{} dropped {} from {}'s roster",
+        user.username, person_id, slug
+    );
+    Ok(Redirect::to(&format!("/orgs/{}/roster", slug)).into_response())
+}
@@ -0,0 +1,124 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        person::SessionUser,
+        search_alert::{self, SearchAlert, SearchAlertModel},
+    },
+    services::search_facets::SearchFilters,
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/search/saved", get(saved_page).post(save_search))
+        .route("/search/saved/{id}/delete", post(delete_search))
+}
+
+#[derive(Template)]
+#[template(path = "search/saved.html")]
+pub struct SavedSearchesTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub searches: Vec<SearchAlert>,
+    pub max_searches: usize,
+    pub success: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SavedQuery {
+    pub success: Option<String>,
+    pub error: Option<String>,
+}
+
+/// The search page's query and facets, as posted by its "Save Search" button
+#[derive(Debug, Deserialize)]
+pub struct SaveForm {
+    #[serde(default)]
+    pub q: String,
+    #[serde(flatten)]
+    pub filters: SearchFilters,
+}
+
+fn person_id(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+fn back_to_saved(key: &str, message: &str) -> Response {
+    Redirect::to(&format!(
+        "/search/saved?{}={}",
+        key,
+        urlencoding::encode(message)
+    ))
+    .into_response()
+}
+
+async fn saved_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(query): Query<SavedQuery>,
+) -> Result<Response, Error> {
+    let searches = SearchAlertModel::list(&person_id(&user)?).await?;
+
+    let base = BaseContext::new()
+        .with_page("search")
+        .with_user(User::from_session_user(&user).await);
+    let template = SavedSearchesTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        searches,
+        max_searches: search_alert::MAX_PER_PERSON,
+        success: query.success,
+        error: query.error,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render saved searches template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn save_search(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Form(form): Form<SaveForm>,
+) -> Result<Response, Error> {
+    match SearchAlertModel::create(&person_id(&user)?, &form.q, &form.filters).await {
+        Ok(()) => {}
+        Err(Error::Validation(message)) => return Ok(back_to_saved("error", &message)),
+        Err(e) => return Err(e),
+    }
+    info!(
+        "{} saved a search: {}",
+        user.username,
+        search_alert::describe(form.q.trim(), &form.filters)
+    );
+    Ok(back_to_saved(
+        "success",
+        "Search saved. We'll let you know when something new matches.",
+    ))
+}
+
+async fn delete_search(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Response, Error> {
+    SearchAlertModel::delete(&person_id(&user)?, &id).await?;
+    Ok(back_to_saved("success", "Saved search removed."))
+}
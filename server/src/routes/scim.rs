@@ -0,0 +1,325 @@
+//! SCIM 2.0 Users endpoint for organization provisioning
+//!
+//! Authenticated with the organization's SCIM bearer token (see
+//! `services::sso`). Users are the organization's accepted members, identified
+//! by their person id and with their email as `userName`.
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use surrealdb::types::RecordId;
+use tracing::{error, info, warn};
+
+use crate::{
+    error::Error,
+    models::organization::{Organization, OrganizationModel},
+    record_id_ext::RecordIdExt,
+    services::sso::{self, ProvisionedUser, SsoConfig},
+};
+
+const USER_SCHEMA: &str = "urn:ietf:params:scim:schemas:core:2.0:User";
+const LIST_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:ListResponse";
+const ERROR_SCHEMA: &str = "urn:ietf:params:scim:api:messages:2.0:Error";
+const MAX_PAGE_SIZE: usize = 200;
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/scim/v2/{slug}/Users", get(list_users).post(create_user))
+        .route(
+            "/scim/v2/{slug}/Users/{id}",
+            get(get_user).put(replace_user).patch(patch_user).delete(delete_user),
+        )
+}
+
+/// SCIM error body (RFC 7644 §3.12)
+struct ScimError {
+    status: StatusCode,
+    detail: String,
+}
+
+impl ScimError {
+    fn new(status: StatusCode, detail: impl Into<String>) -> Self {
+        Self {
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl From<Error> for ScimError {
+    fn from(e: Error) -> Self {
+        let status = e.status_code();
+        if status.is_server_error() {
+            error!("SCIM request failed: {}", e);
+            return Self::new(status, "Internal error");
+        }
+        let detail = match e {
+            Error::Conflict(m) | Error::Validation(m) | Error::BadRequest(m) => m,
+            other => other.to_string(),
+        };
+        Self::new(status, detail)
+    }
+}
+
+impl IntoResponse for ScimError {
+    fn into_response(self) -> Response {
+        scim_json(
+            self.status,
+            json!({
+                "schemas": [ERROR_SCHEMA],
+                "status": self.status.as_u16().to_string(),
+                "detail": self.detail,
+            }),
+        )
+    }
+}
+
+fn scim_json(status: StatusCode, body: Value) -> Response {
+    (status, [(header::CONTENT_TYPE, "application/scim+json")], Json(body)).into_response()
+}
+
+/// Resolve the organization and check the bearer token against its SCIM token
+async fn authorize(slug: &str, headers: &HeaderMap) -> Result<(Organization, SsoConfig), ScimError> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+        .ok_or_else(|| ScimError::new(StatusCode::UNAUTHORIZED, "Bearer token required"))?;
+
+    let organization = match OrganizationModel::new().get_by_slug(slug).await {
+        Ok(org) => org,
+        // Same answer as a bad token, so slugs can't be probed
+        Err(Error::NotFound) => return Err(ScimError::new(StatusCode::UNAUTHORIZED, "Invalid token")),
+        Err(e) => return Err(e.into()),
+    };
+
+    if !sso::verify_scim_token(&organization.id, token).await? {
+        warn!("Rejected SCIM request for {} with an invalid token", slug);
+        return Err(ScimError::new(StatusCode::UNAUTHORIZED, "Invalid token"));
+    }
+
+    let config = sso::get_config(&organization.id)
+        .await?
+        .ok_or_else(|| ScimError::new(StatusCode::UNAUTHORIZED, "Invalid token"))?;
+    Ok((organization, config))
+}
+
+fn person_id(id: &str) -> Result<RecordId, ScimError> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(ScimError::new(StatusCode::NOT_FOUND, "User not found"));
+    }
+    Ok(RecordId::new("person", id))
+}
+
+fn user_resource(slug: &str, user: &ProvisionedUser, active: bool) -> Value {
+    let id = user.person.key_string();
+    let display_name = user.name.clone().unwrap_or_else(|| user.username.clone());
+    json!({
+        "schemas": [USER_SCHEMA],
+        "id": id,
+        "userName": user.email,
+        "displayName": display_name,
+        "name": { "formatted": display_name },
+        "emails": [{ "value": user.email, "primary": true }],
+        "active": active,
+        "meta": {
+            "resourceType": "User",
+            "location": format!("{}/scim/v2/{}/Users/{}", crate::config::app_url(), slug, id),
+        },
+    })
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ListQuery {
+    filter: Option<String>,
+    start_index: Option<usize>,
+    count: Option<usize>,
+}
+
+async fn list_users(
+    Path(slug): Path<String>,
+    Query(query): Query<ListQuery>,
+    headers: HeaderMap,
+) -> Result<Response, ScimError> {
+    let (organization, _) = authorize(&slug, &headers).await?;
+
+    let email = match query.filter.as_deref() {
+        Some(filter) => Some(sso::parse_user_filter(filter).ok_or_else(|| {
+            ScimError::new(StatusCode::BAD_REQUEST, "Only userName eq filters are supported")
+        })?),
+        None => None,
+    };
+
+    let users = sso::members(&organization.id, email.as_deref()).await?;
+    let start = query.start_index.unwrap_or(1).max(1);
+    let count = query.count.unwrap_or(MAX_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    let page: Vec<Value> = users
+        .iter()
+        .skip(start - 1)
+        .take(count)
+        .map(|u| user_resource(&slug, u, true))
+        .collect();
+
+    Ok(scim_json(
+        StatusCode::OK,
+        json!({
+            "schemas": [LIST_SCHEMA],
+            "totalResults": users.len(),
+            "startIndex": start,
+            "itemsPerPage": page.len(),
+            "Resources": page,
+        }),
+    ))
+}
+
+async fn get_user(
+    Path((slug, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, ScimError> {
+    let (organization, _) = authorize(&slug, &headers).await?;
+    let user = sso::member(&organization.id, &person_id(&id)?)
+        .await?
+        .ok_or_else(|| ScimError::new(StatusCode::NOT_FOUND, "User not found"))?;
+    Ok(scim_json(StatusCode::OK, user_resource(&slug, &user, true)))
+}
+
+/// Email for a SCIM user payload: the primary email, else `userName`
+fn payload_email(body: &Value) -> Option<String> {
+    let emails = body.get("emails").and_then(|e| e.as_array());
+    emails
+        .and_then(|e| {
+            e.iter()
+                .find(|e| e.get("primary").and_then(|p| p.as_bool()) == Some(true))
+                .or_else(|| e.first())
+        })
+        .and_then(|e| e.get("value"))
+        .and_then(|v| v.as_str())
+        .or_else(|| body.get("userName").and_then(|u| u.as_str()))
+        .map(|e| e.trim().to_lowercase())
+        .filter(|e| e.contains('@'))
+}
+
+fn payload_name(body: &Value) -> Option<String> {
+    let name = body.get("name");
+    if let Some(formatted) = name.and_then(|n| n.get("formatted")).and_then(|f| f.as_str()) {
+        return Some(formatted.to_string());
+    }
+    let parts: Vec<&str> = ["givenName", "familyName"]
+        .iter()
+        .filter_map(|k| name.and_then(|n| n.get(*k)).and_then(|v| v.as_str()))
+        .collect();
+    if parts.is_empty() {
+        body.get("displayName").and_then(|d| d.as_str()).map(str::to_string)
+    } else {
+        Some(parts.join(" "))
+    }
+}
+
+async fn create_user(
+    Path(slug): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Result<Response, ScimError> {
+    let (organization, config) = authorize(&slug, &headers).await?;
+    let email = payload_email(&body)
+        .ok_or_else(|| ScimError::new(StatusCode::BAD_REQUEST, "userName or emails must contain an email address"))?;
+
+    if !sso::members(&organization.id, Some(&email)).await?.is_empty() {
+        return Err(ScimError::new(StatusCode::CONFLICT, "User already exists"));
+    }
+    if !config.allows_email(&email) {
+        return Err(ScimError::new(StatusCode::BAD_REQUEST, "Email domain is not allowed for this organization"));
+    }
+
+    let person = sso::provision(&config, &organization.id, &email, payload_name(&body).as_deref()).await?;
+    info!("SCIM provisioned {} into {}", email, slug);
+
+    let user = sso::member(&organization.id, &person.id)
+        .await?
+        .ok_or_else(|| ScimError::new(StatusCode::INTERNAL_SERVER_ERROR, "Provisioning failed"))?;
+    Ok(scim_json(StatusCode::CREATED, user_resource(&slug, &user, true)))
+}
+
+/// Apply an `active` change: `false` removes the membership, `true` restores
+/// it for someone on one of the organization's verified domains
+async fn set_active(
+    slug: &str,
+    organization: &Organization,
+    config: &SsoConfig,
+    person: &RecordId,
+    active: bool,
+) -> Result<Response, ScimError> {
+    let existing = sso::member(&organization.id, person).await?;
+
+    if !active {
+        let Some(user) = existing else {
+            return Err(ScimError::new(StatusCode::NOT_FOUND, "User not found"));
+        };
+        sso::deprovision(person, &organization.id).await?;
+        return Ok(scim_json(StatusCode::OK, user_resource(slug, &user, false)));
+    }
+
+    let user = match existing {
+        Some(user) => user,
+        None => {
+            let person = crate::models::person::Person::get(person)
+                .await?
+                .ok_or_else(|| ScimError::new(StatusCode::NOT_FOUND, "User not found"))?;
+            // Only people the organization could have provisioned, on its
+            // verified domains; anyone else looks like they don't exist
+            if !config.allows_email(&person.email) {
+                return Err(ScimError::new(StatusCode::NOT_FOUND, "User not found"));
+            }
+            sso::provision(config, &organization.id, &person.email, None).await?;
+            sso::member(&organization.id, &person.id)
+                .await?
+                .ok_or_else(|| ScimError::new(StatusCode::INTERNAL_SERVER_ERROR, "Provisioning failed"))?
+        }
+    };
+    Ok(scim_json(StatusCode::OK, user_resource(slug, &user, true)))
+}
+
+async fn patch_user(
+    Path((slug, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Result<Response, ScimError> {
+    let (organization, config) = authorize(&slug, &headers).await?;
+    let person = person_id(&id)?;
+
+    match sso::patch_active(&body) {
+        Some(active) => set_active(&slug, &organization, &config, &person, active).await,
+        // Profile attributes belong to the person, so other changes are accepted and ignored
+        None => get_user(Path((slug, id)), headers).await,
+    }
+}
+
+async fn replace_user(
+    Path((slug, id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Result<Response, ScimError> {
+    let (organization, config) = authorize(&slug, &headers).await?;
+    let active = body.get("active").and_then(|a| a.as_bool()).unwrap_or(true);
+    set_active(&slug, &organization, &config, &person_id(&id)?, active).await
+}
+
+async fn delete_user(
+    Path((slug, id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, ScimError> {
+    let (organization, _) = authorize(&slug, &headers).await?;
+    if !sso::deprovision(&person_id(&id)?, &organization.id).await? {
+        return Err(ScimError::new(StatusCode::NOT_FOUND, "User not found"));
+    }
+    info!("SCIM deprovisioned person:{} from {}", id, slug);
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -0,0 +1,652 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Request},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::{AuthenticatedUser, UserExtractor},
+    models::{
+        location::LocationModel,
+        person::SessionUser,
+        production::{Production, ProductionModel},
+        scouting::{self, ScoutingCollection, ScoutingEntry, ScoutingEntryListing, ScoutingModel},
+        share_link::{self, ShareAccess},
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, User, filters},
+    units::{self, UnitSystem},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route(
+            "/productions/{slug}/scouting",
+            get(collections_page).post(create_collection),
+        )
+        .route(
+            "/productions/{slug}/scouting/{collection_id}",
+            get(collection_page),
+        )
+        .route(
+            "/productions/{slug}/scouting/{collection_id}/rename",
+            post(rename_collection),
+        )
+        .route(
+            "/productions/{slug}/scouting/{collection_id}/basecamp",
+            post(set_basecamp),
+        )
+        .route(
+            "/productions/{slug}/scouting/{collection_id}/share",
+            post(share_collection),
+        )
+        .route(
+            "/productions/{slug}/scouting/{collection_id}/delete",
+            post(delete_collection),
+        )
+        .route(
+            "/productions/{slug}/scouting/entries/{entry_id}/note",
+            post(save_note),
+        )
+        .route(
+            "/productions/{slug}/scouting/entries/{entry_id}/remove",
+            post(remove_entry),
+        )
+        .route("/locations/{id}/scout", post(save_location))
+        .route("/scouting/shared/{token}", get(shared_page))
+        .route(
+            "/scouting/shared/{token}/entries/{entry_id}/pick",
+            post(pick_entry),
+        )
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct CollectionRow {
+    pub id: String,
+    pub name: String,
+    pub basecamp: Option<String>,
+    pub locations: i64,
+    pub picked: i64,
+    pub shared: bool,
+}
+
+/// One location in the comparison
+pub struct LocationColumn {
+    pub id: String,
+    pub location_id: String,
+    pub name: String,
+    pub address: String,
+    pub place: String,
+    pub photo: Option<String>,
+    pub thumbnails: Vec<String>,
+    pub capacity: Option<i32>,
+    pub parking: Option<String>,
+    pub amenities: String,
+    pub rates: Vec<String>,
+    /// Distance to basecamp; `None` without coordinates at either end
+    pub distance: Option<String>,
+    pub note: String,
+    pub selected: bool,
+}
+
+#[derive(Template)]
+#[template(path = "productions/scouting.html")]
+pub struct CollectionsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub collections: Vec<CollectionRow>,
+    pub name: String,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/scouting_collection.html")]
+pub struct CollectionTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub collection_id: String,
+    pub name: String,
+    pub basecamp: String,
+    pub basecamp_coordinates: String,
+    pub columns: Vec<LocationColumn>,
+    /// The link just made; its token isn't stored, so this is the only time
+    /// it can be shown
+    pub share_url: Option<String>,
+    pub share_access: String,
+    pub share_expires: Option<String>,
+    pub max_note: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/scouting_shared.html")]
+pub struct SharedCollectionTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub token: String,
+    pub name: String,
+    pub production_title: String,
+    pub basecamp: Option<String>,
+    pub columns: Vec<LocationColumn>,
+    pub can_pick: bool,
+}
+
+// ============================
+// Helpers
+// ============================
+
+fn person_id(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+fn share_url(token: &str) -> String {
+    format!("{}/scouting/shared/{}", crate::config::app_url(), token)
+}
+
+/// Scouting is for the production's members
+async fn check_member(production: &Production, user_id: &str) -> Result<(), Error> {
+    if !ProductionModel::can_edit(&production.id, user_id).await?
+        && !ProductionModel::is_member(&production.id, user_id).await?
+    {
+        return Err(Error::Forbidden);
+    }
+    Ok(())
+}
+
+async fn require_member(slug: &str, user_id: &str) -> Result<Production, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    check_member(&production, user_id).await?;
+    Ok(production)
+}
+
+async fn require_collection(
+    slug: &str,
+    collection_id: &str,
+    user: &SessionUser,
+) -> Result<(ScoutingCollection, Production), Error> {
+    let production = require_member(slug, &user.id).await?;
+    let collection = ScoutingModel::get(collection_id).await?;
+    if collection.production != production.id {
+        return Err(Error::NotFound);
+    }
+    Ok((collection, production))
+}
+
+async fn require_entry(
+    slug: &str,
+    entry_id: &str,
+    user: &SessionUser,
+) -> Result<(ScoutingEntry, ScoutingCollection, Production), Error> {
+    let entry = ScoutingModel::get_entry(entry_id).await?;
+    let (collection, production) =
+        require_collection(slug, &entry.collection.key_string(), user).await?;
+    Ok((entry, collection, production))
+}
+
+fn format_rate(rate: &scouting::ScoutingRate) -> String {
+    let minimum = rate
+        .minimum_duration
+        .map(|m| format!(" (min {})", m))
+        .unwrap_or_default();
+    format!(
+        "{} {:.2} / {}{}",
+        rate.currency, rate.amount, rate.rate_type, minimum
+    )
+}
+
+/// `units` is the viewer's preference; without one, distances are in km
+fn location_column(
+    entry: ScoutingEntryListing,
+    basecamp: Option<(f64, f64)>,
+    units: Option<UnitSystem>,
+) -> LocationColumn {
+    let distance = basecamp
+        .zip(entry.point())
+        .map(|(from, to)| units::distance(units::distance_km(from, to), units.unwrap_or_default()));
+    let place = [
+        entry.city.as_str(),
+        entry.state.as_str(),
+        entry.country.as_str(),
+    ]
+    .iter()
+    .filter(|p| !p.is_empty())
+    .copied()
+    .collect::<Vec<_>>()
+    .join(", ");
+    LocationColumn {
+        id: entry.id.key_string(),
+        location_id: entry.location.key_string(),
+        photo: entry
+            .profile_photo
+            .clone()
+            .or_else(|| entry.photos.first().map(|p| p.url.clone())),
+        thumbnails: entry
+            .photos
+            .iter()
+            .take(4)
+            .map(|p| p.thumbnail_url.clone())
+            .collect(),
+        rates: entry.rates.iter().map(format_rate).collect(),
+        name: entry.name,
+        address: entry.address,
+        place,
+        capacity: entry.max_capacity,
+        parking: entry.parking_info.filter(|p| !p.is_empty()),
+        amenities: entry.amenities.join(", "),
+        distance,
+        note: entry.note.unwrap_or_default(),
+        selected: entry.selected,
+    }
+}
+
+async fn columns(
+    collection: &ScoutingCollection,
+    viewer_id: Option<&str>,
+) -> Result<Vec<LocationColumn>, Error> {
+    let units = units::viewer_preference(viewer_id).await;
+    let basecamp = collection.basecamp_point();
+    Ok(ScoutingModel::entries(&collection.id)
+        .await?
+        .into_iter()
+        .map(|e| location_column(e, basecamp, units))
+        .collect())
+}
+
+async fn render_collections(
+    user: &SessionUser,
+    production: Production,
+    name: String,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let collections = ScoutingModel::for_production(&production.id)
+        .await?
+        .into_iter()
+        .map(|c| CollectionRow {
+            id: c.id.key_string(),
+            name: c.name,
+            basecamp: c.basecamp,
+            locations: c.locations,
+            picked: c.picked,
+            shared: c.shared,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+    let template = CollectionsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        collections,
+        name,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render scouting collections template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn render_collection(
+    user: &SessionUser,
+    collection: ScoutingCollection,
+    production: Production,
+    share_url: Option<String>,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let columns = columns(&collection, Some(&user.id)).await?;
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+    let template = CollectionTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: production.title,
+        production_slug: production.slug,
+        collection_id: collection.id.key_string(),
+        basecamp: collection.basecamp.clone().unwrap_or_default(),
+        basecamp_coordinates: collection
+            .basecamp_point()
+            .map(units::format_coordinates)
+            .unwrap_or_default(),
+        share_url,
+        share_expires: collection
+            .access()
+            .and(collection.share_expires_at)
+            .map(|at| at.format("%b %-d, %Y at %H:%M UTC").to_string()),
+        share_access: collection
+            .access()
+            .map(|a| a.as_str())
+            .unwrap_or("off")
+            .to_string(),
+        name: collection.name,
+        columns,
+        max_note: scouting::MAX_NOTE_CHARS,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render scouting collection template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+fn back_to_collection(slug: &str, collection: &RecordId, entry: Option<&RecordId>) -> Response {
+    let anchor = entry
+        .map(|e| format!("#entry-{}", e.key_string()))
+        .unwrap_or_default();
+    Redirect::to(&format!(
+        "/productions/{}/scouting/{}{}",
+        slug,
+        collection.key_string(),
+        anchor
+    ))
+    .into_response()
+}
+
+// ============================
+// Member handlers
+// ============================
+
+async fn collections_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let production = require_member(&slug, &user.id).await?;
+    render_collections(&user, production, String::new(), None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct NameForm {
+    #[serde(default)]
+    name: String,
+}
+
+async fn create_collection(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<NameForm>,
+) -> Result<Response, Error> {
+    let production = require_member(&slug, &user.id).await?;
+    match ScoutingModel::create(&production.id, &form.name, &person_id(&user)?).await {
+        Ok(collection) => {
+            info!(
+                "{} created scouting collection '{}' on {}",
+                user.username, collection.name, slug
+            );
+            Ok(back_to_collection(&slug, &collection.id, None))
+        }
+        Err(Error::Validation(msg)) => {
+            render_collections(&user, production, form.name, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn collection_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, collection_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let (collection, production) = require_collection(&slug, &collection_id, &user).await?;
+    render_collection(&user, collection, production, None, None).await
+}
+
+async fn rename_collection(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, collection_id)): Path<(String, String)>,
+    Form(form): Form<NameForm>,
+) -> Result<Response, Error> {
+    let (collection, production) = require_collection(&slug, &collection_id, &user).await?;
+    match ScoutingModel::rename(&collection.id, &form.name).await {
+        Ok(()) => Ok(back_to_collection(&slug, &collection.id, None)),
+        Err(Error::Validation(msg)) => {
+            render_collection(&user, collection, production, None, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BasecampForm {
+    #[serde(default)]
+    basecamp: String,
+    #[serde(default)]
+    coordinates: String,
+}
+
+async fn set_basecamp(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, collection_id)): Path<(String, String)>,
+    Form(form): Form<BasecampForm>,
+) -> Result<Response, Error> {
+    let (collection, production) = require_collection(&slug, &collection_id, &user).await?;
+    match scouting::parse_basecamp(&form.basecamp, &form.coordinates) {
+        Ok((basecamp, point)) => {
+            ScoutingModel::set_basecamp(&collection.id, basecamp, point).await?;
+            Ok(back_to_collection(&slug, &collection.id, None))
+        }
+        Err(Error::Validation(msg)) => {
+            render_collection(&user, collection, production, None, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ShareForm {
+    /// "view", "pick" or "off"
+    #[serde(default)]
+    access: String,
+}
+
+async fn share_collection(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, collection_id)): Path<(String, String)>,
+    Form(form): Form<ShareForm>,
+) -> Result<Response, Error> {
+    let (collection, production) = require_collection(&slug, &collection_id, &user).await?;
+    match ShareAccess::parse(&form.access) {
+        Some(access) => {
+            let token = share_link::share(&collection.id, collection.access(), access).await?;
+            info!(
+                "{} shared scouting collection {} ({})",
+                user.username,
+                collection_id,
+                access.as_str()
+            );
+            if let Some(token) = token {
+                let collection = ScoutingModel::get(&collection_id).await?;
+                let url = share_url(&token);
+                return render_collection(&user, collection, production, Some(url), None).await;
+            }
+        }
+        None => {
+            share_link::unshare(&collection.id).await?;
+            info!(
+                "{} stopped sharing scouting collection {}",
+                user.username, collection_id
+            );
+        }
+    }
+    Ok(Redirect::to(&format!(
+        "/productions/{}/scouting/{}#share",
+        slug, collection_id
+    ))
+    .into_response())
+}
+
+async fn delete_collection(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, collection_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let (collection, _) = require_collection(&slug, &collection_id, &user).await?;
+    ScoutingModel::delete(&collection.id).await?;
+    info!(
+        "{} deleted scouting collection {}",
+        user.username, collection_id
+    );
+    Ok(Redirect::to(&format!("/productions/{}/scouting", slug)).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct NoteForm {
+    #[serde(default)]
+    note: String,
+}
+
+async fn save_note(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, entry_id)): Path<(String, String)>,
+    Form(form): Form<NoteForm>,
+) -> Result<Response, Error> {
+    let (entry, collection, production) = require_entry(&slug, &entry_id, &user).await?;
+    match ScoutingModel::set_note(&entry.id, &form.note).await {
+        Ok(()) => Ok(back_to_collection(&slug, &collection.id, Some(&entry.id))),
+        Err(Error::Validation(msg)) => {
+            render_collection(&user, collection, production, None, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn remove_entry(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, entry_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let (entry, collection, _) = require_entry(&slug, &entry_id, &user).await?;
+    ScoutingModel::remove(&entry.id).await?;
+    Ok(back_to_collection(&slug, &collection.id, None))
+}
+
+#[derive(Debug, Deserialize)]
+struct SaveLocationForm {
+    collection_id: String,
+}
+
+/// Save a location to one of the user's scouting collections, from the location page
+async fn save_location(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    Form(form): Form<SaveLocationForm>,
+) -> Result<Response, Error> {
+    let location = LocationModel::get(&RecordId::new("location", id.as_str())).await?;
+    if !location.is_public && !LocationModel::can_edit(&location.id, &user.id).await? {
+        return Err(Error::NotFound);
+    }
+    let collection = ScoutingModel::get(&form.collection_id).await?;
+    let production = ProductionModel::get(&collection.production).await?;
+    check_member(&production, &user.id).await?;
+
+    match ScoutingModel::add(&collection.id, &location.id, &person_id(&user)?).await {
+        Ok(_) => {
+            info!(
+                "{} saved location {} to scouting collection {}",
+                user.username,
+                id,
+                collection.id.display()
+            );
+            Ok(back_to_collection(&production.slug, &collection.id, None))
+        }
+        Err(Error::Validation(msg)) => {
+            render_collection(&user, collection, production, None, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// ============================
+// Shared link handlers
+// ============================
+
+async fn shared_page(Path(token): Path<String>, request: Request) -> Result<Response, Error> {
+    let collection = ScoutingModel::by_share_token(&token).await?;
+    let production = ProductionModel::get(&collection.production).await?;
+    let viewer = request.get_user();
+    let columns = columns(&collection, viewer.as_ref().map(|u| u.id.as_str())).await?;
+
+    let mut base = BaseContext::new().with_page("productions");
+    if let Some(user) = viewer {
+        base = base.with_user(User::from_session_user(&user).await);
+    }
+    let template = SharedCollectionTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        token,
+        can_pick: collection.access() == Some(ShareAccess::Pick),
+        name: collection.name,
+        production_title: production.title,
+        basecamp: collection.basecamp,
+        columns,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!(
+            "Failed to render shared scouting collection template: {}",
+            e
+        );
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct PickForm {
+    selected: Option<String>,
+}
+
+async fn pick_entry(
+    Path((token, entry_id)): Path<(String, String)>,
+    Form(form): Form<PickForm>,
+) -> Result<Response, Error> {
+    let collection = ScoutingModel::by_share_token(&token).await?;
+    if collection.access() != Some(ShareAccess::Pick) {
+        return Err(Error::Forbidden);
+    }
+    let entry = ScoutingModel::get_entry(&entry_id).await?;
+    if entry.collection != collection.id {
+        return Err(Error::NotFound);
+    }
+    ScoutingModel::set_selected(&entry.id, form.selected.is_some()).await?;
+    info!(
+        "Shared link picks updated on scouting collection {}",
+        collection.id.display()
+    );
+    Ok(Redirect::to(&format!("/scouting/shared/{}#entry-{}", token, entry_id)).into_response())
+}
@@ -1,29 +1,40 @@
 use askama::Template;
 use axum::{
-    Router,
+    Form, Router,
     extract::{Query, Request},
+    http::StatusCode,
+    middleware::from_fn,
     response::{Html, IntoResponse},
-    routing::get,
+    routing::{get, post},
 };
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use chrono::Datelike;
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::{debug, error};
 
-use regex::Regex;
-
 use surrealdb::types::RecordId;
 
 use crate::config;
 use crate::error::Error;
-use crate::middleware::UserExtractor;
+use crate::middleware::rate_limit::search_rate_limit_middleware;
+use crate::middleware::{CurrentUser, UserExtractor};
 use crate::models::likes::LikesModel;
-use crate::services::embedding::generate_embedding_async;
+use crate::models::search_history::{self, SearchHistoryModel};
+use crate::services::experiments::{self, SEARCH_RANKING, VISITOR_COOKIE};
 use crate::services::search::{
-    JobSearchResult, LocationSearchResult, OrganizationSearchResult, ProductionSearchResult,
-    SearchParams,
+    CombinedResults, JobSearchResult, LocationSearchResult, MatchedField, OrganizationSearchResult,
+    ProductionSearchResult,
 };
-use crate::services::search_log::log_search;
-use crate::services::search_utils;
+use crate::services::search_cache;
+use crate::services::search_connections::Connections;
+use crate::services::search_facets::{FacetValue, Facets, SearchFilters};
+use crate::services::search_log::{self, log_search_with_id};
+use crate::services::search_scope::{self, OrgScope};
+use crate::services::search_unified::{self, UnifiedResult};
+use crate::services::search_visibility::Viewer;
+use crate::services::spellcheck;
 use crate::templates::User;
 
 mod filters {
@@ -76,13 +87,11 @@ impl From<crate::services::search::PersonSearchResult> for PersonView {
     }
 }
 
+/// The results section of the search page, also served alone by
+/// `/search/results` so facets can narrow it in place
 #[derive(Template)]
-#[template(path = "search/index.html")]
-struct SearchTemplate {
-    app_name: String,
-    year: i32,
-    version: String,
-    active_page: String,
+#[template(path = "search/_results.html")]
+struct SearchResultsTemplate {
     user: Option<User>,
     query: Option<String>,
     has_results: bool,
@@ -94,15 +103,200 @@ struct SearchTemplate {
     jobs: Vec<JobSearchResult>,
     liked_ids: Vec<String>,
     current_user_id: String,
+    /// search_log id that result clicks are reported against
+    search_id: String,
+    filters: SearchFilters,
+    facets: Facets,
+    /// "Why matched" passages by result id
+    matched: HashMap<String, Vec<MatchedField>>,
+    /// `view=unified`: every type in one ranked list instead of sections
+    unified_view: bool,
+    unified: Vec<UnifiedResult>,
+    /// "Did you mean" query when nothing was found
+    suggestion: Option<String>,
+    /// The organization searched within, from `scope=org:<slug>`
+    scope: Option<OrgScope>,
+}
+
+impl SearchResultsTemplate {
+    /// Before anything's been searched
+    fn empty(user: Option<User>, current_user_id: String) -> Self {
+        Self {
+            user,
+            query: None,
+            has_results: false,
+            total_results: 0,
+            people: vec![],
+            organizations: vec![],
+            locations: vec![],
+            productions: vec![],
+            jobs: vec![],
+            liked_ids: vec![],
+            current_user_id,
+            search_id: String::new(),
+            filters: SearchFilters::default(),
+            facets: Facets::default(),
+            matched: HashMap::new(),
+            unified_view: false,
+            unified: vec![],
+            suggestion: None,
+            scope: None,
+        }
+    }
+
+    /// What made a result match, if it has field embeddings
+    fn why(&self, id: &str) -> &[MatchedField] {
+        self.matched.get(id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Keep the unified view on links that narrow or widen the results
+    fn keep_view(&self, mut url: String) -> String {
+        if self.unified_view {
+            url.push_str("&view=unified");
+        }
+        self.keep_scope(url)
+    }
+
+    /// Keep searching within the organization
+    fn keep_scope(&self, mut url: String) -> String {
+        if let Some(scope) = &self.scope {
+            url.push_str("&scope=");
+            url.push_str(&scope.param());
+        }
+        url
+    }
+
+    /// Link narrowing the results to one facet value
+    fn facet_url(&self, field: &str, facet: &FacetValue) -> String {
+        self.keep_view(self.filters.url(
+            self.query.as_deref().unwrap_or(""),
+            field,
+            Some(&facet.value),
+        ))
+    }
+
+    /// Link dropping one filter
+    fn remove_filter_url(&self, field: &str) -> String {
+        self.keep_view(
+            self.filters
+                .url(self.query.as_deref().unwrap_or(""), field, None),
+        )
+    }
+
+    fn clear_filters_url(&self) -> String {
+        self.keep_view(SearchFilters::default().url(self.query.as_deref().unwrap_or(""), "", None))
+    }
+
+    /// Link switching between the grouped sections and the unified list
+    fn view_url(&self, unified: bool) -> String {
+        let page = self.keep_scope(
+            self.filters
+                .url(self.query.as_deref().unwrap_or(""), "", None),
+        );
+        if unified {
+            page + "&view=unified"
+        } else {
+            page
+        }
+    }
+
+    /// Where this section reloads from, with its query, filters and view
+    /// Search page link for the suggested spelling
+    fn suggestion_url(&self, suggestion: &str) -> String {
+        self.keep_view(SearchFilters::default().url(suggestion, "", None))
+    }
+
+    fn fragment_src(&self) -> String {
+        self.view_url(self.unified_view)
+            .replacen("/search?", "/search/results?", 1)
+    }
+}
+
+#[derive(Template)]
+#[template(path = "search/index.html")]
+struct SearchTemplate {
+    app_name: String,
+    year: i32,
+    version: String,
+    active_page: String,
+    user: Option<User>,
+    results: SearchResultsTemplate,
+    /// The searcher's latest queries, shown before anything's searched
+    recent_searches: Vec<String>,
 }
 
 #[derive(Deserialize)]
 pub struct SearchQuery {
     q: Option<String>,
+    /// "unified" merges every result type into one ranked list
+    view: Option<String>,
+    /// "org:<slug>" searches only within that organization
+    scope: Option<String>,
+    /// Facets picked on the results page
+    #[serde(flatten)]
+    filters: SearchFilters,
+}
+
+impl SearchQuery {
+    fn unified_view(&self) -> bool {
+        self.view.as_deref() == Some("unified")
+    }
+
+    /// The organization to search within, if any
+    async fn scope(&self, session_user: Option<&CurrentUser>) -> Result<Option<OrgScope>, Error> {
+        let Some(slug) = search_scope::parse(self.scope.as_deref())? else {
+            return Ok(None);
+        };
+        let searcher = session_user.and_then(|u| RecordId::parse_simple(&u.id).ok());
+        Ok(Some(OrgScope::load(&slug, searcher.as_ref()).await?))
+    }
 }
 
 pub fn router() -> Router {
-    Router::new().route("/search", get(search_page))
+    Router::new()
+        .route(
+            "/search",
+            get(search_page).layer(from_fn(search_rate_limit_middleware)),
+        )
+        .route(
+            "/search/results",
+            get(search_results_fragment).layer(from_fn(search_rate_limit_middleware)),
+        )
+        .route("/search/click", post(record_click))
+}
+
+#[derive(Deserialize)]
+struct ClickForm {
+    sid: String,
+    position: Option<u32>,
+}
+
+/// Beacon sent by the results page when a result is opened
+async fn record_click(Form(form): Form<ClickForm>) -> StatusCode {
+    match search_log::record_click(&form.sid, form.position).await {
+        Ok(()) => StatusCode::NO_CONTENT,
+        Err(e) => {
+            debug!(error = %e, "Failed to record search click");
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
+
+/// Read the anonymous visitor id used for experiment assignment, issuing one if missing
+fn visitor_id(jar: CookieJar) -> (CookieJar, String) {
+    if let Some(id) = jar.get(VISITOR_COOKIE).map(|c| c.value().to_string()) {
+        return (jar, id);
+    }
+
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let cookie = Cookie::build((VISITOR_COOKIE, id.clone()))
+        .path("/")
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .secure(crate::config::cookie_secure())
+        .max_age(cookie::time::Duration::days(365))
+        .build();
+    (jar.add(cookie), id)
 }
 
 async fn search_page(
@@ -110,157 +304,211 @@ async fn search_page(
     request: Request,
 ) -> Result<impl IntoResponse, Error> {
     let query = params.q.as_deref().unwrap_or("").trim();
+    let unified_view = params.unified_view();
+    let (jar, visitor_id) = visitor_id(CookieJar::from_headers(request.headers()));
 
     // Extract user from request
-    let (user, current_user_id) = if let Some(session_user) = request.get_user() {
-        let uid = session_user.id.clone();
-        (Some(User::from_session_user(&session_user).await), Some(uid))
-    } else {
-        (None, None)
+    let session_user = request.get_user();
+    let user = match &session_user {
+        Some(session_user) => Some(User::from_session_user(session_user).await),
+        None => None,
     };
 
-    if query.is_empty() {
-        let template = SearchTemplate {
-            app_name: "SlateHub".to_string(),
-            year: chrono::Utc::now().year(),
-            version: env!("CARGO_PKG_VERSION").to_string(),
-            active_page: "search".to_string(),
-            user: user.clone(),
-            query: None,
-            has_results: false,
-            total_results: 0,
-            people: vec![],
-            organizations: vec![],
-            locations: vec![],
-            productions: vec![],
-            jobs: vec![],
-            liked_ids: vec![],
-            current_user_id: current_user_id.clone().unwrap_or_default(),
-        };
+    let scope = params.scope(session_user.as_deref()).await?;
 
-        let html = template.render().map_err(|e| {
-            error!("Failed to render search template: {}", e);
-            Error::Template(e.to_string())
-        })?;
+    // Signed-in searchers get their recent searches back on the empty page
+    let person = session_user
+        .as_ref()
+        .and_then(|u| RecordId::parse_simple(&u.id).ok());
+    let mut recent_searches = vec![];
 
-        return Ok(Html(html));
-    }
+    let results = if query.is_empty() {
+        if let Some(person) = &person {
+            recent_searches = SearchHistoryModel::recent(person, search_history::RECENT_SHOWN)
+                .await
+                .unwrap_or_default();
+        }
+        let current_user_id = session_user.as_ref().map(|u| u.id.clone());
+        let mut empty =
+            SearchResultsTemplate::empty(user.clone(), current_user_id.unwrap_or_default());
+        empty.scope = scope;
+        empty
+    } else {
+        if let Some(person) = &person {
+            SearchHistoryModel::record(person, query);
+        }
+        search_results(
+            query,
+            params.filters,
+            unified_view,
+            session_user.as_deref(),
+            user.clone(),
+            &visitor_id,
+            scope,
+        )
+        .await?
+    };
 
-    debug!("Search query: {}", query);
+    let template = SearchTemplate {
+        app_name: "SlateHub".to_string(),
+        year: chrono::Utc::now().year(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        active_page: "search".to_string(),
+        user,
+        results,
+        recent_searches,
+    };
 
-    // Detect which entity types the query targets
-    let intent = detect_search_intent(query);
-    debug!("Search intent: {:?}", intent);
+    let html = template.render().map_err(|e| {
+        error!("Failed to render search template: {}", e);
+        Error::Template(e.to_string())
+    })?;
 
-    // Generate embedding once for all search functions
-    let query_embedding = match generate_embedding_async(query).await {
-        Ok(emb) => Some(emb),
-        Err(e) => {
-            debug!(
-                error = %e,
-                query = %query,
-                "Embedding generation failed, falling back to text-only search"
-            );
-            None
-        }
-    };
+    Ok((jar, Html(html)))
+}
 
-    let weights = config::search_weights();
-
-    // --- People: use parse_query for structured filter extraction ---
-    let people = if intent.people {
-        let parsed = search_utils::parse_query(query);
-        let search_params = SearchParams {
-            query: &parsed.cleaned,
-            embedding: query_embedding.as_ref(),
-            weights,
-            limit: 20,
-            offset: 0,
-        };
-        crate::services::search::search_people(&search_params, &parsed, None)
-            .await?
-            .into_iter()
-            .map(PersonView::from)
-            .collect()
-    } else {
-        vec![]
+/// Just the results section, for narrowing by facet without reloading the page
+async fn search_results_fragment(
+    Query(params): Query<SearchQuery>,
+    request: Request,
+) -> Result<impl IntoResponse, Error> {
+    let query = params.q.as_deref().unwrap_or("").trim();
+    let unified_view = params.unified_view();
+    let (jar, visitor_id) = visitor_id(CookieJar::from_headers(request.headers()));
+
+    let session_user = request.get_user();
+    let user = match &session_user {
+        Some(session_user) => Some(User::from_session_user(session_user).await),
+        None => None,
     };
 
-    // --- Non-people: extract location, normalize remaining query ---
-    let (location, cleaned_query) = search_utils::extract_location(query);
-    let normalized = search_utils::normalize_query(&cleaned_query);
-
-    let organizations = if intent.organizations {
-        let search_params = SearchParams {
-            query: &normalized,
-            embedding: query_embedding.as_ref(),
-            weights,
-            limit: 10,
-            offset: 0,
-        };
-        crate::services::search::search_organizations(
-            &search_params,
-            location.as_deref(),
+    let scope = params.scope(session_user.as_deref()).await?;
+
+    let results = if query.is_empty() {
+        let current_user_id = session_user.as_ref().map(|u| u.id.clone());
+        let mut empty = SearchResultsTemplate::empty(user, current_user_id.unwrap_or_default());
+        empty.scope = scope;
+        empty
+    } else {
+        search_results(
+            query,
+            params.filters,
+            unified_view,
+            session_user.as_deref(),
+            user,
+            &visitor_id,
+            scope,
         )
         .await?
-    } else {
-        vec![]
     };
 
-    let locations = if intent.locations {
-        let search_params = SearchParams {
-            query: &normalized,
-            embedding: query_embedding.as_ref(),
-            weights,
-            limit: 10,
-            offset: 0,
-        };
-        // For locations, pass extracted location as city filter
-        crate::services::search::search_locations(
-            &search_params,
-            location.as_deref(),
-            None,
+    let html = results.render().map_err(|e| {
+        error!("Failed to render search results fragment: {}", e);
+        Error::Template(e.to_string())
+    })?;
+
+    Ok((jar, Html(html)))
+}
+
+/// Run a search and lay out its results: ranked for the searcher, without
+/// people they've blocked, narrowed by the picked facets and kept to the
+/// organization searched within
+async fn search_results(
+    query: &str,
+    filters: SearchFilters,
+    unified_view: bool,
+    session_user: Option<&CurrentUser>,
+    user: Option<User>,
+    visitor_id: &str,
+    scope: Option<OrgScope>,
+) -> Result<SearchResultsTemplate, Error> {
+    let current_user_id = session_user.map(|u| u.id.clone());
+
+    debug!("Search query: {}", query);
+
+    // Popular and recently searched queries come pre-embedded
+    let query_embedding = search_cache::embedding(query).await;
+
+    // Ranking experiment: users in the flag's audience get a stable variant
+    let exposure = SEARCH_RANKING.exposure(session_user, visitor_id).await;
+    let variant = exposure.as_ref().map(|e| e.variant);
+    let search_config =
+        config::search_config().map(|weights| experiments::ranking_weights(variant, weights));
+
+    // Signed-in searchers get people and productions tied to them ranked
+    // higher, and find members-only profiles; those results are theirs
+    // alone, so they skip the shared cache, as do searches within an
+    // organization
+    let connections = match current_user_id.as_deref().map(RecordId::parse_simple) {
+        Some(Ok(rid)) => Connections::load(&rid).await.ok(),
+        _ => None,
+    };
+    let viewer = match current_user_id.as_deref().map(RecordId::parse_simple) {
+        Some(Ok(rid)) => Viewer::load(&rid).await.ok(),
+        _ => None,
+    };
+    let results = if connections.is_some() || scope.is_some() {
+        Arc::new(
+            crate::services::search::search_all(
+                query,
+                query_embedding.as_ref(),
+                &search_config,
+                connections.as_ref(),
+                viewer.as_ref(),
+                scope.as_ref(),
+            )
+            .await?,
         )
-        .await?
     } else {
-        vec![]
+        match search_cache::results(query, variant) {
+            Some(results) => results,
+            None => {
+                let results = crate::services::search::search_all(
+                    query,
+                    query_embedding.as_ref(),
+                    &search_config,
+                    None,
+                    None,
+                    None,
+                )
+                .await?;
+                search_cache::store_results(query, variant, results)
+            }
+        }
     };
 
-    let productions = if intent.productions {
-        let search_params = SearchParams {
-            query: &normalized,
-            embedding: query_embedding.as_ref(),
-            weights,
-            limit: 10,
-            offset: 0,
-        };
-        crate::services::search::search_productions(&search_params, None).await?
+    // Shared results only hold public profiles, but may still include people
+    // this viewer blocked or muted, or who blocked them
+    let mut shown = CombinedResults::clone(&results);
+    if let Some(viewer) = &viewer {
+        shown.people.retain(|p| !viewer.hidden.contains(&p.id));
+    }
+
+    let search_id = log_search_with_id(query, "web", "all", Some(shown.total()), exposure.as_ref());
+
+    // Nothing of any type: offer the query with misspelled words corrected
+    let suggestion = if shown.total() == 0 {
+        spellcheck::suggest(query).await
     } else {
-        vec![]
+        None
     };
 
-    let jobs = if intent.jobs {
-        let search_params = SearchParams {
-            query: &normalized,
-            embedding: query_embedding.as_ref(),
-            weights,
-            limit: 10,
-            offset: 0,
-        };
-        crate::services::search::search_jobs(
-            &search_params,
-            location.as_deref(),
-            true,
-        )
-        .await?
+    // Facets narrow what was retrieved; the ranking stays as it was
+    filters.apply(&mut shown);
+    let facets = Facets::from_results(&shown);
+    let unified = if unified_view {
+        search_unified::unified(&shown)
     } else {
         vec![]
     };
 
-    let total_results =
-        people.len() + organizations.len() + locations.len() + productions.len() + jobs.len();
-
-    log_search(query, "web", "all", Some(total_results));
+    let total_results = shown.total();
+    let people: Vec<PersonView> = shown.people.into_iter().map(PersonView::from).collect();
+    let organizations = shown.organizations;
+    let locations = shown.locations;
+    let productions = shown.productions;
+    let jobs = shown.jobs;
+    let matched = shown.matched;
 
     // Fetch liked IDs for people results if user is logged in
     let liked_ids = if let Some(ref uid) = current_user_id {
@@ -284,11 +532,7 @@ async fn search_page(
         vec![]
     };
 
-    let template = SearchTemplate {
-        app_name: "SlateHub".to_string(),
-        year: chrono::Utc::now().year(),
-        version: env!("CARGO_PKG_VERSION").to_string(),
-        active_page: "search".to_string(),
+    Ok(SearchResultsTemplate {
         user,
         query: Some(query.to_string()),
         has_results: total_results > 0,
@@ -300,72 +544,13 @@ async fn search_page(
         jobs,
         liked_ids,
         current_user_id: current_user_id.unwrap_or_default(),
-    };
-
-    let html = template.render().map_err(|e| {
-        error!("Failed to render search template: {}", e);
-        Error::Template(e.to_string())
-    })?;
-
-    Ok(Html(html))
-}
-
-/// Which entity types a search query targets.
-#[derive(Debug)]
-struct SearchIntent {
-    people: bool,
-    organizations: bool,
-    locations: bool,
-    productions: bool,
-    jobs: bool,
-}
-
-/// Detect which entity types the query is targeting based on keywords.
-/// Returns all true (search everything) if no clear intent is detected.
-fn detect_search_intent(query: &str) -> SearchIntent {
-    let q = query.to_lowercase();
-
-    // Location signals — physical places, venues, spaces
-    let is_locations = Regex::new(
-        r"(?i)\b(location|locations|venue|venues|sound stage|soundstage|stage|warehouse|rooftop|loft|desert|beach|forest|outdoor|indoor|studio space|filming location|shoot location)\b"
-    ).unwrap().is_match(&q);
-
-    // Organization signals — companies, agencies, service providers
-    let is_orgs = Regex::new(
-        r"(?i)\b(company|companies|agency|agencies|house|houses|rental|rentals|post house|vfx house|production company|production companies|talent agency|casting agency|studio|studios)\b"
-    ).unwrap().is_match(&q)
-        // "studio" alone is ambiguous — only count as org if combined with service words
-        // but "studios" (plural) is more likely an org
-        && !is_locations; // location takes priority if both match (e.g. "studio space")
-
-    // Production signals — films, shows, credits
-    let is_productions = Regex::new(
-        r"(?i)\b(film|films|movie|movies|show|shows|series|season|documentary|documentaries|short film|shorts|feature|features|directed by|starring|produced by|written by|credits)\b"
-    ).unwrap().is_match(&q);
-
-    // Job signals — employment, gigs, hiring
-    let is_jobs = Regex::new(
-        r"(?i)\b(job|jobs|hiring|position|positions|opening|openings|gig|gigs|vacancy|vacancies|opportunity|opportunities|looking for work|seeking work|casting call|audition|auditions)\b"
-    ).unwrap().is_match(&q);
-
-    let any_specific = is_locations || is_orgs || is_productions || is_jobs;
-
-    if any_specific {
-        SearchIntent {
-            people: is_productions, // include people for "films directed by chris"
-            organizations: is_orgs,
-            locations: is_locations,
-            productions: is_productions,
-            jobs: is_jobs,
-        }
-    } else {
-        // No specific entity keyword — search everything
-        SearchIntent {
-            people: true,
-            organizations: true,
-            locations: true,
-            productions: true,
-            jobs: true,
-        }
-    }
+        search_id,
+        filters,
+        facets,
+        matched,
+        unified_view,
+        unified,
+        suggestion,
+        scope,
+    })
 }
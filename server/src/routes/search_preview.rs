@@ -0,0 +1,174 @@
+use askama::Template;
+use axum::{
+    Router,
+    extract::{Path, Query},
+    response::Html,
+    routing::get,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use surrealdb::types::RecordId;
+use tracing::error;
+
+use crate::{
+    config,
+    error::Error,
+    middleware::{AuthenticatedUser, CurrentUser},
+    models::location::LocationModel,
+    models::organization::OrganizationModel,
+    models::person::Person,
+    models::production::ProductionModel,
+    record_id_ext::RecordIdExt,
+    services::search_preview,
+    templates::{BaseContext, SearchPreviewTemplate, User},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/profile/search-preview", get(profile_preview))
+        .route("/orgs/{slug}/search-preview", get(organization_preview))
+        .route("/locations/{id}/search-preview", get(location_preview))
+        .route("/productions/{slug}/search-preview", get(production_preview))
+}
+
+#[derive(Debug, Deserialize)]
+struct PreviewParams {
+    q: Option<String>,
+}
+
+/// The record shown on the page
+struct Subject {
+    kind: &'static str,
+    id: RecordId,
+    name: String,
+    back_url: String,
+    preview_url: String,
+}
+
+async fn profile_preview(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<PreviewParams>,
+) -> Result<Html<String>, Error> {
+    let person = Person::find_by_username(&user.username)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let subject = Subject {
+        kind: "profile",
+        id: person.id.clone(),
+        name: person.get_display_name(),
+        back_url: format!("/{}", person.username),
+        preview_url: "/profile/search-preview".to_string(),
+    };
+    render(user, subject, params).await
+}
+
+async fn organization_preview(
+    Path(slug): Path<String>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<PreviewParams>,
+) -> Result<Html<String>, Error> {
+    let model = OrganizationModel::new();
+    let organization = model.get_by_slug(&slug).await?;
+    let role = model
+        .get_member_role(&organization.id.to_raw_string(), &user.id)
+        .await?;
+    if role != Some("owner".to_string()) && role != Some("admin".to_string()) {
+        return Err(Error::Forbidden);
+    }
+    let subject = Subject {
+        kind: "organization",
+        id: organization.id.clone(),
+        name: organization.name.clone(),
+        back_url: format!("/orgs/{}", organization.slug),
+        preview_url: format!("/orgs/{}/search-preview", organization.slug),
+    };
+    render(user, subject, params).await
+}
+
+async fn location_preview(
+    Path(id): Path<String>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<PreviewParams>,
+) -> Result<Html<String>, Error> {
+    let location = LocationModel::get(&RecordId::new("location", id.as_str())).await?;
+    if !LocationModel::can_edit(&location.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+    let subject = Subject {
+        kind: "location",
+        id: location.id.clone(),
+        name: location.name.clone(),
+        back_url: format!("/locations/{}", location.id.key_string()),
+        preview_url: format!("/locations/{}/search-preview", location.id.key_string()),
+    };
+    render(user, subject, params).await
+}
+
+async fn production_preview(
+    Path(slug): Path<String>,
+    AuthenticatedUser(user): AuthenticatedUser,
+    Query(params): Query<PreviewParams>,
+) -> Result<Html<String>, Error> {
+    let production = ProductionModel::get_by_slug(&slug).await?;
+    if !ProductionModel::can_edit(&production.id, &user.id).await? {
+        return Err(Error::Forbidden);
+    }
+    let subject = Subject {
+        kind: "production",
+        id: production.id.clone(),
+        name: production.title.clone(),
+        back_url: format!("/productions/{}", production.slug),
+        preview_url: format!("/productions/{}/search-preview", production.slug),
+    };
+    render(user, subject, params).await
+}
+
+async fn render(
+    user: Arc<CurrentUser>,
+    subject: Subject,
+    params: PreviewParams,
+) -> Result<Html<String>, Error> {
+    let mut base = BaseContext::new().with_page("search-preview");
+    base = base.with_user(User::from_session_user(&user).await);
+
+    let preview = search_preview::load(&subject.id).await?;
+    let query = params.q.unwrap_or_default().trim().to_string();
+    let threshold = config::search_config()
+        .for_table(&subject.id.table.to_string())
+        .unwrap_or(config::search_weights())
+        .vector_threshold;
+
+    let mut score = None;
+    let mut error = None;
+    if !query.is_empty() {
+        match search_preview::similarity(&subject.id, &query).await {
+            Ok(similarity) => score = similarity,
+            Err(Error::ExternalService(message)) => error = Some(message),
+            Err(e) => return Err(e),
+        }
+    }
+
+    let template = SearchPreviewTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        kind: subject.kind,
+        name: subject.name,
+        back_url: subject.back_url,
+        preview_url: subject.preview_url,
+        preview,
+        query,
+        score: score.map(|s| format!("{:.2}", s)),
+        matches: score.is_some_and(|s| s > threshold as f64),
+        threshold: format!("{:.2}", threshold),
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render search preview template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html))
+}
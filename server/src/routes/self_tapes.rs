@@ -0,0 +1,923 @@
+use askama::Template;
+use axum::{
+    Form, Json, Router,
+    extract::{Path, Query},
+    http::header,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        job::{JobDetailView, JobModel},
+        notification::NotificationModel,
+        person::SessionUser,
+        selftape::{self, SelfTape, SelfTapeModel, SelfTapeRequest},
+    },
+    record_id_ext::RecordIdExt,
+    services::{
+        mentions,
+        s3::s3,
+        transcode::{self, Rendition},
+        uploads,
+    },
+    templates::{BaseContext, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/jobs/{id}/self-tapes", get(requests_page).post(create_request))
+        .route("/self-tapes", get(my_self_tapes))
+        .route("/self-tapes/{request_id}", get(gallery))
+        .route("/self-tapes/{request_id}/invite", post(invite_more))
+        .route("/self-tapes/{request_id}/close", post(close_request))
+        .route("/self-tapes/submissions/{tape_id}", get(submission_page))
+        .route("/self-tapes/submissions/{tape_id}/uploads", post(start_upload))
+        .route("/self-tapes/submissions/{tape_id}/complete", post(complete_upload))
+        .route("/self-tapes/submissions/{tape_id}/video-status", get(video_status))
+        .route("/self-tapes/submissions/{tape_id}/stream.m3u8", get(stream_playlist))
+        .route("/self-tapes/submissions/{tape_id}/rate", post(rate_tape))
+        .route("/self-tapes/submissions/{tape_id}/comments", post(comment_on_tape))
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct RequestRow {
+    pub id: String,
+    pub role_title: String,
+    pub deadline: String,
+    pub status: String,
+    pub is_past: bool,
+    pub invited: i64,
+    pub submitted: i64,
+}
+
+/// Values to refill the new request form with after an error
+#[derive(Default)]
+pub struct RequestFormValues {
+    pub role_title: String,
+    pub instructions: String,
+    pub deadline_date: String,
+    pub deadline_time: String,
+    pub usernames: String,
+}
+
+pub struct CommentView {
+    pub author_name: String,
+    /// Escaped, with @mentions linked
+    pub body: String,
+    pub created_at: String,
+}
+
+/// How a tape's video is played: the web renditions once they're ready, or
+/// the original when transcoding failed or isn't set up
+#[derive(Default)]
+pub struct Playback {
+    pub video_url: Option<String>,
+    pub content_type: String,
+    pub poster_url: Option<String>,
+    pub hls_url: Option<String>,
+    /// Renditions are still being made
+    pub processing: bool,
+}
+
+pub struct TapeCard {
+    pub id: String,
+    pub person_name: String,
+    pub username: String,
+    pub status: String,
+    pub playback: Playback,
+    pub note: Option<String>,
+    pub submitted_at: Option<String>,
+    pub average: Option<String>,
+    pub average_value: f64,
+    pub rating_count: usize,
+    /// The viewer's own rating, 0 when they haven't rated it
+    pub my_rating: i64,
+    pub comments: Vec<CommentView>,
+}
+
+pub struct MySelfTapeView {
+    pub id: String,
+    pub job_title: String,
+    pub role_title: String,
+    pub deadline: String,
+    pub status: String,
+    pub is_open: bool,
+}
+
+#[derive(Template)]
+#[template(path = "jobs/self_tapes.html")]
+pub struct SelfTapeRequestsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub job_id: String,
+    pub job_title: String,
+    pub roles: Vec<String>,
+    pub requests: Vec<RequestRow>,
+    pub form: RequestFormValues,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "jobs/self_tape_gallery.html")]
+pub struct SelfTapeGalleryTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub request_id: String,
+    pub job_id: String,
+    pub job_title: String,
+    pub role_title: String,
+    pub instructions: String,
+    pub deadline: String,
+    pub status: String,
+    pub accepts_uploads: bool,
+    pub sort: String,
+    pub rating_scale: Vec<i64>,
+    pub tapes: Vec<TapeCard>,
+    pub compare: Vec<TapeCard>,
+    pub submitted_count: usize,
+    /// Where comment boxes look up teammates to @mention
+    pub mentions_url: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "jobs/self_tape_submit.html")]
+pub struct SelfTapeSubmitTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub tape_id: String,
+    pub job_id: String,
+    pub job_title: String,
+    pub role_title: String,
+    pub instructions: String,
+    pub deadline: String,
+    pub accepts_uploads: bool,
+    pub status: String,
+    pub playback: Playback,
+    pub note: String,
+    pub submitted_at: Option<String>,
+    pub accept: String,
+    pub max_bytes: i64,
+}
+
+#[derive(Template)]
+#[template(path = "jobs/my_self_tapes.html")]
+pub struct MySelfTapesTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub tapes: Vec<MySelfTapeView>,
+}
+
+// ============================
+// Helpers
+// ============================
+
+fn person_id(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%b %-d, %Y at %H:%M UTC").to_string()
+}
+
+fn job_key(job: &JobDetailView) -> String {
+    job.id.strip_prefix("job_posting:").unwrap_or(&job.id).to_string()
+}
+
+/// The job, if the user can manage it. Anyone who can edit a job posting is
+/// on its casting team.
+async fn require_casting(job_id: &str, user: &SessionUser) -> Result<JobDetailView, Error> {
+    let job = JobModel::get(job_id, Some(&user.id)).await?;
+    if !job.can_edit {
+        return Err(Error::Forbidden);
+    }
+    Ok(job)
+}
+
+/// The production or organization whose members can be mentioned in a job's
+/// self-tape comments, as `/api/mentions` names it. `None` when a person
+/// posted the job on their own.
+fn mention_team(job: &JobDetailView) -> Option<(&'static str, &str)> {
+    match (job.production_slug.as_deref(), job.poster_type.as_str()) {
+        (Some(slug), _) => Some(("production", slug)),
+        (None, "organization") => Some(("org", job.poster_slug.as_str())),
+        _ => None,
+    }
+}
+
+/// A tape, if it belongs to the user
+async fn require_own_tape(tape_id: &str, user: &SessionUser) -> Result<(SelfTape, SelfTapeRequest), Error> {
+    let tape = SelfTapeModel::get(tape_id).await?;
+    if tape.person != person_id(user)? {
+        return Err(Error::Forbidden);
+    }
+    let request = SelfTapeModel::get_request(&tape.request.key_string()).await?;
+    Ok((tape, request))
+}
+
+/// A tape and its request, if the user is on the job's casting team
+async fn require_reviewer(tape_id: &str, user: &SessionUser) -> Result<(SelfTape, SelfTapeRequest, JobDetailView), Error> {
+    let tape = SelfTapeModel::get(tape_id).await?;
+    let request = SelfTapeModel::get_request(&tape.request.key_string()).await?;
+    let job = require_casting(&request.job.key_string(), user).await?;
+    Ok((tape, request, job))
+}
+
+/// A tape, if the user sent it or is on the job's casting team
+async fn require_viewer(tape_id: &str, user: &SessionUser) -> Result<SelfTape, Error> {
+    let tape = SelfTapeModel::get(tape_id).await?;
+    if tape.person != person_id(user)? {
+        let request = SelfTapeModel::get_request(&tape.request.key_string()).await?;
+        require_casting(&request.job.key_string(), user).await?;
+    }
+    Ok(tape)
+}
+
+async fn playback(
+    tape: &RecordId,
+    video_key: &str,
+    content_type: Option<String>,
+    rendition: Option<Rendition>,
+) -> Result<Playback, Error> {
+    let s3 = s3()?;
+    match rendition {
+        Some(r) if r.is_pending() => Ok(Playback { processing: true, ..Default::default() }),
+        Some(Rendition { status, mp4_key: Some(mp4_key), poster_key, hls_key }) if status == "ready" => Ok(Playback {
+            video_url: Some(s3.generate_download_url(&mp4_key).await?),
+            content_type: "video/mp4".to_string(),
+            poster_url: match poster_key {
+                Some(key) => Some(s3.generate_download_url(&key).await?),
+                None => None,
+            },
+            hls_url: hls_key.map(|_| format!("/self-tapes/submissions/{}/stream.m3u8", tape.key_string())),
+            processing: false,
+        }),
+        _ => Ok(Playback {
+            video_url: Some(s3.generate_download_url(video_key).await?),
+            content_type: content_type.unwrap_or_else(|| "video/mp4".to_string()),
+            ..Default::default()
+        }),
+    }
+}
+
+/// Everyone to invite: the role's applicants if asked for, plus anyone named
+/// by username. Unknown usernames are an error so typos don't go unnoticed.
+async fn invitees(
+    job: &RecordId,
+    role_title: &str,
+    invite_applicants: bool,
+    usernames: &str,
+) -> Result<Vec<RecordId>, Error> {
+    let mut people = if invite_applicants {
+        SelfTapeModel::role_applicants(job, role_title).await?
+    } else {
+        Vec::new()
+    };
+
+    let usernames = selftape::parse_usernames(usernames);
+    if !usernames.is_empty() {
+        let (found, unknown) = SelfTapeModel::people_by_username(&usernames).await?;
+        if !unknown.is_empty() {
+            let unknown: Vec<String> = unknown.iter().map(|u| format!("@{}", u)).collect();
+            return Err(Error::Validation(format!("No one goes by {}", unknown.join(", "))));
+        }
+        people.extend(found);
+    }
+
+    if people.is_empty() {
+        return Err(Error::Validation(
+            "Invite the role's applicants or add the usernames of the people to tape".to_string(),
+        ));
+    }
+    Ok(people)
+}
+
+async fn notify_invited(request: &SelfTapeRequest, job_title: &str, people: &[RecordId]) {
+    let message = format!(
+        "{} would like a self-tape for {} by {}",
+        job_title,
+        request.role_title,
+        format_time(request.deadline)
+    );
+    let notifications = NotificationModel::new();
+    for person in people {
+        if let Err(e) = notifications
+            .create(
+                &person.to_raw_string(),
+                "selftape",
+                "Self-tape requested",
+                &message,
+                Some("/self-tapes"),
+                Some(&request.id.to_raw_string()),
+            )
+            .await
+        {
+            error!("Failed to notify {} of self-tape request: {}", person.display(), e);
+        }
+    }
+}
+
+fn back_to_gallery(request: &RecordId, tape: Option<&RecordId>) -> Response {
+    let anchor = tape.map(|t| format!("#tape-{}", t.key_string())).unwrap_or_default();
+    Redirect::to(&format!("/self-tapes/{}{}", request.key_string(), anchor)).into_response()
+}
+
+async fn render_requests(
+    user: &SessionUser,
+    job: JobDetailView,
+    form: RequestFormValues,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let now = Utc::now();
+    let requests = SelfTapeModel::requests_for_job(&RecordId::new("job_posting", job_key(&job)))
+        .await?
+        .into_iter()
+        .map(|r| RequestRow {
+            id: r.id.key_string(),
+            role_title: r.role_title,
+            deadline: format_time(r.deadline),
+            status: r.status,
+            is_past: r.deadline <= now,
+            invited: r.invited,
+            submitted: r.submitted,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("jobs")
+        .with_user(User::from_session_user(user).await);
+    let template = SelfTapeRequestsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        job_id: job_key(&job),
+        job_title: job.title,
+        roles: job.roles.into_iter().map(|r| r.title).collect(),
+        requests,
+        form,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render self-tape requests template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct GalleryQuery {
+    /// "rating" to list the best-rated tapes first
+    #[serde(default)]
+    sort: String,
+    /// Two tapes to compare side by side
+    a: Option<String>,
+    b: Option<String>,
+}
+
+async fn render_gallery(
+    user: &SessionUser,
+    request: SelfTapeRequest,
+    job: JobDetailView,
+    query: GalleryQuery,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let reviewer = person_id(user)?;
+    let listings = SelfTapeModel::tapes(&request.id).await?;
+    let my_ratings = SelfTapeModel::ratings_by(&request.id, &reviewer).await?;
+    let comments = SelfTapeModel::comments(&request.id).await?;
+
+    let mut tapes = Vec::with_capacity(listings.len());
+    for tape in listings {
+        let playback = match tape.video_key.as_deref() {
+            Some(key) if tape.status == "submitted" => {
+                playback(&tape.id, key, tape.content_type, tape.rendition).await?
+            }
+            _ => Playback::default(),
+        };
+        let average = selftape::average_rating(&tape.ratings);
+        tapes.push(TapeCard {
+            id: tape.id.key_string(),
+            person_name: tape.person_name,
+            username: tape.username,
+            status: tape.status,
+            playback,
+            note: tape.note,
+            submitted_at: tape.submitted_at.map(format_time),
+            average: average.map(|a| format!("{:.1}", a)),
+            average_value: average.unwrap_or(0.0),
+            rating_count: tape.ratings.len(),
+            my_rating: my_ratings
+                .iter()
+                .find(|r| r.selftape == tape.id)
+                .map(|r| r.rating)
+                .unwrap_or(0),
+            comments: comments
+                .iter()
+                .filter(|c| c.selftape == tape.id)
+                .map(|c| CommentView {
+                    author_name: c.author_name.clone(),
+                    body: mentions::link(&c.body),
+                    created_at: format_time(c.created_at),
+                })
+                .collect(),
+        });
+    }
+    if query.sort == "rating" {
+        tapes.sort_by(|a, b| b.average_value.total_cmp(&a.average_value));
+    }
+
+    let mut compare = Vec::new();
+    if let (Some(a), Some(b)) = (query.a.as_deref(), query.b.as_deref())
+        && a != b
+    {
+        for id in [a, b] {
+            if let Some(index) = tapes.iter().position(|t| t.id == id && t.playback.video_url.is_some()) {
+                compare.push(tapes.remove(index));
+            }
+        }
+        if compare.len() < 2 {
+            tapes.append(&mut compare);
+        }
+    }
+
+    let submitted_count = tapes.iter().chain(compare.iter()).filter(|t| t.status == "submitted").count();
+    let accepts_uploads = request.accepts_uploads(Utc::now());
+    let mentions_url = mention_team(&job).map(|(kind, slug)| format!("/api/mentions?{}={}", kind, slug));
+    let base = BaseContext::new()
+        .with_page("jobs")
+        .with_user(User::from_session_user(user).await);
+    let template = SelfTapeGalleryTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        request_id: request.id.key_string(),
+        job_id: job_key(&job),
+        job_title: job.title,
+        role_title: request.role_title,
+        instructions: request.instructions,
+        deadline: format_time(request.deadline),
+        status: request.status,
+        accepts_uploads,
+        sort: query.sort,
+        rating_scale: (1..=5).collect(),
+        tapes,
+        compare,
+        submitted_count,
+        mentions_url,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render self-tape gallery template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+// ============================
+// Casting handlers
+// ============================
+
+async fn requests_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Response, Error> {
+    let job = require_casting(&id, &user).await?;
+    render_requests(&user, job, RequestFormValues::default(), None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct NewRequestForm {
+    #[serde(default)]
+    role_title: String,
+    #[serde(default)]
+    instructions: String,
+    #[serde(default)]
+    deadline_date: String,
+    #[serde(default)]
+    deadline_time: String,
+    invite_applicants: Option<String>,
+    #[serde(default)]
+    usernames: String,
+}
+
+async fn create_request(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    Form(form): Form<NewRequestForm>,
+) -> Result<Response, Error> {
+    let job = require_casting(&id, &user).await?;
+    let job_id = RecordId::new("job_posting", id.as_str());
+
+    let result = async {
+        if !job.roles.iter().any(|r| r.title == form.role_title) {
+            return Err(Error::Validation("Choose the role to tape for".to_string()));
+        }
+        let deadline = selftape::parse_deadline(&form.deadline_date, &form.deadline_time, Utc::now())?;
+        let people = invitees(
+            &job_id,
+            &form.role_title,
+            form.invite_applicants.is_some(),
+            &form.usernames,
+        )
+        .await?;
+        let request = SelfTapeModel::create_request(
+            &job_id,
+            &form.role_title,
+            &form.instructions,
+            deadline,
+            &person_id(&user)?,
+        )
+        .await?;
+        let invited = SelfTapeModel::invite(&request, &people).await?;
+        Ok((request, invited))
+    }
+    .await;
+
+    match result {
+        Ok((request, invited)) => {
+            info!(
+                "{} requested self-tapes for '{}' on job {} from {} people",
+                user.username,
+                request.role_title,
+                id,
+                invited.len()
+            );
+            notify_invited(&request, &job.title, &invited).await;
+            Ok(back_to_gallery(&request.id, None))
+        }
+        Err(Error::Validation(msg)) => {
+            let values = RequestFormValues {
+                role_title: form.role_title,
+                instructions: form.instructions,
+                deadline_date: form.deadline_date,
+                deadline_time: form.deadline_time,
+                usernames: form.usernames,
+            };
+            render_requests(&user, job, values, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn gallery(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(request_id): Path<String>,
+    Query(query): Query<GalleryQuery>,
+) -> Result<Response, Error> {
+    let request = SelfTapeModel::get_request(&request_id).await?;
+    let job = require_casting(&request.job.key_string(), &user).await?;
+    render_gallery(&user, request, job, query, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct InviteForm {
+    invite_applicants: Option<String>,
+    #[serde(default)]
+    usernames: String,
+}
+
+async fn invite_more(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(request_id): Path<String>,
+    Form(form): Form<InviteForm>,
+) -> Result<Response, Error> {
+    let request = SelfTapeModel::get_request(&request_id).await?;
+    let job = require_casting(&request.job.key_string(), &user).await?;
+    if !request.accepts_uploads(Utc::now()) {
+        return render_gallery(
+            &user,
+            request,
+            job,
+            GalleryQuery::default(),
+            Some("This request is closed to new tapes".to_string()),
+        )
+        .await;
+    }
+
+    let result = match invitees(
+        &request.job,
+        &request.role_title,
+        form.invite_applicants.is_some(),
+        &form.usernames,
+    )
+    .await
+    {
+        Ok(people) => SelfTapeModel::invite(&request, &people).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(invited) => {
+            info!("{} invited {} more people to self-tape request {}", user.username, invited.len(), request_id);
+            notify_invited(&request, &job.title, &invited).await;
+            Ok(back_to_gallery(&request.id, None))
+        }
+        Err(Error::Validation(msg)) => render_gallery(&user, request, job, GalleryQuery::default(), Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+async fn close_request(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(request_id): Path<String>,
+) -> Result<Response, Error> {
+    let request = SelfTapeModel::get_request(&request_id).await?;
+    require_casting(&request.job.key_string(), &user).await?;
+    SelfTapeModel::close_request(&request.id).await?;
+    info!("{} closed self-tape request {}", user.username, request_id);
+    Ok(back_to_gallery(&request.id, None))
+}
+
+#[derive(Debug, Deserialize)]
+struct RateForm {
+    rating: i64,
+}
+
+async fn rate_tape(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(tape_id): Path<String>,
+    Form(form): Form<RateForm>,
+) -> Result<Response, Error> {
+    let (tape, request, job) = require_reviewer(&tape_id, &user).await?;
+    match SelfTapeModel::rate(&tape.id, &person_id(&user)?, form.rating).await {
+        Ok(()) => Ok(back_to_gallery(&request.id, Some(&tape.id))),
+        Err(Error::Validation(msg)) => render_gallery(&user, request, job, GalleryQuery::default(), Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CommentForm {
+    #[serde(default)]
+    body: String,
+}
+
+async fn comment_on_tape(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(tape_id): Path<String>,
+    Form(form): Form<CommentForm>,
+) -> Result<Response, Error> {
+    let (tape, request, job) = require_reviewer(&tape_id, &user).await?;
+    match SelfTapeModel::comment(&tape.id, &person_id(&user)?, &form.body).await {
+        Ok(()) => {
+            if let Some((kind, slug)) = mention_team(&job)
+                && let Ok(team) = mentions::scope(kind, slug).await
+            {
+                let link = format!("/self-tapes/{}#tape-{}", request.id.key_string(), tape.id.key_string());
+                let place = format!("on a self-tape for {}", job.title);
+                mentions::notify(&team, &mentions::parse(&form.body), &user, &place, &form.body, &link).await;
+            }
+            Ok(back_to_gallery(&request.id, Some(&tape.id)))
+        }
+        Err(Error::Validation(msg)) => render_gallery(&user, request, job, GalleryQuery::default(), Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+// ============================
+// Actor handlers
+// ============================
+
+async fn my_self_tapes(AuthenticatedUser(user): AuthenticatedUser) -> Result<Response, Error> {
+    let now = Utc::now();
+    let tapes = SelfTapeModel::for_person(&person_id(&user)?)
+        .await?
+        .into_iter()
+        .map(|t| MySelfTapeView {
+            id: t.id.key_string(),
+            job_title: t.job_title,
+            role_title: t.role_title,
+            deadline: format_time(t.deadline),
+            status: t.status,
+            is_open: t.request_status == "open" && now < t.deadline,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("jobs")
+        .with_user(User::from_session_user(&user).await);
+    let template = MySelfTapesTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        tapes,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render my self-tapes template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn submission_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(tape_id): Path<String>,
+) -> Result<Response, Error> {
+    let (tape, request) = require_own_tape(&tape_id, &user).await?;
+    let job = JobModel::get(&request.job.key_string(), Some(&user.id)).await?;
+    let playback = match tape.video_key.as_deref() {
+        Some(key) => {
+            let rendition = transcode::rendition(key).await?;
+            playback(&tape.id, key, tape.content_type.clone(), rendition).await?
+        }
+        None => Playback::default(),
+    };
+
+    let base = BaseContext::new()
+        .with_page("jobs")
+        .with_user(User::from_session_user(&user).await);
+    let template = SelfTapeSubmitTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        tape_id: tape.id.key_string(),
+        job_id: job_key(&job),
+        job_title: job.title,
+        role_title: request.role_title.clone(),
+        instructions: request.instructions.clone(),
+        deadline: format_time(request.deadline),
+        accepts_uploads: request.accepts_uploads(Utc::now()),
+        status: tape.status,
+        playback,
+        note: tape.note.unwrap_or_default(),
+        submitted_at: tape.submitted_at.map(format_time),
+        accept: selftape::VIDEO_TYPES
+            .iter()
+            .map(|(ext, content_type)| format!(".{},{}", ext, content_type))
+            .collect::<Vec<_>>()
+            .join(","),
+        max_bytes: selftape::MAX_VIDEO_BYTES,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render self-tape submission template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct StartUploadRequest {
+    filename: String,
+    size: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct StartUploadResponse {
+    id: String,
+    url: String,
+    chunk_size: i64,
+}
+
+/// Start a resumable upload for the tape's video. The browser sends the file
+/// to the returned URL in chunks and can pick up where it left off if the
+/// connection drops.
+async fn start_upload(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(tape_id): Path<String>,
+    Json(body): Json<StartUploadRequest>,
+) -> Result<Json<StartUploadResponse>, Error> {
+    let (tape, request) = require_own_tape(&tape_id, &user).await?;
+    if !request.accepts_uploads(Utc::now()) {
+        return Err(Error::Conflict("This request is no longer taking self-tapes".to_string()));
+    }
+    let (extension, content_type) = selftape::video_type(&body.filename)
+        .ok_or_else(|| Error::Validation("Upload an MP4, MOV, M4V or WebM video".to_string()))?;
+    if body.size <= 0 || body.size > selftape::MAX_VIDEO_BYTES {
+        return Err(Error::Validation("Self-tapes can be up to 2 GB".to_string()));
+    }
+
+    let key = selftape::video_key(&tape, extension);
+    let session = uploads::start(
+        &tape.person,
+        selftape::UPLOAD_PURPOSE,
+        &key,
+        &body.filename,
+        content_type,
+        body.size,
+    )
+    .await?;
+    let id = session.id.key_string();
+    Ok(Json(StartUploadResponse {
+        url: format!("/uploads/{}", id),
+        id,
+        chunk_size: session.chunk_size,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct CompleteUploadRequest {
+    upload: String,
+    note: Option<String>,
+}
+
+/// Submit the tape once its upload has finished
+async fn complete_upload(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(tape_id): Path<String>,
+    Json(body): Json<CompleteUploadRequest>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let (tape, request) = require_own_tape(&tape_id, &user).await?;
+    if !request.accepts_uploads(Utc::now()) {
+        return Err(Error::Conflict("This request is no longer taking self-tapes".to_string()));
+    }
+    let session = uploads::get(&body.upload, &tape.person).await?;
+    if !selftape::owns_video_key(&tape, &session.key) {
+        return Err(Error::BadRequest("Unknown upload".to_string()));
+    }
+    let session = uploads::claim(&body.upload, &tape.person, selftape::UPLOAD_PURPOSE).await?;
+
+    let note = body.note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    SelfTapeModel::submit(&tape, &session.key, &session.content_type, session.size, note).await?;
+    info!("{} submitted self-tape {}", user.username, tape_id);
+
+    let name = if user.name.is_empty() { &user.username } else { &user.name };
+    if let Err(e) = NotificationModel::new()
+        .create(
+            &request.created_by.to_raw_string(),
+            "selftape",
+            "New self-tape",
+            &format!("{} sent a self-tape for {}", name, request.role_title),
+            Some(&format!("/self-tapes/{}#tape-{}", request.id.key_string(), tape.id.key_string())),
+            Some(&tape.id.to_raw_string()),
+        )
+        .await
+    {
+        error!("Failed to notify casting of self-tape {}: {}", tape_id, e);
+    }
+
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
+
+/// Whether a tape's renditions are ready, for pages waiting on them
+async fn video_status(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(tape_id): Path<String>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let tape = require_viewer(&tape_id, &user).await?;
+    let rendition = match tape.video_key.as_deref() {
+        Some(key) => transcode::rendition(key).await?,
+        None => None,
+    };
+    let status = match rendition {
+        Some(r) => r.status,
+        None if tape.video_key.is_some() => "ready".to_string(),
+        None => "none".to_string(),
+    };
+    Ok(Json(serde_json::json!({ "status": status })))
+}
+
+/// The tape's HLS playlist, pointing at presigned segment URLs
+async fn stream_playlist(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(tape_id): Path<String>,
+) -> Result<Response, Error> {
+    let tape = require_viewer(&tape_id, &user).await?;
+    let key = tape.video_key.ok_or(Error::NotFound)?;
+    let hls_key = transcode::rendition(&key)
+        .await?
+        .filter(Rendition::is_ready)
+        .and_then(|r| r.hls_key)
+        .ok_or(Error::NotFound)?;
+    let playlist = transcode::signed_playlist(&hls_key).await?;
+    Ok((
+        [
+            (header::CONTENT_TYPE, transcode::HLS_CONTENT_TYPE),
+            (header::CACHE_CONTROL, "private, no-store"),
+        ],
+        playlist,
+    )
+        .into_response())
+}
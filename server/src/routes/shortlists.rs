@@ -0,0 +1,694 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Request},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info};
+
+use crate::{
+    error::Error,
+    middleware::{AuthenticatedUser, UserExtractor},
+    models::{
+        job::{JobDetailView, JobModel},
+        offer::{self, OfferModel, OfferTerms},
+        person::SessionUser,
+        production::ProductionModel,
+        selftape::{self, SelfTapeModel},
+        share_link::{self, ShareAccess},
+        shortlist::{self, Shortlist, ShortlistEntry, ShortlistEntryListing, ShortlistModel},
+    },
+    record_id_ext::RecordIdExt,
+    templates::{BaseContext, SelectOption, User, filters},
+    units::{self, UnitSystem},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/jobs/{id}/shortlists", get(shortlists_page).post(create_shortlist))
+        .route("/shortlists/{shortlist_id}", get(shortlist_page))
+        .route("/shortlists/{shortlist_id}/rename", post(rename_shortlist))
+        .route("/shortlists/{shortlist_id}/add", post(add_people))
+        .route("/shortlists/{shortlist_id}/share", post(share_shortlist))
+        .route("/shortlists/{shortlist_id}/offers", post(send_offers))
+        .route("/shortlists/{shortlist_id}/delete", post(delete_shortlist))
+        .route("/shortlists/entries/{entry_id}/note", post(save_note))
+        .route("/shortlists/entries/{entry_id}/select", post(select_entry))
+        .route("/shortlists/entries/{entry_id}/move", post(move_entry))
+        .route("/shortlists/entries/{entry_id}/remove", post(remove_entry))
+        .route("/shortlists/shared/{token}", get(shared_page))
+        .route("/shortlists/shared/{token}/entries/{entry_id}/pick", post(pick_entry))
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct ShortlistRow {
+    pub id: String,
+    pub role_title: String,
+    pub name: String,
+    pub people: i64,
+    pub selected: i64,
+    pub shared: bool,
+}
+
+/// One person on a shortlist. Notes are left empty on the shared page.
+pub struct EntryCard {
+    pub id: String,
+    pub person_name: String,
+    pub username: String,
+    pub avatar: Option<String>,
+    pub headline: Option<String>,
+    pub location: Option<String>,
+    pub age_range: Option<String>,
+    pub height: Option<String>,
+    pub unions: String,
+    pub note: String,
+    pub selected: bool,
+    pub offered_at: Option<String>,
+    pub tape_rating: Option<String>,
+    pub tape_url: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "jobs/shortlists.html")]
+pub struct ShortlistsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub job_id: String,
+    pub job_title: String,
+    pub roles: Vec<String>,
+    pub shortlists: Vec<ShortlistRow>,
+    pub role_title: String,
+    pub name: String,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "jobs/shortlist.html")]
+pub struct ShortlistTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub shortlist_id: String,
+    pub name: String,
+    pub role_title: String,
+    pub job_id: String,
+    pub job_title: String,
+    pub entries: Vec<EntryCard>,
+    pub selected_count: usize,
+    pub unoffered_count: usize,
+    /// The link just made; its token isn't stored, so this is the only time
+    /// it can be shown
+    pub share_url: Option<String>,
+    pub share_access: String,
+    pub share_expires: Option<String>,
+    pub max_note: usize,
+    /// Whether the job is linked to a production, which offers come from
+    pub can_offer: bool,
+    pub rate_types: Vec<SelectOption>,
+    pub default_rate: String,
+    pub today: String,
+    pub max_conditions: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "jobs/shortlist_shared.html")]
+pub struct SharedShortlistTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub token: String,
+    pub name: String,
+    pub role_title: String,
+    pub job_id: String,
+    pub job_title: String,
+    pub entries: Vec<EntryCard>,
+    pub can_pick: bool,
+}
+
+// ============================
+// Helpers
+// ============================
+
+fn person_id(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%b %-d, %Y at %H:%M UTC").to_string()
+}
+
+fn share_url(token: &str) -> String {
+    format!("{}/shortlists/shared/{}", crate::config::app_url(), token)
+}
+
+/// The job, if the user is on its casting team: anyone who can edit it
+async fn require_casting(job_id: &str, user: &SessionUser) -> Result<JobDetailView, Error> {
+    let job = JobModel::get(job_id, Some(&user.id)).await?;
+    if !job.can_edit {
+        return Err(Error::Forbidden);
+    }
+    Ok(job)
+}
+
+async fn require_shortlist(shortlist_id: &str, user: &SessionUser) -> Result<(Shortlist, JobDetailView), Error> {
+    let shortlist = ShortlistModel::get(shortlist_id).await?;
+    let job = require_casting(&shortlist.job.key_string(), user).await?;
+    Ok((shortlist, job))
+}
+
+async fn require_entry(entry_id: &str, user: &SessionUser) -> Result<ShortlistEntry, Error> {
+    let entry = ShortlistModel::get_entry(entry_id).await?;
+    require_shortlist(&entry.shortlist.key_string(), user).await?;
+    Ok(entry)
+}
+
+/// `units` is the viewer's preference; without one, heights show in both systems
+fn entry_card(entry: ShortlistEntryListing, include_note: bool, units: Option<UnitSystem>) -> EntryCard {
+    let average = selftape::average_rating(&entry.tape_ratings);
+    EntryCard {
+        id: entry.id.key_string(),
+        person_name: entry.person_name,
+        username: entry.username,
+        avatar: entry.avatar,
+        headline: entry.headline.filter(|h| !h.is_empty()),
+        location: entry.location.filter(|l| !l.is_empty()),
+        age_range: entry.age_range.map(|r| format!("{}–{}", r.min, r.max)),
+        height: entry.height.filter(|mm| *mm > 0).map(|mm| match units {
+            Some(system) => units::height(mm, system),
+            None => units::height_both(mm, UnitSystem::Metric),
+        }),
+        unions: entry.unions.join(", "),
+        note: if include_note { entry.note.unwrap_or_default() } else { String::new() },
+        selected: entry.selected,
+        offered_at: entry.offered_at.map(format_time),
+        tape_rating: average.map(|a| format!("{:.1}", a)),
+        tape_url: match (entry.tape_request, entry.tape) {
+            (Some(request), Some(tape)) if include_note => Some(format!(
+                "/self-tapes/{}#tape-{}",
+                request.key_string(),
+                tape.key_string()
+            )),
+            _ => None,
+        },
+    }
+}
+
+async fn render_shortlists(
+    user: &SessionUser,
+    job: JobDetailView,
+    role_title: String,
+    name: String,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let job_id = job.id.strip_prefix("job_posting:").unwrap_or(&job.id).to_string();
+    let shortlists = ShortlistModel::for_job(&RecordId::new("job_posting", job_id.as_str()))
+        .await?
+        .into_iter()
+        .map(|s| ShortlistRow {
+            id: s.id.key_string(),
+            role_title: s.role_title,
+            name: s.name,
+            people: s.people,
+            selected: s.selected,
+            shared: s.shared,
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("jobs")
+        .with_user(User::from_session_user(user).await);
+    let template = ShortlistsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        job_id,
+        job_title: job.title,
+        roles: job.roles.into_iter().map(|r| r.title).collect(),
+        shortlists,
+        role_title,
+        name,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render shortlists template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn render_shortlist(
+    user: &SessionUser,
+    shortlist: Shortlist,
+    job: JobDetailView,
+    share_url: Option<String>,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let units = units::viewer_preference(Some(&user.id)).await;
+    let entries: Vec<EntryCard> = ShortlistModel::entries(&shortlist)
+        .await?
+        .into_iter()
+        .map(|e| entry_card(e, true, units))
+        .collect();
+    let selected_count = entries.iter().filter(|e| e.selected).count();
+    let unoffered_count = entries.iter().filter(|e| e.selected && e.offered_at.is_none()).count();
+    let role = job.roles.iter().find(|r| r.title == shortlist.role_title);
+    let rate_type = role.map(|r| offer_rate_type(&r.rate_type)).unwrap_or("daily");
+    let default_rate = role
+        .and_then(|r| r.rate_amount.as_deref())
+        .and_then(|amount| amount.trim().trim_start_matches('$').replace(',', "").parse::<f64>().ok())
+        .map(|amount| amount.to_string())
+        .unwrap_or_default();
+
+    let base = BaseContext::new()
+        .with_page("jobs")
+        .with_user(User::from_session_user(user).await);
+    let template = ShortlistTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        shortlist_id: shortlist.id.key_string(),
+        name: shortlist.name.clone(),
+        role_title: shortlist.role_title.clone(),
+        job_id: shortlist.job.key_string(),
+        job_title: job.title,
+        entries,
+        selected_count,
+        unoffered_count,
+        share_url,
+        share_expires: shortlist
+            .access()
+            .and(shortlist.share_expires_at)
+            .map(format_time),
+        share_access: shortlist.access().map(|a| a.as_str()).unwrap_or("off").to_string(),
+        max_note: shortlist::MAX_NOTE_CHARS,
+        can_offer: job.production_slug.is_some(),
+        rate_types: offer::RATE_TYPES
+            .iter()
+            .map(|(value, label)| SelectOption::new(value, label.to_string(), *value == rate_type))
+            .collect(),
+        default_rate,
+        today: Utc::now().date_naive().to_string(),
+        max_conditions: offer::MAX_CONDITIONS_CHARS,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render shortlist template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+/// The offer rate type closest to how a job role is paid
+fn offer_rate_type(role_rate_type: &str) -> &'static str {
+    let rate_type = role_rate_type.to_lowercase();
+    if rate_type.contains("hour") {
+        "hourly"
+    } else if rate_type.contains("week") {
+        "weekly"
+    } else if rate_type.contains("flat") || rate_type.contains("fixed") {
+        "flat"
+    } else {
+        "daily"
+    }
+}
+
+fn back_to_shortlist(shortlist: &RecordId, entry: Option<&RecordId>) -> Response {
+    let anchor = entry.map(|e| format!("#entry-{}", e.key_string())).unwrap_or_default();
+    Redirect::to(&format!("/shortlists/{}{}", shortlist.key_string(), anchor)).into_response()
+}
+
+// ============================
+// Casting handlers
+// ============================
+
+async fn shortlists_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Response, Error> {
+    let job = require_casting(&id, &user).await?;
+    render_shortlists(&user, job, String::new(), String::new(), None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct NewShortlistForm {
+    #[serde(default)]
+    role_title: String,
+    #[serde(default)]
+    name: String,
+}
+
+async fn create_shortlist(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(id): Path<String>,
+    Form(form): Form<NewShortlistForm>,
+) -> Result<Response, Error> {
+    let job = require_casting(&id, &user).await?;
+    let result = if job.roles.iter().any(|r| r.title == form.role_title) {
+        ShortlistModel::create(
+            &RecordId::new("job_posting", id.as_str()),
+            &form.role_title,
+            &form.name,
+            &person_id(&user)?,
+        )
+        .await
+    } else {
+        Err(Error::Validation("Choose the role to shortlist for".to_string()))
+    };
+
+    match result {
+        Ok(shortlist) => {
+            info!("{} created shortlist '{}' for job {}", user.username, shortlist.name, id);
+            Ok(back_to_shortlist(&shortlist.id, None))
+        }
+        Err(Error::Validation(msg)) => render_shortlists(&user, job, form.role_title, form.name, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+async fn shortlist_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(shortlist_id): Path<String>,
+) -> Result<Response, Error> {
+    let (shortlist, job) = require_shortlist(&shortlist_id, &user).await?;
+    render_shortlist(&user, shortlist, job, None, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct RenameForm {
+    #[serde(default)]
+    name: String,
+}
+
+async fn rename_shortlist(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(shortlist_id): Path<String>,
+    Form(form): Form<RenameForm>,
+) -> Result<Response, Error> {
+    let (shortlist, job) = require_shortlist(&shortlist_id, &user).await?;
+    match ShortlistModel::rename(&shortlist.id, &form.name).await {
+        Ok(()) => Ok(back_to_shortlist(&shortlist.id, None)),
+        Err(Error::Validation(msg)) => {
+            render_shortlist(&user, shortlist, job, None, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AddForm {
+    add_applicants: Option<String>,
+    #[serde(default)]
+    usernames: String,
+}
+
+async fn add_people(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(shortlist_id): Path<String>,
+    Form(form): Form<AddForm>,
+) -> Result<Response, Error> {
+    let (shortlist, job) = require_shortlist(&shortlist_id, &user).await?;
+
+    let result = async {
+        let mut people = if form.add_applicants.is_some() {
+            SelfTapeModel::role_applicants(&shortlist.job, &shortlist.role_title).await?
+        } else {
+            Vec::new()
+        };
+        let usernames = selftape::parse_usernames(&form.usernames);
+        if !usernames.is_empty() {
+            let (found, unknown) = SelfTapeModel::people_by_username(&usernames).await?;
+            if !unknown.is_empty() {
+                let unknown: Vec<String> = unknown.iter().map(|u| format!("@{}", u)).collect();
+                return Err(Error::Validation(format!("No one goes by {}", unknown.join(", "))));
+            }
+            people.extend(found);
+        }
+        if people.is_empty() {
+            return Err(Error::Validation(
+                "Add the role's applicants or the usernames of the people to shortlist".to_string(),
+            ));
+        }
+        ShortlistModel::add(&shortlist.id, &people, &person_id(&user)?).await
+    }
+    .await;
+
+    match result {
+        Ok(added) => {
+            info!("{} added {} people to shortlist {}", user.username, added.len(), shortlist_id);
+            Ok(back_to_shortlist(&shortlist.id, None))
+        }
+        Err(Error::Validation(msg)) => {
+            render_shortlist(&user, shortlist, job, None, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ShareForm {
+    /// "view", "pick" or "off"
+    #[serde(default)]
+    access: String,
+}
+
+async fn share_shortlist(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(shortlist_id): Path<String>,
+    Form(form): Form<ShareForm>,
+) -> Result<Response, Error> {
+    let (shortlist, job) = require_shortlist(&shortlist_id, &user).await?;
+    match ShareAccess::parse(&form.access) {
+        Some(access) => {
+            let token = share_link::share(&shortlist.id, shortlist.access(), access).await?;
+            info!("{} shared shortlist {} ({})", user.username, shortlist_id, access.as_str());
+            if let Some(token) = token {
+                let shortlist = ShortlistModel::get(&shortlist_id).await?;
+                return render_shortlist(&user, shortlist, job, Some(share_url(&token)), None).await;
+            }
+        }
+        None => {
+            share_link::unshare(&shortlist.id).await?;
+            info!("{} stopped sharing shortlist {}", user.username, shortlist_id);
+        }
+    }
+    Ok(Redirect::to(&format!("/shortlists/{}#share", shortlist_id)).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct OffersForm {
+    #[serde(default)]
+    start_date: String,
+    #[serde(default)]
+    end_date: String,
+    #[serde(default)]
+    rate_amount: String,
+    #[serde(default)]
+    rate_type: String,
+    #[serde(default)]
+    conditions: String,
+}
+
+/// Send everyone selected who hasn't had one an offer on the job's production
+async fn send_offers(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(shortlist_id): Path<String>,
+    Form(form): Form<OffersForm>,
+) -> Result<Response, Error> {
+    let (shortlist, job) = require_shortlist(&shortlist_id, &user).await?;
+
+    let Some(production_slug) = job.production_slug.clone() else {
+        let msg = "Link this job to its production to send offers".to_string();
+        return render_shortlist(&user, shortlist, job, None, Some(msg)).await;
+    };
+    let terms = match OfferTerms::parse(
+        &shortlist.role_title,
+        &form.start_date,
+        &form.end_date,
+        &form.rate_amount,
+        &form.rate_type,
+        &form.conditions,
+        Utc::now().date_naive(),
+    ) {
+        Ok(terms) => terms,
+        Err(Error::Validation(msg)) => {
+            return render_shortlist(&user, shortlist, job, None, Some(msg)).await;
+        }
+        Err(e) => return Err(e),
+    };
+    let production = ProductionModel::get_by_slug(&production_slug).await?;
+
+    let offered = ShortlistModel::mark_offered(&shortlist.id).await?;
+    if offered.is_empty() {
+        return render_shortlist(
+            &user,
+            shortlist,
+            job,
+            None,
+            Some("Select the people to offer the role to first".to_string()),
+        )
+        .await;
+    }
+    let sent_by = person_id(&user)?;
+    for entry in &offered {
+        let offer = OfferModel::create(&production.id, &entry.person, &terms, &sent_by, Some(&entry.id)).await?;
+        super::offers::deliver_offer(&offer, &production.title, false).await;
+    }
+    info!(
+        "{} sent offers for '{}' to {} people from shortlist {}",
+        user.username,
+        shortlist.role_title,
+        offered.len(),
+        shortlist_id
+    );
+    Ok(back_to_shortlist(&shortlist.id, None))
+}
+
+async fn delete_shortlist(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(shortlist_id): Path<String>,
+) -> Result<Response, Error> {
+    let (shortlist, _) = require_shortlist(&shortlist_id, &user).await?;
+    ShortlistModel::delete(&shortlist.id).await?;
+    info!("{} deleted shortlist {}", user.username, shortlist_id);
+    Ok(Redirect::to(&format!("/jobs/{}/shortlists", shortlist.job.key_string())).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct NoteForm {
+    #[serde(default)]
+    note: String,
+}
+
+async fn save_note(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(entry_id): Path<String>,
+    Form(form): Form<NoteForm>,
+) -> Result<Response, Error> {
+    let entry = require_entry(&entry_id, &user).await?;
+    match ShortlistModel::set_note(&entry.id, &form.note).await {
+        Ok(()) => Ok(back_to_shortlist(&entry.shortlist, Some(&entry.id))),
+        Err(Error::Validation(msg)) => {
+            let (shortlist, job) = require_shortlist(&entry.shortlist.key_string(), &user).await?;
+            render_shortlist(&user, shortlist, job, None, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SelectForm {
+    selected: Option<String>,
+}
+
+async fn select_entry(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(entry_id): Path<String>,
+    Form(form): Form<SelectForm>,
+) -> Result<Response, Error> {
+    let entry = require_entry(&entry_id, &user).await?;
+    ShortlistModel::set_selected(&entry.id, form.selected.is_some()).await?;
+    Ok(back_to_shortlist(&entry.shortlist, Some(&entry.id)))
+}
+
+#[derive(Debug, Deserialize)]
+struct MoveForm {
+    direction: String,
+}
+
+async fn move_entry(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(entry_id): Path<String>,
+    Form(form): Form<MoveForm>,
+) -> Result<Response, Error> {
+    let entry = require_entry(&entry_id, &user).await?;
+    ShortlistModel::shift(&entry, form.direction == "up").await?;
+    Ok(back_to_shortlist(&entry.shortlist, Some(&entry.id)))
+}
+
+async fn remove_entry(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(entry_id): Path<String>,
+) -> Result<Response, Error> {
+    let entry = require_entry(&entry_id, &user).await?;
+    ShortlistModel::remove(&entry.id).await?;
+    Ok(back_to_shortlist(&entry.shortlist, None))
+}
+
+// ============================
+// Shared link handlers
+// ============================
+
+async fn shared_page(Path(token): Path<String>, request: Request) -> Result<Response, Error> {
+    let shortlist = ShortlistModel::by_share_token(&token).await?;
+    let job = JobModel::get(&shortlist.job.key_string(), None).await?;
+    let viewer = request.get_user();
+    let units = units::viewer_preference(viewer.as_ref().map(|u| u.id.as_str())).await;
+    let entries = ShortlistModel::entries(&shortlist)
+        .await?
+        .into_iter()
+        .map(|e| entry_card(e, false, units))
+        .collect();
+
+    let mut base = BaseContext::new().with_page("jobs");
+    if let Some(user) = viewer {
+        base = base.with_user(User::from_session_user(&user).await);
+    }
+    let template = SharedShortlistTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        token,
+        name: shortlist.name.clone(),
+        role_title: shortlist.role_title.clone(),
+        job_id: shortlist.job.key_string(),
+        job_title: job.title,
+        entries,
+        can_pick: shortlist.access() == Some(ShareAccess::Pick),
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render shared shortlist template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn pick_entry(
+    Path((token, entry_id)): Path<(String, String)>,
+    Form(form): Form<SelectForm>,
+) -> Result<Response, Error> {
+    let shortlist = ShortlistModel::by_share_token(&token).await?;
+    if shortlist.access() != Some(ShareAccess::Pick) {
+        return Err(Error::Forbidden);
+    }
+    let entry = ShortlistModel::get_entry(&entry_id).await?;
+    if entry.shortlist != shortlist.id {
+        return Err(Error::NotFound);
+    }
+    ShortlistModel::set_selected(&entry.id, form.selected.is_some()).await?;
+    info!("Shared link picks updated on shortlist {}", shortlist.id.display());
+    Ok(Redirect::to(&format!("/shortlists/shared/{}#entry-{}", token, entry_id)).into_response())
+}
@@ -0,0 +1,832 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query, Request},
+    http::header,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info, warn};
+
+use crate::{
+    error::Error,
+    middleware::{AuthenticatedUser, UserExtractor},
+    models::{
+        blackout::{self, Blackout, BlackoutModel},
+        call_sheet::{self, CallSheetCrew, CallSheetLocation},
+        daily_report::{self, DailyReportModel},
+        house_rules::{self, HouseRulesModel},
+        location::LocationModel,
+        org_branding::Letterhead,
+        person::SessionUser,
+        production::{Production, ProductionModel},
+        shot_list::{
+            self, CreateShotData, DayProgress, SHOT_ANGLES, SHOT_MOVEMENTS, SHOT_SIZES, SceneShots,
+            ShootDay, ShotListModel,
+        },
+        scouting::ScoutingModel,
+    },
+    record_id_ext::RecordIdExt,
+    services::email::{EmailAttachment, EmailService},
+    templates::{BaseContext, FormatQuery, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/productions/{slug}/shots", get(shot_list_page))
+        .route("/productions/{slug}/shots/days", post(create_day))
+        .route("/productions/{slug}/shots/days/{day_id}", get(print_day))
+        .route("/productions/{slug}/shots/days/{day_id}/export.csv", get(export_day))
+        .route("/productions/{slug}/shots/days/{day_id}/delete", post(delete_day))
+        .route("/productions/{slug}/shots/days/{day_id}/location", post(set_day_location))
+        .route("/productions/{slug}/shots/days/{day_id}/call-sheet.pdf", get(call_sheet_pdf))
+        .route("/productions/{slug}/shots/days/{day_id}/call-sheet/send", post(send_call_sheet))
+        .route("/productions/{slug}/shots/scenes", post(create_scene))
+        .route("/productions/{slug}/shots/scenes/{scene_id}/day", post(schedule_scene))
+        .route("/productions/{slug}/shots/scenes/{scene_id}/delete", post(delete_scene))
+        .route("/productions/{slug}/shots/scenes/{scene_id}/shots", post(create_shot))
+        .route("/productions/{slug}/shots/{shot_id}/captured", post(toggle_captured))
+        .route("/productions/{slug}/shots/{shot_id}/delete", post(delete_shot))
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct ShotView {
+    pub id: String,
+    pub number: i64,
+    pub description: String,
+    pub size: String,
+    pub angle: String,
+    pub movement: String,
+    pub gear: String,
+    pub captured: bool,
+}
+
+pub struct SetupView {
+    pub number: i64,
+    pub shots: Vec<ShotView>,
+}
+
+pub struct SceneView {
+    pub id: String,
+    pub number: String,
+    pub heading: String,
+    pub description: Option<String>,
+    pub day_id: String,
+    pub complete: bool,
+    pub setups: Vec<SetupView>,
+}
+
+pub struct DayView {
+    pub id: String,
+    pub day_number: i64,
+    pub date: String,
+    pub notes: Option<String>,
+    pub progress: DayProgress,
+    pub scenes: Vec<SceneView>,
+    /// Booked location key, empty when none
+    pub location_id: String,
+    pub location_name: Option<String>,
+    pub call_sheet_sent_at: Option<String>,
+    pub call_time: String,
+    pub wrap_time: String,
+    /// Key crew who haven't acknowledged the location's house rules
+    pub pending_acks: Vec<String>,
+    /// Location blackouts the day runs into
+    pub conflicts: Vec<String>,
+}
+
+fn scene_view(scene: SceneShots) -> SceneView {
+    SceneView {
+        complete: scene.is_complete(),
+        id: scene.scene.id.key_string(),
+        number: scene.scene.number,
+        heading: scene.scene.heading,
+        description: scene.scene.description,
+        day_id: scene.scene.shoot_day.map(|d| d.key_string()).unwrap_or_default(),
+        setups: scene
+            .setups
+            .into_iter()
+            .map(|setup| SetupView {
+                number: setup.number,
+                shots: setup
+                    .shots
+                    .into_iter()
+                    .map(|shot| ShotView {
+                        id: shot.id.key_string(),
+                        number: shot.number,
+                        description: shot.description,
+                        size: shot_list::label(SHOT_SIZES, &shot.size),
+                        angle: shot_list::label(SHOT_ANGLES, &shot.angle),
+                        movement: shot_list::label(SHOT_MOVEMENTS, &shot.movement),
+                        gear: shot.gear.join(", "),
+                        captured: shot.captured,
+                    })
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+fn day_view(day: ShootDay, scenes: Vec<SceneShots>) -> DayView {
+    DayView {
+        id: day.id.key_string(),
+        day_number: day.day_number,
+        date: day.date,
+        notes: day.notes,
+        progress: DayProgress::from_scenes(&scenes),
+        scenes: scenes.into_iter().map(scene_view).collect(),
+        location_id: day.location.map(|l| l.key_string()).unwrap_or_default(),
+        location_name: None,
+        call_sheet_sent_at: day
+            .call_sheet_sent_at
+            .map(|at| at.format("%b %-d, %Y %H:%M").to_string()),
+        call_time: day.call_time.unwrap_or_default(),
+        wrap_time: day.wrap_time.unwrap_or_default(),
+        pending_acks: Vec::new(),
+        conflicts: Vec::new(),
+    }
+}
+
+fn options(values: &[(&str, &str)]) -> Vec<SelectOption> {
+    values
+        .iter()
+        .map(|(value, label)| SelectOption::new(value, label.to_string(), false))
+        .collect()
+}
+
+#[derive(Template)]
+#[template(path = "productions/shots.html")]
+pub struct ShotListTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub can_edit: bool,
+    pub days: Vec<DayView>,
+    pub unscheduled: Vec<SceneView>,
+    pub sizes: Vec<SelectOption>,
+    pub angles: Vec<SelectOption>,
+    pub movements: Vec<SelectOption>,
+    /// Scouted or already booked locations a day can be booked at
+    pub locations: Vec<SelectOption>,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/shot_list_print.html")]
+pub struct ShotListPrintTemplate {
+    pub app_name: String,
+    pub production_title: String,
+    pub day: DayView,
+}
+
+#[derive(Template)]
+#[template(path = "productions/schedule_print.html")]
+pub struct SchedulePrintTemplate {
+    pub app_name: String,
+    pub version: String,
+    pub production_title: String,
+    pub production_slug: String,
+    pub printed_on: String,
+    pub days: Vec<DayView>,
+    pub unscheduled: Vec<SceneView>,
+    /// Opened through a guest link, so there's no shot list to go back to
+    pub guest: bool,
+}
+
+// ============================
+// Access
+// ============================
+
+struct Access {
+    production: Production,
+    person: RecordId,
+    can_edit: bool,
+}
+
+/// Shot lists are for the production's members; structure edits are for its
+/// owners and admins.
+async fn require_member(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    let can_edit = ProductionModel::can_edit(&production.id, user_id).await?;
+    if !can_edit && !ProductionModel::is_member(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    let person = RecordId::parse_simple(user_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    Ok(Access {
+        production,
+        person,
+        can_edit,
+    })
+}
+
+async fn require_editor(slug: &str, user_id: &str) -> Result<Production, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    if !ProductionModel::can_edit(&production.id, user_id).await? {
+        return Err(Error::Forbidden);
+    }
+    Ok(production)
+}
+
+fn back_to_list(slug: &str) -> Response {
+    Redirect::to(&format!("/productions/{}/shots", slug)).into_response()
+}
+
+pub(super) async fn load_days(
+    production: &RecordId,
+) -> Result<(Vec<DayView>, Vec<SceneView>), Error> {
+    let days = ShotListModel::days(production).await?;
+    let scenes = ShotListModel::scenes(production, None).await?;
+
+    let mut by_day: Vec<(ShootDay, Vec<SceneShots>)> = days.into_iter().map(|d| (d, Vec::new())).collect();
+    let mut unscheduled = Vec::new();
+    for scene in scenes {
+        match by_day
+            .iter_mut()
+            .find(|(day, _)| scene.scene.shoot_day.as_ref() == Some(&day.id))
+        {
+            Some((_, day_scenes)) => day_scenes.push(scene),
+            None => unscheduled.push(scene_view(scene)),
+        }
+    }
+
+    // Location names, outstanding house rule acknowledgments and blackouts
+    let booked = HouseRulesModel::booked_locations(production).await?;
+    let mut pending = Vec::with_capacity(booked.len());
+    let mut blackouts = Vec::with_capacity(booked.len());
+    for location in &booked {
+        pending.push(if location.house_rules.is_empty() {
+            Vec::new()
+        } else {
+            house_rules::pending_key_people(&HouseRulesModel::acknowledgments(production, location).await?)
+        });
+        blackouts.push(BlackoutModel::for_location(&location.id).await?);
+    }
+
+    let days = by_day
+        .into_iter()
+        .map(|(day, scenes)| {
+            let booked_at = day
+                .location
+                .as_ref()
+                .and_then(|id| booked.iter().position(|l| &l.id == id));
+            let conflicts: Vec<String> = booked_at
+                .map(|i| day_conflicts(&day, &blackouts[i]).iter().map(|b| b.describe()).collect())
+                .unwrap_or_default();
+            let mut view = day_view(day, scenes);
+            if let Some(i) = booked_at {
+                view.location_name = Some(booked[i].name.clone());
+                view.pending_acks = pending[i].clone();
+                view.conflicts = conflicts;
+            }
+            view
+        })
+        .collect();
+    Ok((days, unscheduled))
+}
+
+/// The location blackouts a shoot day runs into
+fn day_conflicts<'a>(day: &ShootDay, blackouts: &'a [Blackout]) -> Vec<&'a Blackout> {
+    let Ok(date) = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d") else {
+        return Vec::new();
+    };
+    let hours = blackout::shoot_hours(day.call_time.as_deref(), day.wrap_time.as_deref());
+    blackout::conflicts(blackouts, date, hours)
+}
+
+/// Locations in the production's scouting collections plus any already
+/// booked, as `(key, "Name, City")`
+async fn location_options(production: &RecordId) -> Result<Vec<SelectOption>, Error> {
+    let mut options: Vec<SelectOption> = ScoutingModel::locations_for(production)
+        .await?
+        .into_iter()
+        .map(|l| SelectOption::new(l.id.key_string(), format!("{}, {}", l.name, l.city), false))
+        .collect();
+    for location in HouseRulesModel::booked_locations(production).await? {
+        let key = location.id.key_string();
+        if !options.iter().any(|o| o.value == key) {
+            options.push(SelectOption::new(&key, format!("{}, {}", location.name, location.city), false));
+        }
+    }
+    Ok(options)
+}
+
+async fn render_page(request_user: &SessionUser, access: Access, error: Option<String>) -> Result<Response, Error> {
+    let (days, unscheduled) = load_days(&access.production.id).await?;
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(request_user).await);
+
+    let template = ShotListTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: access.production.title,
+        production_slug: access.production.slug,
+        can_edit: access.can_edit,
+        days,
+        unscheduled,
+        sizes: options(SHOT_SIZES),
+        angles: options(SHOT_ANGLES),
+        movements: options(SHOT_MOVEMENTS),
+        locations: location_options(&access.production.id).await?,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render shot list template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+// ============================
+// Handlers
+// ============================
+
+async fn shot_list_page(
+    Path(slug): Path<String>,
+    Query(view): Query<FormatQuery>,
+    request: Request,
+) -> Result<Response, Error> {
+    let user = request.get_user().ok_or(Error::Unauthorized)?;
+    let access = require_member(&slug, &user.id).await?;
+    if view.is_print() {
+        return print_schedule(access).await;
+    }
+    render_page(&user, access, None).await
+}
+
+/// Printable shooting schedule: every day on one landscape table
+async fn print_schedule(access: Access) -> Result<Response, Error> {
+    let (days, unscheduled) = load_days(&access.production.id).await?;
+    let base = BaseContext::new();
+    let template = SchedulePrintTemplate {
+        app_name: base.app_name,
+        version: base.version,
+        production_title: access.production.title,
+        production_slug: access.production.slug,
+        printed_on: chrono::Utc::now().format("%b %-d, %Y").to_string(),
+        days,
+        unscheduled,
+        guest: false,
+    };
+    let html = template.render().map_err(|e| {
+        error!("Failed to render printable schedule: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct DayForm {
+    date: String,
+    notes: Option<String>,
+}
+
+async fn create_day(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<DayForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    match ShotListModel::create_day(&production.id, &form.date, form.notes).await {
+        Ok(day) => {
+            info!("{} added shoot day {} to {}", user.username, day.day_number, slug);
+            Ok(back_to_list(&slug))
+        }
+        Err(Error::Validation(msg)) => {
+            let access = require_member(&slug, &user.id).await?;
+            render_page(&user, access, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn delete_day(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    ShotListModel::delete_day(&production.id, &day_id).await?;
+    Ok(back_to_list(&slug))
+}
+
+async fn load_day(production: &RecordId, day_id: &str) -> Result<(ShootDay, Vec<SceneShots>), Error> {
+    let day = ShotListModel::get_day(production, day_id).await?;
+    let scenes = ShotListModel::scenes(production, Some(&day.id)).await?;
+    Ok((day, scenes))
+}
+
+/// Printable shot list for one shoot day
+async fn print_day(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Html<String>, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let (day, scenes) = load_day(&access.production.id, &day_id).await?;
+
+    let template = ShotListPrintTemplate {
+        app_name: BaseContext::new().app_name,
+        production_title: access.production.title,
+        day: day_view(day, scenes),
+    };
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render printable shot list: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+async fn export_day(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let (day, scenes) = load_day(&access.production.id, &day_id).await?;
+    let csv = shot_list::shot_list_csv(&day, &scenes);
+    let filename = format!("{}-day-{}-shot-list.csv", slug, day.day_number);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        csv,
+    )
+        .into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct DayLocationForm {
+    #[serde(default)]
+    location_id: String,
+    #[serde(default)]
+    call_time: String,
+    #[serde(default)]
+    wrap_time: String,
+}
+
+/// Book a shoot day at one of the production's scouted locations with its
+/// call and wrap times, or clear the booking. Refused when the day runs into
+/// one of the location's blackouts.
+async fn set_day_location(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+    Form(form): Form<DayLocationForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    let mut day = ShotListModel::get_day(&production.id, &day_id).await?;
+
+    let hours = daily_report::parse_time(&form.call_time)
+        .and_then(|call| daily_report::parse_time(&form.wrap_time).map(|wrap| (call, wrap)));
+    let (call_time, wrap_time) = match hours {
+        Ok(hours) => hours,
+        Err(Error::Validation(msg)) => {
+            let access = require_member(&slug, &user.id).await?;
+            return render_page(&user, access, Some(msg)).await;
+        }
+        Err(e) => return Err(e),
+    };
+    day.call_time = call_time.clone();
+    day.wrap_time = wrap_time.clone();
+
+    let key = form.location_id.trim();
+    let location = if key.is_empty() {
+        None
+    } else {
+        let known = location_options(&production.id).await?;
+        if !known.iter().any(|o| o.value == key) {
+            let access = require_member(&slug, &user.id).await?;
+            let msg = "Add the location to one of the production's scouting collections first".to_string();
+            return render_page(&user, access, Some(msg)).await;
+        }
+        let location = LocationModel::get(&RecordId::new("location", key)).await?;
+        let blackouts = BlackoutModel::for_location(&location.id).await?;
+        let conflicts = day_conflicts(&day, &blackouts);
+        if !conflicts.is_empty() {
+            let access = require_member(&slug, &user.id).await?;
+            let has_hours = blackout::shoot_hours(day.call_time.as_deref(), day.wrap_time.as_deref()).is_some();
+            let msg = blackout::conflict_message(&location.name, &day.date, &conflicts, has_hours);
+            return render_page(&user, access, Some(msg)).await;
+        }
+        Some(location.id)
+    };
+
+    ShotListModel::set_day_location(&day.id, location).await?;
+    ShotListModel::set_day_hours(&day.id, call_time, wrap_time).await?;
+    info!(
+        "{} {} the location for day {} of {}",
+        user.username,
+        if key.is_empty() { "cleared" } else { "set" },
+        day.day_number,
+        slug
+    );
+    Ok(back_to_list(&slug))
+}
+
+/// The day's location and crew as they go on the call sheet
+async fn call_sheet_details(
+    production: &RecordId,
+    day: &ShootDay,
+) -> Result<(Option<CallSheetLocation>, Vec<CallSheetCrew>), Error> {
+    let location = match &day.location {
+        Some(id) => match LocationModel::get(id).await {
+            Ok(location) => Some(CallSheetLocation {
+                address: [location.address, location.city, location.state]
+                    .into_iter()
+                    .filter(|part| !part.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                name: location.name,
+                parking: location.parking_info,
+                house_rules: location.house_rules.unwrap_or_default(),
+            }),
+            Err(Error::NotFound) => None,
+            Err(e) => return Err(e),
+        },
+        None => None,
+    };
+
+    let crew = ProductionModel::get_members(production)
+        .await?
+        .into_iter()
+        .filter(|m| m.member_type == "person" && m.invitation_status == "accepted")
+        .map(|m| CallSheetCrew {
+            name: m.name,
+            roles: m.production_roles.unwrap_or_default(),
+        })
+        .collect();
+    Ok((location, crew))
+}
+
+async fn call_sheet_pdf(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let (day, scenes) = load_day(&access.production.id, &day_id).await?;
+    let (location, crew) = call_sheet_details(&access.production.id, &day).await?;
+    let letterhead = Letterhead::for_production(&access.production.id).await?;
+    let pdf = call_sheet::call_sheet_pdf(
+        &access.production.title,
+        &day,
+        location.as_ref(),
+        &scenes,
+        &crew,
+        letterhead.as_ref(),
+    );
+    let filename = format!("{}-day-{}-call-sheet.pdf", slug, day.day_number);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("inline; filename=\"{}\"", filename),
+            ),
+        ],
+        pdf,
+    )
+        .into_response())
+}
+
+/// Email the day's call sheet to the daily report distribution list. Held
+/// back until the key crew have acknowledged the location's house rules.
+async fn send_call_sheet(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    if !access.can_edit {
+        return Err(Error::Forbidden);
+    }
+    let (day, scenes) = load_day(&access.production.id, &day_id).await?;
+
+    if let Some(location) = &day.location {
+        let blackouts = BlackoutModel::for_location(location).await?;
+        let conflicts = day_conflicts(&day, &blackouts);
+        if !conflicts.is_empty() {
+            let reasons: Vec<String> = conflicts.iter().map(|b| b.describe()).collect();
+            let msg = format!(
+                "Day {} runs into the location's blackouts ({}). Move the day or book another location before sending the call sheet.",
+                day.day_number,
+                reasons.join("; ")
+            );
+            return render_page(&user, access, Some(msg)).await;
+        }
+
+        let pending = HouseRulesModel::pending_for_day(&access.production.id, location).await?;
+        if !pending.is_empty() {
+            let msg = format!(
+                "The day {} call sheet can't go out until these crew acknowledge the location's house rules: {}",
+                day.day_number,
+                pending.join(", ")
+            );
+            return render_page(&user, access, Some(msg)).await;
+        }
+    }
+
+    let recipients = DailyReportModel::recipients(&access.production.id).await?;
+    if recipients.is_empty() {
+        let msg = "Add a distribution list on the reports page before sending".to_string();
+        return render_page(&user, access, Some(msg)).await;
+    }
+
+    let title = access.production.title.clone();
+    let (location, crew) = call_sheet_details(&access.production.id, &day).await?;
+    let letterhead = Letterhead::for_production(&access.production.id).await?;
+    let attachment = EmailAttachment {
+        filename: format!("{}-day-{}-call-sheet.pdf", slug, day.day_number),
+        content_type: "application/pdf".to_string(),
+        data: call_sheet::call_sheet_pdf(
+            &title,
+            &day,
+            location.as_ref(),
+            &scenes,
+            &crew,
+            letterhead.as_ref(),
+        ),
+    };
+    let subject = format!("{} - Call Sheet, Day {} ({})", title, day.day_number, day.date);
+    let mut text = call_sheet::call_sheet_summary(&title, &day, location.as_ref(), &scenes);
+    let mut html = format!("<pre style=\"font-family: sans-serif\">{}</pre>", ammonia::clean_text(&text));
+    if let Some(letterhead) = &letterhead {
+        text.push_str(&letterhead.brand.email_footer_text());
+        html.push_str(&letterhead.brand.email_footer_html());
+    }
+
+    let email = match EmailService::from_env() {
+        Ok(email) => email,
+        Err(e) => {
+            error!("Email service unavailable for call sheet: {}", e);
+            let msg = "Email isn't configured, so the call sheet couldn't be sent".to_string();
+            return render_page(&user, access, Some(msg)).await;
+        }
+    };
+
+    let mut delivered = 0;
+    let mut failed = Vec::new();
+    for recipient in recipients {
+        match email
+            .send_email_with_attachments(&recipient, None, &subject, &text, &html, std::slice::from_ref(&attachment))
+            .await
+        {
+            Ok(()) => delivered += 1,
+            Err(e) => {
+                warn!("Failed to send call sheet to {}: {}", recipient, e);
+                failed.push(recipient);
+            }
+        }
+    }
+
+    if delivered == 0 {
+        let msg = "The call sheet couldn't be sent. Please try again.".to_string();
+        return render_page(&user, access, Some(msg)).await;
+    }
+
+    ShotListModel::mark_call_sheet_sent(&day.id).await?;
+    info!(
+        "{} sent the day {} call sheet for {} to {} recipient(s)",
+        user.username, day.day_number, slug, delivered
+    );
+
+    if !failed.is_empty() {
+        let msg = format!("Call sheet sent, but couldn't deliver to: {}", failed.join(", "));
+        return render_page(&user, access, Some(msg)).await;
+    }
+    Ok(back_to_list(&slug))
+}
+
+#[derive(Debug, Deserialize)]
+struct SceneForm {
+    number: String,
+    #[serde(default)]
+    heading: String,
+    description: Option<String>,
+    shoot_day: Option<String>,
+}
+
+async fn create_scene(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<SceneForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    match ShotListModel::create_scene(
+        &production.id,
+        &form.number,
+        &form.heading,
+        form.description,
+        form.shoot_day.as_deref(),
+    )
+    .await
+    {
+        Ok(_) => Ok(back_to_list(&slug)),
+        Err(Error::Validation(msg)) => {
+            let access = require_member(&slug, &user.id).await?;
+            render_page(&user, access, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleForm {
+    shoot_day: Option<String>,
+}
+
+async fn schedule_scene(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, scene_id)): Path<(String, String)>,
+    Form(form): Form<ScheduleForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    ShotListModel::schedule_scene(&production.id, &scene_id, form.shoot_day.as_deref()).await?;
+    Ok(back_to_list(&slug))
+}
+
+async fn delete_scene(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, scene_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    ShotListModel::delete_scene(&production.id, &scene_id).await?;
+    Ok(back_to_list(&slug))
+}
+
+#[derive(Debug, Deserialize)]
+struct ShotForm {
+    /// Existing setup number, or "new"
+    setup: Option<String>,
+    #[serde(default)]
+    description: String,
+    size: String,
+    angle: String,
+    movement: String,
+    #[serde(default)]
+    gear: String,
+}
+
+async fn create_shot(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, scene_id)): Path<(String, String)>,
+    Form(form): Form<ShotForm>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    let data = CreateShotData {
+        setup: form.setup.as_deref().and_then(|s| s.parse().ok()),
+        description: form.description,
+        size: form.size,
+        angle: form.angle,
+        movement: form.movement,
+        gear: shot_list::parse_gear(&form.gear),
+    };
+    match ShotListModel::create_shot(&production.id, &scene_id, data).await {
+        Ok(_) => Ok(back_to_list(&slug)),
+        Err(Error::Validation(msg)) => {
+            let access = require_member(&slug, &user.id).await?;
+            render_page(&user, access, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn delete_shot(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, shot_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let production = require_editor(&slug, &user.id).await?;
+    ShotListModel::delete_shot(&production.id, &shot_id).await?;
+    Ok(back_to_list(&slug))
+}
+
+/// On-set "mark as captured" toggle, open to every member
+async fn toggle_captured(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, shot_id)): Path<(String, String)>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let captured = ShotListModel::toggle_captured(&access.production.id, &shot_id, &access.person).await?;
+    info!(
+        "{} marked shot {} on {} as {}",
+        user.username,
+        shot_id,
+        slug,
+        if captured { "captured" } else { "not captured" }
+    );
+    Ok(back_to_list(&slug))
+}
@@ -0,0 +1,393 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Path, Query, Request},
+    http::HeaderMap,
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::{
+    error::Error,
+    middleware::{AuthenticatedUser, UserExtractor},
+    models::organization::{Organization, OrganizationModel},
+    models::person::SessionUser,
+    record_id_ext::RecordIdExt,
+    response,
+    services::{
+        login_security::{self, ClientInfo},
+        sso::{self, AuthState, STATE_COOKIE, SsoConfig, SsoDomain},
+    },
+    templates::{BaseContext, SsoLoginTemplate, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/sso", get(sso_start))
+        .route("/sso/{slug}/login", get(sso_login))
+        .route("/sso/{slug}/callback", get(sso_callback))
+        // Settings for organization owners
+        .route("/orgs/{slug}/sso", get(sso_settings_page).post(save_sso_settings))
+        .route("/orgs/{slug}/sso/domains/verify", post(verify_domain))
+        .route("/orgs/{slug}/sso/scim-token", post(rotate_scim_token))
+        .route("/orgs/{slug}/sso/scim-token/revoke", post(revoke_scim_token))
+}
+
+#[derive(Template)]
+#[template(path = "organizations/sso.html")]
+pub struct OrganizationSsoTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub organization: Organization,
+    pub config: SsoConfig,
+    pub allowed_domains: String,
+    /// Allowed domains with the TXT records that prove them
+    pub domains: Vec<SsoDomain>,
+    pub redirect_uri: String,
+    pub scim_base_url: String,
+    /// Freshly issued SCIM token, shown once
+    pub scim_token: Option<String>,
+    pub error: Option<String>,
+    pub saved: bool,
+}
+
+// ============================
+// Sign-in
+// ============================
+
+#[derive(Debug, Deserialize)]
+struct SsoStartQuery {
+    org: Option<String>,
+    redirect_to: Option<String>,
+}
+
+/// Ask for the organization, then hand over to its login route
+async fn sso_start(Query(query): Query<SsoStartQuery>) -> Result<Response, Error> {
+    match query.org.as_deref().map(str::trim).filter(|o| !o.is_empty()) {
+        Some(org) => {
+            let mut path = format!("/sso/{}/login", urlencoding::encode(&org.to_lowercase()));
+            if let Some(redirect) = &query.redirect_to {
+                path.push_str(&format!("?redirect_to={}", urlencoding::encode(redirect)));
+            }
+            Ok(response::redirect(&path))
+        }
+        None => render_sso_login(String::new(), None, query.redirect_to),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SsoLoginQuery {
+    redirect_to: Option<String>,
+}
+
+async fn sso_login(
+    Path(slug): Path<String>,
+    Query(query): Query<SsoLoginQuery>,
+) -> Result<Response, Error> {
+    let Some((_, config)) = enabled_config(&slug).await? else {
+        return render_sso_login(
+            slug,
+            Some("Single sign-on isn't set up for this organization.".to_string()),
+            query.redirect_to,
+        );
+    };
+
+    let auth = AuthState::generate(query.redirect_to);
+    let url = match sso::authorization_url(&config, &slug, &auth).await {
+        Ok(url) => url,
+        Err(e) => {
+            error!("Failed to start SSO for {}: {}", slug, e);
+            return render_sso_login(
+                slug,
+                Some("Your organization's identity provider couldn't be reached. Please try again later.".to_string()),
+                auth.redirect_to,
+            );
+        }
+    };
+
+    let cookie = Cookie::build((STATE_COOKIE, auth.to_cookie_value()))
+        .path("/sso")
+        .same_site(SameSite::Lax)
+        .http_only(true)
+        .secure(crate::config::cookie_secure())
+        .max_age(cookie::time::Duration::minutes(10))
+        .build();
+
+    Ok((CookieJar::new().add(cookie), Redirect::to(&url)).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct SsoCallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+async fn sso_callback(
+    Path(slug): Path<String>,
+    Query(query): Query<SsoCallbackQuery>,
+    headers: HeaderMap,
+    client: ClientInfo,
+) -> Result<Response, Error> {
+    let jar = CookieJar::from_headers(&headers);
+    let auth = jar
+        .get(STATE_COOKIE)
+        .and_then(|c| AuthState::from_cookie_value(c.value()));
+    let jar = jar.remove(Cookie::build(STATE_COOKIE).path("/sso"));
+
+    if let Some(e) = query.error {
+        warn!("Identity provider returned an error for {}: {} {:?}", slug, e, query.error_description);
+        return render_sso_login(slug, Some("Sign-in was cancelled or refused by your identity provider.".to_string()), None);
+    }
+
+    let (Some(auth), Some(code), Some(state)) = (auth, query.code, query.state) else {
+        return render_sso_login(slug, Some("Your sign-in session expired. Please try again.".to_string()), None);
+    };
+    if state != auth.state {
+        warn!("SSO state mismatch for {}", slug);
+        return render_sso_login(slug, Some("Your sign-in session expired. Please try again.".to_string()), None);
+    }
+
+    let Some((organization, config)) = enabled_config(&slug).await? else {
+        return Err(Error::NotFound);
+    };
+
+    let claims = match sso::complete_login(&config, &slug, &code, &auth).await {
+        Ok(claims) => claims,
+        Err(e) => {
+            error!("SSO sign-in failed for {}: {}", slug, e);
+            return render_sso_login(slug, Some("We couldn't verify your sign-in. Please try again.".to_string()), None);
+        }
+    };
+
+    // Only link accounts by an email address the provider has verified
+    let email = match claims.verified_email() {
+        Some(email) => email.to_string(),
+        None => {
+            return render_sso_login(
+                slug,
+                Some("Your identity provider didn't share a verified email address.".to_string()),
+                None,
+            );
+        }
+    };
+
+    let person = match sso::provision(&config, &organization.id, &email, claims.name.as_deref()).await {
+        Ok(person) => person,
+        Err(Error::Forbidden) => {
+            return render_sso_login(
+                slug,
+                Some(format!("{} can't sign in to {} with single sign-on.", email, organization.name)),
+                None,
+            );
+        }
+        Err(e) => return Err(e),
+    };
+
+    // The identity provider has authenticated this device
+    if let Err(e) = login_security::remember_device(&person.id, &client).await {
+        warn!("Failed to remember SSO sign-in device: {}", e);
+    }
+    login_security::record_attempt(&email, Some(&person.id), &client, true).await;
+    info!("User {} signed in via SSO for {} (subject {})", person.username, slug, claims.sub);
+    crate::services::activity::log_activity(None, "login", "/sso");
+
+    let token = crate::auth::create_jwt(&person.id.to_raw_string(), &person.username, &person.email)?;
+    Ok((jar, super::auth::session_response(token, auth.redirect_to)).into_response())
+}
+
+async fn enabled_config(slug: &str) -> Result<Option<(Organization, SsoConfig)>, Error> {
+    let organization = match OrganizationModel::new().get_by_slug(slug).await {
+        Ok(org) => org,
+        Err(Error::NotFound) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    Ok(sso::get_config(&organization.id)
+        .await?
+        .filter(|c| c.enabled)
+        .map(|c| (organization, c)))
+}
+
+fn render_sso_login(org: String, error: Option<String>, redirect_to: Option<String>) -> Result<Response, Error> {
+    let mut template = SsoLoginTemplate::new(BaseContext::new().with_page("login"));
+    template.org = org;
+    template.error = error;
+    template.redirect_to = redirect_to;
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render SSO login template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+// ============================
+// Settings
+// ============================
+
+/// Only organization owners manage SSO, since it controls who can join
+async fn require_owner(slug: &str, user: &SessionUser) -> Result<Organization, Error> {
+    let model = OrganizationModel::new();
+    let organization = model.get_by_slug(slug).await?;
+    let role = model
+        .get_member_role(&organization.id.to_raw_string(), &user.id)
+        .await?;
+    if role.as_deref() != Some("owner") {
+        return Err(Error::Forbidden);
+    }
+    Ok(organization)
+}
+
+async fn render_settings(
+    user: &SessionUser,
+    organization: Organization,
+    scim_token: Option<String>,
+    error: Option<String>,
+    saved: bool,
+) -> Result<Html<String>, Error> {
+    let base = BaseContext::new()
+        .with_page("edit-organization")
+        .with_user(User::from_session_user(user).await);
+    let config = sso::get_config(&organization.id).await?.unwrap_or_else(|| SsoConfig {
+        default_role: "member".to_string(),
+        ..Default::default()
+    });
+
+    let template = OrganizationSsoTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        allowed_domains: config.allowed_domains.join(", "),
+        domains: sso::domains(&organization.id).await?,
+        redirect_uri: sso::redirect_uri(&organization.slug),
+        scim_base_url: format!("{}/scim/v2/{}", crate::config::app_url(), organization.slug),
+        organization,
+        config,
+        scim_token,
+        error,
+        saved,
+    };
+
+    Ok(Html(template.render().map_err(|e| {
+        error!("Failed to render organization SSO template: {}", e);
+        Error::template(e.to_string())
+    })?))
+}
+
+#[derive(Debug, Deserialize)]
+struct SavedQuery {
+    saved: Option<String>,
+}
+
+async fn sso_settings_page(
+    Path(slug): Path<String>,
+    Query(query): Query<SavedQuery>,
+    request: Request,
+) -> Result<Html<String>, Error> {
+    let user = request.get_user().ok_or(Error::Unauthorized)?;
+    let organization = require_owner(&slug, &user).await?;
+    render_settings(&user, organization, None, None, query.saved.is_some()).await
+}
+
+#[derive(Debug, Deserialize)]
+struct SsoSettingsForm {
+    issuer: String,
+    client_id: String,
+    #[serde(default)]
+    client_secret: String,
+    #[serde(default)]
+    allowed_domains: String,
+    #[serde(default)]
+    default_role: String,
+    enabled: Option<String>,
+}
+
+async fn save_sso_settings(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<SsoSettingsForm>,
+) -> Result<Response, Error> {
+    let organization = require_owner(&slug, &user).await?;
+
+    let config = SsoConfig {
+        issuer: form.issuer,
+        client_id: form.client_id,
+        client_secret: form.client_secret,
+        allowed_domains: sso::parse_domains(&form.allowed_domains),
+        default_role: form.default_role,
+        enabled: form.enabled.is_some(),
+        ..Default::default()
+    };
+
+    match sso::save_config(&organization.id, &config).await {
+        Ok(()) => Ok(response::redirect(&format!("/orgs/{}/sso?saved=1", slug))),
+        Err(Error::Validation(message)) | Err(Error::ExternalService(message)) => {
+            Ok(render_settings(&user, organization, None, Some(message), false)
+                .await?
+                .into_response())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VerifyDomainForm {
+    domain: String,
+}
+
+/// Check the domain's TXT record now
+async fn verify_domain(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+    Form(form): Form<VerifyDomainForm>,
+) -> Result<Html<String>, Error> {
+    let organization = require_owner(&slug, &user).await?;
+    let domain = form.domain.trim().to_lowercase();
+    let error = match sso::verify_domain(&organization.id, &domain).await {
+        Ok(true) => {
+            info!("SSO domain {} verified for {} by {}", domain, slug, user.username);
+            None
+        }
+        Ok(false) => Some(format!(
+            "The TXT record for {} wasn't found yet. DNS changes can take a while to appear.",
+            domain
+        )),
+        Err(Error::Conflict(message)) | Err(Error::ExternalService(message)) => Some(message),
+        Err(e) => return Err(e),
+    };
+    render_settings(&user, organization, None, error, false).await
+}
+
+async fn rotate_scim_token(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Html<String>, Error> {
+    let organization = require_owner(&slug, &user).await?;
+    match sso::rotate_scim_token(&organization.id).await {
+        Ok(token) => {
+            info!("SCIM token issued for {} by {}", slug, user.username);
+            render_settings(&user, organization, Some(token), None, false).await
+        }
+        Err(Error::Validation(message)) => render_settings(&user, organization, None, Some(message), false).await,
+        Err(e) => Err(e),
+    }
+}
+
+async fn revoke_scim_token(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let organization = require_owner(&slug, &user).await?;
+    sso::revoke_scim_token(&organization.id).await?;
+    info!("SCIM token revoked for {} by {}", slug, user.username);
+    Ok(response::redirect(&format!("/orgs/{}/sso", slug)))
+}
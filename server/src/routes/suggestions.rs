@@ -0,0 +1,143 @@
+use askama::Template;
+use axum::{
+    Form, Router,
+    extract::{Query, Request},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::get,
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::error;
+
+use crate::{
+    error::Error,
+    middleware::{AuthenticatedUser, UserExtractor},
+    models::person::SessionUser,
+    services::change_requests::{
+        self, ACTIONS, Contribution, DATASETS, Entry, MAX_PENDING, MySuggestion, Proposal,
+    },
+    templates::{BaseContext, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new().route("/suggest", get(suggest_page).post(submit_suggestion))
+}
+
+/// A curated list with its entries, for the entry picker
+pub struct ListOptions {
+    pub key: String,
+    pub label: String,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Template)]
+#[template(path = "suggest/index.html")]
+pub struct SuggestTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub datasets: Vec<SelectOption>,
+    pub actions: Vec<SelectOption>,
+    pub lists: Vec<ListOptions>,
+    pub departments: Vec<Entry>,
+    /// What was entered, kept when the form comes back with an error
+    pub form: Proposal,
+    pub mine: Vec<MySuggestion>,
+    pub contributions: Vec<Contribution>,
+    pub max_pending: i64,
+    pub message: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuggestQuery {
+    pub message: Option<String>,
+}
+
+fn person_id(user: &SessionUser) -> Result<RecordId, Error> {
+    RecordId::parse_simple(&user.id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+async fn render_page(
+    user: Option<&SessionUser>,
+    form: Proposal,
+    message: Option<String>,
+    error: Option<String>,
+) -> Result<Response, Error> {
+    let mut lists = Vec::with_capacity(DATASETS.len());
+    for dataset in DATASETS {
+        lists.push(ListOptions {
+            key: dataset.key.to_string(),
+            label: dataset.label.to_string(),
+            entries: change_requests::entries(dataset).await?,
+        });
+    }
+    let mine = match user {
+        Some(user) => change_requests::mine(&person_id(user)?).await?,
+        None => Vec::new(),
+    };
+
+    let mut base = BaseContext::new().with_page("suggest");
+    if let Some(user) = user {
+        base = base.with_user(User::from_session_user(user).await);
+    }
+    let template = SuggestTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        datasets: DATASETS
+            .iter()
+            .map(|d| SelectOption::new(d.key, d.label.to_string(), d.key == form.dataset))
+            .collect(),
+        actions: ACTIONS
+            .iter()
+            .map(|(value, label)| {
+                SelectOption::new(value, label.to_string(), *value == form.action)
+            })
+            .collect(),
+        lists,
+        departments: change_requests::departments().await?,
+        form,
+        mine,
+        contributions: change_requests::contributions().await?,
+        max_pending: MAX_PENDING,
+        message,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render suggest page: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn suggest_page(
+    Query(query): Query<SuggestQuery>,
+    request: Request,
+) -> Result<Response, Error> {
+    let user = request.get_user();
+    render_page(user.as_deref(), Proposal::default(), query.message, None).await
+}
+
+async fn submit_suggestion(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Form(form): Form<Proposal>,
+) -> Result<Response, Error> {
+    let person = person_id(&user)?;
+    match change_requests::submit(&person, &form).await {
+        Ok(()) => Ok(Redirect::to(&format!(
+            "/suggest?message={}",
+            urlencoding::encode("Thanks! Your suggestion is waiting for review.")
+        ))
+        .into_response()),
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => {
+            render_page(Some(&user), form, None, Some(msg)).await
+        }
+        Err(e) => Err(e),
+    }
+}
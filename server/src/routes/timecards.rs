@@ -0,0 +1,565 @@
+use askama::Template;
+use axum::{
+    Form, Json, Router,
+    body::Body,
+    extract::Path,
+    http::{HeaderMap, StatusCode, header},
+    response::{Html, IntoResponse, Redirect, Response},
+    routing::{get, post},
+};
+use serde::Deserialize;
+use surrealdb::types::RecordId;
+use tracing::{error, info, warn};
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    models::{
+        person::SessionUser,
+        production::{Production, ProductionModel},
+        shot_list::ShotListModel,
+        timecard::{self, DealData, RATE_TYPES, TimecardListing, TimecardModel},
+    },
+    record_id_ext::RecordIdExt,
+    services::whatsapp,
+    templates::{BaseContext, SelectOption, User, filters},
+};
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/productions/{slug}/timecards", get(timecards_page))
+        .route("/productions/{slug}/timecards/review", get(review_page))
+        .route("/productions/{slug}/timecards/review/{timecard_id}", post(review_timecard))
+        .route("/productions/{slug}/timecards/deals/{person_id}", post(save_deal))
+        .route("/productions/{slug}/timecards/export.csv", get(export_payroll))
+        .route("/productions/{slug}/timecards/{day_id}", post(enter_times))
+        .route("/productions/{slug}/timecards/{day_id}/clock-in", post(clock_in))
+        .route("/productions/{slug}/timecards/{day_id}/clock-out", post(clock_out))
+        .route("/api/whatsapp/clock-in", post(whatsapp_clock_in))
+        .route("/api/whatsapp/clock-out", post(whatsapp_clock_out))
+}
+
+// ============================
+// Views
+// ============================
+
+pub struct MyDayView {
+    pub day_id: String,
+    pub day_number: i64,
+    pub date: String,
+    /// "none", "open", "submitted", "approved" or "rejected"
+    pub status: String,
+    pub time_in: String,
+    pub time_out: String,
+    pub meal_minutes: i64,
+    pub regular_hours: f64,
+    pub overtime_hours: f64,
+    pub double_time_hours: f64,
+    pub note: Option<String>,
+}
+
+pub struct DealView {
+    pub person_id: String,
+    pub name: String,
+    pub rate_types: Vec<SelectOption>,
+    pub rate: String,
+    pub straight_hours: f64,
+    pub double_time_after: f64,
+    pub overtime_multiplier: f64,
+    pub double_time_multiplier: f64,
+}
+
+#[derive(Template)]
+#[template(path = "productions/timecards.html")]
+pub struct TimecardsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub can_approve: bool,
+    pub days: Vec<MyDayView>,
+    pub max_meal_minutes: i64,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/timecards_review.html")]
+pub struct TimecardReviewTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub submitted: Vec<TimecardListing>,
+    pub approved: Vec<TimecardListing>,
+    pub deals: Vec<DealView>,
+    pub error: Option<String>,
+}
+
+// ============================
+// Access
+// ============================
+
+struct Access {
+    production: Production,
+    person: RecordId,
+    can_approve: bool,
+}
+
+/// Timecards are for the production's accepted members. Owners, admins and
+/// producers approve them and set deals.
+async fn require_member(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let production = ProductionModel::get_by_slug(slug).await?;
+    let person = RecordId::parse_simple(user_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    let member = ProductionModel::get_members(&production.id)
+        .await?
+        .into_iter()
+        .find(|m| m.id == user_id && m.invitation_status == "accepted");
+    let can_edit = ProductionModel::can_edit(&production.id, user_id).await?;
+    if member.is_none() && !can_edit {
+        return Err(Error::Forbidden);
+    }
+    let can_approve = can_edit
+        || member.is_some_and(|m| timecard::is_producer(&m.production_roles.unwrap_or_default()));
+    Ok(Access {
+        production,
+        person,
+        can_approve,
+    })
+}
+
+async fn require_approver(slug: &str, user_id: &str) -> Result<Access, Error> {
+    let access = require_member(slug, user_id).await?;
+    if !access.can_approve {
+        return Err(Error::Forbidden);
+    }
+    Ok(access)
+}
+
+fn back_to_timecards(slug: &str) -> Response {
+    Redirect::to(&format!("/productions/{}/timecards", slug)).into_response()
+}
+
+fn back_to_review(slug: &str) -> Response {
+    Redirect::to(&format!("/productions/{}/timecards/review", slug)).into_response()
+}
+
+async fn render_page(user: &SessionUser, access: Access, error: Option<String>) -> Result<Response, Error> {
+    let days = ShotListModel::days(&access.production.id).await?;
+    let timecards = TimecardModel::for_person(&access.production.id, &access.person).await?;
+
+    let days = days
+        .into_iter()
+        .map(|day| {
+            let card = timecards.iter().find(|t| t.shoot_day == day.id);
+            MyDayView {
+                day_id: day.id.key_string(),
+                day_number: day.day_number,
+                date: day.date,
+                status: card.map(|t| t.status.clone()).unwrap_or_else(|| "none".to_string()),
+                time_in: card.map(|t| t.time_in.clone()).unwrap_or_default(),
+                time_out: card.and_then(|t| t.time_out.clone()).unwrap_or_default(),
+                meal_minutes: card.map(|t| t.meal_minutes).unwrap_or_default(),
+                regular_hours: card.map(|t| t.regular_hours).unwrap_or_default(),
+                overtime_hours: card.map(|t| t.overtime_hours).unwrap_or_default(),
+                double_time_hours: card.map(|t| t.double_time_hours).unwrap_or_default(),
+                note: card.and_then(|t| t.note.clone()),
+            }
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+    let template = TimecardsTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: access.production.title,
+        production_slug: access.production.slug,
+        can_approve: access.can_approve,
+        days,
+        max_meal_minutes: timecard::MAX_MEAL_MINUTES,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render timecards template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+async fn render_review(user: &SessionUser, access: Access, error: Option<String>) -> Result<Response, Error> {
+    let production = &access.production.id;
+    let submitted = TimecardModel::with_status(production, "submitted").await?;
+    let approved = TimecardModel::with_status(production, "approved").await?;
+    let saved_deals = TimecardModel::deals(production).await?;
+    let defaults = timecard::OvertimeRules::default();
+
+    let deals = ProductionModel::get_members(production)
+        .await?
+        .into_iter()
+        .filter(|m| m.member_type == "person" && m.invitation_status == "accepted")
+        .filter_map(|m| {
+            let person = RecordId::parse_simple(&m.id).ok()?;
+            let deal = saved_deals.iter().find(|d| d.person == person);
+            let rate_type = deal.map(|d| d.rate_type.as_str()).unwrap_or("hourly");
+            let rules = deal.map(|d| d.rules()).unwrap_or(defaults);
+            Some(DealView {
+                person_id: person.key_string(),
+                name: m.name,
+                rate_types: RATE_TYPES
+                    .iter()
+                    .map(|(value, label)| SelectOption::new(value, label.to_string(), *value == rate_type))
+                    .collect(),
+                rate: deal.and_then(|d| d.rate).map(|r| format!("{:.2}", r)).unwrap_or_default(),
+                straight_hours: rules.straight_hours,
+                double_time_after: rules.double_time_after,
+                overtime_multiplier: rules.overtime_multiplier,
+                double_time_multiplier: rules.double_time_multiplier,
+            })
+        })
+        .collect();
+
+    let base = BaseContext::new()
+        .with_page("productions")
+        .with_user(User::from_session_user(user).await);
+    let template = TimecardReviewTemplate {
+        app_name: base.app_name,
+        year: base.year,
+        version: base.version,
+        active_page: base.active_page,
+        user: base.user,
+        production_title: access.production.title,
+        production_slug: access.production.slug,
+        submitted,
+        approved,
+        deals,
+        error,
+    };
+
+    let html = template.render().map_err(|e| {
+        error!("Failed to render timecard review template: {}", e);
+        Error::template(e.to_string())
+    })?;
+    Ok(Html(html).into_response())
+}
+
+// ============================
+// Crew handlers
+// ============================
+
+async fn timecards_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    render_page(&user, access, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct ClockForm {
+    #[serde(default)]
+    time: String,
+    #[serde(default)]
+    meal_minutes: Option<i64>,
+}
+
+async fn clock_in(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+    Form(form): Form<ClockForm>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let day = ShotListModel::get_day(&access.production.id, &day_id).await?;
+    match TimecardModel::clock_in(&day, &access.person, &form.time).await {
+        Ok(card) => {
+            info!("{} clocked in at {} on day {} of {}", user.username, card.time_in, day.day_number, slug);
+            Ok(back_to_timecards(&slug))
+        }
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => render_page(&user, access, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+async fn clock_out(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+    Form(form): Form<ClockForm>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let day = ShotListModel::get_day(&access.production.id, &day_id).await?;
+    match TimecardModel::clock_out(&day, &access.person, &form.time, form.meal_minutes.unwrap_or(0)).await {
+        Ok(_) => Ok(back_to_timecards(&slug)),
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => render_page(&user, access, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TimesForm {
+    #[serde(default)]
+    time_in: String,
+    #[serde(default)]
+    time_out: String,
+    #[serde(default)]
+    meal_minutes: Option<i64>,
+}
+
+/// Enter or correct a day's times by hand
+async fn enter_times(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, day_id)): Path<(String, String)>,
+    Form(form): Form<TimesForm>,
+) -> Result<Response, Error> {
+    let access = require_member(&slug, &user.id).await?;
+    let day = ShotListModel::get_day(&access.production.id, &day_id).await?;
+    match TimecardModel::submit(
+        &day,
+        &access.person,
+        &form.time_in,
+        &form.time_out,
+        form.meal_minutes.unwrap_or(0),
+    )
+    .await
+    {
+        Ok(_) => Ok(back_to_timecards(&slug)),
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => render_page(&user, access, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+// ============================
+// Approver handlers
+// ============================
+
+async fn review_page(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let access = require_approver(&slug, &user.id).await?;
+    render_review(&user, access, None).await
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewForm {
+    decision: String,
+    note: Option<String>,
+}
+
+async fn review_timecard(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, timecard_id)): Path<(String, String)>,
+    Form(form): Form<ReviewForm>,
+) -> Result<Response, Error> {
+    let access = require_approver(&slug, &user.id).await?;
+    let approve = match form.decision.as_str() {
+        "approve" => true,
+        "reject" => false,
+        other => return Err(Error::BadRequest(format!("Invalid decision: {}", other))),
+    };
+
+    match TimecardModel::review(&access.production.id, &timecard_id, approve, &access.person, form.note).await {
+        Ok(card) => {
+            info!(
+                "{} {} timecard {} on {}",
+                user.username,
+                if approve { "approved" } else { "rejected" },
+                card.id.display(),
+                slug
+            );
+            Ok(back_to_review(&slug))
+        }
+        Err(Error::Conflict(msg)) => render_review(&user, access, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DealForm {
+    rate_type: String,
+    #[serde(default)]
+    rate: String,
+    straight_hours: f64,
+    double_time_after: f64,
+    overtime_multiplier: f64,
+    double_time_multiplier: f64,
+}
+
+async fn save_deal(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path((slug, person_id)): Path<(String, String)>,
+    Form(form): Form<DealForm>,
+) -> Result<Response, Error> {
+    let access = require_approver(&slug, &user.id).await?;
+    let person = RecordId::new("person", person_id.as_str());
+    if !ProductionModel::is_member(&access.production.id, &person.to_raw_string()).await? {
+        return Err(Error::NotFound);
+    }
+
+    let rate = match form.rate.trim() {
+        "" => None,
+        value => match value.parse::<f64>() {
+            Ok(rate) => Some(rate),
+            Err(_) => return render_review(&user, access, Some("Enter the rate as a number".to_string())).await,
+        },
+    };
+    let data = DealData {
+        rate_type: form.rate_type,
+        rate,
+        straight_hours: form.straight_hours,
+        double_time_after: form.double_time_after,
+        overtime_multiplier: form.overtime_multiplier,
+        double_time_multiplier: form.double_time_multiplier,
+    };
+    match TimecardModel::set_deal(&access.production.id, &person, data).await {
+        Ok(()) => {
+            info!("{} updated the deal for {} on {}", user.username, person.display(), slug);
+            Ok(back_to_review(&slug))
+        }
+        Err(Error::Validation(msg)) => render_review(&user, access, Some(msg)).await,
+        Err(e) => Err(e),
+    }
+}
+
+/// Approved hours as CSV for payroll
+async fn export_payroll(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(slug): Path<String>,
+) -> Result<Response, Error> {
+    let access = require_approver(&slug, &user.id).await?;
+    let csv = Body::from_stream(TimecardModel::payroll_export(access.production.id));
+    let filename = format!("{}-payroll.csv", slug);
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        csv,
+    )
+        .into_response())
+}
+
+// ============================
+// WhatsApp bot handlers
+// ============================
+
+#[derive(Debug, Deserialize)]
+pub struct BotClockRequest {
+    /// The sender's number, digits only
+    pub from: String,
+    /// "HH:MM", local to the set
+    pub time: String,
+    /// Production slug, needed when they shoot on more than one today
+    #[serde(default)]
+    pub production: Option<String>,
+    /// Unpaid meal break when clocking out
+    #[serde(default)]
+    pub meal_minutes: Option<i64>,
+}
+
+fn bot_reply(reply: String) -> Response {
+    Json(serde_json::json!({ "reply": reply })).into_response()
+}
+
+/// `/sh clockin` from the WhatsApp bot: clock the sender in on today's shoot
+/// day, the same as the clock-in button on the timecards page
+pub async fn whatsapp_clock_in(
+    headers: HeaderMap,
+    Json(request): Json<BotClockRequest>,
+) -> Result<Response, Error> {
+    if !whatsapp::bot_authorized(&headers) {
+        warn!("Rejected WhatsApp clock-in request with an invalid token");
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+    let Some(person) = whatsapp::person_for_number(&request.from).await? else {
+        return Ok(bot_reply(
+            "Link this number in your SlateHub account settings to clock in here.".to_string(),
+        ));
+    };
+
+    let today = chrono::Utc::now().date_naive().format("%Y-%m-%d").to_string();
+    let mut days = TimecardModel::days_on(&person, &today).await?;
+    if let Some(slug) = request.production.as_deref() {
+        let production = match ProductionModel::get_by_slug(slug).await {
+            Ok(production) => production,
+            Err(Error::NotFound) => {
+                return Ok(bot_reply(format!("There's no production called {}.", slug)));
+            }
+            Err(e) => return Err(e),
+        };
+        days.retain(|d| d.production == production.id);
+    }
+
+    let day = match days.as_slice() {
+        [] => return Ok(bot_reply("You aren't on a shoot day today.".to_string())),
+        [day] => day,
+        _ => {
+            let mut slugs = Vec::new();
+            for day in &days {
+                slugs.push(ProductionModel::get(&day.production).await?.slug);
+            }
+            return Ok(bot_reply(format!(
+                "You're shooting on more than one production today. Say which one, like /sh clockin {} {}",
+                request.time.trim(),
+                slugs.join(" or ")
+            )));
+        }
+    };
+
+    let reply = match TimecardModel::clock_in(day, &person, &request.time).await {
+        Ok(card) => {
+            info!("{} clocked in at {} on day {} from WhatsApp", person.display(), card.time_in, day.day_number);
+            format!("Clocked in at {} for day {}.", card.time_in, day.day_number)
+        }
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => msg,
+        Err(e) => return Err(e),
+    };
+    Ok(bot_reply(reply))
+}
+
+/// `/sh clockout` from the WhatsApp bot: clock the sender out of the shift
+/// they're clocked in on, submitting it for approval
+pub async fn whatsapp_clock_out(
+    headers: HeaderMap,
+    Json(request): Json<BotClockRequest>,
+) -> Result<Response, Error> {
+    if !whatsapp::bot_authorized(&headers) {
+        warn!("Rejected WhatsApp clock-out request with an invalid token");
+        return Ok(StatusCode::UNAUTHORIZED.into_response());
+    }
+    let Some(person) = whatsapp::person_for_number(&request.from).await? else {
+        return Ok(bot_reply(
+            "Link this number in your SlateHub account settings to clock out here.".to_string(),
+        ));
+    };
+    let Some(open) = TimecardModel::open_for(&person).await? else {
+        return Ok(bot_reply("You haven't clocked in.".to_string()));
+    };
+
+    let day = ShotListModel::get_day(&open.production, &open.shoot_day.key_string()).await?;
+    let meal_minutes = request.meal_minutes.unwrap_or(0);
+    let reply = match TimecardModel::clock_out(&day, &person, &request.time, meal_minutes).await {
+        Ok(card) => format!(
+            "Clocked out at {} for day {}: {:.2} hours, sent for approval.",
+            card.time_out.as_deref().unwrap_or(&request.time),
+            day.day_number,
+            card.regular_hours + card.overtime_hours + card.double_time_hours
+        ),
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => msg,
+        Err(e) => return Err(e),
+    };
+    Ok(bot_reply(reply))
+}
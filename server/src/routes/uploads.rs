@@ -0,0 +1,79 @@
+use axum::{
+    Router,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::head,
+};
+use bytes::Bytes;
+use surrealdb::types::RecordId;
+use tracing::info;
+
+use crate::{
+    error::Error,
+    middleware::AuthenticatedUser,
+    services::uploads::{self, UploadSession},
+};
+
+/// Chunk endpoints for resumable uploads. Sessions are started by the feature
+/// the file is for (e.g. `POST /self-tapes/submissions/{tape_id}/uploads`),
+/// which decides where the file goes and who may send it.
+pub fn router() -> Router {
+    Router::new().route(
+        "/uploads/{upload_id}",
+        head(upload_offset).patch(upload_chunk).delete(cancel_upload),
+    )
+}
+
+fn owner(user_id: &str) -> Result<RecordId, Error> {
+    RecordId::parse_simple(user_id).map_err(|e| Error::BadRequest(e.to_string()))
+}
+
+fn offset_response(status: StatusCode, session: &UploadSession) -> Response {
+    (
+        status,
+        [
+            ("Upload-Offset", session.offset().to_string()),
+            ("Upload-Length", session.size.to_string()),
+            ("Cache-Control", "no-store".to_string()),
+        ],
+    )
+        .into_response()
+}
+
+/// How much of the file has arrived, for a client resuming an upload
+async fn upload_offset(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(upload_id): Path<String>,
+) -> Result<Response, Error> {
+    let session = uploads::get(&upload_id, &owner(&user.id)?).await?;
+    Ok(offset_response(StatusCode::OK, &session))
+}
+
+/// Receive the chunk starting at the `Upload-Offset` header
+async fn upload_chunk(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(upload_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, Error> {
+    let offset = headers
+        .get("Upload-Offset")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<i64>().ok())
+        .ok_or_else(|| Error::bad_request("Missing or invalid Upload-Offset header"))?;
+
+    let session = uploads::get(&upload_id, &owner(&user.id)?).await?;
+    let session = uploads::receive_chunk(&session, offset, body).await?;
+    Ok(offset_response(StatusCode::NO_CONTENT, &session))
+}
+
+async fn cancel_upload(
+    AuthenticatedUser(user): AuthenticatedUser,
+    Path(upload_id): Path<String>,
+) -> Result<Response, Error> {
+    let session = uploads::get(&upload_id, &owner(&user.id)?).await?;
+    uploads::abort(&session).await?;
+    info!("{} cancelled upload {}", user.username, upload_id);
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
@@ -1,24 +1,29 @@
 use askama::Template;
 use axum::{
     Router,
-    extract::Request,
+    body::Bytes,
+    extract::{Request, multipart::Multipart},
+    http::{HeaderMap, StatusCode},
     response::{Html, IntoResponse, Redirect, Response},
     routing::{get, post},
 };
 use surrealdb::types::RecordId;
-use tracing::error;
+use tracing::{error, info, warn};
 
 use crate::{
-    db::DB,
     error::Error,
     middleware::{AuthenticatedUser, UserExtractor},
-    templates::{BaseContext, GetVerifiedTemplate, User},
+    models::person::{Person, SessionUser},
+    services::id_verification::{self, DOCUMENT_KINDS, MAX_DOCUMENT_SIZE},
+    templates::{BaseContext, GetVerifiedTemplate, SelectOption, User},
 };
 
 pub fn router() -> Router {
     Router::new()
         .route("/get-verified", get(get_verified_page))
         .route("/get-verified/request", post(request_verification))
+        .route("/get-verified/provider", post(start_provider_verification))
+        .route("/get-verified/callback", post(provider_callback))
 }
 
 fn parse_person_rid(person_id: &str) -> Option<RecordId> {
@@ -29,40 +34,43 @@ fn parse_person_rid(person_id: &str) -> Option<RecordId> {
     }
 }
 
-async fn has_pending_verification(person_id: &str) -> bool {
-    let Some(rid) = parse_person_rid(person_id) else {
-        return false;
-    };
-    if let Ok(mut result) = DB
-        .query("SELECT count() AS c FROM verification_request WHERE person = $pid AND status = 'pending' GROUP ALL")
-        .bind(("pid", rid))
-        .await
-    {
-        if let Ok(Some(row)) = result.take::<Option<serde_json::Value>>(0) {
-            if let Some(c) = row.get("c").and_then(|v| v.as_i64()) {
-                return c > 0;
-            }
-        }
-    }
-    false
-}
-
-async fn get_verified_page(request: Request) -> Result<Response, Error> {
+async fn render_page(request_user: Option<&SessionUser>, error: Option<String>) -> Result<Response, Error> {
     let mut base = BaseContext::new().with_page("get-verified");
-    let mut pending = false;
+    let mut template_status = None;
+    let mut is_verified = false;
 
-    if let Some(user) = request.get_user() {
-        base = base.with_user(User::from_session_user(&user).await);
-        pending = has_pending_verification(&user.id).await;
+    if let Some(user) = request_user {
+        base = base.with_user(User::from_session_user(user).await);
+        if let Some(rid) = parse_person_rid(&user.id) {
+            template_status = id_verification::status(&rid).await.unwrap_or_else(|e| {
+                error!("Failed to load verification status: {}", e);
+                None
+            });
+            is_verified = Person::find_by_id(&user.id)
+                .await
+                .ok()
+                .flatten()
+                .is_some_and(|p| p.verification_status == "identity");
+        }
     }
 
+    let status = template_status.as_ref().map(|s| s.status.as_str());
     let template = GetVerifiedTemplate {
         app_name: base.app_name,
         year: base.year,
         version: base.version,
         active_page: base.active_page,
         user: base.user,
-        has_pending_request: pending,
+        has_pending_request: status == Some("pending"),
+        is_verified,
+        rejected: status == Some("rejected"),
+        rejection_note: template_status.and_then(|s| s.note),
+        document_kinds: DOCUMENT_KINDS
+            .iter()
+            .map(|(value, label)| SelectOption::new(value, label.to_string(), false))
+            .collect(),
+        provider_name: id_verification::provider().map(|p| p.name().to_string()),
+        error,
     };
 
     let html = template.render().map_err(|e| {
@@ -73,26 +81,86 @@ async fn get_verified_page(request: Request) -> Result<Response, Error> {
     Ok(Html(html).into_response())
 }
 
+async fn get_verified_page(request: Request) -> Result<Response, Error> {
+    let user = request.get_user();
+    render_page(user.as_deref(), None).await
+}
+
+/// Upload an ID document for admin review
 async fn request_verification(
     AuthenticatedUser(user): AuthenticatedUser,
+    mut multipart: Multipart,
 ) -> Result<Response, Error> {
-    let person_id = &user.id;
+    let rid = parse_person_rid(&user.id)
+        .ok_or_else(|| Error::BadRequest("Invalid person ID".to_string()))?;
 
-    // Check if already has a request
-    if has_pending_verification(person_id).await {
-        return Ok(Redirect::to("/get-verified").into_response());
+    let mut kind = String::new();
+    let mut document: Option<(String, Bytes)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| Error::bad_request(format!("Failed to read multipart: {}", e)))?
+    {
+        match field.name().unwrap_or("") {
+            "document_kind" => {
+                kind = field.text().await.unwrap_or_default();
+            }
+            "document" => {
+                let content_type = field
+                    .content_type()
+                    .unwrap_or("application/octet-stream")
+                    .to_string();
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|e| Error::bad_request(format!("Failed to read file data: {}", e)))?;
+                if data.len() > MAX_DOCUMENT_SIZE {
+                    return render_page(Some(&*user), Some("File too large. Maximum size is 10MB".to_string())).await;
+                }
+                document = Some((content_type, data));
+            }
+            _ => {}
+        }
+    }
+
+    let Some((content_type, data)) = document else {
+        return render_page(Some(&*user), Some("Choose a file to upload".to_string())).await;
+    };
+
+    match id_verification::submit_document(&rid, &kind, &content_type, data).await {
+        Ok(()) => Ok(Redirect::to("/get-verified").into_response()),
+        Err(Error::Validation(msg)) | Err(Error::Conflict(msg)) => render_page(Some(&*user), Some(msg)).await,
+        Err(e) => Err(e),
     }
+}
 
-    let rid = parse_person_rid(person_id)
+/// Send the person to the configured third-party provider
+async fn start_provider_verification(
+    AuthenticatedUser(user): AuthenticatedUser,
+) -> Result<Response, Error> {
+    let rid = parse_person_rid(&user.id)
         .ok_or_else(|| Error::BadRequest("Invalid person ID".to_string()))?;
 
-    if let Err(e) = DB
-        .query("CREATE verification_request SET person = $pid, status = 'pending', created_at = time::now()")
-        .bind(("pid", rid))
-        .await
-    {
-        error!("Failed to create verification request: {}", e);
+    match id_verification::start_with_provider(&rid).await {
+        Ok(url) => Ok(Redirect::to(&url).into_response()),
+        Err(Error::Conflict(msg)) => render_page(Some(&*user), Some(msg)).await,
+        Err(e) => Err(e),
     }
+}
+
+/// Webhook from the third-party provider with a verification decision
+async fn provider_callback(headers: HeaderMap, body: Bytes) -> Result<Response, Error> {
+    let provider = id_verification::provider().ok_or(Error::NotFound)?;
+    let decision = provider.parse_callback(&headers, &body).map_err(|e| {
+        warn!("Rejected identity provider callback: {}", e);
+        e
+    })?;
 
-    Ok(Redirect::to("/get-verified").into_response())
+    id_verification::apply_provider_decision(&decision).await?;
+    info!(
+        "Identity provider {} request {}",
+        if decision.approved { "approved" } else { "rejected" },
+        decision.reference
+    );
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
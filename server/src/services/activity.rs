@@ -8,7 +8,7 @@ pub fn log_activity(person_id: Option<&str>, event_type: &str, path: &str) {
     let event_type = event_type.to_string();
     let path = path.to_string();
 
-    tokio::spawn(async move {
+    crate::db::spawn(async move {
         let res = if let Some(pid) = &person_id {
             let pid_ref = if pid.starts_with("person:") {
                 pid.clone()
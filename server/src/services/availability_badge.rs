@@ -0,0 +1,225 @@
+//! Embeddable availability badges
+//!
+//! A freelancer turns the badge on from account settings and gets a link with
+//! a random token. `/badge/{token}/availability.svg` draws their current
+//! availability for an `<img>` tag, and `/badge/{token}` serves the same
+//! thing as a small page for an iframe. Availability comes from the status on
+//! their profile, their confirmed bookings and the days they marked
+//! unavailable, so the badge moves to "available from" on its own once a
+//! booking is made. The token is the only
+//! thing that grants access: getting a new link or turning the badge off
+//! replaces it, and every old embed stops working.
+
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::info;
+
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::models::offer::OfferModel;
+use crate::models::person_availability::PersonAvailabilityModel;
+use crate::record_id_ext::RecordIdExt;
+use crate::services::tokens;
+
+const TOKEN_LENGTH: usize = 32;
+
+/// What the badge says
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BadgeStatus {
+    Available,
+    /// Booked until the day before
+    AvailableFrom(NaiveDate),
+    Busy,
+    NotAvailable,
+}
+
+impl BadgeStatus {
+    /// Status from the profile's availability and booked date ranges
+    /// (YYYY-MM-DD, inclusive). Bookings that run back to back from today
+    /// push the date forward; a gap of a day or more ends the run.
+    pub fn from_calendar(
+        availability: Option<&str>,
+        bookings: &[(String, String)],
+        today: NaiveDate,
+    ) -> Self {
+        if availability == Some("not_available") {
+            return BadgeStatus::NotAvailable;
+        }
+
+        let mut ranges: Vec<(NaiveDate, NaiveDate)> = bookings
+            .iter()
+            .filter_map(|(start, end)| {
+                Some((
+                    NaiveDate::parse_from_str(start, "%Y-%m-%d").ok()?,
+                    NaiveDate::parse_from_str(end, "%Y-%m-%d").ok()?,
+                ))
+            })
+            .collect();
+        ranges.sort();
+
+        let mut free = today;
+        for (start, end) in ranges {
+            if start <= free && end >= free {
+                free = end + Duration::days(1);
+            }
+        }
+
+        if free > today {
+            BadgeStatus::AvailableFrom(free)
+        } else if availability == Some("busy") {
+            BadgeStatus::Busy
+        } else {
+            BadgeStatus::Available
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            BadgeStatus::Available => "Available now".to_string(),
+            BadgeStatus::AvailableFrom(date) => {
+                format!("Available from {}", date.format("%b %-d, %Y"))
+            }
+            BadgeStatus::Busy => "Currently busy".to_string(),
+            BadgeStatus::NotAvailable => "Not available".to_string(),
+        }
+    }
+
+    /// Fill colour for the status half of the badge
+    pub fn color(&self) -> &'static str {
+        match self {
+            BadgeStatus::Available => "#2e7d32",
+            BadgeStatus::AvailableFrom(_) => "#b26a00",
+            BadgeStatus::Busy | BadgeStatus::NotAvailable => "#6b6b6b",
+        }
+    }
+
+    /// "available", "booked", "busy" or "not_available", for styling
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BadgeStatus::Available => "available",
+            BadgeStatus::AvailableFrom(_) => "booked",
+            BadgeStatus::Busy => "busy",
+            BadgeStatus::NotAvailable => "not_available",
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Rough width of text at 11px in a sans-serif face
+fn text_width(text: &str) -> usize {
+    text.chars().count() * 7 + 10
+}
+
+/// The badge as a standalone SVG: the person's name on the left and their
+/// status on the right
+pub fn render_svg(name: &str, status: &BadgeStatus) -> String {
+    let label = status.label();
+    let left = text_width(name);
+    let right = text_width(&label);
+    let width = left + right;
+    let (name, label) = (escape(name), escape(&label));
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{name}: {label}"><title>{name}: {label}</title><rect width="{left}" height="20" rx="3" fill="#1a1a1a"/><rect x="{left}" width="{right}" height="20" rx="3" fill="{color}"/><g fill="#fff" font-family="Verdana,DejaVu Sans,sans-serif" font-size="11" text-anchor="middle"><text x="{name_x}" y="14">{name}</text><text x="{label_x}" y="14">{label}</text></g></svg>"##,
+        color = status.color(),
+        name_x = left / 2,
+        label_x = left + right / 2,
+    )
+}
+
+/// A badge ready to draw
+#[derive(Debug, Clone)]
+pub struct Badge {
+    pub name: String,
+    pub username: String,
+    pub status: BadgeStatus,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct BadgeRow {
+    id: RecordId,
+    name: String,
+    username: String,
+    availability: Option<String>,
+}
+
+/// The badge a token points at, while it's turned on. Minors' badges only
+/// work once a guardian has approved their profile.
+pub async fn lookup(token: &str) -> Result<Badge> {
+    if !tokens::is_token(token, TOKEN_LENGTH) {
+        return Err(Error::NotFound);
+    }
+    let row: Option<BadgeRow> = DB
+        .query(
+            "SELECT id, name ?? username AS name, username, profile.availability AS availability
+            FROM person WHERE availability_badge_token = $token
+                AND (is_minor != true OR guardian_approved = true)
+            LIMIT 1",
+        )
+        .bind(("token", token.to_string()))
+        .await?
+        .take(0)?;
+    let row = row.ok_or(Error::NotFound)?;
+
+    let today = Utc::now().date_naive();
+    let from = today.format("%Y-%m-%d").to_string();
+    // Days marked unavailable count like bookings
+    let unavailable = PersonAvailabilityModel::upcoming(&row.id, &from).await?;
+    let bookings: Vec<(String, String)> = OfferModel::upcoming_bookings(&row.id, &from)
+        .await?
+        .into_iter()
+        .map(|b| (b.start_date, b.end_date))
+        .chain(unavailable.into_iter().map(|p| (p.start_date, p.end_date)))
+        .collect();
+
+    Ok(Badge {
+        status: BadgeStatus::from_calendar(row.availability.as_deref(), &bookings, today),
+        name: row.name,
+        username: row.username,
+    })
+}
+
+/// A person's badge token, if the badge is on
+pub async fn get_token(person: &RecordId) -> Result<Option<String>> {
+    let tokens: Vec<Option<String>> = DB
+        .query("SELECT VALUE availability_badge_token FROM $person")
+        .bind(("person", person.clone()))
+        .await?
+        .take(0)?;
+    Ok(tokens.into_iter().next().flatten())
+}
+
+/// Turn the badge on with a fresh token, replacing any existing one
+pub async fn reset_token(person: &RecordId) -> Result<String> {
+    let token = tokens::random_token(TOKEN_LENGTH);
+    DB.query("UPDATE $person SET availability_badge_token = $token")
+        .bind(("person", person.clone()))
+        .bind(("token", token.clone()))
+        .await?
+        .check()?;
+    info!("Availability badge link reset for {}", person.display());
+    Ok(token)
+}
+
+/// Turn the badge off; existing embeds stop loading
+pub async fn revoke(person: &RecordId) -> Result<()> {
+    DB.query("UPDATE $person SET availability_badge_token = NONE")
+        .bind(("person", person.clone()))
+        .await?
+        .check()?;
+    info!("Availability badge turned off for {}", person.display());
+    Ok(())
+}
+
+/// The badge's image and iframe URLs
+pub fn urls(token: &str) -> (String, String) {
+    let base = format!("{}/badge/{}", crate::config::app_url(), token);
+    (format!("{}/availability.svg", base), base)
+}
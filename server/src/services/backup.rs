@@ -0,0 +1,301 @@
+//! Backup and restore archives
+//!
+//! An archive is a zip holding:
+//!
+//! - `db_export.surql` — SurrealDB's own export of every table, taken over HTTP
+//!   (the WS client has no export).
+//! - `manifest.json` — every object in the S3 bucket at backup time, with size,
+//!   ETag and content type, plus a SHA-256 for each file copied into the archive.
+//! - `files/<key>` — the objects themselves, unless the backup was taken with
+//!   the manifest only (for buckets that are replicated or snapshotted separately).
+//!
+//! Used by the `/admin/backup` download and the `backup` CLI.
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Seek, Write};
+use tracing::{info, warn};
+
+use crate::config::DatabaseConfig;
+use crate::error::{Error, Result};
+use crate::services::s3::s3;
+
+pub const DB_EXPORT_NAME: &str = "db_export.surql";
+pub const MANIFEST_NAME: &str = "manifest.json";
+const FILES_PREFIX: &str = "files/";
+const MANIFEST_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format: u32,
+    pub created_at: String,
+    pub app_version: String,
+    pub namespace: String,
+    pub database: String,
+    pub bucket: String,
+    /// Whether the objects were copied into the archive
+    pub files_included: bool,
+    pub objects: Vec<ManifestObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestObject {
+    pub key: String,
+    pub size: u64,
+    pub etag: Option<String>,
+    pub content_type: Option<String>,
+    /// Hex SHA-256 of the copy in the archive; `None` when it wasn't copied
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct RestoreReport {
+    pub database_restored: bool,
+    pub files_uploaded: usize,
+    /// Objects left alone because the bucket already has them
+    pub files_present: usize,
+    /// Objects neither in the archive nor in the bucket
+    pub files_missing: Vec<String>,
+    /// Archive copies whose checksum didn't match the manifest
+    pub files_corrupt: Vec<String>,
+}
+
+/// SurrealDB's HTTP endpoint for the configured connection
+pub fn http_endpoint(db: &DatabaseConfig) -> String {
+    let url = db.connection_url();
+    if let Some(rest) = url.strip_prefix("ws://") {
+        format!("http://{}", rest.trim_end_matches("/rpc"))
+    } else if let Some(rest) = url.strip_prefix("wss://") {
+        format!("https://{}", rest.trim_end_matches("/rpc"))
+    } else if url.starts_with("http://") || url.starts_with("https://") {
+        url
+    } else {
+        format!("http://{}", url)
+    }
+}
+
+fn hex_sha256(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Export every table of the configured namespace and database
+pub async fn export_database(db: &DatabaseConfig) -> Result<Bytes> {
+    let export = reqwest::Client::new()
+        .get(format!("{}/export", http_endpoint(db)))
+        .header("Accept", "application/octet-stream")
+        .header("surreal-ns", &db.namespace)
+        .header("surreal-db", &db.name)
+        .basic_auth(&db.username, Some(&db.password))
+        .send()
+        .await
+        .map_err(|e| Error::Internal(format!("DB export request failed: {}", e)))?
+        .error_for_status()
+        .map_err(|e| Error::Internal(format!("DB export failed: {}", e)))?
+        .bytes()
+        .await
+        .map_err(|e| Error::Internal(format!("DB export read failed: {}", e)))?;
+
+    info!("DB export complete: {} bytes", export.len());
+    Ok(export)
+}
+
+/// Run an export file against the configured namespace and database
+pub async fn import_database(db: &DatabaseConfig, export: Vec<u8>) -> Result<()> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/import", http_endpoint(db)))
+        .header("Accept", "application/json")
+        .header("surreal-ns", &db.namespace)
+        .header("surreal-db", &db.name)
+        .basic_auth(&db.username, Some(&db.password))
+        .body(export)
+        .send()
+        .await
+        .map_err(|e| Error::Internal(format!("DB import request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::Internal(format!("DB import failed ({}): {}", status, body)));
+    }
+    info!("DB import complete");
+    Ok(())
+}
+
+/// Write a full archive. With `include_files` off only the manifest records the bucket.
+pub async fn write_archive<W: Write + Seek>(
+    writer: W,
+    db: &DatabaseConfig,
+    include_files: bool,
+) -> Result<Manifest> {
+    let zip_err = |e: zip::result::ZipError| Error::Internal(format!("Zip error: {}", e));
+    let io_err = |e: std::io::Error| Error::Internal(format!("Zip write error: {}", e));
+
+    let db_export = export_database(db).await?;
+
+    let s3_service = s3()?;
+    let objects = s3_service.list_objects().await?;
+    info!("Found {} files in S3 to back up", objects.len());
+
+    let mut zip = zip::ZipWriter::new(writer);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .large_file(true);
+
+    zip.start_file(DB_EXPORT_NAME, options).map_err(zip_err)?;
+    zip.write_all(&db_export).map_err(io_err)?;
+
+    let mut entries = Vec::with_capacity(objects.len());
+    for object in objects {
+        let mut entry = ManifestObject {
+            key: object.key,
+            size: object.size,
+            etag: object.etag,
+            content_type: None,
+            sha256: None,
+        };
+        if include_files {
+            match s3_service.download_file(&entry.key).await {
+                Ok((data, content_type)) => {
+                    zip.start_file(format!("{}{}", FILES_PREFIX, entry.key), options)
+                        .map_err(zip_err)?;
+                    zip.write_all(&data).map_err(io_err)?;
+                    entry.size = data.len() as u64;
+                    entry.sha256 = Some(hex_sha256(&data));
+                    entry.content_type = Some(content_type);
+                }
+                Err(e) => warn!("Skipping file {} during backup: {}", entry.key, e),
+            }
+        }
+        entries.push(entry);
+    }
+
+    let manifest = Manifest {
+        format: MANIFEST_FORMAT,
+        created_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        namespace: db.namespace.clone(),
+        database: db.name.clone(),
+        bucket: s3_service.bucket_name().to_string(),
+        files_included: include_files,
+        objects: entries,
+    };
+
+    zip.start_file(MANIFEST_NAME, options).map_err(zip_err)?;
+    let json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| Error::Internal(format!("Manifest error: {}", e)))?;
+    zip.write_all(&json).map_err(io_err)?;
+    zip.finish().map_err(zip_err)?;
+
+    Ok(manifest)
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Result<Option<Vec<u8>>> {
+    let mut file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(Error::Internal(format!("Zip error: {}", e))),
+    };
+    let mut data = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut data)
+        .map_err(|e| Error::Internal(format!("Failed to read {} from archive: {}", name, e)))?;
+    Ok(Some(data))
+}
+
+/// Read the manifest of an archive. Archives from before manifests existed
+/// get one listing their `files/` entries.
+pub fn read_manifest<R: Read + Seek>(archive: &mut zip::ZipArchive<R>) -> Result<Manifest> {
+    if let Some(json) = read_entry(archive, MANIFEST_NAME)? {
+        let manifest: Manifest = serde_json::from_slice(&json)
+            .map_err(|e| Error::Validation(format!("Invalid manifest: {}", e)))?;
+        if manifest.format > MANIFEST_FORMAT {
+            return Err(Error::Validation(format!(
+                "Archive format {} is newer than this build supports",
+                manifest.format
+            )));
+        }
+        return Ok(manifest);
+    }
+
+    let mut objects = Vec::new();
+    for i in 0..archive.len() {
+        let file = archive
+            .by_index(i)
+            .map_err(|e| Error::Internal(format!("Zip error: {}", e)))?;
+        if let Some(key) = file.name().strip_prefix(FILES_PREFIX) {
+            objects.push(ManifestObject {
+                key: key.to_string(),
+                size: file.size(),
+                etag: None,
+                content_type: None,
+                sha256: None,
+            });
+        }
+    }
+    Ok(Manifest {
+        format: 0,
+        created_at: String::new(),
+        app_version: String::new(),
+        namespace: String::new(),
+        database: String::new(),
+        bucket: String::new(),
+        files_included: true,
+        objects,
+    })
+}
+
+/// Restore an archive into the configured database and bucket. The database
+/// should be empty; objects already in the bucket are not overwritten.
+pub async fn restore_archive<R: Read + Seek>(
+    reader: R,
+    db: &DatabaseConfig,
+    restore_database: bool,
+    restore_files: bool,
+) -> Result<RestoreReport> {
+    let mut archive = zip::ZipArchive::new(reader)
+        .map_err(|e| Error::Validation(format!("Not a backup archive: {}", e)))?;
+    let manifest = read_manifest(&mut archive)?;
+    let mut report = RestoreReport::default();
+
+    if restore_database {
+        let export = read_entry(&mut archive, DB_EXPORT_NAME)?
+            .ok_or_else(|| Error::Validation(format!("Archive has no {}", DB_EXPORT_NAME)))?;
+        import_database(db, export).await?;
+        report.database_restored = true;
+    }
+
+    if !restore_files {
+        return Ok(report);
+    }
+
+    let s3_service = s3()?;
+    let existing: std::collections::HashSet<String> =
+        s3_service.list_all_objects().await?.into_iter().collect();
+
+    for object in &manifest.objects {
+        if existing.contains(&object.key) {
+            report.files_present += 1;
+            continue;
+        }
+        let Some(data) = read_entry(&mut archive, &format!("{}{}", FILES_PREFIX, object.key))? else {
+            report.files_missing.push(object.key.clone());
+            continue;
+        };
+        if let Some(expected) = &object.sha256 {
+            if hex_sha256(&data) != *expected {
+                report.files_corrupt.push(object.key.clone());
+                continue;
+            }
+        }
+        let content_type = object
+            .content_type
+            .as_deref()
+            .unwrap_or("application/octet-stream");
+        s3_service
+            .upload_file(&object.key, Bytes::from(data), content_type)
+            .await?;
+        report.files_uploaded += 1;
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,671 @@
+//! Suggested edits to curated lists
+//!
+//! The skill list (the `role` table) and the vendor categories organizations
+//! are filed under (`organization_type`) are kept up by the community. Any
+//! member can suggest adding, renaming or removing an entry, with a reason;
+//! each suggestion waits for an admin, and approving it makes the change.
+//! Entries added or renamed this way remember who suggested them, and applied
+//! suggestions are listed on the suggest page with their contributors.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{error, info};
+
+use crate::{
+    db::DB, error::Error, models::notification::NotificationModel, record_id_ext::RecordIdExt,
+};
+
+/// A curated list that takes suggestions
+#[derive(Debug, PartialEq, Eq)]
+pub struct Dataset {
+    /// Name used in forms and stored on the change request
+    pub key: &'static str,
+    pub table: &'static str,
+    pub label: &'static str,
+    /// One entry, as in "Rename skill"
+    pub singular: &'static str,
+}
+
+pub const DATASETS: &[Dataset] = &[
+    Dataset {
+        key: "skill",
+        table: "role",
+        label: "Skills",
+        singular: "skill",
+    },
+    Dataset {
+        key: "vendor_category",
+        table: "organization_type",
+        label: "Vendor categories",
+        singular: "vendor category",
+    },
+];
+
+/// Kinds of change, as (value, label)
+pub const ACTIONS: &[(&str, &str)] = &[("add", "Add"), ("rename", "Rename"), ("remove", "Remove")];
+
+pub const MAX_NAME_LENGTH: usize = 60;
+pub const MAX_REASON_LENGTH: usize = 1000;
+
+/// Suggestions one person can have waiting at once
+pub const MAX_PENDING: i64 = 10;
+
+/// Applied suggestions listed on the suggest page
+const CONTRIBUTIONS_SHOWN: usize = 30;
+
+/// The dataset for a form or stored value
+pub fn dataset(key: &str) -> Option<&'static Dataset> {
+    DATASETS.iter().find(|d| d.key == key)
+}
+
+/// A name with its whitespace tidied, or None when there's nothing left
+pub fn clean_name(name: &str) -> Option<String> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!name.is_empty()).then_some(name)
+}
+
+/// "Add skill “Drone Operator”", "Rename vendor category “Catering Company”
+/// to “Catering”", "Remove skill “Boom Op”"
+pub fn describe(
+    dataset: &Dataset,
+    action: &str,
+    target: Option<&str>,
+    name: Option<&str>,
+) -> String {
+    let target = target.unwrap_or("an entry");
+    let name = name.unwrap_or_default();
+    match action {
+        "add" => format!("Add {} “{}”", dataset.singular, name),
+        "rename" => format!("Rename {} “{}” to “{}”", dataset.singular, target, name),
+        _ => format!("Remove {} “{}”", dataset.singular, target),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Suggesting
+// ---------------------------------------------------------------------------
+
+/// The suggest form as submitted
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Proposal {
+    #[serde(default)]
+    pub dataset: String,
+    #[serde(default)]
+    pub action: String,
+    /// Key of the entry to rename or remove
+    #[serde(default)]
+    pub target: String,
+    /// New name, for add and rename
+    #[serde(default)]
+    pub name: String,
+    /// Key of a new skill's department
+    #[serde(default)]
+    pub department: String,
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// A proposal with every field it needs, from `check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checked {
+    pub dataset: &'static Dataset,
+    pub action: &'static str,
+    pub target: Option<String>,
+    pub name: Option<String>,
+    pub department: Option<String>,
+    pub reason: String,
+}
+
+/// Check a proposal has what its kind of change needs. Whether the entries
+/// it names exist is checked against the database by `submit`.
+pub fn check(proposal: &Proposal) -> Result<Checked, Error> {
+    let dataset = dataset(&proposal.dataset)
+        .ok_or_else(|| Error::Validation("Choose which list the change is to".to_string()))?;
+    let action = ACTIONS
+        .iter()
+        .map(|(value, _)| *value)
+        .find(|value| *value == proposal.action)
+        .ok_or_else(|| Error::Validation("Choose add, rename or remove".to_string()))?;
+
+    let target = match action {
+        "add" => None,
+        _ => Some(
+            Some(proposal.target.trim())
+                .filter(|t| !t.is_empty())
+                .ok_or_else(|| {
+                    Error::Validation(format!("Choose the {} to {}", dataset.singular, action))
+                })?
+                .to_string(),
+        ),
+    };
+
+    let name = match action {
+        "remove" => None,
+        _ => {
+            let name = clean_name(&proposal.name).ok_or_else(|| {
+                Error::Validation(format!("Enter the {}'s new name", dataset.singular))
+            })?;
+            if name.chars().count() > MAX_NAME_LENGTH {
+                return Err(Error::Validation(format!(
+                    "Names can be up to {} characters",
+                    MAX_NAME_LENGTH
+                )));
+            }
+            Some(name)
+        }
+    };
+
+    let department = Some(proposal.department.trim())
+        .filter(|d| !d.is_empty() && dataset.key == "skill" && action == "add")
+        .map(str::to_string);
+
+    let reason = proposal.reason.trim().to_string();
+    if reason.is_empty() {
+        return Err(Error::Validation(
+            "Say why the change is needed, so it can be checked".to_string(),
+        ));
+    }
+    if reason.chars().count() > MAX_REASON_LENGTH {
+        return Err(Error::Validation(format!(
+            "Keep the reason under {} characters",
+            MAX_REASON_LENGTH
+        )));
+    }
+
+    Ok(Checked {
+        dataset,
+        action,
+        target,
+        name,
+        department,
+        reason,
+    })
+}
+
+/// An entry of a curated list, for the suggest form
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub key: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct EntryRow {
+    id: RecordId,
+    name: String,
+}
+
+impl From<EntryRow> for Entry {
+    fn from(row: EntryRow) -> Self {
+        Self {
+            key: row.id.key_string(),
+            name: row.name,
+        }
+    }
+}
+
+/// Every entry of a list, by name
+pub async fn entries(dataset: &Dataset) -> Result<Vec<Entry>, Error> {
+    list(dataset.table).await
+}
+
+/// Departments a new skill can go under
+pub async fn departments() -> Result<Vec<Entry>, Error> {
+    list("department").await
+}
+
+async fn list(table: &str) -> Result<Vec<Entry>, Error> {
+    let rows: Vec<EntryRow> = DB
+        .query(format!("SELECT id, name FROM {} ORDER BY name", table))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list {}: {}", table, e)))?
+        .take(0)?;
+    Ok(rows.into_iter().map(Entry::from).collect())
+}
+
+async fn load_entry(id: &RecordId) -> Result<Option<EntryRow>, Error> {
+    Ok(DB
+        .query("SELECT id, name FROM ONLY $id")
+        .bind(("id", id.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load {}: {}", id.display(), e)))?
+        .take(0)?)
+}
+
+/// The entry of a list with this name, ignoring case
+async fn find_by_name(dataset: &Dataset, name: &str) -> Result<Option<EntryRow>, Error> {
+    Ok(DB
+        .query(format!(
+            "SELECT id, name FROM {} WHERE string::lowercase(name) = string::lowercase($name) LIMIT 1",
+            dataset.table
+        ))
+        .bind(("name", name.to_string()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to look up {}: {}", dataset.table, e)))?
+        .take(0)?)
+}
+
+/// Make sure a new or renamed entry won't clash with another one
+async fn ensure_free(
+    dataset: &Dataset,
+    name: &str,
+    renaming: Option<&RecordId>,
+) -> Result<(), Error> {
+    let Some(existing) = find_by_name(dataset, name).await? else {
+        return Ok(());
+    };
+    if Some(&existing.id) != renaming {
+        return Err(Error::Conflict(format!(
+            "There's already a {} called “{}”",
+            dataset.singular, existing.name
+        )));
+    }
+    if existing.name == name {
+        return Err(Error::Validation(format!(
+            "That's already the {}'s name",
+            dataset.singular
+        )));
+    }
+    Ok(())
+}
+
+/// Queue a suggestion for review
+pub async fn submit(person: &RecordId, proposal: &Proposal) -> Result<(), Error> {
+    let checked = check(proposal)?;
+    let dataset = checked.dataset;
+
+    let pending: Option<i64> = DB
+        .query("RETURN count(SELECT id FROM change_request WHERE person = $person AND status = 'pending')")
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to count suggestions: {}", e)))?
+        .take(0)?;
+    if pending.unwrap_or(0) >= MAX_PENDING {
+        return Err(Error::Conflict(format!(
+            "You have {} suggestions waiting for review. You can suggest more once they've been looked at.",
+            MAX_PENDING
+        )));
+    }
+
+    let target = match &checked.target {
+        Some(key) => Some(
+            load_entry(&RecordId::new(dataset.table, key.as_str()))
+                .await?
+                .ok_or_else(|| {
+                    Error::Validation(format!("That {} no longer exists", dataset.singular))
+                })?,
+        ),
+        None => None,
+    };
+    if let Some(name) = &checked.name {
+        ensure_free(dataset, name, target.as_ref().map(|t| &t.id)).await?;
+    }
+    let department = match &checked.department {
+        Some(key) => Some(
+            load_entry(&RecordId::new("department", key.as_str()))
+                .await?
+                .ok_or_else(|| Error::Validation("Choose a department from the list".to_string()))?
+                .id,
+        ),
+        None => None,
+    };
+
+    DB.query(
+        "CREATE change_request SET person = $person, dataset = $dataset, action = $action,
+            target = $target, target_name = $target_name, name = $name, department = $department,
+            reason = $reason, status = 'pending', created_at = time::now()",
+    )
+    .bind(("person", person.clone()))
+    .bind(("dataset", dataset.key.to_string()))
+    .bind(("action", checked.action.to_string()))
+    .bind(("target_name", target.as_ref().map(|t| t.name.clone())))
+    .bind(("target", target.map(|t| t.id)))
+    .bind(("name", checked.name.clone()))
+    .bind(("department", department))
+    .bind(("reason", checked.reason.clone()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to create suggestion: {}", e)))?
+    .check()
+    .map_err(|e| Error::Database(format!("Failed to create suggestion: {}", e)))?;
+
+    info!(
+        "{} suggested: {}",
+        person.display(),
+        describe(dataset, checked.action, None, checked.name.as_deref())
+    );
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Listing
+// ---------------------------------------------------------------------------
+
+/// A change request, with the names of the records it points at for lists
+#[derive(Debug, Deserialize, SurrealValue)]
+struct RequestRow {
+    id: RecordId,
+    person: RecordId,
+    dataset: String,
+    action: String,
+    target: Option<RecordId>,
+    target_name: Option<String>,
+    name: Option<String>,
+    department: Option<RecordId>,
+    reason: String,
+    status: String,
+    note: Option<String>,
+    reviewed_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    username: Option<String>,
+    person_name: Option<String>,
+    department_name: Option<String>,
+}
+
+/// Selected alongside `*` wherever a `RequestRow` is loaded
+const NAMES: &str =
+    "person.username AS username, person.name AS person_name, department.name AS department_name";
+
+impl RequestRow {
+    fn describe(&self) -> String {
+        match dataset(&self.dataset) {
+            Some(dataset) => describe(
+                dataset,
+                &self.action,
+                self.target_name.as_deref(),
+                self.name.as_deref(),
+            ),
+            None => self.action.clone(),
+        }
+    }
+}
+
+/// One of the viewer's own suggestions
+#[derive(Debug, Clone)]
+pub struct MySuggestion {
+    pub summary: String,
+    /// "pending", "applied" or "rejected"
+    pub status: String,
+    pub note: Option<String>,
+    pub submitted: String,
+}
+
+/// The person's suggestions, newest first
+pub async fn mine(person: &RecordId) -> Result<Vec<MySuggestion>, Error> {
+    let rows: Vec<RequestRow> = DB
+        .query(format!(
+            "SELECT *, {} FROM change_request WHERE person = $person ORDER BY created_at DESC LIMIT 50",
+            NAMES
+        ))
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list suggestions: {}", e)))?
+        .take(0)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| MySuggestion {
+            summary: row.describe(),
+            status: row.status,
+            note: row.note,
+            submitted: row.created_at.format("%b %d, %Y").to_string(),
+        })
+        .collect())
+}
+
+/// A suggestion waiting in the admin review queue
+#[derive(Debug, Clone)]
+pub struct PendingChange {
+    pub id: String,
+    pub list: String,
+    pub summary: String,
+    /// For new skills
+    pub department: Option<String>,
+    pub reason: String,
+    pub username: String,
+    pub name: String,
+    pub submitted: String,
+}
+
+/// Suggestions waiting for an admin, oldest first
+pub async fn pending_reviews() -> Result<Vec<PendingChange>, Error> {
+    let rows: Vec<RequestRow> = DB
+        .query(format!(
+            "SELECT *, {} FROM change_request WHERE status = 'pending' ORDER BY created_at ASC",
+            NAMES
+        ))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list suggestions: {}", e)))?
+        .take(0)?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let username = row.username.clone()?;
+            Some(PendingChange {
+                id: row.id.key_string(),
+                list: dataset(&row.dataset)
+                    .map(|d| d.label.to_string())
+                    .unwrap_or_default(),
+                summary: row.describe(),
+                department: row.department_name,
+                reason: row.reason,
+                name: row.person_name.unwrap_or_else(|| username.clone()),
+                username,
+                submitted: row.created_at.format("%b %d, %Y %H:%M").to_string(),
+            })
+        })
+        .collect())
+}
+
+/// An applied suggestion, credited to the person who made it
+#[derive(Debug, Clone)]
+pub struct Contribution {
+    pub summary: String,
+    pub username: String,
+    pub name: String,
+    pub applied: String,
+}
+
+/// The latest applied suggestions, newest first
+pub async fn contributions() -> Result<Vec<Contribution>, Error> {
+    let rows: Vec<RequestRow> = DB
+        .query(format!(
+            "SELECT *, {} FROM change_request WHERE status = 'applied'
+             ORDER BY reviewed_at DESC LIMIT $limit",
+            NAMES
+        ))
+        .bind(("limit", CONTRIBUTIONS_SHOWN as i64))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list contributions: {}", e)))?
+        .take(0)?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let username = row.username.clone()?;
+            Some(Contribution {
+                summary: row.describe(),
+                name: row.person_name.unwrap_or_else(|| username.clone()),
+                username,
+                applied: row
+                    .reviewed_at
+                    .unwrap_or(row.created_at)
+                    .format("%b %d, %Y")
+                    .to_string(),
+            })
+        })
+        .collect())
+}
+
+// ---------------------------------------------------------------------------
+// Reviewing
+// ---------------------------------------------------------------------------
+
+async fn load_pending(request_id: &str) -> Result<RequestRow, Error> {
+    let row: Option<RequestRow> = DB
+        .query("SELECT * FROM ONLY $id")
+        .bind(("id", RecordId::new("change_request", request_id)))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load suggestion: {}", e)))?
+        .take(0)?;
+    row.filter(|row| row.status == "pending")
+        .ok_or(Error::NotFound)
+}
+
+/// Admin decision on a pending suggestion. Approving makes the change; if it
+/// no longer can be made (the name was taken or the entry is gone since), the
+/// suggestion stays pending and the conflict is returned.
+pub async fn review(
+    request_id: &str,
+    reviewer: &RecordId,
+    approve: bool,
+    note: Option<String>,
+) -> Result<(), Error> {
+    let row = load_pending(request_id).await?;
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+
+    if approve {
+        apply(&row).await?;
+    }
+
+    let status = if approve { "applied" } else { "rejected" };
+    DB.query(
+        "UPDATE $id SET status = $status, note = $note, reviewed_by = $reviewer,
+            reviewed_at = time::now()",
+    )
+    .bind(("id", row.id.clone()))
+    .bind(("status", status.to_string()))
+    .bind(("note", note.clone()))
+    .bind(("reviewer", reviewer.clone()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to record suggestion decision: {}", e)))?;
+    info!(
+        "Suggestion {} by {} {}",
+        row.id.display(),
+        row.person.display(),
+        status
+    );
+
+    let summary = row.describe();
+    let (title, message) = if approve {
+        (
+            "Suggestion applied",
+            format!("Thanks! Your suggestion was applied: {}.", summary),
+        )
+    } else {
+        (
+            "Suggestion not applied",
+            match &note {
+                Some(note) => format!("Your suggestion wasn't applied ({}): {}", summary, note),
+                None => format!("Your suggestion wasn't applied: {}.", summary),
+            },
+        )
+    };
+    if let Err(e) = NotificationModel::new()
+        .create(
+            &row.person.to_raw_string(),
+            "change_request",
+            title,
+            &message,
+            Some("/suggest"),
+            Some(&row.id.to_raw_string()),
+        )
+        .await
+    {
+        error!(
+            "Failed to notify {} about their suggestion: {}",
+            row.person.display(),
+            e
+        );
+    }
+    Ok(())
+}
+
+/// Make the change a suggestion asks for
+async fn apply(row: &RequestRow) -> Result<(), Error> {
+    let dataset = dataset(&row.dataset)
+        .ok_or_else(|| Error::Internal(format!("Unknown dataset {}", row.dataset)))?;
+    let gone = || Error::Conflict(format!("That {} no longer exists", dataset.singular));
+
+    match row.action.as_str() {
+        "add" => {
+            let name = row.name.as_deref().unwrap_or_default();
+            ensure_free(dataset, name, None).await?;
+            let department = if dataset.key == "skill" {
+                ", department = $department"
+            } else {
+                ""
+            };
+            DB.query(format!(
+                "CREATE {} SET name = $name, suggested_by = $person{}",
+                dataset.table, department
+            ))
+            .bind(("name", name.to_string()))
+            .bind(("person", row.person.clone()))
+            .bind(("department", row.department.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to add {}: {}", dataset.singular, e)))?
+            .check()
+            .map_err(|e| Error::Database(format!("Failed to add {}: {}", dataset.singular, e)))?;
+        }
+        "rename" => {
+            let target = row.target.as_ref().ok_or_else(gone)?;
+            load_entry(target).await?.ok_or_else(gone)?;
+            let name = row.name.as_deref().unwrap_or_default();
+            ensure_free(dataset, name, Some(target)).await?;
+            DB.query("UPDATE $target SET name = $name, suggested_by = $person")
+                .bind(("target", target.clone()))
+                .bind(("name", name.to_string()))
+                .bind(("person", row.person.clone()))
+                .await
+                .map_err(|e| {
+                    Error::Database(format!("Failed to rename {}: {}", dataset.singular, e))
+                })?
+                .check()
+                .map_err(|e| {
+                    Error::Database(format!("Failed to rename {}: {}", dataset.singular, e))
+                })?;
+        }
+        _ => {
+            let target = row.target.as_ref().ok_or_else(gone)?;
+            load_entry(target).await?.ok_or_else(gone)?;
+            // Organizations are filed under a category by reference, so one
+            // that's in use has to be emptied first
+            if dataset.key == "vendor_category" {
+                let in_use: Option<i64> = DB
+                    .query("RETURN count(SELECT id FROM organization WHERE type = $target)")
+                    .bind(("target", target.clone()))
+                    .await
+                    .map_err(|e| Error::Database(format!("Failed to count organizations: {}", e)))?
+                    .take(0)?;
+                if let Some(count) = in_use.filter(|c| *c > 0) {
+                    return Err(Error::Conflict(format!(
+                        "{} organizations are still listed under this category",
+                        count
+                    )));
+                }
+            }
+            DB.query("DELETE $target")
+                .bind(("target", target.clone()))
+                .await
+                .map_err(|e| {
+                    Error::Database(format!("Failed to remove {}: {}", dataset.singular, e))
+                })?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete a person's suggestions and their name on entries they suggested,
+/// for account deletion
+pub async fn forget(person: &RecordId) -> Result<(), Error> {
+    DB.query(
+        "DELETE FROM change_request WHERE person = $person;
+         UPDATE role SET suggested_by = NONE WHERE suggested_by = $person;
+         UPDATE organization_type SET suggested_by = NONE WHERE suggested_by = $person;",
+    )
+    .bind(("person", person.clone()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to delete suggestions: {}", e)))?;
+    Ok(())
+}
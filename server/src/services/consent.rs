@@ -0,0 +1,200 @@
+//! Terms of Service and Privacy Policy acceptance
+//!
+//! Each acceptance is stored in `consent` with the document, the version that
+//! was accepted, when, and from where. Versions come from `TERMS_VERSION` and
+//! `PRIVACY_VERSION`; when one is bumped, `consent_middleware` sends signed-in
+//! people to `/legal/accept` before they can continue. Acceptance history is
+//! part of the account data export.
+
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{LazyLock, RwLock};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{info, warn};
+
+use crate::config::{LegalVersions, legal_versions};
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::models::pagination::{self, Cursor, KeyKind, Page, SortKey};
+use crate::record_id_ext::RecordIdExt;
+use crate::services::login_security::ClientInfo;
+
+/// Documents a person has to accept, in the order they are shown
+pub const DOCUMENTS: &[&str] = &["terms", "privacy"];
+
+/// People known to have accepted the current versions. Versions only change
+/// with a restart, so entries never go stale.
+static CURRENT: LazyLock<RwLock<HashSet<String>>> = LazyLock::new(|| RwLock::new(HashSet::new()));
+
+pub fn document_title(document: &str) -> &'static str {
+    match document {
+        "terms" => "Terms of Service",
+        "privacy" => "Privacy Policy",
+        _ => "Policy",
+    }
+}
+
+/// One accepted document version
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ConsentRecord {
+    #[serde(skip_serializing)]
+    pub id: RecordId,
+    pub document: String,
+    pub version: String,
+    pub accepted_at: DateTime<Utc>,
+    pub source: String,
+    pub ip: Option<String>,
+}
+
+/// Documents whose current version isn't among the accepted `(document, version)` pairs
+pub fn outstanding_documents(accepted: &[(String, String)], versions: &LegalVersions) -> Vec<&'static str> {
+    DOCUMENTS
+        .iter()
+        .copied()
+        .filter(|doc| {
+            let current = versions.version_of(doc).unwrap_or_default();
+            !accepted.iter().any(|(d, v)| d == doc && v == current)
+        })
+        .collect()
+}
+
+/// Documents this person still has to accept. Lookup errors are logged and
+/// treated as nothing outstanding, so a database hiccup never locks people out.
+pub async fn outstanding(person: &RecordId) -> Vec<&'static str> {
+    let key = crate::db::tenant_key(&person.to_raw_string());
+    if CURRENT.read().unwrap().contains(&key) {
+        return Vec::new();
+    }
+
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct Row {
+        document: String,
+        version: String,
+    }
+
+    let result = DB
+        .query("SELECT document, version FROM consent WHERE person = $person")
+        .bind(("person", person.clone()))
+        .await
+        .and_then(|mut r| r.take::<Vec<Row>>(0));
+
+    let accepted: Vec<(String, String)> = match result {
+        Ok(rows) => rows.into_iter().map(|r| (r.document, r.version)).collect(),
+        Err(e) => {
+            warn!("Failed to load consent records: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let missing = outstanding_documents(&accepted, legal_versions());
+    if missing.is_empty() {
+        CURRENT.write().unwrap().insert(key);
+    }
+    missing
+}
+
+/// Record acceptance of the current version of `documents`. `source` says
+/// where it happened ("signup", "sso", "interstitial").
+pub async fn accept(
+    person: &RecordId,
+    documents: &[&str],
+    source: &str,
+    client: Option<&ClientInfo>,
+) -> Result<()> {
+    let versions = legal_versions();
+    for document in documents {
+        let Some(version) = versions.version_of(document) else {
+            continue;
+        };
+        DB.query(
+            "CREATE consent SET person = $person, document = $document, version = $version,
+                 source = $source, ip = $ip, user_agent = $user_agent",
+        )
+        .bind(("person", person.clone()))
+        .bind(("document", document.to_string()))
+        .bind(("version", version.to_string()))
+        .bind(("source", source.to_string()))
+        .bind(("ip", client.map(|c| c.ip.clone())))
+        .bind(("user_agent", client.and_then(|c| c.user_agent.clone())))
+        .await?
+        .check()?;
+    }
+
+    info!("Recorded acceptance of {:?} for {} ({})", documents, person.display(), source);
+    // Re-checked from the table on the next request
+    CURRENT
+        .write()
+        .unwrap()
+        .remove(&crate::db::tenant_key(&person.to_raw_string()));
+    Ok(())
+}
+
+/// Acceptances read per page of the export
+const HISTORY_PAGE_SIZE: usize = 500;
+
+const HISTORY_ORDER: &[SortKey] = &[SortKey::desc("accepted_at", KeyKind::Datetime)];
+
+/// One page of a person's acceptances, newest first
+pub async fn history_page(
+    person: &RecordId,
+    cursor: Option<&str>,
+    size: usize,
+) -> Result<Page<ConsentRecord>> {
+    let cursor = match cursor {
+        Some(value) => Some(
+            Cursor::decode(value, "consent", HISTORY_ORDER)
+                .ok_or_else(|| Error::BadRequest("Invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+    let after = match cursor {
+        Some(_) => format!("AND {}", pagination::after_clause(HISTORY_ORDER)),
+        None => String::new(),
+    };
+    let mut query = DB
+        .query(format!(
+            "SELECT id, document, version, accepted_at, source, ip FROM consent
+             WHERE person = $person {after} {} LIMIT $limit",
+            pagination::order_clause(HISTORY_ORDER)
+        ))
+        .bind(("person", person.clone()))
+        .bind(("limit", size as i64 + 1));
+    if let Some(cursor) = &cursor {
+        for bind in cursor.binds() {
+            query = query.bind(bind);
+        }
+        query = query.bind(("after_id", cursor.record_id()));
+    }
+    let rows: Vec<ConsentRecord> = query.await?.take(0)?;
+    Ok(Page::from_rows(rows, size, |record| {
+        Cursor::new(vec![Cursor::datetime(&record.accepted_at)], &record.id)
+    }))
+}
+
+/// Every acceptance on record for a person, newest first, read a page at a
+/// time for the account data export
+pub fn history(person: RecordId) -> impl Stream<Item = Result<Vec<ConsentRecord>>> + Send {
+    async_stream::try_stream! {
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = history_page(&person, cursor.as_deref(), HISTORY_PAGE_SIZE).await?;
+            yield page.items;
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+    }
+}
+
+/// Paths that stay reachable while acceptance is outstanding: the documents
+/// themselves, the acceptance page, signing out, assets and machine endpoints.
+pub fn is_exempt_path(path: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "/legal/", "/terms", "/privacy", "/logout", "/static/", "/api/", "/mcp", "/scim/", "/sso/",
+        "/healthcheck", "/favicon", "/robots", "/sitemap", "/account/export", "/account/delete",
+    ];
+    PREFIXES.iter().any(|p| path.starts_with(p))
+}
@@ -0,0 +1,228 @@
+//! Demo mode
+//!
+//! An admin who turns on demo mode sees every page with people's names,
+//! usernames, avatars, email addresses and phone numbers swapped for made-up
+//! ones, so demos can be recorded and screenshots shared without showing
+//! anyone's data. It's a cookie on the admin's browser, honoured only while
+//! they're signed in as an admin, and it changes nothing but the HTML that
+//! browser receives.
+//!
+//! The rewriting happens on the rendered page. People are found through the
+//! profile links on it (`href="/{username}"`) plus the viewer, so a name
+//! that never appears next to a link to its profile is left alone. Each
+//! person gets the same stand-in on every page.
+
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use surrealdb::types::{RecordId, SurrealValue};
+
+use crate::db::DB;
+use crate::error::Result;
+
+/// Cookie holding the toggle
+pub const COOKIE: &str = "demo_mode";
+
+/// Shown in place of every avatar
+pub const PLACEHOLDER_AVATAR: &str = "/static/images/default-avatar.svg";
+
+/// Candidate usernames looked up per page
+const MAX_LOOKUPS: usize = 200;
+
+/// Names shorter than this are too likely to be ordinary words
+const MIN_NAME_LENGTH: usize = 3;
+
+const FIRST_NAMES: &[&str] = &[
+    "Alex", "Blair", "Casey", "Dana", "Eden", "Finley", "Gray", "Harper", "Indy", "Jordan", "Kai",
+    "Lane", "Morgan", "Noel", "Oakley", "Parker", "Quinn", "Reese", "Sage", "Taylor", "Val",
+    "Wren", "Rowan", "Emery",
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Archer", "Bennett", "Carver", "Dalton", "Ellis", "Fletcher", "Garner", "Hayes", "Irving",
+    "Jensen", "Keller", "Lowry", "Mercer", "Nolan", "Orton", "Prescott", "Quarles", "Rhodes",
+    "Sutton", "Tanner", "Vance", "Whitaker", "Yates", "Ashby",
+];
+
+static PROFILE_LINK: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"href="/([A-Za-z0-9._-]+)""#).unwrap());
+static EMAIL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}").unwrap()
+});
+/// `tel:` links, and international numbers written out on the page
+static PHONE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"tel:[^"'\s>]+|\+\d[\d ().-]{5,}\d"#).unwrap());
+static MENTION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"@([A-Za-z0-9._-]+)").unwrap());
+static IMG: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<img\b[^>]*>").unwrap());
+static IMG_SRC: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\bsrc="([^"]*)""#).unwrap());
+
+fn pick(seed: &str, salt: &str, list: &[&'static str]) -> &'static str {
+    let digest = Sha256::digest(format!("{}:{}", salt, seed).as_bytes());
+    let n = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) as usize;
+    list[n % list.len()]
+}
+
+/// A made-up full name, always the same for the same seed
+pub fn fake_name(seed: &str) -> String {
+    format!(
+        "{} {}",
+        pick(seed, "first", FIRST_NAMES),
+        pick(seed, "last", LAST_NAMES)
+    )
+}
+
+/// "alex.archer" for "Alex Archer"
+pub fn fake_username(seed: &str) -> String {
+    fake_name(seed).to_lowercase().replace(' ', ".")
+}
+
+/// An address at example.com, which can never be delivered to
+pub fn fake_email(seed: &str) -> String {
+    format!("{}@example.com", fake_username(seed))
+}
+
+/// A number from the range reserved for fiction
+pub fn fake_phone(seed: &str) -> String {
+    let digest = Sha256::digest(format!("phone:{}", seed).as_bytes());
+    format!("+1 555 01{:02}", digest[0] % 100)
+}
+
+/// Usernames the page links to, for looking up who's on it
+pub fn profile_links(html: &str) -> Vec<String> {
+    let mut usernames: Vec<String> = Vec::new();
+    for caps in PROFILE_LINK.captures_iter(html) {
+        let username = caps[1].to_string();
+        if !usernames.contains(&username) {
+            usernames.push(username);
+        }
+        if usernames.len() >= MAX_LOOKUPS {
+            break;
+        }
+    }
+    usernames
+}
+
+/// Someone shown on a page
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct Person {
+    pub name: String,
+    pub username: String,
+    pub avatar: Option<String>,
+}
+
+/// Rewrites a page with stand-ins for the people on it
+pub struct Anonymizer {
+    people: Vec<Person>,
+    names: Option<Regex>,
+}
+
+impl Anonymizer {
+    pub fn new(mut people: Vec<Person>) -> Self {
+        // Longest first, so "Ana Maria Lopez" goes before "Ana Maria"
+        people.sort_by_key(|p| std::cmp::Reverse(p.name.len()));
+        let mut usernames: Vec<String> = Vec::new();
+        people.retain(|p| {
+            let new = !usernames.contains(&p.username);
+            usernames.push(p.username.clone());
+            new
+        });
+
+        let names: Vec<String> = people
+            .iter()
+            .filter(|p| p.name.trim().chars().count() >= MIN_NAME_LENGTH)
+            .map(|p| regex::escape(p.name.trim()))
+            .collect();
+        let names = (!names.is_empty())
+            .then(|| Regex::new(&format!(r"\b(?:{})\b", names.join("|"))).ok())
+            .flatten();
+        Self { people, names }
+    }
+
+    fn by_name(&self, name: &str) -> Option<&Person> {
+        self.people.iter().find(|p| p.name.trim() == name)
+    }
+
+    fn is_avatar(&self, tag: &str, src: &str) -> bool {
+        src.starts_with("/api/avatar")
+            || tag.contains("avatar")
+            || self.people.iter().any(|p| p.avatar.as_deref() == Some(src))
+    }
+
+    pub fn apply(&self, html: &str) -> String {
+        let mut html = EMAIL
+            .replace_all(html, |caps: &Captures| fake_email(&caps[0].to_lowercase()))
+            .into_owned();
+        html = PHONE
+            .replace_all(&html, |caps: &Captures| {
+                // Seeded by the digits, so a link and its text get the same number
+                let digits: String = caps[0].chars().filter(char::is_ascii_digit).collect();
+                if caps[0].starts_with("tel:") {
+                    format!("tel:{}", fake_phone(&digits).replace(' ', ""))
+                } else {
+                    fake_phone(&digits)
+                }
+            })
+            .into_owned();
+
+        if let Some(names) = &self.names {
+            html = names
+                .replace_all(&html, |caps: &Captures| match self.by_name(&caps[0]) {
+                    Some(person) => fake_name(&person.username),
+                    None => caps[0].to_string(),
+                })
+                .into_owned();
+        }
+        html = MENTION
+            .replace_all(&html, |caps: &Captures| {
+                match self.people.iter().find(|p| p.username == caps[1]) {
+                    Some(person) => format!("@{}", fake_username(&person.username)),
+                    None => caps[0].to_string(),
+                }
+            })
+            .into_owned();
+
+        IMG.replace_all(&html, |caps: &Captures| {
+            let tag = &caps[0];
+            match IMG_SRC.captures(tag) {
+                Some(src) if !src[1].starts_with("/static/") && self.is_avatar(tag, &src[1]) => {
+                    tag.replacen(&src[0], &format!("src=\"{}\"", PLACEHOLDER_AVATAR), 1)
+                }
+                _ => tag.to_string(),
+            }
+        })
+        .into_owned()
+    }
+}
+
+/// Whether this person is an admin, who alone can use demo mode
+pub async fn allowed(person_id: &str) -> bool {
+    let Ok(id) = RecordId::parse_simple(person_id) else {
+        return false;
+    };
+    let admin: Option<bool> = match DB
+        .query("SELECT VALUE is_admin FROM ONLY $id")
+        .bind(("id", id))
+        .await
+    {
+        Ok(mut response) => response.take(0).unwrap_or(None),
+        Err(_) => None,
+    };
+    admin.unwrap_or(false)
+}
+
+/// The people a page links to
+pub async fn people(usernames: Vec<String>) -> Result<Vec<Person>> {
+    if usernames.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(DB
+        .query(
+            "SELECT name ?? username AS name, username, profile.avatar AS avatar
+            FROM person WHERE username IN $usernames",
+        )
+        .bind(("usernames", usernames))
+        .await?
+        .take(0)?)
+}
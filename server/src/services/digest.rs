@@ -0,0 +1,495 @@
+//! Weekly activity digest email
+//!
+//! People opt in from account settings and choose a weekday and UTC hour. The
+//! `weekly_digest` scheduled task runs every 15 minutes and sends each digest
+//! that has come due since the last one went out: new casting calls matching
+//! the person's skills, profile views, their pending applications, applications
+//! waiting on their own postings, people suggested for those postings, and
+//! upcoming shoot days.
+
+use askama::Template;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, error, info, warn};
+
+use crate::config::app_url;
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::models::analytics::AnalyticsModel;
+use crate::models::match_suggestion::{self, MatchSuggestionModel};
+use crate::record_id_ext::RecordIdExt;
+use crate::services::email::EmailService;
+
+/// Weekday names indexed by `digest_day`
+pub const DAYS: &[&str] = &["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// Most matching casting calls listed in one digest
+const MAX_JOBS: usize = 10;
+
+/// Most suggested matches listed in one digest
+const MAX_SUGGESTIONS: usize = 10;
+
+/// How far ahead shoot days are listed
+const SHOOT_LOOKAHEAD_DAYS: i64 = 14;
+
+/// A person's digest settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigestPreference {
+    pub enabled: bool,
+    /// 0 = Monday … 6 = Sunday
+    pub day: u32,
+    /// Hour of the day, UTC
+    pub hour: u32,
+}
+
+impl Default for DigestPreference {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            day: 0,
+            hour: 8,
+        }
+    }
+}
+
+/// The most recent send slot for `day`/`hour` at or before `now`
+pub fn last_slot(day: u32, hour: u32, now: DateTime<Utc>) -> DateTime<Utc> {
+    let day = day.min(6);
+    let hour = hour.min(23);
+    let days_back = (now.weekday().num_days_from_monday() + 7 - day) % 7;
+    let date = now.date_naive() - Duration::days(days_back as i64);
+    let slot = Utc.from_utc_datetime(&date.and_hms_opt(hour, 0, 0).expect("valid hour"));
+    if slot > now { slot - Duration::days(7) } else { slot }
+}
+
+/// Whether a digest should go out now. A slot missed by more than a day (the
+/// server was down, or the person only just opted in) waits for next week
+/// rather than arriving at an odd time.
+pub fn is_due(pref: &DigestPreference, last_sent: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    if !pref.enabled {
+        return false;
+    }
+    let slot = last_slot(pref.day, pref.hour, now);
+    now - slot < Duration::hours(24) && last_sent.is_none_or(|sent| sent < slot)
+}
+
+/// Whether a casting call fits a person: one of their skills appears in the
+/// posting or role titles, and the posting is in their city (or either side
+/// has no location).
+pub fn job_matches(
+    title: &str,
+    role_titles: &[String],
+    job_location: Option<&str>,
+    skills: &[String],
+    person_location: Option<&str>,
+) -> bool {
+    let titles: Vec<String> = std::iter::once(title)
+        .chain(role_titles.iter().map(String::as_str))
+        .map(str::to_lowercase)
+        .collect();
+    let skill_match = skills
+        .iter()
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .any(|skill| titles.iter().any(|t| t.contains(&skill)));
+    if !skill_match {
+        return false;
+    }
+
+    match (job_location, person_location) {
+        (Some(job), Some(person)) => {
+            let city = person.split(',').next().unwrap_or_default().trim().to_lowercase();
+            city.is_empty() || job.to_lowercase().contains(&city)
+        }
+        _ => true,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestJob {
+    pub title: String,
+    pub location: Option<String>,
+    pub url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestApplication {
+    pub job_title: String,
+    pub role_title: String,
+    pub status: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestSuggestion {
+    pub name: String,
+    pub headline: Option<String>,
+    pub job_title: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct DigestShoot {
+    pub title: String,
+    pub role: Option<String>,
+    pub starts: String,
+    pub url: String,
+}
+
+/// Everything in one person's digest
+#[derive(Debug, Clone, Default)]
+pub struct Digest {
+    pub jobs: Vec<DigestJob>,
+    pub profile_views: u64,
+    pub profile_views_previous: u64,
+    /// Signed-in people behind this week's views
+    pub profile_viewers: u64,
+    pub applications: Vec<DigestApplication>,
+    pub to_review: u64,
+    /// People suggested for the person's open postings this week
+    pub suggestions: Vec<DigestSuggestion>,
+    pub shoots: Vec<DigestShoot>,
+}
+
+impl Digest {
+    /// Nothing worth an email this week
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+            && self.profile_views == 0
+            && self.applications.is_empty()
+            && self.to_review == 0
+            && self.suggestions.is_empty()
+            && self.shoots.is_empty()
+    }
+}
+
+#[derive(Template)]
+#[template(path = "email/weekly_digest.html")]
+struct DigestHtmlTemplate<'a> {
+    name: &'a str,
+    digest: &'a Digest,
+    app_url: &'a str,
+}
+
+#[derive(Template)]
+#[template(path = "email/weekly_digest.txt")]
+struct DigestTextTemplate<'a> {
+    name: &'a str,
+    digest: &'a Digest,
+    app_url: &'a str,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct PreferenceRow {
+    digest_enabled: Option<bool>,
+    digest_day: Option<i64>,
+    digest_hour: Option<i64>,
+}
+
+impl From<PreferenceRow> for DigestPreference {
+    fn from(row: PreferenceRow) -> Self {
+        let default = Self::default();
+        Self {
+            enabled: row.digest_enabled.unwrap_or(default.enabled),
+            day: row.digest_day.map(|d| d.clamp(0, 6) as u32).unwrap_or(default.day),
+            hour: row.digest_hour.map(|h| h.clamp(0, 23) as u32).unwrap_or(default.hour),
+        }
+    }
+}
+
+pub async fn get_preference(person: &RecordId) -> Result<DigestPreference> {
+    let row: Option<PreferenceRow> = DB
+        .query("SELECT digest_enabled, digest_day, digest_hour FROM ONLY $person")
+        .bind(("person", person.clone()))
+        .await?
+        .take(0)?;
+    Ok(row.map(DigestPreference::from).unwrap_or_default())
+}
+
+pub async fn set_preference(person: &RecordId, pref: &DigestPreference) -> Result<()> {
+    if pref.day > 6 || pref.hour > 23 {
+        return Err(Error::Validation("Choose a valid day and hour for your digest".to_string()));
+    }
+    DB.query("UPDATE $person SET digest_enabled = $enabled, digest_day = $day, digest_hour = $hour")
+        .bind(("person", person.clone()))
+        .bind(("enabled", pref.enabled))
+        .bind(("day", pref.day as i64))
+        .bind(("hour", pref.hour as i64))
+        .await?
+        .check()?;
+    info!(
+        "Weekly digest for {} {} ({} {:02}:00 UTC)",
+        person.display(),
+        if pref.enabled { "enabled" } else { "disabled" },
+        DAYS[pref.day as usize],
+        pref.hour
+    );
+    Ok(())
+}
+
+async fn matching_jobs(person: &RecordId) -> Result<Vec<DigestJob>> {
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct Profile {
+        skills: Option<Vec<String>>,
+        location: Option<String>,
+    }
+
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct JobRow {
+        id: RecordId,
+        title: String,
+        location: Option<String>,
+        role_titles: Option<Vec<String>>,
+    }
+
+    let mut result = DB
+        .query("SELECT VALUE profile FROM ONLY $person")
+        .query(
+            "SELECT id, title, location, roles.title AS role_titles FROM job_posting
+             WHERE status = 'open' AND expires_at > time::now() AND created_at > time::now() - 7d
+               AND posted_by != $person
+             ORDER BY created_at DESC LIMIT 200",
+        )
+        .bind(("person", person.clone()))
+        .await?;
+    let profile: Option<Profile> = result.take(0)?;
+    let rows: Vec<JobRow> = result.take(1)?;
+
+    let Some(profile) = profile else {
+        return Ok(Vec::new());
+    };
+    let skills = profile.skills.unwrap_or_default();
+
+    Ok(rows
+        .into_iter()
+        .filter(|job| {
+            job_matches(
+                &job.title,
+                job.role_titles.as_deref().unwrap_or_default(),
+                job.location.as_deref(),
+                &skills,
+                profile.location.as_deref(),
+            )
+        })
+        .take(MAX_JOBS)
+        .map(|job| DigestJob {
+            url: format!("/jobs/{}", job.id.key_string()),
+            title: job.title,
+            location: job.location,
+        })
+        .collect())
+}
+
+async fn pending_applications(person: &RecordId) -> Result<(Vec<DigestApplication>, u64)> {
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct ApplicationRow {
+        job: RecordId,
+        job_title: String,
+        role_title: String,
+        status: String,
+    }
+
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct CountRow {
+        count: u64,
+    }
+
+    let mut result = DB
+        .query(
+            "SELECT out AS job, out.title AS job_title, role_title, status FROM application
+             WHERE in = $person AND status IN ['submitted', 'reviewed', 'shortlisted']
+               AND out.status = 'open'
+             ORDER BY applied_at DESC",
+        )
+        .query(
+            "SELECT count() AS count FROM application
+             WHERE out.posted_by = $person AND status = 'submitted' GROUP ALL",
+        )
+        .bind(("person", person.clone()))
+        .await?;
+    let rows: Vec<ApplicationRow> = result.take(0)?;
+    let to_review: Option<CountRow> = result.take(1)?;
+
+    let applications = rows
+        .into_iter()
+        .map(|row| DigestApplication {
+            url: format!("/jobs/{}", row.job.key_string()),
+            job_title: row.job_title,
+            role_title: row.role_title,
+            status: row.status,
+        })
+        .collect();
+    Ok((applications, to_review.map(|c| c.count).unwrap_or(0)))
+}
+
+async fn suggested_matches(person: &RecordId) -> Result<Vec<DigestSuggestion>> {
+    let suggestions =
+        MatchSuggestionModel::recent_for_poster(person, match_suggestion::INTERVAL_DAYS).await?;
+    Ok(suggestions
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|s| DigestSuggestion {
+            name: s.display_name().to_string(),
+            url: format!("/{}", s.username),
+            headline: s.headline,
+            job_title: s.job_title,
+        })
+        .collect())
+}
+
+async fn upcoming_shoots(person: &RecordId) -> Result<Vec<DigestShoot>> {
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct ShootRow {
+        title: String,
+        slug: String,
+        role: Option<String>,
+        start_date: DateTime<Utc>,
+        end_date: Option<DateTime<Utc>>,
+    }
+
+    let rows: Vec<ShootRow> = DB
+        .query(format!(
+            "SELECT out.title AS title, out.slug AS slug, role,
+                    out.start_date AS start_date, out.end_date AS end_date
+             FROM involvement
+             WHERE in = $person AND out.start_date != NONE
+               AND out.start_date <= time::now() + {SHOOT_LOOKAHEAD_DAYS}d
+               AND (out.end_date ?? out.start_date) >= time::now()
+             ORDER BY start_date"
+        ))
+        .bind(("person", person.clone()))
+        .await?
+        .take(0)?;
+
+    let today: NaiveDate = Utc::now().date_naive();
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let starts = if row.start_date.date_naive() <= today {
+                match row.end_date {
+                    Some(end) => format!("Shooting through {}", end.format("%a %b %-d")),
+                    None => "Shooting now".to_string(),
+                }
+            } else {
+                format!("Starts {}", row.start_date.format("%a %b %-d"))
+            };
+            DigestShoot {
+                url: format!("/productions/{}", row.slug),
+                title: row.title,
+                role: row.role,
+                starts,
+            }
+        })
+        .collect())
+}
+
+/// Gather this week's digest for a person
+pub async fn build(person: &RecordId) -> Result<Digest> {
+    let jobs = matching_jobs(person).await?;
+    let views = AnalyticsModel::get_views_for_period(person, 7).await?;
+    let viewers = AnalyticsModel::get_viewer_count(person, 7).await?;
+    let (applications, to_review) = pending_applications(person).await?;
+    let suggestions = suggested_matches(person).await?;
+    let shoots = upcoming_shoots(person).await?;
+
+    Ok(Digest {
+        jobs,
+        profile_views: views.current,
+        profile_views_previous: views.previous,
+        profile_viewers: viewers,
+        applications,
+        to_review,
+        suggestions,
+        shoots,
+    })
+}
+
+async fn send(email: &str, name: &str, digest: &Digest) -> Result<()> {
+    let app_url = app_url();
+    let html = DigestHtmlTemplate {
+        name,
+        digest,
+        app_url: &app_url,
+    }
+    .render()
+    .map_err(|e| Error::template(e.to_string()))?;
+    let text = DigestTextTemplate {
+        name,
+        digest,
+        app_url: &app_url,
+    }
+    .render()
+    .map_err(|e| Error::template(e.to_string()))?;
+
+    EmailService::from_env()
+        .map_err(|e| Error::ExternalService(e.to_string()))?
+        .send_notification_email(email, Some(name), "Your week on SlateHub", &text, &html)
+        .await
+        .map_err(|e| Error::ExternalService(e.to_string()))
+}
+
+/// Send every digest that is due. Run by the `weekly_digest` scheduled task.
+pub async fn send_due() -> Result<()> {
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct Recipient {
+        id: RecordId,
+        email: String,
+        username: String,
+        name: Option<String>,
+        digest_day: Option<i64>,
+        digest_hour: Option<i64>,
+        digest_last_sent: Option<DateTime<Utc>>,
+    }
+
+    let recipients: Vec<Recipient> = DB
+        .query(
+            "SELECT id, email, username, name, digest_day, digest_hour, digest_last_sent FROM person
+             WHERE digest_enabled = true AND verification_status != 'unverified'",
+        )
+        .await?
+        .take(0)?;
+
+    let now = Utc::now();
+    let mut sent = 0;
+    for recipient in recipients {
+        let pref = DigestPreference::from(PreferenceRow {
+            digest_enabled: Some(true),
+            digest_day: recipient.digest_day,
+            digest_hour: recipient.digest_hour,
+        });
+        if !is_due(&pref, recipient.digest_last_sent, now) {
+            continue;
+        }
+
+        let digest = match build(&recipient.id).await {
+            Ok(digest) => digest,
+            Err(e) => {
+                error!("Failed to build weekly digest for {}: {}", recipient.id.display(), e);
+                continue;
+            }
+        };
+
+        if digest.is_empty() {
+            debug!("Nothing to report for {}, skipping weekly digest", recipient.id.display());
+        } else {
+            let name = recipient.name.as_deref().unwrap_or(&recipient.username);
+            if let Err(e) = send(&recipient.email, name, &digest).await {
+                // Marked as sent anyway, so a bad address isn't retried every run
+                warn!("Failed to send weekly digest to {}: {}", recipient.id.display(), e);
+            } else {
+                sent += 1;
+            }
+        }
+
+        DB.query("UPDATE $person SET digest_last_sent = time::now()")
+            .bind(("person", recipient.id.clone()))
+            .await?
+            .check()?;
+    }
+
+    if sent > 0 {
+        info!("Sent {} weekly digest email(s)", sent);
+    }
+    Ok(())
+}
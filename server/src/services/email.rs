@@ -1,6 +1,5 @@
 use reqwest;
 use serde::{Deserialize, Serialize};
-use std::env;
 use thiserror::Error;
 use tracing::{debug, error, info};
 
@@ -46,6 +45,26 @@ struct Message {
     text_part: Option<String>,
     #[serde(rename = "HTMLPart", skip_serializing_if = "Option::is_none")]
     html_part: Option<String>,
+    #[serde(rename = "Attachments", skip_serializing_if = "Vec::is_empty", default)]
+    attachments: Vec<Attachment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Attachment {
+    #[serde(rename = "ContentType")]
+    content_type: String,
+    #[serde(rename = "Filename")]
+    filename: String,
+    #[serde(rename = "Base64Content")]
+    base64_content: String,
+}
+
+/// A file attached to an outgoing email
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -59,13 +78,13 @@ struct EmailAddress {
 impl EmailService {
     /// Create a new EmailService instance from environment variables
     pub fn from_env() -> Result<Self> {
-        let api_key = env::var("MAILJET_API_KEY")
+        let api_key = crate::settings::var("MAILJET_API_KEY")
             .map_err(|_| EmailError::ConfigError("MAILJET_API_KEY not set".to_string()))?;
-        let api_secret = env::var("MAILJET_API_SECRET")
+        let api_secret = crate::settings::var("MAILJET_API_SECRET")
             .map_err(|_| EmailError::ConfigError("MAILJET_API_SECRET not set".to_string()))?;
         let from_email =
-            env::var("MAILJET_FROM_EMAIL").unwrap_or_else(|_| "noreply@slatehub.com".to_string());
-        let from_name = env::var("MAILJET_FROM_NAME").unwrap_or_else(|_| "SlateHub".to_string());
+            crate::settings::var("MAILJET_FROM_EMAIL").unwrap_or_else(|_| "noreply@slatehub.com".to_string());
+        let from_name = crate::settings::var("MAILJET_FROM_NAME").unwrap_or_else(|_| "SlateHub".to_string());
 
         let client = reqwest::Client::new();
 
@@ -100,8 +119,19 @@ impl EmailService {
             subject: subject.to_string(),
             text_part: text_body.map(|t| t.to_string()),
             html_part: html_body.map(|h| h.to_string()),
+            attachments: Vec::new(),
         };
+        self.deliver(message).await
+    }
 
+    /// Post a message to Mailjet
+    async fn deliver(&self, message: Message) -> Result<()> {
+        let to_email = message
+            .to
+            .first()
+            .map(|t| t.email.clone())
+            .unwrap_or_default();
+        let subject = message.subject.clone();
         let payload = MailjetMessage {
             messages: vec![message],
         };
@@ -385,6 +415,111 @@ impl EmailService {
             .await
     }
 
+    /// Tell an account owner their account was locked after failed sign-ins
+    pub async fn send_lockout_email(
+        &self,
+        to_email: &str,
+        to_name: Option<&str>,
+        ip: &str,
+        minutes: i64,
+    ) -> Result<()> {
+        let subject = "Your SlateHub account was temporarily locked";
+        let reset_url = format!("{}/forgot-password", crate::config::app_url());
+
+        let text_body = format!(
+            "We blocked sign-in to your SlateHub account for {} minutes after several failed attempts.\n\n\
+            Last attempt from IP: {}\n\n\
+            If this was you, wait and try again. If it wasn't, reset your password now:\n\
+            {}\n\n\
+            Best regards,\n\
+            The SlateHub Team",
+            minutes, ip, reset_url
+        );
+
+        let html_body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<head><meta charset="UTF-8"><meta name="viewport" content="width=device-width, initial-scale=1.0"></head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Helvetica, Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background-color: #171717; border-radius: 8px; padding: 30px; margin-bottom: 20px;">
+        <h1 style="color: #d6d8ca; margin-top: 0;">Account Temporarily Locked</h1>
+        <p style="font-size: 16px; color: #d6d8ca;">We blocked sign-in to your account for {} minutes after several failed attempts.</p>
+    </div>
+    <div style="background-color: #ffffff; border: 1px solid #e0e0e0; border-radius: 8px; padding: 30px;">
+        <p style="font-size: 14px; color: #666;">Last attempt from IP <code>{}</code>.</p>
+        <p style="font-size: 14px; color: #666;">If this was you, wait and try again. If it wasn't, reset your password now.</p>
+        <div style="text-align: center; margin: 30px 0;">
+            <a href="{}" style="display: inline-block; background-color: #eb5437; color: white; padding: 14px 36px; text-decoration: none; border-radius: 6px; font-weight: bold; font-size: 16px;">Reset Password</a>
+        </div>
+    </div>
+    <div style="margin-top: 30px; padding-top: 20px; border-top: 1px solid #e0e0e0; text-align: center; color: #999; font-size: 12px;">
+        <p>&copy; 2024 SlateHub. All rights reserved.</p>
+    </div>
+</body>
+</html>"#,
+            minutes, ip, reset_url
+        );
+
+        self.send_email(to_email, to_name, subject, Some(&text_body), Some(&html_body))
+            .await
+    }
+
+    /// Send the one-time code confirming a sign-in from a new device
+    pub async fn send_login_code_email(
+        &self,
+        to_email: &str,
+        to_name: Option<&str>,
+        code: &str,
+        ip: &str,
+        user_agent: &str,
+    ) -> Result<()> {
+        let subject = "Confirm your SlateHub sign-in from a new device";
+
+        let text_body = format!(
+            "Someone signed in to your SlateHub account from a device we don't recognise.\n\n\
+            IP: {}\n\
+            Device: {}\n\n\
+            If this was you, enter this code to finish signing in:\n\
+            {}\n\n\
+            This code will expire in 15 minutes. If it wasn't you, reset your password now.\n\n\
+            Best regards,\n\
+            The SlateHub Team",
+            ip, user_agent, code
+        );
+
+        let html_body = format!(
+            r#"<!DOCTYPE html>
+<html>
+<head><meta charset="UTF-8"><meta name="viewport" content="width=device-width, initial-scale=1.0"></head>
+<body style="font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Helvetica, Arial, sans-serif; line-height: 1.6; color: #333; max-width: 600px; margin: 0 auto; padding: 20px;">
+    <div style="background-color: #171717; border-radius: 8px; padding: 30px; margin-bottom: 20px;">
+        <h1 style="color: #d6d8ca; margin-top: 0;">New Device Sign-in</h1>
+        <p style="font-size: 16px; color: #d6d8ca;">Someone signed in to your account from a device we don't recognise.</p>
+    </div>
+    <div style="background-color: #ffffff; border: 1px solid #e0e0e0; border-radius: 8px; padding: 30px;">
+        <p style="font-size: 14px; color: #666;">IP: <code>{}</code><br>Device: {}</p>
+        <p style="font-size: 14px; color: #666;">If this was you, enter this code to finish signing in:</p>
+        <div style="background-color: #f0f4f8; border: 2px dashed #4a90e2; border-radius: 6px; padding: 20px; text-align: center; margin: 10px 0;">
+            <code style="font-size: 32px; font-weight: bold; color: #4a90e2; letter-spacing: 4px;">{}</code>
+        </div>
+        <p style="font-size: 14px; color: #999; margin-top: 20px;">
+            This code will expire in 15 minutes. If this wasn't you, reset your password now.
+        </p>
+    </div>
+    <div style="margin-top: 30px; padding-top: 20px; border-top: 1px solid #e0e0e0; text-align: center; color: #999; font-size: 12px;">
+        <p>&copy; 2024 SlateHub. All rights reserved.</p>
+    </div>
+</body>
+</html>"#,
+            ip,
+            ammonia::clean_text(user_agent),
+            code
+        );
+
+        self.send_email(to_email, to_name, subject, Some(&text_body), Some(&html_body))
+            .await
+    }
+
     /// Send a generic notification email (e.g., new message notification)
     pub async fn send_notification_email(
         &self,
@@ -398,6 +533,42 @@ impl EmailService {
             .await
     }
 
+    /// Send a notification email with file attachments (e.g., a PDF report)
+    pub async fn send_email_with_attachments(
+        &self,
+        to_email: &str,
+        to_name: Option<&str>,
+        subject: &str,
+        text_body: &str,
+        html_body: &str,
+        attachments: &[EmailAttachment],
+    ) -> Result<()> {
+        use base64::Engine;
+
+        let message = Message {
+            from: EmailAddress {
+                email: self.from_email.clone(),
+                name: Some(self.from_name.clone()),
+            },
+            to: vec![EmailAddress {
+                email: to_email.to_string(),
+                name: to_name.map(|n| n.to_string()),
+            }],
+            subject: subject.to_string(),
+            text_part: Some(text_body.to_string()),
+            html_part: Some(html_body.to_string()),
+            attachments: attachments
+                .iter()
+                .map(|a| Attachment {
+                    content_type: a.content_type.clone(),
+                    filename: a.filename.clone(),
+                    base64_content: base64::engine::general_purpose::STANDARD.encode(&a.data),
+                })
+                .collect(),
+        };
+        self.deliver(message).await
+    }
+
     /// Send feedback notification email
     pub async fn send_feedback_email(
         &self,
@@ -405,7 +576,7 @@ impl EmailService {
         page_url: &str,
         message: &str,
     ) -> Result<()> {
-        let recipient = env::var("FEEDBACK_RECIPIENT_EMAIL")
+        let recipient = crate::settings::var("FEEDBACK_RECIPIENT_EMAIL")
             .unwrap_or_else(|_| self.from_email.clone());
 
         let subject = format!("SlateHub Feedback from {}", username);
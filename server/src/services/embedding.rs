@@ -1,9 +1,16 @@
 use anyhow::Result;
-use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
-use std::sync::OnceLock;
+use fastembed::{
+    EmbeddingModel, InitOptions, RerankInitOptions, RerankerModel, TextEmbedding, TextRerank,
+};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::Duration;
 use surrealdb::types::{RecordId, SurrealValue};
 use tracing::{debug, info, warn};
 
+use crate::record_id_ext::RecordIdExt;
+use crate::units::{self, UnitSystem};
+
 /// Global embedding service instance — written once at startup, read concurrently forever after.
 /// No Mutex needed: OnceLock guarantees safe one-time init, and TextEmbedding::embed takes &self.
 static EMBEDDER: OnceLock<TextEmbedding> = OnceLock::new();
@@ -21,6 +28,11 @@ pub async fn init_embedding_service() -> Result<()> {
     Ok(())
 }
 
+/// Whether the embedding model has been loaded
+pub fn is_initialized() -> bool {
+    EMBEDDER.get().is_some()
+}
+
 /// Generate embedding for a single text (blocking — use generate_embedding_async from async contexts)
 pub fn generate_embedding(text: &str) -> Result<Vec<f32>> {
     let embedder = EMBEDDER.get().ok_or_else(|| {
@@ -41,34 +53,129 @@ pub async fn generate_embedding_async(text: &str) -> Result<Vec<f32>> {
     tokio::task::spawn_blocking(move || generate_embedding(&text)).await?
 }
 
+/// Optional cross-encoder that re-scores search candidates against the query
+/// text, loaded at startup when `SEARCH_RERANK` is on
+static RERANKER: OnceLock<TextRerank> = OnceLock::new();
+
+/// Load the reranker model. Called at startup after `init_embedding_service()`
+/// when `SEARCH_RERANK` is on.
+pub async fn init_reranker() -> Result<()> {
+    info!("Initializing search reranker with BGE-Reranker-Base model");
+
+    let reranker = tokio::task::spawn_blocking(|| {
+        TextRerank::try_new(RerankInitOptions::new(RerankerModel::BGERerankerBase))
+    })
+    .await??;
+
+    RERANKER
+        .set(reranker)
+        .map_err(|_| anyhow::anyhow!("Reranker already initialized"))?;
+
+    info!("Search reranker initialized successfully");
+    Ok(())
+}
+
+/// Whether the reranker model has been loaded
+pub fn reranker_initialized() -> bool {
+    RERANKER.get().is_some()
+}
+
+/// Cross-encoder relevance of each document to `query`, in document order.
+/// Higher is more relevant; the scores are only comparable within one call.
+pub fn rerank(query: &str, documents: &[String]) -> Result<Vec<f32>> {
+    let reranker = RERANKER
+        .get()
+        .ok_or_else(|| anyhow::anyhow!("Reranker not initialized. Call init_reranker() first."))?;
+
+    debug!("Reranking {} documents", documents.len());
+    let documents: Vec<&str> = documents.iter().map(String::as_str).collect();
+    let mut scores = vec![f32::MIN; documents.len()];
+    for result in reranker.rerank(query, documents, false, None)? {
+        if let Some(score) = scores.get_mut(result.index) {
+            *score = result.score;
+        }
+    }
+    Ok(scores)
+}
+
+/// Async-safe reranking, on a blocking thread like `generate_embedding_async`
+pub async fn rerank_async(query: &str, documents: Vec<String>) -> Result<Vec<f32>> {
+    let query = query.to_string();
+    tokio::task::spawn_blocking(move || rerank(&query, &documents)).await?
+}
+
+/// Quiet period after an edit before the record is re-embedded, so a burst of
+/// saves produces one embedding
+const DEBOUNCE: Duration = Duration::from_secs(3);
+
+/// Newest embedding text per record (tenant-keyed) waiting out the debounce
+static QUEUED: LazyLock<Mutex<HashMap<String, String>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
 /// Fire-and-forget: generate embedding and write it to the record in the background.
 /// Durable: writes a `pending_embedding` record before spawning, deletes it on completion.
 /// On server restart, `backfill_pending_embeddings()` re-processes any remaining records.
+///
+/// Debounced: the embedding is generated `DEBOUNCE` after the first call, from the
+/// text of the latest one, so rapid successive edits cost a single embedding.
+/// Nothing is generated when the text matches what the record was embedded
+/// from, i.e. only fields that aren't embedded changed.
 pub fn spawn_embedding_update(record_id: RecordId, embedding_text: String) {
-    tokio::spawn(async move {
+    let key = crate::db::tenant_key(&record_id.to_raw_string());
+    let already_queued = QUEUED
+        .lock()
+        .unwrap()
+        .insert(key.clone(), embedding_text.clone())
+        .is_some();
+    if already_queued {
+        debug!(record_id = ?record_id, "Embedding refresh already queued, deferring to the newest edit");
+        return;
+    }
+
+    crate::db::spawn(async move {
         let db = &crate::db::DB;
 
         // Write pending record for durability — if server crashes, this survives
         if let Err(e) = db
             .query("INSERT INTO pending_embedding (target, embedding_text) VALUES ($target, $text) ON DUPLICATE KEY UPDATE embedding_text = $text")
             .bind(("target", record_id.clone()))
-            .bind(("text", embedding_text.clone()))
+            .bind(("text", embedding_text))
             .await
         {
             warn!(record_id = ?record_id, error = %e, "Failed to write pending_embedding record");
             // Still attempt the embedding — just won't be durable
         }
 
+        tokio::time::sleep(DEBOUNCE).await;
+        let Some(embedding_text) = QUEUED.lock().unwrap().remove(&key) else {
+            return;
+        };
         process_single_embedding(db, record_id, embedding_text).await;
     });
 }
 
 /// Process a single embedding: generate vector, update target record, remove pending record.
+/// Skips the model when the record is already embedded from the same text.
 async fn process_single_embedding(
-    db: &surrealdb::Surreal<surrealdb::engine::remote::ws::Client>,
+    db: &crate::db::Database,
     record_id: RecordId,
     embedding_text: String,
 ) {
+    match embedded_text(db, &record_id).await {
+        Some(previous) if previous == embedding_text => {
+            debug!(record_id = ?record_id, "Embedded fields unchanged, skipping re-embedding");
+            clear_pending(db, &record_id).await;
+            return;
+        }
+        Some(previous) => {
+            debug!(
+                record_id = ?record_id,
+                changed = ?changed_fields(&previous, &embedding_text),
+                "Re-embedding changed fields"
+            );
+        }
+        None => {}
+    }
+
     let text_clone = embedding_text.clone();
     let rid_clone = record_id.clone();
     let embedding = match tokio::task::spawn_blocking(move || generate_embedding(&text_clone))
@@ -85,18 +192,39 @@ async fn process_single_embedding(
         }
     };
 
-    if let Err(e) = db
-        .query("UPDATE $id SET embedding = $embedding, embedding_text = $embedding_text")
-        .bind(("id", record_id.clone()))
-        .bind(("embedding", embedding))
-        .bind(("embedding_text", embedding_text))
-        .await
-    {
+    if let Err(e) = store_embedding(db, record_id.clone(), embedding, embedding_text).await {
         warn!(record_id = ?record_id, error = %e, "Background embedding DB update failed");
         return;
     }
+    crate::services::search_cache::invalidate();
 
     // Success — remove the pending record
+    clear_pending(db, &record_id).await;
+}
+
+/// The text a record's current embedding was generated from, if it has one
+async fn embedded_text(
+    db: &crate::db::Database,
+    record_id: &RecordId,
+) -> Option<String> {
+    match db
+        .query("SELECT VALUE embedding_text FROM $id WHERE (embedding ?? embedding_q) IS NOT NONE")
+        .bind(("id", record_id.clone()))
+        .await
+        .and_then(|mut r| r.take::<Vec<Option<String>>>(0))
+    {
+        Ok(rows) => rows.into_iter().next().flatten(),
+        Err(e) => {
+            warn!(record_id = ?record_id, error = %e, "Failed to read current embedding text");
+            None
+        }
+    }
+}
+
+async fn clear_pending(
+    db: &crate::db::Database,
+    record_id: &RecordId,
+) {
     if let Err(e) = db
         .query("DELETE FROM pending_embedding WHERE target = $target")
         .bind(("target", record_id.clone()))
@@ -108,6 +236,88 @@ async fn process_single_embedding(
     }
 }
 
+/// The `label: value` parts of an embedding text, in order, with repeated
+/// labels merged
+fn labelled_fields(text: &str) -> Vec<(&str, String)> {
+    let mut fields: Vec<(&str, String)> = Vec::new();
+    let mut current = "";
+    for part in text.split(". ") {
+        // Values can contain ". " themselves (bios, experience); only a short
+        // lowercase label before ": " starts a new field
+        let (label, value, continued) = match part.split_once(": ") {
+            Some((label, value))
+                if label.len() <= 40 && label.chars().all(|c| c.is_ascii_lowercase() || c == ' ') =>
+            {
+                current = label;
+                (label, value, false)
+            }
+            _ => (current, part, true),
+        };
+        match fields.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, existing)) => {
+                if continued {
+                    existing.push_str(". ");
+                }
+                existing.push_str(value);
+            }
+            None => fields.push((label, value.to_string())),
+        }
+    }
+    fields
+}
+
+/// Labels of the `label: value` parts that differ between two embedding texts,
+/// e.g. `["background", "skills and abilities"]`, sorted.
+pub fn changed_fields(old: &str, new: &str) -> Vec<String> {
+    let old: HashMap<&str, String> = labelled_fields(old).into_iter().collect();
+    let new: HashMap<&str, String> = labelled_fields(new).into_iter().collect();
+    let mut changed: Vec<String> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|label| old.get(*label) != new.get(*label))
+        .map(|label| label.to_string())
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+/// Passages embedded per record, so a long bio can't crowd out the rest
+pub const MAX_PASSAGES: usize = 32;
+
+/// Words in a list item; longer comma-separated runs are prose, not lists
+const MAX_ITEM_WORDS: usize = 4;
+
+/// The short pieces of an embedding text that search explains matches with,
+/// as (label, text): each sentence of a field, and each item of a
+/// comma-separated list like skills. Unlabelled text is left out.
+pub fn passages(text: &str) -> Vec<(String, String)> {
+    let mut passages: Vec<(String, String)> = Vec::new();
+    for (label, value) in labelled_fields(text) {
+        if label.is_empty() {
+            continue;
+        }
+        for sentence in value.split(". ") {
+            let sentence = sentence.trim().trim_end_matches('.');
+            let items: Vec<&str> = sentence.split(", ").map(str::trim).collect();
+            let is_list = items.len() > 1
+                && items
+                    .iter()
+                    .all(|item| item.split_whitespace().count() <= MAX_ITEM_WORDS);
+            let pieces = if is_list { items } else { vec![sentence] };
+            for piece in pieces.into_iter().filter(|p| !p.is_empty()) {
+                if passages.len() >= MAX_PASSAGES {
+                    return passages;
+                }
+                if !passages.iter().any(|(l, t)| l == label && t == piece) {
+                    passages.push((label.to_string(), piece.to_string()));
+                }
+            }
+        }
+    }
+    passages
+}
+
 /// Process any pending embeddings left over from a previous server run.
 /// Call this once at startup after `init_embedding_service()`.
 pub async fn backfill_pending_embeddings() {
@@ -159,6 +369,252 @@ pub fn generate_embeddings_batch(texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
     Ok(embeddings)
 }
 
+/// How embeddings are stored on records, from `EMBEDDING_PRECISION`.
+///
+/// `Int8` replaces the f32 vector in `embedding` with a scalar-quantized copy in
+/// `embedding_q` (one small int per dimension) plus `embedding_scale`. Search
+/// reads `embedding ?? embedding_q`; cosine similarity ignores the scale, so the
+/// quantized vectors are compared directly and both forms can be mixed while
+/// `convert_precision` works through existing records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    F32,
+    Int8,
+}
+
+impl Precision {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "f32" | "float" => Some(Precision::F32),
+            "int8" | "i8" => Some(Precision::Int8),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Precision::F32 => "f32",
+            Precision::Int8 => "int8",
+        }
+    }
+}
+
+/// A scalar-quantized embedding: `values[i] * scale` approximates the original
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantized {
+    pub values: Vec<i8>,
+    pub scale: f32,
+}
+
+impl Quantized {
+    pub fn dequantize(&self) -> Vec<f32> {
+        self.values.iter().map(|&v| v as f32 * self.scale).collect()
+    }
+
+    /// Values as stored in the database
+    fn stored_values(&self) -> Vec<i64> {
+        self.values.iter().map(|&v| v as i64).collect()
+    }
+}
+
+/// Symmetric int8 quantization with one scale per vector, so the largest
+/// component maps to ±127.
+pub fn quantize(embedding: &[f32]) -> Quantized {
+    let max = embedding.iter().fold(0f32, |max, v| max.max(v.abs()));
+    if max == 0.0 || !max.is_finite() {
+        return Quantized { values: vec![0; embedding.len()], scale: 0.0 };
+    }
+    let scale = max / 127.0;
+    Quantized {
+        values: embedding
+            .iter()
+            .map(|v| (v / scale).round().clamp(-127.0, 127.0) as i8)
+            .collect(),
+        scale,
+    }
+}
+
+/// Write a record's embedding in the configured precision, clearing the other form.
+pub async fn store_embedding(
+    db: &crate::db::Database,
+    record_id: RecordId,
+    embedding: Vec<f32>,
+    embedding_text: String,
+) -> std::result::Result<(), surrealdb::Error> {
+    let storage = crate::config::embedding_storage();
+    match storage.precision {
+        Precision::F32 => {
+            db.query(
+                "UPDATE $id SET embedding = $embedding, embedding_q = NONE, embedding_scale = NONE,
+                     embedding_text = $embedding_text",
+            )
+            .bind(("id", record_id.clone()))
+            .bind(("embedding", embedding))
+            .bind(("embedding_text", embedding_text.clone()))
+            .await?
+            .check()?;
+        }
+        Precision::Int8 => {
+            let quantized = quantize(&embedding);
+            db.query(
+                "UPDATE $id SET embedding = NONE, embedding_q = $values, embedding_scale = $scale,
+                     embedding_text = $embedding_text",
+            )
+            .bind(("id", record_id.clone()))
+            .bind(("values", quantized.stored_values()))
+            .bind(("scale", quantized.scale))
+            .bind(("embedding_text", embedding_text.clone()))
+            .await?
+            .check()?;
+        }
+    }
+    if storage.fields {
+        store_field_embeddings(db, &record_id, &embedding_text).await;
+    }
+    Ok(())
+}
+
+/// One passage of a record's embedding text with its own embedding, which
+/// search compares the query against to explain a match
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, SurrealValue)]
+pub struct FieldEmbedding {
+    pub label: String,
+    pub text: String,
+    /// int8-quantized; similarity doesn't need the scale
+    pub vector: Vec<i64>,
+}
+
+/// Embed each passage of a record's text. Failures are only logged: the
+/// record's main embedding is what search ranks by.
+async fn store_field_embeddings(
+    db: &crate::db::Database,
+    record_id: &RecordId,
+    embedding_text: &str,
+) {
+    let passages = passages(embedding_text);
+    let texts: Vec<String> = passages.iter().map(|(_, text)| text.clone()).collect();
+    let vectors = if texts.is_empty() {
+        Vec::new()
+    } else {
+        match tokio::task::spawn_blocking(move || generate_embeddings_batch(texts)).await {
+            Ok(Ok(vectors)) => vectors,
+            Ok(Err(e)) => {
+                warn!(record_id = ?record_id, error = %e, "Field embeddings failed");
+                return;
+            }
+            Err(e) => {
+                warn!(record_id = ?record_id, error = %e, "Field embedding task panicked");
+                return;
+            }
+        }
+    };
+
+    let fields: Vec<FieldEmbedding> = passages
+        .into_iter()
+        .zip(vectors)
+        .map(|((label, text), vector)| FieldEmbedding {
+            label,
+            text,
+            vector: quantize(&vector).stored_values(),
+        })
+        .collect();
+    if let Err(e) = db
+        .query("UPDATE $id SET embedding_fields = $fields")
+        .bind(("id", record_id.clone()))
+        .bind(("fields", fields))
+        .await
+        .and_then(|r| r.check())
+    {
+        warn!(record_id = ?record_id, error = %e, "Failed to store field embeddings");
+    }
+}
+
+/// Tables whose records carry an embedding
+pub const EMBEDDED_TABLES: &[&str] = &["person", "organization", "location", "production"];
+
+/// Records converted per table per `convert_precision` run
+const CONVERT_BATCH: usize = 500;
+
+/// Move a batch of records stored in the other precision over to the
+/// configured one. Converting to f32 dequantizes, which is close but not
+/// exact; run `rebuild-embeddings` afterwards for full-precision vectors.
+/// Returns how many records were converted.
+pub async fn convert_precision() -> crate::error::Result<usize> {
+    let precision = crate::config::embedding_storage().precision;
+    let mut converted = 0;
+    for table in EMBEDDED_TABLES {
+        converted += match precision {
+            Precision::Int8 => quantize_batch(table).await?,
+            Precision::F32 => dequantize_batch(table).await?,
+        };
+    }
+    if converted > 0 {
+        info!("Converted {} embeddings to {}", converted, precision.as_str());
+    }
+    Ok(converted)
+}
+
+async fn quantize_batch(table: &str) -> crate::error::Result<usize> {
+    #[derive(Debug, serde::Deserialize, SurrealValue)]
+    struct Row {
+        id: RecordId,
+        embedding: Vec<f32>,
+    }
+
+    let db = &crate::db::DB;
+    let rows: Vec<Row> = db
+        .query(format!(
+            "SELECT id, embedding FROM {} WHERE embedding IS NOT NONE LIMIT {}",
+            table, CONVERT_BATCH
+        ))
+        .await?
+        .take(0)?;
+
+    let count = rows.len();
+    for row in rows {
+        let quantized = quantize(&row.embedding);
+        db.query("UPDATE $id SET embedding_q = $values, embedding_scale = $scale, embedding = NONE")
+            .bind(("id", row.id))
+            .bind(("values", quantized.stored_values()))
+            .bind(("scale", quantized.scale))
+            .await?
+            .check()?;
+    }
+    Ok(count)
+}
+
+async fn dequantize_batch(table: &str) -> crate::error::Result<usize> {
+    #[derive(Debug, serde::Deserialize, SurrealValue)]
+    struct Row {
+        id: RecordId,
+        embedding_q: Vec<i64>,
+        embedding_scale: f32,
+    }
+
+    let db = &crate::db::DB;
+    let rows: Vec<Row> = db
+        .query(format!(
+            "SELECT id, embedding_q, embedding_scale FROM {} WHERE embedding_q IS NOT NONE LIMIT {}",
+            table, CONVERT_BATCH
+        ))
+        .await?
+        .take(0)?;
+
+    let count = rows.len();
+    for row in rows {
+        let quantized = Quantized {
+            values: row.embedding_q.iter().map(|&v| v.clamp(-127, 127) as i8).collect(),
+            scale: row.embedding_scale,
+        };
+        db.query("UPDATE $id SET embedding = $embedding, embedding_q = NONE, embedding_scale = NONE")
+            .bind(("id", row.id))
+            .bind(("embedding", quantized.dequantize()))
+            .await?
+            .check()?;
+    }
+    Ok(count)
+}
+
 /// Build optimized text for person/actor embedding
 /// Focuses on: role type, skills, physical attributes, location, experience
 #[allow(clippy::too_many_arguments)]
@@ -171,7 +627,7 @@ pub fn build_person_embedding_text(
     age_range: Option<(i32, i32)>,
     gender: Option<&str>,
     ethnicity: &[String],
-    height_cm: Option<i32>,
+    height_mm: Option<i32>,
     body_type: Option<&str>,
     hair_color: Option<&str>,
     eye_color: Option<&str>,
@@ -181,6 +637,7 @@ pub fn build_person_embedding_text(
     acting_age_range: Option<(i32, i32)>,
     acting_ethnicities: &[String],
     nationality: Option<&str>,
+    audio_work: &[String], // descriptions of audio reels
 ) -> String {
     let mut parts = Vec::new();
 
@@ -215,10 +672,9 @@ pub fn build_person_embedding_text(
         parts.push(format!("Nationality: {}", nat));
     }
 
-    if let Some(height) = height_cm {
-        let feet = height / 30; // rough conversion
-        let inches = (height % 30) * 12 / 30;
-        parts.push(format!("Height: {} cm ({}'{}\")", height, feet, inches));
+    // Both systems, so queries in either match
+    if let Some(mm) = height_mm.filter(|mm| *mm > 0) {
+        parts.push(format!("Height: {}", units::height_both(mm, UnitSystem::Metric)));
     }
 
     if let Some(bt) = body_type {
@@ -267,6 +723,12 @@ pub fn build_person_embedding_text(
         parts.push(format!("Experience: {}", experience.join(". ")));
     }
 
+    // Audio reels: what composers and sound designers have made, as they
+    // described it and as the files are tagged
+    if !audio_work.is_empty() {
+        parts.push(format!("Audio work: {}", audio_work.join(". ")));
+    }
+
     // Parse headline into individual roles and enrich with department + synonyms
     let mut detected_roles = Vec::new();
     if let Some(h) = headline {
@@ -432,6 +894,7 @@ fn enrich_roles(roles: &[String]) -> String {
 
 /// Build optimized text for organization embedding
 /// Focuses on: services, industry specialization, location, size
+#[allow(clippy::too_many_arguments)]
 pub fn build_organization_embedding_text(
     name: &str,
     org_type: &str,
@@ -440,6 +903,7 @@ pub fn build_organization_embedding_text(
     location: Option<&str>,
     founded_year: Option<i32>,
     employees_count: Option<i32>,
+    slate: &[String], // company credits, see `company_credit::slate_line`
 ) -> String {
     let mut parts = Vec::new();
 
@@ -478,6 +942,11 @@ pub fn build_organization_embedding_text(
         parts.push(format!("{} company with {} employees", size, count));
     }
 
+    // Productions the company produced, financed or supplied
+    if !slate.is_empty() {
+        parts.push(format!("Production slate: {}", slate.join(", ")));
+    }
+
     // Description for detailed context
     if let Some(desc) = description {
         parts.push(format!("Description: {}", desc));
@@ -535,6 +1004,7 @@ pub fn build_location_embedding_text(
 
 /// Build optimized text for production embedding
 /// Focuses on: genre, type, description, requirements, timeline
+#[allow(clippy::too_many_arguments)]
 pub fn build_production_embedding_text(
     title: &str,
     production_type: &str,
@@ -543,6 +1013,7 @@ pub fn build_production_embedding_text(
     location: Option<&str>,
     start_date: Option<&str>,
     end_date: Option<&str>,
+    companies: &[String], // company credits, see `company_credit::company_line`
 ) -> String {
     let mut parts = Vec::new();
 
@@ -569,6 +1040,11 @@ pub fn build_production_embedding_text(
         }
     }
 
+    // Producers, financiers and vendors
+    if !companies.is_empty() {
+        parts.push(format!("Companies: {}", companies.join(", ")));
+    }
+
     // Description is critical for understanding the project
     if let Some(desc) = description {
         parts.push(format!("Description: {}", desc));
@@ -0,0 +1,169 @@
+//! A/B experiments on top of feature flags
+//!
+//! An experiment runs only while the feature flag with the same name is on for
+//! the current user, so its audience is controlled from `/admin/flags` with the
+//! usual rollout percentage and organization overrides. Everyone inside the
+//! audience is assigned a variant by a stable hash of their person id (or the
+//! anonymous visitor cookie), so they keep seeing the same variant.
+//!
+//! Each exposed search is written to `search_log` with the experiment and
+//! variant, and result clicks are counted on the same row. `report()` compares
+//! click-through between variants.
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use surrealdb::types::SurrealValue;
+
+use crate::config::SearchWeights;
+use crate::db::DB;
+use crate::error::Result;
+use crate::models::person::SessionUser;
+use crate::services::flags;
+
+/// Cookie holding the id used to assign anonymous visitors to variants
+pub const VISITOR_COOKIE: &str = "visitor_id";
+
+pub struct Experiment {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The first variant is the control
+    pub variants: &'static [&'static str],
+}
+
+/// Ranking weights for web search: current weights against a stronger
+/// semantic (embedding) signal with a lower similarity cutoff.
+pub const SEARCH_RANKING: Experiment = Experiment {
+    name: "search_ranking",
+    description: "Current search weights vs. boosted semantic similarity",
+    variants: &["control", "semantic_boost"],
+};
+
+pub const EXPERIMENTS: &[Experiment] = &[SEARCH_RANKING];
+
+/// The experiment and variant a request was exposed to
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exposure {
+    pub experiment: &'static str,
+    pub variant: &'static str,
+}
+
+impl Experiment {
+    /// Stable variant for a subject (person key or visitor id)
+    pub fn assign(&self, subject: &str) -> &'static str {
+        let index = flags::stable_hash(&format!("{}:{}", self.name, subject)) as usize % self.variants.len();
+        self.variants[index]
+    }
+
+    /// Exposure for this request, or `None` when the experiment's flag is off
+    /// for the user and they should get the default behaviour.
+    pub async fn exposure(&self, user: Option<&SessionUser>, visitor_id: &str) -> Option<Exposure> {
+        if !flags::enabled(self.name, user).await {
+            return None;
+        }
+
+        let subject = match user {
+            Some(u) => u.id.strip_prefix("person:").unwrap_or(&u.id),
+            None if !visitor_id.is_empty() => visitor_id,
+            None => return None,
+        };
+
+        Some(Exposure {
+            experiment: self.name,
+            variant: self.assign(subject),
+        })
+    }
+}
+
+/// Search weights for a `search_ranking` variant
+pub fn ranking_weights(variant: Option<&str>, base: &SearchWeights) -> SearchWeights {
+    match variant {
+        Some("semantic_boost") => SearchWeights {
+            vector_multiplier: base.vector_multiplier * 3 / 2,
+            vector_threshold: (base.vector_threshold - 0.05).max(0.0),
+            ..base.clone()
+        },
+        _ => base.clone(),
+    }
+}
+
+/// Click-through for one variant over the report window
+#[derive(Debug, Clone, Default, Deserialize, SurrealValue)]
+pub struct VariantStats {
+    pub variant: String,
+    pub searches: i64,
+    /// Searches with at least one result click
+    pub clicked_searches: i64,
+    pub clicks: i64,
+}
+
+impl VariantStats {
+    /// Share of searches with at least one click, in percent
+    pub fn ctr(&self) -> f64 {
+        if self.searches == 0 {
+            0.0
+        } else {
+            self.clicked_searches as f64 * 100.0 / self.searches as f64
+        }
+    }
+
+    pub fn clicks_per_search(&self) -> f64 {
+        if self.searches == 0 {
+            0.0
+        } else {
+            self.clicks as f64 / self.searches as f64
+        }
+    }
+}
+
+/// Relative change in CTR of `variant` against `control`, in percent
+pub fn lift(control: &VariantStats, variant: &VariantStats) -> Option<f64> {
+    let base = control.ctr();
+    if base == 0.0 || variant.searches == 0 {
+        None
+    } else {
+        Some((variant.ctr() - base) * 100.0 / base)
+    }
+}
+
+/// Two-proportion z-score for the difference in CTR. |z| >= 1.96 is
+/// significant at the 95% level.
+pub fn z_score(control: &VariantStats, variant: &VariantStats) -> Option<f64> {
+    let (n1, n2) = (control.searches as f64, variant.searches as f64);
+    if n1 == 0.0 || n2 == 0.0 {
+        return None;
+    }
+    let (p1, p2) = (control.clicked_searches as f64 / n1, variant.clicked_searches as f64 / n2);
+    let pooled = (control.clicked_searches + variant.clicked_searches) as f64 / (n1 + n2);
+    let se = (pooled * (1.0 - pooled) * (1.0 / n1 + 1.0 / n2)).sqrt();
+    if se == 0.0 { None } else { Some((p2 - p1) / se) }
+}
+
+/// Per-variant click-through for an experiment since `since`, in the order the
+/// variants are declared (variants without traffic are included with zeros).
+pub async fn report(experiment: &Experiment, since: DateTime<Utc>) -> Result<Vec<VariantStats>> {
+    let rows: Vec<VariantStats> = DB
+        .query(
+            "SELECT variant,
+                    count() AS searches,
+                    count(clicks > 0) AS clicked_searches,
+                    math::sum(clicks) AS clicks
+             FROM search_log
+             WHERE experiment = $experiment AND created_at >= $since
+             GROUP BY variant",
+        )
+        .bind(("experiment", experiment.name.to_string()))
+        .bind(("since", since))
+        .await?
+        .take(0)?;
+
+    Ok(experiment
+        .variants
+        .iter()
+        .map(|v| {
+            rows.iter().find(|r| r.variant == *v).cloned().unwrap_or(VariantStats {
+                variant: v.to_string(),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
@@ -0,0 +1,140 @@
+//! Forwarding feedback
+//!
+//! Admins can forward feedback to `FEEDBACK_WEBHOOK_URL`. The JSON posted has
+//! `title`, `body` and `labels`, which GitHub-style issue APIs take as a new
+//! issue, and `text`, which Slack-style chat webhooks post as a message, along
+//! with the feedback's own fields for anything custom. `FEEDBACK_WEBHOOK_TOKEN`
+//! is sent as a bearer token when set. When the response has an `html_url`, as
+//! a created issue does, it's kept as the feedback's issue link.
+
+use std::time::Duration;
+
+use serde_json::{Value, json};
+use tracing::{info, warn};
+
+use crate::{
+    error::Error,
+    models::feedback::{self, Feedback},
+    record_id_ext::RecordIdExt,
+};
+
+/// Longest title sent, taken from the message's first line
+const MAX_TITLE_CHARS: usize = 80;
+
+pub fn webhook_url() -> Option<String> {
+    crate::settings::var("FEEDBACK_WEBHOOK_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+}
+
+/// "[Bug] First line of the message", shortened to fit
+pub fn title(feedback: &Feedback) -> String {
+    let first_line = feedback.message.lines().next().unwrap_or_default().trim();
+    let mut summary: String = first_line.chars().take(MAX_TITLE_CHARS).collect();
+    if first_line.chars().count() > MAX_TITLE_CHARS {
+        summary.push('…');
+    }
+    let prefix = if feedback.kind == "bug" {
+        "Bug"
+    } else {
+        "Feedback"
+    };
+    format!("[{}] {}", prefix, summary)
+}
+
+/// The JSON posted to the webhook
+pub fn payload(feedback: &Feedback, app_url: &str) -> Value {
+    let title = title(feedback);
+    let mut details = format!(
+        "{}\n\n---\nType: {}\nFrom: {}\nPage: {}{}\nSent: {}",
+        feedback.message,
+        feedback::kind_label(&feedback.kind),
+        feedback.username,
+        app_url,
+        feedback.page_url,
+        feedback.created_at.format("%Y-%m-%d %H:%M UTC"),
+    );
+    if let Some(user_agent) = &feedback.user_agent {
+        details.push_str(&format!("\nBrowser: {}", user_agent));
+    }
+    if !feedback.request_ids.is_empty() {
+        details.push_str(&format!(
+            "\nRecent requests (newest first): {}",
+            feedback.request_ids.join(", ")
+        ));
+    }
+
+    json!({
+        "title": title,
+        "body": details,
+        "labels": [feedback.kind],
+        "text": format!("{}\n{}", title, details),
+        "id": feedback.id.key_string(),
+        "kind": feedback.kind,
+        "username": feedback.username,
+        "page_url": feedback.page_url,
+        "message": feedback.message,
+        "request_ids": feedback.request_ids,
+        "created_at": feedback.created_at.to_rfc3339(),
+    })
+}
+
+/// The created issue's link, when the webhook returns one
+pub fn issue_url(response: &Value) -> Option<String> {
+    response
+        .get("html_url")
+        .and_then(Value::as_str)
+        .filter(|url| url.starts_with("https://") || url.starts_with("http://"))
+        .map(str::to_string)
+}
+
+/// Post the feedback to the webhook, returning the issue link if one was made
+pub async fn forward(feedback: &Feedback) -> Result<Option<String>, Error> {
+    let url = webhook_url()
+        .ok_or_else(|| Error::BadRequest("No feedback webhook is configured".to_string()))?;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .map_err(|e| Error::Internal(format!("Failed to build HTTP client: {}", e)))?;
+    let mut request = client
+        .post(&url)
+        .header("Accept", "application/json")
+        .header("User-Agent", "SlateHub")
+        .json(&payload(feedback, &crate::config::app_url()));
+    if let Ok(token) = crate::settings::var("FEEDBACK_WEBHOOK_TOKEN") {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| Error::ExternalService(format!("Feedback webhook request failed: {}", e)))?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        warn!(
+            "Feedback webhook refused {} ({}): {}",
+            feedback.id.display(),
+            status,
+            body
+        );
+        return Err(Error::ExternalService(format!(
+            "Feedback webhook returned {}",
+            status
+        )));
+    }
+
+    let issue = serde_json::from_str::<Value>(&body)
+        .ok()
+        .and_then(|value| issue_url(&value));
+    info!(
+        "Forwarded feedback {}{}",
+        feedback.id.display(),
+        issue
+            .as_deref()
+            .map(|url| format!(" as {}", url))
+            .unwrap_or_default()
+    );
+    Ok(issue)
+}
@@ -0,0 +1,286 @@
+//! Feature flags
+//!
+//! Flags live in the `feature_flags` table, keyed by name, and are toggled from
+//! `/admin/flags`. A flag is checked with `flags::enabled("new_search_ui", user)`:
+//!
+//! 1. An organization listed in `disabled_orgs` turns the flag off for its members.
+//! 2. An organization listed in `enabled_orgs` turns it on, even while the flag is off.
+//! 3. Otherwise the flag must be `enabled`, and the user must fall inside
+//!    `rollout_percent`. Each user is hashed into a stable bucket per flag, so
+//!    raising the percentage only ever adds users.
+//!
+//! Unknown flags are off. Anonymous visitors only see a flag at 100% rollout.
+//! Flags are cached in memory for a short time; admin changes call `invalidate()`.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{Duration, Instant};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, warn};
+
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::models::person::SessionUser;
+use crate::record_id_ext::RecordIdExt;
+
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default)]
+pub struct FeatureFlag {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+    pub rollout_percent: u8,
+    /// Organization record ids ("organization:xyz") that always get the flag
+    pub enabled_orgs: Vec<String>,
+    /// Organization record ids that never get the flag
+    pub disabled_orgs: Vec<String>,
+}
+
+impl FeatureFlag {
+    fn has_overrides(&self) -> bool {
+        !self.enabled_orgs.is_empty() || !self.disabled_orgs.is_empty()
+    }
+
+    /// Decide the flag for a user. `user_key` is the person id without the table
+    /// prefix, `user_orgs` the record ids of the organizations they belong to.
+    pub fn evaluate(&self, user_key: Option<&str>, user_orgs: &[String]) -> bool {
+        if user_orgs.iter().any(|o| self.disabled_orgs.contains(o)) {
+            return false;
+        }
+        if user_orgs.iter().any(|o| self.enabled_orgs.contains(o)) {
+            return true;
+        }
+        if !self.enabled {
+            return false;
+        }
+        if self.rollout_percent >= 100 {
+            return true;
+        }
+        match user_key {
+            Some(key) => rollout_bucket(&self.name, key) < self.rollout_percent,
+            None => false,
+        }
+    }
+}
+
+/// Stable bucket in 0..100 for a user and flag. Hashing the flag name in keeps
+/// separate flags from rolling out to the same users first.
+pub fn rollout_bucket(flag: &str, user_key: &str) -> u8 {
+    (stable_hash(&format!("{}:{}", flag, user_key)) % 100) as u8
+}
+
+/// FNV-1a, so assignments don't change between builds or restarts
+/// (unlike `std`'s randomly seeded hasher).
+pub fn stable_hash(key: &str) -> u32 {
+    let mut hash: u32 = 0x811c_9dc5;
+    for byte in key.bytes() {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    hash
+}
+
+type FlagMap = Arc<HashMap<String, FeatureFlag>>;
+
+/// Loaded flags per tenant (`None` is the primary install)
+static CACHE: LazyLock<RwLock<HashMap<Option<String>, (Instant, FlagMap)>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Whether `name` is on for this user (or for an anonymous visitor when `None`).
+/// Database errors are logged and treated as "off".
+pub async fn enabled(name: &str, user: Option<&SessionUser>) -> bool {
+    let flags = match load().await {
+        Ok(flags) => flags,
+        Err(e) => {
+            warn!("Failed to load feature flags: {}", e);
+            return false;
+        }
+    };
+
+    let Some(flag) = flags.get(name) else {
+        return false;
+    };
+
+    let user_key = user.map(|u| u.id.strip_prefix("person:").unwrap_or(&u.id));
+    let user_orgs = match user {
+        // Only pay for the membership lookup when the flag has overrides
+        Some(u) if flag.has_overrides() => organizations_for(&u.id).await,
+        _ => Vec::new(),
+    };
+
+    let on = flag.evaluate(user_key, &user_orgs);
+    debug!("Feature flag '{}' is {} for {:?}", name, if on { "on" } else { "off" }, user_key);
+    on
+}
+
+/// All flags, sorted by name, bypassing the cache.
+pub async fn list() -> Result<Vec<FeatureFlag>> {
+    let mut flags: Vec<FeatureFlag> = fetch_all().await?.into_values().collect();
+    flags.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(flags)
+}
+
+/// Create a flag (off, 100% rollout) or update its description.
+pub async fn upsert(name: &str, description: &str) -> Result<()> {
+    validate_name(name)?;
+    DB.query("UPSERT type::record('feature_flags', $name) SET description = $description")
+        .bind(("name", name.to_string()))
+        .bind(("description", description.trim().to_string()))
+        .await?
+        .check()?;
+    invalidate();
+    Ok(())
+}
+
+pub async fn set_state(name: &str, enabled: bool, rollout_percent: u8) -> Result<()> {
+    if rollout_percent > 100 {
+        return Err(Error::Validation("Rollout must be between 0 and 100".to_string()));
+    }
+    DB.query(
+        "UPDATE type::record('feature_flags', $name) SET enabled = $enabled, rollout_percent = $rollout",
+    )
+    .bind(("name", name.to_string()))
+    .bind(("enabled", enabled))
+    .bind(("rollout", rollout_percent as i64))
+    .await?
+    .check()?;
+    invalidate();
+    Ok(())
+}
+
+/// Force a flag on (`Some(true)`), off (`Some(false)`) or back to the default
+/// rules (`None`) for one organization.
+pub async fn set_org_override(name: &str, org: RecordId, state: Option<bool>) -> Result<()> {
+    let query = match state {
+        Some(true) => {
+            "UPDATE type::record('feature_flags', $name) SET
+                enabled_orgs = array::union(enabled_orgs, [$org]),
+                disabled_orgs = array::complement(disabled_orgs, [$org])"
+        }
+        Some(false) => {
+            "UPDATE type::record('feature_flags', $name) SET
+                disabled_orgs = array::union(disabled_orgs, [$org]),
+                enabled_orgs = array::complement(enabled_orgs, [$org])"
+        }
+        None => {
+            "UPDATE type::record('feature_flags', $name) SET
+                enabled_orgs = array::complement(enabled_orgs, [$org]),
+                disabled_orgs = array::complement(disabled_orgs, [$org])"
+        }
+    };
+
+    DB.query(query)
+        .bind(("name", name.to_string()))
+        .bind(("org", org))
+        .await?
+        .check()?;
+    invalidate();
+    Ok(())
+}
+
+pub async fn delete(name: &str) -> Result<()> {
+    DB.query("DELETE type::record('feature_flags', $name)")
+        .bind(("name", name.to_string()))
+        .await?
+        .check()?;
+    invalidate();
+    Ok(())
+}
+
+/// Drop the cached flags so the next check reads the table again.
+pub fn invalidate() {
+    CACHE.write().unwrap().remove(&crate::db::current_tenant());
+}
+
+/// Flag names are used in code and URLs: lowercase letters, digits and underscores.
+pub fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Validation(
+            "Flag names may only contain lowercase letters, digits and underscores".to_string(),
+        ))
+    }
+}
+
+async fn load() -> Result<FlagMap> {
+    let tenant = crate::db::current_tenant();
+    if let Some((loaded_at, flags)) = CACHE.read().unwrap().get(&tenant)
+        && loaded_at.elapsed() < CACHE_TTL
+    {
+        return Ok(flags.clone());
+    }
+
+    let flags = Arc::new(fetch_all().await?);
+    CACHE
+        .write()
+        .unwrap()
+        .insert(tenant, (Instant::now(), flags.clone()));
+    Ok(flags)
+}
+
+async fn fetch_all() -> Result<HashMap<String, FeatureFlag>> {
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct Row {
+        name: String,
+        description: Option<String>,
+        enabled: Option<bool>,
+        rollout_percent: Option<i64>,
+        enabled_orgs: Option<Vec<RecordId>>,
+        disabled_orgs: Option<Vec<RecordId>>,
+    }
+
+    let rows: Vec<Row> = DB
+        .query(
+            "SELECT <string> meta::id(id) AS name, description, enabled, rollout_percent,
+                    enabled_orgs, disabled_orgs
+             FROM feature_flags",
+        )
+        .await?
+        .take(0)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let flag = FeatureFlag {
+                description: row.description.unwrap_or_default(),
+                enabled: row.enabled.unwrap_or(false),
+                rollout_percent: row.rollout_percent.unwrap_or(100).clamp(0, 100) as u8,
+                enabled_orgs: row.enabled_orgs.unwrap_or_default().iter().map(|o| o.to_raw_string()).collect(),
+                disabled_orgs: row.disabled_orgs.unwrap_or_default().iter().map(|o| o.to_raw_string()).collect(),
+                name: row.name,
+            };
+            (flag.name.clone(), flag)
+        })
+        .collect())
+}
+
+async fn organizations_for(person_id: &str) -> Vec<String> {
+    let person = if person_id.starts_with("person:") {
+        RecordId::parse_simple(person_id)
+    } else {
+        Ok(RecordId::new("person", person_id))
+    };
+    let Ok(person) = person else {
+        return Vec::new();
+    };
+
+    let result = DB
+        .query("SELECT VALUE out FROM member_of WHERE in = $person AND invitation_status = 'accepted'")
+        .bind(("person", person))
+        .await
+        .and_then(|mut r| r.take::<Vec<RecordId>>(0));
+
+    match result {
+        Ok(orgs) => orgs.iter().map(|o| o.to_raw_string()).collect(),
+        Err(e) => {
+            warn!("Failed to load organizations for feature flag check: {}", e);
+            Vec::new()
+        }
+    }
+}
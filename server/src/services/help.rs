@@ -0,0 +1,262 @@
+//! Help center
+//!
+//! Help articles are markdown files under `server/help/`, built into the
+//! binary so they ship with the code they describe. Each starts with a short
+//! header:
+//!
+//! ```text
+//! ---
+//! title: Guest links
+//! summary: Give people who aren't on SlateHub limited access to a production.
+//! section: Productions
+//! ---
+//! ```
+//!
+//! The file name is the article's slug, which pages link to from their "?"
+//! help links. Searching the help center matches the query's words against
+//! titles, summaries and text, and when the embedding model is loaded also
+//! ranks articles by meaning, so "stop someone deleting files" finds legal
+//! holds.
+
+use std::sync::{LazyLock, OnceLock};
+
+use tracing::{debug, warn};
+
+use crate::services::embedding;
+
+/// Articles as `(slug, source)`, in the order they're listed
+const SOURCES: &[(&str, &str)] = &[
+    (
+        "getting-started",
+        include_str!("../../help/getting-started.md"),
+    ),
+    ("searching", include_str!("../../help/searching.md")),
+    (
+        "profile-visibility",
+        include_str!("../../help/profile-visibility.md"),
+    ),
+    ("productions", include_str!("../../help/productions.md")),
+    ("deliverables", include_str!("../../help/deliverables.md")),
+    ("guest-links", include_str!("../../help/guest-links.md")),
+    ("legal-holds", include_str!("../../help/legal-holds.md")),
+    ("jobs", include_str!("../../help/jobs.md")),
+    ("messages", include_str!("../../help/messages.md")),
+];
+
+/// Search results shown at most
+pub const MAX_RESULTS: usize = 10;
+
+/// How close in meaning an article has to be to show up without sharing a
+/// word with the query
+const MIN_SIMILARITY: f64 = 0.6;
+
+/// Weight of the word matches against meaning when both are available
+const KEYWORD_WEIGHT: f64 = 0.3;
+
+#[derive(Debug, Clone)]
+pub struct Article {
+    pub slug: String,
+    pub title: String,
+    pub summary: String,
+    pub section: String,
+    /// Markdown, after the header
+    pub body: String,
+}
+
+impl Article {
+    /// Parse an article file. `None` when its header is missing a title.
+    pub fn parse(slug: &str, source: &str) -> Option<Self> {
+        let rest = source.trim_start().strip_prefix("---")?;
+        let (header, body) = rest.split_once("\n---")?;
+        let field = |name: &str| {
+            header
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(key, _)| key.trim() == name)
+                .map(|(_, value)| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        let title = field("title");
+        if title.is_empty() {
+            return None;
+        }
+        Some(Self {
+            slug: slug.to_string(),
+            title,
+            summary: field("summary"),
+            section: field("section"),
+            body: body.trim().to_string(),
+        })
+    }
+
+    /// The article's text as sanitized HTML
+    pub fn html(&self) -> String {
+        crate::markdown::render(&self.body)
+    }
+
+    fn embedding_text(&self) -> String {
+        format!("{}. {}\n\n{}", self.title, self.summary, self.body)
+    }
+}
+
+static ARTICLES: LazyLock<Vec<Article>> = LazyLock::new(|| {
+    SOURCES
+        .iter()
+        .filter_map(|(slug, source)| {
+            let article = Article::parse(slug, source);
+            if article.is_none() {
+                warn!("Help article '{}' has no title and is left out", slug);
+            }
+            article
+        })
+        .collect()
+});
+
+/// Every article, in listing order
+pub fn articles() -> &'static [Article] {
+    &ARTICLES
+}
+
+pub fn article(slug: &str) -> Option<&'static Article> {
+    ARTICLES.iter().find(|article| article.slug == slug)
+}
+
+/// Articles grouped by section, sections in order of their first article
+pub fn sections() -> Vec<(&'static str, Vec<&'static Article>)> {
+    let mut sections: Vec<(&str, Vec<&Article>)> = Vec::new();
+    for article in ARTICLES.iter() {
+        match sections
+            .iter_mut()
+            .find(|(name, _)| *name == article.section)
+        {
+            Some((_, list)) => list.push(article),
+            None => sections.push((article.section.as_str(), vec![article])),
+        }
+    }
+    sections
+}
+
+/// Share of the query's words found in the article, with a word in the
+/// title counting three times and one in the summary twice. 0 when none of
+/// them are.
+pub fn keyword_score(article: &Article, query: &str) -> f64 {
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() > 1)
+        .map(str::to_lowercase)
+        .collect();
+    if words.is_empty() {
+        return 0.0;
+    }
+    let title = article.title.to_lowercase();
+    let summary = article.summary.to_lowercase();
+    let body = article.body.to_lowercase();
+    let hits: f64 = words
+        .iter()
+        .map(|word| {
+            if title.contains(word.as_str()) {
+                3.0
+            } else if summary.contains(word.as_str()) {
+                2.0
+            } else if body.contains(word.as_str()) {
+                1.0
+            } else {
+                0.0
+            }
+        })
+        .sum();
+    hits / (words.len() as f64 * 3.0)
+}
+
+/// Article embeddings, in `articles()` order, made the first time the help
+/// center is searched with the model loaded
+static EMBEDDINGS: OnceLock<Vec<Vec<f32>>> = OnceLock::new();
+
+async fn article_embeddings() -> Option<&'static [Vec<f32>]> {
+    if let Some(embeddings) = EMBEDDINGS.get() {
+        return Some(embeddings);
+    }
+    if !embedding::is_initialized() {
+        return None;
+    }
+    let texts = ARTICLES.iter().map(Article::embedding_text).collect();
+    match tokio::task::spawn_blocking(move || embedding::generate_embeddings_batch(texts)).await {
+        Ok(Ok(embeddings)) => {
+            debug!("Embedded {} help articles", embeddings.len());
+            Some(EMBEDDINGS.get_or_init(|| embeddings))
+        }
+        Ok(Err(e)) => {
+            warn!("Failed to embed help articles: {}", e);
+            None
+        }
+        Err(e) => {
+            warn!("Help article embedding task failed: {}", e);
+            None
+        }
+    }
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0f64, 0f64, 0f64);
+    for (&x, &y) in a.iter().zip(b) {
+        let (x, y) = (x as f64, y as f64);
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// Articles matching `query`, best first. Falls back to word matches alone
+/// when the embedding model isn't loaded.
+pub async fn search(query: &str) -> Vec<&'static Article> {
+    let query = query.trim();
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let similarity = match article_embeddings().await {
+        Some(embeddings) => match embedding::generate_embedding_async(query).await {
+            Ok(query_embedding) => Some(
+                embeddings
+                    .iter()
+                    .map(|e| cosine(&query_embedding, e))
+                    .collect::<Vec<_>>(),
+            ),
+            Err(e) => {
+                warn!("Failed to embed help search query: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut scored: Vec<(f64, &Article)> = ARTICLES
+        .iter()
+        .enumerate()
+        .filter_map(|(i, article)| {
+            let keyword = keyword_score(article, query);
+            let score = match &similarity {
+                Some(similarity) if keyword > 0.0 || similarity[i] >= MIN_SIMILARITY => {
+                    similarity[i] + KEYWORD_WEIGHT * keyword
+                }
+                Some(_) => return None,
+                None if keyword > 0.0 => keyword,
+                None => return None,
+            };
+            Some((score, article))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, article)| article)
+        .collect()
+}
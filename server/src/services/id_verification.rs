@@ -0,0 +1,533 @@
+//! Identity verification workflow
+//!
+//! People prove who they are either by uploading an ID document, which goes
+//! to a private S3 prefix and waits in the admin review queue, or by being sent
+//! to a third-party provider that reports back through a signed webhook. Either
+//! way an approved request sets `verification_status = 'identity'`, which is
+//! what shows the verified badge and what the "verified" search filter matches.
+//! Documents are deleted as soon as a request is reviewed.
+
+use async_trait::async_trait;
+use axum::http::HeaderMap;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::LazyLock;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{error, info, warn};
+use ulid::Ulid;
+
+use crate::{
+    config,
+    db::DB,
+    error::Error,
+    models::notification::NotificationModel,
+    record_id_ext::RecordIdExt,
+    services::s3::s3,
+};
+
+/// S3 prefix for ID documents. `/api/media` refuses to serve anything under it.
+pub const PRIVATE_PREFIX: &str = "private/";
+const DOCUMENT_PREFIX: &str = "private/verification";
+
+/// Maximum ID document size in bytes (10MB)
+pub const MAX_DOCUMENT_SIZE: usize = 10 * 1024 * 1024;
+
+/// Accepted document uploads
+pub const DOCUMENT_CONTENT_TYPES: &[&str] = &["image/jpeg", "image/png", "application/pdf"];
+
+/// Accepted kinds of ID document, as (value, label)
+pub const DOCUMENT_KINDS: &[(&str, &str)] = &[
+    ("passport", "Passport"),
+    ("drivers_license", "Driver's license"),
+    ("national_id", "National ID card"),
+];
+
+/// Whether an S3 key must never be served publicly
+pub fn is_private_key(key: &str) -> bool {
+    key.trim_start_matches('/').starts_with(PRIVATE_PREFIX)
+}
+
+// ---------------------------------------------------------------------------
+// Webhook signatures
+// ---------------------------------------------------------------------------
+
+/// HMAC-SHA256 (RFC 2104)
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block_key = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block_key[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block_key[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(block_key.map(|b| b ^ 0x36));
+    inner.update(message);
+    let inner = inner.finalize();
+
+    let mut outer = Sha256::new();
+    outer.update(block_key.map(|b| b ^ 0x5c));
+    outer.update(inner);
+    outer.finalize().into()
+}
+
+/// Check a hex HMAC-SHA256 signature of `body` in constant time
+pub fn verify_signature(secret: &str, body: &[u8], signature_hex: &str) -> bool {
+    let expected: String = hmac_sha256(secret.as_bytes(), body)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let given = signature_hex.trim().trim_start_matches("sha256=").to_ascii_lowercase();
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+// ---------------------------------------------------------------------------
+// Providers
+// ---------------------------------------------------------------------------
+
+/// Outcome a provider reports for one of our requests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderDecision {
+    /// Our `verification_request` key, passed to the provider as `reference`
+    pub reference: String,
+    pub approved: bool,
+    pub reason: Option<String>,
+}
+
+/// A third-party identity verification service
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// Shown to people on the get-verified page
+    fn name(&self) -> &str;
+
+    /// Where to send someone to verify for the given request
+    async fn start_url(&self, reference: &str, return_url: &str) -> Result<String, Error>;
+
+    /// Authenticate and parse a webhook callback
+    fn parse_callback(&self, headers: &HeaderMap, body: &[u8]) -> Result<ProviderDecision, Error>;
+}
+
+/// A hosted provider configured by start URL and shared webhook secret.
+/// It receives `reference` and `return_url` query parameters and POSTs
+/// `{"reference", "status", "reason"}` back, signed in `X-Signature`.
+pub struct HostedProvider {
+    pub name: String,
+    pub start_url: String,
+    pub secret: String,
+}
+
+#[derive(Deserialize)]
+struct HostedCallback {
+    reference: String,
+    status: String,
+    reason: Option<String>,
+}
+
+#[async_trait]
+impl IdentityProvider for HostedProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn start_url(&self, reference: &str, return_url: &str) -> Result<String, Error> {
+        let separator = if self.start_url.contains('?') { '&' } else { '?' };
+        Ok(format!(
+            "{}{}reference={}&return_url={}",
+            self.start_url,
+            separator,
+            urlencoding::encode(reference),
+            urlencoding::encode(return_url)
+        ))
+    }
+
+    fn parse_callback(&self, headers: &HeaderMap, body: &[u8]) -> Result<ProviderDecision, Error> {
+        let signature = headers
+            .get("x-signature")
+            .and_then(|v| v.to_str().ok())
+            .ok_or(Error::Unauthorized)?;
+        if !verify_signature(&self.secret, body, signature) {
+            return Err(Error::Unauthorized);
+        }
+
+        let callback: HostedCallback = serde_json::from_slice(body)
+            .map_err(|e| Error::BadRequest(format!("Invalid callback body: {}", e)))?;
+        let approved = match callback.status.as_str() {
+            "approved" => true,
+            "rejected" => false,
+            other => return Err(Error::BadRequest(format!("Unknown status: {}", other))),
+        };
+        Ok(ProviderDecision {
+            reference: callback.reference,
+            approved,
+            reason: callback.reason,
+        })
+    }
+}
+
+static PROVIDER: LazyLock<Option<Box<dyn IdentityProvider>>> = LazyLock::new(|| {
+    let settings = config::id_verification();
+    match (&settings.provider_start_url, &settings.provider_secret) {
+        (Some(start_url), Some(secret)) => Some(Box::new(HostedProvider {
+            name: settings.provider_name.clone(),
+            start_url: start_url.clone(),
+            secret: secret.clone(),
+        })),
+        (Some(_), None) => {
+            warn!("ID_VERIFICATION_PROVIDER_URL is set without a secret; provider disabled");
+            None
+        }
+        _ => None,
+    }
+});
+
+/// The configured provider, if any
+pub fn provider() -> Option<&'static dyn IdentityProvider> {
+    PROVIDER.as_deref()
+}
+
+// ---------------------------------------------------------------------------
+// Requests
+// ---------------------------------------------------------------------------
+
+/// A person's latest verification request, for the get-verified page
+#[derive(Debug, Clone)]
+pub struct RequestStatus {
+    pub status: String,
+    pub method: String,
+    pub note: Option<String>,
+}
+
+/// A pending document request in the admin review queue
+#[derive(Debug, Clone)]
+pub struct PendingReview {
+    pub id: String,
+    pub username: String,
+    pub name: String,
+    pub document_kind: String,
+    pub submitted: String,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct RequestRow {
+    id: RecordId,
+    person: RecordId,
+    status: String,
+    method: Option<String>,
+    document_key: Option<String>,
+    note: Option<String>,
+}
+
+/// The person's current request, if they have one
+pub async fn status(person: &RecordId) -> Result<Option<RequestStatus>, Error> {
+    let row: Option<RequestRow> = DB
+        .query("SELECT * FROM verification_request WHERE person = $person LIMIT 1")
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load verification request: {}", e)))?
+        .take(0)?;
+
+    Ok(row.map(|row| RequestStatus {
+        status: row.status,
+        method: row.method.unwrap_or_else(|| "document".to_string()),
+        note: row.note,
+    }))
+}
+
+/// Remove a person's earlier request (and its document) so a new one can be
+/// made. Approved requests are kept.
+async fn clear_previous(person: &RecordId) -> Result<(), Error> {
+    let row: Option<RequestRow> = DB
+        .query("SELECT * FROM verification_request WHERE person = $person LIMIT 1")
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load verification request: {}", e)))?
+        .take(0)?;
+
+    let Some(row) = row else {
+        return Ok(());
+    };
+    match row.status.as_str() {
+        "approved" => return Err(Error::Conflict("You're already verified".to_string())),
+        // Someone who abandoned a provider flow can start it again
+        "pending" if row.method.as_deref() != Some("provider") => {
+            return Err(Error::Conflict(
+                "You already have a verification request in review".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    delete_document(row.document_key.as_deref()).await;
+    DB.query("DELETE $id")
+        .bind(("id", row.id))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to clear verification request: {}", e)))?;
+    Ok(())
+}
+
+async fn delete_document(key: Option<&str>) {
+    let Some(key) = key else {
+        return;
+    };
+    match s3() {
+        Ok(s3) => {
+            if let Err(e) = s3.delete_file(key).await {
+                error!("Failed to delete verification document {}: {}", key, e);
+            }
+        }
+        Err(e) => error!("S3 unavailable, verification document {} not deleted: {}", key, e),
+    }
+}
+
+/// Delete a person's requests and any document still held, for account deletion
+pub async fn forget(person: &RecordId) -> Result<(), Error> {
+    let keys: Vec<String> = DB
+        .query("SELECT VALUE document_key FROM verification_request WHERE person = $person AND document_key != NONE")
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load verification requests: {}", e)))?
+        .take(0)?;
+    for key in &keys {
+        delete_document(Some(key)).await;
+    }
+
+    DB.query("DELETE FROM verification_request WHERE person = $person")
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to delete verification requests: {}", e)))?;
+    Ok(())
+}
+
+/// Store an uploaded ID document privately and queue it for admin review
+pub async fn submit_document(
+    person: &RecordId,
+    kind: &str,
+    content_type: &str,
+    data: Bytes,
+) -> Result<(), Error> {
+    if !DOCUMENT_KINDS.iter().any(|(value, _)| *value == kind) {
+        return Err(Error::Validation("Choose the kind of document you're uploading".to_string()));
+    }
+    if !DOCUMENT_CONTENT_TYPES.contains(&content_type) {
+        return Err(Error::Validation("Upload a JPEG, PNG or PDF".to_string()));
+    }
+    if data.is_empty() {
+        return Err(Error::Validation("Choose a file to upload".to_string()));
+    }
+    if data.len() > MAX_DOCUMENT_SIZE {
+        return Err(Error::Validation("File too large. Maximum size is 10MB".to_string()));
+    }
+
+    clear_previous(person).await?;
+
+    let extension = match content_type {
+        "image/png" => "png",
+        "application/pdf" => "pdf",
+        _ => "jpg",
+    };
+    let key = format!(
+        "{}/{}/{}.{}",
+        DOCUMENT_PREFIX,
+        person.key_string(),
+        Ulid::new(),
+        extension
+    );
+    s3()?.upload_file(&key, data, content_type).await?;
+
+    if let Err(e) = DB
+        .query(
+            "CREATE verification_request SET person = $person, status = 'pending', method = 'document',
+                document_kind = $kind, document_key = $key, created_at = time::now()",
+        )
+        .bind(("person", person.clone()))
+        .bind(("kind", kind.to_string()))
+        .bind(("key", key.clone()))
+        .await
+    {
+        delete_document(Some(&key)).await;
+        return Err(Error::Database(format!("Failed to create verification request: {}", e)));
+    }
+
+    info!("{} submitted an ID document for review", person.display());
+    Ok(())
+}
+
+/// Open a provider request and return the URL to send the person to
+pub async fn start_with_provider(person: &RecordId) -> Result<String, Error> {
+    let provider = provider().ok_or(Error::NotFound)?;
+    clear_previous(person).await?;
+
+    let id = RecordId::new("verification_request", Ulid::new().to_string());
+    DB.query(
+        "CREATE $id SET person = $person, status = 'pending', method = 'provider',
+            provider = $provider, created_at = time::now()",
+    )
+    .bind(("id", id.clone()))
+    .bind(("person", person.clone()))
+    .bind(("provider", provider.name().to_string()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to create verification request: {}", e)))?;
+
+    let return_url = format!("{}/get-verified", config::app_url());
+    provider.start_url(&id.key_string(), &return_url).await
+}
+
+/// Apply a provider's webhook decision
+pub async fn apply_provider_decision(decision: &ProviderDecision) -> Result<(), Error> {
+    let id = RecordId::new("verification_request", decision.reference.as_str());
+    let row: Option<RequestRow> = DB
+        .query("SELECT * FROM ONLY $id")
+        .bind(("id", id))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load verification request: {}", e)))?
+        .take(0)?;
+
+    let row = row.ok_or(Error::NotFound)?;
+    if row.method.as_deref() != Some("provider") || row.status != "pending" {
+        // Providers retry webhooks; a decided request is acknowledged as-is
+        return Ok(());
+    }
+    decide(row, decision.approved, decision.reason.clone(), None).await
+}
+
+/// Pending document requests, oldest first
+pub async fn pending_reviews() -> Result<Vec<PendingReview>, Error> {
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct Row {
+        id: RecordId,
+        username: Option<String>,
+        name: Option<String>,
+        document_kind: Option<String>,
+        created_at: DateTime<Utc>,
+    }
+
+    let rows: Vec<Row> = DB
+        .query(
+            "SELECT id, person.username AS username, person.name AS name, document_kind, created_at
+             FROM verification_request WHERE status = 'pending' AND method = 'document'
+             ORDER BY created_at ASC",
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list verification requests: {}", e)))?
+        .take(0)?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let username = row.username?;
+            let kind = row.document_kind.unwrap_or_default();
+            Some(PendingReview {
+                id: row.id.key_string(),
+                name: row.name.unwrap_or_else(|| username.clone()),
+                username,
+                document_kind: DOCUMENT_KINDS
+                    .iter()
+                    .find(|(value, _)| *value == kind)
+                    .map(|(_, label)| label.to_string())
+                    .unwrap_or(kind),
+                submitted: row.created_at.format("%b %d, %Y %H:%M").to_string(),
+            })
+        })
+        .collect())
+}
+
+async fn load_pending(request_id: &str) -> Result<RequestRow, Error> {
+    let row: Option<RequestRow> = DB
+        .query("SELECT * FROM ONLY $id")
+        .bind(("id", RecordId::new("verification_request", request_id)))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load verification request: {}", e)))?
+        .take(0)?;
+    row.filter(|row| row.status == "pending").ok_or(Error::NotFound)
+}
+
+/// The uploaded document for a pending request, for admin review only
+pub async fn document(request_id: &str) -> Result<(Bytes, String), Error> {
+    let row = load_pending(request_id).await?;
+    let key = row.document_key.ok_or(Error::NotFound)?;
+    s3()?.download_file(&key).await
+}
+
+/// Admin decision on a pending document request
+pub async fn review(
+    request_id: &str,
+    reviewer: &RecordId,
+    approve: bool,
+    note: Option<String>,
+) -> Result<(), Error> {
+    let row = load_pending(request_id).await?;
+    decide(row, approve, note, Some(reviewer)).await
+}
+
+async fn decide(
+    row: RequestRow,
+    approve: bool,
+    note: Option<String>,
+    reviewer: Option<&RecordId>,
+) -> Result<(), Error> {
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+    let status = if approve { "approved" } else { "rejected" };
+
+    let mut query = String::from(
+        "UPDATE $id SET status = $status, note = $note, reviewed_by = $reviewer,
+            reviewed_at = time::now(), document_key = NONE;",
+    );
+    if approve {
+        query.push_str("UPDATE $person SET verification_status = 'identity';");
+    }
+    DB.query(&query)
+        .bind(("id", row.id.clone()))
+        .bind(("person", row.person.clone()))
+        .bind(("status", status))
+        .bind(("note", note.clone()))
+        .bind(("reviewer", reviewer.cloned()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to record verification decision: {}", e)))?;
+
+    // The document has done its job either way
+    delete_document(row.document_key.as_deref()).await;
+
+    info!(
+        "Verification request {} for {} {}",
+        row.id.display(),
+        row.person.display(),
+        status
+    );
+
+    let (title, message) = if approve {
+        (
+            "You're verified",
+            "Your identity has been verified. The verified badge now shows on your profile.".to_string(),
+        )
+    } else {
+        (
+            "Verification not approved",
+            match &note {
+                Some(note) => format!("We couldn't verify your identity: {}. You can try again.", note),
+                None => "We couldn't verify your identity. You can try again.".to_string(),
+            },
+        )
+    };
+    if let Err(e) = NotificationModel::new()
+        .create(
+            &row.person.to_raw_string(),
+            "verification",
+            title,
+            &message,
+            Some("/get-verified"),
+            Some(&row.id.to_raw_string()),
+        )
+        .await
+    {
+        error!("Failed to notify {} about verification: {}", row.person.display(), e);
+    }
+    Ok(())
+}
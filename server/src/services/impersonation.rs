@@ -0,0 +1,267 @@
+//! Admin impersonation ("view as")
+//!
+//! Support staff can sign in as someone to reproduce an issue they reported.
+//! A session is started from the admin people list with a reason, lasts
+//! `IMPERSONATION_SESSION_MINS` and is named in the session cookie's JWT. Every
+//! request made during it is written to `impersonation_action`. Sign-in
+//! details, organization SSO settings, OAuth apps and grants, account
+//! deletion, data export and the admin area stay off limits while
+//! impersonating. Exiting signs the admin back in as themselves.
+
+use axum::http::Method;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::error;
+
+use crate::config::impersonation;
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::models::person::Impersonator;
+use crate::record_id_ext::RecordIdExt;
+use crate::services::login_security::ClientInfo;
+
+pub const MAX_REASON_CHARS: usize = 500;
+
+/// Longest path kept per audited request
+const MAX_PATH_CHARS: usize = 1000;
+
+/// Check the reason an admin gives for starting a session
+pub fn clean_reason(reason: &str) -> Result<String> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(Error::Validation(
+            "Give a reason for viewing as this person, like the support ticket".to_string(),
+        ));
+    }
+    if reason.chars().count() > MAX_REASON_CHARS {
+        return Err(Error::Validation(format!(
+            "The reason can be up to {} characters",
+            MAX_REASON_CHARS
+        )));
+    }
+    Ok(reason.to_string())
+}
+
+/// Requests refused while impersonating: the admin area, and anything that
+/// changes how the person (or their organization's members) signs in, hands
+/// over their data or access to it, or deletes them. Accepting the terms and changing where
+/// WhatsApp messages go are also left to the person themselves.
+pub fn is_blocked(method: &Method, path: &str) -> bool {
+    if path == "/admin" || path.starts_with("/admin/") || path.starts_with("/api/admin/") {
+        return true;
+    }
+    // Apps and the tokens they're issued outlive the session
+    if path == "/account/apps"
+        || path.starts_with("/account/apps/")
+        || path.starts_with("/account/connected-apps/")
+    {
+        return true;
+    }
+    if path == "/account/export" {
+        return true;
+    }
+    if *method == Method::GET {
+        return false;
+    }
+    matches!(
+        path,
+        "/account/change-password"
+            | "/account/change-email"
+            | "/account/change-username"
+            | "/account/delete"
+            | "/account/guardian-link"
+            | "/account/guardian"
+            | "/verify-email"
+            | "/legal/accept"
+            | "/account/whatsapp"
+            | "/oauth/authorize"
+    ) || is_sso_settings(path)
+}
+
+/// `/orgs/{slug}/sso` and everything under it: single sign-on, verified
+/// domains and SCIM tokens
+fn is_sso_settings(path: &str) -> bool {
+    let Some((slug, rest)) = path.strip_prefix("/orgs/").and_then(|p| p.split_once('/')) else {
+        return false;
+    };
+    !slug.is_empty() && (rest == "sso" || rest.starts_with("sso/"))
+}
+
+/// Whether a request is written to the audit trail; static assets are not
+pub fn is_audited(path: &str) -> bool {
+    !path.starts_with("/static/") && !path.starts_with("/favicon")
+}
+
+/// Start a session for `admin` to view the site as `person`
+pub async fn start(
+    admin: &RecordId,
+    admin_username: &str,
+    person: &RecordId,
+    person_username: &str,
+    reason: &str,
+    client: &ClientInfo,
+) -> Result<RecordId> {
+    let reason = clean_reason(reason)?;
+    let expires_at = Utc::now() + Duration::minutes(impersonation().session_mins);
+
+    let id: Option<RecordId> = DB
+        .query(
+            "(CREATE impersonation_session SET admin = $admin, admin_username = $admin_username,
+                 person = $person, person_username = $person_username, reason = $reason,
+                 ip = $ip, user_agent = $user_agent, expires_at = $expires_at
+                 RETURN id)[0].id",
+        )
+        .bind(("admin", admin.clone()))
+        .bind(("admin_username", admin_username.to_string()))
+        .bind(("person", person.clone()))
+        .bind(("person_username", person_username.to_string()))
+        .bind(("reason", reason))
+        .bind(("ip", client.ip.clone()))
+        .bind(("user_agent", client.user_agent.clone()))
+        .bind(("expires_at", expires_at))
+        .await?
+        .take(0)?;
+
+    id.ok_or_else(|| Error::Internal("Failed to start impersonation session".to_string()))
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct LiveSession {
+    admin: RecordId,
+    admin_username: String,
+}
+
+/// The admin behind a session, while it is still running and belongs to `person`
+pub async fn active(session_id: &str, person_id: &str) -> Option<Impersonator> {
+    let session = RecordId::parse_simple(session_id).ok()?;
+    let person = RecordId::parse_simple(person_id).ok()?;
+    let live: Option<LiveSession> = DB
+        .query(
+            "(SELECT admin, admin_username FROM $session
+                 WHERE person = $person AND ended_at = NONE AND expires_at > time::now())[0]",
+        )
+        .bind(("session", session))
+        .bind(("person", person))
+        .await
+        .and_then(|mut r| r.take(0))
+        .unwrap_or_else(|e| {
+            error!(
+                "Failed to look up impersonation session {}: {}",
+                session_id, e
+            );
+            None
+        });
+
+    live.map(|s| Impersonator {
+        session_id: session_id.to_string(),
+        admin_id: s.admin.to_raw_string(),
+        admin_username: s.admin_username,
+    })
+}
+
+/// Write one request to the session's audit trail, in the background
+pub fn record(session_id: &str, method: &Method, path: &str, status: u16) {
+    let Ok(session) = RecordId::parse_simple(session_id) else {
+        return;
+    };
+    let method = method.to_string();
+    let path: String = path.chars().take(MAX_PATH_CHARS).collect();
+    crate::db::spawn(async move {
+        let result = DB
+            .query(
+                "CREATE impersonation_action SET session = $session, method = $method,
+                     path = $path, status = $status",
+            )
+            .bind(("session", session))
+            .bind(("method", method))
+            .bind(("path", path))
+            .bind(("status", status as i64))
+            .await;
+        if let Err(e) = result {
+            error!("Failed to record impersonation action: {}", e);
+        }
+    });
+}
+
+/// End a session; later requests with its token are treated as signed out
+pub async fn end(session_id: &str) -> Result<()> {
+    let session = RecordId::parse_simple(session_id)
+        .map_err(|_| Error::BadRequest("Invalid session".to_string()))?;
+    DB.query("UPDATE $session SET ended_at = time::now() WHERE ended_at = NONE")
+        .bind(("session", session))
+        .await?;
+    Ok(())
+}
+
+/// A session as listed for admins
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct SessionSummary {
+    pub id: RecordId,
+    pub admin_username: String,
+    pub person_username: String,
+    pub reason: String,
+    pub ip: String,
+    pub user_agent: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    pub action_count: i64,
+}
+
+impl SessionSummary {
+    /// When the session stopped, or `None` while it is still running
+    pub fn finished_at(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        self.ended_at
+            .or_else(|| (self.expires_at <= now).then_some(self.expires_at))
+    }
+}
+
+const SUMMARY_FIELDS: &str = "id, admin_username, person_username, reason, ip, user_agent,
+    started_at, expires_at, ended_at,
+    (SELECT count() FROM impersonation_action WHERE session = $parent.id GROUP ALL)[0].count ?? 0
+        AS action_count";
+
+/// Most recent sessions first
+pub async fn recent(limit: usize) -> Result<Vec<SessionSummary>> {
+    let sessions: Vec<SessionSummary> = DB
+        .query(format!(
+            "SELECT {} FROM impersonation_session ORDER BY started_at DESC LIMIT $limit",
+            SUMMARY_FIELDS
+        ))
+        .bind(("limit", limit as i64))
+        .await?
+        .take(0)?;
+    Ok(sessions)
+}
+
+pub async fn get(session: &RecordId) -> Result<Option<SessionSummary>> {
+    let summary: Option<SessionSummary> = DB
+        .query(format!("(SELECT {} FROM $session)[0]", SUMMARY_FIELDS))
+        .bind(("session", session.clone()))
+        .await?
+        .take(0)?;
+    Ok(summary)
+}
+
+/// A request made during a session
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct Action {
+    pub method: String,
+    pub path: String,
+    pub status: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Everything done in a session, in order
+pub async fn actions(session: &RecordId) -> Result<Vec<Action>> {
+    let actions: Vec<Action> = DB
+        .query(
+            "SELECT method, path, status, created_at FROM impersonation_action
+                 WHERE session = $session ORDER BY created_at ASC",
+        )
+        .bind(("session", session.clone()))
+        .await?
+        .take(0)?;
+    Ok(actions)
+}
@@ -134,7 +134,7 @@ impl InvitationService {
                         let url = signup_url.clone();
                         let msg = message.map(|m| m.to_string());
 
-                        tokio::spawn(async move {
+                        crate::db::spawn(async move {
                             if let Err(e) = email_service
                                 .send_invitation_email(&to_email, &org, &inviter, &url, msg.as_deref())
                                 .await
@@ -269,7 +269,7 @@ impl InvitationService {
                         let url = signup_url;
                         let msg = message.map(|m| m.to_string());
 
-                        tokio::spawn(async move {
+                        crate::db::spawn(async move {
                             if let Err(e) = email_service
                                 .send_invitation_email(&to_email, &prod, &inviter, &url, msg.as_deref())
                                 .await
@@ -0,0 +1,380 @@
+//! Failed-login lockout and new-device detection
+//!
+//! Every sign-in attempt is recorded in `login_attempt`. An account is locked
+//! for `LOGIN_LOCKOUT_MINS` once it collects `LOGIN_MAX_FAILURES` failures in
+//! the window since its last successful sign-in, and an IP is blocked the same
+//! way after `LOGIN_MAX_FAILURES_PER_IP` failures across all accounts. The
+//! account owner is emailed when their account gets locked.
+//!
+//! Successful sign-ins are checked against the person's `known_device` rows
+//! (user agent + network prefix). A sign-in from an unrecognised device has to
+//! be confirmed with an emailed code before a session is issued. The first
+//! device a person signs in from is trusted without a code.
+
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::{Extensions, HeaderMap, header, request::Parts};
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{error, info, warn};
+
+use crate::config::login_security;
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::services::email::EmailService;
+use crate::services::flags::stable_hash;
+
+/// Where a sign-in attempt came from
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub ip: String,
+    pub user_agent: Option<String>,
+}
+
+impl ClientInfo {
+    /// Client of a request the server accepted. None only when the router is
+    /// served without connect info (the server always is).
+    pub fn from_parts(headers: &HeaderMap, extensions: &Extensions) -> Option<Self> {
+        let ConnectInfo(peer) = extensions.get::<ConnectInfo<SocketAddr>>()?;
+        Some(Self::new(
+            peer.ip(),
+            headers,
+            login_security().trusted_proxy_hops,
+        ))
+    }
+
+    /// Client behind `trusted_hops` reverse proxies, the last of which
+    /// connected from `peer` (see `client_ip`)
+    pub fn new(peer: IpAddr, headers: &HeaderMap, trusted_hops: usize) -> Self {
+        Self {
+            ip: client_ip(peer, headers, trusted_hops).to_string(),
+            user_agent: headers
+                .get(header::USER_AGENT)
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.chars().take(512).collect()),
+        }
+    }
+
+    /// Device fingerprint: user agent hash plus the network the IP belongs to,
+    /// so a DHCP renewal on the same network doesn't count as a new device.
+    pub fn fingerprint(&self) -> String {
+        format!(
+            "{:08x}:{}",
+            stable_hash(self.user_agent.as_deref().unwrap_or("")),
+            network_prefix(&self.ip)
+        )
+    }
+}
+
+impl<S> FromRequestParts<S> for ClientInfo
+where
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self> {
+        Self::from_parts(&parts.headers, &parts.extensions)
+            .ok_or_else(|| Error::Internal("Client address unavailable".to_string()))
+    }
+}
+
+/// Address a request came from. Without trusted proxies that's the socket
+/// peer, and `X-Forwarded-For` is ignored since the client writes it. Each
+/// trusted proxy appends the address it was connected from, so the client is
+/// `trusted_hops` entries from the right. Entries a trusted proxy didn't
+/// write, or that aren't addresses, are never used: a short or mangled header
+/// gives the furthest address the proxies vouch for.
+pub fn client_ip(peer: IpAddr, headers: &HeaderMap, trusted_hops: usize) -> IpAddr {
+    let forwarded: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .collect();
+
+    let mut ip = peer;
+    for entry in forwarded.iter().rev().take(trusted_hops) {
+        match entry.parse() {
+            Ok(hop) => ip = hop,
+            Err(_) => break,
+        }
+    }
+    ip
+}
+
+/// /24 for IPv4, /64 for IPv6, the raw value for anything else
+pub fn network_prefix(ip: &str) -> String {
+    match ip.parse::<std::net::IpAddr>() {
+        Ok(std::net::IpAddr::V4(v4)) => {
+            let o = v4.octets();
+            format!("{}.{}.{}.0/24", o[0], o[1], o[2])
+        }
+        Ok(std::net::IpAddr::V6(v6)) => {
+            let s = v6.segments();
+            format!("{:x}:{:x}:{:x}:{:x}::/64", s[0], s[1], s[2], s[3])
+        }
+        Err(_) => ip.to_string(),
+    }
+}
+
+/// A sign-in that is currently refused
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lockout {
+    pub until: DateTime<Utc>,
+}
+
+impl Lockout {
+    pub fn minutes_remaining(&self) -> i64 {
+        ((self.until - Utc::now()).num_seconds() + 59).max(60) / 60
+    }
+
+    pub fn message(&self) -> String {
+        let minutes = self.minutes_remaining();
+        format!(
+            "Too many failed sign-in attempts. Please try again in {} minute{}, or reset your password.",
+            minutes,
+            if minutes == 1 { "" } else { "s" }
+        )
+    }
+}
+
+/// Lockout end time for a run of failures: locked while `failures >= max`,
+/// until `lockout` after the most recent failure.
+pub fn lockout_until(
+    failures: u32,
+    max: u32,
+    last_failure: Option<DateTime<Utc>>,
+    lockout: Duration,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if max == 0 || failures < max {
+        return None;
+    }
+    let until = last_failure? + lockout;
+    (until > now).then_some(until)
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct FailureStats {
+    failures: i64,
+    last_failure: Option<DateTime<Utc>>,
+}
+
+/// Whether sign-in for this identifier or from this IP is currently locked.
+pub async fn check_lockout(identifier: &str, client: &ClientInfo) -> Result<Option<Lockout>> {
+    let config = login_security();
+    let now = Utc::now();
+    let lockout = Duration::minutes(config.lockout_mins);
+
+    let mut response = DB
+        .query(
+            "LET $last_success = (SELECT VALUE created_at FROM login_attempt
+                 WHERE identifier = $identifier AND success = true
+                 ORDER BY created_at DESC LIMIT 1)[0];
+             SELECT count() AS failures, math::max(created_at) AS last_failure FROM login_attempt
+                 WHERE identifier = $identifier AND success = false AND created_at > $since
+                 AND ($last_success = NONE OR created_at > $last_success)
+                 GROUP ALL;
+             SELECT count() AS failures, math::max(created_at) AS last_failure FROM login_attempt
+                 WHERE ip = $ip AND success = false AND created_at > $since
+                 GROUP ALL;",
+        )
+        .bind(("identifier", normalize(identifier)))
+        .bind(("ip", client.ip.clone()))
+        .bind(("since", window_start()))
+        .await?;
+
+    let account: Option<FailureStats> = response.take(1)?;
+    let ip: Option<FailureStats> = response.take(2)?;
+
+    let until = [
+        account.and_then(|s| {
+            lockout_until(s.failures as u32, config.max_failures, s.last_failure, lockout, now)
+        }),
+        ip.and_then(|s| {
+            lockout_until(s.failures as u32, config.max_failures_per_ip, s.last_failure, lockout, now)
+        }),
+    ]
+    .into_iter()
+    .flatten()
+    .max();
+
+    Ok(until.map(|until| Lockout { until }))
+}
+
+/// Record a sign-in attempt. A failure that tips an account into lockout emails
+/// the account owner.
+pub async fn record_attempt(
+    identifier: &str,
+    person: Option<&RecordId>,
+    client: &ClientInfo,
+    success: bool,
+) {
+    let identifier = normalize(identifier);
+    let result = DB
+        .query(
+            "CREATE login_attempt SET identifier = $identifier, person = $person, ip = $ip,
+                 user_agent = $user_agent, success = $success",
+        )
+        .bind(("identifier", identifier.clone()))
+        .bind(("person", person.cloned()))
+        .bind(("ip", client.ip.clone()))
+        .bind(("user_agent", client.user_agent.clone()))
+        .bind(("success", success))
+        .await;
+
+    if let Err(e) = result {
+        error!("Failed to record login attempt: {}", e);
+        return;
+    }
+
+    if success {
+        return;
+    }
+
+    // Notify on the failure that starts the lockout, not on every one after it
+    if just_locked(&identifier).await {
+        warn!("Account '{}' locked after repeated failed sign-ins from {}", identifier, client.ip);
+        let lockout = Lockout {
+            until: Utc::now() + Duration::minutes(login_security().lockout_mins),
+        };
+        notify_lockout(&identifier, client.clone(), lockout);
+    }
+}
+
+/// Failures older than this no longer count. Never shorter than the lockout
+/// itself, so a lockout can't expire early because its failures aged out.
+fn window_start() -> DateTime<Utc> {
+    let config = login_security();
+    Utc::now() - Duration::minutes(config.failure_window_mins.max(config.lockout_mins))
+}
+
+/// True when the account's failure count since its last success is exactly the limit
+async fn just_locked(identifier: &str) -> bool {
+    let config = login_security();
+    if config.max_failures == 0 {
+        return false;
+    }
+    let result: Option<i64> = DB
+        .query(
+            "LET $last_success = (SELECT VALUE created_at FROM login_attempt
+                 WHERE identifier = $identifier AND success = true
+                 ORDER BY created_at DESC LIMIT 1)[0];
+             (SELECT count() AS failures FROM login_attempt
+                 WHERE identifier = $identifier AND success = false AND created_at > $since
+                 AND ($last_success = NONE OR created_at > $last_success)
+                 GROUP ALL)[0].failures;",
+        )
+        .bind(("identifier", identifier.to_string()))
+        .bind(("since", window_start()))
+        .await
+        .and_then(|mut r| r.take(1))
+        .unwrap_or(None);
+
+    result == Some(config.max_failures as i64)
+}
+
+fn notify_lockout(identifier: &str, client: ClientInfo, lockout: Lockout) {
+    let identifier = identifier.to_string();
+    crate::db::spawn(async move {
+        let person = match crate::models::person::Person::find_by_email(&identifier).await {
+            Ok(Some(p)) => Some(p),
+            _ => crate::models::person::Person::find_by_username(&identifier).await.ok().flatten(),
+        };
+        let Some(person) = person else {
+            return;
+        };
+
+        match EmailService::from_env() {
+            Ok(email) => {
+                if let Err(e) = email
+                    .send_lockout_email(
+                        &person.email,
+                        Some(&person.get_display_name()),
+                        &client.ip,
+                        lockout.minutes_remaining(),
+                    )
+                    .await
+                {
+                    error!("Failed to send lockout email: {}", e);
+                }
+            }
+            Err(e) => warn!("Email service unavailable, lockout not notified: {}", e),
+        }
+    });
+}
+
+/// Whether a successful sign-in from this client needs a code first. A person
+/// with no known devices yet has this one registered and trusted.
+pub async fn requires_device_verification(person: &RecordId, client: &ClientInfo) -> Result<bool> {
+    if !login_security().verify_new_devices {
+        return Ok(false);
+    }
+
+    let mut response = DB
+        .query(
+            "SELECT count() AS total, count(fingerprint = $fingerprint) AS matching
+             FROM known_device WHERE person = $person GROUP ALL",
+        )
+        .bind(("person", person.clone()))
+        .bind(("fingerprint", client.fingerprint()))
+        .await?;
+
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct Counts {
+        total: i64,
+        matching: i64,
+    }
+    let counts: Option<Counts> = response.take(0)?;
+
+    match counts {
+        Some(c) if c.matching > 0 => {
+            remember_device(person, client).await?;
+            Ok(false)
+        }
+        Some(c) if c.total > 0 => Ok(true),
+        _ => {
+            remember_device(person, client).await?;
+            Ok(false)
+        }
+    }
+}
+
+/// Trust this client for future sign-ins (and refresh `last_seen` if already known)
+pub async fn remember_device(person: &RecordId, client: &ClientInfo) -> Result<()> {
+    DB.query(
+        "UPSERT known_device SET person = $person, fingerprint = $fingerprint,
+             user_agent = $user_agent, ip = $ip
+         WHERE person = $person AND fingerprint = $fingerprint",
+    )
+    .bind(("person", person.clone()))
+    .bind(("fingerprint", client.fingerprint()))
+    .bind(("user_agent", client.user_agent.clone()))
+    .bind(("ip", client.ip.clone()))
+    .await?
+    .check()?;
+
+    info!("Registered sign-in device for {}", crate::record_id_ext::RecordIdExt::display(person));
+    Ok(())
+}
+
+/// Delete a person's sign-in history and trusted devices (account
+/// deletion), including failed attempts against their email or username
+pub async fn forget(person: &RecordId) -> Result<()> {
+    DB.query(
+        "LET $identifiers = [string::lowercase($person.email), string::lowercase($person.username)];
+         DELETE login_attempt WHERE person = $person OR identifier IN $identifiers;
+         DELETE known_device WHERE person = $person;",
+    )
+    .bind(("person", person.clone()))
+    .await?
+    .check()?;
+    Ok(())
+}
+
+fn normalize(identifier: &str) -> String {
+    identifier.trim().to_lowercase()
+}
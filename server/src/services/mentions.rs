@@ -0,0 +1,258 @@
+//! @mentions in messages, daily report notes and self-tape comments
+//!
+//! `@username` anywhere in the text links to that person's profile when it's
+//! shown. Where the text belongs to a production or organization (its
+//! "scope"), the mentioned people who are accepted members of it are also
+//! notified, and `/api/mentions` suggests members as the writer types `@`.
+//! Mentioning someone outside the team does nothing beyond the link, so a
+//! mention can't be used to reach strangers or to show them a page they can't
+//! open.
+//!
+//! Direct messages are only linked: the one person who can read a
+//! conversation already gets a notification for every message in it.
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, warn};
+
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::models::block::BlockModel;
+use crate::models::notification::NotificationModel;
+use crate::models::organization::OrganizationModel;
+use crate::models::person::SessionUser;
+use crate::models::production::ProductionModel;
+use crate::record_id_ext::RecordIdExt;
+
+/// Suggestions returned per lookup
+pub const MAX_SUGGESTIONS: usize = 8;
+
+/// `@` at the start or after anything that can't be part of an email address
+/// or a URL, then a username. Trailing periods are punctuation, not part of
+/// the name.
+static MENTION_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^A-Za-z0-9_@./])@([A-Za-z0-9_.]+)").unwrap());
+
+/// The username a mention refers to, lowercased, and the periods after it
+fn username(raw: &str) -> Option<(String, &str)> {
+    let name = raw.trim_end_matches('.');
+    let valid = (3..=30).contains(&name.len()) && !name.starts_with('.') && !name.contains("..");
+    valid.then(|| (name.to_lowercase(), &raw[name.len()..]))
+}
+
+/// Usernames mentioned in `text`, lowercased, in the order first mentioned
+pub fn parse(text: &str) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    for caps in MENTION_RE.captures_iter(text) {
+        if let Some((name, _)) = username(&caps[2])
+            && !found.contains(&name)
+        {
+            found.push(name);
+        }
+    }
+    found
+}
+
+/// Usernames mentioned in `after` that weren't in `before`, so editing a
+/// note only notifies the people newly mentioned
+pub fn added(before: &str, after: &str) -> Vec<String> {
+    let before = parse(before);
+    parse(after)
+        .into_iter()
+        .filter(|name| !before.contains(name))
+        .collect()
+}
+
+fn link_text(text: &str) -> String {
+    MENTION_RE
+        .replace_all(text, |caps: &Captures| match username(&caps[2]) {
+            Some((name, rest)) => format!(
+                "{}<a href=\"/{}\" data-role=\"mention\">@{}</a>{}",
+                &caps[1],
+                name,
+                &caps[2][..caps[2].len() - rest.len()],
+                rest
+            ),
+            None => caps[0].to_string(),
+        })
+        .into_owned()
+}
+
+/// Link the mentions in already-sanitized HTML, leaving tags and the text of
+/// existing links alone
+pub fn link_html(html: &str) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut in_link = false;
+    let mut rest = html;
+    while !rest.is_empty() {
+        if rest.starts_with('<') {
+            let end = rest.find('>').map_or(rest.len(), |i| i + 1);
+            let tag = rest[1..end].trim_start().to_ascii_lowercase();
+            if tag.starts_with("a ") || tag.starts_with("a>") {
+                in_link = true;
+            } else if tag.starts_with("/a>") || tag.starts_with("/a ") {
+                in_link = false;
+            }
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            if in_link {
+                out.push_str(&rest[..end]);
+            } else {
+                out.push_str(&link_text(&rest[..end]));
+            }
+            rest = &rest[end..];
+        }
+    }
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Escape plain text for HTML and link its mentions
+pub fn link(text: &str) -> String {
+    link_html(&escape(text))
+}
+
+/// A team member offered when typing `@`
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct MentionCandidate {
+    pub username: String,
+    pub name: String,
+    pub avatar: Option<String>,
+}
+
+/// A production (`kind` "production") or organization ("org") by slug,
+/// as named in `/api/mentions` query parameters
+pub async fn scope(kind: &str, slug: &str) -> Result<RecordId> {
+    match kind {
+        "production" => Ok(ProductionModel::get_by_slug(slug).await?.id),
+        "org" => Ok(OrganizationModel::new().get_by_slug(slug).await?.id),
+        _ => Err(Error::BadRequest(format!(
+            "Unknown mention scope: {}",
+            kind
+        ))),
+    }
+}
+
+/// Whether a person has accepted a place in the production or organization
+pub async fn is_member(scope: &RecordId, person_id: &str) -> Result<bool> {
+    let person = RecordId::parse_simple(person_id).map_err(|e| Error::BadRequest(e.to_string()))?;
+    let count: Option<i64> = DB
+        .query(
+            "SELECT VALUE count() FROM member_of
+             WHERE in = $person AND out = $scope AND invitation_status = 'accepted'
+             GROUP ALL",
+        )
+        .bind(("person", person))
+        .bind(("scope", scope.clone()))
+        .await?
+        .take(0)?;
+    Ok(count.unwrap_or(0) > 0)
+}
+
+/// Members whose username or name starts with `prefix`
+pub async fn candidates(scope: &RecordId, prefix: &str) -> Result<Vec<MentionCandidate>> {
+    let prefix = prefix.trim().trim_start_matches('@').to_lowercase();
+    Ok(DB
+        .query(
+            "SELECT in.username AS username, in.name ?? in.username AS name,
+                    in.profile.avatar AS avatar
+             FROM member_of
+             WHERE out = $scope AND invitation_status = 'accepted'
+                 AND type::table(in) = 'person'
+                 AND (string::starts_with(in.username, $prefix)
+                     OR string::starts_with(string::lowercase(in.name ?? ''), $prefix))
+             ORDER BY username ASC
+             LIMIT $limit",
+        )
+        .bind(("scope", scope.clone()))
+        .bind(("prefix", prefix))
+        .bind(("limit", MAX_SUGGESTIONS as i64))
+        .await?
+        .take(0)?)
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct Mentioned {
+    id: RecordId,
+}
+
+fn preview(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(100) {
+        Some((i, _)) => format!("{}…", &text[..i]),
+        None => text.to_string(),
+    }
+}
+
+/// Notify the members of `scope` mentioned in `usernames`, except the author
+/// and anyone who has muted them. `place` finishes "… mentioned you", e.g.
+/// "in the Day 3 report for Night Shift". Failures are logged, never returned:
+/// the note or comment was saved either way.
+pub async fn notify(
+    scope: &RecordId,
+    usernames: &[String],
+    author: &SessionUser,
+    place: &str,
+    text: &str,
+    link: &str,
+) {
+    if usernames.is_empty() {
+        return;
+    }
+    let Ok(author_id) = RecordId::parse_simple(&author.id) else {
+        return;
+    };
+    let mentioned: Vec<Mentioned> = match DB
+        .query(
+            "SELECT in AS id FROM member_of
+             WHERE out = $scope AND invitation_status = 'accepted'
+                 AND type::table(in) = 'person' AND in.username IN $usernames",
+        )
+        .bind(("scope", scope.clone()))
+        .bind(("usernames", usernames.to_vec()))
+        .await
+        .and_then(|mut r| r.take(0))
+    {
+        Ok(mentioned) => mentioned,
+        Err(e) => {
+            warn!("Failed to look up mentioned members: {}", e);
+            return;
+        }
+    };
+
+    let author_name = if author.name.is_empty() {
+        &author.username
+    } else {
+        &author.name
+    };
+    let title = format!("{} mentioned you {}", author_name, place);
+    let message = preview(text);
+    let notifications = NotificationModel::new();
+    for person in mentioned.into_iter().filter(|m| m.id != author_id) {
+        if BlockModel::has_muted(&person.id, &author_id)
+            .await
+            .unwrap_or(false)
+        {
+            debug!("Mentioned person has muted {}, skipping", author.username);
+            continue;
+        }
+        let person_id = person.id.to_raw_string();
+        if let Err(e) = notifications
+            .create(&person_id, "mention", &title, &message, Some(link), None)
+            .await
+        {
+            warn!("Failed to notify {} of a mention: {}", person_id, e);
+        }
+    }
+}
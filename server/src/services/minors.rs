@@ -0,0 +1,273 @@
+//! Minor (under-18) performer protections
+//!
+//! A person is put into minor mode when their birthday puts them under 18.
+//! Minor profiles need a linked guardian account; until the guardian approves
+//! the link the profile stays private and out of search. Once approved, the
+//! profile is public but contact details and sensitive physical fields are
+//! always withheld, both on public pages and in the search embedding, and
+//! only the guardian or members of verified organizations can message them.
+
+use crate::{
+    db::DB,
+    error::Error,
+    models::{notification::NotificationModel, person::{Person, Profile}},
+    record_id_ext::RecordIdExt,
+};
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{error, info};
+
+pub const ADULT_AGE: u32 = 18;
+
+/// Age in whole years on `today` for a "YYYY-MM-DD" birthday. `None` when the
+/// birthday doesn't parse or is in the future.
+pub fn age_on(birthday: &str, today: NaiveDate) -> Option<u32> {
+    let born = NaiveDate::parse_from_str(birthday.trim(), "%Y-%m-%d").ok()?;
+    if born > today {
+        return None;
+    }
+    let mut age = today.year() - born.year();
+    if (today.month(), today.day()) < (born.month(), born.day()) {
+        age -= 1;
+    }
+    u32::try_from(age).ok()
+}
+
+/// Whether a birthday puts someone under 18 on `today`
+pub fn is_under_18(birthday: &str, today: NaiveDate) -> bool {
+    age_on(birthday, today).is_some_and(|age| age < ADULT_AGE)
+}
+
+/// Strip everything a minor profile never shows publicly or embeds for search:
+/// contact details and sensitive physical fields.
+pub fn redact_profile(profile: &mut Profile) {
+    profile.phone = None;
+    profile.website = None;
+    profile.birthday = None;
+    profile.height_mm = None;
+    profile.weight_kg = None;
+    profile.body_type = None;
+    profile.body_type_other = None;
+    profile.ethnicity.clear();
+}
+
+/// Guardian link as seen from the minor's side
+#[derive(Debug, Clone, Default)]
+pub struct GuardianLink {
+    pub is_minor: bool,
+    pub guardian_username: Option<String>,
+    pub guardian_name: Option<String>,
+    pub approved: bool,
+}
+
+/// A minor linked to the current person, for the guardian's page
+#[derive(Debug, Clone)]
+pub struct Ward {
+    pub username: String,
+    pub name: String,
+    pub approved: bool,
+    /// The ward's birthday shows they've turned 18, so the guardian can end minor mode
+    pub can_release: bool,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct LinkRow {
+    is_minor: Option<bool>,
+    guardian_username: Option<String>,
+    guardian_name: Option<String>,
+    guardian_approved: Option<bool>,
+}
+
+/// The minor-mode state and guardian link for a person
+pub async fn get_link(person: &RecordId) -> Result<GuardianLink, Error> {
+    let row: Option<LinkRow> = DB
+        .query(
+            "SELECT is_minor, guardian.username AS guardian_username,
+                    guardian.name AS guardian_name, guardian_approved
+             FROM ONLY $person",
+        )
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load guardian link: {}", e)))?
+        .take(0)?;
+
+    Ok(row
+        .map(|row| GuardianLink {
+            is_minor: row.is_minor.unwrap_or(false),
+            guardian_username: row.guardian_username,
+            guardian_name: row.guardian_name,
+            approved: row.guardian_approved.unwrap_or(false),
+        })
+        .unwrap_or_default())
+}
+
+/// Whether a minor profile is visible to others yet. Adults are always visible.
+pub async fn is_listed(person: &RecordId) -> bool {
+    match get_link(person).await {
+        Ok(link) => !link.is_minor || link.approved,
+        Err(e) => {
+            error!("Failed to check minor status for {}: {}", person.display(), e);
+            false
+        }
+    }
+}
+
+/// Link a minor to a guardian account by username. Replaces any previous
+/// link, which then needs approving again.
+pub async fn request_guardian(minor: &Person, guardian_username: &str) -> Result<(), Error> {
+    if !minor.is_minor {
+        return Err(Error::BadRequest("Only minor profiles need a guardian".to_string()));
+    }
+
+    let guardian = Person::find_by_username(guardian_username.trim().trim_start_matches('@'))
+        .await?
+        .ok_or_else(|| Error::Validation("No account with that username".to_string()))?;
+    if guardian.id == minor.id {
+        return Err(Error::Validation("You can't be your own guardian".to_string()));
+    }
+    if guardian.is_minor {
+        return Err(Error::Validation("A guardian must be an adult account".to_string()));
+    }
+    if guardian.verification_status == "unverified" {
+        return Err(Error::Validation(
+            "Your guardian needs to verify their email address first".to_string(),
+        ));
+    }
+
+    DB.query("UPDATE $minor SET guardian = $guardian, guardian_approved = false")
+        .bind(("minor", minor.id.clone()))
+        .bind(("guardian", guardian.id.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to link guardian: {}", e)))?;
+
+    info!("{} requested {} as guardian", minor.username, guardian.username);
+
+    let notifications = NotificationModel::new();
+    if let Err(e) = notifications
+        .create(
+            &guardian.id.to_raw_string(),
+            "guardian_request",
+            "Guardian request",
+            &format!(
+                "{} has named you as their parent or guardian on SlateHub.",
+                minor.get_display_name()
+            ),
+            Some("/account/guardian"),
+            Some(&minor.id.to_raw_string()),
+        )
+        .await
+    {
+        error!("Failed to notify guardian {}: {}", guardian.username, e);
+    }
+    Ok(())
+}
+
+/// Everyone who has named this person as their guardian
+pub async fn wards(guardian: &RecordId) -> Result<Vec<Ward>, Error> {
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct Row {
+        username: String,
+        name: Option<String>,
+        guardian_approved: Option<bool>,
+        birthday: Option<String>,
+    }
+
+    let rows: Vec<Row> = DB
+        .query(
+            "SELECT username, name, guardian_approved, profile.birthday AS birthday
+             FROM person WHERE guardian = $guardian AND is_minor = true ORDER BY username",
+        )
+        .bind(("guardian", guardian.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list wards: {}", e)))?
+        .take(0)?;
+
+    let today = chrono::Utc::now().date_naive();
+    Ok(rows
+        .into_iter()
+        .map(|row| Ward {
+            name: row.name.unwrap_or_else(|| row.username.clone()),
+            username: row.username,
+            approved: row.guardian_approved.unwrap_or(false),
+            can_release: row
+                .birthday
+                .as_deref()
+                .and_then(|b| age_on(b, today))
+                .is_some_and(|age| age >= ADULT_AGE),
+        })
+        .collect())
+}
+
+/// Act on a ward's link: "approve", "decline" or "release" (end minor mode
+/// once the ward has turned 18). Only the linked guardian can do this.
+pub async fn guardian_action(guardian: &RecordId, ward_username: &str, action: &str) -> Result<(), Error> {
+    let ward = Person::find_by_username(ward_username)
+        .await?
+        .ok_or(Error::NotFound)?;
+    let Some(ward_entry) = wards(guardian).await?.into_iter().find(|w| w.username == ward.username) else {
+        return Err(Error::Forbidden);
+    };
+
+    let query = match action {
+        "approve" => "UPDATE $ward SET guardian_approved = true",
+        "decline" => "UPDATE $ward SET guardian = NONE, guardian_approved = false",
+        "release" if ward_entry.can_release => {
+            "UPDATE $ward SET is_minor = false, guardian = NONE, guardian_approved = false"
+        }
+        "release" => {
+            return Err(Error::Validation(
+                "Minor mode can only end once the profile's birthday shows 18 or over".to_string(),
+            ));
+        }
+        _ => return Err(Error::BadRequest("Unknown action".to_string())),
+    };
+
+    DB.query(query)
+        .bind(("ward", ward.id.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to update guardian link: {}", e)))?;
+
+    info!("Guardian {} {}d link for {}", guardian.display(), action, ward.username);
+
+    // Released profiles get their full embedding back
+    if action == "release"
+        && let Some(ward) = Person::find_by_id(&ward.id.to_raw_string()).await?
+    {
+        ward.refresh_embedding().await;
+    }
+    Ok(())
+}
+
+/// Whether `sender` may message the minor `minor`: their guardian, or an
+/// accepted member of a verified organization.
+pub async fn can_message(minor: &RecordId, sender: &RecordId) -> bool {
+    let result: Result<Option<serde_json::Value>, Error> = async {
+        Ok(DB
+            .query(
+                "RETURN {
+                    guardian: (SELECT VALUE guardian FROM ONLY $minor) = $sender,
+                    org: count(SELECT id FROM member_of
+                        WHERE in = $sender AND invitation_status = 'accepted'
+                        AND out.verified = true) > 0
+                }",
+            )
+            .bind(("minor", minor.clone()))
+            .bind(("sender", sender.clone()))
+            .await?
+            .take(0)?)
+    }
+    .await;
+
+    match result {
+        Ok(Some(v)) => {
+            v.get("guardian").and_then(|g| g.as_bool()).unwrap_or(false)
+                || v.get("org").and_then(|o| o.as_bool()).unwrap_or(false)
+        }
+        Ok(None) => false,
+        Err(e) => {
+            error!("Failed to check messaging for minor {}: {}", minor.display(), e);
+            false
+        }
+    }
+}
@@ -1,12 +1,55 @@
 pub mod activity;
+pub mod availability_badge;
+pub mod backup;
+pub mod change_requests;
+pub mod consent;
+pub mod demo_mode;
+pub mod digest;
 pub mod email;
 pub mod embedding;
+pub mod experiments;
+pub mod feedback;
+pub mod flags;
 pub mod geodata;
+pub mod help;
+pub mod id_verification;
+pub mod impersonation;
 pub mod invitation;
+pub mod login_security;
+pub mod mentions;
+pub mod minors;
+pub mod oauth;
+pub mod onboarding;
+pub mod org_claims;
+pub mod palette;
+pub mod password_policy;
+pub mod public_api;
+pub mod reaction_stream;
+pub mod retention;
 pub mod s3;
+pub mod scheduler;
 pub mod search;
+pub mod search_cache;
+pub mod search_connections;
+pub mod search_facets;
+pub mod search_indexes;
 pub mod search_log;
+pub mod search_preview;
+pub mod search_scope;
+pub mod search_suggest;
+pub mod search_syntax;
+pub mod search_unified;
 pub mod search_utils;
+pub mod search_visibility;
+pub mod spellcheck;
+pub mod sso;
+pub mod status;
+pub mod tenants;
 pub mod tmdb;
+pub mod tokens;
+pub mod transcode;
+pub mod triggers;
+pub mod uploads;
 pub mod notification_stream;
 pub mod verification;
+pub mod whatsapp;
@@ -0,0 +1,698 @@
+//! OAuth2 for third-party apps
+//!
+//! Anyone can register an app (payroll, rental software, ...) at
+//! `/account/apps` with the redirect URIs it uses and the scopes it may ask
+//! for. The app sends people to `/oauth/authorize`, where they see what it
+//! wants and approve or deny it. An approval redirects back with a code the
+//! app exchanges at `/oauth/token`, authenticating with its client secret
+//! (and a PKCE verifier if it sent a challenge), for a one-hour access token
+//! and a 30-day refresh token. The access token is sent as a bearer token to
+//! the `/api/v1` endpoints, each of which needs one scope.
+//!
+//! Client secrets, codes and tokens are stored only as SHA-256 hashes, like
+//! SCIM tokens. Refreshing rotates both tokens. People see the apps they've
+//! authorized next to the ones they've registered, and can disconnect them.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::info;
+
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::record_id_ext::RecordIdExt;
+use crate::services::sso::pkce_challenge;
+use crate::services::tokens::{random_token, token_hash};
+
+/// Scopes apps can ask for, with what the consent screen says they allow
+pub const SCOPES: &[(&str, &str)] = &[
+    (
+        "read:profile",
+        "See your name, username, headline and location",
+    ),
+    ("read:equipment", "See the equipment you own"),
+    ("write:equipment", "Add equipment to your inventory"),
+    (
+        "read:timecards",
+        "See approved timecards on productions where you approve them",
+    ),
+    ("read:jobs", "See new job postings"),
+    (
+        "read:applications",
+        "See applications to job postings you manage",
+    ),
+];
+
+/// Registered apps per person
+pub const MAX_CLIENTS: usize = 10;
+pub const MAX_REDIRECT_URIS: usize = 5;
+pub const MAX_NAME_CHARS: usize = 80;
+pub const MAX_DESCRIPTION_CHARS: usize = 500;
+
+const CODE_MINUTES: i64 = 10;
+/// Seconds an access token lasts
+pub const ACCESS_SECONDS: i64 = 3600;
+const REFRESH_DAYS: i64 = 30;
+
+pub fn scope_description(scope: &str) -> &'static str {
+    SCOPES
+        .iter()
+        .find(|(value, _)| *value == scope)
+        .map_or("", |(_, description)| *description)
+}
+
+fn clean_text(value: &str, max: usize, what: &str) -> Result<Option<String>> {
+    let value = value.trim();
+    if value.chars().count() > max {
+        return Err(Error::Validation(format!(
+            "{} can be up to {} characters",
+            what, max
+        )));
+    }
+    Ok((!value.is_empty()).then(|| value.to_string()))
+}
+
+/// Whether a redirect URI is acceptable: HTTPS, or plain HTTP to the local
+/// machine while an app is being developed, and never with a fragment
+pub fn valid_redirect_uri(uri: &str) -> bool {
+    if uri.contains('#') || uri.chars().any(char::is_whitespace) {
+        return false;
+    }
+    let (secure, rest) = if let Some(rest) = uri.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = uri.strip_prefix("http://") {
+        (false, rest)
+    } else {
+        return false;
+    };
+    let authority = rest.split(['/', '?']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    !host.is_empty() && (secure || matches!(host, "localhost" | "127.0.0.1" | "::1"))
+}
+
+/// Scopes an app asked for, space-separated as in RFC 6749. Asking for none
+/// means everything the app is registered for.
+pub fn parse_scopes(requested: &str, allowed: &[String]) -> Result<Vec<String>> {
+    let mut scopes: Vec<String> = Vec::new();
+    for scope in requested.split_whitespace() {
+        if !allowed.iter().any(|a| a == scope) {
+            return Err(Error::Validation(format!(
+                "The app isn't registered for the \"{}\" scope",
+                scope
+            )));
+        }
+        if !scopes.iter().any(|s| s == scope) {
+            scopes.push(scope.to_string());
+        }
+    }
+    if scopes.is_empty() {
+        scopes = allowed.to_vec();
+    }
+    Ok(scopes)
+}
+
+/// `uri` with query parameters added, keeping any it already has
+pub fn redirect_with(uri: &str, params: &[(&str, &str)]) -> String {
+    let mut out = uri.to_string();
+    for (i, (name, value)) in params.iter().enumerate() {
+        let separator = if i == 0 && !uri.contains('?') {
+            '?'
+        } else {
+            '&'
+        };
+        out.push_str(&format!(
+            "{}{}={}",
+            separator,
+            name,
+            urlencoding::encode(value)
+        ));
+    }
+    out
+}
+
+/// (client_id, client_secret) from an `Authorization: Basic ...` header
+pub fn basic_credentials(header: &str) -> Option<(String, String)> {
+    let encoded = header.strip_prefix("Basic ")?.trim();
+    let decoded = String::from_utf8(STANDARD.decode(encoded).ok()?).ok()?;
+    let (id, secret) = decoded.split_once(':')?;
+    let id = urlencoding::decode(id).ok()?.into_owned();
+    let secret = urlencoding::decode(secret).ok()?.into_owned();
+    (!id.is_empty() && !secret.is_empty()).then_some((id, secret))
+}
+
+/// An app's registration from the form, checked
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientData {
+    pub name: String,
+    pub description: Option<String>,
+    pub homepage_url: Option<String>,
+    pub redirect_uris: Vec<String>,
+    pub scopes: Vec<String>,
+}
+
+impl ClientData {
+    /// `redirect_uris` is one per line
+    pub fn parse(
+        name: &str,
+        description: &str,
+        homepage_url: &str,
+        redirect_uris: &str,
+        scopes: &[String],
+    ) -> Result<Self> {
+        let name = clean_text(name, MAX_NAME_CHARS, "The app's name")?
+            .ok_or_else(|| Error::Validation("Name the app".to_string()))?;
+        let homepage_url = clean_text(homepage_url, 500, "The homepage")?;
+        if let Some(url) = homepage_url.as_deref()
+            && !url.starts_with("https://")
+        {
+            return Err(Error::Validation(
+                "The homepage should start with https://".to_string(),
+            ));
+        }
+        let mut uris: Vec<String> = Vec::new();
+        for uri in redirect_uris
+            .lines()
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+        {
+            if !valid_redirect_uri(uri) {
+                return Err(Error::Validation(format!(
+                    "\"{}\" can't be a redirect URI: use https:// (or http://localhost while developing), without a #fragment",
+                    uri
+                )));
+            }
+            if !uris.iter().any(|u| u == uri) {
+                uris.push(uri.to_string());
+            }
+        }
+        if uris.is_empty() {
+            return Err(Error::Validation(
+                "Add the redirect URI people come back to".to_string(),
+            ));
+        }
+        if uris.len() > MAX_REDIRECT_URIS {
+            return Err(Error::Validation(format!(
+                "An app can have up to {} redirect URIs",
+                MAX_REDIRECT_URIS
+            )));
+        }
+        let mut picked: Vec<String> = Vec::new();
+        for scope in scopes {
+            if !SCOPES.iter().any(|(value, _)| value == scope) {
+                return Err(Error::Validation(format!("Unknown scope \"{}\"", scope)));
+            }
+            if !picked.contains(scope) {
+                picked.push(scope.clone());
+            }
+        }
+        if picked.is_empty() {
+            return Err(Error::Validation(
+                "Pick at least one scope the app needs".to_string(),
+            ));
+        }
+        Ok(Self {
+            name,
+            description: clean_text(description, MAX_DESCRIPTION_CHARS, "The description")?,
+            homepage_url,
+            redirect_uris: uris,
+            scopes: picked,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct OAuthClient {
+    pub id: RecordId,
+    pub owner: RecordId,
+    pub owner_username: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub homepage_url: Option<String>,
+    pub redirect_uris: Vec<String>,
+    pub scopes: Vec<String>,
+    pub client_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OAuthClient {
+    pub fn redirect_uris_text(&self) -> String {
+        self.redirect_uris.join("\n")
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+const CLIENT_FIELDS: &str = "id, owner, owner.username AS owner_username, name, description,
+    homepage_url, redirect_uris, scopes, client_id, created_at";
+
+/// An app someone has authorized, for their account page
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct ConnectedApp {
+    pub client: RecordId,
+    pub name: String,
+    pub homepage_url: Option<String>,
+    pub scopes: Vec<String>,
+    pub authorized_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// What a valid access token lets its app do
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct AccessGrant {
+    pub person: RecordId,
+    pub client: RecordId,
+    pub scopes: Vec<String>,
+}
+
+impl AccessGrant {
+    pub fn allows(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+/// Tokens handed to an app by the token endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: &'static str,
+    pub expires_in: i64,
+    pub refresh_token: String,
+    pub scope: String,
+}
+
+/// Why the token endpoint turned a request down, as RFC 6749 error codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrantError {
+    InvalidClient,
+    InvalidGrant,
+}
+
+impl GrantError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            GrantError::InvalidClient => "invalid_client",
+            GrantError::InvalidGrant => "invalid_grant",
+        }
+    }
+}
+
+// ============================
+// Clients
+// ============================
+
+pub async fn clients_for_owner(owner: &RecordId) -> Result<Vec<OAuthClient>> {
+    Ok(DB
+        .query(format!(
+            "SELECT {} FROM oauth_client WHERE owner = $owner ORDER BY created_at DESC",
+            CLIENT_FIELDS
+        ))
+        .bind(("owner", owner.clone()))
+        .await?
+        .take(0)?)
+}
+
+pub async fn get_client_for_owner(owner: &RecordId, id: &str) -> Result<OAuthClient> {
+    let client: Option<OAuthClient> = DB
+        .query(format!(
+            "SELECT {} FROM $id WHERE owner = $owner",
+            CLIENT_FIELDS
+        ))
+        .bind(("id", RecordId::new("oauth_client", id)))
+        .bind(("owner", owner.clone()))
+        .await?
+        .take(0)?;
+    client.ok_or(Error::NotFound)
+}
+
+pub async fn client_by_client_id(client_id: &str) -> Result<Option<OAuthClient>> {
+    Ok(DB
+        .query(format!(
+            "SELECT {} FROM oauth_client WHERE client_id = $client_id LIMIT 1",
+            CLIENT_FIELDS
+        ))
+        .bind(("client_id", client_id.to_string()))
+        .await?
+        .take(0)?)
+}
+
+/// Register an app. Returns it with its secret, which is only shown once.
+pub async fn register_client(owner: &RecordId, data: &ClientData) -> Result<(OAuthClient, String)> {
+    if clients_for_owner(owner).await?.len() >= MAX_CLIENTS {
+        return Err(Error::Validation(format!(
+            "You can register up to {} apps",
+            MAX_CLIENTS
+        )));
+    }
+    let secret = format!("slh_cs_{}", random_token(40));
+    let id: Option<RecordId> = DB
+        .query(
+            "CREATE oauth_client SET owner = $owner, name = $name, description = $description,
+                homepage_url = $homepage_url, redirect_uris = $redirect_uris, scopes = $scopes,
+                client_id = $client_id, secret_hash = $secret_hash
+             RETURN VALUE id",
+        )
+        .bind(("owner", owner.clone()))
+        .bind(("name", data.name.clone()))
+        .bind(("description", data.description.clone()))
+        .bind(("homepage_url", data.homepage_url.clone()))
+        .bind(("redirect_uris", data.redirect_uris.clone()))
+        .bind(("scopes", data.scopes.clone()))
+        .bind(("client_id", random_token(24)))
+        .bind(("secret_hash", token_hash(&secret)))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to register app: {}", e)))?
+        .take(0)?;
+    let id = id.ok_or_else(|| Error::Internal("App was not created".to_string()))?;
+    info!(
+        "Registered OAuth app {} for {}",
+        id.display(),
+        owner.display()
+    );
+    Ok((get_client_for_owner(owner, &id.key_string()).await?, secret))
+}
+
+/// Update an app. Narrowing its scopes also narrows what it was granted.
+pub async fn update_client(owner: &RecordId, id: &str, data: &ClientData) -> Result<()> {
+    let client = get_client_for_owner(owner, id).await?;
+    DB.query(
+        "UPDATE $client SET name = $name, description = $description,
+            homepage_url = $homepage_url, redirect_uris = $redirect_uris, scopes = $scopes;
+         UPDATE oauth_token SET scopes = scopes.filter(|$s| $s IN $scopes) WHERE client = $client;",
+    )
+    .bind(("client", client.id))
+    .bind(("name", data.name.clone()))
+    .bind(("description", data.description.clone()))
+    .bind(("homepage_url", data.homepage_url.clone()))
+    .bind(("redirect_uris", data.redirect_uris.clone()))
+    .bind(("scopes", data.scopes.clone()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to update app: {}", e)))?
+    .check()?;
+    Ok(())
+}
+
+/// Replace an app's secret. Tokens already issued keep working.
+pub async fn rotate_secret(owner: &RecordId, id: &str) -> Result<(OAuthClient, String)> {
+    let client = get_client_for_owner(owner, id).await?;
+    let secret = format!("slh_cs_{}", random_token(40));
+    DB.query("UPDATE $client SET secret_hash = $secret_hash")
+        .bind(("client", client.id.clone()))
+        .bind(("secret_hash", token_hash(&secret)))
+        .await?
+        .check()?;
+    Ok((client, secret))
+}
+
+/// Delete an app along with every code and token it was given
+pub async fn delete_client(owner: &RecordId, id: &str) -> Result<()> {
+    let client = get_client_for_owner(owner, id).await?;
+    DB.query(
+        "DELETE oauth_code WHERE client = $client;
+         DELETE oauth_token WHERE client = $client;
+         DELETE webhook_subscription WHERE client = $client;
+         DELETE $client;",
+    )
+    .bind(("client", client.id))
+    .await?
+    .check()?;
+    Ok(())
+}
+
+/// The app with this client ID, if the secret is its current one
+pub async fn authenticate_client(client_id: &str, secret: &str) -> Result<Option<OAuthClient>> {
+    let matched: Option<RecordId> = DB
+        .query(
+            "SELECT VALUE id FROM oauth_client
+             WHERE client_id = $client_id AND secret_hash = $secret_hash LIMIT 1",
+        )
+        .bind(("client_id", client_id.to_string()))
+        .bind(("secret_hash", token_hash(secret)))
+        .await?
+        .take(0)?;
+    match matched {
+        Some(_) => client_by_client_id(client_id).await,
+        None => Ok(None),
+    }
+}
+
+// ============================
+// Authorization codes and tokens
+// ============================
+
+/// Issue a code once someone approves an app
+pub async fn issue_code(
+    client: &OAuthClient,
+    person: &RecordId,
+    redirect_uri: &str,
+    scopes: &[String],
+    code_challenge: Option<&str>,
+) -> Result<String> {
+    let code = random_token(40);
+    DB.query(
+        "CREATE oauth_code SET client = $client, person = $person, code_hash = $code_hash,
+            redirect_uri = $redirect_uri, scopes = $scopes, code_challenge = $code_challenge,
+            expires_at = $expires_at",
+    )
+    .bind(("client", client.id.clone()))
+    .bind(("person", person.clone()))
+    .bind(("code_hash", token_hash(&code)))
+    .bind(("redirect_uri", redirect_uri.to_string()))
+    .bind(("scopes", scopes.to_vec()))
+    .bind(("code_challenge", code_challenge.map(str::to_string)))
+    .bind(("expires_at", Utc::now() + Duration::minutes(CODE_MINUTES)))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to issue authorization code: {}", e)))?
+    .check()?;
+    Ok(code)
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct CodeRow {
+    client: RecordId,
+    person: RecordId,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    code_challenge: Option<String>,
+    expires_at: DateTime<Utc>,
+}
+
+/// Whether a PKCE verifier matches the challenge sent with the authorization
+/// request. Codes issued without a challenge don't need one.
+pub fn verifier_matches(challenge: Option<&str>, verifier: Option<&str>) -> bool {
+    match (challenge, verifier) {
+        (None, _) => true,
+        (Some(challenge), Some(verifier)) => pkce_challenge(verifier) == challenge,
+        (Some(_), None) => false,
+    }
+}
+
+async fn issue_tokens(
+    client: &RecordId,
+    person: &RecordId,
+    scopes: Vec<String>,
+) -> Result<TokenResponse> {
+    let access = format!("slh_at_{}", random_token(40));
+    let refresh = format!("slh_rt_{}", random_token(40));
+    let now = Utc::now();
+    DB.query(
+        "CREATE oauth_token SET client = $client, person = $person, access_hash = $access_hash,
+            refresh_hash = $refresh_hash, scopes = $scopes, expires_at = $expires_at,
+            refresh_expires_at = $refresh_expires_at",
+    )
+    .bind(("client", client.clone()))
+    .bind(("person", person.clone()))
+    .bind(("access_hash", token_hash(&access)))
+    .bind(("refresh_hash", token_hash(&refresh)))
+    .bind(("scopes", scopes.clone()))
+    .bind(("expires_at", now + Duration::seconds(ACCESS_SECONDS)))
+    .bind(("refresh_expires_at", now + Duration::days(REFRESH_DAYS)))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to issue tokens: {}", e)))?
+    .check()?;
+    Ok(TokenResponse {
+        access_token: access,
+        token_type: "Bearer",
+        expires_in: ACCESS_SECONDS,
+        refresh_token: refresh,
+        scope: scopes.join(" "),
+    })
+}
+
+/// Trade a code for tokens. The code is used up whether or not the exchange
+/// succeeds.
+pub async fn exchange_code(
+    client: &OAuthClient,
+    code: &str,
+    redirect_uri: &str,
+    verifier: Option<&str>,
+) -> Result<std::result::Result<TokenResponse, GrantError>> {
+    let row: Option<CodeRow> = DB
+        .query(
+            "LET $row = (SELECT client, person, redirect_uri, scopes, code_challenge, expires_at
+                 FROM oauth_code WHERE code_hash = $code_hash)[0];
+             DELETE oauth_code WHERE code_hash = $code_hash;
+             RETURN $row;",
+        )
+        .bind(("code_hash", token_hash(code)))
+        .await?
+        .take(2)?;
+    let Some(row) = row else {
+        return Ok(Err(GrantError::InvalidGrant));
+    };
+    if row.client != client.id
+        || row.redirect_uri != redirect_uri
+        || row.expires_at < Utc::now()
+        || !verifier_matches(row.code_challenge.as_deref(), verifier)
+    {
+        return Ok(Err(GrantError::InvalidGrant));
+    }
+    Ok(Ok(issue_tokens(&row.client, &row.person, row.scopes).await?))
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct TokenRow {
+    id: RecordId,
+    client: RecordId,
+    person: RecordId,
+    scopes: Vec<String>,
+    refresh_expires_at: DateTime<Utc>,
+}
+
+/// Trade a refresh token for new tokens, retiring the old pair
+pub async fn refresh(
+    client: &OAuthClient,
+    refresh_token: &str,
+) -> Result<std::result::Result<TokenResponse, GrantError>> {
+    let row: Option<TokenRow> = DB
+        .query(
+            "SELECT id, client, person, scopes, refresh_expires_at FROM oauth_token
+             WHERE refresh_hash = $refresh_hash LIMIT 1",
+        )
+        .bind(("refresh_hash", token_hash(refresh_token)))
+        .await?
+        .take(0)?;
+    let Some(row) = row else {
+        return Ok(Err(GrantError::InvalidGrant));
+    };
+    if row.client != client.id || row.refresh_expires_at < Utc::now() {
+        return Ok(Err(GrantError::InvalidGrant));
+    }
+    DB.query("DELETE $id").bind(("id", row.id)).await?.check()?;
+    Ok(Ok(issue_tokens(&row.client, &row.person, row.scopes).await?))
+}
+
+/// Revoke the token pair an access or refresh token belongs to (RFC 7009).
+/// Unknown tokens are ignored.
+pub async fn revoke(client: &OAuthClient, token: &str) -> Result<()> {
+    DB.query(
+        "DELETE oauth_token WHERE client = $client
+            AND (access_hash = $hash OR refresh_hash = $hash)",
+    )
+    .bind(("client", client.id.clone()))
+    .bind(("hash", token_hash(token)))
+    .await?
+    .check()?;
+    Ok(())
+}
+
+/// What an unexpired access token allows, noting that it was used
+pub async fn authenticate(access_token: &str) -> Result<Option<AccessGrant>> {
+    Ok(DB
+        .query(
+            "UPDATE oauth_token SET last_used_at = time::now()
+             WHERE access_hash = $access_hash AND expires_at > time::now()
+             RETURN person, client, scopes",
+        )
+        .bind(("access_hash", token_hash(access_token)))
+        .await?
+        .take::<Vec<AccessGrant>>(0)?
+        .into_iter()
+        .next())
+}
+
+// ============================
+// Connected apps
+// ============================
+
+/// Apps someone has authorized that still hold a token. Each authorization
+/// is its own token, so they're merged per app here.
+pub async fn connected_apps(person: &RecordId) -> Result<Vec<ConnectedApp>> {
+    let grants: Vec<ConnectedApp> = DB
+        .query(
+            "SELECT client, client.name AS name, client.homepage_url AS homepage_url, scopes,
+                created_at AS authorized_at, last_used_at
+             FROM oauth_token WHERE person = $person ORDER BY created_at ASC",
+        )
+        .bind(("person", person.clone()))
+        .await?
+        .take(0)?;
+    let mut apps: Vec<ConnectedApp> = Vec::new();
+    for grant in grants {
+        match apps.iter_mut().find(|app| app.client == grant.client) {
+            Some(app) => {
+                for scope in grant.scopes {
+                    if !app.scopes.contains(&scope) {
+                        app.scopes.push(scope);
+                    }
+                }
+                app.last_used_at = app.last_used_at.max(grant.last_used_at);
+            }
+            None => apps.push(grant),
+        }
+    }
+    apps.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    Ok(apps)
+}
+
+/// Revoke everything an app was given by one person
+pub async fn disconnect(person: &RecordId, client: &str) -> Result<()> {
+    DB.query(
+        "DELETE oauth_code WHERE person = $person AND client = $client;
+         DELETE oauth_token WHERE person = $person AND client = $client;
+         DELETE webhook_subscription WHERE person = $person AND client = $client;",
+    )
+    .bind(("person", person.clone()))
+    .bind(("client", RecordId::new("oauth_client", client)))
+    .await?
+    .check()?;
+    Ok(())
+}
+
+/// Delete everything tied to a person (account deletion): the codes and
+/// tokens they granted, and the apps they registered along with whatever
+/// those apps were given by anyone
+pub async fn forget(person: &RecordId) -> Result<()> {
+    DB.query(
+        "LET $clients = (SELECT VALUE id FROM oauth_client WHERE owner = $person);
+         DELETE oauth_code WHERE person = $person OR client IN $clients;
+         DELETE oauth_token WHERE person = $person OR client IN $clients;
+         DELETE webhook_subscription WHERE client IN $clients;
+         DELETE oauth_client WHERE owner = $person;",
+    )
+    .bind(("person", person.clone()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to delete OAuth apps and grants: {}", e)))?
+    .check()?;
+    Ok(())
+}
+
+/// Drop codes nobody exchanged and tokens that can no longer be refreshed
+pub async fn expire_stale() -> Result<()> {
+    DB.query(
+        "DELETE oauth_code WHERE expires_at < time::now();
+         DELETE oauth_token WHERE refresh_expires_at < time::now();",
+    )
+    .await?
+    .check()?;
+    Ok(())
+}
@@ -0,0 +1,233 @@
+//! First-login onboarding
+//!
+//! New accounts land on `/welcome`, a short questionnaire asking what kind of
+//! work the person does, their skills, location, union membership and what
+//! work they're after. The answers fill in the profile, are embedded straight
+//! away rather than after the usual debounce so the person is searchable from
+//! their first minute, and set up saved job searches that alert them when
+//! matching work is posted. `onboarded_at` is set whether they finish or skip.
+
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{info, warn};
+
+use crate::{
+    db::DB,
+    error::Result,
+    models::{audio_reel::AudioReelModel, person::Person, saved_search::SavedSearchModel},
+    record_id_ext::RecordIdExt,
+    services::embedding,
+};
+
+/// Role types offered on the questionnaire, as (label, saved search keywords)
+pub const ROLE_TYPES: &[(&str, &str)] = &[
+    ("Actor", "actor"),
+    ("Voice actor", "voice"),
+    ("Director", "director"),
+    ("Producer", "producer"),
+    ("Writer", "writer"),
+    ("Cinematographer", "cinematographer"),
+    ("Camera crew", "camera"),
+    ("Grip & electric", "grip"),
+    ("Sound", "sound"),
+    ("Editor", "editor"),
+    ("Colorist", "colorist"),
+    ("VFX", "vfx"),
+    ("Art department", "art"),
+    ("Hair & makeup", "makeup"),
+    ("Wardrobe", "wardrobe"),
+    ("Production assistant", "production assistant"),
+    ("Composer", "composer"),
+];
+
+/// Unions offered as checkboxes; anything else goes in the free-text field
+pub const UNIONS: &[&str] = &[
+    "SAG-AFTRA",
+    "AEA",
+    "IATSE",
+    "DGA",
+    "WGA",
+    "Teamsters Local 399",
+    "ACTRA",
+    "Equity UK",
+    "BECTU",
+];
+
+/// Role types a person can pick
+pub const MAX_ROLE_TYPES: usize = 3;
+
+/// Kinds of work a person is after, stored on `person.work_preferences`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, SurrealValue)]
+#[serde(default)]
+#[surreal(default)]
+pub struct WorkPreferences {
+    /// Labels from `ROLE_TYPES`
+    pub role_types: Vec<String>,
+    /// Names from the `production_type` table
+    pub project_types: Vec<String>,
+    pub paid_only: bool,
+    /// Open to remote work
+    pub remote: bool,
+    /// Willing to travel for work
+    pub travel: bool,
+}
+
+/// Everything the questionnaire asks
+#[derive(Debug, Clone, Default)]
+pub struct OnboardingAnswers {
+    pub headline: Option<String>,
+    pub skills: Vec<String>,
+    pub location: Option<String>,
+    pub unions: Vec<String>,
+    /// "available", "busy" or "not_available", as on the profile edit page
+    pub availability: Option<String>,
+    pub preferences: WorkPreferences,
+}
+
+/// Split a comma-separated list, dropping blanks and repeats (ignoring case)
+pub fn split_list(value: &str) -> Vec<String> {
+    let mut items: Vec<String> = Vec::new();
+    for item in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if !items.iter().any(|i| i.eq_ignore_ascii_case(item)) {
+            items.push(item.to_string());
+        }
+    }
+    items
+}
+
+/// Saved searches to set up for these answers, as (keywords, location). One
+/// per role type, limited to the person's location unless they'll travel or
+/// work remotely.
+pub fn alert_queries(answers: &OnboardingAnswers) -> Vec<(String, Option<String>)> {
+    let location = answers
+        .location
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !answers.preferences.travel && !answers.preferences.remote)
+        .map(str::to_string);
+
+    answers
+        .preferences
+        .role_types
+        .iter()
+        .filter_map(|label| ROLE_TYPES.iter().find(|(l, _)| l == label))
+        .map(|(_, keywords)| (keywords.to_string(), location.clone()))
+        .take(MAX_ROLE_TYPES)
+        .collect()
+}
+
+/// Whether a person still has to see the questionnaire. Lookup errors count as
+/// onboarded, so a database hiccup never traps anyone on `/welcome`.
+pub async fn needs_onboarding(person: &RecordId) -> bool {
+    let result: Result<Option<bool>> = async {
+        Ok(DB
+            .query("SELECT VALUE onboarded_at IS NONE FROM ONLY $person")
+            .bind(("person", person.clone()))
+            .await?
+            .take(0)?)
+    }
+    .await;
+    match result {
+        Ok(needs) => needs.unwrap_or(false),
+        Err(e) => {
+            warn!("Failed to check onboarding for {}: {}", person.display(), e);
+            false
+        }
+    }
+}
+
+/// Skip the questionnaire; it won't be shown again
+pub async fn skip(person: &RecordId) -> Result<()> {
+    DB.query("UPDATE $person SET onboarded_at = time::now()")
+        .bind(("person", person.clone()))
+        .await?
+        .check()?;
+    info!("{} skipped onboarding", person.display());
+    Ok(())
+}
+
+/// Save the answers to the profile, embed it and set up job alerts
+pub async fn complete(person: &Person, answers: OnboardingAnswers) -> Result<()> {
+    let mut person = person.clone();
+    let profile = person.profile.get_or_insert_with(Default::default);
+
+    // Fill in rather than overwrite what's already on the profile
+    if profile
+        .headline
+        .as_deref()
+        .is_none_or(|h| h.trim().is_empty())
+    {
+        profile.headline = answers.headline.clone().filter(|h| !h.trim().is_empty());
+    }
+    for skill in &answers.skills {
+        if !profile.skills.iter().any(|s| s.eq_ignore_ascii_case(skill)) {
+            profile.skills.push(skill.clone());
+        }
+    }
+    for union in &answers.unions {
+        if !profile.unions.iter().any(|u| u.eq_ignore_ascii_case(union)) {
+            profile.unions.push(union.clone());
+        }
+    }
+    if let Some(location) = answers.location.clone().filter(|l| !l.trim().is_empty()) {
+        profile.location = Some(location);
+    }
+    if let Some(availability) = answers.availability.clone().filter(|a| !a.is_empty()) {
+        profile.availability = Some(availability);
+    }
+
+    DB.query(
+        "UPDATE $id MERGE {
+            profile: $profile,
+            work_preferences: $preferences,
+            onboarded_at: time::now()
+        }",
+    )
+    .bind(("id", person.id.clone()))
+    .bind(("profile", person.profile.clone()))
+    .bind(("preferences", answers.preferences.clone()))
+    .await?
+    .check()?;
+
+    embed_now(&person).await;
+
+    for (query, location) in alert_queries(&answers) {
+        if let Err(e) = SavedSearchModel::create(&person.id, &query, location.as_deref()).await {
+            warn!(
+                "Failed to save onboarding search '{}' for {}: {}",
+                query, person.username, e
+            );
+        }
+    }
+
+    info!("{} finished onboarding", person.username);
+    Ok(())
+}
+
+/// Embed the profile straight away, falling back to the debounced background
+/// update when the model isn't loaded or fails
+async fn embed_now(person: &Person) {
+    let audio_work = AudioReelModel::embedding_lines(&person.id)
+        .await
+        .unwrap_or_default();
+    let text = person.embedding_text(&audio_work);
+
+    if embedding::is_initialized() {
+        match embedding::generate_embedding_async(&text).await {
+            Ok(vector) => {
+                match embedding::store_embedding(&DB, person.id.clone(), vector, text).await {
+                    Ok(()) => return,
+                    Err(e) => warn!(
+                        "Failed to store onboarding embedding for {}: {}",
+                        person.username, e
+                    ),
+                }
+            }
+            Err(e) => warn!(
+                "Failed to embed onboarding profile for {}: {}",
+                person.username, e
+            ),
+        }
+    }
+    person.refresh_embedding().await;
+}
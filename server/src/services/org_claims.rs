@@ -0,0 +1,656 @@
+//! Claiming pre-seeded organizations
+//!
+//! Imported and seeded organizations have no owner. Anyone can claim one by
+//! proving a connection to it, either with a code sent to an address on the
+//! organization's email domain or by uploading a document (kept under the
+//! private S3 prefix, like ID documents). Every claim then waits for an admin;
+//! approving it makes the claimant the owner, removes any placeholder owner
+//! edges held by site admins, and turns down the other claims on the same
+//! organization.
+
+use bytes::Bytes;
+use chrono::{DateTime, Duration, Utc};
+use rand::Rng;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, error, info};
+use ulid::Ulid;
+
+use crate::{
+    db::DB,
+    error::Error,
+    models::{
+        membership::{CreateMembershipData, InvitationStatus, MembershipModel, MembershipRole},
+        notification::NotificationModel,
+        organization::Organization,
+    },
+    record_id_ext::RecordIdExt,
+    services::{
+        email::EmailService,
+        id_verification::{DOCUMENT_CONTENT_TYPES, MAX_DOCUMENT_SIZE},
+        s3::s3,
+    },
+};
+
+const DOCUMENT_PREFIX: &str = "private/org-claims";
+
+/// How long an emailed claim code stays valid
+const CODE_TTL_MINUTES: i64 = 30;
+
+/// Wrong codes allowed before the claim has to be started again
+pub const MAX_CODE_ATTEMPTS: i64 = 5;
+
+/// Accepted kinds of ownership document, as (value, label)
+pub const DOCUMENT_KINDS: &[(&str, &str)] = &[
+    ("business_registration", "Business registration"),
+    ("tax_document", "Tax or VAT registration"),
+    ("authorization_letter", "Signed letter of authorization"),
+];
+
+/// Webmail domains that never identify an organization
+const FREE_MAIL_DOMAINS: &[&str] = &[
+    "gmail.com",
+    "googlemail.com",
+    "yahoo.com",
+    "hotmail.com",
+    "outlook.com",
+    "live.com",
+    "icloud.com",
+    "me.com",
+    "aol.com",
+    "proton.me",
+    "protonmail.com",
+];
+
+// ---------------------------------------------------------------------------
+// Domains
+// ---------------------------------------------------------------------------
+
+/// The email domain an organization's staff would use: the host of its
+/// website, falling back to its contact address unless that's a webmail one.
+pub fn org_domain(website: Option<&str>, contact_email: Option<&str>) -> Option<String> {
+    let from_website = website.and_then(|url| {
+        let url = url.trim().to_lowercase();
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .unwrap_or(&url);
+        let host = rest
+            .split(['/', '?', '#'])
+            .next()
+            .unwrap_or("")
+            .split(':')
+            .next()
+            .unwrap_or("");
+        let host = host.strip_prefix("www.").unwrap_or(host);
+        (host.contains('.') && !host.starts_with('.')).then(|| host.to_string())
+    });
+
+    from_website.or_else(|| {
+        let domain = email_domain(contact_email?)?;
+        (!FREE_MAIL_DOMAINS.contains(&domain.as_str())).then_some(domain)
+    })
+}
+
+fn email_domain(email: &str) -> Option<String> {
+    let (local, domain) = email.trim().rsplit_once('@')?;
+    let domain = domain.trim_end_matches('.').to_lowercase();
+    (!local.is_empty() && domain.contains('.')).then_some(domain)
+}
+
+/// Whether an email address is on `domain` or one of its subdomains
+pub fn email_matches_domain(email: &str, domain: &str) -> bool {
+    let Some(email_domain) = email_domain(email) else {
+        return false;
+    };
+    let domain = domain.to_lowercase();
+    email_domain == domain || email_domain.ends_with(&format!(".{}", domain))
+}
+
+fn hash_code(code: &str) -> String {
+    Sha256::digest(code.trim().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Claims
+// ---------------------------------------------------------------------------
+
+/// The viewer's claim on an organization, for the claim page
+#[derive(Debug, Clone)]
+pub struct ClaimStatus {
+    pub status: String,
+    pub method: String,
+    pub email: Option<String>,
+    pub note: Option<String>,
+}
+
+/// A claim waiting in the admin review queue
+#[derive(Debug, Clone)]
+pub struct PendingClaim {
+    pub id: String,
+    pub org_name: String,
+    pub org_slug: String,
+    pub username: String,
+    pub name: String,
+    /// "email" or "document"
+    pub method: String,
+    /// The confirmed work address, or the label of the uploaded document
+    pub evidence: String,
+    pub submitted: String,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct ClaimRow {
+    id: RecordId,
+    organization: RecordId,
+    person: RecordId,
+    method: String,
+    status: String,
+    email: Option<String>,
+    code_hash: Option<String>,
+    code_expires_at: Option<DateTime<Utc>>,
+    attempts: Option<i64>,
+    document_key: Option<String>,
+    note: Option<String>,
+}
+
+/// Whether an organization can still be claimed: nobody other than a site
+/// admin holds an accepted owner membership.
+pub async fn is_claimable(organization: &RecordId) -> Result<bool, Error> {
+    let owners: Option<i64> = DB
+        .query(
+            "RETURN count(SELECT id FROM member_of
+                WHERE out = $org AND role = 'owner' AND invitation_status = 'accepted'
+                AND in.is_admin != true)",
+        )
+        .bind(("org", organization.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to count organization owners: {}", e)))?
+        .take(0)?;
+    Ok(owners.unwrap_or(0) == 0)
+}
+
+async fn find_claim(organization: &RecordId, person: &RecordId) -> Result<Option<ClaimRow>, Error> {
+    Ok(DB
+        .query("SELECT * FROM org_claim WHERE organization = $org AND person = $person LIMIT 1")
+        .bind(("org", organization.clone()))
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load organization claim: {}", e)))?
+        .take(0)?)
+}
+
+/// The person's claim on an organization, if they've made one
+pub async fn status(organization: &RecordId, person: &RecordId) -> Result<Option<ClaimStatus>, Error> {
+    Ok(find_claim(organization, person).await?.map(|row| ClaimStatus {
+        status: row.status,
+        method: row.method,
+        email: row.email,
+        note: row.note,
+    }))
+}
+
+/// Remove the person's earlier claim so a new one can be made. Claims already
+/// with an admin can't be replaced.
+async fn clear_previous(organization: &RecordId, person: &RecordId) -> Result<(), Error> {
+    if !is_claimable(organization).await? {
+        return Err(Error::Conflict("This organization already has an owner".to_string()));
+    }
+    let Some(row) = find_claim(organization, person).await? else {
+        return Ok(());
+    };
+    if row.status == "pending" {
+        return Err(Error::Conflict("Your claim is already waiting for review".to_string()));
+    }
+
+    delete_document(row.document_key.as_deref()).await;
+    DB.query("DELETE $id")
+        .bind(("id", row.id))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to clear organization claim: {}", e)))?;
+    Ok(())
+}
+
+async fn delete_document(key: Option<&str>) {
+    let Some(key) = key else {
+        return;
+    };
+    match s3() {
+        Ok(s3) => {
+            if let Err(e) = s3.delete_file(key).await {
+                error!("Failed to delete claim document {}: {}", key, e);
+            }
+        }
+        Err(e) => error!("S3 unavailable, claim document {} not deleted: {}", key, e),
+    }
+}
+
+/// Start an email claim: check the address is on the organization's domain
+/// and send it a six-digit code
+pub async fn start_email(
+    organization: &Organization,
+    person: &RecordId,
+    email: &str,
+) -> Result<(), Error> {
+    let email = email.trim().to_lowercase();
+    let Some(domain) = org_domain(organization.website.as_deref(), organization.contact_email.as_deref())
+    else {
+        return Err(Error::Validation(
+            "This organization has no email domain on file. Upload a document instead.".to_string(),
+        ));
+    };
+    if !email_matches_domain(&email, &domain) {
+        return Err(Error::Validation(format!("Use an email address ending in @{}", domain)));
+    }
+
+    clear_previous(&organization.id, person).await?;
+
+    let code = rand::thread_rng().gen_range(100000..1000000).to_string();
+    DB.query(
+        "CREATE org_claim SET organization = $org, person = $person, method = 'email',
+            status = 'pending_email', email = $email, code_hash = $code_hash,
+            code_expires_at = <datetime>$expires_at, attempts = 0, created_at = time::now()",
+    )
+    .bind(("org", organization.id.clone()))
+    .bind(("person", person.clone()))
+    .bind(("email", email.clone()))
+    .bind(("code_hash", hash_code(&code)))
+    .bind(("expires_at", (Utc::now() + Duration::minutes(CODE_TTL_MINUTES)).to_rfc3339()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to create organization claim: {}", e)))?;
+
+    let subject = format!("Your code to claim {} on SlateHub", organization.name);
+    let text = format!(
+        "Your code to claim {} on SlateHub is {}.\n\nIt expires in {} minutes. If you didn't ask for this, you can ignore this email.",
+        organization.name, code, CODE_TTL_MINUTES
+    );
+    let html = format!(
+        "<p>Your code to claim <strong>{}</strong> on SlateHub is:</p>\
+         <p style=\"font-size: 28px; font-weight: bold; letter-spacing: 4px;\">{}</p>\
+         <p>It expires in {} minutes. If you didn't ask for this, you can ignore this email.</p>",
+        html_escape(&organization.name),
+        code,
+        CODE_TTL_MINUTES
+    );
+    EmailService::from_env()
+        .map_err(|e| Error::ExternalService(e.to_string()))?
+        .send_notification_email(&email, None, &subject, &text, &html)
+        .await
+        .map_err(|e| Error::ExternalService(e.to_string()))?;
+
+    info!("{} started an email claim on {}", person.display(), organization.slug);
+    Ok(())
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Check the emailed code. A correct code sends the claim to admin review.
+pub async fn confirm_code(organization: &RecordId, person: &RecordId, code: &str) -> Result<(), Error> {
+    let row = find_claim(organization, person)
+        .await?
+        .filter(|row| row.status == "pending_email")
+        .ok_or_else(|| Error::Validation("Start your claim again to get a new code".to_string()))?;
+
+    let expired = row.code_expires_at.is_none_or(|at| at < Utc::now());
+    if expired || row.attempts.unwrap_or(0) >= MAX_CODE_ATTEMPTS {
+        return Err(Error::Validation(
+            "That code has expired. Start your claim again to get a new one.".to_string(),
+        ));
+    }
+
+    if row.code_hash.as_deref() != Some(hash_code(code).as_str()) {
+        DB.query("UPDATE $id SET attempts += 1")
+            .bind(("id", row.id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to update organization claim: {}", e)))?;
+        return Err(Error::Validation("That code isn't right".to_string()));
+    }
+
+    DB.query(
+        "UPDATE $id SET status = 'pending', code_hash = NONE, code_expires_at = NONE,
+            created_at = time::now()",
+    )
+    .bind(("id", row.id.clone()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to update organization claim: {}", e)))?;
+
+    info!("{} confirmed an email claim on {}", person.display(), organization.display());
+    Ok(())
+}
+
+/// Store an ownership document privately and queue the claim for review
+pub async fn submit_document(
+    organization: &RecordId,
+    person: &RecordId,
+    kind: &str,
+    content_type: &str,
+    data: Bytes,
+) -> Result<(), Error> {
+    if !DOCUMENT_KINDS.iter().any(|(value, _)| *value == kind) {
+        return Err(Error::Validation("Choose the kind of document you're uploading".to_string()));
+    }
+    if !DOCUMENT_CONTENT_TYPES.contains(&content_type) {
+        return Err(Error::Validation("Upload a JPEG, PNG or PDF".to_string()));
+    }
+    if data.is_empty() {
+        return Err(Error::Validation("Choose a file to upload".to_string()));
+    }
+    if data.len() > MAX_DOCUMENT_SIZE {
+        return Err(Error::Validation("File too large. Maximum size is 10MB".to_string()));
+    }
+
+    clear_previous(organization, person).await?;
+
+    let extension = match content_type {
+        "image/png" => "png",
+        "application/pdf" => "pdf",
+        _ => "jpg",
+    };
+    let key = format!(
+        "{}/{}/{}.{}",
+        DOCUMENT_PREFIX,
+        organization.key_string(),
+        Ulid::new(),
+        extension
+    );
+    s3()?.upload_file(&key, data, content_type).await?;
+
+    if let Err(e) = DB
+        .query(
+            "CREATE org_claim SET organization = $org, person = $person, method = 'document',
+                status = 'pending', document_kind = $kind, document_key = $key, created_at = time::now()",
+        )
+        .bind(("org", organization.clone()))
+        .bind(("person", person.clone()))
+        .bind(("kind", kind.to_string()))
+        .bind(("key", key.clone()))
+        .await
+    {
+        delete_document(Some(&key)).await;
+        return Err(Error::Database(format!("Failed to create organization claim: {}", e)));
+    }
+
+    info!("{} submitted a document claim on {}", person.display(), organization.display());
+    Ok(())
+}
+
+/// Claims waiting for an admin, oldest first
+pub async fn pending_reviews() -> Result<Vec<PendingClaim>, Error> {
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct Row {
+        id: RecordId,
+        org_name: Option<String>,
+        org_slug: Option<String>,
+        username: Option<String>,
+        name: Option<String>,
+        method: String,
+        email: Option<String>,
+        document_kind: Option<String>,
+        created_at: DateTime<Utc>,
+    }
+
+    let rows: Vec<Row> = DB
+        .query(
+            "SELECT id, organization.name AS org_name, organization.slug AS org_slug,
+                    person.username AS username, person.name AS name,
+                    method, email, document_kind, created_at
+             FROM org_claim WHERE status = 'pending'
+             ORDER BY created_at ASC",
+        )
+        .await
+        .map_err(|e| Error::Database(format!("Failed to list organization claims: {}", e)))?
+        .take(0)?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|row| {
+            let username = row.username?;
+            let kind = row.document_kind.unwrap_or_default();
+            let evidence = match row.method.as_str() {
+                "email" => row.email.unwrap_or_default(),
+                _ => DOCUMENT_KINDS
+                    .iter()
+                    .find(|(value, _)| *value == kind)
+                    .map(|(_, label)| label.to_string())
+                    .unwrap_or(kind),
+            };
+            Some(PendingClaim {
+                id: row.id.key_string(),
+                org_name: row.org_name?,
+                org_slug: row.org_slug?,
+                name: row.name.unwrap_or_else(|| username.clone()),
+                username,
+                method: row.method,
+                evidence,
+                submitted: row.created_at.format("%b %d, %Y %H:%M").to_string(),
+            })
+        })
+        .collect())
+}
+
+async fn load_pending(claim_id: &str) -> Result<ClaimRow, Error> {
+    let row: Option<ClaimRow> = DB
+        .query("SELECT * FROM ONLY $id")
+        .bind(("id", RecordId::new("org_claim", claim_id)))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load organization claim: {}", e)))?
+        .take(0)?;
+    row.filter(|row| row.status == "pending").ok_or(Error::NotFound)
+}
+
+/// The uploaded document for a pending claim, for admin review only
+pub async fn document(claim_id: &str) -> Result<(Bytes, String), Error> {
+    let row = load_pending(claim_id).await?;
+    let key = row.document_key.ok_or(Error::NotFound)?;
+    s3()?.download_file(&key).await
+}
+
+/// Admin decision on a pending claim
+pub async fn review(
+    claim_id: &str,
+    reviewer: &RecordId,
+    approve: bool,
+    note: Option<String>,
+) -> Result<(), Error> {
+    let row = load_pending(claim_id).await?;
+    let note = note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+
+    if approve {
+        if !is_claimable(&row.organization).await? {
+            return Err(Error::Conflict("This organization already has an owner".to_string()));
+        }
+        transfer_ownership(&row.organization, &row.person).await?;
+    }
+
+    let status = if approve { "approved" } else { "rejected" };
+    record_decision(&row, status, note.clone(), reviewer).await?;
+    info!(
+        "Organization claim {} by {} on {} {}",
+        row.id.display(),
+        row.person.display(),
+        row.organization.display(),
+        status
+    );
+
+    let org_name: Option<String> = DB
+        .query("SELECT VALUE name FROM ONLY $org")
+        .bind(("org", row.organization.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load organization: {}", e)))?
+        .take(0)?;
+    let org_name = org_name.unwrap_or_else(|| "the organization".to_string());
+
+    let (title, message) = if approve {
+        (
+            "Organization claim approved".to_string(),
+            format!("You're now the owner of {} on SlateHub.", org_name),
+        )
+    } else {
+        (
+            "Organization claim not approved".to_string(),
+            match &note {
+                Some(note) => format!("We couldn't approve your claim on {}: {}", org_name, note),
+                None => format!("We couldn't approve your claim on {}.", org_name),
+            },
+        )
+    };
+    notify(&row, &title, &message).await;
+
+    // One owner per claim: everyone else waiting on this organization is turned down
+    if approve {
+        let others: Vec<ClaimRow> = DB
+            .query("SELECT * FROM org_claim WHERE organization = $org AND id != $id AND status IN ['pending', 'pending_email']")
+            .bind(("org", row.organization.clone()))
+            .bind(("id", row.id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to load organization claims: {}", e)))?
+            .take(0)?;
+        let note = "Another claim on this organization was approved".to_string();
+        for other in others {
+            record_decision(&other, "rejected", Some(note.clone()), reviewer).await?;
+            notify(
+                &other,
+                "Organization claim not approved",
+                &format!("{} has been claimed by someone else.", org_name),
+            )
+            .await;
+        }
+    }
+    Ok(())
+}
+
+async fn record_decision(
+    row: &ClaimRow,
+    status: &str,
+    note: Option<String>,
+    reviewer: &RecordId,
+) -> Result<(), Error> {
+    DB.query(
+        "UPDATE $id SET status = $status, note = $note, reviewed_by = $reviewer,
+            reviewed_at = time::now(), document_key = NONE, code_hash = NONE",
+    )
+    .bind(("id", row.id.clone()))
+    .bind(("status", status.to_string()))
+    .bind(("note", note))
+    .bind(("reviewer", reviewer.clone()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to record claim decision: {}", e)))?;
+
+    // The document has done its job either way
+    delete_document(row.document_key.as_deref()).await;
+    Ok(())
+}
+
+async fn notify(row: &ClaimRow, title: &str, message: &str) {
+    let slug: Option<String> = match DB
+        .query("SELECT VALUE slug FROM ONLY $org")
+        .bind(("org", row.organization.clone()))
+        .await
+    {
+        Ok(mut response) => response.take(0).unwrap_or_default(),
+        Err(e) => {
+            error!("Failed to load organization slug: {}", e);
+            None
+        }
+    };
+    let link = slug.map(|slug| format!("/orgs/{}", slug));
+
+    if let Err(e) = NotificationModel::new()
+        .create(
+            &row.person.to_raw_string(),
+            "org_claim",
+            title,
+            message,
+            link.as_deref(),
+            Some(&row.id.to_raw_string()),
+        )
+        .await
+    {
+        error!("Failed to notify {} about organization claim: {}", row.person.display(), e);
+    }
+}
+
+/// Make the claimant the organization's owner. Placeholder owner edges held
+/// by site admins (from seeding or import) are removed.
+async fn transfer_ownership(organization: &RecordId, person: &RecordId) -> Result<(), Error> {
+    DB.query(
+        "DELETE member_of WHERE out = $org AND role = 'owner' AND in != $person AND in.is_admin = true",
+    )
+    .bind(("org", organization.clone()))
+    .bind(("person", person.clone()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to remove placeholder owners: {}", e)))?;
+
+    let model = MembershipModel::new();
+    let permissions = MembershipModel::get_default_permissions(&MembershipRole::Owner);
+    match model
+        .find_by_person_and_org(&person.to_raw_string(), &organization.to_raw_string())
+        .await?
+    {
+        Some(membership) => {
+            let permissions: Vec<String> = permissions
+                .iter()
+                .map(|p| serde_json::to_string(p).unwrap_or_default().trim_matches('"').to_string())
+                .collect();
+            DB.query(
+                "UPDATE $id SET role = 'owner', permissions = $permissions,
+                    invitation_status = 'accepted', joined_at = time::now()",
+            )
+            .bind(("id", membership.id.clone()))
+            .bind(("permissions", permissions))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to promote claimant: {}", e)))?;
+        }
+        None => {
+            model
+                .create(CreateMembershipData {
+                    person_id: person.key_string(),
+                    organization_id: organization.key_string(),
+                    role: MembershipRole::Owner,
+                    permissions,
+                    invitation_status: InvitationStatus::Accepted,
+                    invited_by: None,
+                })
+                .await?;
+        }
+    }
+    debug!("Transferred ownership of {} to {}", organization.display(), person.display());
+    Ok(())
+}
+
+/// Delete a person's claims and any document still held, for account deletion
+pub async fn forget(person: &RecordId) -> Result<(), Error> {
+    forget_where("person = $record", person).await
+}
+
+/// Delete every claim on an organization, for organization deletion
+pub async fn forget_organization(organization: &RecordId) -> Result<(), Error> {
+    forget_where("organization = $record", organization).await
+}
+
+async fn forget_where(condition: &str, record: &RecordId) -> Result<(), Error> {
+    let keys: Vec<String> = DB
+        .query(format!(
+            "SELECT VALUE document_key FROM org_claim WHERE {} AND document_key != NONE",
+            condition
+        ))
+        .bind(("record", record.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to load organization claims: {}", e)))?
+        .take(0)?;
+    for key in &keys {
+        delete_document(Some(key)).await;
+    }
+
+    DB.query(format!("DELETE FROM org_claim WHERE {}", condition))
+        .bind(("record", record.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to delete organization claims: {}", e)))?;
+    Ok(())
+}
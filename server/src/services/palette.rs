@@ -0,0 +1,269 @@
+//! The Cmd-K command palette
+//!
+//! One list to jump anywhere from: the site's pages and settings, the
+//! signed-in person's productions and recent chats, and the navbar search's
+//! suggestions. Everything is filtered here, on the server: the pages only a
+//! signed-in person has are left out for visitors, productions and chats come
+//! from the caller's own memberships and conversations, and search hits for
+//! people they've blocked or muted, or who blocked them, are dropped.
+
+use serde::Serialize;
+use std::cmp::Reverse;
+use surrealdb::types::{RecordId, SurrealValue};
+
+use crate::db::reader;
+use crate::error::{Error, Result};
+use crate::models::block::BlockModel;
+use crate::services::search_suggest;
+
+/// Items shown per group
+pub const GROUP_LIMIT: usize = 6;
+
+/// Productions and conversations looked through for a query
+const CANDIDATES: usize = 50;
+
+pub const GROUP_PAGES: &str = "Go to";
+pub const GROUP_SETTINGS: &str = "Settings";
+pub const GROUP_PRODUCTIONS: &str = "Your productions";
+pub const GROUP_CHATS: &str = "Recent chats";
+pub const GROUP_SEARCH: &str = "Search";
+
+/// Something to jump to from the palette
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PaletteItem {
+    /// One of the `GROUP_*` headings
+    pub group: &'static str,
+    pub label: String,
+    /// Shown beside the label: a role, or what kind of search hit it is
+    pub hint: Option<String>,
+    pub url: String,
+    /// Other words the item is found by
+    #[serde(skip_serializing)]
+    pub keywords: &'static str,
+}
+
+impl PaletteItem {
+    pub fn new(group: &'static str, label: impl Into<String>, url: impl Into<String>) -> Self {
+        Self {
+            group,
+            label: label.into(),
+            hint: None,
+            url: url.into(),
+            keywords: "",
+        }
+    }
+
+    fn with_hint(mut self, hint: impl Into<String>) -> Self {
+        self.hint = Some(hint.into());
+        self
+    }
+}
+
+/// Pages as (label, URL, other words it's found by, needs an account)
+const PAGES: &[(&str, &str, &str, bool)] = &[
+    ("Home", "/", "start", false),
+    ("People", "/people", "crew cast talent", false),
+    ("Organizations", "/orgs", "companies studios", false),
+    ("Productions", "/productions", "projects films", false),
+    ("Locations", "/locations", "places", false),
+    ("Jobs", "/jobs", "work hiring", false),
+    ("Messages", "/messages", "inbox chats dm", true),
+    ("Notifications", "/notifications", "alerts", true),
+    ("Your profile", "/profile", "me", true),
+    ("My productions", "/my-productions", "projects", true),
+    ("My jobs", "/my-jobs", "postings applications", true),
+    ("My organizations", "/my-orgs", "companies", true),
+    ("Equipment", "/equipment", "gear kit", true),
+    ("Likes", "/likes", "saved favorites", true),
+    ("Saved searches", "/search/saved", "alerts", true),
+];
+
+/// Settings pages, all of which need an account
+const SETTINGS: &[(&str, &str, &str)] = &[
+    ("Account settings", "/account", "password email username"),
+    ("Edit profile", "/profile/edit", "bio skills photo"),
+    ("Availability", "/account/availability", "calendar dates"),
+    ("Search alerts", "/account/alerts", "notifications"),
+    ("Blocked and muted people", "/account/blocks", "block mute"),
+    ("Representation", "/account/representation", "agent manager"),
+    ("Connected apps", "/account/apps", "oauth api"),
+    ("Export your data", "/account/export", "download"),
+];
+
+/// The pages and settings a visitor (or, with `signed_in`, a member) can open
+pub fn targets(signed_in: bool) -> Vec<PaletteItem> {
+    let pages = PAGES
+        .iter()
+        .filter(|(_, _, _, needs_account)| signed_in || !*needs_account)
+        .map(|(label, url, keywords, _)| (GROUP_PAGES, *label, *url, *keywords));
+    let settings = SETTINGS
+        .iter()
+        .filter(|_| signed_in)
+        .map(|(label, url, keywords)| (GROUP_SETTINGS, *label, *url, *keywords));
+    pages
+        .chain(settings)
+        .map(|(group, label, url, keywords)| PaletteItem {
+            keywords,
+            ..PaletteItem::new(group, label, url)
+        })
+        .collect()
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// How well `item` matches `query`: 2 when the label starts with it, 1 when
+/// every word of it starts a word of the label or keywords, None otherwise.
+/// An empty query matches everything.
+pub fn score(query: &str, item: &PaletteItem) -> Option<u8> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Some(0);
+    }
+    if item.label.to_lowercase().starts_with(&query) {
+        return Some(2);
+    }
+    let known: Vec<String> = words(&item.label).chain(words(item.keywords)).collect();
+    words(&query)
+        .all(|q| known.iter().any(|w| w.starts_with(&q)))
+        .then_some(1)
+}
+
+/// The items matching `query`, at most `limit` per group. Groups stay
+/// together in the order they first appear; within one the best matches come
+/// first and ties keep their order.
+pub fn filter(query: &str, items: Vec<PaletteItem>, limit: usize) -> Vec<PaletteItem> {
+    let mut groups: Vec<&'static str> = Vec::new();
+    let mut scored: Vec<(usize, Reverse<u8>, PaletteItem)> = Vec::new();
+    for item in items {
+        let Some(score) = score(query, &item) else {
+            continue;
+        };
+        let group = match groups.iter().position(|g| *g == item.group) {
+            Some(group) => group,
+            None => {
+                groups.push(item.group);
+                groups.len() - 1
+            }
+        };
+        scored.push((group, Reverse(score), item));
+    }
+    scored.sort_by_key(|(group, score, _)| (*group, *score));
+
+    let mut kept: Vec<PaletteItem> = Vec::new();
+    for (_, _, item) in scored {
+        if kept.iter().filter(|k| k.group == item.group).count() < limit {
+            kept.push(item);
+        }
+    }
+    kept
+}
+
+#[derive(Debug, SurrealValue)]
+struct Row {
+    label: String,
+    hint: Option<String>,
+    url: String,
+}
+
+/// The person's productions (newest membership first) and conversations
+/// (latest message first)
+async fn own_items(person: &RecordId) -> Result<Vec<PaletteItem>> {
+    // The inner selects keep the sort key, which `Row` doesn't have
+    let mut response = reader()
+        .query(
+            "SELECT label, hint, url FROM (
+                 SELECT out.title AS label, role AS hint, '/productions/' + out.slug AS url,
+                     created_at
+                 FROM member_of
+                 WHERE in = $person AND <string> type::table(out) = 'production'
+                     AND invitation_status = 'accepted'
+                 ORDER BY created_at DESC LIMIT $limit
+             );
+             SELECT label, hint, url FROM (
+                 SELECT
+                     (IF participant_a = $person THEN participant_b.name ?? participant_b.username
+                      ELSE participant_a.name ?? participant_a.username END) ?? 'Deleted User'
+                         AS label,
+                     NONE AS hint,
+                     '/messages/' + <string> id AS url,
+                     last_message_at
+                 FROM conversation
+                 WHERE (participant_a = $person OR participant_b = $person)
+                     AND $person NOT IN deleted_by
+                 ORDER BY last_message_at DESC LIMIT $limit
+             );",
+        )
+        .bind(("person", person.clone()))
+        .bind(("limit", CANDIDATES as i64))
+        .await?;
+    let productions: Vec<Row> = response.take(0)?;
+    let chats: Vec<Row> = response.take(1)?;
+
+    Ok(productions
+        .into_iter()
+        .map(|row| (GROUP_PRODUCTIONS, row))
+        .chain(chats.into_iter().map(|row| (GROUP_CHATS, row)))
+        .map(|(group, row)| {
+            let item = PaletteItem::new(group, row.label, row.url);
+            match row.hint {
+                Some(hint) => item.with_hint(hint),
+                None => item,
+            }
+        })
+        .collect())
+}
+
+/// Profile URLs of people this person shouldn't be shown
+async fn hidden_urls(person: &RecordId) -> Result<Vec<String>> {
+    let ids = BlockModel::hidden_ids(person)
+        .await?
+        .iter()
+        .map(|id| RecordId::parse_simple(id).map_err(|e| Error::BadRequest(e.to_string())))
+        .collect::<Result<Vec<_>>>()?;
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let usernames: Vec<String> = reader()
+        .query("SELECT VALUE username FROM $ids")
+        .bind(("ids", ids))
+        .await?
+        .take(0)?;
+    Ok(usernames.into_iter().map(|u| format!("/{}", u)).collect())
+}
+
+fn kind_hint(kind: &str) -> &'static str {
+    match kind {
+        "person" => "Person",
+        "organization" => "Org",
+        "location" => "Location",
+        "production" => "Production",
+        _ => "Result",
+    }
+}
+
+/// Everything matching `query` for the caller (`person` when signed in).
+/// An empty query lists the pages, productions and chats without searching.
+pub async fn palette(query: &str, person: Option<&RecordId>) -> Result<Vec<PaletteItem>> {
+    let mut items = targets(person.is_some());
+    let mut hidden = Vec::new();
+    if let Some(person) = person {
+        items.extend(own_items(person).await?);
+        hidden = hidden_urls(person).await?;
+    }
+    let mut items = filter(query, items, GROUP_LIMIT);
+
+    for hit in search_suggest::suggest(query).await {
+        let listed = items.iter().any(|i| i.url == hit.url);
+        if listed || (hit.kind == "person" && hidden.contains(&hit.url)) {
+            continue;
+        }
+        items.push(
+            PaletteItem::new(GROUP_SEARCH, hit.label, hit.url).with_hint(kind_hint(&hit.kind)),
+        );
+    }
+    Ok(items)
+}
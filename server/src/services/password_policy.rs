@@ -0,0 +1,176 @@
+//! Password strength rules and breached-password checks
+//!
+//! `validate` runs the configured rules first, then checks the password against
+//! known breaches using k-anonymity: only the first five hex characters of its
+//! SHA-1 hash are used to pick a range of candidate hashes, and the remaining
+//! suffix is matched locally. Ranges come from `PASSWORD_BREACH_DIR` when set
+//! (one `<PREFIX>.txt` file per prefix, the HIBP downloader layout), otherwise
+//! from the Have I Been Pwned range API while the `hibp_range_check` flag is on.
+//!
+//! Breach lookups fail open: if the range can't be read the password is accepted
+//! and a warning is logged, so an outage never blocks signups.
+
+use sha1::{Digest, Sha1};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::config::{PasswordPolicy, password_policy};
+use crate::services::flags;
+
+/// Feature flag gating the remote HIBP range lookup
+pub const HIBP_FLAG: &str = "hibp_range_check";
+
+const HIBP_RANGE_URL: &str = "https://api.pwnedpasswords.com/range/";
+
+/// Longest password accepted; bounds the cost of hashing
+pub const MAX_LENGTH: usize = 128;
+
+/// Check a new password against the policy and known breaches. `identifiers`
+/// are values the password must not contain (username, email local part).
+/// The error is a user-facing message listing every failed rule, ready to be
+/// shown next to the password field.
+pub async fn validate(password: &str, identifiers: &[&str]) -> Result<(), String> {
+    let policy = password_policy();
+
+    let problems = check_rules(password, policy, identifiers);
+    if !problems.is_empty() {
+        return Err(problems.join(" "));
+    }
+
+    if let Some(count) = breach_count(password).await {
+        debug!("Rejected password found in {} breaches", count);
+        return Err(
+            "This password has appeared in a data breach and can't be used. Please choose a different one."
+                .to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+/// Rule violations for a password, as user-facing sentences.
+pub fn check_rules(password: &str, policy: &PasswordPolicy, identifiers: &[&str]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let length = password.chars().count();
+
+    if length < policy.min_length {
+        problems.push(format!("Password must be at least {} characters.", policy.min_length));
+    }
+    if length > MAX_LENGTH {
+        problems.push(format!("Password must be at most {} characters.", MAX_LENGTH));
+    }
+    if policy.require_mixed_case
+        && !(password.chars().any(|c| c.is_uppercase()) && password.chars().any(|c| c.is_lowercase()))
+    {
+        problems.push("Password must contain both upper and lower case letters.".to_string());
+    }
+    if policy.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+        problems.push("Password must contain a number.".to_string());
+    }
+    if policy.require_symbol && password.chars().all(|c| c.is_alphanumeric()) {
+        problems.push("Password must contain a symbol.".to_string());
+    }
+
+    let lowered = password.to_lowercase();
+    if identifiers
+        .iter()
+        .map(|i| i.trim().to_lowercase())
+        .any(|i| i.len() >= 3 && lowered.contains(&i))
+    {
+        problems.push("Password must not contain your username or email.".to_string());
+    }
+
+    problems
+}
+
+/// One-line summary of the policy for form help text
+pub fn describe(policy: &PasswordPolicy) -> String {
+    let mut extras = Vec::new();
+    if policy.require_mixed_case {
+        extras.push("upper and lower case letters");
+    }
+    if policy.require_digit {
+        extras.push("a number");
+    }
+    if policy.require_symbol {
+        extras.push("a symbol");
+    }
+
+    if extras.is_empty() {
+        format!("Must be at least {} characters long", policy.min_length)
+    } else {
+        format!(
+            "Must be at least {} characters long and include {}",
+            policy.min_length,
+            extras.join(", ")
+        )
+    }
+}
+
+/// Uppercase hex SHA-1, the format used by breach corpora
+pub fn sha1_hex(password: &str) -> String {
+    Sha1::digest(password.as_bytes())
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect()
+}
+
+/// Find a hash suffix in a range listing (`SUFFIX:COUNT` per line). Padding
+/// entries with a count of 0 are ignored.
+pub fn find_in_range(range: &str, suffix: &str) -> Option<u64> {
+    range.lines().find_map(|line| {
+        let (candidate, count) = line.trim().split_once(':')?;
+        if !candidate.eq_ignore_ascii_case(suffix) {
+            return None;
+        }
+        count.trim().parse::<u64>().ok().filter(|c| *c > 0)
+    })
+}
+
+/// Number of breaches the password appears in, or `None` if it isn't known to
+/// be breached (or no breach source is available).
+async fn breach_count(password: &str) -> Option<u64> {
+    let hash = sha1_hex(password);
+    let (prefix, suffix) = hash.split_at(5);
+
+    let range = if let Some(dir) = &password_policy().breach_dir {
+        let path = dir.join(format!("{}.txt", prefix));
+        match tokio::fs::read_to_string(&path).await {
+            Ok(range) => range,
+            Err(e) => {
+                warn!("Failed to read breached-password range {}: {}", path.display(), e);
+                return None;
+            }
+        }
+    } else if flags::enabled(HIBP_FLAG, None).await {
+        fetch_hibp_range(prefix).await?
+    } else {
+        return None;
+    };
+
+    find_in_range(&range, suffix)
+}
+
+async fn fetch_hibp_range(prefix: &str) -> Option<String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(3))
+        .build()
+        .ok()?;
+
+    let result = client
+        .get(format!("{}{}", HIBP_RANGE_URL, prefix))
+        // Pad responses so the response size doesn't reveal the prefix's popularity
+        .header("Add-Padding", "true")
+        .header("User-Agent", "SlateHub")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status());
+
+    match result {
+        Ok(response) => response.text().await.ok(),
+        Err(e) => {
+            warn!("HIBP range lookup failed: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! Public read API for mirroring profiles
+//!
+//! Agency websites poll `/api/v1/public` to keep their talent pages in sync.
+//! Responses carry a strong ETag of their body, so a poll that sends it back in
+//! `If-None-Match` gets an empty 304 when nothing changed, and `Cache-Control`
+//! lets a CDN in front absorb repeat polls.
+//!
+//! Callers are rate limited per minute in fixed windows: by IP, or by OAuth
+//! app when they send an access token (any scope), with a higher allowance
+//! (see `config::PublicApi`). Counters live in memory, so each server instance
+//! counts on its own.
+//!
+//! Only what an anonymous visitor sees on a profile is returned, minus contact
+//! details and physical attributes. Minors' profiles aren't served at all.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::app_url;
+use crate::error::{Error, Result};
+use crate::models::involvement::InvolvementModel;
+use crate::models::organization::OrganizationModel;
+use crate::models::person::Person;
+use crate::record_id_ext::RecordIdExt;
+
+/// Length of a rate-limit window
+pub const WINDOW_SECS: u64 = 60;
+
+/// Counters kept before windows that have ended are swept out
+const MAX_TRACKED: usize = 10_000;
+
+/// Strong ETag for a response body
+pub fn etag_for(body: &[u8]) -> String {
+    let digest = Sha256::digest(body);
+    let hex: String = digest
+        .iter()
+        .take(16)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("\"{}\"", hex)
+}
+
+/// Whether an `If-None-Match` header matches the current ETag. Weak
+/// validators match too, as RFC 9110 asks for GET requests.
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
+
+/// Outcome of counting one request against a caller's allowance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the window resets
+    pub reset_secs: u64,
+}
+
+/// Fixed-window request counters keyed by caller
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<String, (u64, u32)>>,
+}
+
+impl RateLimiter {
+    /// Count a request from `key` at `now` (Unix seconds)
+    pub fn check(&self, key: &str, limit: u32, now: u64) -> RateLimit {
+        let window = now - now % WINDOW_SECS;
+        let reset_secs = window + WINDOW_SECS - now;
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        if windows.len() >= MAX_TRACKED {
+            windows.retain(|_, (start, _)| *start == window);
+        }
+        let entry = windows.entry(key.to_string()).or_insert((window, 0));
+        if entry.0 != window {
+            *entry = (window, 0);
+        }
+        let allowed = entry.1 < limit;
+        if allowed {
+            entry.1 += 1;
+        }
+        RateLimit {
+            allowed,
+            limit,
+            remaining: limit - entry.1,
+            reset_secs,
+        }
+    }
+}
+
+static LIMITER: LazyLock<RateLimiter> = LazyLock::new(RateLimiter::default);
+
+/// Count a request against the shared limiter
+pub fn check_rate(key: &str, limit: u32) -> RateLimit {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    LIMITER.check(key, limit, now)
+}
+
+// ============================
+// Payloads
+// ============================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicPhoto {
+    pub url: String,
+    pub thumbnail_url: String,
+    pub caption: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicReel {
+    pub url: String,
+    pub title: String,
+    pub platform: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicLink {
+    pub platform: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicCredit {
+    pub production: String,
+    pub production_url: String,
+    pub production_type: String,
+    pub role: Option<String>,
+    pub department: Option<String>,
+    pub release_date: Option<String>,
+    pub verified: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicProfile {
+    pub username: String,
+    pub name: String,
+    pub profile_url: String,
+    pub avatar: Option<String>,
+    pub headline: Option<String>,
+    pub bio: Option<String>,
+    pub location: Option<String>,
+    pub website: Option<String>,
+    pub verification_status: String,
+    pub skills: Vec<String>,
+    pub unions: Vec<String>,
+    pub languages: Vec<String>,
+    pub availability: Option<String>,
+    pub photos: Vec<PublicPhoto>,
+    pub reels: Vec<PublicReel>,
+    pub social_links: Vec<PublicLink>,
+    pub credits: Vec<PublicCredit>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PublicMember {
+    pub username: String,
+    pub name: Option<String>,
+    pub role: String,
+    pub profile_url: String,
+    /// Where to fetch the member's profile from this API
+    pub api_url: String,
+}
+
+pub fn profile_api_url(username: &str) -> String {
+    format!("{}/api/v1/public/profiles/{}", app_url(), username)
+}
+
+/// A person's profile as the API serves it
+pub async fn profile(username: &str) -> Result<PublicProfile> {
+    let person = Person::find_by_username(username)
+        .await?
+        .filter(|p| !p.is_minor)
+        .ok_or(Error::NotFound)?;
+    let credits = InvolvementModel::get_for_person(&person.id.to_raw_string())
+        .await?
+        .into_iter()
+        .map(|inv| PublicCredit {
+            production_url: format!("{}/productions/{}", app_url(), inv.production_slug),
+            production: inv.production_title,
+            production_type: inv.production_type,
+            role: inv.role,
+            department: inv.department,
+            release_date: inv.release_date,
+            verified: inv.verification_status == "verified",
+        })
+        .collect();
+
+    let name = person.get_display_name();
+    let avatar = person.get_avatar_url();
+    let profile = person.profile.unwrap_or_default();
+    Ok(PublicProfile {
+        profile_url: format!("{}/{}", app_url(), person.username),
+        username: person.username,
+        name,
+        avatar,
+        headline: profile.headline,
+        bio: profile.bio,
+        location: profile.location,
+        website: profile.website,
+        verification_status: person.verification_status,
+        skills: profile.skills,
+        unions: profile.unions,
+        languages: profile.languages,
+        availability: profile.availability,
+        photos: profile
+            .photos
+            .into_iter()
+            .map(|p| PublicPhoto {
+                url: p.url,
+                thumbnail_url: p.thumbnail_url,
+                caption: p.caption,
+            })
+            .collect(),
+        reels: profile
+            .reels
+            .into_iter()
+            .map(|r| PublicReel {
+                url: r.url,
+                title: r.title,
+                platform: r.platform,
+            })
+            .collect(),
+        social_links: profile
+            .social_links
+            .into_iter()
+            .map(|l| PublicLink {
+                platform: l.platform,
+                url: l.url,
+            })
+            .collect(),
+        credits,
+    })
+}
+
+/// The accepted members of a public organization, e.g. an agency's roster
+pub async fn organization_members(slug: &str) -> Result<Vec<PublicMember>> {
+    let model = OrganizationModel::new();
+    let organization = model.get_by_slug(slug).await?;
+    if !organization.public {
+        return Err(Error::NotFound);
+    }
+    let mut members: Vec<PublicMember> = Vec::new();
+    for member in model.get_members(&organization.id.to_raw_string()).await? {
+        if member.invitation_status != "accepted" {
+            continue;
+        }
+        // Minors aren't served, so they aren't listed either
+        let is_minor = Person::find_by_username(&member.person_username)
+            .await?
+            .is_none_or(|p| p.is_minor);
+        if is_minor {
+            continue;
+        }
+        members.push(PublicMember {
+            profile_url: format!("{}/{}", app_url(), member.person_username),
+            api_url: profile_api_url(&member.person_username),
+            username: member.person_username,
+            name: member.person_name,
+            role: member.role,
+        });
+    }
+    Ok(members)
+}
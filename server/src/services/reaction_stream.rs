@@ -0,0 +1,29 @@
+//! Live reaction updates
+//!
+//! Toggling a reaction publishes which reaction bar changed and who can see
+//! it. `/api/notifications/stream`, which every signed-in page already keeps
+//! open, passes the event on to those of them connected to this instance, and
+//! their page reloads that bar with its own counts.
+
+use std::sync::LazyLock;
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone)]
+pub struct ReactionEvent {
+    /// Element id of the reaction bar, e.g. "reactions-direct_message-abc"
+    pub region: String,
+    /// Person ids who can see the record
+    pub audience: Vec<String>,
+}
+
+static SENDER: LazyLock<broadcast::Sender<ReactionEvent>> =
+    LazyLock::new(|| broadcast::channel(256).0);
+
+pub fn subscribe() -> broadcast::Receiver<ReactionEvent> {
+    SENDER.subscribe()
+}
+
+/// Tell connected viewers a reaction bar changed. Nobody listening is fine.
+pub fn publish(event: ReactionEvent) {
+    let _ = SENDER.send(event);
+}
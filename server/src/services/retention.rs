@@ -0,0 +1,330 @@
+//! Data retention policies
+//!
+//! An organization's owners and admins choose how long it keeps what it
+//! collects: applications to its job postings (once a posting has closed),
+//! invitations nobody took up, and shortlist share links. Each rule is off
+//! until a period is set. The scheduled task applies every organization's
+//! rules once a day, and the retention page previews what the current rules
+//! would remove before anyone runs them by hand. Every purge that removes
+//! something is recorded with its count and who ran it. Applications to
+//! postings for a production under legal hold are kept until it's lifted.
+
+use chrono::{DateTime, Duration, Months, Utc};
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{error, info};
+
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::record_id_ext::RecordIdExt;
+
+pub const MAX_APPLICATION_MONTHS: i64 = 120;
+pub const MAX_DAYS: i64 = 730;
+
+/// Rules, as (key, label)
+pub const RULES: &[(&str, &str)] = &[
+    ("applications", "Applications"),
+    ("invites", "Unaccepted invitations"),
+    ("share_links", "Shortlist share links"),
+];
+
+/// "Unaccepted invitations" for "invites"
+pub fn rule_label(rule: &str) -> &'static str {
+    RULES
+        .iter()
+        .find(|(key, _)| *key == rule)
+        .map_or("Other", |(_, label)| *label)
+}
+
+fn parse_period(value: &str, max: i64, what: &str) -> Result<Option<i64>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    match value.parse::<i64>() {
+        Ok(n) if (1..=max).contains(&n) => Ok(Some(n)),
+        _ => Err(Error::Validation(format!(
+            "{} must be a whole number from 1 to {}, or blank to keep them",
+            what, max
+        ))),
+    }
+}
+
+/// An organization's rules; `None` keeps that kind of data indefinitely
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, SurrealValue)]
+pub struct RetentionPolicy {
+    /// Delete applications to closed postings this many months after they
+    /// were made
+    pub applications_months: Option<i64>,
+    /// Withdraw invitations still pending after this many days
+    pub invites_days: Option<i64>,
+    /// Turn shortlist share links off this many days after they were made
+    pub share_links_days: Option<i64>,
+}
+
+impl RetentionPolicy {
+    /// Rules from the form, where a blank field turns a rule off
+    pub fn parse(
+        applications_months: &str,
+        invites_days: &str,
+        share_links_days: &str,
+    ) -> Result<Self> {
+        Ok(Self {
+            applications_months: parse_period(
+                applications_months,
+                MAX_APPLICATION_MONTHS,
+                "Months to keep applications",
+            )?,
+            invites_days: parse_period(invites_days, MAX_DAYS, "Days to keep invitations")?,
+            share_links_days: parse_period(share_links_days, MAX_DAYS, "Days to keep share links")?,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.applications_months.is_none()
+            && self.invites_days.is_none()
+            && self.share_links_days.is_none()
+    }
+
+    /// Anything older than these is past its rule
+    pub fn cutoffs(&self, now: DateTime<Utc>) -> Cutoffs {
+        Cutoffs {
+            applications: self
+                .applications_months
+                .and_then(|months| now.checked_sub_months(Months::new(months as u32))),
+            invites: self.invites_days.map(|days| now - Duration::days(days)),
+            share_links: self.share_links_days.map(|days| now - Duration::days(days)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cutoffs {
+    pub applications: Option<DateTime<Utc>>,
+    pub invites: Option<DateTime<Utc>>,
+    pub share_links: Option<DateTime<Utc>>,
+}
+
+/// How much each rule removes, or would remove
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PurgeCounts {
+    pub applications: usize,
+    pub invites: usize,
+    pub share_links: usize,
+}
+
+impl PurgeCounts {
+    pub fn total(&self) -> usize {
+        self.applications + self.invites + self.share_links
+    }
+}
+
+/// One purge, for the record on the retention page
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct PurgeRecord {
+    pub rule: String,
+    pub count: i64,
+    /// Who ran it by hand; `None` for the scheduled task
+    pub triggered_by_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PurgeRecord {
+    pub fn rule_label(&self) -> &'static str {
+        rule_label(&self.rule)
+    }
+}
+
+pub async fn get_policy(org: &RecordId) -> Result<RetentionPolicy> {
+    let policy: Option<RetentionPolicy> = DB
+        .query(
+            "SELECT retention_applications_months AS applications_months,
+                retention_invites_days AS invites_days,
+                retention_share_links_days AS share_links_days
+            FROM ONLY $org",
+        )
+        .bind(("org", org.clone()))
+        .await?
+        .take(0)?;
+    Ok(policy.unwrap_or_default())
+}
+
+pub async fn set_policy(org: &RecordId, policy: &RetentionPolicy) -> Result<()> {
+    DB.query(
+        "UPDATE $org SET retention_applications_months = $applications,
+            retention_invites_days = $invites, retention_share_links_days = $share_links",
+    )
+    .bind(("org", org.clone()))
+    .bind(("applications", policy.applications_months))
+    .bind(("invites", policy.invites_days))
+    .bind(("share_links", policy.share_links_days))
+    .await?
+    .check()?;
+    Ok(())
+}
+
+/// What falls under each rule right now: (applications, member_of
+/// invitations, pending_invitation rows, shortlists)
+async fn due(
+    org: &RecordId,
+    cutoffs: &Cutoffs,
+) -> Result<(Vec<RecordId>, Vec<RecordId>, Vec<RecordId>, Vec<RecordId>)> {
+    let mut response = DB
+        .query(
+            "IF $applications_before = NONE { [] } ELSE {
+                SELECT VALUE id FROM application
+                WHERE out.posted_by = $org AND applied_at < $applications_before
+                    AND (out.status != 'open' OR out.expires_at <= time::now())
+                    AND out.related_production NOT IN (
+                        SELECT VALUE production FROM legal_hold
+                        WHERE lifted_at = NONE AND target = production
+                    )
+            };
+            IF $invites_before = NONE { [] } ELSE {
+                SELECT VALUE id FROM member_of
+                WHERE out = $org AND invitation_status = 'pending' AND invited_at < $invites_before
+            };
+            IF $invites_before = NONE { [] } ELSE {
+                SELECT VALUE id FROM pending_invitation
+                WHERE target_type = 'organization' AND target_id = $org_id
+                    AND status = 'pending' AND created_at < $invites_before
+            };
+            IF $share_links_before = NONE { [] } ELSE {
+                SELECT VALUE id FROM shortlist
+                WHERE job.posted_by = $org AND share_expires_at > time::now()
+                    AND (shared_at ?? updated_at) < $share_links_before
+            };",
+        )
+        .bind(("org", org.clone()))
+        .bind(("org_id", org.to_raw_string()))
+        .bind(("applications_before", cutoffs.applications))
+        .bind(("invites_before", cutoffs.invites))
+        .bind(("share_links_before", cutoffs.share_links))
+        .await?;
+    Ok((
+        response.take(0)?,
+        response.take(1)?,
+        response.take(2)?,
+        response.take(3)?,
+    ))
+}
+
+/// What the rules would remove if they ran now
+pub async fn preview(org: &RecordId, policy: &RetentionPolicy) -> Result<PurgeCounts> {
+    let (applications, memberships, pending, shortlists) =
+        due(org, &policy.cutoffs(Utc::now())).await?;
+    Ok(PurgeCounts {
+        applications: applications.len(),
+        invites: memberships.len() + pending.len(),
+        share_links: shortlists.len(),
+    })
+}
+
+/// Apply the rules and record what they removed. `triggered_by` is the
+/// person who ran them by hand.
+pub async fn enforce(
+    org: &RecordId,
+    policy: &RetentionPolicy,
+    triggered_by: Option<&RecordId>,
+) -> Result<PurgeCounts> {
+    let (applications, memberships, pending, shortlists) =
+        due(org, &policy.cutoffs(Utc::now())).await?;
+    let counts = PurgeCounts {
+        applications: applications.len(),
+        invites: memberships.len() + pending.len(),
+        share_links: shortlists.len(),
+    };
+    if counts.total() == 0 {
+        return Ok(counts);
+    }
+
+    DB.query(
+        "UPDATE talent_submission SET application = NONE WHERE application IN $applications;
+        DELETE $applications;
+        DELETE $memberships;
+        DELETE $pending;
+        UPDATE $shortlists SET share_token_hash = NONE, share_access = NONE, shared_at = NONE,
+            share_expires_at = NONE;
+        FOR $purge IN [['applications', $applications_count], ['invites', $invites_count],
+            ['share_links', $share_links_count]] {
+            IF $purge[1] > 0 {
+                CREATE retention_purge SET organization = $org, rule = $purge[0],
+                    count = $purge[1], triggered_by = $triggered_by;
+            };
+        };",
+    )
+    .bind(("applications", applications))
+    .bind(("memberships", memberships))
+    .bind(("pending", pending))
+    .bind(("shortlists", shortlists))
+    .bind(("applications_count", counts.applications as i64))
+    .bind(("invites_count", counts.invites as i64))
+    .bind(("share_links_count", counts.share_links as i64))
+    .bind(("org", org.clone()))
+    .bind(("triggered_by", triggered_by.cloned()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to apply retention rules: {}", e)))?
+    .check()?;
+
+    info!(
+        "Retention purge for {}: {} applications, {} invitations, {} share links{}",
+        org.display(),
+        counts.applications,
+        counts.invites,
+        counts.share_links,
+        triggered_by
+            .map(|person| format!(" (run by {})", person.display()))
+            .unwrap_or_default()
+    );
+    Ok(counts)
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct PolicyRow {
+    id: RecordId,
+    applications_months: Option<i64>,
+    invites_days: Option<i64>,
+    share_links_days: Option<i64>,
+}
+
+/// Apply every organization's rules, for the scheduled task. One
+/// organization failing doesn't hold up the rest.
+pub async fn enforce_all() -> Result<()> {
+    let rows: Vec<PolicyRow> = DB
+        .query(
+            "SELECT id, retention_applications_months AS applications_months,
+                retention_invites_days AS invites_days,
+                retention_share_links_days AS share_links_days
+            FROM organization
+            WHERE retention_applications_months != NONE OR retention_invites_days != NONE
+                OR retention_share_links_days != NONE",
+        )
+        .await?
+        .take(0)?;
+
+    for row in rows {
+        let policy = RetentionPolicy {
+            applications_months: row.applications_months,
+            invites_days: row.invites_days,
+            share_links_days: row.share_links_days,
+        };
+        if let Err(e) = enforce(&row.id, &policy, None).await {
+            error!("Retention rules failed for {}: {}", row.id.display(), e);
+        }
+    }
+    Ok(())
+}
+
+/// The organization's recent purges, newest first
+pub async fn history(org: &RecordId) -> Result<Vec<PurgeRecord>> {
+    Ok(DB
+        .query(
+            "SELECT rule, count, triggered_by.name ?? triggered_by.username AS triggered_by_name,
+                created_at
+            FROM retention_purge WHERE organization = $org
+            ORDER BY created_at DESC LIMIT 50",
+        )
+        .bind(("org", org.clone()))
+        .await?
+        .take(0)?)
+}
@@ -2,18 +2,23 @@
 //!
 //! This module provides a generic S3 interface that works with any S3-compatible
 //! backend (RustFS, AWS S3, etc.) using the AWS S3 SDK.
+//!
+//! In multi-tenant mode keys are transparently stored under the tenant's
+//! prefix (see `services::tenants`), so media URLs and stored keys stay the same.
 
 use aws_config::BehaviorVersion;
 use aws_sdk_s3::{
     Client,
     config::{Credentials, Region},
     primitives::ByteStream,
+    types::{CompletedMultipartUpload, CompletedPart},
 };
 use bytes::Bytes;
 use std::time::Duration;
 use tracing::{debug, info, warn};
 
 use crate::error::{Error, Result};
+use crate::services::tenants;
 
 /// S3 service configuration
 pub struct S3Config {
@@ -27,16 +32,24 @@ pub struct S3Config {
 impl Default for S3Config {
     fn default() -> Self {
         Self {
-            endpoint: std::env::var("S3_ENDPOINT")
+            endpoint: crate::settings::var("S3_ENDPOINT")
                 .unwrap_or_else(|_| "http://localhost:9000".to_string()),
-            access_key: std::env::var("S3_ACCESS_KEY").unwrap_or_else(|_| "admin".to_string()),
-            secret_key: std::env::var("S3_SECRET_KEY").unwrap_or_else(|_| "password".to_string()),
-            bucket_name: std::env::var("S3_BUCKET").unwrap_or_else(|_| "slatehub".to_string()),
-            region: std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+            access_key: crate::settings::var("S3_ACCESS_KEY").unwrap_or_else(|_| "admin".to_string()),
+            secret_key: crate::settings::var("S3_SECRET_KEY").unwrap_or_else(|_| "password".to_string()),
+            bucket_name: crate::settings::var("S3_BUCKET").unwrap_or_else(|_| "slatehub".to_string()),
+            region: crate::settings::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
         }
     }
 }
 
+/// An object as listed in the bucket
+#[derive(Debug, Clone)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+    pub etag: Option<String>,
+}
+
 /// Generic S3-compatible storage service
 pub struct S3Service {
     client: Client,
@@ -187,7 +200,7 @@ impl S3Service {
             .client
             .put_object()
             .bucket(&self.config.bucket_name)
-            .key(key)
+            .key(self.object_key(key))
             .body(body)
             .content_type(content_type);
 
@@ -205,7 +218,9 @@ impl S3Service {
 
         Ok(format!(
             "{}/{}/{}",
-            self.config.endpoint, self.config.bucket_name, key
+            self.config.endpoint,
+            self.config.bucket_name,
+            self.object_key(key)
         ))
     }
 
@@ -222,7 +237,7 @@ impl S3Service {
             .client
             .put_object()
             .bucket(&self.config.bucket_name)
-            .key(key)
+            .key(self.object_key(key))
             .content_type(content_type)
             .presigned(presigning_config)
             .await
@@ -244,7 +259,7 @@ impl S3Service {
             .client
             .get_object()
             .bucket(&self.config.bucket_name)
-            .key(key)
+            .key(self.object_key(key))
             .presigned(presigning_config)
             .await
             .map_err(|e| Error::Internal(format!("Failed to generate presigned URL: {}", e)))?;
@@ -259,7 +274,7 @@ impl S3Service {
         self.client
             .delete_object()
             .bucket(&self.config.bucket_name)
-            .key(key)
+            .key(self.object_key(key))
             .send()
             .await
             .map_err(|e| Error::Internal(format!("Failed to delete file: {}", e)))?;
@@ -268,10 +283,106 @@ impl S3Service {
         Ok(())
     }
 
+    /// Start a multipart upload, returning its upload ID
+    pub async fn create_multipart_upload(&self, key: &str, content_type: &str) -> Result<String> {
+        debug!("Starting multipart upload: {}", key);
+
+        let output = self
+            .client
+            .create_multipart_upload()
+            .bucket(&self.config.bucket_name)
+            .key(self.object_key(key))
+            .content_type(content_type)
+            .send()
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to start multipart upload: {}", e)))?;
+
+        output
+            .upload_id()
+            .map(|id| id.to_string())
+            .ok_or_else(|| Error::Internal("Multipart upload has no upload ID".to_string()))
+    }
+
+    /// Upload one part of a multipart upload, returning its ETag. Parts are
+    /// numbered from 1 and all but the last must be at least 5 MB.
+    pub async fn upload_part(&self, key: &str, upload_id: &str, part_number: i32, data: Bytes) -> Result<String> {
+        let output = self
+            .client
+            .upload_part()
+            .bucket(&self.config.bucket_name)
+            .key(self.object_key(key))
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from(data))
+            .send()
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to upload part {}: {}", part_number, e)))?;
+
+        output
+            .e_tag()
+            .map(|etag| etag.to_string())
+            .ok_or_else(|| Error::Internal(format!("Part {} has no ETag", part_number)))
+    }
+
+    /// Assemble the uploaded parts, given as (part number, ETag), into the object
+    pub async fn complete_multipart_upload(&self, key: &str, upload_id: &str, parts: &[(i32, String)]) -> Result<()> {
+        debug!("Completing multipart upload: {} ({} parts)", key, parts.len());
+
+        let parts = parts
+            .iter()
+            .map(|(number, etag)| CompletedPart::builder().part_number(*number).e_tag(etag).build())
+            .collect();
+
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.config.bucket_name)
+            .key(self.object_key(key))
+            .upload_id(upload_id)
+            .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+            .send()
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to complete multipart upload: {}", e)))?;
+
+        info!("Multipart upload completed: {}", key);
+        Ok(())
+    }
+
+    /// Abandon a multipart upload, freeing the parts stored so far
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        debug!("Aborting multipart upload: {}", key);
+
+        self.client
+            .abort_multipart_upload()
+            .bucket(&self.config.bucket_name)
+            .key(self.object_key(key))
+            .upload_id(upload_id)
+            .send()
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to abort multipart upload: {}", e)))?;
+        Ok(())
+    }
+
     /// List all object keys in the bucket
     pub async fn list_all_objects(&self) -> Result<Vec<String>> {
-        let mut keys = Vec::new();
+        Ok(self.list_objects().await?.into_iter().map(|o| o.key).collect())
+    }
+
+    /// Key of an object in the bucket. Inside a tenant's scope keys are kept
+    /// under the tenant's prefix; callers always deal in unprefixed keys.
+    pub fn object_key(&self, key: &str) -> String {
+        match crate::db::current_tenant() {
+            Some(slug) => format!("{}{}", tenants::s3_prefix(&slug), key),
+            None => key.to_string(),
+        }
+    }
+
+    /// List every object in the bucket with its size and ETag. Inside a
+    /// tenant's scope only that tenant's objects are listed; outside, tenants'
+    /// objects are left out.
+    pub async fn list_objects(&self) -> Result<Vec<ObjectInfo>> {
+        let mut objects = Vec::new();
         let mut continuation_token: Option<String> = None;
+        let tenant_prefix = crate::db::current_tenant().map(|slug| tenants::s3_prefix(&slug));
 
         loop {
             let mut req = self
@@ -279,6 +390,10 @@ impl S3Service {
                 .list_objects_v2()
                 .bucket(&self.config.bucket_name);
 
+            if let Some(prefix) = &tenant_prefix {
+                req = req.prefix(prefix);
+            }
+
             if let Some(token) = continuation_token.take() {
                 req = req.continuation_token(token);
             }
@@ -289,9 +404,19 @@ impl S3Service {
                 .map_err(|e| Error::Internal(format!("Failed to list S3 objects: {}", e)))?;
 
             for obj in resp.contents() {
-                if let Some(key) = obj.key() {
-                    keys.push(key.to_string());
-                }
+                let Some(key) = obj.key() else {
+                    continue;
+                };
+                let key = match &tenant_prefix {
+                    Some(prefix) => key.strip_prefix(prefix.as_str()).unwrap_or(key),
+                    None if key.starts_with(tenants::S3_PREFIX) && tenants::enabled() => continue,
+                    None => key,
+                };
+                objects.push(ObjectInfo {
+                    key: key.to_string(),
+                    size: obj.size().unwrap_or(0).max(0) as u64,
+                    etag: obj.e_tag().map(|t| t.trim_matches('"').to_string()),
+                });
             }
 
             if resp.is_truncated() == Some(true) {
@@ -301,7 +426,7 @@ impl S3Service {
             }
         }
 
-        Ok(keys)
+        Ok(objects)
     }
 
     /// Get the bucket name
@@ -315,7 +440,7 @@ impl S3Service {
             .client
             .head_object()
             .bucket(&self.config.bucket_name)
-            .key(key)
+            .key(self.object_key(key))
             .send()
             .await
         {
@@ -332,7 +457,7 @@ impl S3Service {
             .client
             .get_object()
             .bucket(&self.config.bucket_name)
-            .key(key)
+            .key(self.object_key(key))
             .send()
             .await
             .map_err(|e| Error::Internal(format!("Failed to download file: {}", e)))?;
@@ -356,6 +481,53 @@ impl S3Service {
         );
         Ok((data, content_type))
     }
+
+    /// Stream a file from S3 to local disk, for files too large to hold in memory
+    pub async fn download_to_path(&self, key: &str, path: &std::path::Path) -> Result<u64> {
+        debug!("Downloading file from S3 to {}: {}", path.display(), key);
+
+        let result = self
+            .client
+            .get_object()
+            .bucket(&self.config.bucket_name)
+            .key(self.object_key(key))
+            .send()
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to download file: {}", e)))?;
+
+        let mut file = tokio::fs::File::create(path)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to create {}: {}", path.display(), e)))?;
+        let mut body = result.body.into_async_read();
+        let written = tokio::io::copy(&mut body, &mut file)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to read file data: {}", e)))?;
+
+        info!("File downloaded successfully: {} ({} bytes)", key, written);
+        Ok(written)
+    }
+
+    /// Upload a file from local disk without reading it into memory
+    pub async fn upload_from_path(&self, key: &str, path: &std::path::Path, content_type: &str) -> Result<()> {
+        debug!("Uploading {} to S3: {}", path.display(), key);
+
+        let body = ByteStream::from_path(path)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to read {}: {}", path.display(), e)))?;
+
+        self.client
+            .put_object()
+            .bucket(&self.config.bucket_name)
+            .key(self.object_key(key))
+            .body(body)
+            .content_type(content_type)
+            .send()
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to upload file: {}", e)))?;
+
+        info!("File uploaded successfully: {}", key);
+        Ok(())
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -0,0 +1,541 @@
+//! Recurring background task scheduler
+//!
+//! Subsystems register named tasks at startup with an interval and optional
+//! jitter, then `start()` spawns one loop per task. A task never overlaps with
+//! itself: if the previous run is still in progress when the next tick fires,
+//! the tick is skipped. The outcome of every run is kept in memory and written
+//! to the `scheduled_task` table so it survives restarts and can be inspected
+//! from `/admin/tasks`. In multi-tenant mode each run covers the primary
+//! database and then every active tenant in turn.
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Deserialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use surrealdb::types::SurrealValue;
+use tracing::{debug, error, info, warn};
+
+use crate::db::DB;
+use crate::error::{Error, Result};
+
+type TaskFuture = Pin<Box<dyn Future<Output = Result<()>> + Send>>;
+type TaskFn = Arc<dyn Fn() -> TaskFuture + Send + Sync>;
+
+/// A recurring task definition, built with `ScheduledTask::new` and handed to `register`.
+pub struct ScheduledTask {
+    name: &'static str,
+    description: &'static str,
+    interval: Duration,
+    jitter: Duration,
+    run_on_start: bool,
+    func: TaskFn,
+}
+
+impl ScheduledTask {
+    pub fn new<F, Fut>(name: &'static str, interval: Duration, func: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        Self {
+            name,
+            description: "",
+            interval,
+            jitter: Duration::ZERO,
+            run_on_start: false,
+            func: Arc::new(move || Box::pin(func())),
+        }
+    }
+
+    pub fn with_description(mut self, description: &'static str) -> Self {
+        self.description = description;
+        self
+    }
+
+    /// Add a random delay of up to `jitter` before every run, so tasks with the
+    /// same interval don't all hit the database at the same moment.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Run once shortly after startup instead of waiting a full interval.
+    pub fn run_on_start(mut self) -> Self {
+        self.run_on_start = true;
+        self
+    }
+}
+
+/// Last-run bookkeeping for a task.
+#[derive(Debug, Clone, Default)]
+pub struct TaskStatus {
+    pub last_started: Option<DateTime<Utc>>,
+    pub last_finished: Option<DateTime<Utc>>,
+    pub last_duration_ms: Option<u64>,
+    pub last_error: Option<String>,
+    pub next_run: Option<DateTime<Utc>>,
+    pub run_count: u64,
+    pub failure_count: u64,
+    pub skipped_count: u64,
+}
+
+/// Snapshot of a registered task for display.
+#[derive(Debug, Clone)]
+pub struct TaskInfo {
+    pub name: String,
+    pub description: String,
+    pub interval_secs: u64,
+    pub jitter_secs: u64,
+    pub running: bool,
+    pub status: TaskStatus,
+}
+
+struct RegisteredTask {
+    task: ScheduledTask,
+    running: AtomicBool,
+    status: Mutex<TaskStatus>,
+}
+
+static REGISTRY: LazyLock<Mutex<Vec<Arc<RegisteredTask>>>> =
+    LazyLock::new(|| Mutex::new(Vec::new()));
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Register a task. Must be called before `start()`; later registrations are
+/// started immediately.
+pub fn register(task: ScheduledTask) {
+    let mut registry = REGISTRY.lock().unwrap();
+    if registry.iter().any(|t| t.task.name == task.name) {
+        warn!("Scheduled task '{}' registered twice, ignoring", task.name);
+        return;
+    }
+
+    debug!(
+        "Registered scheduled task '{}' (every {}s, jitter {}s)",
+        task.name,
+        task.interval.as_secs(),
+        task.jitter.as_secs()
+    );
+
+    let entry = Arc::new(RegisteredTask {
+        task,
+        running: AtomicBool::new(false),
+        status: Mutex::new(TaskStatus::default()),
+    });
+    registry.push(entry.clone());
+
+    if STARTED.load(Ordering::Relaxed) {
+        spawn_loop(entry);
+    }
+}
+
+/// Spawn the loop for every registered task. Persisted run history is loaded
+/// first so the admin view keeps showing the last run across restarts.
+pub async fn start() {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        warn!("Scheduler already started");
+        return;
+    }
+
+    load_persisted_status().await;
+
+    let tasks: Vec<Arc<RegisteredTask>> = REGISTRY.lock().unwrap().clone();
+    info!("Starting scheduler with {} task(s)", tasks.len());
+    for entry in tasks {
+        spawn_loop(entry);
+    }
+}
+
+/// Snapshot of all registered tasks, sorted by name.
+pub fn tasks() -> Vec<TaskInfo> {
+    let registry = REGISTRY.lock().unwrap();
+    let mut infos: Vec<TaskInfo> = registry
+        .iter()
+        .map(|t| TaskInfo {
+            name: t.task.name.to_string(),
+            description: t.task.description.to_string(),
+            interval_secs: t.task.interval.as_secs(),
+            jitter_secs: t.task.jitter.as_secs(),
+            running: t.running.load(Ordering::Relaxed),
+            status: t.status.lock().unwrap().clone(),
+        })
+        .collect();
+    infos.sort_by(|a, b| a.name.cmp(&b.name));
+    infos
+}
+
+/// Trigger a task outside its schedule. Returns `Ok(false)` if it is already running.
+pub fn run_now(name: &str) -> Result<bool> {
+    let entry = REGISTRY
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|t| t.task.name == name)
+        .cloned()
+        .ok_or(Error::NotFound)?;
+
+    if entry.running.load(Ordering::Relaxed) {
+        return Ok(false);
+    }
+
+    tokio::spawn(async move {
+        execute(&entry).await;
+    });
+    Ok(true)
+}
+
+fn spawn_loop(entry: Arc<RegisteredTask>) {
+    tokio::spawn(async move {
+        if entry.task.run_on_start {
+            tokio::time::sleep(Duration::from_secs(5) + jitter(entry.task.jitter)).await;
+            execute(&entry).await;
+        }
+
+        loop {
+            let delay = entry.task.interval + jitter(entry.task.jitter);
+            entry.status.lock().unwrap().next_run = chrono::Duration::from_std(delay)
+                .ok()
+                .map(|d| Utc::now() + d);
+
+            tokio::time::sleep(delay).await;
+            execute(&entry).await;
+        }
+    });
+}
+
+fn jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(rand::thread_rng().gen_range(0..=max.as_millis() as u64))
+}
+
+/// Run on a separate task so a panic is reported as a failure rather than
+/// leaving the running flag stuck
+async fn run_guarded<F>(task: F) -> Result<()>
+where
+    F: Future<Output = Result<()>> + Send + 'static,
+{
+    match tokio::spawn(task).await {
+        Ok(result) => result,
+        Err(e) => Err(Error::Internal(format!("task panicked: {}", e))),
+    }
+}
+
+async fn execute(entry: &RegisteredTask) {
+    let name = entry.task.name;
+
+    // Overlap prevention: skip this tick if the previous run hasn't finished
+    if entry.running.swap(true, Ordering::SeqCst) {
+        warn!("Scheduled task '{}' still running, skipping this run", name);
+        entry.status.lock().unwrap().skipped_count += 1;
+        return;
+    }
+
+    info!("Running scheduled task '{}'", name);
+    let started_at = Utc::now();
+    entry.status.lock().unwrap().last_started = Some(started_at);
+
+    let timer = Instant::now();
+    let mut result = run_guarded((entry.task.func)()).await;
+    // In multi-tenant mode the task runs again inside every tenant's database
+    for scope in crate::services::tenants::active_scopes().await {
+        let slug = scope.slug.clone();
+        let tenant_result = run_guarded(crate::db::with_tenant(scope, (entry.task.func)())).await;
+        if let Err(e) = tenant_result {
+            error!("Scheduled task '{}' failed for tenant {}: {}", name, slug, e);
+            result = Err(Error::Internal(format!("tenant {}: {}", slug, e)));
+        }
+    }
+    let duration_ms = timer.elapsed().as_millis() as u64;
+
+    let status = {
+        let mut status = entry.status.lock().unwrap();
+        status.last_finished = Some(Utc::now());
+        status.last_duration_ms = Some(duration_ms);
+        status.run_count += 1;
+        match &result {
+            Ok(()) => {
+                status.last_error = None;
+                info!("Scheduled task '{}' finished in {}ms", name, duration_ms);
+            }
+            Err(e) => {
+                status.failure_count += 1;
+                status.last_error = Some(e.to_string());
+                error!("Scheduled task '{}' failed after {}ms: {}", name, duration_ms, e);
+            }
+        }
+        status.clone()
+    };
+
+    entry.running.store(false, Ordering::SeqCst);
+
+    persist_status(name, &status).await;
+}
+
+async fn persist_status(name: &str, status: &TaskStatus) {
+    let result = DB
+        .query(
+            "UPSERT type::record('scheduled_task', $name) SET
+                last_started = $last_started,
+                last_finished = $last_finished,
+                last_duration_ms = $last_duration_ms,
+                last_error = $last_error,
+                run_count = $run_count,
+                failure_count = $failure_count",
+        )
+        .bind(("name", name.to_string()))
+        .bind(("last_started", status.last_started))
+        .bind(("last_finished", status.last_finished))
+        .bind(("last_duration_ms", status.last_duration_ms.map(|d| d as i64)))
+        .bind(("last_error", status.last_error.clone()))
+        .bind(("run_count", status.run_count as i64))
+        .bind(("failure_count", status.failure_count as i64))
+        .await;
+
+    if let Err(e) = result {
+        warn!("Failed to persist status for scheduled task '{}': {}", name, e);
+    }
+}
+
+async fn load_persisted_status() {
+    #[derive(Debug, Deserialize, SurrealValue)]
+    struct Row {
+        name: String,
+        last_started: Option<DateTime<Utc>>,
+        last_finished: Option<DateTime<Utc>>,
+        last_duration_ms: Option<i64>,
+        last_error: Option<String>,
+        run_count: Option<i64>,
+        failure_count: Option<i64>,
+    }
+
+    let rows: Vec<Row> = match DB
+        .query(
+            "SELECT <string> meta::id(id) AS name, last_started, last_finished, last_duration_ms,
+                    last_error, run_count, failure_count
+             FROM scheduled_task",
+        )
+        .await
+    {
+        Ok(mut response) => response.take(0).unwrap_or_default(),
+        Err(e) => {
+            warn!("Failed to load scheduled task history: {}", e);
+            return;
+        }
+    };
+
+    let registry = REGISTRY.lock().unwrap();
+    for row in rows {
+        if let Some(entry) = registry.iter().find(|t| t.task.name == row.name) {
+            let mut status = entry.status.lock().unwrap();
+            status.last_started = row.last_started;
+            status.last_finished = row.last_finished;
+            status.last_duration_ms = row.last_duration_ms.map(|d| d as u64);
+            status.last_error = row.last_error;
+            status.run_count = row.run_count.unwrap_or(0) as u64;
+            status.failure_count = row.failure_count.unwrap_or(0) as u64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+    use tokio::sync::Notify;
+
+    fn entry(task: ScheduledTask) -> Arc<RegisteredTask> {
+        Arc::new(RegisteredTask {
+            task,
+            running: AtomicBool::new(false),
+            status: Mutex::new(TaskStatus::default()),
+        })
+    }
+
+    fn snapshot(entry: &RegisteredTask) -> TaskStatus {
+        entry.status.lock().unwrap().clone()
+    }
+
+    #[test]
+    fn test_task_builder() {
+        let task = ScheduledTask::new("builder", Duration::from_secs(60), || async { Ok(()) });
+        assert_eq!(task.description, "");
+        assert_eq!(task.jitter, Duration::ZERO);
+        assert!(!task.run_on_start);
+
+        let task = task
+            .with_description("Does things")
+            .with_jitter(Duration::from_secs(10))
+            .run_on_start();
+        assert_eq!(task.description, "Does things");
+        assert_eq!(task.interval, Duration::from_secs(60));
+        assert_eq!(task.jitter, Duration::from_secs(10));
+        assert!(task.run_on_start);
+    }
+
+    #[test]
+    fn test_jitter_stays_within_bounds() {
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(jitter(Duration::from_millis(50)) <= Duration::from_millis(50));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_successful_run_is_recorded() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        let entry = entry(ScheduledTask::new(
+            "succeeds",
+            Duration::from_secs(60),
+            move || {
+                let counter = counter.clone();
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(())
+                }
+            },
+        ));
+
+        execute(&entry).await;
+        execute(&entry).await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        let status = snapshot(&entry);
+        assert_eq!(status.run_count, 2);
+        assert_eq!(status.failure_count, 0);
+        assert!(status.last_error.is_none());
+        assert!(status.last_started.is_some());
+        assert!(status.last_finished >= status.last_started);
+        assert!(status.last_duration_ms.is_some());
+        assert!(!entry.running.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_failures_are_counted_until_a_run_succeeds() {
+        let fail = Arc::new(AtomicBool::new(true));
+        let flag = fail.clone();
+        let entry = entry(ScheduledTask::new(
+            "fails",
+            Duration::from_secs(60),
+            move || {
+                let fail = flag.load(Ordering::SeqCst);
+                async move {
+                    if fail {
+                        Err(Error::Internal("boom".to_string()))
+                    } else {
+                        Ok(())
+                    }
+                }
+            },
+        ));
+
+        execute(&entry).await;
+        let status = snapshot(&entry);
+        assert_eq!(status.run_count, 1);
+        assert_eq!(status.failure_count, 1);
+        assert!(status.last_error.unwrap().contains("boom"));
+
+        // The next good run clears the error but keeps the failure count
+        fail.store(false, Ordering::SeqCst);
+        execute(&entry).await;
+        let status = snapshot(&entry);
+        assert_eq!(status.run_count, 2);
+        assert_eq!(status.failure_count, 1);
+        assert!(status.last_error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_panic_is_reported_as_failure() {
+        async fn explode() -> Result<()> {
+            panic!("task blew up")
+        }
+        let entry = entry(ScheduledTask::new(
+            "panics",
+            Duration::from_secs(60),
+            explode,
+        ));
+
+        execute(&entry).await;
+
+        let status = snapshot(&entry);
+        assert_eq!(status.failure_count, 1);
+        assert!(status.last_error.unwrap().contains("panicked"));
+        // The running flag is released so the next tick can run
+        assert!(!entry.running.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_run_is_skipped() {
+        let release = Arc::new(Notify::new());
+        let wait = release.clone();
+        let entry = entry(ScheduledTask::new(
+            "slow",
+            Duration::from_secs(60),
+            move || {
+                let wait = wait.clone();
+                async move {
+                    wait.notified().await;
+                    Ok(())
+                }
+            },
+        ));
+
+        let first = tokio::spawn({
+            let entry = entry.clone();
+            async move { execute(&entry).await }
+        });
+        while !entry.running.load(Ordering::SeqCst) {
+            tokio::task::yield_now().await;
+        }
+
+        // A tick while the first run is in progress does nothing
+        execute(&entry).await;
+        assert_eq!(snapshot(&entry).skipped_count, 1);
+        assert_eq!(snapshot(&entry).run_count, 0);
+
+        release.notify_one();
+        first.await.unwrap();
+        let status = snapshot(&entry);
+        assert_eq!(status.run_count, 1);
+        assert_eq!(status.skipped_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_registry() {
+        register(
+            ScheduledTask::new("registry_test", Duration::from_secs(60), || async {
+                Ok(())
+            })
+            .with_description("First"),
+        );
+        register(
+            ScheduledTask::new("registry_test", Duration::from_secs(30), || async {
+                Ok(())
+            })
+            .with_description("Second"),
+        );
+
+        // The second registration under the same name is ignored
+        let registered: Vec<TaskInfo> = tasks()
+            .into_iter()
+            .filter(|t| t.name == "registry_test")
+            .collect();
+        assert_eq!(registered.len(), 1);
+        assert_eq!(registered[0].description, "First");
+        assert_eq!(registered[0].interval_secs, 60);
+
+        let names: Vec<String> = tasks().into_iter().map(|t| t.name).collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+
+        assert!(matches!(run_now("no_such_task"), Err(Error::NotFound)));
+        assert!(run_now("registry_test").unwrap());
+    }
+}
@@ -5,24 +5,41 @@
 //!   1. Hard structural filters (location, skill, status, physical attributes) as WHERE clauses
 //!   2. Soft semantic gate: text CONTAINS or vector similarity above threshold
 //!   3. Scoring: weighted text match + vector similarity
+//!   4. Hybrid fusion: a BM25 pass over the table's full-text indexes runs
+//!      first; its hits also pass the gate, and the two rankings are merged
+//!      with reciprocal rank fusion so exact name matches aren't buried
+//!   5. Optional reranking: with `SEARCH_RERANK` on, long queries also take
+//!      the `RERANK_CANDIDATES` rows most similar to the query, ungated,
+//!      re-score them with a cross-encoder and apply the vector threshold
+//!      cutoff afterwards; what's left is a third ranking in the fusion
 //!
 //! All user values flow through `$`-prefixed bind parameters — never `format!()`.
 //! All `id` fields are cast via `<string> id AS id` to avoid RecordId deserialization issues.
 //! Results are deserialized as `serde_json::Value` to sidestep SurrealValue derive limitations.
 
-use serde::Deserialize;
-use tracing::error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use surrealdb::types::RecordId;
+use tracing::{debug, error, warn};
 
-use crate::config::SearchWeights;
-use crate::db::DB;
+use crate::config::{SearchConfig, SearchWeights};
+use crate::db::reader;
 use crate::error::{Error, Result};
-use crate::services::search_utils::ParsedQuery;
+use crate::models::person_availability::{AvailabilityWindow, PersonAvailabilityModel};
+use crate::services::embedding::{self, FieldEmbedding};
+use crate::services::search_connections::{self, Connections};
+use crate::services::search_indexes;
+use crate::services::search_scope::OrgScope;
+use crate::services::search_syntax::SearchSyntax;
+use crate::services::search_utils::{self, ParsedQuery};
+use crate::services::search_visibility::{self, Viewer};
 
 // ---------------------------------------------------------------------------
 // Result types
 // ---------------------------------------------------------------------------
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PersonSearchResult {
     pub id: String,
     pub name: String,
@@ -31,13 +48,15 @@ pub struct PersonSearchResult {
     pub bio: Option<String>,
     pub location: Option<String>,
     pub skills: Vec<String>,
+    pub unions: Vec<String>,
     pub avatar_url: Option<String>,
+    #[serde(skip_serializing)]
     pub embedding_text: Option<String>,
     pub verification_status: String,
     pub score: f64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrganizationSearchResult {
     pub id: String,
     pub name: String,
@@ -45,12 +64,15 @@ pub struct OrganizationSearchResult {
     pub description: Option<String>,
     pub location: Option<String>,
     pub logo: Option<String>,
+    /// Name of the organization type, e.g. "Production Company"
+    pub org_type: Option<String>,
+    #[serde(skip_serializing)]
     pub embedding_text: Option<String>,
     pub verified: bool,
     pub score: f64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocationSearchResult {
     pub id: String,
     pub key: String,
@@ -60,11 +82,12 @@ pub struct LocationSearchResult {
     pub state: String,
     pub description: Option<String>,
     pub profile_photo: Option<String>,
+    #[serde(skip_serializing)]
     pub embedding_text: Option<String>,
     pub score: f64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProductionSearchResult {
     pub id: String,
     pub title: String,
@@ -74,11 +97,12 @@ pub struct ProductionSearchResult {
     pub location: Option<String>,
     pub poster_url: Option<String>,
     pub poster_photo: Option<String>,
+    #[serde(skip_serializing)]
     pub embedding_text: Option<String>,
     pub score: f64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobSearchResult {
     pub id: String,
     pub title: String,
@@ -87,6 +111,7 @@ pub struct JobSearchResult {
     pub poster_name: String,
     pub poster_type: String,
     pub role_count: i64,
+    #[serde(skip_serializing)]
     pub embedding_text: Option<String>,
     pub score: f64,
 }
@@ -98,9 +123,371 @@ pub struct JobSearchResult {
 pub struct SearchParams<'a> {
     pub query: &'a str,
     pub embedding: Option<&'a Vec<f32>>,
-    pub weights: &'a SearchWeights,
+    pub config: &'a SearchConfig,
     pub limit: usize,
     pub offset: usize,
+    /// The signed-in searcher's ties, to rank people and productions
+    /// connected to them higher
+    pub connections: Option<&'a Connections>,
+    /// Who's searching, to leave out people they can't see. `None` searches
+    /// as a signed-out visitor, who only finds public profiles.
+    pub viewer: Option<&'a Viewer>,
+    /// Operators in the query (`search_syntax`), applied as hard filters
+    pub syntax: Option<&'a SearchSyntax>,
+    /// Restricts every type to one organization's records (`search_scope`)
+    pub scope: Option<&'a OrgScope>,
+}
+
+/// Entity types a single-type search can target, as named in `?type=`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchKind {
+    People,
+    Organizations,
+    Locations,
+    Productions,
+    Jobs,
+}
+
+impl SearchKind {
+    pub const ALL: &[SearchKind] = &[
+        SearchKind::People,
+        SearchKind::Organizations,
+        SearchKind::Locations,
+        SearchKind::Productions,
+        SearchKind::Jobs,
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|k| k.as_str() == value)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchKind::People => "people",
+            SearchKind::Organizations => "organizations",
+            SearchKind::Locations => "locations",
+            SearchKind::Productions => "productions",
+            SearchKind::Jobs => "jobs",
+        }
+    }
+}
+
+/// Largest page a paginated search returns
+pub const MAX_PER_PAGE: usize = 50;
+
+/// Page position of a paginated search. Search results are ranked rather
+/// than counted, so there's no total; one extra result is fetched to tell
+/// whether another page follows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Pagination {
+    pub page: usize,
+    pub per_page: usize,
+    pub has_more: bool,
+    pub next_page: Option<usize>,
+}
+
+impl Pagination {
+    /// Page and page size from query parameters: pages count from 1 and
+    /// sizes are kept between 1 and `MAX_PER_PAGE`
+    pub fn clamp(page: Option<usize>, per_page: Option<usize>) -> (usize, usize) {
+        (
+            page.unwrap_or(1).max(1),
+            per_page.unwrap_or(20).clamp(1, MAX_PER_PAGE),
+        )
+    }
+
+    /// `SearchParams` limit and offset for a page, including the extra result
+    pub fn window(page: usize, per_page: usize) -> (usize, usize) {
+        (per_page + 1, (page - 1) * per_page)
+    }
+
+    /// Drop the extra result from a fetch of `window` size, noting whether
+    /// there was one
+    pub fn trim<T>(results: &mut Vec<T>, page: usize, per_page: usize) -> Self {
+        let has_more = results.len() > per_page;
+        results.truncate(per_page);
+        Pagination {
+            page,
+            per_page,
+            has_more,
+            next_page: has_more.then_some(page + 1),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Hybrid retrieval — BM25 keyword ranking fused with the scored ranking
+// ---------------------------------------------------------------------------
+
+/// Rank constant for reciprocal rank fusion; the usual 60 keeps one list's
+/// top hit from drowning out items both lists agree on
+pub const RRF_K: f64 = 60.0;
+
+/// Most keyword hits taken from the BM25 pass per table
+const KEYWORD_CANDIDATES: usize = 50;
+
+/// Rows per table the similarity pass hands the reranker
+pub const RERANK_CANDIDATES: usize = 50;
+
+/// Merge rankings of ids, best first, by reciprocal rank fusion: each id
+/// scores `1 / (RRF_K + rank)` in every ranking it appears in. Ties keep the
+/// order ids were first seen in.
+pub fn reciprocal_rank_fusion(rankings: &[Vec<String>]) -> Vec<(String, f64)> {
+    let mut fused: Vec<(String, f64)> = Vec::new();
+    for ranking in rankings {
+        for (rank, id) in ranking.iter().enumerate() {
+            let score = 1.0 / (RRF_K + rank as f64 + 1.0);
+            match fused.iter_mut().find(|(seen, _)| seen == id) {
+                Some((_, total)) => *total += score,
+                None => fused.push((id.clone(), score)),
+            }
+        }
+    }
+    fused.sort_by(|a, b| b.1.total_cmp(&a.1));
+    fused
+}
+
+/// Ids from `table` whose full-text indexed fields match `query`, by BM25
+/// relevance. Falls back to no keyword hits (the scored ranking alone) if the
+/// indexes are missing, e.g. before `search-indexes apply` has run.
+async fn keyword_ranking(table: &str, query: &str) -> Vec<String> {
+    let fields = search_indexes::fulltext_fields(table);
+    if query.trim().is_empty() || fields.is_empty() {
+        return Vec::new();
+    }
+
+    let matches: Vec<String> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| format!("{} @{}@ $keywords", field, i))
+        .collect();
+    let relevance: Vec<String> = (0..fields.len())
+        .map(|i| format!("(search::score({}) ?? 0)", i))
+        .collect();
+    let sql = format!(
+        "SELECT <string> id AS id, {} AS relevance FROM {} WHERE {} ORDER BY relevance DESC LIMIT $limit",
+        relevance.join(" + "),
+        table,
+        matches.join(" OR "),
+    );
+
+    let rows: Result<Vec<serde_json::Value>> = async {
+        Ok(reader()
+            .query(&sql)
+            .bind(("keywords", query.to_string()))
+            .bind(("limit", KEYWORD_CANDIDATES as i64))
+            .await?
+            .take(0)?)
+    }
+    .await;
+    match rows {
+        Ok(rows) => rows
+            .iter()
+            .map(|r| json_str(r, "id"))
+            .filter(|id| !id.is_empty())
+            .collect(),
+        Err(e) => {
+            warn!(error = %e, table, "Keyword search failed, ranking by score alone");
+            Vec::new()
+        }
+    }
+}
+
+/// Rows to fetch for a page: everything up to its end, plus room for every
+/// keyword hit (they sort first) so fusion sees both rankings in full, up to
+/// the type's `max_candidates`
+fn window(params: &SearchParams<'_>, weights: &SearchWeights, keyword_ids: &[String]) -> i64 {
+    weights.candidate_window(params.offset + params.limit + keyword_ids.len()) as i64
+}
+
+/// A row makes it into fusion if it scored or was a keyword hit
+fn is_candidate(row: &serde_json::Value, keyword_ids: &[String]) -> bool {
+    row["score"].as_f64().unwrap_or(0.0) > 0.0
+        || keyword_ids
+            .iter()
+            .any(|id| row["id"].as_str() == Some(id.as_str()))
+}
+
+/// The query's operators as WHERE conditions for a type's table
+fn syntax_filter(params: &SearchParams<'_>, kind: SearchKind) -> Option<String> {
+    params.syntax.and_then(|s| s.filter(kind))
+}
+
+/// Values the operator conditions read, bound as `$syntax_values`
+fn syntax_values(params: &SearchParams<'_>) -> Vec<String> {
+    params.syntax.map(SearchSyntax::values).unwrap_or_default()
+}
+
+/// The organization scope as a WHERE condition for a type's table
+fn scope_filter(params: &SearchParams<'_>, kind: SearchKind) -> Option<String> {
+    params.scope.map(|_| OrgScope::filter(kind).to_string())
+}
+
+/// A search result that can be re-ranked by fusion
+trait Ranked {
+    fn id(&self) -> &str;
+    fn score(&self) -> f64;
+    fn set_score(&mut self, score: f64);
+}
+
+macro_rules! impl_ranked {
+    ($($ty:ty),*) => {
+        $(impl Ranked for $ty {
+            fn id(&self) -> &str {
+                &self.id
+            }
+            fn score(&self) -> f64 {
+                self.score
+            }
+            fn set_score(&mut self, score: f64) {
+                self.score = score;
+            }
+        })*
+    };
+}
+
+impl_ranked!(
+    PersonSearchResult,
+    OrganizationSearchResult,
+    LocationSearchResult,
+    ProductionSearchResult,
+    JobSearchResult
+);
+
+/// `ranking` with its first `scores.len()` ids reordered by `scores`, best
+/// first; ties and the ids past them keep their order
+pub fn apply_rerank(ranking: Vec<String>, scores: &[f32]) -> Vec<String> {
+    let mut head: Vec<(String, f32)> = ranking.iter().cloned().zip(scores.to_vec()).collect();
+    head.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let rest = ranking.into_iter().skip(head.len());
+    head.into_iter().map(|(id, _)| id).chain(rest).collect()
+}
+
+/// Ids of `ranking` whose similarity in `candidates` is above `threshold`,
+/// in ranking order
+pub fn apply_cutoff(
+    ranking: Vec<String>,
+    candidates: &[(String, f64)],
+    threshold: f64,
+) -> Vec<String> {
+    ranking
+        .into_iter()
+        .filter(|id| {
+            candidates
+                .iter()
+                .any(|(candidate, similarity)| candidate == id && *similarity > threshold)
+        })
+        .collect()
+}
+
+/// Ids of `results` that scored, best first
+fn scored_ranking<T: Ranked>(results: &[T]) -> Vec<String> {
+    let mut scored: Vec<&T> = results.iter().filter(|r| r.score() > 0.0).collect();
+    scored.sort_by(|a, b| b.score().total_cmp(&a.score()));
+    scored.iter().map(|r| r.id().to_string()).collect()
+}
+
+/// The reranker's ranking for `table`. The `RERANK_CANDIDATES` rows most
+/// similar to the query are fetched without the vector threshold, reordered
+/// by how well the cross-encoder finds their text answers the query, and only
+/// then cut to those above the type's `vector_threshold`. Empty unless the
+/// reranker applies to the query; if the pass or the reranker fails, search
+/// goes on without it.
+async fn reranked_ranking(
+    table: &str,
+    weights: &SearchWeights,
+    params: &SearchParams<'_>,
+) -> Vec<String> {
+    let Some(embedding) = params.embedding else {
+        return Vec::new();
+    };
+    if !crate::config::search_rerank().applies_to(params.query)
+        || !embedding::reranker_initialized()
+    {
+        return Vec::new();
+    }
+
+    let sql = format!(
+        "SELECT <string> id AS id, embedding_text ?? '' AS text,
+            vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) AS similarity
+         FROM {} WHERE (embedding ?? embedding_q) IS NOT NONE
+         ORDER BY similarity DESC LIMIT $limit",
+        table,
+    );
+    let rows: Result<Vec<serde_json::Value>> = async {
+        Ok(reader()
+            .query(&sql)
+            .bind(("query_embedding", embedding.clone()))
+            .bind(("limit", RERANK_CANDIDATES as i64))
+            .await?
+            .take(0)?)
+    }
+    .await;
+    let rows: Vec<serde_json::Value> = match rows {
+        Ok(rows) => rows
+            .into_iter()
+            .filter(|r| !json_str(r, "id").is_empty())
+            .collect(),
+        Err(e) => {
+            warn!(error = %e, table, "Similarity pass failed, ranking without the reranker");
+            return Vec::new();
+        }
+    };
+    if rows.len() < 2 {
+        return Vec::new();
+    }
+
+    let candidates: Vec<(String, f64)> = rows
+        .iter()
+        .map(|r| (json_str(r, "id"), r["similarity"].as_f64().unwrap_or(0.0)))
+        .collect();
+    let documents: Vec<String> = rows.iter().map(|r| json_str(r, "text")).collect();
+    let ranking: Vec<String> = candidates.iter().map(|(id, _)| id.clone()).collect();
+    match embedding::rerank_async(params.query, documents).await {
+        Ok(scores) => apply_cutoff(
+            apply_rerank(ranking, &scores),
+            &candidates,
+            weights.vector_threshold,
+        ),
+        Err(e) => {
+            warn!(error = %e, table, "Reranking failed, ranking without the reranker");
+            Vec::new()
+        }
+    }
+}
+
+/// Fuse the scored ranking of `results` with the keyword and reranked
+/// rankings and cut the requested page. Each result's `score` becomes its
+/// fused score.
+async fn fuse<T: Ranked>(
+    mut results: Vec<T>,
+    keyword_ids: &[String],
+    reranked_ids: &[String],
+    params: &SearchParams<'_>,
+) -> Vec<T> {
+    let scored = scored_ranking(&results);
+    let present = |ids: &[String]| -> Vec<String> {
+        ids.iter()
+            .filter(|id| results.iter().any(|r| r.id() == id.as_str()))
+            .cloned()
+            .collect()
+    };
+    let keyword = present(keyword_ids);
+    let reranked = present(reranked_ids);
+
+    let mut fused = Vec::with_capacity(params.limit);
+    for (id, score) in reciprocal_rank_fusion(&[scored, keyword, reranked])
+        .into_iter()
+        .skip(params.offset)
+        .take(params.limit)
+    {
+        if let Some(i) = results.iter().position(|r| r.id() == id) {
+            let mut result = results.swap_remove(i);
+            result.set_score(score);
+            fused.push(result);
+        }
+    }
+    fused
 }
 
 // ---------------------------------------------------------------------------
@@ -111,10 +498,33 @@ pub async fn search_people(
     params: &SearchParams<'_>,
     parsed: &ParsedQuery,
     skill: Option<&str>,
+    available: Option<&AvailabilityWindow>,
+) -> Result<Vec<PersonSearchResult>> {
+    people_query(params, parsed, skill, available, None).await
+}
+
+/// People search restricted to the given people, e.g. those linked to one
+/// production
+pub async fn search_people_within(
+    params: &SearchParams<'_>,
+    parsed: &ParsedQuery,
+    people: &[RecordId],
+) -> Result<Vec<PersonSearchResult>> {
+    people_query(params, parsed, None, None, Some(people)).await
+}
+
+async fn people_query(
+    params: &SearchParams<'_>,
+    parsed: &ParsedQuery,
+    skill: Option<&str>,
+    available: Option<&AvailabilityWindow>,
+    within: Option<&[RecordId]>,
 ) -> Result<Vec<PersonSearchResult>> {
     let query_lower = parsed.cleaned.to_lowercase();
     let empty_emb: Vec<f32> = vec![];
-    let w = params.weights;
+    let w = &params.config.people;
+    let keyword_ids = keyword_ranking("person", params.query).await;
+    let reranked_ids = reranked_ranking("person", w, params).await;
 
     // --- hard filter clauses (structural, use bind params) ---
     let mut hard_parts: Vec<String> = Vec::new();
@@ -149,27 +559,59 @@ pub async fn search_people(
         );
     }
 
+    // Hair, eyes and body type are stored as vocabulary values
+    // (`physical_attributes`), so they match exactly
     if parsed.hair_color.is_some() {
-        hard_parts.push(
-            "string::lowercase(profile.hair_color ?? '') = string::lowercase($hair_filter)"
-                .to_string(),
-        );
+        hard_parts.push("profile.hair_color = $hair_filter".to_string());
     }
 
     if parsed.eye_color.is_some() {
+        hard_parts.push("profile.eye_color = $eye_filter".to_string());
+    }
+
+    if parsed.body_type.is_some() {
         hard_parts.push(
-            "string::lowercase(profile.eye_color ?? '') = string::lowercase($eye_filter)"
-                .to_string(),
+            // Minors' body type is never searchable
+            "profile.body_type = $body_filter AND is_minor != true".to_string(),
         );
     }
 
-    if parsed.body_type.is_some() {
+    // Heights are parsed to mm from either unit system; minors' heights are never searchable
+    if parsed.height_min_mm.is_some() {
+        hard_parts.push("profile.height_mm >= $height_min AND is_minor != true".to_string());
+    }
+
+    if parsed.height_max_mm.is_some() {
         hard_parts.push(
-            "string::lowercase(profile.body_type ?? '') = string::lowercase($body_filter)"
+            "profile.height_mm > 0 AND profile.height_mm <= $height_max AND is_minor != true"
                 .to_string(),
         );
     }
 
+    if parsed.verified_only {
+        hard_parts.push("verification_status = 'identity'".to_string());
+    }
+
+    // Free for the whole window: not marked unavailable or booked on any
+    // day of it, and not "not available" on their profile
+    let busy = match available {
+        Some(window) => {
+            hard_parts.push(
+                "id NOT IN $busy_people AND (profile.availability ?? '') != 'not_available'"
+                    .to_string(),
+            );
+            PersonAvailabilityModel::busy_during(window).await?
+        }
+        None => Vec::new(),
+    };
+
+    if within.is_some() {
+        hard_parts.push("id IN $within_people".to_string());
+    }
+
+    hard_parts.extend(syntax_filter(params, SearchKind::People));
+    hard_parts.extend(scope_filter(params, SearchKind::People));
+
     let has_hard_filters = !hard_parts.is_empty();
     let hard_filter = if has_hard_filters {
         format!("AND {}", hard_parts.join(" AND "))
@@ -192,13 +634,22 @@ pub async fn search_people(
                 OR string::lowercase(embedding_text ?? '') CONTAINS $query_lower
                 OR string::lowercase(string::join(', ', profile.skills ?? [])) CONTAINS $query_lower
                 OR string::lowercase(string::join(', ', profile.languages ?? [])) CONTAINS $query_lower
-                OR (embedding IS NOT NONE AND $has_embedding = true
-                    AND vector::similarity::cosine(embedding, $query_embedding) > {threshold})
+                OR ((embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    AND vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) > {threshold})
+                OR <string> id INSIDE $keyword_ids
             )",
             threshold = w.vector_threshold,
         )
     };
 
+    // People already linked to a production turn up in its own searches
+    // whatever their visibility; everywhere else it's up to the searcher
+    let visibility = if within.is_some() {
+        "true"
+    } else {
+        search_visibility::VIEWER_FILTER
+    };
+
     let sql = format!(
         "SELECT
             <string> id AS id,
@@ -208,43 +659,55 @@ pub async fn search_people(
             profile.bio AS bio,
             profile.location AS location,
             profile.skills AS skills,
+            profile.unions ?? [] AS unions,
             profile.avatar AS avatar_url,
             embedding_text,
             verification_status ?? 'none' AS verification_status,
+            (<string> id INSIDE $keyword_ids) AS keyword_hit,
             <float> (
                 (IF string::lowercase(name ?? '') CONTAINS $query_lower THEN {w_name} ELSE 0 END)
                 + (IF string::lowercase(username ?? '') CONTAINS $query_lower THEN {w_name} ELSE 0 END)
                 + (IF string::lowercase(profile.headline ?? '') CONTAINS $query_lower THEN {w_headline} ELSE 0 END)
                 + (IF string::lowercase(profile.bio ?? '') CONTAINS $query_lower THEN {w_headline} ELSE 0 END)
                 + (IF string::lowercase(profile.location ?? '') CONTAINS $query_lower THEN {w_location} ELSE 0 END)
-                + (IF embedding IS NOT NONE AND $has_embedding = true
-                    THEN vector::similarity::cosine(embedding, $query_embedding) * {w_vector}
+                + (IF (embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    THEN vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) * {w_vector}
                     ELSE 0
                 END)
+                {connection_score}
             ) AS score
         FROM person
         WHERE
             {text_vector_gate}
             {hard_filter}
-        ORDER BY score DESC
-        LIMIT $limit
-        START $offset",
+            AND (is_minor != true OR guardian_approved = true)
+            AND {visibility}
+        ORDER BY keyword_hit DESC, score DESC
+        LIMIT $window",
         w_name = w.name_match,
         w_headline = w.headline_match,
         w_location = w.location_match,
         w_vector = w.vector_multiplier,
+        connection_score = search_connections::person_score(w.connection_match, params.connections),
     );
 
     let has_embedding = params.embedding.is_some();
     let embedding_vec = params.embedding.cloned().unwrap_or(empty_emb);
+    let viewer = params.connections.cloned().unwrap_or_default();
+    let searcher = params.viewer.cloned().unwrap_or_default();
+
+    let scope = params.scope.cloned().unwrap_or_default();
 
-    let mut response = DB
+    let mut response = reader()
         .query(&sql)
         .bind(("query_lower", query_lower))
         .bind(("has_embedding", has_embedding))
         .bind(("query_embedding", embedding_vec))
-        .bind(("limit", params.limit as i64))
-        .bind(("offset", params.offset as i64))
+        .bind(("keyword_ids", keyword_ids.clone()))
+        .bind(("window", window(params, w, &keyword_ids)))
+        .bind(("viewer_city", viewer.city))
+        .bind(("viewer_orgs", viewer.organizations))
+        .bind(("viewer_collaborators", viewer.collaborators))
         .bind(("location_filter", parsed.location.clone().unwrap_or_default()))
         .bind(("skill_filter", skill.unwrap_or("").to_string()))
         .bind(("gender_filter", parsed.gender.clone().unwrap_or_default()))
@@ -253,6 +716,14 @@ pub async fn search_people(
         .bind(("hair_filter", parsed.hair_color.clone().unwrap_or_default()))
         .bind(("eye_filter", parsed.eye_color.clone().unwrap_or_default()))
         .bind(("body_filter", parsed.body_type.clone().unwrap_or_default()))
+        .bind(("height_min", parsed.height_min_mm.unwrap_or(0)))
+        .bind(("height_max", parsed.height_max_mm.unwrap_or(0)))
+        .bind(("busy_people", busy))
+        .bind(("within_people", within.unwrap_or_default().to_vec()))
+        .bind(("visibility_viewer", searcher.person))
+        .bind(("visibility_hidden", searcher.hidden))
+        .bind(("syntax_values", syntax_values(params)))
+        .bind(("scope_people", scope.people))
         .await
         .map_err(|e| {
             error!(error = %e, table = "person", "Search query failed");
@@ -264,9 +735,9 @@ pub async fn search_people(
         Error::Database(e.to_string())
     })?;
 
-    let results = rows
+    let results: Vec<_> = rows
         .into_iter()
-        .filter(|r| r["score"].as_f64().unwrap_or(0.0) > 0.0)
+        .filter(|r| is_candidate(r, &keyword_ids))
         .map(|r| PersonSearchResult {
             id: json_str(&r, "id"),
             name: json_str(&r, "name"),
@@ -278,6 +749,10 @@ pub async fn search_people(
                 .as_array()
                 .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
                 .unwrap_or_default(),
+            unions: r["unions"]
+                .as_array()
+                .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                .unwrap_or_default(),
             avatar_url: json_opt_str(&r, "avatar_url"),
             embedding_text: json_opt_str(&r, "embedding_text"),
             verification_status: json_str_or(&r, "verification_status", "none"),
@@ -285,7 +760,7 @@ pub async fn search_people(
         })
         .collect();
 
-    Ok(results)
+    Ok(fuse(results, &keyword_ids, &reranked_ids, params).await)
 }
 
 // ---------------------------------------------------------------------------
@@ -298,18 +773,31 @@ pub async fn search_organizations(
 ) -> Result<Vec<OrganizationSearchResult>> {
     let query_lower = params.query.to_lowercase();
     let empty_emb: Vec<f32> = vec![];
-    let w = params.weights;
+    let w = &params.config.organizations;
+    let keyword_ids = keyword_ranking("organization", params.query).await;
+    let reranked_ids = reranked_ranking("organization", w, params).await;
 
-    let has_location = location.is_some();
-    let hard_filter = if has_location {
-        "AND (string::lowercase(location ?? '') CONTAINS string::lowercase($location_filter) \
-         OR string::lowercase(embedding_text ?? '') CONTAINS string::lowercase($location_filter))"
-            .to_string()
+    let mut hard_parts: Vec<String> = Vec::new();
+
+    if location.is_some() {
+        hard_parts.push(
+            "(string::lowercase(location ?? '') CONTAINS string::lowercase($location_filter) \
+             OR string::lowercase(embedding_text ?? '') CONTAINS string::lowercase($location_filter))"
+                .to_string(),
+        );
+    }
+
+    hard_parts.extend(syntax_filter(params, SearchKind::Organizations));
+    hard_parts.extend(scope_filter(params, SearchKind::Organizations));
+
+    let has_hard_filters = !hard_parts.is_empty();
+    let hard_filter = if has_hard_filters {
+        format!("AND {}", hard_parts.join(" AND "))
     } else {
         String::new()
     };
 
-    let text_vector_gate = if has_location && query_lower.trim().is_empty() {
+    let text_vector_gate = if has_hard_filters && query_lower.trim().is_empty() {
         "true".to_string()
     } else {
         format!(
@@ -319,8 +807,9 @@ pub async fn search_organizations(
                 OR string::lowercase(description ?? '') CONTAINS $query_lower
                 OR string::lowercase(location ?? '') CONTAINS $query_lower
                 OR string::lowercase(embedding_text ?? '') CONTAINS $query_lower
-                OR (embedding IS NOT NONE AND $has_embedding = true
-                    AND vector::similarity::cosine(embedding, $query_embedding) > {threshold})
+                OR ((embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    AND vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) > {threshold})
+                OR <string> id INSIDE $keyword_ids
             )",
             threshold = w.vector_threshold,
         )
@@ -334,15 +823,17 @@ pub async fn search_organizations(
             description,
             location,
             logo,
+            type.name AS org_type,
             embedding_text,
             (verified ?? false) AS verified,
+            (<string> id INSIDE $keyword_ids) AS keyword_hit,
             <float> (
                 (IF string::lowercase(name ?? '') CONTAINS $query_lower THEN {w_name} ELSE 0 END)
                 + (IF string::lowercase(slug ?? '') CONTAINS $query_lower THEN {w_name} ELSE 0 END)
                 + (IF string::lowercase(description ?? '') CONTAINS $query_lower THEN {w_headline} ELSE 0 END)
                 + (IF string::lowercase(location ?? '') CONTAINS $query_lower THEN {w_location} ELSE 0 END)
-                + (IF embedding IS NOT NONE AND $has_embedding = true
-                    THEN vector::similarity::cosine(embedding, $query_embedding) * {w_vector}
+                + (IF (embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    THEN vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) * {w_vector}
                     ELSE 0
                 END)
             ) AS score
@@ -350,9 +841,8 @@ pub async fn search_organizations(
         WHERE
             {text_vector_gate}
             {hard_filter}
-        ORDER BY score DESC
-        LIMIT $limit
-        START $offset",
+        ORDER BY keyword_hit DESC, score DESC
+        LIMIT $window",
         w_name = w.name_match,
         w_headline = w.headline_match,
         w_location = w.location_match,
@@ -362,14 +852,18 @@ pub async fn search_organizations(
     let has_embedding = params.embedding.is_some();
     let embedding_vec = params.embedding.cloned().unwrap_or(empty_emb);
 
-    let mut response = DB
+    let scope = params.scope.cloned().unwrap_or_default();
+
+    let mut response = reader()
         .query(&sql)
         .bind(("query_lower", query_lower))
         .bind(("has_embedding", has_embedding))
         .bind(("query_embedding", embedding_vec))
-        .bind(("limit", params.limit as i64))
-        .bind(("offset", params.offset as i64))
+        .bind(("keyword_ids", keyword_ids.clone()))
+        .bind(("window", window(params, w, &keyword_ids)))
         .bind(("location_filter", location.unwrap_or("").to_string()))
+        .bind(("syntax_values", syntax_values(params)))
+        .bind(("scope_org", scope.organization))
         .await
         .map_err(|e| {
             error!(error = %e, table = "organization", "Search query failed");
@@ -381,9 +875,9 @@ pub async fn search_organizations(
         Error::Database(e.to_string())
     })?;
 
-    let results = rows
+    let results: Vec<_> = rows
         .into_iter()
-        .filter(|r| r["score"].as_f64().unwrap_or(0.0) > 0.0)
+        .filter(|r| is_candidate(r, &keyword_ids))
         .map(|r| OrganizationSearchResult {
             id: json_str(&r, "id"),
             name: json_str(&r, "name"),
@@ -391,13 +885,14 @@ pub async fn search_organizations(
             description: json_opt_str(&r, "description"),
             location: json_opt_str(&r, "location"),
             logo: json_opt_str(&r, "logo"),
+            org_type: json_opt_str(&r, "org_type"),
             embedding_text: json_opt_str(&r, "embedding_text"),
             verified: r["verified"].as_bool().unwrap_or(false),
             score: r["score"].as_f64().unwrap_or(0.0),
         })
         .collect();
 
-    Ok(results)
+    Ok(fuse(results, &keyword_ids, &reranked_ids, params).await)
 }
 
 // ---------------------------------------------------------------------------
@@ -411,7 +906,9 @@ pub async fn search_locations(
 ) -> Result<Vec<LocationSearchResult>> {
     let query_lower = params.query.to_lowercase();
     let empty_emb: Vec<f32> = vec![];
-    let w = params.weights;
+    let w = &params.config.locations;
+    let keyword_ids = keyword_ranking("location", params.query).await;
+    let reranked_ids = reranked_ranking("location", w, params).await;
 
     let mut hard_parts: Vec<String> = Vec::new();
 
@@ -427,6 +924,9 @@ pub async fn search_locations(
         );
     }
 
+    hard_parts.extend(syntax_filter(params, SearchKind::Locations));
+    hard_parts.extend(scope_filter(params, SearchKind::Locations));
+
     let has_hard_filters = !hard_parts.is_empty();
     let hard_filter = if has_hard_filters {
         format!("AND {}", hard_parts.join(" AND "))
@@ -445,8 +945,9 @@ pub async fn search_locations(
                 OR string::lowercase(address ?? '') CONTAINS $query_lower
                 OR string::lowercase(description ?? '') CONTAINS $query_lower
                 OR string::lowercase(embedding_text ?? '') CONTAINS $query_lower
-                OR (embedding IS NOT NONE AND $has_embedding = true
-                    AND vector::similarity::cosine(embedding, $query_embedding) > {threshold})
+                OR ((embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    AND vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) > {threshold})
+                OR <string> id INSIDE $keyword_ids
             )",
             threshold = w.vector_threshold,
         )
@@ -463,41 +964,52 @@ pub async fn search_locations(
             description,
             profile_photo,
             embedding_text,
+            (<string> id INSIDE $keyword_ids) AS keyword_hit,
             <float> (
                 (IF string::lowercase(name ?? '') CONTAINS $query_lower THEN {w_name} ELSE 0 END)
                 + (IF string::lowercase(city ?? '') CONTAINS $query_lower THEN {w_headline} ELSE 0 END)
                 + (IF string::lowercase(state ?? '') CONTAINS $query_lower THEN {w_location} ELSE 0 END)
                 + (IF string::lowercase(address ?? '') CONTAINS $query_lower THEN {w_location} ELSE 0 END)
                 + (IF string::lowercase(description ?? '') CONTAINS $query_lower THEN {w_location} ELSE 0 END)
-                + (IF embedding IS NOT NONE AND $has_embedding = true
-                    THEN vector::similarity::cosine(embedding, $query_embedding) * {w_vector}
+                + (IF (embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    THEN vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) * {w_vector}
                     ELSE 0
                 END)
             ) AS score
         FROM location
-        WHERE is_public = true AND {text_vector_gate}
+        WHERE {listed} AND {text_vector_gate}
         {hard_filter}
-        ORDER BY score DESC
-        LIMIT $limit
-        START $offset",
+        ORDER BY keyword_hit DESC, score DESC
+        LIMIT $window",
         w_name = w.name_match,
         w_headline = w.headline_match,
         w_location = w.location_match,
         w_vector = w.vector_multiplier,
+        // Scoped to an organization, its members also find its private locations
+        listed = if params.scope.is_some() {
+            "true"
+        } else {
+            "is_public = true"
+        },
     );
 
     let has_embedding = params.embedding.is_some();
     let embedding_vec = params.embedding.cloned().unwrap_or(empty_emb);
 
-    let mut response = DB
+    let scope = params.scope.cloned().unwrap_or_default();
+
+    let mut response = reader()
         .query(&sql)
         .bind(("query_lower", query_lower))
         .bind(("has_embedding", has_embedding))
         .bind(("query_embedding", embedding_vec))
-        .bind(("limit", params.limit as i64))
-        .bind(("offset", params.offset as i64))
+        .bind(("keyword_ids", keyword_ids.clone()))
+        .bind(("window", window(params, w, &keyword_ids)))
         .bind(("city_filter", city.unwrap_or("").to_string()))
         .bind(("state_filter", state.unwrap_or("").to_string()))
+        .bind(("syntax_values", syntax_values(params)))
+        .bind(("scope_org", scope.organization))
+        .bind(("scope_member", scope.member))
         .await
         .map_err(|e| {
             error!(error = %e, table = "location", "Search query failed");
@@ -509,9 +1021,9 @@ pub async fn search_locations(
         Error::Database(e.to_string())
     })?;
 
-    let results = rows
+    let results: Vec<_> = rows
         .into_iter()
-        .filter(|r| r["score"].as_f64().unwrap_or(0.0) > 0.0)
+        .filter(|r| is_candidate(r, &keyword_ids))
         .map(|r| LocationSearchResult {
             id: json_str(&r, "id"),
             key: json_str(&r, "key"),
@@ -526,7 +1038,7 @@ pub async fn search_locations(
         })
         .collect();
 
-    Ok(results)
+    Ok(fuse(results, &keyword_ids, &reranked_ids, params).await)
 }
 
 // ---------------------------------------------------------------------------
@@ -539,16 +1051,29 @@ pub async fn search_productions(
 ) -> Result<Vec<ProductionSearchResult>> {
     let query_lower = params.query.to_lowercase();
     let empty_emb: Vec<f32> = vec![];
-    let w = params.weights;
+    let w = &params.config.productions;
+    let keyword_ids = keyword_ranking("production", params.query).await;
+    let reranked_ids = reranked_ranking("production", w, params).await;
 
-    let has_status = status.is_some();
-    let hard_filter = if has_status {
-        "AND string::lowercase(status ?? '') = string::lowercase($status_filter)".to_string()
+    let mut hard_parts: Vec<String> = Vec::new();
+
+    if status.is_some() {
+        hard_parts.push(
+            "string::lowercase(status ?? '') = string::lowercase($status_filter)".to_string(),
+        );
+    }
+
+    hard_parts.extend(syntax_filter(params, SearchKind::Productions));
+    hard_parts.extend(scope_filter(params, SearchKind::Productions));
+
+    let has_hard_filters = !hard_parts.is_empty();
+    let hard_filter = if has_hard_filters {
+        format!("AND {}", hard_parts.join(" AND "))
     } else {
         String::new()
     };
 
-    let text_vector_gate = if has_status && query_lower.trim().is_empty() {
+    let text_vector_gate = if has_hard_filters && query_lower.trim().is_empty() {
         "true".to_string()
     } else {
         format!(
@@ -557,8 +1082,9 @@ pub async fn search_productions(
                 OR string::lowercase(description ?? '') CONTAINS $query_lower
                 OR string::lowercase(location ?? '') CONTAINS $query_lower
                 OR string::lowercase(embedding_text ?? '') CONTAINS $query_lower
-                OR (embedding IS NOT NONE AND $has_embedding = true
-                    AND vector::similarity::cosine(embedding, $query_embedding) > {threshold})
+                OR ((embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    AND vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) > {threshold})
+                OR <string> id INSIDE $keyword_ids
             )",
             threshold = w.vector_threshold,
         )
@@ -575,39 +1101,50 @@ pub async fn search_productions(
             poster_url,
             poster_photo,
             embedding_text,
+            (<string> id INSIDE $keyword_ids) AS keyword_hit,
             <float> (
                 (IF string::lowercase(title ?? '') CONTAINS $query_lower THEN {w_name} ELSE 0 END)
                 + (IF string::lowercase(description ?? '') CONTAINS $query_lower THEN {w_headline} ELSE 0 END)
                 + (IF string::lowercase(location ?? '') CONTAINS $query_lower THEN {w_location} ELSE 0 END)
-                + (IF embedding IS NOT NONE AND $has_embedding = true
-                    THEN vector::similarity::cosine(embedding, $query_embedding) * {w_vector}
+                + (IF (embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    THEN vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) * {w_vector}
                     ELSE 0
                 END)
+                {connection_score}
             ) AS score
         FROM production
         WHERE
             {text_vector_gate}
             {hard_filter}
-        ORDER BY score DESC
-        LIMIT $limit
-        START $offset",
+        ORDER BY keyword_hit DESC, score DESC
+        LIMIT $window",
         w_name = w.name_match,
         w_headline = w.headline_match,
         w_location = w.location_match,
         w_vector = w.vector_multiplier,
+        connection_score = search_connections::production_score(w.connection_match, params.connections),
     );
 
     let has_embedding = params.embedding.is_some();
     let embedding_vec = params.embedding.cloned().unwrap_or(empty_emb);
+    let viewer = params.connections.cloned().unwrap_or_default();
+
+    let scope = params.scope.cloned().unwrap_or_default();
 
-    let mut response = DB
+    let mut response = reader()
         .query(&sql)
         .bind(("query_lower", query_lower))
         .bind(("has_embedding", has_embedding))
         .bind(("query_embedding", embedding_vec))
-        .bind(("limit", params.limit as i64))
-        .bind(("offset", params.offset as i64))
+        .bind(("keyword_ids", keyword_ids.clone()))
+        .bind(("window", window(params, w, &keyword_ids)))
+        .bind(("viewer_city", viewer.city))
+        .bind(("viewer_orgs", viewer.organizations))
+        .bind(("viewer_productions", viewer.productions))
+        .bind(("viewer_collaborators", viewer.collaborators))
         .bind(("status_filter", status.unwrap_or("").to_string()))
+        .bind(("syntax_values", syntax_values(params)))
+        .bind(("scope_productions", scope.productions))
         .await
         .map_err(|e| {
             error!(error = %e, table = "production", "Search query failed");
@@ -619,9 +1156,9 @@ pub async fn search_productions(
         Error::Database(e.to_string())
     })?;
 
-    let results = rows
+    let results: Vec<_> = rows
         .into_iter()
-        .filter(|r| r["score"].as_f64().unwrap_or(0.0) > 0.0)
+        .filter(|r| is_candidate(r, &keyword_ids))
         .map(|r| ProductionSearchResult {
             id: json_str(&r, "id"),
             title: json_str(&r, "title"),
@@ -636,7 +1173,7 @@ pub async fn search_productions(
         })
         .collect();
 
-    Ok(results)
+    Ok(fuse(results, &keyword_ids, &reranked_ids, params).await)
 }
 
 // ---------------------------------------------------------------------------
@@ -650,7 +1187,9 @@ pub async fn search_jobs(
 ) -> Result<Vec<JobSearchResult>> {
     let query_lower = params.query.to_lowercase();
     let empty_emb: Vec<f32> = vec![];
-    let w = params.weights;
+    let w = &params.config.jobs;
+    let keyword_ids = keyword_ranking("job_posting", params.query).await;
+    let reranked_ids = reranked_ranking("job_posting", w, params).await;
 
     let mut hard_parts: Vec<String> = Vec::new();
 
@@ -666,6 +1205,9 @@ pub async fn search_jobs(
         );
     }
 
+    hard_parts.extend(syntax_filter(params, SearchKind::Jobs));
+    hard_parts.extend(scope_filter(params, SearchKind::Jobs));
+
     let has_hard_filters = !hard_parts.is_empty();
     let hard_filter = if has_hard_filters {
         format!("AND {}", hard_parts.join(" AND "))
@@ -683,8 +1225,9 @@ pub async fn search_jobs(
                 OR string::lowercase(location ?? '') CONTAINS $query_lower
                 OR string::lowercase(string::join(' ', roles.*.title)) CONTAINS $query_lower
                 OR string::lowercase(embedding_text ?? '') CONTAINS $query_lower
-                OR (embedding IS NOT NONE AND $has_embedding = true
-                    AND vector::similarity::cosine(embedding, $query_embedding) > {threshold})
+                OR ((embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    AND vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) > {threshold})
+                OR <string> id INSIDE $keyword_ids
             )",
             threshold = w.vector_threshold,
         )
@@ -699,12 +1242,13 @@ pub async fn search_jobs(
             <string> posted_by AS posted_by_id,
             array::len(roles) AS role_count,
             embedding_text,
+            (<string> id INSIDE $keyword_ids) AS keyword_hit,
             <float> (
                 (IF string::lowercase(title ?? '') CONTAINS $query_lower THEN {w_name} ELSE 0 END)
                 + (IF string::lowercase(description ?? '') CONTAINS $query_lower THEN {w_headline} ELSE 0 END)
                 + (IF string::lowercase(location ?? '') CONTAINS $query_lower THEN {w_location} ELSE 0 END)
-                + (IF embedding IS NOT NONE AND $has_embedding = true
-                    THEN vector::similarity::cosine(embedding, $query_embedding) * {w_vector}
+                + (IF (embedding ?? embedding_q) IS NOT NONE AND $has_embedding = true
+                    THEN vector::similarity::cosine(embedding ?? embedding_q, $query_embedding) * {w_vector}
                     ELSE 0
                 END)
             ) AS score
@@ -712,9 +1256,8 @@ pub async fn search_jobs(
         WHERE
             {text_vector_gate}
             {hard_filter}
-        ORDER BY score DESC
-        LIMIT $limit
-        START $offset",
+        ORDER BY keyword_hit DESC, score DESC
+        LIMIT $window",
         w_name = w.name_match,
         w_headline = w.headline_match,
         w_location = w.location_match,
@@ -724,14 +1267,19 @@ pub async fn search_jobs(
     let has_embedding = params.embedding.is_some();
     let embedding_vec = params.embedding.cloned().unwrap_or(empty_emb);
 
-    let mut response = DB
+    let scope = params.scope.cloned().unwrap_or_default();
+
+    let mut response = reader()
         .query(&sql)
         .bind(("query_lower", query_lower))
         .bind(("has_embedding", has_embedding))
         .bind(("query_embedding", embedding_vec))
-        .bind(("limit", params.limit as i64))
-        .bind(("offset", params.offset as i64))
+        .bind(("keyword_ids", keyword_ids.clone()))
+        .bind(("window", window(params, w, &keyword_ids)))
         .bind(("location_filter", location.unwrap_or("").to_string()))
+        .bind(("syntax_values", syntax_values(params)))
+        .bind(("scope_org", scope.organization))
+        .bind(("scope_productions", scope.productions))
         .await
         .map_err(|e| {
             error!(error = %e, table = "job_posting", "Search query failed");
@@ -746,7 +1294,7 @@ pub async fn search_jobs(
     let mut results = Vec::new();
     for r in &rows {
         let score = r["score"].as_f64().unwrap_or(0.0);
-        if score <= 0.0 {
+        if !is_candidate(r, &keyword_ids) {
             continue;
         }
 
@@ -767,9 +1315,418 @@ pub async fn search_jobs(
         });
     }
 
+    Ok(fuse(results, &keyword_ids, &reranked_ids, params).await)
+}
+
+// ---------------------------------------------------------------------------
+// Combined search (the /search page)
+// ---------------------------------------------------------------------------
+
+/// Results of `search_all`, one list per entity type
+#[derive(Debug, Clone, Default)]
+pub struct CombinedResults {
+    pub people: Vec<PersonSearchResult>,
+    pub organizations: Vec<OrganizationSearchResult>,
+    pub locations: Vec<LocationSearchResult>,
+    pub productions: Vec<ProductionSearchResult>,
+    pub jobs: Vec<JobSearchResult>,
+    /// A type ran out of time and was left out, so these shouldn't be cached
+    pub incomplete: bool,
+    /// "Why matched" passages by result id, for results with field embeddings
+    pub matched: HashMap<String, Vec<MatchedField>>,
+}
+
+impl CombinedResults {
+    pub fn total(&self) -> usize {
+        self.people.len()
+            + self.organizations.len()
+            + self.locations.len()
+            + self.productions.len()
+            + self.jobs.len()
+    }
+}
+
+/// Run one type's search if the query targets it, within that type's time
+/// limit. Returns `None` when it ran out of time.
+async fn search_table<T>(
+    table: &str,
+    wanted: bool,
+    weights: &SearchWeights,
+    search: impl Future<Output = Result<Vec<T>>>,
+) -> Result<Option<Vec<T>>> {
+    if !wanted {
+        return Ok(Some(vec![]));
+    }
+    let Some(limit) = weights.timeout() else {
+        return search.await.map(Some);
+    };
+    match tokio::time::timeout(limit, search).await {
+        Ok(results) => results.map(Some),
+        Err(_) => {
+            warn!(
+                table,
+                timeout_ms = weights.timeout_ms,
+                "Search timed out, leaving the table out"
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Search every entity type the query targets, all at once. A type that
+/// takes longer than its timeout is left out rather than holding up the
+/// rest. Without a `viewer` or `connections`, results only include public
+/// profiles and can be shared; results for a searcher can't be.
+///
+/// Operators in the query (`search_syntax`) filter every type, and
+/// `embedding` should be of the free text around them. With a `scope`, every
+/// type only includes that organization's records (`search_scope`).
+pub async fn search_all(
+    query: &str,
+    embedding: Option<&Vec<f32>>,
+    config: &SearchConfig,
+    connections: Option<&Connections>,
+    viewer: Option<&Viewer>,
+    scope: Option<&OrgScope>,
+) -> Result<CombinedResults> {
+    let syntax = SearchSyntax::parse(query);
+    let query = syntax.text.as_str();
+
+    let mut intent = detect_search_intent(query);
+    if syntax.names_types() {
+        // `type:` overrides what the free text suggests
+        intent = SearchIntent {
+            people: true,
+            organizations: true,
+            locations: true,
+            productions: true,
+            jobs: true,
+        };
+    }
+    intent.people &= syntax.targets(SearchKind::People);
+    intent.organizations &= syntax.targets(SearchKind::Organizations);
+    intent.locations &= syntax.targets(SearchKind::Locations);
+    intent.productions &= syntax.targets(SearchKind::Productions);
+    intent.jobs &= syntax.targets(SearchKind::Jobs);
+    debug!("Search intent: {:?}", intent);
+
+    // --- People: use parse_query for structured filter extraction ---
+    let parsed = search_utils::parse_query(query);
+    let people_params = SearchParams {
+        query: &parsed.cleaned,
+        embedding,
+        config,
+        limit: 20,
+        offset: 0,
+        connections,
+        viewer,
+        syntax: Some(&syntax),
+        scope,
+    };
+
+    // --- Non-people: extract location, normalize remaining query ---
+    let (location, cleaned_query) = search_utils::extract_location(query);
+    let normalized = search_utils::normalize_query(&cleaned_query);
+    let params = SearchParams {
+        query: &normalized,
+        embedding,
+        config,
+        limit: 10,
+        offset: 0,
+        connections,
+        viewer,
+        syntax: Some(&syntax),
+        scope,
+    };
+
+    let (people, organizations, locations, productions, jobs) = tokio::join!(
+        search_table(
+            "person",
+            intent.people,
+            &config.people,
+            search_people(&people_params, &parsed, None, None),
+        ),
+        search_table(
+            "organization",
+            intent.organizations,
+            &config.organizations,
+            search_organizations(&params, location.as_deref()),
+        ),
+        // For locations, pass extracted location as city filter
+        search_table(
+            "location",
+            intent.locations,
+            &config.locations,
+            search_locations(&params, location.as_deref(), None),
+        ),
+        search_table(
+            "production",
+            intent.productions,
+            &config.productions,
+            search_productions(&params, None),
+        ),
+        search_table(
+            "job_posting",
+            intent.jobs,
+            &config.jobs,
+            search_jobs(&params, location.as_deref(), true),
+        ),
+    );
+    let (people, organizations, locations, productions, jobs) =
+        (people?, organizations?, locations?, productions?, jobs?);
+
+    let mut results = CombinedResults {
+        incomplete: people.is_none()
+            || organizations.is_none()
+            || locations.is_none()
+            || productions.is_none()
+            || jobs.is_none(),
+        people: people.unwrap_or_default(),
+        organizations: organizations.unwrap_or_default(),
+        locations: locations.unwrap_or_default(),
+        productions: productions.unwrap_or_default(),
+        jobs: jobs.unwrap_or_default(),
+        matched: HashMap::new(),
+    };
+    if let Some(embedding) = embedding {
+        results.matched = why_matched(query, embedding, &results, config).await;
+    }
     Ok(results)
 }
 
+// ---------------------------------------------------------------------------
+// Why matched
+// ---------------------------------------------------------------------------
+
+/// Most passages explained under one result
+const MAX_MATCHED: usize = 3;
+
+/// Query words shorter than this aren't highlighted
+const MIN_HIGHLIGHT_LENGTH: usize = 3;
+
+/// A passage of a result that's close to the query, e.g. the skill
+/// "steadicam operator" for a search for "camera operator"
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchedField {
+    pub label: String,
+    pub text: String,
+    pub similarity: f64,
+    /// `text` in runs, with the query's words marked
+    pub parts: Vec<Highlight>,
+}
+
+/// A run of a passage's text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlight {
+    pub text: String,
+    pub hit: bool,
+}
+
+fn cosine(a: &[f32], b: &[i64]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0f64, 0f64, 0f64);
+    for (&x, &y) in a.iter().zip(b) {
+        let (x, y) = (x as f64, y as f64);
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    dot / (norm_a.sqrt() * norm_b.sqrt())
+}
+
+/// The passages most similar to the query, above the type's vector
+/// threshold, best first
+pub fn best_matches(
+    query: &str,
+    query_embedding: &[f32],
+    fields: &[FieldEmbedding],
+    threshold: f64,
+) -> Vec<MatchedField> {
+    let mut scored: Vec<(f64, &FieldEmbedding)> = fields
+        .iter()
+        .map(|field| (cosine(query_embedding, &field.vector), field))
+        .filter(|(similarity, _)| *similarity > threshold)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored
+        .into_iter()
+        .take(MAX_MATCHED)
+        .map(|(similarity, field)| MatchedField {
+            label: field.label.clone(),
+            text: field.text.clone(),
+            similarity,
+            parts: highlight(&field.text, query),
+        })
+        .collect()
+}
+
+/// Split `text` into runs, marking words that start with one of the query's
+/// words (ignoring case), so "camera" marks "cameras" too
+pub fn highlight(text: &str, query: &str) -> Vec<Highlight> {
+    let words: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= MIN_HIGHLIGHT_LENGTH)
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut parts: Vec<Highlight> = Vec::new();
+    let mut rest = text;
+    while let Some(first) = rest.chars().next() {
+        let is_word = first.is_alphanumeric();
+        let end = rest
+            .find(|c: char| c.is_alphanumeric() != is_word)
+            .unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(end);
+        let hit = is_word && {
+            let run = run.to_lowercase();
+            words.iter().any(|word| run.starts_with(word.as_str()))
+        };
+        match parts.last_mut() {
+            Some(last) if last.hit == hit => last.text.push_str(run),
+            _ => parts.push(Highlight {
+                text: run.to_string(),
+                hit,
+            }),
+        }
+        rest = tail;
+    }
+    parts
+}
+
+/// "Why matched" passages for the people, organizations, locations and
+/// productions in `results`. Records embedded before field embeddings
+/// existed have none; a failed lookup only leaves the explanations out.
+async fn why_matched(
+    query: &str,
+    embedding: &[f32],
+    results: &CombinedResults,
+    config: &SearchConfig,
+) -> HashMap<String, Vec<MatchedField>> {
+    let thresholds: HashMap<&str, f64> = results
+        .people
+        .iter()
+        .map(|r| (r.id.as_str(), config.people.vector_threshold))
+        .chain(
+            results
+                .organizations
+                .iter()
+                .map(|r| (r.id.as_str(), config.organizations.vector_threshold)),
+        )
+        .chain(
+            results
+                .locations
+                .iter()
+                .map(|r| (r.id.as_str(), config.locations.vector_threshold)),
+        )
+        .chain(
+            results
+                .productions
+                .iter()
+                .map(|r| (r.id.as_str(), config.productions.vector_threshold)),
+        )
+        .collect();
+    let ids: Vec<RecordId> = thresholds
+        .keys()
+        .filter_map(|id| RecordId::parse_simple(id).ok())
+        .collect();
+    if ids.is_empty() {
+        return HashMap::new();
+    }
+
+    let rows: Vec<serde_json::Value> = match reader()
+        .query("SELECT <string> id AS id, embedding_fields FROM $ids WHERE embedding_fields IS NOT NONE")
+        .bind(("ids", ids))
+        .await
+        .and_then(|mut response| response.take(0))
+    {
+        Ok(rows) => rows,
+        Err(e) => {
+            warn!(error = %e, "Failed to load field embeddings");
+            return HashMap::new();
+        }
+    };
+
+    let mut matched = HashMap::new();
+    for row in rows {
+        let id = json_str(&row, "id");
+        let Some(&threshold) = thresholds.get(id.as_str()) else {
+            continue;
+        };
+        let fields: Vec<FieldEmbedding> =
+            serde_json::from_value(row["embedding_fields"].clone()).unwrap_or_default();
+        let best = best_matches(query, embedding, &fields, threshold);
+        if !best.is_empty() {
+            matched.insert(id, best);
+        }
+    }
+    matched
+}
+
+/// Which entity types a search query targets.
+#[derive(Debug)]
+pub struct SearchIntent {
+    pub people: bool,
+    pub organizations: bool,
+    pub locations: bool,
+    pub productions: bool,
+    pub jobs: bool,
+}
+
+/// Detect which entity types the query is targeting based on keywords.
+/// Returns all true (search everything) if no clear intent is detected.
+pub fn detect_search_intent(query: &str) -> SearchIntent {
+    let q = query.to_lowercase();
+
+    // Location signals — physical places, venues, spaces
+    let is_locations = Regex::new(
+        r"(?i)\b(location|locations|venue|venues|sound stage|soundstage|stage|warehouse|rooftop|loft|desert|beach|forest|outdoor|indoor|studio space|filming location|shoot location)\b"
+    ).unwrap().is_match(&q);
+
+    // Organization signals — companies, agencies, service providers
+    let is_orgs = Regex::new(
+        r"(?i)\b(company|companies|agency|agencies|house|houses|rental|rentals|post house|vfx house|production company|production companies|talent agency|casting agency|studio|studios)\b"
+    ).unwrap().is_match(&q)
+        // "studio" alone is ambiguous — only count as org if combined with service words
+        // but "studios" (plural) is more likely an org
+        && !is_locations; // location takes priority if both match (e.g. "studio space")
+
+    // Production signals — films, shows, credits
+    let is_productions = Regex::new(
+        r"(?i)\b(film|films|movie|movies|show|shows|series|season|documentary|documentaries|short film|shorts|feature|features|directed by|starring|produced by|written by|credits)\b"
+    ).unwrap().is_match(&q);
+
+    // Job signals — employment, gigs, hiring
+    let is_jobs = Regex::new(
+        r"(?i)\b(job|jobs|hiring|position|positions|opening|openings|gig|gigs|vacancy|vacancies|opportunity|opportunities|looking for work|seeking work|casting call|audition|auditions)\b"
+    ).unwrap().is_match(&q);
+
+    let any_specific = is_locations || is_orgs || is_productions || is_jobs;
+
+    if any_specific {
+        SearchIntent {
+            people: is_productions, // include people for "films directed by chris"
+            organizations: is_orgs,
+            locations: is_locations,
+            productions: is_productions,
+            jobs: is_jobs,
+        }
+    } else {
+        // No specific entity keyword — search everything
+        SearchIntent {
+            people: true,
+            organizations: true,
+            locations: true,
+            productions: true,
+            jobs: true,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
@@ -790,7 +1747,7 @@ async fn resolve_poster(posted_by_id: &str) -> (String, String) {
     };
 
     let name: Option<String> = match rid {
-        Some(id) => DB
+        Some(id) => reader()
             .query("SELECT VALUE name FROM $id")
             .bind(("id", id))
             .await
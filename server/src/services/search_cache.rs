@@ -0,0 +1,319 @@
+//! Search warm-up and query cache
+//!
+//! The first searches after a deploy used to pay for loading the embedding
+//! model and for cold database caches. `warm_up()` runs right after the model
+//! loads (and again on a schedule): it collects the popular queries —
+//! `SEARCH_WARMUP_QUERIES` plus the most searched queries of the last week —
+//! embeds them in one batch, and runs each through `search_all` so their
+//! results are ready.
+//!
+//! Every other query is cached as it's searched, keyed by its normalized text
+//! (plus the ranking experiment variant and the tenant for results), in LRU
+//! caches of at most `SEARCH_CACHE_MAX_ENTRIES` entries each:
+//!
+//! - Embeddings depend only on the model, so they don't expire. Popular
+//!   queries' are pinned; the rest give way to more recent queries.
+//! - Results expire after `SEARCH_CACHE_TTL_SECS` and are dropped whenever a
+//!   searchable table is written (`listen_for_changes`) or a record's embedding
+//!   is updated, so an edit shows up in search as soon as it would uncached.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+use crate::config::{search_cache as cache_config, search_config};
+use crate::db::{DB, current_tenant};
+use crate::error::{Error, Result};
+use crate::services::embedding;
+use crate::services::search::{CombinedResults, search_all};
+use crate::services::search_syntax::SearchSyntax;
+
+/// Tables whose writes change what `/search` returns
+pub const SEARCHABLE_TABLES: &[&str] =
+    &["person", "organization", "location", "production", "job_posting"];
+
+/// Queries shorter than this aren't worth caching
+const MIN_QUERY_LEN: usize = 2;
+
+/// Normalized form used as the cache key: lowercase, single spaces
+pub fn normalize(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// A map of at most `capacity` entries. Inserting into a full cache evicts
+/// the entry that was used least recently.
+#[derive(Debug)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<K, (u64, V)>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> LruCache<K, V> {
+    /// An empty cache; a capacity of 0 keeps nothing
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// The value for `key`, marking it as just used
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(used, value)| {
+            *used = tick;
+            value.clone()
+        })
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(key, (self.tick, value));
+    }
+
+    pub fn remove(&mut self, key: &K) {
+        self.entries.remove(key);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Embeddings of popular queries, never evicted. Shared by tenants, since they
+/// share the model.
+static EMBEDDINGS: LazyLock<RwLock<HashMap<String, Arc<Vec<f32>>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Embeddings of everything else that was searched recently
+static RECENT_EMBEDDINGS: LazyLock<Mutex<LruCache<String, Arc<Vec<f32>>>>> =
+    LazyLock::new(|| Mutex::new(LruCache::new(cache_config().max_entries)));
+
+type ResultKey = (Option<String>, String, &'static str);
+
+static RESULTS: LazyLock<Mutex<LruCache<ResultKey, (Instant, Arc<CombinedResults>)>>> =
+    LazyLock::new(|| Mutex::new(LruCache::new(cache_config().max_entries)));
+
+/// Embedding for a query's free text (operators filter rather than
+/// describe): from the cache if it was searched recently, generated (and
+/// cached) otherwise. `None` when the model isn't available (text-only
+/// search) or the query is only operators.
+pub async fn embedding(query: &str) -> Option<Vec<f32>> {
+    let syntax = SearchSyntax::parse(query);
+    let query = syntax.text.as_str();
+    if query.is_empty() {
+        return None;
+    }
+    let key = normalize(query);
+    if let Some(cached) = EMBEDDINGS.read().unwrap().get(&key) {
+        return Some(cached.as_ref().clone());
+    }
+    if let Some(cached) = RECENT_EMBEDDINGS.lock().unwrap().get(&key) {
+        return Some(cached.as_ref().clone());
+    }
+    match embedding::generate_embedding_async(query).await {
+        Ok(embedding) => {
+            if key.len() >= MIN_QUERY_LEN {
+                RECENT_EMBEDDINGS
+                    .lock()
+                    .unwrap()
+                    .insert(key, Arc::new(embedding.clone()));
+            }
+            Some(embedding)
+        }
+        Err(e) => {
+            debug!(error = %e, query = %query, "Embedding generation failed, falling back to text-only search");
+            None
+        }
+    }
+}
+
+fn result_key(query: &str, variant: Option<&'static str>) -> ResultKey {
+    (current_tenant(), normalize(query), variant.unwrap_or("default"))
+}
+
+/// Cached results for a query under a ranking variant, if still fresh
+pub fn results(query: &str, variant: Option<&'static str>) -> Option<Arc<CombinedResults>> {
+    let ttl = Duration::from_secs(cache_config().ttl_secs);
+    let key = result_key(query, variant);
+    let mut cache = RESULTS.lock().unwrap();
+    match cache.get(&key) {
+        Some((stored_at, results)) if stored_at.elapsed() < ttl => Some(results),
+        Some(_) => {
+            cache.remove(&key);
+            None
+        }
+        None => None,
+    }
+}
+
+/// Keep results unless a type was left out for running out of time. Returns
+/// them shared either way.
+pub fn store_results(
+    query: &str,
+    variant: Option<&'static str>,
+    results: CombinedResults,
+) -> Arc<CombinedResults> {
+    let results = Arc::new(results);
+    let key = result_key(query, variant);
+    if cache_config().ttl_secs > 0 && !results.incomplete && key.1.len() >= MIN_QUERY_LEN {
+        RESULTS
+            .lock()
+            .unwrap()
+            .insert(key, (Instant::now(), results.clone()));
+    }
+    results
+}
+
+/// Drop every cached result (embeddings stay valid)
+pub fn invalidate() {
+    let mut results = RESULTS.lock().unwrap();
+    if !results.is_empty() {
+        debug!("Invalidating {} cached search results", results.len());
+        results.clear();
+    }
+}
+
+/// The popular queries: configured ones first, then the most searched of the
+/// last seven days, normalized and deduplicated.
+pub async fn popular_queries() -> Result<Vec<String>> {
+    let config = cache_config();
+    let mut queries: Vec<String> = config.warmup_queries.iter().map(|q| normalize(q)).collect();
+
+    if config.popular_from_log > 0 {
+        #[derive(serde::Deserialize, surrealdb::types::SurrealValue)]
+        struct Row {
+            query: String,
+        }
+
+        let rows: Vec<Row> = DB
+            .query(
+                "SELECT query, count() AS searches FROM (
+                     SELECT string::lowercase(string::trim(query)) AS query FROM search_log
+                     WHERE source = 'web' AND created_at > time::now() - 7d
+                 ) GROUP BY query ORDER BY searches DESC LIMIT $limit",
+            )
+            .bind(("limit", config.popular_from_log as i64))
+            .await?
+            .take(0)?;
+        queries.extend(rows.into_iter().map(|r| normalize(&r.query)));
+    }
+
+    let mut seen = HashSet::new();
+    queries.retain(|q| q.len() >= MIN_QUERY_LEN && seen.insert(q.clone()));
+    Ok(queries)
+}
+
+/// Embed the popular queries and cache their results for the current tenant
+pub async fn warm_up() -> Result<()> {
+    let timer = Instant::now();
+    let queries = popular_queries().await?;
+    if queries.is_empty() {
+        debug!("No popular queries to warm up");
+        return Ok(());
+    }
+
+    let missing: Vec<String> = {
+        let embeddings = EMBEDDINGS.read().unwrap();
+        queries.iter().filter(|q| !embeddings.contains_key(*q)).cloned().collect()
+    };
+    if !missing.is_empty() && embedding::is_initialized() {
+        let texts = missing.clone();
+        let batch = tokio::task::spawn_blocking(move || embedding::generate_embeddings_batch(texts))
+            .await
+            .map_err(|e| Error::Internal(format!("Warm-up embedding task failed: {}", e)))?;
+        match batch {
+            Ok(vectors) => {
+                let mut embeddings = EMBEDDINGS.write().unwrap();
+                for (query, vector) in missing.into_iter().zip(vectors) {
+                    embeddings.insert(query, Arc::new(vector));
+                }
+            }
+            Err(e) => warn!("Failed to embed warm-up queries: {}", e),
+        }
+    }
+
+    let mut cached = 0;
+    if cache_config().ttl_secs > 0 {
+        for query in &queries {
+            let vector = EMBEDDINGS.read().unwrap().get(query).cloned();
+            match search_all(query, vector.as_deref(), search_config(), None, None, None).await {
+                Ok(results) => {
+                    store_results(query, None, results);
+                    cached += 1;
+                }
+                Err(e) => warn!("Warm-up search for '{}' failed: {}", query, e),
+            }
+        }
+    }
+
+    info!(
+        "Search warm-up: {} popular queries, {} result sets cached in {}ms",
+        queries.len(),
+        cached,
+        timer.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+/// Watch the searchable tables and drop cached results on any write. Runs for
+/// the life of the process, restarting the LIVE queries if they end.
+pub fn listen_for_changes() {
+    tokio::spawn(async {
+        loop {
+            if let Err(e) = watch_tables().await {
+                error!("Search cache LIVE stream error: {}, restarting in 5s", e);
+            }
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    });
+}
+
+async fn watch_tables() -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use futures::StreamExt;
+
+    let mut streams = Vec::new();
+    for table in SEARCHABLE_TABLES {
+        let stream: surrealdb::Stream<Vec<surrealdb::types::Value>> =
+            crate::db::primary().select(*table).live().await?;
+        streams.push(stream);
+    }
+    info!("Search cache watching {} tables", streams.len());
+
+    let mut events = futures::stream::select_all(streams);
+    while events.next().await.is_some() {
+        invalidate();
+    }
+    warn!("Search cache LIVE stream ended");
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Personalized search ranking
+//!
+//! A signed-in searcher's ties in the graph — the city they're based in, the
+//! organizations they belong to, the productions they've worked on and the
+//! people they've worked with — are loaded once per search. `search_people`
+//! and `search_productions` add the type's `connection_match` to a result's
+//! score for each tie it shares with them, so of two equally good matches the
+//! one the searcher is connected to ranks first.
+//!
+//! Personalized results differ per searcher, so they're never cached.
+
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+
+use crate::db::reader;
+use crate::error::Result;
+use crate::record_id_ext::RecordIdExt;
+
+/// What ties a searcher to the records they find. Ids are strings such as
+/// `"organization:abc"`, matching search rows' `<string> id`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Connections {
+    /// Lowercased, e.g. "los angeles"; empty if they haven't set a location
+    pub city: String,
+    pub organizations: Vec<String>,
+    pub productions: Vec<String>,
+    /// Everyone else on their productions
+    pub collaborators: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, SurrealValue)]
+struct ConnectionsRow {
+    location: Option<String>,
+    organizations: Vec<String>,
+    productions: Vec<String>,
+    collaborators: Vec<String>,
+}
+
+const LOAD: &str = "
+    SELECT
+        profile.location AS location,
+        array::map(->member_of[WHERE invitation_status = 'accepted']->organization, |$o| <string> $o)
+            AS organizations,
+        array::map(array::union(
+            ->member_of[WHERE invitation_status = 'accepted']->production,
+            ->involvement->production
+        ), |$p| <string> $p) AS productions,
+        array::map(array::union(
+            ->member_of[WHERE invitation_status = 'accepted']->production<-member_of[WHERE invitation_status = 'accepted']<-person,
+            ->involvement->production<-involvement<-person
+        ), |$p| <string> $p) AS collaborators
+    FROM ONLY $person";
+
+/// The city part of a free-text location: "Los Angeles, CA" is "los angeles"
+pub fn city_of(location: &str) -> String {
+    location
+        .split(',')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_lowercase()
+}
+
+impl Connections {
+    /// The ties of the person searching
+    pub async fn load(person: &RecordId) -> Result<Self> {
+        let mut response = reader()
+            .query(LOAD)
+            .bind(("person", person.clone()))
+            .await?;
+        let row: Option<ConnectionsRow> = response.take(0)?;
+        let Some(row) = row else {
+            return Ok(Self::default());
+        };
+        let me = person.to_raw_string();
+        Ok(Self {
+            city: city_of(row.location.as_deref().unwrap_or("")),
+            organizations: row.organizations,
+            productions: row.productions,
+            collaborators: row.collaborators.into_iter().filter(|p| *p != me).collect(),
+        })
+    }
+}
+
+/// Ties on a `person` row: same city, a shared organization, worked together
+const PERSON_TIES: &str = "(IF $viewer_city != '' AND string::lowercase(profile.location ?? '') CONTAINS $viewer_city THEN 1 ELSE 0 END)
+                + (IF array::len(array::intersect(array::map(->member_of[WHERE invitation_status = 'accepted']->organization, |$o| <string> $o), $viewer_orgs)) > 0 THEN 1 ELSE 0 END)
+                + (IF <string> id INSIDE $viewer_collaborators THEN 1 ELSE 0 END)";
+
+/// Ties on a `production` row: same city, made by one of their
+/// organizations, worked on by them or someone they've worked with
+const PRODUCTION_TIES: &str = "(IF $viewer_city != '' AND string::lowercase(location ?? '') CONTAINS $viewer_city THEN 1 ELSE 0 END)
+                + (IF array::len(array::intersect(array::map(array::union(<-member_of[WHERE invitation_status = 'accepted']<-organization, <-involvement<-organization), |$o| <string> $o), $viewer_orgs)) > 0 THEN 1 ELSE 0 END)
+                + (IF <string> id INSIDE $viewer_productions
+                    OR array::len(array::intersect(array::map(array::union(<-member_of[WHERE invitation_status = 'accepted']<-person, <-involvement<-person), |$p| <string> $p), $viewer_collaborators)) > 0
+                    THEN 1 ELSE 0 END)";
+
+fn score(ties: &str, weight: i32, connections: Option<&Connections>) -> String {
+    match connections {
+        Some(_) if weight != 0 => format!("+ {} * ({})", weight, ties),
+        _ => String::new(),
+    }
+}
+
+/// Term added to a person's search score, or nothing when no one is signed in
+pub fn person_score(weight: i32, connections: Option<&Connections>) -> String {
+    score(PERSON_TIES, weight, connections)
+}
+
+/// Term added to a production's search score, or nothing when no one is
+/// signed in
+pub fn production_score(weight: i32, connections: Option<&Connections>) -> String {
+    score(PRODUCTION_TIES, weight, connections)
+}
@@ -0,0 +1,245 @@
+//! Facets for the search page
+//!
+//! Filters narrow results that `search_all` has already retrieved and
+//! ranked, so picking one never reruns the query or changes the order. Each
+//! filter applies to the result types that carry its field: a skill or union
+//! filter keeps only people, a production status only productions, an
+//! organization type only organizations, while a location filter applies to
+//! every type. Facet counts are taken from the results that are left.
+
+use serde::{Deserialize, Serialize};
+use surrealdb::types::SurrealValue;
+
+use crate::services::search::CombinedResults;
+
+/// Values listed per facet, most common first
+pub const MAX_FACET_VALUES: usize = 8;
+
+/// Filters picked on the search page, as named in the query string
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, SurrealValue)]
+pub struct SearchFilters {
+    pub skill: Option<String>,
+    pub location: Option<String>,
+    pub union: Option<String>,
+    pub status: Option<String>,
+    pub org_type: Option<String>,
+}
+
+/// One value of a facet and how many results have it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FacetValue {
+    pub value: String,
+    pub count: usize,
+}
+
+/// Facet counts for a set of results
+#[derive(Debug, Clone, Default)]
+pub struct Facets {
+    pub skills: Vec<FacetValue>,
+    pub locations: Vec<FacetValue>,
+    pub unions: Vec<FacetValue>,
+    pub statuses: Vec<FacetValue>,
+    pub org_types: Vec<FacetValue>,
+}
+
+fn same(a: &str, wanted: &str) -> bool {
+    a.trim().to_lowercase() == wanted
+}
+
+/// Whether a location is in the place picked, so "Berlin" also matches
+/// "Berlin, Germany"
+fn in_place(location: Option<&str>, wanted: &str) -> bool {
+    location.is_some_and(|l| l.to_lowercase().contains(wanted))
+}
+
+impl SearchFilters {
+    /// Query string names, with the label shown for each
+    pub const FIELDS: [(&str, &str); 5] = [
+        ("skill", "Skill"),
+        ("location", "Location"),
+        ("union", "Union"),
+        ("status", "Production status"),
+        ("org_type", "Organization type"),
+    ];
+
+    fn get(&self, field: &str) -> Option<&str> {
+        let value = match field {
+            "skill" => &self.skill,
+            "location" => &self.location,
+            "union" => &self.union,
+            "status" => &self.status,
+            "org_type" => &self.org_type,
+            _ => return None,
+        };
+        value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+    }
+
+    /// A filter's value lowercased for matching
+    fn wanted(&self, field: &str) -> Option<String> {
+        self.get(field).map(str::to_lowercase)
+    }
+
+    pub fn is_active(&self) -> bool {
+        Self::FIELDS
+            .iter()
+            .any(|(field, _)| self.get(field).is_some())
+    }
+
+    /// The filters in use as (field, label, value)
+    pub fn active(&self) -> Vec<(&'static str, &'static str, String)> {
+        Self::FIELDS
+            .iter()
+            .filter_map(|&(field, label)| self.get(field).map(|v| (field, label, v.to_string())))
+            .collect()
+    }
+
+    /// Drop results that don't match every filter
+    pub fn apply(&self, results: &mut CombinedResults) {
+        if let Some(skill) = self.wanted("skill") {
+            results
+                .people
+                .retain(|p| p.skills.iter().any(|s| same(s, &skill)));
+            results.organizations.clear();
+            results.locations.clear();
+            results.productions.clear();
+            results.jobs.clear();
+        }
+        if let Some(union) = self.wanted("union") {
+            results
+                .people
+                .retain(|p| p.unions.iter().any(|u| same(u, &union)));
+            results.organizations.clear();
+            results.locations.clear();
+            results.productions.clear();
+            results.jobs.clear();
+        }
+        if let Some(status) = self.wanted("status") {
+            results.productions.retain(|p| same(&p.status, &status));
+            results.people.clear();
+            results.organizations.clear();
+            results.locations.clear();
+            results.jobs.clear();
+        }
+        if let Some(org_type) = self.wanted("org_type") {
+            results
+                .organizations
+                .retain(|o| o.org_type.as_deref().is_some_and(|t| same(t, &org_type)));
+            results.people.clear();
+            results.locations.clear();
+            results.productions.clear();
+            results.jobs.clear();
+        }
+        if let Some(place) = self.wanted("location") {
+            results
+                .people
+                .retain(|p| in_place(p.location.as_deref(), &place));
+            results
+                .organizations
+                .retain(|o| in_place(o.location.as_deref(), &place));
+            results
+                .locations
+                .retain(|l| in_place(Some(&format!("{}, {}", l.city, l.state)), &place));
+            results
+                .productions
+                .retain(|p| in_place(p.location.as_deref(), &place));
+            results
+                .jobs
+                .retain(|j| in_place(j.location.as_deref(), &place));
+        }
+    }
+
+    /// Search page link with `field` set to `value`, or removed when `None`,
+    /// keeping the query and the other filters
+    pub fn url(&self, query: &str, field: &str, value: Option<&str>) -> String {
+        let mut url = format!("/search?q={}", urlencoding::encode(query));
+        for (name, _) in Self::FIELDS {
+            let current = if name == field {
+                value.map(str::trim).filter(|v| !v.is_empty())
+            } else {
+                self.get(name)
+            };
+            if let Some(current) = current {
+                url.push_str(&format!("&{}={}", name, urlencoding::encode(current)));
+            }
+        }
+        url
+    }
+}
+
+/// Count values case-insensitively, keeping the first spelling seen, most
+/// common first and then alphabetically
+fn tally<'a>(values: impl Iterator<Item = &'a str>) -> Vec<FacetValue> {
+    let mut counts: Vec<FacetValue> = Vec::new();
+    for value in values.map(str::trim).filter(|v| !v.is_empty()) {
+        let key = value.to_lowercase();
+        match counts.iter_mut().find(|c| c.value.to_lowercase() == key) {
+            Some(existing) => existing.count += 1,
+            None => counts.push(FacetValue {
+                value: value.to_string(),
+                count: 1,
+            }),
+        }
+    }
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    counts.truncate(MAX_FACET_VALUES);
+    counts
+}
+
+impl Facets {
+    pub fn from_results(results: &CombinedResults) -> Self {
+        let places: Vec<String> = results
+            .locations
+            .iter()
+            .map(|l| format!("{}, {}", l.city, l.state))
+            .collect();
+        let locations = results
+            .people
+            .iter()
+            .filter_map(|p| p.location.as_deref())
+            .chain(
+                results
+                    .organizations
+                    .iter()
+                    .filter_map(|o| o.location.as_deref()),
+            )
+            .chain(places.iter().map(String::as_str))
+            .chain(
+                results
+                    .productions
+                    .iter()
+                    .filter_map(|p| p.location.as_deref()),
+            )
+            .chain(results.jobs.iter().filter_map(|j| j.location.as_deref()));
+
+        Facets {
+            skills: tally(
+                results
+                    .people
+                    .iter()
+                    .flat_map(|p| p.skills.iter().map(String::as_str)),
+            ),
+            locations: tally(locations),
+            unions: tally(
+                results
+                    .people
+                    .iter()
+                    .flat_map(|p| p.unions.iter().map(String::as_str)),
+            ),
+            statuses: tally(results.productions.iter().map(|p| p.status.as_str())),
+            org_types: tally(
+                results
+                    .organizations
+                    .iter()
+                    .filter_map(|o| o.org_type.as_deref()),
+            ),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.skills.is_empty()
+            && self.locations.is_empty()
+            && self.unions.is_empty()
+            && self.statuses.is_empty()
+            && self.org_types.is_empty()
+    }
+}
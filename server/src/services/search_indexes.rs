@@ -0,0 +1,268 @@
+//! Declarative spec for the full-text and vector indexes used by search.
+//!
+//! `db/schema.surql` defines these indexes for fresh databases; this module is
+//! the source of truth the `search-indexes` CLI uses to (re)define and rebuild
+//! them on existing databases without hand-editing SurrealQL.
+
+use crate::db::DB;
+use crate::error::{Error, Result};
+use tracing::info;
+
+/// Dimension of the vectors produced by the configured embedding model (BGE-Large-EN-v1.5).
+pub const EMBEDDING_DIMENSION: usize = 1024;
+
+pub struct AnalyzerSpec {
+    pub name: &'static str,
+    pub tokenizers: &'static str,
+    pub filters: &'static str,
+}
+
+pub enum IndexKind {
+    /// BM25 full-text index over a single field
+    FullText { field: &'static str, analyzer: &'static str },
+    /// Full-text index over word prefixes, for type-ahead suggestions only
+    Prefix { field: &'static str, analyzer: &'static str },
+    /// HNSW cosine vector index
+    Vector { field: &'static str, dimension: usize, efc: u32, m: u32 },
+}
+
+pub struct IndexSpec {
+    pub name: &'static str,
+    pub table: &'static str,
+    pub kind: IndexKind,
+}
+
+pub const ANALYZERS: &[AnalyzerSpec] = &[
+    AnalyzerSpec {
+        name: "profile_analyzer",
+        tokenizers: "blank,class",
+        filters: "lowercase,snowball(english)",
+    },
+    // Names and titles match word for word: no stemming, accents folded
+    AnalyzerSpec {
+        name: "name_analyzer",
+        tokenizers: "blank,class,punct",
+        filters: "lowercase,ascii",
+    },
+    // Type-ahead: every word's leading 2 to 15 characters
+    AnalyzerSpec {
+        name: "prefix_analyzer",
+        tokenizers: "blank,class,punct",
+        filters: "lowercase,ascii,edgengram(2,15)",
+    },
+];
+
+pub const INDEXES: &[IndexSpec] = &[
+    IndexSpec {
+        name: "idx_person_name",
+        table: "person",
+        kind: IndexKind::FullText { field: "name", analyzer: "name_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_person_username",
+        table: "person",
+        kind: IndexKind::FullText { field: "username", analyzer: "name_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_person_headline",
+        table: "person",
+        kind: IndexKind::FullText { field: "profile.headline", analyzer: "profile_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_person_bio",
+        table: "person",
+        kind: IndexKind::FullText { field: "profile.bio", analyzer: "profile_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_organization_name",
+        table: "organization",
+        kind: IndexKind::FullText { field: "name", analyzer: "name_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_organization_description",
+        table: "organization",
+        kind: IndexKind::FullText { field: "description", analyzer: "profile_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_location_name",
+        table: "location",
+        kind: IndexKind::FullText { field: "name", analyzer: "name_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_location_description",
+        table: "location",
+        kind: IndexKind::FullText { field: "description", analyzer: "profile_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_production_title",
+        table: "production",
+        kind: IndexKind::FullText { field: "title", analyzer: "name_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_production_description",
+        table: "production",
+        kind: IndexKind::FullText { field: "description", analyzer: "profile_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_job_title",
+        table: "job_posting",
+        kind: IndexKind::FullText { field: "title", analyzer: "name_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_job_description",
+        table: "job_posting",
+        kind: IndexKind::FullText { field: "description", analyzer: "profile_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_person_suggest",
+        table: "person",
+        kind: IndexKind::Prefix { field: "suggest", analyzer: "prefix_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_organization_suggest",
+        table: "organization",
+        kind: IndexKind::Prefix { field: "suggest", analyzer: "prefix_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_location_suggest",
+        table: "location",
+        kind: IndexKind::Prefix { field: "suggest", analyzer: "prefix_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_production_suggest",
+        table: "production",
+        kind: IndexKind::Prefix { field: "suggest", analyzer: "prefix_analyzer" },
+    },
+    IndexSpec {
+        name: "idx_person_embedding",
+        table: "person",
+        kind: IndexKind::Vector { field: "embedding", dimension: EMBEDDING_DIMENSION, efc: 150, m: 12 },
+    },
+    IndexSpec {
+        name: "idx_organization_embedding",
+        table: "organization",
+        kind: IndexKind::Vector { field: "embedding", dimension: EMBEDDING_DIMENSION, efc: 150, m: 12 },
+    },
+    IndexSpec {
+        name: "idx_location_embedding",
+        table: "location",
+        kind: IndexKind::Vector { field: "embedding", dimension: EMBEDDING_DIMENSION, efc: 150, m: 12 },
+    },
+    IndexSpec {
+        name: "idx_production_embedding",
+        table: "production",
+        kind: IndexKind::Vector { field: "embedding", dimension: EMBEDDING_DIMENSION, efc: 150, m: 12 },
+    },
+];
+
+impl AnalyzerSpec {
+    pub fn define_statement(&self) -> String {
+        format!(
+            "DEFINE ANALYZER OVERWRITE {} TOKENIZERS {} FILTERS {};",
+            self.name, self.tokenizers, self.filters
+        )
+    }
+}
+
+impl IndexSpec {
+    pub fn define_statement(&self) -> String {
+        match &self.kind {
+            IndexKind::FullText { field, analyzer } | IndexKind::Prefix { field, analyzer } => format!(
+                "DEFINE INDEX OVERWRITE {} ON {} FIELDS {} FULLTEXT ANALYZER {} BM25;",
+                self.name, self.table, field, analyzer
+            ),
+            IndexKind::Vector { field, dimension, efc, m } => format!(
+                "DEFINE INDEX OVERWRITE {} ON {} FIELDS {} HNSW DIMENSION {} DIST COSINE TYPE F32 EFC {} M {};",
+                self.name, self.table, field, dimension, efc, m
+            ),
+        }
+    }
+
+    pub fn rebuild_statement(&self) -> String {
+        format!("REBUILD INDEX {} ON {};", self.name, self.table)
+    }
+
+    pub fn kind_label(&self) -> &'static str {
+        match self.kind {
+            IndexKind::FullText { .. } => "fulltext",
+            IndexKind::Prefix { .. } => "prefix",
+            IndexKind::Vector { .. } => "vector",
+        }
+    }
+}
+
+/// Fields with a full-text index on `table`, in spec order. Hybrid search
+/// runs its BM25 pass over these; prefix indexes are left to suggestions.
+pub fn fulltext_fields(table: &str) -> Vec<&'static str> {
+    INDEXES
+        .iter()
+        .filter(|spec| spec.table == table)
+        .filter_map(|spec| match spec.kind {
+            IndexKind::FullText { field, .. } => Some(field),
+            IndexKind::Prefix { .. } | IndexKind::Vector { .. } => None,
+        })
+        .collect()
+}
+
+/// Check every vector index in the spec against the embedding model's output size.
+/// Returns a description of each mismatch.
+pub fn verify_dimensions(model_dimension: usize) -> std::result::Result<(), Vec<String>> {
+    let mismatches: Vec<String> = INDEXES
+        .iter()
+        .filter_map(|spec| match spec.kind {
+            IndexKind::Vector { dimension, .. } if dimension != model_dimension => Some(format!(
+                "{} on {} expects {} dimensions, embedding model produces {}",
+                spec.name, spec.table, dimension, model_dimension
+            )),
+            _ => None,
+        })
+        .collect();
+
+    if mismatches.is_empty() { Ok(()) } else { Err(mismatches) }
+}
+
+/// Names of the indexes currently defined on a table.
+pub async fn existing_indexes(table: &str) -> Result<Vec<String>> {
+    let mut response = DB
+        .query(format!("INFO FOR TABLE {}", table))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to read table info for {}: {}", table, e)))?;
+
+    let info: Option<serde_json::Value> = response
+        .take(0)
+        .map_err(|e| Error::Database(format!("Failed to parse table info for {}: {}", table, e)))?;
+
+    Ok(info
+        .and_then(|v| v.get("indexes").and_then(|i| i.as_object()).map(|o| o.keys().cloned().collect()))
+        .unwrap_or_default())
+}
+
+/// Define all analyzers and indexes from the spec, optionally rebuilding each
+/// index afterwards so existing rows are re-indexed.
+pub async fn apply(rebuild: bool) -> Result<()> {
+    for analyzer in ANALYZERS {
+        info!("Defining analyzer {}", analyzer.name);
+        run(&analyzer.define_statement()).await?;
+    }
+
+    for spec in INDEXES {
+        info!("Defining {} index {} on {}", spec.kind_label(), spec.name, spec.table);
+        run(&spec.define_statement()).await?;
+
+        if rebuild {
+            info!("Rebuilding index {} on {}", spec.name, spec.table);
+            run(&spec.rebuild_statement()).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn run(statement: &str) -> Result<()> {
+    DB.query(statement)
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?
+        .check()
+        .map_err(|e| Error::Database(format!("{}: {}", statement, e)))?;
+    Ok(())
+}
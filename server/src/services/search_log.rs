@@ -1,27 +1,69 @@
 use tracing::warn;
 
 use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::services::experiments::Exposure;
 
 /// Fire-and-forget search log entry. Spawns a background task so it never blocks search results.
 pub fn log_search(query: &str, source: &str, category: &str, result_count: Option<usize>) {
+    log_search_with_id(query, source, category, result_count, None);
+}
+
+/// Like `log_search`, but returns the id of the new entry so result clicks can
+/// be attributed to it, and records the experiment variant the search ran under.
+pub fn log_search_with_id(
+    query: &str,
+    source: &str,
+    category: &str,
+    result_count: Option<usize>,
+    exposure: Option<&Exposure>,
+) -> String {
+    let id = ulid::Ulid::new().to_string().to_lowercase();
     let query = query.to_string();
     let source = source.to_string();
     let category = category.to_string();
     let result_count = result_count.map(|c| c as i64);
+    let experiment = exposure.map(|e| e.experiment.to_string());
+    let variant = exposure.map(|e| e.variant.to_string());
 
-    tokio::spawn(async move {
+    let record_id = id.clone();
+    crate::db::spawn(async move {
         let res = DB
             .query(
-                "CREATE search_log SET query = $query, source = $source, category = $category, result_count = $result_count"
+                "CREATE type::record('search_log', $id) SET query = $query, source = $source, category = $category, result_count = $result_count, experiment = $experiment, variant = $variant"
             )
+            .bind(("id", record_id))
             .bind(("query", query))
             .bind(("source", source))
             .bind(("category", category))
             .bind(("result_count", result_count))
+            .bind(("experiment", experiment))
+            .bind(("variant", variant))
             .await;
 
         if let Err(e) = res {
             warn!(error = %e, "Failed to log search query");
         }
     });
+
+    id
+}
+
+/// Count a click on one of a logged search's results. `position` is the
+/// 1-based rank of the clicked result; the first click's position is kept.
+pub async fn record_click(search_id: &str, position: Option<u32>) -> Result<()> {
+    if search_id.is_empty() || !search_id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(Error::BadRequest("Invalid search id".to_string()));
+    }
+
+    DB.query(
+        "UPDATE type::record('search_log', $id) SET
+            clicks += 1,
+            first_click_position = first_click_position ?? $position",
+    )
+    .bind(("id", search_id.to_string()))
+    .bind(("position", position.map(|p| p as i64)))
+    .await?
+    .check()?;
+    Ok(())
 }
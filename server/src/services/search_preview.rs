@@ -0,0 +1,77 @@
+//! "What search sees": the text a record is embedded from, and how close a
+//! test query lands to it. Owners open this from their profile, organization,
+//! location or production to understand why they do or don't show up.
+//!
+//! The text is the `embedding_text` stored with the record, i.e. exactly what
+//! `build_*_embedding_text` produced when it was last embedded. While a
+//! refresh is waiting in `pending_embedding`, the newer text is shown instead.
+
+use serde::Deserialize;
+use surrealdb::types::{RecordId, SurrealValue};
+
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::services::search_cache;
+
+#[derive(Debug, Clone)]
+pub struct Preview {
+    /// `None` until the record has been embedded or queued once
+    pub text: Option<String>,
+    /// The record has an embedding, so it can match by meaning
+    pub indexed: bool,
+    /// An edit is waiting to be re-embedded
+    pub pending: bool,
+}
+
+pub async fn load(record: &RecordId) -> Result<Preview> {
+    #[derive(Deserialize, SurrealValue)]
+    struct Row {
+        embedding_text: Option<String>,
+        indexed: bool,
+    }
+
+    let mut response = DB
+        .query(
+            "SELECT embedding_text, (embedding ?? embedding_q) IS NOT NONE AS indexed FROM $id;
+             SELECT VALUE embedding_text FROM pending_embedding WHERE target = $id;",
+        )
+        .bind(("id", record.clone()))
+        .await?;
+    let row: Option<Row> = response.take(0)?;
+    let row = row.ok_or(Error::NotFound)?;
+    let pending: Vec<String> = response.take(1)?;
+
+    Ok(match pending.into_iter().next() {
+        Some(text) => Preview {
+            text: Some(text),
+            indexed: row.indexed,
+            pending: true,
+        },
+        None => Preview {
+            text: row.embedding_text,
+            indexed: row.indexed,
+            pending: false,
+        },
+    })
+}
+
+/// Cosine similarity between a query and the record's embedding, the number
+/// search compares against the threshold for its type, e.g.
+/// `SEARCH_PEOPLE_VECTOR_THRESHOLD`. `None` when the record has no embedding
+/// yet.
+pub async fn similarity(record: &RecordId, query: &str) -> Result<Option<f64>> {
+    let embedding = search_cache::embedding(query).await.ok_or_else(|| {
+        Error::ExternalService("Semantic search is unavailable right now".to_string())
+    })?;
+
+    let scores: Vec<f64> = DB
+        .query(
+            "SELECT VALUE vector::similarity::cosine(embedding ?? embedding_q, $query_embedding)
+             FROM $id WHERE (embedding ?? embedding_q) IS NOT NONE",
+        )
+        .bind(("id", record.clone()))
+        .bind(("query_embedding", embedding))
+        .await?
+        .take(0)?;
+    Ok(scores.into_iter().next())
+}
@@ -0,0 +1,140 @@
+//! Searching within an organization
+//!
+//! `scope=org:<slug>`, on the search page or `/api/search`, restricts every
+//! type to what the organization owns or is linked to:
+//!
+//! - people: its members, plus the talent it represents when the searcher is
+//!   a member
+//! - organizations: just the organization
+//! - locations: those it listed, including ones that aren't public when the
+//!   searcher is a member
+//! - productions: those it's a member of
+//! - jobs: those it posted, or that are for its productions
+//!
+//! Everything else about a search — who can see whom, operators, facets —
+//! applies as usual. Scoped results differ per organization and searcher, so
+//! they're never cached.
+
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+
+use crate::db::reader;
+use crate::error::{Error, Result};
+use crate::record_id_ext::RecordIdExt;
+use crate::services::search::SearchKind;
+
+/// The one kind of scope there is so far
+const ORG_PREFIX: &str = "org:";
+
+/// The organization slug in a `scope` parameter. `None` when there's no
+/// scope; anything other than `org:<slug>` is an error.
+pub fn parse(value: Option<&str>) -> Result<Option<String>> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+    let invalid = || Error::BadRequest("`scope` must look like org:<slug>".to_string());
+    let slug = value.strip_prefix(ORG_PREFIX).ok_or_else(invalid)?;
+    if slug.is_empty()
+        || !slug
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(invalid());
+    }
+    Ok(Some(slug.to_string()))
+}
+
+/// The people a scoped search finds: the members, and for a searcher who is
+/// one of them, the talent the organization represents too
+pub fn roster(members: Vec<String>, represented: Vec<String>, member: bool) -> Vec<String> {
+    let mut people = members;
+    if member {
+        for person in represented {
+            if !people.contains(&person) {
+                people.push(person);
+            }
+        }
+    }
+    people
+}
+
+/// What an organization-scoped search may find. Ids are strings such as
+/// `"person:abc"`, matching search rows' `<string> id`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrgScope {
+    /// Raw id, e.g. `"organization:abc"`
+    pub organization: String,
+    pub slug: String,
+    pub name: String,
+    pub people: Vec<String>,
+    pub productions: Vec<String>,
+    /// Whether the searcher is one of its members
+    pub member: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, SurrealValue)]
+struct ScopeRow {
+    organization: String,
+    slug: String,
+    name: String,
+    members: Vec<String>,
+    represented: Vec<String>,
+    productions: Vec<String>,
+}
+
+const LOAD: &str = "
+    SELECT
+        <string> id AS organization,
+        slug,
+        name,
+        array::map(<-member_of[WHERE invitation_status = 'accepted']<-person, |$p| <string> $p)
+            AS members,
+        array::map(<-represented_by[WHERE status = 'accepted']<-person, |$p| <string> $p)
+            AS represented,
+        array::map(->member_of[WHERE invitation_status = 'accepted']->production, |$p| <string> $p)
+            AS productions
+    FROM organization WHERE slug = $slug LIMIT 1";
+
+impl OrgScope {
+    /// The organization with `slug`, as searched by `searcher`
+    pub async fn load(slug: &str, searcher: Option<&RecordId>) -> Result<Self> {
+        let mut response = reader()
+            .query(LOAD)
+            .bind(("slug", slug.to_string()))
+            .await?;
+        let row: Option<ScopeRow> = response.take(0)?;
+        let row = row.ok_or(Error::NotFound)?;
+        let searcher = searcher.map(RecordId::to_raw_string);
+        let member = searcher.is_some_and(|s| row.members.contains(&s));
+        Ok(Self {
+            organization: row.organization,
+            slug: row.slug,
+            name: row.name,
+            people: roster(row.members, row.represented, member),
+            productions: row.productions,
+            member,
+        })
+    }
+
+    /// The `scope` parameter that selects this organization
+    pub fn param(&self) -> String {
+        format!("{}{}", ORG_PREFIX, self.slug)
+    }
+
+    /// The scope as a WHERE condition for a type's table. Reads
+    /// `$scope_org`, `$scope_people`, `$scope_productions` and `$scope_member`.
+    pub fn filter(kind: SearchKind) -> &'static str {
+        match kind {
+            SearchKind::People => "<string> id INSIDE $scope_people",
+            SearchKind::Organizations => "<string> id = $scope_org",
+            SearchKind::Locations => {
+                "<string> created_by = $scope_org AND (is_public = true OR $scope_member = true)"
+            }
+            SearchKind::Productions => "<string> id INSIDE $scope_productions",
+            SearchKind::Jobs => {
+                "(<string> posted_by = $scope_org
+                  OR <string> (related_production ?? '') INSIDE $scope_productions)"
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Type-ahead suggestions for the navbar search box
+//!
+//! People, organizations, locations and productions keep their display name
+//! in a `suggest` field, indexed with `prefix_analyzer`, which stores the
+//! leading 2 to 15 characters of every word. A query goes through the same
+//! analyzer, so "jan smi" finds "Jane Smith" from the index alone. Nothing is
+//! embedded and nothing is ranked by vector, which keeps a keystroke cheap.
+
+use serde::{Deserialize, Serialize};
+use surrealdb::types::SurrealValue;
+use tracing::warn;
+
+use crate::db::reader;
+use crate::error::Result;
+
+/// Suggestions returned per query
+pub const LIMIT: usize = 8;
+
+/// Shortest query looked up; the analyzer's shortest prefix
+pub const MIN_QUERY_CHARS: usize = 2;
+
+/// Longer input is cut off
+const MAX_QUERY_CHARS: usize = 64;
+
+/// Something to jump to from the search box
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Suggestion {
+    /// "person", "organization", "location" or "production"
+    pub kind: String,
+    pub label: String,
+    pub url: String,
+    /// BM25 relevance of the prefix match
+    #[serde(skip_serializing)]
+    pub score: f64,
+}
+
+/// The query as looked up, or `None` if it's too short to suggest anything
+pub fn normalize(query: &str) -> Option<String> {
+    let query: String = query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(MAX_QUERY_CHARS)
+        .collect();
+    let letters = query.chars().filter(|c| c.is_alphanumeric()).count();
+    (letters >= MIN_QUERY_CHARS).then_some(query)
+}
+
+/// Merge the per-table matches: labels that start with the query first, then
+/// by relevance, then shorter labels, keeping `limit`
+pub fn rank(query: &str, mut suggestions: Vec<Suggestion>, limit: usize) -> Vec<Suggestion> {
+    let query = query.to_lowercase();
+    let starts = |s: &Suggestion| s.label.to_lowercase().starts_with(&query);
+    suggestions.sort_by(|a, b| {
+        starts(b)
+            .cmp(&starts(a))
+            .then(b.score.total_cmp(&a.score))
+            .then(a.label.len().cmp(&b.label.len()))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    let mut seen: Vec<String> = Vec::new();
+    suggestions.retain(|s| {
+        let new = !seen.contains(&s.url);
+        seen.push(s.url.clone());
+        new
+    });
+    suggestions.truncate(limit);
+    suggestions
+}
+
+const QUERY: &str = "
+    SELECT 'person' AS kind, suggest AS label, '/' + username AS url, search::score(0) ?? 0 AS score
+    FROM person
+    WHERE suggest @0@ $q AND (is_minor != true OR guardian_approved = true)
+        AND (visibility ?? 'public') = 'public'
+    ORDER BY score DESC LIMIT $limit;
+    SELECT 'organization' AS kind, suggest AS label, '/orgs/' + slug AS url, search::score(0) ?? 0 AS score
+    FROM organization
+    WHERE suggest @0@ $q
+    ORDER BY score DESC LIMIT $limit;
+    SELECT 'location' AS kind, suggest AS label, '/locations/' + <string> meta::id(id) AS url,
+        search::score(0) ?? 0 AS score
+    FROM location
+    WHERE suggest @0@ $q AND is_public = true
+    ORDER BY score DESC LIMIT $limit;
+    SELECT 'production' AS kind, suggest AS label, '/productions/' + slug AS url,
+        search::score(0) ?? 0 AS score
+    FROM production
+    WHERE suggest @0@ $q
+    ORDER BY score DESC LIMIT $limit;
+";
+
+async fn lookup(query: &str) -> Result<Vec<Suggestion>> {
+    let mut response = reader()
+        .query(QUERY)
+        .bind(("q", query.to_string()))
+        .bind(("limit", LIMIT as i64))
+        .await?;
+    let mut suggestions: Vec<Suggestion> = Vec::new();
+    for statement in 0..4 {
+        let rows: Vec<Suggestion> = response.take(statement)?;
+        suggestions.extend(rows);
+    }
+    Ok(suggestions)
+}
+
+/// Up to `LIMIT` suggestions for what's been typed so far. Empty if the
+/// prefix indexes are missing, e.g. before `search-indexes apply` has run.
+pub async fn suggest(query: &str) -> Vec<Suggestion> {
+    let Some(query) = normalize(query) else {
+        return Vec::new();
+    };
+    match lookup(&query).await {
+        Ok(suggestions) => rank(&query, suggestions, LIMIT),
+        Err(e) => {
+            warn!(error = %e, "Search suggestions failed");
+            Vec::new()
+        }
+    }
+}
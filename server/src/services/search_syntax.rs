@@ -0,0 +1,265 @@
+//! Search operators
+//!
+//! Power users can pin a search down with operators alongside the free text:
+//!
+//! ```text
+//! skills:"steadicam" city:Atlanta type:person -student
+//! ```
+//!
+//! - `field:value` keeps results whose field contains the value; quote values
+//!   with spaces (`city:"new orleans"`). `-field:value` drops them instead.
+//! - `type:` picks the result types searched (`person`, `organization`,
+//!   `location`, `production`, `job`); `-type:` leaves one out.
+//! - `-word` (or `-"some phrase"`) drops results that mention it.
+//!
+//! Operators become hard filters in each table's WHERE clause, with their
+//! values bound as `$syntax_values` rather than written into the query. What's
+//! left is the free text, which is parsed, embedded and ranked as usual. As
+//! with facets, a filter on a field only some types have (a skill, a union)
+//! keeps just those types. Words that only look like operators (`note:` or
+//! `10:30`) stay in the free text.
+
+use crate::services::search::SearchKind;
+
+/// A field an operator filters on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Type,
+    Skill,
+    Location,
+    Union,
+    Status,
+    Name,
+    /// Anything searchable; only used by `-word`
+    Text,
+}
+
+impl Field {
+    /// The field an operator name refers to
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "type" => Some(Field::Type),
+            "skill" | "skills" => Some(Field::Skill),
+            "city" | "location" => Some(Field::Location),
+            "union" | "unions" => Some(Field::Union),
+            "status" => Some(Field::Status),
+            "name" | "title" => Some(Field::Name),
+            _ => None,
+        }
+    }
+}
+
+/// One `field:value`, `-field:value` or `-word` from a query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operator {
+    pub field: Field,
+    /// Lowercased; for `type:` the `SearchKind` name, e.g. "people"
+    pub value: String,
+    pub negated: bool,
+}
+
+/// A query split into operators and the free text around them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchSyntax {
+    pub operators: Vec<Operator>,
+    /// Everything that isn't an operator, quotes removed
+    pub text: String,
+}
+
+/// The result type a `type:` value names
+fn kind_named(value: &str) -> Option<SearchKind> {
+    match value {
+        "person" | "people" | "talent" | "crew" => Some(SearchKind::People),
+        "org" | "orgs" | "organization" | "organizations" | "company" | "companies" => {
+            Some(SearchKind::Organizations)
+        }
+        "location" | "locations" => Some(SearchKind::Locations),
+        "production" | "productions" | "film" | "films" | "show" | "shows" => {
+            Some(SearchKind::Productions)
+        }
+        "job" | "jobs" => Some(SearchKind::Jobs),
+        _ => None,
+    }
+}
+
+/// Split on whitespace outside double quotes
+fn tokens(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn unquote(value: &str) -> String {
+    value.replace('"', "").trim().to_string()
+}
+
+/// The operator a token spells, if any
+fn operator(token: &str) -> Option<Operator> {
+    let (negated, body) = match token.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token),
+    };
+    if let Some((name, value)) = body.split_once(':')
+        && let Some(field) = Field::parse(name)
+    {
+        let value = unquote(value).to_lowercase();
+        let value = match field {
+            Field::Type => kind_named(&value)?.as_str().to_string(),
+            _ => value,
+        };
+        return (!value.is_empty()).then_some(Operator {
+            field,
+            value,
+            negated,
+        });
+    }
+    let term = unquote(body).to_lowercase();
+    (negated && !term.is_empty()).then_some(Operator {
+        field: Field::Text,
+        value: term,
+        negated,
+    })
+}
+
+/// Lowercased columns a field is matched against in a type's table; none if
+/// the type doesn't have it
+fn columns(field: Field, kind: SearchKind) -> &'static [&'static str] {
+    use SearchKind::*;
+    match (field, kind) {
+        (Field::Skill, People) => &[
+            "string::lowercase(string::join(', ', profile.skills ?? []))",
+            "string::lowercase(profile.headline ?? '')",
+        ],
+        (Field::Skill, Jobs) => &["string::lowercase(string::join(' ', roles.*.title))"],
+        (Field::Location, People) => &["string::lowercase(profile.location ?? '')"],
+        (Field::Location, Locations) => &[
+            "string::lowercase(city ?? '')",
+            "string::lowercase(state ?? '')",
+        ],
+        (Field::Location, Organizations | Productions | Jobs) => {
+            &["string::lowercase(location ?? '')"]
+        }
+        (Field::Union, People) => &["string::lowercase(string::join(', ', profile.unions ?? []))"],
+        (Field::Status, Productions | Jobs) => &["string::lowercase(status ?? '')"],
+        (Field::Name, People) => &[
+            "string::lowercase(name ?? '')",
+            "string::lowercase(username ?? '')",
+        ],
+        (Field::Name, Organizations | Locations) => &["string::lowercase(name ?? '')"],
+        (Field::Name, Productions | Jobs) => &["string::lowercase(title ?? '')"],
+        (Field::Text, People) => &[
+            "string::lowercase(name ?? '')",
+            "string::lowercase(profile.headline ?? '')",
+            "string::lowercase(profile.bio ?? '')",
+            "string::lowercase(string::join(', ', profile.skills ?? []))",
+            "string::lowercase(embedding_text ?? '')",
+        ],
+        (Field::Text, Organizations | Locations) => &[
+            "string::lowercase(name ?? '')",
+            "string::lowercase(description ?? '')",
+            "string::lowercase(embedding_text ?? '')",
+        ],
+        (Field::Text, Productions | Jobs) => &[
+            "string::lowercase(title ?? '')",
+            "string::lowercase(description ?? '')",
+            "string::lowercase(embedding_text ?? '')",
+        ],
+        _ => &[],
+    }
+}
+
+impl SearchSyntax {
+    /// Pull the operators out of a query
+    pub fn parse(query: &str) -> Self {
+        let mut syntax = SearchSyntax::default();
+        let mut text: Vec<String> = Vec::new();
+        for token in tokens(query) {
+            match operator(&token) {
+                Some(op) => syntax.operators.push(op),
+                None => {
+                    let word = unquote(&token);
+                    if !word.is_empty() {
+                        text.push(word);
+                    }
+                }
+            }
+        }
+        syntax.text = text.join(" ");
+        syntax
+    }
+
+    /// Whether `type:` picks the types to search
+    pub fn names_types(&self) -> bool {
+        self.operators
+            .iter()
+            .any(|o| o.field == Field::Type && !o.negated)
+    }
+
+    /// Whether a type can match at all: it's one of the types named (if any
+    /// are), isn't left out, and has every field filtered on
+    pub fn targets(&self, kind: SearchKind) -> bool {
+        if self.names_types()
+            && !self
+                .operators
+                .iter()
+                .any(|o| o.field == Field::Type && !o.negated && o.value == kind.as_str())
+        {
+            return false;
+        }
+        self.operators.iter().all(|o| match o.field {
+            Field::Type => !(o.negated && o.value == kind.as_str()),
+            field => o.negated || !columns(field, kind).is_empty(),
+        })
+    }
+
+    /// WHERE conditions for a type's table, joined with AND. Value `i` is
+    /// read from `$syntax_values[i]`, bound to `values()`.
+    pub fn filter(&self, kind: SearchKind) -> Option<String> {
+        let parts: Vec<String> = self
+            .operators
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.field != Field::Type)
+            .filter_map(|(i, o)| {
+                let cols = columns(o.field, kind);
+                if cols.is_empty() {
+                    return None;
+                }
+                let any = cols
+                    .iter()
+                    .map(|col| format!("{} CONTAINS $syntax_values[{}]", col, i))
+                    .collect::<Vec<_>>()
+                    .join(" OR ");
+                Some(if o.negated {
+                    format!("!({})", any)
+                } else {
+                    format!("({})", any)
+                })
+            })
+            .collect();
+        (!parts.is_empty()).then(|| parts.join(" AND "))
+    }
+
+    /// The operator values, in the order `filter` refers to them
+    pub fn values(&self) -> Vec<String> {
+        self.operators.iter().map(|o| o.value.clone()).collect()
+    }
+}
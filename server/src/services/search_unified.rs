@@ -0,0 +1,139 @@
+//! Unified search results
+//!
+//! The search page normally shows one section per result type. With
+//! `view=unified` everything `search_all` found is merged into a single list
+//! instead, each result badged with its type.
+//!
+//! Raw scores aren't comparable across types: each type has its own weights,
+//! and some add a connection bonus. So a result's score is first divided by
+//! the best score of its type, putting every type on a 0–1 scale where its top
+//! match is 1, and the list is ordered by that. Results that tie keep the
+//! order of the grouped page (people, organizations, locations, productions,
+//! jobs) and their rank within their type.
+
+use crate::services::search::CombinedResults;
+
+/// One result of any type, with what the unified list shows of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnifiedResult {
+    /// "person", "organization", "location", "production" or "job"
+    pub kind: &'static str,
+    /// Type badge, e.g. "Person"
+    pub label: &'static str,
+    pub id: String,
+    pub title: String,
+    /// Headline, organization type, address, production status or poster
+    pub subtitle: Option<String>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub url: String,
+    /// Score relative to the best result of the same type, from 0 to 1
+    pub relevance: f64,
+}
+
+/// Divide each score by the highest, so the best is 1. All zero (or
+/// negative) scores stay at 0 rather than dividing by zero.
+pub fn relative_scores(scores: &[f64]) -> Vec<f64> {
+    let best = scores.iter().copied().fold(0.0, f64::max);
+    scores
+        .iter()
+        .map(|&s| if best > 0.0 { (s / best).max(0.0) } else { 0.0 })
+        .collect()
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(String::from)
+}
+
+/// Every result in one list, most relevant first
+pub fn unified(results: &CombinedResults) -> Vec<UnifiedResult> {
+    let mut merged = Vec::with_capacity(results.total());
+
+    let scores: Vec<f64> = results.people.iter().map(|p| p.score).collect();
+    for (p, relevance) in results.people.iter().zip(relative_scores(&scores)) {
+        merged.push(UnifiedResult {
+            kind: "person",
+            label: "Person",
+            id: p.id.clone(),
+            title: p.name.clone(),
+            subtitle: non_empty(p.headline.as_deref()),
+            location: non_empty(p.location.as_deref()),
+            description: non_empty(p.bio.as_deref()),
+            image: non_empty(p.avatar_url.as_deref()),
+            url: format!("/{}", p.username),
+            relevance,
+        });
+    }
+
+    let scores: Vec<f64> = results.organizations.iter().map(|o| o.score).collect();
+    for (o, relevance) in results.organizations.iter().zip(relative_scores(&scores)) {
+        merged.push(UnifiedResult {
+            kind: "organization",
+            label: "Organization",
+            id: o.id.clone(),
+            title: o.name.clone(),
+            subtitle: non_empty(o.org_type.as_deref()),
+            location: non_empty(o.location.as_deref()),
+            description: non_empty(o.description.as_deref()),
+            image: non_empty(o.logo.as_deref()),
+            url: format!("/orgs/{}", o.slug),
+            relevance,
+        });
+    }
+
+    let scores: Vec<f64> = results.locations.iter().map(|l| l.score).collect();
+    for (l, relevance) in results.locations.iter().zip(relative_scores(&scores)) {
+        merged.push(UnifiedResult {
+            kind: "location",
+            label: "Location",
+            id: l.id.clone(),
+            title: l.name.clone(),
+            subtitle: non_empty(Some(&l.address)),
+            location: Some(format!("{}, {}", l.city, l.state)),
+            description: non_empty(l.description.as_deref()),
+            image: non_empty(l.profile_photo.as_deref()),
+            url: format!("/locations/{}", l.id),
+            relevance,
+        });
+    }
+
+    let scores: Vec<f64> = results.productions.iter().map(|p| p.score).collect();
+    for (p, relevance) in results.productions.iter().zip(relative_scores(&scores)) {
+        merged.push(UnifiedResult {
+            kind: "production",
+            label: "Production",
+            id: p.id.clone(),
+            title: p.title.clone(),
+            subtitle: non_empty(Some(&p.status)),
+            location: non_empty(p.location.as_deref()),
+            description: non_empty(p.description.as_deref()),
+            image: non_empty(p.poster_photo.as_deref()).or(non_empty(p.poster_url.as_deref())),
+            url: format!("/productions/{}", p.slug),
+            relevance,
+        });
+    }
+
+    let scores: Vec<f64> = results.jobs.iter().map(|j| j.score).collect();
+    for (j, relevance) in results.jobs.iter().zip(relative_scores(&scores)) {
+        merged.push(UnifiedResult {
+            kind: "job",
+            label: "Job",
+            id: j.id.clone(),
+            title: j.title.clone(),
+            subtitle: non_empty(Some(&j.poster_name)),
+            location: non_empty(j.location.as_deref()),
+            description: non_empty(Some(&j.description)),
+            image: None,
+            url: format!("/jobs/{}", j.id),
+            relevance,
+        });
+    }
+
+    // Stable, so ties keep the grouped order
+    merged.sort_by(|a, b| b.relevance.total_cmp(&a.relevance));
+    merged
+}
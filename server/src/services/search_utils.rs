@@ -1,5 +1,8 @@
 use regex::Regex;
 
+use crate::physical_attributes::{Attribute, lookup};
+use crate::units::{HEIGHT_PATTERN, height_from_captures};
+
 /// Normalize common industry search terms to their singular form.
 /// Only depluralize — don't cross-map between different words (e.g., actress stays actress, not actor).
 /// The embedding synonyms handle cross-matching via vector similarity.
@@ -44,15 +47,24 @@ pub struct ParsedQuery {
     pub hair_color: Option<String>,
     pub eye_color: Option<String>,
     pub body_type: Option<String>,
+    /// Height bounds in millimetres, from heights written in either system
+    pub height_min_mm: Option<i32>,
+    pub height_max_mm: Option<i32>,
+    /// Only identity-verified people ("verified actors", "ID-verified editors")
+    pub verified_only: bool,
     pub cleaned: String,
 }
 
 /// Parse natural language query into structured filters + cleaned search text.
-/// Handles: "blonde female actors ages 20-30 in Berlin", "bald men with blue eyes in LA"
+/// Handles: "blonde female actors ages 20-30 in Berlin", "bald men with blue eyes in LA",
+/// "verified cinematographers", "actors over 6ft", "actresses 165-175cm"
 pub fn parse_query(query: &str) -> ParsedQuery {
     let mut cleaned = query.to_string();
     let mut parsed = ParsedQuery::default();
 
+    // Height (before location, so the "in" of "5 ft 10 in" isn't read as a place)
+    cleaned = parse_height_filter(&cleaned, &mut parsed);
+
     // Location: "in <city/region>" at end of query (must be parsed first before other removals)
     let loc_re = Regex::new(r"(?i)\bin\s+(.+)$").unwrap();
     if let Some(caps) = loc_re.captures(&cleaned) {
@@ -80,64 +92,41 @@ pub fn parse_query(query: &str) -> ParsedQuery {
         cleaned = gender_re.replace(&cleaned, "").to_string();
     }
 
-    // Hair color: "blonde hair", "brown-haired", "with red hair", "bald"
-    let hair_re = Regex::new(
-        r"(?i)\b(black|brown|blonde|blond|red|gray|grey|white|bald)(?:[- ]?haired|\s+hair)?\b"
-    ).unwrap();
-    if let Some(m) = hair_re.find(&cleaned) {
-        let h = m.as_str().to_lowercase();
-        parsed.hair_color = Some(match h.as_str() {
-            s if s.contains("black") => "Black",
-            s if s.contains("brown") => "Brown",
-            s if s.contains("blond") => "Blonde",
-            s if s.contains("red") => "Red",
-            s if s.contains("gray") || s.contains("grey") => "Gray",
-            s if s.contains("white") => "White",
-            s if s.contains("bald") => "Bald",
-            _ => "Other",
-        }.to_string());
-        cleaned = hair_re.replace(&cleaned, "").to_string();
-    }
-
-    // Eye color: "blue eyes", "brown-eyed", "with green eyes"
+    // Eye color: "blue eyes", "brown-eyed", "with green eyes" (before hair, so the
+    // "brown" of "brown eyes" isn't read as a hair color)
     let eye_re = Regex::new(
-        r"(?i)\b(?:with\s+)?(brown|blue|green|hazel|gray|grey|black)(?:[- ]?eyed|\s+eyes?)\b"
+        r"(?i)\b(?:with\s+)?((?:light|dark)[- ](?:brown|blue)|brown|blue|green|hazel|gray|grey|black)(?:[- ]?eyed|\s+eyes?)\b"
     ).unwrap();
     if let Some(caps) = eye_re.captures(&cleaned) {
-        let e = caps.get(1).unwrap().as_str().to_lowercase();
-        parsed.eye_color = Some(match e.as_str() {
-            "brown" => "Brown",
-            "blue" => "Blue",
-            "green" => "Green",
-            "hazel" => "Hazel",
-            "gray" | "grey" => "Gray",
-            "black" => "Black",
-            _ => "Other",
-        }.to_string());
+        parsed.eye_color = lookup(Attribute::EyeColor, &caps[1]).map(str::to_string);
         cleaned = eye_re.replace(&cleaned, "").to_string();
     }
 
+    // Hair color: "blonde hair", "brown-haired", "with red hair", "bald", "auburn"
+    let hair_re = Regex::new(
+        r"(?i)\b((?:dirty|platinum|strawberry)[- ]blonde?|black|brown|blonde|blond|brunette|auburn|ginger|redhead|red|gray|grey|silver|white|bald)(?:[- ]?haired|\s+hair)?\b"
+    ).unwrap();
+    if let Some(caps) = hair_re.captures(&cleaned) {
+        parsed.hair_color = lookup(Attribute::HairColor, &caps[1]).map(str::to_string);
+        cleaned = hair_re.replace(&cleaned, "").to_string();
+    }
+
     // Body type: "athletic", "slim", "muscular", "petite", "plus size", "curvy"
     let body_re = Regex::new(
-        r"(?i)\b(athletic|average|slim|slender|curvy|muscular|petite|plus[- ]?size|tall)\b"
+        r"(?i)\b(athletic|toned|average|slim|slender|thin|curvy|hourglass|voluptuous|muscular|petite|plus[- ]?size|full[- ]figured|tall)\b"
     ).unwrap();
-    if let Some(m) = body_re.find(&cleaned) {
-        let b = m.as_str().to_lowercase();
-        parsed.body_type = Some(match b.as_str() {
-            "athletic" => "Athletic",
-            "average" => "Average",
-            "slim" => "Slim",
-            "slender" => "Slender",
-            "curvy" => "Curvy",
-            "muscular" => "Muscular",
-            "petite" => "Petite",
-            s if s.contains("plus") => "Plus Size",
-            "tall" => "Tall",
-            _ => "Other",
-        }.to_string());
+    if let Some(caps) = body_re.captures(&cleaned) {
+        parsed.body_type = lookup(Attribute::BodyType, &caps[1]).map(str::to_string);
         cleaned = body_re.replace(&cleaned, "").to_string();
     }
 
+    // Verified: "verified", "id-verified", "identity verified"
+    let verified_re = Regex::new(r"(?i)\b(?:id[- ]?|identity[- ]?)?verified\b").unwrap();
+    if verified_re.is_match(&cleaned) {
+        parsed.verified_only = true;
+        cleaned = verified_re.replace_all(&cleaned, "").to_string();
+    }
+
     // Clean up filler words left behind
     let filler_re = Regex::new(r"(?i)\b(with|and|who|are|is|that|the|a|an)\b").unwrap();
     cleaned = filler_re.replace_all(&cleaned, "").to_string();
@@ -151,6 +140,88 @@ pub fn parse_query(query: &str) -> ParsedQuery {
     parsed
 }
 
+/// An exact height matches this far either side
+const HEIGHT_TOLERANCE_MM: i32 = 25;
+
+/// Pull a height filter out of the query: a range ("5'8 to 6'", "between
+/// 170cm and 185cm", "165-175cm"), a bound ("over 6ft", "under 1.7m",
+/// "180cm+") or a single height ("6ft tall"). Returns the query without it.
+fn parse_height_filter(query: &str, parsed: &mut ParsedQuery) -> String {
+    let h = HEIGHT_PATTERN;
+
+    // "between 5'8 and 6'2", "5'8 - 6'2", "170cm to 185cm"
+    let range_re = Regex::new(&format!(
+        r"(?i)(?:\bbetween\s+)?{h}\s*(?:-|\u{{2013}}|\bto\b|\band\b)\s*{h}(?:\s+tall\b)?"
+    ))
+    .unwrap();
+    if let Some(caps) = range_re.captures(query)
+        && let (Some(a), Some(b)) = (height_from_captures(&caps, 1), height_from_captures(&caps, 5))
+    {
+        parsed.height_min_mm = Some(a.min(b));
+        parsed.height_max_mm = Some(a.max(b));
+        return range_re.replace(query, "").to_string();
+    }
+
+    // "165-175cm": the unit written once
+    let cm_range_re =
+        Regex::new(r"(?i)\b(\d{3})\s*(?:-|\u{2013}|to)\s*(\d{3})\s*cm\b(?:\s+tall\b)?").unwrap();
+    if let Some(caps) = cm_range_re.captures(query) {
+        let a = caps[1].parse::<i32>().unwrap_or(0) * 10;
+        let b = caps[2].parse::<i32>().unwrap_or(0) * 10;
+        parsed.height_min_mm = Some(a.min(b));
+        parsed.height_max_mm = Some(a.max(b));
+        return cm_range_re.replace(query, "").to_string();
+    }
+
+    // "over 6ft", "taller than 180cm", "at least 5'10"
+    let min_re = Regex::new(&format!(
+        r"(?i)\b(?:over|above|at\s+least|taller\s+than|min(?:imum)?)\s+{h}(?:\s+tall\b)?"
+    ))
+    .unwrap();
+    // "180cm+", "6ft or taller"
+    let min_suffix_re = Regex::new(&format!(
+        r"(?i){h}\s*(?:\+|\bor\s+(?:taller|more|over)\b)"
+    ))
+    .unwrap();
+    // "under 1.7m", "shorter than 5'6", "up to 175cm"
+    let max_re = Regex::new(&format!(
+        r"(?i)\b(?:under|below|at\s+most|shorter\s+than|up\s+to|max(?:imum)?)\s+{h}(?:\s+tall\b)?"
+    ))
+    .unwrap();
+
+    let mut cleaned = query.to_string();
+    for re in [&min_re, &min_suffix_re] {
+        if let Some(caps) = re.captures(&cleaned)
+            && let Some(mm) = height_from_captures(&caps, 1)
+        {
+            parsed.height_min_mm = Some(mm);
+            cleaned = re.replace(&cleaned, "").to_string();
+            break;
+        }
+    }
+    if let Some(caps) = max_re.captures(&cleaned)
+        && let Some(mm) = height_from_captures(&caps, 1)
+    {
+        parsed.height_max_mm = Some(mm);
+        cleaned = max_re.replace(&cleaned, "").to_string();
+    }
+    if parsed.height_min_mm.is_some() || parsed.height_max_mm.is_some() {
+        return cleaned;
+    }
+
+    // "6ft tall", "5'10\"": about that height
+    let exact_re = Regex::new(&format!(r"(?i){h}(?:\s+tall\b)?")).unwrap();
+    if let Some(caps) = exact_re.captures(query)
+        && let Some(mm) = height_from_captures(&caps, 1)
+    {
+        parsed.height_min_mm = Some(mm - HEIGHT_TOLERANCE_MM);
+        parsed.height_max_mm = Some(mm + HEIGHT_TOLERANCE_MM);
+        return exact_re.replace(query, "").to_string();
+    }
+
+    query.to_string()
+}
+
 /// Simple location-only extraction for non-people searches.
 pub fn extract_location(query: &str) -> (Option<String>, String) {
     let loc_re = Regex::new(r"(?i)\bin\s+(.+)$").unwrap();
@@ -0,0 +1,83 @@
+//! Who can find whom
+//!
+//! Each person picks a visibility: `public` profiles turn up for anyone,
+//! `members` ones only for signed-in members, and `hidden` ones in no search,
+//! browse listing or suggestion. On top of that, people who've blocked or
+//! muted each other never see one another. Everyone always finds themselves.
+//!
+//! Lists that are shared between searchers — cached results, suggestions, the
+//! home page — only ever include public profiles. `search_people` checks the
+//! searcher's `Viewer` in the query itself, so filtered-out people never take
+//! up a page's slots.
+
+use surrealdb::types::RecordId;
+
+use crate::error::{Error, Result};
+use crate::models::block::BlockModel;
+use crate::record_id_ext::RecordIdExt;
+
+/// Visibility settings as (value, label)
+pub const VISIBILITIES: &[(&str, &str)] = &[
+    ("public", "Anyone"),
+    ("members", "Signed-in members only"),
+    ("hidden", "Nobody"),
+];
+
+/// Matches people anyone can find, for lists shared between viewers
+pub const PUBLIC_ONLY: &str = "(visibility ?? 'public') = 'public'";
+
+/// Matches people who haven't hidden their profile, for lists only members see
+pub const NOT_HIDDEN: &str = "(visibility ?? 'public') != 'hidden'";
+
+/// Matches the people a viewer can find, given their raw id as
+/// `$visibility_viewer` (empty when signed out) and the people they're
+/// hidden from as `$visibility_hidden`
+pub const VIEWER_FILTER: &str = "(<string> id = $visibility_viewer
+    OR (<string> id NOT INSIDE $visibility_hidden
+        AND ((visibility ?? 'public') = 'public'
+            OR ((visibility ?? 'public') = 'members' AND $visibility_viewer != ''))))";
+
+/// A setting as submitted from account settings
+pub fn parse_visibility(value: &str) -> Result<&'static str> {
+    VISIBILITIES
+        .iter()
+        .map(|(option, _)| *option)
+        .find(|option| *option == value.trim())
+        .ok_or_else(|| Error::Validation("Choose who can find your profile".to_string()))
+}
+
+/// The signed-in person searching. Anonymous searchers have no `Viewer`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Viewer {
+    /// Raw id, e.g. `"person:abc"`
+    pub person: String,
+    /// Raw ids of people they blocked or muted, or who blocked them
+    pub hidden: Vec<String>,
+}
+
+impl Viewer {
+    pub async fn load(person: &RecordId) -> Result<Self> {
+        Ok(Self {
+            person: person.to_raw_string(),
+            hidden: BlockModel::hidden_ids(person).await?,
+        })
+    }
+}
+
+/// Whether `viewer` may find the person with raw id `id` and the given
+/// visibility; the same rule as `VIEWER_FILTER`, for results already loaded
+pub fn can_see(viewer: Option<&Viewer>, id: &str, visibility: &str) -> bool {
+    if let Some(viewer) = viewer {
+        if viewer.person == id {
+            return true;
+        }
+        if viewer.hidden.iter().any(|hidden| hidden == id) {
+            return false;
+        }
+    }
+    match visibility {
+        "hidden" => false,
+        "members" => viewer.is_some(),
+        _ => true,
+    }
+}
@@ -0,0 +1,243 @@
+//! "Did you mean" for searches that find nothing
+//!
+//! The dictionary is every word in what search can find: people's names and
+//! skills, organization and public location names, production and open job
+//! titles, with how often each occurs. Lookups follow SymSpell: each word is
+//! indexed under every string reachable by deleting up to `MAX_DISTANCE`
+//! characters, so a misspelling's own deletes lead straight to the words
+//! within that many edits of it, without scanning the dictionary.
+//!
+//! Dictionaries are built on the first zero-result search, one per tenant,
+//! and rebuilt once they're older than `REFRESH`.
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::db::{current_tenant, reader};
+use crate::error::Result;
+
+/// Most edits between a misspelling and its correction
+pub const MAX_DISTANCE: usize = 2;
+
+/// Words shorter than this are neither indexed nor corrected
+const MIN_WORD_LEN: usize = 3;
+
+/// How long a dictionary is used before it's rebuilt
+const REFRESH: Duration = Duration::from_secs(3600);
+
+/// Words and their deletes, for correcting search terms
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    /// Each word and how many times it occurs
+    words: HashMap<String, u32>,
+    /// Each delete and the words it was made from
+    deletes: HashMap<String, Vec<String>>,
+}
+
+/// The lowercase words of `text` worth indexing or correcting
+fn words_in(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| w.chars().count() >= MIN_WORD_LEN && !w.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_lowercase)
+}
+
+/// Every string made by deleting up to `distance` characters from `word`,
+/// including `word` itself
+fn deletes(word: &str, distance: usize) -> HashSet<String> {
+    let mut found = HashSet::from([word.to_string()]);
+    let mut frontier = vec![word.to_string()];
+    for _ in 0..distance {
+        let mut next = Vec::new();
+        for current in &frontier {
+            let chars: Vec<char> = current.chars().collect();
+            for skip in 0..chars.len() {
+                let shorter: String = chars
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| *i != skip)
+                    .map(|(_, c)| c)
+                    .collect();
+                if found.insert(shorter.clone()) {
+                    next.push(shorter);
+                }
+            }
+        }
+        frontier = next;
+    }
+    found
+}
+
+/// Edits between two words, counting a swap of neighbouring characters as
+/// one (optimal string alignment)
+pub fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Edits allowed for a word of this length; short words get one so
+/// suggestions stay close to what was typed
+fn allowed_distance(word: &str) -> usize {
+    if word.chars().count() <= 4 {
+        1
+    } else {
+        MAX_DISTANCE
+    }
+}
+
+impl Dictionary {
+    /// A dictionary of the words in `phrases`
+    pub fn new<'a>(phrases: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut dictionary = Self::default();
+        for phrase in phrases {
+            for word in words_in(phrase) {
+                dictionary.add(word);
+            }
+        }
+        dictionary
+    }
+
+    fn add(&mut self, word: String) {
+        let count = self.words.entry(word.clone()).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            return;
+        }
+        for delete in deletes(&word, MAX_DISTANCE) {
+            self.deletes.entry(delete).or_default().push(word.clone());
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// The closest word to `word`: fewest edits, then most common, then
+    /// alphabetically first. A word in the dictionary is its own match.
+    pub fn lookup(&self, word: &str) -> Option<&str> {
+        let word = word.to_lowercase();
+        if let Some((known, _)) = self.words.get_key_value(&word) {
+            return Some(known);
+        }
+        let allowed = allowed_distance(&word);
+        deletes(&word, allowed)
+            .iter()
+            .filter_map(|delete| self.deletes.get(delete))
+            .flatten()
+            .map(|candidate| (distance(&word, candidate), candidate.as_str()))
+            .filter(|(edits, _)| *edits <= allowed)
+            .min_by_key(|(edits, candidate)| (*edits, Reverse(self.words[*candidate]), *candidate))
+            .map(|(_, candidate)| candidate)
+    }
+
+    /// `query` with each misspelled word corrected, or None if nothing
+    /// changed. Operators (`field:value`, `-word`), quoted phrases and words
+    /// with digits or punctuation are left as typed.
+    pub fn correct(&self, query: &str) -> Option<String> {
+        let mut changed = false;
+        let corrected: Vec<String> = query
+            .split_whitespace()
+            .map(|token| {
+                let plain =
+                    token.chars().all(char::is_alphabetic) && token.chars().count() >= MIN_WORD_LEN;
+                if !plain {
+                    return token.to_string();
+                }
+                match self.lookup(token) {
+                    Some(word) if word != token.to_lowercase() => {
+                        changed = true;
+                        word.to_string()
+                    }
+                    _ => token.to_string(),
+                }
+            })
+            .collect();
+        changed.then(|| corrected.join(" "))
+    }
+}
+
+static DICTIONARIES: LazyLock<Mutex<HashMap<Option<String>, (Instant, Arc<Dictionary>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Everything search can find, as text to take words from
+async fn load() -> Result<Dictionary> {
+    let mut response = reader()
+        .query(
+            "SELECT VALUE name ?? '' FROM person
+                 WHERE (is_minor != true OR guardian_approved = true)
+                     AND (visibility ?? 'public') = 'public';
+             RETURN array::flatten(SELECT VALUE profile.skills ?? [] FROM person
+                 WHERE is_minor != true OR guardian_approved = true);
+             SELECT VALUE name ?? '' FROM organization;
+             SELECT VALUE name ?? '' FROM location WHERE is_public = true;
+             SELECT VALUE title ?? '' FROM production;
+             SELECT VALUE title ?? '' FROM job_posting
+                 WHERE status = 'open' AND expires_at > time::now();",
+        )
+        .await?;
+    let mut phrases: Vec<String> = Vec::new();
+    for statement in 0..6 {
+        let found: Vec<String> = response.take(statement)?;
+        phrases.extend(found);
+    }
+    Ok(Dictionary::new(phrases.iter().map(String::as_str)))
+}
+
+/// The tenant's dictionary, built or rebuilt if it's missing or stale
+async fn dictionary() -> Result<Arc<Dictionary>> {
+    let tenant = current_tenant();
+    {
+        let cached = DICTIONARIES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((built, dictionary)) = cached.get(&tenant)
+            && built.elapsed() < REFRESH
+        {
+            return Ok(dictionary.clone());
+        }
+    }
+
+    let dictionary = Arc::new(load().await?);
+    debug!("Built spelling dictionary of {} words", dictionary.len());
+    DICTIONARIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(tenant, (Instant::now(), dictionary.clone()));
+    Ok(dictionary)
+}
+
+/// A corrected query to suggest when `query` found nothing, if any word in it
+/// looks misspelled
+pub async fn suggest(query: &str) -> Option<String> {
+    match dictionary().await {
+        Ok(dictionary) => dictionary.correct(query),
+        Err(e) => {
+            warn!("Failed to build spelling dictionary: {}", e);
+            None
+        }
+    }
+}
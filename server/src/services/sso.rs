@@ -0,0 +1,857 @@
+//! Single sign-on and SCIM provisioning for organizations
+//!
+//! An organization owner can connect an OpenID Connect identity provider
+//! (Okta, Entra ID, Google Workspace, ...) from `/orgs/<slug>/sso`. Members then
+//! sign in at `/sso/<slug>/login` with the authorization code flow plus PKCE.
+//! The ID token is checked against the provider's published keys, and the
+//! person is provisioned just in time: an existing account with the same
+//! (verified) email is linked, otherwise a new one is created, and either way
+//! they are made an accepted member of the organization with its default role.
+//!
+//! Only addresses on the organization's verified email domains sign in this
+//! way. The owner proves each domain with a DNS TXT record before SSO can be
+//! turned on, so an organization can't run its own provider to sign in as
+//! someone else's account.
+//!
+//! The same organization can issue a SCIM bearer token so the provider can
+//! provision and deprovision users at `/scim/v2/<slug>/Users`. Deprovisioning
+//! only removes the organization membership: SlateHub accounts belong to the
+//! person, not to the studio that happened to invite them.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, Utc};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use rand::Rng;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, info, warn};
+
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::models::membership::{
+    CreateMembershipData, InvitationStatus, MembershipModel, MembershipRole,
+};
+use crate::models::person::{Person, Profile, validate_username};
+use crate::record_id_ext::RecordIdExt;
+use crate::response;
+use crate::services::tokens::{random_token, token_hash};
+
+/// Cookie carrying `state`, `nonce` and the PKCE verifier between the redirect
+/// to the provider and the callback
+pub const STATE_COOKIE: &str = "sso_state";
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// SSO settings for one organization
+#[derive(Debug, Clone, Default)]
+pub struct SsoConfig {
+    pub organization: Option<RecordId>,
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Email domains allowed to sign in once verified
+    pub allowed_domains: Vec<String>,
+    /// The allowed domains the organization has proven it owns
+    pub verified_domains: Vec<String>,
+    pub default_role: String,
+    pub enabled: bool,
+    pub has_scim_token: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct SsoRow {
+    organization: RecordId,
+    issuer: String,
+    client_id: String,
+    client_secret: String,
+    allowed_domains: Vec<String>,
+    default_role: String,
+    enabled: bool,
+    scim_token_hash: Option<String>,
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<SsoRow> for SsoConfig {
+    fn from(row: SsoRow) -> Self {
+        Self {
+            organization: Some(row.organization),
+            issuer: row.issuer,
+            client_id: row.client_id,
+            client_secret: row.client_secret,
+            allowed_domains: row.allowed_domains,
+            verified_domains: Vec::new(),
+            default_role: row.default_role,
+            enabled: row.enabled,
+            has_scim_token: row.scim_token_hash.is_some(),
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+impl SsoConfig {
+    /// Whether `email` is on a verified domain, the only addresses SSO and
+    /// SCIM can sign in or provision
+    pub fn allows_email(&self, email: &str) -> bool {
+        email_domain_allowed(email, &self.verified_domains)
+    }
+
+    pub fn role(&self) -> MembershipRole {
+        // Owners are never granted by the identity provider
+        match self.default_role.as_str() {
+            "admin" => MembershipRole::Admin,
+            _ => MembershipRole::Member,
+        }
+    }
+}
+
+/// Whether an email's domain is in the allow-list (an empty list allows none).
+/// Subdomains of an allowed domain are accepted too.
+pub fn email_domain_allowed(email: &str, allowed: &[String]) -> bool {
+    let Some((_, domain)) = email.trim().rsplit_once('@') else {
+        return false;
+    };
+    let domain = domain.to_lowercase();
+    allowed.iter().any(|a| {
+        let a = a.trim().trim_start_matches('@').to_lowercase();
+        !a.is_empty() && (domain == a || domain.ends_with(&format!(".{}", a)))
+    })
+}
+
+/// Split a comma/whitespace separated domain list from the settings form
+pub fn parse_domains(input: &str) -> Vec<String> {
+    let mut domains: Vec<String> = Vec::new();
+    for domain in input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(|d| d.trim().trim_start_matches('@').to_lowercase())
+        .filter(|d| !d.is_empty())
+    {
+        if !domains.contains(&domain) {
+            domains.push(domain);
+        }
+    }
+    domains
+}
+
+pub async fn get_config(organization: &RecordId) -> Result<Option<SsoConfig>> {
+    let mut response = DB
+        .query(
+            "SELECT * FROM org_sso WHERE organization = $org LIMIT 1;
+             SELECT VALUE domain FROM sso_domain WHERE organization = $org AND verified_at != NONE",
+        )
+        .bind(("org", organization.clone()))
+        .await?;
+    let row: Option<SsoRow> = response.take(0)?;
+    let verified: Vec<String> = response.take(1)?;
+    Ok(row.map(|row| SsoConfig {
+        verified_domains: verified,
+        ..SsoConfig::from(row)
+    }))
+}
+
+/// Create or update an organization's SSO settings. An empty `client_secret`
+/// keeps the stored one, so the settings form never has to echo it back.
+/// SSO stays off until every allowed domain is verified; the rest of the
+/// settings are saved either way so the owner can go and verify them.
+pub async fn save_config(organization: &RecordId, config: &SsoConfig) -> Result<()> {
+    let issuer = config.issuer.trim().trim_end_matches('/').to_string();
+    if !issuer.starts_with("https://") {
+        return Err(Error::Validation("The issuer must be an https:// URL".to_string()));
+    }
+    if config.client_id.trim().is_empty() {
+        return Err(Error::Validation("Client ID is required".to_string()));
+    }
+    if config.allowed_domains.is_empty() {
+        return Err(Error::Validation(
+            "Add at least one email domain your organization owns".to_string(),
+        ));
+    }
+
+    let existing = get_config(organization).await?;
+    let client_secret = if config.client_secret.trim().is_empty() {
+        match existing {
+            Some(ref e) => e.client_secret.clone(),
+            None => return Err(Error::Validation("Client secret is required".to_string())),
+        }
+    } else {
+        config.client_secret.trim().to_string()
+    };
+
+    sync_domains(organization, &config.allowed_domains).await?;
+    let verified = verified_domains(organization).await?;
+    let unverified = config.allowed_domains.iter().find(|d| !verified.contains(d));
+    let enabled = config.enabled && unverified.is_none();
+    if enabled {
+        // Catch typos in the issuer before members are sent to it
+        discover(&issuer).await?;
+    }
+
+    DB.query(
+        "UPSERT org_sso SET organization = $org, issuer = $issuer, client_id = $client_id,
+             client_secret = $client_secret, allowed_domains = $domains, default_role = $role,
+             enabled = $enabled
+         WHERE organization = $org",
+    )
+    .bind(("org", organization.clone()))
+    .bind(("issuer", issuer))
+    .bind(("client_id", config.client_id.trim().to_string()))
+    .bind(("client_secret", client_secret))
+    .bind(("domains", config.allowed_domains.clone()))
+    .bind(("role", config.role().as_str().to_string()))
+    .bind(("enabled", enabled))
+    .await?
+    .check()?;
+
+    info!("Updated SSO settings for {}", organization.display());
+    match unverified {
+        Some(domain) if config.enabled => Err(Error::Validation(format!(
+            "Verify that your organization owns {} before turning on single sign-on",
+            domain
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// ============================
+// Domain verification
+// ============================
+
+/// DNS-over-HTTPS resolver used to look up verification records
+const DNS_RESOLVER_URL: &str = "https://cloudflare-dns.com/dns-query";
+
+/// TXT record type in DNS JSON answers
+const TXT_RECORD: u64 = 16;
+
+/// An allowed email domain and the TXT record that proves it
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct SsoDomain {
+    pub domain: String,
+    pub token: String,
+    pub verified_at: Option<DateTime<Utc>>,
+}
+
+impl SsoDomain {
+    /// Where the TXT record goes
+    pub fn record_name(&self) -> String {
+        verification_record_name(&self.domain)
+    }
+
+    /// What the TXT record says
+    pub fn record_value(&self) -> String {
+        verification_record_value(&self.token)
+    }
+}
+
+pub fn verification_record_name(domain: &str) -> String {
+    format!("_slatehub-verification.{}", domain)
+}
+
+pub fn verification_record_value(token: &str) -> String {
+    format!("slatehub-verification={}", token)
+}
+
+/// An organization's allowed domains, in the order they were added
+pub async fn domains(organization: &RecordId) -> Result<Vec<SsoDomain>> {
+    let domains: Vec<SsoDomain> = DB
+        .query(
+            "SELECT domain, token, verified_at FROM sso_domain
+             WHERE organization = $org ORDER BY created_at",
+        )
+        .bind(("org", organization.clone()))
+        .await?
+        .take(0)?;
+    Ok(domains)
+}
+
+async fn verified_domains(organization: &RecordId) -> Result<Vec<String>> {
+    let domains: Vec<String> = DB
+        .query("SELECT VALUE domain FROM sso_domain WHERE organization = $org AND verified_at != NONE")
+        .bind(("org", organization.clone()))
+        .await?
+        .take(0)?;
+    Ok(domains)
+}
+
+/// Give each newly allowed domain a verification token and forget the ones
+/// no longer allowed
+async fn sync_domains(organization: &RecordId, allowed: &[String]) -> Result<()> {
+    let existing: Vec<String> = domains(organization)
+        .await?
+        .into_iter()
+        .map(|d| d.domain)
+        .collect();
+    for domain in allowed.iter().filter(|d| !existing.contains(d)) {
+        DB.query("CREATE sso_domain SET organization = $org, domain = $domain, token = $token")
+            .bind(("org", organization.clone()))
+            .bind(("domain", domain.clone()))
+            .bind(("token", random_token(32)))
+            .await?
+            .check()?;
+    }
+    DB.query("DELETE sso_domain WHERE organization = $org AND domain NOT IN $allowed")
+        .bind(("org", organization.clone()))
+        .bind(("allowed", allowed.to_vec()))
+        .await?
+        .check()?;
+    Ok(())
+}
+
+/// Look for the domain's TXT record and mark it verified when it's there.
+/// Returns whether the domain is now verified.
+pub async fn verify_domain(organization: &RecordId, domain: &str) -> Result<bool> {
+    let Some(entry) = domains(organization)
+        .await?
+        .into_iter()
+        .find(|d| d.domain == domain)
+    else {
+        return Err(Error::NotFound);
+    };
+    if entry.verified_at.is_some() {
+        return Ok(true);
+    }
+
+    // One organization per domain, so another can't take over its staff
+    let claimed: Option<RecordId> = DB
+        .query(
+            "SELECT VALUE organization FROM sso_domain
+             WHERE domain = $domain AND organization != $org AND verified_at != NONE LIMIT 1",
+        )
+        .bind(("org", organization.clone()))
+        .bind(("domain", domain.to_string()))
+        .await?
+        .take(0)?;
+    if claimed.is_some() {
+        return Err(Error::Conflict(format!(
+            "{} is already verified by another organization",
+            domain
+        )));
+    }
+
+    let records = txt_records(&entry.record_name()).await?;
+    if !records.contains(&entry.record_value()) {
+        debug!("No verification record for {} yet", domain);
+        return Ok(false);
+    }
+
+    DB.query("UPDATE sso_domain SET verified_at = time::now() WHERE organization = $org AND domain = $domain")
+        .bind(("org", organization.clone()))
+        .bind(("domain", domain.to_string()))
+        .await?
+        .check()?;
+    info!("Verified SSO domain {} for {}", domain, organization.display());
+    Ok(true)
+}
+
+/// TXT records published at `name`
+async fn txt_records(name: &str) -> Result<Vec<String>> {
+    let answer: serde_json::Value = http_client()?
+        .get(DNS_RESOLVER_URL)
+        .query(&[("name", name), ("type", "TXT")])
+        .header("Accept", "application/dns-json")
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::ExternalService(format!("DNS lookup failed for {}: {}", name, e)))?
+        .json()
+        .await
+        .map_err(|e| Error::ExternalService(format!("Invalid DNS response for {}: {}", name, e)))?;
+    Ok(parse_txt_answer(&answer))
+}
+
+/// The TXT strings in a DNS JSON answer. Long records arrive split into
+/// quoted chunks, which are joined back together.
+pub fn parse_txt_answer(answer: &serde_json::Value) -> Vec<String> {
+    let Some(records) = answer.get("Answer").and_then(|a| a.as_array()) else {
+        return Vec::new();
+    };
+    records
+        .iter()
+        .filter(|r| r.get("type").and_then(|t| t.as_u64()) == Some(TXT_RECORD))
+        .filter_map(|r| r.get("data").and_then(|d| d.as_str()))
+        .map(|data| {
+            let data = data.trim();
+            if data.starts_with('"') {
+                data.split('"').skip(1).step_by(2).collect()
+            } else {
+                data.to_string()
+            }
+        })
+        .collect()
+}
+
+// ============================
+// OpenID Connect
+// ============================
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Discovery {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+fn http_client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| Error::Internal(format!("Failed to build HTTP client: {}", e)))
+}
+
+/// Fetch the provider's `.well-known/openid-configuration`
+pub async fn discover(issuer: &str) -> Result<Discovery> {
+    let url = format!("{}/.well-known/openid-configuration", issuer.trim_end_matches('/'));
+    let discovery: Discovery = http_client()?
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::ExternalService(format!("OIDC discovery failed for {}: {}", issuer, e)))?
+        .json()
+        .await
+        .map_err(|e| Error::ExternalService(format!("Invalid OIDC discovery document: {}", e)))?;
+
+    if discovery.issuer.trim_end_matches('/') != issuer.trim_end_matches('/') {
+        return Err(Error::ExternalService(format!(
+            "OIDC issuer mismatch: expected {}, provider reports {}",
+            issuer, discovery.issuer
+        )));
+    }
+    Ok(discovery)
+}
+
+/// Per-login values kept in the state cookie
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthState {
+    pub state: String,
+    pub nonce: String,
+    pub verifier: String,
+    pub redirect_to: Option<String>,
+}
+
+impl AuthState {
+    pub fn generate(redirect_to: Option<String>) -> Self {
+        Self {
+            state: random_token(32),
+            nonce: random_token(32),
+            verifier: random_token(64),
+            redirect_to: response::local_path(redirect_to),
+        }
+    }
+
+    pub fn to_cookie_value(&self) -> String {
+        format!(
+            "{}.{}.{}.{}",
+            self.state,
+            self.nonce,
+            self.verifier,
+            URL_SAFE_NO_PAD.encode(self.redirect_to.as_deref().unwrap_or(""))
+        )
+    }
+
+    pub fn from_cookie_value(value: &str) -> Option<Self> {
+        let mut parts = value.split('.');
+        let state = parts.next()?.to_string();
+        let nonce = parts.next()?.to_string();
+        let verifier = parts.next()?.to_string();
+        let redirect = String::from_utf8(URL_SAFE_NO_PAD.decode(parts.next()?).ok()?).ok()?;
+        if parts.next().is_some() || state.is_empty() || verifier.is_empty() {
+            return None;
+        }
+        Some(Self {
+            state,
+            nonce,
+            verifier,
+            redirect_to: response::local_path(Some(redirect)),
+        })
+    }
+}
+
+/// PKCE S256 code challenge for a verifier (RFC 7636)
+pub fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+pub fn redirect_uri(slug: &str) -> String {
+    format!("{}/sso/{}/callback", crate::config::app_url(), slug)
+}
+
+/// URL to send the browser to for sign-in at the provider
+pub async fn authorization_url(config: &SsoConfig, slug: &str, auth: &AuthState) -> Result<String> {
+    let discovery = discover(&config.issuer).await?;
+    let separator = if discovery.authorization_endpoint.contains('?') { '&' } else { '?' };
+    Ok(format!(
+        "{}{}response_type=code&scope={}&client_id={}&redirect_uri={}&state={}&nonce={}&code_challenge={}&code_challenge_method=S256",
+        discovery.authorization_endpoint,
+        separator,
+        urlencoding::encode("openid email profile"),
+        urlencoding::encode(&config.client_id),
+        urlencoding::encode(&redirect_uri(slug)),
+        auth.state,
+        auth.nonce,
+        pkce_challenge(&auth.verifier),
+    ))
+}
+
+/// Identity asserted by the provider's ID token
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub name: Option<String>,
+    pub nonce: Option<String>,
+}
+
+impl IdTokenClaims {
+    /// The email, only when the provider says it has verified it. A missing
+    /// `email_verified` claim doesn't count.
+    pub fn verified_email(&self) -> Option<&str> {
+        self.email
+            .as_deref()
+            .filter(|_| self.email_verified == Some(true))
+    }
+}
+
+/// Exchange the authorization code and validate the returned ID token
+pub async fn complete_login(
+    config: &SsoConfig,
+    slug: &str,
+    code: &str,
+    auth: &AuthState,
+) -> Result<IdTokenClaims> {
+    let discovery = discover(&config.issuer).await?;
+    let client = http_client()?;
+
+    #[derive(Deserialize)]
+    struct TokenResponse {
+        id_token: String,
+    }
+
+    let redirect = redirect_uri(slug);
+    let tokens: TokenResponse = client
+        .post(&discovery.token_endpoint)
+        .basic_auth(&config.client_id, Some(&config.client_secret))
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect.as_str()),
+            ("code_verifier", auth.verifier.as_str()),
+        ])
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::ExternalService(format!("OIDC token exchange failed: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| Error::ExternalService(format!("Invalid OIDC token response: {}", e)))?;
+
+    let header = decode_header(&tokens.id_token)
+        .map_err(|e| Error::ExternalService(format!("Malformed ID token: {}", e)))?;
+    // The provider signs with its published keys, never with a shared secret
+    if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+        return Err(Error::Unauthorized);
+    }
+
+    let jwks: JwkSet = client
+        .get(&discovery.jwks_uri)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| Error::ExternalService(format!("Failed to fetch provider keys: {}", e)))?
+        .json()
+        .await
+        .map_err(|e| Error::ExternalService(format!("Invalid provider key set: {}", e)))?;
+
+    let jwk = match header.kid.as_deref() {
+        Some(kid) => jwks.find(kid),
+        None => jwks.keys.first(),
+    }
+    .ok_or_else(|| Error::ExternalService("ID token signed with an unknown key".to_string()))?;
+    let key = DecodingKey::from_jwk(jwk)
+        .map_err(|e| Error::ExternalService(format!("Unusable provider key: {}", e)))?;
+
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[&config.client_id]);
+    validation.set_issuer(&[&discovery.issuer]);
+
+    let claims = decode::<IdTokenClaims>(&tokens.id_token, &key, &validation)
+        .map_err(|e| {
+            warn!("Rejected ID token for {}: {}", slug, e);
+            Error::Unauthorized
+        })?
+        .claims;
+
+    if claims.nonce.as_deref() != Some(auth.nonce.as_str()) {
+        warn!("ID token nonce mismatch for {}", slug);
+        return Err(Error::Unauthorized);
+    }
+
+    Ok(claims)
+}
+
+// ============================
+// Provisioning
+// ============================
+
+/// Find or create the person for an SSO identity and make sure they are an
+/// accepted member of the organization. Returns the person. Only addresses on
+/// the organization's verified domains are accepted, so an existing account
+/// is only ever linked when the organization owns its email domain.
+pub async fn provision(
+    config: &SsoConfig,
+    organization: &RecordId,
+    email: &str,
+    name: Option<&str>,
+) -> Result<Person> {
+    let email = email.trim().to_lowercase();
+    if !config.allows_email(&email) {
+        return Err(Error::Forbidden);
+    }
+
+    let person = match Person::find_by_email(&email).await? {
+        Some(person) => person,
+        None => create_person(&email, name).await?,
+    };
+
+    ensure_membership(&person.id, organization, config.role()).await?;
+    Ok(person)
+}
+
+/// Create an account for an email the identity provider vouches for. The
+/// account gets an unusable random password; the person can set one with
+/// "forgot password" if they ever want to sign in without SSO.
+async fn create_person(email: &str, name: Option<&str>) -> Result<Person> {
+    let base = username_from_email(email);
+    let mut username = base.clone();
+    for _ in 0..5 {
+        if Person::find_by_username(&username).await?.is_none() {
+            break;
+        }
+        let suffix: u16 = rand::thread_rng().gen_range(1000..10000);
+        username = format!("{}_{}", &base[..base.len().min(25)], suffix);
+    }
+    let username = validate_username(&username)?;
+    let password_hash = crate::auth::hash_password(&random_token(48))?;
+    let display_name = name.map(str::trim).filter(|n| !n.is_empty()).unwrap_or(&username).to_string();
+
+    let persons: Vec<Person> = DB
+        .query(
+            "CREATE person SET username = $username, email = $email, password = $password, name = $name,
+                 verification_status = 'email', profile = $profile",
+        )
+        .bind(("username", username.clone()))
+        .bind(("email", email.to_string()))
+        .bind(("password", password_hash))
+        .bind(("name", display_name.clone()))
+        .bind(("profile", Profile {
+            name: Some(display_name),
+            ..Default::default()
+        }))
+        .await?
+        .take(0)?;
+
+    let person = persons
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::Internal("Failed to create SSO user".to_string()))?;
+    info!("Provisioned {} via SSO as {}", email, username);
+    Ok(person)
+}
+
+/// Username derived from an email's local part, made valid for
+/// `validate_username`
+pub fn username_from_email(email: &str) -> String {
+    let local = email.split('@').next().unwrap_or_default().to_lowercase();
+    let mut username = String::new();
+    for c in local.chars() {
+        let c = if c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.' {
+            c
+        } else {
+            '_'
+        };
+        if !(c == '.' && username.ends_with('.')) {
+            username.push(c);
+        }
+    }
+    let mut username: String = username.trim_matches('.').chars().take(30).collect();
+    username = username.trim_end_matches('.').to_string();
+    while username.len() < 3 {
+        username.push('_');
+    }
+    username
+}
+
+async fn ensure_membership(person: &RecordId, organization: &RecordId, role: MembershipRole) -> Result<()> {
+    let model = MembershipModel::new();
+    let person_id = person.to_raw_string();
+    let org_id = organization.to_raw_string();
+
+    match model.find_by_person_and_org(&person_id, &org_id).await? {
+        Some(membership) if membership.invitation_status == "accepted" => Ok(()),
+        Some(membership) => model.accept_invitation(&membership.id.to_raw_string()).await,
+        None => {
+            let permissions = MembershipModel::get_default_permissions(&role);
+            model
+                .create(CreateMembershipData {
+                    person_id: person.key_string(),
+                    organization_id: organization.key_string(),
+                    role,
+                    permissions,
+                    invitation_status: InvitationStatus::Accepted,
+                    invited_by: None,
+                })
+                .await?;
+            debug!("Added {} to {} via SSO", person_id, org_id);
+            Ok(())
+        }
+    }
+}
+
+/// Remove a person from the organization. Owners are never removed by the
+/// identity provider, so a misconfigured SCIM client can't orphan a studio.
+pub async fn deprovision(person: &RecordId, organization: &RecordId) -> Result<bool> {
+    let model = MembershipModel::new();
+    let Some(membership) = model
+        .find_by_person_and_org(&person.to_raw_string(), &organization.to_raw_string())
+        .await?
+    else {
+        return Ok(false);
+    };
+
+    if membership.role == MembershipRole::Owner.as_str() {
+        return Err(Error::Conflict("Organization owners can't be deprovisioned".to_string()));
+    }
+
+    model.delete(&membership.id.to_raw_string()).await?;
+    info!("Deprovisioned {} from {}", person.display(), organization.display());
+    Ok(true)
+}
+
+// ============================
+// SCIM
+// ============================
+
+/// Issue a new SCIM bearer token for the organization, replacing any previous
+/// one. Only the hash is stored; the token is shown once.
+pub async fn rotate_scim_token(organization: &RecordId) -> Result<String> {
+    if get_config(organization).await?.is_none() {
+        return Err(Error::Validation("Configure SSO before enabling SCIM".to_string()));
+    }
+    let token = format!("scim_{}", random_token(40));
+    DB.query("UPDATE org_sso SET scim_token_hash = $hash WHERE organization = $org")
+        .bind(("org", organization.clone()))
+        .bind(("hash", token_hash(&token)))
+        .await?
+        .check()?;
+    Ok(token)
+}
+
+pub async fn revoke_scim_token(organization: &RecordId) -> Result<()> {
+    DB.query("UPDATE org_sso SET scim_token_hash = NONE WHERE organization = $org")
+        .bind(("org", organization.clone()))
+        .await?
+        .check()?;
+    Ok(())
+}
+
+/// Whether a bearer token is the organization's current SCIM token
+pub async fn verify_scim_token(organization: &RecordId, token: &str) -> Result<bool> {
+    let hash: Option<String> = DB
+        .query("SELECT VALUE scim_token_hash FROM org_sso WHERE organization = $org LIMIT 1")
+        .bind(("org", organization.clone()))
+        .await?
+        .take::<Option<Option<String>>>(0)?
+        .flatten();
+    Ok(hash.is_some_and(|h| constant_time_eq(h.as_bytes(), token_hash(token).as_bytes())))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// The value of a `userName eq "..."` SCIM filter, the only filter identity
+/// providers send when looking up a user before creating it
+pub fn parse_user_filter(filter: &str) -> Option<String> {
+    let filter = filter.trim();
+    let (attribute, rest) = filter.split_once(char::is_whitespace)?;
+    if !attribute.eq_ignore_ascii_case("userName") && !attribute.eq_ignore_ascii_case("emails.value") {
+        return None;
+    }
+    let (op, value) = rest.trim_start().split_once(char::is_whitespace)?;
+    if !op.eq_ignore_ascii_case("eq") {
+        return None;
+    }
+    let value = value.trim();
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+    Some(value.replace("\\\"", "\""))
+}
+
+/// `active` as set by a SCIM PATCH request, if the request changes it. Handles
+/// both `{"path": "active", "value": false}` and `{"value": {"active": false}}`,
+/// and the string booleans some providers send.
+pub fn patch_active(body: &serde_json::Value) -> Option<bool> {
+    let as_bool = |v: &serde_json::Value| match v {
+        serde_json::Value::Bool(b) => Some(*b),
+        serde_json::Value::String(s) => s.to_lowercase().parse::<bool>().ok(),
+        _ => None,
+    };
+
+    body.get("Operations")?.as_array()?.iter().rev().find_map(|op| {
+        let kind = op.get("op")?.as_str()?.to_lowercase();
+        if kind != "replace" && kind != "add" {
+            return None;
+        }
+        let value = op.get("value")?;
+        match op.get("path").and_then(|p| p.as_str()) {
+            Some(path) if path.eq_ignore_ascii_case("active") => as_bool(value),
+            Some(_) => None,
+            None => value.get("active").and_then(as_bool),
+        }
+    })
+}
+
+/// A member of the organization as seen by the identity provider
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct ProvisionedUser {
+    pub person: RecordId,
+    pub email: String,
+    pub name: Option<String>,
+    pub username: String,
+}
+
+/// Accepted members of an organization, optionally only the one with `email`
+pub async fn members(organization: &RecordId, email: Option<&str>) -> Result<Vec<ProvisionedUser>> {
+    let users: Vec<ProvisionedUser> = DB
+        .query(
+            "SELECT in AS person, in.email AS email, in.name AS name, in.username AS username
+             FROM member_of
+             WHERE out = $org AND invitation_status = 'accepted'
+                 AND ($email = NONE OR in.email = $email)
+             ORDER BY email",
+        )
+        .bind(("org", organization.clone()))
+        .bind(("email", email.map(|e| e.trim().to_lowercase())))
+        .await?
+        .take(0)?;
+    Ok(users)
+}
+
+pub async fn member(organization: &RecordId, person: &RecordId) -> Result<Option<ProvisionedUser>> {
+    let user: Option<ProvisionedUser> = DB
+        .query(
+            "SELECT in AS person, in.email AS email, in.name AS name, in.username AS username
+             FROM member_of
+             WHERE out = $org AND in = $person AND invitation_status = 'accepted'
+             LIMIT 1",
+        )
+        .bind(("org", organization.clone()))
+        .bind(("person", person.clone()))
+        .await?
+        .take(0)?;
+    Ok(user)
+}
@@ -0,0 +1,366 @@
+//! Service status and notices
+//!
+//! Two halves:
+//!
+//! - Readiness: `readiness()` runs the same checks as `/healthcheck` (database,
+//!   file storage, search, disk). `/status` shows them to users as component
+//!   states, cached briefly so the public page can't hammer the backends.
+//! - Notices: incidents and planned maintenance posted from `/admin/status`,
+//!   kept in the `status_notice` table of the primary database. Active notices
+//!   are shown as a banner on every page and listed on `/status`. The layout
+//!   reads them synchronously with `banner()`, which serves an in-memory copy
+//!   and refreshes it in the background every `REFRESH` so other servers pick
+//!   up changes.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, RwLock};
+use std::time::{Duration, Instant};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{info, warn};
+
+use crate::db::primary;
+use crate::error::{Error, Result};
+use crate::record_id_ext::RecordIdExt;
+
+const REFRESH: Duration = Duration::from_secs(30);
+const READINESS_TTL: Duration = Duration::from_secs(15);
+/// Pending embeddings above which search is reported as catching up
+const EMBEDDING_BACKLOG_WARN: i64 = 100;
+/// Resolved notices stay on `/status` this long
+const RESOLVED_HISTORY_DAYS: i64 = 14;
+
+// ============================
+// Readiness
+// ============================
+
+/// Result of the readiness checks
+#[derive(Debug, Clone, Serialize)]
+pub struct Readiness {
+    pub database: bool,
+    pub storage: bool,
+    /// Embedding model loaded, so semantic search works
+    pub search: bool,
+    /// Records waiting for an embedding (new or edited, not yet searchable by meaning)
+    pub embedding_backlog: i64,
+    pub disk_low: bool,
+}
+
+impl Readiness {
+    pub fn all_ok(&self) -> bool {
+        self.database && self.storage && !self.disk_low
+    }
+
+    /// Component states as shown on `/status`
+    pub fn components(&self) -> Vec<Component> {
+        let search = if !self.database {
+            (ComponentState::Outage, "Search is unavailable while the database is down.")
+        } else if !self.search {
+            (
+                ComponentState::Degraded,
+                "Search is matching names and keywords only; results may be less relevant.",
+            )
+        } else if self.embedding_backlog > EMBEDDING_BACKLOG_WARN {
+            (
+                ComponentState::Degraded,
+                "Search is catching up; new and edited profiles may take a few minutes to appear.",
+            )
+        } else {
+            (ComponentState::Operational, "")
+        };
+
+        vec![
+            Component::new(
+                "Website",
+                if self.database { ComponentState::Operational } else { ComponentState::Degraded },
+                if self.database { "" } else { "Pages that need your data may fail to load." },
+            ),
+            Component::new(
+                "Database",
+                if self.database { ComponentState::Operational } else { ComponentState::Outage },
+                if self.database { "" } else { "Sign-in, profiles and messages are unavailable." },
+            ),
+            Component::new("Search", search.0, search.1),
+            Component::new(
+                "Media & uploads",
+                if self.storage { ComponentState::Operational } else { ComponentState::Outage },
+                if self.storage { "" } else { "Photos, reels and uploads are unavailable." },
+            ),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentState {
+    Operational,
+    Degraded,
+    Outage,
+}
+
+impl ComponentState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ComponentState::Operational => "Operational",
+            ComponentState::Degraded => "Degraded",
+            ComponentState::Outage => "Outage",
+        }
+    }
+
+    /// Page heading when this is the overall state
+    pub fn headline(&self) -> &'static str {
+        match self {
+            ComponentState::Operational => "All systems operational",
+            ComponentState::Degraded => "Some features are degraded",
+            ComponentState::Outage => "Partial outage",
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ComponentState::Operational => "operational",
+            ComponentState::Degraded => "degraded",
+            ComponentState::Outage => "outage",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Component {
+    pub name: &'static str,
+    pub state: ComponentState,
+    pub detail: &'static str,
+}
+
+impl Component {
+    fn new(name: &'static str, state: ComponentState, detail: &'static str) -> Self {
+        Self { name, state, detail }
+    }
+}
+
+/// Worst state of any component
+pub fn overall(components: &[Component]) -> ComponentState {
+    components
+        .iter()
+        .map(|c| c.state)
+        .max_by_key(|s| match s {
+            ComponentState::Operational => 0,
+            ComponentState::Degraded => 1,
+            ComponentState::Outage => 2,
+        })
+        .unwrap_or(ComponentState::Operational)
+}
+
+/// Run every readiness check now
+pub async fn readiness() -> Readiness {
+    let database = crate::db::is_healthy().await;
+
+    let storage = match crate::services::s3::s3() {
+        Ok(s3) => s3.file_exists("_healthcheck").await.is_ok(),
+        Err(_) => false,
+    };
+
+    let embedding_backlog = if database {
+        pending_embeddings().await
+    } else {
+        0
+    };
+
+    Readiness {
+        database,
+        storage,
+        search: crate::services::embedding::is_initialized(),
+        embedding_backlog,
+        disk_low: crate::stats::disk_space_low(),
+    }
+}
+
+static READINESS: LazyLock<RwLock<Option<(Instant, Readiness)>>> = LazyLock::new(|| RwLock::new(None));
+
+/// `readiness()`, reusing a result from the last few seconds
+pub async fn cached_readiness() -> Readiness {
+    if let Some((checked_at, readiness)) = READINESS.read().unwrap().as_ref()
+        && checked_at.elapsed() < READINESS_TTL
+    {
+        return readiness.clone();
+    }
+    let readiness = readiness().await;
+    *READINESS.write().unwrap() = Some((Instant::now(), readiness.clone()));
+    readiness
+}
+
+async fn pending_embeddings() -> i64 {
+    #[derive(Deserialize, SurrealValue)]
+    struct Row {
+        count: i64,
+    }
+
+    match primary()
+        .query("SELECT count() AS count FROM pending_embedding GROUP ALL")
+        .await
+        .and_then(|mut r| r.take::<Option<Row>>(0))
+    {
+        Ok(row) => row.map(|r| r.count).unwrap_or(0),
+        Err(e) => {
+            warn!("Failed to count pending embeddings: {}", e);
+            0
+        }
+    }
+}
+
+// ============================
+// Notices
+// ============================
+
+pub const NOTICE_KINDS: &[&str] = &["incident", "maintenance", "info"];
+
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct Notice {
+    pub id: RecordId,
+    /// "incident", "maintenance" or "info"
+    pub kind: String,
+    pub message: String,
+    pub active: bool,
+    pub created_by: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
+
+impl Notice {
+    /// Record key, for admin URLs
+    pub fn key(&self) -> String {
+        self.id.key_string()
+    }
+
+    pub fn kind_label(&self) -> &'static str {
+        match self.kind.as_str() {
+            "incident" => "Incident",
+            "maintenance" => "Maintenance",
+            _ => "Notice",
+        }
+    }
+
+    /// Date part of `created_at`, for display
+    pub fn created_date(&self) -> &str {
+        self.created_at.get(..10).unwrap_or(&self.created_at)
+    }
+}
+
+struct Banner {
+    loaded_at: Option<Instant>,
+    notices: Vec<Notice>,
+}
+
+static BANNER: LazyLock<RwLock<Banner>> = LazyLock::new(|| {
+    RwLock::new(Banner {
+        loaded_at: None,
+        notices: Vec::new(),
+    })
+});
+
+static REFRESHING: AtomicBool = AtomicBool::new(false);
+
+/// Active notices for the page banner, newest first. Never waits on the
+/// database: a stale copy triggers a background refresh.
+pub fn banner() -> Vec<Notice> {
+    let banner = BANNER.read().unwrap();
+    let stale = banner.loaded_at.is_none_or(|t| t.elapsed() > REFRESH);
+    if stale && !REFRESHING.swap(true, Ordering::SeqCst) {
+        // A runtime is only missing when templates render outside the server (tests)
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            handle.spawn(async {
+                if let Err(e) = refresh().await {
+                    warn!("Failed to refresh status notices: {}", e);
+                }
+                REFRESHING.store(false, Ordering::SeqCst);
+            });
+        } else {
+            REFRESHING.store(false, Ordering::SeqCst);
+        }
+    }
+    banner.notices.clone()
+}
+
+/// Reload the active notices into memory
+pub async fn refresh() -> Result<()> {
+    let notices = list_active().await?;
+    let mut banner = BANNER.write().unwrap();
+    banner.notices = notices;
+    banner.loaded_at = Some(Instant::now());
+    Ok(())
+}
+
+const NOTICE_FIELDS: &str = "id, kind, message, active, created_by, <string> created_at AS created_at,
+     IF resolved_at THEN <string> resolved_at END AS resolved_at";
+
+pub async fn list_active() -> Result<Vec<Notice>> {
+    let notices: Vec<Notice> = primary()
+        .query(format!(
+            "SELECT {} FROM status_notice WHERE active = true ORDER BY created_at DESC",
+            NOTICE_FIELDS
+        ))
+        .await?
+        .take(0)?;
+    Ok(notices)
+}
+
+/// Notices resolved in the last two weeks, newest first
+pub async fn list_recent_resolved() -> Result<Vec<Notice>> {
+    let notices: Vec<Notice> = primary()
+        .query(format!(
+            "SELECT {} FROM status_notice
+             WHERE active = false AND resolved_at > time::now() - {}d
+             ORDER BY resolved_at DESC",
+            NOTICE_FIELDS, RESOLVED_HISTORY_DAYS
+        ))
+        .await?
+        .take(0)?;
+    Ok(notices)
+}
+
+/// Every notice, newest first, for the admin page
+pub async fn list_all(limit: usize) -> Result<Vec<Notice>> {
+    let notices: Vec<Notice> = primary()
+        .query(format!(
+            "SELECT {} FROM status_notice ORDER BY created_at DESC LIMIT $limit",
+            NOTICE_FIELDS
+        ))
+        .bind(("limit", limit as i64))
+        .await?
+        .take(0)?;
+    Ok(notices)
+}
+
+/// Post a notice; it shows in the banner straight away on this server
+pub async fn post(kind: &str, message: &str, created_by: &str) -> Result<()> {
+    if !NOTICE_KINDS.contains(&kind) {
+        return Err(Error::Validation(format!("Unknown notice type '{}'", kind)));
+    }
+    let message = message.trim();
+    if message.is_empty() || message.len() > 500 {
+        return Err(Error::Validation("Notices must be 1-500 characters".to_string()));
+    }
+
+    primary()
+        .query(
+            "CREATE status_notice SET kind = $kind, message = $message, active = true,
+                 created_by = $created_by",
+        )
+        .bind(("kind", kind.to_string()))
+        .bind(("message", message.to_string()))
+        .bind(("created_by", created_by.to_string()))
+        .await?
+        .check()?;
+    info!("Status notice posted by {}: [{}] {}", created_by, kind, message);
+    refresh().await
+}
+
+/// Take a notice down and record when it was resolved
+pub async fn resolve(id: RecordId) -> Result<()> {
+    primary()
+        .query("UPDATE $id SET active = false, resolved_at = time::now() WHERE active = true")
+        .bind(("id", id))
+        .await?
+        .check()?;
+    refresh().await
+}
@@ -0,0 +1,305 @@
+//! Multi-tenancy
+//!
+//! With `MULTI_TENANT` on, an install can host private instances (a film
+//! school, a studio) next to the main site. Each tenant gets:
+//!
+//! - its own SurrealDB namespace/database, chosen by the request's hostname.
+//!   The tenant middleware puts the request in the tenant's scope, so `DB`,
+//!   `reader()` and `db::spawn` all resolve to it without call sites knowing.
+//! - its own S3 prefix, `tenants/<slug>/`, added and stripped by `S3Service`.
+//! - its own admins: the tenant's `person.is_admin` flags. Install-wide pages
+//!   (tenants, config, tasks) only answer on the primary hostname.
+//!
+//! The registry lives in the `tenant` table of the primary database, keyed by
+//! slug, and is edited from `/admin/tenants`. It is cached for a short time;
+//! changes call `invalidate()`. With `MULTI_TENANT` off every request is served
+//! from the primary database and the registry is never read.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+use std::time::{Duration, Instant};
+use surrealdb::types::SurrealValue;
+use tracing::{info, warn};
+
+use crate::config::multi_tenancy;
+use crate::db::{self, TenantScope, primary};
+use crate::error::{Error, Result};
+
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Prefix under which tenants' S3 objects live
+pub const S3_PREFIX: &str = "tenants/";
+
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+pub struct Tenant {
+    pub slug: String,
+    pub name: String,
+    /// Lowercase hostnames without ports
+    pub hostnames: Vec<String>,
+    pub namespace: String,
+    pub database: String,
+    pub active: bool,
+    pub created_at: String,
+}
+
+impl Tenant {
+    /// The S3 prefix for this tenant's objects
+    pub fn s3_prefix(&self) -> String {
+        s3_prefix(&self.slug)
+    }
+}
+
+pub fn s3_prefix(slug: &str) -> String {
+    format!("{}{}/", S3_PREFIX, slug)
+}
+
+/// Whether multi-tenant mode is on
+pub fn enabled() -> bool {
+    multi_tenancy().enabled
+}
+
+/// Lowercase a Host header value and drop the port and any trailing dot
+pub fn normalize_host(host: &str) -> String {
+    let host = host.trim().to_ascii_lowercase();
+    let host = if host.starts_with('[') {
+        // IPv6 literal: keep the brackets, drop the port
+        match host.find(']') {
+            Some(end) => host[..=end].to_string(),
+            None => host,
+        }
+    } else {
+        host.split(':').next().unwrap_or_default().to_string()
+    };
+    host.trim_end_matches('.').to_string()
+}
+
+/// Slugs name the tenant's namespace and S3 prefix: lowercase letters, digits
+/// and hyphens, starting with a letter.
+pub fn validate_slug(slug: &str) -> Result<()> {
+    let valid = slug.len() >= 2
+        && slug.len() <= 40
+        && slug.starts_with(|c: char| c.is_ascii_lowercase())
+        && !slug.ends_with('-')
+        && slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Validation(
+            "Tenant slugs are 2-40 lowercase letters, digits and hyphens, starting with a letter"
+                .to_string(),
+        ))
+    }
+}
+
+/// Parse a comma or whitespace separated hostname list
+pub fn parse_hostnames(input: &str) -> Vec<String> {
+    let mut hostnames: Vec<String> = input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .map(normalize_host)
+        .filter(|h| !h.is_empty())
+        .collect();
+    hostnames.sort();
+    hostnames.dedup();
+    hostnames
+}
+
+/// Namespace for a new tenant. Tenants share the primary's database name so
+/// the export/import tooling sees the same layout.
+pub fn namespace_for(slug: &str) -> String {
+    format!("tenant_{}", slug.replace('-', "_"))
+}
+
+type TenantMap = Arc<HashMap<String, Tenant>>;
+
+/// Active tenants by hostname
+static CACHE: LazyLock<RwLock<Option<(Instant, TenantMap)>>> = LazyLock::new(|| RwLock::new(None));
+
+/// The active tenant serving `host`, if any. Database errors are logged and
+/// treated as no tenant.
+pub async fn for_host(host: &str) -> Option<Tenant> {
+    if !enabled() {
+        return None;
+    }
+    let tenants = match load().await {
+        Ok(tenants) => tenants,
+        Err(e) => {
+            warn!("Failed to load tenants: {}", e);
+            return None;
+        }
+    };
+    tenants.get(&normalize_host(host)).cloned()
+}
+
+/// Connect to a tenant's database and return its scope
+pub async fn scope(tenant: &Tenant) -> Result<TenantScope> {
+    db::tenant_scope(&tenant.slug, &tenant.namespace, &tenant.database).await
+}
+
+/// Scopes for every active tenant, for background work that has to visit them
+/// all. Empty in single-tenant mode; tenants that can't connect are skipped.
+pub async fn active_scopes() -> Vec<TenantScope> {
+    if !enabled() {
+        return Vec::new();
+    }
+    let tenants = match list().await {
+        Ok(tenants) => tenants,
+        Err(e) => {
+            warn!("Failed to list tenants: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut scopes = Vec::new();
+    for tenant in tenants.iter().filter(|t| t.active) {
+        match scope(tenant).await {
+            Ok(scope) => scopes.push(scope),
+            Err(e) => warn!("Skipping tenant {}: {}", tenant.slug, e),
+        }
+    }
+    scopes
+}
+
+/// All tenants, sorted by slug, bypassing the cache
+pub async fn list() -> Result<Vec<Tenant>> {
+    let tenants: Vec<Tenant> = primary()
+        .query(
+            "SELECT <string> meta::id(id) AS slug, name, hostnames, namespace, database, active,
+                    <string> created_at AS created_at
+             FROM tenant ORDER BY slug",
+        )
+        .await?
+        .take(0)?;
+    Ok(tenants)
+}
+
+pub async fn get(slug: &str) -> Result<Tenant> {
+    list()
+        .await?
+        .into_iter()
+        .find(|t| t.slug == slug)
+        .ok_or(Error::NotFound)
+}
+
+/// Register a tenant and provision its database: the namespace and database
+/// are defined and `TENANT_SCHEMA_PATH` is run against them.
+pub async fn create(slug: &str, name: &str, hostnames: Vec<String>) -> Result<Tenant> {
+    validate_slug(slug)?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("Tenant name is required".to_string()));
+    }
+    check_hostnames(slug, &hostnames).await?;
+    if get(slug).await.is_ok() {
+        return Err(Error::Conflict(format!("Tenant '{}' already exists", slug)));
+    }
+
+    let namespace = namespace_for(slug);
+    let database = db::database_config()
+        .map(|c| c.name.clone())
+        .ok_or_else(|| Error::Database("Database is not connected".to_string()))?;
+
+    provision(slug, &namespace, &database).await?;
+
+    primary()
+        .query(
+            "CREATE type::record('tenant', $slug) SET name = $name, hostnames = $hostnames,
+                 namespace = $namespace, database = $database, active = true",
+        )
+        .bind(("slug", slug.to_string()))
+        .bind(("name", name.to_string()))
+        .bind(("hostnames", hostnames))
+        .bind(("namespace", namespace.clone()))
+        .bind(("database", database.clone()))
+        .await?
+        .check()?;
+    invalidate();
+
+    info!("Created tenant {} in {}/{}", slug, namespace, database);
+    get(slug).await
+}
+
+async fn provision(slug: &str, namespace: &str, database: &str) -> Result<()> {
+    let path = &multi_tenancy().schema_path;
+    let schema = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to read tenant schema {}: {}", path, e)))?;
+
+    let scope = db::tenant_scope(slug, namespace, database).await?;
+    db::with_tenant(scope, async {
+        db::DB
+            .query(format!(
+                "DEFINE NAMESPACE IF NOT EXISTS `{}`; DEFINE DATABASE IF NOT EXISTS `{}`;",
+                namespace, database
+            ))
+            .await?
+            .check()?;
+        db::DB.query(schema).await?.check()?;
+        Ok::<(), Error>(())
+    })
+    .await
+}
+
+pub async fn update(slug: &str, name: &str, hostnames: Vec<String>, active: bool) -> Result<()> {
+    get(slug).await?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(Error::Validation("Tenant name is required".to_string()));
+    }
+    check_hostnames(slug, &hostnames).await?;
+    primary()
+        .query(
+            "UPDATE type::record('tenant', $slug)
+             SET name = $name, hostnames = $hostnames, active = $active",
+        )
+        .bind(("slug", slug.to_string()))
+        .bind(("name", name.to_string()))
+        .bind(("hostnames", hostnames))
+        .bind(("active", active))
+        .await?
+        .check()?;
+    invalidate();
+    Ok(())
+}
+
+/// A hostname may only belong to one tenant
+async fn check_hostnames(slug: &str, hostnames: &[String]) -> Result<()> {
+    if hostnames.is_empty() {
+        return Err(Error::Validation("At least one hostname is required".to_string()));
+    }
+    for tenant in list().await? {
+        if tenant.slug == slug {
+            continue;
+        }
+        if let Some(taken) = hostnames.iter().find(|h| tenant.hostnames.contains(h)) {
+            return Err(Error::Conflict(format!(
+                "{} already belongs to tenant '{}'",
+                taken, tenant.slug
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Drop the cached registry so the next request reads the table again
+pub fn invalidate() {
+    *CACHE.write().unwrap() = None;
+}
+
+async fn load() -> Result<TenantMap> {
+    if let Some((loaded_at, tenants)) = CACHE.read().unwrap().as_ref()
+        && loaded_at.elapsed() < CACHE_TTL
+    {
+        return Ok(tenants.clone());
+    }
+
+    let mut by_host = HashMap::new();
+    for tenant in list().await?.into_iter().filter(|t| t.active) {
+        for host in &tenant.hostnames {
+            by_host.insert(host.clone(), tenant.clone());
+        }
+    }
+    let tenants = Arc::new(by_host);
+    *CACHE.write().unwrap() = Some((Instant::now(), tenants.clone()));
+    Ok(tenants)
+}
@@ -1,6 +1,5 @@
 use reqwest;
 use serde::{Deserialize, Serialize};
-use std::env;
 use std::sync::LazyLock;
 use thiserror::Error;
 use tracing::{debug, info, warn};
@@ -102,7 +101,7 @@ pub struct TmdbCredit {
 
 impl TmdbService {
     fn from_env() -> Result<Self> {
-        let api_key = env::var("TMDB_API_KEY")
+        let api_key = crate::settings::var("TMDB_API_KEY")
             .map_err(|_| TmdbError::NotConfigured)?;
 
         if api_key.is_empty() {
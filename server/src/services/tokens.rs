@@ -0,0 +1,33 @@
+//! Random tokens for links, secrets and one-time codes
+//!
+//! Every secret SlateHub hands out (share and guest links, badge and inbox
+//! addresses, OAuth codes and client secrets, webhook secrets) is an
+//! alphanumeric string from the thread-local CSPRNG. Tokens that grant access
+//! on their own are stored as `token_hash`, so a leaked database row doesn't
+//! leak the link.
+
+use rand::{Rng, distributions::Alphanumeric};
+use sha2::{Digest, Sha256};
+
+/// A random alphanumeric token of `len` characters
+pub fn random_token(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+/// Whether `token` could have come from `random_token(len)`. Lets lookups
+/// turn away junk before touching the database.
+pub fn is_token(token: &str, len: usize) -> bool {
+    token.len() == len && token.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// SHA-256 of a token as lowercase hex, for storing in place of the token
+pub fn token_hash(token: &str) -> String {
+    Sha256::digest(token.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -0,0 +1,639 @@
+//! Server-side video transcoding
+//!
+//! Uploaded videos arrive in whatever the camera or phone produced: a 4K
+//! HEVC `.mov` won't play in most browsers and is far too heavy to stream to
+//! a reviewer. After an upload, a feature queues a `transcode_job` for the
+//! stored file. A scheduled worker downloads it, hands it to the configured
+//! `Transcoder`, and stores the results next to the original:
+//!
+//! ```text
+//! private/selftapes/{request}/{person}-{ulid}.mov      original
+//! private/selftapes/{request}/{person}-{ulid}/web.mp4  H.264/AAC, faststart
+//! private/selftapes/{request}/{person}-{ulid}/poster.jpg
+//! private/selftapes/{request}/{person}-{ulid}/hls/index.m3u8 (+ segments)
+//! ```
+//!
+//! The job's status ("queued", "processing", "ready" or "failed") is what the
+//! UI shows while renditions are on their way. A failed video falls back to
+//! the original upload. Without ffmpeg configured nothing is queued.
+//!
+//! The same transcoder decodes audio uploads for their waveforms and reads
+//! the tags embedded in them (see `models::audio_reel`).
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::LazyLock;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, error, info, warn};
+
+use crate::{config, db::DB, error::Error, record_id_ext::RecordIdExt, services::s3::s3};
+
+/// Tries before a job is marked failed
+pub const MAX_ATTEMPTS: i64 = 3;
+
+/// Jobs one scheduled run works through
+const JOBS_PER_RUN: usize = 5;
+
+/// Height the web rendition is scaled down to
+const MAX_HEIGHT: u32 = 720;
+
+/// Target HLS segment length in seconds
+const SEGMENT_SECONDS: u32 = 6;
+
+const MP4_NAME: &str = "web.mp4";
+const POSTER_NAME: &str = "poster.jpg";
+const HLS_DIR: &str = "hls";
+const PLAYLIST_NAME: &str = "index.m3u8";
+
+pub const HLS_CONTENT_TYPE: &str = "application/vnd.apple.mpegurl";
+
+// ---------------------------------------------------------------------------
+// Renditions and storage keys
+// ---------------------------------------------------------------------------
+
+/// Files a transcoder produced in its output directory
+#[derive(Debug, Clone)]
+pub struct Renditions {
+    pub mp4: PathBuf,
+    pub poster: PathBuf,
+    pub playlist: PathBuf,
+    pub segments: Vec<PathBuf>,
+}
+
+/// Where the renditions of a stored video go: a folder named after the
+/// original, without its extension
+pub fn rendition_prefix(source_key: &str) -> String {
+    let (dir, file) = source_key.rsplit_once('/').unwrap_or(("", source_key));
+    let stem = file.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(file);
+    if dir.is_empty() {
+        format!("{}/", stem)
+    } else {
+        format!("{}/{}/", dir, stem)
+    }
+}
+
+/// Content type to store a rendition file with
+pub fn rendition_content_type(name: &str) -> &'static str {
+    match name.rsplit_once('.').map(|(_, ext)| ext) {
+        Some("mp4") => "video/mp4",
+        Some("jpg") => "image/jpeg",
+        Some("m3u8") => HLS_CONTENT_TYPE,
+        Some("ts") => "video/mp2t",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The segment files a playlist refers to, in order
+pub fn playlist_segments(playlist: &str) -> Vec<&str> {
+    playlist
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Replace each segment in a playlist with a URL, in order. Renditions are
+/// private, so the playlist that is served points at presigned URLs.
+pub fn rewrite_playlist(playlist: &str, urls: &[String]) -> String {
+    let mut urls = urls.iter();
+    let mut out = String::with_capacity(playlist.len());
+    for line in playlist.lines() {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && !trimmed.starts_with('#') {
+            out.push_str(urls.next().map(String::as_str).unwrap_or(trimmed));
+        } else {
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// ---------------------------------------------------------------------------
+// Transcoders
+// ---------------------------------------------------------------------------
+
+/// Something that turns uploaded media into web renditions
+#[async_trait]
+pub trait Transcoder: Send + Sync {
+    /// Transcode `input`, writing the renditions into `output_dir`
+    async fn transcode(&self, input: &Path, output_dir: &Path) -> Result<Renditions, Error>;
+
+    /// Decode the audio of `input` to mono 16-bit samples at `sample_rate`
+    async fn audio_samples(&self, input: &Path, sample_rate: u32) -> Result<Vec<i16>, Error>;
+
+    /// Metadata tags embedded in `input` (title, artist, genre, ...), with
+    /// lowercase names
+    async fn tags(&self, input: &Path) -> Result<Vec<(String, String)>, Error>;
+}
+
+fn path_arg(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn scale_filter() -> String {
+    format!("scale=-2:'min({},ih)'", MAX_HEIGHT)
+}
+
+/// ffmpeg arguments for the web MP4: H.264 and AAC, at most 720p, with the
+/// index up front so playback starts before the download finishes. Keyframes
+/// every segment length let the HLS rendition be cut from it without
+/// re-encoding.
+pub fn mp4_args(input: &Path, output: &Path) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-nostdin", "-y", "-i"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    args.push(path_arg(input));
+    args.extend(
+        [
+            "-map", "0:v:0", "-map", "0:a:0?",
+            "-c:v", "libx264", "-preset", "veryfast", "-crf", "23",
+            "-profile:v", "high", "-pix_fmt", "yuv420p",
+        ]
+        .iter()
+        .map(|s| s.to_string()),
+    );
+    args.push("-vf".to_string());
+    args.push(scale_filter());
+    args.push("-force_key_frames".to_string());
+    args.push(format!("expr:gte(t,n_forced*{})", SEGMENT_SECONDS));
+    args.extend(
+        ["-c:a", "aac", "-b:a", "128k", "-ac", "2", "-movflags", "+faststart"]
+            .iter()
+            .map(|s| s.to_string()),
+    );
+    args.push(path_arg(output));
+    args
+}
+
+/// ffmpeg arguments to cut a VOD HLS rendition from the web MP4
+pub fn hls_args(mp4: &Path, playlist: &Path, segment_pattern: &Path) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-nostdin", "-y", "-i"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    args.push(path_arg(mp4));
+    args.extend(
+        ["-c", "copy", "-f", "hls", "-hls_playlist_type", "vod", "-hls_time"]
+            .iter()
+            .map(|s| s.to_string()),
+    );
+    args.push(SEGMENT_SECONDS.to_string());
+    args.push("-hls_segment_filename".to_string());
+    args.push(path_arg(segment_pattern));
+    args.push(path_arg(playlist));
+    args
+}
+
+/// ffmpeg arguments for the poster: a representative frame from the start of
+/// the video, which skips the black frames most tapes open with
+pub fn poster_args(mp4: &Path, output: &Path) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-nostdin", "-y", "-i"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    args.push(path_arg(mp4));
+    args.push("-vf".to_string());
+    args.push(format!("thumbnail,{}", scale_filter()));
+    args.extend(["-frames:v", "1", "-q:v", "3"].iter().map(|s| s.to_string()));
+    args.push(path_arg(output));
+    args
+}
+
+/// ffmpeg arguments to decode audio to raw mono 16-bit little-endian samples
+/// on stdout
+pub fn audio_samples_args(input: &Path, sample_rate: u32) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-nostdin", "-i"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    args.push(path_arg(input));
+    args.extend(["-vn", "-ac", "1", "-ar"].iter().map(|s| s.to_string()));
+    args.push(sample_rate.to_string());
+    args.extend(["-f", "s16le", "-"].iter().map(|s| s.to_string()));
+    args
+}
+
+/// ffmpeg arguments to write a file's tags to stdout in ffmetadata format
+pub fn tags_args(input: &Path) -> Vec<String> {
+    let mut args: Vec<String> = ["-hide_banner", "-nostdin", "-i"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    args.push(path_arg(input));
+    args.extend(["-f", "ffmetadata", "-"].iter().map(|s| s.to_string()));
+    args
+}
+
+/// The global tags in ffmetadata output: `key=value` lines before the first
+/// `[STREAM]` or `[CHAPTER]` section, with `\` escapes undone. Empty values
+/// are left out.
+pub fn parse_ffmetadata(text: &str) -> Vec<(String, String)> {
+    let mut tags = Vec::new();
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        if line.starts_with('[') {
+            break;
+        }
+        if line.starts_with(';') || line.is_empty() {
+            continue;
+        }
+        // A trailing backslash continues the value on the next line
+        let mut line = line.to_string();
+        while line.ends_with('\\') && !line.ends_with("\\\\") {
+            line.pop();
+            line.push('\n');
+            match lines.next() {
+                Some(next) => line.push_str(next),
+                None => break,
+            }
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let mut unescaped = String::with_capacity(value.len());
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                if let Some(next) = chars.next() {
+                    unescaped.push(next);
+                }
+            } else {
+                unescaped.push(c);
+            }
+        }
+        let value = unescaped.trim();
+        if !value.is_empty() {
+            tags.push((key.trim().to_lowercase(), value.to_string()));
+        }
+    }
+    tags
+}
+
+/// 16-bit little-endian samples from raw PCM bytes
+pub fn pcm_samples(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}
+
+/// Transcodes with a local ffmpeg binary
+pub struct FfmpegTranscoder {
+    pub ffmpeg_path: String,
+    pub timeout: std::time::Duration,
+}
+
+impl FfmpegTranscoder {
+    async fn run(&self, args: Vec<String>) -> Result<(), Error> {
+        self.output(args, false).await.map(|_| ())
+    }
+
+    /// Run ffmpeg, returning what it wrote to stdout when `capture` is set
+    async fn output(&self, args: Vec<String>, capture: bool) -> Result<Vec<u8>, Error> {
+        debug!("Running {} {}", self.ffmpeg_path, args.join(" "));
+        let child = tokio::process::Command::new(&self.ffmpeg_path)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(if capture { Stdio::piped() } else { Stdio::null() })
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| Error::ExternalService(format!("Failed to start ffmpeg: {}", e)))?;
+
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| Error::ExternalService("ffmpeg timed out".to_string()))?
+            .map_err(|e| Error::ExternalService(format!("ffmpeg failed: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let tail: Vec<&str> = stderr.lines().rev().take(3).collect();
+            return Err(Error::ExternalService(format!(
+                "ffmpeg exited with {}: {}",
+                output.status,
+                tail.into_iter().rev().collect::<Vec<_>>().join(" / ")
+            )));
+        }
+        Ok(output.stdout)
+    }
+}
+
+#[async_trait]
+impl Transcoder for FfmpegTranscoder {
+    async fn transcode(&self, input: &Path, output_dir: &Path) -> Result<Renditions, Error> {
+        let mp4 = output_dir.join(MP4_NAME);
+        let poster = output_dir.join(POSTER_NAME);
+        let hls_dir = output_dir.join(HLS_DIR);
+        let playlist = hls_dir.join(PLAYLIST_NAME);
+        tokio::fs::create_dir_all(&hls_dir)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to create {}: {}", hls_dir.display(), e)))?;
+
+        self.run(mp4_args(input, &mp4)).await?;
+        self.run(poster_args(&mp4, &poster)).await?;
+        self.run(hls_args(&mp4, &playlist, &hls_dir.join("segment_%04d.ts"))).await?;
+
+        let text = tokio::fs::read_to_string(&playlist)
+            .await
+            .map_err(|e| Error::Internal(format!("Failed to read HLS playlist: {}", e)))?;
+        let segments = playlist_segments(&text).into_iter().map(|s| hls_dir.join(s)).collect();
+
+        Ok(Renditions { mp4, poster, playlist, segments })
+    }
+
+    async fn audio_samples(&self, input: &Path, sample_rate: u32) -> Result<Vec<i16>, Error> {
+        let bytes = self.output(audio_samples_args(input, sample_rate), true).await?;
+        Ok(pcm_samples(&bytes))
+    }
+
+    async fn tags(&self, input: &Path) -> Result<Vec<(String, String)>, Error> {
+        let bytes = self.output(tags_args(input), true).await?;
+        Ok(parse_ffmetadata(&String::from_utf8_lossy(&bytes)))
+    }
+}
+
+static TRANSCODER: LazyLock<Option<Box<dyn Transcoder>>> = LazyLock::new(|| {
+    let settings = config::transcoding();
+    settings.ffmpeg_path.as_ref().map(|path| {
+        Box::new(FfmpegTranscoder {
+            ffmpeg_path: path.clone(),
+            timeout: std::time::Duration::from_secs(settings.timeout_secs),
+        }) as Box<dyn Transcoder>
+    })
+});
+
+/// The configured transcoder, if any
+pub fn transcoder() -> Option<&'static dyn Transcoder> {
+    TRANSCODER.as_deref()
+}
+
+// ---------------------------------------------------------------------------
+// Jobs
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct TranscodeJob {
+    pub id: RecordId,
+    pub owner: RecordId,
+    pub source_key: String,
+    /// "queued", "processing", "ready" or "failed"
+    pub status: String,
+    pub attempts: i64,
+    pub error: Option<String>,
+    pub mp4_key: Option<String>,
+    pub poster_key: Option<String>,
+    pub hls_key: Option<String>,
+    /// Every stored rendition file, for cleanup
+    pub files: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// What a page needs to play a video
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Rendition {
+    pub status: String,
+    pub mp4_key: Option<String>,
+    pub poster_key: Option<String>,
+    pub hls_key: Option<String>,
+}
+
+impl Rendition {
+    /// Still being transcoded: the UI shows "processing" instead of a player
+    pub fn is_pending(&self) -> bool {
+        self.status == "queued" || self.status == "processing"
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.status == "ready"
+    }
+}
+
+/// Status after a failed attempt: another try, or give up
+pub fn status_after_failure(attempts: i64) -> &'static str {
+    if attempts >= MAX_ATTEMPTS { "failed" } else { "queued" }
+}
+
+/// Queue a stored video for transcoding. Does nothing when no transcoder is
+/// configured, in which case the original is played as uploaded.
+pub async fn enqueue(owner: &RecordId, source_key: &str) -> Result<(), Error> {
+    if transcoder().is_none() {
+        return Ok(());
+    }
+    DB.query(
+        "CREATE transcode_job SET owner = $owner, source_key = $source_key, status = 'queued',
+            attempts = 0, files = [], created_at = time::now(), updated_at = time::now()",
+    )
+    .bind(("owner", owner.clone()))
+    .bind(("source_key", source_key.to_string()))
+    .await
+    .map_err(|e| Error::Database(format!("Failed to queue transcode: {}", e)))?
+    .check()?;
+    debug!("Queued transcode of {}", source_key);
+    Ok(())
+}
+
+/// The renditions of a stored video, if it was queued for transcoding
+pub async fn rendition(source_key: &str) -> Result<Option<Rendition>, Error> {
+    let rendition: Option<Rendition> = DB
+        .query(
+            "SELECT status, mp4_key, poster_key, hls_key FROM transcode_job
+             WHERE source_key = $source_key LIMIT 1",
+        )
+        .bind(("source_key", source_key.to_string()))
+        .await?
+        .take(0)?;
+    Ok(rendition)
+}
+
+/// A playlist for the HLS rendition that points at presigned segment URLs
+pub async fn signed_playlist(hls_key: &str) -> Result<String, Error> {
+    let s3 = s3()?;
+    let (data, _) = s3.download_file(hls_key).await?;
+    let text = String::from_utf8_lossy(&data).into_owned();
+    let dir = hls_key.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+
+    let mut urls = Vec::new();
+    for segment in playlist_segments(&text) {
+        urls.push(s3.generate_download_url(&format!("{}/{}", dir, segment)).await?);
+    }
+    Ok(rewrite_playlist(&text, &urls))
+}
+
+/// Take the next queued job, if another worker hasn't got to it first
+async fn claim_next() -> Result<Option<TranscodeJob>, Error> {
+    let next: Option<RecordId> = DB
+        .query("SELECT VALUE id FROM transcode_job WHERE status = 'queued' ORDER BY created_at ASC LIMIT 1")
+        .await?
+        .take(0)?;
+    let Some(id) = next else {
+        return Ok(None);
+    };
+    let job: Option<TranscodeJob> = DB
+        .query(
+            "UPDATE $id SET status = 'processing', attempts += 1, updated_at = time::now()
+             WHERE status = 'queued' RETURN AFTER",
+        )
+        .bind(("id", id))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to claim transcode: {}", e)))?
+        .take(0)?;
+    Ok(job)
+}
+
+/// Delete stored files, logging rather than failing when storage is unavailable
+async fn delete_files(keys: &[String]) {
+    let s3 = match s3() {
+        Ok(s3) => s3,
+        Err(e) => {
+            error!("S3 unavailable, {} renditions not deleted: {}", keys.len(), e);
+            return;
+        }
+    };
+    for key in keys {
+        if let Err(e) = s3.delete_file(key).await {
+            error!("Failed to delete rendition {}: {}", key, e);
+        }
+    }
+}
+
+/// Download, transcode and store one video's renditions
+async fn run_job(transcoder: &dyn Transcoder, job: &TranscodeJob, work_dir: &Path) -> Result<Vec<(String, PathBuf)>, Error> {
+    let extension = job.source_key.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("video");
+    let input = work_dir.join(format!("source.{}", extension));
+    let output_dir = work_dir.join("out");
+    tokio::fs::create_dir_all(&output_dir)
+        .await
+        .map_err(|e| Error::Internal(format!("Failed to create {}: {}", output_dir.display(), e)))?;
+
+    s3()?.download_to_path(&job.source_key, &input).await?;
+    let renditions = transcoder.transcode(&input, &output_dir).await?;
+
+    let prefix = rendition_prefix(&job.source_key);
+    let mut files = vec![
+        (format!("{}{}", prefix, MP4_NAME), renditions.mp4),
+        (format!("{}{}", prefix, POSTER_NAME), renditions.poster),
+    ];
+    for segment in renditions.segments {
+        let name = segment.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        files.push((format!("{}{}/{}", prefix, HLS_DIR, name), segment));
+    }
+    // The playlist goes last, so a stored playlist always has its segments
+    files.push((format!("{}{}/{}", prefix, HLS_DIR, PLAYLIST_NAME), renditions.playlist));
+
+    let s3 = s3()?;
+    for (key, path) in &files {
+        s3.upload_from_path(key, path, rendition_content_type(key)).await?;
+    }
+    Ok(files)
+}
+
+async fn process(transcoder: &dyn Transcoder, job: TranscodeJob) -> Result<(), Error> {
+    let work_dir = std::env::temp_dir().join(format!("slatehub-transcode-{}", job.id.key_string()));
+    let result = match tokio::fs::create_dir_all(&work_dir).await {
+        Ok(()) => run_job(transcoder, &job, &work_dir).await,
+        Err(e) => Err(Error::Internal(format!("Failed to create {}: {}", work_dir.display(), e))),
+    };
+    if let Err(e) = tokio::fs::remove_dir_all(&work_dir).await {
+        warn!("Failed to remove {}: {}", work_dir.display(), e);
+    }
+
+    match result {
+        Ok(files) => {
+            let keys: Vec<String> = files.into_iter().map(|(key, _)| key).collect();
+            let prefix = rendition_prefix(&job.source_key);
+            let updated: Option<TranscodeJob> = DB
+                .query(
+                    "UPDATE $id SET status = 'ready', error = NONE, mp4_key = $mp4_key,
+                        poster_key = $poster_key, hls_key = $hls_key, files = $files,
+                        updated_at = time::now() RETURN AFTER",
+                )
+                .bind(("id", job.id.clone()))
+                .bind(("mp4_key", format!("{}{}", prefix, MP4_NAME)))
+                .bind(("poster_key", format!("{}{}", prefix, POSTER_NAME)))
+                .bind(("hls_key", format!("{}{}/{}", prefix, HLS_DIR, PLAYLIST_NAME)))
+                .bind(("files", keys.clone()))
+                .await
+                .map_err(|e| Error::Database(format!("Failed to record renditions: {}", e)))?
+                .take(0)?;
+            if updated.is_none() {
+                // The video was deleted while it was being transcoded
+                delete_files(&keys).await;
+                return Ok(());
+            }
+            info!("Transcoded {} into {} files", job.source_key, keys.len());
+        }
+        Err(e) => {
+            let status = status_after_failure(job.attempts);
+            warn!("Transcode of {} failed (attempt {}): {}", job.source_key, job.attempts, e);
+            DB.query("UPDATE $id SET status = $status, error = $error, updated_at = time::now()")
+                .bind(("id", job.id.clone()))
+                .bind(("status", status.to_string()))
+                .bind(("error", e.to_string()))
+                .await
+                .map_err(|e| Error::Database(format!("Failed to record transcode failure: {}", e)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Work through queued videos. Runs on a schedule. Jobs left "processing" by
+/// a worker that died are retried once they are well past the time the three
+/// ffmpeg runs could take.
+pub async fn process_queue() -> Result<(), Error> {
+    let Some(transcoder) = transcoder() else {
+        return Ok(());
+    };
+
+    let stale_before = Utc::now() - Duration::seconds(config::transcoding().timeout_secs as i64 * 4);
+    DB.query(
+        "UPDATE transcode_job SET status = IF attempts >= $max_attempts THEN 'failed' ELSE 'queued' END,
+            updated_at = time::now()
+         WHERE status = 'processing' AND updated_at < $before",
+    )
+        .bind(("before", stale_before))
+        .bind(("max_attempts", MAX_ATTEMPTS))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to requeue stale transcodes: {}", e)))?;
+
+    for _ in 0..JOBS_PER_RUN {
+        let Some(job) = claim_next().await? else {
+            break;
+        };
+        process(transcoder, job).await?;
+    }
+    Ok(())
+}
+
+/// Delete a video's renditions and its job, when the original is deleted or
+/// replaced
+pub async fn discard(source_key: &str) -> Result<(), Error> {
+    let jobs: Vec<TranscodeJob> = DB
+        .query("DELETE transcode_job WHERE source_key = $source_key RETURN BEFORE")
+        .bind(("source_key", source_key.to_string()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to delete renditions: {}", e)))?
+        .take(0)?;
+    for job in &jobs {
+        delete_files(&job.files).await;
+    }
+    Ok(())
+}
+
+/// Delete the renditions of a person's videos, for account deletion
+pub async fn forget(person: &RecordId) -> Result<(), Error> {
+    let keys: Vec<String> = DB
+        .query("SELECT VALUE source_key FROM transcode_job WHERE owner = $owner")
+        .bind(("owner", person.clone()))
+        .await?
+        .take(0)?;
+    for key in &keys {
+        discard(key).await?;
+    }
+    Ok(())
+}
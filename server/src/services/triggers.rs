@@ -0,0 +1,591 @@
+//! Triggers for workflow tools (Zapier, Make, ...)
+//!
+//! Two ways to hear about new job postings and new applications, both over
+//! `/api/v1` with an OAuth access token:
+//!
+//! - Polling: `/triggers/jobs` and `/triggers/applications` return the newest
+//!   items first. Each carries an opaque `cursor`; passing the newest one back
+//!   as `?cursor=` returns only what came after it, oldest pages first, so a
+//!   burst of more than a page is never skipped. `/sample` variants return a
+//!   fixed item for setting up a workflow before anything real exists.
+//! - REST hooks: `/hooks` subscribes a target URL to `job.created` or
+//!   `application.created`. Each new item is POSTed there in the same shape
+//!   the polling endpoints use, signed with the subscription's secret in
+//!   `X-SlateHub-Signature`. A `410 Gone` reply unsubscribes.
+//!
+//! Applications only go to people who manage the posting: the person who
+//! posted it, or owners and admins of the posting organization.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::time::Duration;
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{info, warn};
+
+use crate::config::app_url;
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::record_id_ext::RecordIdExt;
+use crate::services::id_verification::hmac_sha256;
+use crate::services::tokens::random_token;
+
+/// Items returned per poll
+pub const PAGE_SIZE: usize = 50;
+/// Subscriptions one app may hold for one person
+pub const MAX_HOOKS: usize = 20;
+
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events apps can subscribe to, with the scope each needs
+pub const EVENTS: &[(&str, &str)] = &[
+    ("job.created", "read:jobs"),
+    ("application.created", "read:applications"),
+];
+
+pub fn event_scope(event: &str) -> Option<&'static str> {
+    EVENTS
+        .iter()
+        .find(|(name, _)| *name == event)
+        .map(|(_, scope)| *scope)
+}
+
+// ============================
+// Cursors
+// ============================
+
+/// Position of an item in a trigger's feed: when it was created, then its
+/// record key to break ties
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    pub at: DateTime<Utc>,
+    pub key: String,
+}
+
+impl Cursor {
+    pub fn new(at: DateTime<Utc>, key: impl Into<String>) -> Self {
+        Self {
+            at,
+            key: key.into(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!(
+            "{}|{}",
+            self.at.to_rfc3339_opts(SecondsFormat::Nanos, true),
+            self.key
+        ))
+    }
+
+    pub fn decode(value: &str) -> Option<Self> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(value.trim()).ok()?).ok()?;
+        let (at, key) = raw.split_once('|')?;
+        let valid_key =
+            !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_key {
+            return None;
+        }
+        let at = DateTime::parse_from_rfc3339(at).ok()?.with_timezone(&Utc);
+        Some(Self::new(at, key))
+    }
+}
+
+/// Order and filter for a page of a feed. Without a cursor it's the newest
+/// page; with one it's the oldest page after it, reversed by the caller.
+fn page_clause(cursor: Option<&Cursor>, at_field: &str) -> String {
+    match cursor {
+        None => format!("ORDER BY {} DESC, id DESC LIMIT {}", at_field, PAGE_SIZE),
+        Some(_) => format!(
+            "AND ({at} > $after OR ({at} = $after AND id > $after_id)) ORDER BY {at} ASC, id ASC LIMIT {limit}",
+            at = at_field,
+            limit = PAGE_SIZE
+        ),
+    }
+}
+
+// ============================
+// Payloads
+// ============================
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct TriggerRole {
+    pub title: String,
+    pub description: Option<String>,
+    pub rate_type: String,
+    pub rate_amount: Option<String>,
+    pub location_override: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobTriggerItem {
+    pub id: String,
+    pub cursor: String,
+    pub title: String,
+    pub description: String,
+    pub location: Option<String>,
+    pub poster: String,
+    pub roles: Vec<TriggerRole>,
+    pub url: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplicationTriggerItem {
+    pub id: String,
+    pub cursor: String,
+    pub job_id: String,
+    pub job_title: String,
+    pub job_url: String,
+    pub role_title: String,
+    pub applicant_name: String,
+    pub applicant_username: String,
+    pub applicant_url: String,
+    pub cover_letter: Option<String>,
+    pub status: String,
+    pub applied_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+struct JobRow {
+    id: RecordId,
+    title: String,
+    description: String,
+    location: Option<String>,
+    poster: Option<String>,
+    roles: Vec<TriggerRole>,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl From<JobRow> for JobTriggerItem {
+    fn from(row: JobRow) -> Self {
+        let key = row.id.key_string();
+        Self {
+            cursor: Cursor::new(row.created_at, key.clone()).encode(),
+            url: format!("{}/jobs/{}", app_url(), key),
+            id: key,
+            title: row.title,
+            description: row.description,
+            location: row.location,
+            poster: row.poster.unwrap_or_default(),
+            roles: row.roles,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+struct ApplicationRow {
+    id: RecordId,
+    job: RecordId,
+    job_title: String,
+    role_title: String,
+    applicant_name: Option<String>,
+    applicant_username: String,
+    cover_letter: Option<String>,
+    status: String,
+    applied_at: DateTime<Utc>,
+}
+
+impl From<ApplicationRow> for ApplicationTriggerItem {
+    fn from(row: ApplicationRow) -> Self {
+        let key = row.id.key_string();
+        let job_id = row.job.key_string();
+        Self {
+            cursor: Cursor::new(row.applied_at, key.clone()).encode(),
+            id: key,
+            job_url: format!("{}/jobs/{}", app_url(), job_id),
+            job_id,
+            job_title: row.job_title,
+            role_title: row.role_title,
+            applicant_name: row
+                .applicant_name
+                .unwrap_or_else(|| row.applicant_username.clone()),
+            applicant_url: format!("{}/{}", app_url(), row.applicant_username),
+            applicant_username: row.applicant_username,
+            cover_letter: row.cover_letter,
+            status: row.status,
+            applied_at: row.applied_at,
+        }
+    }
+}
+
+const JOB_FIELDS: &str = "id, title, description, location,
+    posted_by.name ?? posted_by.username AS poster, roles, created_at, expires_at";
+
+const APPLICATION_FIELDS: &str = "id, out AS job, out.title AS job_title, role_title,
+    in.name AS applicant_name, in.username AS applicant_username, cover_letter, status,
+    applied_at";
+
+/// Postings the person manages, as a `$managed` variable for the queries
+/// below
+const MANAGED_POSTERS: &str = "LET $managed = array::concat([$person],
+    (SELECT VALUE out FROM member_of WHERE in = $person
+        AND role IN ['owner', 'admin'] AND invitation_status = 'accepted'));";
+
+fn sample_time() -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339("2026-03-02T09:30:00Z")
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
+/// A made-up posting for setting up a workflow
+pub fn sample_job() -> JobTriggerItem {
+    let at = sample_time();
+    JobTriggerItem {
+        id: "sample".to_string(),
+        cursor: Cursor::new(at, "sample").encode(),
+        title: "Short film crew, 5-day shoot".to_string(),
+        description: "Indie drama shooting in Brooklyn. Meals and travel covered.".to_string(),
+        location: Some("Brooklyn, NY".to_string()),
+        poster: "Northlight Pictures".to_string(),
+        roles: vec![TriggerRole {
+            title: "1st AC".to_string(),
+            description: Some("Own follow focus a plus".to_string()),
+            rate_type: "Daily".to_string(),
+            rate_amount: Some("450".to_string()),
+            location_override: None,
+        }],
+        url: format!("{}/jobs/sample", app_url()),
+        created_at: at,
+        expires_at: at + chrono::Duration::days(30),
+    }
+}
+
+/// A made-up application for setting up a workflow
+pub fn sample_application() -> ApplicationTriggerItem {
+    let at = sample_time();
+    ApplicationTriggerItem {
+        id: "sample".to_string(),
+        cursor: Cursor::new(at, "sample").encode(),
+        job_id: "sample".to_string(),
+        job_title: "Short film crew, 5-day shoot".to_string(),
+        job_url: format!("{}/jobs/sample", app_url()),
+        role_title: "1st AC".to_string(),
+        applicant_name: "Ana Ruiz".to_string(),
+        applicant_username: "ana".to_string(),
+        applicant_url: format!("{}/ana", app_url()),
+        cover_letter: Some(
+            "Pulled focus on two features last year; reel on my profile.".to_string(),
+        ),
+        status: "submitted".to_string(),
+        applied_at: at,
+    }
+}
+
+// ============================
+// Polling
+// ============================
+
+/// Open postings, newest first, or those after `cursor`
+pub async fn jobs_since(cursor: Option<&Cursor>) -> Result<Vec<JobTriggerItem>> {
+    let mut rows: Vec<JobRow> = DB
+        .query(format!(
+            "SELECT {} FROM job_posting WHERE status = 'open' {}",
+            JOB_FIELDS,
+            page_clause(cursor, "created_at")
+        ))
+        .bind(("after", cursor.map(|c| c.at)))
+        .bind((
+            "after_id",
+            cursor.map(|c| RecordId::new("job_posting", c.key.as_str())),
+        ))
+        .await?
+        .take(0)?;
+    if cursor.is_some() {
+        rows.reverse();
+    }
+    Ok(rows.into_iter().map(JobTriggerItem::from).collect())
+}
+
+/// Applications to postings the person manages, newest first, or those
+/// after `cursor`
+pub async fn applications_since(
+    person: &RecordId,
+    cursor: Option<&Cursor>,
+) -> Result<Vec<ApplicationTriggerItem>> {
+    let mut rows: Vec<ApplicationRow> = DB
+        .query(format!(
+            "{} SELECT {} FROM application WHERE out.posted_by IN $managed {}",
+            MANAGED_POSTERS,
+            APPLICATION_FIELDS,
+            page_clause(cursor, "applied_at")
+        ))
+        .bind(("person", person.clone()))
+        .bind(("after", cursor.map(|c| c.at)))
+        .bind((
+            "after_id",
+            cursor.map(|c| RecordId::new("application", c.key.as_str())),
+        ))
+        .await?
+        .take(1)?;
+    if cursor.is_some() {
+        rows.reverse();
+    }
+    Ok(rows.into_iter().map(ApplicationTriggerItem::from).collect())
+}
+
+// ============================
+// REST hooks
+// ============================
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct Subscription {
+    pub id: RecordId,
+    pub event: String,
+    pub target_url: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, SurrealValue)]
+struct Delivery {
+    id: RecordId,
+    target_url: String,
+    secret: String,
+}
+
+/// Whether a hook target is acceptable: HTTPS to a public host, so hooks
+/// can't be pointed at the server's own network
+pub fn valid_target_url(url: &str) -> bool {
+    if url.chars().any(char::is_whitespace) {
+        return false;
+    }
+    let Some(rest) = url.strip_prefix("https://") else {
+        return false;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    }
+    .to_ascii_lowercase();
+    if host.is_empty() || host == "localhost" || host.ends_with(".localhost") {
+        return false;
+    }
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            !(ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified())
+        }
+        Ok(IpAddr::V6(ip)) => !(ip.is_loopback() || ip.is_unspecified()),
+        Err(_) => true,
+    }
+}
+
+/// `X-SlateHub-Signature` for a delivery body
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let hex: String = hmac_sha256(secret.as_bytes(), body)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+pub async fn subscriptions(person: &RecordId, client: &RecordId) -> Result<Vec<Subscription>> {
+    Ok(DB
+        .query(
+            "SELECT id, event, target_url, created_at FROM webhook_subscription
+             WHERE person = $person AND client = $client ORDER BY created_at ASC",
+        )
+        .bind(("person", person.clone()))
+        .bind(("client", client.clone()))
+        .await?
+        .take(0)?)
+}
+
+/// Subscribe a target URL to an event, returning the subscription and the
+/// secret its deliveries are signed with
+pub async fn subscribe(
+    person: &RecordId,
+    client: &RecordId,
+    event: &str,
+    target_url: &str,
+) -> Result<(Subscription, String)> {
+    if event_scope(event).is_none() {
+        return Err(Error::Validation(format!("Unknown event \"{}\"", event)));
+    }
+    let target_url = target_url.trim();
+    if !valid_target_url(target_url) {
+        return Err(Error::Validation(
+            "target_url must be an https:// URL on a public host".to_string(),
+        ));
+    }
+    if subscriptions(person, client).await?.len() >= MAX_HOOKS {
+        return Err(Error::Conflict(format!(
+            "An app can hold up to {} subscriptions",
+            MAX_HOOKS
+        )));
+    }
+    let secret = random_token(40);
+    let created: Option<Subscription> = DB
+        .query(
+            "CREATE ONLY webhook_subscription SET person = $person, client = $client,
+                event = $event, target_url = $target_url, secret = $secret
+             RETURN id, event, target_url, created_at",
+        )
+        .bind(("person", person.clone()))
+        .bind(("client", client.clone()))
+        .bind(("event", event.to_string()))
+        .bind(("target_url", target_url.to_string()))
+        .bind(("secret", secret.clone()))
+        .await?
+        .take(0)?;
+    let subscription =
+        created.ok_or_else(|| Error::Internal("Failed to create subscription".to_string()))?;
+    info!(
+        "{} subscribed {} to {} for {}",
+        client.display(),
+        subscription.id.display(),
+        event,
+        person.display()
+    );
+    Ok((subscription, secret))
+}
+
+pub async fn unsubscribe(person: &RecordId, client: &RecordId, id: &str) -> Result<()> {
+    let deleted: Vec<Subscription> = DB
+        .query(
+            "DELETE webhook_subscription WHERE id = $id AND person = $person AND client = $client
+             RETURN BEFORE",
+        )
+        .bind(("id", RecordId::new("webhook_subscription", id)))
+        .bind(("person", person.clone()))
+        .bind(("client", client.clone()))
+        .await?
+        .take(0)?;
+    if deleted.is_empty() {
+        return Err(Error::NotFound);
+    }
+    Ok(())
+}
+
+async fn deliver<T: Serialize>(event: &str, hooks: Vec<Delivery>, item: &T) {
+    let body = match serde_json::to_vec(item) {
+        Ok(body) => body,
+        Err(e) => {
+            warn!("Failed to encode {} delivery: {}", event, e);
+            return;
+        }
+    };
+    let client = match reqwest::Client::builder().timeout(DELIVERY_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            warn!("Failed to build webhook client: {}", e);
+            return;
+        }
+    };
+    for hook in hooks {
+        let sent = client
+            .post(&hook.target_url)
+            .header("Content-Type", "application/json")
+            .header("X-SlateHub-Event", event)
+            .header("X-SlateHub-Signature", signature(&hook.secret, &body))
+            .body(body.clone())
+            .send()
+            .await;
+        match sent {
+            Ok(response) if response.status() == reqwest::StatusCode::GONE => {
+                info!("{} answered 410, unsubscribing", hook.id.display());
+                if let Err(e) = DB.query("DELETE $id").bind(("id", hook.id.clone())).await {
+                    warn!("Failed to remove {}: {}", hook.id.display(), e);
+                }
+            }
+            Ok(response) if !response.status().is_success() => {
+                warn!(
+                    "{} delivery to {} failed with {}",
+                    event,
+                    hook.id.display(),
+                    response.status()
+                );
+            }
+            Ok(_) => {}
+            Err(e) => warn!("{} delivery to {} failed: {}", event, hook.id.display(), e),
+        }
+    }
+}
+
+/// Tell `job.created` subscribers about a new posting, in the background
+pub fn job_created(key: &str) {
+    let id = RecordId::new("job_posting", key);
+    crate::db::spawn(async move {
+        let result: Result<()> = async {
+            let row: Option<JobRow> = DB
+                .query(format!("SELECT {} FROM ONLY $id", JOB_FIELDS))
+                .bind(("id", id))
+                .await?
+                .take(0)?;
+            let Some(row) = row else { return Ok(()) };
+            let hooks: Vec<Delivery> = DB
+                .query(
+                    "SELECT id, target_url, secret FROM webhook_subscription
+                     WHERE event = 'job.created'",
+                )
+                .await?
+                .take(0)?;
+            if !hooks.is_empty() {
+                deliver("job.created", hooks, &JobTriggerItem::from(row)).await;
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            warn!("job.created deliveries failed: {}", e);
+        }
+    });
+}
+
+/// Tell `application.created` subscribers who manage the posting about a
+/// new application, in the background
+pub fn application_created(person_id: &str, job_id: &str, role_title: &str) {
+    let (Ok(person), Ok(job)) = (
+        RecordId::parse_simple(person_id),
+        RecordId::parse_simple(job_id),
+    ) else {
+        return;
+    };
+    let role_title = role_title.to_string();
+    crate::db::spawn(async move {
+        let result: Result<()> = async {
+            let mut response = DB
+                .query(format!(
+                    "SELECT {} FROM application
+                        WHERE in = $person AND out = $job AND role_title = $role_title
+                        ORDER BY applied_at DESC LIMIT 1;
+                     SELECT id, target_url, secret FROM webhook_subscription
+                        WHERE event = 'application.created' AND (person = $job.posted_by
+                            OR person IN (SELECT VALUE in FROM member_of
+                                WHERE out = $job.posted_by AND role IN ['owner', 'admin']
+                                AND invitation_status = 'accepted'));",
+                    APPLICATION_FIELDS
+                ))
+                .bind(("person", person))
+                .bind(("job", job))
+                .bind(("role_title", role_title))
+                .await?;
+            let rows: Vec<ApplicationRow> = response.take(0)?;
+            let hooks: Vec<Delivery> = response.take(1)?;
+            if let Some(row) = rows.into_iter().next()
+                && !hooks.is_empty()
+            {
+                deliver(
+                    "application.created",
+                    hooks,
+                    &ApplicationTriggerItem::from(row),
+                )
+                .await;
+            }
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            warn!("application.created deliveries failed: {}", e);
+        }
+    });
+}
@@ -0,0 +1,278 @@
+//! Resumable uploads for large files
+//!
+//! A feature that takes big uploads (self-tapes, reels) starts an upload
+//! session for the storage key it wants. The browser then sends the file in
+//! fixed-size chunks, each one stored as a part of an S3 multipart upload.
+//! The session records which parts have arrived, so after a dropped
+//! connection the client asks for the current offset and carries on from
+//! there. The last chunk assembles the object, and the feature claims the
+//! finished session to attach the file.
+//!
+//! The protocol follows tus: `HEAD` reports `Upload-Offset`, and `PATCH` sends
+//! the chunk that starts at that offset.
+
+use bytes::Bytes;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, error, info};
+
+use crate::{db::DB, error::Error, record_id_ext::RecordIdExt, services::s3::s3};
+
+/// Bytes per chunk. S3 needs every part but the last to be at least 5 MB.
+pub const CHUNK_SIZE: i64 = 8 * 1024 * 1024;
+
+/// How long an unfinished or unclaimed upload is kept
+pub const SESSION_TTL_HOURS: i64 = 24;
+
+/// Most parts S3 allows in one multipart upload
+const MAX_PARTS: i64 = 10_000;
+
+/// One received chunk
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct UploadPart {
+    pub number: i64,
+    pub etag: String,
+    pub size: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct UploadSession {
+    pub id: RecordId,
+    pub owner: RecordId,
+    /// What the upload is for, e.g. "selftape". Only that feature can claim it.
+    pub purpose: String,
+    pub key: String,
+    pub multipart_id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: i64,
+    pub chunk_size: i64,
+    pub parts: Vec<UploadPart>,
+    /// "uploading" or "complete"
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl UploadSession {
+    /// Bytes received so far: the chunks in order from the start of the file
+    pub fn offset(&self) -> i64 {
+        received_offset(&self.parts)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.status == "complete"
+    }
+}
+
+/// Number of chunks a file of this size is sent in
+pub fn part_count(size: i64, chunk_size: i64) -> i64 {
+    (size + chunk_size - 1) / chunk_size
+}
+
+/// Offset just past the chunks received in sequence from the start of the file
+pub fn received_offset(parts: &[UploadPart]) -> i64 {
+    let mut offset = 0;
+    let mut number = 1;
+    while let Some(part) = parts.iter().find(|p| p.number == number) {
+        offset += part.size;
+        number += 1;
+    }
+    offset
+}
+
+/// The part number and length of the chunk that should start at `offset`
+pub fn expected_chunk(size: i64, chunk_size: i64, offset: i64) -> Result<(i64, i64), Error> {
+    if offset < 0 || offset >= size || offset % chunk_size != 0 {
+        return Err(Error::BadRequest("Upload-Offset isn't the start of a chunk".to_string()));
+    }
+    Ok((offset / chunk_size + 1, chunk_size.min(size - offset)))
+}
+
+/// Start an upload session for a file that will be stored at `key`
+pub async fn start(
+    owner: &RecordId,
+    purpose: &str,
+    key: &str,
+    filename: &str,
+    content_type: &str,
+    size: i64,
+) -> Result<UploadSession, Error> {
+    if size <= 0 {
+        return Err(Error::Validation("The file is empty".to_string()));
+    }
+    if part_count(size, CHUNK_SIZE) > MAX_PARTS {
+        return Err(Error::Validation("The file is too large".to_string()));
+    }
+
+    let multipart_id = s3()?.create_multipart_upload(key, content_type).await?;
+    debug!("Started upload session for {} ({} bytes)", key, size);
+
+    let session: Option<UploadSession> = DB
+        .query(
+            "CREATE upload_session SET owner = $owner, purpose = $purpose, key = $key,
+                multipart_id = $multipart_id, filename = $filename, content_type = $content_type,
+                size = $size, chunk_size = $chunk_size, parts = [], status = 'uploading',
+                created_at = time::now(), updated_at = time::now(), expires_at = $expires_at",
+        )
+        .bind(("owner", owner.clone()))
+        .bind(("purpose", purpose.to_string()))
+        .bind(("key", key.to_string()))
+        .bind(("multipart_id", multipart_id))
+        .bind(("filename", filename.to_string()))
+        .bind(("content_type", content_type.to_string()))
+        .bind(("size", size))
+        .bind(("chunk_size", CHUNK_SIZE))
+        .bind(("expires_at", Utc::now() + Duration::hours(SESSION_TTL_HOURS)))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to start upload: {}", e)))?
+        .take(0)?;
+    session.ok_or_else(|| Error::Internal("Failed to start upload".to_string()))
+}
+
+/// A session, if it belongs to the person and hasn't expired
+pub async fn get(session_id: &str, owner: &RecordId) -> Result<UploadSession, Error> {
+    let session: Option<UploadSession> = DB
+        .query("SELECT * FROM $id WHERE owner = $owner AND expires_at > time::now()")
+        .bind(("id", RecordId::new("upload_session", session_id)))
+        .bind(("owner", owner.clone()))
+        .await?
+        .take(0)?;
+    session.ok_or(Error::NotFound)
+}
+
+/// Store the chunk starting at `offset`. The offset has to match what the
+/// session has received, as with tus; a client that lost track asks for the
+/// offset again. The final chunk assembles the file.
+pub async fn receive_chunk(session: &UploadSession, offset: i64, data: Bytes) -> Result<UploadSession, Error> {
+    if session.is_complete() {
+        return Err(Error::Conflict("This upload is already complete".to_string()));
+    }
+    if offset != session.offset() {
+        return Err(Error::Conflict(format!(
+            "Expected the chunk at offset {}",
+            session.offset()
+        )));
+    }
+    let (number, length) = expected_chunk(session.size, session.chunk_size, offset)?;
+    if data.len() as i64 != length {
+        return Err(Error::BadRequest(format!("Expected a chunk of {} bytes", length)));
+    }
+
+    let etag = s3()?
+        .upload_part(&session.key, &session.multipart_id, number as i32, data)
+        .await?;
+    let part = UploadPart { number, etag, size: length };
+
+    let mut session: UploadSession = DB
+        .query(
+            "UPDATE $id SET parts = array::push(parts[WHERE number != $number], $part),
+                updated_at = time::now() RETURN AFTER",
+        )
+        .bind(("id", session.id.clone()))
+        .bind(("number", number))
+        .bind(("part", part))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to record upload chunk: {}", e)))?
+        .take::<Option<UploadSession>>(0)?
+        .ok_or(Error::NotFound)?;
+
+    if session.offset() == session.size {
+        let mut parts: Vec<(i32, String)> = session
+            .parts
+            .iter()
+            .map(|p| (p.number as i32, p.etag.clone()))
+            .collect();
+        parts.sort_by_key(|(number, _)| *number);
+        s3()?
+            .complete_multipart_upload(&session.key, &session.multipart_id, &parts)
+            .await?;
+        DB.query("UPDATE $id SET status = 'complete', updated_at = time::now()")
+            .bind(("id", session.id.clone()))
+            .await
+            .map_err(|e| Error::Database(format!("Failed to complete upload: {}", e)))?;
+        session.status = "complete".to_string();
+        info!("Upload {} complete: {} ({} bytes)", session.id.display(), session.key, session.size);
+    }
+    Ok(session)
+}
+
+/// Take a finished upload for a feature to attach. The session is used up,
+/// so the same file can't be claimed twice.
+pub async fn claim(session_id: &str, owner: &RecordId, purpose: &str) -> Result<UploadSession, Error> {
+    let session = get(session_id, owner).await?;
+    if session.purpose != purpose {
+        return Err(Error::NotFound);
+    }
+    if !session.is_complete() {
+        return Err(Error::Validation("The video didn't finish uploading. Please try again.".to_string()));
+    }
+    DB.query("DELETE $id")
+        .bind(("id", session.id.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to claim upload: {}", e)))?;
+    Ok(session)
+}
+
+/// Cancel an upload and throw away what was stored
+pub async fn abort(session: &UploadSession) -> Result<(), Error> {
+    discard(session).await;
+    DB.query("DELETE $id")
+        .bind(("id", session.id.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to cancel upload: {}", e)))?;
+    Ok(())
+}
+
+/// Free a session's storage: the parts of an unfinished upload, or the file
+/// of a finished one nobody claimed
+async fn discard(session: &UploadSession) {
+    let s3 = match s3() {
+        Ok(s3) => s3,
+        Err(e) => {
+            error!("S3 unavailable, upload {} not discarded: {}", session.key, e);
+            return;
+        }
+    };
+    let result = if session.is_complete() {
+        s3.delete_file(&session.key).await
+    } else {
+        s3.abort_multipart_upload(&session.key, &session.multipart_id).await
+    };
+    if let Err(e) = result {
+        error!("Failed to discard upload {}: {}", session.key, e);
+    }
+}
+
+/// Discard expired sessions. Runs on a schedule.
+pub async fn expire_stale() -> Result<(), Error> {
+    let sessions: Vec<UploadSession> = DB
+        .query("SELECT * FROM upload_session WHERE expires_at <= time::now()")
+        .await?
+        .take(0)?;
+    for session in &sessions {
+        discard(session).await;
+    }
+    if !sessions.is_empty() {
+        DB.query("DELETE upload_session WHERE expires_at <= time::now()")
+            .await
+            .map_err(|e| Error::Database(format!("Failed to delete expired uploads: {}", e)))?;
+        info!("Discarded {} expired uploads", sessions.len());
+    }
+    Ok(())
+}
+
+/// Cancel a person's uploads, for account deletion
+pub async fn forget(person: &RecordId) -> Result<(), Error> {
+    let sessions: Vec<UploadSession> = DB
+        .query("SELECT * FROM upload_session WHERE owner = $owner")
+        .bind(("owner", person.clone()))
+        .await?
+        .take(0)?;
+    for session in &sessions {
+        abort(session).await?;
+    }
+    Ok(())
+}
@@ -50,6 +50,7 @@ pub struct VerificationCode {
 pub enum CodeType {
     EmailVerification,
     PasswordReset,
+    LoginVerification,
 }
 
 impl std::fmt::Display for CodeType {
@@ -57,6 +58,7 @@ impl std::fmt::Display for CodeType {
         match self {
             CodeType::EmailVerification => write!(f, "email_verification"),
             CodeType::PasswordReset => write!(f, "password_reset"),
+            CodeType::LoginVerification => write!(f, "login_verification"),
         }
     }
 }
@@ -82,6 +84,7 @@ impl VerificationService {
         let expires_at = match code_type {
             CodeType::EmailVerification => Utc::now() + Duration::hours(24),
             CodeType::PasswordReset => Utc::now() + Duration::hours(1),
+            CodeType::LoginVerification => Utc::now() + Duration::minutes(15),
         };
 
         // Delete any existing unused codes of the same type for this user
@@ -0,0 +1,228 @@
+//! WhatsApp delivery
+//!
+//! People opt in from account settings with the number to message. Messages
+//! for them are queued in `whatsapp_message`; the WhatsApp bot (a linked
+//! device, see `whatsapp-bot/`) polls `/api/whatsapp/outbox` with
+//! `WHATSAPP_BOT_TOKEN`, sends each one and reports back. A message the bot
+//! claimed but never reported on is handed out again after a timeout, up to
+//! the configured number of attempts.
+
+use axum::http::{HeaderMap, header};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use surrealdb::types::{RecordId, SurrealValue};
+use tracing::{debug, info, warn};
+
+use crate::config;
+use crate::db::DB;
+use crate::error::{Error, Result};
+use crate::record_id_ext::RecordIdExt;
+
+/// Most messages handed to the bot in one poll
+pub const MAX_CLAIM: usize = 20;
+
+/// Longest error kept from the bot
+const MAX_ERROR_CHARS: usize = 500;
+
+/// A phone number as WhatsApp addresses it: country code and number, digits
+/// only. Accepts spaces, dashes, dots, brackets and a leading `+` or `00`.
+pub fn normalize_number(input: &str) -> Option<String> {
+    let trimmed = input.trim();
+    let rest = trimmed
+        .strip_prefix('+')
+        .or_else(|| trimmed.strip_prefix("00"))
+        .unwrap_or(trimmed);
+    let mut digits = String::new();
+    for c in rest.chars() {
+        match c {
+            '0'..='9' => digits.push(c),
+            ' ' | '-' | '.' | '(' | ')' => {}
+            _ => return None,
+        }
+    }
+    // A leading zero is a trunk prefix, so the country code is missing
+    if digits.starts_with('0') || !(8..=15).contains(&digits.len()) {
+        return None;
+    }
+    Some(digits)
+}
+
+/// A person's WhatsApp settings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WhatsAppPreference {
+    pub enabled: bool,
+    /// Digits only, with country code
+    pub number: Option<String>,
+}
+
+/// Whether the bot is set up, so there's any point asking people to opt in
+pub fn is_configured() -> bool {
+    config::whatsapp().bot_token.is_some()
+}
+
+/// Check the bot's bearer token
+pub fn verify_token(token: &str) -> bool {
+    let Some(expected) = config::whatsapp().bot_token.as_deref() else {
+        return false;
+    };
+    let (a, b) = (expected.as_bytes(), token.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Whether a request carries the bot's token
+pub fn bot_authorized(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .is_some_and(|token| verify_token(token.trim()))
+}
+
+#[derive(Debug, Deserialize, SurrealValue)]
+struct PreferenceRow {
+    whatsapp_enabled: Option<bool>,
+    whatsapp_number: Option<String>,
+}
+
+pub async fn get_preference(person: &RecordId) -> Result<WhatsAppPreference> {
+    let row: Option<PreferenceRow> = DB
+        .query("SELECT whatsapp_enabled, whatsapp_number FROM ONLY $person")
+        .bind(("person", person.clone()))
+        .await?
+        .take(0)?;
+    Ok(row
+        .map(|row| WhatsAppPreference {
+            enabled: row.whatsapp_enabled.unwrap_or(false),
+            number: row.whatsapp_number,
+        })
+        .unwrap_or_default())
+}
+
+/// Save a person's settings from the number as they typed it. Turning
+/// messages on needs a valid number; an empty number turns them off.
+pub async fn set_preference(person: &RecordId, enabled: bool, number: &str) -> Result<WhatsAppPreference> {
+    let number = if number.trim().is_empty() {
+        None
+    } else {
+        Some(normalize_number(number).ok_or_else(|| {
+            Error::Validation("Enter your WhatsApp number with its country code, like +1 555 123 4567".to_string())
+        })?)
+    };
+    if enabled && number.is_none() {
+        return Err(Error::Validation("Enter the number to send WhatsApp messages to".to_string()));
+    }
+    DB.query("UPDATE $person SET whatsapp_enabled = $enabled, whatsapp_number = $number")
+        .bind(("person", person.clone()))
+        .bind(("enabled", enabled))
+        .bind(("number", number.clone()))
+        .await?
+        .check()?;
+    info!(
+        "WhatsApp messages for {} {}",
+        person.display(),
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(WhatsAppPreference { enabled, number })
+}
+
+/// Queue a message for a person, if they've opted in and the bot is set up
+pub async fn queue(person: &RecordId, body: &str) -> Result<()> {
+    if !is_configured() {
+        return Ok(());
+    }
+    let pref = get_preference(person).await?;
+    let (true, Some(number)) = (pref.enabled, pref.number) else {
+        return Ok(());
+    };
+    DB.query("CREATE whatsapp_message SET person = $person, phone = $phone, body = $body, status = 'queued'")
+        .bind(("person", person.clone()))
+        .bind(("phone", number))
+        .bind(("body", body.to_string()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to queue WhatsApp message: {}", e)))?
+        .check()?;
+    debug!("Queued WhatsApp message for {}", person.display());
+    Ok(())
+}
+
+/// The person who opted in with this number, for commands sent to the bot
+pub async fn person_for_number(number: &str) -> Result<Option<RecordId>> {
+    let Some(number) = normalize_number(number) else {
+        return Ok(None);
+    };
+    let person: Option<RecordId> = DB
+        .query("SELECT VALUE id FROM person WHERE whatsapp_enabled = true AND whatsapp_number = $number LIMIT 1")
+        .bind(("number", number))
+        .await?
+        .take(0)?;
+    Ok(person)
+}
+
+/// A message handed to the bot
+#[derive(Debug, Clone, Serialize, Deserialize, SurrealValue)]
+pub struct OutboxMessage {
+    pub id: RecordId,
+    pub phone: String,
+    pub body: String,
+}
+
+/// Hand the bot the next queued messages, oldest first, along with any it
+/// claimed earlier without reporting back. Messages out of attempts fail.
+pub async fn claim() -> Result<Vec<OutboxMessage>> {
+    let settings = config::whatsapp();
+    let cutoff = Utc::now() - Duration::seconds(settings.claim_timeout_secs);
+    let mut response = DB
+        .query(
+            "UPDATE whatsapp_message SET status = 'failed', error = 'Not confirmed by the bot'
+                WHERE status = 'sending' AND claimed_at < $cutoff AND attempts >= $max_attempts;
+             LET $ids = SELECT VALUE id FROM whatsapp_message
+                WHERE status = 'queued' OR (status = 'sending' AND claimed_at < $cutoff)
+                ORDER BY created_at ASC LIMIT $limit;
+             UPDATE $ids SET status = 'sending', claimed_at = time::now(), attempts += 1 RETURN AFTER;",
+        )
+        .bind(("cutoff", cutoff))
+        .bind(("max_attempts", settings.max_attempts))
+        .bind(("limit", MAX_CLAIM as i64))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to claim WhatsApp messages: {}", e)))?;
+    let messages: Vec<OutboxMessage> = response.take(2)?;
+    if !messages.is_empty() {
+        debug!("Handed {} WhatsApp messages to the bot", messages.len());
+    }
+    Ok(messages)
+}
+
+/// Record what the bot did with a message. A failed send goes back in the
+/// queue until it runs out of attempts.
+pub async fn mark(message_id: &str, sent: bool, error: Option<&str>) -> Result<()> {
+    let id = RecordId::new("whatsapp_message", message_id);
+    let updated: Option<serde_json::Value> = if sent {
+        DB.query("UPDATE $id SET status = 'sent', sent_at = time::now(), error = NONE WHERE status = 'sending' RETURN id")
+            .bind(("id", id))
+            .await?
+            .take(0)?
+    } else {
+        let error: String = error.unwrap_or("Unknown error").chars().take(MAX_ERROR_CHARS).collect();
+        warn!("WhatsApp message {} failed: {}", message_id, error);
+        DB.query(
+            "UPDATE $id SET status = IF attempts >= $max_attempts THEN 'failed' ELSE 'queued' END,
+                error = $error
+             WHERE status = 'sending' RETURN id",
+        )
+        .bind(("id", id))
+        .bind(("max_attempts", config::whatsapp().max_attempts))
+        .bind(("error", error))
+        .await?
+        .take(0)?
+    };
+    updated.map(|_| ()).ok_or(Error::NotFound)
+}
+
+/// Delete a person's messages, for account deletion
+pub async fn forget(person: &RecordId) -> Result<()> {
+    DB.query("DELETE whatsapp_message WHERE person = $person")
+        .bind(("person", person.clone()))
+        .await
+        .map_err(|e| Error::Database(format!("Failed to delete WhatsApp messages: {}", e)))?;
+    Ok(())
+}
@@ -0,0 +1,451 @@
+//! Settings sources
+//!
+//! Every setting is looked up by its environment variable name with `settings::var`,
+//! which resolves, in order of precedence:
+//!
+//! 1. An override saved from `/admin/config` (the `config_override` table).
+//!    Overrides are read once at startup by `load_overrides()`, so they take
+//!    effect on the next restart, and only settings marked `overridable` accept one.
+//! 2. The process environment (including `.env`).
+//! 3. A TOML file, `slatehub.toml` in the working directory or the path in
+//!    `SLATEHUB_CONFIG`. Tables are flattened into variable names, so
+//!    `[search] vector_threshold = 0.7` sets `SEARCH_VECTOR_THRESHOLD`.
+//! 4. The default in the code that reads the setting.
+//!
+//! `SETTINGS` lists every known setting with its type; `validate()` checks the
+//! resolved values at startup so a typo fails the boot rather than silently
+//! falling back to a default.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::sync::{LazyLock, RwLock};
+use surrealdb::types::SurrealValue;
+use tracing::{info, warn};
+
+use crate::config::ConfigError;
+use crate::db::primary;
+use crate::error::{Error, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Text,
+    Int,
+    Float,
+    Bool,
+    Port,
+    Url,
+}
+
+impl Kind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Kind::Text => "text",
+            Kind::Int => "integer",
+            Kind::Float => "number",
+            Kind::Bool => "true/false",
+            Kind::Port => "port",
+            Kind::Url => "URL",
+        }
+    }
+
+    /// Check a raw value, returning a message suitable for the admin form.
+    pub fn check(&self, value: &str) -> std::result::Result<(), String> {
+        let value = value.trim();
+        let ok = match self {
+            Kind::Text => true,
+            Kind::Int => value.parse::<i64>().is_ok(),
+            Kind::Float => value.parse::<f64>().map(|v| v.is_finite()).unwrap_or(false),
+            Kind::Bool => matches!(value, "true" | "false" | "1" | "0"),
+            Kind::Port => value.parse::<u16>().is_ok(),
+            Kind::Url => value.starts_with("http://") || value.starts_with("https://"),
+        };
+        if ok {
+            Ok(())
+        } else {
+            Err(format!("must be a {}", self.label()))
+        }
+    }
+}
+
+/// A known setting
+#[derive(Debug, Clone, Copy)]
+pub struct Setting {
+    pub key: &'static str,
+    pub kind: Kind,
+    pub description: &'static str,
+    /// Never shown in full on `/admin/config`
+    pub secret: bool,
+    /// Can be changed from `/admin/config`
+    pub overridable: bool,
+}
+
+const fn setting(key: &'static str, kind: Kind, description: &'static str) -> Setting {
+    Setting { key, kind, description, secret: false, overridable: true }
+}
+
+/// Needed before the database is reachable, or too sensitive to store in it
+const fn boot(key: &'static str, kind: Kind, description: &'static str) -> Setting {
+    Setting { key, kind, description, secret: false, overridable: false }
+}
+
+const fn secret(key: &'static str, description: &'static str) -> Setting {
+    Setting { key, kind: Kind::Text, description, secret: true, overridable: false }
+}
+
+pub const SETTINGS: &[Setting] = &[
+    boot("SERVER_HOST", Kind::Text, "Address the server binds to"),
+    boot("SERVER_PORT", Kind::Port, "Port the server binds to"),
+    boot("APP_URL", Kind::Url, "Public URL used in emails and links"),
+    boot("COOKIE_SECURE", Kind::Bool, "Only send session cookies over HTTPS"),
+    boot("LOG_FORMAT", Kind::Text, "Log output: dev, pretty, compact or json"),
+    boot("COMPRESSION_MIN_BYTES", Kind::Int, "Smallest response compressed with brotli or gzip"),
+    boot("DB_HOST", Kind::Text, "SurrealDB host"),
+    boot("DB_PORT", Kind::Port, "SurrealDB port"),
+    boot("DB_USERNAME", Kind::Text, "SurrealDB user"),
+    secret("DB_PASSWORD", "SurrealDB password"),
+    boot("DB_NAMESPACE", Kind::Text, "SurrealDB namespace"),
+    boot("DB_NAME", Kind::Text, "SurrealDB database"),
+    boot("DATABASE_URL", Kind::Text, "Full connection URL, overriding host and port"),
+    boot("DATABASE_URLS", Kind::Text, "Comma-separated failover endpoints"),
+    boot("DATABASE_READ_URL", Kind::Text, "Read replica for search and browse"),
+    boot("DB_HEALTH_CHECK_SECS", Kind::Int, "Seconds between connection health checks"),
+    setting("DB_SLOW_QUERY_MS", Kind::Int, "Log queries slower than this (0 = off)"),
+    setting("DB_QUERY_BUDGET", Kind::Int, "Warn above this many queries per request (0 = off)"),
+    setting("DB_QUERY_BUDGET_ENFORCE", Kind::Bool, "Fail queries once the budget is exceeded"),
+    boot("S3_ENDPOINT", Kind::Url, "S3-compatible storage endpoint"),
+    boot("S3_ACCESS_KEY", Kind::Text, "Storage access key"),
+    secret("S3_SECRET_KEY", "Storage secret key"),
+    boot("S3_BUCKET", Kind::Text, "Storage bucket"),
+    boot("S3_REGION", Kind::Text, "Storage region"),
+    secret("JWT_SECRET", "Session token signing key"),
+    setting("JWT_DURATION", Kind::Int, "Session length in seconds"),
+    setting("PASSWORD_MIN_LENGTH", Kind::Int, "Minimum password length (at least 8)"),
+    setting("PASSWORD_REQUIRE_MIXED_CASE", Kind::Bool, "Require upper and lower case letters"),
+    setting("PASSWORD_REQUIRE_DIGIT", Kind::Bool, "Require a digit"),
+    setting("PASSWORD_REQUIRE_SYMBOL", Kind::Bool, "Require a symbol"),
+    boot("PASSWORD_BREACH_DIR", Kind::Text, "Directory of breached-password range files"),
+    setting("LOGIN_MAX_FAILURES", Kind::Int, "Failed logins before an account locks"),
+    setting("LOGIN_MAX_FAILURES_PER_IP", Kind::Int, "Failed logins before an IP is blocked"),
+    setting("LOGIN_FAILURE_WINDOW_MINS", Kind::Int, "Window failed logins are counted over"),
+    setting("LOGIN_LOCKOUT_MINS", Kind::Int, "How long a lockout lasts"),
+    setting("LOGIN_VERIFY_NEW_DEVICES", Kind::Bool, "Email a code for sign-ins from new devices"),
+    boot("TRUSTED_PROXY_HOPS", Kind::Int, "Reverse proxies whose X-Forwarded-For is trusted"),
+    setting("IMPERSONATION_SESSION_MINS", Kind::Int, "Length of admin impersonation sessions"),
+    setting("TERMS_VERSION", Kind::Text, "Current Terms of Service version"),
+    setting("PRIVACY_VERSION", Kind::Text, "Current Privacy Policy version"),
+    setting("ID_VERIFICATION_PROVIDER_NAME", Kind::Text, "Identity verification provider shown to users"),
+    boot("ID_VERIFICATION_PROVIDER_URL", Kind::Url, "Identity verification start URL"),
+    secret("ID_VERIFICATION_PROVIDER_SECRET", "Identity verification webhook secret"),
+    boot("FFMPEG_PATH", Kind::Text, "ffmpeg binary used for transcoding"),
+    setting("TRANSCODE_TIMEOUT_SECS", Kind::Int, "Longest a single transcode may run"),
+    boot("MAILJET_API_KEY", Kind::Text, "Mailjet API key"),
+    secret("MAILJET_API_SECRET", "Mailjet API secret"),
+    setting("MAILJET_FROM_EMAIL", Kind::Text, "Sender address for outgoing email"),
+    setting("MAILJET_FROM_NAME", Kind::Text, "Sender name for outgoing email"),
+    setting("FEEDBACK_RECIPIENT_EMAIL", Kind::Text, "Where feedback submissions are sent"),
+    boot("FEEDBACK_WEBHOOK_URL", Kind::Url, "Webhook or issue tracker that admins forward feedback to"),
+    secret("FEEDBACK_WEBHOOK_TOKEN", "Bearer token sent when forwarding feedback"),
+    secret("TMDB_API_KEY", "TMDB API key for production imports"),
+    setting("SEARCH_WEIGHT_NAME", Kind::Int, "Search score for a name match"),
+    setting("SEARCH_WEIGHT_HEADLINE", Kind::Int, "Search score for a headline match"),
+    setting("SEARCH_WEIGHT_LOCATION", Kind::Int, "Search score for a location match"),
+    setting("SEARCH_WEIGHT_VECTOR", Kind::Int, "Multiplier for vector similarity"),
+    setting("SEARCH_VECTOR_THRESHOLD", Kind::Float, "Minimum vector similarity for a result"),
+    setting("SEARCH_WEIGHT_CONNECTION", Kind::Int, "Search score per tie between a result and the signed-in searcher"),
+    setting("SEARCH_MAX_CANDIDATES", Kind::Int, "Most results ranked per type for one search (0 = no limit)"),
+    setting("SEARCH_TIMEOUT_MS", Kind::Int, "Longest one type's search may take before it's left out (0 = no limit)"),
+    setting("SEARCH_PEOPLE_WEIGHT_NAME", Kind::Int, "Name match score for people"),
+    setting("SEARCH_PEOPLE_WEIGHT_HEADLINE", Kind::Int, "Headline match score for people"),
+    setting("SEARCH_PEOPLE_WEIGHT_LOCATION", Kind::Int, "Location match score for people"),
+    setting("SEARCH_PEOPLE_WEIGHT_VECTOR", Kind::Int, "Vector similarity multiplier for people"),
+    setting("SEARCH_PEOPLE_VECTOR_THRESHOLD", Kind::Float, "Minimum vector similarity for people"),
+    setting("SEARCH_PEOPLE_WEIGHT_CONNECTION", Kind::Int, "Score per tie between a person and the searcher"),
+    setting("SEARCH_PEOPLE_MAX_CANDIDATES", Kind::Int, "Most people ranked for one search (0 = no limit)"),
+    setting("SEARCH_PEOPLE_TIMEOUT_MS", Kind::Int, "Longest the people search may take (0 = no limit)"),
+    setting("SEARCH_ORGANIZATIONS_WEIGHT_NAME", Kind::Int, "Name match score for organizations"),
+    setting("SEARCH_ORGANIZATIONS_WEIGHT_HEADLINE", Kind::Int, "Headline match score for organizations"),
+    setting("SEARCH_ORGANIZATIONS_WEIGHT_LOCATION", Kind::Int, "Location match score for organizations"),
+    setting("SEARCH_ORGANIZATIONS_WEIGHT_VECTOR", Kind::Int, "Vector similarity multiplier for organizations"),
+    setting("SEARCH_ORGANIZATIONS_VECTOR_THRESHOLD", Kind::Float, "Minimum vector similarity for organizations"),
+    setting("SEARCH_ORGANIZATIONS_MAX_CANDIDATES", Kind::Int, "Most organizations ranked for one search (0 = no limit)"),
+    setting("SEARCH_ORGANIZATIONS_TIMEOUT_MS", Kind::Int, "Longest the organizations search may take (0 = no limit)"),
+    setting("SEARCH_LOCATIONS_WEIGHT_NAME", Kind::Int, "Name match score for locations"),
+    setting("SEARCH_LOCATIONS_WEIGHT_HEADLINE", Kind::Int, "Headline match score for locations"),
+    setting("SEARCH_LOCATIONS_WEIGHT_LOCATION", Kind::Int, "Location match score for locations"),
+    setting("SEARCH_LOCATIONS_WEIGHT_VECTOR", Kind::Int, "Vector similarity multiplier for locations"),
+    setting("SEARCH_LOCATIONS_VECTOR_THRESHOLD", Kind::Float, "Minimum vector similarity for locations"),
+    setting("SEARCH_LOCATIONS_MAX_CANDIDATES", Kind::Int, "Most locations ranked for one search (0 = no limit)"),
+    setting("SEARCH_LOCATIONS_TIMEOUT_MS", Kind::Int, "Longest the locations search may take (0 = no limit)"),
+    setting("SEARCH_PRODUCTIONS_WEIGHT_NAME", Kind::Int, "Name match score for productions"),
+    setting("SEARCH_PRODUCTIONS_WEIGHT_HEADLINE", Kind::Int, "Headline match score for productions"),
+    setting("SEARCH_PRODUCTIONS_WEIGHT_LOCATION", Kind::Int, "Location match score for productions"),
+    setting("SEARCH_PRODUCTIONS_WEIGHT_VECTOR", Kind::Int, "Vector similarity multiplier for productions"),
+    setting("SEARCH_PRODUCTIONS_VECTOR_THRESHOLD", Kind::Float, "Minimum vector similarity for productions"),
+    setting("SEARCH_PRODUCTIONS_WEIGHT_CONNECTION", Kind::Int, "Score per tie between a production and the searcher"),
+    setting("SEARCH_PRODUCTIONS_MAX_CANDIDATES", Kind::Int, "Most productions ranked for one search (0 = no limit)"),
+    setting("SEARCH_PRODUCTIONS_TIMEOUT_MS", Kind::Int, "Longest the productions search may take (0 = no limit)"),
+    setting("SEARCH_JOBS_WEIGHT_NAME", Kind::Int, "Name match score for jobs"),
+    setting("SEARCH_JOBS_WEIGHT_HEADLINE", Kind::Int, "Headline match score for jobs"),
+    setting("SEARCH_JOBS_WEIGHT_LOCATION", Kind::Int, "Location match score for jobs"),
+    setting("SEARCH_JOBS_WEIGHT_VECTOR", Kind::Int, "Vector similarity multiplier for jobs"),
+    setting("SEARCH_JOBS_VECTOR_THRESHOLD", Kind::Float, "Minimum vector similarity for jobs"),
+    setting("SEARCH_JOBS_MAX_CANDIDATES", Kind::Int, "Most jobs ranked for one search (0 = no limit)"),
+    setting("SEARCH_JOBS_TIMEOUT_MS", Kind::Int, "Longest the jobs search may take (0 = no limit)"),
+    setting("SEARCH_WARMUP_QUERIES", Kind::Text, "Comma-separated queries embedded and cached at startup"),
+    setting("SEARCH_WARMUP_TOP", Kind::Int, "Most searched recent queries to warm up as well"),
+    setting("SEARCH_CACHE_TTL_SECS", Kind::Int, "How long query results are cached (0 = off)"),
+    setting("SEARCH_CACHE_MAX_ENTRIES", Kind::Int, "Most recent queries whose embeddings and results are cached"),
+    setting("SEARCH_RATE_LIMIT", Kind::Int, "Searches allowed per IP per minute (0 = no limit)"),
+    boot("SEARCH_RERANK", Kind::Bool, "Load a cross-encoder to rerank long searches"),
+    setting("SEARCH_RERANK_MIN_WORDS", Kind::Int, "Fewest words in a query before it is reranked"),
+    setting("EMBEDDING_PRECISION", Kind::Text, "How embeddings are stored: f32 or int8"),
+    setting("EMBEDDING_FIELDS", Kind::Bool, "Embed each passage of a record to explain search matches"),
+    setting("MCP_SEARCH_WEIGHT_NAME", Kind::Int, "MCP search score for a name match"),
+    setting("MCP_SEARCH_WEIGHT_HEADLINE", Kind::Int, "MCP search score for a headline match"),
+    setting("MCP_SEARCH_WEIGHT_LOCATION", Kind::Int, "MCP search score for a location match"),
+    setting("MCP_SEARCH_WEIGHT_VECTOR", Kind::Int, "MCP multiplier for vector similarity"),
+    setting("MCP_SEARCH_VECTOR_THRESHOLD", Kind::Float, "MCP minimum vector similarity"),
+    setting("MCP_SEARCH_MAX_CANDIDATES", Kind::Int, "MCP most results ranked per type (0 = no limit)"),
+    secret("WHATSAPP_BOT_TOKEN", "Token the WhatsApp bot authenticates with"),
+    setting("WHATSAPP_CLAIM_TIMEOUT_SECS", Kind::Int, "Retry WhatsApp messages unsent after this long"),
+    setting("WHATSAPP_MAX_ATTEMPTS", Kind::Int, "Attempts before a WhatsApp message is dropped"),
+    boot("INBOUND_MAIL_DOMAIN", Kind::Text, "Domain production inbox addresses are on"),
+    secret("INBOUND_MAIL_SECRET", "Token the inbound mail webhook authenticates with"),
+    boot("MULTI_TENANT", Kind::Bool, "Serve tenants from their own namespace, chosen by hostname"),
+    boot("TENANT_SCHEMA_PATH", Kind::Text, "Schema applied when provisioning a tenant"),
+];
+
+/// Older names still accepted for a setting
+const ALIASES: &[(&str, &str)] = &[("DB_USERNAME", "DB_USER"), ("DB_PASSWORD", "DB_PASS")];
+
+pub fn find(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|s| s.key == key)
+}
+
+/// Where a setting's value came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Override,
+    Env,
+    File,
+    Default,
+}
+
+impl Source {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Source::Override => "override",
+            Source::Env => "environment",
+            Source::File => "config file",
+            Source::Default => "default",
+        }
+    }
+}
+
+static OVERRIDES: LazyLock<RwLock<HashMap<String, String>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+static FILE: LazyLock<std::result::Result<HashMap<String, String>, String>> =
+    LazyLock::new(|| {
+        dotenv::dotenv().ok();
+        let path = env::var("SLATEHUB_CONFIG").unwrap_or_else(|_| "slatehub.toml".to_string());
+        match std::fs::read_to_string(&path) {
+            Ok(text) => parse_toml(&text).map_err(|e| format!("{}: {}", path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(format!("{}: {}", path, e)),
+        }
+    });
+
+/// Flatten a TOML document into variable names: `[search] vector_threshold`
+/// becomes `SEARCH_VECTOR_THRESHOLD`. Arrays are joined with commas.
+pub fn parse_toml(text: &str) -> std::result::Result<HashMap<String, String>, String> {
+    fn flatten(prefix: &str, table: &toml::Table, out: &mut HashMap<String, String>) {
+        for (key, value) in table {
+            let name = if prefix.is_empty() {
+                key.to_uppercase()
+            } else {
+                format!("{}_{}", prefix, key.to_uppercase())
+            };
+            match value {
+                toml::Value::Table(inner) => flatten(&name, inner, out),
+                other => {
+                    out.insert(name, scalar(other));
+                }
+            }
+        }
+    }
+    fn scalar(value: &toml::Value) -> String {
+        match value {
+            toml::Value::String(s) => s.clone(),
+            toml::Value::Array(items) => items.iter().map(scalar).collect::<Vec<_>>().join(","),
+            other => other.to_string(),
+        }
+    }
+
+    let table: toml::Table = text.parse().map_err(|e: toml::de::Error| e.message().to_string())?;
+    let mut out = HashMap::new();
+    flatten("", &table, &mut out);
+    Ok(out)
+}
+
+/// Resolve a setting with its source. Aliases are tried after the main name
+/// at each level.
+pub fn resolve(key: &str) -> Option<(String, Source)> {
+    let names: Vec<&str> = std::iter::once(key)
+        .chain(ALIASES.iter().filter(|(k, _)| *k == key).map(|(_, alias)| *alias))
+        .collect();
+
+    if let Some(value) = names.iter().find_map(|n| OVERRIDES.read().unwrap().get(*n).cloned()) {
+        return Some((value, Source::Override));
+    }
+    if let Some(value) = names.iter().find_map(|n| env::var(n).ok()) {
+        return Some((value, Source::Env));
+    }
+    if let Ok(file) = FILE.as_ref() {
+        if let Some(value) = names.iter().find_map(|n| file.get(*n).cloned()) {
+            return Some((value, Source::File));
+        }
+    }
+    None
+}
+
+/// Drop-in replacement for `std::env::var` that also consults the config
+/// file and admin overrides.
+pub fn var(key: &str) -> std::result::Result<String, env::VarError> {
+    resolve(key).map(|(value, _)| value).ok_or(env::VarError::NotPresent)
+}
+
+/// Check every known setting that has a value, plus the config file itself.
+/// Empty values count as unset.
+pub fn validate() -> std::result::Result<(), ConfigError> {
+    let mut problems = Vec::new();
+    if let Err(e) = FILE.as_ref() {
+        problems.push(format!("config file {}", e));
+    }
+    for setting in SETTINGS {
+        if let Some((value, source)) = resolve(setting.key) {
+            if value.trim().is_empty() {
+                continue;
+            }
+            if let Err(e) = setting.kind.check(&value) {
+                problems.push(format!("{} ({}) {}", setting.key, source.label(), e));
+            }
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ConfigError::Invalid(problems))
+    }
+}
+
+/// Show the first and last characters of secrets only
+pub fn redact(value: &str) -> String {
+    if value.chars().count() <= 8 {
+        "••••••".to_string()
+    } else {
+        let head: String = value.chars().take(2).collect();
+        let tail: String = value.chars().rev().take(2).collect::<Vec<_>>().into_iter().rev().collect();
+        format!("{}••••••{}", head, tail)
+    }
+}
+
+/// A row on `/admin/config`
+#[derive(Debug, Clone)]
+pub struct SettingView {
+    pub setting: &'static Setting,
+    pub value: Option<String>,
+    pub source: Source,
+    /// Saved override waiting for a restart, when it differs from the live value
+    pub pending: Option<String>,
+}
+
+/// Every known setting with its redacted value and where it came from.
+pub async fn list() -> Result<Vec<SettingView>> {
+    let saved = fetch_overrides().await?;
+    Ok(SETTINGS
+        .iter()
+        .map(|setting| {
+            let (value, source) = match resolve(setting.key) {
+                Some((value, source)) => (Some(value), source),
+                None => (None, Source::Default),
+            };
+            let pending = saved
+                .get(setting.key)
+                .filter(|v| source != Source::Override || value.as_deref() != Some(v.as_str()))
+                .cloned();
+            let value = if setting.secret {
+                value.map(|v| redact(&v))
+            } else {
+                value
+            };
+            SettingView { setting, value, source, pending }
+        })
+        .collect())
+}
+
+#[derive(Deserialize, SurrealValue)]
+struct OverrideRow {
+    key: String,
+    value: String,
+}
+
+async fn fetch_overrides() -> Result<HashMap<String, String>> {
+    let rows: Vec<OverrideRow> = primary()
+        .query("SELECT record::id(id) AS key, value FROM config_override")
+        .await?
+        .take(0)?;
+    Ok(rows.into_iter().map(|r| (r.key, r.value)).collect())
+}
+
+/// Read saved overrides into memory. Called once at startup, after the
+/// database connects; overrides that no longer validate are skipped.
+pub async fn load_overrides() -> Result<()> {
+    let mut loaded = HashMap::new();
+    for (key, value) in fetch_overrides().await? {
+        match find(&key) {
+            Some(setting) if setting.overridable => match setting.kind.check(&value) {
+                Ok(()) => {
+                    loaded.insert(key, value);
+                }
+                Err(e) => warn!("Ignoring config override {}: {}", key, e),
+            },
+            _ => warn!("Ignoring config override for unknown or fixed setting {}", key),
+        }
+    }
+    if !loaded.is_empty() {
+        info!("Loaded {} config override(s)", loaded.len());
+    }
+    *OVERRIDES.write().unwrap() = loaded;
+    Ok(())
+}
+
+/// Save an override, applied on the next restart.
+pub async fn set_override(key: &str, value: &str) -> Result<()> {
+    let setting = find(key)
+        .filter(|s| s.overridable)
+        .ok_or_else(|| Error::Validation(format!("{} cannot be changed here", key)))?;
+    let value = value.trim();
+    setting
+        .kind
+        .check(value)
+        .map_err(|e| Error::Validation(format!("{} {}", key, e)))?;
+
+    primary()
+        .query("UPSERT type::record('config_override', $key) SET value = $value")
+        .bind(("key", key.to_string()))
+        .bind(("value", value.to_string()))
+        .await?
+        .check()?;
+    Ok(())
+}
+
+pub async fn clear_override(key: &str) -> Result<()> {
+    primary()
+        .query("DELETE type::record('config_override', $key)")
+        .bind(("key", key.to_string()))
+        .await?
+        .check()?;
+    Ok(())
+}
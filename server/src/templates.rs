@@ -6,8 +6,11 @@ use crate::db::DB;
 use crate::models::likes::{LikedLocation, LikedPerson};
 use crate::models::notification::NotificationModel;
 use crate::models::person::SessionUser;
+use crate::physical_attributes::{self, Attribute};
+use crate::units::{self, UnitSystem};
+use crate::validation::FieldErrors;
 
-mod filters {
+pub(crate) mod filters {
     /// Convert a relative path to an absolute URL using APP_URL
     pub fn abs_url(path: &str) -> askama::Result<String> {
         let base = crate::config::app_url();
@@ -79,6 +82,7 @@ pub struct User {
     pub notification_count: u32,    // Unread notification count
     pub is_identity_verified: bool, // Whether user has identity verification
     pub is_admin: bool,             // Whether user is a system administrator
+    pub impersonated_by: Option<String>, // Admin username while an admin is viewing as this user
 }
 
 impl User {
@@ -112,6 +116,10 @@ impl User {
             notification_count,
             is_identity_verified,
             is_admin,
+            impersonated_by: session_user
+                .impersonator
+                .as_ref()
+                .map(|i| i.admin_username.clone()),
         }
     }
 
@@ -240,6 +248,164 @@ pub struct LoginTemplate {
     pub redirect_to: Option<String>,
 }
 
+/// New-device sign-in confirmation page template
+#[derive(Template)]
+#[template(path = "auth/login_verify.html")]
+pub struct LoginVerifyTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub error: Option<String>,
+    /// Username or email the person signed in with
+    pub identifier: String,
+    pub redirect_to: Option<String>,
+}
+
+/// One `<option>` in a select, with its selected state worked out up front
+pub struct SelectOption {
+    pub value: String,
+    pub label: String,
+    pub selected: bool,
+}
+
+impl SelectOption {
+    pub fn new(value: impl ToString, label: String, selected: bool) -> Self {
+        Self {
+            value: value.to_string(),
+            label,
+            selected,
+        }
+    }
+}
+
+/// `?format=print` on pages with a printable variant
+#[derive(Debug, Default, Deserialize)]
+pub struct FormatQuery {
+    pub format: Option<String>,
+}
+
+impl FormatQuery {
+    pub fn is_print(&self) -> bool {
+        self.format.as_deref() == Some("print")
+    }
+}
+
+/// A policy document awaiting (re-)acceptance
+pub struct PendingDocument {
+    pub title: String,
+    pub url: String,
+    pub version: String,
+}
+
+/// Terms / privacy re-acceptance interstitial
+#[derive(Template)]
+#[template(path = "legal/accept.html")]
+pub struct LegalAcceptTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub error: Option<String>,
+    pub documents: Vec<PendingDocument>,
+    /// Page to return to after accepting
+    pub next: String,
+}
+
+/// Blocked and muted people, under account settings
+#[derive(Template)]
+#[template(path = "account/blocks.html")]
+pub struct AccountBlocksTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub blocked: Vec<crate::models::block::BlockedPerson>,
+    pub muted: Vec<crate::models::block::BlockedPerson>,
+    pub success: Option<String>,
+}
+
+/// Saved job searches, under account settings
+#[derive(Template)]
+#[template(path = "account/alerts.html")]
+pub struct AccountAlertsTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub searches: Vec<crate::models::saved_search::SavedSearch>,
+    pub success: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Availability calendar: days the person can't work, under account settings
+#[derive(Template)]
+#[template(path = "account/availability.html")]
+pub struct AccountAvailabilityTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    /// Weeks from this one, with what's keeping them busy each day
+    pub calendar: Vec<Vec<crate::models::production_gear::CalendarDay>>,
+    /// Unavailable ranges that haven't ended, soonest first
+    pub periods: Vec<crate::models::person_availability::UnavailablePeriod>,
+    pub bookings: Vec<crate::models::offer::Booking>,
+    pub success: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Representation page: the agency representing the person, under account settings
+#[derive(Template)]
+#[template(path = "account/representation.html")]
+pub struct AccountRepresentationTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub representation: Option<crate::models::representation::Representation>,
+    pub contact_mode: crate::models::representation::ContactMode,
+    pub contact_modes: [crate::models::representation::ContactMode; 2],
+    /// Submissions agencies made for this person, newest first
+    pub submissions: Vec<crate::models::talent_submission::Submission>,
+    pub success: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Guardian page: minors who named the current person as their guardian
+#[derive(Template)]
+#[template(path = "account/guardian.html")]
+pub struct AccountGuardianTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub wards: Vec<crate::services::minors::Ward>,
+    pub success: Option<String>,
+}
+
+/// Organization single sign-on entry page
+#[derive(Template)]
+#[template(path = "auth/sso_login.html")]
+pub struct SsoLoginTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub error: Option<String>,
+    /// Organization slug to prefill
+    pub org: String,
+    pub redirect_to: Option<String>,
+}
+
 /// Invite landing page template (OG unfurl + auto-redirect)
 #[derive(Template)]
 #[template(path = "auth/invite_landing.html")]
@@ -267,8 +433,14 @@ pub struct SignupTemplate {
     pub active_page: String,
     pub user: Option<User>,
     pub error: Option<String>,
+    /// Problems with what was submitted, shown under each input
+    pub errors: FieldErrors,
+    pub prefill_username: Option<String>,
     pub prefill_email: Option<String>,
     pub redirect: Option<String>,
+    /// Password policy summary shown under new-password fields
+    pub password_help: String,
+    pub password_min_length: usize,
 }
 
 /// Email verification page template
@@ -313,6 +485,9 @@ pub struct ResetPasswordTemplate {
     pub success: Option<String>,
     pub email: Option<String>,
     pub code: Option<String>,
+    /// Password policy summary shown under new-password fields
+    pub password_help: String,
+    pub password_min_length: usize,
 }
 
 /// Profile page template
@@ -326,6 +501,15 @@ pub struct ProfileTemplate {
     pub user: Option<User>,
     pub profile: ProfileData,
     pub is_liked: bool,
+    /// The viewer has blocked / muted this person
+    pub is_blocked: bool,
+    pub is_muted: bool,
+    /// The owner's completeness checklist; `None` for everyone else
+    pub completeness: Option<crate::models::completeness::Completeness>,
+    /// The agency confirmed to represent this person
+    pub representation: Option<crate::models::representation::Representation>,
+    /// Inquiries go to the representative rather than the person
+    pub contact_via_representative: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -348,6 +532,11 @@ pub struct ProfileData {
     pub social_links: Vec<SocialLinkDisplay>,
     pub reels: Vec<ReelDisplay>,
     pub photos: Vec<PhotoDisplay>,
+    pub audio_reels: Vec<AudioReelDisplay>,
+    /// Upcoming date ranges the person is booked through accepted offers
+    pub booked_dates: Vec<String>,
+    /// Upcoming date ranges the person marked unavailable
+    pub unavailable_dates: Vec<String>,
     pub is_own_profile: bool,
     pub is_public: bool,
     pub verification_status: String,
@@ -357,8 +546,11 @@ pub struct ProfileData {
     pub height_mm: Option<i32>,
     pub weight_kg: Option<i32>,
     pub body_type: Option<String>,
+    pub body_type_other: Option<String>,
     pub hair_color: Option<String>,
+    pub hair_color_other: Option<String>,
     pub eye_color: Option<String>,
+    pub eye_color_other: Option<String>,
     pub ethnicity: Vec<String>,
     pub acting_age_range_min: Option<i32>,
     pub acting_age_range_max: Option<i32>,
@@ -366,6 +558,51 @@ pub struct ProfileData {
     pub nationality: Option<String>,
     pub messaging_preference: String,
     pub phone: Option<String>,
+    /// Units to show heights and weights in, "metric" or "imperial": the
+    /// viewer's preference on profiles, the owner's on the edit page. Unset,
+    /// the page picks from the browser's locale.
+    pub units: Option<String>,
+    /// Language physical attribute labels are shown in
+    pub lang: String,
+}
+
+impl ProfileData {
+    fn unit_system(&self) -> UnitSystem {
+        UnitSystem::from_preference(self.units.as_deref())
+    }
+
+    pub fn height_text(&self) -> Option<String> {
+        self.height_mm
+            .filter(|mm| *mm > 0)
+            .map(|mm| units::height(mm, self.unit_system()))
+    }
+
+    pub fn weight_text(&self) -> Option<String> {
+        self.weight_kg
+            .filter(|kg| *kg > 0)
+            .map(|kg| units::weight(kg, self.unit_system()))
+    }
+
+    fn attribute_text(&self, attribute: Attribute, value: &Option<String>, other: &Option<String>) -> Option<String> {
+        let value = value.as_deref().filter(|v| !v.is_empty())?;
+        match other.as_deref().filter(|o| value == physical_attributes::OTHER && !o.trim().is_empty()) {
+            Some(other) => Some(other.to_string()),
+            None => Some(physical_attributes::label(attribute, value, &self.lang)),
+        }
+    }
+
+    /// Body type in the page's language, or the person's own words for "Other"
+    pub fn body_type_text(&self) -> Option<String> {
+        self.attribute_text(Attribute::BodyType, &self.body_type, &self.body_type_other)
+    }
+
+    pub fn hair_color_text(&self) -> Option<String> {
+        self.attribute_text(Attribute::HairColor, &self.hair_color, &self.hair_color_other)
+    }
+
+    pub fn eye_color_text(&self) -> Option<String> {
+        self.attribute_text(Attribute::EyeColor, &self.eye_color, &self.eye_color_other)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -394,6 +631,26 @@ pub struct PhotoDisplay {
     pub caption: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioReelDisplay {
+    pub id: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub genre: Option<String>,
+    pub mood: Option<String>,
+    pub instruments: Vec<String>,
+    pub duration: Option<String>,
+    /// Presigned URL for the audio file
+    pub url: String,
+    pub content_type: String,
+    /// Waveform peaks as a JSON array, for the scrubber
+    pub peaks: String,
+    /// The waveform is still being made
+    pub processing: bool,
+    /// Tags identified in the file, as (label, value)
+    pub tags: Vec<(String, String)>,
+}
+
 /// Display struct for involvement-based credits (graph traversal)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InvolvementDisplay {
@@ -437,12 +694,18 @@ pub struct ProfileEditTemplate {
     pub user: Option<User>,
     pub profile: ProfileData,
     pub platforms: Vec<SocialPlatformOption>,
+    pub body_types: Vec<SelectOption>,
+    pub hair_colors: Vec<SelectOption>,
+    pub eye_colors: Vec<SelectOption>,
     pub error: Option<String>,
+    /// Why the last save was rejected, shown next to each field
+    pub errors: FieldErrors,
     pub success: Option<String>,
     pub photo_count: usize,
     pub photo_limit: Option<usize>,
     pub reel_count: usize,
     pub reel_limit: Option<usize>,
+    pub audio_reel_limit: Option<usize>,
     pub is_identity_verified: bool,
 }
 
@@ -514,6 +777,8 @@ pub struct ProductionTemplate {
     pub production: ProductionDetail,
     pub production_roles: Vec<String>,
     pub org_production_roles: Vec<String>,
+    /// Stored value and label of each company credit role
+    pub company_roles: &'static [(&'static str, &'static str)],
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -533,6 +798,8 @@ pub struct ProductionDetail {
     pub person_members: Vec<ProductionMemberView>,
     pub org_members: Vec<ProductionMemberView>,
     pub can_edit: bool,
+    /// Member of the production, with access to its shot list
+    pub is_member: bool,
     pub poster_url: Option<String>,
     pub poster_photo: Option<String>,
     pub header_photo: Option<String>,
@@ -545,8 +812,16 @@ pub struct ProductionDetail {
     pub cast: Vec<CastCrewMember>,
     pub crew: Vec<CastCrewMember>,
     pub pending_credits: Vec<CastCrewMember>,
+    /// Producers, financiers and vendors credited on the production
+    pub companies: Vec<crate::models::company_credit::ProductionCompany>,
     pub budget_level: Option<String>,
     pub production_tier: Option<String>,
+    /// Where the deliverables checklist stands, for members once it has any
+    pub deliverables: Option<crate::models::deliverable::Rollup>,
+    /// Festival selections its owners chose to show
+    pub selections: Vec<crate::models::festival_submission::FestivalSubmission>,
+    /// Whether the production's press kit is public
+    pub press_kit_published: bool,
     pub pending_email_invites: Vec<PendingEmailInvite>,
 }
 
@@ -585,6 +860,44 @@ pub struct ProductionMemberView {
     pub is_verified: bool,
 }
 
+/// Printable crew list: everyone who accepted a place on the production
+#[derive(Template)]
+#[template(path = "productions/crew_print.html")]
+pub struct CrewListPrintTemplate {
+    pub app_name: String,
+    pub version: String,
+    pub production_title: String,
+    pub production_slug: String,
+    pub production_type: String,
+    pub printed_on: String,
+    pub members: Vec<ProductionMemberView>,
+}
+
+/// Contact sheet, for the production's members
+#[derive(Template)]
+#[template(path = "productions/contacts.html")]
+pub struct ContactSheetTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub production_title: String,
+    pub production_slug: String,
+    pub contacts: Vec<crate::models::production::CrewContact>,
+}
+
+#[derive(Template)]
+#[template(path = "productions/contacts_print.html")]
+pub struct ContactSheetPrintTemplate {
+    pub app_name: String,
+    pub version: String,
+    pub production_title: String,
+    pub production_slug: String,
+    pub printed_on: String,
+    pub contacts: Vec<crate::models::production::CrewContact>,
+}
+
 /// Organization option for ownership dropdown
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrgOption {
@@ -695,6 +1008,26 @@ pub struct LocationTemplate {
     pub user: Option<User>,
     pub location: LocationDetail,
     pub is_liked: bool,
+    /// The viewer's scouting collections, for "Save to scouting"
+    pub scouting_collections: Vec<SelectOption>,
+    /// Vendors near the location by category; empty without coordinates
+    pub nearby_services: Vec<NearbyServiceGroup>,
+}
+
+/// One category in a location's nearby services panel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearbyServiceGroup {
+    pub label: String,
+    pub services: Vec<NearbyServiceView>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearbyServiceView {
+    pub name: String,
+    pub slug: String,
+    pub logo: Option<String>,
+    pub verified: bool,
+    pub distance: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -722,14 +1055,25 @@ pub struct LocationDetail {
     pub restrictions: Option<Vec<String>>,
     pub parking_info: Option<String>,
     pub max_capacity: Option<i32>,
+    pub house_rules: Vec<String>,
     pub profile_photo: Option<String>,
     pub photos: Vec<LocationPhoto>,
     pub created_at: String,
     pub updated_at: String,
     pub rates: Vec<RateView>,
+    pub blackouts: Vec<BlackoutView>,
     pub can_edit: bool,
 }
 
+/// A location blackout that hasn't ended
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlackoutView {
+    pub id: String,
+    pub label: String,
+    /// "22:00-07:00 every day"
+    pub when: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateView {
     pub id: String,
@@ -749,7 +1093,9 @@ pub struct LocationCreateTemplate {
     pub version: String,
     pub active_page: String,
     pub user: Option<User>,
-    pub errors: Option<Vec<String>>,
+    /// What was entered, when re-rendering after a failed submit
+    pub form: crate::models::location::CreateLocationForm,
+    pub errors: FieldErrors,
 }
 
 /// Location edit form template
@@ -783,6 +1129,10 @@ pub struct LocationEditData {
     pub restrictions: Option<String>,
     pub parking_info: Option<String>,
     pub max_capacity: Option<i32>,
+    /// "latitude, longitude", empty when not set
+    pub coordinates: String,
+    /// One rule per line
+    pub house_rules: String,
     pub profile_photo: Option<String>,
     pub photos: Vec<LocationPhoto>,
 }
@@ -802,6 +1152,18 @@ pub struct PeopleTemplate {
     pub liked_ids: Vec<String>,
     pub current_user_id: String,
     pub has_more: bool,
+    /// Where the next page of the unfiltered listing starts
+    pub next_cursor: Option<String>,
+    /// Exact-match physical attribute filters
+    pub hair_colors: Vec<SelectOption>,
+    pub eye_colors: Vec<SelectOption>,
+    pub body_types: Vec<SelectOption>,
+    /// Shoot window people must be free for ("YYYY-MM-DD", empty for none)
+    pub available_from: String,
+    pub available_to: String,
+    /// The attribute and availability filters as query parameters
+    /// ("&hair=Red"), for the next page
+    pub attribute_query: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -817,6 +1179,46 @@ pub struct PersonCard {
     pub is_identity_verified: bool,
 }
 
+/// "What search sees" page for a profile, organization, location or production
+#[derive(Template)]
+#[template(path = "search/preview.html")]
+pub struct SearchPreviewTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    /// "profile", "organization", "location" or "production"
+    pub kind: &'static str,
+    pub name: String,
+    pub back_url: String,
+    /// This page's URL, the test-query form submits to it
+    pub preview_url: String,
+    pub preview: crate::services::search_preview::Preview,
+    pub query: String,
+    /// Similarity to the test query, formatted
+    pub score: Option<String>,
+    /// The test query would match by meaning
+    pub matches: bool,
+    pub threshold: String,
+    pub error: Option<String>,
+}
+
+/// Public service status page
+#[derive(Template)]
+#[template(path = "status/index.html")]
+pub struct StatusTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub overall: crate::services::status::ComponentState,
+    pub components: Vec<crate::services::status::Component>,
+    pub notices: Vec<crate::services::status::Notice>,
+    pub resolved: Vec<crate::services::status::Notice>,
+}
+
 /// About page template
 #[derive(Template)]
 #[template(path = "about/index.html")]
@@ -863,6 +1265,14 @@ pub struct GetVerifiedTemplate {
     pub active_page: String,
     pub user: Option<User>,
     pub has_pending_request: bool,
+    pub is_verified: bool,
+    /// Why the last request was turned down, if it was
+    pub rejection_note: Option<String>,
+    pub rejected: bool,
+    pub document_kinds: Vec<SelectOption>,
+    /// Set when a third-party provider is configured
+    pub provider_name: Option<String>,
+    pub error: Option<String>,
 }
 
 /// Account settings page template
@@ -877,9 +1287,33 @@ pub struct AccountSettingsTemplate {
     pub username: String,
     pub email: String,
     pub messaging_preference: String,
+    /// "metric" or "imperial"
+    pub units: &'static str,
     pub show_contact_info: bool,
+    /// Who can find the profile in search and browse
+    pub visibility_options: Vec<SelectOption>,
+    /// Whether recent searches are kept
+    pub search_history_enabled: bool,
+    pub digest_enabled: bool,
+    pub digest_days: Vec<SelectOption>,
+    pub digest_hours: Vec<SelectOption>,
+    /// Whether WhatsApp delivery is set up on this server
+    pub whatsapp_available: bool,
+    pub whatsapp_enabled: bool,
+    /// As saved, with country code, e.g. "+15551234567"
+    pub whatsapp_number: String,
+    /// Embeddable availability badge image and iframe URLs, while it's on
+    pub badge_image_url: Option<String>,
+    pub badge_frame_url: Option<String>,
+    /// Public profile the badge snippets link to
+    pub profile_url: String,
+    /// Minor-mode state and guardian link; the section only shows for minors
+    pub minor: crate::services::minors::GuardianLink,
     pub error: Option<String>,
     pub success: Option<String>,
+    /// Password policy summary shown under new-password fields
+    pub password_help: String,
+    pub password_min_length: usize,
 }
 
 /// Likes page template
@@ -913,6 +1347,12 @@ pub struct ProfileAnalyticsTemplate {
     pub views_90d: crate::models::analytics::PeriodStat,
     pub views_1y: crate::models::analytics::PeriodStat,
     pub referrer_breakdown: Vec<crate::models::analytics::ReferrerCount>,
+    pub recent_viewer_count: u64,
+    pub anonymous_views_30d: u64,
+    /// Named recent viewers; empty unless the person is on the pro plan
+    pub recent_viewers: Vec<crate::models::analytics::RecentViewer>,
+    pub is_pro: bool,
+    pub browse_anonymously: bool,
 }
 
 // ============================
@@ -1027,6 +1467,8 @@ pub struct JobTemplate {
     pub active_page: String,
     pub user: Option<User>,
     pub job: JobDetailView,
+    /// Clients the viewer can submit, as an agent
+    pub clients: Vec<crate::models::representation::RosterEntry>,
 }
 
 /// Job create form
@@ -1040,7 +1482,11 @@ pub struct JobCreateTemplate {
     pub user: Option<User>,
     pub pay_rate_types: Vec<String>,
     pub user_organizations: Vec<JobOrgOption>,
-    pub errors: Option<Vec<String>>,
+    /// What was entered, when re-rendering after a failed submit
+    pub form: crate::models::job::CreateJobForm,
+    /// Role blocks to show; one blank role on a new form
+    pub roles: Vec<JobRoleEditData>,
+    pub errors: FieldErrors,
 }
 
 /// Job edit form
@@ -1077,6 +1523,8 @@ pub struct MyJobsTemplate {
     pub user: Option<User>,
     pub postings: Vec<JobListView>,
     pub applications: Vec<UserApplicationView>,
+    /// Suggested people for open postings, awaiting feedback
+    pub suggestions: Vec<crate::models::match_suggestion::SuggestedMatch>,
 }
 
 // ============================
@@ -1087,8 +1535,11 @@ pub mod equipment {
     use crate::models::equipment::{
         Equipment, EquipmentCategory, EquipmentCondition, EquipmentKit, EquipmentRental,
     };
+    use crate::models::equipment_service::{ServiceEvent, ServiceStatus};
     use crate::models::person::SessionUser;
+    use crate::models::production_gear::{CalendarDay, GearBooking};
     use askama::Template;
+    use surrealdb::types::RecordId;
 
     /// Custom Askama filters for equipment templates
     mod filters {
@@ -1117,7 +1568,7 @@ pub mod equipment {
         pub active_page: String,
         pub user: Option<super::User>,
         pub current_user: Option<SessionUser>,
-        pub equipment: Vec<Equipment>,
+        pub items: EquipmentItemsTemplate,
         pub kits: Vec<EquipmentKit>,
         pub owner_type: String,
         pub owner_id: String,
@@ -1125,6 +1576,18 @@ pub mod equipment {
         pub error_message: Option<String>,
     }
 
+    /// The equipment list's items section, also served alone by
+    /// `/equipment/items` so filters apply without reloading the page
+    #[derive(Template)]
+    #[template(path = "equipment/_items.html")]
+    pub struct EquipmentItemsTemplate {
+        pub equipment: Vec<Equipment>,
+        pub owner_type: String,
+        pub owner_id: String,
+        /// `/equipment/items` with the owner and filters shown
+        pub fragment_src: String,
+    }
+
     /// Equipment form template (for create/edit)
     #[derive(Template)]
     #[template(path = "equipment/form.html")]
@@ -1156,6 +1619,16 @@ pub mod equipment {
         pub current_user: Option<SessionUser>,
         pub equipment: Equipment,
         pub rentals: Vec<EquipmentRental>,
+        pub service_events: Vec<ServiceEvent>,
+        pub service_status: ServiceStatus,
+        /// "120 h or 14 days to go" when it has a schedule
+        pub service_remaining: Option<String>,
+        pub service_kinds: Vec<(&'static str, &'static str)>,
+        /// "YYYY-MM-DD", the latest date a service can be logged for
+        pub today: String,
+        /// Production bookings from today on
+        pub bookings: Vec<GearBooking>,
+        pub calendar: Vec<Vec<CalendarDay>>,
         pub can_edit: bool,
         pub page_title: String,
         pub error_message: Option<String>,
@@ -1212,6 +1685,8 @@ pub mod equipment {
         pub equipment: Option<Equipment>,
         pub kit: Option<EquipmentKit>,
         pub conditions: Vec<EquipmentCondition>,
+        /// Upcoming production bookings of the item, or the kit's items
+        pub bookings: Vec<GearBooking>,
         pub page_title: String,
         pub error_message: Option<String>,
     }
@@ -1232,6 +1707,48 @@ pub mod equipment {
         pub error_message: Option<String>,
     }
 
+    /// An item looked up from its label
+    pub struct ScanItem {
+        /// "equipment" or "kit"
+        pub kind: String,
+        /// Record key
+        pub id: String,
+        pub name: String,
+        pub code: String,
+        pub detail_url: String,
+        pub category: String,
+        /// Listed condition; kits don't have one
+        pub condition: Option<String>,
+        pub condition_id: Option<String>,
+        pub owner_type: String,
+        pub owner_person: Option<RecordId>,
+        pub owner_organization: Option<RecordId>,
+        pub available: bool,
+        pub out_of_service: bool,
+        /// Who has it and since when, while it's checked out
+        pub out_to: Option<String>,
+        pub out_since: Option<String>,
+        /// Whether the viewer has it or owns it, so can check it back in
+        pub can_checkin: bool,
+    }
+
+    /// Scan page: a code entry box, or the scanned item with one-tap
+    /// checkout or check-in
+    #[derive(Template)]
+    #[template(path = "equipment/scan.html")]
+    pub struct EquipmentScanTemplate {
+        pub app_name: String,
+        pub year: i32,
+        pub version: String,
+        pub active_page: String,
+        pub user: Option<super::User>,
+        pub current_user: Option<SessionUser>,
+        pub item: Option<ScanItem>,
+        pub notice: Option<String>,
+        pub page_title: String,
+        pub error_message: Option<String>,
+    }
+
     /// Rental history template
     #[derive(Template)]
     #[template(path = "equipment/rental_history.html")]
@@ -1248,6 +1765,98 @@ pub mod equipment {
     }
 }
 
+// Error pages (rendered by middleware::error_handler)
+
+#[derive(Template)]
+#[template(path = "errors/401.html")]
+pub struct UnauthorizedErrorTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub status_code: u16,
+    pub status_text: String,
+    pub error_code: String,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+    pub request_path: Option<String>,
+    pub timestamp: Option<String>,
+    pub retry_after: Option<u64>,
+}
+
+#[derive(Template)]
+#[template(path = "errors/403.html")]
+pub struct ForbiddenErrorTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub status_code: u16,
+    pub status_text: String,
+    pub error_code: String,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+    pub request_path: Option<String>,
+    pub timestamp: Option<String>,
+    pub retry_after: Option<u64>,
+}
+
+#[derive(Template)]
+#[template(path = "errors/404.html")]
+pub struct NotFoundErrorTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub status_code: u16,
+    pub status_text: String,
+    pub error_code: String,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+    pub request_path: Option<String>,
+    pub timestamp: Option<String>,
+    pub retry_after: Option<u64>,
+}
+
+#[derive(Template)]
+#[template(path = "errors/500.html")]
+pub struct ServerErrorTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub status_code: u16,
+    pub status_text: String,
+    pub error_code: String,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+    pub request_path: Option<String>,
+    pub timestamp: Option<String>,
+    pub retry_after: Option<u64>,
+}
+
+#[derive(Template)]
+#[template(path = "errors/generic.html")]
+pub struct GenericErrorTemplate {
+    pub app_name: String,
+    pub year: i32,
+    pub version: String,
+    pub active_page: String,
+    pub user: Option<User>,
+    pub status_code: u16,
+    pub status_text: String,
+    pub error_code: String,
+    pub message: Option<String>,
+    pub request_id: Option<String>,
+    pub request_path: Option<String>,
+    pub timestamp: Option<String>,
+    pub retry_after: Option<u64>,
+}
+
 // Helper struct for backwards compatibility
 pub struct BaseContext {
     pub app_name: String,
@@ -1316,6 +1925,134 @@ impl LoginTemplate {
     }
 }
 
+impl LoginVerifyTemplate {
+    pub fn new(base: BaseContext) -> Self {
+        Self {
+            app_name: base.app_name,
+            year: base.year,
+            version: base.version,
+            active_page: base.active_page,
+            user: base.user,
+            error: None,
+            identifier: String::new(),
+            redirect_to: None,
+        }
+    }
+}
+
+impl LegalAcceptTemplate {
+    pub fn new(base: BaseContext) -> Self {
+        Self {
+            app_name: base.app_name,
+            year: base.year,
+            version: base.version,
+            active_page: base.active_page,
+            user: base.user,
+            error: None,
+            documents: Vec::new(),
+            next: "/".to_string(),
+        }
+    }
+}
+
+impl AccountBlocksTemplate {
+    pub fn new(base: BaseContext) -> Self {
+        Self {
+            app_name: base.app_name,
+            year: base.year,
+            version: base.version,
+            active_page: base.active_page,
+            user: base.user,
+            blocked: Vec::new(),
+            muted: Vec::new(),
+            success: None,
+        }
+    }
+}
+
+impl AccountAlertsTemplate {
+    pub fn new(base: BaseContext) -> Self {
+        Self {
+            app_name: base.app_name,
+            year: base.year,
+            version: base.version,
+            active_page: base.active_page,
+            user: base.user,
+            searches: Vec::new(),
+            success: None,
+            error: None,
+        }
+    }
+}
+
+impl AccountAvailabilityTemplate {
+    pub fn new(base: BaseContext) -> Self {
+        Self {
+            app_name: base.app_name,
+            year: base.year,
+            version: base.version,
+            active_page: base.active_page,
+            user: base.user,
+            calendar: Vec::new(),
+            periods: Vec::new(),
+            bookings: Vec::new(),
+            success: None,
+            error: None,
+        }
+    }
+}
+
+impl AccountRepresentationTemplate {
+    pub fn new(base: BaseContext) -> Self {
+        Self {
+            app_name: base.app_name,
+            year: base.year,
+            version: base.version,
+            active_page: base.active_page,
+            user: base.user,
+            representation: None,
+            contact_mode: Default::default(),
+            contact_modes: crate::models::representation::ContactMode::ALL,
+            submissions: Vec::new(),
+            success: None,
+            error: None,
+        }
+    }
+
+    fn mode_checked(&self, mode: &crate::models::representation::ContactMode) -> bool {
+        *mode == self.contact_mode
+    }
+}
+
+impl AccountGuardianTemplate {
+    pub fn new(base: BaseContext) -> Self {
+        Self {
+            app_name: base.app_name,
+            year: base.year,
+            version: base.version,
+            active_page: base.active_page,
+            user: base.user,
+            wards: Vec::new(),
+            success: None,
+        }
+    }
+}
+
+impl SsoLoginTemplate {
+    pub fn new(base: BaseContext) -> Self {
+        Self {
+            app_name: base.app_name,
+            year: base.year,
+            version: base.version,
+            active_page: base.active_page,
+            user: base.user,
+            error: None,
+            org: String::new(),
+            redirect_to: None,
+        }
+    }
+}
+
 impl SignupTemplate {
     pub fn new(base: BaseContext) -> Self {
         Self {
@@ -1325,8 +2062,12 @@ impl SignupTemplate {
             active_page: base.active_page,
             user: base.user,
             error: None,
+            errors: FieldErrors::new(),
+            prefill_username: None,
             prefill_email: None,
             redirect: None,
+            password_help: crate::services::password_policy::describe(crate::config::password_policy()),
+            password_min_length: crate::config::password_policy().min_length,
         }
     }
 }
@@ -1374,6 +2115,8 @@ impl ResetPasswordTemplate {
             success: None,
             email: None,
             code: None,
+            password_help: crate::services::password_policy::describe(crate::config::password_policy()),
+            password_min_length: crate::config::password_policy().min_length,
         }
     }
 }
@@ -1408,8 +2151,20 @@ impl PeopleTemplate {
             liked_ids: vec![],
             current_user_id: String::new(),
             has_more: false,
+            next_cursor: None,
+            hair_colors: vec![],
+            eye_colors: vec![],
+            body_types: vec![],
+            available_from: String::new(),
+            available_to: String::new(),
+            attribute_query: String::new(),
         }
     }
+
+    /// Whether a search or an attribute filter is applied
+    pub fn is_filtered(&self) -> bool {
+        self.filter.is_some() || !self.attribute_query.is_empty()
+    }
 }
 
 impl AboutTemplate {
@@ -1493,9 +2248,64 @@ impl AccountSettingsTemplate {
             username: String::new(),
             email: String::new(),
             messaging_preference: "anyone".to_string(),
+            units: "metric",
             show_contact_info: false,
+            visibility_options: Vec::new(),
+            search_history_enabled: true,
+            digest_enabled: false,
+            digest_days: Vec::new(),
+            digest_hours: Vec::new(),
+            whatsapp_available: crate::services::whatsapp::is_configured(),
+            whatsapp_enabled: false,
+            whatsapp_number: String::new(),
+            badge_image_url: None,
+            badge_frame_url: None,
+            profile_url: String::new(),
+            minor: Default::default(),
             error: None,
             success: None,
+            password_help: crate::services::password_policy::describe(crate::config::password_policy()),
+            password_min_length: crate::config::password_policy().min_length,
+        }
+    }
+}
+
+impl AccountSettingsTemplate {
+    /// Fill the profile visibility control from the saved setting
+    pub fn set_visibility(&mut self, visibility: &str) {
+        self.visibility_options = crate::services::search_visibility::VISIBILITIES
+            .iter()
+            .map(|(value, label)| SelectOption::new(value, label.to_string(), *value == visibility))
+            .collect();
+    }
+
+    /// Fill the weekly digest controls from the saved preference
+    pub fn set_digest(&mut self, pref: crate::services::digest::DigestPreference) {
+        self.digest_enabled = pref.enabled;
+        self.digest_days = crate::services::digest::DAYS
+            .iter()
+            .enumerate()
+            .map(|(i, day)| SelectOption::new(i, day.to_string(), i as u32 == pref.day))
+            .collect();
+        self.digest_hours = (0..24)
+            .map(|h| SelectOption::new(h, format!("{:02}:00 UTC", h), h as u32 == pref.hour))
+            .collect();
+    }
+
+    /// Fill the WhatsApp controls from the saved preference
+    pub fn set_whatsapp(&mut self, pref: crate::services::whatsapp::WhatsAppPreference) {
+        self.whatsapp_enabled = pref.enabled;
+        self.whatsapp_number = pref.number.map(|n| format!("+{}", n)).unwrap_or_default();
+    }
+
+    /// Fill the availability badge section from the saved token. Call once
+    /// `username` is set, for the profile link in the snippets.
+    pub fn set_badge(&mut self, token: Option<String>) {
+        self.profile_url = format!("{}/{}", crate::config::app_url(), self.username);
+        if let Some(token) = token {
+            let (image, frame) = crate::services::availability_badge::urls(&token);
+            self.badge_image_url = Some(image);
+            self.badge_frame_url = Some(frame);
         }
     }
 }
@@ -0,0 +1,224 @@
+//! Units of measure
+//!
+//! Heights are stored in millimetres (`profile.height_mm`) and weights in
+//! kilograms (`profile.weight_kg`). People choose whether they read them in
+//! metric or imperial (`person.units`); everything that shows or parses a
+//! height or weight goes through here so profiles, exports, search filters
+//! and the embedding text agree. Distances between map coordinates (locations
+//! and scouting basecamps) are worked out and shown here too.
+
+use regex::Regex;
+use std::sync::LazyLock;
+use surrealdb::types::RecordId;
+use tracing::warn;
+
+use crate::db::DB;
+use crate::error::Result;
+use crate::record_id_ext::RecordIdExt;
+
+const MM_PER_INCH: f64 = 25.4;
+const LBS_PER_KG: f64 = 2.204_62;
+const KM_PER_MILE: f64 = 1.609_344;
+pub const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnitSystem {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+impl UnitSystem {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "metric" => Some(UnitSystem::Metric),
+            "imperial" => Some(UnitSystem::Imperial),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnitSystem::Metric => "metric",
+            UnitSystem::Imperial => "imperial",
+        }
+    }
+
+    /// The system for a stored preference, metric when none is set
+    pub fn from_preference(preference: Option<&str>) -> Self {
+        preference.and_then(Self::parse).unwrap_or_default()
+    }
+}
+
+/// A person's stored unit preference, if they have chosen one
+pub async fn preference(person_id: &RecordId) -> Result<Option<UnitSystem>> {
+    let units: Option<String> = DB
+        .query("SELECT VALUE units FROM ONLY $id")
+        .bind(("id", person_id.clone()))
+        .await?
+        .take(0)?;
+    Ok(units.as_deref().and_then(UnitSystem::parse))
+}
+
+/// The viewer's preference for display: `None` for guests, for people who
+/// haven't chosen, and when it can't be read
+pub async fn viewer_preference(viewer_id: Option<&str>) -> Option<UnitSystem> {
+    let viewer_id = viewer_id?;
+    let id = if viewer_id.contains(':') {
+        RecordId::parse_simple(viewer_id).ok()?
+    } else {
+        RecordId::new("person", viewer_id)
+    };
+    match preference(&id).await {
+        Ok(units) => units,
+        Err(e) => {
+            warn!("Failed to read unit preference for {}: {}", id.display(), e);
+            None
+        }
+    }
+}
+
+// ============================
+// Conversions
+// ============================
+
+/// Millimetres to whole centimetres
+pub fn mm_to_cm(mm: i32) -> i32 {
+    (mm as f64 / 10.0).round() as i32
+}
+
+/// Millimetres to feet and inches, rounded to the nearest inch
+/// (1803 mm is 5'11", 1829 mm is 6'0")
+pub fn mm_to_feet_inches(mm: i32) -> (i32, i32) {
+    let inches = (mm as f64 / MM_PER_INCH).round() as i32;
+    (inches / 12, inches % 12)
+}
+
+pub fn feet_inches_to_mm(feet: i32, inches: i32) -> i32 {
+    ((feet * 12 + inches) as f64 * MM_PER_INCH).round() as i32
+}
+
+pub fn kg_to_lbs(kg: i32) -> i32 {
+    (kg as f64 * LBS_PER_KG).round() as i32
+}
+
+pub fn lbs_to_kg(lbs: f64) -> i32 {
+    (lbs / LBS_PER_KG).round() as i32
+}
+
+// ============================
+// Formatting
+// ============================
+
+/// "180 cm" or "5'11""
+pub fn height(mm: i32, system: UnitSystem) -> String {
+    match system {
+        UnitSystem::Metric => format!("{} cm", mm_to_cm(mm)),
+        UnitSystem::Imperial => {
+            let (feet, inches) = mm_to_feet_inches(mm);
+            format!("{}'{}\"", feet, inches)
+        }
+    }
+}
+
+/// Both units, the preferred one first: "180 cm (5'11")" or "5'11" (180 cm)"
+pub fn height_both(mm: i32, system: UnitSystem) -> String {
+    let other = match system {
+        UnitSystem::Metric => UnitSystem::Imperial,
+        UnitSystem::Imperial => UnitSystem::Metric,
+    };
+    format!("{} ({})", height(mm, system), height(mm, other))
+}
+
+/// "75 kg" or "165 lbs"
+pub fn weight(kg: i32, system: UnitSystem) -> String {
+    match system {
+        UnitSystem::Metric => format!("{} kg", kg),
+        UnitSystem::Imperial => format!("{} lbs", kg_to_lbs(kg)),
+    }
+}
+
+/// "4.2 km" or "2.6 mi"; whole numbers from ten up ("38 km")
+pub fn distance(km: f64, system: UnitSystem) -> String {
+    let (value, unit) = match system {
+        UnitSystem::Metric => (km, "km"),
+        UnitSystem::Imperial => (km / KM_PER_MILE, "mi"),
+    };
+    if value < 10.0 {
+        format!("{:.1} {}", value, unit)
+    } else {
+        format!("{:.0} {}", value, unit)
+    }
+}
+
+// ============================
+// Distances
+// ============================
+
+/// Straight-line (great-circle) distance in kilometres between two
+/// `(latitude, longitude)` points
+pub fn distance_km(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+}
+
+/// Coordinates written as "34.0522, -118.2437" (a space works as well as a
+/// comma). `None` when either number is missing or out of range.
+pub fn parse_coordinates(text: &str) -> Option<(f64, f64)> {
+    let mut parts = text
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty());
+    let latitude: f64 = parts.next()?.parse().ok()?;
+    let longitude: f64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some()
+        || !(-90.0..=90.0).contains(&latitude)
+        || !(-180.0..=180.0).contains(&longitude)
+    {
+        return None;
+    }
+    Some((latitude, longitude))
+}
+
+/// Coordinates as they're written in forms
+pub fn format_coordinates(point: (f64, f64)) -> String {
+    format!("{:.6}, {:.6}", point.0, point.1)
+}
+
+// ============================
+// Parsing
+// ============================
+
+/// A height with its unit, as written in a search query. Metric is "180cm",
+/// "180 cm" or "1.8m"; imperial is "5'11"", "5'11", "5 ft 11 inches" or "6ft".
+/// A bare "in" isn't taken for inches, so "5'11 in London" keeps its place.
+/// Only plausible adult heights are recognised, so "35cm" or "1990's" aren't.
+/// Captures: 1 metres, 2 centimetres, 3 feet, 4 inches.
+pub const HEIGHT_PATTERN: &str = r#"(?:\b([12]\.\d{1,2})\s*m(?:eters?|etres?)?\b|\b(\d{3})\s*(?:cm|centimet(?:er|re)s?)\b|\b([3-8])\s*(?:'|′|ft\b|feet\b|foot\b)(?:\s*(\d{1,2})(?:\s*(?:"|″|''|inch(?:es)?\b))?)?)"#;
+
+static HEIGHT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(&format!("(?i)^{}$", HEIGHT_PATTERN)).unwrap());
+
+/// Millimetres from the captures of a `HEIGHT_PATTERN` match that starts at
+/// capture group `first`
+pub fn height_from_captures(caps: &regex::Captures, first: usize) -> Option<i32> {
+    let group = |i: usize| caps.get(first + i).map(|m| m.as_str());
+    if let Some(metres) = group(0) {
+        let metres: f64 = metres.parse().ok()?;
+        return Some((metres * 1000.0).round() as i32);
+    }
+    if let Some(cm) = group(1) {
+        return cm.parse::<i32>().ok().map(|cm| cm * 10);
+    }
+    let feet: i32 = group(2)?.parse().ok()?;
+    let inches: i32 = group(3).map(|i| i.parse().unwrap_or(0)).unwrap_or(0);
+    (inches < 12).then(|| feet_inches_to_mm(feet, inches))
+}
+
+/// Millimetres from a height written in either system
+pub fn parse_height(text: &str) -> Option<i32> {
+    let caps = HEIGHT_RE.captures(text.trim())?;
+    height_from_captures(&caps, 1).filter(|mm| *mm > 0)
+}
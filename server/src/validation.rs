@@ -0,0 +1,143 @@
+//! Form validation with field-level errors
+//!
+//! A form implements [`Validate`] to check what was submitted. Each problem is
+//! recorded against the name of the input it concerns, so the form's template
+//! can show the message next to that input and the handler can re-render the
+//! form with a 422 (see `response::invalid_form`) rather than failing the whole
+//! request. Where there's no form to go back to, `FieldErrors` converts into a
+//! single `Error::Validation`.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::error::Error;
+
+static EMAIL_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap());
+
+/// A submitted form that can check itself
+pub trait Validate {
+    /// Everything wrong with the submission; empty if it can be saved
+    fn validate(&self) -> FieldErrors;
+}
+
+/// Messages keyed by the form field they belong to, in the order they were
+/// found
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldErrors {
+    errors: Vec<(String, String)>,
+}
+
+impl FieldErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a problem with `field`
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.errors.push((field.to_string(), message.into()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Whether `field` has a problem, for marking the input `aria-invalid`
+    pub fn has(&self, field: &str) -> bool {
+        self.errors.iter().any(|(f, _)| f == field)
+    }
+
+    /// The first message for `field`, shown inline under the input
+    pub fn get(&self, field: &str) -> Option<&str> {
+        self.errors
+            .iter()
+            .find(|(f, _)| f == field)
+            .map(|(_, m)| m.as_str())
+    }
+
+    /// Every message, for a summary above the form
+    pub fn messages(&self) -> Vec<&str> {
+        self.errors.iter().map(|(_, m)| m.as_str()).collect()
+    }
+
+    /// Ok if nothing was recorded
+    pub fn into_result(self) -> Result<(), Self> {
+        if self.is_empty() { Ok(()) } else { Err(self) }
+    }
+
+    /// `value` must not be blank
+    pub fn required(&mut self, field: &str, value: &str, label: &str) {
+        if value.trim().is_empty() {
+            self.add(field, format!("{} is required", label));
+        }
+    }
+
+    /// `value` must be at most `max` characters
+    pub fn max_chars(&mut self, field: &str, value: &str, max: usize, label: &str) {
+        if value.chars().count() > max {
+            self.add(
+                field,
+                format!("{} must be {} characters or fewer", label, max),
+            );
+        }
+    }
+
+    /// `value`, if given, must look like an email address
+    pub fn email(&mut self, field: &str, value: &str) {
+        let value = value.trim();
+        if !value.is_empty() && !EMAIL_RE.is_match(value) {
+            self.add(field, "Enter a valid email address");
+        }
+    }
+
+    /// `value`, if given, must be an http(s) link
+    pub fn url(&mut self, field: &str, value: &str) {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        let host = value
+            .strip_prefix("https://")
+            .or_else(|| value.strip_prefix("http://"))
+            .and_then(|rest| rest.split(['/', '?', '#']).next())
+            .unwrap_or("");
+        if !host.contains('.') || host.contains(char::is_whitespace) {
+            self.add(field, "Enter a full link starting with https://");
+        }
+    }
+
+    /// `value`, if given, must be a whole number between `min` and `max`
+    pub fn int_range(&mut self, field: &str, value: &str, min: i64, max: i64, label: &str) {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+        match value.parse::<i64>() {
+            Ok(n) if (min..=max).contains(&n) => {}
+            _ => self.add(
+                field,
+                format!("{} must be a number from {} to {}", label, min, max),
+            ),
+        }
+    }
+
+    /// Merge in a message from one of the model-level checks, such as
+    /// `validate_username`, against `field`
+    pub fn check(&mut self, field: &str, result: crate::error::Result<impl Sized>) {
+        if let Err(e) = result {
+            let message = e.public_message().unwrap_or_else(|| e.title().to_string());
+            self.add(field, message);
+        }
+    }
+}
+
+impl From<FieldErrors> for Error {
+    fn from(errors: FieldErrors) -> Self {
+        Error::Validation(errors.messages().join("; "))
+    }
+}
@@ -2,6 +2,7 @@
 pub struct UploadLimits {
     pub max_photos: Option<usize>,  // None = unlimited
     pub max_reels: Option<usize>,   // None = unlimited
+    pub max_audio_reels: Option<usize>, // None = unlimited
 }
 
 /// Returns the upload limits for a given verification status.
@@ -10,10 +11,12 @@ pub fn limits_for_status(verification_status: &str) -> UploadLimits {
         "identity" => UploadLimits {
             max_photos: Some(20),
             max_reels: None,
+            max_audio_reels: None,
         },
         _ => UploadLimits {
             max_photos: Some(3),
             max_reels: Some(3),
+            max_audio_reels: Some(3),
         },
     }
 }
@@ -0,0 +1,85 @@
+/**
+ * Partial Page Updates
+ * A region is an element with an id and data-fragment-src, the route that
+ * renders just that element (e.g. /search/results). Refreshing a region
+ * fetches the route and swaps the element for the response, so a page can
+ * update one section without a full reload:
+ *
+ *   - a link marked data-fragment-link inside a region loads its query
+ *     string into the region and becomes the page's URL
+ *   - a form with data-fragment-target="<region id>" does the same with its
+ *     fields, on submit and on change
+ *   - SlateHubFragments.refresh(id) reloads a region as it stands
+ *
+ * If a fetch fails, the browser falls back to loading the full page.
+ */
+
+(function () {
+    function region(id) {
+        const el = document.getElementById(id);
+        return el && el.dataset.fragmentSrc ? el : null;
+    }
+
+    function load(el, query, pageUrl) {
+        const url = el.dataset.fragmentSrc.split('?')[0] + query;
+        el.setAttribute('aria-busy', 'true');
+        return fetch(url, { headers: { Accept: 'text/html' }, credentials: 'same-origin' })
+            .then(function (res) {
+                if (!res.ok) throw new Error('HTTP ' + res.status);
+                return res.text();
+            })
+            .then(function (html) {
+                const next = document.createRange().createContextualFragment(html).firstElementChild;
+                if (!next) throw new Error('Empty fragment');
+                el.replaceWith(next);
+                if (pageUrl) history.replaceState(null, '', pageUrl);
+                next.dispatchEvent(new CustomEvent('fragment:loaded', { bubbles: true }));
+                return next;
+            })
+            .catch(function () {
+                if (pageUrl) window.location.href = pageUrl;
+                else el.removeAttribute('aria-busy');
+            });
+    }
+
+    function queryOf(src) {
+        const i = src.indexOf('?');
+        return i < 0 ? '' : src.slice(i);
+    }
+
+    document.addEventListener('click', function (e) {
+        const link = e.target.closest('a[data-fragment-link]');
+        if (!link || e.defaultPrevented || e.button !== 0) return;
+        if (e.metaKey || e.ctrlKey || e.shiftKey || e.altKey) return;
+        const el = link.closest('[data-fragment-src]');
+        if (!el || !el.id) return;
+        e.preventDefault();
+        load(el, new URL(link.href).search, link.href);
+    });
+
+    function submit(form) {
+        const el = region(form.dataset.fragmentTarget);
+        if (!el) return false;
+        const fields = new URLSearchParams(new FormData(form)).toString();
+        const query = fields ? '?' + fields : '';
+        load(el, query, (form.getAttribute('action') || window.location.pathname) + query);
+        return true;
+    }
+
+    document.addEventListener('submit', function (e) {
+        const form = e.target.closest('form[data-fragment-target]');
+        if (form && submit(form)) e.preventDefault();
+    });
+
+    document.addEventListener('change', function (e) {
+        const form = e.target.closest('form[data-fragment-target]');
+        if (form) submit(form);
+    });
+
+    window.SlateHubFragments = {
+        refresh: function (id) {
+            const el = region(id);
+            return el ? load(el, queryOf(el.dataset.fragmentSrc)) : Promise.resolve(null);
+        },
+    };
+})();
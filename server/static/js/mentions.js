@@ -0,0 +1,129 @@
+/**
+ * @mention Autocomplete
+ * Textareas with a data-mentions attribute (the /api/mentions URL for their
+ * production or organization) offer matching teammates while an @word is
+ * being typed. Arrow keys move through the list, Enter or Tab inserts the
+ * highlighted username, Escape closes it.
+ */
+
+(function () {
+    const DEBOUNCE_MS = 150;
+    const MENTION_AT_CARET = /(^|[^A-Za-z0-9_@.\/])@([A-Za-z0-9_.]*)$/;
+
+    function attach(textarea) {
+        const list = document.createElement('ul');
+        list.dataset.role = 'mention-suggestions';
+        list.setAttribute('role', 'listbox');
+        list.hidden = true;
+        textarea.insertAdjacentElement('afterend', list);
+        textarea.parentElement.dataset.mentionHost = '';
+
+        let timer = null;
+        let controller = null;
+        let active = -1;
+
+        function options() {
+            return Array.from(list.querySelectorAll('[role="option"]'));
+        }
+
+        function close() {
+            list.hidden = true;
+            list.replaceChildren();
+            active = -1;
+        }
+
+        function highlight(index) {
+            const items = options();
+            if (!items.length) return;
+            active = (index + items.length) % items.length;
+            items.forEach(function (item, i) {
+                item.setAttribute('aria-selected', i === active ? 'true' : 'false');
+            });
+        }
+
+        // The partial @name just before the caret, if one is being typed
+        function typed() {
+            const before = textarea.value.slice(0, textarea.selectionStart);
+            const match = before.match(MENTION_AT_CARET);
+            return match ? match[2] : null;
+        }
+
+        function insert(username) {
+            const caret = textarea.selectionStart;
+            const before = textarea.value.slice(0, caret).replace(/@[A-Za-z0-9_.]*$/, '@' + username + ' ');
+            textarea.value = before + textarea.value.slice(caret);
+            textarea.selectionStart = textarea.selectionEnd = before.length;
+            textarea.focus();
+            close();
+        }
+
+        function render(people) {
+            if (!people.length) {
+                close();
+                return;
+            }
+            list.replaceChildren();
+            people.forEach(function (person) {
+                const item = document.createElement('li');
+                item.setAttribute('role', 'option');
+                item.setAttribute('aria-selected', 'false');
+                const name = document.createElement('span');
+                name.dataset.role = 'mention-name';
+                name.textContent = person.name;
+                const username = document.createElement('span');
+                username.dataset.role = 'mention-username';
+                username.textContent = '@' + person.username;
+                item.append(name, username);
+                item.addEventListener('mousedown', function (e) {
+                    e.preventDefault();
+                    insert(person.username);
+                });
+                list.append(item);
+            });
+            highlight(0);
+            list.hidden = false;
+        }
+
+        function lookup() {
+            const q = typed();
+            if (q === null) {
+                close();
+                return;
+            }
+            if (controller) controller.abort();
+            controller = new AbortController();
+            fetch(textarea.dataset.mentions + '&q=' + encodeURIComponent(q), { signal: controller.signal })
+                .then(function (res) { return res.ok ? res.json() : { people: [] }; })
+                .then(function (data) {
+                    // Typing may have moved on while this was in flight
+                    if (typed() === q) render(data.people || []);
+                })
+                .catch(function () {});
+        }
+
+        textarea.addEventListener('input', function () {
+            clearTimeout(timer);
+            timer = setTimeout(lookup, DEBOUNCE_MS);
+        });
+
+        textarea.addEventListener('keydown', function (e) {
+            if (list.hidden) return;
+            if (e.key === 'ArrowDown') {
+                e.preventDefault();
+                highlight(active + 1);
+            } else if (e.key === 'ArrowUp') {
+                e.preventDefault();
+                highlight(active - 1);
+            } else if ((e.key === 'Enter' || e.key === 'Tab') && active >= 0) {
+                e.preventDefault();
+                insert(options()[active].querySelector('[data-role="mention-username"]').textContent.slice(1));
+            } else if (e.key === 'Escape') {
+                close();
+            }
+        });
+
+        textarea.addEventListener('blur', close);
+    }
+
+    document.querySelectorAll('textarea[data-mentions]').forEach(attach);
+})();
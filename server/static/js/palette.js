@@ -0,0 +1,153 @@
+/**
+ * Command Palette
+ * Cmd-K (Ctrl-K elsewhere) opens a box to jump anywhere from: pages,
+ * settings, your productions and recent chats, and search suggestions, all
+ * from /api/palette, which leaves out whatever the visitor can't open. Arrow
+ * keys move through the list, Enter opens the highlighted item (or runs a
+ * full search when none is), Escape closes it.
+ */
+
+(function () {
+    const DEBOUNCE_MS = 120;
+
+    let dialog = null;
+    let input = null;
+    let list = null;
+    let timer = null;
+    let controller = null;
+    let active = -1;
+
+    function options() {
+        return Array.from(list.querySelectorAll('[role="option"]'));
+    }
+
+    function highlight(index) {
+        const items = options();
+        if (!items.length) return;
+        active = (index + items.length) % items.length;
+        items.forEach(function (item, i) {
+            item.setAttribute('aria-selected', i === active ? 'true' : 'false');
+        });
+        items[active].scrollIntoView({ block: 'nearest' });
+        input.setAttribute('aria-activedescendant', items[active].id);
+    }
+
+    function render(items) {
+        list.replaceChildren();
+        input.removeAttribute('aria-activedescendant');
+        active = -1;
+        if (!items.length) {
+            const empty = document.createElement('li');
+            empty.dataset.role = 'palette-empty';
+            empty.textContent = 'Nothing matches';
+            list.append(empty);
+            return;
+        }
+        let group = null;
+        items.forEach(function (item, i) {
+            if (item.group !== group) {
+                group = item.group;
+                const heading = document.createElement('li');
+                heading.dataset.role = 'palette-group';
+                heading.setAttribute('role', 'presentation');
+                heading.textContent = group;
+                list.append(heading);
+            }
+            const option = document.createElement('li');
+            option.id = 'palette-option-' + i;
+            option.setAttribute('role', 'option');
+            option.setAttribute('aria-selected', 'false');
+            const link = document.createElement('a');
+            link.href = item.url;
+            link.tabIndex = -1;
+            const label = document.createElement('span');
+            label.dataset.role = 'palette-label';
+            label.textContent = item.label;
+            link.append(label);
+            if (item.hint) {
+                const hint = document.createElement('span');
+                hint.dataset.role = 'palette-hint';
+                hint.textContent = item.hint;
+                link.append(hint);
+            }
+            option.append(link);
+            list.append(option);
+        });
+    }
+
+    function lookup() {
+        const q = input.value.trim();
+        if (controller) controller.abort();
+        controller = new AbortController();
+        fetch('/api/palette?q=' + encodeURIComponent(q), {
+            signal: controller.signal,
+            credentials: 'same-origin',
+        })
+            .then(function (res) { return res.ok ? res.json() : { items: [] }; })
+            .then(function (data) {
+                // Typing may have moved on while this was in flight
+                if (input.value.trim() === q) render(data.items || []);
+            })
+            .catch(function () {});
+    }
+
+    function build() {
+        dialog = document.createElement('dialog');
+        dialog.dataset.component = 'palette';
+        dialog.setAttribute('aria-label', 'Jump to');
+        input = document.createElement('input');
+        input.type = 'search';
+        input.placeholder = 'Jump to a page, production, chat or person…';
+        input.autocomplete = 'off';
+        input.setAttribute('role', 'combobox');
+        input.setAttribute('aria-controls', 'palette-options');
+        input.setAttribute('aria-expanded', 'true');
+        list = document.createElement('ul');
+        list.id = 'palette-options';
+        list.setAttribute('role', 'listbox');
+        dialog.append(input, list);
+        document.body.append(dialog);
+
+        input.addEventListener('input', function () {
+            clearTimeout(timer);
+            timer = setTimeout(lookup, DEBOUNCE_MS);
+        });
+
+        input.addEventListener('keydown', function (e) {
+            if (e.key === 'ArrowDown') {
+                e.preventDefault();
+                highlight(active + 1);
+            } else if (e.key === 'ArrowUp') {
+                e.preventDefault();
+                highlight(active - 1);
+            } else if (e.key === 'Enter') {
+                e.preventDefault();
+                if (active >= 0) {
+                    window.location.href = options()[active].querySelector('a').href;
+                } else if (input.value.trim()) {
+                    window.location.href = '/search?q=' + encodeURIComponent(input.value.trim());
+                }
+            }
+        });
+
+        // A click on the backdrop lands on the dialog itself
+        dialog.addEventListener('click', function (e) {
+            if (e.target === dialog) dialog.close();
+        });
+    }
+
+    function open() {
+        if (!dialog) build();
+        if (dialog.open) return;
+        input.value = '';
+        dialog.showModal();
+        lookup();
+    }
+
+    document.addEventListener('keydown', function (e) {
+        if (e.key.toLowerCase() === 'k' && (e.metaKey || e.ctrlKey) && !e.altKey) {
+            e.preventDefault();
+            open();
+        }
+    });
+})();
@@ -0,0 +1,37 @@
+/**
+ * Reactions
+ * Reaction buttons post without leaving the page: the response is the
+ * updated bar, swapped in place. Without scripts the form posts normally and
+ * returns to the page. Other viewers' bars reload when the notification
+ * stream sends a reaction-update (see partials/header.html).
+ */
+
+(function () {
+    document.addEventListener('submit', function (e) {
+        const form = e.target.closest('form[data-role="reaction-toggle"]');
+        const bar = form && form.closest('[data-role="reactions"]');
+        if (!bar) return;
+        e.preventDefault();
+
+        const body = new URLSearchParams(new FormData(form));
+        // Without `next` the server answers with the bar instead of a redirect
+        body.delete('next');
+        fetch(form.action, {
+            method: 'POST',
+            body: body,
+            headers: { Accept: 'text/html' },
+            credentials: 'same-origin',
+        })
+            .then(function (res) {
+                if (!res.ok) throw new Error('HTTP ' + res.status);
+                return res.text();
+            })
+            .then(function (html) {
+                const next = document.createRange().createContextualFragment(html).firstElementChild;
+                if (next) bar.replaceWith(next);
+            })
+            .catch(function () {
+                form.submit();
+            });
+    });
+})();
@@ -0,0 +1,144 @@
+/**
+ * Resumable Uploads
+ * Sends a file to an upload session in chunks. If the connection drops it
+ * asks the server how much arrived and carries on from there, and the session
+ * is remembered so a reload of the page can resume it too.
+ */
+
+class ResumableUpload {
+    /**
+     * @param {File} file
+     * @param {object} options
+     * @param {function(File): Promise<{id: string, url: string, chunk_size: number}>} options.start
+     *     Starts a new upload session for the file
+     * @param {string} options.storageKey Where to remember the session between page loads
+     * @param {function(number): void} [options.onProgress] Called with 0–100
+     */
+    constructor(file, options) {
+        this.file = file;
+        this.start = options.start;
+        this.onProgress = options.onProgress || function () {};
+        this.storageKey = 'resumable-upload:' + options.storageKey + ':' + file.name + ':' + file.size + ':' + file.lastModified;
+        this.maxRetries = 5;
+        this.cancelled = false;
+    }
+
+    /** Upload the whole file, resolving with the session ID */
+    async upload() {
+        let session = await this.resume();
+        let offset = session ? session.offset : 0;
+        if (!session) {
+            session = await this.start(this.file);
+            this.remember(session);
+        }
+
+        let failures = 0;
+        while (offset < this.file.size) {
+            if (this.cancelled) throw new Error('Upload cancelled');
+            const chunk = this.file.slice(offset, offset + session.chunk_size);
+            try {
+                offset = await this.sendChunk(session.url, offset, chunk);
+                failures = 0;
+                this.onProgress(Math.round(offset / this.file.size * 100));
+            } catch (e) {
+                if (e.fatal || ++failures > this.maxRetries) throw e;
+                await this.wait(failures);
+                offset = await this.currentOffset(session.url);
+            }
+        }
+
+        this.forget();
+        return session.id;
+    }
+
+    cancel() {
+        this.cancelled = true;
+    }
+
+    async sendChunk(url, offset, chunk) {
+        let response;
+        try {
+            response = await fetch(url, {
+                method: 'PATCH',
+                headers: {
+                    'Content-Type': 'application/offset+octet-stream',
+                    'Upload-Offset': String(offset)
+                },
+                body: chunk
+            });
+        } catch (e) {
+            throw new Error('The connection dropped. Retrying…');
+        }
+        // A mismatched offset is recoverable: ask where to carry on from
+        if (response.status === 409) throw new Error('Upload out of step');
+        if (!response.ok) {
+            const error = new Error(await this.errorMessage(response));
+            error.fatal = response.status < 500;
+            throw error;
+        }
+        return Number(response.headers.get('Upload-Offset'));
+    }
+
+    async currentOffset(url) {
+        const response = await fetch(url, { method: 'HEAD' });
+        if (!response.ok) {
+            const error = new Error('The upload expired. Please start again.');
+            error.fatal = true;
+            this.forget();
+            throw error;
+        }
+        return Number(response.headers.get('Upload-Offset'));
+    }
+
+    /** A remembered session for this file that the server still has */
+    async resume() {
+        let session;
+        try {
+            session = JSON.parse(localStorage.getItem(this.storageKey));
+        } catch (e) {
+            session = null;
+        }
+        if (!session) return null;
+        try {
+            session.offset = await this.currentOffset(session.url);
+            return session;
+        } catch (e) {
+            return null;
+        }
+    }
+
+    remember(session) {
+        try {
+            localStorage.setItem(this.storageKey, JSON.stringify({
+                id: session.id,
+                url: session.url,
+                chunk_size: session.chunk_size
+            }));
+        } catch (e) {
+            // Private browsing: uploads still resume within the page
+        }
+    }
+
+    forget() {
+        try {
+            localStorage.removeItem(this.storageKey);
+        } catch (e) {
+            // Nothing stored
+        }
+    }
+
+    async errorMessage(response) {
+        try {
+            const data = await response.json();
+            return data.detail || data.error || 'Upload failed';
+        } catch (e) {
+            return 'Upload failed';
+        }
+    }
+
+    wait(attempt) {
+        return new Promise(resolve => setTimeout(resolve, Math.min(30000, 1000 * Math.pow(2, attempt - 1))));
+    }
+}
+
+window.ResumableUpload = ResumableUpload;
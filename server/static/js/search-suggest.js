@@ -0,0 +1,120 @@
+/**
+ * Navbar Search Suggestions
+ * Looks up people, organizations, locations and productions as the visitor
+ * types and lists them under the search box. Arrow keys move through the
+ * list, Enter opens the highlighted one (or runs a full search when none
+ * is), Escape closes it.
+ */
+
+(function () {
+    const form = document.getElementById('nav-search');
+    if (!form) return;
+    const input = form.querySelector('input[name="q"]');
+    const list = form.querySelector('[data-role="suggestions"]');
+
+    const DEBOUNCE_MS = 150;
+    const MIN_CHARS = 2;
+    const KIND_LABELS = {
+        person: 'Person',
+        organization: 'Org',
+        location: 'Location',
+        production: 'Production',
+    };
+
+    let timer = null;
+    let controller = null;
+    let active = -1;
+
+    function options() {
+        return Array.from(list.querySelectorAll('[role="option"]'));
+    }
+
+    function close() {
+        list.hidden = true;
+        list.replaceChildren();
+        input.setAttribute('aria-expanded', 'false');
+        input.removeAttribute('aria-activedescendant');
+        active = -1;
+    }
+
+    function highlight(index) {
+        const items = options();
+        if (!items.length) return;
+        active = (index + items.length) % items.length;
+        items.forEach(function (item, i) {
+            item.setAttribute('aria-selected', i === active ? 'true' : 'false');
+        });
+        input.setAttribute('aria-activedescendant', items[active].id);
+    }
+
+    function render(suggestions) {
+        if (!suggestions.length) {
+            close();
+            return;
+        }
+        list.replaceChildren();
+        suggestions.forEach(function (s, i) {
+            const item = document.createElement('li');
+            item.id = 'nav-search-option-' + i;
+            item.setAttribute('role', 'option');
+            item.setAttribute('aria-selected', 'false');
+            const link = document.createElement('a');
+            link.href = s.url;
+            link.tabIndex = -1;
+            const label = document.createElement('span');
+            label.dataset.role = 'suggestion-label';
+            label.textContent = s.label;
+            const kind = document.createElement('span');
+            kind.dataset.role = 'suggestion-kind';
+            kind.textContent = KIND_LABELS[s.kind] || s.kind;
+            link.append(label, kind);
+            item.append(link);
+            list.append(item);
+        });
+        active = -1;
+        list.hidden = false;
+        input.setAttribute('aria-expanded', 'true');
+    }
+
+    function lookup() {
+        const q = input.value.trim();
+        if (q.length < MIN_CHARS) {
+            close();
+            return;
+        }
+        if (controller) controller.abort();
+        controller = new AbortController();
+        fetch('/api/search/suggest?q=' + encodeURIComponent(q), { signal: controller.signal })
+            .then(function (res) { return res.ok ? res.json() : { suggestions: [] }; })
+            .then(function (data) {
+                // Typing may have moved on while this was in flight
+                if (input.value.trim() === q) render(data.suggestions || []);
+            })
+            .catch(function () {});
+    }
+
+    input.addEventListener('input', function () {
+        clearTimeout(timer);
+        timer = setTimeout(lookup, DEBOUNCE_MS);
+    });
+
+    input.addEventListener('keydown', function (e) {
+        if (list.hidden) return;
+        if (e.key === 'ArrowDown') {
+            e.preventDefault();
+            highlight(active + 1);
+        } else if (e.key === 'ArrowUp') {
+            e.preventDefault();
+            highlight(active - 1);
+        } else if (e.key === 'Enter' && active >= 0) {
+            e.preventDefault();
+            window.location.href = options()[active].querySelector('a').href;
+        } else if (e.key === 'Escape') {
+            close();
+        }
+    });
+
+    document.addEventListener('click', function (e) {
+        if (!form.contains(e.target)) close();
+    });
+})();
@@ -0,0 +1,32 @@
+/**
+ * Video Processing
+ * Placeholders for videos that are still being transcoded poll their status
+ * URL and reload the page once the renditions are ready (or transcoding gave
+ * up and the original will be played instead). Audio reels waiting on their
+ * waveform use the same attribute.
+ */
+
+(function () {
+    const POLL_MS = 10000;
+    const placeholders = document.querySelectorAll('[data-video-status]');
+    if (!placeholders.length) return;
+
+    async function poll() {
+        for (const el of placeholders) {
+            try {
+                const response = await fetch(el.dataset.videoStatus, { headers: { Accept: 'application/json' } });
+                if (!response.ok) continue;
+                const data = await response.json();
+                if (data.status !== 'queued' && data.status !== 'processing') {
+                    window.location.reload();
+                    return;
+                }
+            } catch (e) {
+                // Try again on the next round
+            }
+        }
+        setTimeout(poll, POLL_MS);
+    }
+
+    setTimeout(poll, POLL_MS);
+})();
@@ -0,0 +1,154 @@
+/**
+ * Waveform Players
+ * Draws an audio reel's waveform from the peaks the server worked out and
+ * uses it as a scrubber: click or drag to seek, arrow keys to step. Only one
+ * reel plays at a time.
+ */
+
+(function () {
+    const players = document.querySelectorAll('[data-role="waveform-player"]');
+    if (!players.length) return;
+
+    const BAR_WIDTH = 2;
+    const BAR_GAP = 1;
+    const STEP_SECONDS = 5;
+
+    function formatTime(seconds) {
+        if (!isFinite(seconds)) return '0:00';
+        const total = Math.round(seconds);
+        const minutes = Math.floor(total / 60);
+        const secs = total % 60;
+        return minutes + ':' + String(secs).padStart(2, '0');
+    }
+
+    /** Resample the peaks to one value per bar */
+    function barsFor(peaks, count) {
+        if (!peaks.length) return new Array(count).fill(0.05);
+        const bars = [];
+        for (let i = 0; i < count; i++) {
+            const start = Math.floor(i * peaks.length / count);
+            const end = Math.max(start + 1, Math.floor((i + 1) * peaks.length / count));
+            bars.push(Math.max.apply(null, peaks.slice(start, end)));
+        }
+        return bars;
+    }
+
+    function setup(player) {
+        const canvas = player.querySelector('[data-role="waveform"]');
+        const audio = player.querySelector('audio');
+        const button = player.querySelector('[data-action="toggle-audio"]');
+        const time = player.querySelector('[data-role="audio-time"]');
+        let peaks = [];
+        try {
+            peaks = JSON.parse(player.dataset.peaks || '[]');
+        } catch (e) {
+            peaks = [];
+        }
+
+        const styles = getComputedStyle(player);
+        const played = styles.getPropertyValue('--waveform-played').trim() || '#eb5437';
+        const unplayed = styles.getPropertyValue('--waveform-unplayed').trim() || 'rgba(214, 216, 202, 0.35)';
+
+        function progress() {
+            return audio.duration ? audio.currentTime / audio.duration : 0;
+        }
+
+        function draw() {
+            const ratio = window.devicePixelRatio || 1;
+            const width = canvas.clientWidth;
+            const height = canvas.clientHeight;
+            canvas.width = width * ratio;
+            canvas.height = height * ratio;
+            const ctx = canvas.getContext('2d');
+            ctx.scale(ratio, ratio);
+            ctx.clearRect(0, 0, width, height);
+
+            const count = Math.max(1, Math.floor(width / (BAR_WIDTH + BAR_GAP)));
+            const bars = barsFor(peaks, count);
+            const playedBars = progress() * count;
+            bars.forEach(function (peak, i) {
+                const barHeight = Math.max(1, peak * height);
+                ctx.fillStyle = i < playedBars ? played : unplayed;
+                ctx.fillRect(i * (BAR_WIDTH + BAR_GAP), (height - barHeight) / 2, BAR_WIDTH, barHeight);
+            });
+            canvas.setAttribute('aria-valuenow', String(Math.round(progress() * 100)));
+        }
+
+        function showTime() {
+            time.textContent = audio.currentTime > 0 || !audio.paused
+                ? formatTime(audio.currentTime)
+                : formatTime(audio.duration);
+        }
+
+        function seekTo(fraction) {
+            const seek = function () {
+                audio.currentTime = Math.min(Math.max(fraction, 0), 1) * audio.duration;
+                draw();
+                showTime();
+            };
+            if (audio.readyState >= 1) {
+                seek();
+            } else {
+                audio.preload = 'metadata';
+                audio.addEventListener('loadedmetadata', seek, { once: true });
+                audio.load();
+            }
+        }
+
+        function fractionAt(event) {
+            const rect = canvas.getBoundingClientRect();
+            return (event.clientX - rect.left) / rect.width;
+        }
+
+        button.addEventListener('click', function () {
+            if (audio.paused) {
+                document.querySelectorAll('[data-role="waveform-player"] audio').forEach(function (other) {
+                    if (other !== audio) other.pause();
+                });
+                audio.play();
+            } else {
+                audio.pause();
+            }
+        });
+
+        audio.addEventListener('play', function () {
+            button.querySelector('[data-role="icon-play"]').hidden = true;
+            button.querySelector('[data-role="icon-pause"]').hidden = false;
+        });
+        audio.addEventListener('pause', function () {
+            button.querySelector('[data-role="icon-play"]').hidden = false;
+            button.querySelector('[data-role="icon-pause"]').hidden = true;
+        });
+        audio.addEventListener('timeupdate', function () {
+            draw();
+            showTime();
+        });
+        audio.addEventListener('loadedmetadata', showTime);
+
+        let dragging = false;
+        canvas.addEventListener('pointerdown', function (event) {
+            dragging = true;
+            canvas.setPointerCapture(event.pointerId);
+            seekTo(fractionAt(event));
+        });
+        canvas.addEventListener('pointermove', function (event) {
+            if (dragging) seekTo(fractionAt(event));
+        });
+        canvas.addEventListener('pointerup', function () {
+            dragging = false;
+        });
+        canvas.addEventListener('keydown', function (event) {
+            if (!audio.duration) return;
+            if (event.key === 'ArrowRight' || event.key === 'ArrowLeft') {
+                event.preventDefault();
+                const step = event.key === 'ArrowRight' ? STEP_SECONDS : -STEP_SECONDS;
+                seekTo((audio.currentTime + step) / audio.duration);
+            }
+        });
+
+        window.addEventListener('resize', draw);
+        draw();
+    }
+
+    players.forEach(setup);
+})();
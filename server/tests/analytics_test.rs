@@ -0,0 +1,199 @@
+mod common;
+
+use slatehub::db::DB;
+use slatehub::models::analytics::AnalyticsModel;
+use slatehub::models::person::Person;
+use surrealdb::types::RecordId;
+
+async fn seed_person(username: &str) -> RecordId {
+    let id: Option<RecordId> = DB
+        .query(
+            "CREATE ONLY person CONTENT {
+                email: string::concat($username, '@example.com'),
+                password: 'hashed_password',
+                username: $username,
+                profile: { name: $username, skills: [], social_links: [], ethnicity: [], unions: [], languages: [], experience: [], education: [], reels: [], media_other: [], awards: [] }
+            } RETURN VALUE id",
+        )
+        .bind(("username", username.to_string()))
+        .await
+        .expect("Failed to create test person")
+        .take(0)
+        .expect("Failed to take person ID");
+    id.expect("No person record returned from CREATE")
+}
+
+async fn view(profile: &RecordId, viewer: Option<&RecordId>) {
+    AnalyticsModel::record_view(profile, viewer, Some("https://www.google.com/search"), None)
+        .await
+        .expect("Failed to record view");
+}
+
+fn clean_all() {
+    common::clean_table("profile_view");
+    common::clean_table("person");
+}
+
+#[test]
+fn test_views_are_recorded_with_their_viewer() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let profile = seed_person("profile").await;
+        let viewer = seed_person("viewer").await;
+
+        view(&profile, Some(&viewer)).await;
+        view(&profile, None).await;
+
+        assert_eq!(AnalyticsModel::get_total_views(&profile).await.unwrap(), 2);
+        let referrers = AnalyticsModel::get_referrer_breakdown(&profile)
+            .await
+            .unwrap();
+        assert_eq!(referrers.len(), 1);
+        assert_eq!(referrers[0].source, "google");
+        assert_eq!(referrers[0].count, 2);
+
+        // Signed-out views count, but only signed-in viewers are listed
+        let viewers = AnalyticsModel::get_recent_viewers(&profile, 30, 20)
+            .await
+            .unwrap();
+        assert_eq!(viewers.len(), 1);
+        assert_eq!(viewers[0].username, "viewer");
+        assert_eq!(
+            AnalyticsModel::get_viewer_count(&profile, 30)
+                .await
+                .unwrap(),
+            1
+        );
+    });
+}
+
+#[test]
+fn test_repeat_views_are_one_viewer() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let profile = seed_person("profile").await;
+        let viewer = seed_person("viewer").await;
+
+        for _ in 0..3 {
+            view(&profile, Some(&viewer)).await;
+        }
+
+        assert_eq!(AnalyticsModel::get_total_views(&profile).await.unwrap(), 3);
+        assert_eq!(
+            AnalyticsModel::get_viewer_count(&profile, 30)
+                .await
+                .unwrap(),
+            1
+        );
+        let viewers = AnalyticsModel::get_recent_viewers(&profile, 30, 20)
+            .await
+            .unwrap();
+        assert_eq!(viewers.len(), 1);
+        assert_eq!(viewers[0].views, 3);
+    });
+}
+
+#[test]
+fn test_anonymous_viewers_are_counted_but_not_named() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let profile = seed_person("profile").await;
+        let named = seed_person("named").await;
+        let anonymous = seed_person("anonymous").await;
+        AnalyticsModel::set_browse_anonymously(&anonymous, true)
+            .await
+            .unwrap();
+        assert!(
+            AnalyticsModel::browses_anonymously(&anonymous)
+                .await
+                .unwrap()
+        );
+        assert!(!AnalyticsModel::browses_anonymously(&named).await.unwrap());
+
+        view(&profile, Some(&named)).await;
+        view(&profile, Some(&anonymous)).await;
+
+        assert_eq!(
+            AnalyticsModel::get_anonymous_views(&profile, 30)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            AnalyticsModel::get_viewer_count(&profile, 30)
+                .await
+                .unwrap(),
+            2
+        );
+        let viewers = AnalyticsModel::get_recent_viewers(&profile, 30, 20)
+            .await
+            .unwrap();
+        let usernames: Vec<&str> = viewers.iter().map(|v| v.username.as_str()).collect();
+        assert_eq!(usernames, ["named"]);
+
+        // Once it is off again, new views are attributed
+        AnalyticsModel::set_browse_anonymously(&anonymous, false)
+            .await
+            .unwrap();
+        view(&profile, Some(&anonymous)).await;
+        assert_eq!(
+            AnalyticsModel::get_recent_viewers(&profile, 30, 20)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    });
+}
+
+#[test]
+fn test_only_pro_members_see_their_viewers() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let person = seed_person("member").await;
+        assert!(!Person::is_pro(&person).await);
+
+        DB.query("UPDATE $id SET plan = 'pro'")
+            .bind(("id", person.clone()))
+            .await
+            .expect("Failed to set plan");
+        assert!(Person::is_pro(&person).await);
+
+        // Unknown people are on the free plan
+        assert!(!Person::is_pro(&RecordId::new("person", "missing")).await);
+    });
+}
+
+#[test]
+fn test_forget_deletes_views_either_way() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let leaving = seed_person("leaving").await;
+        let staying = seed_person("staying").await;
+
+        view(&leaving, Some(&staying)).await;
+        view(&staying, Some(&leaving)).await;
+        view(&staying, None).await;
+
+        AnalyticsModel::forget(&leaving).await.unwrap();
+
+        assert_eq!(AnalyticsModel::get_total_views(&leaving).await.unwrap(), 0);
+        assert_eq!(AnalyticsModel::get_total_views(&staying).await.unwrap(), 1);
+        assert!(
+            AnalyticsModel::get_recent_viewers(&staying, 30, 20)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    });
+}
@@ -0,0 +1,176 @@
+use chrono::Utc;
+use slatehub::error::Error;
+use slatehub::models::audio_reel::{
+    AudioReel, AudioReelDetails, FileTag, MAX_TITLE_CHARS, audio_key, audio_type, compute_peaks,
+    format_duration, identified_tags, owns_audio_key, parse_instruments,
+};
+use surrealdb::types::RecordId;
+
+fn tag(name: &str, value: &str) -> FileTag {
+    FileTag {
+        name: name.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn reel(title: &str) -> AudioReel {
+    AudioReel {
+        id: RecordId::new("audio_reel", "a1"),
+        person: RecordId::new("person", "p1"),
+        title: title.to_string(),
+        description: None,
+        genre: None,
+        mood: None,
+        instruments: vec![],
+        transcript: None,
+        file_key: "profiles/p1/audio/01ABC.mp3".to_string(),
+        content_type: "audio/mpeg".to_string(),
+        size: 1024,
+        duration_seconds: None,
+        peaks: vec![],
+        file_tags: vec![],
+        status: "ready".to_string(),
+        attempts: 0,
+        error: None,
+        position: 0,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[test]
+fn peaks_are_normalised_per_slice() {
+    let samples = [0, 100, -200, 50, 0, 0, 400, -100];
+    assert_eq!(compute_peaks(&samples, 4), vec![0.25, 0.5, 0.0, 1.0]);
+}
+
+#[test]
+fn peaks_never_outnumber_samples() {
+    assert_eq!(compute_peaks(&[10, -20], 400), vec![0.5, 1.0]);
+    assert!(compute_peaks(&[], 400).is_empty());
+    assert!(compute_peaks(&[1, 2, 3], 0).is_empty());
+}
+
+#[test]
+fn silence_stays_flat() {
+    assert_eq!(compute_peaks(&[0; 8], 4), vec![0.0; 4]);
+}
+
+#[test]
+fn loudest_negative_sample_does_not_overflow() {
+    assert_eq!(compute_peaks(&[i16::MIN, i16::MAX], 2), vec![1.0, 1.0]);
+}
+
+#[test]
+fn durations_read_like_a_player() {
+    assert_eq!(format_duration(0.0), "0:00");
+    assert_eq!(format_duration(185.4), "3:05");
+    assert_eq!(format_duration(3723.0), "1:02:03");
+    assert_eq!(format_duration(-3.0), "0:00");
+}
+
+#[test]
+fn instruments_are_split_and_deduplicated() {
+    assert_eq!(
+        parse_instruments("Piano, cello,\nPiano , , modular synth"),
+        vec!["Piano", "cello", "modular synth"]
+    );
+    assert!(parse_instruments("  ").is_empty());
+}
+
+#[test]
+fn details_are_trimmed() {
+    let details = AudioReelDetails::parse(" Night Drive ", "", " Synthwave ", "  ", "synth, drums", "").unwrap();
+    assert_eq!(details.title, "Night Drive");
+    assert_eq!(details.description, None);
+    assert_eq!(details.genre.as_deref(), Some("Synthwave"));
+    assert_eq!(details.mood, None);
+    assert_eq!(details.instruments, vec!["synth", "drums"]);
+    assert_eq!(details.transcript, None);
+}
+
+#[test]
+fn details_need_a_title_of_sensible_length() {
+    assert!(matches!(
+        AudioReelDetails::parse("  ", "", "", "", "", ""),
+        Err(Error::Validation(_))
+    ));
+    let long = "a".repeat(MAX_TITLE_CHARS + 1);
+    assert!(matches!(
+        AudioReelDetails::parse(&long, "", "", "", "", ""),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn audio_types_come_from_the_extension() {
+    assert_eq!(audio_type("theme.MP3"), Some(("mp3", "audio/mpeg")));
+    assert_eq!(audio_type("stems.final.wav"), Some(("wav", "audio/wav")));
+    assert_eq!(audio_type("voice.m4a"), Some(("m4a", "audio/mp4")));
+    assert_eq!(audio_type("clip.mov"), None);
+    assert_eq!(audio_type("noextension"), None);
+}
+
+#[test]
+fn audio_keys_belong_to_their_person() {
+    let person = RecordId::new("person", "p1");
+    let key = audio_key(&person, "flac");
+    assert!(key.starts_with("profiles/p1/audio/"));
+    assert!(key.ends_with(".flac"));
+    assert!(owns_audio_key(&person, &key));
+
+    let other = RecordId::new("person", "p2");
+    assert!(!owns_audio_key(&other, &key));
+    assert!(!owns_audio_key(&person, "profiles/p1/audio/"));
+    assert!(!owns_audio_key(&person, "profiles/p1/audio/../photos/x.jpg"));
+    assert!(!owns_audio_key(&person, "profiles/p1/photos/x.jpg"));
+}
+
+#[test]
+fn only_known_tags_are_kept_in_order() {
+    let tags = vec![
+        ("encoder".to_string(), "Lavf60".to_string()),
+        ("genre".to_string(), "Ambient".to_string()),
+        ("title".to_string(), "Tide".to_string()),
+    ];
+    assert_eq!(identified_tags(&tags), vec![tag("title", "Tide"), tag("genre", "Ambient")]);
+}
+
+#[test]
+fn tags_have_readable_labels() {
+    assert_eq!(tag("album_artist", "x").label(), "Album artist");
+    assert_eq!(tag("custom", "x").label(), "custom");
+}
+
+#[test]
+fn embedding_line_covers_details_and_tags() {
+    let mut r = reel("Night Drive");
+    r.genre = Some("Synthwave".to_string());
+    r.mood = Some("Moody".to_string());
+    r.instruments = vec!["Juno-60".to_string(), "drum machine".to_string()];
+    r.duration_seconds = Some(185.0);
+    r.description = Some("Main title for a short film".to_string());
+    r.file_tags = vec![tag("title", "night drive"), tag("composer", "Sam Rivera")];
+    r.transcript = Some("la ".repeat(200));
+
+    let line = r.embedding_line();
+    assert!(line.starts_with("Night Drive; genre Synthwave; mood Moody; instruments Juno-60, drum machine"));
+    assert!(line.contains("length 3:05"));
+    assert!(line.contains("Main title for a short film"));
+    assert!(line.contains("composer Sam Rivera"));
+    // The title tag only repeats the title
+    assert!(!line.contains("title night drive"));
+    // Long transcripts are cut short
+    let words = line.split("words: ").nth(1).unwrap();
+    assert_eq!(words.chars().count(), 300);
+}
+
+#[test]
+fn pending_reels_are_still_processing() {
+    let mut r = reel("Tide");
+    assert!(!r.is_pending());
+    r.status = "queued".to_string();
+    assert!(r.is_pending());
+    r.status = "processing".to_string();
+    assert!(r.is_pending());
+}
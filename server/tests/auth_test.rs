@@ -1,4 +1,6 @@
-use slatehub::auth::{create_jwt, decode_jwt, hash_password, verify_password};
+use slatehub::auth::{
+    create_impersonation_jwt, create_jwt, decode_jwt, hash_password, verify_password,
+};
 
 #[test]
 fn test_password_hashing() {
@@ -25,4 +27,22 @@ fn test_jwt_creation_and_validation() {
     assert_eq!(claims.sub, user_id);
     assert_eq!(claims.username, username);
     assert_eq!(claims.email, email);
+    assert_eq!(claims.imp, None);
+}
+
+#[test]
+fn test_impersonation_jwt_names_its_session() {
+    let token = create_impersonation_jwt(
+        "person:test123",
+        "testuser",
+        "test@example.com",
+        "impersonation_session:abc",
+        3600,
+    )
+    .expect("Should create JWT");
+
+    let claims = decode_jwt(&token).expect("Should decode JWT");
+    assert_eq!(claims.sub, "person:test123");
+    assert_eq!(claims.imp.as_deref(), Some("impersonation_session:abc"));
+    assert_eq!(claims.exp - claims.iat, 3600);
 }
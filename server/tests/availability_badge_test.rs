@@ -0,0 +1,70 @@
+use chrono::NaiveDate;
+use slatehub::services::availability_badge::{BadgeStatus, render_svg};
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn booking(start: &str, end: &str) -> (String, String) {
+    (start.to_string(), end.to_string())
+}
+
+#[test]
+fn test_status_without_bookings_follows_profile() {
+    let today = date("2026-03-10");
+    assert_eq!(
+        BadgeStatus::from_calendar(None, &[], today),
+        BadgeStatus::Available
+    );
+    assert_eq!(
+        BadgeStatus::from_calendar(Some("available"), &[], today),
+        BadgeStatus::Available
+    );
+    assert_eq!(
+        BadgeStatus::from_calendar(Some("busy"), &[], today),
+        BadgeStatus::Busy
+    );
+    assert_eq!(
+        BadgeStatus::from_calendar(
+            Some("not_available"),
+            &[booking("2026-03-09", "2026-03-12")],
+            today
+        ),
+        BadgeStatus::NotAvailable
+    );
+}
+
+#[test]
+fn test_back_to_back_bookings_push_the_date() {
+    let today = date("2026-03-10");
+    let bookings = [
+        booking("2026-03-13", "2026-03-20"),
+        booking("2026-03-08", "2026-03-12"),
+        booking("2026-03-25", "2026-03-30"),
+    ];
+    let status = BadgeStatus::from_calendar(Some("available"), &bookings, today);
+    assert_eq!(status, BadgeStatus::AvailableFrom(date("2026-03-21")));
+    assert_eq!(status.label(), "Available from Mar 21, 2026");
+}
+
+#[test]
+fn test_future_bookings_leave_today_free() {
+    let today = date("2026-03-10");
+    let bookings = [
+        booking("2026-03-11", "2026-03-15"),
+        booking("bad", "2026-03-12"),
+    ];
+    assert_eq!(
+        BadgeStatus::from_calendar(None, &bookings, today),
+        BadgeStatus::Available
+    );
+}
+
+#[test]
+fn test_svg_escapes_the_name() {
+    let svg = render_svg("Ada <\"Lovelace\"> & co", &BadgeStatus::Available);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.contains("Ada &lt;&quot;Lovelace&quot;&gt; &amp; co"));
+    assert!(svg.contains("Available now"));
+    assert!(!svg.contains("<\"Lovelace"));
+}
@@ -0,0 +1,83 @@
+use slatehub::config::DatabaseConfig;
+use slatehub::services::backup::{MANIFEST_NAME, Manifest, ManifestObject, http_endpoint, read_manifest};
+use std::io::{Cursor, Write};
+
+fn db(host: &str) -> DatabaseConfig {
+    DatabaseConfig {
+        host: host.to_string(),
+        port: 8000,
+        username: "root".to_string(),
+        password: "root".to_string(),
+        namespace: "slatehub".to_string(),
+        name: "main".to_string(),
+    }
+}
+
+fn archive(entries: &[(&str, &[u8])]) -> zip::ZipArchive<Cursor<Vec<u8>>> {
+    let mut buffer = Vec::new();
+    {
+        let mut zip = zip::ZipWriter::new(Cursor::new(&mut buffer));
+        for (name, data) in entries {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+    zip::ZipArchive::new(Cursor::new(buffer)).unwrap()
+}
+
+#[test]
+fn test_http_endpoint_from_host_and_port() {
+    assert_eq!(http_endpoint(&db("surrealdb")), "http://surrealdb:8000");
+}
+
+#[test]
+fn test_manifest_round_trip() {
+    let manifest = Manifest {
+        format: 1,
+        created_at: "2026-10-16T00:00:00Z".to_string(),
+        app_version: "1.1.1".to_string(),
+        namespace: "slatehub".to_string(),
+        database: "main".to_string(),
+        bucket: "slatehub".to_string(),
+        files_included: false,
+        objects: vec![ManifestObject {
+            key: "profiles/alice/avatar.jpg".to_string(),
+            size: 1024,
+            etag: Some("abc".to_string()),
+            content_type: None,
+            sha256: None,
+        }],
+    };
+    let json = serde_json::to_vec(&manifest).unwrap();
+    let mut zip = archive(&[(MANIFEST_NAME, &json), ("db_export.surql", b"OPTION IMPORT;")]);
+
+    let read = read_manifest(&mut zip).unwrap();
+    assert!(!read.files_included);
+    assert_eq!(read.objects.len(), 1);
+    assert_eq!(read.objects[0].key, "profiles/alice/avatar.jpg");
+    assert_eq!(read.objects[0].etag.as_deref(), Some("abc"));
+}
+
+#[test]
+fn test_legacy_archive_lists_its_files() {
+    let mut zip = archive(&[
+        ("db_export.surql", b"OPTION IMPORT;"),
+        ("files/profiles/alice/avatar.jpg", b"jpeg"),
+        ("files/locations/studio/photo.png", b"png!"),
+    ]);
+
+    let manifest = read_manifest(&mut zip).unwrap();
+    assert_eq!(manifest.format, 0);
+    let mut keys: Vec<&str> = manifest.objects.iter().map(|o| o.key.as_str()).collect();
+    keys.sort();
+    assert_eq!(keys, ["locations/studio/photo.png", "profiles/alice/avatar.jpg"]);
+    assert!(manifest.objects.iter().all(|o| o.sha256.is_none() && o.size == 4));
+}
+
+#[test]
+fn test_newer_manifest_format_is_rejected() {
+    let json = br#"{"format":99,"created_at":"","app_version":"","namespace":"","database":"","bucket":"","files_included":true,"objects":[]}"#;
+    let mut zip = archive(&[(MANIFEST_NAME, json)]);
+    assert!(read_manifest(&mut zip).is_err());
+}
@@ -0,0 +1,156 @@
+use chrono::{NaiveDate, NaiveTime, Utc};
+use slatehub::error::Error;
+use slatehub::models::blackout::{
+    Blackout, MAX_LABEL_CHARS, clean_blackout, conflict_message, conflicts, shoot_hours,
+};
+use surrealdb::types::RecordId;
+
+fn blackout(
+    label: &str,
+    dates: (Option<&str>, Option<&str>),
+    times: Option<(&str, &str)>,
+) -> Blackout {
+    Blackout {
+        id: RecordId::new("location_blackout", label.to_lowercase().replace(' ', "_")),
+        location: RecordId::new("location", "diner"),
+        label: label.to_string(),
+        start_date: dates.0.map(str::to_string),
+        end_date: dates.1.map(str::to_string),
+        start_time: times.map(|t| t.0.to_string()),
+        end_time: times.map(|t| t.1.to_string()),
+        created_at: Utc::now(),
+    }
+}
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn hours(call: &str, wrap: &str) -> Option<(NaiveTime, NaiveTime)> {
+    shoot_hours(Some(call), Some(wrap))
+}
+
+#[test]
+fn test_clean_blackout() {
+    let data = clean_blackout(" Private event ", "2026-06-01", "2026-06-03", "", "").unwrap();
+    assert_eq!(data.label, "Private event");
+    assert_eq!(data.start_date.as_deref(), Some("2026-06-01"));
+    assert_eq!(data.end_date.as_deref(), Some("2026-06-03"));
+    assert_eq!(data.start_time, None);
+
+    let quiet = clean_blackout("Quiet hours", "", "", "22:00", "0700").unwrap();
+    assert_eq!(quiet.start_time.as_deref(), Some("22:00"));
+    assert_eq!(quiet.end_time.as_deref(), Some("07:00"));
+    assert_eq!(quiet.start_date, None);
+}
+
+#[test]
+fn test_clean_blackout_rejects() {
+    let invalid = |result: Result<_, Error>| matches!(result, Err(Error::Validation(_)));
+    assert!(invalid(clean_blackout("", "2026-06-01", "", "", "")));
+    assert!(invalid(clean_blackout(
+        &"x".repeat(MAX_LABEL_CHARS + 1),
+        "2026-06-01",
+        "",
+        "",
+        ""
+    )));
+    assert!(invalid(clean_blackout("Event", "June 1", "", "", "")));
+    assert!(invalid(clean_blackout(
+        "Event",
+        "2026-06-03",
+        "2026-06-01",
+        "",
+        ""
+    )));
+    assert!(invalid(clean_blackout("Quiet", "", "", "22:00", "")));
+    assert!(invalid(clean_blackout("Quiet", "", "", "22:00", "22:00")));
+    // Nothing to black out
+    assert!(invalid(clean_blackout("Always", "", "", "", "")));
+}
+
+#[test]
+fn test_whole_day_blackouts() {
+    let event = blackout(
+        "Private event",
+        (Some("2026-06-01"), Some("2026-06-03")),
+        None,
+    );
+    assert!(event.conflicts_with(date("2026-06-01"), None));
+    assert!(event.conflicts_with(date("2026-06-03"), hours("07:00", "19:00")));
+    assert!(!event.conflicts_with(date("2026-06-04"), None));
+    assert!(!event.conflicts_with(date("2026-05-31"), hours("07:00", "19:00")));
+    // A night shoot starting the evening before runs into it
+    assert!(event.conflicts_with(date("2026-05-31"), hours("18:00", "04:00")));
+
+    let open = blackout("Renovation", (Some("2026-07-01"), None), None);
+    assert!(open.conflicts_with(date("2027-01-15"), None));
+    assert!(!open.conflicts_with(date("2026-06-30"), hours("07:00", "19:00")));
+}
+
+#[test]
+fn test_quiet_hours() {
+    let quiet = blackout("Quiet hours", (None, None), Some(("22:00", "07:00")));
+    assert!(!quiet.conflicts_with(date("2026-06-01"), hours("08:00", "20:00")));
+    assert!(quiet.conflicts_with(date("2026-06-01"), hours("12:00", "23:00")));
+    // The early call falls in the previous night's quiet hours
+    assert!(quiet.conflicts_with(date("2026-06-01"), hours("06:00", "16:00")));
+    assert!(quiet.conflicts_with(date("2026-06-01"), hours("18:00", "02:00")));
+    // Without hours the day could run into them
+    assert!(quiet.conflicts_with(date("2026-06-01"), None));
+
+    let weekend = blackout(
+        "Market day",
+        (Some("2026-06-06"), Some("2026-06-06")),
+        Some(("06:00", "14:00")),
+    );
+    assert!(weekend.conflicts_with(date("2026-06-06"), hours("10:00", "18:00")));
+    assert!(!weekend.conflicts_with(date("2026-06-06"), hours("15:00", "23:00")));
+    assert!(!weekend.conflicts_with(date("2026-06-07"), hours("07:00", "19:00")));
+}
+
+#[test]
+fn test_descriptions() {
+    assert_eq!(
+        blackout("Quiet hours", (None, None), Some(("22:00", "07:00"))).describe(),
+        "Quiet hours (22:00-07:00 every day)"
+    );
+    assert_eq!(
+        blackout("Event", (Some("2026-06-01"), Some("2026-06-03")), None).when(),
+        "all day 2026-06-01 to 2026-06-03"
+    );
+    assert_eq!(
+        blackout("Event", (Some("2026-06-01"), Some("2026-06-01")), None).when(),
+        "all day on 2026-06-01"
+    );
+    assert!(blackout("Event", (None, Some("2026-06-01")), None).is_over(date("2026-06-02")));
+}
+
+#[test]
+fn test_conflict_messages() {
+    let blackouts = [
+        blackout("Quiet hours", (None, None), Some(("22:00", "07:00"))),
+        blackout(
+            "Private event",
+            (Some("2026-06-10"), Some("2026-06-10")),
+            None,
+        ),
+    ];
+
+    let found = conflicts(&blackouts, date("2026-06-01"), None);
+    assert_eq!(found.len(), 1);
+    let message = conflict_message("Rosie's Diner", "2026-06-01", &found, false);
+    assert_eq!(
+        message,
+        "Rosie's Diner isn't available on 2026-06-01: Quiet hours (22:00-07:00 every day). \
+         Set call and wrap times outside those hours to book it."
+    );
+
+    let found = conflicts(&blackouts, date("2026-06-10"), hours("08:00", "18:00"));
+    assert_eq!(found.len(), 1);
+    let message = conflict_message("Rosie's Diner", "2026-06-10", &found, true);
+    assert!(message.contains("Private event (all day on 2026-06-10)"));
+    assert!(!message.contains("call and wrap"));
+
+    assert!(conflicts(&blackouts, date("2026-06-11"), hours("08:00", "18:00")).is_empty());
+}
@@ -0,0 +1,241 @@
+mod common;
+
+use slatehub::db::DB;
+use slatehub::models::block::{BlockKind, BlockModel};
+use slatehub::models::notification::NotificationModel;
+use slatehub::models::person::SessionUser;
+use slatehub::record_id_ext::RecordIdExt;
+use slatehub::services::mentions;
+use slatehub::services::search_visibility::{VIEWER_FILTER, Viewer};
+use surrealdb::types::RecordId;
+
+async fn seed_person(username: &str) -> RecordId {
+    let id: Option<RecordId> = DB
+        .query(
+            "CREATE ONLY person CONTENT {
+                email: string::concat($username, '@example.com'),
+                password: 'hashed_password',
+                username: $username,
+                name: $username,
+                profile: { name: $username, skills: [], social_links: [], ethnicity: [], unions: [], languages: [], experience: [], education: [], reels: [], media_other: [], awards: [] }
+            } RETURN VALUE id",
+        )
+        .bind(("username", username.to_string()))
+        .await
+        .expect("Failed to create test person")
+        .take(0)
+        .expect("Failed to take person ID");
+    id.expect("No person record returned from CREATE")
+}
+
+/// A production everyone in `members` has joined
+async fn seed_production(members: &[&RecordId]) -> RecordId {
+    let id: Option<RecordId> = DB
+        .query(
+            "CREATE ONLY production CONTENT {
+                title: 'Night Shift', slug: 'night-shift', type: 'Film', status: 'Development'
+            } RETURN VALUE id",
+        )
+        .await
+        .expect("Failed to create test production")
+        .take(0)
+        .expect("Failed to take production ID");
+    let id = id.expect("No production record returned from CREATE");
+    for member in members {
+        DB.query("RELATE $person->member_of->$production SET role = 'member', invitation_status = 'accepted'")
+            .bind(("person", (*member).clone()))
+            .bind(("production", id.clone()))
+            .await
+            .expect("Failed to add member");
+    }
+    id
+}
+
+/// Usernames `person` finds in search, through the same filter as
+/// `search_people`
+async fn found_by(person: &RecordId) -> Vec<String> {
+    let viewer = Viewer::load(person).await.unwrap();
+    DB.query(format!(
+        "SELECT VALUE username FROM person WHERE {VIEWER_FILTER} ORDER BY username"
+    ))
+    .bind(("visibility_viewer", viewer.person))
+    .bind(("visibility_hidden", viewer.hidden))
+    .await
+    .unwrap()
+    .take(0)
+    .unwrap()
+}
+
+async fn set(person: &RecordId, target: &RecordId, kind: BlockKind, on: bool) {
+    BlockModel::set(person, target, kind, on).await.unwrap();
+}
+
+fn session_user(id: &RecordId, username: &str) -> SessionUser {
+    SessionUser {
+        id: id.to_raw_string(),
+        username: username.to_string(),
+        email: format!("{username}@example.com"),
+        name: username.to_string(),
+        impersonator: None,
+    }
+}
+
+async fn unread(person: &RecordId) -> u32 {
+    NotificationModel::new()
+        .get_unread_count(&person.to_raw_string())
+        .await
+        .unwrap()
+}
+
+fn clean_all() {
+    common::clean_table("blocks");
+    common::clean_table("mutes");
+    common::clean_table("notification");
+    common::clean_table("member_of");
+    common::clean_table("production");
+    common::clean_table("person");
+}
+
+#[test]
+fn test_blocks_hide_both_people_from_search_until_lifted() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let ana = seed_person("ana").await;
+        let ben = seed_person("ben").await;
+        let _cam = seed_person("cam").await;
+
+        set(&ana, &ben, BlockKind::Block, true).await;
+        assert_eq!(found_by(&ana).await, ["ana", "cam"]);
+        assert_eq!(found_by(&ben).await, ["ben", "cam"]);
+
+        set(&ana, &ben, BlockKind::Block, false).await;
+        assert_eq!(found_by(&ana).await, ["ana", "ben", "cam"]);
+        assert_eq!(found_by(&ben).await, ["ana", "ben", "cam"]);
+    });
+}
+
+#[test]
+fn test_mutes_only_hide_from_the_muter() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let ana = seed_person("ana").await;
+        let ben = seed_person("ben").await;
+
+        set(&ana, &ben, BlockKind::Mute, true).await;
+        assert_eq!(found_by(&ana).await, ["ana"]);
+        assert_eq!(found_by(&ben).await, ["ana", "ben"]);
+
+        set(&ana, &ben, BlockKind::Mute, false).await;
+        assert_eq!(found_by(&ana).await, ["ana", "ben"]);
+    });
+}
+
+#[test]
+fn test_blocks_stop_messages_both_ways_until_lifted() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let ana = seed_person("ana").await;
+        let ben = seed_person("ben").await;
+
+        set(&ana, &ben, BlockKind::Block, true).await;
+        assert!(BlockModel::is_blocked_between(&ana, &ben).await.unwrap());
+        assert!(BlockModel::is_blocked_between(&ben, &ana).await.unwrap());
+        assert!(BlockModel::has_blocked(&ana, &ben).await.unwrap());
+        assert!(!BlockModel::has_blocked(&ben, &ana).await.unwrap());
+
+        // Blocking twice is a no-op, and one unblock lifts it
+        set(&ana, &ben, BlockKind::Block, true).await;
+        set(&ana, &ben, BlockKind::Block, false).await;
+        assert!(!BlockModel::is_blocked_between(&ana, &ben).await.unwrap());
+        assert!(!BlockModel::is_blocked_between(&ben, &ana).await.unwrap());
+    });
+}
+
+#[test]
+fn test_mutes_let_messages_through() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let ana = seed_person("ana").await;
+        let ben = seed_person("ben").await;
+
+        set(&ana, &ben, BlockKind::Mute, true).await;
+        assert!(BlockModel::has_muted(&ana, &ben).await.unwrap());
+        assert!(!BlockModel::has_muted(&ben, &ana).await.unwrap());
+        assert!(!BlockModel::is_blocked_between(&ana, &ben).await.unwrap());
+    });
+}
+
+#[test]
+fn test_muted_people_dont_notify_until_unmuted() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let ana = seed_person("ana").await;
+        let ben = seed_person("ben").await;
+        let production = seed_production(&[&ana, &ben]).await;
+        let mention = |text: &'static str| {
+            let production = production.clone();
+            let ben = session_user(&ben, "ben");
+            async move {
+                mentions::notify(
+                    &production,
+                    &["ana".to_string()],
+                    &ben,
+                    "in the Day 3 report for Night Shift",
+                    text,
+                    "/productions/night-shift",
+                )
+                .await
+            }
+        };
+
+        mention("@ana can you check this?").await;
+        assert_eq!(unread(&ana).await, 1);
+
+        set(&ana, &ben, BlockKind::Mute, true).await;
+        mention("@ana again").await;
+        assert_eq!(unread(&ana).await, 1);
+
+        set(&ana, &ben, BlockKind::Mute, false).await;
+        mention("@ana and once more").await;
+        assert_eq!(unread(&ana).await, 2);
+    });
+}
+
+#[test]
+fn test_blocked_and_muted_people_are_listed() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let ana = seed_person("ana").await;
+        let ben = seed_person("ben").await;
+        let cam = seed_person("cam").await;
+
+        set(&ana, &ben, BlockKind::Block, true).await;
+        set(&ana, &cam, BlockKind::Mute, true).await;
+
+        let blocked = BlockModel::list(&ana, BlockKind::Block).await.unwrap();
+        let muted = BlockModel::list(&ana, BlockKind::Mute).await.unwrap();
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].username, "ben");
+        assert_eq!(muted.len(), 1);
+        assert_eq!(muted[0].username, "cam");
+
+        // People can't block themselves
+        assert!(
+            BlockModel::set(&ana, &ana, BlockKind::Block, true)
+                .await
+                .is_err()
+        );
+    });
+}
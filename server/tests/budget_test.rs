@@ -0,0 +1,64 @@
+use chrono::Utc;
+use slatehub::error::Error;
+use slatehub::models::budget::{
+    BudgetLine, BudgetLineData, MAX_DESCRIPTION_CHARS, category_label, parse_amount, totals,
+};
+use surrealdb::types::RecordId;
+
+fn line(category: &str, amount: f64) -> BudgetLine {
+    BudgetLine {
+        id: RecordId::new("budget_line", format!("{}_{}", category, amount)),
+        category: category.to_string(),
+        description: "Line".to_string(),
+        amount,
+        vendor_name: None,
+        vendor_slug: None,
+        quote: None,
+        created_at: Utc::now(),
+    }
+}
+
+#[test]
+fn test_parse_amount() {
+    assert_eq!(parse_amount("1250").unwrap(), 1250.0);
+    assert_eq!(parse_amount(" $12,500.75 ").unwrap(), 12500.75);
+    assert!(parse_amount("0").is_err());
+    assert!(parse_amount("-5").is_err());
+    assert!(parse_amount("lots").is_err());
+}
+
+#[test]
+fn test_budget_line_data() {
+    let data = BudgetLineData::parse("travel", " Flights to Vancouver ", "2,400").unwrap();
+    assert_eq!(data.category, "travel");
+    assert_eq!(data.description, "Flights to Vancouver");
+    assert_eq!(data.amount, 2400.0);
+
+    let invalid = |result: Result<_, Error>| matches!(result, Err(Error::Validation(_)));
+    assert!(invalid(BudgetLineData::parse("catering", "Lunch", "300")));
+    assert!(invalid(BudgetLineData::parse("other", " ", "300")));
+    assert!(invalid(BudgetLineData::parse(
+        "other",
+        &"x".repeat(MAX_DESCRIPTION_CHARS + 1),
+        "300"
+    )));
+    assert!(invalid(BudgetLineData::parse("other", "Lunch", "")));
+}
+
+#[test]
+fn test_totals() {
+    let lines = [
+        line("travel", 400.0),
+        line("equipment", 1250.0),
+        line("equipment", 750.5),
+    ];
+    let (total, by_category) = totals(&lines);
+    assert_eq!(total, 2400.5);
+    assert_eq!(
+        by_category,
+        [("Equipment rental", 2000.5), ("Travel", 400.0)]
+    );
+    assert_eq!(totals(&[]), (0.0, Vec::new()));
+    assert_eq!(category_label("post"), "Post-production");
+    assert_eq!(lines[1].amount_label(), "$1,250.00");
+}
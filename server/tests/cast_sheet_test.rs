@@ -0,0 +1,57 @@
+use image::DynamicImage;
+use slatehub::models::cast_sheet::{COLUMNS, CastSheetEntry, cast_sheet_pdf, initials};
+use slatehub::pdf::PageSize;
+
+fn as_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn entry(name: &str, role: &str, phone: Option<&str>, headshot: bool) -> CastSheetEntry {
+    CastSheetEntry {
+        name: name.to_string(),
+        role: Some(role.to_string()),
+        phone: phone.map(str::to_string),
+        headshot: headshot.then(|| DynamicImage::new_rgb8(8, 10)),
+    }
+}
+
+#[test]
+fn test_initials() {
+    assert_eq!(initials("Alex Morgan"), "AM");
+    assert_eq!(initials("jo"), "J");
+    assert_eq!(initials("Mary Ann de la Cruz"), "MA");
+    assert_eq!(initials("  "), "");
+}
+
+#[test]
+fn test_cast_sheet_pdf() {
+    let entries = vec![
+        entry("Alex Morgan", "Detective Shaw", Some("555-0100"), true),
+        entry("Sam Lee", "Young Shaw", None, false),
+    ];
+    let pdf = as_text(&cast_sheet_pdf("Harbor Lights", &entries, PageSize::LETTER));
+    assert!(pdf.starts_with("%PDF-"));
+    assert!(pdf.contains("(Harbor Lights - Cast) Tj"));
+    assert!(pdf.contains("(Alex Morgan) Tj"));
+    assert!(pdf.contains("(Detective Shaw) Tj"));
+    assert!(pdf.contains("(555-0100) Tj"));
+    // No headshot, so the cell shows initials
+    assert!(pdf.contains("(SL) Tj"));
+    assert_eq!(pdf.matches("/Filter /DCTDecode").count(), 1);
+}
+
+#[test]
+fn test_cast_sheet_pdf_rows() {
+    let entries: Vec<_> = (0..COLUMNS * 3)
+        .map(|i| entry(&format!("Actor {}", i), "Extra", None, true))
+        .collect();
+    let pdf = as_text(&cast_sheet_pdf("Harbor Lights", &entries, PageSize::A4));
+    assert_eq!(pdf.matches("/Filter /DCTDecode").count(), COLUMNS * 3);
+    assert!(pdf.contains(&format!("(Actor {}) Tj", COLUMNS * 3 - 1)));
+}
+
+#[test]
+fn test_cast_sheet_pdf_empty() {
+    let pdf = as_text(&cast_sheet_pdf("Harbor Lights", &[], PageSize::LETTER));
+    assert!(pdf.contains("(No cast has been credited yet.) Tj"));
+}
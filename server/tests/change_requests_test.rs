@@ -0,0 +1,122 @@
+use slatehub::error::Error;
+use slatehub::services::change_requests::{
+    MAX_NAME_LENGTH, Proposal, check, clean_name, dataset, describe,
+};
+
+fn proposal(dataset: &str, action: &str) -> Proposal {
+    Proposal {
+        dataset: dataset.to_string(),
+        action: action.to_string(),
+        reason: "It's a common credit".to_string(),
+        ..Default::default()
+    }
+}
+
+fn validation_error(proposal: &Proposal) -> String {
+    match check(proposal) {
+        Err(Error::Validation(msg)) => msg,
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn names_have_their_whitespace_tidied() {
+    assert_eq!(
+        clean_name("  Drone   Operator \n").as_deref(),
+        Some("Drone Operator")
+    );
+    assert_eq!(clean_name("   "), None);
+}
+
+#[test]
+fn datasets_map_to_their_tables() {
+    assert_eq!(dataset("skill").unwrap().table, "role");
+    assert_eq!(
+        dataset("vendor_category").unwrap().table,
+        "organization_type"
+    );
+    assert!(dataset("person").is_none());
+}
+
+#[test]
+fn adding_needs_a_name_but_no_target() {
+    let mut add = proposal("skill", "add");
+    add.target = "role:gaffer".to_string();
+    assert!(validation_error(&add).contains("new name"));
+
+    add.name = " Drone  Operator ".to_string();
+    let checked = check(&add).unwrap();
+    assert_eq!(checked.target, None);
+    assert_eq!(checked.name.as_deref(), Some("Drone Operator"));
+}
+
+#[test]
+fn renaming_and_removing_need_a_target() {
+    let mut rename = proposal("vendor_category", "rename");
+    rename.name = "Catering".to_string();
+    assert!(validation_error(&rename).contains("vendor category to rename"));
+    rename.target = "catering_company".to_string();
+    assert_eq!(
+        check(&rename).unwrap().target.as_deref(),
+        Some("catering_company")
+    );
+
+    let mut remove = proposal("skill", "remove");
+    remove.name = "ignored".to_string();
+    remove.target = "boom_op".to_string();
+    assert_eq!(check(&remove).unwrap().name, None);
+}
+
+#[test]
+fn department_only_applies_to_new_skills() {
+    let mut add = proposal("skill", "add");
+    add.name = "Drone Operator".to_string();
+    add.department = "camera".to_string();
+    assert_eq!(check(&add).unwrap().department.as_deref(), Some("camera"));
+
+    let mut vendor = proposal("vendor_category", "add");
+    vendor.name = "Drone Services".to_string();
+    vendor.department = "camera".to_string();
+    assert_eq!(check(&vendor).unwrap().department, None);
+}
+
+#[test]
+fn unknown_lists_actions_and_missing_reasons_are_refused() {
+    let mut bad = proposal("person", "add");
+    bad.name = "Someone".to_string();
+    assert!(check(&bad).is_err());
+
+    let mut bad = proposal("skill", "merge");
+    bad.name = "Someone".to_string();
+    assert!(check(&bad).is_err());
+
+    let mut add = proposal("skill", "add");
+    add.name = "Drone Operator".to_string();
+    add.reason = "   ".to_string();
+    assert!(validation_error(&add).contains("why"));
+}
+
+#[test]
+fn long_names_are_refused() {
+    let mut add = proposal("skill", "add");
+    add.name = "x".repeat(MAX_NAME_LENGTH + 1);
+    assert!(validation_error(&add).contains("characters"));
+}
+
+#[test]
+fn changes_are_described_for_reviewers() {
+    let skill = dataset("skill").unwrap();
+    assert_eq!(
+        describe(skill, "add", None, Some("Drone Operator")),
+        "Add skill “Drone Operator”"
+    );
+    let vendor = dataset("vendor_category").unwrap();
+    assert_eq!(
+        describe(vendor, "rename", Some("Catering Company"), Some("Catering")),
+        "Rename vendor category “Catering Company” to “Catering”"
+    );
+    assert_eq!(
+        describe(skill, "remove", Some("Boom Op"), None),
+        "Remove skill “Boom Op”"
+    );
+}
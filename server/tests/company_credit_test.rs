@@ -0,0 +1,32 @@
+use slatehub::models::company_credit::{COMPANY_ROLES, company_line, role_label, slate_line};
+
+#[test]
+fn test_role_labels() {
+    assert_eq!(role_label("producer"), "Producer");
+    assert_eq!(role_label("financier"), "Financier");
+    assert_eq!(role_label("vendor"), "Vendor");
+    assert_eq!(role_label("caterer"), "Company");
+    assert_eq!(COMPANY_ROLES.len(), 3);
+}
+
+#[test]
+fn test_slate_line() {
+    assert_eq!(slate_line("producer", "Harbor Lights", None), "producer of Harbor Lights");
+    assert_eq!(slate_line("financier", "Harbor Lights", Some("")), "financier of Harbor Lights");
+    assert_eq!(
+        slate_line("vendor", "Harbor Lights", Some("camera package")),
+        "vendor on Harbor Lights (camera package)"
+    );
+}
+
+#[test]
+fn test_company_line() {
+    assert_eq!(
+        company_line("Northlight Pictures", "producer", None),
+        "Northlight Pictures (producer)"
+    );
+    assert_eq!(
+        company_line("Lens House", "vendor", Some("camera package")),
+        "Lens House (vendor: camera package)"
+    );
+}
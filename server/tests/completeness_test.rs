@@ -0,0 +1,85 @@
+use slatehub::models::completeness::{CREDITS_WANTED, completeness};
+use slatehub::models::person::{Photo, Profile, Reel, SocialLink};
+
+fn full_profile() -> Profile {
+    Profile {
+        avatar: Some("/api/media/avatars/alex.jpg".to_string()),
+        headline: Some("Actor & Voice Artist".to_string()),
+        bio: Some(
+            "Stage-trained actor with ten years of screen work, from indie features to \
+             network procedurals."
+                .to_string(),
+        ),
+        location: Some("Atlanta, GA".to_string()),
+        skills: vec![
+            "Stage combat".to_string(),
+            "Horse riding".to_string(),
+            "Dialects".to_string(),
+        ],
+        reels: vec![Reel {
+            url: "https://vimeo.com/1".to_string(),
+            title: "Drama reel".to_string(),
+            platform: "vimeo".to_string(),
+            video_id: "1".to_string(),
+        }],
+        photos: vec![Photo {
+            url: "/api/media/photos/1.jpg".to_string(),
+            thumbnail_url: "/api/media/photos/1_thumb.jpg".to_string(),
+            caption: String::new(),
+        }],
+        social_links: vec![SocialLink {
+            platform: "instagram".to_string(),
+            url: "https://instagram.com/alex".to_string(),
+        }],
+        ..Profile::default()
+    }
+}
+
+#[test]
+fn test_empty_profile() {
+    let result = completeness(None, 0);
+    assert_eq!(result.score, 0);
+    assert!(!result.is_complete());
+    assert_eq!(result.remaining().len(), result.items.len());
+    assert_eq!(result.items.iter().map(|i| i.points).sum::<u32>(), 100);
+    // Biggest wins first
+    assert_eq!(result.remaining()[0].points, 20);
+}
+
+#[test]
+fn test_full_profile() {
+    let result = completeness(Some(&full_profile()), CREDITS_WANTED + 2);
+    assert_eq!(result.score, 100);
+    assert!(result.is_complete());
+    assert!(result.remaining().is_empty());
+}
+
+#[test]
+fn test_partial_credits() {
+    let profile = full_profile();
+    let none = completeness(Some(&profile), 0);
+    let one = completeness(Some(&profile), 1);
+    let two = completeness(Some(&profile), 2);
+    assert!(none.score < one.score && one.score < two.score && two.score < 100);
+
+    let labels = |c: &slatehub::models::completeness::Completeness| {
+        c.remaining()
+            .iter()
+            .map(|i| i.label.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(labels(&none), vec!["Add 3 credits"]);
+    assert_eq!(labels(&one), vec!["Add 2 more credits"]);
+    assert_eq!(labels(&two), vec!["Add 1 more credit"]);
+}
+
+#[test]
+fn test_blank_fields_do_not_count() {
+    let mut profile = full_profile();
+    profile.headline = Some("   ".to_string());
+    profile.bio = Some("Actor.".to_string());
+    profile.skills = vec!["Dialects".to_string(), String::new(), " ".to_string()];
+    let result = completeness(Some(&profile), CREDITS_WANTED);
+    assert_eq!(result.score, 100 - 10 - 15 - 10);
+    assert_eq!(result.remaining().len(), 3);
+}
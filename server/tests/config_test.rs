@@ -1,4 +1,4 @@
-use slatehub::config::{DatabaseConfig, ServerConfig};
+use slatehub::config::{DatabaseConfig, SearchConfig, SearchRerank, SearchWeights, ServerConfig};
 
 #[test]
 fn test_database_connection_url() {
@@ -24,3 +24,75 @@ fn test_server_socket_addr() {
     let addr = config.socket_addr().unwrap();
     assert_eq!(addr.to_string(), "127.0.0.1:3000");
 }
+
+#[test]
+fn test_search_weights_fall_back_to_defaults() {
+    let defaults = SearchWeights {
+        vector_threshold: 0.6,
+        max_candidates: 200,
+        ..SearchWeights::DEFAULT
+    };
+    let weights = SearchWeights::from_prefix("SLATEHUB_TEST_UNSET", &defaults);
+    assert_eq!(weights.name_match, 50);
+    assert!((weights.vector_threshold - 0.6).abs() < 1e-9);
+    assert_eq!(weights.max_candidates, 200);
+}
+
+#[test]
+fn test_search_candidate_window() {
+    assert_eq!(SearchWeights::DEFAULT.candidate_window(120), 120);
+
+    let capped = SearchWeights {
+        max_candidates: 100,
+        ..SearchWeights::DEFAULT
+    };
+    assert_eq!(capped.candidate_window(120), 100);
+    assert_eq!(capped.candidate_window(30), 30);
+}
+
+#[test]
+fn test_search_timeout() {
+    assert_eq!(
+        SearchWeights::DEFAULT.timeout(),
+        Some(std::time::Duration::from_millis(3000))
+    );
+
+    let unlimited = SearchWeights {
+        timeout_ms: 0,
+        ..SearchWeights::DEFAULT
+    };
+    assert_eq!(unlimited.timeout(), None);
+}
+
+#[test]
+fn test_search_config_per_type() {
+    let config = SearchConfig::uniform(&SearchWeights::DEFAULT).map(|w| SearchWeights {
+        vector_threshold: w.vector_threshold - 0.1,
+        ..w.clone()
+    });
+    assert!((config.jobs.vector_threshold - 0.65).abs() < 1e-9);
+
+    let mut config = config;
+    config.people.vector_threshold = 0.8;
+    let people = config.for_table("person").unwrap();
+    assert!((people.vector_threshold - 0.8).abs() < 1e-9);
+    assert!(config.for_table("job_posting").is_some());
+    assert!(config.for_table("equipment").is_none());
+}
+
+#[test]
+fn test_rerank_only_long_queries() {
+    let rerank = SearchRerank {
+        enabled: true,
+        min_words: 4,
+    };
+    assert!(rerank.applies_to("gaffer who has lit night exteriors"));
+    assert!(!rerank.applies_to("gaffer"));
+    assert!(!rerank.applies_to("  night   exterior  gaffer "));
+
+    let off = SearchRerank {
+        enabled: false,
+        ..rerank
+    };
+    assert!(!off.applies_to("gaffer who has lit night exteriors"));
+}
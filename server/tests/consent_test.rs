@@ -0,0 +1,35 @@
+use slatehub::config::LegalVersions;
+use slatehub::services::consent::{is_exempt_path, outstanding_documents};
+
+fn versions() -> LegalVersions {
+    LegalVersions {
+        terms: "2026-03".to_string(),
+        privacy: "2026-01".to_string(),
+    }
+}
+
+fn accepted(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs.iter().map(|(d, v)| (d.to_string(), v.to_string())).collect()
+}
+
+#[test]
+fn test_outstanding_documents() {
+    assert_eq!(outstanding_documents(&[], &versions()), vec!["terms", "privacy"]);
+
+    let current = accepted(&[("terms", "2026-03"), ("privacy", "2026-01")]);
+    assert!(outstanding_documents(&current, &versions()).is_empty());
+
+    // Only the bumped document needs accepting again
+    let old_terms = accepted(&[("terms", "2025-06"), ("privacy", "2026-01")]);
+    assert_eq!(outstanding_documents(&old_terms, &versions()), vec!["terms"]);
+}
+
+#[test]
+fn test_exempt_paths() {
+    assert!(is_exempt_path("/legal/accept"));
+    assert!(is_exempt_path("/terms"));
+    assert!(is_exempt_path("/static/css/main.css"));
+    assert!(is_exempt_path("/api/search"));
+    assert!(!is_exempt_path("/"));
+    assert!(!is_exempt_path("/profile"));
+}
@@ -0,0 +1,149 @@
+use chrono::Utc;
+use slatehub::models::daily_report::{
+    CrewTime, DailyReport, Incident, crew_text, hours_between, incidents_text,
+    is_assistant_director, parse_crew, parse_incidents, parse_recipients, parse_time, report_pdf,
+    report_summary,
+};
+use slatehub::models::shot_list::ShootDay;
+use surrealdb::types::RecordId;
+
+fn day() -> ShootDay {
+    ShootDay {
+        id: RecordId::new("shoot_day", "d1"),
+        production: RecordId::new("production", "p"),
+        day_number: 3,
+        date: "2026-05-04".to_string(),
+        notes: None,
+        location: None,
+        call_sheet_sent_at: None,
+        call_time: None,
+        wrap_time: None,
+        created_at: Utc::now(),
+    }
+}
+
+fn report() -> DailyReport {
+    DailyReport {
+        id: RecordId::new("daily_report", "r1"),
+        production: RecordId::new("production", "p"),
+        shoot_day: RecordId::new("shoot_day", "d1"),
+        status: "draft".to_string(),
+        call_time: Some("07:00".to_string()),
+        first_shot: Some("08:15".to_string()),
+        lunch_start: None,
+        lunch_end: None,
+        wrap_time: Some("19:30".to_string()),
+        weather: Some("Overcast".to_string()),
+        notes: None,
+        scenes_scheduled: vec!["12".to_string(), "12A".to_string(), "14".to_string()],
+        scenes_completed: vec!["12".to_string()],
+        shots_total: 10,
+        shots_captured: 6,
+        crew: vec![CrewTime {
+            name: "Sam Reyes".to_string(),
+            role: "Gaffer".to_string(),
+            time_in: "06:30".to_string(),
+            time_out: "20:00".to_string(),
+        }],
+        incidents: vec![Incident {
+            time: "14:30".to_string(),
+            description: "Light stand tipped, no injuries".to_string(),
+        }],
+        sent_to: vec![],
+        sent_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[test]
+fn times_are_normalized() {
+    assert_eq!(parse_time("7:05").unwrap().as_deref(), Some("07:05"));
+    assert_eq!(parse_time("0705").unwrap().as_deref(), Some("07:05"));
+    assert_eq!(parse_time(" 19:30 ").unwrap().as_deref(), Some("19:30"));
+    assert_eq!(parse_time("").unwrap(), None);
+    assert!(parse_time("25:00").is_err());
+    assert!(parse_time("noon").is_err());
+}
+
+#[test]
+fn hours_wrap_past_midnight() {
+    assert_eq!(hours_between("07:00", "19:30"), Some(12.5));
+    assert_eq!(hours_between("18:00", "02:00"), Some(8.0));
+    assert_eq!(hours_between("", "02:00"), None);
+}
+
+#[test]
+fn crew_lines_round_trip() {
+    let crew = parse_crew("Sam Reyes | Gaffer | 6:30 | 20:00\n\nAlex Kim | Grip\nJo").unwrap();
+    assert_eq!(crew.len(), 3);
+    assert_eq!(crew[0].time_in, "06:30");
+    assert_eq!(crew[1].role, "Grip");
+    assert_eq!(crew[1].time_out, "");
+    assert_eq!(crew[2].name, "Jo");
+
+    assert_eq!(parse_crew(&crew_text(&crew)).unwrap(), crew);
+}
+
+#[test]
+fn crew_lines_need_a_name_and_valid_times() {
+    assert!(parse_crew(" | Gaffer").is_err());
+    assert!(parse_crew("Sam | Gaffer | late").is_err());
+}
+
+#[test]
+fn incidents_take_an_optional_leading_time() {
+    let incidents = parse_incidents("14:30 Light stand tipped\nRain delay of 40 minutes\n9:00");
+    assert_eq!(incidents[0].time, "14:30");
+    assert_eq!(incidents[0].description, "Light stand tipped");
+    assert_eq!(incidents[1].time, "");
+    assert_eq!(incidents[1].description, "Rain delay of 40 minutes");
+    // A bare time is kept as the description
+    assert_eq!(incidents[2].description, "9:00");
+
+    assert_eq!(parse_incidents(&incidents_text(&incidents[..2])), incidents[..2].to_vec());
+}
+
+#[test]
+fn recipients_are_validated_and_deduplicated() {
+    let recipients = parse_recipients("Producer@Example.com, upm@example.com\nproducer@example.com;").unwrap();
+    assert_eq!(recipients, vec!["producer@example.com", "upm@example.com"]);
+
+    assert!(parse_recipients("not-an-email").is_err());
+    assert!(parse_recipients("a@localhost").is_err());
+    assert!(parse_recipients("").unwrap().is_empty());
+}
+
+#[test]
+fn assistant_directors_are_recognized() {
+    assert!(is_assistant_director(&["1st Assistant Director".to_string()]));
+    assert!(is_assistant_director(&["Producer".to_string(), "2nd AD".to_string()]));
+    assert!(!is_assistant_director(&["Director".to_string()]));
+    assert!(!is_assistant_director(&["Head of Advertising".to_string()]));
+}
+
+#[test]
+fn remaining_scenes_exclude_completed() {
+    assert_eq!(report().scenes_remaining(), vec!["12A", "14"]);
+}
+
+#[test]
+fn pdf_includes_report_sections() {
+    let pdf = String::from_utf8_lossy(&report_pdf("Night Shift", &day(), &report())).into_owned();
+    assert!(pdf.starts_with("%PDF-"));
+    assert!(pdf.contains("(Night Shift) Tj"));
+    assert!(pdf.contains("DRAFT"));
+    assert!(pdf.contains("(Sam Reyes) Tj"));
+    assert!(pdf.contains("(13.5) Tj"));
+    assert!(pdf.contains("(Light stand tipped, no injuries) Tj"));
+    assert!(pdf.contains("(6 of 10) Tj"));
+}
+
+#[test]
+fn summary_lists_progress() {
+    let summary = report_summary("Night Shift", &day(), &report());
+    assert!(summary.contains("Day 3 (2026-05-04)"));
+    assert!(summary.contains("Scenes remaining: 12A, 14"));
+    assert!(summary.contains("Shots captured: 6 of 10"));
+    assert!(summary.contains("Incidents: 1"));
+}
@@ -0,0 +1,134 @@
+use chrono::Utc;
+use slatehub::error::Error;
+use slatehub::models::deliverable::{
+    Deliverable, DeliverableData, MAX_TITLE_CHARS, Rollup, attachment_allowed, file_key,
+    kind_label, rollup, safe_file_name, size_label, status_label,
+};
+use surrealdb::types::RecordId;
+
+fn deliverable(title: &str, status: &str, due_date: Option<&str>) -> Deliverable {
+    Deliverable {
+        id: RecordId::new("deliverable", title),
+        kind: "master".to_string(),
+        title: title.to_string(),
+        specs: None,
+        due_date: due_date.map(str::to_string),
+        vendor: None,
+        vendor_org_name: None,
+        vendor_slug: None,
+        vendor_name: None,
+        status: status.to_string(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[test]
+fn test_labels() {
+    assert_eq!(kind_label("captions"), "Captions & subtitles");
+    assert_eq!(kind_label("hologram"), "Other");
+    assert_eq!(status_label("in_progress"), "In progress");
+    assert_eq!(status_label("lost"), "Not started");
+}
+
+#[test]
+fn test_deliverable_data() {
+    let data = DeliverableData::parse(
+        "dcp",
+        " Festival DCP ",
+        " Flat, 5.1, encrypted ",
+        "2026-09-01",
+        "",
+    )
+    .unwrap();
+    assert_eq!(data.title, "Festival DCP");
+    assert_eq!(data.specs.as_deref(), Some("Flat, 5.1, encrypted"));
+    assert_eq!(data.due_date.as_deref(), Some("2026-09-01"));
+    assert_eq!(data.vendor_name, None);
+
+    let undated = DeliverableData::parse("artwork", "Key art", "", "", " Poster House ").unwrap();
+    assert_eq!(undated.due_date, None);
+    assert_eq!(undated.vendor_name.as_deref(), Some("Poster House"));
+
+    let invalid = |result: Result<_, Error>| matches!(result, Err(Error::Validation(_)));
+    assert!(invalid(DeliverableData::parse(
+        "hologram", "Master", "", "", ""
+    )));
+    assert!(invalid(DeliverableData::parse("master", "  ", "", "", "")));
+    assert!(invalid(DeliverableData::parse(
+        "master",
+        &"x".repeat(MAX_TITLE_CHARS + 1),
+        "",
+        "",
+        ""
+    )));
+    assert!(invalid(DeliverableData::parse(
+        "master",
+        "Master",
+        "",
+        "next week",
+        ""
+    )));
+}
+
+#[test]
+fn test_overdue() {
+    let today = "2026-06-10";
+    assert!(deliverable("Master", "in_progress", Some("2026-06-09")).is_overdue(today));
+    assert!(!deliverable("Master", "in_progress", Some("2026-06-10")).is_overdue(today));
+    assert!(!deliverable("Master", "delivered", Some("2026-06-01")).is_overdue(today));
+    assert!(!deliverable("Master", "not_started", None).is_overdue(today));
+}
+
+#[test]
+fn test_rollup() {
+    let today = "2026-06-10";
+    let checklist = vec![
+        deliverable("Master", "approved", Some("2026-06-01")),
+        deliverable("DCP", "delivered", Some("2026-06-05")),
+        deliverable("Captions", "in_progress", Some("2026-06-08")),
+        deliverable("Key art", "not_started", Some("2026-06-20")),
+        deliverable("E&O", "not_started", None),
+    ];
+    let summary = rollup(&checklist, today);
+    assert_eq!(
+        summary,
+        Rollup {
+            total: 5,
+            done: 2,
+            approved: 1,
+            overdue: 1,
+            next_due: Some("2026-06-08".to_string()),
+        }
+    );
+    assert_eq!(summary.percent(), 40);
+    assert_eq!(summary.summary(), "2 of 5 delivered, 1 overdue");
+
+    let empty = rollup(&[], today);
+    assert_eq!(empty.percent(), 0);
+    assert_eq!(empty.summary(), "0 of 0 delivered");
+}
+
+#[test]
+fn test_attachments() {
+    assert!(attachment_allowed("captions_en.SRT"));
+    assert!(attachment_allowed("QC report.pdf"));
+    assert!(!attachment_allowed("setup.exe"));
+    assert!(!attachment_allowed("no-extension"));
+
+    assert_eq!(
+        safe_file_name("C:\\Users\\me\\QC Report (v2).pdf"),
+        "QC-Report-v2-.pdf"
+    );
+    assert_eq!(safe_file_name("../../etc/passwd"), "passwd");
+    assert_eq!(safe_file_name("\"quoted\".srt"), "quoted-.srt");
+    assert_eq!(safe_file_name("***"), "file");
+
+    assert_eq!(
+        file_key("prod1", "del1", "01J0", "key art.psd"),
+        "private/deliverables/prod1/del1/01J0-key-art.psd"
+    );
+
+    assert_eq!(size_label(512), "512 B");
+    assert_eq!(size_label(2048), "2 KB");
+    assert_eq!(size_label(5 * 1024 * 1024 + 200 * 1024), "5.2 MB");
+}
@@ -0,0 +1,69 @@
+use slatehub::services::demo_mode::{
+    Anonymizer, PLACEHOLDER_AVATAR, Person, fake_email, fake_name, fake_username, profile_links,
+};
+
+fn person(name: &str, username: &str, avatar: Option<&str>) -> Person {
+    Person {
+        name: name.to_string(),
+        username: username.to_string(),
+        avatar: avatar.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_fakes_are_stable() {
+    assert_eq!(fake_name("jane"), fake_name("jane"));
+    assert_eq!(
+        fake_username("jane"),
+        fake_name("jane").to_lowercase().replace(' ', ".")
+    );
+    assert!(fake_email("jane@studio.com").ends_with("@example.com"));
+}
+
+#[test]
+fn test_profile_links() {
+    let html = r#"<a href="/jane">Jane</a> <a href="/search?q=x">x</a> <a href="/jane">again</a> <a href="/sam.lee">Sam</a>"#;
+    assert_eq!(profile_links(html), vec!["jane", "sam.lee"]);
+}
+
+#[test]
+fn test_names_and_mentions_are_replaced() {
+    let anonymizer = Anonymizer::new(vec![person("Jane Doe", "jane", None)]);
+    let html = anonymizer.apply(r#"<a href="/jane">Jane Doe</a> <small>@jane</small> @janet"#);
+    assert!(!html.contains("Jane Doe"));
+    assert!(html.contains(&fake_name("jane")));
+    assert!(html.contains(&format!("@{}", fake_username("jane"))));
+    // Links still work, and other handles are left alone
+    assert!(html.contains(r#"href="/jane""#));
+    assert!(html.contains("@janet"));
+}
+
+#[test]
+fn test_contact_details_are_replaced() {
+    let anonymizer = Anonymizer::new(vec![]);
+    let html = anonymizer.apply(
+        r#"<a href="mailto:jane@studio.com">jane@studio.com</a> <a href="tel:+4915112345678">+49 151 1234 5678</a> on 2026-03-10"#,
+    );
+    assert!(!html.contains("studio.com"));
+    assert!(!html.contains("12345678"));
+    assert!(!html.contains("1234 5678"));
+    assert!(html.contains("2026-03-10"));
+}
+
+#[test]
+fn test_avatars_are_replaced() {
+    let anonymizer = Anonymizer::new(vec![person(
+        "Jane Doe",
+        "jane",
+        Some("/api/media/avatars/jane.jpg"),
+    )]);
+    let html = anonymizer.apply(concat!(
+        r#"<img src="/api/media/avatars/jane.jpg" alt="x" />"#,
+        r#"<img src="/api/avatar?id=person:abc" />"#,
+        r#"<img src="/api/media/posters/film.jpg" alt="Poster" />"#,
+        r#"<img src="/static/images/logo.svg" />"#,
+    ));
+    assert_eq!(html.matches(PLACEHOLDER_AVATAR).count(), 2);
+    assert!(html.contains("/api/media/posters/film.jpg"));
+    assert!(html.contains("/static/images/logo.svg"));
+}
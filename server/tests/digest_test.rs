@@ -0,0 +1,57 @@
+use chrono::{DateTime, TimeZone, Utc};
+use slatehub::services::digest::{DigestPreference, is_due, job_matches, last_slot};
+
+fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+}
+
+fn pref(day: u32, hour: u32) -> DigestPreference {
+    DigestPreference {
+        enabled: true,
+        day,
+        hour,
+    }
+}
+
+#[test]
+fn test_last_slot() {
+    // 2026-10-14 is a Wednesday
+    let now = at(2026, 10, 14, 10, 30);
+    assert_eq!(last_slot(2, 8, now), at(2026, 10, 14, 8, 0));
+    assert_eq!(last_slot(2, 11, now), at(2026, 10, 7, 11, 0));
+    assert_eq!(last_slot(0, 8, now), at(2026, 10, 12, 8, 0));
+    assert_eq!(last_slot(6, 23, now), at(2026, 10, 11, 23, 0));
+}
+
+#[test]
+fn test_is_due_once_per_slot() {
+    let now = at(2026, 10, 14, 8, 10);
+    assert!(is_due(&pref(2, 8), None, now));
+    assert!(is_due(&pref(2, 8), Some(at(2026, 10, 7, 8, 5)), now));
+    assert!(!is_due(&pref(2, 8), Some(at(2026, 10, 14, 8, 5)), now));
+}
+
+#[test]
+fn test_is_due_skips_stale_and_disabled() {
+    // Monday's slot was two days ago, so wait for next week
+    assert!(!is_due(&pref(0, 8), None, at(2026, 10, 14, 8, 10)));
+    assert!(!is_due(&pref(2, 9), None, at(2026, 10, 14, 8, 59)));
+
+    let disabled = DigestPreference {
+        enabled: false,
+        ..pref(2, 8)
+    };
+    assert!(!is_due(&disabled, None, at(2026, 10, 14, 8, 10)));
+}
+
+#[test]
+fn test_job_matches() {
+    let skills = vec!["Gaffer".to_string(), "Grip".to_string()];
+    let roles = vec!["Key Grip".to_string()];
+
+    assert!(job_matches("Indie feature crew", &roles, Some("Atlanta, GA"), &skills, Some("Atlanta, GA")));
+    assert!(job_matches("Gaffer needed", &[], None, &skills, Some("Austin, TX")));
+    assert!(!job_matches("Gaffer needed", &[], Some("Austin, TX"), &skills, Some("Atlanta, GA")));
+    assert!(!job_matches("Lead actor", &["Villain".to_string()], None, &skills, None));
+    assert!(!job_matches("Gaffer needed", &[], None, &[], None));
+}
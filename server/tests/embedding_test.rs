@@ -1,4 +1,7 @@
-use slatehub::services::embedding::{build_location_embedding_text, build_person_embedding_text};
+use slatehub::services::embedding::{
+    build_location_embedding_text, build_organization_embedding_text, build_person_embedding_text,
+    build_production_embedding_text,
+};
 
 #[test]
 fn test_person_embedding_text() {
@@ -11,7 +14,7 @@ fn test_person_embedding_text() {
         Some((25, 35)),
         Some("male"),
         &vec!["caucasian".to_string()],
-        Some(180),
+        Some(1800),
         Some("athletic"),
         Some("brown"),
         Some("blue"),
@@ -21,6 +24,7 @@ fn test_person_embedding_text() {
         Some((30, 40)),
         &vec!["latino".to_string(), "mediterranean".to_string()],
         Some("american"),
+        &[],
     );
 
     // Embedding text is lowercased for case-insensitive matching
@@ -30,6 +34,35 @@ fn test_person_embedding_text() {
     assert!(text.contains("25-35 years old"));
     assert!(text.contains("los angeles"));
     assert!(text.contains("acting, singing"));
+    assert!(text.contains("height: 180 cm (5'11\")"));
+    assert!(!text.contains("audio work"));
+}
+
+#[test]
+fn test_person_embedding_text_with_audio_work() {
+    let text = build_person_embedding_text(
+        "Ana Ruiz",
+        Some("Composer"),
+        None,
+        &[],
+        None,
+        None,
+        None,
+        &[],
+        None,
+        None,
+        None,
+        None,
+        &[],
+        &[],
+        &[],
+        None,
+        &[],
+        None,
+        &["Night Drive; genre Synthwave; instruments Moog, drum machine".to_string()],
+    );
+
+    assert!(text.contains("audio work: night drive; genre synthwave; instruments moog, drum machine"));
 }
 
 #[test]
@@ -52,3 +85,128 @@ fn test_location_embedding_text() {
     assert!(text.contains("natural light"));
     assert!(text.contains("50 people"));
 }
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    dot / (norm(a) * norm(b))
+}
+
+#[test]
+fn test_quantize_round_trip_keeps_similarity() {
+    use slatehub::services::embedding::quantize;
+
+    let a: Vec<f32> = (0..1024).map(|i| ((i * 37 % 101) as f32 / 50.0 - 1.0) * 0.05).collect();
+    let b: Vec<f32> = (0..1024).map(|i| ((i * 53 % 97) as f32 / 48.0 - 1.0) * 0.05).collect();
+
+    let qa = quantize(&a);
+    assert_eq!(qa.values.len(), 1024);
+    assert!(qa.values.iter().any(|&v| v == 127 || v == -127));
+    assert!(cosine(&a, &qa.dequantize()) > 0.999);
+
+    // Cosine ignores the scale, so quantized values compare like the originals
+    let as_f32 = |v: &[i8]| v.iter().map(|&x| x as f32).collect::<Vec<_>>();
+    let qb = quantize(&b);
+    let exact = cosine(&a, &b);
+    assert!((cosine(&as_f32(&qa.values), &as_f32(&qb.values)) - exact).abs() < 0.01);
+    assert!((cosine(&as_f32(&qa.values), &b) - exact).abs() < 0.01);
+}
+
+#[test]
+fn test_quantize_zero_vector() {
+    let q = slatehub::services::embedding::quantize(&[0.0; 8]);
+    assert_eq!(q.values, vec![0; 8]);
+    assert_eq!(q.scale, 0.0);
+}
+
+#[test]
+fn test_precision_parse() {
+    use slatehub::services::embedding::Precision;
+
+    assert_eq!(Precision::parse("int8"), Some(Precision::Int8));
+    assert_eq!(Precision::parse(" F32 "), Some(Precision::F32));
+    assert_eq!(Precision::parse("f16"), None);
+}
+
+#[test]
+fn test_changed_fields_names_edited_parts() {
+    use slatehub::services::embedding::changed_fields;
+
+    let old = "name: jane. role: editor. background: cut two features. loves docs. skills and abilities: avid";
+    let new = "name: jane. role: editor. background: cut three features. loves docs. skills and abilities: avid";
+    assert_eq!(changed_fields(old, new), vec!["background".to_string()]);
+
+    let added = format!("{}. languages: french", old);
+    assert_eq!(changed_fields(old, &added), vec!["languages".to_string()]);
+    assert!(changed_fields(old, old).is_empty());
+}
+
+#[test]
+fn test_passages_split_fields_into_sentences_and_items() {
+    use slatehub::services::embedding::passages;
+
+    let text = "jane doe. role: editor. background: cut two features. loves docs. skills and abilities: avid, da vinci resolve, color grading";
+    let passages: Vec<(String, String)> = passages(text);
+    let pairs: Vec<(&str, &str)> = passages
+        .iter()
+        .map(|(label, text)| (label.as_str(), text.as_str()))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            ("role", "editor"),
+            ("background", "cut two features"),
+            ("background", "loves docs"),
+            ("skills and abilities", "avid"),
+            ("skills and abilities", "da vinci resolve"),
+            ("skills and abilities", "color grading"),
+        ]
+    );
+}
+
+#[test]
+fn test_organization_embedding_text_includes_slate() {
+    let text = build_organization_embedding_text(
+        "Lens House",
+        "Rental House",
+        None,
+        &["camera rental".to_string()],
+        None,
+        None,
+        None,
+        &[
+            "vendor on Harbor Lights (camera package)".to_string(),
+            "financier of Night Shift".to_string(),
+        ],
+    );
+
+    assert!(text.contains("production slate: vendor on harbor lights (camera package), financier of night shift"));
+}
+
+#[test]
+fn test_production_embedding_text_includes_companies() {
+    let text = build_production_embedding_text(
+        "Harbor Lights",
+        "Feature Film",
+        "Post-Production",
+        None,
+        None,
+        None,
+        None,
+        &["Northlight Pictures (producer)".to_string()],
+    );
+
+    assert!(text.contains("companies: northlight pictures (producer)"));
+
+    let without = build_production_embedding_text(
+        "Harbor Lights",
+        "Feature Film",
+        "Post-Production",
+        None,
+        None,
+        None,
+        None,
+        &[],
+    );
+    assert!(!without.contains("companies"));
+}
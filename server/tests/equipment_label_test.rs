@@ -0,0 +1,89 @@
+use slatehub::models::equipment_label::{
+    LABEL_COLUMNS, LABEL_ROWS, Label, ScannedCode, labels_pdf, parse_scan, qr_modules, scan_path,
+    scan_reply,
+};
+
+const ID: &str = "3f2b8c1e-9d4a-4e7b-8a61-0c5d2e7f9b14";
+
+fn label(i: usize) -> Label {
+    Label {
+        name: format!("ARRI SkyPanel S60-C #{}", i),
+        code: format!("EQ-{}", ID),
+        detail: Some("S/N 12345".to_string()),
+    }
+}
+
+#[test]
+fn test_parse_scanned_codes() {
+    let equipment = ScannedCode::Equipment(format!("EQ-{}", ID));
+    assert_eq!(parse_scan(&format!("EQ-{}", ID)), Some(equipment.clone()));
+    assert_eq!(
+        parse_scan(&format!("  eq-{}  ", ID.to_uppercase())),
+        Some(equipment.clone())
+    );
+    assert_eq!(
+        parse_scan(&format!(
+            "https://slatehub.com/equipment/scan/EQ-{}?from=label",
+            ID
+        )),
+        Some(equipment)
+    );
+    assert_eq!(
+        parse_scan(&format!("KIT-{}", ID)),
+        Some(ScannedCode::Kit(format!("KIT-{}", ID)))
+    );
+
+    assert_eq!(parse_scan("EQ-123"), None);
+    assert_eq!(parse_scan(&format!("XX-{}", ID)), None);
+    assert_eq!(parse_scan("C-Stand"), None);
+    assert_eq!(parse_scan(""), None);
+}
+
+#[test]
+fn test_qr_modules() {
+    let url = format!("https://slatehub.com{}", scan_path(&format!("EQ-{}", ID)));
+    let rows = qr_modules(&url).unwrap();
+    assert!(rows.iter().all(|row| row.len() == rows.len()));
+    // Quiet zone stays light; finder pattern starts dark inside it
+    assert!(rows[0].iter().all(|dark| !dark));
+    assert!(rows[1].iter().all(|dark| !dark));
+    assert!(rows[2][2]);
+}
+
+#[test]
+fn test_label_sheet_pages() {
+    let per_page = LABEL_COLUMNS * LABEL_ROWS;
+    let labels: Vec<Label> = (0..per_page + 1).map(label).collect();
+    let pdf = labels_pdf(&labels, "https://slatehub.com").unwrap();
+    let text = String::from_utf8_lossy(&pdf);
+    assert_eq!(text.matches("/Type /Page ").count(), 2);
+    assert!(text.contains("S/N 12345"));
+    assert!(text.contains(&format!("EQ-{}", ID)));
+
+    let long = Label {
+        name: "Very long equipment name that will not fit on a single label line".to_string(),
+        code: format!("EQ-{}", ID),
+        detail: None,
+    };
+    let text =
+        String::from_utf8_lossy(&labels_pdf(&[long], "https://slatehub.com").unwrap()).into_owned();
+    assert!(text.contains("...) Tj"));
+    assert_eq!(text.matches("/Type /Page ").count(), 1);
+}
+
+#[test]
+fn test_bot_scan_reply() {
+    let reply = scan_reply(
+        "C-Stand",
+        None,
+        true,
+        "https://slatehub.com/equipment/scan/EQ-1",
+    );
+    assert_eq!(
+        reply,
+        "*C-Stand*\nAvailable\n\nTap to check it out: https://slatehub.com/equipment/scan/EQ-1"
+    );
+    let reply = scan_reply("C-Stand", Some("Ada Lovelace"), false, "https://x/scan");
+    assert!(reply.contains("Checked out to Ada Lovelace"));
+    assert!(reply.contains("Tap to check it in"));
+}
@@ -0,0 +1,147 @@
+use chrono::NaiveDate;
+use slatehub::error::Error;
+use slatehub::models::equipment_service::{
+    MAX_DESCRIPTION_CHARS, ServiceStatus, clean_intervals, clean_service_event, format_hours,
+    kind_label, service_status,
+};
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_clean_service_event() {
+    let today = date("2026-06-15");
+    let data = clean_service_event(
+        "service",
+        "2026-06-10",
+        " Cleaned sensor ",
+        "412.5",
+        "$1,250",
+        " Pro Camera Repair ",
+        today,
+    )
+    .unwrap();
+    assert_eq!(data.performed_on, "2026-06-10");
+    assert_eq!(data.description, "Cleaned sensor");
+    assert_eq!(data.usage_hours, Some(412.5));
+    assert_eq!(data.cost, Some(1250.0));
+    assert_eq!(data.vendor.as_deref(), Some("Pro Camera Repair"));
+
+    // A reading needs nothing but the hours, and defaults to today
+    let reading = clean_service_event("usage", "", "", "430", "", "", today).unwrap();
+    assert_eq!(reading.performed_on, "2026-06-15");
+    assert_eq!(reading.description, "Hour meter reading");
+    assert_eq!(reading.vendor, None);
+}
+
+#[test]
+fn test_clean_service_event_rejects() {
+    let today = date("2026-06-15");
+    let invalid = |result: Result<_, Error>| matches!(result, Err(Error::Validation(_)));
+    assert!(invalid(clean_service_event(
+        "tuneup", "", "Oil", "", "", "", today
+    )));
+    assert!(invalid(clean_service_event(
+        "repair",
+        "2026-06-16",
+        "Fixed mount",
+        "",
+        "",
+        "",
+        today
+    )));
+    assert!(invalid(clean_service_event(
+        "repair", "June 1", "Fixed", "", "", "", today
+    )));
+    assert!(invalid(clean_service_event(
+        "repair", "", "", "", "", "", today
+    )));
+    assert!(invalid(clean_service_event(
+        "usage", "", "", "", "", "", today
+    )));
+    assert!(invalid(clean_service_event(
+        "usage", "", "", "-3", "", "", today
+    )));
+    assert!(invalid(clean_service_event(
+        "service", "", "Oil", "", "lots", "", today
+    )));
+    assert!(invalid(clean_service_event(
+        "service",
+        "",
+        &"x".repeat(MAX_DESCRIPTION_CHARS + 1),
+        "",
+        "",
+        "",
+        today
+    )));
+}
+
+#[test]
+fn test_clean_intervals() {
+    assert_eq!(clean_intervals("", "").unwrap(), (None, None));
+    assert_eq!(clean_intervals("200", "").unwrap(), (Some(200.0), None));
+    assert_eq!(clean_intervals("", "180").unwrap(), (None, Some(180)));
+    assert!(clean_intervals("0", "").is_err());
+    assert!(clean_intervals("", "0").is_err());
+    assert!(clean_intervals("", "6 months").is_err());
+}
+
+#[test]
+fn test_service_status_by_hours() {
+    let added = date("2026-01-01");
+    let today = date("2026-06-15");
+    let status = |usage, last| service_status(usage, Some(200.0), last, None, None, added, today);
+    assert_eq!(status(100.0, None), ServiceStatus::Ok);
+    assert_eq!(status(185.0, None), ServiceStatus::DueSoon);
+    assert_eq!(status(200.0, None), ServiceStatus::Due);
+    // Counted from the reading at the last service
+    assert_eq!(status(350.0, Some(200.0)), ServiceStatus::Ok);
+    assert_eq!(status(410.0, Some(200.0)), ServiceStatus::Due);
+}
+
+#[test]
+fn test_service_status_by_days() {
+    let added = date("2026-01-01");
+    let status = |last: Option<&str>, today: &str| {
+        service_status(
+            0.0,
+            None,
+            None,
+            Some(90),
+            last.map(date),
+            added,
+            date(today),
+        )
+    };
+    assert_eq!(status(None, "2026-02-01"), ServiceStatus::Ok);
+    assert_eq!(status(None, "2026-03-26"), ServiceStatus::DueSoon);
+    assert_eq!(status(None, "2026-04-01"), ServiceStatus::Due);
+    assert_eq!(status(Some("2026-03-15"), "2026-04-01"), ServiceStatus::Ok);
+
+    // Whichever interval comes first
+    let both = service_status(
+        250.0,
+        Some(200.0),
+        None,
+        Some(365),
+        None,
+        added,
+        date("2026-02-01"),
+    );
+    assert_eq!(both, ServiceStatus::Due);
+    assert_eq!(
+        service_status(500.0, None, None, None, None, added, date("2030-01-01")),
+        ServiceStatus::Unscheduled
+    );
+}
+
+#[test]
+fn test_labels() {
+    assert_eq!(format_hours(412.0), "412");
+    assert_eq!(format_hours(412.46), "412.5");
+    assert_eq!(kind_label("usage"), "Hour meter reading");
+    assert_eq!(kind_label("unknown"), "Other");
+    assert_eq!(ServiceStatus::Due.label(), "Due for service");
+    assert_eq!(ServiceStatus::DueSoon.key(), "due-soon");
+}
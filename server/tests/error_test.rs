@@ -0,0 +1,30 @@
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use slatehub::error::{Error, PROBLEM_JSON};
+
+#[test]
+fn test_error_codes_and_status() {
+    assert_eq!(Error::NotFound.code(), "not_found");
+    assert_eq!(Error::NotFound.status_code(), StatusCode::NOT_FOUND);
+    assert_eq!(Error::validation("bad").code(), "validation_failed");
+    assert_eq!(Error::validation("bad").status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(Error::internal("boom").status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+#[test]
+fn test_internal_details_not_exposed() {
+    assert_eq!(Error::internal("connection string leaked").public_message(), None);
+    assert_eq!(Error::database("SELECT * FROM secret").public_message(), None);
+    assert_eq!(
+        Error::bad_request("Missing name").public_message(),
+        Some("Missing name".to_string())
+    );
+}
+
+#[test]
+fn test_into_response_is_problem_json() {
+    let response = Error::conflict("Username taken").into_response();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    assert_eq!(response.headers().get("content-type").unwrap(), PROBLEM_JSON);
+    assert_eq!(response.headers().get("X-Error-Code").unwrap(), "conflict");
+}
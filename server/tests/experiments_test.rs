@@ -0,0 +1,62 @@
+use slatehub::config::SearchWeights;
+use slatehub::services::experiments::{SEARCH_RANKING, VariantStats, lift, ranking_weights, z_score};
+
+fn stats(variant: &str, searches: i64, clicked_searches: i64) -> VariantStats {
+    VariantStats {
+        variant: variant.to_string(),
+        searches,
+        clicked_searches,
+        clicks: clicked_searches,
+    }
+}
+
+#[test]
+fn test_assignment_is_stable_and_covers_variants() {
+    let first = SEARCH_RANKING.assign("alice");
+    assert_eq!(first, SEARCH_RANKING.assign("alice"));
+
+    let control = (0..1000)
+        .filter(|i| SEARCH_RANKING.assign(&format!("visitor{}", i)) == "control")
+        .count();
+    assert!(control > 400 && control < 600, "control got {} of 1000", control);
+}
+
+#[test]
+fn test_ranking_weights() {
+    let base = SearchWeights {
+        name_match: 50,
+        headline_match: 20,
+        location_match: 10,
+        vector_multiplier: 50,
+        vector_threshold: 0.75,
+        connection_match: 10,
+        max_candidates: 0,
+        timeout_ms: 0,
+    };
+
+    let control = ranking_weights(Some("control"), &base);
+    assert_eq!(control.vector_multiplier, 50);
+
+    let boosted = ranking_weights(Some("semantic_boost"), &base);
+    assert_eq!(boosted.vector_multiplier, 75);
+    assert!((boosted.vector_threshold - 0.70).abs() < 1e-9);
+    assert_eq!(boosted.name_match, 50);
+}
+
+#[test]
+fn test_ctr_lift_and_significance() {
+    let control = stats("control", 1000, 200);
+    let variant = stats("semantic_boost", 1000, 260);
+
+    assert!((control.ctr() - 20.0).abs() < 1e-9);
+    assert!((lift(&control, &variant).unwrap() - 30.0).abs() < 1e-9);
+    assert!(z_score(&control, &variant).unwrap() > 1.96);
+
+    let same = stats("semantic_boost", 1000, 205);
+    assert!(z_score(&control, &same).unwrap().abs() < 1.96);
+
+    let empty = stats("semantic_boost", 0, 0);
+    assert_eq!(empty.ctr(), 0.0);
+    assert!(lift(&control, &empty).is_none());
+    assert!(z_score(&control, &empty).is_none());
+}
@@ -0,0 +1,141 @@
+use axum::http::{HeaderMap, HeaderValue, header};
+use chrono::{TimeZone, Utc};
+use slatehub::error::Error;
+use slatehub::middleware::recent_requests::{KEPT_PER_VISITOR, RecentRequests, visitor_key};
+use slatehub::models::feedback::{
+    Feedback, FeedbackKind, MAX_MESSAGE_CHARS, MAX_NOTE_CHARS, parse_note, parse_status,
+    parse_submission, status_label,
+};
+use slatehub::services::feedback::{issue_url, payload, title};
+use surrealdb::types::RecordId;
+
+fn feedback(kind: &str, message: &str) -> Feedback {
+    Feedback {
+        id: RecordId::new("feedback", "f1"),
+        username: "ana".to_string(),
+        kind: kind.to_string(),
+        page_url: "/productions/night-shoot".to_string(),
+        message: message.to_string(),
+        request_ids: vec!["01J2".to_string(), "01J1".to_string()],
+        user_agent: Some("Firefox".to_string()),
+        status: "new".to_string(),
+        note: None,
+        triaged_by_name: None,
+        triaged_at: None,
+        forwarded_at: None,
+        issue_url: None,
+        forward_error: None,
+        created_at: Utc.with_ymd_and_hms(2026, 3, 4, 12, 30, 0).unwrap(),
+    }
+}
+
+#[test]
+fn submissions_are_checked() {
+    let submission = parse_submission(Some("bug"), "  Upload stalls  ", "/media").unwrap();
+    assert_eq!(submission.kind, FeedbackKind::Bug);
+    assert_eq!(submission.message, "Upload stalls");
+    assert_eq!(submission.page_url, "/media");
+
+    // The widget's older form sends no kind
+    let submission = parse_submission(None, "Love it", "/").unwrap();
+    assert_eq!(submission.kind, FeedbackKind::Feedback);
+
+    assert!(matches!(
+        parse_submission(Some("spam"), "Hi", "/"),
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        parse_submission(None, "   ", "/"),
+        Err(Error::Validation(_))
+    ));
+    let long = "é".repeat(MAX_MESSAGE_CHARS + 1);
+    assert!(parse_submission(None, &long, "/").is_err());
+    assert!(parse_submission(None, &long[..long.len() - 2], "/").is_ok());
+}
+
+#[test]
+fn page_urls_stay_on_the_site() {
+    let page = |url: &str| parse_submission(None, "Hi", url).unwrap().page_url;
+    assert_eq!(page("/jobs?page=2"), "/jobs?page=2");
+    assert_eq!(page("https://example.com/"), "/");
+    assert_eq!(page("//example.com/"), "/");
+    assert_eq!(page("/\\example.com/"), "/");
+    assert_eq!(page(""), "/");
+}
+
+#[test]
+fn statuses_and_notes() {
+    assert_eq!(parse_status("resolved").unwrap(), "resolved");
+    assert!(parse_status("closed").is_err());
+    assert_eq!(status_label("dismissed"), "Dismissed");
+
+    assert_eq!(parse_note("  ").unwrap(), None);
+    assert_eq!(
+        parse_note(" Fixed in 1.4 ").unwrap().as_deref(),
+        Some("Fixed in 1.4")
+    );
+    assert!(parse_note(&"x".repeat(MAX_NOTE_CHARS + 1)).is_err());
+}
+
+#[test]
+fn recent_requests_are_kept_per_visitor_newest_first() {
+    let recent = RecentRequests::default();
+    for i in 0..KEPT_PER_VISITOR + 3 {
+        recent.record("person:ana", &format!("r{}", i), 100);
+    }
+    recent.record("visitor:abc", "other", 100);
+
+    let ids = recent.recent("person:ana");
+    assert_eq!(ids.len(), KEPT_PER_VISITOR);
+    assert_eq!(ids[0], format!("r{}", KEPT_PER_VISITOR + 2));
+    assert_eq!(ids.last().unwrap(), "r3");
+    assert_eq!(recent.recent("visitor:abc"), vec!["other".to_string()]);
+    assert!(recent.recent("visitor:nobody").is_empty());
+}
+
+#[test]
+fn visitors_are_told_apart_by_the_visitor_cookie() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(visitor_key(None, &headers), None);
+
+    headers.insert(
+        header::COOKIE,
+        HeaderValue::from_static("theme=dark; visitor_id=abc123"),
+    );
+    assert_eq!(
+        visitor_key(None, &headers).as_deref(),
+        Some("visitor:abc123")
+    );
+}
+
+#[test]
+fn forwarded_feedback_reads_as_an_issue() {
+    let item = feedback("bug", "Upload stalls at 99%\nOn a 2GB file");
+    assert_eq!(title(&item), "[Bug] Upload stalls at 99%");
+    assert!(title(&feedback("feedback", &"a".repeat(200))).ends_with('…'));
+
+    let body = payload(&item, "https://slatehub.com");
+    assert_eq!(body["title"], "[Bug] Upload stalls at 99%");
+    assert_eq!(body["labels"][0], "bug");
+    let text = body["body"].as_str().unwrap();
+    assert!(text.starts_with("Upload stalls at 99%\nOn a 2GB file"));
+    assert!(text.contains("Page: https://slatehub.com/productions/night-shoot"));
+    assert!(text.contains("01J2, 01J1"));
+    assert!(body["text"].as_str().unwrap().starts_with("[Bug] "));
+    assert_eq!(body["id"], "f1");
+}
+
+#[test]
+fn issue_links_come_from_the_response() {
+    let created =
+        serde_json::json!({ "number": 12, "html_url": "https://github.com/o/r/issues/12" });
+    assert_eq!(
+        issue_url(&created).as_deref(),
+        Some("https://github.com/o/r/issues/12")
+    );
+    assert_eq!(issue_url(&serde_json::json!({ "ok": true })), None);
+    assert_eq!(
+        issue_url(&serde_json::json!({ "html_url": "javascript:alert(1)" })),
+        None
+    );
+}
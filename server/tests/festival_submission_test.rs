@@ -0,0 +1,228 @@
+use chrono::{NaiveDate, Utc};
+use slatehub::error::Error;
+use slatehub::models::festival_submission::{
+    FestivalSubmission, Outcome, SubmissionData, adjacent_months, due_phrase, fee_totals,
+    month_calendar, month_start,
+};
+use surrealdb::types::RecordId;
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn submission(
+    festival: &str,
+    status: &str,
+    deadline: &str,
+    fee: Option<f64>,
+) -> FestivalSubmission {
+    FestivalSubmission {
+        id: RecordId::new("festival_submission", festival),
+        festival: festival.to_string(),
+        festival_url: None,
+        category: None,
+        deadline: deadline.to_string(),
+        notification_date: None,
+        fee,
+        status: status.to_string(),
+        result: "pending".to_string(),
+        award: None,
+        public: false,
+        notes: None,
+        updated_at: Utc::now(),
+    }
+}
+
+fn invalid<T>(result: Result<T, Error>) -> bool {
+    matches!(result, Err(Error::Validation(_)))
+}
+
+#[test]
+fn test_submission_data() {
+    let data = SubmissionData::parse(
+        " Sundance ",
+        "https://sundance.org",
+        "Short Documentary",
+        "2026-09-15",
+        "2026-12-01",
+        "$85",
+        "",
+    )
+    .unwrap();
+    assert_eq!(data.festival, "Sundance");
+    assert_eq!(data.festival_url.as_deref(), Some("https://sundance.org"));
+    assert_eq!(data.deadline, "2026-09-15");
+    assert_eq!(data.fee, Some(85.0));
+    assert_eq!(data.notes, None);
+
+    let free = SubmissionData::parse("Local Fest", "", "", "2026-09-15", "", "", "").unwrap();
+    assert_eq!(free.fee, None);
+    assert_eq!(free.notification_date, None);
+
+    assert!(invalid(SubmissionData::parse(
+        "",
+        "",
+        "",
+        "2026-09-15",
+        "",
+        "",
+        ""
+    )));
+    assert!(invalid(SubmissionData::parse(
+        "Fest", "", "", "", "", "", ""
+    )));
+    assert!(invalid(SubmissionData::parse(
+        "Fest", "", "", "soon", "", "", ""
+    )));
+    assert!(invalid(SubmissionData::parse(
+        "Fest",
+        "javascript:alert(1)",
+        "",
+        "2026-09-15",
+        "",
+        "",
+        ""
+    )));
+    assert!(invalid(SubmissionData::parse(
+        "Fest",
+        "",
+        "",
+        "2026-09-15",
+        "2026-09-01",
+        "",
+        ""
+    )));
+    assert!(invalid(SubmissionData::parse(
+        "Fest",
+        "",
+        "",
+        "2026-09-15",
+        "",
+        "free",
+        ""
+    )));
+}
+
+#[test]
+fn test_outcome() {
+    let selected = Outcome::parse("submitted", "selected", " Jury Prize ", true).unwrap();
+    assert_eq!(selected.award.as_deref(), Some("Jury Prize"));
+    assert!(selected.public);
+
+    // Only selections can be shown publicly
+    let pending = Outcome::parse("submitted", "pending", "", true).unwrap();
+    assert!(!pending.public);
+
+    assert!(invalid(Outcome::parse("planned", "selected", "", false)));
+    assert!(invalid(Outcome::parse(
+        "submitted",
+        "not_selected",
+        "Best Short",
+        false
+    )));
+    assert!(invalid(Outcome::parse("entered", "pending", "", false)));
+    assert!(invalid(Outcome::parse("submitted", "won", "", false)));
+}
+
+#[test]
+fn test_deadlines() {
+    let today = date("2026-06-10");
+    let soon = submission("Soon", "planned", "2026-06-13", None);
+    assert_eq!(soon.days_to_deadline(today), Some(3));
+    assert_eq!(soon.deadline_note(today).as_deref(), Some("Due in 3 days"));
+    assert!(soon.needs_reminder(today));
+
+    let later = submission("Later", "planned", "2026-07-10", None);
+    assert!(!later.needs_reminder(today));
+
+    let passed = submission("Passed", "planned", "2026-06-01", None);
+    assert_eq!(
+        passed.deadline_note(today).as_deref(),
+        Some("Deadline passed")
+    );
+    assert!(!passed.needs_reminder(today));
+
+    let sent = submission("Sent", "submitted", "2026-06-12", None);
+    assert_eq!(sent.deadline_note(today), None);
+    assert!(!sent.needs_reminder(today));
+
+    assert_eq!(due_phrase(0), "today");
+    assert_eq!(due_phrase(1), "tomorrow");
+    assert_eq!(due_phrase(5), "in 5 days");
+}
+
+#[test]
+fn test_laurel() {
+    let mut selection = submission("Tribeca", "submitted", "2026-01-20", None);
+    selection.result = "selected".to_string();
+    assert_eq!(selection.laurel(), "Official Selection");
+    assert_eq!(selection.year(), "2026");
+    selection.award = Some("Best Narrative Short".to_string());
+    assert_eq!(selection.laurel(), "Best Narrative Short");
+}
+
+#[test]
+fn test_fee_totals() {
+    let submissions = vec![
+        submission("A", "submitted", "2026-05-01", Some(60.0)),
+        submission("B", "submitted", "2026-05-02", Some(40.0)),
+        submission("C", "planned", "2026-08-01", Some(95.0)),
+        submission("D", "withdrawn", "2026-05-03", Some(50.0)),
+        submission("E", "planned", "2026-08-02", None),
+    ];
+    assert_eq!(fee_totals(&submissions), (100.0, 95.0));
+}
+
+#[test]
+fn test_months() {
+    let today = date("2026-10-16");
+    assert_eq!(month_start(None, today), date("2026-10-01"));
+    assert_eq!(month_start(Some("2027-02"), today), date("2027-02-01"));
+    assert_eq!(month_start(Some("garbage"), today), date("2026-10-01"));
+
+    assert_eq!(
+        adjacent_months(date("2026-01-01")),
+        (date("2025-12-01"), date("2026-02-01"))
+    );
+    assert_eq!(
+        adjacent_months(date("2026-12-01")),
+        (date("2026-11-01"), date("2027-01-01"))
+    );
+}
+
+#[test]
+fn test_month_calendar() {
+    let mut sundance = submission("Sundance", "planned", "2026-10-16", None);
+    sundance.notification_date = Some("2026-11-02".to_string());
+    let withdrawn = submission("Gone", "withdrawn", "2026-10-16", None);
+    let weeks = month_calendar(
+        &[sundance, withdrawn],
+        date("2026-10-01"),
+        date("2026-10-16"),
+    );
+
+    // October 2026 starts on a Thursday and ends on a Saturday
+    assert_eq!(weeks.len(), 5);
+    assert!(weeks.iter().all(|week| week.len() == 7));
+    assert_eq!(weeks[0][0].date, "2026-09-28");
+    assert!(!weeks[0][0].in_month);
+    assert_eq!(weeks[4][6].date, "2026-11-01");
+
+    let deadline_day = weeks
+        .iter()
+        .flatten()
+        .find(|day| day.date == "2026-10-16")
+        .unwrap();
+    assert!(deadline_day.is_today);
+    assert_eq!(deadline_day.events.len(), 1);
+    assert_eq!(deadline_day.events[0].kind, "deadline");
+    assert_eq!(deadline_day.events[0].label, "Sundance deadline");
+
+    // Results fall in the next month, off this calendar
+    assert!(
+        weeks
+            .iter()
+            .flatten()
+            .all(|day| day.events.iter().all(|e| e.kind == "deadline"))
+    );
+}
@@ -0,0 +1,59 @@
+use slatehub::services::flags::{FeatureFlag, rollout_bucket, validate_name};
+
+fn flag(enabled: bool, rollout_percent: u8) -> FeatureFlag {
+    FeatureFlag {
+        name: "new_search_ui".to_string(),
+        enabled,
+        rollout_percent,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_disabled_and_full_rollout() {
+    assert!(!flag(false, 100).evaluate(Some("alice"), &[]));
+    assert!(flag(true, 100).evaluate(Some("alice"), &[]));
+    assert!(flag(true, 100).evaluate(None, &[]));
+    assert!(!flag(true, 0).evaluate(Some("alice"), &[]));
+}
+
+#[test]
+fn test_partial_rollout_is_stable_and_monotonic() {
+    let users: Vec<String> = (0..1000).map(|i| format!("user{}", i)).collect();
+    let on_at = |percent: u8| -> Vec<&String> {
+        users
+            .iter()
+            .filter(|u| flag(true, percent).evaluate(Some(u), &[]))
+            .collect()
+    };
+
+    let ten = on_at(10);
+    let fifty = on_at(50);
+    assert!(ten.len() > 50 && ten.len() < 150, "10% rollout hit {} of 1000", ten.len());
+    assert!(ten.iter().all(|u| fifty.contains(u)));
+
+    assert_eq!(rollout_bucket("new_search_ui", "alice"), rollout_bucket("new_search_ui", "alice"));
+    assert!(!flag(true, 50).evaluate(None, &[]));
+}
+
+#[test]
+fn test_org_overrides() {
+    let org = "organization:acme".to_string();
+
+    let mut forced_on = flag(false, 0);
+    forced_on.enabled_orgs = vec![org.clone()];
+    assert!(forced_on.evaluate(Some("alice"), std::slice::from_ref(&org)));
+    assert!(!forced_on.evaluate(Some("alice"), &[]));
+
+    let mut forced_off = flag(true, 100);
+    forced_off.disabled_orgs = vec![org.clone()];
+    forced_off.enabled_orgs = vec!["organization:other".to_string()];
+    assert!(!forced_off.evaluate(Some("alice"), &[org, "organization:other".to_string()]));
+}
+
+#[test]
+fn test_validate_name() {
+    assert!(validate_name("hybrid_search_v2").is_ok());
+    assert!(validate_name("").is_err());
+    assert!(validate_name("New-Search").is_err());
+}
@@ -0,0 +1,138 @@
+use chrono::NaiveDate;
+use slatehub::error::Error;
+use slatehub::models::gear_quote::{
+    MAX_NOTE_CHARS, QuoteAction, QuotePrice, QuoteRequest, budget_description, is_expired,
+    next_status,
+};
+use surrealdb::types::RecordId;
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn gear() -> Vec<RecordId> {
+    vec![
+        RecordId::new("equipment", "c70"),
+        RecordId::new("equipment", "skypanel"),
+    ]
+}
+
+#[test]
+fn test_next_status() {
+    use QuoteAction::*;
+    assert_eq!(next_status("requested", Quote), Some("quoted"));
+    assert_eq!(next_status("quoted", Quote), Some("quoted"));
+    assert_eq!(next_status("requested", Decline), Some("declined"));
+    assert_eq!(next_status("quoted", Accept), Some("accepted"));
+    assert_eq!(next_status("requested", Accept), None);
+    assert_eq!(next_status("quoted", Withdraw), Some("withdrawn"));
+    assert_eq!(next_status("accepted", Withdraw), None);
+    assert_eq!(next_status("declined", Quote), None);
+    assert_eq!(next_status("withdrawn", Accept), None);
+}
+
+#[test]
+fn test_quote_request() {
+    let today = date("2026-06-01");
+    let selected = vec!["skypanel".to_string(), "c70".to_string(), "c70".to_string()];
+    let request = QuoteRequest::parse(
+        &selected,
+        &gear(),
+        "2026-06-10",
+        "2026-06-12",
+        " Need a prep day ",
+        today,
+    )
+    .unwrap();
+    assert_eq!(
+        request.equipment,
+        [
+            RecordId::new("equipment", "skypanel"),
+            RecordId::new("equipment", "c70")
+        ]
+    );
+    assert_eq!(request.start_date, "2026-06-10");
+    assert_eq!(request.end_date, "2026-06-12");
+    assert_eq!(request.note.as_deref(), Some("Need a prep day"));
+
+    let invalid = |result: Result<_, Error>| matches!(result, Err(Error::Validation(_)));
+    let c70 = vec!["c70".to_string()];
+    assert!(invalid(QuoteRequest::parse(
+        &[],
+        &gear(),
+        "2026-06-10",
+        "2026-06-12",
+        "",
+        today
+    )));
+    assert!(invalid(QuoteRequest::parse(
+        &["alexa".to_string()],
+        &gear(),
+        "2026-06-10",
+        "2026-06-12",
+        "",
+        today
+    )));
+    assert!(invalid(QuoteRequest::parse(
+        &c70,
+        &gear(),
+        "",
+        "",
+        "",
+        today
+    )));
+    assert!(invalid(QuoteRequest::parse(
+        &c70,
+        &gear(),
+        "2026-05-30",
+        "2026-06-02",
+        "",
+        today
+    )));
+    assert!(invalid(QuoteRequest::parse(
+        &c70,
+        &gear(),
+        "2026-06-10",
+        "2026-06-12",
+        &"x".repeat(MAX_NOTE_CHARS + 1),
+        today
+    )));
+}
+
+#[test]
+fn test_quote_price() {
+    let today = date("2026-06-01");
+    let price = QuotePrice::parse("$1,250.50", "2026-06-05", " Deposit on pickup ", today).unwrap();
+    assert_eq!(price.price, 1250.5);
+    assert_eq!(price.valid_until.as_deref(), Some("2026-06-05"));
+    assert_eq!(price.note.as_deref(), Some("Deposit on pickup"));
+    assert_eq!(
+        QuotePrice::parse("900", "", "", today).unwrap().valid_until,
+        None
+    );
+
+    assert!(QuotePrice::parse("0", "", "", today).is_err());
+    assert!(QuotePrice::parse("free", "", "", today).is_err());
+    assert!(QuotePrice::parse("900", "2026-05-31", "", today).is_err());
+    assert!(QuotePrice::parse("900", "next week", "", today).is_err());
+}
+
+#[test]
+fn test_is_expired() {
+    assert!(!is_expired(None, "2026-06-01"));
+    assert!(!is_expired(Some("2026-06-01"), "2026-06-01"));
+    assert!(is_expired(Some("2026-05-31"), "2026-06-01"));
+}
+
+#[test]
+fn test_budget_description() {
+    let items = vec!["Canon C70".to_string(), "SkyPanel S60".to_string()];
+    assert_eq!(
+        budget_description("Northlight Rentals", &items, "2026-06-01", "2026-06-05"),
+        "Northlight Rentals: Canon C70, SkyPanel S60 (2026-06-01 to 2026-06-05)"
+    );
+    let many = vec!["Cine Lens".to_string(); 40];
+    let long = budget_description("Northlight Rentals", &many, "2026-06-01", "2026-06-01");
+    assert_eq!(long.chars().count(), 200);
+    assert!(long.ends_with('…'));
+}
@@ -0,0 +1,77 @@
+use chrono::{Duration, Utc};
+use slatehub::error::Error;
+use slatehub::models::guest_link::{GuestLink, GuestLinkData, MAX_LABEL_CHARS, scope_label};
+use surrealdb::types::RecordId;
+
+fn link(expires_in: Duration, revoked: bool) -> GuestLink {
+    let now = Utc::now();
+    GuestLink {
+        id: RecordId::new("guest_link", "g1"),
+        production: RecordId::new("production", "p1"),
+        label: "Jo Park, colorist".to_string(),
+        scope: "schedule".to_string(),
+        deliverable: None,
+        deliverable_title: None,
+        expires_at: now + expires_in,
+        revoked_at: revoked.then_some(now),
+        last_used_at: None,
+        created_by: RecordId::new("person", "owner"),
+        creator_name: None,
+        created_at: now,
+    }
+}
+
+fn validation_error(result: Result<GuestLinkData, Error>) -> String {
+    match result {
+        Err(Error::Validation(msg)) => msg,
+        other => panic!("expected a validation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn schedule_links_need_no_deliverable() {
+    let data = GuestLinkData::parse("  Jo Park  ", "schedule", "d1", "7").unwrap();
+    assert_eq!(data.label, "Jo Park");
+    assert_eq!(data.scope, "schedule");
+    assert_eq!(data.deliverable, None);
+    assert_eq!(data.days, 7);
+}
+
+#[test]
+fn upload_links_need_a_deliverable() {
+    let msg = validation_error(GuestLinkData::parse("Jo", "upload", " ", "7"));
+    assert!(msg.contains("deliverable"));
+
+    let data = GuestLinkData::parse("Jo", "upload", "master", "30").unwrap();
+    assert_eq!(data.deliverable.as_deref(), Some("master"));
+}
+
+#[test]
+fn unknown_scopes_and_expiries_are_refused() {
+    assert!(GuestLinkData::parse("Jo", "edit", "", "7").is_err());
+    assert!(GuestLinkData::parse("Jo", "schedule", "", "365").is_err());
+    assert!(GuestLinkData::parse("Jo", "schedule", "", "soon").is_err());
+}
+
+#[test]
+fn labels_are_required_and_limited() {
+    assert!(validation_error(GuestLinkData::parse(" ", "schedule", "", "7")).contains("who"));
+    let long = "x".repeat(MAX_LABEL_CHARS + 1);
+    assert!(GuestLinkData::parse(&long, "schedule", "", "7").is_err());
+}
+
+#[test]
+fn links_stop_working_when_expired_or_revoked() {
+    let now = Utc::now();
+    assert_eq!(link(Duration::days(1), false).status(now), "active");
+    assert!(link(Duration::days(1), false).is_active(now));
+    assert_eq!(link(Duration::days(-1), false).status(now), "expired");
+    assert_eq!(link(Duration::days(1), true).status(now), "revoked");
+    assert!(!link(Duration::days(-1), true).is_active(now));
+}
+
+#[test]
+fn scopes_have_labels() {
+    assert_eq!(scope_label("schedule"), "View the shooting schedule");
+    assert_eq!(scope_label("upload"), "Upload files to one deliverable");
+}
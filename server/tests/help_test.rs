@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::Path;
+
+use slatehub::services::help::{self, Article, keyword_score};
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Every `/help/<slug>` link in the files under `dir`
+fn help_links(dir: &Path, links: &mut Vec<(String, String)>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            help_links(&path, links);
+            continue;
+        }
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        for (_, rest) in text
+            .match_indices("/help/")
+            .map(|(i, _)| text.split_at(i + 6))
+        {
+            let slug: String = rest
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+                .collect();
+            if !slug.is_empty() {
+                links.push((path.display().to_string(), slug));
+            }
+        }
+    }
+}
+
+#[test]
+fn every_article_file_is_listed_and_parses() {
+    for entry in fs::read_dir(manifest_dir().join("help")).unwrap() {
+        let path = entry.unwrap().path();
+        let slug = path.file_stem().unwrap().to_str().unwrap();
+        let article = help::article(slug).unwrap_or_else(|| panic!("{slug} isn't listed"));
+        assert!(!article.summary.is_empty(), "{slug} has no summary");
+        assert!(!article.section.is_empty(), "{slug} has no section");
+        assert!(!article.body.is_empty(), "{slug} has no text");
+    }
+}
+
+#[test]
+fn help_links_point_at_articles() {
+    let mut links = vec![];
+    help_links(&manifest_dir().join("templates"), &mut links);
+    help_links(&manifest_dir().join("help"), &mut links);
+    assert!(!links.is_empty());
+    for (file, slug) in links {
+        assert!(
+            help::article(&slug).is_some(),
+            "{file} links to missing help article {slug}"
+        );
+    }
+}
+
+#[test]
+fn articles_need_a_title() {
+    let article = Article::parse(
+        "guest-links",
+        "---\ntitle: Guest links\nsummary: Limited access\nsection: Productions\n---\n\nText.",
+    )
+    .unwrap();
+    assert_eq!(article.title, "Guest links");
+    assert_eq!(article.section, "Productions");
+    assert_eq!(article.body, "Text.");
+
+    assert!(Article::parse("x", "---\nsummary: No title\n---\nText.").is_none());
+    assert!(Article::parse("x", "Just text").is_none());
+}
+
+#[test]
+fn title_matches_outrank_text_matches() {
+    let holds = help::article("legal-holds").unwrap();
+    let productions = help::article("productions").unwrap();
+    assert!(keyword_score(holds, "legal hold") > keyword_score(productions, "legal hold"));
+    assert_eq!(keyword_score(holds, "zzzz"), 0.0);
+    assert_eq!(keyword_score(holds, "  "), 0.0);
+}
+
+#[tokio::test]
+async fn searching_without_the_model_uses_word_matches() {
+    let results = help::search("guest link").await;
+    assert_eq!(
+        results.first().map(|a| a.slug.as_str()),
+        Some("guest-links")
+    );
+    assert!(help::search("").await.is_empty());
+}
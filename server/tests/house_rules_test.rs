@@ -0,0 +1,120 @@
+use chrono::Utc;
+use slatehub::error::Error;
+use slatehub::models::call_sheet::{CallSheetLocation, call_sheet_pdf, call_sheet_summary};
+use slatehub::models::house_rules::{
+    Acknowledgment, MAX_RULE_CHARS, MAX_RULES, is_key_role, parse_rules, pending_key_people,
+};
+use slatehub::models::shot_list::ShootDay;
+use surrealdb::types::RecordId;
+
+fn roles(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn ack(name: &str, role: &str, acknowledged: bool) -> Acknowledgment {
+    let roles = roles(&[role]);
+    Acknowledgment {
+        person_id: format!("person:{}", name.to_lowercase()),
+        name: name.to_string(),
+        key_role: is_key_role(&roles),
+        roles,
+        acknowledged_at: acknowledged.then(Utc::now),
+    }
+}
+
+fn day() -> ShootDay {
+    ShootDay {
+        id: RecordId::new("shoot_day", "d1"),
+        production: RecordId::new("production", "p"),
+        day_number: 2,
+        date: "2026-06-01".to_string(),
+        notes: None,
+        location: Some(RecordId::new("location", "diner")),
+        call_sheet_sent_at: None,
+        call_time: None,
+        wrap_time: None,
+        created_at: Utc::now(),
+    }
+}
+
+#[test]
+fn test_key_roles() {
+    assert!(is_key_role(&roles(&["Director"])));
+    assert!(is_key_role(&roles(&["Line Producer"])));
+    assert!(is_key_role(&roles(&["1st AD"])));
+    assert!(is_key_role(&roles(&["Location Manager"])));
+    assert!(is_key_role(&roles(&["UPM"])));
+    assert!(is_key_role(&roles(&["Gaffer", "Executive Producer"])));
+
+    // Department heads called "director" don't hold up call sheets
+    assert!(!is_key_role(&roles(&["Art Director"])));
+    assert!(!is_key_role(&roles(&["Director of Photography"])));
+    assert!(!is_key_role(&roles(&["Gaffer"])));
+    assert!(!is_key_role(&[]));
+}
+
+#[test]
+fn test_rules_one_per_line() {
+    let rules =
+        parse_rules("- No smoking indoors\n\n* Load in via side gate\n  • Quiet after 10pm  \n")
+            .unwrap();
+    assert_eq!(
+        rules,
+        vec![
+            "No smoking indoors",
+            "Load in via side gate",
+            "Quiet after 10pm"
+        ]
+    );
+    assert!(parse_rules("  \n ").unwrap().is_empty());
+}
+
+#[test]
+fn test_rule_limits() {
+    let too_many = ["Rule"; MAX_RULES + 1].join("\n");
+    assert!(matches!(parse_rules(&too_many), Err(Error::Validation(_))));
+    assert!(matches!(
+        parse_rules(&"x".repeat(MAX_RULE_CHARS + 1)),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn test_pending_key_people() {
+    let acknowledgments = vec![
+        ack("Ada", "Director", true),
+        ack("Ben", "1st AD", false),
+        ack("Cy", "Gaffer", false),
+        ack("Dee", "Producer", false),
+    ];
+    assert_eq!(
+        pending_key_people(&acknowledgments),
+        vec!["Ben (1st AD)", "Dee (Producer)"]
+    );
+
+    let all_in = vec![ack("Ada", "Director", true), ack("Cy", "Gaffer", false)];
+    assert!(pending_key_people(&all_in).is_empty());
+}
+
+#[test]
+fn test_call_sheet_lists_house_rules() {
+    let location = CallSheetLocation {
+        name: "Rosie's Diner".to_string(),
+        address: "12 Main St, Burbank, CA".to_string(),
+        parking: Some("Lot behind the building".to_string()),
+        house_rules: roles(&["No smoking indoors", "Quiet after 10pm"]),
+    };
+    let summary = call_sheet_summary("Night Shift", &day(), Some(&location), &[]);
+    assert!(summary.contains("Call Sheet, Day 2 (2026-06-01)"));
+    assert!(summary.contains("Location: Rosie's Diner, 12 Main St"));
+    assert!(summary.contains("- Quiet after 10pm"));
+
+    let summary = call_sheet_summary("Night Shift", &day(), None, &[]);
+    assert!(summary.contains("Location: TBC"));
+    assert!(!summary.contains("House rules"));
+
+    let pdf = call_sheet_pdf("Night Shift", &day(), Some(&location), &[], &[], None);
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("1. No smoking indoors"));
+    assert!(text.contains("2. Quiet after 10pm"));
+}
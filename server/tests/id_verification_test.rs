@@ -0,0 +1,99 @@
+use axum::http::HeaderMap;
+use slatehub::services::id_verification::{
+    HostedProvider, IdentityProvider, hmac_sha256, is_private_key, verify_signature,
+};
+use slatehub::services::search_utils::parse_query;
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn provider() -> HostedProvider {
+    HostedProvider {
+        name: "Acme ID".to_string(),
+        start_url: "https://id.example.com/start".to_string(),
+        secret: "shh".to_string(),
+    }
+}
+
+fn signed(body: &str, secret: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "x-signature",
+        hex(&hmac_sha256(secret.as_bytes(), body.as_bytes())).parse().unwrap(),
+    );
+    headers
+}
+
+#[test]
+fn test_hmac_sha256_rfc4231() {
+    // RFC 4231 test case 2
+    assert_eq!(
+        hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+    // RFC 4231 test case 6: key longer than the block size
+    assert_eq!(
+        hex(&hmac_sha256(
+            &[0xaa; 131],
+            b"Test Using Larger Than Block-Size Key - Hash Key First"
+        )),
+        "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+    );
+}
+
+#[test]
+fn test_verify_signature() {
+    let sig = hex(&hmac_sha256(b"secret", b"{}"));
+    assert!(verify_signature("secret", b"{}", &sig));
+    assert!(verify_signature("secret", b"{}", &format!("sha256={}", sig.to_uppercase())));
+    assert!(!verify_signature("other", b"{}", &sig));
+    assert!(!verify_signature("secret", b"{ }", &sig));
+    assert!(!verify_signature("secret", b"{}", ""));
+}
+
+#[test]
+fn test_private_keys() {
+    assert!(is_private_key("private/verification/abc/doc.pdf"));
+    assert!(is_private_key("/private/verification/abc/doc.pdf"));
+    assert!(!is_private_key("profiles/abc/photo.jpg"));
+}
+
+#[tokio::test]
+async fn test_hosted_start_url() {
+    let url = provider()
+        .start_url("01ABC", "https://slatehub.com/get-verified")
+        .await
+        .unwrap();
+    assert_eq!(
+        url,
+        "https://id.example.com/start?reference=01ABC&return_url=https%3A%2F%2Fslatehub.com%2Fget-verified"
+    );
+}
+
+#[test]
+fn test_hosted_callback() {
+    let body = r#"{"reference":"01ABC","status":"rejected","reason":"Document expired"}"#;
+    let decision = provider().parse_callback(&signed(body, "shh"), body.as_bytes()).unwrap();
+    assert_eq!(decision.reference, "01ABC");
+    assert!(!decision.approved);
+    assert_eq!(decision.reason.as_deref(), Some("Document expired"));
+
+    // Wrong secret or missing signature is refused
+    assert!(provider().parse_callback(&signed(body, "nope"), body.as_bytes()).is_err());
+    assert!(provider().parse_callback(&HeaderMap::new(), body.as_bytes()).is_err());
+
+    let unknown = r#"{"reference":"01ABC","status":"maybe"}"#;
+    assert!(provider().parse_callback(&signed(unknown, "shh"), unknown.as_bytes()).is_err());
+}
+
+#[test]
+fn test_parse_query_verified() {
+    let parsed = parse_query("verified cinematographers in Berlin");
+    assert!(parsed.verified_only);
+    assert_eq!(parsed.location.as_deref(), Some("Berlin"));
+    assert_eq!(parsed.cleaned, "cinematographer");
+
+    assert!(parse_query("ID-verified editors").verified_only);
+    assert!(!parse_query("editors").verified_only);
+}
@@ -0,0 +1,130 @@
+use axum::http::Method;
+use chrono::{Duration, TimeZone, Utc};
+use slatehub::error::Error;
+use slatehub::services::impersonation::{
+    MAX_REASON_CHARS, SessionSummary, clean_reason, is_audited, is_blocked,
+};
+use surrealdb::types::RecordId;
+
+#[test]
+fn a_reason_is_required() {
+    assert_eq!(
+        clean_reason("  Ticket #4821: can't see offers  ").unwrap(),
+        "Ticket #4821: can't see offers"
+    );
+    assert!(matches!(clean_reason("   "), Err(Error::Validation(_))));
+    assert!(matches!(
+        clean_reason(&"x".repeat(MAX_REASON_CHARS + 1)),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn admin_area_is_off_limits() {
+    assert!(is_blocked(&Method::GET, "/admin"));
+    assert!(is_blocked(&Method::GET, "/admin/people"));
+    assert!(is_blocked(&Method::POST, "/admin/people/abc/impersonate"));
+    assert!(!is_blocked(&Method::GET, "/administrator"));
+}
+
+#[test]
+fn sign_in_details_and_deletion_are_off_limits() {
+    for path in [
+        "/account/change-password",
+        "/account/change-email",
+        "/account/change-username",
+        "/account/delete",
+        "/verify-email",
+    ] {
+        assert!(is_blocked(&Method::POST, path), "{}", path);
+    }
+    assert!(is_blocked(&Method::GET, "/account/export"));
+}
+
+#[test]
+fn oauth_apps_and_grants_are_off_limits() {
+    assert!(is_blocked(&Method::POST, "/oauth/authorize"));
+    assert!(is_blocked(&Method::GET, "/account/apps"));
+    assert!(is_blocked(&Method::POST, "/account/apps"));
+    assert!(is_blocked(&Method::POST, "/account/apps/abc"));
+    assert!(is_blocked(&Method::POST, "/account/apps/abc/secret"));
+    assert!(is_blocked(&Method::POST, "/account/apps/abc/delete"));
+    assert!(is_blocked(
+        &Method::POST,
+        "/account/connected-apps/abc/disconnect"
+    ));
+    // The consent screen can be looked at, not answered
+    assert!(!is_blocked(&Method::GET, "/oauth/authorize"));
+    assert!(!is_blocked(&Method::GET, "/account/applications"));
+}
+
+#[test]
+fn terms_and_whatsapp_changes_are_off_limits() {
+    assert!(is_blocked(&Method::POST, "/legal/accept"));
+    assert!(is_blocked(&Method::POST, "/account/whatsapp"));
+    // The interstitial itself can still be viewed
+    assert!(!is_blocked(&Method::GET, "/legal/accept"));
+}
+
+#[test]
+fn organization_sso_settings_are_off_limits() {
+    for path in [
+        "/orgs/acme/sso",
+        "/orgs/acme/sso/domains/verify",
+        "/orgs/acme/sso/scim-token",
+        "/orgs/acme/sso/scim-token/revoke",
+    ] {
+        assert!(is_blocked(&Method::POST, path), "{}", path);
+    }
+    // The settings page can still be looked at
+    assert!(!is_blocked(&Method::GET, "/orgs/acme/sso"));
+    assert!(!is_blocked(&Method::POST, "/orgs/acme/ssouth"));
+    assert!(!is_blocked(&Method::POST, "/orgs/acme/members"));
+}
+
+#[test]
+fn everyday_use_is_allowed() {
+    assert!(!is_blocked(&Method::GET, "/account"));
+    assert!(!is_blocked(&Method::POST, "/account/digest"));
+    assert!(!is_blocked(&Method::GET, "/offers"));
+    assert!(!is_blocked(&Method::POST, "/offers/abc/accept"));
+    assert!(!is_blocked(&Method::POST, "/impersonation/exit"));
+    assert!(!is_blocked(&Method::POST, "/logout"));
+}
+
+#[test]
+fn static_assets_are_not_audited() {
+    assert!(is_audited("/profile"));
+    assert!(is_audited("/api/notifications"));
+    assert!(!is_audited("/static/css/main.css"));
+    assert!(!is_audited("/favicon.ico"));
+}
+
+#[test]
+fn sessions_finish_when_ended_or_expired() {
+    let started = Utc.with_ymd_and_hms(2026, 10, 16, 9, 0, 0).unwrap();
+    let mut session = SessionSummary {
+        id: RecordId::new("impersonation_session", "s1"),
+        admin_username: "support".to_string(),
+        person_username: "maya".to_string(),
+        reason: "Ticket #4821".to_string(),
+        ip: "203.0.113.7".to_string(),
+        user_agent: None,
+        started_at: started,
+        expires_at: started + Duration::hours(1),
+        ended_at: None,
+        action_count: 0,
+    };
+
+    assert_eq!(session.finished_at(started + Duration::minutes(30)), None);
+    assert_eq!(
+        session.finished_at(started + Duration::hours(2)),
+        Some(started + Duration::hours(1))
+    );
+
+    session.ended_at = Some(started + Duration::minutes(10));
+    assert_eq!(
+        session.finished_at(started + Duration::minutes(30)),
+        Some(started + Duration::minutes(10))
+    );
+}
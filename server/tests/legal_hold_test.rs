@@ -0,0 +1,97 @@
+use chrono::Utc;
+use slatehub::error::Error;
+use slatehub::models::legal_hold::{HoldTarget, LegalHold, MAX_REASON_CHARS, parse_reason};
+use surrealdb::types::RecordId;
+
+fn hold(kind: &str, title: Option<&str>, lifted: bool) -> LegalHold {
+    let production = RecordId::new("production", "p1");
+    LegalHold {
+        id: RecordId::new("legal_hold", "h1"),
+        target: match kind {
+            "production" => production.clone(),
+            table => RecordId::new(table, "d1"),
+        },
+        production,
+        target_kind: kind.to_string(),
+        target_title: title.map(str::to_string),
+        reason: "Smith v. Studio".to_string(),
+        placed_by_name: Some("Ana".to_string()),
+        placed_at: Utc::now(),
+        lifted_by_name: None,
+        lifted_at: lifted.then(Utc::now),
+    }
+}
+
+#[test]
+fn targets_parse_from_the_form() {
+    assert_eq!(
+        HoldTarget::parse("production").unwrap(),
+        HoldTarget::Production
+    );
+    assert_eq!(
+        HoldTarget::parse("script:abc123").unwrap(),
+        HoldTarget::Script("abc123".to_string())
+    );
+    assert_eq!(
+        HoldTarget::parse(" deliverable:master_1 ").unwrap(),
+        HoldTarget::Deliverable("master_1".to_string())
+    );
+}
+
+#[test]
+fn unknown_targets_are_refused() {
+    for value in [
+        "",
+        "budget:abc",
+        "script:",
+        "script:a b",
+        "deliverable:x;DELETE",
+    ] {
+        assert!(
+            matches!(HoldTarget::parse(value), Err(Error::Validation(_))),
+            "{value}"
+        );
+    }
+}
+
+#[test]
+fn targets_name_the_held_record() {
+    let production = RecordId::new("production", "p1");
+    assert_eq!(HoldTarget::Production.record_id(&production), production);
+    assert_eq!(
+        HoldTarget::Script("s1".to_string()).record_id(&production),
+        RecordId::new("production_script", "s1")
+    );
+}
+
+#[test]
+fn reasons_are_required_and_limited() {
+    assert_eq!(
+        parse_reason("  Smith v. Studio ").unwrap(),
+        "Smith v. Studio"
+    );
+    assert!(parse_reason(" ").is_err());
+    assert!(parse_reason(&"x".repeat(MAX_REASON_CHARS + 1)).is_err());
+}
+
+#[test]
+fn holds_describe_what_they_cover() {
+    assert_eq!(
+        hold("production", None, false).target_label(),
+        "Whole production"
+    );
+    assert_eq!(
+        hold("production_script", Some("Draft"), false).target_label(),
+        "Script: Draft"
+    );
+    assert_eq!(
+        hold("deliverable", None, false).target_label(),
+        "Deliverable: (deleted)"
+    );
+}
+
+#[test]
+fn lifted_holds_are_no_longer_active() {
+    assert!(hold("production", None, false).is_active());
+    assert!(!hold("production", None, true).is_active());
+}
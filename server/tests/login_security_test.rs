@@ -0,0 +1,78 @@
+use std::net::IpAddr;
+
+use axum::http::{HeaderMap, HeaderValue, header};
+use chrono::{Duration, Utc};
+use slatehub::services::login_security::{ClientInfo, client_ip, lockout_until, network_prefix};
+
+#[test]
+fn test_lockout_until() {
+    let now = Utc::now();
+    let lockout = Duration::minutes(15);
+
+    assert_eq!(lockout_until(4, 5, Some(now), lockout, now), None);
+    assert_eq!(lockout_until(5, 5, Some(now), lockout, now), Some(now + lockout));
+    // Lockout has passed
+    assert_eq!(lockout_until(9, 5, Some(now - Duration::minutes(20)), lockout, now), None);
+    // Limit of zero disables lockout
+    assert_eq!(lockout_until(100, 0, Some(now), lockout, now), None);
+}
+
+fn ip(s: &str) -> IpAddr {
+    s.parse().unwrap()
+}
+
+fn forwarded_for(value: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-forwarded-for", HeaderValue::from_static(value));
+    headers
+}
+
+#[test]
+fn test_direct_clients_use_the_socket_address() {
+    let mut headers = forwarded_for("203.0.113.7");
+    headers.insert(header::USER_AGENT, HeaderValue::from_static("Mozilla/5.0"));
+
+    // Without trusted proxies the header is the client's own and is ignored
+    let client = ClientInfo::new(ip("198.51.100.20"), &headers, 0);
+    assert_eq!(client.ip, "198.51.100.20");
+    assert_eq!(client.user_agent.as_deref(), Some("Mozilla/5.0"));
+
+    assert_eq!(client_ip(ip("198.51.100.20"), &HeaderMap::new(), 0), ip("198.51.100.20"));
+}
+
+#[test]
+fn test_trusted_proxies_vouch_for_forwarded_addresses() {
+    let proxy = ip("10.0.0.2");
+
+    // One proxy: the entry it appended, not the spoofed one before it
+    let headers = forwarded_for("1.2.3.4, 203.0.113.7");
+    assert_eq!(client_ip(proxy, &headers, 1), ip("203.0.113.7"));
+
+    // Two proxies: the CDN appended the client, the load balancer the CDN
+    let headers = forwarded_for("1.2.3.4, 203.0.113.7, 10.0.0.1");
+    assert_eq!(client_ip(proxy, &headers, 2), ip("203.0.113.7"));
+
+    // Header split across several lines
+    let mut headers = forwarded_for("203.0.113.7");
+    headers.append("x-forwarded-for", HeaderValue::from_static("10.0.0.1"));
+    assert_eq!(client_ip(proxy, &headers, 2), ip("203.0.113.7"));
+
+    // Fewer entries than proxies, or a mangled one: the furthest vouched-for address
+    assert_eq!(client_ip(proxy, &forwarded_for("203.0.113.7"), 2), ip("203.0.113.7"));
+    assert_eq!(client_ip(proxy, &HeaderMap::new(), 1), proxy);
+    assert_eq!(client_ip(proxy, &forwarded_for("203.0.113.7, unknown"), 2), proxy);
+    assert_eq!(client_ip(proxy, &forwarded_for("unknown, 10.0.0.1"), 2), ip("10.0.0.1"));
+}
+
+#[test]
+fn test_fingerprint_groups_by_network() {
+    let a = ClientInfo { ip: "203.0.113.7".to_string(), user_agent: Some("Mozilla/5.0".to_string()) };
+    let b = ClientInfo { ip: "203.0.113.99".to_string(), user_agent: Some("Mozilla/5.0".to_string()) };
+    let c = ClientInfo { ip: "198.51.100.7".to_string(), user_agent: Some("Mozilla/5.0".to_string()) };
+
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_ne!(a.fingerprint(), c.fingerprint());
+
+    assert_eq!(network_prefix("2001:db8:1:2:3:4:5:6"), "2001:db8:1:2::/64");
+    assert_eq!(network_prefix("unknown"), "unknown");
+}
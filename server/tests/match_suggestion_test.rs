@@ -0,0 +1,56 @@
+use slatehub::models::match_suggestion::{job_text, tune_query};
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    dot / (norm(a) * norm(b))
+}
+
+#[test]
+fn test_tune_query_without_feedback_is_normalized_base() {
+    let tuned = tune_query(&[3.0, 4.0], &[], &[]);
+    assert!((tuned[0] - 0.6).abs() < 1e-6);
+    assert!((tuned[1] - 0.8).abs() < 1e-6);
+}
+
+#[test]
+fn test_tune_query_moves_toward_liked_and_away_from_disliked() {
+    let base = [1.0, 0.0, 0.0];
+    let liked = vec![vec![0.0, 1.0, 0.0]];
+    let disliked = vec![vec![0.0, 0.0, 1.0]];
+    let tuned = tune_query(&base, &liked, &disliked);
+
+    assert!(cosine(&tuned, &liked[0]) > cosine(&base, &liked[0]));
+    assert!(cosine(&tuned, &disliked[0]) < cosine(&base, &disliked[0]));
+    // The posting itself still dominates
+    assert!(tuned[0] > tuned[1]);
+    let norm: f32 = tuned.iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((norm - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_tune_query_ignores_scale_and_bad_vectors() {
+    let base = [1.0, 0.0];
+    // An int8-style vector counts the same as its unit-length equivalent
+    let small = tune_query(&base, &[vec![0.0, 1.0]], &[]);
+    let large = tune_query(&base, &[vec![0.0, 127.0]], &[]);
+    for (a, b) in small.iter().zip(&large) {
+        assert!((a - b).abs() < 1e-6);
+    }
+
+    let ignored = tune_query(&base, &[vec![0.0, 0.0], vec![1.0, 2.0, 3.0]], &[]);
+    assert_eq!(ignored, vec![1.0, 0.0]);
+}
+
+#[test]
+fn test_job_text() {
+    assert_eq!(
+        job_text(
+            "Indie feature",
+            "  Shooting in Atlanta.  ",
+            &["Gaffer".to_string(), "Key Grip".to_string()]
+        ),
+        "Indie feature\nRoles: Gaffer, Key Grip\nShooting in Atlanta."
+    );
+    assert_eq!(job_text("Short film", "", &[]), "Short film");
+}
@@ -0,0 +1,46 @@
+use slatehub::services::mentions::{added, link, link_html, parse};
+
+#[test]
+fn test_parse_finds_usernames_once_in_order() {
+    assert_eq!(
+        parse("@Jane_Doe and @sam.k, can you check this with @jane_doe?"),
+        vec!["jane_doe", "sam.k"]
+    );
+    // Sentence-ending periods aren't part of the name
+    assert_eq!(parse("Thanks @mia."), vec!["mia"]);
+}
+
+#[test]
+fn test_parse_skips_emails_urls_and_invalid_names() {
+    assert!(parse("mail jane@example.com").is_empty());
+    assert!(parse("https://example.com/@jane").is_empty());
+    assert!(parse("@ab is too short, @@double and @a..b aren't names").is_empty());
+}
+
+#[test]
+fn test_added_only_returns_new_mentions() {
+    assert_eq!(added("Ask @jane", "Ask @jane and @sam"), vec!["sam"]);
+    assert!(added("@jane", "@JANE again").is_empty());
+}
+
+#[test]
+fn test_link_escapes_text_and_links_mentions() {
+    assert_eq!(
+        link("<b>@Jane</b> & @ab."),
+        "&lt;b&gt;<a href=\"/jane\" data-role=\"mention\">@Jane</a>&lt;/b&gt; &amp; @ab."
+    );
+    assert_eq!(
+        link("Thanks @mia."),
+        "Thanks <a href=\"/mia\" data-role=\"mention\">@mia</a>."
+    );
+}
+
+#[test]
+fn test_link_html_leaves_tags_and_existing_links_alone() {
+    let html = "<p>Hi @jane</p><a href=\"https://x.com/@sam\">@sam</a>";
+    assert_eq!(
+        link_html(html),
+        "<p>Hi <a href=\"/jane\" data-role=\"mention\">@jane</a></p>\
+         <a href=\"https://x.com/@sam\">@sam</a>"
+    );
+}
@@ -0,0 +1,74 @@
+use chrono::NaiveDate;
+use slatehub::models::person::Profile;
+use slatehub::services::minors::{age_on, is_under_18, redact_profile};
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+#[test]
+fn test_age_on_birthday_boundary() {
+    let today = date(2026, 10, 16);
+    assert_eq!(age_on("2008-10-16", today), Some(18));
+    assert_eq!(age_on("2008-10-17", today), Some(17));
+    assert_eq!(age_on("2008-10-15", today), Some(18));
+    assert_eq!(age_on("2012-01-01", today), Some(14));
+}
+
+#[test]
+fn test_age_on_leap_day() {
+    // Someone born on Feb 29 turns a year older on Mar 1 in common years
+    assert_eq!(age_on("2008-02-29", date(2026, 2, 28)), Some(17));
+    assert_eq!(age_on("2008-02-29", date(2026, 3, 1)), Some(18));
+}
+
+#[test]
+fn test_age_on_invalid() {
+    let today = date(2026, 10, 16);
+    assert_eq!(age_on("", today), None);
+    assert_eq!(age_on("16/10/2008", today), None);
+    assert_eq!(age_on("2030-01-01", today), None);
+}
+
+#[test]
+fn test_is_under_18() {
+    let today = date(2026, 10, 16);
+    assert!(is_under_18("2010-05-01", today));
+    assert!(is_under_18("2008-10-17", today));
+    assert!(!is_under_18("2008-10-16", today));
+    assert!(!is_under_18("1990-01-01", today));
+    // Unparseable birthdays never switch on minor mode
+    assert!(!is_under_18("not a date", today));
+}
+
+#[test]
+fn test_redact_profile() {
+    let mut profile = Profile {
+        headline: Some("Actor".to_string()),
+        phone: Some("555-0100".to_string()),
+        website: Some("https://example.com".to_string()),
+        birthday: Some("2012-01-01".to_string()),
+        height_mm: Some(1500),
+        weight_kg: Some(40),
+        body_type: Some("Other".to_string()),
+        body_type_other: Some("Wiry".to_string()),
+        ethnicity: vec!["Latino".to_string()],
+        hair_color: Some("Brown".to_string()),
+        skills: vec!["Singing".to_string()],
+        ..Default::default()
+    };
+    redact_profile(&mut profile);
+
+    assert!(profile.phone.is_none());
+    assert!(profile.website.is_none());
+    assert!(profile.birthday.is_none());
+    assert!(profile.height_mm.is_none());
+    assert!(profile.weight_kg.is_none());
+    assert!(profile.body_type.is_none());
+    assert!(profile.body_type_other.is_none());
+    assert!(profile.ethnicity.is_empty());
+    // Casting details that aren't sensitive stay
+    assert_eq!(profile.headline.as_deref(), Some("Actor"));
+    assert_eq!(profile.hair_color.as_deref(), Some("Brown"));
+    assert_eq!(profile.skills, vec!["Singing".to_string()]);
+}
@@ -0,0 +1,51 @@
+use slatehub::models::nearby_services::{
+    DEFAULT_RADIUS_KM, MAX_RADIUS_KM, bounding_box, category, clamp_radius,
+};
+use slatehub::units::distance_km;
+
+#[test]
+fn test_categories_by_key_or_type() {
+    assert_eq!(
+        category("rental").map(|c| c.org_type),
+        Some("Equipment Rental")
+    );
+    assert_eq!(
+        category(" Catering ").map(|c| c.org_type),
+        Some("Catering Company")
+    );
+    assert_eq!(
+        category("catering company").map(|c| c.key),
+        Some("catering")
+    );
+    assert!(category("talent").is_none());
+}
+
+#[test]
+fn test_radius_is_clamped() {
+    assert_eq!(clamp_radius(None), DEFAULT_RADIUS_KM);
+    assert_eq!(clamp_radius(Some(10.0)), 10.0);
+    assert_eq!(clamp_radius(Some(0.0)), 1.0);
+    assert_eq!(clamp_radius(Some(5000.0)), MAX_RADIUS_KM);
+    assert_eq!(clamp_radius(Some(f64::NAN)), DEFAULT_RADIUS_KM);
+}
+
+#[test]
+fn test_bounding_box_contains_the_radius() {
+    let center = (34.0522, -118.2437);
+    let ((min_lat, max_lat), longitudes) = bounding_box(center, 50.0);
+    let (min_lng, max_lng) = longitudes.unwrap();
+    // Each edge of the box is at least the radius away
+    assert!(distance_km(center, (min_lat, center.1)) >= 49.99);
+    assert!(distance_km(center, (max_lat, center.1)) >= 49.99);
+    assert!(distance_km(center, (center.0, min_lng)) >= 49.99);
+    assert!(distance_km(center, (center.0, max_lng)) >= 49.99);
+}
+
+#[test]
+fn test_bounding_box_drops_longitude_across_the_antimeridian() {
+    let (_, longitudes) = bounding_box((-17.7, 179.9), 50.0);
+    assert!(longitudes.is_none());
+    let ((_, max_lat), longitudes) = bounding_box((89.9, 0.0), 50.0);
+    assert_eq!(max_lat, 90.0);
+    assert!(longitudes.is_none());
+}
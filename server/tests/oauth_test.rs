@@ -0,0 +1,264 @@
+mod common;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use slatehub::db::DB;
+use slatehub::error::Error;
+use slatehub::services::oauth::{
+    self, ClientData, OAuthClient, basic_credentials, parse_scopes, redirect_with,
+    scope_description, valid_redirect_uri, verifier_matches,
+};
+use slatehub::services::sso::pkce_challenge;
+use surrealdb::types::RecordId;
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn invalid<T>(result: Result<T, Error>) -> bool {
+    matches!(result, Err(Error::Validation(_)))
+}
+
+#[test]
+fn test_valid_redirect_uri() {
+    assert!(valid_redirect_uri(
+        "https://payroll.example.com/oauth/callback"
+    ));
+    assert!(valid_redirect_uri("https://example.com/cb?source=slatehub"));
+    assert!(valid_redirect_uri("http://localhost:3000/callback"));
+    assert!(valid_redirect_uri("http://127.0.0.1/callback"));
+    assert!(valid_redirect_uri("http://[::1]:8080/callback"));
+
+    assert!(!valid_redirect_uri("http://example.com/callback"));
+    assert!(!valid_redirect_uri("https://example.com/callback#done"));
+    assert!(!valid_redirect_uri("https:///callback"));
+    assert!(!valid_redirect_uri("myapp://callback"));
+    assert!(!valid_redirect_uri("http://localhost.example.com/callback"));
+}
+
+#[test]
+fn test_parse_scopes() {
+    let allowed = strings(&["read:profile", "write:equipment"]);
+    assert_eq!(
+        parse_scopes("write:equipment  read:profile write:equipment", &allowed).unwrap(),
+        strings(&["write:equipment", "read:profile"])
+    );
+    // Nothing asked for means everything the app is registered for
+    assert_eq!(parse_scopes(" ", &allowed).unwrap(), allowed);
+    assert!(invalid(parse_scopes("read:timecards", &allowed)));
+
+    assert!(!scope_description("read:profile").is_empty());
+    assert_eq!(scope_description("admin"), "");
+}
+
+#[test]
+fn test_redirect_with() {
+    assert_eq!(
+        redirect_with(
+            "https://example.com/cb",
+            &[("code", "abc"), ("state", "x y&z")]
+        ),
+        "https://example.com/cb?code=abc&state=x%20y%26z"
+    );
+    assert_eq!(
+        redirect_with(
+            "https://example.com/cb?source=slatehub",
+            &[("error", "access_denied")]
+        ),
+        "https://example.com/cb?source=slatehub&error=access_denied"
+    );
+}
+
+#[test]
+fn test_basic_credentials() {
+    let header = format!("Basic {}", STANDARD.encode("client123:slh_cs_secret"));
+    assert_eq!(
+        basic_credentials(&header),
+        Some(("client123".to_string(), "slh_cs_secret".to_string()))
+    );
+    assert_eq!(basic_credentials("Bearer abc"), None);
+    assert_eq!(basic_credentials("Basic !!!"), None);
+    assert_eq!(
+        basic_credentials(&format!("Basic {}", STANDARD.encode("client123:"))),
+        None
+    );
+}
+
+#[test]
+fn test_verifier_matches() {
+    let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+    let challenge = pkce_challenge(verifier);
+    assert!(verifier_matches(Some(&challenge), Some(verifier)));
+    assert!(!verifier_matches(Some(&challenge), Some("wrong")));
+    assert!(!verifier_matches(Some(&challenge), None));
+    // Codes issued without a challenge don't need a verifier
+    assert!(verifier_matches(None, None));
+}
+
+#[test]
+fn test_client_data() {
+    let data = ClientData::parse(
+        " Crew Payroll ",
+        "",
+        "https://payroll.example.com",
+        "https://payroll.example.com/cb\n\nhttp://localhost:3000/cb\nhttps://payroll.example.com/cb",
+        &strings(&["read:profile", "read:timecards", "read:profile"]),
+    )
+    .unwrap();
+    assert_eq!(data.name, "Crew Payroll");
+    assert_eq!(data.description, None);
+    assert_eq!(
+        data.redirect_uris,
+        strings(&["https://payroll.example.com/cb", "http://localhost:3000/cb"])
+    );
+    assert_eq!(data.scopes, strings(&["read:profile", "read:timecards"]));
+
+    let scopes = strings(&["read:profile"]);
+    assert!(invalid(ClientData::parse(
+        "",
+        "",
+        "",
+        "https://a.io/cb",
+        &scopes
+    )));
+    assert!(invalid(ClientData::parse("App", "", "", "", &scopes)));
+    assert!(invalid(ClientData::parse(
+        "App",
+        "",
+        "",
+        "http://a.io/cb",
+        &scopes
+    )));
+    assert!(invalid(ClientData::parse(
+        "App",
+        "",
+        "http://a.io",
+        "https://a.io/cb",
+        &scopes
+    )));
+    assert!(invalid(ClientData::parse(
+        "App",
+        "",
+        "",
+        "https://a.io/cb",
+        &[]
+    )));
+    assert!(invalid(ClientData::parse(
+        "App",
+        "",
+        "",
+        "https://a.io/cb",
+        &strings(&["admin"])
+    )));
+    let many = (0..6)
+        .map(|i| format!("https://a.io/cb{}", i))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(invalid(ClientData::parse("App", "", "", &many, &scopes)));
+}
+
+async fn seed_person(username: &str) -> RecordId {
+    let id: Option<RecordId> = DB
+        .query(
+            "CREATE ONLY person CONTENT {
+                email: string::concat($username, '@example.com'),
+                password: 'hashed_password',
+                username: $username,
+                profile: { name: $username, skills: [], social_links: [], ethnicity: [], unions: [], languages: [], experience: [], education: [], reels: [], media_other: [], awards: [] }
+            } RETURN VALUE id",
+        )
+        .bind(("username", username.to_string()))
+        .await
+        .expect("Failed to create test person")
+        .take(0)
+        .expect("Failed to take person ID");
+    id.expect("No person record returned from CREATE")
+}
+
+async fn register(owner: &RecordId) -> OAuthClient {
+    let data = ClientData {
+        name: "App".to_string(),
+        description: None,
+        homepage_url: None,
+        redirect_uris: strings(&["https://app.example.com/callback"]),
+        scopes: strings(&["read:profile"]),
+    };
+    oauth::register_client(owner, &data)
+        .await
+        .expect("Failed to register app")
+        .0
+}
+
+/// Authorize an app and trade the code for tokens
+async fn authorize(client: &OAuthClient, person: &RecordId) {
+    let redirect_uri = "https://app.example.com/callback";
+    let code = oauth::issue_code(
+        client,
+        person,
+        redirect_uri,
+        &strings(&["read:profile"]),
+        None,
+    )
+    .await
+    .expect("Failed to issue code");
+    oauth::exchange_code(client, &code, redirect_uri, None)
+        .await
+        .expect("Failed to exchange code")
+        .expect("Code was refused");
+}
+
+async fn count(table: &str, field: &str, value: &RecordId) -> i64 {
+    let total: Option<i64> = DB
+        .query(format!(
+            "(SELECT count() FROM {table} WHERE {field} = $value GROUP ALL)[0].count"
+        ))
+        .bind(("value", value.clone()))
+        .await
+        .expect("Failed to count rows")
+        .take(0)
+        .expect("Failed to take count");
+    total.unwrap_or(0)
+}
+
+#[test]
+fn test_forget_revokes_grants_and_deletes_apps() {
+    common::setup_test_db();
+    for table in ["oauth_code", "oauth_token", "oauth_client", "person"] {
+        common::clean_table(table);
+    }
+
+    common::run(async {
+        let leaving = seed_person("leaving").await;
+        let staying = seed_person("staying").await;
+        let leaving_app = register(&leaving).await;
+        let staying_app = register(&staying).await;
+
+        authorize(&staying_app, &leaving).await;
+        authorize(&leaving_app, &staying).await;
+        authorize(&staying_app, &staying).await;
+        oauth::issue_code(
+            &staying_app,
+            &leaving,
+            "https://app.example.com/callback",
+            &[],
+            None,
+        )
+        .await
+        .expect("Failed to issue code");
+
+        oauth::forget(&leaving)
+            .await
+            .expect("Failed to forget person");
+
+        // Their apps, the grants they made, and grants made to their apps
+        assert_eq!(count("oauth_client", "owner", &leaving).await, 0);
+        assert_eq!(count("oauth_code", "person", &leaving).await, 0);
+        assert_eq!(count("oauth_token", "person", &leaving).await, 0);
+        assert_eq!(count("oauth_token", "client", &leaving_app.id).await, 0);
+
+        // Someone else's app keeps its other grants
+        assert_eq!(count("oauth_client", "owner", &staying).await, 1);
+        assert_eq!(count("oauth_token", "person", &staying).await, 1);
+        assert_eq!(count("oauth_token", "client", &staying_app.id).await, 1);
+    });
+}
@@ -0,0 +1,141 @@
+use chrono::{NaiveDate, Utc};
+use slatehub::error::Error;
+use slatehub::models::offer::{
+    CounterTerms, Offer, OfferAction, OfferTerms, dates_label, next_status, rate_label,
+};
+use surrealdb::types::RecordId;
+
+fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(y, m, d).unwrap()
+}
+
+fn today() -> NaiveDate {
+    date(2026, 10, 16)
+}
+
+fn terms(start: &str, end: &str, rate: &str, rate_type: &str) -> Result<OfferTerms, Error> {
+    OfferTerms::parse("Gaffer", start, end, rate, rate_type, "", today())
+}
+
+fn offer() -> Offer {
+    Offer {
+        id: RecordId::new("offer", "o1"),
+        production: RecordId::new("production", "p1"),
+        person: RecordId::new("person", "a"),
+        role_title: "Gaffer".to_string(),
+        start_date: "2026-11-02".to_string(),
+        end_date: "2026-11-06".to_string(),
+        rate_amount: 650.0,
+        rate_type: "daily".to_string(),
+        conditions: None,
+        status: "sent".to_string(),
+        counter_rate_amount: None,
+        counter_start_date: None,
+        counter_end_date: None,
+        response_note: None,
+        shortlist_entry: None,
+        sent_by: RecordId::new("person", "b"),
+        responded_at: None,
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    }
+}
+
+#[test]
+fn people_answer_sent_offers() {
+    assert_eq!(next_status("sent", OfferAction::Accept), Some("accepted"));
+    assert_eq!(next_status("sent", OfferAction::Decline), Some("declined"));
+    assert_eq!(next_status("sent", OfferAction::Counter), Some("countered"));
+    assert_eq!(next_status("countered", OfferAction::Accept), None);
+    assert_eq!(next_status("countered", OfferAction::Counter), None);
+}
+
+#[test]
+fn productions_answer_counters() {
+    assert_eq!(next_status("countered", OfferAction::AcceptCounter), Some("accepted"));
+    assert_eq!(next_status("countered", OfferAction::Revise), Some("sent"));
+    assert_eq!(next_status("countered", OfferAction::Withdraw), Some("withdrawn"));
+    assert_eq!(next_status("sent", OfferAction::AcceptCounter), None);
+}
+
+#[test]
+fn settled_offers_are_final() {
+    for status in ["accepted", "declined", "withdrawn"] {
+        for action in [
+            OfferAction::Accept,
+            OfferAction::Decline,
+            OfferAction::Counter,
+            OfferAction::AcceptCounter,
+            OfferAction::Revise,
+            OfferAction::Withdraw,
+        ] {
+            assert_eq!(next_status(status, action), None, "{} {:?}", status, action);
+        }
+    }
+}
+
+#[test]
+fn terms_are_checked() {
+    let ok = terms("2026-11-02", "2026-11-06", "650", "daily").unwrap();
+    assert_eq!(ok.start_date, date(2026, 11, 2));
+    assert_eq!(ok.end_date, date(2026, 11, 6));
+    assert_eq!(ok.rate_amount, 650.0);
+    assert_eq!(ok.conditions, None);
+
+    assert!(matches!(terms("2026-10-15", "", "650", "daily"), Err(Error::Validation(_))));
+    assert!(matches!(terms("2026-11-06", "2026-11-02", "650", "daily"), Err(Error::Validation(_))));
+    assert!(matches!(terms("2026-11-02", "2027-11-03", "650", "daily"), Err(Error::Validation(_))));
+    assert!(matches!(terms("11/02/2026", "", "650", "daily"), Err(Error::Validation(_))));
+    assert!(matches!(terms("2026-11-02", "", "0", "daily"), Err(Error::Validation(_))));
+    assert!(matches!(terms("2026-11-02", "", "650", "monthly"), Err(Error::Validation(_))));
+    assert!(matches!(
+        OfferTerms::parse("  ", "2026-11-02", "", "650", "daily", "", today()),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn blank_end_date_books_one_day() {
+    let one_day = terms("2026-10-16", "", "$1,200", "flat").unwrap();
+    assert_eq!(one_day.start_date, one_day.end_date);
+    assert_eq!(one_day.rate_amount, 1200.0);
+}
+
+#[test]
+fn counters_have_to_change_something() {
+    let offer = offer();
+    assert!(matches!(
+        CounterTerms::parse(&offer, "", "", "", "Can we talk?", today()),
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        CounterTerms::parse(&offer, "650", "2026-11-02", "", "", today()),
+        Err(Error::Validation(_))
+    ));
+
+    let rate = CounterTerms::parse(&offer, "800", "", "", " Kit included ", today()).unwrap();
+    assert_eq!(rate.rate_amount, Some(800.0));
+    assert_eq!(rate.dates, None);
+    assert_eq!(rate.note.as_deref(), Some("Kit included"));
+
+    // A new start keeps the offered end date
+    let dates = CounterTerms::parse(&offer, "", "2026-11-03", "", "", today()).unwrap();
+    assert_eq!(dates.rate_amount, None);
+    assert_eq!(dates.dates, Some((date(2026, 11, 3), date(2026, 11, 6))));
+}
+
+#[test]
+fn rates_read_naturally() {
+    assert_eq!(rate_label(650.0, "daily"), "650 per day");
+    assert_eq!(rate_label(42.5, "hourly"), "42.50 per hour");
+    assert_eq!(rate_label(2500.0, "weekly"), "2,500 per week");
+    assert_eq!(rate_label(1250000.0, "flat"), "1,250,000 flat");
+}
+
+#[test]
+fn date_ranges_read_naturally() {
+    assert_eq!(dates_label("2026-11-02", "2026-11-02"), "Nov 2, 2026");
+    assert_eq!(dates_label("2026-11-02", "2026-11-06"), "Nov 2 – Nov 6, 2026");
+    assert_eq!(dates_label("2026-12-28", "2027-01-03"), "Dec 28, 2026 – Jan 3, 2027");
+    assert_eq!(dates_label("soon", "later"), "soon – later");
+}
@@ -0,0 +1,107 @@
+use slatehub::models::saved_search::matches;
+use slatehub::services::onboarding::{
+    MAX_ROLE_TYPES, OnboardingAnswers, WorkPreferences, alert_queries, split_list,
+};
+
+fn answers(roles: &[&str], location: Option<&str>) -> OnboardingAnswers {
+    OnboardingAnswers {
+        location: location.map(str::to_string),
+        preferences: WorkPreferences {
+            role_types: roles.iter().map(|r| r.to_string()).collect(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_alert_queries_one_per_role_in_location() {
+    let queries = alert_queries(&answers(
+        &["Camera crew", "Editor"],
+        Some("Berlin, Germany"),
+    ));
+    assert_eq!(
+        queries,
+        vec![
+            ("camera".to_string(), Some("Berlin, Germany".to_string())),
+            ("editor".to_string(), Some("Berlin, Germany".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn test_alert_queries_anywhere_when_travelling_or_remote() {
+    let mut travelling = answers(&["Sound"], Some("Atlanta, GA"));
+    travelling.preferences.travel = true;
+    assert_eq!(
+        alert_queries(&travelling),
+        vec![("sound".to_string(), None)]
+    );
+
+    let mut remote = answers(&["VFX"], Some("Atlanta, GA"));
+    remote.preferences.remote = true;
+    assert_eq!(alert_queries(&remote), vec![("vfx".to_string(), None)]);
+
+    assert_eq!(
+        alert_queries(&answers(&["Writer"], Some("  "))),
+        vec![("writer".to_string(), None)]
+    );
+}
+
+#[test]
+fn test_alert_queries_ignore_unknown_and_extra_roles() {
+    assert!(alert_queries(&answers(&["Astronaut"], None)).is_empty());
+
+    let many = answers(&["Actor", "Director", "Producer", "Writer", "Editor"], None);
+    assert_eq!(alert_queries(&many).len(), MAX_ROLE_TYPES);
+}
+
+#[test]
+fn test_split_list() {
+    assert_eq!(
+        split_list(" Steadicam, , DaVinci Resolve,steadicam "),
+        vec!["Steadicam".to_string(), "DaVinci Resolve".to_string()]
+    );
+    assert!(split_list("").is_empty());
+}
+
+#[test]
+fn test_matches_keywords() {
+    let job = "Camera Operator needed for a short film. Steadicam a plus.";
+    assert!(matches("camera", None, job, Some("Berlin")));
+    assert!(matches("camera steadicam", None, job, None));
+    assert!(!matches("camera drone", None, job, None));
+    assert!(matches("operator", None, job, None));
+    assert!(!matches("art", None, "Shoot starts in May", None));
+    assert!(matches("art", None, "Art department lead", None));
+}
+
+#[test]
+fn test_matches_location() {
+    let job = "Sound mixer for a documentary";
+    assert!(matches(
+        "sound",
+        Some("Berlin"),
+        job,
+        Some("Berlin, Germany")
+    ));
+    assert!(matches(
+        "sound",
+        Some("Berlin, Germany"),
+        job,
+        Some("berlin")
+    ));
+    assert!(matches(
+        "sound",
+        Some("Berlin, Germany"),
+        job,
+        Some("Remote")
+    ));
+    assert!(!matches(
+        "sound",
+        Some("Hamburg"),
+        job,
+        Some("Berlin, Germany")
+    ));
+    assert!(!matches("sound", Some("Hamburg"), job, None));
+}
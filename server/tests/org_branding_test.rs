@@ -0,0 +1,127 @@
+use chrono::Utc;
+use image::{DynamicImage, Rgba, RgbaImage};
+use slatehub::models::call_sheet::call_sheet_pdf;
+use slatehub::models::org_branding::{
+    Brand, Letterhead, flatten, hex_rgb, parse_color, parse_colors, process_wordmark, text_on,
+};
+use slatehub::models::shot_list::ShootDay;
+use surrealdb::types::RecordId;
+
+fn brand(color: Option<&str>, accent: Option<&str>) -> Brand {
+    Brand {
+        name: "Northlight Pictures".to_string(),
+        slug: "northlight".to_string(),
+        image: None,
+        color: color.map(str::to_string),
+        accent: accent.map(str::to_string),
+    }
+}
+
+#[test]
+fn test_parse_color() {
+    assert_eq!(
+        parse_color("#1F6FEB", "Colour").unwrap().as_deref(),
+        Some("#1f6feb")
+    );
+    assert_eq!(
+        parse_color("1f6feb", "Colour").unwrap().as_deref(),
+        Some("#1f6feb")
+    );
+    assert_eq!(
+        parse_color(" #abc ", "Colour").unwrap().as_deref(),
+        Some("#aabbcc")
+    );
+    assert_eq!(parse_color("", "Colour").unwrap(), None);
+    assert!(parse_color("#12345", "Colour").is_err());
+    assert!(parse_color("red", "Colour").is_err());
+    assert!(parse_color("#ggghhh", "Colour").is_err());
+
+    let (color, accent) = parse_colors("#000", "").unwrap();
+    assert_eq!(color.as_deref(), Some("#000000"));
+    assert_eq!(accent, None);
+    assert!(parse_colors("#000", "nope").is_err());
+}
+
+#[test]
+fn test_hex_rgb_and_contrast() {
+    assert_eq!(hex_rgb("#eb5437"), Some([235, 84, 55]));
+    assert_eq!(hex_rgb("eb5437"), None);
+    assert_eq!(text_on([255, 240, 200]), [0, 0, 0]);
+    assert_eq!(text_on([20, 40, 90]), [255, 255, 255]);
+}
+
+#[test]
+fn test_brand_falls_back_to_defaults() {
+    let plain = brand(None, None);
+    assert_eq!(plain.color(), "#eb5437");
+    assert_eq!(plain.accent(), "#eb5437");
+
+    let branded = brand(Some("#102030"), None);
+    assert_eq!(branded.accent(), "#102030");
+    assert_eq!(branded.text_color(), "#ffffff");
+    assert_eq!(
+        brand(Some("#102030"), Some("#ffcc00")).accent_rgb(),
+        [255, 204, 0]
+    );
+}
+
+#[test]
+fn test_email_footer_escapes_the_name() {
+    let mut brand = brand(Some("#102030"), None);
+    brand.name = "<b>Northlight</b>".to_string();
+    let html = brand.email_footer_html();
+    assert!(!html.contains("<b>"));
+    assert!(html.contains("#102030"));
+    assert!(brand.email_footer_text().contains("/orgs/northlight"));
+}
+
+#[test]
+fn test_process_wordmark_scales_down_to_png() {
+    let wide = DynamicImage::ImageRgba8(RgbaImage::new(1600, 200));
+    let mut data = std::io::Cursor::new(Vec::new());
+    wide.write_to(&mut data, image::ImageFormat::Png).unwrap();
+
+    let png = process_wordmark(&data.into_inner()).unwrap();
+    let processed = image::load_from_memory(&png).unwrap();
+    assert_eq!((processed.width(), processed.height()), (800, 100));
+    assert!(png.starts_with(b"\x89PNG"));
+
+    assert!(process_wordmark(b"not an image").is_err());
+}
+
+#[test]
+fn test_flatten_fills_transparency() {
+    let mut image = RgbaImage::new(2, 1);
+    image.put_pixel(0, 0, Rgba([0, 0, 0, 0]));
+    image.put_pixel(1, 0, Rgba([255, 255, 255, 255]));
+    let flat = flatten(&DynamicImage::ImageRgba8(image), [10, 20, 30]).to_rgb8();
+    assert_eq!(flat.get_pixel(0, 0).0, [10, 20, 30]);
+    assert_eq!(flat.get_pixel(1, 0).0, [255, 255, 255]);
+}
+
+#[test]
+fn test_call_sheet_letterhead() {
+    let day = ShootDay {
+        id: RecordId::new("shoot_day", "d2"),
+        production: RecordId::new("production", "p"),
+        day_number: 2,
+        date: "2026-06-02".to_string(),
+        notes: None,
+        location: None,
+        call_sheet_sent_at: None,
+        call_time: None,
+        wrap_time: None,
+        created_at: Utc::now(),
+    };
+    let letterhead = Letterhead {
+        brand: brand(Some("#102030"), None),
+        image: None,
+    };
+    let pdf = call_sheet_pdf("Night Shift", &day, None, &[], &[], Some(&letterhead));
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("(CALL SHEET)"));
+    assert!(text.contains("(Northlight Pictures)"));
+
+    let plain = call_sheet_pdf("Night Shift", &day, None, &[], &[], None);
+    assert!(!String::from_utf8_lossy(&plain).contains("(CALL SHEET)"));
+}
@@ -0,0 +1,48 @@
+use slatehub::services::id_verification::is_private_key;
+use slatehub::services::org_claims::{email_matches_domain, org_domain};
+
+#[test]
+fn domain_comes_from_website_host() {
+    assert_eq!(
+        org_domain(Some("https://www.acmefilms.com/about?x=1"), None).as_deref(),
+        Some("acmefilms.com")
+    );
+    assert_eq!(
+        org_domain(Some("http://studio.example.co.uk:8080"), None).as_deref(),
+        Some("studio.example.co.uk")
+    );
+    assert_eq!(org_domain(Some("Acmefilms.COM"), None).as_deref(), Some("acmefilms.com"));
+}
+
+#[test]
+fn domain_falls_back_to_contact_email() {
+    assert_eq!(
+        org_domain(None, Some("hello@acmefilms.com")).as_deref(),
+        Some("acmefilms.com")
+    );
+    assert_eq!(
+        org_domain(Some("not a url"), Some("hello@acmefilms.com")).as_deref(),
+        Some("acmefilms.com")
+    );
+}
+
+#[test]
+fn webmail_contact_address_gives_no_domain() {
+    assert_eq!(org_domain(None, Some("acmefilms@gmail.com")), None);
+    assert_eq!(org_domain(None, None), None);
+}
+
+#[test]
+fn email_must_be_on_domain_or_subdomain() {
+    assert!(email_matches_domain("jane@acmefilms.com", "acmefilms.com"));
+    assert!(email_matches_domain("Jane@Post.AcmeFilms.com", "acmefilms.com"));
+    assert!(!email_matches_domain("jane@notacmefilms.com", "acmefilms.com"));
+    assert!(!email_matches_domain("jane@acmefilms.com.evil.io", "acmefilms.com"));
+    assert!(!email_matches_domain("@acmefilms.com", "acmefilms.com"));
+    assert!(!email_matches_domain("acmefilms.com", "acmefilms.com"));
+}
+
+#[test]
+fn claim_documents_are_private() {
+    assert!(is_private_key("private/org-claims/acme/01H.pdf"));
+}
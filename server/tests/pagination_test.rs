@@ -0,0 +1,98 @@
+use slatehub::models::pagination::{Cursor, KeyKind, Page, SortKey, after_clause, order_clause};
+use surrealdb::types::RecordId;
+
+const ORDER: &[SortKey] = &[
+    SortKey::desc("_vord", KeyKind::Bool).computed("(verification_status = 'identity')"),
+    SortKey::desc("created_at", KeyKind::Datetime),
+];
+
+#[test]
+fn test_order_clause_ends_with_id() {
+    assert_eq!(
+        order_clause(ORDER),
+        "ORDER BY _vord DESC, created_at DESC, id DESC"
+    );
+    assert_eq!(
+        order_clause(&[SortKey::asc("date", KeyKind::Text)]),
+        "ORDER BY date ASC, id ASC"
+    );
+    assert_eq!(order_clause(&[]), "ORDER BY id ASC");
+}
+
+#[test]
+fn test_after_clause_breaks_ties_key_by_key() {
+    assert_eq!(
+        after_clause(ORDER),
+        "(((verification_status = 'identity') < <bool> $after_0) \
+         OR ((verification_status = 'identity') = <bool> $after_0 AND created_at < <datetime> $after_1) \
+         OR ((verification_status = 'identity') = <bool> $after_0 AND created_at = <datetime> $after_1 AND id < $after_id))"
+    );
+    assert_eq!(
+        after_clause(&[SortKey::asc("date", KeyKind::Text).computed("shoot_day.date")]),
+        "((shoot_day.date > $after_0) OR (shoot_day.date = $after_0 AND id > $after_id))"
+    );
+}
+
+#[test]
+fn test_cursor_round_trip() {
+    let cursor = Cursor::new(
+        vec![
+            "true".to_string(),
+            "2026-03-01T09:30:00.000000000Z".to_string(),
+        ],
+        &RecordId::new("person", "abc123"),
+    );
+    let encoded = cursor.encode();
+    assert!(
+        encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    );
+    let decoded = Cursor::decode(&encoded, "person", ORDER).unwrap();
+    assert_eq!(decoded, cursor);
+    assert_eq!(
+        decoded.binds(),
+        vec![
+            ("after_0".to_string(), "true".to_string()),
+            (
+                "after_1".to_string(),
+                "2026-03-01T09:30:00.000000000Z".to_string()
+            ),
+        ]
+    );
+    assert_eq!(decoded.record_id(), Some(RecordId::new("person", "abc123")));
+}
+
+#[test]
+fn test_cursor_decode_rejects_other_orders() {
+    let cursor = Cursor::new(
+        vec!["true".to_string(), "2026-03-01T09:30:00Z".to_string()],
+        &RecordId::new("person", "abc123"),
+    )
+    .encode();
+    // Another table
+    assert!(Cursor::decode(&cursor, "timecard", ORDER).is_none());
+    // Another number of keys
+    assert!(Cursor::decode(&cursor, "person", &ORDER[..1]).is_none());
+
+    let bad_kind = Cursor::new(
+        vec!["yes".to_string(), "2026-03-01T09:30:00Z".to_string()],
+        &RecordId::new("person", "abc123"),
+    )
+    .encode();
+    assert!(Cursor::decode(&bad_kind, "person", ORDER).is_none());
+    assert!(Cursor::decode("not a cursor", "person", ORDER).is_none());
+}
+
+#[test]
+fn test_page_from_rows() {
+    let cursor = |n: &u32| Cursor::new(vec![n.to_string()], &RecordId::new("item", *n as i64));
+
+    let page = Page::from_rows(vec![1, 2, 3], 2, cursor);
+    assert_eq!(page.items, vec![1, 2]);
+    assert_eq!(page.next, Some(cursor(&2).encode()));
+
+    let last = Page::from_rows(vec![1, 2], 2, cursor);
+    assert_eq!(last.items, vec![1, 2]);
+    assert_eq!(last.next, None);
+}
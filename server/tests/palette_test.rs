@@ -0,0 +1,55 @@
+use slatehub::services::palette::{
+    GROUP_CHATS, GROUP_PAGES, GROUP_SETTINGS, PaletteItem, filter, score, targets,
+};
+
+fn labels(items: &[PaletteItem]) -> Vec<&str> {
+    items.iter().map(|i| i.label.as_str()).collect()
+}
+
+#[test]
+fn test_visitors_get_no_account_pages() {
+    let visitor = targets(false);
+    assert!(visitor.iter().any(|i| i.url == "/people"));
+    assert!(visitor.iter().all(|i| i.group != GROUP_SETTINGS));
+    assert!(!visitor.iter().any(|i| i.url == "/messages"));
+
+    let member = targets(true);
+    assert!(member.iter().any(|i| i.url == "/messages"));
+    assert!(member.iter().any(|i| i.url == "/account/blocks"));
+}
+
+#[test]
+fn test_score() {
+    let blocks = targets(true)
+        .into_iter()
+        .find(|i| i.url == "/account/blocks")
+        .unwrap();
+    assert_eq!(score("", &blocks), Some(0));
+    assert_eq!(score("Blocked", &blocks), Some(2));
+    assert_eq!(score("muted peo", &blocks), Some(1));
+    // Found by its keywords too
+    assert_eq!(score("mute", &blocks), Some(1));
+    assert_eq!(score("unblock", &blocks), None);
+}
+
+#[test]
+fn test_filter_ranks_and_limits_per_group() {
+    let items = vec![
+        PaletteItem::new(GROUP_CHATS, "Sam Janssen", "/messages/conversation:1"),
+        PaletteItem::new(GROUP_CHATS, "Jane Doe", "/messages/conversation:2"),
+        PaletteItem::new(GROUP_CHATS, "Janet Lee", "/messages/conversation:3"),
+        PaletteItem::new(GROUP_PAGES, "Jobs", "/jobs"),
+        PaletteItem::new(GROUP_PAGES, "Projects of Jane", "/jane"),
+    ];
+    let found = filter("jan", items.clone(), 6);
+    assert_eq!(
+        labels(&found),
+        vec!["Jane Doe", "Janet Lee", "Sam Janssen", "Projects of Jane"]
+    );
+
+    let found = filter("jan", items.clone(), 1);
+    assert_eq!(labels(&found), vec!["Jane Doe", "Projects of Jane"]);
+
+    // Nothing typed keeps everything in order
+    assert_eq!(filter("", items.clone(), 6), items);
+}
@@ -0,0 +1,47 @@
+use slatehub::config::PasswordPolicy;
+use slatehub::services::password_policy::{check_rules, describe, find_in_range, sha1_hex};
+
+fn policy() -> PasswordPolicy {
+    PasswordPolicy {
+        min_length: 10,
+        require_mixed_case: true,
+        require_digit: true,
+        require_symbol: false,
+        breach_dir: None,
+    }
+}
+
+#[test]
+fn test_rules() {
+    assert!(check_rules("Correct horse 42", &policy(), &[]).is_empty());
+
+    let problems = check_rules("short", &policy(), &[]);
+    assert_eq!(problems.len(), 3, "{:?}", problems);
+
+    let problems = check_rules("Janedoe-2024!", &policy(), &["janedoe", "jane"]);
+    assert_eq!(problems, vec!["Password must not contain your username or email.".to_string()]);
+}
+
+#[test]
+fn test_describe() {
+    assert_eq!(
+        describe(&policy()),
+        "Must be at least 10 characters long and include upper and lower case letters, a number"
+    );
+}
+
+#[test]
+fn test_breach_range_lookup() {
+    // SHA-1 of "password"
+    let hash = sha1_hex("password");
+    assert_eq!(hash, "5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8");
+
+    let (_, suffix) = hash.split_at(5);
+    let range = "003D68EB55068C33ACE09247EE4C639306B:3\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\r\n";
+    assert_eq!(find_in_range(range, suffix), Some(9545824));
+    assert_eq!(find_in_range(range, "0000000000000000000000000000000000A"), None);
+
+    // Padding entries report a count of zero
+    let padded = "1E4C9B93F3F0682250B6CF8331B7EE68FD8:0\n";
+    assert_eq!(find_in_range(padded, suffix), None);
+}
@@ -0,0 +1,116 @@
+use slatehub::pdf::{Flow, Font, PageSize, PdfDocument, encode_text, text_width, wrap};
+
+fn as_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[test]
+fn document_has_header_xref_and_trailer() {
+    let mut doc = PdfDocument::new(PageSize::A4);
+    doc.text(40.0, 800.0, 12.0, Font::Bold, "Hello");
+    let pdf = as_text(&doc.finish());
+
+    assert!(pdf.starts_with("%PDF-1.4"));
+    assert!(pdf.contains("/BaseFont /Helvetica "));
+    assert!(pdf.contains("/BaseFont /Helvetica-Bold "));
+    assert!(pdf.contains("(Hello) Tj"));
+    assert!(pdf.contains("/MediaBox [0 0 595 842]"));
+    assert!(pdf.trim_end().ends_with("%%EOF"));
+}
+
+#[test]
+fn xref_offsets_point_at_objects() {
+    let mut doc = PdfDocument::new(PageSize::LETTER);
+    doc.new_page();
+    let bytes = doc.finish();
+    let pdf = as_text(&bytes);
+
+    let xref = bytes.windows(5).rposition(|w| w == b"xref\n").unwrap();
+    let entries: Vec<usize> = as_text(&bytes[xref..])
+        .lines()
+        .skip(3)
+        .take_while(|l| l.ends_with(" n "))
+        .map(|l| l[..10].parse().unwrap())
+        .collect();
+    // Catalog, pages, two fonts and a page/content pair per page
+    assert_eq!(entries.len(), 8);
+    for (i, offset) in entries.iter().enumerate() {
+        assert!(bytes[*offset..].starts_with(format!("{} 0 obj", i + 1).as_bytes()));
+    }
+
+    let startxref: usize = pdf.lines().rev().nth(1).unwrap().parse().unwrap();
+    assert_eq!(startxref, xref);
+}
+
+#[test]
+fn text_is_escaped_and_winansi_encoded() {
+    assert_eq!(encode_text("a (b) \\ c"), "(a \\(b\\) \\\\ c)");
+    assert_eq!(encode_text("Café – 5€"), "(Caf\\351 \\226 5\\200)");
+    assert_eq!(encode_text("日本"), "(??)");
+}
+
+#[test]
+fn wrap_respects_width() {
+    let text = "The quick brown fox jumps over the lazy dog again and again";
+    let lines = wrap(text, 100.0, 10.0, Font::Regular);
+    assert!(lines.len() > 1);
+    for line in &lines {
+        assert!(text_width(line, 10.0, Font::Regular) <= 100.0);
+    }
+    assert_eq!(lines.join(" "), text);
+}
+
+#[test]
+fn wrap_splits_long_words_and_keeps_blank_lines() {
+    let lines = wrap("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa", 30.0, 10.0, Font::Regular);
+    assert!(lines.len() > 1);
+    assert_eq!(lines.concat(), "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+
+    assert_eq!(wrap("one\n\ntwo", 200.0, 10.0, Font::Regular), vec!["one", "", "two"]);
+}
+
+#[test]
+fn landscape_swaps_dimensions() {
+    let size = PageSize::A4.landscape();
+    assert_eq!(size.width, 842.0);
+    assert_eq!(size.height, 595.0);
+}
+
+#[test]
+fn long_tables_break_pages_and_number_them() {
+    let mut flow = Flow::new(PageSize::A4).with_footer("Report");
+    let rows: Vec<Vec<String>> = (0..200).map(|i| vec![format!("Row {}", i), "x".to_string()]).collect();
+    flow.table(&["Name", "Value"], &[1.0, 1.0], &rows, 9.0);
+    let pdf = as_text(&flow.finish());
+
+    assert!(pdf.contains("(Report  |  Page 1)"));
+    assert!(pdf.contains("(Row 199)"));
+    // The header repeats on every page
+    let pages = pdf.matches("/Type /Page ").count();
+    assert!(pages > 1);
+    assert_eq!(pdf.matches("(Name) Tj").count(), pages);
+}
+
+#[test]
+fn images_are_embedded_as_jpeg() {
+    let mut doc = PdfDocument::new(PageSize::A4);
+    let image = image::DynamicImage::new_rgb8(4, 4);
+    let id = doc.add_image(&image).unwrap();
+    doc.draw_image(id, 10.0, 10.0, 40.0, 40.0);
+    let pdf = as_text(&doc.finish());
+
+    assert!(pdf.contains("/Filter /DCTDecode"));
+    assert!(pdf.contains("/Im0 Do"));
+    assert!(pdf.contains("/XObject << /Im0 5 0 R >>"));
+}
+
+#[test]
+fn colours_are_scaled_to_unit_range() {
+    let mut doc = PdfDocument::new(PageSize::A4);
+    doc.fill_rect_rgb(0.0, 800.0, 595.0, 42.0, [235, 84, 0]);
+    doc.text_rgb(40.0, 815.0, 14.0, Font::Bold, "Brand", [255, 255, 255]);
+    let pdf = as_text(&doc.finish());
+
+    assert!(pdf.contains("q 0.92 0.33 0 rg 0 800 595 42 re f Q"));
+    assert!(pdf.contains("q 1 1 1 rg BT /F2 14 Tf 40 815 Td (Brand) Tj ET Q"));
+}
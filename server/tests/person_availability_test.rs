@@ -0,0 +1,106 @@
+use chrono::{NaiveDate, Utc};
+use slatehub::error::Error;
+use slatehub::models::offer::Booking;
+use slatehub::models::person_availability::{
+    AvailabilityWindow, UnavailablePeriod, calendar_weeks, clean_period,
+};
+use surrealdb::types::RecordId;
+
+fn date(value: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+}
+
+fn period(start: &str, end: &str, note: Option<&str>) -> UnavailablePeriod {
+    UnavailablePeriod {
+        id: RecordId::new("unavailable_period", start),
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+        note: note.map(str::to_string),
+        created_at: Utc::now(),
+    }
+}
+
+#[test]
+fn test_window_from_search_params() {
+    assert_eq!(AvailabilityWindow::parse(None, Some("")).unwrap(), None);
+    let window = AvailabilityWindow::parse(Some("2026-06-01"), Some("2026-06-05"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (window.first_day(), window.last_day()),
+        ("2026-06-01".to_string(), "2026-06-05".to_string())
+    );
+    // Either date alone is a single day
+    let window = AvailabilityWindow::parse(None, Some("2026-06-03"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (window.from, window.to),
+        (date("2026-06-03"), date("2026-06-03"))
+    );
+}
+
+#[test]
+fn test_window_rejects_bad_dates() {
+    assert!(matches!(
+        AvailabilityWindow::parse(Some("June 1"), None),
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        AvailabilityWindow::parse(Some("2026-06-05"), Some("2026-06-01")),
+        Err(Error::Validation(_))
+    ));
+    assert!(matches!(
+        AvailabilityWindow::parse(Some("2026-01-01"), Some("2027-06-01")),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn test_clean_period_defaults_end_and_trims_note() {
+    assert_eq!(
+        clean_period("2026-06-01", "", "  ").unwrap(),
+        ("2026-06-01".to_string(), "2026-06-01".to_string(), None)
+    );
+    assert_eq!(
+        clean_period("2026-06-01", "2026-06-04", " Holiday ")
+            .unwrap()
+            .2,
+        Some("Holiday".to_string())
+    );
+    assert!(clean_period("", "2026-06-04", "").is_err());
+    assert!(clean_period("2026-06-01", "", &"x".repeat(121)).is_err());
+}
+
+#[test]
+fn test_calendar_marks_unavailable_and_booked_days() {
+    let periods = [
+        period("2026-06-02", "2026-06-03", Some("Holiday")),
+        period("2026-06-10", "2026-06-10", None),
+    ];
+    let bookings = [Booking {
+        production_title: "Night Shift".to_string(),
+        production_slug: "night-shift".to_string(),
+        role_title: "Gaffer".to_string(),
+        start_date: "2026-06-03".to_string(),
+        end_date: "2026-06-04".to_string(),
+    }];
+    // Wednesday; the calendar starts on Monday June 1
+    let weeks = calendar_weeks(&periods, &bookings, date("2026-06-03"), 2);
+    assert_eq!(weeks.len(), 2);
+    let day = |d: &str| {
+        weeks
+            .iter()
+            .flatten()
+            .find(|day| day.date == d)
+            .unwrap()
+            .clone()
+    };
+    assert!(day("2026-06-01").booked_for.is_empty());
+    assert!(day("2026-06-01").is_past);
+    assert_eq!(day("2026-06-02").booked_for, vec!["Holiday"]);
+    assert_eq!(day("2026-06-03").booked_for, vec!["Holiday", "Night Shift"]);
+    assert!(day("2026-06-03").is_today);
+    assert_eq!(day("2026-06-04").booked_for, vec!["Night Shift"]);
+    assert_eq!(day("2026-06-10").booked_for, vec!["Unavailable"]);
+}
@@ -0,0 +1,76 @@
+use slatehub::physical_attributes::{
+    Attribute, OTHER, describe, label, lang_from_accept_language, lookup, normalize,
+};
+use slatehub::services::search_utils::parse_query;
+
+#[test]
+fn test_lookup_matches_values_labels_and_synonyms() {
+    assert_eq!(lookup(Attribute::HairColor, "Blonde"), Some("Blonde"));
+    assert_eq!(lookup(Attribute::HairColor, "  blond "), Some("Blonde"));
+    assert_eq!(lookup(Attribute::HairColor, "Auburn"), Some("Red"));
+    assert_eq!(lookup(Attribute::HairColor, "salt-and-pepper"), Some("Gray"));
+    assert_eq!(lookup(Attribute::EyeColor, "grey"), Some("Gray"));
+    assert_eq!(lookup(Attribute::BodyType, "plus-size"), Some("Plus Size"));
+    assert_eq!(lookup(Attribute::BodyType, "Slim"), Some("Slender"));
+    // Translated labels map back to the stored value
+    assert_eq!(lookup(Attribute::HairColor, "Pelirrojo"), Some("Red"));
+    assert_eq!(lookup(Attribute::EyeColor, "noisette"), Some("Hazel"));
+    assert_eq!(lookup(Attribute::HairColor, "teal"), None);
+    assert_eq!(lookup(Attribute::HairColor, ""), None);
+}
+
+#[test]
+fn test_normalize_keeps_unknown_answers_as_other() {
+    assert_eq!(
+        normalize(Attribute::HairColor, "ginger"),
+        (Some("Red".to_string()), None)
+    );
+    assert_eq!(
+        normalize(Attribute::HairColor, " Teal ombre "),
+        (Some(OTHER.to_string()), Some("Teal ombre".to_string()))
+    );
+    assert_eq!(normalize(Attribute::BodyType, "  "), (None, None));
+}
+
+#[test]
+fn test_label_is_localized() {
+    assert_eq!(label(Attribute::HairColor, "Red", "en"), "Red");
+    assert_eq!(label(Attribute::HairColor, "Red", "es"), "Pelirrojo");
+    assert_eq!(label(Attribute::BodyType, "Athletic", "fr"), "Athlétique");
+    // Unsupported languages fall back to English, unknown values show as is
+    assert_eq!(label(Attribute::EyeColor, "Blue", "de"), "Blue");
+    assert_eq!(label(Attribute::EyeColor, "Violet", "es"), "Violet");
+}
+
+#[test]
+fn test_describe_prefers_own_words_for_other() {
+    assert_eq!(describe(Some("Other"), Some("Teal")), Some("Teal"));
+    assert_eq!(describe(Some("Other"), Some(" ")), Some("Other"));
+    assert_eq!(describe(Some("Brown"), Some("stale")), Some("Brown"));
+    assert_eq!(describe(Some(""), None), None);
+    assert_eq!(describe(None, None), None);
+}
+
+#[test]
+fn test_lang_from_accept_language() {
+    assert_eq!(lang_from_accept_language(Some("es-MX,es;q=0.9,en;q=0.8")), "es");
+    assert_eq!(lang_from_accept_language(Some("de-DE, fr;q=0.7")), "fr");
+    assert_eq!(lang_from_accept_language(Some("de-DE")), "en");
+    assert_eq!(lang_from_accept_language(None), "en");
+}
+
+#[test]
+fn test_query_attributes_use_the_vocabulary() {
+    let parsed = parse_query("auburn haired actresses with grey eyes");
+    assert_eq!(parsed.hair_color.as_deref(), Some("Red"));
+    assert_eq!(parsed.eye_color.as_deref(), Some("Gray"));
+    assert_eq!(parsed.cleaned, "actress");
+
+    let parsed = parse_query("slim dancers");
+    assert_eq!(parsed.body_type.as_deref(), Some("Slender"));
+    assert_eq!(parsed.cleaned, "dancers");
+
+    let parsed = parse_query("platinum blonde models");
+    assert_eq!(parsed.hair_color.as_deref(), Some("Blonde"));
+    assert_eq!(parsed.cleaned, "model");
+}
@@ -0,0 +1,135 @@
+use image::DynamicImage;
+use slatehub::models::person::{AgeRange, DateRange, Education, Profile};
+use slatehub::models::portfolio::{
+    Portfolio, PortfolioCredit, Still, education_line, height_text, media_key, page_size,
+    portfolio_details, portfolio_pdf, release_year,
+};
+use slatehub::pdf::PageSize;
+use slatehub::units::UnitSystem;
+
+fn as_text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn portfolio() -> Portfolio {
+    Portfolio {
+        name: "Alex Morgan".to_string(),
+        headline: Some("Actor & Voice Artist".to_string()),
+        location: Some("Atlanta, GA".to_string()),
+        profile_url: "https://slatehub.com/alexmorgan".to_string(),
+        website: None,
+        phone: None,
+        bio: Some("Stage-trained actor with ten years of screen work.".to_string()),
+        details: vec![("Height", height_text(1750, UnitSystem::Metric))],
+        credits: vec![PortfolioCredit {
+            production: "Harbor Lights".to_string(),
+            role: "Detective Shaw".to_string(),
+            kind: "Feature".to_string(),
+            year: "2023".to_string(),
+        }],
+        education: vec![],
+        awards: vec![],
+        headshot: None,
+        stills: vec![],
+    }
+}
+
+#[test]
+fn page_sizes_are_named() {
+    assert_eq!(page_size("letter"), Some(PageSize::LETTER));
+    assert_eq!(page_size("a4"), Some(PageSize::A4));
+    assert_eq!(page_size("tabloid"), None);
+}
+
+#[test]
+fn only_uploaded_media_has_a_key() {
+    assert_eq!(
+        media_key("/api/media/profiles/p1/photos/a.jpg"),
+        Some("profiles/p1/photos/a.jpg")
+    );
+    assert_eq!(media_key("/api/media/"), None);
+    assert_eq!(media_key("/api/media/../private/x.pdf"), None);
+    assert_eq!(media_key("https://example.com/a.jpg"), None);
+}
+
+#[test]
+fn heights_show_both_units_preferred_first() {
+    assert_eq!(height_text(1800, UnitSystem::Metric), "180 cm (5'11\")");
+    assert_eq!(height_text(1520, UnitSystem::Metric), "152 cm (5'0\")");
+    assert_eq!(height_text(1829, UnitSystem::Imperial), "6'0\" (183 cm)");
+}
+
+#[test]
+fn release_year_needs_four_digits() {
+    assert_eq!(release_year(Some("2021-06-04")), Some("2021".to_string()));
+    assert_eq!(release_year(Some("TBA")), None);
+    assert_eq!(release_year(None), None);
+}
+
+#[test]
+fn education_reads_as_one_line() {
+    let education = Education {
+        institution: "RADA".to_string(),
+        degree: Some("BA".to_string()),
+        field: Some("Acting".to_string()),
+        dates: Some(DateRange {
+            start: Some("2012".to_string()),
+            end: Some("2015".to_string()),
+        }),
+    };
+    assert_eq!(education_line(&education), "RADA - BA, Acting (2012 - 2015)");
+
+    let bare = Education {
+        institution: "Second City".to_string(),
+        degree: None,
+        field: Some(String::new()),
+        dates: None,
+    };
+    assert_eq!(education_line(&bare), "Second City");
+}
+
+#[test]
+fn details_skip_empty_fields() {
+    let profile = Profile {
+        acting_age_range: Some(AgeRange { min: 25, max: 35 }),
+        height_mm: Some(1800),
+        hair_color: Some("Brown".to_string()),
+        eye_color: Some(String::new()),
+        languages: vec!["English".to_string(), "Spanish".to_string()],
+        ..Default::default()
+    };
+    assert_eq!(
+        portfolio_details(&profile, UnitSystem::Metric),
+        vec![
+            ("Playing age", "25 - 35".to_string()),
+            ("Height", "180 cm (5'11\")".to_string()),
+            ("Hair", "Brown".to_string()),
+            ("Languages", "English, Spanish".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn pdf_is_branded_and_lists_credits() {
+    let pdf = as_text(&portfolio_pdf(&portfolio(), PageSize::LETTER));
+    assert!(pdf.starts_with("%PDF-"));
+    assert!(pdf.contains("(SLATEHUB) Tj"));
+    assert!(pdf.contains("(Alex Morgan) Tj"));
+    assert!(pdf.contains("(Credits) Tj"));
+    assert!(pdf.contains("(Harbor Lights) Tj"));
+    assert!(!pdf.contains("/XObject"));
+}
+
+#[test]
+fn headshot_and_stills_are_embedded() {
+    let mut p = portfolio();
+    p.headshot = Some(DynamicImage::new_rgb8(8, 10));
+    p.stills = vec![
+        Still { image: DynamicImage::new_rgb8(16, 9), caption: "On set".to_string() },
+        Still { image: DynamicImage::new_rgb8(9, 16), caption: String::new() },
+    ];
+    let pdf = as_text(&portfolio_pdf(&p, PageSize::A4));
+    assert!(pdf.contains("(Photos) Tj"));
+    assert!(pdf.contains("(On set) Tj"));
+    assert_eq!(pdf.matches("/Filter /DCTDecode").count(), 3);
+}
@@ -0,0 +1,50 @@
+use slatehub::models::preload::{Preload, statement_count};
+use slatehub::models::{person, production};
+
+#[test]
+fn test_preload_sql_is_one_statement() {
+    let preload = Preload::new("production")
+        .with("members", "SELECT * FROM member_of WHERE out = $parent.id")
+        .with("is_claimed", "count(<-member_of[WHERE role = 'owner']) > 0");
+    let sql = preload.sql("production WHERE slug = $slug LIMIT 1");
+    assert_eq!(
+        sql,
+        "SELECT $this AS production,\n    \
+         (SELECT * FROM member_of WHERE out = $parent.id) AS members,\n    \
+         count(<-member_of[WHERE role = 'owner']) > 0 AS is_claimed \
+         FROM production WHERE slug = $slug LIMIT 1"
+    );
+    assert_eq!(statement_count(&sql), 1);
+}
+
+#[test]
+fn test_page_preloads_take_one_round_trip() {
+    let production = production::page_preload();
+    assert_eq!(
+        production.relation_names(),
+        vec!["members", "credits", "is_claimed"]
+    );
+    assert_eq!(
+        statement_count(&production.sql("production WHERE slug = $slug LIMIT 1")),
+        1
+    );
+
+    let person = person::page_preload();
+    assert_eq!(person.relation_names(), vec!["credits", "media"]);
+    let sql = person.sql("person WHERE username = $username LIMIT 1");
+    assert_eq!(statement_count(&sql), 1);
+    assert_eq!(sql.matches("$parent.id").count(), 2);
+}
+
+#[test]
+fn test_statement_count() {
+    assert_eq!(statement_count(""), 0);
+    assert_eq!(statement_count("SELECT * FROM person"), 1);
+    assert_eq!(statement_count("SELECT * FROM person;"), 1);
+    assert_eq!(statement_count("SELECT 1; SELECT 2;\n SELECT 3"), 3);
+    // Semicolons in strings and subqueries don't split statements
+    assert_eq!(
+        statement_count("SELECT 'a;b', (SELECT 1; SELECT 2) AS x FROM person"),
+        1
+    );
+}
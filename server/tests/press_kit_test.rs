@@ -0,0 +1,214 @@
+use slatehub::error::Error;
+use slatehub::models::press_kit::{
+    PressContact, PressCredit, PressKitContent, PressKitData, parse_contacts, press_stills,
+    press_text, still_file_name, trailer_embed, zip_name,
+};
+use slatehub::models::production::ProductionPhoto;
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+fn photo(url: &str, caption: &str) -> ProductionPhoto {
+    ProductionPhoto {
+        url: url.to_string(),
+        thumbnail_url: format!("{}?thumb", url),
+        caption: caption.to_string(),
+    }
+}
+
+fn invalid<T>(result: Result<T, Error>) -> bool {
+    matches!(result, Err(Error::Validation(_)))
+}
+
+#[test]
+fn test_parse_contacts() {
+    let contacts = parse_contacts(
+        &strings(&["Publicity", "", ""]),
+        &strings(&[" Dana Lee ", "Sam Ortiz", ""]),
+        &strings(&["dana@example.com", "", ""]),
+        &strings(&["", "+1 555 0100", ""]),
+    )
+    .unwrap();
+    assert_eq!(
+        contacts,
+        vec![
+            PressContact {
+                role: "Publicity".to_string(),
+                name: "Dana Lee".to_string(),
+                email: Some("dana@example.com".to_string()),
+                phone: None,
+            },
+            PressContact {
+                role: "Press".to_string(),
+                name: "Sam Ortiz".to_string(),
+                email: None,
+                phone: Some("+1 555 0100".to_string()),
+            },
+        ]
+    );
+
+    // A role on its own is a blank row
+    assert!(
+        parse_contacts(&strings(&["Sales"]), &[], &[], &[])
+            .unwrap()
+            .is_empty()
+    );
+
+    assert!(invalid(parse_contacts(
+        &[],
+        &strings(&[""]),
+        &strings(&["dana@example.com"]),
+        &[]
+    )));
+    assert!(invalid(parse_contacts(&[], &strings(&["Dana"]), &[], &[])));
+    assert!(invalid(parse_contacts(
+        &[],
+        &strings(&["Dana"]),
+        &strings(&["dana at example"]),
+        &[]
+    )));
+    assert!(invalid(parse_contacts(
+        &[],
+        &strings(&["A", "B", "C", "D"]),
+        &strings(&["a@x.io", "b@x.io", "c@x.io", "d@x.io"]),
+        &[]
+    )));
+}
+
+#[test]
+fn test_press_kit_data() {
+    let gallery = strings(&["/api/media/a.jpg", "/api/media/b.jpg"]);
+    let data = PressKitData::parse(
+        true,
+        " A lighthouse keeper finds a radio. ",
+        "Long synopsis",
+        "",
+        "https://vimeo.com/123456789",
+        &strings(&["/api/media/b.jpg", "/api/media/a.jpg", "/api/media/b.jpg"]),
+        &gallery,
+        Vec::new(),
+    )
+    .unwrap();
+    assert_eq!(
+        data.logline.as_deref(),
+        Some("A lighthouse keeper finds a radio.")
+    );
+    assert_eq!(data.statement, None);
+    // Kept in the order picked, without repeats
+    assert_eq!(
+        data.stills,
+        strings(&["/api/media/b.jpg", "/api/media/a.jpg"])
+    );
+
+    // Drafts can be saved without a synopsis, published kits can't
+    assert!(PressKitData::parse(false, "", "", "", "", &[], &gallery, Vec::new()).is_ok());
+    assert!(invalid(PressKitData::parse(
+        true,
+        "",
+        "",
+        "",
+        "",
+        &[],
+        &gallery,
+        Vec::new()
+    )));
+    assert!(invalid(PressKitData::parse(
+        false,
+        "",
+        "",
+        "",
+        "https://example.com/trailer.mp4",
+        &[],
+        &gallery,
+        Vec::new()
+    )));
+    assert!(invalid(PressKitData::parse(
+        false,
+        "",
+        "",
+        "",
+        "",
+        &strings(&["https://elsewhere.com/c.jpg"]),
+        &gallery,
+        Vec::new()
+    )));
+}
+
+#[test]
+fn test_trailer_embed() {
+    assert_eq!(
+        trailer_embed("https://www.youtube.com/watch?v=dQw4w9WgXcQ").as_deref(),
+        Some("https://www.youtube.com/embed/dQw4w9WgXcQ")
+    );
+    assert_eq!(
+        trailer_embed("https://vimeo.com/123456789").as_deref(),
+        Some("https://player.vimeo.com/video/123456789")
+    );
+    assert_eq!(trailer_embed("https://example.com/trailer"), None);
+}
+
+#[test]
+fn test_stills() {
+    let photos = vec![
+        photo("/api/media/a.jpg", "Dawn"),
+        photo("/api/media/b.PNG", ""),
+    ];
+    let picked = strings(&[
+        "/api/media/b.PNG",
+        "/api/media/gone.jpg",
+        "/api/media/a.jpg",
+    ]);
+    let stills = press_stills(&photos, &picked);
+    assert_eq!(stills.len(), 2);
+    assert_eq!(stills[0].url, "/api/media/b.PNG");
+    assert_eq!(stills[1].caption, "Dawn");
+
+    assert_eq!(still_file_name(0, "/api/media/b.PNG"), "stills/01.png");
+    assert_eq!(still_file_name(9, "/api/media/c.webp"), "stills/10.webp");
+    assert_eq!(
+        still_file_name(2, "/api/media/no-extension"),
+        "stills/03.jpg"
+    );
+    assert_eq!(zip_name("harbor-lights"), "harbor-lights-press-kit.zip");
+}
+
+#[test]
+fn test_press_text() {
+    let content = PressKitContent {
+        title: "Harbor Lights".to_string(),
+        tagline: "Short Film · 2026".to_string(),
+        logline: Some("A lighthouse keeper finds a radio.".to_string()),
+        synopsis: Some("Long synopsis".to_string()),
+        statement: None,
+        trailer_url: Some("https://vimeo.com/123456789".to_string()),
+        stills: vec![photo("/api/media/a.jpg", "Dawn")],
+        cast: vec![PressCredit {
+            role: "Maya".to_string(),
+            name: "Ana Ruiz".to_string(),
+        }],
+        crew: vec![PressCredit {
+            role: "Director".to_string(),
+            name: "Jo Park".to_string(),
+        }],
+        companies: Vec::new(),
+        selections: Vec::new(),
+        contacts: vec![PressContact {
+            role: "Publicity".to_string(),
+            name: "Dana Lee".to_string(),
+            email: Some("dana@example.com".to_string()),
+            phone: Some("+1 555 0100".to_string()),
+        }],
+        page_url: "https://slatehub.com/productions/harbor-lights/press".to_string(),
+    };
+    let text = press_text(&content);
+    assert!(text.starts_with("Harbor Lights\nShort Film · 2026\n"));
+    assert!(text.contains("\nSynopsis\n--------\nLong synopsis\n"));
+    assert!(!text.contains("Director's Statement"));
+    assert!(text.contains("Key Crew\n--------\nDirector: Jo Park\n"));
+    assert!(text.contains("Cast\n----\nAna Ruiz as Maya\n"));
+    assert!(!text.contains("Companies"));
+    assert!(text.contains("Publicity: Dana Lee (dana@example.com, +1 555 0100)"));
+    assert!(text.contains("stills/01.jpg: Dawn"));
+    assert!(text.ends_with("Online: https://slatehub.com/productions/harbor-lights/press\n"));
+}
@@ -0,0 +1,189 @@
+use chrono::{NaiveDate, Utc};
+use slatehub::error::Error;
+use slatehub::models::equipment_label::checkout_reply;
+use slatehub::models::production_gear::{
+    GearBooking, ManifestLine, calendar_weeks, clean_booking_dates, conflict_message, conflicting,
+    format_value, manifest_csv, manifest_pdf, manifest_total,
+};
+use surrealdb::types::RecordId;
+
+fn line(name: &str, serial: Option<&str>, value: Option<f64>) -> ManifestLine {
+    ManifestLine {
+        id: RecordId::new("production_gear", name.to_lowercase().replace(' ', "_")),
+        equipment: RecordId::new("equipment", name.to_lowercase().replace(' ', "_")),
+        name: name.to_string(),
+        category: "camera".to_string(),
+        manufacturer: Some("Canon".to_string()),
+        model: Some("C70".to_string()),
+        serial_number: serial.map(str::to_string),
+        owner_name: Some("Northlight Rentals".to_string()),
+        kit_name: None,
+        replacement_value: value,
+        start_date: None,
+        end_date: None,
+        added_at: Utc::now(),
+    }
+}
+
+fn booking(production: &str, equipment: &str, start: &str, end: &str) -> GearBooking {
+    GearBooking {
+        id: RecordId::new("production_gear", format!("{}_{}", production, equipment)),
+        production: RecordId::new("production", production),
+        production_title: production.replace('_', " "),
+        production_slug: production.replace('_', "-"),
+        equipment: RecordId::new("equipment", equipment),
+        equipment_name: equipment.replace('_', " "),
+        start_date: start.to_string(),
+        end_date: end.to_string(),
+    }
+}
+
+#[test]
+fn test_format_value() {
+    assert_eq!(format_value(0.0), "0.00");
+    assert_eq!(format_value(950.5), "950.50");
+    assert_eq!(format_value(12500.0), "12,500.00");
+    assert_eq!(format_value(1234567.891), "1,234,567.89");
+}
+
+#[test]
+fn test_manifest_total() {
+    let lines = [
+        line("A Camera", Some("SN-1"), Some(5500.0)),
+        line("B Camera", Some("SN-2"), Some(5500.0)),
+        line("Monitor", None, None),
+    ];
+    assert_eq!(manifest_total(&lines), (11000.0, 1));
+    assert_eq!(manifest_total(&[]), (0.0, 0));
+    assert_eq!(lines[0].make_and_model(), "Canon C70");
+    assert_eq!(lines[0].value_label().as_deref(), Some("$5,500.00"));
+    assert_eq!(lines[2].value_label(), None);
+}
+
+#[test]
+fn test_manifest_csv() {
+    let lines = [
+        line("A Camera", Some("SN-1"), Some(5500.0)),
+        line("Monitor, 7\"", None, None),
+    ];
+    let csv = manifest_csv(&lines);
+    let rows: Vec<&str> = csv.split("\r\n").collect();
+    assert_eq!(
+        rows[0],
+        "Item,Category,Manufacturer,Model,Serial Number,Owner,Kit,Replacement Value"
+    );
+    assert_eq!(
+        rows[1],
+        "A Camera,camera,Canon,C70,SN-1,Northlight Rentals,,5500.00"
+    );
+    assert!(rows[2].starts_with("\"Monitor, 7\"\"\",camera"));
+    assert_eq!(rows[3], "Total,,,,,,,5500.00");
+}
+
+#[test]
+fn test_manifest_pdf() {
+    let lines = [line("A Camera", Some("SN-1"), Some(5500.0))];
+    let pdf =
+        String::from_utf8_lossy(&manifest_pdf("Night Shift", &lines, "June 1, 2026")).into_owned();
+    assert!(pdf.starts_with("%PDF-"));
+    assert!(pdf.contains("(Night Shift) Tj"));
+    assert!(pdf.contains("(SN-1) Tj"));
+    assert!(pdf.contains("($5,500.00) Tj"));
+}
+
+#[test]
+fn test_clean_booking_dates() {
+    assert_eq!(clean_booking_dates("", " ").unwrap(), None);
+    assert_eq!(
+        clean_booking_dates(" 2026-06-01", "2026-06-05 ").unwrap(),
+        Some(("2026-06-01".to_string(), "2026-06-05".to_string()))
+    );
+    assert_eq!(
+        clean_booking_dates("2026-06-01", "2026-06-01").unwrap(),
+        Some(("2026-06-01".to_string(), "2026-06-01".to_string()))
+    );
+
+    let invalid = |result: Result<_, Error>| matches!(result, Err(Error::Validation(_)));
+    assert!(invalid(clean_booking_dates("2026-06-01", "")));
+    assert!(invalid(clean_booking_dates("", "2026-06-05")));
+    assert!(invalid(clean_booking_dates("June 1", "2026-06-05")));
+    assert!(invalid(clean_booking_dates("2026-06-05", "2026-06-01")));
+    assert!(invalid(clean_booking_dates("2026-01-01", "2027-01-02")));
+}
+
+#[test]
+fn test_conflicting() {
+    let bookings = [
+        booking("night_shift", "canon_c70", "2026-06-01", "2026-06-05"),
+        booking("day_off", "canon_c70", "2026-06-10", "2026-06-10"),
+    ];
+    let titles = |found: Vec<&GearBooking>| -> Vec<String> {
+        found.iter().map(|b| b.production_title.clone()).collect()
+    };
+    assert_eq!(
+        titles(conflicting(&bookings, "2026-06-05", "2026-06-10", None)),
+        ["night shift", "day off"]
+    );
+    assert!(conflicting(&bookings, "2026-06-06", "2026-06-09", None).is_empty());
+    assert!(conflicting(&bookings, "2026-05-20", "2026-05-31", None).is_empty());
+
+    // A production's own booking never conflicts with itself
+    let own = RecordId::new("production", "night_shift");
+    assert_eq!(
+        titles(conflicting(
+            &bookings,
+            "2026-06-01",
+            "2026-06-30",
+            Some(&own)
+        )),
+        ["day off"]
+    );
+
+    let found = conflicting(&bookings, "2026-06-01", "2026-06-30", None);
+    assert_eq!(
+        conflict_message(&found),
+        "canon c70 is booked for night shift, 2026-06-01 to 2026-06-05; \
+         canon c70 is booked for day off, 2026-06-10."
+    );
+}
+
+#[test]
+fn test_checkout_reply() {
+    assert_eq!(
+        checkout_reply("Canon C70", None, &[]),
+        "*Canon C70* is checked out to you."
+    );
+    let later = booking("night_shift", "canon_c70", "2026-06-10", "2026-06-12");
+    let reply = checkout_reply("Canon C70", Some("2026-06-05"), &[&later]);
+    assert!(reply.starts_with("*Canon C70* is checked out to you until 2026-06-05."));
+    assert!(
+        reply.contains("Heads up: canon c70 is booked for night shift, 2026-06-10 to 2026-06-12.")
+    );
+}
+
+#[test]
+fn test_calendar_weeks() {
+    // A Wednesday
+    let today = NaiveDate::from_ymd_opt(2026, 6, 3).unwrap();
+    let bookings = [booking(
+        "night_shift",
+        "canon_c70",
+        "2026-06-02",
+        "2026-06-08",
+    )];
+    let weeks = calendar_weeks(&bookings, today, 2);
+    assert_eq!(weeks.len(), 2);
+    assert!(weeks.iter().all(|week| week.len() == 7));
+
+    // Starts on the Monday of this week
+    assert_eq!(weeks[0][0].date, "2026-06-01");
+    assert!(weeks[0][0].is_past);
+    assert!(weeks[0][0].booked_for.is_empty());
+    assert_eq!(weeks[0][1].booked_for, ["night shift"]);
+    assert!(weeks[0][2].is_today);
+    assert!(!weeks[0][2].is_past);
+    assert_eq!(weeks[1][0].date, "2026-06-08");
+    assert_eq!(weeks[1][0].booked_for, ["night shift"]);
+    assert!(weeks[1][1].booked_for.is_empty());
+    assert_eq!(weeks[1][6].day, 14);
+}
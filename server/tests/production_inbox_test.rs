@@ -0,0 +1,110 @@
+use base64::Engine;
+use slatehub::models::production_inbox::{
+    InboundAttachment, InboundEmail, MAX_ATTACHMENTS, MAX_SUBJECT_CHARS, address, file_key,
+    parse_mailbox, token_from_recipients,
+};
+
+fn attachment(filename: &str, data: &[u8]) -> InboundAttachment {
+    InboundAttachment {
+        filename: filename.to_string(),
+        content_type: Some("application/pdf".to_string()),
+        content: base64::engine::general_purpose::STANDARD.encode(data),
+    }
+}
+
+#[test]
+fn test_parse_mailbox() {
+    assert_eq!(
+        parse_mailbox("\"Jane Doe\" <Jane@Example.com>"),
+        (Some("Jane Doe".to_string()), "jane@example.com".to_string())
+    );
+    assert_eq!(
+        parse_mailbox("<quotes@rentals.com>"),
+        (None, "quotes@rentals.com".to_string())
+    );
+    assert_eq!(
+        parse_mailbox(" quotes@rentals.com "),
+        (None, "quotes@rentals.com".to_string())
+    );
+}
+
+#[test]
+fn test_token_from_recipients() {
+    let to = address("my-film", "k3x9q2m7p4ra", "inbox.example.com");
+    assert_eq!(to, "my-film.k3x9q2m7p4ra@inbox.example.com");
+    assert_eq!(
+        token_from_recipients(&to, "inbox.example.com").as_deref(),
+        Some("k3x9q2m7p4ra")
+    );
+
+    // Among other recipients, and with the case changed by a mail client
+    let header = "Jane <jane@example.com>, \"My Film\" <My-Film.K3X9Q2M7P4RA@Inbox.Example.com>";
+    assert_eq!(
+        token_from_recipients(header, "inbox.example.com").as_deref(),
+        Some("k3x9q2m7p4ra")
+    );
+
+    // Other domains and malformed tokens don't match
+    assert_eq!(token_from_recipients(&to, "example.com"), None);
+    assert_eq!(
+        token_from_recipients("my-film.short@inbox.example.com", "inbox.example.com"),
+        None
+    );
+    assert_eq!(
+        token_from_recipients(
+            "my-film.k3x9q2m7p4r!@inbox.example.com",
+            "inbox.example.com"
+        ),
+        None
+    );
+}
+
+#[test]
+fn test_clean_subject_and_body() {
+    let mut email = InboundEmail::default();
+    assert_eq!(email.clean_subject(), "(no subject)");
+    assert_eq!(email.clean_body(), None);
+
+    email.subject = "x".repeat(MAX_SUBJECT_CHARS + 10);
+    email.text = Some("  Quote attached.\n  ".to_string());
+    assert_eq!(email.clean_subject().chars().count(), MAX_SUBJECT_CHARS);
+    assert_eq!(email.clean_body().as_deref(), Some("Quote attached."));
+}
+
+#[test]
+fn test_attachments() {
+    let email = InboundEmail {
+        attachments: vec![
+            attachment("Camera Quote.pdf", b"%PDF-1.7"),
+            attachment("setup.exe", b"MZ"),
+            attachment("empty.pdf", b""),
+            InboundAttachment {
+                filename: "broken.pdf".to_string(),
+                content_type: None,
+                content: "not base64!".to_string(),
+            },
+        ],
+        ..Default::default()
+    };
+    let kept = email.attachments();
+    assert_eq!(kept.len(), 1);
+    assert_eq!(kept[0].file_name, "Camera-Quote.pdf");
+    assert_eq!(kept[0].mime_type, "application/pdf");
+    assert_eq!(&kept[0].data[..], b"%PDF-1.7");
+
+    let many = InboundEmail {
+        attachments: (0..MAX_ATTACHMENTS + 3)
+            .map(|i| attachment(&format!("page-{}.pdf", i), b"data"))
+            .collect(),
+        ..Default::default()
+    };
+    assert_eq!(many.attachments().len(), MAX_ATTACHMENTS);
+}
+
+#[test]
+fn test_file_key() {
+    assert_eq!(
+        file_key("film", "msg", "01J", "Vendor Quote.pdf"),
+        "private/inbox/film/msg/01J-Vendor-Quote.pdf"
+    );
+}
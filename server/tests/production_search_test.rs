@@ -0,0 +1,51 @@
+use slatehub::models::production::{ProductionLink, link_labels};
+use surrealdb::types::RecordId;
+
+fn link(person: &str, kind: &str, role: Option<&str>) -> ProductionLink {
+    ProductionLink {
+        person: RecordId::new("person", person),
+        kind: kind.to_string(),
+        role: role.map(str::to_string),
+    }
+}
+
+#[test]
+fn links_are_labelled_by_kind() {
+    assert_eq!(link("a", "crew", Some("Gaffer")).label(), "Crew: Gaffer");
+    assert_eq!(
+        link("a", "cast", Some("Ophelia")).label(),
+        "Cast as Ophelia"
+    );
+    assert_eq!(
+        link("a", "applicant", Some("Drone Operator")).label(),
+        "Applied for Drone Operator"
+    );
+    assert_eq!(
+        link("a", "member", Some("Director, Producer")).label(),
+        "Team: Director, Producer"
+    );
+    assert_eq!(
+        link("a", "credit", Some("Thanks")).label(),
+        "Credit: Thanks"
+    );
+}
+
+#[test]
+fn blank_roles_leave_just_the_kind() {
+    assert_eq!(link("a", "member", Some("")).label(), "Team");
+    assert_eq!(link("a", "crew", Some("  ")).label(), "Crew");
+    assert_eq!(link("a", "applicant", None).label(), "Applicant");
+}
+
+#[test]
+fn labels_are_grouped_per_person() {
+    let labels = link_labels(&[
+        link("a", "member", Some("Gaffer")),
+        link("a", "crew", Some("Gaffer")),
+        link("a", "crew", Some("Gaffer")),
+        link("b", "applicant", Some("Grip")),
+    ]);
+    assert_eq!(labels.len(), 2);
+    assert_eq!(labels["person:a"], vec!["Team: Gaffer", "Crew: Gaffer"]);
+    assert_eq!(labels["person:b"], vec!["Applied for Grip"]);
+}
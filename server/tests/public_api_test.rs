@@ -0,0 +1,46 @@
+use slatehub::services::public_api::{RateLimiter, WINDOW_SECS, etag_for, etag_matches};
+
+#[test]
+fn test_etag_for() {
+    let etag = etag_for(br#"{"username":"ana"}"#);
+    assert!(etag.starts_with('"') && etag.ends_with('"'));
+    assert_eq!(etag.len(), 34);
+    assert_eq!(etag, etag_for(br#"{"username":"ana"}"#));
+    assert_ne!(etag, etag_for(br#"{"username":"ana.r"}"#));
+}
+
+#[test]
+fn test_etag_matches() {
+    let etag = etag_for(b"body");
+    assert!(etag_matches(&etag, &etag));
+    assert!(etag_matches(&format!("W/{}", etag), &etag));
+    assert!(etag_matches(&format!("\"stale\", {}", etag), &etag));
+    assert!(etag_matches("*", &etag));
+    assert!(!etag_matches("\"stale\"", &etag));
+    assert!(!etag_matches("", &etag));
+}
+
+#[test]
+fn test_rate_limiter() {
+    let limiter = RateLimiter::default();
+    let start = 1_800_000_000 - 1_800_000_000 % WINDOW_SECS;
+
+    let first = limiter.check("ip:203.0.113.5", 2, start + 10);
+    assert!(first.allowed);
+    assert_eq!(first.remaining, 1);
+    assert_eq!(first.reset_secs, WINDOW_SECS - 10);
+
+    assert!(limiter.check("ip:203.0.113.5", 2, start + 11).allowed);
+    let over = limiter.check("ip:203.0.113.5", 2, start + 12);
+    assert!(!over.allowed);
+    assert_eq!(over.remaining, 0);
+
+    // Other callers have their own allowance
+    assert!(limiter.check("app:abc", 2, start + 12).allowed);
+
+    // A new window starts over
+    let next = limiter.check("ip:203.0.113.5", 2, start + WINDOW_SECS);
+    assert!(next.allowed);
+    assert_eq!(next.remaining, 1);
+    assert_eq!(next.reset_secs, WINDOW_SECS);
+}
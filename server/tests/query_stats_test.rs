@@ -0,0 +1,102 @@
+mod common;
+
+use slatehub::config::QueryInstrumentation;
+use slatehub::db::{DB, QueryStats, current_query_count, is_slow, reader, with_query_stats};
+use std::time::Duration;
+
+fn settings(slow_query_ms: u64, query_budget: u32, enforce_budget: bool) -> QueryInstrumentation {
+    QueryInstrumentation {
+        slow_query_ms,
+        query_budget,
+        enforce_budget,
+    }
+}
+
+#[test]
+fn test_slow_query_threshold() {
+    let on = settings(250, 0, false);
+    assert!(!is_slow(&on, Duration::from_millis(249)));
+    assert!(is_slow(&on, Duration::from_millis(250)));
+    assert!(is_slow(&on, Duration::from_secs(2)));
+
+    // 0 turns slow-query logging off
+    let off = settings(0, 0, false);
+    assert!(!is_slow(&off, Duration::from_secs(60)));
+}
+
+#[test]
+fn test_queries_are_timed() {
+    let stats = QueryStats::new("/test");
+    stats.record(Duration::from_millis(3));
+    stats.record(Duration::from_micros(2500));
+    assert_eq!(stats.count(), 2);
+    assert_eq!(stats.total_ms(), 5);
+}
+
+#[test]
+fn test_budget_is_only_enforced_when_asked() {
+    let stats = QueryStats::new("/people/{username}");
+    for _ in 0..3 {
+        stats.record(Duration::ZERO);
+    }
+
+    // Over budget, but only warned about by the middleware
+    assert_eq!(
+        stats.budget_error(&settings(250, 3, false), "src/a.rs:1:1"),
+        None
+    );
+    // No budget
+    assert_eq!(
+        stats.budget_error(&settings(250, 0, true), "src/a.rs:1:1"),
+        None
+    );
+    // Still within it
+    assert_eq!(
+        stats.budget_error(&settings(250, 4, true), "src/a.rs:1:1"),
+        None
+    );
+
+    let error = stats
+        .budget_error(&settings(250, 3, true), "src/a.rs:1:1")
+        .expect("the fourth query is over a budget of three");
+    assert!(error.contains("query budget of 3"));
+    assert!(error.contains("/people/{username}"));
+    assert!(error.contains("src/a.rs:1:1"));
+}
+
+#[test]
+fn test_every_query_is_counted() {
+    common::setup_test_db();
+
+    common::run(async {
+        let (count, stats) = with_query_stats("/test".to_string(), async {
+            DB.query("RETURN 1").await.unwrap();
+            DB.query("RETURN $value").bind(("value", 2)).await.unwrap();
+            reader().query("RETURN 3").await.unwrap();
+            current_query_count()
+        })
+        .await;
+        assert_eq!(count, Some(3));
+        assert_eq!(stats.count(), 3);
+        assert_eq!(stats.route, "/test");
+
+        // Queries outside a request aren't counted anywhere
+        DB.query("RETURN 4").await.unwrap();
+        assert_eq!(current_query_count(), None);
+        assert_eq!(stats.count(), 3);
+    });
+}
+
+#[test]
+fn test_failed_queries_are_counted() {
+    common::setup_test_db();
+
+    common::run(async {
+        let (_, stats) = with_query_stats("/test".to_string(), async {
+            let response = DB.query("THROW 'nope'").await.unwrap();
+            assert!(response.check().is_err());
+        })
+        .await;
+        assert_eq!(stats.count(), 1);
+    });
+}
@@ -0,0 +1,33 @@
+use slatehub::models::reaction::{tally, target};
+
+#[test]
+fn test_tally_counts_in_palette_order() {
+    let counts = tally(
+        [
+            ("🎬", "person:a"),
+            ("👍", "person:b"),
+            ("🎬", "person:b"),
+            ("👍", "person:c"),
+        ],
+        "person:a",
+    );
+    let summary: Vec<(&str, usize, bool)> = counts
+        .iter()
+        .map(|c| (c.emoji.as_str(), c.count, c.mine))
+        .collect();
+    assert_eq!(summary, vec![("👍", 2, false), ("🎬", 2, true)]);
+}
+
+#[test]
+fn test_tally_ignores_unknown_emoji() {
+    assert!(tally([("🦀", "person:a")], "person:a").is_empty());
+    assert!(tally([], "person:a").is_empty());
+}
+
+#[test]
+fn test_target_only_accepts_reactable_tables() {
+    assert!(target("direct_message", "abc").is_ok());
+    assert!(target("inbox_message", "abc").is_ok());
+    assert!(target("person", "abc").is_err());
+    assert!(target("direct_message", "").is_err());
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use slatehub::config::DatabaseConfig;
+use slatehub::db::{self, DB, DB_READ};
+
+/// Each database holds a probe record naming which one it is, so a read shows
+/// where it was routed
+const PROBE: &str = "SELECT VALUE source FROM ONLY replica_probe:one";
+
+async fn reader_source() -> Option<String> {
+    db::reader()
+        .query(PROBE)
+        .await
+        .expect("Failed to read through reader()")
+        .take(0)
+        .expect("Failed to take source")
+}
+
+async fn primary_source() -> Option<String> {
+    DB.query(PROBE)
+        .await
+        .expect("Failed to read through DB")
+        .take(0)
+        .expect("Failed to take source")
+}
+
+fn replica_config() -> DatabaseConfig {
+    DatabaseConfig {
+        host: "localhost".to_string(),
+        port: 8100,
+        username: "root".to_string(),
+        password: "root".to_string(),
+        namespace: "slatehub-test".to_string(),
+        name: "replica".to_string(),
+    }
+}
+
+// One test, because the replica connection is process-wide: the fallback has
+// to be checked before the replica is connected
+#[test]
+fn test_reads_go_to_the_replica_and_writes_to_the_primary() {
+    common::setup_test_db();
+
+    common::run(async {
+        DB.query("DELETE replica_probe; CREATE replica_probe:one SET source = 'primary'")
+            .await
+            .expect("Failed to seed primary");
+
+        // No replica configured: reads fall back to the primary
+        assert!(!db::replica_available());
+        assert_eq!(reader_source().await.as_deref(), Some("primary"));
+
+        // SAFETY: nothing else in this test binary reads the environment concurrently
+        unsafe { std::env::set_var("DATABASE_READ_URL", "localhost:8100") };
+        db::connect_replica(&replica_config()).await;
+        assert!(db::replica_available());
+
+        DB_READ
+            .query("DELETE replica_probe; CREATE replica_probe:one SET source = 'replica'")
+            .await
+            .expect("Failed to seed replica");
+
+        // Reads go to the replica, DB stays on the primary
+        assert_eq!(reader_source().await.as_deref(), Some("replica"));
+        assert_eq!(primary_source().await.as_deref(), Some("primary"));
+
+        // Writes land on the primary only
+        DB.query("UPDATE replica_probe:one SET source = 'written'")
+            .await
+            .expect("Failed to write");
+        assert_eq!(primary_source().await.as_deref(), Some("written"));
+        assert_eq!(reader_source().await.as_deref(), Some("replica"));
+
+        // Read-your-writes flows can pin reads to the primary
+        let pinned = db::prefer_primary(reader_source()).await;
+        assert_eq!(pinned.as_deref(), Some("written"));
+
+        DB.query("DELETE replica_probe").await.unwrap();
+        DB_READ.query("DELETE replica_probe").await.unwrap();
+    });
+}
@@ -0,0 +1,88 @@
+use slatehub::db::{READ_RETRIES, is_connection_error, retry_read, should_retry_read};
+use slatehub::error::Error;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+const DROPPED: &str = "There was an error processing a remote WS request: Connection reset";
+
+#[test]
+fn test_dropped_connections_are_retried() {
+    for message in [
+        DROPPED,
+        "WebSocket protocol error",
+        "Broken pipe (os error 32)",
+        "The connection timed out",
+        "channel closed",
+    ] {
+        assert!(is_connection_error(message), "{message}");
+        assert!(should_retry_read(0, message), "{message}");
+    }
+}
+
+#[test]
+fn test_query_errors_are_not_retried() {
+    for message in [
+        "Parse error: Unexpected token `FORM`",
+        "Found NONE for field `name`, with record `person:a`, but expected a string",
+        "IAM error: Not enough permissions to perform this action",
+    ] {
+        assert!(!is_connection_error(message), "{message}");
+        assert!(!should_retry_read(0, message), "{message}");
+    }
+}
+
+#[test]
+fn test_reads_are_retried_twice() {
+    assert_eq!(READ_RETRIES, 2);
+    assert!(should_retry_read(0, DROPPED));
+    assert!(should_retry_read(1, DROPPED));
+    assert!(!should_retry_read(2, DROPPED));
+}
+
+#[tokio::test]
+async fn test_retry_read_gives_up_after_the_retries() {
+    let attempts = AtomicU32::new(0);
+    let result: Result<(), Error> = retry_read("test", || async {
+        attempts.fetch_add(1, Ordering::Relaxed);
+        Err(Error::Database(DROPPED.to_string()))
+    })
+    .await;
+    assert!(matches!(result, Err(Error::Database(_))));
+    assert_eq!(attempts.load(Ordering::Relaxed), READ_RETRIES + 1);
+}
+
+#[tokio::test]
+async fn test_retry_read_returns_the_first_success() {
+    let attempts = AtomicU32::new(0);
+    let result = retry_read("test", || async {
+        if attempts.fetch_add(1, Ordering::Relaxed) == 0 {
+            Err(Error::Database(DROPPED.to_string()))
+        } else {
+            Ok(7)
+        }
+    })
+    .await;
+    assert_eq!(result.unwrap(), 7);
+    assert_eq!(attempts.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+async fn test_retry_read_only_retries_database_errors() {
+    let attempts = AtomicU32::new(0);
+    let result: Result<(), Error> = retry_read("test", || async {
+        attempts.fetch_add(1, Ordering::Relaxed);
+        Err(Error::Database("Parse error: Unexpected token".to_string()))
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::Relaxed), 1);
+
+    // A dropped connection to some other service isn't the database's
+    let attempts = AtomicU32::new(0);
+    let result: Result<(), Error> = retry_read("test", || async {
+        attempts.fetch_add(1, Ordering::Relaxed);
+        Err(Error::ExternalService(DROPPED.to_string()))
+    })
+    .await;
+    assert!(result.is_err());
+    assert_eq!(attempts.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,46 @@
+use slatehub::models::representation::{ContactMode, Representation, redirect_notice};
+use surrealdb::types::RecordId;
+
+fn representation(contact_username: Option<&str>) -> Representation {
+    Representation {
+        id: RecordId::new("represented_by", "r1"),
+        organization: RecordId::new("organization", "o1"),
+        org_name: "North Star Talent".to_string(),
+        org_slug: "north-star".to_string(),
+        status: "accepted".to_string(),
+        contact: contact_username.map(|_| RecordId::new("person", "p2")),
+        contact_username: contact_username.map(str::to_string),
+        contact_name: None,
+    }
+}
+
+#[test]
+fn test_contact_mode_round_trip() {
+    for mode in ContactMode::ALL {
+        assert_eq!(ContactMode::parse(mode.as_str()), Some(mode));
+    }
+    assert_eq!(ContactMode::parse("agent"), None);
+    assert_eq!(ContactMode::default(), ContactMode::Direct);
+}
+
+#[test]
+fn test_redirect_notice_names_the_contact() {
+    let notice = redirect_notice("Ada", &representation(Some("sam")));
+    assert_eq!(
+        notice,
+        "Ada takes inquiries through North Star Talent. Message their representative, @sam, instead."
+    );
+    assert_eq!(
+        redirect_notice("Ada", &representation(None)),
+        "Ada takes inquiries through North Star Talent."
+    );
+}
+
+#[test]
+fn test_contact_display_prefers_name() {
+    let mut rep = representation(Some("sam"));
+    assert_eq!(rep.contact_display(), Some("sam"));
+    rep.contact_name = Some("Sam Reyes".to_string());
+    assert_eq!(rep.contact_display(), Some("Sam Reyes"));
+    assert!(rep.is_accepted());
+}
@@ -0,0 +1,102 @@
+use futures::{StreamExt, executor::block_on, stream};
+use serde_json::json;
+use slatehub::error::Error;
+use slatehub::response::{is_local_path, json_list_chunks, json_stream_chunks, local_path};
+
+fn joined(head: serde_json::Value, key: Option<&str>, items: Vec<u32>) -> serde_json::Value {
+    let body: String = json_list_chunks(head, key, items)
+        .collect::<serde_json::Result<Vec<String>>>()
+        .unwrap()
+        .concat();
+    serde_json::from_str(&body).unwrap()
+}
+
+#[test]
+fn test_json_list_matches_whole_serialization() {
+    let items: Vec<u32> = (0..250).collect();
+    assert_eq!(
+        joined(
+            json!({ "query": "gaffer", "page": 2 }),
+            Some("results"),
+            items.clone()
+        ),
+        json!({ "query": "gaffer", "page": 2, "results": items })
+    );
+}
+
+#[test]
+fn test_json_list_without_head_or_key() {
+    assert_eq!(
+        joined(json!({}), Some("equipment"), vec![1, 2]),
+        json!({ "equipment": [1, 2] })
+    );
+    assert_eq!(joined(json!({}), None, vec![1, 2]), json!([1, 2]));
+    assert_eq!(joined(json!({}), None, vec![]), json!([]));
+}
+
+#[test]
+fn test_json_list_is_chunked() {
+    let items: Vec<u32> = (0..250).collect();
+    // Opening, three chunks of items, closing
+    assert_eq!(json_list_chunks(json!({}), None, items).count(), 5);
+}
+
+fn streamed(pages: Vec<Result<Vec<u32>, Error>>) -> Vec<Result<String, Error>> {
+    block_on(
+        json_stream_chunks(
+            json!({ "exported_at": "now" }),
+            Some("items"),
+            stream::iter(pages),
+        )
+        .collect(),
+    )
+}
+
+#[test]
+fn test_json_stream_matches_whole_serialization() {
+    let chunks = streamed(vec![Ok(vec![1, 2]), Ok(vec![]), Ok(vec![3])]);
+    // Opening, two non-empty pages, closing
+    assert_eq!(chunks.len(), 4);
+    let body: String = chunks.into_iter().map(Result::unwrap).collect();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&body).unwrap(),
+        json!({ "exported_at": "now", "items": [1, 2, 3] })
+    );
+}
+
+#[test]
+fn test_json_stream_stops_at_an_error() {
+    let chunks = streamed(vec![
+        Ok(vec![1]),
+        Err(Error::Database("gone".to_string())),
+        Ok(vec![2]),
+    ]);
+    assert_eq!(chunks.len(), 3);
+    assert!(chunks[2].is_err());
+}
+
+#[test]
+fn test_local_paths_stay_on_site() {
+    for path in ["/", "/profile", "/orgs/acme?tab=members", "/search?q=a//b"] {
+        assert!(is_local_path(path), "{}", path);
+    }
+    for path in [
+        "",
+        "profile",
+        "https://evil.com",
+        "//evil.com",
+        "/\\evil.com",
+        "/\tevil.com",
+        "/\n/evil.com",
+        "/profile\r\nSet-Cookie: a=b",
+    ] {
+        assert!(!is_local_path(path), "{:?}", path);
+    }
+
+    assert_eq!(
+        local_path(Some("/jobs".to_string())).as_deref(),
+        Some("/jobs")
+    );
+    assert_eq!(local_path(Some("/\\evil.com".to_string())), None);
+    assert_eq!(local_path(None), None);
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, TimeZone, Utc};
+use slatehub::services::retention::{PurgeCounts, RetentionPolicy, rule_label};
+
+fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()
+}
+
+#[test]
+fn test_blank_fields_keep_everything() {
+    let policy = RetentionPolicy::parse("", " ", "").unwrap();
+    assert!(policy.is_empty());
+    assert_eq!(policy.cutoffs(at(2026, 3, 10)).applications, None);
+}
+
+#[test]
+fn test_parse_periods() {
+    let policy = RetentionPolicy::parse("18", "30", " 90 ").unwrap();
+    assert_eq!(policy.applications_months, Some(18));
+    assert_eq!(policy.invites_days, Some(30));
+    assert_eq!(policy.share_links_days, Some(90));
+}
+
+#[test]
+fn test_parse_rejects_out_of_range() {
+    assert!(RetentionPolicy::parse("0", "", "").is_err());
+    assert!(RetentionPolicy::parse("121", "", "").is_err());
+    assert!(RetentionPolicy::parse("", "-5", "").is_err());
+    assert!(RetentionPolicy::parse("", "", "731").is_err());
+    assert!(RetentionPolicy::parse("six", "", "").is_err());
+}
+
+#[test]
+fn test_cutoffs() {
+    let policy = RetentionPolicy::parse("6", "14", "").unwrap();
+    let cutoffs = policy.cutoffs(at(2026, 3, 31));
+    // Months clamp to the end of shorter months
+    assert_eq!(cutoffs.applications, Some(at(2025, 9, 30)));
+    assert_eq!(cutoffs.invites, Some(at(2026, 3, 17)));
+    assert_eq!(cutoffs.share_links, None);
+}
+
+#[test]
+fn test_purge_totals_and_labels() {
+    let counts = PurgeCounts {
+        applications: 3,
+        invites: 2,
+        share_links: 1,
+    };
+    assert_eq!(counts.total(), 6);
+    assert_eq!(rule_label("invites"), "Unaccepted invitations");
+    assert_eq!(rule_label("unknown"), "Other");
+}
@@ -0,0 +1,40 @@
+use slatehub::error::Error;
+use slatehub::models::scouting::{MAX_NOTE_CHARS, clean_name, clean_note, parse_basecamp};
+
+#[test]
+fn test_collection_names_are_trimmed_and_required() {
+    assert_eq!(clean_name("  Diner, Act 2 ").unwrap(), "Diner, Act 2");
+    assert!(matches!(clean_name("   "), Err(Error::Validation(_))));
+    assert!(matches!(
+        clean_name(&"x".repeat(121)),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn test_empty_notes_clear() {
+    assert_eq!(
+        clean_note("  Loud at lunch  ").unwrap().as_deref(),
+        Some("Loud at lunch")
+    );
+    assert_eq!(clean_note("   ").unwrap(), None);
+    assert!(clean_note(&"x".repeat(MAX_NOTE_CHARS + 1)).is_err());
+}
+
+#[test]
+fn test_basecamp() {
+    let (name, point) = parse_basecamp("Stage 4", "34.0522, -118.2437").unwrap();
+    assert_eq!(name.as_deref(), Some("Stage 4"));
+    assert_eq!(point, Some((34.0522, -118.2437)));
+
+    // A name alone is fine; distances just aren't shown
+    assert_eq!(
+        parse_basecamp("Stage 4", " ").unwrap(),
+        (Some("Stage 4".to_string()), None)
+    );
+    assert_eq!(parse_basecamp("", "").unwrap(), (None, None));
+    assert!(matches!(
+        parse_basecamp("Stage 4", "somewhere"),
+        Err(Error::Validation(_))
+    ));
+}
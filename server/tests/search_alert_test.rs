@@ -0,0 +1,85 @@
+use slatehub::models::search_alert::{MAX_SEEN, describe, remember, result_ids, summary};
+use slatehub::services::search::{CombinedResults, PersonSearchResult, ProductionSearchResult};
+use slatehub::services::search_facets::SearchFilters;
+
+fn ids(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn test_result_ids_cover_people_and_productions() {
+    let results = CombinedResults {
+        people: vec![PersonSearchResult {
+            id: "person:a".to_string(),
+            name: "A".to_string(),
+            username: "a".to_string(),
+            headline: None,
+            bio: None,
+            location: None,
+            skills: vec![],
+            unions: vec![],
+            avatar_url: None,
+            embedding_text: None,
+            verification_status: "none".to_string(),
+            score: 1.0,
+        }],
+        organizations: vec![],
+        locations: vec![],
+        productions: vec![ProductionSearchResult {
+            id: "production:p".to_string(),
+            title: "Night Shift".to_string(),
+            slug: "night-shift".to_string(),
+            status: "Filming".to_string(),
+            description: None,
+            location: None,
+            poster_url: None,
+            poster_photo: None,
+            embedding_text: None,
+            score: 1.0,
+        }],
+        jobs: vec![],
+        incomplete: false,
+        matched: Default::default(),
+    };
+    assert_eq!(result_ids(&results), ids(&["person:a", "production:p"]));
+}
+
+#[test]
+fn test_remember_adds_without_duplicates() {
+    let seen = ids(&["person:a", "person:b"]);
+    assert_eq!(
+        remember(&seen, &ids(&["person:b", "person:c"])),
+        ids(&["person:a", "person:b", "person:c"])
+    );
+}
+
+#[test]
+fn test_remember_forgets_oldest_first() {
+    let seen: Vec<String> = (0..MAX_SEEN).map(|i| format!("person:{}", i)).collect();
+    let kept = remember(&seen, &ids(&["person:new"]));
+    assert_eq!(kept.len(), MAX_SEEN);
+    assert_eq!(kept.first().map(String::as_str), Some("person:1"));
+    assert_eq!(kept.last().map(String::as_str), Some("person:new"));
+}
+
+#[test]
+fn test_summary() {
+    assert_eq!(summary(1, 0), "1 new person");
+    assert_eq!(summary(3, 0), "3 new people");
+    assert_eq!(summary(0, 2), "2 new productions");
+    assert_eq!(summary(2, 1), "2 new people and 1 new production");
+}
+
+#[test]
+fn test_describe_lists_filters() {
+    let filters = SearchFilters {
+        location: Some("Berlin".to_string()),
+        skill: Some("Steadicam".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        describe("camera operator", &filters),
+        "camera operator · Skill: Steadicam · Location: Berlin"
+    );
+    assert_eq!(describe("gaffer", &SearchFilters::default()), "gaffer");
+}
@@ -0,0 +1,47 @@
+use slatehub::services::search::detect_search_intent;
+use slatehub::services::search_cache::{LruCache, normalize};
+
+#[test]
+fn test_normalize_collapses_case_and_whitespace() {
+    assert_eq!(normalize("  Sound   Mixer "), "sound mixer");
+    assert_eq!(normalize("DP\tin\nAtlanta"), "dp in atlanta");
+    assert_eq!(normalize("   "), "");
+}
+
+#[test]
+fn test_normalized_queries_share_an_intent() {
+    let a = detect_search_intent("Actors in Atlanta");
+    let b = detect_search_intent(&normalize("  actors   in atlanta"));
+    assert_eq!(a.people, b.people);
+    assert_eq!(a.organizations, b.organizations);
+    assert_eq!(a.locations, b.locations);
+    assert_eq!(a.productions, b.productions);
+    assert_eq!(a.jobs, b.jobs);
+}
+
+#[test]
+fn test_lru_cache_evicts_least_recently_used() {
+    let mut cache = LruCache::new(2);
+    cache.insert("actor", 1);
+    cache.insert("editor", 2);
+    // Reading "actor" makes "editor" the oldest
+    assert_eq!(cache.get(&"actor"), Some(1));
+    cache.insert("gaffer", 3);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&"editor"), None);
+    assert_eq!(cache.get(&"actor"), Some(1));
+    assert_eq!(cache.get(&"gaffer"), Some(3));
+
+    // Replacing a key doesn't evict anything
+    cache.insert("gaffer", 4);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(&"gaffer"), Some(4));
+}
+
+#[test]
+fn test_lru_cache_with_no_capacity_keeps_nothing() {
+    let mut cache = LruCache::new(0);
+    cache.insert("actor", 1);
+    assert!(cache.is_empty());
+    assert_eq!(cache.get(&"actor"), None);
+}
@@ -0,0 +1,34 @@
+use slatehub::config::SearchWeights;
+use slatehub::services::search_connections::{
+    Connections, city_of, person_score, production_score,
+};
+
+#[test]
+fn test_city_of() {
+    assert_eq!(city_of("Los Angeles, CA"), "los angeles");
+    assert_eq!(city_of("  Atlanta "), "atlanta");
+    assert_eq!(city_of(""), "");
+}
+
+#[test]
+fn test_scores_only_for_signed_in_searchers() {
+    let connections = Connections {
+        city: "atlanta".to_string(),
+        ..Connections::default()
+    };
+    let weight = SearchWeights::DEFAULT.connection_match;
+
+    assert_eq!(person_score(weight, None), "");
+    assert_eq!(production_score(weight, None), "");
+    assert_eq!(person_score(0, Some(&connections)), "");
+
+    let person = person_score(weight, Some(&connections));
+    assert!(person.starts_with(&format!("+ {} * (", weight)));
+    assert!(person.contains("$viewer_city"));
+    assert!(person.contains("$viewer_orgs"));
+    assert!(person.contains("$viewer_collaborators"));
+
+    let production = production_score(weight, Some(&connections));
+    assert!(production.contains("$viewer_productions"));
+    assert!(production.contains("$viewer_collaborators"));
+}
@@ -0,0 +1,156 @@
+use slatehub::services::search::{
+    CombinedResults, OrganizationSearchResult, PersonSearchResult, ProductionSearchResult,
+};
+use slatehub::services::search_facets::{FacetValue, Facets, SearchFilters};
+
+fn person(id: &str, location: &str, skills: &[&str], unions: &[&str]) -> PersonSearchResult {
+    PersonSearchResult {
+        id: id.to_string(),
+        name: id.to_string(),
+        username: id.to_string(),
+        headline: None,
+        bio: None,
+        location: Some(location.to_string()),
+        skills: skills.iter().map(|s| s.to_string()).collect(),
+        unions: unions.iter().map(|u| u.to_string()).collect(),
+        avatar_url: None,
+        embedding_text: None,
+        verification_status: "none".to_string(),
+        score: 1.0,
+    }
+}
+
+fn results() -> CombinedResults {
+    CombinedResults {
+        people: vec![
+            person("person:a", "Berlin, Germany", &["Steadicam", "Drone"], &[]),
+            person(
+                "person:b",
+                "Los Angeles, CA",
+                &["steadicam"],
+                &["SAG-AFTRA"],
+            ),
+            person("person:c", "Berlin", &["Editing"], &["SAG-AFTRA"]),
+        ],
+        organizations: vec![OrganizationSearchResult {
+            id: "organization:x".to_string(),
+            name: "Lantern Films".to_string(),
+            slug: "lantern".to_string(),
+            description: None,
+            location: Some("Berlin, Germany".to_string()),
+            logo: None,
+            org_type: Some("Production Company".to_string()),
+            embedding_text: None,
+            verified: false,
+            score: 1.0,
+        }],
+        locations: vec![],
+        productions: vec![ProductionSearchResult {
+            id: "production:p".to_string(),
+            title: "Night Shift".to_string(),
+            slug: "night-shift".to_string(),
+            status: "Filming".to_string(),
+            description: None,
+            location: None,
+            poster_url: None,
+            poster_photo: None,
+            embedding_text: None,
+            score: 1.0,
+        }],
+        jobs: vec![],
+        incomplete: false,
+        matched: Default::default(),
+    }
+}
+
+fn ids(results: &CombinedResults) -> Vec<&str> {
+    results.people.iter().map(|p| p.id.as_str()).collect()
+}
+
+#[test]
+fn test_facet_counts_merge_spellings() {
+    let facets = Facets::from_results(&results());
+    assert_eq!(
+        facets.skills[0],
+        FacetValue {
+            value: "Steadicam".to_string(),
+            count: 2
+        }
+    );
+    assert_eq!(facets.unions[0].count, 2);
+    assert_eq!(facets.locations[0].value, "Berlin, Germany");
+    assert_eq!(facets.locations[0].count, 2);
+    assert_eq!(facets.statuses[0].value, "Filming");
+    assert_eq!(facets.org_types[0].value, "Production Company");
+}
+
+#[test]
+fn test_skill_filter_keeps_only_matching_people() {
+    let mut filtered = results();
+    SearchFilters {
+        skill: Some("STEADICAM ".to_string()),
+        ..Default::default()
+    }
+    .apply(&mut filtered);
+    assert_eq!(ids(&filtered), vec!["person:a", "person:b"]);
+    assert!(filtered.organizations.is_empty());
+    assert!(filtered.productions.is_empty());
+}
+
+#[test]
+fn test_location_filter_applies_to_every_type() {
+    let mut filtered = results();
+    SearchFilters {
+        location: Some("berlin".to_string()),
+        ..Default::default()
+    }
+    .apply(&mut filtered);
+    assert_eq!(ids(&filtered), vec!["person:a", "person:c"]);
+    assert_eq!(filtered.organizations.len(), 1);
+    assert!(filtered.productions.is_empty());
+}
+
+#[test]
+fn test_filters_combine() {
+    let mut filtered = results();
+    SearchFilters {
+        location: Some("Berlin".to_string()),
+        union: Some("sag-aftra".to_string()),
+        ..Default::default()
+    }
+    .apply(&mut filtered);
+    assert_eq!(ids(&filtered), vec!["person:c"]);
+    assert_eq!(filtered.total(), 1);
+}
+
+#[test]
+fn test_blank_filters_are_ignored() {
+    let filters = SearchFilters {
+        skill: Some("  ".to_string()),
+        ..Default::default()
+    };
+    assert!(!filters.is_active());
+    let mut filtered = results();
+    filters.apply(&mut filtered);
+    assert_eq!(filtered.total(), results().total());
+}
+
+#[test]
+fn test_urls_keep_query_and_other_filters() {
+    let filters = SearchFilters {
+        skill: Some("Steadicam".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        filters.url("camera op", "location", Some("Los Angeles, CA")),
+        "/search?q=camera%20op&skill=Steadicam&location=Los%20Angeles%2C%20CA"
+    );
+    assert_eq!(
+        filters.url("camera op", "skill", None),
+        "/search?q=camera%20op"
+    );
+    assert_eq!(
+        filters.active(),
+        vec![("skill", "Skill", "Steadicam".to_string())]
+    );
+}
@@ -0,0 +1,21 @@
+use slatehub::models::search_history::{MAX_QUERY_CHARS, normalize_query};
+
+#[test]
+fn queries_are_kept_with_whitespace_collapsed() {
+    assert_eq!(
+        normalize_query("  gaffer   in\tAtlanta ").as_deref(),
+        Some("gaffer in Atlanta")
+    );
+    assert_eq!(
+        normalize_query("skills:\"steadicam\" -student").as_deref(),
+        Some("skills:\"steadicam\" -student")
+    );
+}
+
+#[test]
+fn empty_and_overlong_queries_are_not_kept() {
+    assert_eq!(normalize_query(""), None);
+    assert_eq!(normalize_query("   "), None);
+    assert!(normalize_query(&"a".repeat(MAX_QUERY_CHARS)).is_some());
+    assert_eq!(normalize_query(&"a".repeat(MAX_QUERY_CHARS + 1)), None);
+}
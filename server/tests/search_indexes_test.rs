@@ -0,0 +1,55 @@
+use slatehub::services::search_indexes::{
+    EMBEDDING_DIMENSION, INDEXES, IndexKind, fulltext_fields, verify_dimensions,
+};
+
+#[test]
+fn test_spec_matches_embedding_dimension() {
+    assert!(verify_dimensions(EMBEDDING_DIMENSION).is_ok());
+
+    let mismatches = verify_dimensions(384).unwrap_err();
+    let vector_count = INDEXES
+        .iter()
+        .filter(|s| matches!(s.kind, IndexKind::Vector { .. }))
+        .count();
+    assert_eq!(mismatches.len(), vector_count);
+}
+
+#[test]
+fn test_define_statements() {
+    let person = INDEXES.iter().find(|s| s.name == "idx_person_embedding").unwrap();
+    assert_eq!(
+        person.define_statement(),
+        "DEFINE INDEX OVERWRITE idx_person_embedding ON person FIELDS embedding HNSW DIMENSION 1024 DIST COSINE TYPE F32 EFC 150 M 12;"
+    );
+    assert_eq!(person.rebuild_statement(), "REBUILD INDEX idx_person_embedding ON person;");
+
+    let bio = INDEXES.iter().find(|s| s.name == "idx_person_bio").unwrap();
+    assert!(bio.define_statement().contains("FULLTEXT ANALYZER profile_analyzer BM25"));
+
+    let suggest = INDEXES.iter().find(|s| s.name == "idx_person_suggest").unwrap();
+    assert_eq!(
+        suggest.define_statement(),
+        "DEFINE INDEX OVERWRITE idx_person_suggest ON person FIELDS suggest FULLTEXT ANALYZER prefix_analyzer BM25;"
+    );
+    assert_eq!(suggest.kind_label(), "prefix");
+}
+
+#[test]
+fn test_index_names_unique() {
+    let mut names: Vec<&str> = INDEXES.iter().map(|s| s.name).collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), INDEXES.len());
+}
+
+#[test]
+fn test_fulltext_fields() {
+    assert_eq!(
+        fulltext_fields("person"),
+        vec!["name", "username", "profile.headline", "profile.bio"]
+    );
+    for table in ["organization", "location", "production", "job_posting"] {
+        assert!(!fulltext_fields(table).is_empty(), "{} has no full-text index", table);
+    }
+    assert!(fulltext_fields("feedback").is_empty());
+}
@@ -0,0 +1,50 @@
+use askama::Template;
+use slatehub::services::search_preview::Preview;
+use slatehub::templates::SearchPreviewTemplate;
+
+fn template(preview: Preview, query: &str, score: Option<&str>, matches: bool) -> SearchPreviewTemplate {
+    SearchPreviewTemplate {
+        app_name: "SlateHub".to_string(),
+        year: 2026,
+        version: "test".to_string(),
+        active_page: "search-preview".to_string(),
+        user: None,
+        kind: "profile",
+        name: "Jane Doe".to_string(),
+        back_url: "/janedoe".to_string(),
+        preview_url: "/profile/search-preview".to_string(),
+        preview,
+        query: query.to_string(),
+        score: score.map(str::to_string),
+        matches,
+        threshold: "0.75".to_string(),
+        error: None,
+    }
+}
+
+#[test]
+fn test_preview_shows_embedding_text_and_score() {
+    let preview = Preview {
+        text: Some("name: jane doe. role: editor".to_string()),
+        indexed: true,
+        pending: false,
+    };
+    let html = template(preview, "documentary editor", Some("0.81"), true)
+        .render()
+        .unwrap();
+    assert!(html.contains("name: jane doe. role: editor"));
+    assert!(html.contains("Similarity 0.81"));
+    assert!(html.contains("would find your profile"));
+}
+
+#[test]
+fn test_preview_without_embedding() {
+    let preview = Preview {
+        text: None,
+        indexed: false,
+        pending: false,
+    };
+    let html = template(preview, "editor", None, false).render().unwrap();
+    assert!(html.contains("Not searchable by meaning yet"));
+    assert!(html.contains("can't be compared"));
+}
@@ -0,0 +1,73 @@
+use slatehub::error::Error;
+use slatehub::services::search::SearchKind;
+use slatehub::services::search_scope::{OrgScope, parse, roster};
+
+fn ids(ids: &[&str]) -> Vec<String> {
+    ids.iter().map(|id| id.to_string()).collect()
+}
+
+#[test]
+fn scopes_name_an_organization() {
+    assert_eq!(parse(None).unwrap(), None);
+    assert_eq!(parse(Some("  ")).unwrap(), None);
+    assert_eq!(
+        parse(Some("org:night-owl_films")).unwrap().as_deref(),
+        Some("night-owl_films")
+    );
+    assert_eq!(parse(Some(" org:acme ")).unwrap().as_deref(), Some("acme"));
+}
+
+#[test]
+fn anything_else_is_refused() {
+    for scope in ["acme", "org:", "person:ana", "org:acme films", "org:a'b"] {
+        assert!(
+            matches!(parse(Some(scope)), Err(Error::BadRequest(_))),
+            "{scope}"
+        );
+    }
+}
+
+#[test]
+fn members_also_find_represented_talent() {
+    let members = ids(&["person:a", "person:b"]);
+    let represented = ids(&["person:b", "person:c"]);
+
+    assert_eq!(
+        roster(members.clone(), represented.clone(), false),
+        ids(&["person:a", "person:b"])
+    );
+    assert_eq!(
+        roster(members, represented, true),
+        ids(&["person:a", "person:b", "person:c"])
+    );
+}
+
+#[test]
+fn every_type_is_filtered() {
+    assert!(OrgScope::filter(SearchKind::People).contains("$scope_people"));
+    assert!(OrgScope::filter(SearchKind::Organizations).contains("$scope_org"));
+    assert!(OrgScope::filter(SearchKind::Productions).contains("$scope_productions"));
+
+    // Private locations only for members
+    let locations = OrgScope::filter(SearchKind::Locations);
+    assert!(locations.contains("created_by = $scope_org"));
+    assert!(locations.contains("$scope_member"));
+
+    // Jobs the organization posted, or for its productions
+    let jobs = OrgScope::filter(SearchKind::Jobs);
+    assert!(jobs.contains("posted_by = $scope_org"));
+    assert!(jobs.contains("$scope_productions"));
+}
+
+#[test]
+fn the_parameter_round_trips() {
+    let scope = OrgScope {
+        slug: "acme".to_string(),
+        ..OrgScope::default()
+    };
+    assert_eq!(scope.param(), "org:acme");
+    assert_eq!(
+        parse(Some(&scope.param())).unwrap().as_deref(),
+        Some("acme")
+    );
+}
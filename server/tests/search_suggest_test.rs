@@ -0,0 +1,53 @@
+use slatehub::services::search_suggest::{Suggestion, normalize, rank};
+
+fn suggestion(kind: &str, label: &str, url: &str, score: f64) -> Suggestion {
+    Suggestion {
+        kind: kind.to_string(),
+        label: label.to_string(),
+        url: url.to_string(),
+        score,
+    }
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(normalize("  jane   smi ").as_deref(), Some("jane smi"));
+    assert_eq!(normalize("j"), None);
+    assert_eq!(normalize(" - "), None);
+    assert_eq!(normalize(&"a".repeat(200)).map(|q| q.len()), Some(64));
+}
+
+#[test]
+fn test_rank_puts_leading_matches_first() {
+    let ranked = rank(
+        "jan",
+        vec![
+            suggestion("production", "Meet Jane", "/productions/meet-jane", 4.0),
+            suggestion("person", "Janet Lee", "/janet", 1.0),
+            suggestion("person", "Jane Smith", "/jane", 1.0),
+            suggestion("organization", "January Films", "/orgs/january", 2.0),
+        ],
+        8,
+    );
+    let labels: Vec<&str> = ranked.iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(
+        labels,
+        vec!["January Films", "Janet Lee", "Jane Smith", "Meet Jane"]
+    );
+}
+
+#[test]
+fn test_rank_dedups_and_limits() {
+    let ranked = rank(
+        "st",
+        vec![
+            suggestion("location", "Studio A", "/locations/a", 1.0),
+            suggestion("location", "Studio A", "/locations/a", 1.0),
+            suggestion("location", "Studio B", "/locations/b", 1.0),
+            suggestion("location", "Studio C", "/locations/c", 1.0),
+        ],
+        2,
+    );
+    let urls: Vec<&str> = ranked.iter().map(|s| s.url.as_str()).collect();
+    assert_eq!(urls, vec!["/locations/a", "/locations/b"]);
+}
@@ -0,0 +1,84 @@
+use slatehub::services::search::SearchKind;
+use slatehub::services::search_syntax::{Field, Operator, SearchSyntax};
+
+fn op(field: Field, value: &str, negated: bool) -> Operator {
+    Operator {
+        field,
+        value: value.to_string(),
+        negated,
+    }
+}
+
+#[test]
+fn test_parse_splits_operators_from_free_text() {
+    let syntax =
+        SearchSyntax::parse(r#"skills:"Steadicam Op" city:Atlanta type:person -student handheld"#);
+    assert_eq!(
+        syntax.operators,
+        vec![
+            op(Field::Skill, "steadicam op", false),
+            op(Field::Location, "atlanta", false),
+            op(Field::Type, "people", false),
+            op(Field::Text, "student", true),
+        ]
+    );
+    assert_eq!(syntax.text, "handheld");
+    assert_eq!(
+        syntax.values(),
+        vec!["steadicam op", "atlanta", "people", "student"]
+    );
+}
+
+#[test]
+fn test_parse_leaves_lookalikes_in_the_text() {
+    let syntax =
+        SearchSyntax::parse(r#"call time 10:30 note: type:banana "night exterior" - sci-fi"#);
+    assert!(syntax.operators.is_empty());
+    assert_eq!(
+        syntax.text,
+        "call time 10:30 note: type:banana night exterior - sci-fi"
+    );
+}
+
+#[test]
+fn test_targets_follow_types_and_fields() {
+    let syntax = SearchSyntax::parse("skills:gaffer -type:job");
+    assert!(syntax.targets(SearchKind::People));
+    assert!(!syntax.targets(SearchKind::Jobs));
+    // Organizations have no skills to match
+    assert!(!syntax.targets(SearchKind::Organizations));
+
+    let syntax = SearchSyntax::parse("type:org type:production -union:sag");
+    assert!(syntax.names_types());
+    assert!(syntax.targets(SearchKind::Organizations));
+    assert!(syntax.targets(SearchKind::Productions));
+    assert!(!syntax.targets(SearchKind::People));
+
+    let plain = SearchSyntax::parse("cinematographer in Berlin");
+    assert!(!plain.names_types());
+    assert!(SearchKind::ALL.iter().all(|&k| plain.targets(k)));
+}
+
+#[test]
+fn test_filter_binds_values_by_position() {
+    let syntax = SearchSyntax::parse("type:person city:atlanta -status:wrapped -student");
+    assert_eq!(
+        syntax.filter(SearchKind::Productions).unwrap(),
+        "(string::lowercase(location ?? '') CONTAINS $syntax_values[1]) \
+         AND !(string::lowercase(status ?? '') CONTAINS $syntax_values[2]) \
+         AND !(string::lowercase(title ?? '') CONTAINS $syntax_values[3] \
+         OR string::lowercase(description ?? '') CONTAINS $syntax_values[3] \
+         OR string::lowercase(embedding_text ?? '') CONTAINS $syntax_values[3])"
+    );
+    // Status isn't a person field, so its exclusion doesn't apply to people
+    let people = syntax.filter(SearchKind::People).unwrap();
+    assert!(
+        people
+            .starts_with("(string::lowercase(profile.location ?? '') CONTAINS $syntax_values[1])")
+    );
+    assert!(!people.contains("$syntax_values[2]"));
+    assert_eq!(
+        SearchSyntax::parse("steadicam").filter(SearchKind::People),
+        None
+    );
+}
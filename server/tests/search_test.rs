@@ -0,0 +1,154 @@
+use slatehub::services::embedding::FieldEmbedding;
+use slatehub::services::search::{
+    Highlight, MAX_PER_PAGE, Pagination, RRF_K, SearchKind, apply_cutoff, apply_rerank,
+    best_matches, highlight, reciprocal_rank_fusion,
+};
+
+fn ids(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn test_fusion_rewards_agreement() {
+    // The exact name match is only 4th by vector score but 1st by keyword
+    let scored = ids(&["person:a", "person:b", "person:c", "person:exact"]);
+    let keyword = ids(&["person:exact", "person:b"]);
+    let fused = reciprocal_rank_fusion(&[scored, keyword]);
+
+    let order: Vec<&str> = fused.iter().map(|(id, _)| id.as_str()).collect();
+    assert_eq!(
+        order,
+        vec!["person:b", "person:exact", "person:a", "person:c"]
+    );
+
+    let expected = 1.0 / (RRF_K + 2.0) + 1.0 / (RRF_K + 2.0);
+    assert!((fused[0].1 - expected).abs() < 1e-12);
+}
+
+#[test]
+fn test_fusion_keyword_only_hits_count() {
+    let fused = reciprocal_rank_fusion(&[ids(&["a"]), ids(&["b"])]);
+    assert_eq!(fused.len(), 2);
+    // Equal scores keep first-seen order
+    assert_eq!(fused[0].0, "a");
+    assert_eq!(fused[0].1, fused[1].1);
+}
+
+#[test]
+fn test_rerank_reorders_the_head() {
+    let ranking = ids(&["a", "b", "c", "d"]);
+    // Only the first three were reranked; "d" stays behind them
+    let reranked = apply_rerank(ranking.clone(), &[0.1, 2.5, 0.1]);
+    assert_eq!(reranked, ids(&["b", "a", "c", "d"]));
+
+    assert_eq!(apply_rerank(ranking.clone(), &[]), ranking);
+    // More scores than ids can't add any
+    assert_eq!(apply_rerank(ids(&["a"]), &[1.0, 2.0]), ids(&["a"]));
+}
+
+#[test]
+fn test_cutoff_comes_after_the_rerank() {
+    let candidates = vec![
+        ("a".to_string(), 0.9),
+        ("b".to_string(), 0.6),
+        ("c".to_string(), 0.8),
+    ];
+    // "b" was fetched below the threshold; reranking it first doesn't keep it
+    let reranked = apply_rerank(ids(&["a", "b", "c"]), &[0.2, 3.0, 1.5]);
+    assert_eq!(reranked, ids(&["b", "c", "a"]));
+    assert_eq!(apply_cutoff(reranked, &candidates, 0.75), ids(&["c", "a"]));
+    assert!(apply_cutoff(ids(&["z"]), &candidates, 0.0).is_empty());
+}
+
+#[test]
+fn test_fusion_empty() {
+    assert!(reciprocal_rank_fusion(&[]).is_empty());
+    assert!(reciprocal_rank_fusion(&[vec![], vec![]]).is_empty());
+}
+
+#[test]
+fn test_search_kind_parse() {
+    assert_eq!(SearchKind::parse("people"), Some(SearchKind::People));
+    assert_eq!(SearchKind::parse("jobs"), Some(SearchKind::Jobs));
+    assert_eq!(SearchKind::parse("People"), None);
+    for kind in SearchKind::ALL {
+        assert_eq!(SearchKind::parse(kind.as_str()), Some(*kind));
+    }
+}
+
+#[test]
+fn test_pagination_window_and_trim() {
+    assert_eq!(Pagination::clamp(None, None), (1, 20));
+    assert_eq!(Pagination::clamp(Some(0), Some(500)), (1, MAX_PER_PAGE));
+    assert_eq!(Pagination::window(3, 10), (11, 20));
+
+    let mut full: Vec<u32> = (0..11).collect();
+    let pagination = Pagination::trim(&mut full, 3, 10);
+    assert_eq!(full.len(), 10);
+    assert!(pagination.has_more);
+    assert_eq!(pagination.next_page, Some(4));
+
+    let mut last: Vec<u32> = (0..4).collect();
+    let pagination = Pagination::trim(&mut last, 4, 10);
+    assert_eq!(last.len(), 4);
+    assert!(!pagination.has_more);
+    assert_eq!(pagination.next_page, None);
+}
+
+fn field(label: &str, text: &str, vector: Vec<i64>) -> FieldEmbedding {
+    FieldEmbedding {
+        label: label.to_string(),
+        text: text.to_string(),
+        vector,
+    }
+}
+
+#[test]
+fn test_best_matches_ranks_passages_above_threshold() {
+    let fields = vec![
+        field("skills and abilities", "steadicam", vec![127, 0, 0]),
+        field("skills and abilities", "catering", vec![0, 127, 0]),
+        field(
+            "background",
+            "camera operator on two features",
+            vec![100, 0, 60],
+        ),
+    ];
+    let matched = best_matches("camera", &[1.0, 0.0, 0.0], &fields, 0.5);
+    let texts: Vec<&str> = matched.iter().map(|m| m.text.as_str()).collect();
+    assert_eq!(texts, vec!["steadicam", "camera operator on two features"]);
+    assert!((matched[0].similarity - 1.0).abs() < 1e-9);
+    assert_eq!(matched[1].label, "background");
+
+    // Vectors of another dimension never match
+    assert!(best_matches("camera", &[1.0, 0.0], &fields, 0.0).is_empty());
+}
+
+#[test]
+fn test_best_matches_keeps_top_three() {
+    let fields: Vec<FieldEmbedding> = (0..5)
+        .map(|i| field("credits", &format!("film {}", i), vec![127, i]))
+        .collect();
+    let matched = best_matches("film", &[1.0, 0.0], &fields, 0.5);
+    let texts: Vec<&str> = matched.iter().map(|m| m.text.as_str()).collect();
+    assert_eq!(texts, vec!["film 0", "film 1", "film 2"]);
+}
+
+#[test]
+fn test_highlight_marks_query_words() {
+    let hit = |text: &str| Highlight {
+        text: text.to_string(),
+        hit: true,
+    };
+    let miss = |text: &str| Highlight {
+        text: text.to_string(),
+        hit: false,
+    };
+    assert_eq!(
+        highlight("Cameras for a documentary", "camera doc in LA"),
+        vec![hit("Cameras"), miss(" for a "), hit("documentary")]
+    );
+    // Short query words are ignored, and so is text with no match
+    assert_eq!(highlight("an editor", "an"), vec![miss("an editor")]);
+    assert!(highlight("", "camera").is_empty());
+}
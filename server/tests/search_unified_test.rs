@@ -0,0 +1,101 @@
+use slatehub::services::search::{
+    CombinedResults, OrganizationSearchResult, PersonSearchResult, ProductionSearchResult,
+};
+use slatehub::services::search_unified::{relative_scores, unified};
+
+fn person(id: &str, score: f64) -> PersonSearchResult {
+    PersonSearchResult {
+        id: id.to_string(),
+        name: id.to_string(),
+        username: id.trim_start_matches("person:").to_string(),
+        headline: Some("Gaffer".to_string()),
+        bio: None,
+        location: None,
+        skills: vec![],
+        unions: vec![],
+        avatar_url: None,
+        embedding_text: None,
+        verification_status: "none".to_string(),
+        score,
+    }
+}
+
+fn organization(id: &str, score: f64) -> OrganizationSearchResult {
+    OrganizationSearchResult {
+        id: id.to_string(),
+        name: id.to_string(),
+        slug: "lantern".to_string(),
+        description: Some("  ".to_string()),
+        location: None,
+        logo: None,
+        org_type: None,
+        embedding_text: None,
+        verified: false,
+        score,
+    }
+}
+
+fn production(id: &str, score: f64) -> ProductionSearchResult {
+    ProductionSearchResult {
+        id: id.to_string(),
+        title: id.to_string(),
+        slug: "night-shift".to_string(),
+        status: "Filming".to_string(),
+        description: None,
+        location: None,
+        poster_url: Some("https://example.com/poster.jpg".to_string()),
+        poster_photo: None,
+        embedding_text: None,
+        score,
+    }
+}
+
+#[test]
+fn test_relative_scores_put_the_best_at_one() {
+    assert_eq!(relative_scores(&[200.0, 100.0, 50.0]), vec![1.0, 0.5, 0.25]);
+    assert_eq!(relative_scores(&[0.0, 0.0]), vec![0.0, 0.0]);
+    assert!(relative_scores(&[]).is_empty());
+}
+
+#[test]
+fn test_unified_ranks_across_types_by_relative_score() {
+    // People score on a much larger scale than organizations here; relative
+    // to their own type the second organization beats the second person
+    let results = CombinedResults {
+        people: vec![person("person:a", 400.0), person("person:b", 100.0)],
+        organizations: vec![
+            organization("organization:x", 20.0),
+            organization("organization:y", 15.0),
+        ],
+        locations: vec![],
+        productions: vec![production("production:p", 5.0)],
+        jobs: vec![],
+        incomplete: false,
+        matched: Default::default(),
+    };
+
+    let list = unified(&results);
+    let ids: Vec<&str> = list.iter().map(|r| r.id.as_str()).collect();
+    // Every type's best ties at 1 and keeps the grouped order
+    assert_eq!(
+        ids,
+        vec![
+            "person:a",
+            "organization:x",
+            "production:p",
+            "organization:y",
+            "person:b"
+        ]
+    );
+    assert_eq!(list[0].kind, "person");
+    assert_eq!(list[0].label, "Person");
+    assert_eq!(list[0].url, "/a");
+    assert_eq!(list[0].subtitle.as_deref(), Some("Gaffer"));
+    assert_eq!(list[1].url, "/orgs/lantern");
+    assert_eq!(list[1].description, None);
+    assert_eq!(
+        list[2].image.as_deref(),
+        Some("https://example.com/poster.jpg")
+    );
+    assert_eq!(list[3].relevance, 0.75);
+}
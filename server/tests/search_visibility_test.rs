@@ -0,0 +1,47 @@
+use slatehub::error::Error;
+use slatehub::services::search_visibility::{Viewer, can_see, parse_visibility};
+
+fn viewer(person: &str, hidden: &[&str]) -> Viewer {
+    Viewer {
+        person: person.to_string(),
+        hidden: hidden.iter().map(|id| id.to_string()).collect(),
+    }
+}
+
+#[test]
+fn signed_out_visitors_only_find_public_profiles() {
+    assert!(can_see(None, "person:a", "public"));
+    assert!(!can_see(None, "person:a", "members"));
+    assert!(!can_see(None, "person:a", "hidden"));
+}
+
+#[test]
+fn members_find_members_only_profiles() {
+    let me = viewer("person:me", &[]);
+    assert!(can_see(Some(&me), "person:a", "public"));
+    assert!(can_see(Some(&me), "person:a", "members"));
+    assert!(!can_see(Some(&me), "person:a", "hidden"));
+}
+
+#[test]
+fn blocked_people_are_never_found() {
+    let me = viewer("person:me", &["person:blocked"]);
+    assert!(!can_see(Some(&me), "person:blocked", "public"));
+    assert!(can_see(Some(&me), "person:other", "public"));
+}
+
+#[test]
+fn people_always_find_themselves() {
+    let me = viewer("person:me", &[]);
+    assert!(can_see(Some(&me), "person:me", "hidden"));
+}
+
+#[test]
+fn only_known_settings_are_accepted() {
+    assert_eq!(parse_visibility(" members ").unwrap(), "members");
+    assert!(matches!(
+        parse_visibility("friends"),
+        Err(Error::Validation(_))
+    ));
+    assert!(parse_visibility("").is_err());
+}
@@ -0,0 +1,112 @@
+use chrono::{Duration, TimeZone, Utc};
+use slatehub::error::Error;
+use slatehub::models::selftape::{
+    SelfTape, SelfTapeRequest, average_rating, owns_video_key, parse_deadline, parse_usernames,
+    video_key, video_type,
+};
+use surrealdb::types::RecordId;
+
+fn tape(request: &str, person: &str) -> SelfTape {
+    SelfTape {
+        id: RecordId::new("selftape", "t1"),
+        request: RecordId::new("selftape_request", request),
+        person: RecordId::new("person", person),
+        status: "requested".to_string(),
+        video_key: None,
+        content_type: None,
+        size: None,
+        note: None,
+        submitted_at: None,
+        created_at: Utc::now(),
+    }
+}
+
+fn request(status: &str, deadline: chrono::DateTime<Utc>) -> SelfTapeRequest {
+    SelfTapeRequest {
+        id: RecordId::new("selftape_request", "r1"),
+        job: RecordId::new("job_posting", "j1"),
+        role_title: "Lead".to_string(),
+        instructions: "Read sides 1-3".to_string(),
+        deadline,
+        created_by: RecordId::new("person", "casting"),
+        status: status.to_string(),
+        created_at: Utc::now(),
+    }
+}
+
+#[test]
+fn video_type_by_extension() {
+    assert_eq!(video_type("audition.MP4"), Some(("mp4", "video/mp4")));
+    assert_eq!(video_type("take.two.mov"), Some(("mov", "video/quicktime")));
+    assert_eq!(video_type("clip.webm"), Some(("webm", "video/webm")));
+    assert_eq!(video_type("headshot.jpg"), None);
+    assert_eq!(video_type("no_extension"), None);
+}
+
+#[test]
+fn video_keys_belong_to_their_tape() {
+    let mine = tape("r1", "sam");
+    let key = video_key(&mine, "mp4");
+    assert!(key.starts_with("private/selftapes/r1/sam-"));
+    assert!(key.ends_with(".mp4"));
+    assert!(owns_video_key(&mine, &key));
+
+    let theirs = tape("r1", "alex");
+    assert!(!owns_video_key(&theirs, &key));
+    assert!(!owns_video_key(&tape("r2", "sam"), &key));
+    assert!(!owns_video_key(&mine, "private/selftapes/r1/sam-x/../../other.mp4"));
+    assert!(!owns_video_key(&mine, "profiles/sam/photo.jpg"));
+}
+
+#[test]
+fn video_keys_are_unique_per_upload() {
+    let mine = tape("r1", "sam");
+    assert_ne!(video_key(&mine, "mp4"), video_key(&mine, "mp4"));
+}
+
+#[test]
+fn usernames_are_split_and_deduplicated() {
+    assert_eq!(
+        parse_usernames("@Sam, alex\n@sam  jordan,,"),
+        vec!["sam".to_string(), "alex".to_string(), "jordan".to_string()]
+    );
+    assert!(parse_usernames("  ,\n ").is_empty());
+}
+
+#[test]
+fn deadline_parses_date_and_time() {
+    let now = Utc.with_ymd_and_hms(2026, 5, 1, 12, 0, 0).unwrap();
+    assert_eq!(
+        parse_deadline("2026-05-03", "17:30", now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 5, 3, 17, 30, 0).unwrap()
+    );
+    assert_eq!(
+        parse_deadline("2026-05-03", "", now).unwrap(),
+        Utc.with_ymd_and_hms(2026, 5, 3, 23, 59, 0).unwrap()
+    );
+}
+
+#[test]
+fn deadline_rejects_past_and_malformed_values() {
+    let now = Utc.with_ymd_and_hms(2026, 5, 1, 12, 0, 0).unwrap();
+    assert!(matches!(parse_deadline("2026-04-30", "", now), Err(Error::Validation(_))));
+    assert!(matches!(parse_deadline("2026-05-01", "11:00", now), Err(Error::Validation(_))));
+    assert!(matches!(parse_deadline("", "", now), Err(Error::Validation(_))));
+    assert!(matches!(parse_deadline("2026-05-03", "5pm", now), Err(Error::Validation(_))));
+}
+
+#[test]
+fn average_rating_rounds_to_one_decimal() {
+    assert_eq!(average_rating(&[]), None);
+    assert_eq!(average_rating(&[4]), Some(4.0));
+    assert_eq!(average_rating(&[5, 4, 4]), Some(4.3));
+    assert_eq!(average_rating(&[1, 2]), Some(1.5));
+}
+
+#[test]
+fn uploads_close_at_the_deadline_or_when_closed() {
+    let now = Utc::now();
+    assert!(request("open", now + Duration::hours(1)).accepts_uploads(now));
+    assert!(!request("open", now - Duration::minutes(1)).accepts_uploads(now));
+    assert!(!request("closed", now + Duration::hours(1)).accepts_uploads(now));
+}
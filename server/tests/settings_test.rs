@@ -0,0 +1,69 @@
+use slatehub::settings::{Kind, SETTINGS, find, parse_toml, redact};
+
+#[test]
+fn test_toml_tables_flatten_into_variable_names() {
+    let values = parse_toml(
+        r#"
+        app_url = "https://slatehub.com"
+
+        [search]
+        vector_threshold = 0.7
+        weight_name = 40
+
+        [db]
+        query_budget_enforce = true
+
+        [database]
+        urls = ["ws://a:8000", "ws://b:8000"]
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(values["APP_URL"], "https://slatehub.com");
+    assert_eq!(values["SEARCH_VECTOR_THRESHOLD"], "0.7");
+    assert_eq!(values["SEARCH_WEIGHT_NAME"], "40");
+    assert_eq!(values["DB_QUERY_BUDGET_ENFORCE"], "true");
+    assert_eq!(values["DATABASE_URLS"], "ws://a:8000,ws://b:8000");
+}
+
+#[test]
+fn test_invalid_toml_is_an_error() {
+    assert!(parse_toml("[search\nweight_name = 1").is_err());
+}
+
+#[test]
+fn test_kind_checks() {
+    assert!(Kind::Int.check("250").is_ok());
+    assert!(Kind::Int.check("fast").is_err());
+    assert!(Kind::Float.check(" 0.75 ").is_ok());
+    assert!(Kind::Float.check("NaN").is_err());
+    assert!(Kind::Bool.check("1").is_ok());
+    assert!(Kind::Bool.check("yes").is_err());
+    assert!(Kind::Port.check("70000").is_err());
+    assert!(Kind::Url.check("https://slatehub.com").is_ok());
+    assert!(Kind::Url.check("slatehub.com").is_err());
+}
+
+#[test]
+fn test_secrets_are_never_overridable_or_shown() {
+    for setting in SETTINGS.iter().filter(|s| s.secret) {
+        assert!(!setting.overridable, "{} must not be overridable", setting.key);
+    }
+    assert!(find("JWT_SECRET").unwrap().secret);
+    assert!(!find("SERVER_PORT").unwrap().overridable);
+    assert!(find("SEARCH_VECTOR_THRESHOLD").unwrap().overridable);
+
+    assert_eq!(redact("short"), "••••••");
+    let shown = redact("sk_live_1234567890");
+    assert!(shown.starts_with("sk") && shown.ends_with("90"));
+    assert!(!shown.contains("1234"));
+}
+
+#[test]
+fn test_setting_keys_are_unique() {
+    let mut keys: Vec<&str> = SETTINGS.iter().map(|s| s.key).collect();
+    keys.sort();
+    let before = keys.len();
+    keys.dedup();
+    assert_eq!(before, keys.len());
+}
@@ -0,0 +1,30 @@
+use chrono::{Duration, Utc};
+use slatehub::models::share_link::{ShareAccess, live_access, lookup_hash};
+use slatehub::services::tokens::{random_token, token_hash};
+
+#[test]
+fn share_access_round_trips() {
+    for access in [ShareAccess::View, ShareAccess::Pick] {
+        assert_eq!(ShareAccess::parse(access.as_str()), Some(access));
+    }
+    assert_eq!(ShareAccess::parse("off"), None);
+    assert_eq!(ShareAccess::parse("edit"), None);
+}
+
+#[test]
+fn links_work_until_they_expire() {
+    let tomorrow = Some(Utc::now() + Duration::days(1));
+    let a_minute_ago = Some(Utc::now() - Duration::minutes(1));
+    assert_eq!(live_access(Some("view"), tomorrow), Some(ShareAccess::View));
+    assert_eq!(live_access(Some("pick"), a_minute_ago), None);
+    assert_eq!(live_access(Some("pick"), None), None);
+    assert_eq!(live_access(None, tomorrow), None);
+}
+
+#[test]
+fn links_are_looked_up_by_hash() {
+    let token = random_token(32);
+    assert_eq!(lookup_hash(&token), Some(token_hash(&token)));
+    assert_eq!(lookup_hash("short"), None);
+    assert_eq!(lookup_hash(&format!("{}/", random_token(31))), None);
+}
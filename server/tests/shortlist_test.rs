@@ -0,0 +1,68 @@
+use chrono::{Duration, Utc};
+use slatehub::error::Error;
+use slatehub::models::share_link::ShareAccess;
+use slatehub::models::shortlist::{
+    MAX_NAME_CHARS, MAX_NOTE_CHARS, Shortlist, clean_name, clean_note, shifted,
+};
+use surrealdb::types::RecordId;
+
+fn ids(keys: &[&str]) -> Vec<RecordId> {
+    keys.iter().map(|k| RecordId::new("shortlist_entry", *k)).collect()
+}
+
+#[test]
+fn share_links_stop_working_when_they_expire() {
+    let mut shortlist = Shortlist {
+        id: RecordId::new("shortlist", "s1"),
+        job: RecordId::new("job_posting", "j1"),
+        role_title: "Lead".to_string(),
+        name: "First choices".to_string(),
+        created_by: RecordId::new("person", "p1"),
+        share_access: Some("pick".to_string()),
+        share_expires_at: Some(Utc::now() + Duration::days(1)),
+        created_at: Utc::now(),
+        updated_at: Utc::now(),
+    };
+    assert_eq!(shortlist.access(), Some(ShareAccess::Pick));
+
+    shortlist.share_expires_at = Some(Utc::now() - Duration::minutes(1));
+    assert_eq!(shortlist.access(), None);
+
+    shortlist.share_expires_at = None;
+    assert_eq!(shortlist.access(), None);
+}
+
+#[test]
+fn names_are_trimmed_and_required() {
+    assert_eq!(clean_name("  First choices ").unwrap(), "First choices");
+    assert!(matches!(clean_name("   "), Err(Error::Validation(_))));
+    assert!(matches!(
+        clean_name(&"a".repeat(MAX_NAME_CHARS + 1)),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn empty_notes_clear_the_note() {
+    assert_eq!(clean_note("  Great chemistry read ").unwrap().as_deref(), Some("Great chemistry read"));
+    assert_eq!(clean_note(" \n ").unwrap(), None);
+    assert!(matches!(
+        clean_note(&"a".repeat(MAX_NOTE_CHARS + 1)),
+        Err(Error::Validation(_))
+    ));
+}
+
+#[test]
+fn entries_move_one_place() {
+    let order = ids(&["a", "b", "c"]);
+    assert_eq!(shifted(&order, &order[1], true), Some(ids(&["b", "a", "c"])));
+    assert_eq!(shifted(&order, &order[1], false), Some(ids(&["a", "c", "b"])));
+}
+
+#[test]
+fn entries_stop_at_the_ends() {
+    let order = ids(&["a", "b", "c"]);
+    assert_eq!(shifted(&order, &order[0], true), None);
+    assert_eq!(shifted(&order, &order[2], false), None);
+    assert_eq!(shifted(&order, &RecordId::new("shortlist_entry", "z"), true), None);
+}
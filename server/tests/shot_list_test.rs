@@ -0,0 +1,123 @@
+use chrono::Utc;
+use slatehub::csv;
+use slatehub::models::shot_list::{
+    DayProgress, Scene, SceneShots, ShootDay, Shot, group_setups, parse_gear, scene_order,
+    shot_list_csv,
+};
+use surrealdb::types::RecordId;
+
+fn shot(setup: i64, number: i64, captured: bool) -> Shot {
+    Shot {
+        id: RecordId::new("shot", format!("s{}", number)),
+        production: RecordId::new("production", "p"),
+        scene: RecordId::new("scene", "a"),
+        setup,
+        number,
+        description: format!("Shot {}", number),
+        size: "cu".to_string(),
+        angle: "eye_level".to_string(),
+        movement: "static".to_string(),
+        gear: vec![],
+        captured,
+        captured_at: None,
+        captured_by: None,
+        created_at: Utc::now(),
+    }
+}
+
+fn scene(number: &str, shots: Vec<Shot>) -> SceneShots {
+    SceneShots {
+        scene: Scene {
+            id: RecordId::new("scene", number),
+            production: RecordId::new("production", "p"),
+            number: number.to_string(),
+            heading: "INT. KITCHEN - NIGHT".to_string(),
+            description: None,
+            shoot_day: None,
+            created_at: Utc::now(),
+        },
+        setups: group_setups(shots),
+    }
+}
+
+#[test]
+fn shots_group_by_setup_in_order() {
+    let setups = group_setups(vec![shot(2, 3, false), shot(1, 2, false), shot(1, 1, false)]);
+    assert_eq!(setups.len(), 2);
+    assert_eq!(setups[0].number, 1);
+    assert_eq!(
+        setups[0].shots.iter().map(|s| s.number).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert_eq!(setups[1].number, 2);
+}
+
+#[test]
+fn progress_counts_shots_and_completed_scenes() {
+    let scenes = vec![
+        scene("1", vec![shot(1, 1, true), shot(1, 2, true)]),
+        scene("2", vec![shot(1, 1, true), shot(2, 2, false)]),
+        scene("3", vec![]),
+    ];
+    let progress = DayProgress::from_scenes(&scenes);
+    assert_eq!(
+        progress,
+        DayProgress {
+            shots_total: 4,
+            shots_captured: 3,
+            scenes_total: 3,
+            scenes_completed: 1,
+        }
+    );
+    assert_eq!(progress.percent(), 75);
+    assert_eq!(DayProgress::default().percent(), 0);
+}
+
+#[test]
+fn gear_list_is_trimmed_and_deduplicated() {
+    assert_eq!(
+        parse_gear(" Slider, dolly track ,,slider\nV-mount batteries"),
+        vec!["Slider", "dolly track", "V-mount batteries"]
+    );
+}
+
+#[test]
+fn scenes_sort_numerically_then_by_suffix() {
+    let mut numbers = vec!["10A", "2", "10", "1B"];
+    numbers.sort_by_key(|n| scene_order(n));
+    assert_eq!(numbers, vec!["1B", "2", "10", "10A"]);
+}
+
+#[test]
+fn csv_quotes_and_neutralises_formulas() {
+    assert_eq!(csv::field("plain"), "plain");
+    assert_eq!(csv::field("a, b"), "\"a, b\"");
+    assert_eq!(csv::field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(csv::field("=SUM(A1)"), "'=SUM(A1)");
+    assert_eq!(csv::row(&["a", "b"]), "a,b\r\n");
+}
+
+#[test]
+fn shot_list_exports_one_row_per_shot() {
+    let day = ShootDay {
+        id: RecordId::new("shoot_day", "d1"),
+        production: RecordId::new("production", "p"),
+        day_number: 3,
+        date: "2026-05-01".to_string(),
+        notes: None,
+        location: None,
+        call_sheet_sent_at: None,
+        call_time: None,
+        wrap_time: None,
+        created_at: Utc::now(),
+    };
+    let out = shot_list_csv(&day, &[scene("4", vec![shot(1, 1, true), shot(2, 2, false)])]);
+    let lines: Vec<&str> = out.trim_end().split("\r\n").collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("Day,Date,Scene"));
+    assert_eq!(
+        lines[1],
+        "3,2026-05-01,4,INT. KITCHEN - NIGHT,1,1,Close-up,Eye level,Static,Shot 1,,yes"
+    );
+    assert!(lines[2].ends_with(",no"));
+}
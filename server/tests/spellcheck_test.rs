@@ -0,0 +1,47 @@
+use slatehub::services::spellcheck::{Dictionary, distance};
+
+fn dictionary() -> Dictionary {
+    Dictionary::new([
+        "Jane Smith",
+        "Cinematographer, Steadicam operator",
+        "Cinematographer",
+        "Cinema Paradiso",
+        "Atlanta Studios",
+        "Gaffer",
+    ])
+}
+
+#[test]
+fn test_distance_counts_swaps_as_one_edit() {
+    assert_eq!(distance("gaffer", "gaffer"), 0);
+    assert_eq!(distance("gafer", "gaffer"), 1);
+    assert_eq!(distance("gaffre", "gaffer"), 1);
+    assert_eq!(distance("steadicam", "stedicamm"), 2);
+}
+
+#[test]
+fn test_lookup_prefers_fewest_edits_then_most_common() {
+    let dictionary = dictionary();
+    assert_eq!(dictionary.lookup("Atlanta"), Some("atlanta"));
+    assert_eq!(dictionary.lookup("stedicam"), Some("steadicam"));
+    assert_eq!(dictionary.lookup("cinematograher"), Some("cinematographer"));
+    // Too far from anything to be a misspelling
+    assert_eq!(dictionary.lookup("lighting"), None);
+    // Short words get a single edit
+    assert_eq!(dictionary.lookup("jn"), None);
+}
+
+#[test]
+fn test_correct_only_rewrites_misspelled_words() {
+    let dictionary = dictionary();
+    assert_eq!(
+        dictionary.correct("stedicam operater in Atlanta"),
+        Some("steadicam operator in Atlanta".to_string())
+    );
+    assert_eq!(dictionary.correct("steadicam operator"), None);
+    // Operators and quoted phrases stay as typed
+    assert_eq!(
+        dictionary.correct("skill:stedicam -gafer \"jane smth\" gafer"),
+        Some("skill:stedicam -gafer \"jane smth\" gaffer".to_string())
+    );
+}
@@ -0,0 +1,112 @@
+use serde_json::json;
+use slatehub::services::sso::{
+    AuthState, IdTokenClaims, SsoConfig, email_domain_allowed, parse_domains, parse_txt_answer,
+    parse_user_filter, patch_active, pkce_challenge, username_from_email,
+    verification_record_name, verification_record_value,
+};
+
+#[test]
+fn test_pkce_challenge_rfc7636_vector() {
+    assert_eq!(
+        pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+        "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    );
+}
+
+#[test]
+fn test_email_domain_allowed() {
+    let allowed = vec!["studio.com".to_string()];
+    assert!(email_domain_allowed("ana@studio.com", &allowed));
+    assert!(email_domain_allowed("Ana@Post.Studio.com", &allowed));
+    assert!(!email_domain_allowed("ana@notstudio.com", &allowed));
+    assert!(!email_domain_allowed("not-an-email", &allowed));
+    assert!(!email_domain_allowed("anyone@example.com", &[]));
+}
+
+#[test]
+fn test_only_verified_domains_sign_in() {
+    let config = SsoConfig {
+        allowed_domains: vec!["studio.com".to_string(), "post.studio.com".to_string()],
+        verified_domains: vec!["post.studio.com".to_string()],
+        ..Default::default()
+    };
+    assert!(config.allows_email("ana@post.studio.com"));
+    assert!(!config.allows_email("ana@studio.com"));
+    assert!(!SsoConfig::default().allows_email("ana@studio.com"));
+}
+
+#[test]
+fn test_email_must_be_verified_by_the_provider() {
+    let claims = |email_verified| IdTokenClaims {
+        sub: "123".to_string(),
+        email: Some("ana@studio.com".to_string()),
+        email_verified,
+        name: None,
+        nonce: None,
+    };
+    assert_eq!(claims(Some(true)).verified_email(), Some("ana@studio.com"));
+    assert_eq!(claims(Some(false)).verified_email(), None);
+    // Providers that leave the claim out don't vouch for the address
+    assert_eq!(claims(None).verified_email(), None);
+}
+
+#[test]
+fn test_domain_verification_records() {
+    assert_eq!(verification_record_name("studio.com"), "_slatehub-verification.studio.com");
+    assert_eq!(verification_record_value("abc"), "slatehub-verification=abc");
+
+    let answer = json!({"Status": 0, "Answer": [
+        {"name": "_slatehub-verification.studio.com", "type": 16, "data": "\"slatehub-verification=abc\""},
+        {"name": "_slatehub-verification.studio.com", "type": 16, "data": "\"v=spf1 \" \"-all\""},
+        {"name": "studio.com", "type": 5, "data": "other.example.com."}
+    ]});
+    assert_eq!(parse_txt_answer(&answer), vec!["slatehub-verification=abc", "v=spf1 -all"]);
+    assert!(parse_txt_answer(&json!({"Status": 3})).is_empty());
+}
+
+#[test]
+fn test_parse_domains() {
+    assert_eq!(
+        parse_domains("Studio.com, @post.studio.com\nstudio.com"),
+        vec!["studio.com", "post.studio.com"]
+    );
+    assert!(parse_domains("  ").is_empty());
+}
+
+#[test]
+fn test_auth_state_cookie_round_trip() {
+    let state = AuthState::generate(Some("/orgs/acme".to_string()));
+    assert_eq!(AuthState::from_cookie_value(&state.to_cookie_value()), Some(state));
+
+    // Off-site redirects are dropped
+    assert_eq!(AuthState::generate(Some("//evil.com".to_string())).redirect_to, None);
+    assert_eq!(AuthState::generate(Some("/\\evil.com".to_string())).redirect_to, None);
+    assert_eq!(AuthState::from_cookie_value("garbage"), None);
+}
+
+#[test]
+fn test_parse_user_filter() {
+    assert_eq!(parse_user_filter(r#"userName eq "ana@studio.com""#).as_deref(), Some("ana@studio.com"));
+    assert_eq!(parse_user_filter(r#"username EQ "ana@studio.com""#).as_deref(), Some("ana@studio.com"));
+    assert_eq!(parse_user_filter(r#"userName co "ana""#), None);
+    assert_eq!(parse_user_filter(r#"externalId eq "123""#), None);
+}
+
+#[test]
+fn test_patch_active() {
+    let okta = json!({"Operations": [{"op": "replace", "value": {"active": false}}]});
+    assert_eq!(patch_active(&okta), Some(false));
+
+    let entra = json!({"Operations": [{"op": "Replace", "path": "active", "value": "False"}]});
+    assert_eq!(patch_active(&entra), Some(false));
+
+    let rename = json!({"Operations": [{"op": "replace", "path": "displayName", "value": "Ana"}]});
+    assert_eq!(patch_active(&rename), None);
+}
+
+#[test]
+fn test_username_from_email() {
+    assert_eq!(username_from_email("Ana.Lopez+crew@studio.com"), "ana.lopez_crew");
+    assert_eq!(username_from_email("..jo@studio.com"), "jo_");
+    assert_eq!(username_from_email(&format!("{}@studio.com", "a".repeat(40))).len(), 30);
+}
@@ -0,0 +1,52 @@
+use slatehub::services::status::{ComponentState, Readiness, overall};
+
+fn healthy() -> Readiness {
+    Readiness {
+        database: true,
+        storage: true,
+        search: true,
+        embedding_backlog: 0,
+        disk_low: false,
+    }
+}
+
+fn state_of(readiness: &Readiness, name: &str) -> ComponentState {
+    readiness
+        .components()
+        .into_iter()
+        .find(|c| c.name == name)
+        .map(|c| c.state)
+        .unwrap()
+}
+
+#[test]
+fn test_healthy_install_is_operational() {
+    let components = healthy().components();
+    assert!(components.iter().all(|c| c.state == ComponentState::Operational));
+    assert_eq!(overall(&components), ComponentState::Operational);
+}
+
+#[test]
+fn test_search_degrades_without_model_or_with_backlog() {
+    let no_model = Readiness { search: false, ..healthy() };
+    assert_eq!(state_of(&no_model, "Search"), ComponentState::Degraded);
+
+    let backlog = Readiness { embedding_backlog: 5_000, ..healthy() };
+    assert_eq!(state_of(&backlog, "Search"), ComponentState::Degraded);
+    assert_eq!(overall(&backlog.components()), ComponentState::Degraded);
+}
+
+#[test]
+fn test_database_down_is_an_outage() {
+    let down = Readiness { database: false, ..healthy() };
+    assert_eq!(state_of(&down, "Database"), ComponentState::Outage);
+    assert_eq!(state_of(&down, "Search"), ComponentState::Outage);
+    assert_eq!(overall(&down.components()), ComponentState::Outage);
+}
+
+#[test]
+fn test_disk_space_is_not_shown_publicly() {
+    let low_disk = Readiness { disk_low: true, ..healthy() };
+    assert!(!low_disk.all_ok());
+    assert_eq!(overall(&low_disk.components()), ComponentState::Operational);
+}
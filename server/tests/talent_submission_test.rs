@@ -0,0 +1,52 @@
+use chrono::Utc;
+use slatehub::models::talent_submission::{Submission, status_label};
+use surrealdb::types::RecordId;
+
+fn submission(status: &str, application_status: Option<&str>, name: Option<&str>) -> Submission {
+    Submission {
+        id: RecordId::new("talent_submission", "s1"),
+        job: RecordId::new("job_posting", "j1"),
+        job_title: "Night Shift".to_string(),
+        role_title: "Lead".to_string(),
+        note: None,
+        status: status.to_string(),
+        application_status: application_status.map(str::to_string),
+        talent_username: "ada".to_string(),
+        talent_name: name.map(str::to_string),
+        agency_name: "North Star Talent".to_string(),
+        agency_slug: "north-star".to_string(),
+        agent_name: Some("Sam".to_string()),
+        created_at: Utc::now(),
+    }
+}
+
+#[test]
+fn test_status_label_before_the_client_decides() {
+    assert_eq!(status_label("pending", None), "Awaiting client approval");
+    assert_eq!(status_label("declined", None), "Declined by client");
+}
+
+#[test]
+fn test_status_label_follows_the_application() {
+    assert_eq!(status_label("approved", None), "Submitted");
+    assert_eq!(status_label("approved", Some("pending")), "Submitted");
+    assert_eq!(status_label("approved", Some("reviewed")), "Reviewed");
+    assert_eq!(status_label("approved", Some("shortlisted")), "Shortlisted");
+    assert_eq!(status_label("approved", Some("rejected")), "Not selected");
+    assert_eq!(status_label("approved", Some("withdrawn")), "Withdrawn");
+}
+
+#[test]
+fn test_submission_helpers() {
+    let pending = submission("pending", None, Some("Ada Lovelace"));
+    assert!(pending.is_pending());
+    assert_eq!(pending.key(), "s1");
+    assert_eq!(pending.job_key(), "j1");
+    assert_eq!(pending.talent_display(), "Ada Lovelace");
+    assert_eq!(pending.status_label(), "Awaiting client approval");
+
+    let approved = submission("approved", Some("shortlisted"), Some("  "));
+    assert!(!approved.is_pending());
+    assert_eq!(approved.talent_display(), "ada");
+    assert_eq!(approved.status_label(), "Shortlisted");
+}
@@ -1,4 +1,10 @@
-use slatehub::templates::{BaseContext, IndexTemplate};
+use askama::Template;
+use slatehub::models::production::CrewContact;
+use slatehub::templates::equipment::EquipmentItemsTemplate;
+use slatehub::templates::{
+    BaseContext, ContactSheetPrintTemplate, CrewListPrintTemplate, FormatQuery, IndexTemplate,
+    ProductionMemberView,
+};
 
 #[test]
 fn test_base_context() {
@@ -20,3 +26,102 @@ fn test_template_creation() {
     assert_eq!(template.active_page, "index");
     assert_eq!(template.app_name, "SlateHub");
 }
+
+#[test]
+fn test_format_query() {
+    let print = FormatQuery {
+        format: Some("print".to_string()),
+    };
+    assert!(print.is_print());
+    assert!(!FormatQuery::default().is_print());
+    assert!(
+        !FormatQuery {
+            format: Some("csv".to_string())
+        }
+        .is_print()
+    );
+}
+
+#[test]
+fn test_crew_list_print() {
+    let html = CrewListPrintTemplate {
+        app_name: "SlateHub".to_string(),
+        version: "1".to_string(),
+        production_title: "Night Shift".to_string(),
+        production_slug: "night-shift".to_string(),
+        production_type: "Short Film".to_string(),
+        printed_on: "Mar 2, 2026".to_string(),
+        members: vec![ProductionMemberView {
+            id: "abc".to_string(),
+            name: "Ana Ruiz".to_string(),
+            username: Some("ana".to_string()),
+            slug: None,
+            avatar: None,
+            role: "member".to_string(),
+            production_roles: Some(vec!["Gaffer".to_string(), "Best Boy".to_string()]),
+            member_type: "person".to_string(),
+            invitation_status: "accepted".to_string(),
+            is_verified: false,
+        }],
+    }
+    .render()
+    .unwrap();
+    assert!(html.contains("/static/css/print.css"));
+    assert!(html.contains("Gaffer, Best Boy"));
+    assert!(html.contains("/ana"));
+    assert!(!html.contains("site-header"));
+}
+
+#[test]
+fn test_contact_sheet_print() {
+    let html = ContactSheetPrintTemplate {
+        app_name: "SlateHub".to_string(),
+        version: "1".to_string(),
+        production_title: "Night Shift".to_string(),
+        production_slug: "night-shift".to_string(),
+        printed_on: "Mar 2, 2026".to_string(),
+        contacts: vec![
+            CrewContact {
+                name: "Ana Ruiz".to_string(),
+                username: "ana".to_string(),
+                production_roles: Some(vec!["Gaffer".to_string()]),
+                email: Some("ana@example.com".to_string()),
+                phone: Some("555-0100".to_string()),
+            },
+            CrewContact {
+                name: "Sam Lee".to_string(),
+                username: "sam".to_string(),
+                production_roles: None,
+                email: None,
+                phone: None,
+            },
+        ],
+    }
+    .render()
+    .unwrap();
+    assert!(html.contains("2 people"));
+    assert!(html.contains("ana@example.com"));
+    assert!(html.contains("555-0100"));
+    assert!(html.contains("Sam Lee"));
+}
+
+#[test]
+fn test_equipment_items_fragment() {
+    let html = EquipmentItemsTemplate {
+        equipment: vec![],
+        owner_type: "person".to_string(),
+        owner_id: "person:ana".to_string(),
+        fragment_src: "/equipment/items?owner_type=person&owner_id=person%3Aana".to_string(),
+    }
+    .render()
+    .unwrap();
+    // The fragment is the element it replaces, so it can be swapped in whole
+    assert!(
+        html.trim_start()
+            .starts_with(r#"<section id="section-equipment-list""#)
+    );
+    assert!(html.contains("data-fragment-src="));
+    assert!(html.trim_end().ends_with("</section>"));
+    assert!(html.contains("No equipment items found."));
+    assert!(!html.contains("<html"));
+}
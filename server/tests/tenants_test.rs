@@ -0,0 +1,45 @@
+use slatehub::db;
+use slatehub::services::tenants::{
+    namespace_for, normalize_host, parse_hostnames, s3_prefix, validate_slug,
+};
+
+#[test]
+fn test_normalize_host_drops_port_case_and_trailing_dot() {
+    assert_eq!(normalize_host("School.Example.com:3000"), "school.example.com");
+    assert_eq!(normalize_host("school.example.com."), "school.example.com");
+    assert_eq!(normalize_host(" localhost "), "localhost");
+    assert_eq!(normalize_host("[::1]:8080"), "[::1]");
+}
+
+#[test]
+fn test_parse_hostnames_splits_and_dedups() {
+    assert_eq!(
+        parse_hostnames("b.example.com, a.example.com\nB.example.com:443"),
+        vec!["a.example.com".to_string(), "b.example.com".to_string()]
+    );
+    assert!(parse_hostnames(" , ").is_empty());
+}
+
+#[test]
+fn test_slug_validation() {
+    assert!(validate_slug("nyfa").is_ok());
+    assert!(validate_slug("film-school-2").is_ok());
+    assert!(validate_slug("a").is_err());
+    assert!(validate_slug("2fast").is_err());
+    assert!(validate_slug("Film").is_err());
+    assert!(validate_slug("trailing-").is_err());
+    assert!(validate_slug("has space").is_err());
+    assert!(validate_slug("../escape").is_err());
+}
+
+#[test]
+fn test_namespace_and_s3_prefix() {
+    assert_eq!(namespace_for("film-school"), "tenant_film_school");
+    assert_eq!(s3_prefix("film-school"), "tenants/film-school/");
+}
+
+#[test]
+fn test_primary_install_has_no_tenant() {
+    assert_eq!(db::current_tenant(), None);
+    assert_eq!(db::tenant_key("person:abc"), "person:abc");
+}
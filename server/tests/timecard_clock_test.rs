@@ -0,0 +1,108 @@
+mod common;
+
+use slatehub::db::DB;
+use slatehub::error::Error;
+use slatehub::models::timecard::TimecardModel;
+use surrealdb::types::RecordId;
+
+async fn seed_person(username: &str) -> RecordId {
+    let id: Option<RecordId> = DB
+        .query(
+            "CREATE ONLY person CONTENT {
+                email: string::concat($username, '@example.com'),
+                password: 'hashed_password',
+                username: $username,
+                profile: { name: $username, skills: [], social_links: [], ethnicity: [], unions: [], languages: [], experience: [], education: [], reels: [], media_other: [], awards: [] }
+            } RETURN VALUE id",
+        )
+        .bind(("username", username.to_string()))
+        .await
+        .expect("Failed to create test person")
+        .take(0)
+        .expect("Failed to take person ID");
+    id.expect("No person record returned from CREATE")
+}
+
+/// A production `member` has joined with `status`, shooting on `date`
+async fn seed_production(slug: &str, member: &RecordId, status: &str, date: &str) -> RecordId {
+    let id: Option<RecordId> = DB
+        .query(
+            "CREATE ONLY production CONTENT {
+                title: $slug, slug: $slug, type: 'Film', status: 'Production'
+            } RETURN VALUE id",
+        )
+        .bind(("slug", slug.to_string()))
+        .await
+        .expect("Failed to create test production")
+        .take(0)
+        .expect("Failed to take production ID");
+    let id = id.expect("No production record returned from CREATE");
+    DB.query(
+        "RELATE $person->member_of->$production SET role = 'member', invitation_status = $status;
+         CREATE shoot_day SET production = $production, day_number = 1, date = $date",
+    )
+    .bind(("person", member.clone()))
+    .bind(("production", id.clone()))
+    .bind(("status", status.to_string()))
+    .bind(("date", date.to_string()))
+    .await
+    .expect("Failed to seed membership and shoot day");
+    id
+}
+
+fn clean_all() {
+    common::clean_table("timecard");
+    common::clean_table("shoot_day");
+    common::clean_table("member_of");
+    common::clean_table("production");
+    common::clean_table("person");
+}
+
+#[test]
+fn test_todays_days_are_only_on_joined_productions() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let crew = seed_person("gaffer").await;
+        let joined = seed_production("night-shift", &crew, "accepted", "2026-06-01").await;
+        seed_production("pending", &crew, "pending", "2026-06-01").await;
+        seed_production("tomorrow", &crew, "accepted", "2026-06-02").await;
+
+        let days = TimecardModel::days_on(&crew, "2026-06-01").await.unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].production, joined);
+    });
+}
+
+#[test]
+fn test_clocking_out_finds_the_open_shift() {
+    common::setup_test_db();
+    clean_all();
+
+    common::run(async {
+        let crew = seed_person("grip").await;
+        seed_production("night-shift", &crew, "accepted", "2026-06-01").await;
+        assert!(TimecardModel::open_for(&crew).await.unwrap().is_none());
+
+        let day = TimecardModel::days_on(&crew, "2026-06-01")
+            .await
+            .unwrap()
+            .remove(0);
+        TimecardModel::clock_in(&day, &crew, "07:00").await.unwrap();
+        assert!(matches!(
+            TimecardModel::clock_in(&day, &crew, "07:30").await,
+            Err(Error::Conflict(_))
+        ));
+
+        let open = TimecardModel::open_for(&crew).await.unwrap().unwrap();
+        assert_eq!(open.shoot_day, day.id);
+        assert_eq!(open.time_in, "07:00");
+
+        let card = TimecardModel::clock_out(&day, &crew, "17:30", 30)
+            .await
+            .unwrap();
+        assert_eq!(card.status, "submitted");
+        assert!(TimecardModel::open_for(&crew).await.unwrap().is_none());
+    });
+}
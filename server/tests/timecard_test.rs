@@ -0,0 +1,199 @@
+use chrono::Utc;
+use slatehub::models::daily_report::{CrewTime, fill_crew_times};
+use slatehub::models::timecard::{
+    CrewDeal, DealData, HoursBreakdown, OvertimeRules, TimecardListing, compute_hours,
+    is_producer, payroll_csv,
+};
+use surrealdb::types::RecordId;
+
+fn deal(rate_type: &str, rate: Option<f64>) -> CrewDeal {
+    CrewDeal {
+        id: RecordId::new("crew_deal", "d"),
+        production: RecordId::new("production", "p"),
+        person: RecordId::new("person", "sam"),
+        rate_type: rate_type.to_string(),
+        rate,
+        straight_hours: 10.0,
+        double_time_after: 12.0,
+        overtime_multiplier: 1.5,
+        double_time_multiplier: 2.0,
+        updated_at: Utc::now(),
+    }
+}
+
+fn listing(person: &str, name: &str, time_in: &str, time_out: Option<&str>, status: &str) -> TimecardListing {
+    TimecardListing {
+        id: RecordId::new("timecard", person),
+        person: RecordId::new("person", person),
+        person_name: name.to_string(),
+        shoot_day: RecordId::new("shoot_day", "d1"),
+        date: "2026-05-04".to_string(),
+        day_number: 3,
+        time_in: time_in.to_string(),
+        time_out: time_out.map(str::to_string),
+        meal_minutes: 30,
+        status: status.to_string(),
+        regular_hours: 10.0,
+        overtime_hours: 2.0,
+        double_time_hours: 0.5,
+        note: None,
+    }
+}
+
+#[test]
+fn short_day_is_all_straight_time() {
+    let hours = compute_hours("08:00", "16:30", 30, &OvertimeRules::default()).unwrap();
+    assert_eq!(
+        hours,
+        HoursBreakdown {
+            worked: 8.0,
+            regular: 8.0,
+            overtime: 0.0,
+            double_time: 0.0
+        }
+    );
+}
+
+#[test]
+fn long_day_splits_into_overtime_and_double_time() {
+    let hours = compute_hours("06:00", "20:00", 60, &OvertimeRules::default()).unwrap();
+    assert_eq!(hours.worked, 13.0);
+    assert_eq!(hours.regular, 10.0);
+    assert_eq!(hours.overtime, 2.0);
+    assert_eq!(hours.double_time, 1.0);
+}
+
+#[test]
+fn overnight_shifts_wrap_past_midnight() {
+    let hours = compute_hours("18:00", "04:00", 0, &OvertimeRules::default()).unwrap();
+    assert_eq!(hours.worked, 10.0);
+    assert_eq!(hours.overtime, 0.0);
+}
+
+#[test]
+fn deal_rules_change_the_thresholds() {
+    let rules = OvertimeRules {
+        straight_hours: 8.0,
+        double_time_after: 8.0,
+        ..OvertimeRules::default()
+    };
+    let hours = compute_hours("09:00", "19:00", 0, &rules).unwrap();
+    assert_eq!(hours.regular, 8.0);
+    assert_eq!(hours.overtime, 0.0);
+    assert_eq!(hours.double_time, 2.0);
+}
+
+#[test]
+fn meal_longer_than_the_shift_leaves_zero_hours() {
+    let hours = compute_hours("09:00", "09:30", 60, &OvertimeRules::default()).unwrap();
+    assert_eq!(hours.worked, 0.0);
+    assert!(compute_hours("", "09:30", 0, &OvertimeRules::default()).is_none());
+}
+
+#[test]
+fn hourly_deal_pays_overtime_at_multipliers() {
+    let hours = HoursBreakdown {
+        worked: 13.0,
+        regular: 10.0,
+        overtime: 2.0,
+        double_time: 1.0,
+    };
+    // 10 * 40 + 2 * 60 + 1 * 80
+    assert_eq!(deal("hourly", Some(40.0)).gross_pay(&hours), Some(600.0));
+    assert_eq!(deal("hourly", None).gross_pay(&hours), None);
+}
+
+#[test]
+fn daily_deal_guarantees_the_day_rate() {
+    let deal = deal("daily", Some(500.0));
+    assert_eq!(deal.hourly_rate(), Some(50.0));
+
+    let short = HoursBreakdown {
+        worked: 6.0,
+        regular: 6.0,
+        ..Default::default()
+    };
+    assert_eq!(deal.gross_pay(&short), Some(500.0));
+
+    let long = HoursBreakdown {
+        worked: 11.0,
+        regular: 10.0,
+        overtime: 1.0,
+        double_time: 0.0,
+    };
+    assert_eq!(deal.gross_pay(&long), Some(575.0));
+}
+
+#[test]
+fn deal_validation() {
+    let valid = DealData {
+        rate_type: "hourly".to_string(),
+        rate: Some(45.0),
+        straight_hours: 10.0,
+        double_time_after: 12.0,
+        overtime_multiplier: 1.5,
+        double_time_multiplier: 2.0,
+    };
+    assert!(valid.validate().is_ok());
+    assert!(DealData { rate_type: "weekly".to_string(), ..valid.clone() }.validate().is_err());
+    assert!(DealData { rate: Some(-1.0), ..valid.clone() }.validate().is_err());
+    assert!(DealData { double_time_after: 9.0, ..valid.clone() }.validate().is_err());
+    assert!(DealData { overtime_multiplier: 0.5, ..valid.clone() }.validate().is_err());
+}
+
+#[test]
+fn producers_are_recognized() {
+    assert!(is_producer(&["Line Producer".to_string()]));
+    assert!(is_producer(&["Unit Production Manager".to_string()]));
+    assert!(!is_producer(&["Gaffer".to_string()]));
+}
+
+#[test]
+fn payroll_csv_includes_hours_and_pay() {
+    let cards = vec![
+        listing("sam", "Sam Reyes", "06:00", Some("19:00"), "approved"),
+        listing("jo", "Jo Park", "07:00", Some("19:30"), "approved"),
+    ];
+    let csv = payroll_csv(&cards, &[deal("hourly", Some(40.0))]);
+    let lines: Vec<&str> = csv.split("\r\n").collect();
+
+    assert!(lines[0].starts_with("Date,Day,Name,In,Out,Meal (min),Regular,Overtime,Double Time,Total Hours"));
+    assert_eq!(
+        lines[1],
+        "2026-05-04,3,Sam Reyes,06:00,19:00,30,10.00,2.00,0.50,12.50,hourly,40.00,560.00"
+    );
+    // No deal on file: hours only
+    assert!(lines[2].ends_with(",12.50,,,"));
+}
+
+#[test]
+fn timecards_fill_report_crew_times() {
+    let mut crew = vec![
+        CrewTime {
+            name: "Sam Reyes".to_string(),
+            role: "Gaffer".to_string(),
+            ..Default::default()
+        },
+        CrewTime {
+            name: "Jo Park".to_string(),
+            role: "Grip".to_string(),
+            time_in: "06:45".to_string(),
+            time_out: String::new(),
+        },
+    ];
+    let cards = vec![
+        listing("sam", "Sam Reyes", "06:00", None, "open"),
+        listing("jo", "Jo Park", "07:00", Some("19:30"), "submitted"),
+        listing("al", "Al Diaz", "08:00", Some("18:00"), "submitted"),
+        listing("ed", "Ed Moss", "08:00", Some("18:00"), "rejected"),
+    ];
+    fill_crew_times(&mut crew, &cards);
+
+    assert_eq!(crew[0].time_in, "06:00");
+    assert_eq!(crew[0].time_out, "");
+    // Times the AD entered are kept
+    assert_eq!(crew[1].time_in, "06:45");
+    assert_eq!(crew[1].time_out, "19:30");
+    assert_eq!(crew.len(), 3);
+    assert_eq!(crew[2].name, "Al Diaz");
+}
@@ -0,0 +1,25 @@
+use slatehub::services::tokens::{is_token, random_token, token_hash};
+
+#[test]
+fn random_tokens_are_alphanumeric_and_distinct() {
+    let token = random_token(32);
+    assert!(is_token(&token, 32));
+    assert_ne!(token, random_token(32));
+    assert_eq!(random_token(12).len(), 12);
+}
+
+#[test]
+fn junk_is_not_a_token() {
+    assert!(!is_token("abc", 32));
+    assert!(!is_token(&"a".repeat(33), 32));
+    assert!(!is_token(&format!("{}/", "a".repeat(31)), 32));
+}
+
+#[test]
+fn token_hash_is_hex_sha256() {
+    assert_eq!(
+        token_hash("abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+    assert_ne!(token_hash("scim_token"), token_hash("scim_token2"));
+}
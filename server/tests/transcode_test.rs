@@ -0,0 +1,162 @@
+use std::path::Path;
+
+use slatehub::services::transcode::{
+    MAX_ATTEMPTS, Rendition, audio_samples_args, hls_args, mp4_args, parse_ffmetadata, pcm_samples,
+    playlist_segments, poster_args, rendition_content_type, rendition_prefix, rewrite_playlist,
+    status_after_failure, tags_args,
+};
+
+const PLAYLIST: &str = "#EXTM3U
+#EXT-X-VERSION:3
+#EXT-X-TARGETDURATION:6
+#EXT-X-PLAYLIST-TYPE:VOD
+#EXTINF:6.000000,
+segment_0000.ts
+#EXTINF:3.200000,
+segment_0001.ts
+#EXT-X-ENDLIST
+";
+
+fn rendition(status: &str) -> Rendition {
+    Rendition {
+        status: status.to_string(),
+        mp4_key: None,
+        poster_key: None,
+        hls_key: None,
+    }
+}
+
+#[test]
+fn renditions_sit_in_a_folder_named_after_the_original() {
+    assert_eq!(
+        rendition_prefix("private/selftapes/req1/p1-01ABC.mov"),
+        "private/selftapes/req1/p1-01ABC/"
+    );
+    assert_eq!(rendition_prefix("clip.mp4"), "clip/");
+    assert_eq!(rendition_prefix("private/reels/noext"), "private/reels/noext/");
+}
+
+#[test]
+fn rendition_content_types() {
+    assert_eq!(rendition_content_type("a/web.mp4"), "video/mp4");
+    assert_eq!(rendition_content_type("a/poster.jpg"), "image/jpeg");
+    assert_eq!(rendition_content_type("a/hls/index.m3u8"), "application/vnd.apple.mpegurl");
+    assert_eq!(rendition_content_type("a/hls/segment_0000.ts"), "video/mp2t");
+    assert_eq!(rendition_content_type("a/other"), "application/octet-stream");
+}
+
+#[test]
+fn mp4_is_h264_aac_faststart() {
+    let args = mp4_args(Path::new("/tmp/in.mov"), Path::new("/tmp/out/web.mp4"));
+    let joined = args.join(" ");
+    assert!(joined.contains("-i /tmp/in.mov"));
+    assert!(joined.contains("-c:v libx264"));
+    assert!(joined.contains("-pix_fmt yuv420p"));
+    assert!(joined.contains("-c:a aac"));
+    assert!(joined.contains("-movflags +faststart"));
+    assert!(joined.contains("min(720,ih)"));
+    // Audio is optional so silent tapes still transcode
+    assert!(args.iter().any(|a| a == "0:a:0?"));
+    assert_eq!(args.last().map(String::as_str), Some("/tmp/out/web.mp4"));
+}
+
+#[test]
+fn hls_is_cut_from_the_mp4_without_reencoding() {
+    let args = hls_args(
+        Path::new("/tmp/out/web.mp4"),
+        Path::new("/tmp/out/hls/index.m3u8"),
+        Path::new("/tmp/out/hls/segment_%04d.ts"),
+    );
+    let joined = args.join(" ");
+    assert!(joined.contains("-c copy"));
+    assert!(joined.contains("-hls_playlist_type vod"));
+    assert!(joined.contains("-hls_segment_filename /tmp/out/hls/segment_%04d.ts"));
+    assert_eq!(args.last().map(String::as_str), Some("/tmp/out/hls/index.m3u8"));
+}
+
+#[test]
+fn poster_is_a_single_frame() {
+    let args = poster_args(Path::new("/tmp/out/web.mp4"), Path::new("/tmp/out/poster.jpg"));
+    let joined = args.join(" ");
+    assert!(joined.contains("-frames:v 1"));
+    assert!(joined.contains("thumbnail,"));
+    assert_eq!(args.last().map(String::as_str), Some("/tmp/out/poster.jpg"));
+}
+
+#[test]
+fn playlist_segments_skip_tags() {
+    assert_eq!(playlist_segments(PLAYLIST), vec!["segment_0000.ts", "segment_0001.ts"]);
+    assert!(playlist_segments("#EXTM3U\n").is_empty());
+}
+
+#[test]
+fn rewritten_playlist_points_at_urls() {
+    let urls = vec!["https://s3/a?sig=1".to_string(), "https://s3/b?sig=2".to_string()];
+    let rewritten = rewrite_playlist(PLAYLIST, &urls);
+    assert!(rewritten.contains("#EXTINF:6.000000,\nhttps://s3/a?sig=1\n"));
+    assert!(rewritten.contains("#EXTINF:3.200000,\nhttps://s3/b?sig=2\n"));
+    assert!(!rewritten.contains("segment_000"));
+    assert!(rewritten.ends_with("#EXT-X-ENDLIST\n"));
+}
+
+#[test]
+fn failed_jobs_retry_until_the_limit() {
+    assert_eq!(status_after_failure(1), "queued");
+    assert_eq!(status_after_failure(MAX_ATTEMPTS - 1), "queued");
+    assert_eq!(status_after_failure(MAX_ATTEMPTS), "failed");
+}
+
+#[test]
+fn pending_renditions_show_as_processing() {
+    assert!(rendition("queued").is_pending());
+    assert!(rendition("processing").is_pending());
+    assert!(!rendition("ready").is_pending());
+    assert!(!rendition("failed").is_pending());
+    assert!(rendition("ready").is_ready());
+}
+
+#[test]
+fn audio_is_decoded_to_mono_pcm_on_stdout() {
+    let args = audio_samples_args(Path::new("/tmp/in.flac"), 8000);
+    let joined = args.join(" ");
+    assert!(joined.contains("-i /tmp/in.flac"));
+    assert!(joined.contains("-vn -ac 1 -ar 8000"));
+    assert!(joined.ends_with("-f s16le -"));
+}
+
+#[test]
+fn tags_are_written_as_ffmetadata() {
+    let args = tags_args(Path::new("/tmp/in.mp3"));
+    assert!(args.join(" ").ends_with("-i /tmp/in.mp3 -f ffmetadata -"));
+}
+
+#[test]
+fn ffmetadata_global_tags_are_parsed() {
+    let text = r";FFMETADATA1
+TITLE=Night Drive
+artist=Sam Rivera
+comment=Strings\=live\; synths\\pads
+genre=
+lyrics=first line\
+second line
+[STREAM]
+title=Stream title
+";
+    assert_eq!(
+        parse_ffmetadata(text),
+        vec![
+            ("title".to_string(), "Night Drive".to_string()),
+            ("artist".to_string(), "Sam Rivera".to_string()),
+            ("comment".to_string(), "Strings=live; synths\\pads".to_string()),
+            ("lyrics".to_string(), "first line\nsecond line".to_string()),
+        ]
+    );
+    assert!(parse_ffmetadata("").is_empty());
+}
+
+#[test]
+fn pcm_samples_are_little_endian() {
+    assert_eq!(pcm_samples(&[0x01, 0x00, 0xff, 0xff, 0x00, 0x80]), vec![1, -1, i16::MIN]);
+    // A trailing odd byte is ignored
+    assert_eq!(pcm_samples(&[0x10, 0x00, 0x7f]), vec![16]);
+}
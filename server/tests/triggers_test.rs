@@ -0,0 +1,79 @@
+use chrono::{TimeZone, Utc};
+use slatehub::services::id_verification::verify_signature;
+use slatehub::services::triggers::{
+    Cursor, event_scope, sample_application, sample_job, signature, valid_target_url,
+};
+
+#[test]
+fn test_cursor_round_trip() {
+    let at = Utc.with_ymd_and_hms(2026, 5, 4, 12, 30, 0).unwrap()
+        + chrono::Duration::nanoseconds(123_456);
+    let cursor = Cursor::new(at, "k3j2h1");
+    let encoded = cursor.encode();
+    assert!(
+        encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    );
+    assert_eq!(Cursor::decode(&encoded), Some(cursor));
+
+    assert_eq!(Cursor::decode(""), None);
+    assert_eq!(Cursor::decode("not a cursor"), None);
+    // Keys are only ever record keys
+    let forged = Cursor::new(at, "x; DELETE job_posting").encode();
+    assert_eq!(Cursor::decode(&forged), None);
+}
+
+#[test]
+fn test_samples_carry_cursors() {
+    let job = sample_job();
+    assert!(Cursor::decode(&job.cursor).is_some());
+    assert!(!job.roles.is_empty());
+    let application = sample_application();
+    assert!(Cursor::decode(&application.cursor).is_some());
+    assert_eq!(application.job_title, job.title);
+}
+
+#[test]
+fn test_event_scope() {
+    assert_eq!(event_scope("job.created"), Some("read:jobs"));
+    assert_eq!(
+        event_scope("application.created"),
+        Some("read:applications")
+    );
+    assert_eq!(event_scope("job.deleted"), None);
+}
+
+#[test]
+fn test_valid_target_url() {
+    assert!(valid_target_url(
+        "https://hooks.zapier.com/hooks/standard/123/abc"
+    ));
+    assert!(valid_target_url(
+        "https://hook.eu1.make.com/xyz?source=slatehub"
+    ));
+    assert!(valid_target_url("https://203.0.113.7/hook"));
+
+    assert!(!valid_target_url("http://hooks.zapier.com/hooks/123"));
+    assert!(!valid_target_url("https://localhost/hook"));
+    assert!(!valid_target_url("https://api.localhost:8443/hook"));
+    assert!(!valid_target_url("https://127.0.0.1/hook"));
+    assert!(!valid_target_url("https://10.0.0.4/hook"));
+    assert!(!valid_target_url("https://192.168.1.20:8080/hook"));
+    assert!(!valid_target_url(
+        "https://169.254.169.254/latest/meta-data"
+    ));
+    assert!(!valid_target_url("https://[::1]/hook"));
+    assert!(!valid_target_url("https:///hook"));
+    assert!(!valid_target_url("https://example.com/a hook"));
+}
+
+#[test]
+fn test_signature() {
+    let body = br#"{"id":"abc"}"#;
+    let signed = signature("whsec", body);
+    let hex = signed.strip_prefix("sha256=").unwrap();
+    assert_eq!(hex.len(), 64);
+    assert!(verify_signature("whsec", body, hex));
+    assert!(!verify_signature("other", body, hex));
+}
@@ -0,0 +1,126 @@
+use slatehub::services::search_utils::parse_query;
+use slatehub::units::{
+    UnitSystem, distance, distance_km, feet_inches_to_mm, format_coordinates, height, height_both,
+    kg_to_lbs, lbs_to_kg, mm_to_feet_inches, parse_coordinates, parse_height, weight,
+};
+
+#[test]
+fn test_feet_and_inches_round_to_the_nearest_inch() {
+    assert_eq!(mm_to_feet_inches(1800), (5, 11));
+    assert_eq!(mm_to_feet_inches(1829), (6, 0));
+    // 71.7 inches rounds up and carries into the next foot
+    assert_eq!(mm_to_feet_inches(1822), (6, 0));
+    assert_eq!(feet_inches_to_mm(5, 11), 1803);
+    assert_eq!(mm_to_feet_inches(feet_inches_to_mm(5, 4)), (5, 4));
+}
+
+#[test]
+fn test_formatting_follows_the_unit_system() {
+    assert_eq!(height(1800, UnitSystem::Metric), "180 cm");
+    assert_eq!(height(1800, UnitSystem::Imperial), "5'11\"");
+    assert_eq!(height_both(1651, UnitSystem::Imperial), "5'5\" (165 cm)");
+    assert_eq!(weight(75, UnitSystem::Metric), "75 kg");
+    assert_eq!(weight(75, UnitSystem::Imperial), "165 lbs");
+    assert_eq!(kg_to_lbs(75), 165);
+    assert_eq!(lbs_to_kg(165.0), 75);
+}
+
+#[test]
+fn test_unit_preference() {
+    assert_eq!(UnitSystem::parse("Imperial"), Some(UnitSystem::Imperial));
+    assert_eq!(UnitSystem::parse("furlongs"), None);
+    assert_eq!(UnitSystem::from_preference(None), UnitSystem::Metric);
+    assert_eq!(
+        UnitSystem::from_preference(Some("imperial")).as_str(),
+        "imperial"
+    );
+}
+
+#[test]
+fn test_parse_height_in_either_system() {
+    assert_eq!(parse_height("180cm"), Some(1800));
+    assert_eq!(parse_height("180 cm"), Some(1800));
+    assert_eq!(parse_height("1.8m"), Some(1800));
+    assert_eq!(parse_height("5'11\""), Some(1803));
+    assert_eq!(parse_height("5'11"), Some(1803));
+    assert_eq!(parse_height("5 ft 11 inches"), Some(1803));
+    assert_eq!(parse_height("6ft"), Some(1829));
+    assert_eq!(parse_height("5'13"), None);
+    assert_eq!(parse_height("35cm"), None);
+    assert_eq!(parse_height("tall"), None);
+}
+
+#[test]
+fn test_query_height_bounds() {
+    let parsed = parse_query("male actors over 6ft in London");
+    assert_eq!(parsed.height_min_mm, Some(1829));
+    assert_eq!(parsed.height_max_mm, None);
+    assert_eq!(parsed.location.as_deref(), Some("London"));
+    assert_eq!(parsed.cleaned, "actor");
+
+    let parsed = parse_query("actresses under 170cm");
+    assert_eq!(parsed.height_max_mm, Some(1700));
+    assert_eq!(parsed.height_min_mm, None);
+}
+
+#[test]
+fn test_query_height_ranges() {
+    let parsed = parse_query("actors between 5'8 and 6'2");
+    assert_eq!(parsed.height_min_mm, Some(1727));
+    assert_eq!(parsed.height_max_mm, Some(1880));
+    assert_eq!(parsed.cleaned, "actor");
+
+    let parsed = parse_query("dancers 175-165cm");
+    assert_eq!(parsed.height_min_mm, Some(1650));
+    assert_eq!(parsed.height_max_mm, Some(1750));
+}
+
+#[test]
+fn test_query_exact_height_absorbs_tall() {
+    let parsed = parse_query("6ft tall actors");
+    assert_eq!(parsed.height_min_mm, Some(1804));
+    assert_eq!(parsed.height_max_mm, Some(1854));
+    // "tall" belongs to the height, not the body type
+    assert_eq!(parsed.body_type, None);
+    assert_eq!(parsed.cleaned, "actor");
+}
+
+#[test]
+fn test_query_without_height() {
+    let parsed = parse_query("1990's sitcom writers");
+    assert_eq!(parsed.height_min_mm, None);
+    assert_eq!(parsed.height_max_mm, None);
+}
+
+#[test]
+fn test_distance_between_coordinates() {
+    // Downtown Los Angeles to Santa Monica pier, about 24 km
+    let km = distance_km((34.0522, -118.2437), (34.0094, -118.4973));
+    assert!((km - 23.8).abs() < 0.5, "got {}", km);
+    assert_eq!(distance_km((51.5, -0.12), (51.5, -0.12)), 0.0);
+
+    assert_eq!(distance(4.24, UnitSystem::Metric), "4.2 km");
+    assert_eq!(distance(4.24, UnitSystem::Imperial), "2.6 mi");
+    assert_eq!(distance(38.4, UnitSystem::Metric), "38 km");
+}
+
+#[test]
+fn test_parse_coordinates() {
+    assert_eq!(
+        parse_coordinates("34.0522, -118.2437"),
+        Some((34.0522, -118.2437))
+    );
+    assert_eq!(
+        parse_coordinates(" 34.0522 -118.2437 "),
+        Some((34.0522, -118.2437))
+    );
+    assert_eq!(parse_coordinates("91, 0"), None);
+    assert_eq!(parse_coordinates("0, 181"), None);
+    assert_eq!(parse_coordinates("34.0522"), None);
+    assert_eq!(parse_coordinates("34.0522, -118.2437, 12"), None);
+    assert_eq!(parse_coordinates("north, west"), None);
+    assert_eq!(
+        parse_coordinates(&format_coordinates((34.0522, -118.2437))),
+        Some((34.0522, -118.2437))
+    );
+}
@@ -0,0 +1,49 @@
+use slatehub::error::Error;
+use slatehub::services::uploads::{CHUNK_SIZE, UploadPart, expected_chunk, part_count, received_offset};
+
+fn part(number: i64, size: i64) -> UploadPart {
+    UploadPart {
+        number,
+        etag: format!("etag-{}", number),
+        size,
+    }
+}
+
+#[test]
+fn chunk_size_meets_the_s3_part_minimum() {
+    assert!(CHUNK_SIZE >= 5 * 1024 * 1024);
+}
+
+#[test]
+fn part_count_rounds_up() {
+    assert_eq!(part_count(1, 10), 1);
+    assert_eq!(part_count(10, 10), 1);
+    assert_eq!(part_count(11, 10), 2);
+    assert_eq!(part_count(25, 10), 3);
+}
+
+#[test]
+fn offset_counts_parts_in_sequence() {
+    assert_eq!(received_offset(&[]), 0);
+    assert_eq!(received_offset(&[part(1, 10), part(2, 10)]), 20);
+    assert_eq!(received_offset(&[part(2, 10), part(1, 10), part(3, 5)]), 25);
+    // A gap stops the count until it's filled
+    assert_eq!(received_offset(&[part(1, 10), part(3, 10)]), 10);
+    assert_eq!(received_offset(&[part(2, 10)]), 0);
+}
+
+#[test]
+fn expected_chunk_gives_part_number_and_length() {
+    assert_eq!(expected_chunk(25, 10, 0).unwrap(), (1, 10));
+    assert_eq!(expected_chunk(25, 10, 10).unwrap(), (2, 10));
+    assert_eq!(expected_chunk(25, 10, 20).unwrap(), (3, 5));
+    assert_eq!(expected_chunk(10, 10, 0).unwrap(), (1, 10));
+}
+
+#[test]
+fn expected_chunk_rejects_offsets_off_a_chunk_boundary() {
+    assert!(matches!(expected_chunk(25, 10, 5), Err(Error::BadRequest(_))));
+    assert!(matches!(expected_chunk(25, 10, 25), Err(Error::BadRequest(_))));
+    assert!(matches!(expected_chunk(25, 10, 30), Err(Error::BadRequest(_))));
+    assert!(matches!(expected_chunk(25, 10, -10), Err(Error::BadRequest(_))));
+}
@@ -0,0 +1,149 @@
+use slatehub::error::Error;
+use slatehub::models::job::CreateJobForm;
+use slatehub::models::location::CreateLocationForm;
+use slatehub::models::person::CreateUser;
+use slatehub::validation::{FieldErrors, Validate};
+
+#[test]
+fn test_field_errors_keep_first_message_per_field() {
+    let mut errors = FieldErrors::new();
+    assert!(errors.is_empty());
+
+    errors.required("name", "  ", "Name");
+    errors.add("name", "Name is taken");
+    errors.max_chars("bio", "A long story", 5, "Bio");
+
+    assert_eq!(errors.len(), 3);
+    assert!(errors.has("name"));
+    assert!(!errors.has("email"));
+    assert_eq!(errors.get("name"), Some("Name is required"));
+    assert_eq!(errors.get("email"), None);
+    assert_eq!(
+        errors.messages(),
+        vec![
+            "Name is required",
+            "Name is taken",
+            "Bio must be 5 characters or fewer"
+        ]
+    );
+}
+
+#[test]
+fn test_checks_skip_blank_optional_values() {
+    let mut errors = FieldErrors::new();
+    errors.email("email", "");
+    errors.url("website", " ");
+    errors.int_range("age", "", 0, 100, "Age");
+    assert!(errors.into_result().is_ok());
+}
+
+#[test]
+fn test_format_checks() {
+    let mut errors = FieldErrors::new();
+    errors.email("a", "jane@example.com");
+    errors.url("b", "https://example.com/reel?t=1");
+    errors.int_range("c", "40", 0, 100, "Age");
+    assert!(errors.is_empty());
+
+    errors.email("a", "jane@example");
+    errors.url("b", "example.com");
+    errors.url("b2", "https://localhost");
+    errors.int_range("c", "140", 0, 100, "Age");
+    errors.int_range("c2", "forty", 0, 100, "Age");
+    for field in ["a", "b", "b2", "c", "c2"] {
+        assert!(errors.has(field), "{} should be invalid", field);
+    }
+}
+
+#[test]
+fn test_field_errors_convert_to_validation_error() {
+    let mut errors = FieldErrors::new();
+    errors.add("title", "Title is required");
+    errors.add("roles", "At least one role is required");
+    let error: Error = errors.into();
+    assert_eq!(error.code(), "validation_failed");
+    assert_eq!(
+        error.public_message().as_deref(),
+        Some("Title is required; At least one role is required")
+    );
+}
+
+#[test]
+fn test_signup_form_errors_by_field() {
+    let form = CreateUser {
+        username: "..jane".to_string(),
+        email: "not-an-email".to_string(),
+        password: String::new(),
+        redirect: None,
+    };
+    let errors = form.validate();
+    assert!(errors.has("username"));
+    assert_eq!(errors.get("email"), Some("Enter a valid email address"));
+    assert_eq!(errors.get("password"), Some("Password is required"));
+
+    let form = CreateUser {
+        username: "jane_doe".to_string(),
+        email: "jane@example.com".to_string(),
+        password: "correct horse battery staple".to_string(),
+        redirect: None,
+    };
+    assert!(form.validate().is_empty());
+}
+
+#[test]
+fn test_location_form_errors_by_field() {
+    let errors = CreateLocationForm::default().validate();
+    for field in [
+        "name",
+        "address",
+        "city",
+        "state",
+        "country",
+        "contact_name",
+        "contact_email",
+    ] {
+        assert!(errors.has(field), "{} should be required", field);
+    }
+
+    let form = CreateLocationForm {
+        name: "Warehouse".to_string(),
+        address: "1 Main St".to_string(),
+        city: "Atlanta".to_string(),
+        state: "GA".to_string(),
+        country: "USA".to_string(),
+        contact_name: "Sam".to_string(),
+        contact_email: "sam@example".to_string(),
+        max_capacity: Some(0),
+        ..CreateLocationForm::default()
+    };
+    let errors = form.validate();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.has("contact_email"));
+    assert!(errors.has("max_capacity"));
+}
+
+#[test]
+fn test_job_form_needs_a_titled_role() {
+    let form = CreateJobForm {
+        title: "Short film crew".to_string(),
+        description: "Three days in Atlanta".to_string(),
+        role_title: vec!["  ".to_string()],
+        ..CreateJobForm::default()
+    };
+    assert_eq!(
+        form.validate().get("roles"),
+        Some("At least one role is required")
+    );
+
+    let form = CreateJobForm {
+        role_title: vec![String::new(), "Gaffer".to_string()],
+        role_rate_amount: vec![String::new(), "$400".to_string()],
+        ..form
+    };
+    assert!(form.validate().is_empty());
+    let roles = form.roles();
+    assert_eq!(roles.len(), 1);
+    assert_eq!(roles[0].title, "Gaffer");
+    assert_eq!(roles[0].rate_type, "TBD");
+    assert_eq!(roles[0].rate_amount.as_deref(), Some("$400"));
+}
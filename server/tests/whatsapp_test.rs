@@ -0,0 +1,22 @@
+use slatehub::services::whatsapp::normalize_number;
+
+#[test]
+fn numbers_keep_only_digits() {
+    assert_eq!(normalize_number("+1 (555) 123-4567").as_deref(), Some("15551234567"));
+    assert_eq!(normalize_number("0044 20 7946 0958").as_deref(), Some("442079460958"));
+    assert_eq!(normalize_number(" 49.30.1234567 ").as_deref(), Some("49301234567"));
+}
+
+#[test]
+fn numbers_need_a_country_code() {
+    assert_eq!(normalize_number("020 7946 0958"), None);
+    assert_eq!(normalize_number("5551234"), None);
+}
+
+#[test]
+fn junk_is_rejected() {
+    assert_eq!(normalize_number(""), None);
+    assert_eq!(normalize_number("+1 555 CALL NOW"), None);
+    assert_eq!(normalize_number("+1 555 123 4567 ext 2"), None);
+    assert_eq!(normalize_number("1234567890123456"), None);
+}
@@ -12,6 +12,10 @@ use whatsapp_rust::Client;
 use whatsapp_rust_tokio_transport::TokioWebSocketTransportFactory;
 use whatsapp_rust_ureq_http_client::UreqHttpClient;
 
+mod outbox;
+mod scan;
+mod timecard;
+
 /// Equipment item with name and optional quantity
 #[derive(Clone, Debug)]
 struct EquipmentItem {
@@ -46,11 +50,15 @@ fn parse_command(text: &str) -> Option<(&str, &str)> {
     Some((command, args))
 }
 
-/// Process equipment commands and return response text
+/// Process equipment commands and return response text. `photo` is set when
+/// the command came as a photo caption.
 async fn process_command(
+    client: &Client,
     command: &str,
     args: &str,
     chat_id: &str,
+    sender: &Jid,
+    photo: Option<&wa::message::ImageMessage>,
     store: &EquipmentStore,
 ) -> Option<String> {
     let command = command.to_lowercase();
@@ -62,7 +70,11 @@ async fn process_command(
                 /sh remove <item> - Remove equipment\n\
                 /sh list - Show all equipment\n\
                 /sh clear - Clear all equipment\n\
-                /sh update <item> x <quantity> - Update quantity\n\n\
+                /sh update <item> x <quantity> - Update quantity\n\
+                /sh scan <code> - Look up SlateHub gear by its label code (or caption a photo of the label)\n\
+                /sh checkout <code> [until YYYY-MM-DD] - Check SlateHub gear out to yourself\n\
+                /sh clockin <HH:MM> [production] - Clock in for today's shoot day\n\
+                /sh clockout <HH:MM> [meal <minutes>] - Clock out and submit your timecard\n\n\
                 _Examples:_\n\
                 /sh add ARRI Alexa Mini\n\
                 /sh add C-Stand x 5\n\
@@ -155,6 +167,41 @@ async fn process_command(
             }
         }
 
+        "scan" => {
+            if !args.is_empty() {
+                return Some(scan::lookup(sender, args).await);
+            }
+            Some(match photo {
+                Some(image) => scan::lookup_photo(client, image, sender).await,
+                None => "Usage: /sh scan <code>\nSend a photo of the label with /sh scan as the caption, or the code printed under the QR, like /sh scan EQ-...".to_string(),
+            })
+        }
+
+        "checkout" => match scan::parse_checkout(args) {
+            Some((code, until)) => Some(scan::checkout(sender, code, until).await),
+            None => Some(
+                "Usage: /sh checkout <code> [until YYYY-MM-DD]\nExample: /sh checkout EQ-... until 2026-06-05"
+                    .to_string(),
+            ),
+        },
+
+        "clockin" => match timecard::parse_clock_in(args) {
+            Some((time, production)) => Some(timecard::clock_in(sender, time, production).await),
+            None => Some(
+                "Usage: /sh clockin <HH:MM> [production]\nExample: /sh clockin 07:30".to_string(),
+            ),
+        },
+
+        "clockout" => match timecard::parse_clock_out(args) {
+            Some((time, meal_minutes)) => {
+                Some(timecard::clock_out(sender, time, meal_minutes).await)
+            }
+            None => Some(
+                "Usage: /sh clockout <HH:MM> [meal <minutes>]\nExample: /sh clockout 19:00 meal 30"
+                    .to_string(),
+            ),
+        },
+
         "clear" | "reset" => {
             let mut store = store.write().await;
             store.remove(chat_id);
@@ -344,6 +391,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 Event::Connected(_) => {
                     info!("Connected to WhatsApp!");
                     println!("\n*** Connected to WhatsApp! ***\n");
+                    outbox::start(client.clone());
                 }
 
                 Event::PairSuccess(pair_info) => {
@@ -389,8 +437,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         if let Some((command, args)) = parse_command(&text) {
                             println!("[COMMAND] cmd={} args={}", command, args);
 
-                            if let Some(response) =
-                                process_command(command, args, &chat_id, &store).await
+                            if let Some(response) = process_command(
+                                &client,
+                                command,
+                                args,
+                                &chat_id,
+                                sender,
+                                msg.image_message.as_deref(),
+                                &store,
+                            )
+                            .await
                             {
                                 send_reply(&client, chat, &response, message_id, sender, &msg)
                                     .await;
@@ -452,5 +508,12 @@ fn extract_message_text(msg: &wa::Message) -> Option<String> {
         }
     }
 
+    // Photos carry their text as a caption
+    if let Some(ref image) = msg.image_message {
+        if let Some(ref caption) = image.caption {
+            return Some(caption.clone());
+        }
+    }
+
     None
 }
@@ -0,0 +1,158 @@
+//! Delivery of messages queued by SlateHub
+//!
+//! People who turn on WhatsApp messages in their account settings get offers
+//! and booking confirmations here as well. The server queues them; this polls
+//! `{SLATEHUB_URL}/api/whatsapp/outbox` with `WHATSAPP_BOT_TOKEN`, sends each
+//! message and reports whether it went out. Messages that fail are retried by
+//! the server a few times before it gives up.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+use wacore_binary::jid::Jid;
+use waproto::whatsapp as wa;
+use whatsapp_rust::Client;
+
+/// Whether the poller is already running; `Connected` fires on every reconnect
+static STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Where SlateHub is and the token the bot uses with it
+#[derive(Clone, Debug)]
+pub(crate) struct OutboxConfig {
+    pub(crate) url: String,
+    pub(crate) token: String,
+    interval: Duration,
+}
+
+impl OutboxConfig {
+    pub(crate) fn from_env() -> Option<Self> {
+        let url = std::env::var("SLATEHUB_URL").ok().filter(|s| !s.is_empty())?;
+        let token = std::env::var("WHATSAPP_BOT_TOKEN").ok().filter(|s| !s.is_empty())?;
+        let interval = std::env::var("WHATSAPP_POLL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(15);
+        Some(Self {
+            url: url.trim_end_matches('/').to_string(),
+            token,
+            interval: Duration::from_secs(interval),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Outbox {
+    messages: Vec<OutboxMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OutboxMessage {
+    id: String,
+    /// Phone number with country code, digits only
+    to: String,
+    body: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    sent: bool,
+    error: Option<String>,
+}
+
+pub(crate) fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(30)))
+        .build()
+        .into()
+}
+
+fn fetch(config: &OutboxConfig) -> Result<Vec<OutboxMessage>, ureq::Error> {
+    let outbox: Outbox = agent()
+        .get(&format!("{}/api/whatsapp/outbox", config.url))
+        .header("Authorization", &format!("Bearer {}", config.token))
+        .call()?
+        .body_mut()
+        .read_json()?;
+    Ok(outbox.messages)
+}
+
+fn report(config: &OutboxConfig, id: &str, report: &Report) -> Result<(), ureq::Error> {
+    agent()
+        .post(&format!("{}/api/whatsapp/outbox/{}", config.url, id))
+        .header("Authorization", &format!("Bearer {}", config.token))
+        .send_json(report)?;
+    Ok(())
+}
+
+async fn send(client: &Arc<Client>, message: &OutboxMessage) -> Result<(), String> {
+    let jid: Jid = format!("{}@s.whatsapp.net", message.to)
+        .parse()
+        .map_err(|e| format!("Invalid number: {:?}", e))?;
+    let text = wa::Message {
+        conversation: Some(message.body.clone()),
+        ..Default::default()
+    };
+    client
+        .send_message(jid, text)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("{:?}", e))
+}
+
+async fn poll(config: &OutboxConfig, client: &Arc<Client>) {
+    let fetch_config = config.clone();
+    let messages = match tokio::task::spawn_blocking(move || fetch(&fetch_config)).await {
+        Ok(Ok(messages)) => messages,
+        Ok(Err(e)) => {
+            warn!("Failed to fetch the SlateHub outbox: {}", e);
+            return;
+        }
+        Err(e) => {
+            error!("Outbox fetch task failed: {}", e);
+            return;
+        }
+    };
+    if messages.is_empty() {
+        return;
+    }
+    debug!("Sending {} queued messages", messages.len());
+
+    for message in messages {
+        let result = send(client, &message).await;
+        let outcome = Report {
+            sent: result.is_ok(),
+            error: result.err(),
+        };
+        match &outcome.error {
+            None => info!("Sent queued message {}", message.id),
+            Some(e) => warn!("Failed to send queued message {}: {}", message.id, e),
+        }
+        let report_config = config.clone();
+        let id = message.id.clone();
+        match tokio::task::spawn_blocking(move || report(&report_config, &id, &outcome)).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!("Failed to report message {}: {}", message.id, e),
+            Err(e) => error!("Outbox report task failed: {}", e),
+        }
+    }
+}
+
+/// Start polling the outbox, once, if SlateHub delivery is configured
+pub fn start(client: Arc<Client>) {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let Some(config) = OutboxConfig::from_env() else {
+        info!("SLATEHUB_URL or WHATSAPP_BOT_TOKEN not set; not delivering SlateHub messages");
+        return;
+    };
+    info!("Delivering SlateHub messages from {} every {:?}", config.url, config.interval);
+    tokio::spawn(async move {
+        loop {
+            poll(&config, &client).await;
+            tokio::time::sleep(config.interval).await;
+        }
+    });
+}
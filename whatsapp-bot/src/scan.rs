@@ -0,0 +1,168 @@
+//! Equipment lookups for `/sh scan` and checkouts for `/sh checkout`
+//!
+//! SlateHub equipment labels carry a QR code linking to the item's scan page
+//! and the code itself printed underneath ("EQ-..." or "KIT-..."). Someone
+//! who has linked their number in SlateHub can send the code or link here, or
+//! a photo of the label with `/sh scan` as its caption, and the bot reads the
+//! QR itself; SlateHub replies with the item, whether it's out and the link to
+//! check it out or in.
+//!
+//! `/sh checkout <code> [until YYYY-MM-DD]` checks the item out to the
+//! sender. SlateHub refuses it while a production the sender isn't on has the
+//! gear booked for those dates, and warns about bookings after it.
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+use wacore_binary::jid::Jid;
+use waproto::whatsapp as wa;
+use whatsapp_rust::Client;
+
+use crate::outbox::{agent, OutboxConfig};
+
+#[derive(Debug, Serialize)]
+struct ScanRequest {
+    from: String,
+    code: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CheckoutRequest {
+    from: String,
+    code: String,
+    until: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScanReply {
+    reply: String,
+}
+
+/// What to tell someone whose photo has no readable QR code
+const PHOTO_HELP: &str = "I couldn't find a QR code in that photo. Try again closer to the \
+    label, or send the code printed under the QR:\n\
+    /sh scan EQ-...";
+
+/// The digits of a sender's number, from "15551234567@s.whatsapp.net" or
+/// "15551234567:12@s.whatsapp.net"
+pub(crate) fn sender_number(sender: &Jid) -> String {
+    sender
+        .to_string()
+        .split(['@', ':'])
+        .next()
+        .unwrap_or("")
+        .to_string()
+}
+
+pub(crate) fn post_blocking(
+    config: &OutboxConfig,
+    path: &str,
+    request: &impl Serialize,
+) -> Result<String, ureq::Error> {
+    let reply: ScanReply = agent()
+        .post(&format!("{}{}", config.url, path))
+        .header("Authorization", &format!("Bearer {}", config.token))
+        .send_json(request)?
+        .body_mut()
+        .read_json()?;
+    Ok(reply.reply)
+}
+
+/// Ask SlateHub about a scanned code, returning the reply to send
+pub async fn lookup(sender: &Jid, code: &str) -> String {
+    let Some(config) = OutboxConfig::from_env() else {
+        return "Equipment lookups aren't set up on this bot.".to_string();
+    };
+    let request = ScanRequest {
+        from: sender_number(sender),
+        code: code.to_string(),
+    };
+    match tokio::task::spawn_blocking(move || {
+        post_blocking(&config, "/api/whatsapp/scan", &request)
+    })
+    .await
+    {
+        Ok(Ok(reply)) => reply,
+        Ok(Err(e)) => {
+            warn!("Equipment lookup failed: {}", e);
+            "Couldn't reach SlateHub to look that up. Try again in a moment.".to_string()
+        }
+        Err(e) => {
+            error!("Equipment lookup task failed: {}", e);
+            "Couldn't reach SlateHub to look that up. Try again in a moment.".to_string()
+        }
+    }
+}
+
+/// The text of the first QR code found in a photo: a label's scan link
+pub fn decode_qr(photo: &[u8]) -> Option<String> {
+    let image = image::load_from_memory(photo).ok()?.to_luma8();
+    let mut prepared = rqrr::PreparedImage::prepare(image);
+    prepared
+        .detect_grids()
+        .into_iter()
+        .find_map(|grid| grid.decode().ok().map(|(_, content)| content))
+}
+
+/// Read the label in a photo sent with `/sh scan` and look it up
+pub async fn lookup_photo(
+    client: &Client,
+    image: &wa::message::ImageMessage,
+    sender: &Jid,
+) -> String {
+    let photo = match client.download(image).await {
+        Ok(photo) => photo,
+        Err(e) => {
+            warn!("Failed to download scanned photo: {}", e);
+            return "Couldn't download that photo. Try sending it again.".to_string();
+        }
+    };
+    match tokio::task::spawn_blocking(move || decode_qr(&photo)).await {
+        Ok(Some(code)) => lookup(sender, &code).await,
+        Ok(None) => PHOTO_HELP.to_string(),
+        Err(e) => {
+            error!("QR decoding task failed: {}", e);
+            PHOTO_HELP.to_string()
+        }
+    }
+}
+
+/// Split `/sh checkout` arguments into the code and optional return date:
+/// "EQ-123 until 2026-06-05". `None` if anything else follows the code.
+pub fn parse_checkout(args: &str) -> Option<(String, Option<String>)> {
+    let mut words = args.split_whitespace();
+    let code = words.next()?.to_string();
+    match (words.next(), words.next(), words.next()) {
+        (None, _, _) => Some((code, None)),
+        (Some(until), Some(date), None) if until.eq_ignore_ascii_case("until") => {
+            Some((code, Some(date.to_string())))
+        }
+        _ => None,
+    }
+}
+
+/// Check a code out to the sender, returning the reply to send
+pub async fn checkout(sender: &Jid, code: String, until: Option<String>) -> String {
+    let Some(config) = OutboxConfig::from_env() else {
+        return "Equipment checkouts aren't set up on this bot.".to_string();
+    };
+    let request = CheckoutRequest {
+        from: sender_number(sender),
+        code,
+        until,
+    };
+    match tokio::task::spawn_blocking(move || {
+        post_blocking(&config, "/api/whatsapp/checkout", &request)
+    })
+    .await
+    {
+        Ok(Ok(reply)) => reply,
+        Ok(Err(e)) => {
+            warn!("Equipment checkout failed: {}", e);
+            "Couldn't reach SlateHub to check that out. Try again in a moment.".to_string()
+        }
+        Err(e) => {
+            error!("Equipment checkout task failed: {}", e);
+            "Couldn't reach SlateHub to check that out. Try again in a moment.".to_string()
+        }
+    }
+}
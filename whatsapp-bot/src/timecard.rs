@@ -0,0 +1,85 @@
+//! Clocking in and out for `/sh clockin` and `/sh clockout`
+//!
+//! Crew on a production can clock in for today's shoot day and clock out
+//! again from WhatsApp instead of the timecards page. Times are the set's
+//! local "HH:MM", as on the web. Clocking out submits the timecard for
+//! approval like the clock-out button does.
+
+use serde::Serialize;
+use tracing::{error, warn};
+use wacore_binary::jid::Jid;
+
+use crate::outbox::OutboxConfig;
+use crate::scan::{post_blocking, sender_number};
+
+#[derive(Debug, Serialize)]
+struct ClockRequest {
+    from: String,
+    time: String,
+    production: Option<String>,
+    meal_minutes: Option<i64>,
+}
+
+/// Split `/sh clockin` arguments into the time and optional production slug:
+/// "07:30" or "07:30 night-shift"
+pub fn parse_clock_in(args: &str) -> Option<(String, Option<String>)> {
+    let mut words = args.split_whitespace();
+    let time = words.next()?.to_string();
+    match (words.next(), words.next()) {
+        (production, None) => Some((time, production.map(str::to_string))),
+        _ => None,
+    }
+}
+
+/// Split `/sh clockout` arguments into the time and optional meal break:
+/// "19:00" or "19:00 meal 30"
+pub fn parse_clock_out(args: &str) -> Option<(String, Option<i64>)> {
+    let mut words = args.split_whitespace();
+    let time = words.next()?.to_string();
+    match (words.next(), words.next(), words.next()) {
+        (None, _, _) => Some((time, None)),
+        (Some(meal), Some(minutes), None) if meal.eq_ignore_ascii_case("meal") => {
+            Some((time, Some(minutes.parse().ok()?)))
+        }
+        _ => None,
+    }
+}
+
+async fn clock(path: &'static str, request: ClockRequest) -> String {
+    let Some(config) = OutboxConfig::from_env() else {
+        return "Timecards aren't set up on this bot.".to_string();
+    };
+    match tokio::task::spawn_blocking(move || post_blocking(&config, path, &request)).await {
+        Ok(Ok(reply)) => reply,
+        Ok(Err(e)) => {
+            warn!("Timecard request to {} failed: {}", path, e);
+            "Couldn't reach SlateHub to update your timecard. Try again in a moment.".to_string()
+        }
+        Err(e) => {
+            error!("Timecard task failed: {}", e);
+            "Couldn't reach SlateHub to update your timecard. Try again in a moment.".to_string()
+        }
+    }
+}
+
+/// Clock the sender in on today's shoot day, returning the reply to send
+pub async fn clock_in(sender: &Jid, time: String, production: Option<String>) -> String {
+    let request = ClockRequest {
+        from: sender_number(sender),
+        time,
+        production,
+        meal_minutes: None,
+    };
+    clock("/api/whatsapp/clock-in", request).await
+}
+
+/// Clock the sender out of their open shift, returning the reply to send
+pub async fn clock_out(sender: &Jid, time: String, meal_minutes: Option<i64>) -> String {
+    let request = ClockRequest {
+        from: sender_number(sender),
+        time,
+        production: None,
+        meal_minutes,
+    };
+    clock("/api/whatsapp/clock-out", request).await
+}